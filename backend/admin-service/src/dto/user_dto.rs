@@ -15,6 +15,16 @@ pub struct UpdateUserRequest {
     pub password: Option<String>,
 }
 
+/// Role assignment, optionally bounded in time (e.g. a locum covering for a week)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssignRoleRequest {
+    pub role_id: Uuid,
+    /// When the role takes effect; immediately if omitted
+    pub valid_from: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the role lapses; never if omitted
+    pub valid_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -1,8 +1,12 @@
-use axum::{Json, extract::{State, Path}, http::StatusCode, response::IntoResponse};
-use serde::Deserialize;
+use axum::{Json, extract::{State, Path, Query}, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use shared::infrastructure::encryption::{FieldEncryptionMigrator, MasterKey};
+use shared::{AppError, RequestContext};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use super::key_ceremony_handlers::{record_event, record_failure, require_super_user};
+
 // Type aliases for convenience
 type ConcreteAppState = shared::AppState<
     authz_core::auth::LoginUseCase,
@@ -13,30 +17,275 @@ type ConcreteAppState = shared::AppState<
     crate::use_cases::setup::CreateSuperAdminUseCase,
 >;
 
+const MASTER_KEY_RESOURCE: &str = "master_key";
+const DEK_RESOURCE: &str = "encryption_key";
+const ENCRYPTION_MIGRATION_RESOURCE: &str = "encryption_migration";
+
+/// Runs listed by the migration status endpoint when no column is given
+const RECENT_ENCRYPTION_MIGRATIONS: i64 = 20;
+
 #[derive(Debug, Deserialize)]
 pub struct RotateDekRequest {
     pub reason: String,
 }
 
-/// Rotate master key (admin only)
-/// This only re-encrypts DEKs in vault, NOT user data
+#[derive(Debug, Deserialize)]
+pub struct EncryptColumnRequest {
+    pub table: String,
+    pub column: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EncryptionMigrationStatusQuery {
+    pub table: Option<String>,
+    pub column: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateMasterKeyResponse {
+    pub key_fingerprint: String,
+    pub key_version: i32,
+    pub deks_rewrapped: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateDekResponse {
+    pub old_key_id: Uuid,
+    pub new_key_id: Uuid,
+}
+
+fn rotation_error_status(error: &AppError) -> StatusCode {
+    match error {
+        AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        AppError::InvalidState(_) => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Generate a new master key and rewrap every stored DEK with it (super admin only)
+/// POST /v1/admin/encryption/rotate-master-key
+///
+/// This only rewraps DEKs, NOT user data
 pub async fn rotate_master_key(
-    State(_state): State<Arc<ConcreteAppState>>,
+    State(state): State<Arc<ConcreteAppState>>,
+    ctx: RequestContext,
 ) -> impl IntoResponse {
-    // TODO: Implement master key rotation
-    // This requires:
-    // 1. Getting old master key
-    // 2. Generating new master key
-    // 3. Re-encrypting all DEKs
-    // 4. Updating master key storage
-    
-    (
-        StatusCode::NOT_IMPLEMENTED,
-        Json(serde_json::json!({
-            "error": "Master key rotation not yet implemented"
-        })),
+    let pool = state.database_pool.as_ref();
+    let details = serde_json::json!({
+        "old_key_fingerprint": state.dek_manager.master_key().fingerprint(),
+    });
+
+    if let Err(response) = require_super_user(&state, &ctx, MASTER_KEY_RESOURCE, "master_key.rotate", &details).await {
+        return response;
+    }
+
+    let location = concat!(file!(), ":", line!());
+    let result = match MasterKey::generate() {
+        Ok(new_master_key) => state.dek_manager.rotate_master_key(new_master_key).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(deks_rewrapped) => {
+            let master_key = state.dek_manager.master_key();
+            let key_fingerprint = master_key.fingerprint();
+            let mut event = details.clone();
+            event["key_fingerprint"] = serde_json::json!(key_fingerprint);
+            event["key_version"] = serde_json::json!(master_key.version());
+            event["deks_rewrapped"] = serde_json::json!(deks_rewrapped);
+            event["success"] = serde_json::json!(true);
+            record_event(pool, MASTER_KEY_RESOURCE, ctx.user_id, "master_key.rotate", event).await;
+
+            (
+                StatusCode::OK,
+                Json(RotateMasterKeyResponse {
+                    key_fingerprint,
+                    key_version: master_key.version(),
+                    deks_rewrapped,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "rotate_master_key");
+            record_failure(pool, MASTER_KEY_RESOURCE, ctx.user_id, "master_key.rotate", details, &e.to_string()).await;
+            (
+                rotation_error_status(&e),
+                Json(serde_json::json!({
+                    "error": format!("Failed to rotate master key: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Replace a DEK and re-encrypt the values encrypted with it (super admin only)
+/// POST /v1/admin/encryption/rotate-dek/{key_id}
+pub async fn rotate_dek(
+    State(state): State<Arc<ConcreteAppState>>,
+    ctx: RequestContext,
+    Path(key_id): Path<Uuid>,
+) -> impl IntoResponse {
+    let pool = state.database_pool.as_ref();
+    let details = serde_json::json!({ "key_id": key_id });
+
+    if let Err(response) = require_super_user(&state, &ctx, DEK_RESOURCE, "dek.rotate", &details).await {
+        return response;
+    }
+
+    let location = concat!(file!(), ":", line!());
+    match state.dek_manager.rotate_dek(key_id).await {
+        Ok(new_key_id) => {
+            record_event(pool, DEK_RESOURCE, ctx.user_id, "dek.rotate", serde_json::json!({
+                "key_id": key_id,
+                "new_key_id": new_key_id,
+                "success": true,
+            }))
+            .await;
+
+            (
+                StatusCode::OK,
+                Json(RotateDekResponse {
+                    old_key_id: key_id,
+                    new_key_id,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "rotate_dek");
+            record_failure(pool, DEK_RESOURCE, ctx.user_id, "dek.rotate", details, &e.to_string()).await;
+            (
+                rotation_error_status(&e),
+                Json(serde_json::json!({
+                    "error": format!("Failed to rotate DEK: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Encrypt the existing plaintext values of a column in the background (super admin only)
+/// POST /v1/admin/encryption/migrate
+///
+/// Only registered encrypted fields can be migrated; each value is encrypted
+/// with the DEK its repository decrypts it with. Responds 202 with the started
+/// run; GET /v1/admin/encryption/migrate/status reports its progress.
+pub async fn start_encryption_migration(
+    State(state): State<Arc<ConcreteAppState>>,
+    ctx: RequestContext,
+    Json(request): Json<EncryptColumnRequest>,
+) -> impl IntoResponse {
+    let pool = state.database_pool.as_ref();
+    let details = serde_json::json!({ "table": request.table, "column": request.column });
+
+    if let Err(response) = require_super_user(
+        &state,
+        &ctx,
+        ENCRYPTION_MIGRATION_RESOURCE,
+        "encryption_migration.start",
+        &details,
     )
-        .into_response()
+    .await
+    {
+        return response;
+    }
+
+    let location = concat!(file!(), ":", line!());
+    let started = async {
+        let field = FieldEncryptionMigrator::registered(&request.table, &request.column)?;
+        let migration = FieldEncryptionMigrator::start(field, pool).await?;
+        Ok::<_, AppError>((field, migration))
+    }
+    .await;
+
+    match started {
+        Ok((field, migration)) => {
+            let mut event = details.clone();
+            event["migration_id"] = serde_json::json!(migration.id);
+            event["total_rows"] = serde_json::json!(migration.total_rows);
+            event["success"] = serde_json::json!(true);
+            record_event(pool, ENCRYPTION_MIGRATION_RESOURCE, ctx.user_id, "encryption_migration.start", event).await;
+
+            let response = (StatusCode::ACCEPTED, Json(migration.clone())).into_response();
+            let pool = state.database_pool.clone();
+            let dek_manager = state.dek_manager.clone();
+            tokio::spawn(async move {
+                match FieldEncryptionMigrator::resume(migration, field, &dek_manager, &pool).await {
+                    Ok(migration) => tracing::info!(
+                        table = %migration.table_name,
+                        column = %migration.column_name,
+                        migrated_rows = migration.migrated_rows,
+                        "Encrypted existing column values"
+                    ),
+                    Err(e) => e.log_with_operation(concat!(file!(), ":", line!()), "encrypt_column_values"),
+                }
+            });
+            response
+        }
+        Err(e) => {
+            e.log_with_operation(location, "start_encryption_migration");
+            record_failure(pool, ENCRYPTION_MIGRATION_RESOURCE, ctx.user_id, "encryption_migration.start", details, &e.to_string()).await;
+            let status = match e {
+                AppError::Validation(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": format!("Failed to start encryption migration: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Progress of column encryption migrations
+/// GET /v1/admin/encryption/migrate/status?table=&column=
+///
+/// With `table` and `column`, the latest run over that column; otherwise the
+/// most recent runs over any column.
+pub async fn get_encryption_migration_status(
+    State(state): State<Arc<ConcreteAppState>>,
+    Query(query): Query<EncryptionMigrationStatusQuery>,
+) -> impl IntoResponse {
+    let pool = state.database_pool.as_ref();
+    let location = concat!(file!(), ":", line!());
+
+    let result = match (&query.table, &query.column) {
+        (Some(table), Some(column)) => match FieldEncryptionMigrator::latest(table, column, pool).await {
+            Ok(Some(migration)) => Ok(serde_json::json!(migration)),
+            Ok(None) => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({
+                        "error": format!("No encryption migration of {}.{}", table, column)
+                    })),
+                )
+                    .into_response();
+            }
+            Err(e) => Err(e),
+        },
+        _ => FieldEncryptionMigrator::recent(RECENT_ENCRYPTION_MIGRATIONS, pool)
+            .await
+            .map(|migrations| serde_json::json!({ "migrations": migrations })),
+    };
+
+    match result {
+        Ok(body) => (StatusCode::OK, Json(body)).into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "get_encryption_migration_status");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to get encryption migration status: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
 }
 
 /// Rotate user DEK
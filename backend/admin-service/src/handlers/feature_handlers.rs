@@ -0,0 +1,19 @@
+//! Feature flag handlers
+//!
+//! Report which features this deployment serves; flags are set with
+//! `FEATURE_*` environment variables.
+
+use axum::{Json, http::StatusCode, response::IntoResponse};
+use shared::config::features::{FeatureFlags, FEATURE_NAMES};
+
+/// List every feature flag and whether it is on
+/// GET /v1/admin/features
+pub async fn list_feature_flags() -> impl IntoResponse {
+    let flags = FeatureFlags::from_env();
+    let features: serde_json::Map<String, serde_json::Value> = FEATURE_NAMES
+        .iter()
+        .map(|name| (name.to_string(), serde_json::Value::Bool(flags.is_enabled(name))))
+        .collect();
+
+    (StatusCode::OK, Json(serde_json::json!({ "features": features })))
+}
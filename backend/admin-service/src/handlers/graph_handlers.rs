@@ -123,6 +123,23 @@ pub async fn invalidate_graph_cache(
     }
 }
 
+/// Permission cache hit/miss/eviction counters and size
+pub async fn get_cache_stats(
+    State(state): State<Arc<ConcreteAppState>>,
+) -> impl IntoResponse {
+    if let Some(cache) = &state.graph_cache {
+        (StatusCode::OK, Json(cache.stats())).into_response()
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({
+                "error": "Graph cache not enabled"
+            })),
+        )
+            .into_response()
+    }
+}
+
 /// Find permission paths for debugging
 pub async fn find_permission_paths(
     State(state): State<Arc<ConcreteAppState>>,
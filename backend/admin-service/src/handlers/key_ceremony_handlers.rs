@@ -0,0 +1,253 @@
+//! Master key ceremony handlers
+//!
+//! Split the active master key into Shamir shares for custodians, and
+//! verify that a set of custodian shares recovers it. Restricted to super
+//! users; every attempt is written to `audit_logs`.
+
+use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use shared::domain::repositories::UserRepository;
+use shared::infrastructure::encryption::MasterKey;
+use shared::infrastructure::repositories::UserRepositoryImpl;
+use shared::RequestContext;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+const AUDIT_RESOURCE: &str = "master_key";
+
+#[derive(Debug, Deserialize)]
+pub struct SplitMasterKeyRequest {
+    pub threshold: usize,
+    pub total_shares: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SplitMasterKeyResponse {
+    /// Hex-encoded shares, one per custodian
+    pub shares: Vec<String>,
+    pub threshold: usize,
+    pub key_fingerprint: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecoverMasterKeyRequest {
+    /// Hex-encoded shares
+    pub shares: Vec<String>,
+    pub threshold: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoverMasterKeyResponse {
+    pub key_fingerprint: String,
+    /// Whether the recovered key is the one currently protecting DEKs
+    pub matches_active_key: bool,
+}
+
+/// Split the active master key into Shamir shares
+/// POST /v1/admin/encryption/key-ceremony/split
+pub async fn split_master_key(
+    State(state): State<Arc<ConcreteAppState>>,
+    ctx: RequestContext,
+    Json(request): Json<SplitMasterKeyRequest>,
+) -> impl IntoResponse {
+    let pool = state.database_pool.as_ref();
+    let details = serde_json::json!({
+        "threshold": request.threshold,
+        "total_shares": request.total_shares,
+    });
+
+    if let Err(response) = require_super_user(&state, &ctx, AUDIT_RESOURCE, "key_ceremony.split", &details).await {
+        return response;
+    }
+
+    let master_key = state.dek_manager.master_key();
+    let location = concat!(file!(), ":", line!());
+    match master_key.split_into_shares(request.threshold, request.total_shares) {
+        Ok(shares) => {
+            let key_fingerprint = master_key.fingerprint();
+            record_event(pool, AUDIT_RESOURCE, ctx.user_id, "key_ceremony.split", serde_json::json!({
+                "threshold": request.threshold,
+                "total_shares": request.total_shares,
+                "key_fingerprint": key_fingerprint,
+                "success": true,
+            }))
+            .await;
+
+            (
+                StatusCode::OK,
+                Json(SplitMasterKeyResponse {
+                    shares: shares.iter().map(hex::encode).collect(),
+                    threshold: request.threshold,
+                    key_fingerprint,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "split_master_key");
+            record_failure(pool, AUDIT_RESOURCE, ctx.user_id, "key_ceremony.split", details, &e.to_string()).await;
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("Failed to split master key: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Recover a master key from Shamir shares and compare it with the active key
+/// POST /v1/admin/encryption/key-ceremony/recover
+pub async fn recover_master_key(
+    State(state): State<Arc<ConcreteAppState>>,
+    ctx: RequestContext,
+    Json(request): Json<RecoverMasterKeyRequest>,
+) -> impl IntoResponse {
+    let pool = state.database_pool.as_ref();
+    // Never record the shares themselves
+    let details = serde_json::json!({
+        "threshold": request.threshold,
+        "shares_provided": request.shares.len(),
+    });
+
+    if let Err(response) = require_super_user(&state, &ctx, AUDIT_RESOURCE, "key_ceremony.recover", &details).await {
+        return response;
+    }
+
+    let shares: Result<Vec<Vec<u8>>, _> = request.shares.iter().map(hex::decode).collect();
+    let shares = match shares {
+        Ok(shares) => shares,
+        Err(e) => {
+            record_failure(pool, AUDIT_RESOURCE, ctx.user_id, "key_ceremony.recover", details, "invalid share encoding").await;
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("Shares must be hex-encoded: {}", e)
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let location = concat!(file!(), ":", line!());
+    match MasterKey::from_shamir_shares(shares, request.threshold) {
+        Ok(recovered) => {
+            let key_fingerprint = recovered.fingerprint();
+            let matches_active_key = key_fingerprint == state.dek_manager.master_key().fingerprint();
+            record_event(pool, AUDIT_RESOURCE, ctx.user_id, "key_ceremony.recover", serde_json::json!({
+                "threshold": request.threshold,
+                "shares_provided": request.shares.len(),
+                "key_fingerprint": key_fingerprint,
+                "matches_active_key": matches_active_key,
+                "success": true,
+            }))
+            .await;
+
+            (
+                StatusCode::OK,
+                Json(RecoverMasterKeyResponse {
+                    key_fingerprint,
+                    matches_active_key,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "recover_master_key");
+            record_failure(pool, AUDIT_RESOURCE, ctx.user_id, "key_ceremony.recover", details, &e.to_string()).await;
+            (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!("Failed to recover master key: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Reject callers that are not super users, auditing the denied attempt
+pub(crate) async fn require_super_user(
+    state: &ConcreteAppState,
+    ctx: &RequestContext,
+    resource: &str,
+    action: &str,
+    details: &serde_json::Value,
+) -> Result<(), axum::response::Response> {
+    let user_repository = UserRepositoryImpl::new(state.database_service.clone());
+    let location = concat!(file!(), ":", line!());
+    let is_super_user = match user_repository.find_by_id(ctx.user_id).await {
+        Ok(user) => user.is_some_and(|u| u.is_super_user),
+        Err(e) => {
+            e.log_with_operation(location, action);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to load user: {}", e)
+                })),
+            )
+                .into_response());
+        }
+    };
+
+    if is_super_user {
+        return Ok(());
+    }
+
+    tracing::warn!("Key management denied: user {} is not a super user ({})", ctx.user_id, action);
+    record_failure(state.database_pool.as_ref(), resource, ctx.user_id, action, details.clone(), "forbidden").await;
+    Err((
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({
+            "error": "Key management is restricted to super admins"
+        })),
+    )
+        .into_response())
+}
+
+pub(crate) async fn record_failure(
+    pool: &PgPool,
+    resource: &str,
+    user_id: Uuid,
+    action: &str,
+    mut details: serde_json::Value,
+    reason: &str,
+) {
+    details["success"] = serde_json::Value::Bool(false);
+    details["reason"] = serde_json::Value::String(reason.to_string());
+    record_event(pool, resource, user_id, action, details).await;
+}
+
+/// Write a key management event to the audit trail
+///
+/// Audit failures are logged rather than failing the request.
+pub(crate) async fn record_event(pool: &PgPool, resource: &str, user_id: Uuid, action: &str, details: serde_json::Value) {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO audit_logs (user_id, action, resource, details)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        action,
+        resource,
+        details
+    )
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result {
+        tracing::error!("Failed to write key management audit log ({}): {}", action, e);
+    }
+}
@@ -5,10 +5,14 @@ pub mod group_handlers;
 pub mod permission_handlers;
 pub mod permission_check_handlers;
 pub mod encryption_handlers;
+pub mod key_ceremony_handlers;
 pub mod graph_handlers;
 pub mod ui_entity_handlers;
 pub mod dashboard_handlers;
 pub mod workflow_handlers;
+pub mod rule_handlers;
+pub mod storage_handlers;
+pub mod feature_handlers;
 
 pub use admin_handlers::*;
 pub use setup_handlers::*;
@@ -17,8 +21,12 @@ pub use group_handlers::*;
 pub use permission_handlers::*;
 pub use permission_check_handlers::*;
 pub use encryption_handlers::*;
+pub use key_ceremony_handlers::*;
 pub use graph_handlers::*;
 pub use ui_entity_handlers::*;
 pub use dashboard_handlers::*;
 pub use workflow_handlers::*;
+pub use rule_handlers::*;
+pub use storage_handlers::*;
+pub use feature_handlers::*;
 
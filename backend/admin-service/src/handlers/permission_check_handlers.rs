@@ -1,5 +1,6 @@
 use axum::{Json, extract::{State, Path}, http::StatusCode, response::IntoResponse};
 use serde::{Deserialize, Serialize};
+use shared::RequestContext;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -18,6 +19,9 @@ pub struct CheckPermissionRequest {
     pub user: String, // user:{id} or just user ID
     pub relation: String,
     pub object: String,
+    /// Consistency token from a relationship write; the check sees at least that write
+    #[serde(default)]
+    pub zookie: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +29,22 @@ pub struct BatchCheckPermissionRequest {
     pub checks: Vec<(String, String, String)>, // (user, relation, object)
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ExplainDenialRequest {
+    pub subject_type: String, // e.g. "user"
+    pub subject_id: String,
+    pub object_type: String, // e.g. "patient"
+    pub object_id: String,
+    pub relation: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExplainPermissionRequest {
+    pub subject: String, // e.g. "user:{id}" or "group:{name}"
+    pub object: String,
+    pub relation: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct CheckPermissionResponse {
     pub allowed: bool,
@@ -59,12 +79,21 @@ pub async fn check_permission(
         format!("user:{}", request.user)
     };
     
-    match state.permission_checker.check(&user_str, &request.relation, &request.object).await {
+    match state
+        .permission_checker
+        .check(&user_str, &request.relation, &request.object, request.zookie.as_deref())
+        .await
+    {
         Ok(allowed) => (
             StatusCode::OK,
             Json(CheckPermissionResponse { allowed }),
         )
             .into_response(),
+        Err(shared::AppError::Validation(msg)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": msg })),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({
@@ -109,6 +138,89 @@ pub async fn check_permissions_batch(
     }
 }
 
+/// Explain why a permission check is denied (admin debugging)
+pub async fn explain_denial(
+    State(state): State<Arc<ConcreteAppState>>,
+    Json(request): Json<ExplainDenialRequest>,
+) -> impl IntoResponse {
+    match state
+        .permission_checker
+        .explain_denial(
+            &request.subject_type,
+            &request.subject_id,
+            &request.object_type,
+            &request.object_id,
+            &request.relation,
+        )
+        .await
+    {
+        Ok(explanation) => (StatusCode::OK, Json(explanation)).into_response(),
+        Err(shared::AppError::Validation(message)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": message
+            })),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": format!("Failed to explain denial: {}", e)
+            })),
+        )
+            .into_response(),
+    }
+}
+
+/// Trace how a permission check is evaluated (authorization audit)
+///
+/// Requires the `explain_permissions` capability on `system:authorization`,
+/// since the trace reveals how relationships are structured.
+pub async fn explain_permission(
+    State(state): State<Arc<ConcreteAppState>>,
+    ctx: RequestContext,
+    Json(request): Json<ExplainPermissionRequest>,
+) -> impl IntoResponse {
+    let caller = format!("user:{}", ctx.user_id);
+    match state.permission_checker.can_explain(&caller).await {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::warn!("Permission explain denied: {} lacks the explain_permissions capability", caller);
+            return (
+                StatusCode::FORBIDDEN,
+                Json(serde_json::json!({
+                    "error": "Explaining permissions requires the explain_permissions capability"
+                })),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to check explain capability: {}", e)
+                })),
+            )
+                .into_response();
+        }
+    }
+
+    match state
+        .permission_checker
+        .explain(&request.subject, &request.object, &request.relation)
+        .await
+    {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": format!("Failed to explain permission: {}", e)
+            })),
+        )
+            .into_response(),
+    }
+}
+
 /// Get all permissions for a user
 pub async fn get_user_permissions(
     State(state): State<Arc<ConcreteAppState>>,
@@ -0,0 +1,134 @@
+//! Decision rule handlers
+//!
+//! Backtest a loaded decision rule against historical contexts before it is
+//! deployed. Cases come from the request body, or from
+//! `rule_execution_history` when none are supplied.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use shared::application::services::{RuleContext, RuleResult};
+use shared::{AppError, AppResult};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+/// Cases replayed from history when the request doesn't set a limit
+const DEFAULT_HISTORY_LIMIT: i64 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct BacktestCase {
+    pub context: RuleContext,
+    pub expected: RuleResult,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BacktestRuleRequest {
+    /// Historical contexts with their expected outcomes
+    #[serde(default)]
+    pub cases: Vec<BacktestCase>,
+    /// Most recent executions to replay when `cases` is empty
+    pub history_limit: Option<i64>,
+}
+
+/// Replay a rule against historical data and report precision/recall
+/// POST /v1/admin/rules/{id}/backtest
+pub async fn backtest_rule(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(rule_id): Path<String>,
+    Json(request): Json<BacktestRuleRequest>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+
+    let (contexts, expected): (Vec<RuleContext>, Vec<RuleResult>) = if request.cases.is_empty() {
+        let limit = request.history_limit.unwrap_or(DEFAULT_HISTORY_LIMIT);
+        match load_execution_history(state.database_pool.as_ref(), &rule_id, limit).await {
+            Ok(cases) => cases.into_iter().unzip(),
+            Err(e) => {
+                e.log_with_operation(location, "backtest_rule");
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({
+                        "error": format!("Failed to load rule execution history: {}", e)
+                    })),
+                )
+                    .into_response();
+            }
+        }
+    } else {
+        request.cases.into_iter().map(|case| (case.context, case.expected)).unzip()
+    };
+
+    if contexts.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("No historical cases available for rule {}", rule_id)
+            })),
+        )
+            .into_response();
+    }
+
+    match state.rules_engine.backtest(&rule_id, contexts, expected).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "backtest_rule");
+            let status = match e {
+                AppError::NotFound(_) => StatusCode::NOT_FOUND,
+                AppError::Validation(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (
+                status,
+                Json(serde_json::json!({
+                    "error": format!("Failed to backtest rule: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Load the most recent recorded executions of a rule
+async fn load_execution_history(
+    pool: &PgPool,
+    rule_id: &str,
+    limit: i64,
+) -> AppResult<Vec<(RuleContext, RuleResult)>> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT context_snapshot, result
+        FROM rule_execution_history
+        WHERE rule_id = $1
+        ORDER BY executed_at DESC
+        LIMIT $2
+        "#,
+        rule_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let context = serde_json::from_value(row.context_snapshot)
+                .map_err(|e| AppError::Internal(format!("Invalid context snapshot: {}", e)))?;
+            let result = serde_json::from_value(row.result)
+                .map_err(|e| AppError::Internal(format!("Invalid recorded result: {}", e)))?;
+            Ok((context, result))
+        })
+        .collect()
+}
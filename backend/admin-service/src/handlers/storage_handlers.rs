@@ -0,0 +1,67 @@
+//! Storage handlers
+//!
+//! Check locally stored files against the SHA-256 checksums recorded when
+//! they were written, to find corrupted or tampered files.
+
+use axum::{Json, http::StatusCode, response::IntoResponse};
+use shared::config::providers::ProviderConfig;
+use shared::infrastructure::providers::local_storage_path;
+use shared::infrastructure::storage::{IntegrityStatus, LocalFsStorage};
+use shared::AppError;
+
+/// Verify every stored file against its checksum
+/// GET /v1/admin/storage/integrity-check
+pub async fn check_storage_integrity() -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+
+    let config = match ProviderConfig::from_env().map_err(|e| AppError::Configuration(e.to_string())) {
+        Ok(config) => config,
+        Err(e) => {
+            e.log_with_operation(location, "check_storage_integrity");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to load storage config: {}", e)
+                })),
+            )
+                .into_response();
+        }
+    };
+    let Some(path) = local_storage_path(&config.storage) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": "Integrity checks are only available for local storage"
+            })),
+        )
+            .into_response();
+    };
+
+    match LocalFsStorage::new(path).verify_all().await {
+        Ok(reports) => {
+            let mismatched = reports.iter().filter(|r| r.status == IntegrityStatus::Mismatch).count();
+            if mismatched > 0 {
+                tracing::warn!(mismatched, "Stored files failed their integrity check");
+            }
+            (
+                StatusCode::OK,
+                Json(serde_json::json!({
+                    "files_checked": reports.len(),
+                    "mismatched": mismatched,
+                    "files": reports
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "check_storage_integrity");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to check storage integrity: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,7 +1,50 @@
-use axum::{Json, extract::Path, http::StatusCode, response::IntoResponse};
-use crate::dto::{CreateUserRequest, UpdateUserRequest};
+use axum::{Json, extract::{Path, State}, http::StatusCode, response::IntoResponse};
+use crate::dto::{AssignRoleRequest, CreateUserRequest, UpdateUserRequest};
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+use shared::domain::entities::{ProvisioningStep, Session, UserProvisioningChecklist};
+use std::sync::Arc;
 use uuid::Uuid;
 
+// Type aliases for convenience
+type ConcreteAppState = shared::AppState<
+    authz_core::auth::LoginUseCase,
+    authz_core::auth::RefreshTokenUseCase,
+    authz_core::auth::LogoutUseCase,
+    authz_core::auth::UserInfoUseCase,
+    crate::use_cases::setup::SetupOrganizationUseCase,
+    crate::use_cases::setup::CreateSuperAdminUseCase,
+>;
+
+#[derive(Debug, Serialize)]
+pub struct ProvisioningStatusResponse {
+    pub checklist: UserProvisioningChecklist,
+    /// Steps whose dependencies are complete and that can run now
+    pub executable_steps: Vec<ProvisioningStep>,
+}
+
+/// Active session of a user, as listed to admins
+#[derive(Debug, Serialize)]
+pub struct UserSessionResponse {
+    pub session_id: Uuid,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_active_at: DateTime<Utc>,
+}
+
+impl From<Session> for UserSessionResponse {
+    fn from(session: Session) -> Self {
+        Self {
+            session_id: session.id,
+            ip_address: session.ip_address.to_string(),
+            user_agent: session.user_agent,
+            created_at: session.created_at,
+            last_active_at: session.last_activity_at,
+        }
+    }
+}
+
 pub async fn create_user(
     Json(_request): Json<CreateUserRequest>,
 ) -> impl IntoResponse {
@@ -31,3 +74,166 @@ pub async fn delete_user(
     (StatusCode::NOT_IMPLEMENTED, Json(serde_json::json!({"error": "Not yet implemented - database not configured"})))
 }
 
+
+/// Get a user's provisioning checklist and the steps that can currently run
+pub async fn get_user_provisioning_status(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    use shared::domain::repositories::UserProvisioningChecklistRepository;
+    use shared::infrastructure::repositories::UserProvisioningChecklistRepositoryImpl;
+
+    let repository = UserProvisioningChecklistRepositoryImpl::new(state.database_service.clone());
+
+    let location = concat!(file!(), ":", line!());
+    match repository.find_by_user_id(id).await {
+        Ok(Some(checklist)) => {
+            let executable_steps = checklist.executable_steps();
+            (
+                StatusCode::OK,
+                Json(ProvisioningStatusResponse {
+                    checklist,
+                    executable_steps,
+                }),
+            )
+                .into_response()
+        }
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": "Provisioning checklist not found"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "get_user_provisioning_status");
+            (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": format!("Failed to get provisioning status: {}", e)
+            })),
+        )
+                .into_response()
+        }
+    }
+}
+
+/// Force a user to log out everywhere: revoke all refresh tokens and end all sessions
+pub async fn force_logout_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let revoked_tokens = match state.logout_use_case
+        .logout_all(id, authz_core::auth::LogoutReason::AdminForced)
+        .await
+    {
+        Ok(count) => count,
+        Err(e) => {
+            e.log_with_operation(location, "force_logout_user");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("{}", e)}))).into_response();
+        }
+    };
+
+    let ended_sessions = match state.session_service.invalidate_all_for_user(id).await {
+        Ok(count) => count,
+        Err(e) => {
+            e.log_with_operation(location, "force_logout_user");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("{}", e)}))).into_response();
+        }
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "userId": id,
+        "revokedTokens": revoked_tokens,
+        "endedSessions": ended_sessions,
+    }))).into_response()
+}
+
+/// List a user's active sessions
+pub async fn list_user_sessions(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    match state.session_service.list_active_for_user(id).await {
+        Ok(sessions) => {
+            let sessions: Vec<UserSessionResponse> = sessions.into_iter().map(UserSessionResponse::from).collect();
+            (StatusCode::OK, Json(serde_json::json!(sessions))).into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "list_user_sessions");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
+        }
+    }
+}
+
+/// Force one session of a user to log out, leaving their other devices signed in
+pub async fn end_user_session(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path((id, session_id)): Path<(Uuid, Uuid)>,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    match state.session_service.end_user_session(id, session_id).await {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({
+            "userId": id,
+            "sessionId": session_id,
+        }))).into_response(),
+        Err(shared::AppError::NotFound(message)) => {
+            (StatusCode::NOT_FOUND, Json(serde_json::json!({"error": message}))).into_response()
+        }
+        Err(e) => {
+            e.log_with_operation(location, "end_user_session");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
+        }
+    }
+}
+
+/// Assign a role to a user, optionally only between `valid_from` and `valid_until`
+pub async fn assign_role_to_user(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AssignRoleRequest>,
+) -> impl IntoResponse {
+    use crate::use_cases::user::AssignRoleUseCase;
+    use shared::infrastructure::repositories::{
+        AuditLogRepositoryImpl, PermissionRepositoryImpl, RoleRepositoryImpl, UserRepositoryImpl,
+    };
+
+    let permission_repo = Arc::new(PermissionRepositoryImpl::new(state.database_pool.as_ref().clone()));
+    let role_repository = Box::new(RoleRepositoryImpl::new(
+        state.database_service.clone(),
+        state.relationship_store.clone(),
+        permission_repo,
+    ));
+    let mut use_case = AssignRoleUseCase::new(
+        Box::new(UserRepositoryImpl::new(state.database_service.clone())),
+        role_repository,
+        state.relationship_store.clone(),
+    )
+    .with_audit_log(Arc::new(AuditLogRepositoryImpl::new(state.database_service.clone())));
+    if let Some(graph_cache) = &state.graph_cache {
+        use_case = use_case.with_graph_cache(graph_cache.clone());
+    }
+
+    let location = concat!(file!(), ":", line!());
+    match use_case.execute(id, request).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "success": true,
+                "message": "Role assigned to user"
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            e.log_with_operation(location, "assign_role_to_user");
+            let status = match &e {
+                shared::AppError::NotFound(_) => StatusCode::NOT_FOUND,
+                shared::AppError::Validation(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
+        }
+    }
+}
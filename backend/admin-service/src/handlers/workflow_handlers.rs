@@ -16,8 +16,18 @@ use shared::domain::entities::{
     VisualWorkflow, VisualWorkflowSummary, WorkflowInstance,
 };
 use shared::domain::repositories::VisualWorkflowRepository;
+use shared::domain::state_machine::{
+    AppointmentMachine, AppointmentStateMachine, OrderMachine, OrderStateMachine, SchemaError,
+    WorkflowVariableSchema,
+};
 use shared::infrastructure::repositories::VisualWorkflowRepositoryImpl;
 use shared::RequestContext;
+use shared::application::services::connectors::{create_connector_registry, ConnectorRegistry};
+use shared::application::services::{
+    validate_variables, WorkflowDefinition, WorkflowInstance as EngineInstance,
+    WorkflowSchemaIssue,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::error;
 use uuid::Uuid;
@@ -80,6 +90,11 @@ pub struct StartWorkflowRequest {
     pub correlation_id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CancelInstanceRequest {
+    pub reason: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListInstancesQuery {
     pub status: Option<String>,
@@ -272,15 +287,121 @@ fn error_response(err: shared::AppError) -> impl IntoResponse {
 // Workflow Definition Handlers
 // ============================================================================
 
+/// Connectors workflow actions are checked against
+fn connector_registry() -> ConnectorRegistry {
+    create_connector_registry("http://localhost:8080/api")
+}
+
+/// Engine definition of a stored workflow, for export and cancellation
+fn engine_definition(workflow: VisualWorkflow) -> Result<WorkflowDefinition, serde_json::Error> {
+    Ok(WorkflowDefinition {
+        id: workflow.id.to_string(),
+        name: workflow.name,
+        description: workflow.description,
+        version: workflow.version,
+        category: workflow.category,
+        nodes: serde_json::from_value(workflow.nodes)?,
+        edges: serde_json::from_value(workflow.edges)?,
+        input_schema: workflow.input_schema,
+        output_schema: workflow.output_schema,
+        variables: Vec::new(),
+        trigger: Default::default(),
+        is_active: workflow.is_active,
+        organization_id: Some(workflow.organization_id.to_string()),
+        tags: workflow.tags.unwrap_or_default(),
+        created_at: workflow.created_at,
+        updated_at: workflow.updated_at,
+        created_by: workflow.created_by.map(|id| id.to_string()),
+    })
+}
+
+/// Engine view of a stored instance
+///
+/// History entries that are not execution steps (e.g. recorded events) are
+/// left out.
+fn engine_instance(instance: &WorkflowInstance) -> Result<EngineInstance, serde_json::Error> {
+    let history = instance
+        .history
+        .as_array()
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| serde_json::from_value(entry.clone()).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(EngineInstance {
+        id: instance.id.to_string(),
+        workflow_id: instance.workflow_id.to_string(),
+        workflow_version: instance.workflow_version,
+        status: serde_json::from_value(serde_json::Value::String(instance.status.clone()))?,
+        current_nodes: instance.current_nodes.clone(),
+        variables: serde_json::from_value(instance.variables.clone()).unwrap_or_default(),
+        history,
+        started_at: instance.started_at,
+        completed_at: instance.completed_at,
+        error: instance.error.clone(),
+        parent_instance_id: instance.parent_instance_id.map(|id| id.to_string()),
+        correlation_id: instance.correlation_id.clone(),
+        cancellation_reason: None,
+    })
+}
+
+fn invalid_schema_response(issues: Vec<WorkflowSchemaIssue>) -> axum::response::Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(serde_json::json!({
+            "error": "Invalid workflow definition",
+            "issues": issues
+        })),
+    )
+        .into_response()
+}
+
 /// Create a new workflow
 /// POST /v1/admin/workflows
+///
+/// Accepts either the designer's request body or a JSON Schema workflow
+/// document (one with a `$schema`, see
+/// [`shared::application::services::workflow_schema`]). A document's
+/// connector actions are checked before it is saved; its `$id`, trigger and
+/// variables are not stored, and the workflow gets a new ID.
 pub async fn create_workflow(
     State(state): State<Arc<ConcreteAppState>>,
     ctx: RequestContext,
-    Json(request): Json<CreateWorkflowRequest>,
+    Json(body): Json<serde_json::Value>,
 ) -> impl IntoResponse {
     let repo = VisualWorkflowRepositoryImpl::new(state.database_service.clone());
 
+    let request = if body.get("$schema").is_some() {
+        let definition = match WorkflowDefinition::from_json_schema(&body.to_string(), &connector_registry()) {
+            Ok(definition) => definition,
+            Err(e) => return invalid_schema_response(e.issues),
+        };
+        CreateWorkflowRequest {
+            name: definition.name,
+            description: definition.description,
+            category: definition.category,
+            nodes: serde_json::to_value(&definition.nodes).unwrap_or_default(),
+            edges: serde_json::to_value(&definition.edges).unwrap_or_default(),
+            input_schema: definition.input_schema,
+            output_schema: definition.output_schema,
+            tags: Some(definition.tags).filter(|tags| !tags.is_empty()),
+        }
+    } else {
+        match serde_json::from_value::<CreateWorkflowRequest>(body) {
+            Ok(request) => request,
+            Err(e) => {
+                return (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    Json(serde_json::json!({ "error": format!("Invalid workflow: {}", e) })),
+                )
+                    .into_response()
+            }
+        }
+    };
+
     let org_id = match ctx.organization_id {
         Some(id) => id,
         None => {
@@ -383,6 +504,64 @@ pub async fn get_workflow(
     }
 }
 
+/// Export a workflow as a JSON Schema document
+/// GET /v1/admin/workflows/:id/schema
+pub async fn get_workflow_schema(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    let repo = VisualWorkflowRepositoryImpl::new(state.database_service.clone());
+
+    let workflow = match repo.find_workflow_by_id(id).await {
+        Ok(Some(workflow)) => workflow,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": format!("Workflow {} not found", id) })),
+            )
+                .into_response()
+        }
+        Err(err) => return error_response(err).into_response(),
+    };
+
+    match engine_definition(workflow) {
+        Ok(definition) => (
+            StatusCode::OK,
+            [(axum::http::header::CONTENT_TYPE, "application/schema+json")],
+            definition.to_json_schema(),
+        )
+            .into_response(),
+        Err(e) => {
+            error!(error = %e, workflow_id = %id, "Stored workflow has invalid nodes or edges");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": format!("Workflow {} cannot be exported: {}", id, e) })),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateWorkflowSchemaResponse {
+    pub valid: bool,
+    pub errors: Vec<WorkflowSchemaIssue>,
+}
+
+/// Validate a JSON Schema workflow document without saving it
+/// POST /v1/admin/workflows/validate
+pub async fn validate_workflow_schema(body: String) -> impl IntoResponse {
+    let errors = match WorkflowDefinition::from_json_schema(&body, &connector_registry()) {
+        Ok(_) => Vec::new(),
+        Err(e) => e.issues,
+    };
+    let resp = ValidateWorkflowSchemaResponse {
+        valid: errors.is_empty(),
+        errors,
+    };
+    (StatusCode::OK, Json(serde_json::to_value(resp).unwrap_or_default())).into_response()
+}
+
 /// Update a workflow
 /// PUT /v1/admin/workflows/:id
 pub async fn update_workflow(
@@ -483,6 +662,122 @@ pub async fn deactivate_workflow(
 // Workflow Instance Handlers
 // ============================================================================
 
+#[derive(Debug, Serialize)]
+pub struct ContextViolation {
+    pub node_id: String,
+    #[serde(flatten)]
+    pub error: SchemaError,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateContextResponse {
+    pub valid: bool,
+    pub errors: Vec<ContextViolation>,
+}
+
+/// Validate a context against every node's input schema without starting execution
+/// POST /v1/admin/workflows/:id/validate-context
+pub async fn validate_context(
+    State(state): State<Arc<ConcreteAppState>>,
+    Path(id): Path<Uuid>,
+    Json(context): Json<HashMap<String, serde_json::Value>>,
+) -> impl IntoResponse {
+    let repo = VisualWorkflowRepositoryImpl::new(state.database_service.clone());
+
+    let workflow = match repo.find_workflow_by_id(id).await {
+        Ok(Some(workflow)) => workflow,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": format!("Workflow {} not found", id) })),
+            )
+                .into_response()
+        }
+        Err(err) => return error_response(err).into_response(),
+    };
+
+    let mut errors = Vec::new();
+    for (node_id, schema) in node_input_schemas(&workflow.nodes) {
+        let mut variables = context.clone();
+        if let Err(violations) = validate_variables(&mut variables, &schema) {
+            errors.extend(violations.into_iter().map(|error| ContextViolation {
+                node_id: node_id.clone(),
+                error,
+            }));
+        }
+    }
+
+    let resp = ValidateContextResponse {
+        valid: errors.is_empty(),
+        errors,
+    };
+    (StatusCode::OK, Json(serde_json::to_value(resp).unwrap_or_default())).into_response()
+}
+
+/// Collect `config.input_schema` from each node that declares one
+fn node_input_schemas(nodes: &serde_json::Value) -> Vec<(String, Vec<WorkflowVariableSchema>)> {
+    nodes
+        .as_array()
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter_map(|node| {
+                    let id = node.get("id")?.as_str()?.to_string();
+                    let schema = node.get("config")?.get("input_schema")?.clone();
+                    let schema: Vec<WorkflowVariableSchema> = serde_json::from_value(schema).ok()?;
+                    Some((id, schema))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiagramQuery {
+    /// `mermaid` (default) or `dot`
+    pub format: Option<String>,
+}
+
+/// Render a compiled-in state machine as a Mermaid or DOT diagram
+/// GET /v1/admin/workflows/:machine_name/diagram
+pub async fn get_machine_diagram(
+    Path(machine_name): Path<String>,
+    Query(query): Query<DiagramQuery>,
+) -> impl IntoResponse {
+    let dot = match query.format.as_deref() {
+        None | Some("mermaid") => false,
+        Some("dot") => true,
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({ "error": format!("Unknown diagram format: {}", other) })),
+            )
+                .into_response()
+        }
+    };
+
+    let diagram = match (machine_name.as_str(), dot) {
+        ("appointment", false) => AppointmentMachine::diagram(),
+        ("appointment", true) => AppointmentMachine::diagram_dot(),
+        ("order", false) => OrderMachine::diagram(),
+        ("order", true) => OrderMachine::diagram_dot(),
+        _ => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": format!("State machine {} not found", machine_name) })),
+            )
+                .into_response()
+        }
+    };
+
+    (
+        StatusCode::OK,
+        [(axum::http::header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        diagram,
+    )
+        .into_response()
+}
+
 /// Start a workflow instance
 /// POST /v1/admin/workflows/:id/start
 pub async fn start_instance(
@@ -595,13 +890,108 @@ pub async fn resume_instance(
 
 /// Cancel a workflow instance
 /// POST /v1/admin/workflow-instances/:id/cancel
+///
+/// The instance is cancelled through the workflow engine, which rejects
+/// finished instances and rolls back completed steps. Open human tasks are
+/// cancelled and the reason is recorded in the instance history.
 pub async fn cancel_instance(
     State(state): State<Arc<ConcreteAppState>>,
+    ctx: RequestContext,
     Path(id): Path<Uuid>,
+    Json(request): Json<CancelInstanceRequest>,
 ) -> impl IntoResponse {
     let repo = VisualWorkflowRepositoryImpl::new(state.database_service.clone());
 
-    match repo.update_instance_status(id, "cancelled", None).await {
+    if request.reason.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "reason is required" })),
+        )
+            .into_response();
+    }
+
+    let instance = match repo.get_instance(id).await {
+        Ok(Some(instance)) => instance,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": format!("Instance {} not found", id) })),
+            )
+                .into_response()
+        }
+        Err(err) => return error_response(err).into_response(),
+    };
+    let workflow = match repo.find_workflow_by_id(instance.workflow_id).await {
+        Ok(Some(workflow)) => workflow,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(serde_json::json!({ "error": format!("Workflow {} not found", instance.workflow_id) })),
+            )
+                .into_response()
+        }
+        Err(err) => return error_response(err).into_response(),
+    };
+
+    let (definition, restored) = match engine_definition(workflow)
+        .and_then(|definition| Ok((definition, engine_instance(&instance)?)))
+    {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!(error = %e, instance_id = %id, "Stored workflow instance cannot be loaded");
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({ "error": format!("Instance {} cannot be loaded: {}", id, e) })),
+            )
+                .into_response();
+        }
+    };
+
+    let engine = &state.workflow_engine;
+    if engine.get_workflow(&definition.id).await.is_none() {
+        if let Err(err) = engine.register_workflow(definition).await {
+            return error_response(err).into_response();
+        }
+    }
+    engine.restore_instance(restored).await;
+    let result = engine.cancel(&id.to_string(), request.reason.clone()).await;
+    // The database stays the instance's record; the engine only cancels it
+    let cancelled = engine.remove_instance(&id.to_string()).await;
+    if let Err(err) = result {
+        return error_response(err).into_response();
+    }
+    let Some(cancelled) = cancelled else {
+        return error_response(shared::AppError::Internal(format!("Instance {} was lost while cancelling", id)))
+            .into_response();
+    };
+
+    let tasks = match repo.get_tasks_by_instance(id).await {
+        Ok(tasks) => tasks,
+        Err(err) => return error_response(err).into_response(),
+    };
+    for task in tasks {
+        if matches!(task.status.as_str(), "pending" | "claimed") {
+            if let Err(err) = repo.cancel_task(task.id).await {
+                return error_response(err).into_response();
+            }
+        }
+    }
+
+    let step = serde_json::json!({
+        "event": "cancelled",
+        "reason": request.reason,
+        "cancelled_by": ctx.user_id,
+        "at": chrono::Utc::now(),
+    });
+    if let Err(err) = repo.append_instance_history(id, step).await {
+        return error_response(err).into_response();
+    }
+
+    let status = serde_json::to_value(&cancelled.status)
+        .ok()
+        .and_then(|s| s.as_str().map(str::to_string))
+        .unwrap_or_else(|| "cancelled".to_string());
+    match repo.update_instance_status(id, &status, cancelled.error).await {
         Ok(instance) => {
             let resp: InstanceResponse = instance.into();
             (StatusCode::OK, Json(serde_json::to_value(resp).unwrap_or_default())).into_response()
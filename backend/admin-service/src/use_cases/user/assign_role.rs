@@ -1,5 +1,8 @@
-use shared::domain::repositories::{UserRepository, RoleRepository};
-use shared::infrastructure::zanzibar::RelationshipStore;
+use crate::dto::AssignRoleRequest;
+use chrono::Utc;
+use shared::domain::entities::{Relationship, RelationshipWrite};
+use shared::domain::repositories::{AuditLogEntry, AuditLogRepository, UserRepository, RoleRepository};
+use shared::infrastructure::zanzibar::{GraphCache, RelationshipStore};
 use shared::AppResult;
 use uuid::Uuid;
 use std::sync::Arc;
@@ -8,6 +11,8 @@ pub struct AssignRoleUseCase {
     user_repository: Box<dyn UserRepository>,
     role_repository: Box<dyn RoleRepository>,
     relationship_store: Arc<RelationshipStore>,
+    graph_cache: Option<Arc<GraphCache>>,
+    audit_log: Option<Arc<dyn AuditLogRepository>>,
 }
 
 impl AssignRoleUseCase {
@@ -20,14 +25,37 @@ impl AssignRoleUseCase {
             user_repository,
             role_repository,
             relationship_store,
+            graph_cache: None,
+            audit_log: None,
         }
     }
 
+    /// Invalidate cached permission checks for the user after assignment
+    pub fn with_graph_cache(mut self, graph_cache: Arc<GraphCache>) -> Self {
+        self.graph_cache = Some(graph_cache);
+        self
+    }
+
+    /// Record assignments, with their validity window, in `audit_log`
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLogRepository>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
     pub async fn execute(
         &self,
         user_id: Uuid,
-        role_id: Uuid,
+        request: AssignRoleRequest,
     ) -> AppResult<()> {
+        let valid_from = request.valid_from.unwrap_or_else(Utc::now);
+        if let Some(valid_until) = request.valid_until {
+            if valid_until <= valid_from {
+                return Err(shared::AppError::Validation(
+                    "valid_until must be after valid_from".to_string()
+                ));
+            }
+        }
+
         // Verify user exists
         let _user = self.user_repository
             .find_by_id(user_id)
@@ -38,23 +66,55 @@ impl AssignRoleUseCase {
 
         // Verify role exists
         let role = self.role_repository
-            .find_by_id(role_id)
+            .find_by_id(request.role_id)
             .await?
             .ok_or_else(|| shared::AppError::NotFound(
-                format!("Role {} not found", role_id)
+                format!("Role {} not found", request.role_id)
             ))?;
 
-        // Create Zanzibar relationship: user#has_role@role
+        // Create Zanzibar relationship: user#has_role@role, valid in [valid_from, valid_until)
         let user_str = format!("user:{}", user_id);
         let role_str = format!("role:{}", role.name);
-        
+        let relationship = Relationship::new_with_validity(
+            user_str.clone(),
+            "has_role".to_string(),
+            role_str,
+            valid_from,
+            request.valid_until,
+        );
+
+        // Upsert so re-assigning an existing role replaces its validity window
         self.relationship_store
-            .add(&user_str, "has_role", &role_str)
+            .repository()
+            .write_tuples(vec![RelationshipWrite::Write(relationship)])
             .await?;
 
         // Role assignment is now Zanzibar-only, no need for user_roles table
 
+        if let Some(cache) = &self.graph_cache {
+            cache.invalidate_for_subject(&user_str);
+        }
+
+        if let Some(audit_log) = &self.audit_log {
+            let entry = AuditLogEntry {
+                // Administrator who made the assignment, when known
+                user_id: shared::RequestContext::current().map(|ctx| ctx.user_id),
+                action: "assign_role".to_string(),
+                resource: "user".to_string(),
+                resource_id: Some(user_id),
+                details: serde_json::json!({
+                    "role_id": role.id,
+                    "role": role.name,
+                    "valid_from": valid_from,
+                    "valid_until": request.valid_until,
+                }),
+            };
+            // The role is already assigned; a failed audit write must not fail the request
+            if let Err(e) = audit_log.create(entry).await {
+                e.log_with_operation(concat!(file!(), ":", line!()), "assign_role_audit");
+            }
+        }
+
         Ok(())
     }
 }
-
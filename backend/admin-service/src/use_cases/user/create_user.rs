@@ -1,7 +1,8 @@
 use crate::dto::{CreateUserRequest, UserResponse};
 use shared::domain::entities::{User, UserProvisioningChecklist};
-use shared::domain::repositories::UserRepository;
+use shared::domain::repositories::{UserProvisioningChecklistRepository, UserRepository};
 use shared::infrastructure::encryption::DekManager;
+use shared::infrastructure::validation::PasswordValidator;
 use shared::infrastructure::zanzibar::RelationshipStore;
 use shared::AppResult;
 use bcrypt::{hash, DEFAULT_COST};
@@ -13,6 +14,8 @@ pub struct CreateUserUseCase {
     dek_manager: Arc<DekManager>,
     #[allow(dead_code)]
     relationship_store: Arc<RelationshipStore>,
+    password_validator: Arc<PasswordValidator>,
+    checklist_repository: Box<dyn UserProvisioningChecklistRepository>,
 }
 
 impl CreateUserUseCase {
@@ -20,14 +23,23 @@ impl CreateUserUseCase {
         user_repository: Box<dyn UserRepository>,
         dek_manager: Arc<DekManager>,
         relationship_store: Arc<RelationshipStore>,
+        checklist_repository: Box<dyn UserProvisioningChecklistRepository>,
     ) -> Self {
         Self {
             user_repository,
             dek_manager,
             relationship_store,
+            password_validator: Arc::new(PasswordValidator::default()),
+            checklist_repository,
         }
     }
 
+    /// Validate passwords against a configured policy (e.g. with the HIBP check)
+    pub fn with_password_validator(mut self, password_validator: Arc<PasswordValidator>) -> Self {
+        self.password_validator = password_validator;
+        self
+    }
+
     pub async fn execute(&self, request: CreateUserRequest) -> AppResult<UserResponse> {
         // Initialize provisioning checklist
         let mut checklist = UserProvisioningChecklist::new(Uuid::new_v4()); // Will be updated with actual user_id
@@ -41,6 +53,8 @@ impl CreateUserUseCase {
             return Err(shared::AppError::Validation("User with this username already exists".to_string()));
         }
 
+        self.password_validator.validate(&request.password).await?;
+
         // Hash password
         let password_hash = hash(&request.password, DEFAULT_COST)
             .map_err(|e| shared::AppError::Internal(format!("Password hashing failed: {}", e)))?;
@@ -55,31 +69,38 @@ impl CreateUserUseCase {
 
         // Generate user DEK
         checklist.mark_item_in_progress("generate_dek");
-        self.dek_manager
-            .generate_dek(created_user.id, "user")
-            .await
-            .map_err(|e| {
-                checklist.mark_item_failed("generate_dek", format!("{}", e));
-                shared::AppError::Encryption(format!("Failed to generate user DEK: {}", e))
-            })?;
-        checklist.mark_item_completed("generate_dek");
-        checklist.mark_item_completed("store_dek"); // DEK is stored in vault by generate_dek
-
-        // Create default Zanzibar relationships (if any)
-        checklist.mark_item_in_progress("create_relationships");
-        // Default relationships can be added here if needed
-        checklist.mark_item_completed("create_relationships");
-
-        // Organization membership (if organization_id is provided in request)
-        // This would need to be added to CreateUserRequest if needed
-        checklist.mark_item_completed("organization_membership");
+        let dek_result = self.dek_manager.generate_dek(created_user.id, "user").await;
+        match &dek_result {
+            Ok(_) => {
+                checklist.mark_item_completed("generate_dek");
+                checklist.mark_item_completed("store_dek"); // DEK is stored in vault by generate_dek
+            }
+            Err(e) => checklist.mark_item_failed("generate_dek", format!("{}", e)),
+        }
 
-        // Default app access (can be added based on default role)
-        checklist.mark_item_completed("grant_app_access");
+        // Organization, relationships, realm, role and vault access are only
+        // set up by ProvisionUserUseCase; they can be run later as steps
+        for item in [
+            "organization_membership",
+            "create_relationships",
+            "grant_app_access",
+            "realm_assignment",
+            "assign_role",
+            "vault_user_creation",
+            "vault_token_creation",
+        ] {
+            checklist.mark_item_skipped(item);
+        }
 
         // Audit log (handled by repository)
         checklist.mark_item_completed("audit_log");
 
+        // Persist so the status can be queried and skipped steps run later
+        if let Err(e) = self.checklist_repository.save(&checklist).await {
+            tracing::warn!("Failed to save provisioning checklist for user {}: {}", created_user.id, e);
+        }
+
+        dek_result.map_err(|e| shared::AppError::Encryption(format!("Failed to generate user DEK: {}", e)))?;
         Ok(UserResponse::from(created_user))
     }
 }
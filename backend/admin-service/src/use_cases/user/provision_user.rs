@@ -3,19 +3,19 @@
 //! Creates a user with complete access setup including:
 //! - Admin service user creation
 //! - Realm assignment for organization
-//! - Role assignment via Zanzibar
-//! - App access via Zanzibar
+//! - Organization membership, role assignment and app access via Zanzibar,
+//!   written in a single transaction
 //! - Vault user creation (optional)
 //! - Realm-scoped policy creation
 //! - Vault token creation (optional)
 
 use crate::dto::UserResponse;
-use shared::domain::entities::{User, UserProvisioningChecklist};
-use shared::domain::repositories::{UserRepository, RoleRepository};
+use shared::domain::entities::{ProvisioningStep, Relationship, User, UserProvisioningChecklist};
+use shared::domain::repositories::{UserRepository, RoleRepository, UserProvisioningChecklistRepository};
 use shared::infrastructure::encryption::DekManager;
 use shared::infrastructure::encryption::vault_impl::RustyVaultClient;
 use shared::infrastructure::zanzibar::RelationshipStore;
-use shared::AppResult;
+use shared::{AppError, AppResult};
 use bcrypt::{hash, DEFAULT_COST};
 use uuid::Uuid;
 use std::sync::Arc;
@@ -71,6 +71,7 @@ pub struct ProvisionUserUseCase {
     dek_manager: Arc<DekManager>,
     relationship_store: Arc<RelationshipStore>,
     vault_client: Option<Arc<RustyVaultClient>>,
+    checklist_repository: Box<dyn UserProvisioningChecklistRepository>,
 }
 
 impl ProvisionUserUseCase {
@@ -80,6 +81,7 @@ impl ProvisionUserUseCase {
         dek_manager: Arc<DekManager>,
         relationship_store: Arc<RelationshipStore>,
         vault_client: Option<Arc<RustyVaultClient>>,
+        checklist_repository: Box<dyn UserProvisioningChecklistRepository>,
     ) -> Self {
         Self {
             user_repository,
@@ -87,12 +89,111 @@ impl ProvisionUserUseCase {
             dek_manager,
             relationship_store,
             vault_client,
+            checklist_repository,
         }
     }
 
+    /// Fail with `PreconditionFailed` unless every dependency of `step` is completed
+    pub fn check_preconditions(checklist: &UserProvisioningChecklist, step: ProvisioningStep) -> AppResult<()> {
+        let unmet = checklist.unmet_dependencies(step);
+        if unmet.is_empty() {
+            return Ok(());
+        }
+
+        let waiting_on: Vec<&str> = unmet.iter().map(|s| s.as_str()).collect();
+        Err(AppError::PreconditionFailed(format!(
+            "Cannot run '{}' until these steps are completed: {}",
+            step.as_str(),
+            waiting_on.join(", ")
+        )))
+    }
+
+    /// Run (or retry) a single provisioning step for an already provisioned user
+    /// Returns the updated checklist.
+    pub async fn execute_step(&self, user_id: Uuid, step: ProvisioningStep) -> AppResult<UserProvisioningChecklist> {
+        let mut checklist = self
+            .checklist_repository
+            .find_by_user_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("No provisioning checklist for user {}", user_id)))?;
+
+        Self::check_preconditions(&checklist, step)?;
+
+        checklist.mark_item_in_progress(step.as_str());
+        let result = self.run_step(&mut checklist, step).await;
+        match &result {
+            Ok(()) => checklist.mark_item_completed(step.as_str()),
+            Err(e) => checklist.mark_item_failed(step.as_str(), format!("{}", e)),
+        }
+        self.checklist_repository.save(&checklist).await?;
+
+        result.map(|_| checklist)
+    }
+
+    async fn run_step(&self, checklist: &mut UserProvisioningChecklist, step: ProvisioningStep) -> AppResult<()> {
+        let user_id = checklist.user_id;
+        let user_str = format!("user:{}", user_id);
+
+        match step {
+            ProvisioningStep::CreateUser => {
+                self.user_repository
+                    .find_by_id(user_id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound(format!("User {} not found", user_id)))?;
+            }
+            ProvisioningStep::GenerateDek => {
+                self.dek_manager
+                    .generate_dek(user_id, "user")
+                    .await
+                    .map_err(|e| AppError::Encryption(format!("Failed to generate user DEK: {}", e)))?;
+            }
+            // DEK is stored in vault by generate_dek
+            ProvisioningStep::StoreDek | ProvisioningStep::CreateRelationships | ProvisioningStep::AuditLog => {}
+            ProvisioningStep::RealmAssignment => {
+                if let (Some(vault_client), Some(organization_id)) = (&self.vault_client, checklist.organization_id) {
+                    checklist.realm_id = Some(vault_client.get_or_create_realm_for_org(organization_id).await?);
+                }
+            }
+            ProvisioningStep::OrganizationMembership => {
+                let organization_id = Self::organization_for(checklist)?;
+                let org_str = format!("organization:{}", organization_id);
+                self.relationship_store
+                    .add_with_organization(&user_str, "member_of", &org_str, Some(organization_id))
+                    .await?;
+            }
+            ProvisioningStep::GrantAppAccess => {
+                let organization_id = Self::organization_for(checklist)?;
+                for app_name in ["admin-ui", "client-app"] {
+                    let app_object = format!("organization:{}/app:{}", organization_id, app_name);
+                    self.relationship_store
+                        .add_with_organization(&user_str, "can_access", &app_object, Some(organization_id))
+                        .await?;
+                }
+            }
+            ProvisioningStep::AssignRole
+            | ProvisioningStep::VaultUserCreation
+            | ProvisioningStep::VaultTokenCreation => {
+                // These need inputs (role, password) that are only present in the provisioning request
+                return Err(AppError::Validation(format!(
+                    "Step '{}' can only run as part of a provisioning request",
+                    step.as_str()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn organization_for(checklist: &UserProvisioningChecklist) -> AppResult<Uuid> {
+        checklist.organization_id.ok_or_else(|| {
+            AppError::Validation(format!("User {} was not provisioned into an organization", checklist.user_id))
+        })
+    }
+
     pub async fn execute(&self, request: ProvisionUserRequest) -> AppResult<ProvisionUserResponse> {
         // Initialize provisioning checklist
         let mut checklist = UserProvisioningChecklist::new(Uuid::new_v4());
+        checklist.organization_id = Some(request.organization_id);
 
         // Step 1: Check if user already exists
         if self.user_repository.find_by_email(&request.email).await?.is_some() {
@@ -142,83 +243,90 @@ impl ProvisionUserUseCase {
                 }
             }
         } else {
-            checklist.mark_item_completed("realm_assignment");
+            checklist.mark_item_skipped("realm_assignment");
             None
         };
+        checklist.realm_id = realm_id;
 
-        // Step 6: Assign organization membership
-        checklist.mark_item_in_progress("organization_membership");
+        // Steps 6-8: Organization membership, role and app access, written in one transaction
         let user_str = format!("user:{}", created_user.id);
         let org_str = format!("organization:{}", request.organization_id);
-        
-        if let Err(e) = self.relationship_store
-            .add_with_organization(&user_str, "member_of", &org_str, Some(request.organization_id))
-            .await
-        {
-            checklist.mark_item_failed("organization_membership", format!("{}", e));
-            tracing::error!("Failed to create organization membership: {}", e);
-        } else {
-            checklist.mark_item_completed("organization_membership");
-        }
-
-        // Step 7: Assign role (if provided)
-        let assigned_role_name = if let Some(ref role_name) = request.role_name {
+        let organization_id = Some(request.organization_id);
+        checklist.mark_item_in_progress("organization_membership");
+        let mut tuples = vec![Relationship::new_with_organization(
+            user_str.clone(),
+            "member_of".to_string(),
+            org_str,
+            organization_id,
+        )];
+
+        // Role (if provided)
+        let role = if let Some(ref role_name) = request.role_name {
             checklist.mark_item_in_progress("create_relationships");
-            
+
             // Look up the role
-            match self.role_repository.find_by_name(role_name).await? {
-                Some(role) => {
-                    let role_str = format!("role:{}", role.name);
-                    if let Err(e) = self.relationship_store
-                        .add_with_organization(&user_str, "has_role", &role_str, Some(request.organization_id))
-                        .await
-                    {
-                        checklist.mark_item_failed("create_relationships", format!("{}", e));
-                        tracing::error!("Failed to assign role: {}", e);
-                        None
-                    } else {
-                        checklist.mark_item_completed("create_relationships");
-                        Some(role.name)
-                    }
-                }
+            let role = self.role_repository.find_by_name(role_name).await?;
+            match role {
+                Some(ref role) => tuples.push(Relationship::new_with_organization(
+                    user_str.clone(),
+                    "has_role".to_string(),
+                    format!("role:{}", role.name),
+                    organization_id,
+                )),
                 None => {
                     checklist.mark_item_failed("create_relationships", format!("Role '{}' not found", role_name));
                     tracing::warn!("Role '{}' not found", role_name);
-                    None
                 }
             }
+            role
         } else {
-            checklist.mark_item_completed("create_relationships");
             None
         };
 
-        // Step 8: Grant app access
-        let mut granted_apps = Vec::new();
+        // App access
         checklist.mark_item_in_progress("grant_app_access");
-        
         let apps_to_grant = if request.app_access.is_empty() {
             // Default apps
             vec!["admin-ui".to_string(), "client-app".to_string()]
         } else {
             request.app_access.clone()
         };
-
         for app_name in &apps_to_grant {
-            let app_object = format!("organization:{}/app:{}", request.organization_id, app_name);
-            if let Err(e) = self.relationship_store
-                .add_with_organization(&user_str, "can_access", &app_object, Some(request.organization_id))
-                .await
-            {
-                tracing::warn!("Failed to grant access to {}: {}", app_name, e);
-            } else {
-                granted_apps.push(app_name.clone());
-            }
+            tuples.push(Relationship::new_with_organization(
+                user_str.clone(),
+                "can_access".to_string(),
+                format!("organization:{}/app:{}", request.organization_id, app_name),
+                organization_id,
+            ));
         }
-        checklist.mark_item_completed("grant_app_access");
+
+        let (assigned_role_name, granted_apps) = match self.relationship_store.repository().write_batch(tuples).await {
+            Ok(_) => {
+                checklist.mark_item_completed("organization_membership");
+                // A role that was not found stays failed
+                if role.is_some() {
+                    checklist.mark_item_completed("create_relationships");
+                    checklist.mark_item_completed("assign_role");
+                } else if request.role_name.is_none() {
+                    checklist.mark_item_completed("create_relationships");
+                    checklist.mark_item_skipped("assign_role");
+                }
+                checklist.mark_item_completed("grant_app_access");
+                (role.map(|r| r.name), apps_to_grant)
+            }
+            Err(e) => {
+                tracing::error!("Failed to create relationships for user {}: {}", created_user.id, e);
+                for item in ["organization_membership", "create_relationships", "grant_app_access"] {
+                    checklist.mark_item_failed(item, format!("{}", e));
+                }
+                (None, Vec::new())
+            }
+        };
 
         // Step 9: Create vault user (optional)
         if request.create_vault_user {
             if let (Some(ref vault_client), Some(realm_id)) = (&self.vault_client, realm_id) {
+                Self::check_preconditions(&checklist, ProvisioningStep::VaultUserCreation)?;
                 checklist.mark_item_in_progress("vault_user_creation");
                 
                 // Build policies based on role
@@ -242,12 +350,17 @@ impl ProvisionUserUseCase {
                         tracing::warn!("Failed to create vault user: {}", e);
                     }
                 }
+            } else {
+                checklist.mark_item_skipped("vault_user_creation");
             }
+        } else {
+            checklist.mark_item_skipped("vault_user_creation");
         }
 
         // Step 10: Create vault token (optional)
         let vault_token = if request.create_vault_token {
             if let (Some(ref vault_client), Some(realm_id)) = (&self.vault_client, realm_id) {
+                Self::check_preconditions(&checklist, ProvisioningStep::VaultTokenCreation)?;
                 checklist.mark_item_in_progress("vault_token_creation");
                 
                 // Build policies based on role and apps
@@ -277,15 +390,22 @@ impl ProvisionUserUseCase {
                     }
                 }
             } else {
+                checklist.mark_item_skipped("vault_token_creation");
                 None
             }
         } else {
+            checklist.mark_item_skipped("vault_token_creation");
             None
         };
 
         // Audit log
         checklist.mark_item_completed("audit_log");
 
+        // Persist so steps can be retried and progress queried later
+        if let Err(e) = self.checklist_repository.save(&checklist).await {
+            tracing::warn!("Failed to save provisioning checklist for user {}: {}", created_user.id, e);
+        }
+
         Ok(ProvisionUserResponse {
             user: UserResponse::from(created_user),
             realm_id,
@@ -369,3 +489,38 @@ impl GrantVaultAccessUseCase {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_access_blocked_until_user_created() {
+        let mut checklist = UserProvisioningChecklist::new(Uuid::new_v4());
+        checklist.mark_item_completed("realm_assignment");
+
+        let err = ProvisionUserUseCase::check_preconditions(&checklist, ProvisioningStep::VaultUserCreation)
+            .unwrap_err();
+        assert!(matches!(err, AppError::PreconditionFailed(ref msg) if msg.contains("create_user")));
+        assert!(!checklist.executable_steps().contains(&ProvisioningStep::VaultUserCreation));
+
+        checklist.mark_item_completed("create_user");
+
+        assert!(ProvisionUserUseCase::check_preconditions(&checklist, ProvisioningStep::VaultUserCreation).is_ok());
+        assert!(checklist.executable_steps().contains(&ProvisioningStep::VaultUserCreation));
+    }
+
+    #[test]
+    fn test_skipped_steps_can_run_later_but_do_not_satisfy_dependencies() {
+        let mut checklist = UserProvisioningChecklist::new(Uuid::new_v4());
+        for step in ProvisioningStep::ALL {
+            checklist.mark_item_completed(step.as_str());
+        }
+        checklist.mark_item_skipped("realm_assignment");
+        checklist.mark_item_skipped("vault_user_creation");
+
+        assert!(checklist.is_completed());
+        assert!(checklist.executable_steps().contains(&ProvisioningStep::RealmAssignment));
+        assert!(ProvisionUserUseCase::check_preconditions(&checklist, ProvisioningStep::VaultUserCreation).is_err());
+    }
+}
@@ -354,7 +354,10 @@ async fn main() {
     };
 
     // Test vault connectivity by creating DEK manager
-    let dek_manager = DekManager::new(master_key, vault);
+    let mut dek_manager = DekManager::new(master_key, vault);
+    if let Some(transit_key) = &settings.encryption.transit_key {
+        dek_manager = dek_manager.with_transit_key(transit_key);
+    }
     println!("✓ DEK Manager initialized\n");
 
     // Collect organization information
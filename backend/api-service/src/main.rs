@@ -14,11 +14,14 @@ async fn async_main() -> Result<(), String> {
     dotenv::dotenv().ok();
 
     // Load configuration
-    let settings = shared::config::Settings::from_env()
-        .map_err(|e| {
-            eprintln!("Failed to load configuration: {}", e);
-            format!("Failed to load configuration: {}", e)
-        })?;
+    let settings = shared::config::Settings::from_env_validated()
+        .unwrap_or_else(|errors| {
+            eprintln!("Invalid configuration:");
+            for error in &errors {
+                eprintln!("  - {}", error);
+            }
+            std::process::exit(1);
+        });
 
     // Initialize logger with settings and deployment config (single point of control for dev mode)
     shared::infrastructure::logging::init_from_settings_with_deployment(
@@ -84,6 +87,12 @@ async fn async_main() -> Result<(), String> {
         &settings.oidc.jwt_secret,
         settings.oidc.issuer.clone(),
         settings.oidc.jwt_expiration,
+    )
+    // Refresh tokens are valid until the absolute bound; the database row holds the sliding expiry
+    .with_refresh_token_days(i64::from(settings.oidc.max_refresh_token_days));
+    let refresh_token_policy = authz_core::auth::RefreshTokenPolicy::new(
+        settings.oidc.refresh_token_extend_days,
+        settings.oidc.max_refresh_token_days,
     );
     let token_manager_arc = Arc::new(token_manager.clone());
 
@@ -94,9 +103,73 @@ async fn async_main() -> Result<(), String> {
     
     let permission_repository = Arc::new(shared::infrastructure::repositories::PermissionRepositoryImpl::new(pool.clone()));
     
+    // Initialize vault (OpenBao/KMS) first - needed for master key storage
+    info!("Initializing vault...");
+    use shared::config::providers::ProviderConfig;
+    use shared::infrastructure::providers::create_kms_provider;
+    let provider_config = ProviderConfig::from_env()
+        .map_err(|e| format!("Failed to load provider config: {}", e))?;
+    let vault: Arc<dyn shared::infrastructure::encryption::Vault> = Arc::from(
+        create_kms_provider(&provider_config.kms)
+            .map_err(|e| format!("Failed to create KMS provider: {}", e))?,
+    );
+    info!("Vault initialized");
+
+    // Initialize master key: OpenBao/Vault first, then file/environment fallbacks,
+    // generating one on first-time setup; wherever it came from, keep it in the vault
+    info!("Initializing master key...");
+    use shared::infrastructure::encryption::{MasterKeyLoader, MasterKeySource};
+
+    let mut master_key_sources = vec![MasterKeySource::Vault(vault.clone())];
+    if let Some(path) = &settings.encryption.master_key_path {
+        master_key_sources.push(MasterKeySource::File(path.into()));
+    }
+    master_key_sources.push(MasterKeySource::Environment("MASTER_KEY".to_string()));
+    master_key_sources.push(MasterKeySource::Generate);
+
+    let (master_key, master_key_source) = MasterKeyLoader::new(master_key_sources)
+        .persist_to(MasterKeySource::Vault(vault.clone()))
+        .load()
+        .await
+        .map_err(|e| format!("Cannot load master key. Set up OpenBao/Vault or configure MASTER_KEY_PATH/MASTER_KEY: {}", e))?;
+    info!("Master key loaded from {}", master_key_source.describe());
+
+    // The key's version is not stored with it; stored DEKs record the version that wraps them
+    let master_key = match shared::infrastructure::repositories::KeyRepositoryImpl::latest_key_version(&pool).await {
+        Ok(Some(version)) => master_key.with_version(version),
+        Ok(None) => master_key,
+        Err(e) => {
+            tracing::warn!("Failed to read master key version: {}", e);
+            master_key
+        }
+    };
+    info!("Master key initialized (version {})", master_key.version());
+
+    // Create DEK Manager
+    use shared::infrastructure::encryption::DekManager;
+    let mut dek_manager = DekManager::new(master_key, Box::new(vault.clone())).with_database_storage(pool.clone());
+    if let Some(transit_key) = &settings.encryption.transit_key {
+        dek_manager = dek_manager.with_transit_key(transit_key);
+    }
+    let dek_manager = Arc::new(dek_manager);
+    info!("DEK Manager initialized");
+
+    // Users are looked up by a deterministic ciphertext of their email
+    let field_encryption = Arc::new(
+        shared::infrastructure::encryption::FieldEncryption::new(dek_manager.clone())
+            .await
+            .map_err(|e| format!("Failed to load field encryption key: {}", e))?,
+    );
+    let user_repository = || {
+        Box::new(
+            shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())
+                .with_field_encryption(field_encryption.clone()),
+        )
+    };
+
     // Initialize use cases using DatabaseService
     let get_permissions_use_case = authz_core::authorization::GetUserPermissionsUseCase::new(
-        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
+        user_repository(),
         Box::new(shared::infrastructure::repositories::RoleRepositoryImpl::new(
             database_service.clone(),
             relationship_store.clone(),
@@ -105,8 +178,62 @@ async fn async_main() -> Result<(), String> {
         Box::new(shared::infrastructure::repositories::PermissionRepositoryImpl::new(pool.clone())),
     );
 
-    let login_use_case = Arc::new(authz_core::auth::LoginUseCase::new(
-        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
+    // Initialize RustyVault client for realm lookups, token minting and TOTP MFA
+    // This is optional - if vault is not configured, realm features will be disabled
+    info!("Initializing RustyVault client...");
+    use shared::infrastructure::encryption::RustyVaultClient;
+    let vault_client = match RustyVaultClient::from_env() {
+        Ok(client) => {
+            info!("RustyVault client initialized");
+            Some(Arc::new(client))
+        }
+        Err(e) => {
+            tracing::warn!("RustyVault client not available (vault features disabled): {}", e);
+            None
+        }
+    };
+
+    // One Redis connection shared by login lockout, the session cache and idempotency keys, when configured
+    let redis_store: Option<Arc<dyn shared::infrastructure::session::RedisStore>> =
+        match &settings.session.redis_url {
+            Some(redis_url) => Some(Arc::new(
+                shared::infrastructure::session::RedisConnection::connect(redis_url)
+                    .await
+                    .map_err(|e| format!("Failed to connect to Redis: {}", e))?,
+            )),
+            None => None,
+        };
+
+    // Failed logins are counted per username; Redis shares the counts between instances
+    let lockout_policy = authz_core::auth::LockoutPolicy::new(
+        settings.session.login_max_attempts,
+        settings.session.login_lockout_duration_minutes,
+    );
+    let attempt_tracker = Arc::new(match &redis_store {
+        Some(redis) => authz_core::auth::LoginAttemptTracker::new(
+            Arc::new(authz_core::auth::RedisLoginAttemptStore::new(redis.clone())),
+            lockout_policy,
+        ),
+        None => authz_core::auth::LoginAttemptTracker::in_memory(lockout_policy),
+    });
+
+    let claims_transformer = authz_core::oidc::ClaimsTransformer::new(
+        Arc::new(shared::infrastructure::repositories::RoleRepositoryImpl::new(
+            database_service.clone(),
+            relationship_store.clone(),
+            permission_repository.clone(),
+        )),
+        permission_repository.clone(),
+    )
+    .with_claim_overrides(settings.oidc.claim_overrides.clone());
+    // Realm claim overrides are keyed by the organization's vault realm
+    let claims_transformer = match &vault_client {
+        Some(client) => claims_transformer.with_realm_resolver(client.clone()),
+        None => claims_transformer,
+    };
+
+    let login_use_case = authz_core::auth::LoginUseCase::new(
+        user_repository(),
         Box::new(shared::infrastructure::repositories::RefreshTokenRepositoryImpl::new(pool.clone())),
         Box::new(shared::infrastructure::repositories::RoleRepositoryImpl::new(
             database_service.clone(),
@@ -115,20 +242,27 @@ async fn async_main() -> Result<(), String> {
         )),
         Box::new(shared::infrastructure::repositories::PermissionRepositoryImpl::new(pool.clone())),
         token_manager.clone(),
-    ));
+    ).with_claims_transformer(claims_transformer)
+    .with_attempt_tracker(attempt_tracker)
+    .with_refresh_token_policy(refresh_token_policy);
+    // Users enrolled in vault TOTP must present a code at login
+    let login_use_case = Arc::new(match &vault_client {
+        Some(client) => login_use_case.with_mfa_verifier(client.clone()),
+        None => login_use_case,
+    });
 
     let refresh_token_use_case = Arc::new(authz_core::auth::RefreshTokenUseCase::new(
-        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
+        user_repository(),
         Box::new(shared::infrastructure::repositories::RefreshTokenRepositoryImpl::new(pool.clone())),
         token_manager.clone(),
-    ));
+    ).with_policy(refresh_token_policy));
 
     let logout_use_case = Arc::new(authz_core::auth::LogoutUseCase::new(
         Box::new(shared::infrastructure::repositories::RefreshTokenRepositoryImpl::new(pool.clone())),
-    ));
+    ).with_audit_log(Arc::new(shared::infrastructure::repositories::AuditLogRepositoryImpl::new(database_service.clone()))));
 
     let userinfo_use_case = Arc::new(authz_core::auth::UserInfoUseCase::new(
-        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
+        user_repository(),
         get_permissions_use_case,
     ));
 
@@ -136,14 +270,24 @@ async fn async_main() -> Result<(), String> {
     info!("Initializing graph cache...");
     use shared::infrastructure::zanzibar::GraphCache;
     let graph_cache = if settings.graph_cache.enabled {
-        Arc::new(GraphCache::new(settings.graph_cache.ttl_seconds, true))
+        Arc::new(GraphCache::with_config(
+            shared::infrastructure::zanzibar::GraphCacheConfig {
+                default_ttl_secs: settings.graph_cache.ttl_seconds.max(0) as u64,
+                ttl_by_relation: settings.graph_cache.ttl_by_relation.clone(),
+                max_entries: settings.graph_cache.max_entries,
+            },
+            true,
+        ))
     } else {
         info!("Graph cache disabled");
         Arc::new(GraphCache::disabled())
     };
-    info!("Graph cache initialized: enabled={}, ttl={}s", 
+    info!("Graph cache initialized: enabled={}, ttl={}s, max_entries={}", 
         settings.graph_cache.enabled, 
-        settings.graph_cache.ttl_seconds);
+        settings.graph_cache.ttl_seconds,
+        settings.graph_cache.max_entries);
+    crate::presentation::api::metrics::register_graph_cache(graph_cache.clone())
+        .map_err(|e| format!("Failed to register graph cache metrics: {}", e))?;
 
     // Permission checker (uses relationship_store with optional graph cache)
     let permission_checker = Arc::new(
@@ -161,113 +305,30 @@ async fn async_main() -> Result<(), String> {
     
     let setup_organization_use_case = Arc::new(admin_service::use_cases::setup::SetupOrganizationUseCase::new(
         Box::new(shared::infrastructure::repositories::SetupRepositoryImpl::new(pool.clone())),
-        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
+        user_repository(),
     ));
     
     let create_super_admin_use_case = Arc::new(admin_service::use_cases::setup::CreateSuperAdminUseCase::new(
         Box::new(shared::infrastructure::repositories::SetupRepositoryImpl::new(pool.clone())),
-        Box::new(shared::infrastructure::repositories::UserRepositoryImpl::new(database_service.clone())),
+        user_repository(),
     ));
 
-    // Initialize vault (OpenBao/KMS) first - needed for master key storage
-    info!("Initializing vault...");
-    use shared::config::providers::ProviderConfig;
-    use shared::infrastructure::providers::create_kms_provider;
-    let provider_config = ProviderConfig::from_env()
-        .map_err(|e| format!("Failed to load provider config: {}", e))?;
-    let vault = create_kms_provider(&provider_config.kms)
-        .map_err(|e| format!("Failed to create KMS provider: {}", e))?;
-    info!("Vault initialized");
 
-    // Initialize master key
-    info!("Initializing master key...");
-    use shared::infrastructure::encryption::MasterKey;
-    use std::path::Path;
-    
-    // Try to load master key from OpenBao/Vault first (preferred)
-    let master_key = match MasterKey::from_vault(vault.as_ref()).await {
-        Ok(Some(key)) => {
-            info!("Master key loaded from OpenBao/Vault");
-            key
-        }
-        Ok(None) => {
-            // Master key doesn't exist in vault - try fallback sources
-            info!("Master key not found in OpenBao/Vault, trying fallback sources...");
-            
-            if let Some(path) = &settings.encryption.master_key_path {
-                // Load from file
-                match MasterKey::from_file(Path::new(path)) {
-                    Ok(key) => {
-                        info!("Master key loaded from file (fallback)");
-                        // Try to store in vault for future use
-                        let _ = key.save_to_vault(vault.as_ref()).await;
-                        key
-                    }
-                    Err(_) => {
-                        // Generate new master key
-                        info!("Generating new master key...");
-                        let key = MasterKey::generate()
-                            .map_err(|e| format!("Failed to generate master key: {}", e))?;
-                        
-                        // Try to store in vault first
-                        if let Err(e) = key.save_to_vault(vault.as_ref()).await {
-                            tracing::warn!("Failed to store master key in vault: {}, saving to file", e);
-                            std::fs::create_dir_all(Path::new(path).parent().unwrap())
-                                .map_err(|e| format!("Failed to create master key directory: {}", e))?;
-                            key.save_to_file(Path::new(path))
-                                .map_err(|e| format!("Failed to save master key: {}", e))?;
-                            info!("Generated and saved master key to: {}", path);
-                        } else {
-                            info!("Generated and stored master key in OpenBao/Vault");
-                        }
-                        key
-                    }
-                }
-            } else if let Ok(_) = std::env::var("MASTER_KEY") {
-                // Load from environment (hex-encoded)
-                let key = MasterKey::from_env("MASTER_KEY")
-                    .map_err(|e| format!("Failed to load master key from environment: {}", e))?;
-                info!("Master key loaded from environment (fallback)");
-                // Try to store in vault for future use
-                let _ = key.save_to_vault(vault.as_ref()).await;
-                key
-            } else {
-                // Generate new master key (first-time setup)
-                info!("Generating new master key...");
-                let key = MasterKey::generate()
-                    .map_err(|e| format!("Failed to generate master key: {}", e))?;
-                
-                // Try to store in vault
-                if let Err(e) = key.save_to_vault(vault.as_ref()).await {
-                    tracing::warn!("Failed to store master key in vault: {}", e);
-                    tracing::warn!("Master key generated but not persisted. Set up OpenBao/Vault or MASTER_KEY_PATH/MASTER_KEY env var.");
-                } else {
-                    info!("Generated and stored master key in OpenBao/Vault");
-                }
-                key
-            }
-        }
-        Err(e) => {
-            tracing::warn!("Failed to retrieve master key from OpenBao/Vault: {}, using fallback", e);
-            
-            // Fallback to file or environment
-            if let Some(path) = &settings.encryption.master_key_path {
-                MasterKey::from_file(Path::new(path))
-                    .map_err(|e| format!("Failed to load master key from file: {}", e))?
-            } else if let Ok(_) = std::env::var("MASTER_KEY") {
-                MasterKey::from_env("MASTER_KEY")
-                    .map_err(|e| format!("Failed to load master key from environment: {}", e))?
-            } else {
-                return Err(format!("Cannot load master key. Set up OpenBao/Vault or configure MASTER_KEY_PATH/MASTER_KEY"));
-            }
-        }
-    };
-    info!("Master key initialized");
+    // Dependencies probed by /health and /ready
+    use shared::infrastructure::health::{DatabaseProbe, HealthChecker, MumpsProbe, SessionCacheProbe, VaultProbe};
+    let mut health_checker = HealthChecker::new()
+        .with_probe(Arc::new(DatabaseProbe(database_service.clone())))
+        .with_probe(Arc::new(VaultProbe(vault.clone())));
+    if let Some(redis) = &redis_store {
+        health_checker = health_checker.with_probe(Arc::new(SessionCacheProbe(redis.clone())));
+    }
+    if std::env::var("YOTTADB_URL").is_ok() {
+        health_checker = health_checker.with_probe(Arc::new(MumpsProbe(Arc::new(
+            shared::infrastructure::database::mumps::YottaDbAdapter::from_env(),
+        ))));
+    }
+    let health_checker = Arc::new(health_checker);
 
-    // Create DEK Manager
-    use shared::infrastructure::encryption::DekManager;
-    let dek_manager = Arc::new(DekManager::new(master_key, vault));
-    info!("DEK Manager initialized");
 
     // Create role repository (uses relationship_store and permission_repository)
     let role_repository = Arc::new(shared::infrastructure::repositories::RoleRepositoryImpl::new(
@@ -279,32 +340,148 @@ async fn async_main() -> Result<(), String> {
     // Initialize session service
     info!("Initializing session service...");
     let session_repository = Arc::new(shared::infrastructure::repositories::SessionRepositoryImpl::new(database_service.clone()));
-    let session_cache = Arc::new(shared::infrastructure::session::SessionCache::with_max_entries(
-        settings.session.cache_max_entries,
-    ));
-    info!("Session cache configured with max {} entries", settings.session.cache_max_entries);
-    let session_service = Arc::new(shared::infrastructure::session::SessionService::new(
-        session_repository,
-        session_cache,
-        settings.session.clone(),
-    ));
+    let session_cache: Box<dyn shared::infrastructure::session::SessionCacheBackend> =
+        match &redis_store {
+            Some(redis) => {
+                let cache = shared::infrastructure::session::RedisSessionCache::with_store(
+                    redis.clone(),
+                    settings.session.cache_max_entries,
+                )
+                .await
+                .map_err(|e| format!("Failed to start Redis session cache: {}", e))?;
+                info!("Session cache backed by Redis (max {} local entries)", settings.session.cache_max_entries);
+                Box::new(cache)
+            }
+            None => {
+                info!("Session cache configured with max {} entries", settings.session.cache_max_entries);
+                Box::new(shared::infrastructure::session::SessionCache::with_max_entries(
+                    settings.session.cache_max_entries,
+                ))
+            }
+        };
+    let session_service = Arc::new(
+        shared::infrastructure::session::SessionService::new(
+            session_repository,
+            session_cache,
+            settings.session.clone(),
+        )
+        .with_audit_log(Arc::new(shared::infrastructure::repositories::AuditLogRepositoryImpl::new(
+            database_service.clone(),
+        ))),
+    );
     info!("Session service initialized");
 
-    // Initialize RustyVault client for realm lookups and token minting
-    // This is optional - if vault is not configured, realm features will be disabled
-    info!("Initializing RustyVault client...");
-    use shared::infrastructure::encryption::RustyVaultClient;
-    let vault_client = match RustyVaultClient::from_env() {
-        Ok(client) => {
-            info!("RustyVault client initialized");
-            Some(Arc::new(client))
-        }
-        Err(e) => {
-            tracing::warn!("RustyVault client not available (vault features disabled): {}", e);
-            None
-        }
+    // Responses to POSTs retried with an Idempotency-Key, in Postgres and (when configured) Redis
+    let idempotency_store = {
+        let store = crate::presentation::api::middleware::IdempotencyStore::new(Arc::new(
+            shared::infrastructure::repositories::IdempotencyKeyRepositoryImpl::new(database_service.clone()),
+        ));
+        Arc::new(match &redis_store {
+            Some(redis) => store.with_redis(redis.clone()),
+            None => store,
+        })
     };
 
+    // Patients store their SSN last four as a deterministic ciphertext
+    let ehr_patient_repository: Arc<dyn shared::domain::repositories::ehr::EhrPatientRepository> = Arc::new(
+        shared::infrastructure::repositories::ehr::EhrPatientRepositoryImpl::new(database_service.clone())
+            .with_field_encryption(field_encryption.clone()),
+    );
+
+    // Start background job worker for long-running EHR tasks
+    info!("Starting background job worker...");
+    let job_queue = Arc::new(shared::infrastructure::jobs::JobQueue::new(Arc::new(pool.clone())));
+    shared::application::services::register_ehr_job_handlers(
+        shared::infrastructure::jobs::JobWorker::new(job_queue),
+        ehr_patient_repository.clone(),
+    )
+    .spawn();
+    info!("Background job worker started");
+
+    // Purge expired (time-bounded) role assignments hourly
+    tokio::spawn(shared::infrastructure::zanzibar::expired_role_cleanup::run(Arc::new(
+        shared::infrastructure::repositories::RelationshipRepositoryImpl::new(pool.clone()),
+    )));
+
+    // Copy clinical documents from YottaDB into the full-text index every minute
+    tokio::spawn(
+        shared::application::services::DocumentSyncTask::new(
+            Arc::new(shared::infrastructure::database::mumps::YottaDbAdapter::from_env()),
+            Arc::new(shared::infrastructure::repositories::ehr::EhrDocumentSearchRepositoryImpl::new(
+                database_service.clone(),
+            )),
+        )
+        .run(),
+    );
+
+    // Patient document storage, with presigned URLs for direct uploads
+    let storage_url_signer = shared::infrastructure::storage::StorageUrlSigner::new(
+        &settings.storage.public_url,
+        &settings.oidc.jwt_secret,
+    );
+    let document_storage: Arc<dyn shared::infrastructure::storage::Storage> = Arc::from(
+        shared::infrastructure::providers::create_presigning_storage_provider(
+            &provider_config.storage,
+            storage_url_signer.clone(),
+        )
+        .map_err(|e| format!("Failed to create document storage: {}", e))?,
+    );
+
+    // Decision rules are loaded from the database, and evaluations are recorded for backtests
+    let rules_engine = shared::application::services::create_persistent_rules_engine(Arc::new(
+        shared::infrastructure::repositories::DecisionRuleRepositoryImpl::new(database_service.clone()),
+    ))
+    .await
+    .map_err(|e| format!("Failed to load decision rules: {}", e))?;
+
+    // One workflow engine for the whole process; the scheduler starts its cron-triggered workflows
+    let workflow_engine = shared::application::services::create_workflow_engine_with_rules(rules_engine.clone());
+    let cron_scheduler = shared::application::services::CronScheduler::start(workflow_engine.clone());
+    info!("Workflow cron scheduler started");
+
+    // Escalate human tasks whose SLA lapsed
+    Arc::new(shared::application::services::TaskSlaEnforcer::new(workflow_engine.clone()))
+        .schedule(&cron_scheduler)
+        .await
+        .map_err(|e| format!("Failed to schedule task SLA enforcement: {}", e))?;
+
+    // Cancel orders left unsigned for a day
+    Arc::new(shared::domain::state_machine::StaleOrderCanceller::new(
+        Arc::new(shared::infrastructure::database::mumps::YottaDbAdapter::from_env()),
+        Arc::new(shared::infrastructure::repositories::AuditLogRepositoryImpl::new(database_service.clone())),
+        shared::domain::state_machine::DEFAULT_STALE_ORDER_AGE,
+    ))
+    .schedule(&cron_scheduler)
+    .await
+    .map_err(|e| format!("Failed to schedule stale order cancellation: {}", e))?;
+
+    // Remind patients of confirmed appointments a day ahead; the consumer
+    // records each reminder on its appointment
+    let appointment_reminders = Arc::new(
+        shared::infrastructure::repositories::ehr::EhrAppointmentReminderRepositoryImpl::new(database_service.clone()),
+    );
+    let (reminder_queue, reminders) =
+        tokio::sync::mpsc::channel(shared::domain::state_machine::REMINDER_QUEUE_CAPACITY);
+    tokio::spawn(shared::domain::state_machine::record_reminders(reminders, appointment_reminders.clone()));
+    Arc::new(shared::domain::state_machine::ReminderScheduler::new(appointment_reminders, Arc::new(reminder_queue)))
+        .schedule(&cron_scheduler)
+        .await
+        .map_err(|e| format!("Failed to schedule appointment reminders: {}", e))?;
+
+    // Move audit log rows past their retention to cold storage
+    let audit_archive_storage: Arc<dyn shared::infrastructure::storage::Storage> = Arc::from(
+        shared::infrastructure::providers::create_storage_provider(&provider_config.storage)
+            .map_err(|e| format!("Failed to create audit archive storage: {}", e))?,
+    );
+    Arc::new(shared::infrastructure::audit_archive::AuditArchiver::new(
+        settings.audit_retention,
+        pool.clone(),
+        audit_archive_storage,
+    ))
+    .schedule(&cron_scheduler)
+    .await
+    .map_err(|e| format!("Failed to schedule audit log archiving: {}", e))?;
+
     // Create application state
     use api_service::AppState;
     let app_state = AppState {
@@ -322,22 +499,39 @@ async fn async_main() -> Result<(), String> {
         create_super_admin_use_case,
         dek_manager,
         role_repository,
+        ehr_patient_repository,
         graph_cache: Some(graph_cache),
         session_service,
+        rules_engine,
+        workflow_engine,
+        appointment_events: Arc::new(shared::application::services::AppointmentEventBroadcaster::new()),
         vault_client,
+        require_access_reason: settings.hipaa.require_access_reason,
+        body_sampler: shared::infrastructure::logging::BodySampler::new(settings.logging.body_sampling_rate),
+        document_storage,
+        storage_url_signer,
+        features: settings.deployment.features,
     };
 
     // Build application router with state, middleware, and CORS
     let app_state_arc = Arc::new(app_state);
     
     // Create public routes (no auth required)
-    // All routes use /v1/ prefix for versioning (except /health)
+    // All routes use /v1/ prefix for versioning (except the health checks)
     let public_routes = axum::Router::new()
-        .route("/health", axum::routing::get(|| async { "OK" })) // Health check stays unversioned
         .route("/v1/auth/login", axum::routing::post(crate::presentation::api::handlers::login))
         .route("/v1/setup/status", axum::routing::get(admin_service::handlers::check_setup_status))
         .route("/v1/setup/initialize", axum::routing::post(admin_service::handlers::initialize_setup))
         .route("/v1/services/status", axum::routing::get(crate::presentation::api::handlers::get_service_status))
+        // Presigned URLs carry their own authorization
+        .route(
+            "/v1/storage/presigned",
+            axum::routing::get(crate::presentation::api::handlers::get_presigned_object)
+                .put(crate::presentation::api::handlers::put_presigned_object)
+                .layer(axum::extract::DefaultBodyLimit::max(
+                    crate::presentation::api::handlers::MAX_PRESIGNED_UPLOAD_BYTES,
+                )),
+        )
         .with_state(app_state_arc.clone());
     
     // Create protected routes with middleware
@@ -345,6 +539,7 @@ async fn async_main() -> Result<(), String> {
     let protected_routes = axum::Router::new()
         // Auth routes
         .route("/v1/auth/logout", axum::routing::post(crate::presentation::api::handlers::logout))
+        .route("/v1/auth/logout/all", axum::routing::post(crate::presentation::api::handlers::logout_all))
         .route("/v1/auth/token", axum::routing::post(crate::presentation::api::handlers::refresh_token))
         .route("/v1/auth/userinfo", axum::routing::get(crate::presentation::api::handlers::userinfo))
         // User routes
@@ -352,9 +547,16 @@ async fn async_main() -> Result<(), String> {
         .route("/v1/users/{id}", axum::routing::get(admin_service::handlers::get_user))
         .route("/v1/users/{id}", axum::routing::post(admin_service::handlers::update_user))
         .route("/v1/users/{id}", axum::routing::delete(admin_service::handlers::delete_user))
+        .route("/v1/admin/users/{id}/provisioning-status", axum::routing::get(admin_service::handlers::get_user_provisioning_status))
+        .route("/v1/admin/users/{id}/sessions", axum::routing::get(admin_service::handlers::list_user_sessions))
+        .route("/v1/admin/users/{id}/sessions", axum::routing::delete(admin_service::handlers::force_logout_user))
+        .route("/v1/admin/users/{id}/sessions/{session_id}", axum::routing::delete(admin_service::handlers::end_user_session))
+        .route("/v1/admin/users/{id}/roles", axum::routing::post(admin_service::handlers::assign_role_to_user))
         // Permission check routes
         .route("/v1/admin/permissions/check", axum::routing::post(admin_service::handlers::check_permission))
         .route("/v1/admin/permissions/check-batch", axum::routing::post(admin_service::handlers::check_permissions_batch))
+        .route("/v1/admin/permissions/explain-denial", axum::routing::post(admin_service::handlers::explain_denial))
+        .route("/v1/admin/permissions/explain", axum::routing::post(admin_service::handlers::explain_permission))
         .route("/v1/admin/permissions/user/{id}", axum::routing::get(admin_service::handlers::get_user_permissions))
         .route("/v1/admin/permissions/user/{id}/pages", axum::routing::get(admin_service::handlers::get_user_pages))
         .route("/v1/admin/permissions/user/{id}/buttons/{page}", axum::routing::get(admin_service::handlers::get_user_buttons))
@@ -382,15 +584,31 @@ async fn async_main() -> Result<(), String> {
         .route("/v1/admin/groups/{group_id}/roles/{role_id}", axum::routing::post(admin_service::handlers::assign_role_to_group))
         // Dashboard routes
         .route("/v1/admin/dashboard/stats", axum::routing::get(admin_service::handlers::get_dashboard_stats))
+        .route("/v1/admin/storage/integrity-check", axum::routing::get(admin_service::handlers::check_storage_integrity))
+        .route("/v1/admin/cache/stats", axum::routing::get(admin_service::handlers::get_cache_stats))
+        .route("/v1/admin/features", axum::routing::get(admin_service::handlers::list_feature_flags))
+        // Master key ceremony and key rotation routes (super admin only)
+        .route("/v1/admin/encryption/key-ceremony/split", axum::routing::post(admin_service::handlers::split_master_key))
+        .route("/v1/admin/encryption/key-ceremony/recover", axum::routing::post(admin_service::handlers::recover_master_key))
+        .route("/v1/admin/encryption/rotate-master-key", axum::routing::post(admin_service::handlers::rotate_master_key))
+        .route("/v1/admin/encryption/rotate-dek/{key_id}", axum::routing::post(admin_service::handlers::rotate_dek))
+        .route("/v1/admin/encryption/migrate", axum::routing::post(admin_service::handlers::start_encryption_migration))
+        .route("/v1/admin/encryption/migrate/status", axum::routing::get(admin_service::handlers::get_encryption_migration_status))
+        // Decision rules
+        .route("/v1/admin/rules/{id}/backtest", axum::routing::post(admin_service::handlers::backtest_rule))
         // Visual Workflow Management (n8n-style)
         .route("/v1/admin/workflows", axum::routing::post(admin_service::handlers::workflow_handlers::create_workflow))
         .route("/v1/admin/workflows", axum::routing::get(admin_service::handlers::workflow_handlers::list_workflows))
+        .route("/v1/admin/workflows/validate", axum::routing::post(admin_service::handlers::workflow_handlers::validate_workflow_schema))
         .route("/v1/admin/workflows/{id}", axum::routing::get(admin_service::handlers::workflow_handlers::get_workflow))
+        .route("/v1/admin/workflows/{id}/schema", axum::routing::get(admin_service::handlers::workflow_handlers::get_workflow_schema))
         .route("/v1/admin/workflows/{id}", axum::routing::put(admin_service::handlers::workflow_handlers::update_workflow))
         .route("/v1/admin/workflows/{id}", axum::routing::delete(admin_service::handlers::workflow_handlers::delete_workflow))
         .route("/v1/admin/workflows/{id}/clone", axum::routing::post(admin_service::handlers::workflow_handlers::clone_workflow))
         .route("/v1/admin/workflows/{id}/activate", axum::routing::post(admin_service::handlers::workflow_handlers::activate_workflow))
         .route("/v1/admin/workflows/{id}/deactivate", axum::routing::post(admin_service::handlers::workflow_handlers::deactivate_workflow))
+        .route("/v1/admin/workflows/{id}/validate-context", axum::routing::post(admin_service::handlers::workflow_handlers::validate_context))
+        .route("/v1/admin/workflows/{id}/diagram", axum::routing::get(admin_service::handlers::workflow_handlers::get_machine_diagram))
         .route("/v1/admin/workflows/{id}/start", axum::routing::post(admin_service::handlers::workflow_handlers::start_instance))
         .route("/v1/admin/workflows/{id}/instances", axum::routing::get(admin_service::handlers::workflow_handlers::list_instances))
         .route("/v1/admin/workflow-instances/{id}", axum::routing::get(admin_service::handlers::workflow_handlers::get_instance))
@@ -409,6 +627,22 @@ async fn async_main() -> Result<(), String> {
         .route("/v1/vault/secrets/{*path}", axum::routing::post(crate::presentation::api::handlers::write_secret))
         .route("/v1/vault/secrets/{*path}", axum::routing::delete(crate::presentation::api::handlers::delete_secret))
         .route("/v1/vault/capabilities", axum::routing::post(crate::presentation::api::handlers::check_capabilities))
+        // EHR routes
+        .route("/v1/ehr/import/fhir-bundle", axum::routing::post(crate::presentation::api::handlers::ehr::fhir_import_handlers::import_fhir_bundle))
+        .route("/v1/ehr/appointments/stream", axum::routing::get(crate::presentation::api::handlers::ehr::appointment_handlers::stream_appointment_status))
+        .route("/v1/ehr/appointments/{id}/check-in", axum::routing::post(crate::presentation::api::handlers::ehr::appointment_handlers::check_in_appointment))
+        .route("/v1/ehr/appointments/{id}/cancel", axum::routing::post(crate::presentation::api::handlers::ehr::appointment_handlers::cancel_appointment))
+        .route("/v1/ehr/patients/{id}/medication-reconciliation", axum::routing::get(crate::presentation::api::handlers::ehr::medication_reconciliation_handlers::get_medication_reconciliation))
+        .route("/v1/ehr/documents/search", axum::routing::get(crate::presentation::api::handlers::ehr::document_handlers::search_documents))
+        .route("/v1/ehr/documents/upload-url", axum::routing::post(crate::presentation::api::handlers::ehr::document_handlers::create_document_upload_url))
+        .route("/v1/ehr/documents/{ien}/confirm-upload", axum::routing::post(crate::presentation::api::handlers::ehr::document_handlers::confirm_document_upload))
+        .route("/v1/ehr/patients/merge", axum::routing::post(crate::presentation::api::handlers::ehr::patient_merge_handlers::merge_patient_records))
+        .route("/v1/ehr/patients/unmerge", axum::routing::post(crate::presentation::api::handlers::ehr::patient_merge_handlers::unmerge_patient_record))
+        // FHIR R4 routes (404 while the fhir_export feature is off)
+        .route("/v1/fhir/metadata", axum::routing::get(crate::presentation::api::handlers::ehr::fhir_handlers::fhir_metadata))
+        .route("/v1/fhir/Patient", axum::routing::get(crate::presentation::api::handlers::ehr::fhir_handlers::search_fhir_patients))
+        .route("/v1/fhir/Patient", axum::routing::post(crate::presentation::api::handlers::ehr::fhir_handlers::create_fhir_patient))
+        .route("/v1/fhir/Patient/{id}", axum::routing::get(crate::presentation::api::handlers::ehr::fhir_handlers::read_fhir_patient))
         // Workflow routes (n8n-style orchestration)
         .route("/v1/workflows", axum::routing::get(crate::presentation::api::handlers::workflow_handlers::list_workflows))
         .route("/v1/workflows", axum::routing::post(crate::presentation::api::handlers::workflow_handlers::create_workflow))
@@ -423,7 +657,20 @@ async fn async_main() -> Result<(), String> {
         .route("/v1/workflow-tasks/{task_id}/complete", axum::routing::post(crate::presentation::api::handlers::workflow_handlers::complete_task))
         .route("/v1/workflows/events/{event_type}", axum::routing::post(crate::presentation::api::handlers::workflow_handlers::emit_event))
         .route("/v1/connectors", axum::routing::get(crate::presentation::api::handlers::workflow_handlers::list_connectors))
+        // Background job routes
+        .route("/v1/jobs", axum::routing::post(crate::presentation::api::handlers::job_handlers::enqueue_job))
+        .route("/v1/jobs/{id}/status", axum::routing::get(crate::presentation::api::handlers::job_handlers::get_job_status))
         .with_state(app_state_arc.clone())
+        // Innermost, so retries replayed from the idempotency store still pass auth and ACL
+        .layer(axum::middleware::from_fn_with_state(
+            idempotency_store,
+            crate::presentation::api::middleware::idempotency_middleware,
+        ))
+        // Runs after auth (layers wrap outward): needs RequestContext for EHR audit
+        .layer(axum::middleware::from_fn_with_state(
+            app_state_arc.clone(),
+            crate::presentation::api::middleware::patient_context_middleware,
+        ))
         .layer(axum::middleware::from_fn_with_state(
             app_state_arc.clone(),
             crate::presentation::api::middleware::acl_middleware,
@@ -433,17 +680,21 @@ async fn async_main() -> Result<(), String> {
             crate::presentation::api::middleware::auth_middleware,
         ));
     
+    // Health checks stay unversioned
     let api_routes = axum::Router::new()
+        .merge(shared::infrastructure::health::health_routes(health_checker.clone()))
         .merge(public_routes)
         .merge(protected_routes);
 
     let app = axum::Router::new()
-        .route("/health", axum::routing::get(|| async { "OK" })) // Root health check for Docker
+        .merge(shared::infrastructure::health::health_routes(health_checker)) // Root health checks for Docker
+        .route("/metrics", axum::routing::get(crate::presentation::api::metrics::metrics_handler))
         .nest("/api", api_routes)
         // Middleware order (from outer to inner):
         // 1. Request ID middleware - generates request ID
         // 2. Session middleware - creates/gets session, extracts IP
         // 3. Request logging middleware - logs requests (runs before and after handler)
+        // Tracing middleware wraps all of them so their logs carry the trace ID
         .layer(axum::middleware::from_fn_with_state(
             app_state_arc.clone(),
             crate::presentation::api::middleware::request_logging_middleware,
@@ -453,6 +704,7 @@ async fn async_main() -> Result<(), String> {
             crate::presentation::api::middleware::session_middleware,
         ))
         .layer(axum::middleware::from_fn(crate::presentation::api::middleware::request_id_middleware))
+        .layer(axum::middleware::from_fn(shared::infrastructure::tracing::tracing_middleware))
         .layer({
             // Build CORS layer with app-specific origins (required for credentials)
             // Combine all allowed origins from both admin-ui and client-ui
@@ -489,6 +741,8 @@ async fn async_main() -> Result<(), String> {
                     axum::http::HeaderName::from_static("x-session-token"),
                     axum::http::HeaderName::from_static("x-app-type"),
                     axum::http::HeaderName::from_static("x-app-device"),
+                    axum::http::HeaderName::from_static("hipaa-access-reason"),
+                    axum::http::HeaderName::from_static("idempotency-key"),
                 ])
                 .expose_headers([
                     axum::http::HeaderName::from_static("x-request-id"),
@@ -37,7 +37,9 @@ pub async fn login(
         }
     };
     
-    match state.login_use_case.execute(login_request).await {
+    // The refresh token issued is bound to this client
+    let device_fingerprint = shared::device_fingerprint(&parts.headers);
+    match state.login_use_case.execute(login_request, &device_fingerprint).await {
         Ok(mut response) => {
             // If we have a session, authenticate it
             if let Some(sess) = session {
@@ -83,12 +85,24 @@ pub async fn login(
         },
         Err(e) => {
             e.log_with_operation(location, "login");
+            // Password was correct; the client should prompt for a TOTP code and retry
+            if let shared::AppError::MfaRequired(_) = e {
+                return (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"error": format!("{}", e), "mfa_required": true}))).into_response();
+            }
             let status = match e {
                 shared::AppError::Authentication(_) => StatusCode::UNAUTHORIZED,
                 shared::AppError::NotFound(_) => StatusCode::NOT_FOUND,
+                shared::AppError::AccountLocked { .. } | shared::AppError::TooManyAttempts { .. } => {
+                    StatusCode::TOO_MANY_REQUESTS
+                }
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             };
-            (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response()
+            let mut response = (status, Json(serde_json::json!({"error": format!("{}", e)}))).into_response();
+            // Locked accounts tell the client when to try again
+            if let Some(seconds) = e.retry_after_seconds() {
+                response.headers_mut().insert("Retry-After", HeaderValue::from(seconds));
+            }
+            response
         }
     }
 }
@@ -146,17 +160,49 @@ pub async fn logout(
     response
 }
 
+/// Log the caller out on every device: revoke all refresh tokens and end all sessions
+pub async fn logout_all(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+) -> impl IntoResponse {
+    let location = concat!(file!(), ":", line!());
+    let revoked_tokens = match state.logout_use_case
+        .logout_all(context.user_id, authz_core::auth::LogoutReason::UserInitiated)
+        .await
+    {
+        Ok(count) => count,
+        Err(e) => {
+            e.log_with_operation(location, "logout_all");
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"error": format!("{}", e)}))).into_response();
+        }
+    };
+
+    // Session cookies on other devices stop working too
+    let ended_sessions = state.session_service.invalidate_all_for_user(context.user_id).await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to end sessions on global logout: {}", e);
+            0
+        });
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "message": "Logged out on all devices",
+        "revokedTokens": revoked_tokens,
+        "endedSessions": ended_sessions,
+    }))).into_response()
+}
+
 pub async fn refresh_token(
     State(state): State<Arc<AppState>>,
+    context: RequestContext,
     Json(request): Json<RefreshTokenRequest>,
 ) -> impl IntoResponse {
     let location = concat!(file!(), ":", line!());
-    match state.refresh_token_use_case.execute(request).await {
+    match state.refresh_token_use_case.execute(request, &context).await {
         Ok(response) => (StatusCode::OK, Json(response)).into_response(),
         Err(e) => {
             e.log_with_operation(location, "refresh_token");
             let status = match e {
-                shared::AppError::Authentication(_) => StatusCode::UNAUTHORIZED,
+                shared::AppError::Authentication(_) | shared::AppError::UnauthorizedDevice(_) => StatusCode::UNAUTHORIZED,
                 shared::AppError::NotFound(_) => StatusCode::NOT_FOUND,
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             };
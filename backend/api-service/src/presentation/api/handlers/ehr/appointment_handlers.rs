@@ -10,18 +10,31 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
     Json,
 };
 use chrono::{DateTime, Utc, NaiveDate, Datelike};
 use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use std::convert::Infallible;
 use std::sync::Arc;
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use shared::application::services::{AppointmentEventBroadcaster, SharedAppointmentEvents};
+use shared::domain::state_machine::{
+    AppointmentContext, AppointmentMachine, AppointmentStateMachine, AppointmentStateMachineEvent,
+    AppointmentStatus,
+};
 use shared::shared::error::AppError;
 use shared::shared::api_response::{ApiError, ApiResponse};
 use super::AppState;
 
+/// SSE event name for appointment status changes
+pub const APPOINTMENT_STATUS_EVENT: &str = "appointment_status_changed";
+
 // ============================================================================
 // Request/Response Types
 // ============================================================================
@@ -452,6 +465,15 @@ pub async fn check_in_appointment(
         Utc::now()
     };
 
+    let mut tx = state.database_pool.begin().await.map_err(|e| {
+        error!("Failed to begin transaction: {:?}", e);
+        AppError::from(e)
+    })?;
+    let (status, mut ctx) =
+        lock_for_transition(&mut tx, appointment_id, organization_id, &state.appointment_events).await?;
+    AppointmentMachine::transition(&status, AppointmentStateMachineEvent::CheckIn, &mut ctx)
+        .map_err(|e| AppError::InvalidState(e.to_string()))?;
+
     let appointment = sqlx::query_as!(
         AppointmentResponse,
         r#"
@@ -474,16 +496,13 @@ pub async fn check_in_appointment(
         appointment_id,
         organization_id
     )
-    .fetch_one(state.database_pool.as_ref())
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         error!("Failed to check in appointment: {:?}", e);
-        if matches!(e, sqlx::Error::RowNotFound) {
-            AppError::NotFound("Appointment not found".to_string())
-        } else {
-            AppError::Internal(format!("Failed to check in appointment: {}", e))
-        }
+        AppError::Internal(format!("Failed to check in appointment: {}", e))
     })?;
+    tx.commit().await.map_err(AppError::from)?;
 
     info!("Checked in appointment: {}", appointment.id);
     Ok(Json(ApiResponse::success(appointment)))
@@ -502,6 +521,16 @@ pub async fn cancel_appointment(
 
     info!("Cancelling appointment: {}", appointment_id);
 
+    let mut tx = state.database_pool.begin().await.map_err(|e| {
+        error!("Failed to begin transaction: {:?}", e);
+        AppError::from(e)
+    })?;
+    let (status, mut ctx) =
+        lock_for_transition(&mut tx, appointment_id, organization_id, &state.appointment_events).await?;
+    ctx.cancellation_reason = payload.cancellation_reason.clone();
+    AppointmentMachine::transition(&status, AppointmentStateMachineEvent::Cancel, &mut ctx)
+        .map_err(|e| AppError::InvalidState(e.to_string()))?;
+
     let appointment = sqlx::query_as!(
         AppointmentResponse,
         r#"
@@ -525,21 +554,106 @@ pub async fn cancel_appointment(
         appointment_id,
         organization_id
     )
-    .fetch_one(state.database_pool.as_ref())
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         error!("Failed to cancel appointment: {:?}", e);
-        if matches!(e, sqlx::Error::RowNotFound) {
-            AppError::NotFound("Appointment not found".to_string())
-        } else {
-            AppError::Internal(format!("Failed to cancel appointment: {}", e))
-        }
+        AppError::Internal(format!("Failed to cancel appointment: {}", e))
     })?;
+    tx.commit().await.map_err(AppError::from)?;
 
     info!("Cancelled appointment: {}", appointment.id);
     Ok(Json(ApiResponse::success(appointment)))
 }
 
+/// GET /v1/ehr/appointments/stream - Live appointment status changes (SSE)
+pub async fn stream_appointment_status(
+    State(state): State<Arc<AppState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    appointment_status_sse(&state.appointment_events)
+}
+
+/// Stream status changes published after the call as SSE events
+///
+/// A forwarding task relays broadcast events into the response body and
+/// stops as soon as the client disconnects (the body, and with it the
+/// receiver, is dropped).
+pub fn appointment_status_sse(
+    events: &AppointmentEventBroadcaster,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let mut updates = events.subscribe();
+    let (tx, rx) = mpsc::channel::<Event>(16);
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tx.closed() => break,
+                update = updates.recv() => match update {
+                    Ok(update) => {
+                        let event = match Event::default()
+                            .event(APPOINTMENT_STATUS_EVENT)
+                            .id(update.id.to_string())
+                            .json_data(&update)
+                        {
+                            Ok(event) => event,
+                            Err(e) => {
+                                warn!("Failed to serialize appointment status event: {}", e);
+                                continue;
+                            }
+                        };
+                        if tx.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Appointment status stream lagged, skipped {} events", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+            }
+        }
+    });
+
+    Sse::new(ReceiverStream::new(rx).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+/// Lock an appointment row and build the state machine context for a status change
+///
+/// The row stays locked until `tx` ends, so the transition starts from the
+/// status stored when the change is committed. The context publishes the
+/// change to the live appointment stream.
+async fn lock_for_transition(
+    tx: &mut Transaction<'_, Postgres>,
+    appointment_id: Uuid,
+    organization_id: Uuid,
+    events: &SharedAppointmentEvents,
+) -> Result<(AppointmentStatus, AppointmentContext), AppError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT a.status, a.scheduled_datetime,
+               TRIM(COALESCE(p.first_name, '') || ' ' || COALESCE(p.last_name, '')) AS "patient_name!"
+        FROM appointments a
+        LEFT JOIN ehr_patients p ON p.id = a.patient_id
+        WHERE a.id = $1 AND a.organization_id = $2 AND a.deleted_at IS NULL
+        FOR UPDATE OF a
+        "#,
+        appointment_id,
+        organization_id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| {
+        error!("Failed to lock appointment: {:?}", e);
+        AppError::from(e)
+    })?
+    .ok_or_else(|| AppError::NotFound("Appointment not found".to_string()))?;
+
+    let status = row.status.parse::<AppointmentStatus>().map_err(AppError::Internal)?;
+    let ctx = AppointmentContext::new(row.scheduled_datetime)
+        .with_status_events(appointment_id, row.patient_name, events.clone());
+    Ok((status, ctx))
+}
+
 /// DELETE /v1/ehr/appointments/:id - Delete appointment (soft delete)
 #[tracing::instrument(skip(state))]
 pub async fn delete_appointment(
@@ -0,0 +1,153 @@
+// Clinical Document Handlers
+// Full-text search over ^TIU(8925) documents indexed in PostgreSQL, and
+// direct upload of document files to storage
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+use uuid::Uuid;
+
+use shared::domain::repositories::ehr::{DocumentSearchResult, EhrDocumentSearchRepository};
+use shared::infrastructure::database::mumps::YottaDbAdapter;
+use shared::infrastructure::repositories::ehr::EhrDocumentSearchRepositoryImpl;
+use shared::shared::api_response::{ApiError, ApiResponse};
+use shared::shared::error::AppError;
+
+use super::AppState;
+
+/// How long an upload URL can be used for
+const UPLOAD_URL_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+/// Storage prefix of uploaded document files
+const DOCUMENT_KEY_PREFIX: &str = "documents/";
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct SearchDocumentsQuery {
+    pub q: String,
+    pub patient_ien: Option<i64>,
+    #[serde(rename = "type")]
+    pub document_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUploadUrlRequest {
+    /// Name of the file being uploaded, kept (sanitized) in its storage key
+    pub file_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadUrlResponse {
+    /// PUT the file's bytes here
+    pub upload_url: String,
+    /// Pass to confirm-upload once the upload has finished
+    pub document_key: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmUploadRequest {
+    pub document_key: String,
+}
+
+/// `documents/{uuid}/{file name}`, with characters other than letters,
+/// digits, `.`, `-` and `_` in the file name replaced by `_`
+fn document_key(file_name: &str) -> Result<String, AppError> {
+    let file_name: String = file_name
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    if file_name.trim_matches('.').is_empty() {
+        return Err(AppError::Validation("file_name is required".to_string()));
+    }
+    Ok(format!("{}{}/{}", DOCUMENT_KEY_PREFIX, Uuid::new_v4(), file_name))
+}
+
+/// Whether `key` could have been issued by `document_key`
+fn is_document_key(key: &str) -> bool {
+    key.strip_prefix(DOCUMENT_KEY_PREFIX)
+        .and_then(|rest| rest.split_once('/'))
+        .is_some_and(|(id, file_name)| {
+            Uuid::parse_str(id).is_ok() && !file_name.is_empty() && !file_name.contains('/') && file_name != ".."
+        })
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/ehr/documents/search?q=&patient_ien=&type= - Search document text
+///
+/// Documents are indexed from YottaDB once a minute, so a note saved moments
+/// ago may not be found yet.
+#[tracing::instrument(skip(state))]
+pub async fn search_documents(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchDocumentsQuery>,
+) -> Result<Json<ApiResponse<Vec<DocumentSearchResult>>>, ApiError> {
+    if query.q.trim().is_empty() {
+        return Err(AppError::Validation("Search query q is required".to_string()).into());
+    }
+    info!("Searching documents: {}", query.q);
+
+    let repository = EhrDocumentSearchRepositoryImpl::new(state.database_service.clone());
+    let results = repository
+        .search(&query.q, query.patient_ien, query.document_type.as_deref())
+        .await?;
+    Ok(Json(ApiResponse::success(results)))
+}
+
+/// POST /v1/ehr/documents/upload-url - Get a URL to upload a document file to
+///
+/// The client PUTs the file straight to storage, so large scans and DICOM
+/// files never pass through the API, then links it to its document with
+/// confirm-upload.
+#[tracing::instrument(skip(state))]
+pub async fn create_document_upload_url(
+    State(state): State<Arc<AppState>>,
+    Json(payload): Json<CreateUploadUrlRequest>,
+) -> Result<Json<ApiResponse<UploadUrlResponse>>, ApiError> {
+    let document_key = document_key(&payload.file_name)?;
+    info!("Issuing upload URL for {}", document_key);
+
+    let url = state.document_storage.presign_put(&document_key, UPLOAD_URL_LIFETIME).await?;
+    Ok(Json(ApiResponse::success(UploadUrlResponse {
+        upload_url: url.url,
+        document_key,
+        expires_at: url.expires_at,
+    })))
+}
+
+/// POST /v1/ehr/documents/:ien/confirm-upload - Link an uploaded file to a TIU document
+///
+/// Records the file's storage key on the document; the upload itself is not
+/// re-checked.
+#[tracing::instrument(skip(state))]
+pub async fn confirm_document_upload(
+    State(state): State<Arc<AppState>>,
+    Path(ien): Path<i64>,
+    Json(payload): Json<ConfirmUploadRequest>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, ApiError> {
+    if !is_document_key(&payload.document_key) {
+        return Err(AppError::Validation(format!("{} is not a document upload key", payload.document_key)).into());
+    }
+    info!("Linking {} to document {}", payload.document_key, ien);
+
+    if !YottaDbAdapter::from_env().set_document_file(ien, &payload.document_key).await? {
+        return Err(AppError::NotFound(format!("Document {} not found", ien)).into());
+    }
+    Ok(Json(ApiResponse::success(serde_json::json!({
+        "ien": ien,
+        "document_key": payload.document_key
+    }))))
+}
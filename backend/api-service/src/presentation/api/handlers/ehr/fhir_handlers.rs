@@ -0,0 +1,213 @@
+// FHIR Patient Handlers
+// FHIR R4 read/search/create over the YottaDB patient store
+// Every endpoint answers 404 while the fhir_export feature flag is off
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value as JsonValue};
+use std::sync::Arc;
+use tracing::{error, info};
+
+use shared::application::services::{CreatePatientDto, EhrPatientDto, EhrService, FhirBundle, FhirBundleEntry};
+use shared::domain::fhir::{FhirIssue, FhirPatientMapper, FhirValidator};
+use shared::RequestContext;
+
+use crate::presentation::api::AppState;
+
+/// Media type for FHIR JSON resources
+pub const FHIR_JSON: &str = "application/fhir+json";
+
+const DEFAULT_SEARCH_COUNT: usize = 20;
+const MAX_SEARCH_COUNT: usize = 100;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct FhirPatientSearchQuery {
+    /// Name prefix, matched against the `LAST,FIRST` index
+    pub name: Option<String>,
+    #[serde(rename = "_count")]
+    pub count: Option<usize>,
+}
+
+fn fhir_response(status: StatusCode, body: JsonValue) -> Response {
+    (status, [(header::CONTENT_TYPE, FHIR_JSON)], Json(body)).into_response()
+}
+
+/// OperationOutcome with one issue per entry
+fn operation_outcome(status: StatusCode, code: &str, issues: &[FhirIssue]) -> Response {
+    let issue: Vec<JsonValue> = issues
+        .iter()
+        .map(|i| {
+            json!({
+                "severity": "error",
+                "code": code,
+                "diagnostics": i.diagnostics,
+                "expression": [i.expression],
+            })
+        })
+        .collect();
+    fhir_response(status, json!({ "resourceType": "OperationOutcome", "issue": issue }))
+}
+
+fn single_issue(status: StatusCode, code: &str, diagnostics: impl Into<String>) -> Response {
+    operation_outcome(status, code, &[FhirIssue { expression: "Patient".to_string(), diagnostics: diagnostics.into() }])
+}
+
+fn patient_resource(patient: &EhrPatientDto) -> JsonValue {
+    serde_json::to_value(FhirPatientMapper::to_fhir(patient)).unwrap_or(JsonValue::Null)
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/fhir/metadata - CapabilityStatement for the FHIR endpoints
+pub async fn fhir_metadata(State(state): State<Arc<AppState>>, context: RequestContext) -> Response {
+    shared::feature_required!(state.features_for(&context).await, "fhir_export");
+
+    fhir_response(
+        StatusCode::OK,
+        json!({
+            "resourceType": "CapabilityStatement",
+            "status": "active",
+            "date": chrono::Utc::now().format("%Y-%m-%d").to_string(),
+            "kind": "instance",
+            "software": { "name": "Health V1", "version": env!("CARGO_PKG_VERSION") },
+            "fhirVersion": "4.0.1",
+            "format": [FHIR_JSON],
+            "rest": [{
+                "mode": "server",
+                "resource": [{
+                    "type": "Patient",
+                    "interaction": [
+                        { "code": "read" },
+                        { "code": "search-type" },
+                        { "code": "create" }
+                    ],
+                    "searchParam": [
+                        { "name": "name", "type": "string" },
+                        { "name": "_count", "type": "number" }
+                    ]
+                }]
+            }]
+        }),
+    )
+}
+
+/// GET /v1/fhir/Patient/:id - Read a patient by IEN
+#[tracing::instrument(skip(state, context))]
+pub async fn read_fhir_patient(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+    Path(id): Path<String>,
+) -> Response {
+    shared::feature_required!(state.features_for(&context).await, "fhir_export");
+
+    let Ok(ien) = id.parse::<i64>() else {
+        return single_issue(StatusCode::NOT_FOUND, "not-found", format!("Patient/{} not found", id));
+    };
+
+    match EhrService::from_env().get_patient_by_ien(ien).await {
+        Ok(Some(patient)) => fhir_response(StatusCode::OK, patient_resource(&patient)),
+        Ok(None) => single_issue(StatusCode::NOT_FOUND, "not-found", format!("Patient/{} not found", ien)),
+        Err(e) => {
+            error!("Failed to read FHIR Patient {}: {}", ien, e);
+            single_issue(StatusCode::INTERNAL_SERVER_ERROR, "exception", "Failed to read patient")
+        }
+    }
+}
+
+/// GET /v1/fhir/Patient?name= - Search patients, returned as a searchset Bundle
+#[tracing::instrument(skip(state, context))]
+pub async fn search_fhir_patients(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+    Query(query): Query<FhirPatientSearchQuery>,
+) -> Response {
+    shared::feature_required!(state.features_for(&context).await, "fhir_export");
+
+    let count = query.count.unwrap_or(DEFAULT_SEARCH_COUNT).clamp(1, MAX_SEARCH_COUNT);
+    let ehr_service = EhrService::from_env();
+    let patients = match query.name.as_deref().map(str::trim).filter(|n| !n.is_empty()) {
+        Some(name) => ehr_service.search_patients(name, count).await,
+        None => ehr_service.list_patients(count, 0).await,
+    };
+
+    match patients {
+        Ok(patients) => {
+            let bundle = FhirBundle {
+                resource_type: "Bundle".to_string(),
+                bundle_type: Some("searchset".to_string()),
+                total: Some(patients.len()),
+                entry: patients
+                    .iter()
+                    .map(|p| FhirBundleEntry {
+                        full_url: Some(format!("Patient/{}", p.ien)),
+                        resource: Some(patient_resource(p)),
+                    })
+                    .collect(),
+            };
+            fhir_response(StatusCode::OK, serde_json::to_value(bundle).unwrap_or(JsonValue::Null))
+        }
+        Err(e) => {
+            error!("Failed to search FHIR Patients: {}", e);
+            single_issue(StatusCode::INTERNAL_SERVER_ERROR, "exception", "Failed to search patients")
+        }
+    }
+}
+
+/// POST /v1/fhir/Patient - Register a patient from a FHIR Patient resource
+///
+/// The resource is validated first; every problem is reported as an
+/// OperationOutcome issue with status 422.
+#[tracing::instrument(skip(state, context, resource))]
+pub async fn create_fhir_patient(
+    State(state): State<Arc<AppState>>,
+    context: RequestContext,
+    Json(resource): Json<JsonValue>,
+) -> Response {
+    shared::feature_required!(state.features_for(&context).await, "fhir_export");
+
+    let fhir = match FhirValidator::validate_patient(&resource) {
+        Ok(fhir) => fhir,
+        Err(issues) => return operation_outcome(StatusCode::UNPROCESSABLE_ENTITY, "invalid", &issues),
+    };
+    let patient = match FhirPatientMapper::from_fhir(&fhir) {
+        Ok(patient) => patient,
+        Err(e) => return single_issue(StatusCode::UNPROCESSABLE_ENTITY, "invalid", e),
+    };
+
+    let request = CreatePatientDto {
+        first_name: patient.first_name,
+        last_name: patient.last_name,
+        sex: patient.sex,
+        date_of_birth: patient.date_of_birth,
+        ssn: patient.ssn,
+        mrn: patient.mrn,
+    };
+
+    match EhrService::from_env().create_patient(request).await {
+        Ok(created) => {
+            info!("Created patient {} from FHIR resource", created.ien);
+            let location = format!("Patient/{}", created.ien);
+            (
+                StatusCode::CREATED,
+                [(header::CONTENT_TYPE, FHIR_JSON.to_string()), (header::LOCATION, location)],
+                Json(patient_resource(&created)),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            error!("Failed to create FHIR Patient: {}", e);
+            single_issue(StatusCode::INTERNAL_SERVER_ERROR, "exception", "Failed to create patient")
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// FHIR Import Handlers
+// Bulk import of patient demographics from FHIR R4 Bundles
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use super::AppState;
+use shared::application::services::{BulkImportResult, EhrService, FhirBundle};
+use shared::shared::api_response::{ApiError, ApiResponse};
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FhirImportQuery {
+    /// Patients inserted per transaction
+    pub batch_size: Option<usize>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/ehr/import/fhir-bundle - Import patients from a FHIR Bundle
+///
+/// Accepts `application/fhir+json`. Invalid entries are reported per entry
+/// index without aborting the rest of the import.
+#[tracing::instrument(skip(state, bundle))]
+pub async fn import_fhir_bundle(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<FhirImportQuery>,
+    Json(bundle): Json<FhirBundle>,
+) -> Result<Json<ApiResponse<BulkImportResult>>, ApiError> {
+    let organization_id = Uuid::nil(); // Use system org for now
+    info!("Importing FHIR bundle with {} entries", bundle.entry.len());
+
+    let mut ehr_service = EhrService::from_env()
+        .with_patient_repository(state.ehr_patient_repository.clone());
+    if let Some(batch_size) = query.batch_size {
+        ehr_service = ehr_service.with_import_batch_size(batch_size.min(500));
+    }
+
+    let result = ehr_service.import_fhir_bundle(bundle, organization_id).await?;
+    Ok(Json(ApiResponse::success(result)))
+}
@@ -0,0 +1,41 @@
+// Medication Reconciliation Handlers
+// Compare a visit's active medication list with the patient's previous visit
+
+use axum::{
+    extract::{Path, Query},
+    Json,
+};
+use serde::Deserialize;
+use tracing::info;
+
+use shared::application::services::{MedicationReconciliationUseCase, ReconciliationResult};
+use shared::shared::api_response::{ApiError, ApiResponse};
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct MedicationReconciliationQuery {
+    pub visit_ien: i64,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/ehr/patients/:id/medication-reconciliation?visit_ien= - Reconcile medications
+///
+/// `:id` is the patient's VistA IEN.
+#[tracing::instrument]
+pub async fn get_medication_reconciliation(
+    Path(patient_ien): Path<i64>,
+    Query(query): Query<MedicationReconciliationQuery>,
+) -> Result<Json<ApiResponse<ReconciliationResult>>, ApiError> {
+    info!("Reconciling medications for patient {} at visit {}", patient_ien, query.visit_ien);
+
+    let result = MedicationReconciliationUseCase::from_env()
+        .reconcile(patient_ien, query.visit_ien)
+        .await?;
+    Ok(Json(ApiResponse::success(result)))
+}
@@ -10,12 +10,17 @@ pub mod anatomy_findings_handlers;
 pub mod appointment_handlers;
 pub mod body_system_handlers;
 pub mod clinical_note_handlers;
+pub mod document_handlers;
 pub mod encounter_handlers;
+pub mod fhir_handlers;
+pub mod fhir_import_handlers;
 pub mod imaging_orders_handlers;
 pub mod lab_orders_handlers;
 pub mod lab_results_handlers;
 pub mod lab_tests_handlers;
+pub mod medication_reconciliation_handlers;
 pub mod patient_handlers;
+pub mod patient_merge_handlers;
 pub mod pharmacy_handlers;
 pub mod problem_list_handlers;
 pub mod vital_signs_handlers;
@@ -24,12 +29,17 @@ pub use anatomy_findings_handlers::*;
 pub use appointment_handlers::*;
 pub use body_system_handlers::*;
 pub use clinical_note_handlers::*;
+pub use document_handlers::*;
 pub use encounter_handlers::*;
+pub use fhir_handlers::*;
+pub use fhir_import_handlers::*;
 pub use imaging_orders_handlers::*;
 pub use lab_orders_handlers::*;
 pub use lab_results_handlers::*;
 pub use lab_tests_handlers::*;
+pub use medication_reconciliation_handlers::*;
 pub use patient_handlers::*;
+pub use patient_merge_handlers::*;
 pub use pharmacy_handlers::*;
 pub use problem_list_handlers::*;
 pub use vital_signs_handlers::*;
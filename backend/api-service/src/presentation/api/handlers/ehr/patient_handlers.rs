@@ -14,6 +14,7 @@ use uuid::Uuid;
 use super::AppState;
 use shared::shared::api_response::{ApiError, ApiResponse};
 use shared::shared::error::AppError;
+use shared::RequestContext;
 
 // ============================================================================
 // Database Row Types (for sqlx::query_as!())
@@ -670,13 +671,14 @@ pub async fn get_patient_banner(
 }
 
 /// POST /v1/ehr/patients - Create patient
-#[tracing::instrument(skip(state, payload))]
+#[tracing::instrument(skip(state, ctx, payload))]
 pub async fn create_patient(
     State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
     Json(payload): Json<CreatePatientRequest>,
 ) -> Result<Json<ApiResponse<PatientResponse>>, ApiError> {
     let organization_id = Uuid::nil(); // Use system org for now
-    let user_id = Uuid::nil(); // Use system user for now
+    let user_id = ctx.user_id;
     info!("Creating patient: {} {}", payload.first_name, payload.last_name);
 
     // Assertion 1: Validate sex
@@ -756,14 +758,15 @@ pub async fn create_patient(
 }
 
 /// PUT /v1/ehr/patients/:id - Update patient
-#[tracing::instrument(skip(state, payload))]
+#[tracing::instrument(skip(state, ctx, payload))]
 pub async fn update_patient(
     State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
     Path(patient_id): Path<Uuid>,
     Json(payload): Json<UpdatePatientRequest>,
 ) -> Result<Json<ApiResponse<PatientResponse>>, ApiError> {
     let organization_id = Uuid::nil(); // Use system org for now
-    let user_id = Uuid::nil(); // Use system user for now
+    let user_id = ctx.user_id;
     info!("Updating patient: {}", patient_id);
 
     // Verify patient exists
@@ -870,13 +873,14 @@ pub async fn update_patient(
 }
 
 /// DELETE /v1/ehr/patients/:id - Soft delete patient
-#[tracing::instrument(skip(state))]
+#[tracing::instrument(skip(state, ctx))]
 pub async fn delete_patient(
     State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
     Path(patient_id): Path<Uuid>,
 ) -> Result<Json<ApiResponse<serde_json::Value>>, ApiError> {
     let organization_id = Uuid::nil(); // Use system org for now
-    let user_id = Uuid::nil(); // Use system user for now
+    let user_id = ctx.user_id;
     info!("Deleting patient: {}", patient_id);
 
     let result = sqlx::query!(
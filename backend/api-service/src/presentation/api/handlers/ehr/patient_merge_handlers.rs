@@ -0,0 +1,75 @@
+// Patient Merge Handlers
+// Merge duplicate VistA patient registrations (^DPT) and undo merges
+
+use axum::{extract::State, Json};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+
+use super::AppState;
+use shared::application::services::{MergeResult, PatientMergeUseCase};
+use shared::infrastructure::repositories::AuditLogRepositoryImpl;
+use shared::shared::api_response::{ApiError, ApiResponse};
+use shared::RequestContext;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergePatientRecordsRequest {
+    /// IEN of the record that is kept
+    pub winner_ien: i64,
+    /// IEN of the duplicate whose records move to the winner
+    pub loser_ien: i64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmergePatientRecordRequest {
+    /// IEN of the duplicate to restore
+    pub merged_ien: i64,
+}
+
+fn merge_use_case(state: &AppState) -> PatientMergeUseCase {
+    PatientMergeUseCase::from_env(Arc::new(AuditLogRepositoryImpl::new(state.database_service.clone())))
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/ehr/patients/merge - Merge a duplicate patient by VistA IEN
+///
+/// Moves the loser's visits, labs, medications, orders and other records to
+/// the winner and hides the loser behind a merged-into pointer.
+#[tracing::instrument(skip(state, ctx))]
+pub async fn merge_patient_records(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(payload): Json<MergePatientRecordsRequest>,
+) -> Result<Json<ApiResponse<MergeResult>>, ApiError> {
+    info!("Merging patient {} into {}", payload.loser_ien, payload.winner_ien);
+
+    let result = merge_use_case(&state)
+        .merge(payload.winner_ien, payload.loser_ien, ctx.user_id)
+        .await?;
+    Ok(Json(ApiResponse::success(result)))
+}
+
+/// POST /v1/ehr/patients/unmerge - Restore a merged patient and move its records back
+#[tracing::instrument(skip(state, ctx))]
+pub async fn unmerge_patient_record(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(payload): Json<UnmergePatientRecordRequest>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, ApiError> {
+    info!("User {} unmerging patient {}", ctx.user_id, payload.merged_ien);
+
+    merge_use_case(&state).unmerge(payload.merged_ien).await?;
+    Ok(Json(ApiResponse::success(serde_json::json!({
+        "ien": payload.merged_ien,
+        "unmerged": true
+    }))))
+}
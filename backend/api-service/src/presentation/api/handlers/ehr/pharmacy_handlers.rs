@@ -13,6 +13,7 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use shared::RequestContext;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -580,8 +581,11 @@ pub async fn get_drug_contraindications(
 /// POST /v1/pharmacy/interactions/check
 pub async fn check_interactions(
     State(state): State<Arc<AppState>>,
+    context: RequestContext,
     Json(request): Json<InteractionCheckRequest>,
 ) -> impl IntoResponse {
+    shared::feature_required!(state.features_for(&context).await, "drug_interaction_check");
+
     if request.drug_ids.len() < 2 {
         return (
             StatusCode::BAD_REQUEST,
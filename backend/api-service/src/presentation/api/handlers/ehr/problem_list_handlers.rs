@@ -8,6 +8,7 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
+use shared::infrastructure::validation::Icd10Validator;
 use shared::shared::api_response::ApiError;
 use shared::shared::error::AppError;
 use std::sync::Arc;
@@ -155,6 +156,15 @@ pub struct ProblemComment {
     pub encounter_id: Option<Uuid>,
 }
 
+/// Created problem, with any data-quality warnings that did not block creation
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateProblemResponse {
+    #[serde(flatten)]
+    pub problem: Problem,
+    pub warnings: Vec<String>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ProblemListResponse {
@@ -173,7 +183,7 @@ pub struct ProblemListResponse {
 pub async fn create_problem(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<CreateProblemRequest>,
-) -> Result<(StatusCode, Json<Problem>), ApiError> {
+) -> Result<(StatusCode, Json<CreateProblemResponse>), ApiError> {
     // Use a system user ID for now - in production, extract from auth middleware
     let user_id = Uuid::nil();
 
@@ -221,6 +231,22 @@ pub async fn create_problem(
         }
     }
 
+    // ICD-10-CM check is advisory: legacy entry may carry retired or local
+    // codes, so an unknown code is reported back instead of rejected
+    let mut warnings = Vec::new();
+    let mut icd10_description = payload.icd10_description;
+    if let Some(code) = payload.icd10_code.as_deref().filter(|code| !code.trim().is_empty()) {
+        match Icd10Validator::validate(code) {
+            Ok(entry) => {
+                icd10_description.get_or_insert(entry.description);
+            }
+            Err(e) => {
+                tracing::warn!("Problem for patient {}: {}", payload.patient_id, e);
+                warnings.push(e.to_string());
+            }
+        }
+    }
+
     // Parse onset date if provided
     let onset_date = if let Some(date_str) = payload.onset_date {
         Some(chrono::NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
@@ -270,7 +296,7 @@ pub async fn create_problem(
         payload.problem_code.as_deref(),                    // $5: problem_code
         payload.problem_code_system.as_deref(),             // $6: problem_code_system
         payload.icd10_code.as_deref(),                      // $7: icd10_code
-        payload.icd10_description.as_deref(),               // $8: icd10_description
+        icd10_description.as_deref(),                       // $8: icd10_description
         payload.snomed_code.as_deref(),                     // $9: snomed_code
         payload.snomed_description.as_deref(),              // $10: snomed_description
         onset_date,                                         // $11: onset_date
@@ -289,7 +315,7 @@ pub async fn create_problem(
     .await
     .map_err(|e| AppError::Internal(format!("Failed to create problem: {}", e)))?;
 
-    Ok((StatusCode::CREATED, Json(problem)))
+    Ok((StatusCode::CREATED, Json(CreateProblemResponse { problem, warnings })))
 }
 
 /// Get problem by ID
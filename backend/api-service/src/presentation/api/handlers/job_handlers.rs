@@ -0,0 +1,101 @@
+// Job Handlers
+// Enqueue long-running EHR tasks and poll their status
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use super::AppState;
+use shared::application::services::ehr_jobs::{BULK_IMPORT_JOB, CCD_GENERATE_JOB, FHIR_EXPORT_JOB};
+use shared::infrastructure::jobs::{JobQueue, JobStatus};
+use shared::shared::api_response::{ApiError, ApiResponse};
+use shared::shared::error::AppError;
+
+const SUPPORTED_JOB_TYPES: &[&str] = &[CCD_GENERATE_JOB, FHIR_EXPORT_JOB, BULK_IMPORT_JOB];
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueJobRequest {
+    pub job_type: String,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnqueueJobResponse {
+    pub job_id: Uuid,
+    pub status: JobStatus,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobStatusResponse {
+    pub id: Uuid,
+    pub job_type: String,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    pub result: Option<Value>,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// POST /v1/jobs - Enqueue a background job
+#[tracing::instrument(skip(state, request))]
+pub async fn enqueue_job(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<EnqueueJobRequest>,
+) -> Result<Json<ApiResponse<EnqueueJobResponse>>, ApiError> {
+    if !SUPPORTED_JOB_TYPES.contains(&request.job_type.as_str()) {
+        return Err(AppError::Validation(format!("Unsupported job type: {}", request.job_type)).into());
+    }
+
+    let queue = JobQueue::new(state.database_pool.clone());
+    let job_id = queue.enqueue(&request.job_type, request.payload).await?;
+    info!("Enqueued {} job {}", request.job_type, job_id);
+
+    Ok(Json(ApiResponse::success(EnqueueJobResponse {
+        job_id,
+        status: JobStatus::Pending,
+    })))
+}
+
+/// GET /v1/jobs/{id}/status - Poll a background job
+#[tracing::instrument(skip(state))]
+pub async fn get_job_status(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<JobStatusResponse>>, ApiError> {
+    let queue = JobQueue::new(state.database_pool.clone());
+    let job = queue
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Job {} not found", id)))?;
+
+    Ok(Json(ApiResponse::success(JobStatusResponse {
+        id: job.id,
+        job_type: job.job_type,
+        status: job.status,
+        created_at: job.created_at,
+        started_at: job.started_at,
+        completed_at: job.completed_at,
+        error: job.error,
+        result: job.result,
+    })))
+}
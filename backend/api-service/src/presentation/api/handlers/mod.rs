@@ -6,8 +6,10 @@ pub mod billing;
 pub mod cds_handlers;
 pub mod communications_handlers;
 pub mod ehr;
+pub mod job_handlers;
 pub mod opd_handlers;
 pub mod service_handlers;
+pub mod storage_handlers;
 pub mod vault_handlers;
 pub mod workflow_handlers;
 pub mod worklist_handlers;
@@ -17,8 +19,10 @@ pub use billing::*;
 pub use cds_handlers::*;
 pub use communications_handlers::*;
 pub use ehr::*;
+pub use job_handlers::*;
 pub use opd_handlers::*;
 pub use service_handlers::*;
+pub use storage_handlers::*;
 pub use vault_handlers::*;
 pub use workflow_handlers::*;
 pub use worklist_handlers::*;
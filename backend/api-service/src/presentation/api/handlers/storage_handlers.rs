@@ -0,0 +1,64 @@
+// Storage Handlers
+// Serve presigned URLs issued for storage backends that cannot presign their own
+
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+
+use super::AppState;
+use shared::infrastructure::storage::PresignedMethod;
+use shared::shared::api_response::ApiError;
+use shared::shared::error::AppError;
+
+/// Largest object a presigned URL can upload
+pub const MAX_PRESIGNED_UPLOAD_BYTES: usize = 100 * 1024 * 1024;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct PresignedObjectQuery {
+    pub token: String,
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// GET /v1/storage/presigned?token= - Download the object a presigned URL names
+#[tracing::instrument(skip_all)]
+pub async fn get_presigned_object(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PresignedObjectQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let key = state.storage_url_signer.verify(&query.token, PresignedMethod::Get)?;
+    let data = state
+        .document_storage
+        .get(&key)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Object {} not found", key)))?;
+    Ok(([(header::CONTENT_TYPE, "application/octet-stream")], data))
+}
+
+/// PUT /v1/storage/presigned?token= - Upload the object a presigned URL names
+///
+/// The request body is the object's content; an existing object is replaced.
+#[tracing::instrument(skip_all)]
+pub async fn put_presigned_object(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PresignedObjectQuery>,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let key = state.storage_url_signer.verify(&query.token, PresignedMethod::Put)?;
+    info!("Storing {} bytes at {} via presigned URL", body.len(), key);
+
+    state.document_storage.put(&key, &body).await?;
+    Ok(StatusCode::OK)
+}
@@ -9,6 +9,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use shared::RequestContext;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::Utc;
@@ -222,9 +223,12 @@ pub async fn delete_workflow(
 /// Start a workflow instance
 pub async fn start_workflow_instance(
     State(state): State<Arc<AppState>>,
+    context: RequestContext,
     Path(workflow_id): Path<Uuid>,
     Json(req): Json<StartWorkflowRequest>,
 ) -> impl IntoResponse {
+    shared::feature_required!(state.features_for(&context).await, "workflow_engine");
+
     let instance_id = Uuid::new_v4();
     let now = Utc::now();
 
@@ -438,9 +442,12 @@ pub async fn complete_task(
 /// Emit an event to trigger workflows (n8n-style webhook)
 pub async fn emit_event(
     State(state): State<Arc<AppState>>,
+    context: RequestContext,
     Path(event_type): Path<String>,
     Json(req): Json<EmitEventRequest>,
 ) -> impl IntoResponse {
+    shared::feature_required!(state.features_for(&context).await, "workflow_engine");
+
     let event_id = Uuid::new_v4();
     let now = Utc::now();
 
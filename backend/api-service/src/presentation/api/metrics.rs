@@ -0,0 +1,110 @@
+//! Prometheus metrics for authentication and authorization checks
+
+use std::sync::{Arc, LazyLock};
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{Encoder, Gauge, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+use shared::infrastructure::zanzibar::GraphCache;
+
+pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+static AUTH_CHECKS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("api_auth_checks_total", "Authentication checks by result (success, failure)"),
+        &["result"],
+    ))
+});
+
+static PERMISSION_CHECKS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("api_permission_checks_total", "ACL checks by result (allowed, denied, error)"),
+        &["result"],
+    ))
+});
+
+fn register(collector: prometheus::Result<IntCounterVec>) -> IntCounterVec {
+    let collector = collector.expect("valid metric definition");
+    REGISTRY.register(Box::new(collector.clone())).expect("metric registered once");
+    collector
+}
+
+pub fn record_auth_check(authenticated: bool) {
+    let result = if authenticated { "success" } else { "failure" };
+    AUTH_CHECKS_TOTAL.with_label_values(&[result]).inc();
+}
+
+/// Record the outcome of the ACL middleware from the status it rejected with
+pub fn record_permission_check(rejection: Option<StatusCode>) {
+    let result = match rejection {
+        None => "allowed",
+        Some(StatusCode::FORBIDDEN) => "denied",
+        Some(_) => "error",
+    };
+    PERMISSION_CHECKS_TOTAL.with_label_values(&[result]).inc();
+}
+
+/// Permission graph cache statistics, read from the cache on every scrape
+struct GraphCacheCollector {
+    cache: Arc<GraphCache>,
+    hit_ratio: Gauge,
+    hits: IntGauge,
+    misses: IntGauge,
+    evictions: IntGauge,
+    entries: IntGauge,
+}
+
+impl GraphCacheCollector {
+    fn new(cache: Arc<GraphCache>) -> prometheus::Result<Self> {
+        Ok(Self {
+            cache,
+            hit_ratio: Gauge::new("zanzibar_cache_hit_ratio", "Fraction of permission cache lookups served from cache")?,
+            hits: IntGauge::new("zanzibar_cache_hits", "Permission cache hits since startup")?,
+            misses: IntGauge::new("zanzibar_cache_misses", "Permission cache misses since startup")?,
+            evictions: IntGauge::new("zanzibar_cache_evictions", "Permission cache entries evicted since startup")?,
+            entries: IntGauge::new("zanzibar_cache_entries", "Cached permission check results")?,
+        })
+    }
+
+    fn gauges(&self) -> [&dyn Collector; 5] {
+        [&self.hit_ratio, &self.hits, &self.misses, &self.evictions, &self.entries]
+    }
+}
+
+impl Collector for GraphCacheCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.gauges().into_iter().flat_map(Collector::desc).collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let stats = self.cache.stats();
+        self.hit_ratio.set(stats.hit_ratio());
+        self.hits.set(stats.hits as i64);
+        self.misses.set(stats.misses as i64);
+        self.evictions.set(stats.evictions as i64);
+        self.entries.set(stats.current_size as i64);
+        self.gauges().into_iter().flat_map(Collector::collect).collect()
+    }
+}
+
+/// Expose the permission graph cache statistics on `GET /metrics`
+pub fn register_graph_cache(cache: Arc<GraphCache>) -> prometheus::Result<()> {
+    REGISTRY.register(Box::new(GraphCacheCollector::new(cache)?))
+}
+
+/// `GET /metrics` in the Prometheus text format
+pub async fn metrics_handler() -> Response {
+    LazyLock::force(&AUTH_CHECKS_TOTAL);
+    LazyLock::force(&PERMISSION_CHECKS_TOTAL);
+
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    match encoder.encode(&REGISTRY.gather(), &mut body) {
+        Ok(()) => (StatusCode::OK, [(header::CONTENT_TYPE, encoder.format_type().to_string())], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
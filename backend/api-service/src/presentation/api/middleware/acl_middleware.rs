@@ -17,6 +17,16 @@ pub async fn acl_middleware(
     State(state): State<Arc<AppState>>,
     request: Request,
     next: Next,
+) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
+    let result = check_access(state, request, next).await;
+    crate::presentation::api::metrics::record_permission_check(result.as_ref().err().map(|(status, _)| *status));
+    result
+}
+
+async fn check_access(
+    state: Arc<AppState>,
+    request: Request,
+    next: Next,
 ) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
     // Get request context (set by auth_middleware)
     let context = request.extensions()
@@ -61,11 +71,11 @@ pub async fn acl_middleware(
             let resource = request.uri().path();
             
             // Try common relations: viewer, editor, owner
-            let has_access = state.permission_checker.check(&user_id_str, "viewer", resource).await
+            let has_access = state.permission_checker.check(&user_id_str, "viewer", resource, None).await
                 .unwrap_or(false)
-                || state.permission_checker.check(&user_id_str, "editor", resource).await
+                || state.permission_checker.check(&user_id_str, "editor", resource, None).await
                 .unwrap_or(false)
-                || state.permission_checker.check(&user_id_str, "owner", resource).await
+                || state.permission_checker.check(&user_id_str, "owner", resource, None).await
                 .unwrap_or(false);
 
             if !has_access {
@@ -96,11 +106,11 @@ pub async fn acl_middleware(
         
         // Check if user has any relationship with the resource
         // This is a basic check - can be enhanced with specific relations
-        let has_relationship = state.permission_checker.check(&user_id_str, "viewer", &resource_id).await
+        let has_relationship = state.permission_checker.check(&user_id_str, "viewer", &resource_id, None).await
             .unwrap_or(false)
-            || state.permission_checker.check(&user_id_str, "editor", &resource_id).await
+            || state.permission_checker.check(&user_id_str, "editor", &resource_id, None).await
             .unwrap_or(false)
-            || state.permission_checker.check(&user_id_str, "owner", &resource_id).await
+            || state.permission_checker.check(&user_id_str, "owner", &resource_id, None).await
             .unwrap_or(false);
 
         // If user has no relationship and is not the owner/admin, deny access
@@ -7,6 +7,8 @@ use axum::{
 use std::sync::Arc;
 use uuid::Uuid;
 use shared::RequestContext;
+use shared::infrastructure::oidc::API_SERVICE_AUDIENCE;
+use shared::infrastructure::tracing::SpanContext;
 use shared::domain::repositories::UserRepository;
 use shared::infrastructure::repositories::UserRepositoryImpl;
 use super::super::AppState;
@@ -19,6 +21,16 @@ use super::session_middleware::{get_session, get_app_type, get_app_device};
 /// 2. Fall back to JWT token validation (for mobile/API clients)
 pub async fn auth_middleware(
     State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
+    let result = authenticate(state, request, next).await;
+    crate::presentation::api::metrics::record_auth_check(result.is_ok());
+    result
+}
+
+async fn authenticate(
+    state: Arc<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, (StatusCode, axum::Json<serde_json::Value>)> {
@@ -31,6 +43,7 @@ pub async fn auth_middleware(
     // Get app type and device from request (set by session_middleware)
     let request_app_type = get_app_type(&request);
     let request_app_device = get_app_device(&request);
+    let device_fingerprint = shared::device_fingerprint(request.headers());
 
     // Priority 1: Check for authenticated session (session-based auth for web UIs)
     if let Some(session) = get_session(&request) {
@@ -74,7 +87,8 @@ pub async fn auth_middleware(
                             user_info.permissions.unwrap_or_default(),
                         )
                         .with_session(session.id)
-                        .with_ip_address(session.ip_address);
+                        .with_ip_address(session.ip_address)
+                        .with_device_fingerprint(device_fingerprint);
 
                         if let Some(org_id) = session.organization_id {
                             context = context.with_organization(org_id);
@@ -88,8 +102,12 @@ pub async fn auth_middleware(
                             context = context.with_app_device(app_device);
                         }
 
-                        request.extensions_mut().insert(context);
-                        let response = next.run(request).await;
+                        if let Some(span_context) = request.extensions().get::<SpanContext>() {
+                            context = context.with_span_context(span_context.clone());
+                        }
+
+                        request.extensions_mut().insert(context.clone());
+                        let response = context.scope(next.run(request)).await;
                         return Ok(response);
                     }
                     Err(e) => {
@@ -126,7 +144,7 @@ pub async fn auth_middleware(
     let token = &auth_header[7..];
 
     // Validate token using TokenManager
-    let claims = state.token_manager.validate_token(token)
+    let claims = state.token_manager.verify_token_for_audience(token, API_SERVICE_AUDIENCE)
         .map_err(|e| {
             (
                 StatusCode::UNAUTHORIZED,
@@ -163,7 +181,7 @@ pub async fn auth_middleware(
     let organization_id = user.and_then(|u| u.organization_id);
 
     // Get session from extensions (set by session_middleware)
-    if let Some(session) = get_session(&request) {
+    let context = if let Some(session) = get_session(&request) {
         // Authenticate the session if it's a ghost session
         if session.is_ghost_session() {
             if let Err(e) = state.session_service.authenticate_session(
@@ -201,7 +219,7 @@ pub async fn auth_middleware(
             context = context.with_app_device(app_device);
         }
 
-        request.extensions_mut().insert(context);
+        context
     } else {
         // No session found - create context without session info
         let mut context = RequestContext::new(
@@ -224,9 +242,19 @@ pub async fn auth_middleware(
             context = context.with_app_device(app_device);
         }
 
-        request.extensions_mut().insert(context);
-    }
+        context
+    };
+
+    let context = context.with_device_fingerprint(device_fingerprint);
+
+    // Span context from the tracing middleware, for downstream propagation
+    let context = match request.extensions().get::<SpanContext>() {
+        Some(span_context) => context.with_span_context(span_context.clone()),
+        None => context,
+    };
 
-    let response = next.run(request).await;
+    request.extensions_mut().insert(context.clone());
+    // Expose the context to repositories for audit field population
+    let response = context.scope(next.run(request)).await;
     Ok(response)
 }
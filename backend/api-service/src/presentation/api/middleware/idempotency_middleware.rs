@@ -0,0 +1,256 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use shared::domain::repositories::{IdempotencyKeyRepository, IdempotencyRecord};
+use shared::infrastructure::session::RedisStore;
+use shared::{AppResult, RequestContext};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Header carrying the client-generated key (a UUID) of a retryable POST
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+const CACHE_KEY_PREFIX: &str = "idempotency_cache:";
+
+/// How long a recorded response is replayed
+const IDEMPOTENCY_TTL_HOURS: i64 = 24;
+
+/// How long a reservation holds its key; after that the request is taken to
+/// have died with its instance and a retry runs it again
+const RESERVATION_TIMEOUT_MINUTES: i64 = 5;
+
+/// Redis copy of an [`IdempotencyRecord`]
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    user_id: Uuid,
+    created_at: DateTime<Utc>,
+    status: u16,
+    body: String,
+}
+
+fn cache_key(key: Uuid) -> String {
+    format!("{}{}", CACHE_KEY_PREFIX, key)
+}
+
+/// Responses recorded per idempotency key
+///
+/// Postgres (`idempotency_keys`) is the durable copy; Redis, when configured,
+/// answers repeats without a database round trip.
+pub struct IdempotencyStore {
+    repository: Arc<dyn IdempotencyKeyRepository>,
+    cache: Option<Arc<dyn RedisStore>>,
+}
+
+impl IdempotencyStore {
+    pub fn new(repository: Arc<dyn IdempotencyKeyRepository>) -> Self {
+        Self { repository, cache: None }
+    }
+
+    /// Cache recorded responses in Redis as well
+    pub fn with_redis(mut self, cache: Arc<dyn RedisStore>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Response recorded for `key` within the last 24 hours, or its live reservation
+    async fn find(&self, key: Uuid) -> AppResult<Option<IdempotencyRecord>> {
+        if let Some(cache) = &self.cache {
+            match cache.get(&cache_key(key)).await {
+                Ok(Some(value)) => {
+                    if let Ok(cached) = serde_json::from_str::<CachedResponse>(&value) {
+                        return Ok(Some(IdempotencyRecord {
+                            key,
+                            user_id: cached.user_id,
+                            created_at: cached.created_at,
+                            status: Some(cached.status),
+                            body: cached.body.into_bytes(),
+                        }));
+                    }
+                }
+                Ok(None) => {}
+                // Fall back to the database
+                Err(e) => tracing::warn!("Idempotency cache lookup failed: {}", e),
+            }
+        }
+
+        let (stale_before, expired_before) = Self::cutoffs();
+        Ok(self.repository.find(key).await?.filter(|record| {
+            record.created_at >= expired_before && !(record.is_pending() && record.created_at < stale_before)
+        }))
+    }
+
+    /// Reservations and responses older than these no longer hold their key
+    fn cutoffs() -> (DateTime<Utc>, DateTime<Utc>) {
+        let now = Utc::now();
+        (
+            now - chrono::Duration::minutes(RESERVATION_TIMEOUT_MINUTES),
+            now - chrono::Duration::hours(IDEMPOTENCY_TTL_HOURS),
+        )
+    }
+
+    /// Reserve `key` for `user_id`, returning whether it was free
+    async fn reserve(&self, key: Uuid, user_id: Uuid) -> AppResult<bool> {
+        let (stale_before, expired_before) = Self::cutoffs();
+        self.repository.reserve(key, user_id, stale_before, expired_before).await
+    }
+
+    async fn release(&self, key: Uuid) -> AppResult<()> {
+        self.repository.release(key).await
+    }
+
+    async fn save(&self, record: IdempotencyRecord) -> AppResult<()> {
+        self.repository.complete(&record).await?;
+
+        let Some(cache) = &self.cache else {
+            return Ok(());
+        };
+        let Some(status) = record.status else {
+            return Ok(());
+        };
+        // Bodies that are not UTF-8 are replayed from the database only
+        let Ok(body) = String::from_utf8(record.body) else {
+            return Ok(());
+        };
+        let cached = CachedResponse {
+            user_id: record.user_id,
+            created_at: record.created_at,
+            status,
+            body,
+        };
+        let value = serde_json::to_string(&cached).unwrap_or_default();
+        let ttl_seconds = (IDEMPOTENCY_TTL_HOURS * 60 * 60) as u64;
+        if let Err(e) = cache.set_ex(&cache_key(record.key), &value, ttl_seconds).await {
+            tracing::warn!("Failed to cache idempotent response: {}", e);
+        }
+        Ok(())
+    }
+}
+
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// Answer a request whose key is already held by `record`
+fn existing_response(record: IdempotencyRecord, user_id: Uuid) -> Response {
+    if record.user_id != user_id {
+        return error_response(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("{} was already used by another user", IDEMPOTENCY_KEY_HEADER),
+        );
+    }
+    match record.status {
+        Some(status) => {
+            let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+            (status, [(header::CONTENT_TYPE, "application/json")], record.body).into_response()
+        }
+        None => error_response(
+            StatusCode::CONFLICT,
+            format!("A request with this {} is still in progress; retry later", IDEMPOTENCY_KEY_HEADER),
+        ),
+    }
+}
+
+/// Replay recorded responses for retried POST requests
+/// A POST with an `Idempotency-Key: <uuid>` header runs the handler once; a
+/// successful response is recorded and returned as-is for every retry with
+/// the same key by the same user, without running the handler again. The key
+/// is reserved before the handler runs, so a retry arriving while the first
+/// request is still running gets 409. Reusing another user's key is rejected
+/// with 422.
+/// Must run after auth middleware (needs `RequestContext`).
+pub async fn idempotency_middleware(
+    State(store): State<Arc<IdempotencyStore>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+    let Some(header_value) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let Some(key) = header_value.to_str().ok().and_then(|v| Uuid::parse_str(v.trim()).ok()) else {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("{} header must be a UUID", IDEMPOTENCY_KEY_HEADER),
+        );
+    };
+    let Some(user_id) = request.extensions().get::<RequestContext>().map(|ctx| ctx.user_id) else {
+        return error_response(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Request context not found - authentication middleware must run first".to_string(),
+        );
+    };
+
+    let reserved = match store.find(key).await {
+        Ok(Some(record)) => return existing_response(record, user_id),
+        Ok(None) => store.reserve(key, user_id).await,
+        Err(e) => Err(e),
+    };
+    match reserved {
+        Ok(true) => {}
+        // Another request took the key between the lookup and the reservation
+        Ok(false) => {
+            return match store.find(key).await {
+                Ok(Some(record)) => existing_response(record, user_id),
+                Ok(None) => error_response(
+                    StatusCode::CONFLICT,
+                    format!("A request with this {} is still in progress; retry later", IDEMPOTENCY_KEY_HEADER),
+                ),
+                Err(e) => {
+                    e.log_with_operation(concat!(file!(), ":", line!()), "idempotency_lookup");
+                    error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check idempotency key".to_string())
+                }
+            };
+        }
+        Err(e) => {
+            e.log_with_operation(concat!(file!(), ":", line!()), "idempotency_reserve");
+            return error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to check idempotency key".to_string(),
+            );
+        }
+    }
+
+    let response = next.run(request).await;
+    // Failed requests may be retried for real
+    if !response.status().is_success() {
+        if let Err(e) = store.release(key).await {
+            e.log_with_operation(concat!(file!(), ":", line!()), "idempotency_release");
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read response body for idempotency key {}: {}", key, e);
+            if let Err(e) = store.release(key).await {
+                e.log_with_operation(concat!(file!(), ":", line!()), "idempotency_release");
+            }
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read response".to_string());
+        }
+    };
+
+    let record = IdempotencyRecord {
+        key,
+        user_id,
+        created_at: Utc::now(),
+        status: Some(parts.status.as_u16()),
+        body: bytes.to_vec(),
+    };
+    // The handler has already run, so its response goes out regardless
+    if let Err(e) = store.save(record).await {
+        e.log_with_operation(concat!(file!(), ":", line!()), "idempotency_save");
+    }
+
+    Response::from_parts(parts, Body::from(bytes))
+}
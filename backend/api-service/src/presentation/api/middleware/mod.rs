@@ -5,10 +5,14 @@ pub mod request_id;
 pub mod app_access_middleware;
 pub mod session_middleware;
 pub mod request_logging_middleware;
+pub mod patient_context_middleware;
+pub mod idempotency_middleware;
 
 pub use auth_middleware::auth_middleware;
 pub use acl_middleware::acl_middleware;
 pub use request_id::request_id_middleware;
 pub use session_middleware::session_middleware;
 pub use request_logging_middleware::request_logging_middleware;
+pub use patient_context_middleware::patient_context_middleware;
+pub use idempotency_middleware::{idempotency_middleware, IdempotencyStore, IDEMPOTENCY_KEY_HEADER};
 
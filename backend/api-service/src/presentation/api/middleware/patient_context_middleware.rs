@@ -0,0 +1,87 @@
+use axum::{
+    extract::{RawPathParams, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json, RequestExt,
+};
+use shared::domain::entities::{UserActivityEvent, EHR_ACCESS_EVENT};
+use shared::domain::repositories::UserActivityEventRepository;
+use shared::infrastructure::repositories::UserActivityEventRepositoryImpl;
+use shared::{RequestContext, HIPAA_ACCESS_REASON_HEADER};
+use std::sync::Arc;
+use super::super::AppState;
+use super::session_middleware::get_session;
+
+/// Path parameters that carry a patient IEN
+const PATIENT_PATH_PARAMS: &[&str] = &["patient_ien", "ien"];
+
+/// Attach patient context to EHR requests for HIPAA audit logging
+/// Reads the patient IEN from the path and the `HIPAA-Access-Reason` header,
+/// records them on the `RequestContext`, and writes a `user_activity_events` row.
+/// Must run after auth middleware (needs `RequestContext`).
+pub async fn patient_context_middleware(
+    State(state): State<Arc<AppState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    if !path.contains("/v1/ehr/") {
+        return next.run(request).await;
+    }
+
+    let access_reason = request
+        .headers()
+        .get(HIPAA_ACCESS_REASON_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+
+    if state.require_access_reason && access_reason.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": format!("{} header is required for EHR requests", HIPAA_ACCESS_REASON_HEADER)
+            })),
+        )
+            .into_response();
+    }
+
+    let patient_id = match request.extract_parts::<RawPathParams>().await {
+        Ok(params) => params
+            .iter()
+            .find(|(name, _)| PATIENT_PATH_PARAMS.contains(name))
+            .and_then(|(_, value)| value.parse::<i64>().ok()),
+        Err(_) => None,
+    };
+
+    let context = request.extensions_mut().get_mut::<RequestContext>().map(|context| {
+        context.set_patient_context(patient_id, access_reason);
+        context.clone()
+    });
+    let session_id = get_session(&request).map(|s| s.id);
+    let method = request.method().to_string();
+
+    let mut response = next.run(request).await;
+
+    if let Some(context) = context {
+        let event = UserActivityEvent::new(context.user_id, EHR_ACCESS_EVENT, method, path)
+            .with_session_id_opt(session_id.or(context.session_id))
+            .with_request_id(context.request_id.clone())
+            .with_patient_context(context.patient_id, context.access_reason.clone())
+            .with_status_code(response.status().as_u16());
+
+        // Log asynchronously (don't block response)
+        let repository = UserActivityEventRepositoryImpl::new(state.database_service.clone());
+        tokio::spawn(async move {
+            if let Err(e) = repository.create(event).await {
+                tracing::warn!("Failed to log user activity event: {}", e);
+            }
+        });
+
+        // Surface the patient context to request_logging_middleware
+        response.extensions_mut().insert(context);
+    }
+
+    response
+}
@@ -5,7 +5,9 @@ use axum::{
 };
 use shared::domain::entities::RequestLog;
 use shared::domain::repositories::RequestLogRepository;
+use shared::infrastructure::logging::{sample_request_body, sample_response_body};
 use shared::infrastructure::repositories::RequestLogRepositoryImpl;
+use shared::RequestContext;
 use std::sync::Arc;
 use std::time::Instant;
 use uuid::Uuid;
@@ -14,6 +16,8 @@ use super::session_middleware::get_session;
 
 /// Request logging middleware that logs all HTTP requests
 /// Captures IP, request_id, session_id, timing, and sizes
+/// A fraction of requests (`AppState::body_sampler`) also has its JSON request
+/// body and the first 2 KB of its JSON response body logged, redacted.
 pub async fn request_logging_middleware(
     State(state): State<Arc<AppState>>,
     request: Request,
@@ -69,8 +73,21 @@ pub async fn request_logging_middleware(
     // Estimate request size (approximate)
     let request_size_bytes = estimate_request_size(&request);
 
+    // Sample bodies for debugging; the decision is logged either way
+    let body_sampled = state.body_sampler.should_sample();
+    let (request, request_body) = if body_sampled {
+        sample_request_body(request).await
+    } else {
+        (request, None)
+    };
+
     // Process request
     let response = next.run(request).await;
+    let (response, response_body) = if body_sampled {
+        sample_response_body(response).await
+    } else {
+        (response, None)
+    };
 
     // Calculate response time
     let response_time_ms = start_time.elapsed().as_millis() as u64;
@@ -98,6 +115,11 @@ pub async fn request_logging_middleware(
         }
     });
 
+    // Patient context is set on EHR requests by patient_context_middleware
+    let patient_context = response.extensions().get::<RequestContext>();
+    let patient_id = patient_context.and_then(|c| c.patient_id);
+    let access_reason = patient_context.and_then(|c| c.access_reason.clone());
+
     // Log to tracing as well
     tracing::info!(
         method = %method,
@@ -106,6 +128,11 @@ pub async fn request_logging_middleware(
         response_time_ms = response_time_ms,
         session_id = %session_id,
         request_id = %request_id,
+        patient_id = ?patient_id,
+        access_reason = ?access_reason,
+        body_sampled = body_sampled,
+        request_body = request_body.as_deref(),
+        response_body = response_body.as_deref(),
         "Request completed"
     );
 
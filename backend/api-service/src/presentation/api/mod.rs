@@ -1,6 +1,7 @@
 pub mod routes;
 pub mod handlers;
 pub mod middleware;
+pub mod metrics;
 // Re-export AppState type alias
 pub type AppState = shared::AppState<
     authz_core::auth::LoginUseCase,
@@ -1,10 +1,11 @@
 use axum::{
     Router,
+    extract::DefaultBodyLimit,
     routing::{get, post, put, delete},
 };
 use crate::presentation::api::handlers::*;
 use crate::presentation::api::handlers::workflow_handlers;
-use crate::presentation::api::handlers::ehr::{anatomy_findings_handlers, appointment_handlers, body_system_handlers, clinical_note_handlers, encounter_handlers, imaging_orders_handlers, patient_handlers, pharmacy_handlers, problem_list_handlers, vital_signs_handlers};
+use crate::presentation::api::handlers::ehr::{anatomy_findings_handlers, appointment_handlers, body_system_handlers, clinical_note_handlers, document_handlers, encounter_handlers, fhir_handlers, fhir_import_handlers, imaging_orders_handlers, medication_reconciliation_handlers, patient_handlers, patient_merge_handlers, pharmacy_handlers, problem_list_handlers, vital_signs_handlers};
 use crate::presentation::api::handlers::billing::{service_catalog_handlers, invoice_handlers, payment_handlers};
 use admin_service::handlers::*;
 use std::sync::Arc;
@@ -18,7 +19,14 @@ pub fn create_router() -> Router<Arc<super::AppState>> {
         .route("/v1/auth/login", post(login))
         .route("/v1/setup/status", get(check_setup_status))
         .route("/v1/setup/initialize", post(initialize_setup))
-        .route("/v1/services/status", get(get_service_status));
+        .route("/v1/services/status", get(get_service_status))
+        // Presigned URLs carry their own authorization
+        .route(
+            "/v1/storage/presigned",
+            get(get_presigned_object)
+                .put(put_presigned_object)
+                .layer(DefaultBodyLimit::max(MAX_PRESIGNED_UPLOAD_BYTES)),
+        );
 
     // Protected routes (authentication required)
     // Apply auth middleware first, then ACL middleware
@@ -27,6 +35,7 @@ pub fn create_router() -> Router<Arc<super::AppState>> {
     // All routes use /v1/ prefix for versioning
     let protected_routes = Router::new()
         .route("/v1/auth/logout", post(logout))
+        .route("/v1/auth/logout/all", post(logout_all))
         .route("/v1/auth/token", post(refresh_token))
         .route("/v1/auth/userinfo", get(userinfo))
         .route("/v1/users", post(create_user))
@@ -47,11 +56,21 @@ pub fn create_router() -> Router<Arc<super::AppState>> {
         .route("/v1/ehr/patients/mrn/:mrn", get(patient_handlers::get_patient_by_mrn))
         .route("/v1/ehr/patients/ien/:ien", get(patient_handlers::get_patient_by_ien))
         .route("/v1/ehr/patients/find-duplicates", post(patient_handlers::find_duplicate_patients))
-        .route("/v1/ehr/patients/merge", post(patient_handlers::merge_patients))
+        .route("/v1/ehr/patients/merge", post(patient_merge_handlers::merge_patient_records))
+        .route("/v1/ehr/patients/unmerge", post(patient_merge_handlers::unmerge_patient_record))
+        .route("/v1/ehr/patients/:id/medication-reconciliation", get(medication_reconciliation_handlers::get_medication_reconciliation))
+        // FHIR import
+        .route("/v1/ehr/import/fhir-bundle", post(fhir_import_handlers::import_fhir_bundle))
+        // FHIR R4 Patient
+        .route("/v1/fhir/metadata", get(fhir_handlers::fhir_metadata))
+        .route("/v1/fhir/Patient", get(fhir_handlers::search_fhir_patients))
+        .route("/v1/fhir/Patient", post(fhir_handlers::create_fhir_patient))
+        .route("/v1/fhir/Patient/:id", get(fhir_handlers::read_fhir_patient))
         // Appointment routes
         .route("/v1/ehr/appointments", get(appointment_handlers::list_appointments))
         .route("/v1/ehr/appointments", post(appointment_handlers::create_appointment))
         .route("/v1/ehr/appointments/availability", get(appointment_handlers::check_availability))
+        .route("/v1/ehr/appointments/stream", get(appointment_handlers::stream_appointment_status))
         .route("/v1/ehr/appointments/:id", get(appointment_handlers::get_appointment))
         .route("/v1/ehr/appointments/:id", put(appointment_handlers::update_appointment))
         .route("/v1/ehr/appointments/:id", delete(appointment_handlers::delete_appointment))
@@ -65,6 +84,11 @@ pub fn create_router() -> Router<Arc<super::AppState>> {
         .route("/v1/ehr/clinical-notes/:id", put(clinical_note_handlers::update_clinical_note))
         .route("/v1/ehr/clinical-notes/:id", delete(clinical_note_handlers::delete_clinical_note))
         .route("/v1/ehr/clinical-notes/:id/sign", post(clinical_note_handlers::sign_clinical_note))
+        // Clinical document full-text search
+        .route("/v1/ehr/documents/search", get(document_handlers::search_documents))
+        // Direct document file uploads
+        .route("/v1/ehr/documents/upload-url", post(document_handlers::create_document_upload_url))
+        .route("/v1/ehr/documents/:ien/confirm-upload", post(document_handlers::confirm_document_upload))
         // Vital signs routes
         .route("/v1/ehr/vital-signs", get(vital_signs_handlers::list_vital_signs))
         .route("/v1/ehr/vital-signs", post(vital_signs_handlers::create_vital_signs))
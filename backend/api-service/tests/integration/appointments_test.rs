@@ -252,3 +252,62 @@ async fn test_appointment_duration_boundaries() {
 
     common::teardown_test_app(&app).await;
 }
+
+#[tokio::test]
+async fn test_appointment_status_stream_delivers_events() {
+    use api_service::presentation::api::handlers::ehr::appointment_handlers::appointment_status_sse;
+    use axum::{body::Body, extract::State, http::Request, routing::get, Router};
+    use shared::application::services::AppointmentEventBroadcaster;
+    use shared::domain::state_machine::{
+        AppointmentContext, AppointmentMachine, AppointmentStateMachine, AppointmentStateMachineEvent,
+        AppointmentStatus,
+    };
+    use std::sync::Arc;
+    use tokio_stream::StreamExt;
+    use tower::ServiceExt;
+
+    let events = Arc::new(AppointmentEventBroadcaster::new());
+    let router = Router::new()
+        .route(
+            "/v1/ehr/appointments/stream",
+            get(|State(events): State<Arc<AppointmentEventBroadcaster>>| async move {
+                appointment_status_sse(&events)
+            }),
+        )
+        .with_state(events.clone());
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri("/v1/ehr/appointments/stream")
+                .body(Body::empty())
+                .expect("Failed to build request"),
+        )
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    // Check-in publishes through the appointment state machine
+    let appointment_id = uuid::Uuid::new_v4();
+    let mut ctx = AppointmentContext::new(chrono::Utc::now() + chrono::Duration::days(1))
+        .with_status_events(appointment_id, "Jane Doe", events.clone());
+    AppointmentMachine::transition(&AppointmentStatus::Scheduled, AppointmentStateMachineEvent::CheckIn, &mut ctx)
+        .expect("Check-in is a valid transition");
+
+    let mut body = response.into_body().into_data_stream();
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(1), body.next())
+        .await
+        .expect("Timed out waiting for SSE event")
+        .expect("Stream ended")
+        .expect("Failed to read frame");
+    let frame = String::from_utf8(frame.to_vec()).expect("SSE frame is not UTF-8");
+
+    assert!(frame.contains("event: appointment_status_changed\n"));
+    assert!(frame.contains("id: 1\n"));
+    assert!(frame.contains(&format!("\"appointmentId\":\"{}\"", appointment_id)));
+    assert!(frame.contains("\"oldStatus\":\"scheduled\""));
+    assert!(frame.contains("\"newStatus\":\"checked_in\""));
+    assert!(frame.ends_with("\n\n"));
+}
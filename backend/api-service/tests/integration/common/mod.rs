@@ -1,3 +1,6 @@
+// Each test target uses a different subset of these helpers
+#![allow(dead_code)]
+
 /**
  * Common Integration Test Utilities
  *
@@ -6,7 +9,6 @@
 
 use axum::Router;
 use sqlx::PgPool;
-use std::sync::Arc;
 use tower::ServiceExt;
 
 /// Test application state
@@ -48,7 +50,7 @@ pub async fn setup_test_app() -> TestApp {
 /// Create test router with all routes
 ///
 /// Note: This is a placeholder - adjust based on your actual API service structure
-async fn create_test_router(pool: PgPool) -> Router {
+async fn create_test_router(_pool: PgPool) -> Router {
     // TODO: Import and use your actual app creation function
     // Example:
     // api_service::create_app(pool).await
@@ -95,7 +97,7 @@ pub async fn make_request<T: serde::Serialize>(
 ) -> axum::http::Response<axum::body::Body> {
     use axum::http::Request;
 
-    let mut request_builder = Request::builder()
+    let request_builder = Request::builder()
         .method(method)
         .uri(uri)
         .header("Content-Type", "application/json");
@@ -142,7 +144,7 @@ pub async fn make_authenticated_request<T: serde::Serialize>(
 ) -> axum::http::Response<axum::body::Body> {
     use axum::http::Request;
 
-    let mut request_builder = Request::builder()
+    let request_builder = Request::builder()
         .method(method)
         .uri(uri)
         .header("Content-Type", "application/json")
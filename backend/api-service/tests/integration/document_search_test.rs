@@ -0,0 +1,116 @@
+/**
+ * Clinical Document Search Integration Tests
+ *
+ * Tests full-text search over the PostgreSQL document index: stemmed matches,
+ * highlighted snippets, filters and retracted documents.
+ */
+
+mod common;
+
+use chrono::Utc;
+use common::*;
+use shared::domain::repositories::ehr::{EhrDocumentSearchRepository, IndexedDocument};
+use shared::infrastructure::database::DatabaseService;
+use shared::infrastructure::repositories::ehr::EhrDocumentSearchRepositoryImpl;
+use std::sync::Arc;
+
+const PATIENT_IEN: i64 = 990_001;
+
+fn document(ien: i64, document_type: &str, content: &str) -> IndexedDocument {
+    IndexedDocument {
+        ien,
+        patient_ien: PATIENT_IEN,
+        document_type: document_type.to_string(),
+        title: "Follow-up visit".to_string(),
+        content: content.to_string(),
+        status: "signed".to_string(),
+        created_at: Utc::now(),
+    }
+}
+
+async fn remove_documents(app: &TestApp, iens: &[i64]) {
+    sqlx::query("DELETE FROM ehr_document_index WHERE ien = ANY($1)")
+        .bind(iens)
+        .execute(&app.pool)
+        .await
+        .expect("Failed to remove indexed documents");
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_search_highlights_stemmed_matches() {
+    let app = setup_test_app().await;
+    let repository = EhrDocumentSearchRepositoryImpl::new(Arc::new(DatabaseService::new(app.pool.clone())));
+    let iens = [990_101, 990_102];
+
+    repository
+        .upsert(&document(
+            iens[0],
+            "progress_note",
+            "Patient reports persistent dizziness when standing. Orthostatic vitals obtained.",
+        ))
+        .await
+        .expect("Failed to index document");
+    repository
+        .upsert(&document(iens[1], "progress_note", "Routine visit, no complaints."))
+        .await
+        .expect("Failed to index document");
+
+    let results = repository
+        .search("dizzy standing", Some(PATIENT_IEN), None)
+        .await
+        .expect("Failed to search documents");
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].ien, iens[0]);
+    assert!(results[0].snippet.contains("<mark>standing</mark>"), "snippet: {}", results[0].snippet);
+
+    remove_documents(&app, &iens).await;
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_search_filters_and_excludes_retracted() {
+    let app = setup_test_app().await;
+    let repository = EhrDocumentSearchRepositoryImpl::new(Arc::new(DatabaseService::new(app.pool.clone())));
+    let iens = [990_201, 990_202, 990_203];
+
+    repository
+        .upsert(&document(iens[0], "progress_note", "Cellulitis of the left forearm."))
+        .await
+        .expect("Failed to index document");
+    repository
+        .upsert(&document(iens[1], "discharge_summary", "Discharged after cellulitis treatment."))
+        .await
+        .expect("Failed to index document");
+    let mut retracted = document(iens[2], "progress_note", "Cellulitis entered in error.");
+    retracted.status = "retracted".to_string();
+    repository.upsert(&retracted).await.expect("Failed to index document");
+
+    let results = repository
+        .search("cellulitis", Some(PATIENT_IEN), Some("progress_note"))
+        .await
+        .expect("Failed to search documents");
+    assert_eq!(results.iter().map(|r| r.ien).collect::<Vec<_>>(), vec![iens[0]]);
+
+    let results = repository
+        .search("cellulitis", Some(PATIENT_IEN + 1), None)
+        .await
+        .expect("Failed to search documents");
+    assert!(results.is_empty());
+
+    // Re-indexing replaces the stored text
+    repository
+        .upsert(&document(iens[0], "progress_note", "Abscess drained."))
+        .await
+        .expect("Failed to index document");
+    let results = repository
+        .search("cellulitis", Some(PATIENT_IEN), Some("progress_note"))
+        .await
+        .expect("Failed to search documents");
+    assert!(results.is_empty());
+
+    remove_documents(&app, &iens).await;
+    teardown_test_app(&app).await;
+}
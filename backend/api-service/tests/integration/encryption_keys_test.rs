@@ -0,0 +1,138 @@
+/**
+ * Encryption Key Storage Integration Tests
+ *
+ * Tests that DEKs stored in PostgreSQL are wrapped with the master key, and
+ * that encrypted values survive DEK and master key rotation.
+ */
+
+mod common;
+
+use async_trait::async_trait;
+use common::*;
+use shared::domain::entities::EncryptionKey;
+use shared::domain::repositories::KeyRepository;
+use shared::infrastructure::encryption::{DekManager, MasterKey, Vault};
+use shared::infrastructure::repositories::KeyRepositoryImpl;
+use shared::AppResult;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Vault holding only the master key; DEKs are stored in the database
+#[derive(Default)]
+struct MasterKeyVault {
+    master_key: Mutex<Option<Vec<u8>>>,
+}
+
+#[async_trait]
+impl Vault for MasterKeyVault {
+    async fn store_dek(&self, _entity_id: &str, _entity_type: &str, _encrypted_dek: &[u8]) -> AppResult<()> { Ok(()) }
+    async fn get_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+    async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> { Ok(()) }
+    async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> { Ok(()) }
+    async fn store_master_key(&self, master_key: &[u8]) -> AppResult<()> {
+        *self.master_key.lock().unwrap() = Some(master_key.to_vec());
+        Ok(())
+    }
+    async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+        Ok(self.master_key.lock().unwrap().clone())
+    }
+}
+
+fn dek_manager(app: &TestApp) -> DekManager {
+    let master_key = MasterKey::generate().expect("Failed to generate master key");
+    DekManager::new(master_key, Box::new(MasterKeyVault::default())).with_database_storage(app.pool.clone())
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_database_never_contains_plaintext_dek() {
+    let app = setup_test_app().await;
+    let master_key = Arc::new(MasterKey::generate().expect("Failed to generate master key"));
+    let repository = KeyRepositoryImpl::new(app.pool.clone(), master_key.clone());
+
+    let dek = vec![0x42u8; 32];
+    let key = EncryptionKey::new(Uuid::new_v4(), "patient".to_string(), dek.clone(), master_key.version());
+    let saved = repository.create(key).await.expect("Failed to save DEK");
+
+    let (stored, key_version): (Vec<u8>, i32) = sqlx::query_as(
+        "SELECT key_material_wrapped, key_version FROM encryption_keys WHERE id = $1",
+    )
+    .bind(saved.id)
+    .fetch_one(&app.pool)
+    .await
+    .expect("Failed to load stored DEK");
+
+    assert_eq!(key_version, master_key.version());
+    assert!(
+        !stored.windows(dek.len()).any(|window| window == dek.as_slice()),
+        "Stored key material contains the plaintext DEK"
+    );
+
+    let loaded = repository
+        .find_by_id(saved.id)
+        .await
+        .expect("Failed to load DEK")
+        .expect("DEK not found");
+    assert_eq!(loaded.key_material, dek);
+
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_values_decrypt_after_dek_rotation() {
+    let app = setup_test_app().await;
+    let manager = dek_manager(&app);
+    let patient_id = Uuid::new_v4();
+
+    let mrn = manager.encrypt_value(patient_id, "patient", b"MRN-0042").await.expect("Failed to encrypt");
+    let ssn = manager.encrypt_value(patient_id, "patient", b"123-45-6789").await.expect("Failed to encrypt");
+    let repository = KeyRepositoryImpl::new(app.pool.clone(), manager.master_key());
+    let old_key = repository
+        .find_active_by_entity(patient_id, "patient")
+        .await
+        .expect("Failed to load DEK")
+        .expect("DEK not found");
+
+    let new_key_id = manager.rotate_dek(old_key.id).await.expect("Failed to rotate DEK");
+
+    assert_ne!(new_key_id, old_key.id);
+    assert_eq!(manager.decrypt_value(mrn).await.expect("Failed to decrypt"), b"MRN-0042");
+    assert_eq!(manager.decrypt_value(ssn).await.expect("Failed to decrypt"), b"123-45-6789");
+
+    let (key_id,): (Uuid,) = sqlx::query_as("SELECT key_id FROM encrypted_values WHERE id = $1")
+        .bind(mrn)
+        .fetch_one(&app.pool)
+        .await
+        .expect("Failed to load encrypted value");
+    assert_eq!(key_id, new_key_id);
+    let old_key = repository.find_by_id(old_key.id).await.expect("Failed to load DEK").expect("DEK not found");
+    assert!(!old_key.is_active);
+    assert!(manager.rotate_dek(old_key.id).await.is_err(), "A rotated DEK was rotated again");
+
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_values_decrypt_after_master_key_rotation() {
+    let app = setup_test_app().await;
+    let manager = dek_manager(&app);
+    let patient_id = Uuid::new_v4();
+    let mrn = manager.encrypt_value(patient_id, "patient", b"MRN-0042").await.expect("Failed to encrypt");
+    let dek = manager.get_dek(patient_id, "patient").await.expect("Failed to load DEK");
+
+    let new_master_key = MasterKey::generate().expect("Failed to generate master key");
+    let fingerprint = new_master_key.fingerprint();
+    let rewrapped = manager.rotate_master_key(new_master_key).await.expect("Failed to rotate master key");
+
+    assert!(rewrapped >= 1);
+    assert_eq!(manager.master_key().fingerprint(), fingerprint);
+    assert_eq!(manager.master_key().version(), 2);
+    // DEKs are rewrapped, not replaced
+    assert_eq!(manager.get_dek(patient_id, "patient").await.expect("Failed to load DEK"), dek);
+    assert_eq!(manager.decrypt_value(mrn).await.expect("Failed to decrypt"), b"MRN-0042");
+    assert_eq!(KeyRepositoryImpl::latest_key_version(&app.pool).await.expect("Failed to load version"), Some(2));
+
+    teardown_test_app(&app).await;
+}
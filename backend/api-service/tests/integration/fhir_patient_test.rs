@@ -0,0 +1,181 @@
+/**
+ * FHIR Patient Integration Tests
+ *
+ * Round-trips patients through the FHIR R4 Patient endpoints and checks the
+ * CapabilityStatement.
+ */
+
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use common::*;
+use serde_json::{json, Value};
+
+const IEN_SYSTEM: &str = "urn:oid:2.16.840.1.113883.4.6";
+
+async fn login(app: &TestApp) -> String {
+    let response = make_request(
+        app,
+        Method::POST,
+        "/api/v1/auth/login",
+        Some(json!({
+            "email": "admin@test.com",
+            "password": "testpassword123"
+        })),
+    )
+    .await;
+    assert_status(&response, StatusCode::OK);
+    let login: Value = extract_json_body(response).await;
+    login["data"]["accessToken"].as_str().expect("Missing access token").to_string()
+}
+
+fn ien_identifier(patient: &Value) -> Option<String> {
+    patient["identifier"]
+        .as_array()?
+        .iter()
+        .find(|i| i["system"] == IEN_SYSTEM)
+        .and_then(|i| i["value"].as_str())
+        .map(str::to_string)
+}
+
+#[tokio::test]
+#[ignore] // Requires test database and YottaDB - run with: cargo test --test '*' -- --ignored
+async fn test_fhir_patient_round_trip_preserves_ien() {
+    let app = setup_test_app().await;
+    let token = login(&app).await;
+
+    let resource = json!({
+        "resourceType": "Patient",
+        "name": [{ "use": "official", "family": "Roundtrip", "given": ["Fhir"] }],
+        "gender": "female",
+        "birthDate": "1975-09-30"
+    });
+    let response = make_authenticated_request(&app, Method::POST, "/api/v1/fhir/Patient", &token, Some(resource)).await;
+    assert_status(&response, StatusCode::CREATED);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/fhir+json");
+    let location = response.headers()[header::LOCATION].to_str().expect("Invalid Location").to_string();
+
+    let created: Value = extract_json_body(response).await;
+    let ien = ien_identifier(&created).expect("Created patient has no IEN identifier");
+    assert_eq!(location, format!("Patient/{}", ien));
+    assert_eq!(created["id"], ien.as_str());
+
+    let response = make_authenticated_request(
+        &app,
+        Method::GET,
+        &format!("/api/v1/fhir/{}", location),
+        &token,
+        None::<()>,
+    )
+    .await;
+    assert_status(&response, StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "application/fhir+json");
+
+    let read: Value = extract_json_body(response).await;
+    assert_eq!(ien_identifier(&read).as_deref(), Some(ien.as_str()));
+    assert_eq!(read["gender"], "female");
+    assert_eq!(read["birthDate"], "1975-09-30");
+    assert_eq!(read["name"][0]["family"], "ROUNDTRIP");
+
+    let response = make_authenticated_request(
+        &app,
+        Method::GET,
+        "/api/v1/fhir/Patient?name=ROUNDTRIP",
+        &token,
+        None::<()>,
+    )
+    .await;
+    assert_status(&response, StatusCode::OK);
+    let bundle: Value = extract_json_body(response).await;
+    assert_eq!(bundle["type"], "searchset");
+    let found = bundle["entry"]
+        .as_array()
+        .expect("Missing entries")
+        .iter()
+        .any(|e| ien_identifier(&e["resource"]).as_deref() == Some(ien.as_str()));
+    assert!(found, "Search did not return patient {}", ien);
+
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_fhir_patient_missing_fields_rejected() {
+    let app = setup_test_app().await;
+    let token = login(&app).await;
+
+    let resource = json!({ "resourceType": "Patient", "gender": "male" });
+    let response = make_authenticated_request(&app, Method::POST, "/api/v1/fhir/Patient", &token, Some(resource)).await;
+    assert_status(&response, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let outcome: Value = extract_json_body(response).await;
+    assert_eq!(outcome["resourceType"], "OperationOutcome");
+    let expressions: Vec<&str> = outcome["issue"]
+        .as_array()
+        .expect("Missing issues")
+        .iter()
+        .filter_map(|i| i["expression"][0].as_str())
+        .collect();
+    assert_eq!(expressions, ["Patient.name", "Patient.birthDate"]);
+
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_fhir_metadata_lists_patient_interactions() {
+    let app = setup_test_app().await;
+    let token = login(&app).await;
+
+    let response = make_authenticated_request(&app, Method::GET, "/api/v1/fhir/metadata", &token, None::<()>).await;
+    assert_status(&response, StatusCode::OK);
+
+    let capability: Value = extract_json_body(response).await;
+    assert_eq!(capability["resourceType"], "CapabilityStatement");
+    assert_eq!(capability["fhirVersion"], "4.0.1");
+    let resource = &capability["rest"][0]["resource"][0];
+    assert_eq!(resource["type"], "Patient");
+    assert_eq!(resource["interaction"].as_array().map(Vec::len), Some(3));
+
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database and YottaDB - run with: cargo test --test '*' -- --ignored
+async fn test_fhir_endpoints_follow_fhir_export_feature_flag() {
+    let app = setup_test_app().await;
+    let token = login(&app).await;
+
+    let resource = json!({
+        "resourceType": "Patient",
+        "name": [{ "use": "official", "family": "Flagged", "given": ["Fhir"] }],
+        "gender": "male",
+        "birthDate": "1980-01-15"
+    });
+    let response = make_authenticated_request(&app, Method::POST, "/api/v1/fhir/Patient", &token, Some(resource)).await;
+    assert_status(&response, StatusCode::CREATED);
+    let created: Value = extract_json_body(response).await;
+    let ien = ien_identifier(&created).expect("Created patient has no IEN identifier");
+    let path = format!("/api/v1/fhir/Patient/{}", ien);
+
+    // Flags are read per request, so the handler sees the change immediately
+    std::env::set_var("FEATURE_FHIR_EXPORT", "false");
+    let response = make_authenticated_request(&app, Method::GET, &path, &token, None::<()>).await;
+    assert_status(&response, StatusCode::NOT_FOUND);
+    let body: Value = extract_json_body(response).await;
+    assert_eq!(body, json!({ "error": "feature_disabled", "feature": "fhir_export" }));
+
+    let response = make_authenticated_request(&app, Method::GET, "/api/v1/admin/features", &token, None::<()>).await;
+    assert_status(&response, StatusCode::OK);
+    let features: Value = extract_json_body(response).await;
+    assert_eq!(features["features"]["fhir_export"], false);
+
+    std::env::set_var("FEATURE_FHIR_EXPORT", "true");
+    let response = make_authenticated_request(&app, Method::GET, &path, &token, None::<()>).await;
+    assert_status(&response, StatusCode::OK);
+    let read: Value = extract_json_body(response).await;
+    assert_eq!(ien_identifier(&read).as_deref(), Some(ien.as_str()));
+
+    std::env::remove_var("FEATURE_FHIR_EXPORT");
+    teardown_test_app(&app).await;
+}
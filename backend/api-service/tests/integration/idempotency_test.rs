@@ -0,0 +1,187 @@
+/**
+ * Idempotency Key Integration Tests
+ *
+ * Tests that POSTs retried with the same Idempotency-Key run the handler once
+ * and replay the recorded response, that a retry racing the original request
+ * is turned away, and that keys cannot be reused across users.
+ */
+
+mod common;
+
+use api_service::presentation::api::middleware::{idempotency_middleware, IdempotencyStore, IDEMPOTENCY_KEY_HEADER};
+use axum::{
+    body::Body,
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+    routing::post,
+    Json, Router,
+};
+use common::*;
+use shared::infrastructure::database::DatabaseService;
+use shared::infrastructure::repositories::IdempotencyKeyRepositoryImpl;
+use shared::RequestContext;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Notify, Semaphore};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+/// Stands in for `create_patient`, counting MUMPS writes instead of running them
+fn patient_router(app: &TestApp, mumps_writes: Arc<AtomicUsize>) -> Router {
+    let gate = Arc::new(Semaphore::new(Semaphore::MAX_PERMITS));
+    gated_patient_router(app, mumps_writes, Arc::new(Notify::new()), gate)
+}
+
+/// [`patient_router`] whose handler signals `entered` and then waits for a
+/// permit from `gate` before writing
+fn gated_patient_router(
+    app: &TestApp,
+    mumps_writes: Arc<AtomicUsize>,
+    entered: Arc<Notify>,
+    gate: Arc<Semaphore>,
+) -> Router {
+    let store = Arc::new(IdempotencyStore::new(Arc::new(IdempotencyKeyRepositoryImpl::new(Arc::new(
+        DatabaseService::new(app.pool.clone()),
+    )))));
+
+    Router::new()
+        .route(
+            "/v1/ehr/patients",
+            post(move || {
+                let mumps_writes = mumps_writes.clone();
+                let (entered, gate) = (entered.clone(), gate.clone());
+                async move {
+                    entered.notify_one();
+                    let _permit = gate.acquire().await.expect("gate closed");
+                    let ien = mumps_writes.fetch_add(1, Ordering::SeqCst) + 1;
+                    (StatusCode::CREATED, Json(serde_json::json!({ "ien": ien, "id": Uuid::new_v4() })))
+                }
+            }),
+        )
+        .layer(axum::middleware::from_fn_with_state(store, idempotency_middleware))
+        // Stands in for auth_middleware: the user comes from the X-Test-User header
+        .layer(axum::middleware::from_fn(|mut request: Request, next: Next| async move {
+            let user_id = request
+                .headers()
+                .get("X-Test-User")
+                .and_then(|h| h.to_str().ok())
+                .and_then(|v| Uuid::parse_str(v).ok())
+                .expect("X-Test-User header");
+            request.extensions_mut().insert(RequestContext::new(
+                Uuid::new_v4().to_string(),
+                user_id,
+                "clerk@example.com".to_string(),
+                None,
+                vec![],
+            ));
+            next.run(request).await
+        }))
+}
+
+async fn create_patient(router: &Router, key: Uuid, user_id: Uuid) -> Response {
+    let request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/v1/ehr/patients")
+        .header("content-type", "application/json")
+        .header(IDEMPOTENCY_KEY_HEADER, key.to_string())
+        .header("X-Test-User", user_id.to_string())
+        .body(Body::from(r#"{"firstName":"Retry","lastName":"Test","sex":"F"}"#))
+        .unwrap();
+    router.clone().oneshot(request).await.unwrap()
+}
+
+async fn remove_key(app: &TestApp, key: Uuid) {
+    sqlx::query("DELETE FROM idempotency_keys WHERE key = $1")
+        .bind(key)
+        .execute(&app.pool)
+        .await
+        .expect("Failed to remove idempotency key");
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_retried_create_patient_writes_once() {
+    let app = setup_test_app().await;
+    let mumps_writes = Arc::new(AtomicUsize::new(0));
+    let router = patient_router(&app, mumps_writes.clone());
+    let key = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+
+    let first = create_patient(&router, key, user_id).await;
+    assert_eq!(first.status(), StatusCode::CREATED);
+    let first_body = axum::body::to_bytes(first.into_body(), usize::MAX).await.unwrap();
+
+    let second = create_patient(&router, key, user_id).await;
+    assert_eq!(second.status(), StatusCode::CREATED);
+    let second_body = axum::body::to_bytes(second.into_body(), usize::MAX).await.unwrap();
+
+    assert_eq!(mumps_writes.load(Ordering::SeqCst), 1);
+    assert_eq!(first_body, second_body);
+
+    // A new key is a new request
+    let other_key = Uuid::new_v4();
+    assert_eq!(create_patient(&router, other_key, user_id).await.status(), StatusCode::CREATED);
+    assert_eq!(mumps_writes.load(Ordering::SeqCst), 2);
+
+    remove_key(&app, key).await;
+    remove_key(&app, other_key).await;
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_key_reuse_by_another_user_is_rejected() {
+    let app = setup_test_app().await;
+    let mumps_writes = Arc::new(AtomicUsize::new(0));
+    let router = patient_router(&app, mumps_writes.clone());
+    let key = Uuid::new_v4();
+
+    assert_eq!(create_patient(&router, key, Uuid::new_v4()).await.status(), StatusCode::CREATED);
+    let response = create_patient(&router, key, Uuid::new_v4()).await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(mumps_writes.load(Ordering::SeqCst), 1);
+
+    remove_key(&app, key).await;
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_concurrent_retry_waits_for_the_original_request() {
+    let app = setup_test_app().await;
+    let mumps_writes = Arc::new(AtomicUsize::new(0));
+    let entered = Arc::new(Notify::new());
+    let gate = Arc::new(Semaphore::new(0));
+    let router = gated_patient_router(&app, mumps_writes.clone(), entered.clone(), gate.clone());
+    let key = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+
+    let original = tokio::spawn({
+        let router = router.clone();
+        async move { create_patient(&router, key, user_id).await }
+    });
+    entered.notified().await;
+
+    // The original request holds the key while its handler runs
+    let retry = create_patient(&router, key, user_id).await;
+    assert_eq!(retry.status(), StatusCode::CONFLICT);
+
+    gate.add_permits(1);
+    let original = original.await.unwrap();
+    assert_eq!(original.status(), StatusCode::CREATED);
+    let original_body = axum::body::to_bytes(original.into_body(), usize::MAX).await.unwrap();
+
+    gate.add_permits(1);
+    let replayed = create_patient(&router, key, user_id).await;
+    assert_eq!(replayed.status(), StatusCode::CREATED);
+    let replayed_body = axum::body::to_bytes(replayed.into_body(), usize::MAX).await.unwrap();
+
+    assert_eq!(mumps_writes.load(Ordering::SeqCst), 1);
+    assert_eq!(original_body, replayed_body);
+
+    remove_key(&app, key).await;
+    teardown_test_app(&app).await;
+}
@@ -0,0 +1,159 @@
+/**
+ * Patient Integration Tests
+ *
+ * Tests that patient records carry audit fields from the authenticated request,
+ * and that MRN and SSN lookups work without storing the SSN in plaintext.
+ */
+
+mod common;
+
+use async_trait::async_trait;
+use axum::http::{Method, StatusCode};
+use chrono::NaiveDate;
+use common::*;
+use serde_json::json;
+use shared::domain::entities::ehr::{EhrPatient, Gender};
+use shared::domain::repositories::ehr::EhrPatientRepository;
+use shared::infrastructure::database::DatabaseService;
+use shared::infrastructure::encryption::{DekManager, FieldEncryption, MasterKey, Vault};
+use shared::infrastructure::repositories::ehr::EhrPatientRepositoryImpl;
+use shared::{AppResult, RequestContext};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Vault without storage; deterministic encryption only needs the master key
+struct NullVault;
+
+#[async_trait]
+impl Vault for NullVault {
+    async fn store_dek(&self, _entity_id: &str, _entity_type: &str, _encrypted_dek: &[u8]) -> AppResult<()> { Ok(()) }
+    async fn get_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+    async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> { Ok(()) }
+    async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> { Ok(()) }
+    async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> { Ok(()) }
+    async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_create_patient_populates_created_by() {
+    let app = setup_test_app().await;
+
+    // Login as the test admin
+    let login_response = make_request(
+        &app,
+        Method::POST,
+        "/api/v1/auth/login",
+        Some(json!({
+            "email": "admin@test.com",
+            "password": "testpassword123"
+        })),
+    )
+    .await;
+    assert_status(&login_response, StatusCode::OK);
+    let login: serde_json::Value = extract_json_body(login_response).await;
+    let token = login["data"]["accessToken"].as_str().expect("Missing access token");
+
+    let payload = json!({
+        "firstName": "Audit",
+        "lastName": "Trail",
+        "dateOfBirth": "1980-04-12",
+        "sex": "F"
+    });
+
+    let response = make_authenticated_request(
+        &app,
+        Method::POST,
+        "/api/v1/ehr/patients",
+        token,
+        Some(payload),
+    )
+    .await;
+    assert_status(&response, StatusCode::OK);
+
+    let body: serde_json::Value = extract_json_body(response).await;
+    let patient_id = Uuid::parse_str(body["data"]["id"].as_str().expect("Missing patient id"))
+        .expect("Invalid patient id");
+
+    let created_by: Option<Uuid> =
+        sqlx::query_scalar("SELECT created_by FROM ehr_patients WHERE id = $1")
+            .bind(patient_id)
+            .fetch_one(&app.pool)
+            .await
+            .expect("Failed to load patient");
+
+    assert_eq!(created_by, Some(*shared::testing::TEST_ADMIN_UUID));
+
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_find_by_mrn_and_ssn_logs_access_without_plaintext_ssn() {
+    let app = setup_test_app().await;
+    let master_key = MasterKey::generate().expect("Failed to generate master key");
+    let field_encryption = Arc::new(
+        FieldEncryption::new(Arc::new(DekManager::new(master_key, Box::new(NullVault))))
+            .await
+            .expect("Failed to load field encryption key"),
+    );
+    let repository = EhrPatientRepositoryImpl::new(Arc::new(DatabaseService::new(app.pool.clone())))
+        .with_field_encryption(field_encryption);
+    let org_id = *shared::testing::TEST_ORG_UUID;
+
+    let mut patient = EhrPatient::new(
+        org_id,
+        "Admission".to_string(),
+        "Lookup".to_string(),
+        NaiveDate::from_ymd_opt(1975, 6, 30).expect("Invalid date"),
+        Gender::Female,
+        "MRN-SSN-0001".to_string(),
+    );
+    patient.ssn_last_four = Some("6789".to_string());
+    let created = repository.create(patient).await.expect("Failed to create patient");
+
+    let by_mrn = repository
+        .find_by_mrn("MRN-SSN-0001", org_id)
+        .await
+        .expect("Failed to look up by MRN")
+        .expect("Patient not found by MRN");
+    assert_eq!(by_mrn.id, created.id);
+
+    let ctx = RequestContext::new(
+        "ssn-search".to_string(),
+        *shared::testing::TEST_ADMIN_UUID,
+        "admin@test.com".to_string(),
+        None,
+        vec![],
+    );
+    let by_ssn = ctx
+        .scope(repository.find_by_ssn("6789", org_id))
+        .await
+        .expect("Failed to look up by SSN");
+    assert_eq!(by_ssn.iter().map(|p| p.id).collect::<Vec<_>>(), vec![created.id]);
+    assert!(repository.find_by_ssn("123-45-6789", org_id).await.is_err(), "Full SSN was searched");
+
+    let (user_id, patient_ien, access_type): (Option<Uuid>, Option<i64>, String) = sqlx::query_as(
+        "SELECT user_id, patient_ien, access_type FROM patient_access_log WHERE patient_ien = $1",
+    )
+    .bind(created.ien)
+    .fetch_one(&app.pool)
+    .await
+    .expect("Missing access log entry");
+    assert_eq!(user_id, Some(*shared::testing::TEST_ADMIN_UUID));
+    assert_eq!(patient_ien, Some(created.ien));
+    assert_eq!(access_type, "ssn_search");
+
+    let (row, ciphertext): (String, Option<Vec<u8>>) = sqlx::query_as(
+        "SELECT row_to_json(p)::text, ssn_last4_ciphertext FROM ehr_patients p WHERE id = $1",
+    )
+    .bind(created.id)
+    .fetch_one(&app.pool)
+    .await
+    .expect("Failed to load patient");
+    let ciphertext = ciphertext.expect("SSN ciphertext not stored");
+    assert!(!ciphertext.windows(4).any(|window| window == b"6789"), "Ciphertext contains the SSN");
+    assert!(!row.contains("\"6789\""), "Plaintext SSN stored: {}", row);
+
+    teardown_test_app(&app).await;
+}
@@ -0,0 +1,102 @@
+/**
+ * Relationship Batch Write Integration Tests
+ *
+ * Tests that batched tuple writes and deletes are transactional and idempotent,
+ * and that tuple writes record the requesting user.
+ */
+
+mod common;
+
+use common::*;
+use shared::domain::entities::{Relationship, RelationshipKey, RelationshipWrite};
+use shared::domain::repositories::RelationshipRepository;
+use shared::infrastructure::repositories::RelationshipRepositoryImpl;
+use shared::testing::{TEST_ADMIN_UUID, TEST_ORG_UUID};
+use shared::RequestContext;
+use uuid::Uuid;
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_write_batch_is_idempotent() {
+    let app = setup_test_app().await;
+    let repository = RelationshipRepositoryImpl::new(app.pool.clone());
+
+    let user = format!("user:{}", Uuid::new_v4());
+    // Organization-scoped, as the unique key treats a NULL organization as distinct
+    let tuples: Vec<Relationship> = (0..100)
+        .map(|i| {
+            Relationship::new_with_organization(
+                user.clone(),
+                "can_access".to_string(),
+                format!("app:batch-{}", i),
+                Some(*TEST_ORG_UUID),
+            )
+        })
+        .collect();
+
+    let inserted = repository.write_batch(tuples.clone()).await.expect("Failed to write batch");
+    assert_eq!(inserted, 100);
+    assert_eq!(repository.find_by_user(&user).await.expect("Failed to load relationships").len(), 100);
+
+    // Rerunning the same batch inserts nothing and does not fail
+    let inserted = repository.write_batch(tuples.clone()).await.expect("Failed to rewrite batch");
+    assert_eq!(inserted, 0);
+
+    let keys: Vec<RelationshipKey> = tuples
+        .iter()
+        .map(|t| RelationshipKey {
+            user: t.user.clone(),
+            relation: t.relation.clone(),
+            object: t.object.clone(),
+            organization_id: t.organization_id,
+        })
+        .collect();
+    let deleted = repository.delete_batch(keys).await.expect("Failed to delete batch");
+    assert_eq!(deleted, 100);
+
+    sqlx::query("DELETE FROM relationships WHERE \"user\" = $1")
+        .bind(&user)
+        .execute(&app.pool)
+        .await
+        .expect("Failed to remove relationships");
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_write_tuples_records_request_user() {
+    let app = setup_test_app().await;
+    let repository = RelationshipRepositoryImpl::new(app.pool.clone());
+
+    let user = format!("user:{}", Uuid::new_v4());
+    let tuple = Relationship::new_with_organization(
+        user.clone(),
+        "can_access".to_string(),
+        "app:audited".to_string(),
+        Some(*TEST_ORG_UUID),
+    );
+    let ctx = RequestContext::new(
+        "tuple-write".to_string(),
+        *TEST_ADMIN_UUID,
+        "admin@test.com".to_string(),
+        None,
+        vec![],
+    );
+    let response = ctx
+        .scope(repository.write_tuples(vec![RelationshipWrite::Write(tuple)]))
+        .await
+        .expect("Failed to write tuples");
+    assert_eq!(response.written, 1);
+
+    let stored = repository.find_by_user(&user).await.expect("Failed to load relationships");
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].created_by, Some(*TEST_ADMIN_UUID));
+    assert_eq!(stored[0].updated_by, Some(*TEST_ADMIN_UUID));
+
+    sqlx::query("DELETE FROM relationships WHERE \"user\" = $1")
+        .bind(&user)
+        .execute(&app.pool)
+        .await
+        .expect("Failed to remove relationships");
+    teardown_test_app(&app).await;
+}
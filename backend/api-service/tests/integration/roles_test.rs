@@ -0,0 +1,107 @@
+/**
+ * Role Assignment Integration Tests
+ *
+ * Tests that time-bounded role assignments lapse and are purged, and that role
+ * updates record the requesting user.
+ */
+
+mod common;
+
+use chrono::{Duration, Utc};
+use common::*;
+use shared::domain::entities::{Relationship, RelationshipWrite, Role};
+use shared::domain::repositories::{RelationshipRepository, RoleRepository};
+use shared::infrastructure::database::DatabaseService;
+use shared::infrastructure::repositories::{PermissionRepositoryImpl, RelationshipRepositoryImpl, RoleRepositoryImpl};
+use shared::infrastructure::zanzibar::RelationshipStore;
+use shared::testing::TEST_ADMIN_UUID;
+use shared::RequestContext;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_time_bounded_role_lapses() {
+    let app = setup_test_app().await;
+    let repository = RelationshipRepositoryImpl::new(app.pool.clone());
+
+    let user = format!("user:{}", Uuid::new_v4());
+    let now = Utc::now();
+    let locum = Relationship::new_with_validity(
+        user.clone(),
+        "has_role".to_string(),
+        "role:doctor".to_string(),
+        now,
+        Some(now + Duration::seconds(1)),
+    );
+    repository
+        .write_tuples(vec![RelationshipWrite::Write(locum)])
+        .await
+        .expect("Failed to assign role");
+
+    let roles = repository.find_roles_for_user(&user).await.expect("Failed to load roles");
+    assert_eq!(roles.len(), 1);
+    assert_eq!(roles[0].object, "role:doctor");
+
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+
+    let roles = repository.find_roles_for_user(&user).await.expect("Failed to load roles");
+    assert!(roles.is_empty(), "Expired role is still returned: {:?}", roles);
+
+    let deleted = repository.delete_expired().await.expect("Failed to delete expired relationships");
+    assert!(deleted >= 1);
+    assert!(repository.find_by_user(&user).await.expect("Failed to load relationships").is_empty());
+
+    teardown_test_app(&app).await;
+}
+
+#[tokio::test]
+#[ignore] // Requires test database - run with: cargo test --test '*' -- --ignored
+async fn test_role_update_records_updated_by() {
+    let app = setup_test_app().await;
+    let database_service = Arc::new(DatabaseService::new(app.pool.clone()));
+    let relationship_store = Arc::new(RelationshipStore::new(Box::new(RelationshipRepositoryImpl::new(
+        app.pool.clone(),
+    ))));
+    let repository = RoleRepositoryImpl::new(
+        database_service,
+        relationship_store,
+        Arc::new(PermissionRepositoryImpl::new(app.pool.clone())),
+    );
+
+    let parent = repository
+        .create(Role::new(format!("attending-{}", Uuid::new_v4()), None))
+        .await
+        .expect("Failed to create role");
+    let child = repository
+        .create(Role::new(format!("resident-{}", Uuid::new_v4()), None))
+        .await
+        .expect("Failed to create role");
+
+    let ctx = RequestContext::new(
+        "role-update".to_string(),
+        *TEST_ADMIN_UUID,
+        "admin@test.com".to_string(),
+        None,
+        vec![],
+    );
+    ctx.scope(repository.set_parent_role(child.id, Some(parent.id)))
+        .await
+        .expect("Failed to set parent role");
+
+    let (parent_id, updated_by): (Option<Uuid>, Option<Uuid>) =
+        sqlx::query_as("SELECT role_parent_id, updated_by FROM roles WHERE id = $1")
+            .bind(child.id)
+            .fetch_one(&app.pool)
+            .await
+            .expect("Failed to load role");
+    assert_eq!(parent_id, Some(parent.id));
+    assert_eq!(updated_by, Some(*TEST_ADMIN_UUID));
+
+    sqlx::query("DELETE FROM roles WHERE id = ANY($1)")
+        .bind(vec![child.id, parent.id])
+        .execute(&app.pool)
+        .await
+        .expect("Failed to remove roles");
+    teardown_test_app(&app).await;
+}
@@ -0,0 +1,271 @@
+//! Brute-force protection for login
+//!
+//! Failed logins are counted per username. Once `max_attempts` failures
+//! accumulate the account is locked until the attempts expire,
+//! `lockout_duration` after the most recent failure. A successful login
+//! clears the count.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use shared::infrastructure::session::RedisStore;
+use shared::{AppError, AppResult};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Default failures allowed before an account is locked
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Default minutes failed attempts are remembered
+pub const DEFAULT_LOCKOUT_DURATION_MINUTES: u64 = 15;
+
+/// When to lock an account and for how long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    pub max_attempts: u32,
+    pub lockout_duration: Duration,
+}
+
+impl LockoutPolicy {
+    pub fn new(max_attempts: u32, lockout_duration_minutes: u64) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            lockout_duration: Duration::minutes(i64::try_from(lockout_duration_minutes).unwrap_or(i64::MAX / 60_000)),
+        }
+    }
+}
+
+impl Default for LockoutPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ATTEMPTS, DEFAULT_LOCKOUT_DURATION_MINUTES)
+    }
+}
+
+/// Failed attempts for one username
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginAttempts {
+    pub count: u32,
+    /// When the attempts are forgotten
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Where failed attempts are kept
+#[async_trait]
+pub trait LoginAttemptStore: Send + Sync {
+    async fn get(&self, username: &str) -> AppResult<Option<LoginAttempts>>;
+
+    /// Count one more failure and move the expiry to `expires_at` as a single
+    /// atomic step, so concurrent failures are never lost; attempts expired
+    /// at `now` start again from zero. Returns the new count.
+    async fn increment(&self, username: &str, now: DateTime<Utc>, expires_at: DateTime<Utc>) -> AppResult<u32>;
+
+    async fn clear(&self, username: &str) -> AppResult<()>;
+}
+
+/// Attempts kept in process memory (single instance deployments)
+#[derive(Default)]
+pub struct InMemoryLoginAttemptStore {
+    attempts: Mutex<HashMap<String, LoginAttempts>>,
+}
+
+#[async_trait]
+impl LoginAttemptStore for InMemoryLoginAttemptStore {
+    async fn get(&self, username: &str) -> AppResult<Option<LoginAttempts>> {
+        let attempts = self.attempts.lock()
+            .map_err(|_| AppError::Internal("Login attempt store poisoned".to_string()))?;
+        Ok(attempts.get(username).cloned())
+    }
+
+    async fn increment(&self, username: &str, now: DateTime<Utc>, expires_at: DateTime<Utc>) -> AppResult<u32> {
+        let mut stored = self.attempts.lock()
+            .map_err(|_| AppError::Internal("Login attempt store poisoned".to_string()))?;
+        // Drop expired entries so usernames that stop failing do not accumulate
+        stored.retain(|_, a| a.expires_at > now);
+        let attempts = stored
+            .entry(username.to_string())
+            .or_insert(LoginAttempts { count: 0, expires_at });
+        attempts.count += 1;
+        attempts.expires_at = expires_at;
+        Ok(attempts.count)
+    }
+
+    async fn clear(&self, username: &str) -> AppResult<()> {
+        let mut stored = self.attempts.lock()
+            .map_err(|_| AppError::Internal("Login attempt store poisoned".to_string()))?;
+        stored.remove(username);
+        Ok(())
+    }
+}
+
+/// Attempts shared between instances as `login_failures:{username}` Redis
+/// counters expiring with the attempts
+pub struct RedisLoginAttemptStore {
+    redis: Arc<dyn RedisStore>,
+}
+
+impl RedisLoginAttemptStore {
+    pub fn new(redis: Arc<dyn RedisStore>) -> Self {
+        Self { redis }
+    }
+
+    fn key(username: &str) -> String {
+        format!("login_failures:{}", username)
+    }
+}
+
+#[async_trait]
+impl LoginAttemptStore for RedisLoginAttemptStore {
+    async fn get(&self, username: &str) -> AppResult<Option<LoginAttempts>> {
+        let key = Self::key(username);
+        let Some(data) = self.redis.get(&key).await? else {
+            return Ok(None);
+        };
+        let count = data
+            .parse()
+            .map_err(|e| AppError::Internal(format!("Invalid login attempts for {}: {}", username, e)))?;
+        // The counter expired between the two reads
+        let Some(ttl_seconds) = self.redis.ttl(&key).await? else {
+            return Ok(None);
+        };
+        let expires_at = Utc::now() + Duration::seconds(i64::try_from(ttl_seconds).unwrap_or(i64::MAX / 1000));
+        Ok(Some(LoginAttempts { count, expires_at }))
+    }
+
+    async fn increment(&self, username: &str, now: DateTime<Utc>, expires_at: DateTime<Utc>) -> AppResult<u32> {
+        // Redis expires the counter itself, so `now` only sets the TTL
+        let ttl_seconds = (expires_at - now).num_seconds().max(1) as u64;
+        let count = self.redis.incr_ex(&Self::key(username), ttl_seconds).await?;
+        Ok(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+
+    async fn clear(&self, username: &str) -> AppResult<()> {
+        self.redis.del(&[Self::key(username)]).await
+    }
+}
+
+/// Counts failed logins and rejects locked accounts
+pub struct LoginAttemptTracker {
+    store: Arc<dyn LoginAttemptStore>,
+    policy: LockoutPolicy,
+}
+
+impl LoginAttemptTracker {
+    pub fn new(store: Arc<dyn LoginAttemptStore>, policy: LockoutPolicy) -> Self {
+        Self { store, policy }
+    }
+
+    /// Tracker keeping attempts in process memory
+    pub fn in_memory(policy: LockoutPolicy) -> Self {
+        Self::new(Arc::new(InMemoryLoginAttemptStore::default()), policy)
+    }
+
+    /// Usernames are matched case-insensitively
+    fn key(username: &str) -> String {
+        username.trim().to_lowercase()
+    }
+
+    async fn current(&self, key: &str, now: DateTime<Utc>) -> AppResult<Option<LoginAttempts>> {
+        Ok(self.store.get(key).await?.filter(|a| a.expires_at > now))
+    }
+
+    /// `AccountLocked` if the username has reached the failure limit
+    pub async fn check(&self, username: &str) -> AppResult<()> {
+        self.check_at(username, Utc::now()).await
+    }
+
+    async fn check_at(&self, username: &str, now: DateTime<Utc>) -> AppResult<()> {
+        match self.current(&Self::key(username), now).await? {
+            Some(attempts) if attempts.count >= self.policy.max_attempts => {
+                let remaining_seconds = (attempts.expires_at - now).num_seconds().max(1) as u64;
+                Err(AppError::AccountLocked {
+                    remaining_minutes: remaining_seconds.div_ceil(60),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Count a failed login; `TooManyAttempts` if this failure locks the account
+    pub async fn record_failure(&self, username: &str) -> AppResult<()> {
+        self.record_failure_at(username, Utc::now()).await
+    }
+
+    async fn record_failure_at(&self, username: &str, now: DateTime<Utc>) -> AppResult<()> {
+        let expires_at = now + self.policy.lockout_duration;
+        let count = self.store.increment(&Self::key(username), now, expires_at).await?;
+
+        if count >= self.policy.max_attempts {
+            return Err(AppError::TooManyAttempts {
+                retry_after_seconds: self.policy.lockout_duration.num_seconds().max(0) as u64,
+            });
+        }
+        Ok(())
+    }
+
+    /// Forget failed attempts after a successful login
+    pub async fn clear(&self, username: &str) -> AppResult<()> {
+        self.store.clear(&Self::key(username)).await
+    }
+}
+
+impl Default for LoginAttemptTracker {
+    fn default() -> Self {
+        Self::in_memory(LockoutPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn limit_reached_locks_until_attempts_expire() {
+        let tracker = LoginAttemptTracker::in_memory(LockoutPolicy::new(3, 15));
+        let start = Utc::now();
+
+        for _ in 0..2 {
+            tracker.record_failure_at("nurse@example.com", start).await.unwrap();
+        }
+        assert!(tracker.check_at("nurse@example.com", start).await.is_ok());
+
+        let err = tracker.record_failure_at("Nurse@Example.com", start).await.unwrap_err();
+        assert!(matches!(err, AppError::TooManyAttempts { retry_after_seconds: 900 }));
+
+        let err = tracker.check_at("nurse@example.com", start + Duration::minutes(1)).await.unwrap_err();
+        assert!(matches!(err, AppError::AccountLocked { remaining_minutes: 14 }));
+
+        let after = start + Duration::minutes(15) + Duration::seconds(1);
+        assert!(tracker.check_at("nurse@example.com", after).await.is_ok());
+        // Expired attempts no longer count towards the limit
+        assert!(tracker.record_failure_at("nurse@example.com", after).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn concurrent_failures_are_all_counted() {
+        let tracker = Arc::new(LoginAttemptTracker::in_memory(LockoutPolicy::new(100, 15)));
+        let now = Utc::now();
+
+        let failures: Vec<_> = (0..20)
+            .map(|_| {
+                let tracker = tracker.clone();
+                tokio::spawn(async move { tracker.record_failure_at("nurse@example.com", now).await })
+            })
+            .collect();
+        for failure in failures {
+            failure.await.unwrap().unwrap();
+        }
+
+        let attempts = tracker.current("nurse@example.com", now).await.unwrap().unwrap();
+        assert_eq!(attempts.count, 20);
+    }
+
+    #[tokio::test]
+    async fn usernames_are_tracked_separately() {
+        let tracker = LoginAttemptTracker::in_memory(LockoutPolicy::new(1, 15));
+        let now = Utc::now();
+
+        assert!(tracker.record_failure_at("a@example.com", now).await.is_err());
+        assert!(tracker.check_at("a@example.com", now).await.is_err());
+        assert!(tracker.check_at("b@example.com", now).await.is_ok());
+    }
+}
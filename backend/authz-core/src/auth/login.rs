@@ -1,12 +1,16 @@
 use crate::dto::{LoginRequest, LoginResponse, LoginUserResponse};
 use shared::domain::repositories::{UserRepository, RefreshTokenRepository, RoleRepository, PermissionRepository};
-use crate::oidc::TokenManager;
+use crate::oidc::{ClaimsTransformer, TokenManager, API_SERVICE_AUDIENCE};
+use super::lockout::LoginAttemptTracker;
+use super::mfa::MfaVerifier;
+use super::refresh_token::RefreshTokenPolicy;
 use shared::AppResult;
 use bcrypt::verify;
 use uuid::Uuid;
-use chrono::{Utc, Duration};
+use chrono::Utc;
 use sha2::{Sha256, Digest};
 use std::collections::HashSet;
+use std::sync::Arc;
 
 pub struct LoginUseCase {
     user_repository: Box<dyn UserRepository>,
@@ -14,6 +18,10 @@ pub struct LoginUseCase {
     role_repository: Box<dyn RoleRepository>,
     permission_repository: Box<dyn PermissionRepository>,
     token_manager: TokenManager,
+    claims_transformer: Option<ClaimsTransformer>,
+    mfa_verifier: Option<Arc<dyn MfaVerifier>>,
+    attempt_tracker: Arc<LoginAttemptTracker>,
+    refresh_token_policy: RefreshTokenPolicy,
 }
 
 impl LoginUseCase {
@@ -30,8 +38,51 @@ impl LoginUseCase {
             role_repository,
             permission_repository,
             token_manager,
+            claims_transformer: None,
+            mfa_verifier: None,
+            attempt_tracker: Arc::new(LoginAttemptTracker::default()),
+            refresh_token_policy: RefreshTokenPolicy::default(),
         }
     }
+
+    /// Enrich access token claims (roles, permissions, realm custom claims) before signing
+    pub fn with_claims_transformer(mut self, claims_transformer: ClaimsTransformer) -> Self {
+        self.claims_transformer = Some(claims_transformer);
+        self
+    }
+
+    /// Require a second factor from users enrolled with `mfa_verifier`
+    pub fn with_mfa_verifier(mut self, mfa_verifier: Arc<dyn MfaVerifier>) -> Self {
+        self.mfa_verifier = Some(mfa_verifier);
+        self
+    }
+
+    /// Count failed logins in `attempt_tracker` (defaults to an in-memory tracker)
+    pub fn with_attempt_tracker(mut self, attempt_tracker: Arc<LoginAttemptTracker>) -> Self {
+        self.attempt_tracker = attempt_tracker;
+        self
+    }
+
+    /// Expiry of issued refresh tokens
+    pub fn with_refresh_token_policy(mut self, refresh_token_policy: RefreshTokenPolicy) -> Self {
+        self.refresh_token_policy = refresh_token_policy;
+        self
+    }
+
+    /// Count a failed login, returning the error to report for it
+    async fn reject(&self, email: &str, err: shared::AppError, location: &str) -> shared::AppError {
+        let err = match self.attempt_tracker.record_failure(email).await {
+            Ok(()) => err,
+            Err(locked @ shared::AppError::TooManyAttempts { .. }) => locked,
+            Err(e) => {
+                // Lockout is best-effort; a broken store must not block logins
+                e.log_with_operation(location, "login_attempts");
+                err
+            }
+        };
+        err.log_with_operation(location, "login");
+        err
+    }
     
     async fn get_user_role_and_permissions(&self, user_id: Uuid, is_super_user: bool) -> AppResult<(String, Vec<String>)> {
         // Super users bypass permission checks - return all permissions
@@ -74,21 +125,29 @@ impl LoginUseCase {
         Ok((primary_role, permission_names))
     }
 
-    pub async fn execute(&self, request: LoginRequest) -> AppResult<LoginResponse> {
+    /// Authenticate the user; the refresh token issued is bound to `device_fingerprint`
+    pub async fn execute(&self, request: LoginRequest, device_fingerprint: &str) -> AppResult<LoginResponse> {
         let location = concat!(file!(), ":", line!());
+        // Locked accounts are rejected before any password is checked
+        self.attempt_tracker.check(&request.email).await.map_err(|e| {
+            e.log_with_operation(location, "login");
+            e
+        })?;
+
         // Find user by email
-        let user = self.user_repository
+        let user = match self.user_repository
             .find_by_email(&request.email)
             .await
             .map_err(|e| {
                 e.log_with_operation(location, "login");
                 e
-            })?
-            .ok_or_else(|| {
+            })? {
+            Some(user) => user,
+            None => {
                 let err = shared::AppError::Authentication("Invalid credentials".to_string());
-                err.log_with_operation(location, "login");
-                err
-            })?;
+                return Err(self.reject(&request.email, err, location).await);
+            }
+        };
 
         // Verify password
         if !verify(&request.password, &user.password_hash)
@@ -98,8 +157,7 @@ impl LoginUseCase {
                 err
             })? {
             let err = shared::AppError::Authentication("Invalid credentials".to_string());
-            err.log_with_operation(location, "login");
-            return Err(err);
+            return Err(self.reject(&request.email, err, location).await);
         }
 
         // Check if user is active
@@ -109,6 +167,26 @@ impl LoginUseCase {
             return Err(err);
         }
 
+        // Second factor, checked only after the password so enrolment cannot be probed
+        if let Some(verifier) = &self.mfa_verifier {
+            if verifier.is_enrolled(&user).await? {
+                let token = request.mfa_token.as_deref().ok_or_else(|| {
+                    let err = shared::AppError::MfaRequired("MFA token required".to_string());
+                    err.log_with_operation(location, "login");
+                    err
+                })?;
+                if !verifier.verify(&user, token).await? {
+                    let err = shared::AppError::Authentication("Invalid MFA token".to_string());
+                    return Err(self.reject(&request.email, err, location).await);
+                }
+            }
+        }
+
+        // Credentials accepted; forget earlier failures (best-effort)
+        if let Err(e) = self.attempt_tracker.clear(&request.email).await {
+            e.log_with_operation(location, "login_attempts");
+        }
+
         // Get user roles and permissions
         let (primary_role, permissions) = self.get_user_role_and_permissions(user.id, user.is_super_user).await
             .map_err(|e| {
@@ -121,13 +199,25 @@ impl LoginUseCase {
         
         // Generate tokens with role, permissions, and organization context
         // Note: realm_id is populated by the handler after vault lookup
-        let access_token = self.token_manager.generate_access_token_with_context(
+        let base_claims = self.token_manager.build_access_claims_for_audience(
             &user,
             primary_role.as_str(),
             &permissions,
             organization_id.clone(),
             None, // realm_id will be populated by handler
-        )?;
+            API_SERVICE_AUDIENCE,
+        );
+        let access_token = match &self.claims_transformer {
+            Some(transformer) => {
+                let enriched = transformer.enrich(base_claims, user.id).await
+                    .map_err(|e| {
+                        e.log_with_operation(location, "login");
+                        e
+                    })?;
+                self.token_manager.sign_claims(&enriched)?
+            }
+            None => self.token_manager.sign_claims(&base_claims)?,
+        };
         
         let refresh_token_string = self.token_manager.generate_refresh_token_with_context(
             &user,
@@ -141,14 +231,16 @@ impl LoginUseCase {
         let token_hash = format!("{:x}", hasher.finalize());
 
         // Store refresh token in database
+        let now = Utc::now();
         let refresh_token = shared::domain::repositories::refresh_token_repository::RefreshToken {
             id: Uuid::new_v4(),
             user_id: user.id,
             token_hash,
-            expires_at: Utc::now() + Duration::days(7),
-            created_at: Utc::now(),
+            expires_at: self.refresh_token_policy.initial_expiry(now),
+            created_at: now,
             revoked_at: None,
             is_revoked: false,
+            device_fingerprint: device_fingerprint.to_string(),
         };
         self.refresh_token_repository.create(refresh_token).await?;
 
@@ -180,3 +272,113 @@ impl LoginUseCase {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::lockout::LockoutPolicy;
+    use crate::test_support::{
+        CatalogPermissionRepository, HierarchyRoleRepository, InMemoryRefreshTokenRepository, StaticUserRepository,
+    };
+    use async_trait::async_trait;
+    use shared::domain::entities::User;
+    use shared::AppError;
+
+    const FINGERPRINT: &str = "test-device";
+
+    /// Every user is enrolled and "123456" is the only valid code
+    struct StaticMfaVerifier;
+
+    #[async_trait]
+    impl MfaVerifier for StaticMfaVerifier {
+        async fn is_enrolled(&self, _user: &User) -> AppResult<bool> { Ok(true) }
+        async fn verify(&self, _user: &User, token: &str) -> AppResult<bool> { Ok(token == "123456") }
+    }
+
+    fn login_use_case() -> LoginUseCase {
+        let password_hash = bcrypt::hash("password123", 4).unwrap();
+        let user = User::new("mfa@example.com".to_string(), "mfa".to_string(), password_hash);
+        LoginUseCase::new(
+            Box::new(StaticUserRepository { user }),
+            Box::new(InMemoryRefreshTokenRepository::default()),
+            Box::new(HierarchyRoleRepository::default()),
+            Box::new(CatalogPermissionRepository::default()),
+            TokenManager::new("test-secret", "health-v1".to_string(), 3600),
+        )
+        .with_mfa_verifier(Arc::new(StaticMfaVerifier))
+    }
+
+    fn request(mfa_token: Option<&str>) -> LoginRequest {
+        LoginRequest {
+            email: "mfa@example.com".to_string(),
+            password: "password123".to_string(),
+            mfa_token: mfa_token.map(String::from),
+        }
+    }
+
+    fn wrong_password() -> LoginRequest {
+        LoginRequest {
+            password: "wrong".to_string(),
+            ..request(Some("123456"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enrolled_user_needs_mfa_token() {
+        let use_case = login_use_case();
+
+        let err = use_case.execute(request(None), FINGERPRINT).await.unwrap_err();
+        assert!(matches!(err, AppError::MfaRequired(_)));
+
+        let response = use_case.execute(request(Some("123456")), FINGERPRINT).await.expect("login succeeds");
+        assert_eq!(response.user.email, "mfa@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_wrong_mfa_token_rejected() {
+        let err = login_use_case().execute(request(Some("000000")), FINGERPRINT).await.unwrap_err();
+        assert!(matches!(err, AppError::Authentication(_)));
+    }
+
+    #[tokio::test]
+    async fn test_sixth_failed_attempt_is_locked() {
+        let use_case = login_use_case();
+
+        for _ in 0..4 {
+            let err = use_case.execute(wrong_password(), FINGERPRINT).await.unwrap_err();
+            assert!(matches!(err, AppError::Authentication(_)));
+        }
+        let err = use_case.execute(wrong_password(), FINGERPRINT).await.unwrap_err();
+        assert!(matches!(err, AppError::TooManyAttempts { retry_after_seconds: 900 }));
+
+        // Even the right password is refused while locked
+        let err = use_case.execute(request(Some("123456")), FINGERPRINT).await.unwrap_err();
+        assert!(matches!(err, AppError::AccountLocked { remaining_minutes: 15 }));
+        assert_eq!(err.retry_after_seconds(), Some(900));
+    }
+
+    #[tokio::test]
+    async fn test_successful_login_resets_attempts() {
+        let use_case = login_use_case();
+
+        for _ in 0..3 {
+            assert!(use_case.execute(wrong_password(), FINGERPRINT).await.is_err());
+        }
+        use_case.execute(request(Some("123456")), FINGERPRINT).await.expect("4th attempt succeeds");
+
+        for _ in 0..4 {
+            let err = use_case.execute(wrong_password(), FINGERPRINT).await.unwrap_err();
+            assert!(matches!(err, AppError::Authentication(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wrong_mfa_token_counts_as_failure() {
+        let use_case = login_use_case()
+            .with_attempt_tracker(Arc::new(LoginAttemptTracker::in_memory(LockoutPolicy::new(2, 15))));
+
+        assert!(matches!(use_case.execute(request(Some("000000")), FINGERPRINT).await, Err(AppError::Authentication(_))));
+        assert!(matches!(use_case.execute(request(Some("000000")), FINGERPRINT).await, Err(AppError::TooManyAttempts { .. })));
+        assert!(matches!(use_case.execute(request(Some("123456")), FINGERPRINT).await, Err(AppError::AccountLocked { .. })));
+    }
+}
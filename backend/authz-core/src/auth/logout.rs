@@ -1,18 +1,61 @@
-use shared::domain::repositories::RefreshTokenRepository;
+use shared::domain::repositories::{AuditLogEntry, AuditLogRepository, RefreshTokenRepository};
 use shared::AppResult;
 use sha2::{Sha256, Digest};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Why all of a user's devices were logged out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogoutReason {
+    UserInitiated,
+    AdminForced,
+    PasswordChanged,
+}
+
+/// Audit record of a global logout
+#[derive(Debug, Clone, Serialize)]
+pub struct LogoutEvent {
+    pub user_id: Uuid,
+    pub reason: LogoutReason,
+    /// Refresh tokens revoked, one per logged-in device
+    pub device_count: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl LogoutEvent {
+    fn to_audit_entry(&self) -> AuditLogEntry {
+        AuditLogEntry {
+            user_id: Some(self.user_id),
+            action: "logout_all".to_string(),
+            resource: "user".to_string(),
+            resource_id: Some(self.user_id),
+            details: serde_json::to_value(self).unwrap_or_default(),
+        }
+    }
+}
 
 pub struct LogoutUseCase {
     refresh_token_repository: Box<dyn RefreshTokenRepository>,
+    audit_log: Option<Arc<dyn AuditLogRepository>>,
 }
 
 impl LogoutUseCase {
     pub fn new(refresh_token_repository: Box<dyn RefreshTokenRepository>) -> Self {
         Self {
             refresh_token_repository,
+            audit_log: None,
         }
     }
 
+    /// Record global logouts as `LogoutEvent`s in `audit_log`
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLogRepository>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
     pub async fn execute(&self, refresh_token: &str) -> AppResult<()> {
         // Hash the refresh token to look it up
         let mut hasher = Sha256::new();
@@ -24,5 +67,99 @@ impl LogoutUseCase {
 
         Ok(())
     }
+
+    /// Revoke every refresh token of the user, returning how many were revoked
+    pub async fn logout_all(&self, user_id: Uuid, reason: LogoutReason) -> AppResult<u64> {
+        let device_count = self.refresh_token_repository.revoke_all_for_user(user_id).await?;
+
+        let event = LogoutEvent {
+            user_id,
+            reason,
+            device_count,
+            timestamp: Utc::now(),
+        };
+        if let Some(audit_log) = &self.audit_log {
+            // The tokens are already revoked; a failed audit write must not undo the logout
+            if let Err(e) = audit_log.create(event.to_audit_entry()).await {
+                e.log_with_operation(concat!(file!(), ":", line!()), "logout_all_audit");
+            }
+        }
+
+        Ok(device_count)
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::InMemoryRefreshTokenRepository;
+    use async_trait::async_trait;
+    use chrono::Duration;
+    use shared::domain::repositories::refresh_token_repository::RefreshToken;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingAuditLog {
+        entries: Mutex<Vec<AuditLogEntry>>,
+    }
+
+    #[async_trait]
+    impl AuditLogRepository for RecordingAuditLog {
+        async fn create(&self, entry: AuditLogEntry) -> AppResult<()> {
+            self.entries.lock().unwrap().push(entry);
+            Ok(())
+        }
+    }
+
+    async fn issue(tokens: &InMemoryRefreshTokenRepository, user_id: Uuid) {
+        tokens.create(RefreshToken {
+            id: Uuid::new_v4(),
+            user_id,
+            token_hash: Uuid::new_v4().to_string(),
+            expires_at: Utc::now() + Duration::days(7),
+            created_at: Utc::now(),
+            revoked_at: None,
+            is_revoked: false,
+            device_fingerprint: "device".to_string(),
+        }).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_logout_all_revokes_every_token_of_user() {
+        let tokens = InMemoryRefreshTokenRepository::default();
+        let audit_log = Arc::new(RecordingAuditLog::default());
+        let use_case = LogoutUseCase::new(Box::new(tokens.clone())).with_audit_log(audit_log.clone());
+        let user_id = Uuid::new_v4();
+        let other_user_id = Uuid::new_v4();
+        for _ in 0..3 {
+            issue(&tokens, user_id).await;
+        }
+        issue(&tokens, other_user_id).await;
+
+        let revoked = use_case.logout_all(user_id, LogoutReason::AdminForced).await.unwrap();
+
+        assert_eq!(revoked, 3);
+        let user_tokens = tokens.find_by_user_id(user_id).await.unwrap();
+        assert_eq!(user_tokens.len(), 3);
+        assert!(user_tokens.iter().all(|t| t.is_revoked && t.revoked_at.is_some()));
+        assert!(tokens.find_by_user_id(other_user_id).await.unwrap().iter().all(|t| !t.is_revoked));
+
+        let entries = audit_log.entries.lock().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, "logout_all");
+        assert_eq!(entries[0].resource_id, Some(user_id));
+        assert_eq!(entries[0].details["reason"], "admin_forced");
+        assert_eq!(entries[0].details["device_count"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_logout_all_twice_revokes_nothing_new() {
+        let tokens = InMemoryRefreshTokenRepository::default();
+        let use_case = LogoutUseCase::new(Box::new(tokens.clone()));
+        let user_id = Uuid::new_v4();
+        issue(&tokens, user_id).await;
+
+        assert_eq!(use_case.logout_all(user_id, LogoutReason::UserInitiated).await.unwrap(), 1);
+        assert_eq!(use_case.logout_all(user_id, LogoutReason::UserInitiated).await.unwrap(), 0);
+    }
+}
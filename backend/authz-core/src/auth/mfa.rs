@@ -0,0 +1,32 @@
+//! Second-factor verification for login
+//!
+//! Users enrolled in TOTP must present a code alongside their password.
+//! The verifier is pluggable so the login use case stays independent of
+//! where TOTP keys live.
+
+use async_trait::async_trait;
+use shared::domain::entities::User;
+use shared::infrastructure::encryption::RustyVaultClient;
+use shared::AppResult;
+
+/// Checks enrolment and codes for a user's second factor
+#[async_trait]
+pub trait MfaVerifier: Send + Sync {
+    /// Whether the user has a second factor registered
+    async fn is_enrolled(&self, user: &User) -> AppResult<bool>;
+
+    /// Verify a submitted code; a code must not be accepted twice
+    async fn verify(&self, user: &User, token: &str) -> AppResult<bool>;
+}
+
+/// TOTP keys in RustyVault, named by user ID
+#[async_trait]
+impl MfaVerifier for RustyVaultClient {
+    async fn is_enrolled(&self, user: &User) -> AppResult<bool> {
+        self.totp_key_exists(&user.id.to_string()).await
+    }
+
+    async fn verify(&self, user: &User, token: &str) -> AppResult<bool> {
+        self.validate_totp(&user.id.to_string(), token).await
+    }
+}
@@ -1,9 +1,13 @@
+pub mod lockout;
 pub mod login;
 pub mod logout;
+pub mod mfa;
 pub mod refresh_token;
 pub mod userinfo;
 
+pub use lockout::{LockoutPolicy, LoginAttemptStore, LoginAttemptTracker, RedisLoginAttemptStore};
 pub use login::LoginUseCase;
-pub use logout::LogoutUseCase;
-pub use refresh_token::RefreshTokenUseCase;
+pub use logout::{LogoutEvent, LogoutReason, LogoutUseCase};
+pub use mfa::MfaVerifier;
+pub use refresh_token::{RefreshTokenPolicy, RefreshTokenUseCase};
 pub use userinfo::UserInfoUseCase;
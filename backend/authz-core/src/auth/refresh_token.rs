@@ -1,15 +1,46 @@
 use crate::dto::{RefreshTokenRequest, RefreshTokenResponse};
 use shared::domain::repositories::{UserRepository, RefreshTokenRepository};
 use crate::oidc::TokenManager;
-use shared::AppResult;
+use shared::{AppResult, RequestContext};
 use uuid::Uuid;
-use chrono::{Utc, Duration};
+use chrono::{DateTime, Utc, Duration};
 use sha2::{Sha256, Digest};
 
+/// How long refresh tokens stay valid
+///
+/// Each use slides the expiry to `extend_by` from now, but a token never
+/// outlives `max_lifetime` from the login that issued it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshTokenPolicy {
+    pub extend_by: Duration,
+    pub max_lifetime: Duration,
+}
+
+impl RefreshTokenPolicy {
+    pub fn new(extend_days: u32, max_days: u32) -> Self {
+        Self {
+            extend_by: Duration::days(i64::from(extend_days)),
+            max_lifetime: Duration::days(i64::from(max_days)),
+        }
+    }
+
+    /// Expiry of a token issued at `issued_at`
+    pub fn initial_expiry(&self, issued_at: DateTime<Utc>) -> DateTime<Utc> {
+        issued_at + self.extend_by.min(self.max_lifetime)
+    }
+}
+
+impl Default for RefreshTokenPolicy {
+    fn default() -> Self {
+        Self::new(7, 30)
+    }
+}
+
 pub struct RefreshTokenUseCase {
     user_repository: Box<dyn UserRepository>,
     refresh_token_repository: Box<dyn RefreshTokenRepository>,
     token_manager: TokenManager,
+    policy: RefreshTokenPolicy,
 }
 
 impl RefreshTokenUseCase {
@@ -22,10 +53,18 @@ impl RefreshTokenUseCase {
             user_repository,
             refresh_token_repository,
             token_manager,
+            policy: RefreshTokenPolicy::default(),
         }
     }
 
-    pub async fn execute(&self, request: RefreshTokenRequest) -> AppResult<RefreshTokenResponse> {
+    pub fn with_policy(mut self, policy: RefreshTokenPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Issue a new access token, extending the refresh token's expiry.
+    /// `context` must carry the device fingerprint of the calling client.
+    pub async fn execute(&self, request: RefreshTokenRequest, context: &RequestContext) -> AppResult<RefreshTokenResponse> {
         // Hash the refresh token to look it up in database
         let mut hasher = Sha256::new();
         hasher.update(request.refresh_token.as_bytes());
@@ -47,6 +86,13 @@ impl RefreshTokenUseCase {
             return Err(shared::AppError::Authentication("Token mismatch".to_string()));
         }
 
+        // Only the client that logged in may refresh
+        if context.device_fingerprint.as_deref() != Some(refresh_token.device_fingerprint.as_str()) {
+            return Err(shared::AppError::UnauthorizedDevice(
+                "Refresh token was issued to a different device".to_string(),
+            ));
+        }
+
         // Get user
         let user = self.user_repository
             .find_by_id(user_id)
@@ -58,35 +104,114 @@ impl RefreshTokenUseCase {
             return Err(shared::AppError::Authentication("User account is inactive".to_string()));
         }
 
-        // Revoke old refresh token
-        self.refresh_token_repository.revoke_token(&token_hash).await?;
+        // Slide the expiry, capped at the absolute lifetime from login
+        let not_after = refresh_token.created_at + self.policy.max_lifetime;
+        self.refresh_token_repository
+            .use_token(&token_hash, self.policy.extend_by, not_after)
+            .await?
+            .filter(|token| token.expires_at > Utc::now())
+            .ok_or_else(|| shared::AppError::Authentication("Refresh token expired".to_string()))?;
 
-        // Generate new tokens
         let access_token = self.token_manager.generate_access_token(&user)?;
-        let new_refresh_token_string = self.token_manager.generate_refresh_token(&user)?;
 
-        // Hash new refresh token
-        let mut hasher = Sha256::new();
-        hasher.update(new_refresh_token_string.as_bytes());
-        let new_token_hash = format!("{:x}", hasher.finalize());
+        Ok(RefreshTokenResponse {
+            access_token,
+            refresh_token: request.refresh_token,
+            expires_in: 3600,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{InMemoryRefreshTokenRepository, StaticUserRepository};
+    use shared::domain::entities::User;
+    use shared::domain::repositories::refresh_token_repository::RefreshToken;
+    use shared::AppError;
 
-        // Store new refresh token
-        let new_refresh_token = shared::domain::repositories::refresh_token_repository::RefreshToken {
+    const FINGERPRINT: &str = "laptop-fingerprint";
+
+    struct Fixture {
+        use_case: RefreshTokenUseCase,
+        tokens: InMemoryRefreshTokenRepository,
+        user: User,
+        refresh_token: String,
+        token_hash: String,
+    }
+
+    /// A refresh token issued `age` ago that currently expires in an hour
+    async fn issued(age: Duration) -> Fixture {
+        let user = User::new("nurse@example.com".to_string(), "nurse".to_string(), "hash".to_string());
+        let token_manager = TokenManager::new("test-secret", "health-v1".to_string(), 3600)
+            .with_refresh_token_days(30);
+        let refresh_token = token_manager.generate_refresh_token(&user).unwrap();
+        let token_hash = format!("{:x}", Sha256::digest(refresh_token.as_bytes()));
+
+        let tokens = InMemoryRefreshTokenRepository::default();
+        tokens.create(RefreshToken {
             id: Uuid::new_v4(),
             user_id: user.id,
-            token_hash: new_token_hash,
-            expires_at: Utc::now() + Duration::days(7),
-            created_at: Utc::now(),
+            token_hash: token_hash.clone(),
+            expires_at: Utc::now() + Duration::hours(1),
+            created_at: Utc::now() - age,
             revoked_at: None,
             is_revoked: false,
-        };
-        self.refresh_token_repository.create(new_refresh_token).await?;
+            device_fingerprint: FINGERPRINT.to_string(),
+        }).await.unwrap();
 
-        Ok(RefreshTokenResponse {
-            access_token,
-            refresh_token: new_refresh_token_string,
-            expires_in: 3600,
-        })
+        let use_case = RefreshTokenUseCase::new(
+            Box::new(StaticUserRepository { user: user.clone() }),
+            Box::new(tokens.clone()),
+            token_manager,
+        ).with_policy(RefreshTokenPolicy::new(7, 30));
+        Fixture { use_case, tokens, user, refresh_token, token_hash }
+    }
+
+    impl Fixture {
+        async fn refresh(&self, fingerprint: &str) -> AppResult<RefreshTokenResponse> {
+            let context = RequestContext::new("req-1".to_string(), self.user.id, self.user.email.clone(), None, vec![])
+                .with_device_fingerprint(fingerprint.to_string());
+            let request = RefreshTokenRequest { refresh_token: self.refresh_token.clone() };
+            self.use_case.execute(request, &context).await
+        }
     }
-}
 
+    #[tokio::test]
+    async fn test_use_slides_expiry() {
+        let fixture = issued(Duration::days(1)).await;
+
+        let response = fixture.refresh(FINGERPRINT).await.expect("refresh succeeds");
+        assert_eq!(response.refresh_token, fixture.refresh_token);
+
+        let expires_in = fixture.tokens.token(&fixture.token_hash).expires_at - Utc::now();
+        assert!(expires_in > Duration::days(7) - Duration::minutes(1));
+        assert!(expires_in <= Duration::days(7));
+    }
+
+    #[tokio::test]
+    async fn test_other_device_rejected() {
+        let fixture = issued(Duration::days(1)).await;
+        let expires_at = fixture.tokens.token(&fixture.token_hash).expires_at;
+
+        let err = fixture.refresh("phone-fingerprint").await.unwrap_err();
+        assert!(matches!(err, AppError::UnauthorizedDevice(_)));
+        assert_eq!(fixture.tokens.token(&fixture.token_hash).expires_at, expires_at);
+    }
+
+    #[tokio::test]
+    async fn test_max_lifetime_bounds_continuous_use() {
+        let fixture = issued(Duration::days(29)).await;
+        let not_after = fixture.tokens.token(&fixture.token_hash).created_at + Duration::days(30);
+
+        // Used daily, the token still stops at 30 days from login
+        for _ in 0..3 {
+            fixture.refresh(FINGERPRINT).await.expect("refresh succeeds");
+            assert_eq!(fixture.tokens.token(&fixture.token_hash).expires_at, not_after);
+        }
+
+        let expired = issued(Duration::days(31)).await;
+        let err = expired.refresh(FINGERPRINT).await.unwrap_err();
+        assert!(matches!(err, AppError::Authentication(_)));
+    }
+}
@@ -1,4 +1,6 @@
+use shared::domain::entities::Permission;
 use shared::domain::repositories::{UserRepository, RoleRepository, PermissionRepository};
+use shared::domain::services::PermissionExpander;
 use shared::AppResult;
 use uuid::Uuid;
 
@@ -48,23 +50,96 @@ impl GetUserPermissionsUseCase {
             .map(|r| r.name.clone())
             .unwrap_or_else(|| "".to_string());
 
-        // Collect all unique permission IDs from all roles
-        let mut permission_ids = std::collections::HashSet::new();
+        // Collect effective permissions (direct + inherited) across all roles,
+        // deduplicating where roles share ancestors
+        let mut permissions = std::collections::BTreeMap::new();
         for role in &user_roles {
-            for permission_id in &role.permissions {
-                permission_ids.insert(*permission_id);
+            for permission in self.role_repository.get_effective_permissions(role.id).await? {
+                permissions.entry(permission.id).or_insert(permission);
             }
         }
+        let mut raw: Vec<Permission> = permissions.into_values().collect();
 
-        // Get permission names
-        let mut permission_names = Vec::new();
-        for permission_id in permission_ids {
-            if let Some(permission) = self.permission_repository.find_by_id(permission_id).await? {
-                permission_names.push(permission.name);
-            }
+        // Wildcards such as `ehr:*` grant every matching permission in the catalog
+        if raw.iter().any(|p| PermissionExpander::is_wildcard(&p.name)) {
+            let all_permissions = self.permission_repository.list().await?;
+            raw = PermissionExpander::expand(&raw, &all_permissions);
         }
 
+        let mut permission_names: Vec<String> = raw.into_iter().map(|p| p.name).collect();
+        permission_names.sort();
+
         Ok((primary_role, permission_names))
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{CatalogPermissionRepository, HierarchyRoleRepository, StaticUserRepository};
+    use shared::domain::entities::{Role, User};
+    use std::collections::HashMap;
+
+    fn permission(resource: &str, action: &str) -> Permission {
+        Permission::new(format!("{}:{}", action, resource), resource.to_string(), action.to_string(), None)
+    }
+
+    #[tokio::test]
+    async fn test_leaf_role_inherits_all_ancestor_permissions() {
+        let staff = Role::new("staff".to_string(), None);
+        let clinician = Role::new("clinician".to_string(), None);
+        let doctor = Role::new("doctor".to_string(), None);
+
+        let direct = HashMap::from([
+            (staff.id, vec![permission("schedule", "read")]),
+            (clinician.id, vec![permission("patient", "read")]),
+            (doctor.id, vec![permission("prescription", "write")]),
+        ]);
+        let parents = HashMap::from([(doctor.id, clinician.id), (clinician.id, staff.id)]);
+
+        let user = User::new("doc@example.com".to_string(), "doc".to_string(), "hash".to_string());
+        let role_repository = HierarchyRoleRepository {
+            // Holding both doctor and clinician must not duplicate shared ancestor permissions
+            user_roles: vec![doctor.id, clinician.id],
+            roles: HashMap::from([(staff.id, staff), (clinician.id, clinician), (doctor.id, doctor)]),
+            parents,
+            direct,
+        };
+
+        let use_case = GetUserPermissionsUseCase::new(
+            Box::new(StaticUserRepository { user: user.clone() }),
+            Box::new(role_repository),
+            Box::new(CatalogPermissionRepository::default()),
+        );
+
+        let (_, permissions) = use_case.execute(user.id).await.expect("permissions resolve");
+        assert_eq!(
+            permissions,
+            vec!["read:patient".to_string(), "read:schedule".to_string(), "write:prescription".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn wildcard_role_permissions_are_expanded() {
+        let named = |name: &str| Permission::new(name.to_string(), String::new(), String::new(), None);
+        let catalog = vec![named("ehr:*"), named("ehr:patients:view"), named("ehr:notes:create"), named("lab:results:view")];
+        let nurse = Role::new("nurse".to_string(), None);
+        let role_repository = HierarchyRoleRepository {
+            user_roles: vec![nurse.id],
+            direct: HashMap::from([(nurse.id, vec![catalog[0].clone(), catalog[1].clone()])]),
+            roles: HashMap::from([(nurse.id, nurse)]),
+            parents: HashMap::new(),
+        };
+        let user = User::new("nurse@example.com".to_string(), "nurse".to_string(), "hash".to_string());
+
+        let use_case = GetUserPermissionsUseCase::new(
+            Box::new(StaticUserRepository { user: user.clone() }),
+            Box::new(role_repository),
+            Box::new(CatalogPermissionRepository { permissions: catalog }),
+        );
+
+        let (_, permissions) = use_case.execute(user.id).await.expect("permissions resolve");
+        assert_eq!(permissions, vec!["ehr:notes:create".to_string(), "ehr:patients:view".to_string()]);
+    }
+}
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
+    /// TOTP code, required when the user is enrolled in MFA
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mfa_token: Option<String>,
 }
 
 // Frontend-compatible camelCase response
@@ -4,7 +4,10 @@ pub mod oidc;
 pub mod zanzibar;
 pub mod dto;
 
+#[cfg(test)]
+mod test_support;
+
 pub use auth::{LoginUseCase, LogoutUseCase, RefreshTokenUseCase, UserInfoUseCase};
 pub use authorization::GetUserPermissionsUseCase;
-pub use oidc::{TokenManager, Jwks, OidcProvider, Claims};
+pub use oidc::{TokenManager, Jwks, OidcProvider, Claims, ClaimsTransformer, EnrichedClaims};
 
@@ -0,0 +1,279 @@
+//! Claims transformation for JWT enrichment
+//!
+//! Downstream services need more than the basic `sub`/`exp` claims. The
+//! `ClaimsTransformer` resolves a user's roles and permissions and merges in
+//! per-realm custom claims before the token is signed.
+//!
+//! Login only knows the user's organization, so the transformer looks up the
+//! organization's realm with a [`RealmResolver`] when one is configured.
+
+use async_trait::async_trait;
+use shared::domain::repositories::{PermissionRepository, RoleRepository};
+use shared::infrastructure::encryption::RustyVaultClient;
+use shared::infrastructure::oidc::Claims;
+use shared::AppResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Registered and core claims that realm overrides are never allowed to replace
+const RESERVED_CLAIMS: &[&str] = &[
+    "sub", "email", "exp", "iat", "iss", "aud", "role", "roles", "permissions",
+    "organization_id", "realm_id",
+];
+
+/// Claims payload after enrichment, ready to be signed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnrichedClaims {
+    #[serde(flatten)]
+    pub base: Claims,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Realm-specific custom claims (e.g. `facility_code`)
+    #[serde(flatten)]
+    pub custom: HashMap<String, Value>,
+}
+
+/// Finds the realm an organization's users belong to
+#[async_trait]
+pub trait RealmResolver: Send + Sync {
+    async fn realm_for_organization(&self, organization_id: Uuid) -> AppResult<Uuid>;
+}
+
+/// Realms in RustyVault, one per organization
+#[async_trait]
+impl RealmResolver for RustyVaultClient {
+    async fn realm_for_organization(&self, organization_id: Uuid) -> AppResult<Uuid> {
+        self.get_or_create_realm_for_org(organization_id).await
+    }
+}
+
+pub struct ClaimsTransformer {
+    role_repository: Arc<dyn RoleRepository>,
+    permission_repository: Arc<dyn PermissionRepository>,
+    /// Custom claims keyed by realm ID (falls back to organization ID)
+    claim_overrides: HashMap<String, HashMap<String, Value>>,
+    /// Fills in `realm_id` for claims that only carry an organization
+    realm_resolver: Option<Arc<dyn RealmResolver>>,
+}
+
+impl ClaimsTransformer {
+    pub fn new(
+        role_repository: Arc<dyn RoleRepository>,
+        permission_repository: Arc<dyn PermissionRepository>,
+    ) -> Self {
+        Self {
+            role_repository,
+            permission_repository,
+            claim_overrides: HashMap::new(),
+            realm_resolver: None,
+        }
+    }
+
+    /// Resolve the realm of claims issued without one, such as at login
+    pub fn with_realm_resolver(mut self, realm_resolver: Arc<dyn RealmResolver>) -> Self {
+        self.realm_resolver = Some(realm_resolver);
+        self
+    }
+
+    /// Replace the per-realm custom claim configuration
+    pub fn with_claim_overrides(mut self, claim_overrides: HashMap<String, HashMap<String, Value>>) -> Self {
+        self.claim_overrides = claim_overrides;
+        self
+    }
+
+    /// Set custom claims injected for every user in the given realm
+    pub fn set_realm_overrides(&mut self, realm_id: impl Into<String>, claims: HashMap<String, Value>) {
+        self.claim_overrides.insert(realm_id.into(), claims);
+    }
+
+    /// Enrich base claims with the user's roles, permissions and realm custom claims
+    pub async fn enrich(&self, mut base_claims: Claims, user_id: Uuid) -> AppResult<EnrichedClaims> {
+        let user_roles = self.role_repository.get_user_roles(user_id).await?;
+
+        let roles: Vec<String> = user_roles
+            .iter()
+            .map(|r| r.name.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        if base_claims.role.is_none() {
+            base_claims.role = user_roles
+                .iter()
+                .find(|r| r.name == "admin")
+                .or_else(|| user_roles.first())
+                .map(|r| r.name.clone());
+        }
+
+        // Merge role permissions with any permissions already present (e.g. super user)
+        let mut permissions: BTreeSet<String> = base_claims
+            .permissions
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+
+        let permission_ids: Vec<Uuid> = user_roles
+            .iter()
+            .flat_map(|r| r.permissions.iter().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if !permission_ids.is_empty() {
+            permissions.extend(
+                self.permission_repository
+                    .find_by_ids(&permission_ids)
+                    .await?
+                    .into_iter()
+                    .map(|p| p.name),
+            );
+        }
+
+        base_claims.permissions = if permissions.is_empty() {
+            None
+        } else {
+            Some(permissions.into_iter().collect())
+        };
+
+        if base_claims.realm_id.is_none() {
+            base_claims.realm_id = self.resolve_realm(&base_claims).await;
+        }
+        let custom = self.realm_claims(&base_claims);
+
+        Ok(EnrichedClaims {
+            base: base_claims,
+            roles,
+            custom,
+        })
+    }
+
+    /// Realm of the claims' organization; lookup failures leave the realm
+    /// unset rather than failing the login
+    async fn resolve_realm(&self, claims: &Claims) -> Option<String> {
+        let resolver = self.realm_resolver.as_ref()?;
+        let organization_id = claims.organization_id.as_deref().and_then(|id| Uuid::parse_str(id).ok())?;
+        match resolver.realm_for_organization(organization_id).await {
+            Ok(realm_id) => Some(realm_id.to_string()),
+            Err(e) => {
+                e.log_with_operation(concat!(file!(), ":", line!()), "resolve_realm");
+                None
+            }
+        }
+    }
+
+    /// Resolve custom claims for the claims' realm, skipping reserved claim names
+    fn realm_claims(&self, claims: &Claims) -> HashMap<String, Value> {
+        let realm_key = claims.realm_id.as_ref().or(claims.organization_id.as_ref());
+
+        realm_key
+            .and_then(|key| self.claim_overrides.get(key))
+            .map(|overrides| {
+                overrides
+                    .iter()
+                    .filter(|(name, _)| !RESERVED_CLAIMS.contains(&name.as_str()))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{CatalogPermissionRepository, HierarchyRoleRepository};
+    use shared::domain::entities::{Permission, Role, User};
+    use shared::AppError;
+
+    struct StaticRealmResolver {
+        realm_id: Option<Uuid>,
+    }
+
+    #[async_trait]
+    impl RealmResolver for StaticRealmResolver {
+        async fn realm_for_organization(&self, _organization_id: Uuid) -> AppResult<Uuid> {
+            self.realm_id.ok_or_else(|| AppError::Encryption("vault unavailable".to_string()))
+        }
+    }
+
+    fn claims(organization_id: Uuid) -> Claims {
+        let user = User::new("nurse@example.com".to_string(), "nurse".to_string(), "hash".to_string());
+        shared::infrastructure::oidc::TokenManager::new("test-secret", "health-v1".to_string(), 3600)
+            .build_access_claims(&user, "", &[], Some(organization_id.to_string()), None)
+    }
+
+    fn transformer(permissions: Vec<Permission>, role: Role) -> ClaimsTransformer {
+        let roles = HierarchyRoleRepository {
+            user_roles: vec![role.id],
+            roles: HashMap::from([(role.id, role)]),
+            ..HierarchyRoleRepository::default()
+        };
+        ClaimsTransformer::new(Arc::new(roles), Arc::new(CatalogPermissionRepository { permissions }))
+    }
+
+    #[tokio::test]
+    async fn test_roles_and_permissions_are_merged() {
+        let read = Permission::new("read:patient".to_string(), "patient".to_string(), "read".to_string(), None);
+        let write = Permission::new("write:patient".to_string(), "patient".to_string(), "write".to_string(), None);
+        let mut nurse = Role::new("nurse".to_string(), None);
+        nurse.permissions = vec![read.id, write.id, Uuid::new_v4()];
+
+        let enriched = transformer(vec![read, write], nurse)
+            .enrich(claims(Uuid::new_v4()), Uuid::new_v4())
+            .await
+            .unwrap();
+
+        assert_eq!(enriched.roles, vec!["nurse".to_string()]);
+        assert_eq!(enriched.base.role.as_deref(), Some("nurse"));
+        assert_eq!(
+            enriched.base.permissions,
+            Some(vec!["read:patient".to_string(), "write:patient".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_realm_overrides_apply_at_login() {
+        let organization_id = Uuid::new_v4();
+        let realm_id = Uuid::new_v4();
+        let overrides = HashMap::from([(
+            realm_id.to_string(),
+            HashMap::from([
+                ("facility_code".to_string(), serde_json::json!("ICU-2")),
+                ("sub".to_string(), serde_json::json!("spoofed")),
+            ]),
+        )]);
+
+        let enriched = transformer(Vec::new(), Role::new("nurse".to_string(), None))
+            .with_claim_overrides(overrides)
+            .with_realm_resolver(Arc::new(StaticRealmResolver { realm_id: Some(realm_id) }))
+            .enrich(claims(organization_id), Uuid::new_v4())
+            .await
+            .unwrap();
+
+        assert_eq!(enriched.base.realm_id, Some(realm_id.to_string()));
+        assert_eq!(enriched.custom.get("facility_code"), Some(&serde_json::json!("ICU-2")));
+        assert!(!enriched.custom.contains_key("sub"));
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_realm_falls_back_to_organization() {
+        let organization_id = Uuid::new_v4();
+        let overrides = HashMap::from([(
+            organization_id.to_string(),
+            HashMap::from([("facility_code".to_string(), serde_json::json!("OPD"))]),
+        )]);
+
+        let enriched = transformer(Vec::new(), Role::new("nurse".to_string(), None))
+            .with_claim_overrides(overrides)
+            .with_realm_resolver(Arc::new(StaticRealmResolver { realm_id: None }))
+            .enrich(claims(organization_id), Uuid::new_v4())
+            .await
+            .unwrap();
+
+        assert_eq!(enriched.base.realm_id, None);
+        assert_eq!(enriched.custom.get("facility_code"), Some(&serde_json::json!("OPD")));
+    }
+}
@@ -1,4 +1,6 @@
 pub mod token;
+pub mod claims_transformer;
 
 pub use token::TokenManager;
-pub use shared::infrastructure::oidc::{OidcProvider, Jwks, Claims};
+pub use claims_transformer::{ClaimsTransformer, EnrichedClaims, RealmResolver};
+pub use shared::infrastructure::oidc::{OidcProvider, Jwks, Claims, API_SERVICE_AUDIENCE, RUSTYVAULT_AUDIENCE};
@@ -0,0 +1,147 @@
+//! In-memory repositories shared by the use case tests
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use shared::domain::entities::{Permission, Role, User};
+use shared::domain::repositories::refresh_token_repository::RefreshToken;
+use shared::domain::repositories::{PermissionRepository, RefreshTokenRepository, RoleRepository, UserRepository};
+use shared::AppResult;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Holds a single user, found by ID or email
+pub struct StaticUserRepository {
+    pub user: User,
+}
+
+#[async_trait]
+impl UserRepository for StaticUserRepository {
+    async fn create(&self, user: User) -> AppResult<User> { Ok(user) }
+    async fn find_by_id(&self, id: Uuid) -> AppResult<Option<User>> {
+        Ok((id == self.user.id).then(|| self.user.clone()))
+    }
+    async fn find_by_email(&self, email: &str) -> AppResult<Option<User>> {
+        Ok((email == self.user.email).then(|| self.user.clone()))
+    }
+    async fn find_by_username(&self, _username: &str) -> AppResult<Option<User>> { Ok(None) }
+    async fn update(&self, user: User) -> AppResult<User> { Ok(user) }
+    async fn delete(&self, _id: Uuid) -> AppResult<()> { Ok(()) }
+    async fn list(&self, _limit: u32, _offset: u32) -> AppResult<Vec<User>> { Ok(Vec::new()) }
+}
+
+/// Mirrors the SQL of `RefreshTokenRepositoryImpl`
+#[derive(Clone, Default)]
+pub struct InMemoryRefreshTokenRepository {
+    pub tokens: Arc<Mutex<Vec<RefreshToken>>>,
+}
+
+impl InMemoryRefreshTokenRepository {
+    pub fn token(&self, token_hash: &str) -> RefreshToken {
+        let tokens = self.tokens.lock().unwrap();
+        tokens.iter().find(|t| t.token_hash == token_hash).cloned().unwrap()
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for InMemoryRefreshTokenRepository {
+    async fn create(&self, token: RefreshToken) -> AppResult<RefreshToken> {
+        self.tokens.lock().unwrap().push(token.clone());
+        Ok(token)
+    }
+    async fn find_by_token_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
+        let tokens = self.tokens.lock().unwrap();
+        Ok(tokens.iter()
+            .find(|t| t.token_hash == token_hash && !t.is_revoked && t.expires_at > Utc::now())
+            .cloned())
+    }
+    async fn find_by_user_id(&self, user_id: Uuid) -> AppResult<Vec<RefreshToken>> {
+        let tokens = self.tokens.lock().unwrap();
+        Ok(tokens.iter().filter(|t| t.user_id == user_id).cloned().collect())
+    }
+    async fn use_token(&self, token_hash: &str, extend_by: Duration, not_after: DateTime<Utc>) -> AppResult<Option<RefreshToken>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        let now = Utc::now();
+        Ok(tokens.iter_mut()
+            .find(|t| t.token_hash == token_hash && !t.is_revoked && t.expires_at > now)
+            .map(|t| {
+                t.expires_at = (now + extend_by).min(not_after);
+                t.clone()
+            }))
+    }
+    async fn revoke_token(&self, _token_hash: &str) -> AppResult<()> { Ok(()) }
+    async fn revoke_all_user_tokens(&self, user_id: Uuid) -> AppResult<()> {
+        self.revoke_all_for_user(user_id).await.map(|_| ())
+    }
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> AppResult<u64> {
+        let mut tokens = self.tokens.lock().unwrap();
+        let mut revoked = 0;
+        for token in tokens.iter_mut().filter(|t| t.user_id == user_id && !t.is_revoked) {
+            token.is_revoked = true;
+            token.revoked_at = Some(Utc::now());
+            revoked += 1;
+        }
+        Ok(revoked)
+    }
+    async fn delete_expired_tokens(&self) -> AppResult<u64> { Ok(0) }
+}
+
+/// Role hierarchy held in memory: each role has direct permissions and an optional parent
+#[derive(Default)]
+pub struct HierarchyRoleRepository {
+    pub roles: HashMap<Uuid, Role>,
+    pub parents: HashMap<Uuid, Uuid>,
+    pub direct: HashMap<Uuid, Vec<Permission>>,
+    pub user_roles: Vec<Uuid>,
+}
+
+#[async_trait]
+impl RoleRepository for HierarchyRoleRepository {
+    async fn create(&self, role: Role) -> AppResult<Role> { Ok(role) }
+    async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Role>> { Ok(self.roles.get(&id).cloned()) }
+    async fn find_by_name(&self, name: &str) -> AppResult<Option<Role>> {
+        Ok(self.roles.values().find(|r| r.name == name).cloned())
+    }
+    async fn list(&self) -> AppResult<Vec<Role>> { Ok(self.roles.values().cloned().collect()) }
+    async fn add_permission_to_role(&self, _role_id: Uuid, _permission_id: Uuid) -> AppResult<()> { Ok(()) }
+    async fn remove_permission_from_role(&self, _role_id: Uuid, _permission_id: Uuid) -> AppResult<()> { Ok(()) }
+    async fn get_role_permissions(&self, role_id: Uuid) -> AppResult<Vec<Uuid>> {
+        Ok(self.direct.get(&role_id).map(|p| p.iter().map(|p| p.id).collect()).unwrap_or_default())
+    }
+    async fn get_user_roles(&self, _user_id: Uuid) -> AppResult<Vec<Role>> {
+        Ok(self.user_roles.iter().filter_map(|id| self.roles.get(id).cloned()).collect())
+    }
+    async fn set_parent_role(&self, _role_id: Uuid, _parent_role_id: Option<Uuid>) -> AppResult<()> { Ok(()) }
+    async fn get_effective_permissions(&self, role_id: Uuid) -> AppResult<Vec<Permission>> {
+        let mut permissions = Vec::new();
+        let mut current = Some(role_id);
+        while let Some(id) = current {
+            permissions.extend(self.direct.get(&id).cloned().unwrap_or_default());
+            current = self.parents.get(&id).copied();
+        }
+        Ok(permissions)
+    }
+}
+
+/// A fixed list of permissions; empty by default
+#[derive(Default)]
+pub struct CatalogPermissionRepository {
+    pub permissions: Vec<Permission>,
+}
+
+#[async_trait]
+impl PermissionRepository for CatalogPermissionRepository {
+    async fn create(&self, permission: Permission) -> AppResult<Permission> { Ok(permission) }
+    async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Permission>> {
+        Ok(self.permissions.iter().find(|p| p.id == id).cloned())
+    }
+    async fn find_by_ids(&self, ids: &[Uuid]) -> AppResult<Vec<Permission>> {
+        Ok(self.permissions.iter().filter(|p| ids.contains(&p.id)).cloned().collect())
+    }
+    async fn find_by_name(&self, name: &str) -> AppResult<Option<Permission>> {
+        Ok(self.permissions.iter().find(|p| p.name == name).cloned())
+    }
+    async fn find_by_resource_and_action(&self, _resource: &str, _action: &str) -> AppResult<Option<Permission>> { Ok(None) }
+    async fn list(&self) -> AppResult<Vec<Permission>> { Ok(self.permissions.clone()) }
+    async fn list_by_resource(&self, _resource: &str) -> AppResult<Vec<Permission>> { Ok(Vec::new()) }
+}
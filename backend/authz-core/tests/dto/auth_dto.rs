@@ -6,6 +6,7 @@ fn test_login_request_serialization() {
     let request = LoginRequest {
         email: "test@example.com".to_string(),
         password: "password123".to_string(),
+        mfa_token: None,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -20,6 +21,7 @@ fn test_login_request_deserialization() {
     
     assert_eq!(request.email, "test@example.com");
     assert_eq!(request.password, "password123");
+    assert!(request.mfa_token.is_none());
 }
 
 #[test]
@@ -1,4 +1,4 @@
-use authz_core::oidc::TokenManager;
+use authz_core::oidc::{OidcProvider, TokenManager, API_SERVICE_AUDIENCE, RUSTYVAULT_AUDIENCE};
 use shared::domain::entities::User;
 use uuid::Uuid;
 
@@ -124,3 +124,57 @@ fn test_token_manager_validate_invalid_token() {
     assert!(claims_result.is_err());
 }
 
+
+#[test]
+fn test_verify_token_for_audience_rejects_other_service() {
+    let manager = TokenManager::new(
+        "test-secret-key",
+        "test-issuer".to_string(),
+        3600,
+    );
+    
+    let user = User::new(
+        "test@example.com".to_string(),
+        "testuser".to_string(),
+        "hash".to_string(),
+    );
+    
+    let claims = manager.build_access_claims_for_audience(&user, "admin", &[], None, None, RUSTYVAULT_AUDIENCE);
+    let vault_token = manager.sign_claims(&claims).unwrap();
+    let api_token = manager.generate_access_token(&user).unwrap();
+    
+    // Each service accepts only tokens issued for it
+    assert!(manager.verify_token_for_audience(&vault_token, RUSTYVAULT_AUDIENCE).is_ok());
+    assert!(manager.verify_token_for_audience(&vault_token, API_SERVICE_AUDIENCE).is_err());
+    assert!(manager.verify_token_for_audience(&api_token, API_SERVICE_AUDIENCE).is_ok());
+    assert!(manager.verify_token_for_audience(&api_token, RUSTYVAULT_AUDIENCE).is_err());
+}
+
+#[test]
+fn test_oidc_provider_verify_token_for_audience() {
+    let manager = TokenManager::new(
+        "test-secret-key",
+        "test-issuer".to_string(),
+        3600,
+    );
+    let provider = OidcProvider::new(
+        "test-issuer".to_string(),
+        "client".to_string(),
+        "secret".to_string(),
+    );
+    
+    let user = User::new(
+        "test@example.com".to_string(),
+        "testuser".to_string(),
+        "hash".to_string(),
+    );
+    let token = manager.generate_access_token(&user).unwrap();
+    
+    // Verification requires a configured token manager
+    assert!(provider.verify_token_for_audience(&token, API_SERVICE_AUDIENCE).is_err());
+    
+    let provider = provider.with_token_manager(manager);
+    let claims = provider.verify_token_for_audience(&token, API_SERVICE_AUDIENCE).unwrap();
+    assert_eq!(claims.sub, user.id.to_string());
+    assert!(provider.verify_token_for_audience(&token, RUSTYVAULT_AUDIENCE).is_err());
+}
@@ -0,0 +1,9 @@
+-- Rollback: Remove role hierarchy
+
+DROP INDEX IF EXISTS idx_roles_role_parent_id;
+
+ALTER TABLE roles
+DROP CONSTRAINT IF EXISTS roles_parent_not_self;
+
+ALTER TABLE roles
+DROP COLUMN IF EXISTS role_parent_id;
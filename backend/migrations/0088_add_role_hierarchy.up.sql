@@ -0,0 +1,23 @@
+-- Migration: Add role hierarchy
+-- Description: Allows a role to extend a parent role (e.g. Doctor -> Clinician -> Staff)
+--              so permissions are inherited transitively
+-- Related Entity: src/domain/entities/role.rs (Role)
+--
+-- Schema Changes:
+--   - Adds: roles.role_parent_id (self-referencing FK)
+--
+-- Indexes Created:
+--   - idx_roles_role_parent_id (Partial B-tree, on role_parent_id WHERE role_parent_id IS NOT NULL)
+
+ALTER TABLE roles
+ADD COLUMN IF NOT EXISTS role_parent_id UUID REFERENCES roles(id) ON DELETE SET NULL;
+
+ALTER TABLE roles
+ADD CONSTRAINT roles_parent_not_self CHECK (role_parent_id IS NULL OR role_parent_id <> id);
+
+-- Hierarchy traversal (recursive CTE joins children to parents on this column)
+CREATE INDEX IF NOT EXISTS idx_roles_role_parent_id
+ON roles(role_parent_id)
+WHERE role_parent_id IS NOT NULL;
+
+COMMENT ON COLUMN roles.role_parent_id IS 'Parent role whose permissions this role inherits';
@@ -0,0 +1,6 @@
+-- Rollback: Drop background jobs table
+
+DROP INDEX IF EXISTS idx_jobs_job_type;
+DROP INDEX IF EXISTS idx_jobs_pending;
+
+DROP TABLE IF EXISTS jobs;
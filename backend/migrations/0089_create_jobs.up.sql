@@ -0,0 +1,35 @@
+-- Migration: Create background jobs table
+-- Description: Durable queue for long-running tasks (CCD generation, FHIR export,
+--              bulk import) processed asynchronously by JobWorker
+-- Related Entity: src/infrastructure/jobs/mod.rs (Job)
+--
+-- Tables Created:
+--   - jobs
+--
+-- Indexes Created:
+--   - idx_jobs_pending (Partial B-tree, on created_at WHERE status = 'pending')
+--   - idx_jobs_job_type (B-tree, on job_type)
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    job_type TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}',
+    status TEXT NOT NULL DEFAULT 'pending',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    error TEXT,
+    result JSONB,
+
+    CONSTRAINT jobs_status_check CHECK (status IN ('pending', 'running', 'completed', 'failed'))
+);
+
+-- Worker polling (SELECT ... FOR UPDATE SKIP LOCKED, oldest first)
+CREATE INDEX IF NOT EXISTS idx_jobs_pending
+ON jobs(created_at)
+WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_jobs_job_type ON jobs(job_type);
+
+COMMENT ON TABLE jobs IS 'Background job queue for long-running EHR tasks';
+COMMENT ON COLUMN jobs.result IS 'Handler output, set when status = completed';
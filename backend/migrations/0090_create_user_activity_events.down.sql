@@ -0,0 +1,6 @@
+-- Rollback: Drop user activity events table
+
+DROP INDEX IF EXISTS idx_user_activity_events_patient_id;
+DROP INDEX IF EXISTS idx_user_activity_events_user_id;
+
+DROP TABLE IF EXISTS user_activity_events;
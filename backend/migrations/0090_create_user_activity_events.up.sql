@@ -0,0 +1,37 @@
+-- Migration: Create user activity events table
+-- Description: HIPAA access log for EHR requests, recording which patient was
+--              accessed, by whom, and the reason given in HIPAA-Access-Reason
+-- Related Entity: src/domain/entities/user_activity_event.rs (UserActivityEvent)
+--
+-- Tables Created:
+--   - user_activity_events
+--
+-- Indexes Created:
+--   - idx_user_activity_events_user_id (B-tree, on user_id, created_at)
+--   - idx_user_activity_events_patient_id (B-tree, on patient_id, created_at WHERE patient_id IS NOT NULL)
+
+CREATE TABLE IF NOT EXISTS user_activity_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    session_id UUID,
+    request_id TEXT,
+    event_type TEXT NOT NULL,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    patient_id BIGINT,
+    access_reason TEXT,
+    status_code INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_activity_events_user_id
+ON user_activity_events(user_id, created_at DESC);
+
+-- "Who accessed this patient's record" queries
+CREATE INDEX IF NOT EXISTS idx_user_activity_events_patient_id
+ON user_activity_events(patient_id, created_at DESC)
+WHERE patient_id IS NOT NULL;
+
+COMMENT ON TABLE user_activity_events IS 'HIPAA access log of user activity on EHR records';
+COMMENT ON COLUMN user_activity_events.patient_id IS 'Patient IEN taken from the request path';
+COMMENT ON COLUMN user_activity_events.access_reason IS 'Value of the HIPAA-Access-Reason request header';
@@ -0,0 +1,5 @@
+-- Rollback: Drop user provisioning checklists table
+
+DROP INDEX IF EXISTS idx_user_provisioning_checklists_status;
+
+DROP TABLE IF EXISTS user_provisioning_checklists;
@@ -0,0 +1,26 @@
+-- Migration: Create user provisioning checklists table
+-- Description: Persists each user's provisioning checklist so individual steps
+--              can be retried and their progress queried after provisioning
+-- Related Entity: src/domain/entities/user_provisioning_checklist.rs (UserProvisioningChecklist)
+--
+-- Tables Created:
+--   - user_provisioning_checklists
+--
+-- Indexes Created:
+--   - idx_user_provisioning_checklists_status (B-tree, on overall_status)
+
+CREATE TABLE IF NOT EXISTS user_provisioning_checklists (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    checklist JSONB NOT NULL,
+    overall_status TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Find users whose provisioning is incomplete or failed
+CREATE INDEX IF NOT EXISTS idx_user_provisioning_checklists_status
+ON user_provisioning_checklists(overall_status);
+
+COMMENT ON TABLE user_provisioning_checklists IS 'Provisioning checklist (steps, statuses, dependencies) per user';
+COMMENT ON COLUMN user_provisioning_checklists.checklist IS 'Serialized UserProvisioningChecklist';
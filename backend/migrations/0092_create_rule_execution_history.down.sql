@@ -0,0 +1,5 @@
+-- Rollback: Drop rule execution history table
+
+DROP INDEX IF EXISTS idx_rule_execution_history_rule;
+
+DROP TABLE IF EXISTS rule_execution_history;
@@ -0,0 +1,29 @@
+-- Migration: Create rule execution history table
+-- Description: Records the context and outcome of each decision rule evaluation
+--              so rules can be backtested against real historical inputs
+-- Related Service: shared/src/application/services/rules_engine.rs (RulesEngine::backtest)
+--
+-- Tables Created:
+--   - rule_execution_history
+--
+-- Indexes Created:
+--   - idx_rule_execution_history_rule (B-tree, on rule_id, executed_at)
+
+CREATE TABLE IF NOT EXISTS rule_execution_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rule_id VARCHAR(255) NOT NULL,
+    rule_version INTEGER,
+    context_snapshot JSONB NOT NULL,
+    result JSONB NOT NULL,
+    matched BOOLEAN NOT NULL,
+    organization_id UUID REFERENCES organizations(id) ON DELETE CASCADE,
+    executed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Backtests replay the most recent executions of a single rule
+CREATE INDEX IF NOT EXISTS idx_rule_execution_history_rule
+ON rule_execution_history(rule_id, executed_at DESC);
+
+COMMENT ON TABLE rule_execution_history IS 'Decision rule evaluations, replayed by rule backtests';
+COMMENT ON COLUMN rule_execution_history.context_snapshot IS 'Serialized RuleContext the rule was evaluated against';
+COMMENT ON COLUMN rule_execution_history.result IS 'Serialized RuleResult recorded as the expected outcome';
@@ -0,0 +1,6 @@
+-- Rollback: Drop wrapped DEK storage
+
+DROP INDEX IF EXISTS idx_encryption_keys_active_unique;
+DROP INDEX IF EXISTS idx_encryption_keys_entity_composite;
+
+DROP TABLE IF EXISTS encryption_keys;
@@ -0,0 +1,44 @@
+-- Migration: Store wrapped DEKs in encryption_keys
+-- Description: Reintroduces database DEK storage. Key material is wrapped with
+--              the master key (AES-256 key wrap, RFC 3394) before it is written,
+--              so the table never holds plaintext DEKs. The 0004 table and its
+--              encrypted_key/nonce columns were dropped in 0017; key material
+--              now lives in key_material_wrapped alongside the master key version.
+-- Related Entity: src/domain/entities/encryption_key.rs (EncryptionKey)
+-- Related Repository: src/infrastructure/repositories/key_repository_impl.rs
+--
+-- Tables Created:
+--   - encryption_keys
+--
+-- Indexes Created:
+--   - idx_encryption_keys_entity_composite (B-tree composite, on entity_id, entity_type)
+--   - idx_encryption_keys_active_unique (Unique Partial, on entity_id, entity_type WHERE is_active = true)
+
+CREATE TABLE IF NOT EXISTS encryption_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    entity_id UUID NOT NULL,
+    entity_type VARCHAR(255) NOT NULL,
+    key_material_wrapped BYTEA NOT NULL,
+    key_version INTEGER NOT NULL DEFAULT 1,
+    key_algorithm VARCHAR(50) NOT NULL DEFAULT 'AES-256-KW',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rotated_at TIMESTAMPTZ,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    -- Audit fields
+    request_id VARCHAR(255),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_by UUID,
+    updated_by UUID,
+    system_id VARCHAR(255),
+    version BIGINT NOT NULL DEFAULT 1
+);
+
+CREATE INDEX IF NOT EXISTS idx_encryption_keys_entity_composite ON encryption_keys(entity_id, entity_type);
+
+-- One active DEK per entity
+CREATE UNIQUE INDEX IF NOT EXISTS idx_encryption_keys_active_unique
+    ON encryption_keys(entity_id, entity_type)
+    WHERE is_active = true;
+
+COMMENT ON COLUMN encryption_keys.key_material_wrapped IS 'DEK wrapped with the master key (RFC 3394); never plaintext';
+COMMENT ON COLUMN encryption_keys.key_version IS 'Version of the master key that wrapped key_material_wrapped';
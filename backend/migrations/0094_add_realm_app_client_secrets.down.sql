@@ -0,0 +1,10 @@
+-- Rollback: Remove realm application client secrets
+
+DROP INDEX IF EXISTS idx_vault_tokens_client_app_id;
+
+ALTER TABLE vault_tokens
+DROP COLUMN IF EXISTS client_app_id;
+
+ALTER TABLE vault_realm_applications
+DROP COLUMN IF EXISTS client_secret_rotated_at,
+DROP COLUMN IF EXISTS client_secret_hash;
@@ -0,0 +1,27 @@
+-- Migration: Add client secrets to realm applications
+-- Description: Stores the BLAKE3 hash of each application's OAuth2 client secret
+--              with its last rotation time, and links vault tokens to the client
+--              that obtained them so a rotation can revoke them
+-- Related Store: rustyvault-service/src/modules/realm/app_store.rs (RealmApplicationStore::rotate_client_secret)
+--
+-- Columns Added:
+--   - vault_realm_applications.client_secret_hash
+--   - vault_realm_applications.client_secret_rotated_at
+--   - vault_tokens.client_app_id
+--
+-- Indexes Created:
+--   - idx_vault_tokens_client_app_id (B-tree, on client_app_id)
+
+ALTER TABLE vault_realm_applications
+ADD COLUMN IF NOT EXISTS client_secret_hash VARCHAR(64),
+ADD COLUMN IF NOT EXISTS client_secret_rotated_at TIMESTAMPTZ;
+
+ALTER TABLE vault_tokens
+ADD COLUMN IF NOT EXISTS client_app_id UUID REFERENCES vault_realm_applications(id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_vault_tokens_client_app_id
+ON vault_tokens(client_app_id);
+
+COMMENT ON COLUMN vault_realm_applications.client_secret_hash IS 'BLAKE3 hash of the client secret; the plaintext is only shown once';
+COMMENT ON COLUMN vault_realm_applications.client_secret_rotated_at IS 'Last client secret rotation, used to limit rotations to one per 24 hours';
+COMMENT ON COLUMN vault_tokens.client_app_id IS 'Realm application whose client credentials obtained this token';
@@ -0,0 +1,4 @@
+-- Rollback: Remove refresh token device fingerprints
+
+ALTER TABLE refresh_tokens
+DROP COLUMN IF EXISTS device_fingerprint;
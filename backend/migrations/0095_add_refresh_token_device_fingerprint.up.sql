@@ -0,0 +1,14 @@
+-- Migration: Bind refresh tokens to the device that logged in
+-- Description: Stores the SHA-256 of the User-Agent and Accept-Language headers
+--              seen at login; a refresh from a client with a different fingerprint
+--              is rejected. Tokens issued before this migration have an empty
+--              fingerprint and stop refreshing, so those users log in again.
+-- Related Use Case: authz-core/src/auth/refresh_token.rs (RefreshTokenUseCase)
+--
+-- Columns Added:
+--   - refresh_tokens.device_fingerprint
+
+ALTER TABLE refresh_tokens
+ADD COLUMN IF NOT EXISTS device_fingerprint VARCHAR(64) NOT NULL DEFAULT '';
+
+COMMENT ON COLUMN refresh_tokens.device_fingerprint IS 'SHA-256 (hex) of User-Agent and Accept-Language at login';
@@ -0,0 +1,6 @@
+-- Rollback: Remove searchable encrypted user email
+
+DROP INDEX IF EXISTS idx_users_email_ciphertext;
+
+ALTER TABLE users
+DROP COLUMN IF EXISTS email_ciphertext;
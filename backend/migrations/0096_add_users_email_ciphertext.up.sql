@@ -0,0 +1,21 @@
+-- Migration: Searchable encrypted user email
+-- Description: Stores the AES-SIV (deterministic) encryption of the email so
+--              find_by_email can look users up by ciphertext. Rows written
+--              without field encryption keep a NULL ciphertext and are still
+--              matched on the plaintext email.
+-- Related Use Case: shared/src/infrastructure/repositories/user_repository_impl.rs
+--
+-- Columns Added:
+--   - users.email_ciphertext
+--
+-- Indexes Created:
+--   - idx_users_email_ciphertext (unique, partial on email_ciphertext IS NOT NULL)
+
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS email_ciphertext BYTEA;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_ciphertext
+ON users(email_ciphertext)
+WHERE email_ciphertext IS NOT NULL;
+
+COMMENT ON COLUMN users.email_ciphertext IS 'AES-SIV ciphertext of email with context users.email';
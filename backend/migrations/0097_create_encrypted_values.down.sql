@@ -0,0 +1,5 @@
+-- Rollback: Remove values encrypted with a stored DEK
+
+DROP INDEX IF EXISTS idx_encrypted_values_key_id;
+
+DROP TABLE IF EXISTS encrypted_values;
@@ -0,0 +1,25 @@
+-- Migration: Values encrypted with a stored DEK
+-- Description: Each row is encrypted (AES-256-GCM, nonce || ciphertext) with
+--              the DEK in encryption_keys it references. DekManager::rotate_dek
+--              re-encrypts the rows of a DEK under its replacement and repoints
+--              key_id in the same transaction.
+-- Related Service: shared/src/infrastructure/encryption/dek_manager.rs
+-- Related Repository: src/infrastructure/repositories/key_repository_impl.rs
+--
+-- Tables Created:
+--   - encrypted_values
+--
+-- Indexes Created:
+--   - idx_encrypted_values_key_id (B-tree, on key_id)
+
+CREATE TABLE IF NOT EXISTS encrypted_values (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    key_id UUID NOT NULL REFERENCES encryption_keys(id),
+    encrypted_value BYTEA NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_encrypted_values_key_id ON encrypted_values(key_id);
+
+COMMENT ON COLUMN encrypted_values.encrypted_value IS 'AES-256-GCM nonce (12 bytes) followed by ciphertext, under the DEK key_id';
@@ -0,0 +1,6 @@
+-- Rollback: Remove the clinical document full-text index
+
+DROP INDEX IF EXISTS idx_ehr_document_index_patient_ien;
+DROP INDEX IF EXISTS idx_ehr_document_index_content_tsv;
+
+DROP TABLE IF EXISTS ehr_document_index;
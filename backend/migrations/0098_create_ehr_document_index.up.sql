@@ -0,0 +1,33 @@
+-- Migration: Full-text index of clinical documents
+-- Description: Copy of ^TIU(8925) document titles and text (with addenda),
+--              kept current by DocumentSyncTask, which polls YottaDB every
+--              60 seconds. content_tsv weights the title above the body.
+-- Related Service: shared/src/application/services/document_sync.rs
+-- Related Repository: src/infrastructure/repositories/ehr/document_search_repository_impl.rs
+--
+-- Tables Created:
+--   - ehr_document_index
+--
+-- Indexes Created:
+--   - idx_ehr_document_index_content_tsv (GIN, on content_tsv)
+--   - idx_ehr_document_index_patient_ien (B-tree, on patient_ien)
+
+CREATE TABLE IF NOT EXISTS ehr_document_index (
+    ien BIGINT PRIMARY KEY,  -- VistA ^TIU (File #8925) IEN
+    patient_ien BIGINT NOT NULL,
+    document_type VARCHAR(50) NOT NULL,
+    title TEXT NOT NULL DEFAULT '',
+    content TEXT NOT NULL DEFAULT '',
+    status VARCHAR(20) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    content_tsv TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', title), 'A') ||
+        setweight(to_tsvector('english', content), 'B')
+    ) STORED
+);
+
+CREATE INDEX IF NOT EXISTS idx_ehr_document_index_content_tsv ON ehr_document_index USING GIN (content_tsv);
+CREATE INDEX IF NOT EXISTS idx_ehr_document_index_patient_ien ON ehr_document_index(patient_ien);
+
+COMMENT ON COLUMN ehr_document_index.content IS 'Note text followed by each addendum, as stored in ^TIU(8925,IEN,"TEXT") and ^TIU(8925,IEN,"ADD")';
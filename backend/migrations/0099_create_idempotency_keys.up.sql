@@ -0,0 +1,18 @@
+-- Migration: Idempotency keys for retried POST requests
+-- Description: Responses recorded under a client-supplied Idempotency-Key so
+--              a retried request is answered without running the handler
+--              again. Redis (idempotency_cache:{key}) caches them for 24
+--              hours; this table keeps them across restarts.
+-- Related Middleware: api-service/src/presentation/api/middleware/idempotency_middleware.rs
+-- Related Repository: src/infrastructure/repositories/idempotency_key_repository_impl.rs
+--
+-- Tables Created:
+--   - idempotency_keys
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    key UUID PRIMARY KEY,
+    user_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    status INTEGER NOT NULL,
+    body BYTEA NOT NULL
+);
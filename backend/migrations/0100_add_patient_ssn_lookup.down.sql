@@ -0,0 +1,10 @@
+-- Rollback: Remove patient SSN lookup and its access log
+
+DROP INDEX IF EXISTS idx_patient_access_log_user_id;
+DROP INDEX IF EXISTS idx_patient_access_log_patient_ien;
+DROP TABLE IF EXISTS patient_access_log;
+
+DROP INDEX IF EXISTS idx_ehr_patients_ssn_last4_ciphertext;
+
+ALTER TABLE ehr_patients
+DROP COLUMN IF EXISTS ssn_last4_ciphertext;
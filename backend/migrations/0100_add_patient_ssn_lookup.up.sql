@@ -0,0 +1,46 @@
+-- Migration: Patient lookup by SSN last four, with an access log
+-- Description: Stores the AES-SIV (deterministic) encryption of each patient's
+--              SSN last four digits so find_by_ssn can search by ciphertext;
+--              the digits themselves are never stored. Every SSN search is
+--              recorded in patient_access_log.
+-- Related Use Case: shared/src/infrastructure/repositories/ehr/patient_repository_impl.rs
+--
+-- Columns Added:
+--   - ehr_patients.ssn_last4_ciphertext
+--
+-- Tables Created:
+--   - patient_access_log
+--
+-- Indexes Created:
+--   - idx_ehr_patients_ssn_last4_ciphertext (B-tree, on organization_id, ssn_last4_ciphertext WHERE ssn_last4_ciphertext IS NOT NULL)
+--   - idx_patient_access_log_patient_ien (B-tree, on patient_ien, timestamp WHERE patient_ien IS NOT NULL)
+--   - idx_patient_access_log_user_id (B-tree, on user_id, timestamp)
+
+ALTER TABLE ehr_patients
+ADD COLUMN IF NOT EXISTS ssn_last4_ciphertext BYTEA;
+
+CREATE INDEX IF NOT EXISTS idx_ehr_patients_ssn_last4_ciphertext
+ON ehr_patients(organization_id, ssn_last4_ciphertext)
+WHERE ssn_last4_ciphertext IS NOT NULL;
+
+COMMENT ON COLUMN ehr_patients.ssn_last4_ciphertext IS 'AES-SIV ciphertext of the SSN last four digits with context ehr_patients.ssn_last4';
+
+CREATE TABLE IF NOT EXISTS patient_access_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID,
+    patient_ien BIGINT,
+    access_type TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- "Who looked this patient up" queries
+CREATE INDEX IF NOT EXISTS idx_patient_access_log_patient_ien
+ON patient_access_log(patient_ien, timestamp DESC)
+WHERE patient_ien IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_patient_access_log_user_id
+ON patient_access_log(user_id, timestamp DESC);
+
+COMMENT ON TABLE patient_access_log IS 'HIPAA log of patient lookups by sensitive identifiers';
+COMMENT ON COLUMN patient_access_log.patient_ien IS 'Patient found, NULL when the search matched nobody';
+COMMENT ON COLUMN patient_access_log.access_type IS 'Lookup performed, e.g. ssn_search';
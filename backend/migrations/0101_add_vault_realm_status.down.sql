@@ -0,0 +1,6 @@
+-- Rollback: Remove vault realm status
+
+ALTER TABLE vault_realms
+DROP CONSTRAINT IF EXISTS vault_realms_status_check,
+DROP COLUMN IF EXISTS status_reason,
+DROP COLUMN IF EXISTS status;
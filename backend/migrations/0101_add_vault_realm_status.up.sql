@@ -0,0 +1,16 @@
+-- Migration: Add status to vault realms
+-- Description: Lets a realm be suspended without deleting its data. Requests
+--              to a suspended realm are refused until it is activated again.
+-- Related Store: rustyvault-service/src/modules/realm/realm_store.rs (RealmStore::suspend, RealmStore::activate)
+--
+-- Columns Added:
+--   - vault_realms.status
+--   - vault_realms.status_reason
+
+ALTER TABLE vault_realms
+ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'active',
+ADD COLUMN IF NOT EXISTS status_reason TEXT,
+ADD CONSTRAINT vault_realms_status_check CHECK (status IN ('active', 'suspended', 'deleted'));
+
+COMMENT ON COLUMN vault_realms.status IS 'active, suspended (requests refused, data kept) or deleted';
+COMMENT ON COLUMN vault_realms.status_reason IS 'Why the realm was suspended, cleared on activation';
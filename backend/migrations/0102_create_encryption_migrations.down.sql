@@ -0,0 +1,3 @@
+-- Rollback: Remove encryption migration progress
+
+DROP TABLE IF EXISTS encryption_migrations;
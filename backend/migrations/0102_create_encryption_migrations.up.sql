@@ -0,0 +1,30 @@
+-- Migration: Progress of encrypting existing column values
+-- Description: One row per run of FieldEncryptionMigrator, which encrypts the
+--              plaintext values left in a column when it joins field-level
+--              encryption. migrated_rows is updated after every batch so a
+--              running migration can report its progress.
+-- Related Service: shared/src/infrastructure/encryption/field_encryption_migration.rs
+--
+-- Tables Created:
+--   - encryption_migrations
+--
+-- Indexes Created:
+--   - idx_encryption_migrations_column (B-tree, on table_name, column_name, started_at)
+
+CREATE TABLE IF NOT EXISTS encryption_migrations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    table_name TEXT NOT NULL,
+    column_name TEXT NOT NULL,
+    key_id UUID NOT NULL,
+    migrated_rows BIGINT NOT NULL DEFAULT 0,
+    total_rows BIGINT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_encryption_migrations_column ON encryption_migrations(table_name, column_name, started_at DESC);
+
+COMMENT ON COLUMN encryption_migrations.key_id IS 'DEK (encryption_keys.id) the values were encrypted with';
+COMMENT ON COLUMN encryption_migrations.total_rows IS 'Plaintext values in the column when the run started';
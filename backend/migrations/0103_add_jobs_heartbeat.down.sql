@@ -0,0 +1,5 @@
+-- Rollback: Remove job heartbeats
+
+DROP INDEX IF EXISTS idx_jobs_running_heartbeat;
+
+ALTER TABLE jobs DROP COLUMN IF EXISTS heartbeat_at;
@@ -0,0 +1,22 @@
+-- Migration: Lease running jobs with a heartbeat
+-- Description: A worker refreshes heartbeat_at while it processes a job. A
+--              running job whose heartbeat is older than the lease timeout
+--              belongs to a crashed worker and is claimed again.
+-- Related Entity: src/infrastructure/jobs/mod.rs (Job)
+--
+-- Columns Added:
+--   - jobs.heartbeat_at
+--
+-- Indexes Created:
+--   - idx_jobs_running_heartbeat (Partial B-tree, on heartbeat_at WHERE status = 'running')
+
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS heartbeat_at TIMESTAMPTZ;
+
+UPDATE jobs SET heartbeat_at = started_at WHERE status = 'running' AND heartbeat_at IS NULL;
+
+-- Expired lease lookup in claim_next
+CREATE INDEX IF NOT EXISTS idx_jobs_running_heartbeat
+ON jobs(heartbeat_at)
+WHERE status = 'running';
+
+COMMENT ON COLUMN jobs.heartbeat_at IS 'Last heartbeat of the worker processing the job, set while status = running';
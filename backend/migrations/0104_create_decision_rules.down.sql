@@ -0,0 +1,5 @@
+-- Rollback: Drop decision rules table
+
+DROP INDEX IF EXISTS idx_decision_rules_active;
+
+DROP TABLE IF EXISTS decision_rules;
@@ -0,0 +1,29 @@
+-- Migration: Create decision rules table
+-- Description: Persists the decision rules evaluated by the rules engine so
+--              they are loaded again when the service starts
+-- Related Service: shared/src/application/services/rules_engine.rs (DecisionRule)
+--
+-- Tables Created:
+--   - decision_rules
+--
+-- Indexes Created:
+--   - idx_decision_rules_active (Partial B-tree, on category WHERE is_active = true)
+
+CREATE TABLE IF NOT EXISTS decision_rules (
+    id VARCHAR(255) PRIMARY KEY,
+    category VARCHAR(50) NOT NULL,
+    rule JSONB NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    version INTEGER NOT NULL DEFAULT 1,
+    organization_id UUID REFERENCES organizations(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Active rules are loaded at startup
+CREATE INDEX IF NOT EXISTS idx_decision_rules_active
+ON decision_rules(category)
+WHERE is_active = true;
+
+COMMENT ON TABLE decision_rules IS 'Decision rules loaded into the rules engine at startup';
+COMMENT ON COLUMN decision_rules.rule IS 'Serialized DecisionRule';
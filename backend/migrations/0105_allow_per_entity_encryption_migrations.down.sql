@@ -0,0 +1,7 @@
+-- Rollback: Require a single DEK per encryption migration
+
+DELETE FROM encryption_migrations WHERE key_id IS NULL;
+
+ALTER TABLE encryption_migrations ALTER COLUMN key_id SET NOT NULL;
+
+COMMENT ON COLUMN encryption_migrations.key_id IS 'DEK (encryption_keys.id) the values were encrypted with';
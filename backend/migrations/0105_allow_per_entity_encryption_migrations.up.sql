@@ -0,0 +1,13 @@
+-- Migration: Record encryption migrations that use per-entity DEKs
+-- Description: FieldEncryptionMigrator encrypts each row with the DEK of the
+--              entity it belongs to, the same DEK its repository decrypts
+--              with, so a run no longer has a single key. key_id stays set
+--              on runs recorded before.
+-- Related Service: shared/src/infrastructure/encryption/field_encryption_migration.rs
+--
+-- Columns Altered:
+--   - encryption_migrations.key_id (nullable)
+
+ALTER TABLE encryption_migrations ALTER COLUMN key_id DROP NOT NULL;
+
+COMMENT ON COLUMN encryption_migrations.key_id IS 'DEK (encryption_keys.id) of runs that used a single key; NULL when each row uses its entity''s DEK';
@@ -0,0 +1,6 @@
+-- Rollback: Record idempotency keys only after the handler has run
+
+DELETE FROM idempotency_keys WHERE status IS NULL OR body IS NULL;
+
+ALTER TABLE idempotency_keys ALTER COLUMN body SET NOT NULL;
+ALTER TABLE idempotency_keys ALTER COLUMN status SET NOT NULL;
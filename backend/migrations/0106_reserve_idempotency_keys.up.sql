@@ -0,0 +1,16 @@
+-- Migration: Reserve idempotency keys before the handler runs
+-- Description: The middleware inserts a pending row (status and body NULL)
+--              for a key before running the handler and fills in the
+--              response afterwards, so a concurrent retry with the same key
+--              sees the reservation instead of running the handler again.
+-- Related Middleware: api-service/src/presentation/api/middleware/idempotency_middleware.rs
+-- Related Repository: src/infrastructure/repositories/idempotency_key_repository_impl.rs
+--
+-- Columns Altered:
+--   - idempotency_keys.status (nullable)
+--   - idempotency_keys.body (nullable)
+
+ALTER TABLE idempotency_keys ALTER COLUMN status DROP NOT NULL;
+ALTER TABLE idempotency_keys ALTER COLUMN body DROP NOT NULL;
+
+COMMENT ON COLUMN idempotency_keys.status IS 'HTTP status of the recorded response; NULL while the original request is still running';
@@ -1,6 +1,7 @@
 //! Vault-specific configuration extending health-v1 settings
 
 use serde::{Deserialize, Serialize};
+use shared::config::{ConfigValidationError, EnvValidator};
 use std::env;
 
 /// Vault service settings extending health-v1 configuration
@@ -64,9 +65,34 @@ pub struct AuthConfig {
     /// Higher values = more secure but slower
     /// Cost 12 = ~181ms, Cost 14 = ~724ms per hash
     pub bcrypt_cost: u32,
+    /// Shared JWT signing secret; when set, health-v1 access tokens issued
+    /// for the `rustyvault` audience are accepted alongside vault tokens
+    pub jwt_secret: Option<String>,
+    /// Expected `iss` claim for health-v1 access tokens
+    pub jwt_issuer: String,
 }
 
 impl VaultSettings {
+    /// Load settings, reporting every missing or invalid critical value
+    pub fn from_env_validated() -> Result<Self, Vec<ConfigValidationError>> {
+        Self::validate_env(|key| env::var(key).ok())?;
+        Self::from_env().map_err(|e| vec![ConfigValidationError::new("configuration", e.to_string())])
+    }
+
+    /// Validate the environment variables `from_env` reads
+    pub fn validate_env<F>(lookup: F) -> Result<(), Vec<ConfigValidationError>>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let mut validator = EnvValidator::new(lookup);
+        validator.postgres_url("DATABASE_URL");
+        validator.optional_port("VAULT_SERVER_PORT");
+        // JWT acceptance is optional for the vault, but a configured secret must be usable
+        validator.jwt_secret("JWT_SECRET", false);
+        validator.optional_url("OIDC_ISSUER");
+        validator.finish()
+    }
+
     pub fn from_env() -> Result<Self, config::ConfigError> {
         let server = ServerConfig {
             host: env::var("VAULT_SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
@@ -97,6 +123,7 @@ impl VaultSettings {
         let logging = shared::config::settings::LoggingConfig {
             level: env::var("LOG_LEVEL").unwrap_or_else(|_| "info".to_string()),
             rust_log: env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()),
+            body_sampling_rate: 0.0,
         };
 
         let deployment = shared::config::DeploymentConfig::from_env()
@@ -154,6 +181,8 @@ impl VaultSettings {
 
         let auth = AuthConfig {
             bcrypt_cost,
+            jwt_secret: env::var("JWT_SECRET").ok().filter(|s| !s.is_empty()),
+            jwt_issuer: env::var("OIDC_ISSUER").unwrap_or_else(|_| "http://localhost:4117".to_string()),
         };
 
         Ok(VaultSettings {
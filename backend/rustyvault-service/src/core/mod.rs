@@ -2,7 +2,9 @@
 //!
 //! Adapted from RustyVault to work with health-v1 infrastructure
 
+pub mod snapshot;
 pub mod vault_core;
 
+pub use snapshot::{SnapshotManifest, MAX_SNAPSHOT_SIZE};
 pub use vault_core::{VaultCore, SealConfig};
 
@@ -0,0 +1,297 @@
+//! Vault snapshots for disaster recovery
+//!
+//! A snapshot is laid out as
+//!
+//! ```text
+//! magic(8) | manifest length(4) | manifest | barrier init length(4) | barrier init | payload
+//! ```
+//!
+//! Lengths are big-endian and the manifest is msgpack. The payload is the
+//! gzip-compressed msgpack list of physical entries, encrypted with the
+//! barrier key. The barrier init (encrypted with the unseal key) is carried
+//! alongside so a fresh instance can recover the barrier key from it.
+
+use std::io::{Read, Write};
+
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::{VaultError, VaultResult};
+
+/// Format version written into new snapshots
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Largest snapshot accepted for restore
+pub const MAX_SNAPSHOT_SIZE: usize = 256 * 1024 * 1024;
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"RVSNAP\0\0";
+const LENGTH_SIZE: usize = 4;
+
+/// Header at the start of every snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub entry_count: u64,
+    /// SHA-256 of the encrypted payload, checked before anything is decrypted
+    pub checksum: [u8; 32],
+}
+
+/// One physical storage entry, as stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    pub key: String,
+    #[serde(with = "serde_bytes")]
+    pub value: Vec<u8>,
+}
+
+/// A parsed snapshot whose payload is still encrypted
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub manifest: SnapshotManifest,
+    pub barrier_init: Vec<u8>,
+    pub payload: Vec<u8>,
+}
+
+impl Snapshot {
+    pub fn new(entry_count: u64, barrier_init: Vec<u8>, payload: Vec<u8>) -> Self {
+        let manifest = SnapshotManifest {
+            version: SNAPSHOT_VERSION,
+            created_at: Utc::now(),
+            entry_count,
+            checksum: Sha256::digest(&payload).into(),
+        };
+        Self { manifest, barrier_init, payload }
+    }
+
+    pub fn to_bytes(&self) -> VaultResult<Vec<u8>> {
+        let manifest = rmp_serde::to_vec_named(&self.manifest)
+            .map_err(|e| VaultError::Vault(format!("Failed to encode snapshot manifest: {}", e)))?;
+
+        let mut out = Vec::with_capacity(
+            SNAPSHOT_MAGIC.len() + 2 * LENGTH_SIZE + manifest.len() + self.barrier_init.len() + self.payload.len(),
+        );
+        out.extend_from_slice(SNAPSHOT_MAGIC);
+        write_section(&mut out, &manifest)?;
+        write_section(&mut out, &self.barrier_init)?;
+        out.extend_from_slice(&self.payload);
+        Ok(out)
+    }
+
+    /// Parse a snapshot and verify its version and checksum
+    pub fn from_bytes(bytes: &[u8]) -> VaultResult<Self> {
+        let rest = bytes.strip_prefix(SNAPSHOT_MAGIC.as_slice())
+            .ok_or_else(|| VaultError::Vault("Not a vault snapshot".to_string()))?;
+        let (manifest, rest) = read_section(rest)?;
+        let (barrier_init, payload) = read_section(rest)?;
+
+        let manifest: SnapshotManifest = rmp_serde::from_slice(manifest)
+            .map_err(|e| VaultError::Vault(format!("Invalid snapshot manifest: {}", e)))?;
+        if manifest.version != SNAPSHOT_VERSION {
+            return Err(VaultError::Vault(format!("Unsupported snapshot version {}", manifest.version)));
+        }
+        let checksum: [u8; 32] = Sha256::digest(payload).into();
+        if checksum != manifest.checksum {
+            return Err(VaultError::Vault("Snapshot checksum mismatch".to_string()));
+        }
+
+        Ok(Self {
+            manifest,
+            barrier_init: barrier_init.to_vec(),
+            payload: payload.to_vec(),
+        })
+    }
+}
+
+/// Encode entries as gzip-compressed msgpack
+pub fn pack_entries(entries: &[SnapshotEntry]) -> VaultResult<Vec<u8>> {
+    let encoded = rmp_serde::to_vec(entries)
+        .map_err(|e| VaultError::Vault(format!("Failed to encode snapshot entries: {}", e)))?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&encoded)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decode entries written by `pack_entries`
+pub fn unpack_entries(packed: &[u8]) -> VaultResult<Vec<SnapshotEntry>> {
+    let mut encoded = Vec::new();
+    GzDecoder::new(packed).read_to_end(&mut encoded)?;
+    rmp_serde::from_slice(&encoded)
+        .map_err(|e| VaultError::Vault(format!("Invalid snapshot entries: {}", e)))
+}
+
+fn write_section(out: &mut Vec<u8>, section: &[u8]) -> VaultResult<()> {
+    let len = u32::try_from(section.len())
+        .map_err(|_| VaultError::Vault("Snapshot section too large".to_string()))?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(section);
+    Ok(())
+}
+
+/// Split a length-prefixed section off the front of `bytes`
+fn read_section(bytes: &[u8]) -> VaultResult<(&[u8], &[u8])> {
+    let truncated = || VaultError::Vault("Snapshot is truncated".to_string());
+    if bytes.len() < LENGTH_SIZE {
+        return Err(truncated());
+    }
+    let (len, rest) = bytes.split_at(LENGTH_SIZE);
+    let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+    if rest.len() < len {
+        return Err(truncated());
+    }
+    Ok(rest.split_at(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use serde_json::json;
+    use crate::core::{SealConfig, VaultCore};
+    use crate::logical::{Backend, Request};
+    use crate::modules::kv::KvBackend;
+    use crate::errors::VaultResult;
+    use crate::storage::physical_file::FileBackend;
+    use crate::storage::StorageBackend;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// File storage whose `fail_at`-th write (counting from 1) fails
+    struct FailingBackend {
+        inner: FileBackend,
+        writes: AtomicUsize,
+        fail_at: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl StorageBackend for FailingBackend {
+        async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
+            self.inner.get(key).await
+        }
+
+        async fn put(&self, key: &str, value: &[u8]) -> VaultResult<()> {
+            if self.writes.fetch_add(1, Ordering::SeqCst) + 1 == self.fail_at.load(Ordering::SeqCst) {
+                return Err(VaultError::Vault("Injected write failure".to_string()));
+            }
+            self.inner.put(key, value).await
+        }
+
+        async fn delete(&self, key: &str) -> VaultResult<()> {
+            self.inner.delete(key).await
+        }
+
+        async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
+            self.inner.list(prefix).await
+        }
+    }
+
+    /// Initialized, unsealed single-share vault and its unseal key
+    async fn unsealed_core(dir: &tempfile::TempDir) -> (VaultCore, Vec<u8>) {
+        let backend: Arc<dyn StorageBackend> = Arc::new(FileBackend::new(dir.path()).unwrap());
+        let core = VaultCore::new(backend);
+        let result = core.init(&SealConfig { secret_shares: 1, secret_threshold: 1 }).await.unwrap();
+        let key = result.secret_shares[0].clone();
+        assert!(core.unseal(&key).await.unwrap());
+        (core, key)
+    }
+
+    fn kv(core: &VaultCore) -> KvBackend {
+        KvBackend::new(core.barrier.clone(), "secret".to_string())
+    }
+
+    async fn read_password(kv: &KvBackend, index: usize) -> Option<serde_json::Value> {
+        let mut req = Request::new_read_request(format!("secret/app/{}", index));
+        kv.handle_request(&mut req).await.unwrap()
+            .and_then(|resp| resp.data)
+            .and_then(|data| data.get("data")?.get("password").cloned())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restores_into_fresh_instance() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let (source, key) = unsealed_core(&source_dir).await;
+        let source_kv = kv(&source);
+        for i in 0..10 {
+            let data = json!({ "password": format!("secret-{}", i) });
+            let mut req = Request::new_write_request(format!("secret/app/{}", i), data.as_object().cloned());
+            source_kv.handle_request(&mut req).await.unwrap();
+        }
+
+        let snapshot = source.snapshot().await.unwrap();
+        // Secret values never appear in the clear
+        assert!(!snapshot.windows(b"secret-0".len()).any(|w| w == b"secret-0"));
+        assert!(Snapshot::from_bytes(&snapshot).unwrap().manifest.entry_count > 10);
+
+        let target_dir = tempfile::tempdir().unwrap();
+        let target = VaultCore::new(Arc::new(FileBackend::new(target_dir.path()).unwrap()));
+        target.restore(&snapshot, &key).await.unwrap();
+
+        assert!(!target.is_sealed());
+        let target_kv = kv(&target);
+        for i in 0..10 {
+            assert_eq!(read_password(&target_kv, i).await, Some(json!(format!("secret-{}", i))));
+        }
+
+        // Still readable after the post-restore key rotation and an unseal
+        target.seal().await.unwrap();
+        assert!(target.unseal(&key).await.unwrap());
+        assert_eq!(read_password(&target_kv, 9).await, Some(json!("secret-9")));
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_wrong_key_and_corruption() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let (source, key) = unsealed_core(&source_dir).await;
+        let snapshot = source.snapshot().await.unwrap();
+
+        let target_dir = tempfile::tempdir().unwrap();
+        let (target, _) = unsealed_core(&target_dir).await;
+        let wrong_key = vec![0u8; key.len()];
+        assert!(target.restore(&snapshot, &wrong_key).await.is_err());
+
+        let mut corrupted = snapshot.clone();
+        let last = corrupted.len() - 1;
+        corrupted[last] ^= 0xff;
+        assert!(matches!(
+            target.restore(&corrupted, &key).await,
+            Err(VaultError::Vault(message)) if message.contains("checksum")
+        ));
+
+        // A rejected restore leaves the target vault as it was
+        assert!(!target.is_sealed());
+        assert!(target.snapshot().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_failed_restore_keeps_previous_entries() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let (source, source_key) = unsealed_core(&source_dir).await;
+        let snapshot = source.snapshot().await.unwrap();
+
+        let target_dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(FailingBackend {
+            inner: FileBackend::new(target_dir.path()).unwrap(),
+            writes: AtomicUsize::new(0),
+            fail_at: AtomicUsize::new(0),
+        });
+        let target = VaultCore::new(backend.clone());
+        let result = target.init(&SealConfig { secret_shares: 1, secret_threshold: 1 }).await.unwrap();
+        let target_key = result.secret_shares[0].clone();
+        assert!(target.unseal(&target_key).await.unwrap());
+        let data = json!({ "password": "target-secret" });
+        let mut req = Request::new_write_request("secret/app/0".to_string(), data.as_object().cloned());
+        kv(&target).handle_request(&mut req).await.unwrap();
+        let before = target.barrier.stored_entries().await.unwrap();
+
+        // Fail the second write of the import, after the snapshot's first entry landed
+        backend.fail_at.store(backend.writes.load(Ordering::SeqCst) + 2, Ordering::SeqCst);
+        assert!(target.restore(&snapshot, &source_key).await.is_err());
+
+        assert!(target.is_sealed());
+        assert_eq!(target.barrier.stored_entries().await.unwrap(), before);
+        assert!(target.unseal(&target_key).await.unwrap());
+        assert_eq!(read_password(&kv(&target), 0).await, Some(json!("target-secret")));
+    }
+}
@@ -3,16 +3,16 @@
 //! Adapted from RustyVault's Core to integrate with health-v1 infrastructure
 
 use std::sync::Arc;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use zeroize::{Zeroize, Zeroizing};
 use crate::errors::{VaultError, VaultResult};
 use crate::logical::{Request, Response};
-use crate::storage::{StorageBackend, SecurityBarrier, barrier_aes_gcm::AESGCMBarrier};
-use crate::shamir::{ShamirSecret, SHAMIR_OVERHEAD};
+use crate::storage::{StorageBackend, SecurityBarrier, BarrierStats, BARRIER_INIT_PATH, SEAL_CONFIG_PATH, barrier_aes_gcm::{AESGCMBarrier, RotationProgress, RotationStatus}};
+use crate::core::snapshot::{self, Snapshot, SnapshotEntry};
+use crate::shamir::{AddShareResult, ShamirSecret, ShamirSession, SHAMIR_OVERHEAD};
 use crate::router::Router;
 
-const SEAL_CONFIG_PATH: &str = "core/seal-config";
-
 /// Seal configuration
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SealConfig {
@@ -43,7 +43,7 @@ pub struct InitResult {
 pub struct CoreState {
     pub sealed: bool,
     pub hmac_key: Vec<u8>,
-    unseal_key_shares: Vec<Vec<u8>>,
+    unseal_session: Option<ShamirSession>,
     kek: Vec<u8>,
 }
 
@@ -51,7 +51,7 @@ impl Default for CoreState {
     fn default() -> Self {
         Self {
             sealed: true,
-            unseal_key_shares: Vec::new(),
+            unseal_session: None,
             hmac_key: Vec::new(),
             kek: Vec::new(),
         }
@@ -64,6 +64,7 @@ pub struct VaultCore {
     pub barrier: Arc<AESGCMBarrier>,
     pub router: Arc<Router>,
     pub state: Arc<std::sync::Mutex<CoreState>>,
+    pub rotation_progress: Arc<RotationProgress>,
 }
 
 impl VaultCore {
@@ -74,6 +75,7 @@ impl VaultCore {
             barrier,
             router: Arc::new(Router::new()),
             state: Arc::new(std::sync::Mutex::new(CoreState::default())),
+            rotation_progress: Arc::new(RotationProgress::default()),
         }
     }
 
@@ -83,6 +85,7 @@ impl VaultCore {
             barrier,
             router: Arc::new(Router::new()),
             state: Arc::new(std::sync::Mutex::new(CoreState::default())),
+            rotation_progress: Arc::new(RotationProgress::default()),
         }
     }
 
@@ -163,28 +166,24 @@ impl VaultCore {
         // Process unseal key and get KEK - all in a block so lock is dropped before await
         let kek: Option<Zeroizing<Vec<u8>>> = {
             let mut state = self.state.lock().unwrap();
-            
-            // Check for duplicate key
-            if state.unseal_key_shares.iter().any(|v| *v == key) {
-                None // Will return Ok(false) below
-            } else {
-                state.unseal_key_shares.push(key.to_vec());
-
-                if state.unseal_key_shares.len() < seal_config.secret_threshold as usize {
-                    None // Not enough keys yet, will return Ok(false) below
-                } else {
-                    // Combine keys using Shamir
-                    let key_shares = state.unseal_key_shares.clone();
-                    let result = if seal_config.secret_threshold == 1 {
-                        Some(Zeroizing::new(key_shares[0].clone()))
-                    } else if let Some(res) = ShamirSecret::combine(key_shares) {
-                        Some(Zeroizing::new(res))
-                    } else {
-                        state.unseal_key_shares.clear();
-                        return Err(VaultError::Vault("Failed to combine keys".to_string()));
-                    };
-                    result
+            let session = state.unseal_session.get_or_insert_with(|| {
+                ShamirSession::new(seal_config.secret_threshold, seal_config.secret_shares)
+            });
+
+            // A single-share vault uses the raw KEK, which carries no share index
+            let index = if seal_config.secret_threshold == 1 { 1 } else { key[key.len() - 1] };
+            match session.add_share(index, key.to_vec()) {
+                AddShareResult::NeedMore(_) => None, // Will return Ok(false) below
+                AddShareResult::Invalid => {
+                    return Err(VaultError::Vault("Invalid unseal key share".to_string()));
                 }
+                AddShareResult::Ready => match session.reconstruct() {
+                    Ok(kek) => Some(Zeroizing::new(kek)),
+                    Err(e) => {
+                        state.unseal_session = None;
+                        return Err(e);
+                    }
+                },
             }
             // Lock is dropped here at end of block
         };
@@ -195,28 +194,34 @@ impl VaultCore {
             None => return Ok(false),
         };
 
-        // Now we can await - lock is not held
-        self.barrier.unseal(kek.as_slice()).await?;
-
-        // Re-acquire lock to update state
-        {
-            let mut state = self.state.lock().unwrap();
-            state.hmac_key = self.barrier.derive_hmac_key()?;
-            state.sealed = false;
-            state.kek = kek.as_slice().to_vec();
-            state.unseal_key_shares.clear();
+        // Now we can await - lock is not held. A wrong KEK fails authenticated
+        // decryption here, so start the ceremony over.
+        if let Err(e) = self.barrier.unseal(kek.as_slice()).await {
+            self.state.lock().unwrap().unseal_session = None;
+            return Err(e);
         }
 
+        self.mark_unsealed(kek.as_slice())?;
         Ok(true)
     }
 
+    /// Record an unsealed barrier and the KEK that unsealed it
+    fn mark_unsealed(&self, kek: &[u8]) -> VaultResult<()> {
+        let mut state = self.state.lock().unwrap();
+        state.hmac_key = self.barrier.derive_hmac_key()?;
+        state.sealed = false;
+        state.kek = kek.to_vec();
+        state.unseal_session = None;
+        Ok(())
+    }
+
     pub async fn seal(&self) -> VaultResult<()> {
         self.barrier.seal()?;
         let mut state = self.state.lock().unwrap();
         state.sealed = true;
         state.kek.clear();
         state.hmac_key.clear();
-        state.unseal_key_shares.clear();
+        state.unseal_session = None;
         Ok(())
     }
 
@@ -227,6 +232,127 @@ impl VaultCore {
         self.router.route(req).await
     }
 
+    /// Rotate the barrier encryption key
+    ///
+    /// Every stored entry is re-encrypted with `new_key` before the old key is
+    /// discarded. Only allowed while the vault is unsealed.
+    pub async fn rotate_encryption_key(&self, new_key: [u8; 32]) -> VaultResult<()> {
+        let new_key = Zeroizing::new(new_key);
+
+        // The barrier init is encrypted with the KEK, which is only held while unsealed
+        let kek = {
+            let state = self.state.lock().unwrap();
+            if state.sealed {
+                return Err(VaultError::Vault("Vault is sealed".to_string()));
+            }
+            Zeroizing::new(state.kek.clone())
+        };
+
+        if !self.rotation_progress.try_start() {
+            return Err(VaultError::Vault("Key rotation already in progress".to_string()));
+        }
+        let result = self.barrier
+            .rotate_key(kek.as_slice(), new_key.as_slice(), &self.rotation_progress)
+            .await;
+        self.rotation_progress.finish();
+        result?;
+
+        let mut state = self.state.lock().unwrap();
+        state.hmac_key = self.barrier.derive_hmac_key()?;
+        Ok(())
+    }
+
+    /// Serialize every barrier entry into an encrypted snapshot
+    ///
+    /// See [`crate::core::snapshot`] for the format. Only allowed while
+    /// unsealed and no key rotation is running.
+    pub async fn snapshot(&self) -> VaultResult<Vec<u8>> {
+        if self.is_sealed() {
+            return Err(VaultError::Vault("Vault is sealed".to_string()));
+        }
+        if self.rotation_status().in_progress {
+            return Err(VaultError::Vault("Key rotation in progress".to_string()));
+        }
+
+        let entries: Vec<SnapshotEntry> = self.barrier.export_entries().await?
+            .into_iter()
+            .map(|(key, value)| SnapshotEntry { key, value })
+            .collect();
+        let barrier_init = entries.iter()
+            .find(|entry| entry.key == BARRIER_INIT_PATH)
+            .map(|entry| entry.value.clone())
+            .ok_or_else(|| VaultError::Vault("Vault not initialized".to_string()))?;
+
+        let payload = self.barrier.encrypt_snapshot(&snapshot::pack_entries(&entries)?)?;
+        Snapshot::new(entries.len() as u64, barrier_init, payload).to_bytes()
+    }
+
+    /// Replace every barrier entry with those in `snapshot` and unseal with them
+    ///
+    /// `unseal_key` must unseal the snapshot's vault on its own (the unseal
+    /// key of a single-share vault). Storage is only touched once the
+    /// snapshot has been verified and decrypted, and if writing it fails the
+    /// previous entries are kept and the vault is left sealed. The barrier key is rotated
+    /// afterwards: this instance's nonce counter does not know which nonces
+    /// the snapshot's key has already used.
+    pub async fn restore(&self, snapshot: &[u8], unseal_key: &[u8]) -> VaultResult<()> {
+        let snapshot = Snapshot::from_bytes(snapshot)?;
+        let payload = self.barrier.decrypt_snapshot(unseal_key, &snapshot.barrier_init, &snapshot.payload)?;
+        let entries: Vec<(String, Vec<u8>)> = snapshot::unpack_entries(&payload)?
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect();
+        if entries.len() as u64 != snapshot.manifest.entry_count {
+            return Err(VaultError::Vault("Snapshot entry count does not match its manifest".to_string()));
+        }
+
+        if !self.rotation_progress.try_start() {
+            return Err(VaultError::Vault("Key rotation in progress".to_string()));
+        }
+        let result = self.replace_storage(&entries, unseal_key).await;
+        self.rotation_progress.finish();
+        result?;
+
+        let mut new_key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut new_key);
+        self.rotate_encryption_key(new_key).await?;
+
+        tracing::info!(
+            "Vault restored from snapshot taken at {} ({} entries)",
+            snapshot.manifest.created_at,
+            snapshot.manifest.entry_count
+        );
+        Ok(())
+    }
+
+    /// Swap in `entries` while sealed; on failure storage keeps its previous
+    /// entries and the vault stays sealed
+    async fn replace_storage(&self, entries: &[(String, Vec<u8>)], unseal_key: &[u8]) -> VaultResult<()> {
+        let previous = self.barrier.stored_entries().await?;
+        self.seal().await?;
+        self.barrier.import_entries(entries).await?;
+        if let Err(e) = self.barrier.unseal(unseal_key).await {
+            self.barrier.import_entries(&previous).await?;
+            return Err(e);
+        }
+        self.mark_unsealed(unseal_key)
+    }
+
+    /// Current barrier encryption key version
+    pub fn key_version(&self) -> u32 {
+        self.barrier.key_version()
+    }
+
+    /// Progress of the current (or last) key rotation
+    pub fn rotation_status(&self) -> RotationStatus {
+        self.rotation_progress.status()
+    }
+
+    /// Barrier nonce counter value, for monitoring
+    pub fn nonce_count(&self) -> u64 {
+        self.barrier.nonce_count()
+    }
+
     pub fn is_sealed(&self) -> bool {
         let state = self.state.lock().unwrap();
         state.sealed
@@ -234,7 +360,7 @@ impl VaultCore {
 
     pub fn unseal_progress(&self) -> usize {
         let state = self.state.lock().unwrap();
-        state.unseal_key_shares.len()
+        state.unseal_session.as_ref().map_or(0, |session| session.len())
     }
 
     pub async fn seal_config(&self) -> VaultResult<SealConfig> {
@@ -10,7 +10,11 @@ use axum::{
 use serde_json::{json, Value};
 use uuid::Uuid;
 
+use shared::AppError;
+
+use crate::errors::VaultError;
 use crate::http::routes::AppState;
+use crate::modules::auth::CreateTokenRequest;
 use crate::modules::realm::{CreateAppRequest, UpdateAppRequest};
 use crate::parse_uuid;
 
@@ -333,3 +337,150 @@ pub async fn register_default_apps(
     }
 }
 
+
+/// Rotate an application's client secret
+/// The new secret is returned once; tokens issued with the old secret are revoked
+pub async fn rotate_client_secret(
+    state: Arc<AppState>,
+    realm_id: String,
+    app_id: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let app_store = state.app_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "app store not initialized" })),
+        )
+    })?;
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    let realm_id = parse_uuid!(realm_id, "realm ID");
+    let app_id = parse_uuid!(app_id, "application ID");
+
+    // The application must belong to the realm in the path
+    match app_store.get(app_id).await {
+        Ok(Some(app)) if app.realm_id == realm_id => {}
+        Ok(_) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({ "error": "application not found" })),
+            ))
+        }
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            ))
+        }
+    }
+
+    match app_store.rotate_client_secret(app_id, token_store).await {
+        Ok(rotation) => Ok(Json(json!({
+            "data": {
+                "app_id": app_id,
+                "client_secret": rotation.client_secret,
+                "rotated_at": rotation.rotated_at,
+                "next_rotation_allowed_at": rotation.next_rotation_allowed_at,
+            }
+        }))),
+        Err(VaultError::Shared(AppError::TooManyAttempts { retry_after_seconds })) => Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(json!({
+                "error": "client secret rotation limit reached",
+                "retry_after_seconds": retry_after_seconds,
+            })),
+        )),
+        Err(VaultError::Shared(AppError::NotFound(message))) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": message })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
+
+/// Log in with an application's client credentials
+/// Issued tokens are revoked when the application's client secret is rotated
+pub async fn client_credentials_login(
+    state: Arc<AppState>,
+    realm_id: String,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let app_store = state.app_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "app store not initialized" })),
+        )
+    })?;
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    let realm_id = parse_uuid!(realm_id, "realm ID");
+    let client_id = payload
+        .get("client_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "client_id is required" })),
+            )
+        })?;
+    let client_id = parse_uuid!(client_id, "client ID");
+    let client_secret = payload
+        .get("client_secret")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(json!({ "error": "client_secret is required" })),
+            )
+        })?;
+
+    let app = match app_store.verify_client_secret(realm_id, client_id, client_secret).await {
+        Ok(app) => app,
+        Err(VaultError::Shared(AppError::Authentication(message))) => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": message })),
+            ))
+        }
+        Err(e) => {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": e.to_string() })),
+            ))
+        }
+    };
+
+    let request = CreateTokenRequest {
+        display_name: format!("client-{}", app.app_name),
+        policies: app.client_policies(),
+        meta: Some(json!({ "realm_id": realm_id, "app_id": app.id })),
+        ..Default::default()
+    };
+    match token_store.create_client_token(&request, app.id, "auth/client/login").await {
+        Ok((entry, client_token)) => Ok(Json(json!({
+            "auth": {
+                "client_token": client_token,
+                "policies": entry.policies,
+                "token_ttl": entry.ttl,
+                "renewable": entry.renewable,
+                "realm_id": realm_id
+            }
+        }))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
@@ -7,11 +7,13 @@ use axum::{
     http::StatusCode,
     Json,
 };
+use chrono::Duration;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::http::routes::AppState;
 use crate::modules::auth::CreateAppRoleRequest;
+use crate::modules::wrapping::DEFAULT_WRAP_TTL_SECONDS;
 use crate::{require_context, parse_uuid, require_field};
 
 // ============================================================================
@@ -263,3 +265,38 @@ pub async fn realm_approle_login(
         )),
     }
 }
+
+// ============================================================================
+// Response Wrapping
+// ============================================================================
+
+/// Generate a secret ID for a global AppRole, returning only a single-use
+/// wrapping token for it
+pub async fn generate_approle_secret_id_wrapped(
+    state: Arc<AppState>,
+    role_name: String,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let approle_backend = require_context!(state, approle_backend, "approle auth not enabled");
+
+    let ttl = payload
+        .get("ttl")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(DEFAULT_WRAP_TTL_SECONDS);
+    if ttl <= 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "ttl must be positive" })),
+        ));
+    }
+
+    match approle_backend.generate_secret_id_wrapped(&role_name, Duration::seconds(ttl)).await {
+        Ok(wrapped) => Ok(Json(json!({
+            "wrap_info": wrapped
+        }))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
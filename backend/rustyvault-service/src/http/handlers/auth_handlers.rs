@@ -253,6 +253,46 @@ pub async fn revoke_self_token(
     }
 }
 
+/// Delete expired and orphan tokens now instead of waiting for the schedule
+pub async fn tidy_tokens(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    match token_store.tidy().await {
+        Ok(result) => Ok(Json(json!({ "data": result }))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
+
+/// Count tokens by state
+pub async fn token_stats(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let token_store = state.token_store.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "error": "token store not initialized" })),
+        )
+    })?;
+
+    match token_store.stats().await {
+        Ok(stats) => Ok(Json(json!({ "data": stats }))),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
+
 // ============================================================================
 // UserPass Handlers
 // ============================================================================
@@ -416,7 +456,9 @@ pub async fn userpass_login(
             )
         })?;
 
-    match userpass.login(&username, password).await {
+    let totp_code = payload.get("totp").and_then(|v| v.as_str());
+
+    match userpass.login_with_totp(&username, password, totp_code, None).await {
         Ok(response) => Ok(Json(json!({
             "auth": {
                 "client_token": response.client_token,
@@ -617,7 +659,9 @@ pub async fn realm_userpass_login(
             )
         })?;
 
-    match userpass.login_in_realm(&username, password, Some(realm_uuid)).await {
+    let totp_code = payload.get("totp").and_then(|v| v.as_str());
+
+    match userpass.login_with_totp(&username, password, totp_code, Some(realm_uuid)).await {
         Ok(response) => Ok(Json(json!({
             "auth": {
                 "client_token": response.client_token,
@@ -3,8 +3,11 @@
 pub mod app_handlers;
 pub mod approle_handlers;
 pub mod auth_handlers;
+pub mod pki_handlers;
 pub mod policy_handlers;
 pub mod realm_handlers;
 pub mod secrets_handlers;
 pub mod sys_handlers;
+pub mod totp_handlers;
+pub mod transit_handlers;
 
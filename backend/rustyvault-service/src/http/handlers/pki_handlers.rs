@@ -0,0 +1,71 @@
+//! PKI (certificate authority) handlers
+//!
+//! Requests are routed through core to the `pki` backend, so a sealed
+//! vault rejects them before the CA key is loaded.
+
+use axum::{
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::{json, Value, Map};
+use std::sync::Arc;
+use crate::errors::VaultError;
+use crate::http::routes::AppState;
+use crate::logical::Request as LogicalRequest;
+
+/// Route a PKI request through core
+async fn handle_pki_request(
+    state: Arc<AppState>,
+    mut req: LogicalRequest,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if state.core.is_sealed() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Vault is sealed"})),
+        ));
+    }
+
+    let response = state.core.handle_request(&mut req).await
+        .map_err(|e| {
+            let status = match e {
+                // Bad input: unknown role, disallowed name, missing CA
+                VaultError::Vault(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(json!({"error": e.to_string()})))
+        })?;
+
+    match response {
+        Some(resp) => {
+            let mut result = Map::new();
+            if let Some(data) = resp.data {
+                result.insert("data".to_string(), Value::Object(data));
+            }
+            Ok(Json(Value::Object(result)))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Not found"})),
+        )),
+    }
+}
+
+/// POST /v1/pki/{path} for root/generate, roles/{name}, issue/{role} and revoke
+pub async fn pki_write_with_state(
+    state: Arc<AppState>,
+    path: String,
+    payload: Option<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let data = payload.and_then(|p| p.as_object().cloned());
+    let req = LogicalRequest::new_write_request(format!("pki/{}", path), data);
+    handle_pki_request(state, req).await
+}
+
+/// GET /v1/pki/{path} for ca, crl, roles/{name} and cert/{serial}
+pub async fn pki_read_with_state(
+    state: Arc<AppState>,
+    path: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let req = LogicalRequest::new_read_request(format!("pki/{}", path));
+    handle_pki_request(state, req).await
+}
@@ -11,7 +11,8 @@ use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::http::routes::AppState;
-use crate::modules::policy::Policy;
+use crate::logical::request::{Operation, Request};
+use crate::modules::policy::{Policy, PolicySimulator};
 use crate::{require_context, parse_uuid, require_field};
 
 /// List all policies
@@ -159,6 +160,76 @@ pub async fn check_capabilities(
     }
 }
 
+/// Simulate a request against an unsaved policy (dry run)
+///
+/// `policy_hcl` holds the policy text in the same format as `write_policy`.
+/// Policies named in `token_policies` are loaded from the store and combined
+/// with it, as they would be on a token.
+pub async fn simulate_policy(
+    state: Arc<AppState>,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let policy_content = require_field!(payload, "policy_hcl", as_str, "policy_hcl is required");
+    let path = require_field!(payload, "path", as_str, "path is required");
+    let operation = payload
+        .get("operation")
+        .and_then(|v| v.as_str())
+        .map(Operation::from)
+        .unwrap_or_default();
+
+    let mut policy = Policy::from_json(policy_content).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("invalid policy: {}", e) })),
+        )
+    })?;
+    if policy.name.is_empty() {
+        policy.name = "simulated".to_string();
+    }
+
+    let token_policies: Vec<String> = payload
+        .get("token_policies")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut policies = vec![Arc::new(policy)];
+    if !token_policies.is_empty() {
+        let policy_store = require_context!(state, policy_store, "policy store not initialized");
+        for name in &token_policies {
+            // Unknown names are skipped, as when building a token's ACL
+            match policy_store.get_policy_global(name).await {
+                Ok(Some(policy)) => policies.push(policy),
+                Ok(None) => {}
+                Err(e) => {
+                    return Err((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({ "error": e.to_string() })),
+                    ));
+                }
+            }
+        }
+    }
+
+    let request = Request {
+        path: path.to_string(),
+        operation,
+        ..Default::default()
+    };
+
+    match PolicySimulator::evaluate_all(&policies, &request) {
+        Ok(result) => Ok(Json(json!(result))),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
+
 // ============================================================
 // Realm-scoped policy handlers
 // ============================================================
@@ -8,11 +8,12 @@ use axum::{
     Json,
 };
 use serde_json::{json, Value};
+use shared::config::{FeatureFlags, RealmFeatureFlags};
 use uuid::Uuid;
 
 use crate::http::routes::AppState;
-use crate::modules::realm::{CreateRealmRequest, UpdateRealmRequest};
-use crate::{require_context, parse_uuid};
+use crate::modules::realm::{CreateRealmRequest, RealmManager, UpdateRealmRequest};
+use crate::{require_context, parse_uuid, require_field};
 
 /// List all realms
 pub async fn list_realms(
@@ -32,6 +33,8 @@ pub async fn list_realms(
                         "description": r.description,
                         "organization_id": r.organization_id,
                         "is_active": r.is_active,
+                        "status": r.status,
+                        "status_reason": r.status_reason,
                         "default_lease_ttl": r.default_lease_ttl,
                         "max_lease_ttl": r.max_lease_ttl,
                         "created_at": r.created_at,
@@ -112,6 +115,8 @@ pub async fn create_realm(
                 "organization_id": realm.organization_id,
                 "config": realm.config,
                 "is_active": realm.is_active,
+                "status": realm.status,
+                "status_reason": realm.status_reason,
                 "default_lease_ttl": realm.default_lease_ttl,
                 "max_lease_ttl": realm.max_lease_ttl,
                 "created_at": realm.created_at,
@@ -156,6 +161,8 @@ pub async fn get_realm(
                 "organization_id": realm.organization_id,
                 "config": realm.config,
                 "is_active": realm.is_active,
+                "status": realm.status,
+                "status_reason": realm.status_reason,
                 "default_lease_ttl": realm.default_lease_ttl,
                 "max_lease_ttl": realm.max_lease_ttl,
                 "created_at": realm.created_at,
@@ -205,6 +212,8 @@ pub async fn update_realm(
                 "organization_id": realm.organization_id,
                 "config": realm.config,
                 "is_active": realm.is_active,
+                "status": realm.status,
+                "status_reason": realm.status_reason,
                 "default_lease_ttl": realm.default_lease_ttl,
                 "max_lease_ttl": realm.max_lease_ttl,
                 "created_at": realm.created_at,
@@ -251,6 +260,119 @@ pub async fn delete_realm(
     }
 }
 
+/// Suspend a realm, revoking its tokens and refusing its requests until it
+/// is activated
+pub async fn suspend_realm(
+    state: Arc<AppState>,
+    realm_id: String,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let realm_store = require_context!(state, realm_store, "realm store not initialized");
+    let token_store = require_context!(state, token_store, "token store not initialized");
+
+    let id = parse_uuid!(realm_id, "realm ID");
+    let reason = require_field!(payload, "reason", as_str, "reason is required");
+
+    let manager = RealmManager::new(realm_store.clone()).with_token_store(token_store.clone());
+    realm_status_response(manager.suspend(id, reason).await)
+}
+
+/// Activate a suspended realm
+pub async fn activate_realm(
+    state: Arc<AppState>,
+    realm_id: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let realm_store = require_context!(state, realm_store, "realm store not initialized");
+
+    let id = parse_uuid!(realm_id, "realm ID");
+
+    realm_status_response(RealmManager::new(realm_store.clone()).activate(id).await)
+}
+
+fn realm_status_response(
+    result: crate::errors::VaultResult<crate::modules::realm::Realm>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match result {
+        Ok(realm) => Ok(Json(json!({
+            "data": {
+                "id": realm.id,
+                "name": realm.name,
+                "status": realm.status,
+                "status_reason": realm.status_reason,
+                "updated_at": realm.updated_at,
+            }
+        }))),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            Err((
+                status,
+                Json(json!({ "error": e.to_string() })),
+            ))
+        }
+    }
+}
+
+/// A realm's feature flag overrides and the flags that apply to it
+pub async fn get_realm_features(
+    state: Arc<AppState>,
+    realm_id: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let realm_store = require_context!(state, realm_store, "realm store not initialized");
+
+    let id = parse_uuid!(realm_id, "realm ID");
+
+    realm_features_response(id, realm_store.feature_flags(id).await)
+}
+
+/// Replace a realm's feature flag overrides; omitted flags follow the deployment
+pub async fn update_realm_features(
+    state: Arc<AppState>,
+    realm_id: String,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let realm_store = require_context!(state, realm_store, "realm store not initialized");
+
+    let id = parse_uuid!(realm_id, "realm ID");
+    let overrides: RealmFeatureFlags = serde_json::from_value(payload.0).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": format!("invalid feature flags: {}", e) })),
+        )
+    })?;
+
+    realm_features_response(id, realm_store.set_feature_flags(id, &overrides).await)
+}
+
+fn realm_features_response(
+    realm_id: Uuid,
+    result: crate::errors::VaultResult<RealmFeatureFlags>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    match result {
+        Ok(overrides) => Ok(Json(json!({
+            "data": {
+                "realm_id": realm_id,
+                "overrides": overrides,
+                "features": FeatureFlags::from_env().with_overrides(&overrides),
+            }
+        }))),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+            Err((
+                status,
+                Json(json!({ "error": e.to_string() })),
+            ))
+        }
+    }
+}
+
 /// Get realms by organization ID
 pub async fn get_realms_by_organization(
     state: Arc<AppState>,
@@ -273,6 +395,8 @@ pub async fn get_realms_by_organization(
                         "description": r.description,
                         "organization_id": r.organization_id,
                         "is_active": r.is_active,
+                        "status": r.status,
+                        "status_reason": r.status_reason,
                         "default_lease_ttl": r.default_lease_ttl,
                         "max_lease_ttl": r.max_lease_ttl,
                         "created_at": r.created_at,
@@ -9,6 +9,7 @@ use axum::{
     response::Json,
 };
 use serde_json::{json, Value, Map};
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 use crate::http::routes::AppState;
@@ -42,7 +43,12 @@ async fn handle_secret_request(
 
     // Create logical request
     let mut req = match operation {
-        Operation::Read => LogicalRequest::new_read_request(&path),
+        Operation::Read => {
+            // Read parameters (version, compare_to) travel in the request data
+            let mut req = LogicalRequest::new_read_request(&path);
+            req.data = data;
+            req
+        }
         Operation::Write => LogicalRequest::new_write_request(&path, data),
         Operation::Delete => LogicalRequest::new_delete_request(&path, data),
         Operation::List => LogicalRequest::new_list_request(&path),
@@ -86,6 +92,45 @@ pub async fn read_secret_with_state(
     handle_secret_request(state, Method::GET, format!("secret/{}", path), None).await
 }
 
+/// Version parameters for KV reads: `?version=N&compare_to=M`
+fn version_params(
+    query: &HashMap<String, String>,
+) -> Result<Option<Map<String, Value>>, (StatusCode, Json<Value>)> {
+    let mut params = Map::new();
+    for name in ["version", "compare_to"] {
+        if let Some(raw) = query.get(name) {
+            let version = raw.parse::<u64>().ok().filter(|v| *v > 0).ok_or_else(|| (
+                StatusCode::BAD_REQUEST,
+                Json(json!({"error": format!("{} must be a positive integer", name)})),
+            ))?;
+            params.insert(name.to_string(), Value::Number(version.into()));
+        }
+    }
+    Ok((!params.is_empty()).then_some(params))
+}
+
+/// Read a secret version, or diff two versions (direct state parameter)
+///
+/// GET /v1/secret/data/{path}?version=N&compare_to=M
+pub async fn read_secret_data_with_state(
+    state: Arc<AppState>,
+    path: String,
+    query: HashMap<String, String>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let params = version_params(&query)?;
+    handle_secret_request(state, Method::GET, format!("secret/{}", path), params).await
+}
+
+/// Read secret version history without secret data (direct state parameter)
+///
+/// GET /v1/secret/metadata/{path}
+pub async fn read_secret_metadata_with_state(
+    state: Arc<AppState>,
+    path: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    handle_secret_request(state, Method::GET, format!("secret/metadata/{}", path), None).await
+}
+
 /// Write secret endpoint (with State extractor)
 pub async fn write_secret(
     State(state): State<Arc<AppState>>,
@@ -177,7 +222,12 @@ async fn handle_realm_secret_request(
 
     // Create logical request with realm context
     let mut req = match operation {
-        Operation::Read => LogicalRequest::new_read_request(&path),
+        Operation::Read => {
+            // Read parameters (version, compare_to) travel in the request data
+            let mut req = LogicalRequest::new_read_request(&path);
+            req.data = data;
+            req
+        }
         Operation::Write => LogicalRequest::new_write_request(&path, data),
         Operation::Delete => LogicalRequest::new_delete_request(&path, data),
         Operation::List => LogicalRequest::new_list_request(&path),
@@ -212,16 +262,18 @@ async fn handle_realm_secret_request(
     }
 }
 
-/// Read a secret from a specific realm
+/// Read a secret from a specific realm, optionally at `?version=N` or diffed `&compare_to=M`
 pub async fn read_realm_secret(
     state: Arc<AppState>,
     realm_id: String,
     path: String,
+    query: HashMap<String, String>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     // ✨ DRY: Using parse_uuid! macro
     let realm_id = parse_uuid!(realm_id, "realm ID");
+    let params = version_params(&query)?;
     
-    handle_realm_secret_request(state, realm_id, Method::GET, format!("secret/{}", path), None).await
+    handle_realm_secret_request(state, realm_id, Method::GET, format!("secret/{}", path), params).await
 }
 
 /// Write a secret to a specific realm
@@ -12,6 +12,7 @@ use base64::Engine;
 use std::collections::HashMap;
 use crate::http::routes::AppState;
 use crate::modules::auth::CreateTokenRequest;
+use crate::{require_context, require_field};
 
 /// Health check endpoint (with State extractor)
 pub async fn health_check(
@@ -104,6 +105,122 @@ pub async fn seal_with_state(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Rotate the barrier encryption key to a freshly generated 256-bit key
+pub async fn rotate_with_state(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if state.core.is_sealed() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Vault is sealed"})),
+        ));
+    }
+
+    let mut new_key = [0u8; 32];
+    {
+        use rand::RngCore;
+        rand::thread_rng().fill_bytes(&mut new_key);
+    }
+
+    state.core.rotate_encryption_key(new_key).await
+        .map_err(|e| (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        ))?;
+
+    Ok(Json(json!({
+        "rotated_at": chrono::Utc::now().to_rfc3339(),
+        "key_version": state.core.key_version(),
+    })))
+}
+
+/// Key rotation progress
+pub async fn rotate_status_with_state(
+    state: Arc<AppState>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let status = state.core.rotation_status();
+    Ok(Json(json!({
+        "in_progress": status.in_progress,
+        "total_entries": status.total_entries,
+        "processed_entries": status.processed_entries,
+        "key_version": state.core.key_version(),
+        "nonce_count": state.core.nonce_count(),
+    })))
+}
+
+/// Download an encrypted snapshot of all barrier data
+pub async fn snapshot_with_state(
+    state: Arc<AppState>,
+) -> Result<Response, (StatusCode, Json<Value>)> {
+    if state.core.is_sealed() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Vault is sealed"})),
+        ));
+    }
+
+    let snapshot = state.core.snapshot().await
+        .map_err(|e| (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        ))?;
+
+    let filename = format!("rustyvault-snapshot-{}.snap", chrono::Utc::now().format("%Y%m%d-%H%M%S"));
+    let disposition = format!("attachment; filename=\"{}\"", filename);
+
+    let disposition_header = HeaderValue::from_str(&disposition)
+        .map_err(|_| (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to create response headers"})),
+        ))?;
+
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_DISPOSITION, disposition_header)
+        .body(Body::from(snapshot))
+        .map_err(|_| (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": "Failed to create response"})),
+        ))?
+        .into_response())
+}
+
+/// Replace all barrier data with an uploaded snapshot
+///
+/// The body is the snapshot file; the `X-Vault-Unseal-Key` header carries
+/// the snapshot vault's unseal key (base64). The vault is unsealed afterwards.
+pub async fn restore_with_state(
+    state: Arc<AppState>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let key_str = headers.get("X-Vault-Unseal-Key")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Missing 'X-Vault-Unseal-Key' header"})),
+        ))?;
+
+    let key = base64::engine::general_purpose::STANDARD.decode(key_str)
+        .map_err(|_| (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": "Invalid base64 key"})),
+        ))?;
+
+    state.core.restore(&body, &key).await
+        .map_err(|e| (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": e.to_string()})),
+        ))?;
+
+    Ok(Json(json!({
+        "restored_at": chrono::Utc::now().to_rfc3339(),
+        "sealed": state.core.is_sealed(),
+        "key_version": state.core.key_version(),
+    })))
+}
+
 /// Unseal endpoint (with State extractor)
 pub async fn unseal(
     State(state): State<Arc<AppState>>,
@@ -338,3 +455,44 @@ pub async fn get_keys_authenticated_with_state(
     })))
 }
 
+
+/// Look up a wrapping token without unwrapping it
+pub async fn lookup_wrapping_token(
+    state: Arc<AppState>,
+    token: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let wrapper = require_context!(state, response_wrapper, "response wrapping not enabled");
+
+    match wrapper.lookup(&token).await {
+        Ok(Some(info)) => Ok(Json(json!({ "data": info }))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "wrapping token is not valid or does not exist" })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
+
+/// Unwrap a wrapped response; the wrapping token cannot be used again
+pub async fn unwrap(
+    state: Arc<AppState>,
+    payload: Json<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let wrapper = require_context!(state, response_wrapper, "response wrapping not enabled");
+    let token = require_field!(payload, "token", as_str, "token is required");
+
+    match wrapper.unwrap(token).await {
+        Ok(Some(response)) => Ok(Json(json!({ "data": response }))),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": "wrapping token is not valid or does not exist" })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": e.to_string() })),
+        )),
+    }
+}
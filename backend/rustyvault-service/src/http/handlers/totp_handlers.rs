@@ -0,0 +1,98 @@
+//! TOTP (second factor) handlers
+//!
+//! Requests are routed through core to the `auth/totp` backend, so a sealed
+//! vault rejects them before any TOTP secret is loaded.
+
+use axum::{
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::{json, Value, Map};
+use std::sync::Arc;
+use crate::errors::VaultError;
+use crate::http::routes::AppState;
+use crate::logical::Request as LogicalRequest;
+
+/// Route a TOTP request through core
+async fn handle_totp_request(
+    state: Arc<AppState>,
+    mut req: LogicalRequest,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if state.core.is_sealed() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Vault is sealed"})),
+        ));
+    }
+
+    let response = state.core.handle_request(&mut req).await
+        .map_err(|e| {
+            let status = match e {
+                // Bad input: missing name or code, unknown key
+                VaultError::Vault(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(json!({"error": e.to_string()})))
+        })?;
+
+    match response {
+        Some(resp) => {
+            let mut result = Map::new();
+            if let Some(data) = resp.data {
+                result.insert("data".to_string(), Value::Object(data));
+            }
+            Ok(Json(Value::Object(result)))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "TOTP key not found"})),
+        )),
+    }
+}
+
+/// POST /v1/auth/totp/create
+pub async fn create_key_with_state(
+    state: Arc<AppState>,
+    payload: Option<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let data = payload.and_then(|p| p.as_object().cloned());
+    let req = LogicalRequest::new_write_request("auth/totp/create", data);
+    handle_totp_request(state, req).await
+}
+
+/// GET /v1/auth/totp/code/{name}
+pub async fn generate_code_with_state(
+    state: Arc<AppState>,
+    name: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let req = LogicalRequest::new_read_request(format!("auth/totp/code/{}", name));
+    handle_totp_request(state, req).await
+}
+
+/// POST /v1/auth/totp/validate
+pub async fn validate_code_with_state(
+    state: Arc<AppState>,
+    payload: Option<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let data = payload.and_then(|p| p.as_object().cloned());
+    let req = LogicalRequest::new_write_request("auth/totp/validate", data);
+    handle_totp_request(state, req).await
+}
+
+/// GET /v1/auth/totp/keys/{name}
+pub async fn read_key_with_state(
+    state: Arc<AppState>,
+    name: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let req = LogicalRequest::new_read_request(format!("auth/totp/keys/{}", name));
+    handle_totp_request(state, req).await
+}
+
+/// DELETE /v1/auth/totp/keys/{name}
+pub async fn delete_key_with_state(
+    state: Arc<AppState>,
+    name: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let req = LogicalRequest::new_delete_request(format!("auth/totp/keys/{}", name), None);
+    handle_totp_request(state, req).await
+}
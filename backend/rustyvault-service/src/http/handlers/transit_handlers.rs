@@ -0,0 +1,72 @@
+//! Transit (encryption as a service) handlers
+//!
+//! Requests are routed through core to the `transit` backend, so a sealed
+//! vault rejects them before any key is loaded.
+
+use axum::{
+    http::StatusCode,
+    response::Json,
+};
+use serde_json::{json, Value, Map};
+use std::sync::Arc;
+use crate::errors::VaultError;
+use crate::http::routes::AppState;
+use crate::logical::Request as LogicalRequest;
+
+/// Route a transit request through core
+async fn handle_transit_request(
+    state: Arc<AppState>,
+    mut req: LogicalRequest,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    if state.core.is_sealed() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({"error": "Vault is sealed"})),
+        ));
+    }
+
+    let response = state.core.handle_request(&mut req).await
+        .map_err(|e| {
+            let status = match e {
+                // Bad input: unknown key, malformed or tampered ciphertext
+                VaultError::Vault(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(json!({"error": e.to_string()})))
+        })?;
+
+    match response {
+        Some(resp) => {
+            let mut result = Map::new();
+            if let Some(data) = resp.data {
+                result.insert("data".to_string(), Value::Object(data));
+            }
+            Ok(Json(Value::Object(result)))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({"error": "Encryption key not found"})),
+        )),
+    }
+}
+
+/// POST /v1/transit/{operation}/{key_name} for encrypt, decrypt, rotate, rewrap and keys
+pub async fn transit_operation_with_state(
+    state: Arc<AppState>,
+    operation: &str,
+    key_name: String,
+    payload: Option<Value>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let data = payload.and_then(|p| p.as_object().cloned());
+    let req = LogicalRequest::new_write_request(format!("transit/{}/{}", operation, key_name), data);
+    handle_transit_request(state, req).await
+}
+
+/// GET /v1/transit/keys/{key_name}
+pub async fn read_key_with_state(
+    state: Arc<AppState>,
+    key_name: String,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let req = LogicalRequest::new_read_request(format!("transit/keys/{}", key_name));
+    handle_transit_request(state, req).await
+}
@@ -3,7 +3,8 @@
 //! This middleware:
 //! 1. Extracts the token from headers
 //! 2. Extracts realm context from the request path
-//! 3. Validates the token against the TokenStore
+//! 3. Validates the token against the TokenStore (or, for JWTs, verifies the
+//!    `rustyvault` audience)
 //! 4. Checks ACL policies for the requested path (realm-aware)
 //! 5. Attaches token info and realm context to request for handlers
 //! 6. Logs all authentication/authorization decisions (HIPAA audit trail)
@@ -15,13 +16,16 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use chrono::{DateTime, Utc};
 use serde_json::json;
+use shared::infrastructure::oidc::RUSTYVAULT_AUDIENCE;
 use std::sync::Arc;
 use std::time::Instant;
 use uuid::Uuid;
 
 use crate::http::routes::AppState;
 use crate::modules::auth::TokenEntry;
+use crate::modules::realm::RealmStatus;
 use crate::logical::request::{Operation, RealmContext};
 use crate::services::audit_logger::{AuditLogEntry, AuthResult, hash_token_for_audit};
 
@@ -130,6 +134,91 @@ fn is_userpass_login(path: &str, realm_context: &RealmContext) -> bool {
     check_path.starts_with("/v1/auth/userpass/login/")
 }
 
+/// Check if a path is a realm application's client credentials login
+fn is_client_credentials_login(realm_context: &RealmContext) -> bool {
+    realm_context.is_realm_scoped && realm_context.stripped_path == "/v1/auth/client/login"
+}
+
+/// Verify a health-v1 JWT issued for the vault audience
+///
+/// Returns `None` when the token is not a JWT or JWT verification is not
+/// configured, so the caller falls back to the TokenStore. Tokens issued for
+/// other services (e.g. `api-service`) are rejected.
+fn verify_jwt_token(state: &AppState, raw_token: &str) -> Option<shared::AppResult<TokenEntry>> {
+    let verifier = state.jwt_verifier.as_ref()?;
+    if raw_token.matches('.').count() != 2 {
+        return None;
+    }
+
+    Some(
+        verifier
+            .verify_token_for_audience(raw_token, RUSTYVAULT_AUDIENCE)
+            .map(|claims| {
+                let now = Utc::now();
+                TokenEntry {
+                    id: Uuid::new_v4(),
+                    token_hash: hash_token_for_audit(raw_token),
+                    display_name: format!("jwt-{}", claims.email),
+                    policies: vec!["default".to_string()],
+                    parent: None,
+                    ttl: (claims.exp - now.timestamp()).max(0),
+                    expires_at: DateTime::from_timestamp(claims.exp, 0),
+                    created_at: DateTime::from_timestamp(claims.iat, 0).unwrap_or(now),
+                    last_used_at: None,
+                    num_uses: 0,
+                    path: "auth/jwt".to_string(),
+                    meta: Some(json!({
+                        "organization_id": claims.organization_id,
+                        "realm_id": claims.realm_id,
+                        "role": claims.role,
+                    })),
+                    renewable: false,
+                    entity_id: Uuid::parse_str(&claims.sub).ok(),
+                }
+            }),
+    )
+}
+
+/// Rejection for requests to a realm that is not active
+///
+/// Unknown realms are left to the handlers to report.
+async fn check_realm_status(state: &AppState, realm_context: &RealmContext) -> Option<Response> {
+    let (Some(realm_id), Some(realm_store)) = (realm_context.realm_id, state.realm_store.as_ref()) else {
+        return None;
+    };
+
+    match realm_store.get(realm_id).await {
+        Ok(Some(realm)) => match realm.status {
+            RealmStatus::Active => None,
+            RealmStatus::Suspended => Some(
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({
+                        "error": "realm_suspended",
+                        "reason": realm.status_reason.unwrap_or_default()
+                    })),
+                )
+                    .into_response(),
+            ),
+            RealmStatus::Deleted => Some(
+                (StatusCode::NOT_FOUND, Json(json!({ "error": "realm not found" }))).into_response(),
+            ),
+        },
+        Ok(None) => None,
+        Err(e) => {
+            // SECURITY: fail secure if the realm's status cannot be checked
+            tracing::error!("Failed to check status of realm {}: {}", realm_id, e);
+            Some(
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({ "error": "authorization service unavailable" })),
+                )
+                    .into_response(),
+            )
+        }
+    }
+}
+
 /// Authentication middleware
 pub async fn auth_middleware(
     state: Arc<AppState>,
@@ -161,8 +250,14 @@ pub async fn auth_middleware(
         return Ok(next.run(req).await);
     }
 
-    // Allow userpass login without auth (logged in userpass handler)
-    if is_userpass_login(&path, &realm_context) {
+    // Suspended realms refuse everything but public paths, including logins
+    if let Some(rejection) = check_realm_status(&state, &realm_context).await {
+        return Err(rejection);
+    }
+
+    // Allow userpass and client credentials logins without auth (they
+    // authenticate with their own credentials)
+    if is_userpass_login(&path, &realm_context) || is_client_credentials_login(&realm_context) {
         return Ok(next.run(req).await);
     }
 
@@ -224,23 +319,47 @@ pub async fn auth_middleware(
             .into_response()
     })?;
 
-    let token_entry = match token_store.lookup_token(&raw_token).await {
-        Ok(Some(entry)) => entry,
-        Ok(None) => {
+    let token_entry = match verify_jwt_token(&state, &raw_token) {
+        Some(Ok(entry)) => entry,
+        Some(Err(e)) => {
+            let _ = log_auth_failure(
+                &state,
+                request_id,
+                &path,
+                &method,
+                Some(hash_token_for_audit(&raw_token)),
+                AuthResult::Denied,
+                &format!("jwt validation failed: {}", e),
+                remote_addr,
+                user_agent,
+                realm_context.realm_id,
+                start_time.elapsed().as_millis() as i32,
+            );
+
             return Err((
                 StatusCode::UNAUTHORIZED,
                 Json(json!({ "error": "invalid or expired token" })),
             )
                 .into_response());
         }
-        Err(e) => {
-            tracing::error!("Token lookup failed: {}", e);
-            return Err((
-                StatusCode::SERVICE_UNAVAILABLE,
-                Json(json!({ "error": "authentication service error" })),
-            )
-                .into_response());
-        }
+        None => match token_store.lookup_token(&raw_token).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => {
+                return Err((
+                    StatusCode::UNAUTHORIZED,
+                    Json(json!({ "error": "invalid or expired token" })),
+                )
+                    .into_response());
+            }
+            Err(e) => {
+                tracing::error!("Token lookup failed: {}", e);
+                return Err((
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({ "error": "authentication service error" })),
+                )
+                    .into_response());
+            }
+        },
     };
 
     // Atomically decrement token usage (prevents race condition)
@@ -469,4 +588,11 @@ mod tests {
         let ctx = RealmContext::from_path(&path);
         assert!(is_public_path(&path, &ctx));
     }
+
+    #[test]
+    fn test_client_credentials_login_is_realm_scoped() {
+        let path = format!("/v1/realm/{}/auth/client/login", Uuid::new_v4());
+        assert!(is_client_credentials_login(&RealmContext::from_path(&path)));
+        assert!(!is_client_credentials_login(&RealmContext::from_path("/v1/auth/client/login")));
+    }
 }
@@ -7,15 +7,17 @@ use axum::{
 };
 use std::sync::Arc;
 use tower_http::cors::{CorsLayer, AllowOrigin};
-use crate::http::handlers::{app_handlers, approle_handlers, auth_handlers, policy_handlers, realm_handlers, secrets_handlers, sys_handlers};
+use crate::http::handlers::{app_handlers, approle_handlers, auth_handlers, pki_handlers, policy_handlers, realm_handlers, secrets_handlers, sys_handlers, totp_handlers, transit_handlers};
 use crate::http::middleware::auth_middleware;
 use crate::modules::auth::{AppRoleBackend, TokenStore, UserPassBackend};
 use crate::modules::policy::PolicyStore;
 use crate::modules::realm::{RealmStore, RealmApplicationStore};
+use crate::modules::wrapping::ResponseWrapper;
 use crate::config::VaultSettings;
 use crate::services::key_storage::KeyStorage;
 use crate::services::audit_logger::AuditLogger;
 use crate::http::middleware::RateLimiter;
+use shared::infrastructure::oidc::OidcProvider;
 
 /// App state for routes
 pub struct AppState {
@@ -26,9 +28,12 @@ pub struct AppState {
     pub approle_backend: Option<Arc<AppRoleBackend>>,
     pub realm_store: Option<Arc<RealmStore>>,
     pub app_store: Option<Arc<RealmApplicationStore>>,
+    pub response_wrapper: Option<Arc<ResponseWrapper>>,
     pub key_storage: Arc<KeyStorage>,
     pub audit_logger: Arc<AuditLogger>,
     pub rate_limiter: Arc<RateLimiter>,
+    /// Verifies health-v1 JWTs issued for the `rustyvault` audience (None = vault tokens only)
+    pub jwt_verifier: Option<Arc<OidcProvider>>,
 }
 
 /// Create the vault API router
@@ -133,6 +138,26 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                     auth_handlers::userpass_login(state, username, payload).await
                 }
             }
+        }))
+        // Holding the wrapping token is the credential, so unwrapping doesn't require auth
+        .route("/v1/sys/wrapping/lookup/{token}", axum::routing::get({
+            let state = state_clone.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let token = path.0;
+                async move {
+                    sys_handlers::lookup_wrapping_token(state, token).await
+                }
+            }
+        }))
+        .route("/v1/sys/wrapping/unwrap", axum::routing::post({
+            let state = state_clone.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::unwrap(state, payload).await
+                }
+            }
         }));
     
     // Protected routes (auth required)
@@ -158,10 +183,87 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        .route("/v1/sys/rotate", axum::routing::post({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::rotate_with_state(state).await
+                }
+            }
+        }))
+        .route("/v1/sys/rotate/status", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::rotate_status_with_state(state).await
+                }
+            }
+        }))
+        .route("/v1/sys/snapshot", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    sys_handlers::snapshot_with_state(state).await
+                }
+            }
+        }))
+        .route("/v1/sys/restore", axum::routing::post({
+            let state = state_clone2.clone();
+            move |headers: axum::http::HeaderMap, body: axum::body::Bytes| {
+                let state = state.clone();
+                async move {
+                    sys_handlers::restore_with_state(state, headers, body).await
+                }
+            }
+        }).layer(axum::extract::DefaultBodyLimit::max(crate::core::MAX_SNAPSHOT_SIZE)))
         
         // ============================================================
         // Secrets routes
         // ============================================================
+        // KV2-style paths over the same secrets: versions, diffs and history
+        .route("/v1/secret/data/{*path}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, query: axum::extract::Query<std::collections::HashMap<String, String>>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    secrets_handlers::read_secret_data_with_state(state, path_str, query.0).await
+                }
+            }
+        }))
+        .route("/v1/secret/data/{*path}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    secrets_handlers::write_secret_with_state(state, path_str, payload).await
+                }
+            }
+        }))
+        .route("/v1/secret/data/{*path}", axum::routing::delete({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    secrets_handlers::delete_secret_with_state(state, path_str).await
+                }
+            }
+        }))
+        .route("/v1/secret/metadata/{*path}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let path_str = path.0;
+                async move {
+                    secrets_handlers::read_secret_metadata_with_state(state, path_str).await
+                }
+            }
+        }))
         .route("/v1/secret/{*path}", axum::routing::get({
             let state = state_clone2.clone();
             move |path: axum::extract::Path<String>| {
@@ -193,6 +295,199 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
             }
         }))
         
+        // ============================================================
+        // Transit routes
+        // ============================================================
+        .route("/v1/transit/encrypt/{key_name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                let key_name = path.0;
+                async move {
+                    transit_handlers::transit_operation_with_state(state, "encrypt", key_name, payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/transit/decrypt/{key_name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                let key_name = path.0;
+                async move {
+                    transit_handlers::transit_operation_with_state(state, "decrypt", key_name, payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/transit/rotate/{key_name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                let key_name = path.0;
+                async move {
+                    transit_handlers::transit_operation_with_state(state, "rotate", key_name, payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/transit/rewrap/{key_name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                let key_name = path.0;
+                async move {
+                    transit_handlers::transit_operation_with_state(state, "rewrap", key_name, payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/transit/keys/{key_name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                let key_name = path.0;
+                async move {
+                    transit_handlers::transit_operation_with_state(state, "keys", key_name, payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/transit/keys/{key_name}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let key_name = path.0;
+                async move {
+                    transit_handlers::read_key_with_state(state, key_name).await
+                }
+            }
+        }))        
+        // ============================================================
+        // TOTP routes
+        // ============================================================
+        .route("/v1/auth/totp/create", axum::routing::post({
+            let state = state_clone2.clone();
+            move |payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                async move {
+                    totp_handlers::create_key_with_state(state, payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/auth/totp/code/{name}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let name = path.0;
+                async move {
+                    totp_handlers::generate_code_with_state(state, name).await
+                }
+            }
+        }))
+        .route("/v1/auth/totp/validate", axum::routing::post({
+            let state = state_clone2.clone();
+            move |payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                async move {
+                    totp_handlers::validate_code_with_state(state, payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/auth/totp/keys/{name}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let name = path.0;
+                async move {
+                    totp_handlers::read_key_with_state(state, name).await
+                }
+            }
+        }))
+        .route("/v1/auth/totp/keys/{name}", axum::routing::delete({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let name = path.0;
+                async move {
+                    totp_handlers::delete_key_with_state(state, name).await
+                }
+            }
+        }))
+        // ============================================================
+        // PKI routes
+        // ============================================================
+        .route("/v1/pki/root/generate", axum::routing::post({
+            let state = state_clone2.clone();
+            move |payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                async move {
+                    pki_handlers::pki_write_with_state(state, "root/generate".to_string(), payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/pki/roles/{role_name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                let role_name = path.0;
+                async move {
+                    pki_handlers::pki_write_with_state(state, format!("roles/{}", role_name), payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/pki/roles/{role_name}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let role_name = path.0;
+                async move {
+                    pki_handlers::pki_read_with_state(state, format!("roles/{}", role_name)).await
+                }
+            }
+        }))
+        .route("/v1/pki/issue/{role_name}", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                let role_name = path.0;
+                async move {
+                    pki_handlers::pki_write_with_state(state, format!("issue/{}", role_name), payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/pki/revoke", axum::routing::post({
+            let state = state_clone2.clone();
+            move |payload: Option<axum::extract::Json<serde_json::Value>>| {
+                let state = state.clone();
+                async move {
+                    pki_handlers::pki_write_with_state(state, "revoke".to_string(), payload.map(|p| p.0)).await
+                }
+            }
+        }))
+        .route("/v1/pki/ca", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    pki_handlers::pki_read_with_state(state, "ca".to_string()).await
+                }
+            }
+        }))
+        .route("/v1/pki/crl", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    pki_handlers::pki_read_with_state(state, "crl".to_string()).await
+                }
+            }
+        }))
+        .route("/v1/pki/cert/{serial}", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let serial = path.0;
+                async move {
+                    pki_handlers::pki_read_with_state(state, format!("cert/{}", serial)).await
+                }
+            }
+        }))
         // ============================================================
         // Policy routes
         // ============================================================
@@ -235,6 +530,15 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        .route("/v1/sys/policies/simulate", axum::routing::post({
+            let state = state_clone2.clone();
+            move |payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                async move {
+                    policy_handlers::simulate_policy(state, payload).await
+                }
+            }
+        }))
         .route("/v1/sys/capabilities", axum::routing::post({
             let state = state_clone2.clone();
             move |payload: axum::extract::Json<serde_json::Value>| {
@@ -312,6 +616,24 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        .route("/v1/sys/token/tidy", axum::routing::post({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    auth_handlers::tidy_tokens(state).await
+                }
+            }
+        }))
+        .route("/v1/sys/token/stats", axum::routing::get({
+            let state = state_clone2.clone();
+            move || {
+                let state = state.clone();
+                async move {
+                    auth_handlers::token_stats(state).await
+                }
+            }
+        }))
         
         // ============================================================
         // UserPass routes
@@ -407,6 +729,46 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        .route("/v1/realms/{realm_id}/suspend", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let realm_id = path.0;
+                async move {
+                    realm_handlers::suspend_realm(state, realm_id, payload).await
+                }
+            }
+        }))
+        .route("/v1/realms/{realm_id}/activate", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let realm_id = path.0;
+                async move {
+                    realm_handlers::activate_realm(state, realm_id).await
+                }
+            }
+        }))
+        .route("/v1/realms/{realm_id}/features", axum::routing::get({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>| {
+                let state = state.clone();
+                let realm_id = path.0;
+                async move {
+                    realm_handlers::get_realm_features(state, realm_id).await
+                }
+            }
+        }))
+        .route("/v1/realms/{realm_id}/features", axum::routing::put({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let realm_id = path.0;
+                async move {
+                    realm_handlers::update_realm_features(state, realm_id, payload).await
+                }
+            }
+        }))
         .route("/v1/sys/realm/organization/{organization_id}", axum::routing::get({
             let state = state_clone2.clone();
             move |path: axum::extract::Path<String>| {
@@ -481,6 +843,26 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
                 }
             }
         }))
+        .route("/v1/realm/{realm_id}/applications/{app_id}/rotate-secret", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<(String, String)>| {
+                let state = state.clone();
+                let (realm_id, app_id) = path.0;
+                async move {
+                    app_handlers::rotate_client_secret(state, realm_id, app_id).await
+                }
+            }
+        }))
+        .route("/v1/realm/{realm_id}/auth/client/login", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let realm_id = path.0;
+                async move {
+                    app_handlers::client_credentials_login(state, realm_id, payload).await
+                }
+            }
+        }))
 
         // ==========================================
         // Realm-Scoped UserPass Routes
@@ -536,6 +918,20 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
             }
         }))
 
+        // ==========================================
+        // AppRole Routes
+        // ==========================================
+        .route("/v1/auth/approle/role/{role_name}/secret-id/wrap", axum::routing::post({
+            let state = state_clone2.clone();
+            move |path: axum::extract::Path<String>, payload: axum::extract::Json<serde_json::Value>| {
+                let state = state.clone();
+                let role_name = path.0;
+                async move {
+                    approle_handlers::generate_approle_secret_id_wrapped(state, role_name, payload).await
+                }
+            }
+        }))
+
         // ==========================================
         // Realm-Scoped AppRole Routes
         // ==========================================
@@ -670,11 +1066,11 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
         // Read secret within a realm
         .route("/v1/realm/{realm_id}/secret/data/{*path}", axum::routing::get({
             let state = state_clone2.clone();
-            move |path: axum::extract::Path<(String, String)>| {
+            move |path: axum::extract::Path<(String, String)>, query: axum::extract::Query<std::collections::HashMap<String, String>>| {
                 let state = state.clone();
                 let (realm_id, secret_path) = path.0;
                 async move {
-                    secrets_handlers::read_realm_secret(state, realm_id, secret_path).await
+                    secrets_handlers::read_realm_secret(state, realm_id, secret_path, query.0).await
                 }
             }
         }))
@@ -736,5 +1132,6 @@ pub fn create_router(state: Arc<AppState>, settings: &VaultSettings) -> Router {
     Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .layer(middleware::from_fn(shared::infrastructure::tracing::tracing_middleware))
         .layer(cors_layer)
 }
@@ -23,11 +23,14 @@ async fn async_main() -> Result<(), String> {
     dotenv::dotenv().ok();
 
     // Load configuration
-    let settings = config::VaultSettings::from_env()
-        .map_err(|e| {
-            eprintln!("Failed to load configuration: {}", e);
-            format!("Failed to load configuration: {}", e)
-        })?;
+    let settings = config::VaultSettings::from_env_validated()
+        .unwrap_or_else(|errors| {
+            eprintln!("Invalid configuration:");
+            for error in &errors {
+                eprintln!("  - {}", error);
+            }
+            std::process::exit(1);
+        });
 
     // Initialize logger with settings and deployment config (single point of control for dev mode)
     shared::infrastructure::logging::init_from_settings_with_deployment(
@@ -72,7 +75,11 @@ async fn async_main() -> Result<(), String> {
     let metadata_store = Arc::new(storage::MetadataStore::new(Arc::new(pool.clone())));
     
     // Create barrier once and share it between BarrierStore and VaultCore
-    let barrier = Arc::new(storage::barrier_aes_gcm::AESGCMBarrier::new(physical_backend.clone()));
+    let nonce_counter = Arc::new(storage::nonce_counter::NonceCounter::open(
+        physical_backend.entry_path(storage::NONCE_COUNTER_PATH),
+    ).map_err(|e| format!("Failed to open nonce counter: {}", e))?);
+    let barrier = Arc::new(storage::barrier_aes_gcm::AESGCMBarrier::new(physical_backend.clone())
+        .with_nonce_counter(nonce_counter));
     let barrier_store = Arc::new(storage::BarrierStore::with_barrier(barrier.clone()));
     let storage_adapter = Arc::new(storage::StorageAdapter::new(
         metadata_store,
@@ -88,6 +95,27 @@ async fn async_main() -> Result<(), String> {
         "secret".to_string(),
     ));
     vault_core.router.add_backend("secret".to_string(), kv_backend);
+
+    // Register transit (encryption as a service) backend at "transit" mount
+    let transit_backend = Arc::new(modules::transit::TransitBackend::new(
+        barrier_store.barrier(),
+        "transit".to_string(),
+    ));
+    vault_core.router.add_backend("transit".to_string(), transit_backend);
+
+    // Register PKI (internal certificate authority) backend at "pki" mount
+    let pki_backend = Arc::new(modules::pki::PkiBackend::new(
+        barrier_store.barrier(),
+        "pki".to_string(),
+    ));
+    vault_core.router.add_backend("pki".to_string(), pki_backend);
+
+    // Register TOTP (second factor) backend at "auth/totp" mount
+    let totp_backend = Arc::new(modules::auth::TotpBackend::new(
+        barrier_store.barrier(),
+        "auth/totp",
+    ));
+    vault_core.router.add_backend("auth/totp".to_string(), totp_backend.clone());
     
     info!("Vault core initialized");
 
@@ -101,20 +129,34 @@ async fn async_main() -> Result<(), String> {
     let token_store = Arc::new(modules::auth::TokenStore::new(pool.clone()));
     info!("Token store initialized");
 
-    // Initialize UserPass backend
-    let userpass_backend = Arc::new(modules::auth::UserPassBackend::new(
+    // Delete expired and orphan tokens hourly
+    services::token_tidier::TokenTidier::new(token_store.clone())
+        .spawn()
+        .map_err(|e| format!("Failed to schedule token tidying: {}", e))?;
+    info!("Token tidying scheduled");
+
+    // Initialize UserPass backend; TOTP codes are only asked for while the totp_mfa feature is on
+    let userpass_backend = modules::auth::UserPassBackend::new(
         pool.clone(),
         "auth/userpass",
         settings.auth.bcrypt_cost,
-    ));
+    );
+    let userpass_backend = Arc::new(if settings.deployment.features.totp_mfa {
+        userpass_backend.with_totp(totp_backend)
+    } else {
+        userpass_backend
+    });
     info!("UserPass backend initialized with bcrypt cost {}", settings.auth.bcrypt_cost);
 
+    // Wrapped responses (e.g. AppRole secret IDs) wait in the cubbyhole until unwrapped
+    let response_wrapper = Arc::new(modules::wrapping::ResponseWrapper::new(barrier_store.barrier()));
+
     // Initialize AppRole backend
     let approle_backend = Arc::new(modules::auth::AppRoleBackend::new(
         pool.clone(),
         "auth/approle",
         settings.auth.bcrypt_cost,
-    ));
+    ).with_response_wrapper(response_wrapper.clone()));
     info!("AppRole backend initialized with bcrypt cost {}", settings.auth.bcrypt_cost);
 
     // Initialize Realm store
@@ -141,6 +183,23 @@ async fn async_main() -> Result<(), String> {
     ));
     info!("Rate limiter initialized (5 attempts/min, 15min lockout)");
 
+    // Accept health-v1 access tokens issued for this service when a signing secret is shared
+    let jwt_verifier = settings.auth.jwt_secret.as_ref().map(|secret| {
+        let token_manager = shared::infrastructure::oidc::TokenManager::new(
+            secret,
+            settings.auth.jwt_issuer.clone(),
+            0,
+        );
+        Arc::new(
+            shared::infrastructure::oidc::OidcProvider::new(
+                settings.auth.jwt_issuer.clone(),
+                String::new(),
+                String::new(),
+            )
+            .with_token_manager(token_manager),
+        )
+    });
+
     let app_state = Arc::new(http::routes::AppState {
         core: vault_core,
         policy_store: Some(policy_store),
@@ -149,9 +208,11 @@ async fn async_main() -> Result<(), String> {
         approle_backend: Some(approle_backend),
         realm_store: Some(realm_store),
         app_store: Some(app_store),
+        response_wrapper: Some(response_wrapper),
         key_storage,
         audit_logger,
         rate_limiter,
+        jwt_verifier,
     });
 
     // Create router - using closures to capture state
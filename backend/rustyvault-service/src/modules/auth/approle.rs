@@ -11,11 +11,13 @@ use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use super::token::{CreateTokenRequest, TokenStore};
 use crate::errors::{VaultError, VaultResult};
 use crate::logical::{Backend, Request, Response};
+use crate::modules::wrapping::{ResponseWrapper, WrappedToken};
 
 /// AppRole entry in the database
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,7 +91,7 @@ pub struct SecretIdEntry {
 }
 
 /// Response when generating a secret ID
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecretIdResponse {
     pub secret_id: String,
     pub accessor: Uuid,
@@ -114,6 +116,7 @@ pub struct AppRoleBackend {
     token_store: TokenStore,
     mount_path: String,
     bcrypt_cost: u32,
+    wrapper: Option<Arc<ResponseWrapper>>,
 }
 
 impl AppRoleBackend {
@@ -125,9 +128,16 @@ impl AppRoleBackend {
             token_store,
             mount_path: mount_path.to_string(),
             bcrypt_cost,
+            wrapper: None,
         }
     }
 
+    /// Enable response wrapping of secret IDs
+    pub fn with_response_wrapper(mut self, wrapper: Arc<ResponseWrapper>) -> Self {
+        self.wrapper = Some(wrapper);
+        self
+    }
+
     /// Create or update an AppRole
     pub async fn create_role(&self, request: &CreateAppRoleRequest) -> VaultResult<AppRoleEntry> {
         let role_name = request.role_name.to_lowercase().trim().to_string();
@@ -459,6 +469,21 @@ impl AppRoleBackend {
         })
     }
 
+    /// Generate a secret ID for a global role and return only a single-use
+    /// wrapping token for it
+    ///
+    /// The secret ID is stored in the cubbyhole until the token is unwrapped
+    /// or `ttl` passes, so it is never exposed to whoever requested it.
+    pub async fn generate_secret_id_wrapped(&self, role_name: &str, ttl: Duration) -> VaultResult<WrappedToken> {
+        let wrapper = self
+            .wrapper
+            .as_ref()
+            .ok_or_else(|| VaultError::Vault("response wrapping not enabled".to_string()))?;
+        let response = self.generate_secret_id(role_name, None, None).await?;
+        let creation_path = format!("{}/role/{}/secret-id", self.mount_path, role_name);
+        wrapper.wrap(serde_json::to_value(&response)?, ttl, &creation_path).await
+    }
+
     /// Login with role_id and secret_id
     pub async fn login(
         &self,
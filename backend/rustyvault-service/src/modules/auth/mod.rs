@@ -4,10 +4,12 @@
 //! - Token: Token-based authentication (core)
 //! - UserPass: Username/password authentication
 //! - AppRole: Application role-based authentication
+//! - TOTP: Time-based one-time passwords (second factor)
 //! - Cert: X.509 certificate authentication (planned)
 
 pub mod approle;
 pub mod token;
+pub mod totp;
 pub mod userpass;
 
 // Re-export commonly used types
@@ -15,8 +17,9 @@ pub use approle::{
     AppRoleBackend, AppRoleEntry, CreateAppRoleRequest, SecretIdResponse,
 };
 pub use token::{
-    CreateTokenRequest, TokenEntry, TokenStore,
+    CreateTokenRequest, TokenEntry, TokenStore, TokenStoreStats, TokenTidyResult,
 };
+pub use totp::TotpBackend;
 pub use userpass::{
     CreateUserRequest, UserPassBackend,
 };
@@ -120,6 +120,24 @@ pub struct CreateTokenResponse {
     pub renewable: bool,
 }
 
+/// Token counts reported by `GET /v1/sys/token/stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TokenStoreStats {
+    pub total: u64,
+    /// Tokens that have not expired (including ones that never expire)
+    pub active: u64,
+    pub expired: u64,
+    /// Tokens whose parent token no longer exists
+    pub orphan: u64,
+}
+
+/// Tokens removed by a tidy run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TokenTidyResult {
+    pub expired_deleted: u64,
+    pub orphans_deleted: u64,
+}
+
 /// Token store for managing tokens
 pub struct TokenStore {
     pool: PgPool,
@@ -137,6 +155,29 @@ impl TokenStore {
         request: &CreateTokenRequest,
         parent_token: Option<&TokenEntry>,
         path: &str,
+    ) -> VaultResult<(TokenEntry, String)> {
+        self.insert_token(request, parent_token, path, None).await
+    }
+
+    /// Create a token for a realm application's client credentials
+    ///
+    /// The token is linked to the application, so rotating its client secret
+    /// revokes it (see `revoke_for_client`).
+    pub async fn create_client_token(
+        &self,
+        request: &CreateTokenRequest,
+        app_id: Uuid,
+        path: &str,
+    ) -> VaultResult<(TokenEntry, String)> {
+        self.insert_token(request, None, path, Some(app_id)).await
+    }
+
+    async fn insert_token(
+        &self,
+        request: &CreateTokenRequest,
+        parent_token: Option<&TokenEntry>,
+        path: &str,
+        client_app_id: Option<Uuid>,
     ) -> VaultResult<(TokenEntry, String)> {
         // Generate token ID and raw token
         let id = Uuid::new_v4();
@@ -190,9 +231,10 @@ impl TokenStore {
             r#"
             INSERT INTO vault_tokens (
                 id, token_hash, display_name, policies, parent_id,
-                ttl, expires_at, created_at, num_uses, path, meta, renewable
+                ttl, expires_at, created_at, num_uses, path, meta, renewable,
+                client_app_id
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             "#,
             entry.id,
             &entry.token_hash,
@@ -205,7 +247,8 @@ impl TokenStore {
             entry.num_uses,
             &entry.path,
             entry.meta.as_ref(),
-            entry.renewable
+            entry.renewable,
+            client_app_id
         )
         .execute(&self.pool)
         .await
@@ -330,6 +373,32 @@ impl TokenStore {
         Ok(())
     }
 
+    /// Revoke every token issued to a realm application's client credentials
+    ///
+    /// Child tokens are removed with their parents. Returns the number of
+    /// client tokens revoked.
+    pub async fn revoke_for_client(&self, app_id: Uuid) -> VaultResult<u64> {
+        let result = sqlx::query!("DELETE FROM vault_tokens WHERE client_app_id = $1", app_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to revoke client tokens: {}", e)))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Revoke every token issued in a realm
+    ///
+    /// Child tokens are removed with their parents. Returns the number of
+    /// realm tokens revoked.
+    pub async fn revoke_by_realm(&self, realm_id: Uuid) -> VaultResult<u64> {
+        let result = sqlx::query!("DELETE FROM vault_tokens WHERE realm_id = $1", realm_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to revoke realm tokens: {}", e)))?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Renew a token
     pub async fn renew_token(&self, raw_token: &str, increment: Option<i64>) -> VaultResult<TokenEntry> {
         let entry = self
@@ -381,7 +450,7 @@ impl TokenStore {
     }
 
     /// Clean up expired tokens
-    pub async fn cleanup_expired_tokens(&self) -> VaultResult<u64> {
+    pub async fn cleanup_expired(&self) -> VaultResult<u64> {
         let result = sqlx::query!("DELETE FROM vault_tokens WHERE expires_at < NOW()")
             .execute(&self.pool)
             .await
@@ -389,6 +458,69 @@ impl TokenStore {
 
         Ok(result.rows_affected())
     }
+
+    /// Delete tokens whose parent token no longer exists
+    ///
+    /// Revocation normally removes children with their parent, but tokens
+    /// deleted outside of it leave their children behind. Children of
+    /// deleted orphans are orphans too, so this repeats until none are left.
+    pub async fn cleanup_orphans(&self) -> VaultResult<u64> {
+        let mut deleted = 0;
+        loop {
+            let result = sqlx::query!(
+                r#"
+                DELETE FROM vault_tokens
+                WHERE parent_id IS NOT NULL
+                  AND parent_id NOT IN (SELECT id FROM vault_tokens)
+                "#
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| VaultError::Vault(format!("failed to cleanup orphan tokens: {}", e)))?;
+
+            if result.rows_affected() == 0 {
+                return Ok(deleted);
+            }
+            deleted += result.rows_affected();
+        }
+    }
+
+    /// Delete expired tokens, then the orphans they leave behind
+    pub async fn tidy(&self) -> VaultResult<TokenTidyResult> {
+        let expired_deleted = self.cleanup_expired().await?;
+        let orphans_deleted = self.cleanup_orphans().await?;
+        Ok(TokenTidyResult {
+            expired_deleted,
+            orphans_deleted,
+        })
+    }
+
+    /// Count tokens by state
+    pub async fn stats(&self) -> VaultResult<TokenStoreStats> {
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) AS "total!",
+                COUNT(*) FILTER (WHERE t.expires_at IS NULL OR t.expires_at >= NOW()) AS "active!",
+                COUNT(*) FILTER (WHERE t.expires_at < NOW()) AS "expired!",
+                COUNT(*) FILTER (
+                    WHERE t.parent_id IS NOT NULL
+                      AND NOT EXISTS (SELECT 1 FROM vault_tokens p WHERE p.id = t.parent_id)
+                ) AS "orphan!"
+            FROM vault_tokens t
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to count tokens: {}", e)))?;
+
+        Ok(TokenStoreStats {
+            total: row.total as u64,
+            active: row.active as u64,
+            expired: row.expired as u64,
+            orphan: row.orphan as u64,
+        })
+    }
 }
 
 /// Hash a token for storage
@@ -470,5 +602,44 @@ mod tests {
         entry.expires_at = None;
         assert!(!entry.is_expired());
     }
-}
 
+    fn token_request(display_name: &str) -> CreateTokenRequest {
+        CreateTokenRequest {
+            display_name: display_name.to_string(),
+            policies: vec!["default".to_string()],
+            ttl: 3600,
+            renewable: true,
+            num_uses: 0,
+            meta: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires test database to be running
+    async fn test_cleanup_orphans_deletes_children_of_revoked_parent() {
+        let pool = shared::testing::create_test_pool().await;
+        let store = TokenStore::new(pool.clone());
+
+        let (parent, _) = store.create_token(&token_request("parent"), None, "auth/token/create").await.unwrap();
+        let (_, child_token) = store
+            .create_token(&token_request("child"), Some(&parent), "auth/token/create")
+            .await
+            .unwrap();
+        let before = store.stats().await.unwrap();
+
+        // Delete the parent without cascading to its child
+        let mut tx = pool.begin().await.unwrap();
+        sqlx::query("SET LOCAL session_replication_role = replica").execute(&mut *tx).await.unwrap();
+        sqlx::query("DELETE FROM vault_tokens WHERE id = $1").bind(parent.id).execute(&mut *tx).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let stats = store.stats().await.unwrap();
+        assert_eq!(stats.orphan, before.orphan + 1);
+        assert_eq!(stats.total, before.total - 1);
+        assert!(store.lookup_token(&child_token).await.unwrap().is_some());
+
+        assert!(store.cleanup_orphans().await.unwrap() >= 1);
+        assert!(store.lookup_token(&child_token).await.unwrap().is_none());
+        assert_eq!(store.stats().await.unwrap().orphan, 0);
+    }
+}
@@ -0,0 +1,332 @@
+//! TOTP authentication method for RustyVault
+//!
+//! Provides time-based one-time passwords (RFC 6238) as a second factor:
+//! - Key provisioning with an `otpauth://` URL for authenticator apps
+//! - Code validation with a 30-second step and ±1 step clock-skew tolerance
+//! - Replay protection through a per-key ring buffer of used codes
+//!
+//! Keys are barrier entries under `auth/totp/keys/{name}`, so their secrets
+//! are encrypted at rest and unavailable while the vault is sealed.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use async_trait::async_trait;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::sync::Mutex;
+use totp_rs::{Algorithm, TOTP};
+use zeroize::Zeroize;
+use crate::errors::{VaultError, VaultResult};
+use crate::logical::{Backend, Operation, Request, Response};
+use crate::storage::StorageBackend;
+
+const SECRET_SIZE: usize = 20;
+const DIGITS: usize = 6;
+/// Length of a time step in seconds
+const PERIOD: u64 = 30;
+/// Steps accepted either side of the current one
+const SKEW: u64 = 1;
+/// Used codes remembered per key; must cover the 2 * SKEW + 1 accepted steps
+const USED_CODES_CAPACITY: usize = 8;
+const DEFAULT_ISSUER: &str = "Health V1";
+
+/// A code accepted for a key, kept to reject its replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UsedCode {
+    step: u64,
+    code: String,
+}
+
+/// Stored TOTP key
+#[derive(Serialize, Deserialize)]
+struct TotpKeyEntry {
+    secret: Vec<u8>,
+    issuer: String,
+    account_name: String,
+    #[serde(default)]
+    used_codes: VecDeque<UsedCode>,
+}
+
+impl Drop for TotpKeyEntry {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+impl TotpKeyEntry {
+    fn totp(&self) -> VaultResult<TOTP> {
+        TOTP::new(
+            Algorithm::SHA1,
+            DIGITS,
+            0,
+            PERIOD,
+            self.secret.clone(),
+            Some(self.issuer.clone()),
+            self.account_name.clone(),
+        )
+        .map_err(|e| VaultError::Vault(format!("Invalid TOTP key: {}", e)))
+    }
+}
+
+/// Constant-time comparison so response timing does not leak matching digits
+fn codes_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// TOTP backend for second-factor authentication
+pub struct TotpBackend {
+    storage: Arc<dyn StorageBackend>,
+    mount_path: String,
+    /// Serializes validation so a code cannot be accepted twice concurrently
+    validate_lock: Mutex<()>,
+}
+
+impl TotpBackend {
+    pub fn new(storage: Arc<dyn StorageBackend>, mount_path: &str) -> Self {
+        Self {
+            storage,
+            mount_path: mount_path.to_string(),
+            validate_lock: Mutex::new(()),
+        }
+    }
+
+    fn key_path(&self, name: &str) -> String {
+        format!("{}/keys/{}", self.mount_path, name)
+    }
+
+    async fn load_key(&self, name: &str) -> VaultResult<Option<TotpKeyEntry>> {
+        match self.storage.get(&self.key_path(name)).await? {
+            Some(mut data) => {
+                let entry = serde_json::from_slice(&data);
+                data.zeroize();
+                Ok(Some(entry?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn store_key(&self, name: &str, entry: &TotpKeyEntry) -> VaultResult<()> {
+        let mut data = serde_json::to_vec(entry)?;
+        let result = self.storage.put(&self.key_path(name), &data).await;
+        data.zeroize();
+        result
+    }
+
+    /// Provision a new key, replacing any existing one
+    ///
+    /// Returns the base32 secret and an `otpauth://` URL for enrolment.
+    pub async fn create_key(&self, name: &str, issuer: Option<&str>, account_name: Option<&str>) -> VaultResult<(String, String)> {
+        let issuer = issuer.unwrap_or(DEFAULT_ISSUER);
+        let account_name = account_name.unwrap_or(name);
+        if issuer.contains(':') || account_name.contains(':') {
+            return Err(VaultError::Vault("issuer and account_name must not contain ':'".to_string()));
+        }
+
+        let mut secret = vec![0u8; SECRET_SIZE];
+        rand::thread_rng().fill_bytes(&mut secret);
+        let entry = TotpKeyEntry {
+            secret,
+            issuer: issuer.to_string(),
+            account_name: account_name.to_string(),
+            used_codes: VecDeque::new(),
+        };
+        let totp = entry.totp()?;
+        self.store_key(name, &entry).await?;
+
+        Ok((totp.get_secret_base32(), totp.get_url()))
+    }
+
+    pub async fn has_key(&self, name: &str) -> VaultResult<bool> {
+        Ok(self.storage.get(&self.key_path(name)).await?.is_some())
+    }
+
+    pub async fn delete_key(&self, name: &str) -> VaultResult<()> {
+        self.storage.delete(&self.key_path(name)).await
+    }
+
+    /// Current code for a key (for testing and support tooling)
+    pub async fn generate_code(&self, name: &str) -> VaultResult<Option<String>> {
+        match self.load_key(name).await? {
+            Some(entry) => Ok(Some(entry.totp()?.generate(unix_now()))),
+            None => Ok(None),
+        }
+    }
+
+    /// Validate a submitted code against the current time
+    pub async fn validate(&self, name: &str, code: &str) -> VaultResult<bool> {
+        self.validate_at(name, code, unix_now()).await
+    }
+
+    /// Validate a submitted code at `time` (unix seconds)
+    ///
+    /// Codes from the previous or next step are accepted to tolerate clock
+    /// skew. Each accepted code is recorded and rejected if submitted again.
+    pub async fn validate_at(&self, name: &str, code: &str, time: u64) -> VaultResult<bool> {
+        let _guard = self.validate_lock.lock().await;
+        let mut entry = self.load_key(name).await?
+            .ok_or_else(|| VaultError::Vault(format!("TOTP key {} not found", name)))?;
+        let totp = entry.totp()?;
+
+        let current = time / PERIOD;
+        let matched = (current.saturating_sub(SKEW)..=current + SKEW)
+            .find(|step| codes_match(&totp.generate(step * PERIOD), code));
+        let Some(step) = matched else {
+            return Ok(false);
+        };
+
+        if entry.used_codes.iter().any(|used| used.step == step) {
+            tracing::warn!("Rejected replayed TOTP code for key {}", name);
+            return Ok(false);
+        }
+
+        entry.used_codes.push_back(UsedCode { step, code: code.to_string() });
+        while entry.used_codes.len() > USED_CODES_CAPACITY {
+            entry.used_codes.pop_front();
+        }
+        self.store_key(name, &entry).await?;
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl Backend for TotpBackend {
+    async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
+        let path = req.path.trim_start_matches(&self.mount_path).trim_start_matches('/').to_string();
+        let parts: Vec<&str> = path.split('/').collect();
+        let data = req.data.take().unwrap_or_default();
+        let field = |key: &str| data.get(key).and_then(|v| v.as_str());
+
+        match (req.operation, parts.as_slice()) {
+            // Provision a key: POST /auth/totp/create
+            (Operation::Write, ["create"]) => {
+                let name = field("name")
+                    .ok_or_else(|| VaultError::Vault("name is required".to_string()))?;
+                let (key, url) = self.create_key(name, field("issuer"), field("account_name")).await?;
+
+                let mut response = Map::new();
+                response.insert("key".to_string(), Value::String(key));
+                response.insert("url".to_string(), Value::String(url));
+                Ok(Some(Response::new().data(response)))
+            }
+
+            // Current code: GET /auth/totp/code/:name
+            (Operation::Read, ["code", name]) => {
+                Ok(self.generate_code(name).await?.map(|code| {
+                    let mut response = Map::new();
+                    response.insert("code".to_string(), Value::String(code));
+                    Response::new().data(response)
+                }))
+            }
+
+            // Validate a code: POST /auth/totp/validate
+            (Operation::Write, ["validate"]) => {
+                let name = field("name")
+                    .ok_or_else(|| VaultError::Vault("name is required".to_string()))?;
+                let code = field("code")
+                    .ok_or_else(|| VaultError::Vault("code is required".to_string()))?;
+                let valid = self.validate(name, code).await?;
+
+                let mut response = Map::new();
+                response.insert("valid".to_string(), Value::Bool(valid));
+                Ok(Some(Response::new().data(response)))
+            }
+
+            // Key metadata: GET /auth/totp/keys/:name
+            (Operation::Read, ["keys", name]) => {
+                Ok(self.load_key(name).await?.map(|entry| {
+                    let mut response = Map::new();
+                    response.insert("issuer".to_string(), Value::String(entry.issuer.clone()));
+                    response.insert("account_name".to_string(), Value::String(entry.account_name.clone()));
+                    response.insert("digits".to_string(), Value::from(DIGITS));
+                    response.insert("period".to_string(), Value::from(PERIOD));
+                    Response::new().data(response)
+                }))
+            }
+
+            // Remove a key: DELETE /auth/totp/keys/:name
+            (Operation::Delete, ["keys", name]) => {
+                self.delete_key(name).await?;
+                Ok(Some(Response::default()))
+            }
+
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::barrier_aes_gcm::AESGCMBarrier;
+    use crate::storage::physical_file::FileBackend;
+    use crate::storage::SecurityBarrier;
+
+    async fn totp_backend(dir: &tempfile::TempDir) -> TotpBackend {
+        let backend: Arc<dyn StorageBackend> = Arc::new(FileBackend::new(dir.path()).unwrap());
+        let barrier = Arc::new(AESGCMBarrier::new(backend));
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        TotpBackend::new(barrier, "auth/totp")
+    }
+
+    async fn code_at(totp: &TotpBackend, name: &str, time: u64) -> String {
+        totp.load_key(name).await.unwrap().unwrap().totp().unwrap().generate(time)
+    }
+
+    #[tokio::test]
+    async fn test_code_replay_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let totp = totp_backend(&dir).await;
+        totp.create_key("alice", None, None).await.unwrap();
+
+        let now = 1_700_000_000;
+        let code = code_at(&totp, "alice", now).await;
+        assert!(totp.validate_at("alice", &code, now).await.unwrap());
+        assert!(!totp.validate_at("alice", &code, now).await.unwrap());
+        // Still rejected once the clock moves into the next step
+        assert!(!totp.validate_at("alice", &code, now + PERIOD).await.unwrap());
+
+        let next = code_at(&totp, "alice", now + PERIOD).await;
+        assert!(totp.validate_at("alice", &next, now + PERIOD).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_tolerance() {
+        let dir = tempfile::tempdir().unwrap();
+        let totp = totp_backend(&dir).await;
+        totp.create_key("bob", None, None).await.unwrap();
+
+        let now = 1_700_000_010;
+        let behind = code_at(&totp, "bob", now - PERIOD).await;
+        let ahead = code_at(&totp, "bob", now + PERIOD).await;
+        let stale = code_at(&totp, "bob", now - 2 * PERIOD).await;
+        let early = code_at(&totp, "bob", now + 2 * PERIOD).await;
+
+        assert!(totp.validate_at("bob", &behind, now).await.unwrap());
+        assert!(totp.validate_at("bob", &ahead, now).await.unwrap());
+        assert!(!totp.validate_at("bob", &stale, now).await.unwrap());
+        assert!(!totp.validate_at("bob", &early, now).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_create_returns_otpauth_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let totp = totp_backend(&dir).await;
+
+        let (key, url) = totp.create_key("carol", Some("Health"), Some("carol@example.com")).await.unwrap();
+        assert!(url.starts_with("otpauth://totp/"));
+        assert!(url.contains(&key));
+        assert!(totp.has_key("carol").await.unwrap());
+        assert!(!totp.has_key("dave").await.unwrap());
+    }
+}
@@ -4,6 +4,9 @@
 //! - User CRUD operations (realm-scoped)
 //! - Password hashing with bcrypt
 //! - Token issuance on successful login
+//! - Optional TOTP second factor for users with a registered key
+
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use bcrypt::{hash, verify, DEFAULT_COST};
@@ -13,6 +16,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use super::token::{CreateTokenRequest, TokenStore};
+use super::totp::TotpBackend;
 use crate::errors::{VaultError, VaultResult};
 use crate::logical::{Backend, Request, Response};
 
@@ -112,6 +116,8 @@ pub struct UserPassBackend {
     token_store: TokenStore,
     mount_path: String,
     bcrypt_cost: u32,
+    /// Second factor; users with a TOTP key must present a code at login
+    totp: Option<Arc<TotpBackend>>,
 }
 
 impl UserPassBackend {
@@ -123,6 +129,21 @@ impl UserPassBackend {
             token_store,
             mount_path: mount_path.to_string(),
             bcrypt_cost,
+            totp: None,
+        }
+    }
+
+    /// Require a TOTP code at login for users with a registered key
+    pub fn with_totp(mut self, totp: Arc<TotpBackend>) -> Self {
+        self.totp = Some(totp);
+        self
+    }
+
+    /// Name of a user's TOTP key: the username, prefixed by the realm for realm users
+    pub fn totp_key_name(username: &str, realm_id: Option<Uuid>) -> String {
+        match realm_id {
+            Some(realm_id) => format!("{}.{}", realm_id, username),
+            None => username.to_string(),
         }
     }
 
@@ -501,6 +522,17 @@ impl UserPassBackend {
 
     /// Login with username and password in a specific realm
     pub async fn login_in_realm(&self, username: &str, password: &str, realm_id: Option<Uuid>) -> VaultResult<LoginResponse> {
+        self.login_with_totp(username, password, None, realm_id).await
+    }
+
+    /// Login with username, password and, for users with a TOTP key, a TOTP code
+    pub async fn login_with_totp(
+        &self,
+        username: &str,
+        password: &str,
+        totp_code: Option<&str>,
+        realm_id: Option<Uuid>,
+    ) -> VaultResult<LoginResponse> {
         let user = self
             .get_user_in_realm(username, realm_id)
             .await?
@@ -519,6 +551,18 @@ impl UserPassBackend {
             return Err(VaultError::Vault("invalid username or password".to_string()));
         }
 
+        // Second factor, checked only after the password so it cannot be probed
+        if let Some(totp) = &self.totp {
+            let key_name = Self::totp_key_name(&user.username, user.realm_id);
+            if totp.has_key(&key_name).await? {
+                let code = totp_code
+                    .ok_or_else(|| VaultError::Vault("totp code required".to_string()))?;
+                if !totp.validate(&key_name, code).await? {
+                    return Err(VaultError::Vault("invalid totp code".to_string()));
+                }
+            }
+        }
+
         // Create token for the user
         let request = CreateTokenRequest {
             display_name: format!("userpass-{}", user.username),
@@ -698,7 +742,9 @@ impl Backend for UserPassBackend {
                     .and_then(|v| v.as_str())
                     .ok_or_else(|| VaultError::Vault("password is required".to_string()))?;
 
-                let response = self.login_in_realm(username, password, realm_id).await?;
+                let totp_code = body.get("totp").and_then(|v| v.as_str());
+
+                let response = self.login_with_totp(username, password, totp_code, realm_id).await?;
 
                 let mut data = serde_json::Map::new();
                 data.insert("client_token".to_string(), serde_json::json!(response.client_token.clone()));
@@ -4,15 +4,78 @@
 //! 
 //! Supports both global secrets and realm-scoped secrets.
 //! Realm-scoped secrets are stored under `realm-{realm_id}/` prefix.
+//!
+//! Every write is also kept under `versions/{version}/`, so earlier versions
+//! can be read with a `version` parameter and compared with `compare_to`.
+//! Reads of `metadata/{key}` return the version history without secret data.
 
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use async_trait::async_trait;
+use serde::Serialize;
 use serde_json::{Map, Value};
 use uuid::Uuid;
-use crate::errors::VaultResult;
+use crate::errors::{VaultError, VaultResult};
 use crate::logical::{Backend, Request, Response, Operation};
 use crate::storage::StorageBackend;
 
+/// Key-level difference between two versions of a secret
+///
+/// Changed keys are named without their old or new values, so a diff can be
+/// logged or returned without exposing secret material.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecretDiff {
+    pub version: u64,
+    pub compare_to: u64,
+    /// Keys present in `version` but not in `compare_to`
+    pub added: Vec<String>,
+    /// Keys present in `compare_to` but not in `version`
+    pub removed: Vec<String>,
+    /// Keys present in both whose values differ
+    pub changed: Vec<ChangedField>,
+}
+
+/// A key whose value differs between two versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChangedField {
+    pub key: String,
+}
+
+impl SecretDiff {
+    /// Compare the `data` maps of two versions
+    pub fn between(version: u64, data: &Map<String, Value>, compare_to: u64, base: &Map<String, Value>) -> Self {
+        let keys: BTreeSet<&String> = data.keys().chain(base.keys()).collect();
+        let mut diff = SecretDiff {
+            version,
+            compare_to,
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        };
+        for key in keys {
+            match (data.get(key), base.get(key)) {
+                (Some(_), None) => diff.added.push(key.clone()),
+                (None, Some(_)) => diff.removed.push(key.clone()),
+                (Some(new), Some(old)) if new != old => diff.changed.push(ChangedField { key: key.clone() }),
+                _ => {}
+            }
+        }
+        diff
+    }
+}
+
+/// Parse a version parameter given as a number or numeric string
+fn version_param(data: &Map<String, Value>, name: &str) -> VaultResult<Option<u64>> {
+    match data.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Number(n)) => n.as_u64().filter(|v| *v > 0).map(Some)
+            .ok_or_else(|| VaultError::Vault(format!("invalid {}: {}", name, n))),
+        Some(Value::String(s)) => s.parse::<u64>().ok().filter(|v| *v > 0).map(Some)
+            .ok_or_else(|| VaultError::Vault(format!("invalid {}: {}", name, s))),
+        Some(other) => Err(VaultError::Vault(format!("invalid {}: {}", name, other))),
+    }
+}
+
 /// KV secrets engine backend
 pub struct KvBackend {
     storage: Arc<dyn StorageBackend>,
@@ -48,6 +111,14 @@ impl KvBackend {
         }
     }
 
+    /// Generate storage path for one version of a secret
+    fn version_path(&self, key: &str, version: u64, realm_id: Option<Uuid>) -> String {
+        match realm_id {
+            Some(rid) => format!("{}/realm-{}/versions/{}/{}", self.mount_path, rid, version, key),
+            None => format!("{}/versions/{}/{}", self.mount_path, version, key),
+        }
+    }
+
     /// Generate list path prefix
     fn list_path_prefix(&self, realm_id: Option<Uuid>) -> String {
         match realm_id {
@@ -85,15 +156,11 @@ impl KvBackend {
         let metadata_path = self.metadata_path(key, realm_id);
 
         // Get existing version
-        let version = if let Some(meta_data) = self.storage.get(&metadata_path).await? {
-            let meta: Map<String, Value> = serde_json::from_slice(&meta_data)
-                .map_err(|e| crate::errors::VaultError::Serialization(e))?;
-            meta.get("current_version")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) + 1
-        } else {
-            1
-        };
+        let previous_metadata = self.read_metadata_map(key, realm_id).await?;
+        let version = previous_metadata.as_ref()
+            .and_then(|meta| meta.get("current_version"))
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) + 1;
 
         // Store data with version
         let mut versioned_data = Map::new();
@@ -106,11 +173,26 @@ impl KvBackend {
         let data_json = serde_json::to_vec(&versioned_data)
             .map_err(|e| crate::errors::VaultError::Serialization(e))?;
         self.storage.put(&data_path, &data_json).await?;
+        self.storage.put(&self.version_path(key, version, realm_id), &data_json).await?;
+
+        // Update metadata, carrying over the history of earlier versions
+        let created_time = Value::String(chrono::Utc::now().to_rfc3339());
+        let mut versions = previous_metadata
+            .and_then(|mut meta| meta.remove("versions"))
+            .and_then(|v| match v {
+                Value::Object(versions) => Some(versions),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let mut version_meta = Map::new();
+        version_meta.insert("created_time".to_string(), created_time.clone());
+        version_meta.insert("deletion_time".to_string(), Value::Null);
+        versions.insert(version.to_string(), Value::Object(version_meta));
 
-        // Update metadata
         let mut metadata = Map::new();
         metadata.insert("current_version".to_string(), Value::Number(version.into()));
-        metadata.insert("created_time".to_string(), Value::String(chrono::Utc::now().to_rfc3339()));
+        metadata.insert("created_time".to_string(), created_time);
+        metadata.insert("versions".to_string(), Value::Object(versions));
         if let Some(rid) = realm_id {
             metadata.insert("realm_id".to_string(), Value::String(rid.to_string()));
         }
@@ -135,8 +217,15 @@ impl KvBackend {
         if let Some(meta_data) = self.storage.get(&metadata_path).await? {
             let mut meta: Map<String, Value> = serde_json::from_slice(&meta_data)
                 .map_err(|e| crate::errors::VaultError::Serialization(e))?;
+            let deletion_time = Value::String(chrono::Utc::now().to_rfc3339());
+            let current = meta.get("current_version").and_then(|v| v.as_u64());
+            if let (Some(current), Some(Value::Object(versions))) = (current, meta.get_mut("versions")) {
+                if let Some(Value::Object(version_meta)) = versions.get_mut(&current.to_string()) {
+                    version_meta.insert("deletion_time".to_string(), deletion_time.clone());
+                }
+            }
             meta.insert("deleted".to_string(), Value::Bool(true));
-            meta.insert("deletion_time".to_string(), Value::String(chrono::Utc::now().to_rfc3339()));
+            meta.insert("deletion_time".to_string(), deletion_time);
             
             let meta_json = serde_json::to_vec(&meta)
                 .map_err(|e| crate::errors::VaultError::Serialization(e))?;
@@ -146,6 +235,144 @@ impl KvBackend {
         Ok(None)
     }
 
+    /// Permanently remove a secret, all its versions and its metadata
+    ///
+    /// Unlike a delete, nothing stays readable afterwards.
+    pub async fn destroy_secret(&self, key: &str, realm_id: Option<Uuid>) -> VaultResult<()> {
+        if let Some(meta) = self.read_metadata_map(key, realm_id).await? {
+            if let Some(Value::Object(versions)) = meta.get("versions") {
+                for version in versions.keys().filter_map(|v| v.parse::<u64>().ok()) {
+                    self.storage.delete(&self.version_path(key, version, realm_id)).await?;
+                }
+            }
+        }
+        self.storage.delete(&self.storage_path(key, realm_id)).await?;
+        self.storage.delete(&self.metadata_path(key, realm_id)).await
+    }
+
+    async fn read_metadata_map(&self, key: &str, realm_id: Option<Uuid>) -> VaultResult<Option<Map<String, Value>>> {
+        match self.storage.get(&self.metadata_path(key, realm_id)).await? {
+            Some(meta_data) => Ok(Some(serde_json::from_slice(&meta_data)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Stored entry (`data`, `version`, ...) for one version of a secret
+    async fn read_version_entry(&self, key: &str, version: u64, realm_id: Option<Uuid>) -> VaultResult<Option<Map<String, Value>>> {
+        if let Some(entry) = self.storage.get(&self.version_path(key, version, realm_id)).await? {
+            return Ok(Some(serde_json::from_slice(&entry)?));
+        }
+
+        // Secrets written before versions were kept only have their latest copy
+        let Some(entry) = self.storage.get(&self.storage_path(key, realm_id)).await? else {
+            return Ok(None);
+        };
+        let entry: Map<String, Value> = serde_json::from_slice(&entry)?;
+        let latest = entry.get("version").and_then(|v| v.as_u64());
+        Ok((latest == Some(version)).then_some(entry))
+    }
+
+    async fn read_secret_version(&self, key: &str, version: u64, realm_id: Option<Uuid>) -> VaultResult<Option<Response>> {
+        let Some(mut entry) = self.read_version_entry(key, version, realm_id).await? else {
+            return Ok(None);
+        };
+        if let Some(rid) = realm_id {
+            entry.insert("realm_id".to_string(), Value::String(rid.to_string()));
+        }
+        Ok(Some(Response::new().data(entry)))
+    }
+
+    /// Diff the `data` of two versions; None if either version does not exist
+    pub async fn diff_versions(&self, key: &str, version: u64, compare_to: u64, realm_id: Option<Uuid>) -> VaultResult<Option<SecretDiff>> {
+        let data_of = |entry: Map<String, Value>| match entry.get("data") {
+            Some(Value::Object(data)) => data.clone(),
+            _ => Map::new(),
+        };
+        let Some(entry) = self.read_version_entry(key, version, realm_id).await? else {
+            return Ok(None);
+        };
+        let Some(base) = self.read_version_entry(key, compare_to, realm_id).await? else {
+            return Ok(None);
+        };
+        Ok(Some(SecretDiff::between(version, &data_of(entry), compare_to, &data_of(base))))
+    }
+
+    /// Version history of a secret: numbers, creation times and deletion status only
+    async fn read_metadata(&self, key: &str, realm_id: Option<Uuid>) -> VaultResult<Option<Response>> {
+        let Some(meta) = self.read_metadata_map(key, realm_id).await? else {
+            return Ok(None);
+        };
+        let current_version = meta.get("current_version").and_then(|v| v.as_u64()).unwrap_or(0);
+
+        let mut versions = Vec::new();
+        match meta.get("versions") {
+            Some(Value::Object(history)) => {
+                let mut numbered: Vec<(u64, &Value)> = history.iter()
+                    .filter_map(|(n, v)| n.parse::<u64>().ok().map(|n| (n, v)))
+                    .collect();
+                numbered.sort_by_key(|(n, _)| *n);
+                for (number, version_meta) in numbered {
+                    let deletion_time = version_meta.get("deletion_time").cloned().unwrap_or(Value::Null);
+                    versions.push(serde_json::json!({
+                        "version": number,
+                        "created_time": version_meta.get("created_time").cloned().unwrap_or(Value::Null),
+                        "deletion_time": deletion_time,
+                        "deleted": !deletion_time.is_null(),
+                    }));
+                }
+            }
+            // Written before version history was recorded: only the latest is known
+            _ if current_version > 0 => versions.push(serde_json::json!({
+                "version": current_version,
+                "created_time": meta.get("created_time").cloned().unwrap_or(Value::Null),
+                "deletion_time": meta.get("deletion_time").cloned().unwrap_or(Value::Null),
+                "deleted": meta.get("deleted").and_then(|v| v.as_bool()).unwrap_or(false),
+            })),
+            _ => {}
+        }
+
+        let mut data = Map::new();
+        data.insert("current_version".to_string(), Value::Number(current_version.into()));
+        data.insert("created_time".to_string(), meta.get("created_time").cloned().unwrap_or(Value::Null));
+        data.insert("deleted".to_string(), Value::Bool(meta.get("deleted").and_then(|v| v.as_bool()).unwrap_or(false)));
+        data.insert("versions".to_string(), Value::Array(versions));
+        if let Some(rid) = realm_id {
+            data.insert("realm_id".to_string(), Value::String(rid.to_string()));
+        }
+        Ok(Some(Response::new().data(data)))
+    }
+
+    /// Read the latest version, a specific `version`, or a diff against `compare_to`
+    async fn read_secret_with_params(&self, key: &str, params: Option<Map<String, Value>>, realm_id: Option<Uuid>) -> VaultResult<Option<Response>> {
+        let params = params.unwrap_or_default();
+        let version = version_param(&params, "version")?;
+        let compare_to = version_param(&params, "compare_to")?;
+
+        match (version, compare_to) {
+            (None, None) => self.read_secret(key, realm_id).await,
+            (Some(version), None) => self.read_secret_version(key, version, realm_id).await,
+            (version, Some(compare_to)) => {
+                // Without an explicit version, compare the latest one
+                let version = match version {
+                    Some(version) => version,
+                    None => match self.read_metadata_map(key, realm_id).await?
+                        .and_then(|meta| meta.get("current_version").and_then(|v| v.as_u64()))
+                    {
+                        Some(current) => current,
+                        None => return Ok(None),
+                    },
+                };
+                let Some(diff) = self.diff_versions(key, version, compare_to, realm_id).await? else {
+                    return Ok(None);
+                };
+                match serde_json::to_value(diff)? {
+                    Value::Object(data) => Ok(Some(Response::new().data(data))),
+                    _ => Ok(None),
+                }
+            }
+        }
+    }
+
     async fn list_secrets(&self, prefix: &str, realm_id: Option<Uuid>) -> VaultResult<Option<Response>> {
         let list_prefix = self.list_path_prefix(realm_id);
         let list_path = format!("{}{}", list_prefix, prefix);
@@ -218,7 +445,10 @@ impl Backend for KvBackend {
         };
 
         match req.operation {
-            Operation::Read => self.read_secret(&key, realm_id).await,
+            Operation::Read => match key.strip_prefix("metadata/") {
+                Some(secret_key) => self.read_metadata(secret_key, realm_id).await,
+                None => self.read_secret_with_params(&key, req.data.take(), realm_id).await,
+            },
             Operation::Write => {
                 let data = req.data.take();
                 self.write_secret(&key, data.unwrap_or_default(), realm_id).await
@@ -229,3 +459,96 @@ impl Backend for KvBackend {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::storage::barrier_aes_gcm::AESGCMBarrier;
+    use crate::storage::physical_file::FileBackend;
+    use crate::storage::SecurityBarrier;
+
+    async fn kv_backend(dir: &tempfile::TempDir) -> KvBackend {
+        let backend: Arc<dyn StorageBackend> = Arc::new(FileBackend::new(dir.path()).unwrap());
+        let barrier = Arc::new(AESGCMBarrier::new(backend));
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        KvBackend::new(barrier, "secret".to_string())
+    }
+
+    async fn write(kv: &KvBackend, data: Value) {
+        let mut req = Request::new_write_request("secret/app/db", data.as_object().cloned());
+        kv.handle_request(&mut req).await.unwrap();
+    }
+
+    async fn read(kv: &KvBackend, path: &str, params: Value) -> Option<Map<String, Value>> {
+        let mut req = Request::new_read_request(path);
+        req.data = params.as_object().cloned();
+        kv.handle_request(&mut req).await.unwrap().and_then(|resp| resp.data)
+    }
+
+    fn keys(value: &Value) -> Vec<String> {
+        value.as_array().unwrap().iter()
+            .map(|v| v.get("key").unwrap_or(v).as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_diff_across_three_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let kv = kv_backend(&dir).await;
+
+        write(&kv, json!({"host": "db.internal", "password": "one"})).await;
+        // v2 adds a key and modifies a value
+        write(&kv, json!({"host": "db.internal", "password": "two", "port": 5432})).await;
+        // v3 removes a key
+        write(&kv, json!({"password": "two", "port": 5432})).await;
+
+        let diff = read(&kv, "secret/app/db", json!({"version": 2, "compare_to": 1})).await.unwrap();
+        assert_eq!(keys(&diff["added"]), vec!["port"]);
+        assert!(keys(&diff["removed"]).is_empty());
+        assert_eq!(keys(&diff["changed"]), vec!["password"]);
+        // Only key names are returned, never values
+        let serialized = serde_json::to_string(&diff).unwrap();
+        assert!(!serialized.contains("one") && !serialized.contains("two"));
+
+        let diff = read(&kv, "secret/app/db", json!({"version": "3", "compare_to": "2"})).await.unwrap();
+        assert!(keys(&diff["added"]).is_empty());
+        assert_eq!(keys(&diff["removed"]), vec!["host"]);
+        assert!(keys(&diff["changed"]).is_empty());
+
+        // Latest version against the first
+        let diff = read(&kv, "secret/app/db", json!({"compare_to": 1})).await.unwrap();
+        assert_eq!(diff["version"], 3);
+        assert_eq!(keys(&diff["added"]), vec!["port"]);
+        assert_eq!(keys(&diff["removed"]), vec!["host"]);
+        assert_eq!(keys(&diff["changed"]), vec!["password"]);
+
+        // Earlier versions stay readable, unknown ones are not found
+        let v1 = read(&kv, "secret/app/db", json!({"version": 1})).await.unwrap();
+        assert_eq!(v1["data"]["password"], "one");
+        assert!(read(&kv, "secret/app/db", json!({"version": 4, "compare_to": 1})).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_metadata_lists_versions_without_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let kv = kv_backend(&dir).await;
+
+        write(&kv, json!({"password": "one"})).await;
+        write(&kv, json!({"password": "two"})).await;
+        let mut req = Request::new_delete_request("secret/app/db", None);
+        kv.handle_request(&mut req).await.unwrap();
+
+        let meta = read(&kv, "secret/metadata/app/db", Value::Null).await.unwrap();
+        assert_eq!(meta["current_version"], 2);
+        assert_eq!(meta["deleted"], true);
+        let versions = meta["versions"].as_array().unwrap();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions[0]["version"], 1);
+        assert_eq!(versions[0]["deleted"], false);
+        assert_eq!(versions[1]["deleted"], true);
+        assert!(!serde_json::to_string(&meta).unwrap().contains("one"));
+    }
+}
@@ -5,4 +5,6 @@ pub mod pki;
 pub mod auth;
 pub mod policy;
 pub mod realm;
+pub mod transit;
+pub mod wrapping;
 
@@ -1,27 +1,676 @@
-//! PKI (Public Key Infrastructure) module
+//! PKI (Public Key Infrastructure) secrets engine module
 //!
-//! Placeholder for PKI module adaptation from RustyVault
+//! Internal certificate authority for service-to-service mTLS, following the
+//! HashiCorp Vault PKI API. The root CA (RSA-4096 or ECDSA P-256), roles,
+//! issued certificates and the CRL are all barrier entries under the mount:
+//!
+//! - `{mount}/ca` - root certificate and its private key
+//! - `{mount}/roles/{name}` - issuing constraints (key type, TTLs, domains)
+//! - `{mount}/certs/{serial}` - index of every issued certificate
+//! - `{mount}/revoked` - revoked serials, the source of the CRL
+//! - `{mount}/crl` - current signed CRL, rebuilt on every revocation
 
+use std::sync::Arc;
 use async_trait::async_trait;
-use crate::errors::VaultResult;
-use crate::logical::{Backend, Request, Response};
+use rand::RngCore;
+use rcgen::{
+    BasicConstraints, CertificateParams, CertificateRevocationListParams, DistinguishedName,
+    DnType, ExtendedKeyUsagePurpose, IsCa, KeyIdMethod, KeyPair, KeyUsagePurpose,
+    RevokedCertParams, SerialNumber, PKCS_ECDSA_P256_SHA256, PKCS_RSA_SHA256,
+};
+use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use time::{Duration, OffsetDateTime};
+use tokio::sync::Mutex;
+use zeroize::Zeroize;
+use crate::errors::{VaultError, VaultResult};
+use crate::logical::{Backend, Operation, Request, Response};
+use crate::storage::StorageBackend;
+
+/// Root CA lifetime when `ttl` is not given (10 years)
+const DEFAULT_ROOT_TTL_SECS: i64 = 10 * 365 * 24 * 3600;
+/// Role `max_ttl` when not configured (30 days)
+const DEFAULT_MAX_TTL_SECS: i64 = 30 * 24 * 3600;
+/// Role `ttl` when not configured (72 hours)
+const DEFAULT_TTL_SECS: i64 = 72 * 3600;
+/// How long a CRL stays valid before it is rebuilt on read
+const CRL_LIFETIME_SECS: i64 = 72 * 3600;
+const SERIAL_BYTES: usize = 16;
+
+/// Private key algorithm for a CA or role
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyType {
+    Rsa,
+    #[default]
+    Ec,
+}
+
+impl KeyType {
+    pub fn parse(s: &str) -> VaultResult<Self> {
+        match s {
+            "rsa" => Ok(KeyType::Rsa),
+            "ec" => Ok(KeyType::Ec),
+            _ => Err(VaultError::Vault(format!("Unsupported key type: {}. Expected 'rsa' or 'ec'", s))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyType::Rsa => "rsa",
+            KeyType::Ec => "ec",
+        }
+    }
+}
+
+/// Generate a private key: RSA with `rsa_bits`, or ECDSA P-256
+fn generate_key(key_type: KeyType, rsa_bits: usize) -> VaultResult<KeyPair> {
+    match key_type {
+        KeyType::Ec => KeyPair::generate_for(&PKCS_ECDSA_P256_SHA256)
+            .map_err(|e| VaultError::Vault(format!("Key generation failed: {}", e))),
+        KeyType::Rsa => {
+            // ring cannot generate RSA keys, so generate with `rsa` and import
+            let key = rsa::RsaPrivateKey::new(&mut rand::thread_rng(), rsa_bits)
+                .map_err(|e| VaultError::Vault(format!("Key generation failed: {}", e)))?;
+            let pem = key.to_pkcs8_pem(LineEnding::LF)
+                .map_err(|e| VaultError::Vault(format!("Key encoding failed: {}", e)))?;
+            load_key(KeyType::Rsa, &pem)
+        }
+    }
+}
+
+fn load_key(key_type: KeyType, pem: &str) -> VaultResult<KeyPair> {
+    let result = match key_type {
+        KeyType::Rsa => KeyPair::from_pem_and_sign_algo(pem, &PKCS_RSA_SHA256),
+        KeyType::Ec => KeyPair::from_pem_and_sign_algo(pem, &PKCS_ECDSA_P256_SHA256),
+    };
+    result.map_err(|e| VaultError::Vault(format!("Invalid private key: {}", e)))
+}
+
+/// Random positive serial, formatted `xx:xx:...` like Vault
+fn generate_serial() -> String {
+    let mut bytes = [0u8; SERIAL_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    // Positive and without a leading zero byte, so DER keeps every byte
+    bytes[0] = (bytes[0] & 0x7f) | 0x01;
+    format_serial(&bytes)
+}
+
+fn format_serial(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":")
+}
+
+fn parse_serial(serial: &str) -> VaultResult<SerialNumber> {
+    let bytes = serial
+        .split([':', '-'])
+        .map(|part| u8::from_str_radix(part, 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| VaultError::Vault(format!("Invalid serial number: {}", serial)))?;
+    Ok(SerialNumber::from(bytes))
+}
+
+/// Parse a TTL like `72h`, `30m`, `3600s`, `30d` or plain seconds
+fn parse_ttl(value: &Value) -> VaultResult<i64> {
+    let invalid = || VaultError::Vault(format!("Invalid TTL: {}", value));
+    if let Some(secs) = value.as_i64() {
+        return if secs > 0 { Ok(secs) } else { Err(invalid()) };
+    }
+    let s = value.as_str().ok_or_else(invalid)?.trim();
+    let (number, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(idx) => s.split_at(idx),
+        None => (s, "s"),
+    };
+    let number: i64 = number.parse().map_err(|_| invalid())?;
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 24 * 3600,
+        _ => return Err(invalid()),
+    };
+    match number.checked_mul(multiplier) {
+        Some(secs) if secs > 0 => Ok(secs),
+        _ => Err(invalid()),
+    }
+}
+
+fn timestamp(secs: i64) -> VaultResult<OffsetDateTime> {
+    OffsetDateTime::from_unix_timestamp(secs)
+        .map_err(|e| VaultError::Vault(format!("Invalid timestamp: {}", e)))
+}
+
+/// Root certificate with its private key
+#[derive(Serialize, Deserialize)]
+struct CaEntry {
+    key_type: KeyType,
+    key_pem: String,
+    common_name: String,
+    serial_number: String,
+    not_before: i64,
+    not_after: i64,
+    certificate: String,
+}
+
+impl Drop for CaEntry {
+    fn drop(&mut self) {
+        self.key_pem.zeroize();
+    }
+}
 
-/// PKI backend
+impl CaEntry {
+    fn params(&self) -> VaultResult<CertificateParams> {
+        let mut params = CertificateParams::default();
+        let mut dn = DistinguishedName::new();
+        dn.push(DnType::CommonName, self.common_name.as_str());
+        params.distinguished_name = dn;
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        params.key_usages = vec![
+            KeyUsagePurpose::KeyCertSign,
+            KeyUsagePurpose::CrlSign,
+            KeyUsagePurpose::DigitalSignature,
+        ];
+        params.serial_number = Some(parse_serial(&self.serial_number)?);
+        params.not_before = timestamp(self.not_before)?;
+        params.not_after = timestamp(self.not_after)?;
+        Ok(params)
+    }
+
+    /// Issuer certificate and key for signing
+    ///
+    /// Re-signing the stored parameters gives the same subject and key
+    /// identifier as the stored certificate, which is all signing needs.
+    fn issuer(&self) -> VaultResult<(rcgen::Certificate, KeyPair)> {
+        let key = load_key(self.key_type, &self.key_pem)?;
+        let cert = self.params()?.self_signed(&key)
+            .map_err(|e| VaultError::Vault(format!("Failed to load CA: {}", e)))?;
+        Ok((cert, key))
+    }
+}
+
+/// Constraints for certificates issued under a role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PkiRole {
+    key_type: KeyType,
+    key_bits: usize,
+    /// Default TTL in seconds
+    ttl: i64,
+    /// Upper bound for requested TTLs in seconds
+    max_ttl: i64,
+    /// Allowed common names; empty allows any
+    allowed_domains: Vec<String>,
+    allow_subdomains: bool,
+}
+
+impl PkiRole {
+    fn from_request(data: &Map<String, Value>) -> VaultResult<Self> {
+        let key_type = match data.get("key_type").and_then(|v| v.as_str()) {
+            Some(key_type) => KeyType::parse(key_type)?,
+            None => KeyType::default(),
+        };
+        let key_bits = data.get("key_bits").and_then(|v| v.as_u64()).unwrap_or(2048) as usize;
+        if key_type == KeyType::Rsa && ![2048, 3072, 4096].contains(&key_bits) {
+            return Err(VaultError::Vault(format!("Unsupported RSA key size: {}", key_bits)));
+        }
+        let max_ttl = data.get("max_ttl").map(parse_ttl).transpose()?.unwrap_or(DEFAULT_MAX_TTL_SECS);
+        let ttl = data.get("ttl").map(parse_ttl).transpose()?.unwrap_or(DEFAULT_TTL_SECS).min(max_ttl);
+        let allowed_domains = match data.get("allowed_domains") {
+            Some(Value::String(domains)) => domains.split(',').map(|d| d.trim().to_string()).filter(|d| !d.is_empty()).collect(),
+            Some(Value::Array(domains)) => domains.iter().filter_map(|d| d.as_str().map(String::from)).collect(),
+            _ => Vec::new(),
+        };
+        let allow_subdomains = data.get("allow_subdomains").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        Ok(Self {
+            key_type,
+            key_bits,
+            ttl,
+            max_ttl,
+            allowed_domains,
+            allow_subdomains,
+        })
+    }
+
+    fn allows(&self, name: &str) -> bool {
+        self.allowed_domains.is_empty()
+            || self.allowed_domains.iter().any(|domain| {
+                name == domain || (self.allow_subdomains && name.ends_with(&format!(".{}", domain)))
+            })
+    }
+
+    fn to_map(&self, name: &str) -> Map<String, Value> {
+        let mut data = Map::new();
+        data.insert("name".to_string(), Value::String(name.to_string()));
+        data.insert("key_type".to_string(), Value::String(self.key_type.as_str().to_string()));
+        data.insert("key_bits".to_string(), Value::Number(self.key_bits.into()));
+        data.insert("ttl".to_string(), Value::Number(self.ttl.into()));
+        data.insert("max_ttl".to_string(), Value::Number(self.max_ttl.into()));
+        data.insert("allowed_domains".to_string(), Value::Array(
+            self.allowed_domains.iter().map(|d| Value::String(d.clone())).collect()
+        ));
+        data.insert("allow_subdomains".to_string(), Value::Bool(self.allow_subdomains));
+        data
+    }
+}
+
+/// Index entry for an issued certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IssuedCert {
+    serial_number: String,
+    common_name: String,
+    not_after: i64,
+    certificate: String,
+    revoked_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RevokedEntry {
+    serial_number: String,
+    revoked_at: i64,
+}
+
+/// Revoked serials plus the number of the last CRL built from them
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RevocationIndex {
+    crl_number: u64,
+    entries: Vec<RevokedEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CrlEntry {
+    crl: String,
+    next_update: i64,
+}
+
+/// PKI secrets engine backend
 pub struct PkiBackend {
-    // TODO: Add PKI-specific fields
+    storage: Arc<dyn StorageBackend>,
+    mount_path: String,
+    /// Serializes CA creation, revocation and CRL rebuilds
+    write_lock: Mutex<()>,
 }
 
 impl PkiBackend {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(storage: Arc<dyn StorageBackend>, mount_path: String) -> Self {
+        Self {
+            storage,
+            mount_path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn path(&self, key: &str) -> String {
+        format!("{}/{}", self.mount_path, key)
+    }
+
+    async fn load<T: serde::de::DeserializeOwned>(&self, key: &str) -> VaultResult<Option<T>> {
+        match self.storage.get(&self.path(key)).await? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn store<T: Serialize>(&self, key: &str, value: &T) -> VaultResult<()> {
+        let mut data = serde_json::to_vec(value)?;
+        let result = self.storage.put(&self.path(key), &data).await;
+        data.zeroize();
+        result
+    }
+
+    async fn require_ca(&self) -> VaultResult<CaEntry> {
+        self.load("ca").await?
+            .ok_or_else(|| VaultError::Vault("No root CA has been generated".to_string()))
     }
+
+    // ==========================================
+    // Operations
+    // ==========================================
+
+    /// Generate the root CA; fails if one already exists
+    pub async fn generate_root(&self, data: &Map<String, Value>) -> VaultResult<Map<String, Value>> {
+        let common_name = data.get("common_name").and_then(|v| v.as_str())
+            .ok_or_else(|| VaultError::Vault("Missing required field: common_name".to_string()))?;
+        let key_type = match data.get("key_type").and_then(|v| v.as_str()) {
+            Some(key_type) => KeyType::parse(key_type)?,
+            None => KeyType::default(),
+        };
+        let ttl = data.get("ttl").map(parse_ttl).transpose()?.unwrap_or(DEFAULT_ROOT_TTL_SECS);
+
+        let _guard = self.write_lock.lock().await;
+        if self.load::<CaEntry>("ca").await?.is_some() {
+            return Err(VaultError::Vault("Root CA already exists".to_string()));
+        }
+
+        let key = generate_key(key_type, 4096)?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let mut ca = CaEntry {
+            key_type,
+            key_pem: key.serialize_pem(),
+            common_name: common_name.to_string(),
+            serial_number: generate_serial(),
+            not_before: now,
+            not_after: now + ttl,
+            certificate: String::new(),
+        };
+        let cert = ca.params()?.self_signed(&key)
+            .map_err(|e| VaultError::Vault(format!("Failed to generate root CA: {}", e)))?;
+        ca.certificate = cert.pem();
+        self.store("ca", &ca).await?;
+
+        // Publish an empty CRL straight away
+        self.rebuild_crl(&ca).await?;
+
+        let mut response = Map::new();
+        response.insert("certificate".to_string(), Value::String(ca.certificate.clone()));
+        response.insert("issuing_ca".to_string(), Value::String(ca.certificate.clone()));
+        response.insert("serial_number".to_string(), Value::String(ca.serial_number.clone()));
+        response.insert("expiration".to_string(), Value::Number(ca.not_after.into()));
+        Ok(response)
+    }
+
+    pub async fn write_role(&self, name: &str, data: &Map<String, Value>) -> VaultResult<Map<String, Value>> {
+        let role = PkiRole::from_request(data)?;
+        self.store(&format!("roles/{}", name), &role).await?;
+        Ok(role.to_map(name))
+    }
+
+    pub async fn read_role(&self, name: &str) -> VaultResult<Option<Map<String, Value>>> {
+        let role: Option<PkiRole> = self.load(&format!("roles/{}", name)).await?;
+        Ok(role.map(|role| role.to_map(name)))
+    }
+
+    /// Issue a leaf certificate under `role_name`, returning its private key
+    pub async fn issue(&self, role_name: &str, data: &Map<String, Value>) -> VaultResult<Map<String, Value>> {
+        let role: PkiRole = self.load(&format!("roles/{}", role_name)).await?
+            .ok_or_else(|| VaultError::Vault(format!("Role {} not found", role_name)))?;
+        let ca = self.require_ca().await?;
+
+        let common_name = data.get("common_name").and_then(|v| v.as_str())
+            .ok_or_else(|| VaultError::Vault("Missing required field: common_name".to_string()))?;
+        let alt_names: Vec<String> = match data.get("alt_names") {
+            Some(Value::String(names)) => names.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect(),
+            Some(Value::Array(names)) => names.iter().filter_map(|n| n.as_str().map(String::from)).collect(),
+            _ => Vec::new(),
+        };
+        for name in std::iter::once(common_name).chain(alt_names.iter().map(String::as_str)) {
+            if !role.allows(name) {
+                return Err(VaultError::Vault(format!("{} is not allowed by role {}", name, role_name)));
+            }
+        }
+
+        // Requested TTL is capped by the role and by the CA's own expiry
+        let requested = data.get("ttl").map(parse_ttl).transpose()?.unwrap_or(role.ttl);
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let not_after = (now + requested.min(role.max_ttl)).min(ca.not_after);
+
+        let mut sans = vec![common_name.to_string()];
+        sans.extend(alt_names.into_iter().filter(|n| n != common_name));
+        let mut params = CertificateParams::new(sans)
+            .map_err(|e| VaultError::Vault(format!("Invalid subject names: {}", e)))?;
+        let mut dn = DistinguishedName::new();
+        dn.push(DnType::CommonName, common_name);
+        params.distinguished_name = dn;
+        params.key_usages = vec![KeyUsagePurpose::DigitalSignature, KeyUsagePurpose::KeyEncipherment];
+        params.extended_key_usages = vec![
+            ExtendedKeyUsagePurpose::ServerAuth,
+            ExtendedKeyUsagePurpose::ClientAuth,
+        ];
+        params.use_authority_key_identifier_extension = true;
+        let serial_number = generate_serial();
+        params.serial_number = Some(parse_serial(&serial_number)?);
+        params.not_before = timestamp(now)?;
+        params.not_after = timestamp(not_after)?;
+
+        let leaf_key = generate_key(role.key_type, role.key_bits)?;
+        let (issuer, issuer_key) = ca.issuer()?;
+        let cert = params.signed_by(&leaf_key, &issuer, &issuer_key)
+            .map_err(|e| VaultError::Vault(format!("Failed to issue certificate: {}", e)))?;
+
+        let issued = IssuedCert {
+            serial_number: serial_number.clone(),
+            common_name: common_name.to_string(),
+            not_after,
+            certificate: cert.pem(),
+            revoked_at: None,
+        };
+        self.store(&format!("certs/{}", serial_number.replace(':', "-")), &issued).await?;
+
+        let mut response = Map::new();
+        response.insert("certificate".to_string(), Value::String(issued.certificate));
+        response.insert("issuing_ca".to_string(), Value::String(ca.certificate.clone()));
+        response.insert("private_key".to_string(), Value::String(leaf_key.serialize_pem()));
+        response.insert("private_key_type".to_string(), Value::String(role.key_type.as_str().to_string()));
+        response.insert("serial_number".to_string(), Value::String(serial_number));
+        response.insert("expiration".to_string(), Value::Number(not_after.into()));
+        Ok(response)
+    }
+
+    /// Revoke an issued certificate and publish a new CRL
+    pub async fn revoke(&self, serial_number: &str) -> VaultResult<Map<String, Value>> {
+        let serial_number = format_serial(&parse_serial(serial_number)?.to_bytes());
+        let cert_key = format!("certs/{}", serial_number.replace(':', "-"));
+
+        let _guard = self.write_lock.lock().await;
+        let mut issued: IssuedCert = self.load(&cert_key).await?
+            .ok_or_else(|| VaultError::Vault(format!("Certificate {} not found", serial_number)))?;
+
+        let revoked_at = match issued.revoked_at {
+            // Revoking twice is a no-op
+            Some(revoked_at) => revoked_at,
+            None => {
+                let revoked_at = OffsetDateTime::now_utc().unix_timestamp();
+                issued.revoked_at = Some(revoked_at);
+                self.store(&cert_key, &issued).await?;
+
+                let mut index: RevocationIndex = self.load("revoked").await?.unwrap_or_default();
+                index.entries.push(RevokedEntry {
+                    serial_number: serial_number.clone(),
+                    revoked_at,
+                });
+                self.store("revoked", &index).await?;
+
+                let ca = self.require_ca().await?;
+                self.rebuild_crl(&ca).await?;
+                revoked_at
+            }
+        };
+
+        let mut response = Map::new();
+        response.insert("revocation_time".to_string(), Value::Number(revoked_at.into()));
+        Ok(response)
+    }
+
+    /// Sign a new CRL listing every revoked serial
+    async fn rebuild_crl(&self, ca: &CaEntry) -> VaultResult<String> {
+        let mut index: RevocationIndex = self.load("revoked").await?.unwrap_or_default();
+        index.crl_number += 1;
+
+        let revoked_certs = index.entries.iter()
+            .map(|entry| {
+                Ok(RevokedCertParams {
+                    serial_number: parse_serial(&entry.serial_number)?,
+                    revocation_time: timestamp(entry.revoked_at)?,
+                    reason_code: None,
+                    invalidity_date: None,
+                })
+            })
+            .collect::<VaultResult<Vec<_>>>()?;
+
+        let now = OffsetDateTime::now_utc();
+        let next_update = now + Duration::seconds(CRL_LIFETIME_SECS);
+        let params = CertificateRevocationListParams {
+            this_update: now,
+            next_update,
+            crl_number: SerialNumber::from(index.crl_number.to_be_bytes().to_vec()),
+            issuing_distribution_point: None,
+            revoked_certs,
+            key_identifier_method: KeyIdMethod::Sha256,
+        };
+        let (issuer, issuer_key) = ca.issuer()?;
+        let crl = params.signed_by(&issuer, &issuer_key)
+            .map_err(|e| VaultError::Vault(format!("Failed to sign CRL: {}", e)))?
+            .pem()
+            .map_err(|e| VaultError::Vault(format!("Failed to encode CRL: {}", e)))?;
+
+        self.store("revoked", &index).await?;
+        self.store("crl", &CrlEntry { crl: crl.clone(), next_update: next_update.unix_timestamp() }).await?;
+        Ok(crl)
+    }
+
+    /// Current CRL in PEM form, rebuilt if it has expired
+    pub async fn crl(&self) -> VaultResult<String> {
+        if let Some(entry) = self.load::<CrlEntry>("crl").await? {
+            if entry.next_update > OffsetDateTime::now_utc().unix_timestamp() {
+                return Ok(entry.crl);
+            }
+        }
+
+        let _guard = self.write_lock.lock().await;
+        let ca = self.require_ca().await?;
+        self.rebuild_crl(&ca).await
+    }
+
+    pub async fn read_ca(&self) -> VaultResult<Option<String>> {
+        Ok(self.load::<CaEntry>("ca").await?.map(|ca| ca.certificate.clone()))
+    }
+
+    pub async fn read_cert(&self, serial_number: &str) -> VaultResult<Option<Map<String, Value>>> {
+        let serial_number = format_serial(&parse_serial(serial_number)?.to_bytes());
+        let issued: Option<IssuedCert> = self.load(&format!("certs/{}", serial_number.replace(':', "-"))).await?;
+        Ok(issued.map(|issued| {
+            let mut data = Map::new();
+            data.insert("serial_number".to_string(), Value::String(issued.serial_number));
+            data.insert("common_name".to_string(), Value::String(issued.common_name));
+            data.insert("certificate".to_string(), Value::String(issued.certificate));
+            data.insert("expiration".to_string(), Value::Number(issued.not_after.into()));
+            data.insert("revocation_time".to_string(), issued.revoked_at.map(Value::from).unwrap_or(Value::Null));
+            data
+        }))
+    }
+}
+
+fn single_field(key: &str, value: String) -> Option<Response> {
+    let mut data = Map::new();
+    data.insert(key.to_string(), Value::String(value));
+    Some(Response::new().data(data))
 }
 
 #[async_trait]
 impl Backend for PkiBackend {
-    async fn handle_request(&self, _req: &mut Request) -> VaultResult<Option<Response>> {
-        // TODO: Implement PKI operations
-        Ok(None)
+    async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
+        let path = req.path.strip_prefix(&format!("{}/", self.mount_path))
+            .unwrap_or(&req.path)
+            .to_string();
+        let data = req.data.take().unwrap_or_default();
+
+        // Route on the path prefix; the remainder names a role or serial
+        let (prefix, rest) = match path.split_once('/') {
+            Some((prefix, rest)) => (prefix, rest),
+            None => (path.as_str(), ""),
+        };
+
+        match (req.operation, prefix, rest) {
+            (Operation::Write, "root", "generate") => Ok(Some(Response::new().data(self.generate_root(&data).await?))),
+            (Operation::Read, "ca", "") => Ok(self.read_ca().await?.and_then(|ca| single_field("certificate", ca))),
+            (Operation::Write, "roles", name) if !name.is_empty() => Ok(Some(Response::new().data(self.write_role(name, &data).await?))),
+            (Operation::Read, "roles", name) if !name.is_empty() => Ok(self.read_role(name).await?.map(|role| Response::new().data(role))),
+            (Operation::Write, "issue", role) if !role.is_empty() => Ok(Some(Response::new().data(self.issue(role, &data).await?))),
+            (Operation::Write, "revoke", "") => {
+                let serial_number = data.get("serial_number").and_then(|v| v.as_str())
+                    .ok_or_else(|| VaultError::Vault("Missing required field: serial_number".to_string()))?;
+                Ok(Some(Response::new().data(self.revoke(serial_number).await?)))
+            }
+            (Operation::Read, "crl", "") => Ok(single_field("crl", self.crl().await?)),
+            (Operation::Read, "cert", serial) if !serial.is_empty() => Ok(self.read_cert(serial).await?.map(|cert| Response::new().data(cert))),
+            _ => Ok(None),
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use crate::storage::barrier_aes_gcm::AESGCMBarrier;
+    use crate::storage::physical_file::FileBackend;
+    use crate::storage::SecurityBarrier;
+
+    async fn pki_backend(dir: &tempfile::TempDir) -> PkiBackend {
+        let backend: Arc<dyn StorageBackend> = Arc::new(FileBackend::new(dir.path()).unwrap());
+        let barrier = Arc::new(AESGCMBarrier::new(backend));
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        PkiBackend::new(barrier, "pki".to_string())
+    }
+
+    async fn write(pki: &PkiBackend, path: &str, data: Value) -> Map<String, Value> {
+        let mut req = Request::new_write_request(path, data.as_object().cloned());
+        pki.handle_request(&mut req).await.unwrap().unwrap().data.unwrap()
+    }
+
+    /// DER of the CRL in a PEM string
+    fn pem_contents(pem: &str) -> Vec<u8> {
+        let body: String = pem.lines().filter(|line| !line.starts_with("-----")).collect();
+        STANDARD.decode(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_issue_revoke_and_crl() {
+        let dir = tempfile::tempdir().unwrap();
+        let pki = pki_backend(&dir).await;
+
+        let root = write(&pki, "pki/root/generate", serde_json::json!({"common_name": "Health Root CA"})).await;
+        assert!(root["certificate"].as_str().unwrap().starts_with("-----BEGIN CERTIFICATE-----"));
+
+        write(&pki, "pki/roles/service", serde_json::json!({
+            "allowed_domains": "svc.internal",
+            "allow_subdomains": true,
+            "max_ttl": "24h",
+        })).await;
+
+        let leaf = write(&pki, "pki/issue/service", serde_json::json!({
+            "common_name": "api.svc.internal",
+            "ttl": "720h",
+        })).await;
+        let serial = leaf["serial_number"].as_str().unwrap().to_string();
+        assert!(leaf["private_key"].as_str().unwrap().contains("PRIVATE KEY"));
+        // Requested TTL is capped at the role's max_ttl
+        let expiration = leaf["expiration"].as_i64().unwrap();
+        assert!(expiration <= OffsetDateTime::now_utc().unix_timestamp() + 24 * 3600);
+
+        write(&pki, "pki/revoke", serde_json::json!({"serial_number": serial})).await;
+
+        let mut req = Request::new_read_request("pki/crl");
+        let crl = pki.handle_request(&mut req).await.unwrap().unwrap().data.unwrap();
+        let der = pem_contents(crl["crl"].as_str().unwrap());
+
+        // The revoked serial appears in the CRL as a DER INTEGER
+        let serial_bytes = parse_serial(&serial).unwrap().to_bytes();
+        let mut encoded = vec![0x02, serial_bytes.len() as u8];
+        encoded.extend_from_slice(&serial_bytes);
+        assert!(der.windows(encoded.len()).any(|window| window == encoded.as_slice()));
+    }
+
+    #[tokio::test]
+    async fn test_issue_rejects_names_outside_role() {
+        let dir = tempfile::tempdir().unwrap();
+        let pki = pki_backend(&dir).await;
+        write(&pki, "pki/root/generate", serde_json::json!({"common_name": "Health Root CA"})).await;
+        write(&pki, "pki/roles/service", serde_json::json!({"allowed_domains": ["svc.internal"]})).await;
+
+        let data = serde_json::json!({"common_name": "evil.example.com"});
+        let mut req = Request::new_write_request("pki/issue/service", data.as_object().cloned());
+        assert!(pki.handle_request(&mut req).await.is_err());
+    }
+
+    #[test]
+    fn test_parse_ttl() {
+        assert_eq!(parse_ttl(&Value::from("72h")).unwrap(), 72 * 3600);
+        assert_eq!(parse_ttl(&Value::from("30m")).unwrap(), 1800);
+        assert_eq!(parse_ttl(&Value::from("90")).unwrap(), 90);
+        assert_eq!(parse_ttl(&Value::from(3600)).unwrap(), 3600);
+        assert!(parse_ttl(&Value::from("1w")).is_err());
+        assert!(parse_ttl(&Value::from(0)).is_err());
+    }
+}
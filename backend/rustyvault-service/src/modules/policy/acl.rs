@@ -143,45 +143,59 @@ impl ACL {
             });
         }
 
+        match self.matching_rule(req) {
+            Some((_, perms)) => self.check_permissions(perms, req, check_only),
+            // No match found, deny
+            None => Ok(ACLResults::default()),
+        }
+    }
+
+    /// The rule that decides `req`, as written in the policy (e.g. `secret/*`), with its permissions
+    ///
+    /// Exact rules win over prefix rules, which win over segment wildcard rules.
+    /// Always `None` for the root policy, which has no rules.
+    pub fn matching_rule(&self, req: &Request) -> Option<(String, &Permissions)> {
         let path = ensure_no_leading_slash(&req.path);
 
         // Try exact match first
         if let Some(perms) = self.exact_rules.get(&path) {
-            return self.check_permissions(perms, req, check_only);
+            return Some((path, perms));
         }
 
         // For list operations, also try without trailing slash
         if req.operation == Operation::List {
             let trimmed = path.trim_end_matches('/');
             if let Some(perms) = self.exact_rules.get(trimmed) {
-                return self.check_permissions(perms, req, check_only);
+                return Some((trimmed.to_string(), perms));
             }
         }
 
         // Try prefix match
-        if let Some(perms) = self.get_prefix_permissions(&path) {
-            return self.check_permissions(&perms, req, check_only);
+        if let Some((prefix, perms)) = self.get_prefix_permissions(&path) {
+            return Some((format!("{}*", prefix), perms));
         }
 
         // Try segment wildcard match
-        if let Some(perms) = self.get_wildcard_permissions(&path) {
-            return self.check_permissions(&perms, req, check_only);
-        }
-
-        // No match found, deny
-        Ok(ACLResults::default())
+        self.get_wildcard_permissions(&path)
+            .map(|(wc_path, perms, is_prefix)| {
+                let rule = if is_prefix { format!("{}*", wc_path) } else { wc_path.clone() };
+                (rule, perms)
+            })
     }
 
     /// Get permissions from prefix rules
-    fn get_prefix_permissions(&self, path: &str) -> Option<Permissions> {
+    fn get_prefix_permissions<'p>(&self, path: &'p str) -> Option<(&'p str, &Permissions)> {
         // Find the longest matching prefix
-        self.prefix_rules.get_ancestor_value(path).cloned()
+        (0..=path.len())
+            .rev()
+            .filter(|&end| path.is_char_boundary(end))
+            .find_map(|end| self.prefix_rules.get(&path[..end]).map(|perms| (&path[..end], perms)))
     }
 
     /// Get permissions from segment wildcard rules
-    fn get_wildcard_permissions(&self, path: &str) -> Option<Permissions> {
+    fn get_wildcard_permissions(&self, path: &str) -> Option<(&String, &Permissions, bool)> {
         let path_parts: Vec<&str> = path.split('/').collect();
-        let mut best_match: Option<&Permissions> = None;
+        let mut best_match: Option<(&String, &Permissions, bool)> = None;
         let mut best_specificity = 0;
 
         for (wc_path, perms, is_prefix) in &self.segment_wildcard_paths {
@@ -218,11 +232,11 @@ impl ACL {
 
             if matches && specificity > best_specificity {
                 best_specificity = specificity;
-                best_match = Some(perms);
+                best_match = Some((wc_path, perms, *is_prefix));
             }
         }
 
-        best_match.cloned()
+        best_match
     }
 
     /// Check if permissions allow the operation
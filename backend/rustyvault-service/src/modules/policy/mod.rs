@@ -5,11 +5,14 @@
 //! - Policy structures for defining access rules
 //! - ACL evaluation engine
 //! - Policy storage with PostgreSQL backend
+//! - Policy simulation (dry run) for unsaved policies
 
 pub mod acl;
 pub mod policy;
 pub mod policy_store;
+pub mod simulator;
 
 // Re-export commonly used types
 pub use policy::Policy;
 pub use policy_store::PolicyStore;
+pub use simulator::{PolicySimulator, SimulationResult};
@@ -0,0 +1,160 @@
+//! Policy simulation (dry run)
+//!
+//! Evaluates a request against policies that have not been stored, using the
+//! same ACL as the live check, and explains the decision.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::acl::ACL;
+use super::policy::{to_capability_strings, Capability, Policy};
+use crate::errors::VaultResult;
+use crate::logical::request::{Operation, Request};
+
+/// Outcome of a simulated request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimulationResult {
+    /// Whether the live check would allow the request
+    pub allowed: bool,
+    /// The rule that decided it, as written in the policy (e.g. `secret/*`)
+    pub matching_rule: Option<String>,
+    /// Capabilities the matching rule grants, after merging all policies
+    pub capabilities: Vec<String>,
+    /// Human readable explanation of the decision
+    pub reason: String,
+}
+
+/// Evaluates requests against in-memory policies
+pub struct PolicySimulator;
+
+impl PolicySimulator {
+    /// Evaluate `request` against a single policy
+    pub fn evaluate(policy: &Policy, request: &Request) -> VaultResult<SimulationResult> {
+        Self::evaluate_all(&[Arc::new(policy.clone())], request)
+    }
+
+    /// Evaluate `request` against policies combined as on a token
+    pub fn evaluate_all(policies: &[Arc<Policy>], request: &Request) -> VaultResult<SimulationResult> {
+        let acl = ACL::new(policies)?;
+        let result = acl.allow_operation(request, false)?;
+
+        if result.is_root {
+            return Ok(SimulationResult {
+                allowed: true,
+                matching_rule: None,
+                capabilities: vec![Capability::Root.to_string()],
+                reason: "the root policy allows every request".to_string(),
+            });
+        }
+
+        let Some((rule, perms)) = acl.matching_rule(request) else {
+            return Ok(SimulationResult {
+                allowed: false,
+                matching_rule: None,
+                capabilities: Vec::new(),
+                reason: format!("no rule matches path \"{}\"", request.path),
+            });
+        };
+
+        let capabilities = to_capability_strings(perms.capabilities_bitmap);
+        let reason = if perms.capabilities_bitmap & Capability::Deny.to_bits() != 0 {
+            format!("rule \"{}\" denies path \"{}\"", rule, request.path)
+        } else if result.allowed {
+            format!("rule \"{}\" grants {}", rule, required_capabilities(request.operation))
+        } else {
+            format!("rule \"{}\" does not grant {}", rule, required_capabilities(request.operation))
+        };
+
+        Ok(SimulationResult {
+            allowed: result.allowed,
+            matching_rule: Some(rule),
+            capabilities,
+            reason,
+        })
+    }
+}
+
+/// Capabilities that permit `operation`, as listed in `Permissions::check_operation`
+fn required_capabilities(operation: Operation) -> &'static str {
+    match operation {
+        Operation::Read => "read",
+        Operation::Write => "create or update",
+        Operation::Delete => "delete",
+        Operation::List => "list",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_policy(name: &str, json: &str) -> Policy {
+        let mut policy = Policy::from_json(json).unwrap();
+        policy.name = name.to_string();
+        policy
+    }
+
+    #[test]
+    fn test_prefix_rule_matches_nested_path() {
+        let policy = create_test_policy(
+            "app",
+            r#"{
+                "path": {
+                    "secret/*": {
+                        "capabilities": ["read"]
+                    }
+                }
+            }"#,
+        );
+
+        let result =
+            PolicySimulator::evaluate(&policy, &Request::new_read_request("secret/myapp/password")).unwrap();
+        assert!(result.allowed);
+        assert_eq!(result.matching_rule.as_deref(), Some("secret/*"));
+        assert_eq!(result.capabilities, vec!["read".to_string()]);
+
+        let result = PolicySimulator::evaluate(&policy, &Request::new_write_request("secret/myapp/password", None))
+            .unwrap();
+        assert!(!result.allowed);
+        assert_eq!(result.matching_rule.as_deref(), Some("secret/*"));
+
+        let result = PolicySimulator::evaluate(&policy, &Request::new_read_request("sys/policies")).unwrap();
+        assert!(!result.allowed);
+        assert_eq!(result.matching_rule, None);
+    }
+
+    #[test]
+    fn test_deny_overrides_allow_for_same_path() {
+        let allow = create_test_policy(
+            "allow",
+            r#"{
+                "path": {
+                    "secret/myapp/*": {
+                        "capabilities": ["read", "list"]
+                    }
+                }
+            }"#,
+        );
+        let deny = create_test_policy(
+            "deny",
+            r#"{
+                "path": {
+                    "secret/myapp/*": {
+                        "capabilities": ["deny"]
+                    }
+                }
+            }"#,
+        );
+        let request = Request::new_read_request("secret/myapp/password");
+
+        assert!(PolicySimulator::evaluate(&allow, &request).unwrap().allowed);
+        for policies in [[allow.clone(), deny.clone()], [deny, allow]] {
+            let policies: Vec<Arc<Policy>> = policies.into_iter().map(Arc::new).collect();
+            let result = PolicySimulator::evaluate_all(&policies, &request).unwrap();
+            assert!(!result.allowed);
+            assert_eq!(result.matching_rule.as_deref(), Some("secret/myapp/*"));
+            assert_eq!(result.capabilities, vec!["deny".to_string()]);
+        }
+    }
+}
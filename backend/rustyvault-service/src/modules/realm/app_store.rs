@@ -8,7 +8,16 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use shared::AppError;
+
 use crate::errors::{VaultError, VaultResult};
+use crate::modules::auth::TokenStore;
+
+/// Minimum time between client secret rotations for one application
+const CLIENT_SECRET_ROTATION_INTERVAL_HOURS: i64 = 24;
+
+/// Random bytes in a generated client secret
+const CLIENT_SECRET_BYTES: usize = 32;
 
 /// Application types supported in the system
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -111,6 +120,24 @@ pub struct RealmApplication {
     pub updated_by: Option<Uuid>,
 }
 
+impl RealmApplication {
+    /// Policies for tokens issued to this application's client credentials
+    ///
+    /// Taken from `config.policies`; `default` when none are configured.
+    pub fn client_policies(&self) -> Vec<String> {
+        let policies: Vec<String> = self.config.as_ref()
+            .and_then(|config| config.get("policies"))
+            .and_then(|policies| policies.as_array())
+            .map(|policies| policies.iter().filter_map(|p| p.as_str().map(str::to_string)).collect())
+            .unwrap_or_default();
+        if policies.is_empty() {
+            vec!["default".to_string()]
+        } else {
+            policies
+        }
+    }
+}
+
 /// Request to create a new realm application
 #[derive(Debug, Clone, Deserialize)]
 pub struct CreateAppRequest {
@@ -132,6 +159,15 @@ pub struct UpdateAppRequest {
     pub is_active: Option<bool>,
 }
 
+/// Result of rotating an application's client secret
+#[derive(Debug, Clone, Serialize)]
+pub struct ClientSecretRotation {
+    /// New plaintext secret; only its hash is stored, so it is shown once
+    pub client_secret: String,
+    pub rotated_at: DateTime<Utc>,
+    pub next_rotation_allowed_at: DateTime<Utc>,
+}
+
 /// Realm application store for managing applications in realms
 pub struct RealmApplicationStore {
     pool: PgPool,
@@ -408,6 +444,102 @@ impl RealmApplicationStore {
         Ok(())
     }
 
+    /// Rotate an application's OAuth2 client secret
+    ///
+    /// Stores only the BLAKE3 hash of the new secret and revokes every token
+    /// issued with the old one. Rotations are limited to one per 24 hours.
+    pub async fn rotate_client_secret(
+        &self,
+        app_id: Uuid,
+        token_store: &TokenStore,
+    ) -> VaultResult<ClientSecretRotation> {
+        let client_secret = generate_client_secret();
+        let secret_hash = hash_client_secret(&client_secret);
+
+        // The rotation window is checked in the same statement that updates
+        // the hash so concurrent rotations cannot both succeed
+        let rotated_at = sqlx::query_scalar!(
+            r#"
+            UPDATE vault_realm_applications
+            SET client_secret_hash = $2,
+                client_secret_rotated_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $1
+              AND (client_secret_rotated_at IS NULL
+                   OR client_secret_rotated_at <= NOW() - make_interval(hours => $3))
+            RETURNING client_secret_rotated_at as "client_secret_rotated_at!"
+            "#,
+            app_id,
+            secret_hash,
+            CLIENT_SECRET_ROTATION_INTERVAL_HOURS as i32
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to rotate client secret: {}", e)))?;
+
+        let rotated_at = match rotated_at {
+            Some(rotated_at) => rotated_at,
+            None => {
+                let last_rotated_at = sqlx::query_scalar!(
+                    "SELECT client_secret_rotated_at FROM vault_realm_applications WHERE id = $1",
+                    app_id
+                )
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| VaultError::Vault(format!("failed to rotate client secret: {}", e)))?
+                .ok_or_else(|| VaultError::Shared(AppError::NotFound("application not found".to_string())))?;
+
+                let next_allowed = last_rotated_at.map(next_rotation_allowed_at).unwrap_or_else(Utc::now);
+                return Err(VaultError::Shared(AppError::TooManyAttempts {
+                    retry_after_seconds: (next_allowed - Utc::now()).num_seconds().max(0) as u64,
+                }));
+            }
+        };
+
+        let revoked = token_store.revoke_for_client(app_id).await?;
+        tracing::info!("Rotated client secret for application {} ({} tokens revoked)", app_id, revoked);
+
+        Ok(ClientSecretRotation {
+            client_secret,
+            rotated_at,
+            next_rotation_allowed_at: next_rotation_allowed_at(rotated_at),
+        })
+    }
+
+    /// Check an application's client credentials
+    ///
+    /// Returns the application when it is active in `realm_id` and
+    /// `client_secret` matches its current secret. Every failure is the same
+    /// authentication error, so callers cannot probe for applications.
+    pub async fn verify_client_secret(
+        &self,
+        realm_id: Uuid,
+        app_id: Uuid,
+        client_secret: &str,
+    ) -> VaultResult<RealmApplication> {
+        let invalid = || VaultError::Shared(AppError::Authentication("invalid client credentials".to_string()));
+
+        let secret_hash = sqlx::query_scalar!(
+            r#"
+            SELECT client_secret_hash
+            FROM vault_realm_applications
+            WHERE id = $1 AND realm_id = $2 AND is_active = true
+            "#,
+            app_id,
+            realm_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to verify client secret: {}", e)))?
+        .flatten()
+        .ok_or_else(invalid)?;
+
+        if !client_secret_matches(&secret_hash, client_secret) {
+            return Err(invalid());
+        }
+        self.get(app_id).await?.ok_or_else(invalid)
+    }
+
     /// Check if an application allows a specific auth method
     pub async fn is_auth_method_allowed(
         &self,
@@ -477,6 +609,29 @@ impl RealmApplicationStore {
     }
 }
 
+/// Generate a new client secret (hex-encoded random bytes)
+fn generate_client_secret() -> String {
+    use rand::RngCore;
+    let mut bytes = [0u8; CLIENT_SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Hash a client secret for storage
+fn hash_client_secret(secret: &str) -> String {
+    blake3::hash(secret.as_bytes()).to_hex().to_string()
+}
+
+/// Compare a client secret against its stored hash in constant time
+fn client_secret_matches(secret_hash: &str, client_secret: &str) -> bool {
+    // blake3::Hash equality is constant time
+    blake3::Hash::from_hex(secret_hash).is_ok_and(|hash| hash == blake3::hash(client_secret.as_bytes()))
+}
+
+fn next_rotation_allowed_at(rotated_at: DateTime<Utc>) -> DateTime<Utc> {
+    rotated_at + chrono::Duration::hours(CLIENT_SECRET_ROTATION_INTERVAL_HOURS)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -498,5 +653,34 @@ mod tests {
         assert_eq!(AuthMethod::from_str("approle"), Some(AuthMethod::AppRole));
         assert_eq!(AuthMethod::from_str("invalid"), None);
     }
-}
 
+    #[test]
+    fn test_generate_client_secret() {
+        let secret = generate_client_secret();
+        assert_eq!(secret.len(), CLIENT_SECRET_BYTES * 2);
+        assert_ne!(secret, generate_client_secret());
+
+        let hash = hash_client_secret(&secret);
+        assert_eq!(hash.len(), 64); // BLAKE3 produces 64 hex chars
+        assert_ne!(hash, secret);
+    }
+
+    #[test]
+    fn test_client_secret_matches_stored_hash() {
+        let secret = generate_client_secret();
+        let hash = hash_client_secret(&secret);
+
+        assert!(client_secret_matches(&hash, &secret));
+        assert!(!client_secret_matches(&hash, &generate_client_secret()));
+        assert!(!client_secret_matches("not-a-hash", &secret));
+    }
+
+    #[test]
+    fn test_next_rotation_allowed_after_interval() {
+        let rotated_at = Utc::now();
+        assert_eq!(
+            next_rotation_allowed_at(rotated_at) - rotated_at,
+            chrono::Duration::hours(24)
+        );
+    }
+}
@@ -10,6 +10,7 @@ mod realm_store;
 pub use app_store::{
     AppType,
     AuthMethod,
+    ClientSecretRotation,
     CreateAppRequest,
     RealmApplication,
     RealmApplicationStore,
@@ -18,6 +19,7 @@ pub use app_store::{
 pub use realm_store::{
     CreateRealmRequest,
     Realm,
+    RealmStatus,
     RealmStore,
     UpdateRealmRequest,
 };
@@ -26,16 +28,24 @@ use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::errors::VaultResult;
+use crate::modules::auth::TokenStore;
 
 /// Realm manager for managing vault realms
 pub struct RealmManager {
     store: Arc<RealmStore>,
+    token_store: Option<Arc<TokenStore>>,
 }
 
 impl RealmManager {
     /// Create a new realm manager
     pub fn new(store: Arc<RealmStore>) -> Self {
-        Self { store }
+        Self { store, token_store: None }
+    }
+
+    /// Revoke a realm's tokens when it is suspended
+    pub fn with_token_store(mut self, token_store: Arc<TokenStore>) -> Self {
+        self.token_store = Some(token_store);
+        self
     }
 
     /// Create a new realm
@@ -73,6 +83,23 @@ impl RealmManager {
         self.store.update(id, request).await
     }
 
+    /// Suspend a realm and revoke every token issued in it
+    pub async fn suspend(&self, id: Uuid, reason: &str) -> VaultResult<Realm> {
+        let realm = self.store.suspend(id, reason).await?;
+        if let Some(token_store) = &self.token_store {
+            let revoked = token_store.revoke_by_realm(id).await?;
+            tracing::info!("Suspended realm {} and revoked {} tokens", id, revoked);
+        }
+        Ok(realm)
+    }
+
+    /// Activate a suspended realm
+    ///
+    /// Tokens revoked on suspension stay revoked; clients log in again.
+    pub async fn activate(&self, id: Uuid) -> VaultResult<Realm> {
+        self.store.activate(id).await
+    }
+
     /// Delete a realm
     pub async fn delete_realm(&self, id: Uuid) -> VaultResult<()> {
         self.store.delete(id).await
@@ -4,11 +4,26 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use shared::config::{FeatureFlags, RealmFeatureFlags};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::errors::{VaultError, VaultResult};
 
+/// Key of a realm's feature flag overrides in its config
+pub const FEATURE_FLAGS_CONFIG_KEY: &str = "feature_flags";
+
+/// Whether a realm serves requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RealmStatus {
+    Active,
+    /// Requests are refused until the realm is activated; its data is kept
+    Suspended,
+    Deleted,
+}
+
 /// Realm entity representing a multi-tenant namespace in vault
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Realm {
@@ -21,6 +36,9 @@ pub struct Realm {
     pub default_lease_ttl: Option<i32>,
     pub max_lease_ttl: Option<i32>,
     pub is_active: bool,
+    pub status: RealmStatus,
+    /// Why the realm was suspended
+    pub status_reason: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub request_id: Option<String>,
@@ -28,6 +46,16 @@ pub struct Realm {
     pub updated_by: Option<Uuid>,
 }
 
+impl Realm {
+    /// Feature flag overrides stored in the realm's config
+    pub fn feature_flags(&self) -> VaultResult<RealmFeatureFlags> {
+        match self.config.as_ref().and_then(|config| config.get(FEATURE_FLAGS_CONFIG_KEY)) {
+            Some(flags) => Ok(serde_json::from_value(flags.clone())?),
+            None => Ok(RealmFeatureFlags::default()),
+        }
+    }
+}
+
 /// Request to create a new realm
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CreateRealmRequest {
@@ -82,6 +110,7 @@ impl RealmStore {
             RETURNING
                 id, name, description, display_name, organization_id, config,
                 default_lease_ttl, max_lease_ttl, is_active,
+                status as "status: RealmStatus", status_reason,
                 created_at, updated_at, request_id, created_by, updated_by
             "#,
             &request.name,
@@ -113,6 +142,7 @@ impl RealmStore {
             SELECT
                 id, name, description, display_name, organization_id, config,
                 default_lease_ttl, max_lease_ttl, is_active,
+                status as "status: RealmStatus", status_reason,
                 created_at, updated_at, request_id, created_by, updated_by
             FROM vault_realms
             WHERE id = $1
@@ -134,6 +164,7 @@ impl RealmStore {
             SELECT
                 id, name, description, display_name, organization_id, config,
                 default_lease_ttl, max_lease_ttl, is_active,
+                status as "status: RealmStatus", status_reason,
                 created_at, updated_at, request_id, created_by, updated_by
             FROM vault_realms
             WHERE name = $1
@@ -155,6 +186,7 @@ impl RealmStore {
             SELECT
                 id, name, description, display_name, organization_id, config,
                 default_lease_ttl, max_lease_ttl, is_active,
+                status as "status: RealmStatus", status_reason,
                 created_at, updated_at, request_id, created_by, updated_by
             FROM vault_realms
             WHERE organization_id = $1
@@ -178,6 +210,7 @@ impl RealmStore {
             SELECT
                 id, name, description, display_name, organization_id, config,
                 default_lease_ttl, max_lease_ttl, is_active,
+                status as "status: RealmStatus", status_reason,
                 created_at, updated_at, request_id, created_by, updated_by
             FROM vault_realms
             ORDER BY name
@@ -198,6 +231,7 @@ impl RealmStore {
             SELECT
                 id, name, description, display_name, organization_id, config,
                 default_lease_ttl, max_lease_ttl, is_active,
+                status as "status: RealmStatus", status_reason,
                 created_at, updated_at, request_id, created_by, updated_by
             FROM vault_realms
             WHERE organization_id = $1
@@ -264,6 +298,7 @@ impl RealmStore {
             RETURNING 
                 id, name, description, display_name, organization_id, config,
                 default_lease_ttl, max_lease_ttl, is_active,
+                status, status_reason,
                 created_at, updated_at, request_id, created_by, updated_by
             "#,
             set_clauses.join(", ")
@@ -303,6 +338,75 @@ impl RealmStore {
         Ok(realm)
     }
 
+    /// Suspend a realm, refusing its requests until it is activated
+    pub async fn suspend(&self, id: Uuid, reason: &str) -> VaultResult<Realm> {
+        self.set_status(id, RealmStatus::Suspended, Some(reason)).await
+    }
+
+    /// Activate a suspended realm
+    pub async fn activate(&self, id: Uuid) -> VaultResult<Realm> {
+        self.set_status(id, RealmStatus::Active, None).await
+    }
+
+    async fn set_status(&self, id: Uuid, status: RealmStatus, reason: Option<&str>) -> VaultResult<Realm> {
+        sqlx::query_as!(
+            Realm,
+            r#"
+            UPDATE vault_realms
+            SET status = $2, status_reason = $3, updated_at = NOW()
+            WHERE id = $1
+            RETURNING
+                id, name, description, display_name, organization_id, config,
+                default_lease_ttl, max_lease_ttl, is_active,
+                status as "status: RealmStatus", status_reason,
+                created_at, updated_at, request_id, created_by, updated_by
+            "#,
+            id,
+            status as RealmStatus,
+            reason
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to update realm status: {}", e)))?
+        .ok_or_else(|| VaultError::Vault("realm not found".to_string()))
+    }
+
+    /// A realm's overrides of the deployment's feature flags
+    pub async fn feature_flags(&self, id: Uuid) -> VaultResult<RealmFeatureFlags> {
+        let realm = self
+            .get(id)
+            .await?
+            .ok_or_else(|| VaultError::Vault("realm not found".to_string()))?;
+        realm.feature_flags()
+    }
+
+    /// Replace a realm's feature flag overrides
+    pub async fn set_feature_flags(&self, id: Uuid, flags: &RealmFeatureFlags) -> VaultResult<RealmFeatureFlags> {
+        let flags_json = serde_json::to_value(flags)?;
+        sqlx::query!(
+            r#"
+            UPDATE vault_realms
+            SET config = jsonb_set(COALESCE(config, '{}'::jsonb), ARRAY[$2::text], $3), updated_at = NOW()
+            WHERE id = $1
+            RETURNING id
+            "#,
+            id,
+            FEATURE_FLAGS_CONFIG_KEY,
+            flags_json
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| VaultError::Vault(format!("failed to update realm feature flags: {}", e)))?
+        .ok_or_else(|| VaultError::Vault("realm not found".to_string()))?;
+
+        Ok(*flags)
+    }
+
+    /// The deployment's feature flags with a realm's overrides applied
+    pub async fn effective_feature_flags(&self, id: Uuid, deployment: &FeatureFlags) -> VaultResult<FeatureFlags> {
+        Ok(deployment.with_overrides(&self.feature_flags(id).await?))
+    }
+
     /// Delete a realm
     pub async fn delete(&self, id: Uuid) -> VaultResult<()> {
         let result = sqlx::query!("DELETE FROM vault_realms WHERE id = $1", id)
@@ -390,5 +494,40 @@ mod tests {
         let json = serde_json::to_string(&request).unwrap();
         assert!(json.contains("test-realm"));
     }
-}
 
+    #[test]
+    fn test_realm_status_serialization() {
+        assert_eq!(serde_json::to_string(&RealmStatus::Suspended).unwrap(), "\"suspended\"");
+        let status: RealmStatus = serde_json::from_str("\"active\"").unwrap();
+        assert_eq!(status, RealmStatus::Active);
+    }
+
+    #[test]
+    fn test_realm_feature_flags_from_config() {
+        let now = Utc::now();
+        let mut realm = Realm {
+            id: Uuid::new_v4(),
+            name: "test-realm".to_string(),
+            description: None,
+            display_name: None,
+            organization_id: None,
+            config: Some(serde_json::json!({"key": "value"})),
+            default_lease_ttl: None,
+            max_lease_ttl: None,
+            is_active: true,
+            status: RealmStatus::Active,
+            status_reason: None,
+            created_at: now,
+            updated_at: now,
+            request_id: None,
+            created_by: None,
+            updated_by: None,
+        };
+        assert_eq!(realm.feature_flags().unwrap(), RealmFeatureFlags::default());
+
+        realm.config = Some(serde_json::json!({"feature_flags": {"fhir_export": false}}));
+        let flags = FeatureFlags::default().with_overrides(&realm.feature_flags().unwrap());
+        assert!(!flags.fhir_export);
+        assert!(flags.workflow_engine);
+    }
+}
@@ -0,0 +1,441 @@
+//! Transit secrets engine module
+//!
+//! Encryption as a service, following the HashiCorp Vault Transit API.
+//! Named keys are kept in barrier storage under `{mount}/keys/{name}` and are
+//! never returned to clients. Rotating a key adds a new version; ciphertexts
+//! record the version that produced them: `vault:v{version}:base64(nonce+ciphertext)`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use ring::aead::{self as ring_aead, LessSafeKey, UnboundKey, CHACHA20_POLY1305};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::sync::Mutex;
+use zeroize::Zeroize;
+use crate::errors::{VaultError, VaultResult};
+use crate::logical::{Backend, Operation, Request, Response};
+use crate::storage::StorageBackend;
+
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const CIPHERTEXT_PREFIX: &str = "vault:v";
+
+/// Encryption algorithm of a named key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KeyType {
+    #[default]
+    #[serde(rename = "aes256-gcm96")]
+    Aes256Gcm96,
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+impl KeyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyType::Aes256Gcm96 => "aes256-gcm96",
+            KeyType::ChaCha20Poly1305 => "chacha20-poly1305",
+        }
+    }
+
+    pub fn parse(s: &str) -> VaultResult<Self> {
+        match s {
+            "aes256-gcm96" => Ok(KeyType::Aes256Gcm96),
+            "chacha20-poly1305" => Ok(KeyType::ChaCha20Poly1305),
+            _ => Err(VaultError::Vault(format!("Unsupported key type: {}", s))),
+        }
+    }
+}
+
+/// A named key with every version it has had
+#[derive(Clone, Serialize, Deserialize)]
+struct TransitKey {
+    name: String,
+    key_type: KeyType,
+    latest_version: u32,
+    /// Key material by version
+    keys: BTreeMap<u32, Vec<u8>>,
+    created_time: String,
+}
+
+impl Drop for TransitKey {
+    fn drop(&mut self) {
+        for key in self.keys.values_mut() {
+            key.zeroize();
+        }
+    }
+}
+
+impl TransitKey {
+    fn new(name: &str, key_type: KeyType) -> Self {
+        let mut key = Self {
+            name: name.to_string(),
+            key_type,
+            latest_version: 0,
+            keys: BTreeMap::new(),
+            created_time: chrono::Utc::now().to_rfc3339(),
+        };
+        key.add_version();
+        key
+    }
+
+    /// Generate a new version and make it the one used for encryption
+    fn add_version(&mut self) -> u32 {
+        let mut material = vec![0u8; KEY_SIZE];
+        rand::thread_rng().fill_bytes(&mut material);
+        self.latest_version += 1;
+        self.keys.insert(self.latest_version, material);
+        self.latest_version
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> VaultResult<String> {
+        let key = &self.keys[&self.latest_version];
+        let sealed = seal(self.key_type, key, plaintext)?;
+        Ok(format!("{}{}:{}", CIPHERTEXT_PREFIX, self.latest_version, STANDARD.encode(sealed)))
+    }
+
+    fn decrypt(&self, ciphertext: &str) -> VaultResult<Vec<u8>> {
+        let (version, sealed) = parse_ciphertext(ciphertext)?;
+        let key = self.keys.get(&version).ok_or_else(|| {
+            VaultError::Vault(format!("Key version {} of {} does not exist", version, self.name))
+        })?;
+        open(self.key_type, key, &sealed)
+    }
+
+    /// Metadata safe to return to clients (no key material)
+    fn info(&self) -> Map<String, Value> {
+        let mut data = Map::new();
+        data.insert("name".to_string(), Value::String(self.name.clone()));
+        data.insert("type".to_string(), Value::String(self.key_type.as_str().to_string()));
+        data.insert("latest_version".to_string(), Value::Number(self.latest_version.into()));
+        data.insert("versions".to_string(), Value::Array(
+            self.keys.keys().map(|v| Value::Number((*v).into())).collect()
+        ));
+        data.insert("created_time".to_string(), Value::String(self.created_time.clone()));
+        data
+    }
+}
+
+/// Split `vault:v{version}:{base64}` into the version and the raw nonce+ciphertext
+fn parse_ciphertext(ciphertext: &str) -> VaultResult<(u32, Vec<u8>)> {
+    let invalid = || VaultError::Vault("Invalid ciphertext format".to_string());
+    let rest = ciphertext.strip_prefix(CIPHERTEXT_PREFIX).ok_or_else(invalid)?;
+    let (version, encoded) = rest.split_once(':').ok_or_else(invalid)?;
+    let version: u32 = version.parse().map_err(|_| invalid())?;
+    let sealed = STANDARD.decode(encoded).map_err(|_| invalid())?;
+    Ok((version, sealed))
+}
+
+/// Encrypt with a fresh random nonce; returns nonce + ciphertext + tag
+fn seal(key_type: KeyType, key: &[u8], plaintext: &[u8]) -> VaultResult<Vec<u8>> {
+    match key_type {
+        KeyType::Aes256Gcm96 => {
+            let cipher = Aes256Gcm::new_from_slice(key)
+                .map_err(|e| VaultError::Vault(format!("Failed to create cipher: {}", e)))?;
+            let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+            let ciphertext = cipher.encrypt(&nonce, plaintext)
+                .map_err(|e| VaultError::Vault(format!("Encryption failed: {}", e)))?;
+            let mut out = nonce.to_vec();
+            out.extend_from_slice(&ciphertext);
+            Ok(out)
+        }
+        KeyType::ChaCha20Poly1305 => {
+            let cipher = chacha_key(key)?;
+            let mut nonce = [0u8; NONCE_SIZE];
+            rand::thread_rng().fill_bytes(&mut nonce);
+            let mut in_out = plaintext.to_vec();
+            cipher
+                .seal_in_place_append_tag(
+                    ring_aead::Nonce::assume_unique_for_key(nonce),
+                    ring_aead::Aad::empty(),
+                    &mut in_out,
+                )
+                .map_err(|_| VaultError::Vault("Encryption failed".to_string()))?;
+            let mut out = nonce.to_vec();
+            out.extend_from_slice(&in_out);
+            Ok(out)
+        }
+    }
+}
+
+/// Decrypt nonce + ciphertext + tag produced by `seal`
+fn open(key_type: KeyType, key: &[u8], sealed: &[u8]) -> VaultResult<Vec<u8>> {
+    if sealed.len() < NONCE_SIZE {
+        return Err(VaultError::Vault("Ciphertext too short".to_string()));
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_SIZE);
+
+    match key_type {
+        KeyType::Aes256Gcm96 => {
+            let cipher = Aes256Gcm::new_from_slice(key)
+                .map_err(|e| VaultError::Vault(format!("Failed to create cipher: {}", e)))?;
+            cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map_err(|_| VaultError::Vault("Decryption failed".to_string()))
+        }
+        KeyType::ChaCha20Poly1305 => {
+            let cipher = chacha_key(key)?;
+            let nonce = ring_aead::Nonce::try_assume_unique_for_key(nonce)
+                .map_err(|_| VaultError::Vault("Invalid nonce".to_string()))?;
+            let mut in_out = ciphertext.to_vec();
+            let plaintext = cipher
+                .open_in_place(nonce, ring_aead::Aad::empty(), &mut in_out)
+                .map_err(|_| VaultError::Vault("Decryption failed".to_string()))?;
+            Ok(plaintext.to_vec())
+        }
+    }
+}
+
+fn chacha_key(key: &[u8]) -> VaultResult<LessSafeKey> {
+    let key = UnboundKey::new(&CHACHA20_POLY1305, key)
+        .map_err(|_| VaultError::Vault("Failed to create cipher: invalid key length".to_string()))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Transit secrets engine backend
+pub struct TransitBackend {
+    storage: Arc<dyn StorageBackend>,
+    mount_path: String,
+    /// Serializes key creation and rotation (read-modify-write of a key entry)
+    write_lock: Mutex<()>,
+}
+
+impl TransitBackend {
+    pub fn new(storage: Arc<dyn StorageBackend>, mount_path: String) -> Self {
+        Self {
+            storage,
+            mount_path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn key_path(&self, name: &str) -> String {
+        format!("{}/keys/{}", self.mount_path, name)
+    }
+
+    async fn load_key(&self, name: &str) -> VaultResult<Option<TransitKey>> {
+        match self.storage.get(&self.key_path(name)).await? {
+            Some(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save_key(&self, key: &TransitKey) -> VaultResult<()> {
+        let mut data = serde_json::to_vec(key)?;
+        let result = self.storage.put(&self.key_path(&key.name), &data).await;
+        data.zeroize();
+        result
+    }
+
+    async fn require_key(&self, name: &str) -> VaultResult<TransitKey> {
+        self.load_key(name).await?
+            .ok_or_else(|| VaultError::Vault(format!("Encryption key {} not found", name)))
+    }
+
+    /// Create a named key; an existing key is returned unchanged
+    async fn upsert_key(&self, name: &str, key_type: KeyType) -> VaultResult<TransitKey> {
+        let _guard = self.write_lock.lock().await;
+        if let Some(key) = self.load_key(name).await? {
+            return Ok(key);
+        }
+        let key = TransitKey::new(name, key_type);
+        self.save_key(&key).await?;
+        Ok(key)
+    }
+
+    // ==========================================
+    // Public API
+    // ==========================================
+
+    /// Create a named key of the given type
+    pub async fn create_key(&self, name: &str, key_type: KeyType) -> VaultResult<Map<String, Value>> {
+        Ok(self.upsert_key(name, key_type).await?.info())
+    }
+
+    /// Encrypt under the latest version of `name`, creating the key on first use
+    pub async fn encrypt(&self, name: &str, plaintext: &[u8], key_type: KeyType) -> VaultResult<String> {
+        let key = match self.load_key(name).await? {
+            Some(key) => key,
+            None => self.upsert_key(name, key_type).await?,
+        };
+        key.encrypt(plaintext)
+    }
+
+    /// Decrypt a ciphertext produced by any version of `name`
+    pub async fn decrypt(&self, name: &str, ciphertext: &str) -> VaultResult<Vec<u8>> {
+        self.require_key(name).await?.decrypt(ciphertext)
+    }
+
+    /// Add a new key version; returns the new latest version
+    pub async fn rotate(&self, name: &str) -> VaultResult<u32> {
+        let _guard = self.write_lock.lock().await;
+        let mut key = self.require_key(name).await?;
+        let version = key.add_version();
+        self.save_key(&key).await?;
+        Ok(version)
+    }
+
+    /// Re-encrypt a ciphertext under the latest key version
+    ///
+    /// The plaintext never leaves the backend.
+    pub async fn rewrap(&self, name: &str, ciphertext: &str) -> VaultResult<String> {
+        let key = self.require_key(name).await?;
+        let mut plaintext = key.decrypt(ciphertext)?;
+        let result = key.encrypt(&plaintext);
+        plaintext.zeroize();
+        result
+    }
+
+    /// Key metadata (type and versions, never key material)
+    pub async fn read_key(&self, name: &str) -> VaultResult<Option<Map<String, Value>>> {
+        Ok(self.load_key(name).await?.map(|key| key.info()))
+    }
+}
+
+fn required_str<'a>(data: &'a Map<String, Value>, field: &str) -> VaultResult<&'a str> {
+    data.get(field)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| VaultError::Vault(format!("Missing required field: {}", field)))
+}
+
+fn key_type_field(data: &Map<String, Value>) -> VaultResult<KeyType> {
+    match data.get("type").and_then(|v| v.as_str()) {
+        Some(key_type) => KeyType::parse(key_type),
+        None => Ok(KeyType::default()),
+    }
+}
+
+fn ciphertext_response(ciphertext: String) -> VaultResult<Option<Response>> {
+    let (version, _) = parse_ciphertext(&ciphertext)?;
+    let mut data = Map::new();
+    data.insert("ciphertext".to_string(), Value::String(ciphertext));
+    data.insert("key_version".to_string(), Value::Number(version.into()));
+    Ok(Some(Response::new().data(data)))
+}
+
+#[async_trait]
+impl Backend for TransitBackend {
+    async fn handle_request(&self, req: &mut Request) -> VaultResult<Option<Response>> {
+        // Paths look like {mount}/{operation}/{key_name}
+        let path = req.path.strip_prefix(&format!("{}/", self.mount_path))
+            .unwrap_or(&req.path)
+            .to_string();
+        let (action, name) = match path.split_once('/') {
+            Some((action, name)) if !name.is_empty() => (action.to_string(), name.to_string()),
+            _ => return Ok(None),
+        };
+        let data = req.data.take().unwrap_or_default();
+
+        match (req.operation, action.as_str()) {
+            (Operation::Read, "keys") => Ok(self.read_key(&name).await?.map(|info| Response::new().data(info))),
+            (Operation::Write, "keys") => {
+                let info = self.create_key(&name, key_type_field(&data)?).await?;
+                Ok(Some(Response::new().data(info)))
+            }
+            (Operation::Write, "encrypt") => {
+                let plaintext = STANDARD.decode(required_str(&data, "plaintext")?)
+                    .map_err(|_| VaultError::Vault("plaintext must be base64-encoded".to_string()))?;
+                let ciphertext = self.encrypt(&name, &plaintext, key_type_field(&data)?).await?;
+                ciphertext_response(ciphertext)
+            }
+            (Operation::Write, "decrypt") => {
+                let mut plaintext = self.decrypt(&name, required_str(&data, "ciphertext")?).await?;
+                let mut response = Map::new();
+                response.insert("plaintext".to_string(), Value::String(STANDARD.encode(&plaintext)));
+                plaintext.zeroize();
+                Ok(Some(Response::new().data(response)))
+            }
+            (Operation::Write, "rotate") => {
+                self.rotate(&name).await?;
+                Ok(self.read_key(&name).await?.map(|info| Response::new().data(info)))
+            }
+            (Operation::Write, "rewrap") => {
+                let ciphertext = self.rewrap(&name, required_str(&data, "ciphertext")?).await?;
+                ciphertext_response(ciphertext)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::barrier_aes_gcm::AESGCMBarrier;
+    use crate::storage::physical_file::FileBackend;
+    use crate::storage::SecurityBarrier;
+
+    async fn transit_backend(dir: &tempfile::TempDir) -> TransitBackend {
+        let backend: Arc<dyn StorageBackend> = Arc::new(FileBackend::new(dir.path()).unwrap());
+        let barrier = Arc::new(AESGCMBarrier::new(backend));
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        TransitBackend::new(barrier, "transit".to_string())
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_rotate_rewrap_decrypt() {
+        for key_type in [KeyType::Aes256Gcm96, KeyType::ChaCha20Poly1305] {
+            let dir = tempfile::tempdir().unwrap();
+            let transit = transit_backend(&dir).await;
+
+            let v1 = transit.encrypt("patients", b"123-45-6789", key_type).await.unwrap();
+            assert!(v1.starts_with("vault:v1:"));
+
+            assert_eq!(transit.rotate("patients").await.unwrap(), 2);
+            let v2 = transit.rewrap("patients", &v1).await.unwrap();
+            assert!(v2.starts_with("vault:v2:"));
+
+            assert_eq!(transit.decrypt("patients", &v2).await.unwrap(), b"123-45-6789");
+            // Old versions stay decryptable after rotation
+            assert_eq!(transit.decrypt("patients", &v1).await.unwrap(), b"123-45-6789");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decrypt_rejects_tampered_ciphertext() {
+        let dir = tempfile::tempdir().unwrap();
+        let transit = transit_backend(&dir).await;
+        let ciphertext = transit.encrypt("patients", b"secret", KeyType::Aes256Gcm96).await.unwrap();
+
+        let (version, mut sealed) = parse_ciphertext(&ciphertext).unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 0x01;
+        let tampered = format!("vault:v{}:{}", version, STANDARD.encode(sealed));
+
+        assert!(transit.decrypt("patients", &tampered).await.is_err());
+        assert!(transit.decrypt("patients", "vault:v9:AAAA").await.is_err());
+        assert!(transit.decrypt("patients", "not-a-ciphertext").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_request_routes_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let transit = transit_backend(&dir).await;
+
+        let mut data = Map::new();
+        data.insert("plaintext".to_string(), Value::String(STANDARD.encode(b"hello")));
+        data.insert("type".to_string(), Value::String("chacha20-poly1305".to_string()));
+        let mut req = Request::new_write_request("transit/encrypt/app", Some(data));
+        let resp = transit.handle_request(&mut req).await.unwrap().unwrap();
+        let ciphertext = resp.data.unwrap()["ciphertext"].as_str().unwrap().to_string();
+
+        let mut req = Request::new_read_request("transit/keys/app");
+        let info = transit.handle_request(&mut req).await.unwrap().unwrap().data.unwrap();
+        assert_eq!(info["type"], "chacha20-poly1305");
+        assert!(!info.contains_key("keys"));
+
+        let mut data = Map::new();
+        data.insert("ciphertext".to_string(), Value::String(ciphertext));
+        let mut req = Request::new_write_request("transit/decrypt/app", Some(data));
+        let resp = transit.handle_request(&mut req).await.unwrap().unwrap();
+        assert_eq!(resp.data.unwrap()["plaintext"], STANDARD.encode(b"hello"));
+    }
+}
@@ -0,0 +1,205 @@
+//! Response wrapping
+//!
+//! Instead of returning a sensitive response (such as an AppRole secret ID)
+//! directly, it is stored in the `cubbyhole` KV mount and only a single-use
+//! wrapping token is returned. Whoever holds the token can unwrap the
+//! response exactly once; unwrapping destroys the stored copy, so a token
+//! that was intercepted and redeemed is noticed by the intended recipient.
+//!
+//! Entries are stored under `cubbyhole/{sha256(wrapping_token)}` so the
+//! token itself never appears in storage paths.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use crate::errors::{VaultError, VaultResult};
+use crate::logical::{Backend, Request};
+use crate::modules::kv::KvBackend;
+use crate::storage::StorageBackend;
+
+/// Mount path wrapped responses are stored under
+pub const CUBBYHOLE_MOUNT: &str = "cubbyhole";
+
+/// Lifetime of a wrapping token when the caller does not choose one
+pub const DEFAULT_WRAP_TTL_SECONDS: i64 = 300;
+
+/// Token returned in place of a wrapped response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrappedToken {
+    pub token: String,
+    /// Seconds the token can be unwrapped for
+    pub ttl: i64,
+    pub creation_time: DateTime<Utc>,
+    /// API path that produced the wrapped response
+    pub creation_path: String,
+}
+
+/// What a wrapping token holds, without the wrapped response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapInfo {
+    pub creation_path: String,
+    pub creation_time: DateTime<Utc>,
+    pub creation_ttl: i64,
+}
+
+impl WrapInfo {
+    fn expires_at(&self) -> DateTime<Utc> {
+        self.creation_time + Duration::seconds(self.creation_ttl)
+    }
+}
+
+/// Stores wrapped responses in the cubbyhole and hands them out once
+pub struct ResponseWrapper {
+    cubbyhole: KvBackend,
+    /// Serializes unwraps so two concurrent requests cannot both redeem a token
+    unwrap_lock: Mutex<()>,
+}
+
+impl ResponseWrapper {
+    pub fn new(storage: Arc<dyn StorageBackend>) -> Self {
+        Self {
+            cubbyhole: KvBackend::new(storage, CUBBYHOLE_MOUNT.to_string()),
+            unwrap_lock: Mutex::new(()),
+        }
+    }
+
+    /// Store `response` and return a wrapping token that unwraps it once
+    /// within `ttl`
+    pub async fn wrap(&self, response: Value, ttl: Duration, creation_path: &str) -> VaultResult<WrappedToken> {
+        if ttl <= Duration::zero() {
+            return Err(VaultError::Vault("wrapping TTL must be positive".to_string()));
+        }
+
+        let token = format!("hvw.{}", generate_random_string(26));
+        let info = WrapInfo {
+            creation_path: creation_path.to_string(),
+            creation_time: Utc::now(),
+            creation_ttl: ttl.num_seconds(),
+        };
+
+        let mut entry = Map::new();
+        entry.insert("info".to_string(), serde_json::to_value(&info)?);
+        entry.insert("response".to_string(), response);
+        let mut req = Request::new_write_request(cubbyhole_path(&token), Some(entry));
+        self.cubbyhole.handle_request(&mut req).await?;
+
+        Ok(WrappedToken {
+            token,
+            ttl: info.creation_ttl,
+            creation_time: info.creation_time,
+            creation_path: info.creation_path,
+        })
+    }
+
+    /// Details of a wrapping token that can still be unwrapped
+    pub async fn lookup(&self, token: &str) -> VaultResult<Option<WrapInfo>> {
+        Ok(self.read(token).await?.map(|(info, _)| info))
+    }
+
+    /// The wrapped response, destroying it so the token cannot be used again
+    ///
+    /// Returns `None` for unknown, already unwrapped and expired tokens.
+    pub async fn unwrap(&self, token: &str) -> VaultResult<Option<Value>> {
+        let _guard = self.unwrap_lock.lock().await;
+        let Some((_, response)) = self.read(token).await? else {
+            return Ok(None);
+        };
+        self.cubbyhole.destroy_secret(&token_key(token), None).await?;
+        Ok(Some(response))
+    }
+
+    /// Stored entry for `token`; expired entries are destroyed and not returned
+    async fn read(&self, token: &str) -> VaultResult<Option<(WrapInfo, Value)>> {
+        let mut req = Request::new_read_request(cubbyhole_path(token));
+        let Some(mut entry) = self
+            .cubbyhole
+            .handle_request(&mut req)
+            .await?
+            .and_then(|resp| resp.data)
+            .and_then(|mut data| data.remove("data"))
+        else {
+            return Ok(None);
+        };
+
+        let info: WrapInfo = serde_json::from_value(entry["info"].take())?;
+        if info.expires_at() <= Utc::now() {
+            self.cubbyhole.destroy_secret(&token_key(token), None).await?;
+            return Ok(None);
+        }
+        Ok(Some((info, entry["response"].take())))
+    }
+}
+
+fn token_key(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn cubbyhole_path(token: &str) -> String {
+    format!("{}/{}", CUBBYHOLE_MOUNT, token_key(token))
+}
+
+fn generate_random_string(len: usize) -> String {
+    use rand::Rng;
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use crate::storage::barrier_aes_gcm::AESGCMBarrier;
+    use crate::storage::physical_file::FileBackend;
+    use crate::storage::SecurityBarrier;
+
+    async fn wrapper(dir: &tempfile::TempDir) -> ResponseWrapper {
+        let backend: Arc<dyn StorageBackend> = Arc::new(FileBackend::new(dir.path()).unwrap());
+        let barrier = Arc::new(AESGCMBarrier::new(backend));
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        ResponseWrapper::new(barrier)
+    }
+
+    #[tokio::test]
+    async fn test_wrapped_response_unwraps_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let wrapper = wrapper(&dir).await;
+        let response = json!({"secret_id": "s3cr3t", "ttl": 3600});
+
+        let wrapped = wrapper
+            .wrap(response.clone(), Duration::seconds(60), "auth/approle/role/app/secret-id")
+            .await
+            .unwrap();
+        assert!(wrapped.token.starts_with("hvw."));
+        assert_eq!(wrapped.ttl, 60);
+
+        let info = wrapper.lookup(&wrapped.token).await.unwrap().unwrap();
+        assert_eq!(info.creation_path, "auth/approle/role/app/secret-id");
+        assert_eq!(info.creation_ttl, 60);
+
+        assert_eq!(wrapper.unwrap(&wrapped.token).await.unwrap(), Some(response));
+        assert!(wrapper.unwrap(&wrapped.token).await.unwrap().is_none());
+        assert!(wrapper.lookup(&wrapped.token).await.unwrap().is_none());
+        assert!(wrapper.unwrap("hvw.unknown").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_cannot_be_unwrapped() {
+        let dir = tempfile::tempdir().unwrap();
+        let wrapper = wrapper(&dir).await;
+
+        let wrapped = wrapper.wrap(json!({"secret_id": "s3cr3t"}), Duration::seconds(1), "test").await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        assert!(wrapper.unwrap(&wrapped.token).await.unwrap().is_none());
+
+        assert!(wrapper.wrap(json!({}), Duration::zero(), "test").await.is_err());
+    }
+}
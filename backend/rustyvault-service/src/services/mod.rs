@@ -1,4 +1,3 @@
 pub mod key_storage;
 pub mod audit_logger;
-
-
+pub mod token_tidier;
@@ -0,0 +1,67 @@
+//! Scheduled token tidying
+//!
+//! Expired tokens and tokens whose parent was deleted outside of revocation
+//! (orphans) would otherwise stay in `vault_tokens` forever. `TokenTidier`
+//! runs [`TokenStore::tidy`] on a cron schedule, hourly by default;
+//! `POST /v1/sys/token/tidy` runs the same cleanup on demand.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use shared::application::services::parse_cron_expression;
+
+use crate::errors::VaultResult;
+use crate::modules::auth::TokenStore;
+
+/// Hourly, on the hour (UTC)
+pub const DEFAULT_TOKEN_TIDY_SCHEDULE: &str = "0 * * * *";
+
+/// Deletes expired and orphan tokens on a schedule
+pub struct TokenTidier {
+    token_store: Arc<TokenStore>,
+    /// 5-field cron expression, see [`parse_cron_expression`]
+    schedule: String,
+}
+
+impl TokenTidier {
+    /// Tidy tokens on [`DEFAULT_TOKEN_TIDY_SCHEDULE`]
+    pub fn new(token_store: Arc<TokenStore>) -> Self {
+        Self {
+            token_store,
+            schedule: DEFAULT_TOKEN_TIDY_SCHEDULE.to_string(),
+        }
+    }
+
+    /// Run on a different cron schedule
+    pub fn with_schedule(mut self, expression: impl Into<String>) -> VaultResult<Self> {
+        let expression = expression.into();
+        parse_cron_expression(&expression)?;
+        self.schedule = expression;
+        Ok(self)
+    }
+
+    /// Tidy tokens at every scheduled time
+    pub fn spawn(self) -> VaultResult<tokio::task::JoinHandle<()>> {
+        let schedule = parse_cron_expression(&self.schedule)?;
+        Ok(tokio::spawn(async move {
+            loop {
+                let now = Utc::now();
+                let Some(next) = schedule.after(&now).next() else {
+                    tracing::warn!("Token tidy schedule '{}' has no further runs", self.schedule);
+                    return;
+                };
+                tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+
+                match self.token_store.tidy().await {
+                    Ok(result) if result.expired_deleted + result.orphans_deleted > 0 => tracing::info!(
+                        "Tidied {} expired and {} orphan tokens",
+                        result.expired_deleted,
+                        result.orphans_deleted
+                    ),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to tidy tokens: {}", e),
+                }
+            }
+        }))
+    }
+}
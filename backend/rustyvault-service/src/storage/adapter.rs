@@ -62,5 +62,27 @@ impl StorageBackend for StorageAdapter {
             self.barrier_store.list(prefix).await
         }
     }
+
+    async fn batch_get(&self, keys: &[String]) -> VaultResult<Vec<Option<Vec<u8>>>> {
+        let (metadata_keys, barrier_keys): (Vec<String>, Vec<String>) = keys
+            .iter()
+            .cloned()
+            .partition(|key| self.is_metadata_key(key));
+
+        let mut metadata_values = self.metadata_store.batch_get(&metadata_keys).await?.into_iter();
+        let mut barrier_values = self.barrier_store.batch_get(&barrier_keys).await?.into_iter();
+
+        // Reassemble in the caller's key order
+        Ok(keys
+            .iter()
+            .map(|key| {
+                if self.is_metadata_key(key) {
+                    metadata_values.next().flatten()
+                } else {
+                    barrier_values.next().flatten()
+                }
+            })
+            .collect())
+    }
 }
 
@@ -20,3 +20,8 @@ pub trait SecurityBarrier: Send + Sync {
     fn derive_hmac_key(&self) -> VaultResult<Vec<u8>>;
 }
 
+/// Counters exposed for monitoring
+pub trait BarrierStats {
+    /// Nonce counter values consumed so far (0 when nonces are random)
+    fn nonce_count(&self) -> u64;
+}
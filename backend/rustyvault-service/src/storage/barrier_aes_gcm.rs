@@ -3,6 +3,7 @@
 //! Adapted from RustyVault to use aes-gcm crate instead of OpenSSL
 
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use arc_swap::ArcSwap;
 use aes_gcm::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
@@ -13,15 +14,18 @@ use zeroize::{Zeroize, Zeroizing};
 use sha2::{Sha256, Digest};
 use async_trait::async_trait;
 use crate::errors::{VaultError, VaultResult};
-use crate::storage::{StorageBackend, SecurityBarrier, BARRIER_INIT_PATH};
+use crate::storage::{StorageBackend, SecurityBarrier, BarrierStats, BARRIER_INIT_PATH, NONCE_COUNTER_PATH, SEAL_CONFIG_PATH};
+use crate::storage::nonce_counter::NonceCounter;
 
 const EPOCH_SIZE: usize = 4;
-const KEY_EPOCH: u8 = 1;
+const KEY_EPOCH: u32 = 1;
 const AES_GCM_VERSION1: u8 = 0x1;
 const AES_GCM_VERSION2: u8 = 0x2;
 const AES_BLOCK_SIZE: usize = 16;
 const NONCE_SIZE: usize = 12; // GCM standard nonce size
 const TAG_SIZE: usize = 16;
+/// Path passed to encrypt/decrypt for snapshot payloads (never stored)
+const SNAPSHOT_PATH: &str = "sys/snapshot";
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Zeroize)]
 #[serde(deny_unknown_fields)]
@@ -29,6 +33,16 @@ const TAG_SIZE: usize = 16;
 struct BarrierInit {
     version: u32,
     key: Vec<u8>,
+    /// Epoch written into every ciphertext produced with `key`
+    #[serde(default = "default_key_version")]
+    key_version: u32,
+    /// Key of the previous epoch, kept only while a rotation is in progress
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    previous_key: Option<Vec<u8>>,
+}
+
+fn default_key_version() -> u32 {
+    KEY_EPOCH
 }
 
 #[derive(Debug, Clone, Zeroize)]
@@ -36,6 +50,8 @@ struct BarrierInit {
 struct BarrierInfo {
     sealed: bool,
     key: Option<Zeroizing<Vec<u8>>>,
+    key_version: u32,
+    previous_key: Option<Zeroizing<Vec<u8>>>,
     aes_gcm_version_byte: u8,
 }
 
@@ -44,14 +60,72 @@ impl Default for BarrierInfo {
         Self {
             sealed: true,
             key: None,
+            key_version: KEY_EPOCH,
+            previous_key: None,
             aes_gcm_version_byte: AES_GCM_VERSION2,
         }
     }
 }
 
+/// Point-in-time view of a key rotation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RotationStatus {
+    pub in_progress: bool,
+    pub total_entries: u64,
+    pub processed_entries: u64,
+}
+
+/// Progress counters for a running key rotation
+///
+/// Shared with the HTTP layer so large vaults can be polled while entries
+/// are being re-encrypted.
+#[derive(Debug, Default)]
+pub struct RotationProgress {
+    in_progress: AtomicBool,
+    total_entries: AtomicU64,
+    processed_entries: AtomicU64,
+}
+
+impl RotationProgress {
+    /// Mark a rotation as started; returns false if one is already running
+    pub fn try_start(&self) -> bool {
+        let started = self
+            .in_progress
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if started {
+            self.total_entries.store(0, Ordering::Release);
+            self.processed_entries.store(0, Ordering::Release);
+        }
+        started
+    }
+
+    pub fn finish(&self) {
+        self.in_progress.store(false, Ordering::Release);
+    }
+
+    fn set_total(&self, total: u64) {
+        self.total_entries.store(total, Ordering::Release);
+    }
+
+    fn advance(&self) {
+        self.processed_entries.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn status(&self) -> RotationStatus {
+        RotationStatus {
+            in_progress: self.in_progress.load(Ordering::Acquire),
+            total_entries: self.total_entries.load(Ordering::Acquire),
+            processed_entries: self.processed_entries.load(Ordering::Acquire),
+        }
+    }
+}
+
 pub struct AESGCMBarrier {
     barrier_info: ArcSwap<BarrierInfo>,
     backend: Arc<dyn StorageBackend>,
+    /// Source of deterministic nonces; random nonces are used when unset
+    nonce_counter: Option<Arc<NonceCounter>>,
 }
 
 impl AESGCMBarrier {
@@ -59,12 +133,26 @@ impl AESGCMBarrier {
         Self {
             backend,
             barrier_info: ArcSwap::from_pointee(BarrierInfo::default()),
+            nonce_counter: None,
         }
     }
 
+    /// Build nonces from a persistent counter instead of the RNG
+    pub fn with_nonce_counter(mut self, counter: Arc<NonceCounter>) -> Self {
+        self.nonce_counter = Some(counter);
+        self
+    }
+
     fn init_cipher(&self, key: &[u8]) -> VaultResult<()> {
+        self.install_keys(key, KEY_EPOCH, None)
+    }
+
+    /// Swap in the active key (and optional previous-epoch key) in one store
+    fn install_keys(&self, key: &[u8], key_version: u32, previous_key: Option<&[u8]>) -> VaultResult<()> {
         let mut barrier_info = (*self.barrier_info.load_full()).clone();
         barrier_info.key = Some(Zeroizing::new(key.to_vec()));
+        barrier_info.key_version = key_version;
+        barrier_info.previous_key = previous_key.map(|k| Zeroizing::new(k.to_vec()));
         self.barrier_info.store(Arc::new(barrier_info));
         Ok(())
     }
@@ -75,10 +163,16 @@ impl AESGCMBarrier {
             key.zeroize();
         }
         barrier_info.key = None;
+        barrier_info.previous_key = None;
         self.barrier_info.store(Arc::new(barrier_info));
         Ok(())
     }
 
+    /// Current encryption key version (epoch)
+    pub fn key_version(&self) -> u32 {
+        self.barrier_info.load().key_version
+    }
+
     fn encrypt(&self, _path: &str, plaintext: &[u8]) -> VaultResult<Vec<u8>> {
         let barrier_info = self.barrier_info.load();
         let key = barrier_info.key.as_ref()
@@ -89,11 +183,14 @@ impl AESGCMBarrier {
             .map_err(|e| VaultError::Vault(format!("Failed to create cipher: {}", e)))?;
 
         // Generate nonce
-        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let nonce = match &self.nonce_counter {
+            Some(counter) => Nonce::from(counter.next_nonce()?),
+            None => Aes256Gcm::generate_nonce(&mut OsRng),
+        };
 
         // Prepare output buffer: epoch(4) + version(1) + nonce(12) + ciphertext + tag(16)
         let mut out = vec![0u8; EPOCH_SIZE + 1 + NONCE_SIZE + plaintext.len() + TAG_SIZE];
-        out[3] = KEY_EPOCH;
+        out[..EPOCH_SIZE].copy_from_slice(&barrier_info.key_version.to_be_bytes());
         out[4] = barrier_info.aes_gcm_version_byte;
         out[5..5 + NONCE_SIZE].copy_from_slice(nonce.as_slice());
 
@@ -111,13 +208,19 @@ impl AESGCMBarrier {
             return Err(VaultError::Vault("Ciphertext too short".to_string()));
         }
 
-        if ciphertext[0] != 0 || ciphertext[1] != 0 || ciphertext[2] != 0 || ciphertext[3] != KEY_EPOCH {
-            return Err(VaultError::Vault("Epoch mismatch".to_string()));
-        }
+        let epoch = u32::from_be_bytes([ciphertext[0], ciphertext[1], ciphertext[2], ciphertext[3]]);
 
         let barrier_info = self.barrier_info.load();
-        let key = barrier_info.key.as_ref()
-            .ok_or_else(|| VaultError::Vault("Barrier not initialized".to_string()))?;
+        let key = if epoch == barrier_info.key_version {
+            barrier_info.key.as_ref()
+                .ok_or_else(|| VaultError::Vault("Barrier not initialized".to_string()))?
+        } else {
+            // Entries not yet re-encrypted by an in-progress rotation
+            match barrier_info.previous_key.as_ref() {
+                Some(previous_key) if epoch.checked_add(1) == Some(barrier_info.key_version) => previous_key,
+                _ => return Err(VaultError::Vault("Epoch mismatch".to_string())),
+            }
+        };
 
         let _version = ciphertext[4];
         let nonce = Nonce::from_slice(&ciphertext[5..5 + NONCE_SIZE]);
@@ -133,6 +236,216 @@ impl AESGCMBarrier {
 
         Ok(plaintext)
     }
+
+    /// Encrypt the barrier init with the KEK and write it to the backend
+    async fn write_barrier_init(&self, kek: &[u8], barrier_init: &BarrierInit) -> VaultResult<()> {
+        let serialized = Zeroizing::new(serde_json::to_vec(barrier_init)
+            .map_err(|e| VaultError::Serialization(e))?);
+
+        let mut kek_barrier = AESGCMBarrier::new(self.backend.clone());
+        kek_barrier.nonce_counter = self.nonce_counter.clone();
+        kek_barrier.init_cipher(kek)?;
+        let value = kek_barrier.encrypt(BARRIER_INIT_PATH, &serialized)?;
+        self.backend.put(BARRIER_INIT_PATH, &value).await
+    }
+
+    /// Recursively list every key in the physical backend
+    async fn list_all_keys(&self) -> VaultResult<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut pending = self.backend.list("").await?;
+
+        while let Some(entry) = pending.pop() {
+            match self.backend.list(&entry).await {
+                Ok(children) if !children.is_empty() => pending.extend(children),
+                // Empty directories are left behind after deletes
+                Ok(_) if self.backend.get(&entry).await.is_err() => {}
+                _ => keys.push(entry),
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Re-encrypt every stored entry under `new_key` and make it the active key
+    ///
+    /// The new key is persisted (alongside the old one) and activated before
+    /// any entry is rewritten, so reads keep working throughout. If an earlier
+    /// rotation was interrupted, its entries are moved to the current key
+    /// first, so the previous key is never overwritten while still in use.
+    /// Returns the new key version.
+    pub async fn rotate_key(&self, kek: &[u8], new_key: &[u8], progress: &RotationProgress) -> VaultResult<u32> {
+        if self.sealed()? {
+            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+        }
+
+        let (min, max) = self.key_length_range();
+        if new_key.len() < min || new_key.len() > max {
+            return Err(VaultError::Vault("Invalid key length".to_string()));
+        }
+
+        if self.barrier_info.load().previous_key.is_some() {
+            tracing::info!("Finishing interrupted rotation to key version {}", self.key_version());
+            self.complete_rotation(kek, progress).await?;
+        }
+
+        let barrier_info = self.barrier_info.load_full();
+        let old_key = barrier_info.key.clone()
+            .ok_or_else(|| VaultError::Vault("Barrier not initialized".to_string()))?;
+        let new_version = barrier_info.key_version.checked_add(1)
+            .ok_or_else(|| VaultError::Vault("Key version overflow".to_string()))?;
+
+        // 1. Persist both keys so entries stay readable if rotation is interrupted
+        self.write_barrier_init(kek, &BarrierInit {
+            version: 1,
+            key: new_key.to_vec(),
+            key_version: new_version,
+            previous_key: Some(old_key.to_vec()),
+        }).await?;
+
+        // 2. Switch the active key; old-epoch entries decrypt with the previous key
+        self.install_keys(new_key, new_version, Some(old_key.as_slice()))?;
+
+        // 3. Re-encrypt every entry written under the old key, then forget it
+        self.complete_rotation(kek, progress).await?;
+
+        tracing::info!("Barrier encryption key rotated to version {}", new_version);
+        Ok(new_version)
+    }
+
+    /// Re-encrypt every entry still under the previous key with the active key,
+    /// then drop the previous key from the stored barrier init
+    async fn complete_rotation(&self, kek: &[u8], progress: &RotationProgress) -> VaultResult<()> {
+        let barrier_info = self.barrier_info.load_full();
+        let key = barrier_info.key.clone()
+            .ok_or_else(|| VaultError::Vault("Barrier not initialized".to_string()))?;
+        let key_version = barrier_info.key_version;
+
+        let keys = self.list_all_keys().await?;
+        progress.set_total(keys.len() as u64);
+
+        for entry in keys {
+            // These entries are stored outside the barrier
+            if ![BARRIER_INIT_PATH, NONCE_COUNTER_PATH, SEAL_CONFIG_PATH].contains(&entry.as_str()) {
+                self.reencrypt_entry(&entry, key_version).await?;
+            }
+            progress.advance();
+        }
+
+        self.write_barrier_init(kek, &BarrierInit {
+            version: 1,
+            key: key.to_vec(),
+            key_version,
+            previous_key: None,
+        }).await?;
+        self.install_keys(&key, key_version, None)
+    }
+
+    /// Every physical entry as stored (still encrypted), sorted by key
+    ///
+    /// The nonce counter is left out: it tracks this instance's nonces and
+    /// must never be rolled back.
+    pub async fn export_entries(&self) -> VaultResult<Vec<(String, Vec<u8>)>> {
+        if self.sealed()? {
+            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+        }
+        self.stored_entries().await
+    }
+
+    /// Every physical entry except the nonce counter, sealed or not
+    pub async fn stored_entries(&self) -> VaultResult<Vec<(String, Vec<u8>)>> {
+        let keys: Vec<String> = self.list_all_keys().await?
+            .into_iter()
+            .filter(|key| key != NONCE_COUNTER_PATH)
+            .collect();
+        let values = self.backend.batch_get(&keys).await?;
+        Ok(keys
+            .into_iter()
+            .zip(values)
+            .filter_map(|(key, value)| value.map(|value| (key, value)))
+            .collect())
+    }
+
+    /// Replace every physical entry (except the nonce counter) with `entries`
+    ///
+    /// Entries are written as given, so they must come from `export_entries`.
+    /// Old entries are only deleted once every new one is written; if any
+    /// write or delete fails the previous entries are written back, so
+    /// storage holds either the old or the new entries, never a mix.
+    pub async fn import_entries(&self, entries: &[(String, Vec<u8>)]) -> VaultResult<()> {
+        let previous = self.stored_entries().await?;
+        if let Err(e) = self.replace_entries(entries, &previous).await {
+            if let Err(rollback) = self.replace_entries(&previous, entries).await {
+                tracing::error!("Failed to restore entries after a failed import: {}", rollback);
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Write `entries`, then delete the keys of `current` they do not include
+    async fn replace_entries(&self, entries: &[(String, Vec<u8>)], current: &[(String, Vec<u8>)]) -> VaultResult<()> {
+        for (key, value) in entries {
+            if key != NONCE_COUNTER_PATH {
+                self.backend.put(key, value).await?;
+            }
+        }
+        let kept: std::collections::HashSet<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        for (key, _) in current {
+            if !kept.contains(key.as_str()) && key != NONCE_COUNTER_PATH {
+                self.backend.delete(key).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encrypt a snapshot payload with the active key
+    pub fn encrypt_snapshot(&self, payload: &[u8]) -> VaultResult<Vec<u8>> {
+        if self.sealed()? {
+            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+        }
+        self.encrypt(SNAPSHOT_PATH, payload)
+    }
+
+    /// Decrypt a snapshot payload with the key from the snapshot's own barrier init
+    ///
+    /// `barrier_init` is the stored (KEK-encrypted) barrier init of the vault
+    /// the snapshot was taken from, so this works on a fresh instance and
+    /// leaves this barrier's keys untouched.
+    pub fn decrypt_snapshot(&self, kek: &[u8], barrier_init: &[u8], payload: &[u8]) -> VaultResult<Zeroizing<Vec<u8>>> {
+        let kek_barrier = AESGCMBarrier::new(self.backend.clone());
+        kek_barrier.init_cipher(kek)?;
+        let value = Zeroizing::new(kek_barrier.decrypt(BARRIER_INIT_PATH, barrier_init)?);
+        let barrier_init: BarrierInit = serde_json::from_slice(&value)
+            .map_err(|e| VaultError::Serialization(e))?;
+
+        let snapshot_barrier = AESGCMBarrier::new(self.backend.clone());
+        snapshot_barrier.install_keys(
+            barrier_init.key.as_slice(),
+            barrier_init.key_version,
+            barrier_init.previous_key.as_deref(),
+        )?;
+        snapshot_barrier.decrypt(SNAPSHOT_PATH, payload).map(Zeroizing::new)
+    }
+
+    async fn reencrypt_entry(&self, key: &str, new_version: u32) -> VaultResult<()> {
+        let Some(ciphertext) = self.backend.get(key).await? else {
+            return Ok(());
+        };
+
+        if ciphertext.len() >= EPOCH_SIZE && ciphertext[..EPOCH_SIZE] == new_version.to_be_bytes() {
+            return Ok(());
+        }
+
+        // An entry that cannot be decrypted must stop the rotation: dropping
+        // the previous key afterwards would lose it for good
+        let plaintext = Zeroizing::new(self.decrypt(key, &ciphertext).map_err(|e| {
+            VaultError::Vault(format!("Cannot re-encrypt {}: {}", key, e))
+        })?);
+
+        let reencrypted = self.encrypt(key, &plaintext)?;
+        self.backend.put(key, &reencrypted).await
+    }
 }
 
 #[async_trait]
@@ -163,18 +476,12 @@ impl SecurityBarrier for AESGCMBarrier {
         let barrier_init = BarrierInit {
             version: 1,
             key: encrypt_key.to_vec(),
+            key_version: KEY_EPOCH,
+            previous_key: None,
         };
 
-        let serialized_barrier_init = serde_json::to_string(&barrier_init)
-            .map_err(|e| VaultError::Serialization(e))?;
-
         // Use KEK to encrypt the barrier init
-        self.init_cipher(kek)?;
-        let value = self.encrypt(BARRIER_INIT_PATH, serialized_barrier_init.as_bytes())?;
-        self.backend.put(BARRIER_INIT_PATH, &value).await?;
-        self.reset_cipher()?;
-
-        Ok(())
+        self.write_barrier_init(kek, &barrier_init).await
     }
 
     fn generate_key(&self) -> VaultResult<Zeroizing<Vec<u8>>> {
@@ -210,8 +517,12 @@ impl SecurityBarrier for AESGCMBarrier {
         let barrier_init: BarrierInit = serde_json::from_slice(&value)
             .map_err(|e| VaultError::Serialization(e))?;
 
-        // Use the real encryption key
-        self.init_cipher(barrier_init.key.as_slice())?;
+        // Use the real encryption key (plus the previous one if a rotation was interrupted)
+        self.install_keys(
+            barrier_init.key.as_slice(),
+            barrier_init.key_version,
+            barrier_init.previous_key.as_deref(),
+        )?;
 
         let mut barrier_info = (*self.barrier_info.load_full()).clone();
         barrier_info.sealed = false;
@@ -243,6 +554,12 @@ impl SecurityBarrier for AESGCMBarrier {
     }
 }
 
+impl BarrierStats for AESGCMBarrier {
+    fn nonce_count(&self) -> u64 {
+        self.nonce_counter.as_ref().map_or(0, |counter| counter.count())
+    }
+}
+
 #[async_trait]
 impl StorageBackend for AESGCMBarrier {
     async fn get(&self, key: &str) -> VaultResult<Option<Vec<u8>>> {
@@ -284,5 +601,145 @@ impl StorageBackend for AESGCMBarrier {
         keys.sort();
         Ok(keys)
     }
+
+    async fn batch_get(&self, keys: &[String]) -> VaultResult<Vec<Option<Vec<u8>>>> {
+        if self.sealed()? {
+            return Err(VaultError::Vault("Barrier is sealed".to_string()));
+        }
+
+        let encrypted = self.backend.batch_get(keys).await?;
+        keys.iter()
+            .zip(encrypted)
+            .map(|(key, value)| value.map(|ciphertext| self.decrypt(key, &ciphertext)).transpose())
+            .collect()
+    }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::physical_file::FileBackend;
+
+    async fn unsealed_barrier(dir: &tempfile::TempDir) -> (AESGCMBarrier, Zeroizing<Vec<u8>>) {
+        let backend: Arc<dyn StorageBackend> = Arc::new(FileBackend::new(dir.path()).unwrap());
+        let barrier = AESGCMBarrier::new(backend);
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        (barrier, kek)
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_reencrypts_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let (barrier, kek) = unsealed_barrier(&dir).await;
+        barrier.put("secret/app/db", b"password").await.unwrap();
+        barrier.put("secret/api-key", b"key").await.unwrap();
+
+        let new_key = barrier.generate_key().unwrap();
+        let progress = RotationProgress::default();
+        let version = barrier.rotate_key(&kek, &new_key, &progress).await.unwrap();
+
+        assert_eq!(version, 2);
+        assert_eq!(barrier.key_version(), 2);
+        let status = progress.status();
+        assert_eq!(status.processed_entries, status.total_entries);
+        assert_eq!(barrier.get("secret/app/db").await.unwrap(), Some(b"password".to_vec()));
+
+        // Entries survive a seal/unseal cycle with only the new key
+        barrier.seal().unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        assert_eq!(barrier.get("secret/api-key").await.unwrap(), Some(b"key".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_finishes_interrupted_rotation_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let (barrier, kek) = unsealed_barrier(&dir).await;
+        barrier.put("secret/app/db", b"password").await.unwrap();
+        let first_key = barrier.barrier_info.load().key.clone().unwrap();
+
+        // Rotation to version 2 stopped before any entry was re-encrypted
+        let second_key = barrier.generate_key().unwrap();
+        barrier.write_barrier_init(&kek, &BarrierInit {
+            version: 1,
+            key: second_key.to_vec(),
+            key_version: 2,
+            previous_key: Some(first_key.to_vec()),
+        }).await.unwrap();
+        barrier.seal().unwrap();
+        barrier.unseal(&kek).await.unwrap();
+
+        let third_key = barrier.generate_key().unwrap();
+        let version = barrier.rotate_key(&kek, &third_key, &RotationProgress::default()).await.unwrap();
+
+        assert_eq!(version, 3);
+        barrier.seal().unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        assert_eq!(barrier.get("secret/app/db").await.unwrap(), Some(b"password".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_keeps_previous_key_when_entry_cannot_be_decrypted() {
+        let dir = tempfile::tempdir().unwrap();
+        let (barrier, kek) = unsealed_barrier(&dir).await;
+        barrier.put("secret/app/db", b"password").await.unwrap();
+        let mut corrupt = barrier.backend.get("secret/app/db").await.unwrap().unwrap();
+        let last = corrupt.len() - 1;
+        corrupt[last] ^= 0xff;
+        barrier.backend.put("secret/corrupt", &corrupt).await.unwrap();
+
+        let new_key = barrier.generate_key().unwrap();
+        let result = barrier.rotate_key(&kek, &new_key, &RotationProgress::default()).await;
+        assert!(result.is_err());
+
+        // The old key is still stored, so entries not yet re-encrypted stay readable
+        barrier.seal().unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        assert_eq!(barrier.key_version(), 2);
+        assert!(barrier.barrier_info.load().previous_key.is_some());
+        assert_eq!(barrier.get("secret/app/db").await.unwrap(), Some(b"password".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_counter_nonces_are_unique_under_concurrency() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileBackend::new(dir.path()).unwrap();
+        let counter = Arc::new(NonceCounter::open(backend.entry_path(crate::storage::NONCE_COUNTER_PATH)).unwrap());
+        let barrier = AESGCMBarrier::new(Arc::new(backend)).with_nonce_counter(counter);
+        let kek = barrier.generate_key().unwrap();
+        barrier.init(&kek).await.unwrap();
+        barrier.unseal(&kek).await.unwrap();
+        let start = barrier.nonce_count();
+
+        let nonces: Vec<Vec<u8>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| {
+                    (0..1250)
+                        .map(|_| barrier.encrypt("secret/x", b"data").unwrap()[5..5 + NONCE_SIZE].to_vec())
+                        .collect::<Vec<_>>()
+                }))
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+
+        let unique: std::collections::HashSet<_> = nonces.iter().collect();
+        assert_eq!(unique.len(), 10_000);
+        assert_eq!(barrier.nonce_count(), start + 10_000);
+        // Counter-built ciphertexts still round-trip
+        barrier.put("secret/app", b"value").await.unwrap();
+        assert_eq!(barrier.get("secret/app").await.unwrap(), Some(b"value".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_requires_unsealed_barrier() {
+        let dir = tempfile::tempdir().unwrap();
+        let (barrier, kek) = unsealed_barrier(&dir).await;
+        barrier.seal().unwrap();
+
+        let new_key = barrier.generate_key().unwrap();
+        let result = barrier.rotate_key(&kek, &new_key, &RotationProgress::default()).await;
+        assert!(result.is_err());
+    }
+}
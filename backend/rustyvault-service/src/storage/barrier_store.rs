@@ -47,5 +47,9 @@ impl StorageBackend for BarrierStore {
     async fn list(&self, prefix: &str) -> VaultResult<Vec<String>> {
         self.barrier.list(prefix).await
     }
+
+    async fn batch_get(&self, keys: &[String]) -> VaultResult<Vec<Option<Vec<u8>>>> {
+        self.barrier.batch_get(keys).await
+    }
 }
 
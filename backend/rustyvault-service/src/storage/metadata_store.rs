@@ -1,5 +1,6 @@
 //! Metadata storage using health-v1 PostgreSQL database
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use async_trait::async_trait;
 use sqlx::PgPool;
@@ -62,5 +63,24 @@ impl StorageBackend for MetadataStore {
 
         Ok(keys)
     }
+
+    async fn batch_get(&self, keys: &[String]) -> VaultResult<Vec<Option<Vec<u8>>>> {
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query!(
+            "SELECT key, value FROM vault_metadata WHERE key = ANY($1)",
+            keys
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        let found: HashMap<String, Vec<u8>> = rows
+            .into_iter()
+            .map(|row| (row.key, row.value))
+            .collect();
+        Ok(keys.iter().map(|key| found.get(key).cloned()).collect())
+    }
 }
 
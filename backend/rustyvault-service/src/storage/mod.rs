@@ -8,14 +8,21 @@ pub mod barrier_store;
 pub mod adapter;
 pub mod barrier;
 pub mod barrier_aes_gcm;
+pub mod nonce_counter;
 pub mod physical_file;
 
 pub use storage_backend::StorageBackend;
 pub use metadata_store::MetadataStore;
 pub use barrier_store::BarrierStore;
 pub use adapter::StorageAdapter;
-pub use barrier::SecurityBarrier;
+pub use barrier::{BarrierStats, SecurityBarrier};
 
 /// Path for barrier initialization data
 pub const BARRIER_INIT_PATH: &str = "core/barrier-init";
 
+/// Path of the barrier nonce counter in the file backend (stored unencrypted)
+pub const NONCE_COUNTER_PATH: &str = "core/nonce-counter";
+
+/// Path of the seal configuration in the physical backend (stored unencrypted)
+pub const SEAL_CONFIG_PATH: &str = "core/seal-config";
+
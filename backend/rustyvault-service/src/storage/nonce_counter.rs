@@ -0,0 +1,128 @@
+//! Persistent nonce counter for the AES-GCM barrier
+//!
+//! Random 96-bit nonces collide with probability ~2^-32 after 2^32 encryptions,
+//! which a long-lived barrier key can reach. Nonces are instead built as
+//! `counter(8, big-endian) || random(4)`, where the counter is persisted and
+//! fsynced before the nonce is handed out, so a value is never issued twice
+//! under the same key, even across restarts.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use rand::RngCore;
+use crate::errors::{VaultError, VaultResult};
+
+/// Values skipped on startup in case a crash lost the last counter write
+pub const NONCE_SAFETY_MARGIN: u64 = 1000;
+
+const COUNTER_SIZE: usize = 8;
+
+pub struct NonceCounter {
+    next: AtomicU64,
+    /// Counter file plus the highest value known to be on disk
+    file: Mutex<(File, u64)>,
+}
+
+impl NonceCounter {
+    /// Open (or create) the counter file and take an exclusive lock on it
+    ///
+    /// The stored value is advanced by `NONCE_SAFETY_MARGIN` and persisted
+    /// before any nonce is issued.
+    pub fn open(path: impl AsRef<Path>) -> VaultResult<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(VaultError::Io)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .map_err(VaultError::Io)?;
+        // flock(2) on POSIX: a second process sharing the counter would reuse values
+        file.try_lock()
+            .map_err(|_| VaultError::Vault("Nonce counter is locked by another process".to_string()))?;
+
+        let mut buf = Vec::with_capacity(COUNTER_SIZE);
+        file.read_to_end(&mut buf).map_err(VaultError::Io)?;
+        let stored = match buf.len() {
+            0 => 0,
+            COUNTER_SIZE => u64::from_be_bytes(buf.try_into().expect("length checked")),
+            _ => return Err(VaultError::Vault("Nonce counter file is corrupt".to_string())),
+        };
+
+        let start = stored.checked_add(NONCE_SAFETY_MARGIN)
+            .ok_or_else(|| VaultError::Vault("Nonce counter exhausted".to_string()))?;
+        Self::persist(&mut file, start)?;
+
+        Ok(Self {
+            next: AtomicU64::new(start),
+            file: Mutex::new((file, start)),
+        })
+    }
+
+    fn persist(file: &mut File, value: u64) -> VaultResult<()> {
+        file.seek(SeekFrom::Start(0)).map_err(VaultError::Io)?;
+        file.write_all(&value.to_be_bytes()).map_err(VaultError::Io)?;
+        file.sync_data().map_err(VaultError::Io)
+    }
+
+    /// Reserve the next counter value, persisting it before returning
+    pub fn next(&self) -> VaultResult<u64> {
+        let value = self.next.fetch_add(1, Ordering::SeqCst);
+        if value == u64::MAX {
+            return Err(VaultError::Vault("Nonce counter exhausted".to_string()));
+        }
+
+        let mut guard = self.file.lock().unwrap();
+        let (file, persisted) = &mut *guard;
+        // Concurrent callers share one write: whoever gets the lock first
+        // persists the highest value reserved so far
+        if *persisted <= value {
+            let high = self.next.load(Ordering::SeqCst);
+            Self::persist(file, high)?;
+            *persisted = high;
+        }
+        Ok(value)
+    }
+
+    /// Build a 96-bit GCM nonce: counter (8 bytes) followed by 4 random bytes
+    pub fn next_nonce(&self) -> VaultResult<[u8; COUNTER_SIZE + 4]> {
+        let mut nonce = [0u8; COUNTER_SIZE + 4];
+        nonce[..COUNTER_SIZE].copy_from_slice(&self.next()?.to_be_bytes());
+        rand::thread_rng().fill_bytes(&mut nonce[COUNTER_SIZE..]);
+        Ok(nonce)
+    }
+
+    /// Counter values issued or skipped so far
+    pub fn count(&self) -> u64 {
+        self.next.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_persists_across_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("core/nonce-counter");
+
+        let counter = NonceCounter::open(&path).unwrap();
+        let first = counter.next().unwrap();
+        assert_eq!(first, NONCE_SAFETY_MARGIN);
+        let last = (0..10).map(|_| counter.next().unwrap()).last().unwrap();
+
+        // The lock is exclusive while the counter is open
+        assert!(NonceCounter::open(&path).is_err());
+        drop(counter);
+
+        let reopened = NonceCounter::open(&path).unwrap();
+        assert!(reopened.next().unwrap() > last + NONCE_SAFETY_MARGIN);
+    }
+}
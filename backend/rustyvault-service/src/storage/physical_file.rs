@@ -31,6 +31,12 @@ impl FileBackend {
         };
         (dir_path, file_name)
     }
+
+    /// On-disk location of `key`, for entries that need direct file access
+    pub fn entry_path(&self, key: &str) -> PathBuf {
+        let (dir_path, file_name) = self.path_key(key);
+        dir_path.join(file_name)
+    }
 }
 
 #[async_trait]
@@ -17,5 +17,17 @@ pub trait StorageBackend: Send + Sync {
 
     /// List keys with prefix
     async fn list(&self, prefix: &str) -> VaultResult<Vec<String>>;
+
+    /// Get many values, in the same order as `keys`
+    ///
+    /// Backends that can fetch several keys in one roundtrip should override
+    /// this; the default reads them one at a time.
+    async fn batch_get(&self, keys: &[String]) -> VaultResult<Vec<Option<Vec<u8>>>> {
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            values.push(self.get(key).await?);
+        }
+        Ok(values)
+    }
 }
 
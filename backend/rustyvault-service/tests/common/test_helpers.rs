@@ -154,10 +154,12 @@ pub fn create_test_settings(port: u16, database_url: &str, storage_path: &str) -
         logging: LoggingConfig {
             level: "error".to_string(),
             rust_log: "error".to_string(),
+            body_sampling_rate: 0.0,
         },
         deployment: DeploymentConfig {
             environment: shared::config::deployment::DeploymentEnvironment::Development,
             cloud_provider: shared::config::deployment::CloudProvider::None,
+            features: shared::config::FeatureFlags::default(),
         },
         barrier: BarrierConfig {
             algorithm: "aes-gcm".to_string(),
@@ -213,3 +213,103 @@ async fn test_delete_approle() {
     
     assert_eq!(get_response.status(), 404, "AppRole should not exist after deletion");
 }
+
+#[tokio::test]
+#[ignore]
+async fn test_wrapped_secret_id_unwraps_once() {
+    let client = create_docker_compose_test_env().await.expect("Failed to setup test environment");
+    let (root_token, _keys) = setup_initialized_vault(&client, 5, 3).await;
+    
+    let role_name = "wrapped-secret-id-role";
+    
+    // Create AppRole
+    client
+        .post(&format!("/v1/auth/approle/role/{}", role_name))
+        .header("X-Vault-Token", &root_token)
+        .json(&serde_json::json!({
+            "policies": ["default"],
+            "bind_secret_id": true
+        }))
+        .send()
+        .await
+        .expect("Failed to create AppRole");
+    
+    let role_response = client
+        .get(&format!("/v1/auth/approle/role/{}", role_name))
+        .header("X-Vault-Token", &root_token)
+        .send()
+        .await
+        .expect("Failed to get AppRole");
+    
+    let role_data: serde_json::Value = role_response.json().await.expect("Failed to parse response");
+    let role_id = role_data.get("data")
+        .and_then(|v| v.get("role_id"))
+        .and_then(|v| v.as_str())
+        .expect("Response should have role_id");
+    
+    // Generate a wrapped secret-id
+    let wrap_response = client
+        .post(&format!("/v1/auth/approle/role/{}/secret-id/wrap", role_name))
+        .header("X-Vault-Token", &root_token)
+        .json(&serde_json::json!({ "ttl": 60 }))
+        .send()
+        .await
+        .expect("Failed to generate wrapped secret-id");
+    
+    assert!(wrap_response.status().is_success(), "Wrapped secret-id generation should succeed");
+    
+    let wrap_data: serde_json::Value = wrap_response.json().await.expect("Failed to parse response");
+    assert!(wrap_data.get("data").is_none(), "Wrapped response should not contain the secret-id");
+    let wrapping_token = wrap_data.get("wrap_info")
+        .and_then(|v| v.get("token"))
+        .and_then(|v| v.as_str())
+        .expect("Response should have a wrapping token");
+    
+    // Lookup shows the token without unwrapping it
+    let lookup_response = client
+        .get(&format!("/v1/sys/wrapping/lookup/{}", wrapping_token))
+        .send()
+        .await
+        .expect("Failed to lookup wrapping token");
+    
+    assert!(lookup_response.status().is_success(), "Wrapping token lookup should succeed");
+    
+    // Unwrap once
+    let unwrap_response = client
+        .post("/v1/sys/wrapping/unwrap")
+        .json(&serde_json::json!({ "token": wrapping_token }))
+        .send()
+        .await
+        .expect("Failed to unwrap");
+    
+    assert!(unwrap_response.status().is_success(), "First unwrap should succeed");
+    
+    let unwrap_data: serde_json::Value = unwrap_response.json().await.expect("Failed to parse response");
+    let secret_id = unwrap_data.get("data")
+        .and_then(|v| v.get("secret_id"))
+        .and_then(|v| v.as_str())
+        .expect("Unwrapped response should have secret_id");
+    
+    // The unwrapped secret-id is valid
+    let login_response = client
+        .post("/v1/auth/approle/login")
+        .json(&serde_json::json!({
+            "role_id": role_id,
+            "secret_id": secret_id
+        }))
+        .send()
+        .await
+        .expect("Failed to login");
+    
+    assert!(login_response.status().is_success(), "AppRole login with the unwrapped secret-id should succeed");
+    
+    // The wrapping token is single-use
+    let second_unwrap = client
+        .post("/v1/sys/wrapping/unwrap")
+        .json(&serde_json::json!({ "token": wrapping_token }))
+        .send()
+        .await
+        .expect("Failed to unwrap");
+    
+    assert_eq!(second_unwrap.status(), 404, "Second unwrap should not find the token");
+}
@@ -278,3 +278,83 @@ async fn test_realm_isolation() {
     // Should not find the secret in realm2 (isolation)
     assert_ne!(response.status(), 200, "Secret from realm1 should not be accessible in realm2");
 }
+
+#[tokio::test]
+#[ignore]
+async fn test_suspended_realm_refuses_requests_until_activated() {
+    let client = create_docker_compose_test_env().await.expect("Failed to setup test environment");
+    let (root_token, _keys) = setup_initialized_vault(&client, 5, 3).await;
+    
+    // Create a realm
+    let response = client
+        .post("/v1/sys/realm")
+        .header("X-Vault-Token", &root_token)
+        .json(&serde_json::json!({
+            "name": "suspend-test-realm",
+            "organization_id": "123e4567-e89b-12d3-a456-426614174010"
+        }))
+        .send()
+        .await
+        .expect("Failed to create realm");
+    
+    let result: serde_json::Value = response.json().await.expect("Failed to parse response");
+    let realm_id = result.get("realm_id")
+        .and_then(|v| v.as_str())
+        .expect("Response should include realm_id")
+        .to_string();
+    let secret_path = format!("/v1/realm/{}/secret/data/app/config", realm_id);
+    
+    // Write a secret in the realm
+    client
+        .post(&secret_path)
+        .header("X-Vault-Token", &root_token)
+        .json(&serde_json::json!({ "data": { "api_key": "secret" } }))
+        .send()
+        .await
+        .expect("Failed to write secret");
+    
+    // Suspend the realm
+    let response = client
+        .post(&format!("/v1/realms/{}/suspend", realm_id))
+        .header("X-Vault-Token", &root_token)
+        .json(&serde_json::json!({ "reason": "unpaid invoice" }))
+        .send()
+        .await
+        .expect("Failed to suspend realm");
+    
+    assert!(response.status().is_success(), "Realm suspension should succeed");
+    
+    // Reads are refused while suspended
+    let response = client
+        .get(&secret_path)
+        .header("X-Vault-Token", &root_token)
+        .send()
+        .await
+        .expect("Failed to read secret");
+    
+    assert_eq!(response.status(), 503, "Suspended realm should refuse requests");
+    
+    let result: serde_json::Value = response.json().await.expect("Failed to parse response");
+    assert_eq!(result.get("error").and_then(|v| v.as_str()), Some("realm_suspended"));
+    assert_eq!(result.get("reason").and_then(|v| v.as_str()), Some("unpaid invoice"));
+    
+    // Activate the realm
+    let response = client
+        .post(&format!("/v1/realms/{}/activate", realm_id))
+        .header("X-Vault-Token", &root_token)
+        .send()
+        .await
+        .expect("Failed to activate realm");
+    
+    assert!(response.status().is_success(), "Realm activation should succeed");
+    
+    // Reads succeed again
+    let response = client
+        .get(&secret_path)
+        .header("X-Vault-Token", &root_token)
+        .send()
+        .await
+        .expect("Failed to read secret");
+    
+    assert!(response.status().is_success(), "Activated realm should serve requests");
+}
@@ -0,0 +1,82 @@
+//! Appointment Status Events
+//!
+//! In-process fan-out of appointment status changes so front-end queue
+//! dashboards can follow them live (see the `/v1/ehr/appointments/stream`
+//! SSE endpoint).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per subscriber before slow subscribers start lagging
+const APPOINTMENT_EVENT_CAPACITY: usize = 256;
+
+/// An appointment moved from one status to another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppointmentStatusEvent {
+    /// Monotonic event counter, used as the SSE event id
+    pub id: u64,
+    pub appointment_id: Uuid,
+    pub patient_name: String,
+    pub old_status: String,
+    pub new_status: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Broadcast channel for appointment status changes
+#[derive(Debug)]
+pub struct AppointmentEventBroadcaster {
+    sender: broadcast::Sender<AppointmentStatusEvent>,
+    event_counter: AtomicU64,
+}
+
+impl AppointmentEventBroadcaster {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(APPOINTMENT_EVENT_CAPACITY);
+        Self {
+            sender,
+            event_counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Receive every status change published after this call
+    pub fn subscribe(&self) -> broadcast::Receiver<AppointmentStatusEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Publish a status change to all current subscribers
+    ///
+    /// Having no subscribers is not an error; the event is simply dropped.
+    pub fn publish(
+        &self,
+        appointment_id: Uuid,
+        patient_name: impl Into<String>,
+        old_status: impl Into<String>,
+        new_status: impl Into<String>,
+    ) -> AppointmentStatusEvent {
+        let event = AppointmentStatusEvent {
+            id: self.event_counter.fetch_add(1, Ordering::Relaxed) + 1,
+            appointment_id,
+            patient_name: patient_name.into(),
+            old_status: old_status.into(),
+            new_status: new_status.into(),
+            timestamp: Utc::now(),
+        };
+        let _ = self.sender.send(event.clone());
+        event
+    }
+}
+
+impl Default for AppointmentEventBroadcaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shared appointment event broadcaster (thread-safe)
+pub type SharedAppointmentEvents = Arc<AppointmentEventBroadcaster>;
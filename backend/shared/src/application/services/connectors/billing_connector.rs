@@ -1,22 +1,241 @@
 //! Billing Connector - Invoice and payment management
+//!
+//! Also generates X12 837P (professional) claims for insurance submission.
 
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
 use serde_json::{json, Value};
 
 use super::{Connector, ConnectorAction, ConnectorParameter};
 use crate::shared::{AppError, AppResult};
 
+/// Interchange sender ID used when none is configured
+pub const DEFAULT_X12_SENDER_ID: &str = "HEALTHV1";
+
+/// Interchange receiver ID used when none is configured
+pub const DEFAULT_X12_RECEIVER_ID: &str = "CLEARINGHOUSE";
+
+/// Implementation guide of the generated 837P
+const X12_837P_VERSION: &str = "005010X222A1";
+
+/// Most diagnosis codes an 837P claim can carry in its HI segment
+const MAX_DIAGNOSIS_CODES: usize = 12;
+
 pub struct BillingConnector {
     api_base_url: String,
+    /// ISA06/GS02 of generated claims
+    x12_sender_id: String,
+    /// ISA08/GS03 of generated claims
+    x12_receiver_id: String,
+}
+
+/// Input of the `generateClaim` action
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ClaimRequest {
+    pub patient_ien: i64,
+    pub visit_ien: i64,
+    /// ICD-10-CM codes; the first is the principal diagnosis
+    pub diagnosis_codes: Vec<String>,
+    /// CPT/HCPCS codes, one service line each
+    pub procedure_codes: Vec<String>,
+    /// Billing provider NPI
+    pub provider_npi: String,
+    pub payer_id: String,
+    /// Charge per procedure, in `procedure_codes` order; missing charges are 0
+    #[serde(default)]
+    pub charges: Vec<f64>,
+}
+
+impl ClaimRequest {
+    fn validate(&self) -> AppResult<()> {
+        if self.diagnosis_codes.is_empty() {
+            return Err(AppError::Validation("at least one diagnosis code required".to_string()));
+        }
+        if self.diagnosis_codes.len() > MAX_DIAGNOSIS_CODES {
+            return Err(AppError::Validation(format!(
+                "at most {} diagnosis codes allowed", MAX_DIAGNOSIS_CODES
+            )));
+        }
+        if self.procedure_codes.is_empty() {
+            return Err(AppError::Validation("at least one procedure code required".to_string()));
+        }
+        if self.provider_npi.len() != 10 || !self.provider_npi.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AppError::Validation("provider_npi must be 10 digits".to_string()));
+        }
+        if self.payer_id.trim().is_empty() {
+            return Err(AppError::Validation("payer_id required".to_string()));
+        }
+        Ok(())
+    }
+
+    fn charge(&self, line: usize) -> f64 {
+        self.charges.get(line).copied().unwrap_or(0.0)
+    }
+}
+
+/// Strip X12 delimiters (`*`, `~`, `:`, `^`) from an element value
+fn element(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '*' | '~' | ':' | '^'))
+        .collect()
+}
+
+/// ISA IDs are fixed-width: padded or truncated to 15 characters
+fn isa_id(value: &str) -> String {
+    format!("{:<15.15}", element(value))
+}
+
+/// Monetary amount without trailing zeros (`120`, `45.5`)
+fn amount(value: f64) -> String {
+    let formatted = format!("{:.2}", value);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Builds X12 837P claim interchanges
+///
+/// One interchange holds one functional group with one transaction set
+/// and a single claim. Segments end with `~`, elements are separated by
+/// `*` and components by `:`.
+#[derive(Debug, Clone)]
+pub struct ClaimBuilder {
+    sender_id: String,
+    receiver_id: String,
+    control_number: u32,
+    created_at: NaiveDateTime,
+}
+
+impl ClaimBuilder {
+    pub fn new(sender_id: &str, receiver_id: &str) -> Self {
+        Self {
+            sender_id: sender_id.to_string(),
+            receiver_id: receiver_id.to_string(),
+            control_number: 1,
+            created_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+
+    /// Interchange and group control number (ISA13/IEA02, GS06/GE02)
+    pub fn control_number(mut self, control_number: u32) -> Self {
+        self.control_number = control_number % 1_000_000_000;
+        self
+    }
+
+    /// Creation time written to ISA09/10, GS04/05 and BHT04/05
+    pub fn created_at(mut self, created_at: NaiveDateTime) -> Self {
+        self.created_at = created_at;
+        self
+    }
+
+    /// Generate the 837P interchange for a claim
+    pub fn build(&self, claim_id: &str, claim: &ClaimRequest) -> AppResult<String> {
+        claim.validate()?;
+
+        let sender = element(&self.sender_id);
+        let receiver = element(&self.receiver_id);
+        let date = self.created_at.format("%Y%m%d").to_string();
+        let time = self.created_at.format("%H%M").to_string();
+        let control = format!("{:09}", self.control_number);
+        let claim_id = element(claim_id);
+        let total: f64 = (0..claim.procedure_codes.len()).map(|line| claim.charge(line)).sum();
+
+        // Transaction set body, ST through the last service line
+        let mut transaction = vec![
+            format!("ST*837*0001*{}", X12_837P_VERSION),
+            format!("BHT*0019*00*{}*{}*{}*CH", claim_id, date, time),
+            // 1000A submitter, 1000B receiver
+            format!("NM1*41*2*{}*****46*{}", sender, sender),
+            "PER*IC*BILLING".to_string(),
+            format!("NM1*40*2*{}*****46*{}", receiver, receiver),
+            // 2000A/2010AA billing provider
+            "HL*1**20*1".to_string(),
+            format!("NM1*85*2*BILLING PROVIDER*****XX*{}", element(&claim.provider_npi)),
+            // 2000B subscriber (the patient), 2010BA subscriber, 2010BB payer
+            "HL*2*1*22*0".to_string(),
+            "SBR*P*18*******CI".to_string(),
+            format!("NM1*IL*1*PATIENT*****MI*{}", claim.patient_ien),
+            format!("NM1*PR*2*PAYER*****PI*{}", element(&claim.payer_id)),
+            // 2300 claim
+            format!("CLM*{}*{}***11:B:1*Y*A*Y*Y", claim_id, amount(total)),
+            format!("REF*EA*{}", claim.visit_ien),
+        ];
+
+        let diagnoses: Vec<String> = claim.diagnosis_codes.iter().enumerate()
+            .map(|(i, code)| {
+                let qualifier = if i == 0 { "ABK" } else { "ABF" };
+                format!("{}:{}", qualifier, element(code).replace('.', ""))
+            })
+            .collect();
+        transaction.push(format!("HI*{}", diagnoses.join("*")));
+
+        // 2400 service lines, each pointing at the principal diagnosis
+        for (line, code) in claim.procedure_codes.iter().enumerate() {
+            transaction.push(format!("LX*{}", line + 1));
+            transaction.push(format!(
+                "SV1*HC:{}*{}*UN*1***1",
+                element(code),
+                amount(claim.charge(line))
+            ));
+        }
+        // SE01 counts every segment from ST to SE inclusive
+        transaction.push(format!("SE*{}*0001", transaction.len() + 1));
+
+        let mut segments = vec![
+            format!(
+                "ISA*00*{:10}*00*{:10}*ZZ*{}*ZZ*{}*{}*{}*^*00501*{}*0*P*:",
+                "", "",
+                isa_id(&self.sender_id),
+                isa_id(&self.receiver_id),
+                self.created_at.format("%y%m%d"),
+                time,
+                control
+            ),
+            format!("GS*HC*{}*{}*{}*{}*{}*X*{}", sender, receiver, date, time, self.control_number, X12_837P_VERSION),
+        ];
+        segments.extend(transaction);
+        segments.push(format!("GE*1*{}", self.control_number));
+        segments.push(format!("IEA*1*{}", control));
+
+        Ok(segments.iter().map(|segment| format!("{}~", segment)).collect())
+    }
 }
 
 impl BillingConnector {
     pub fn new(api_base_url: &str) -> Self {
         Self {
             api_base_url: api_base_url.to_string(),
+            x12_sender_id: DEFAULT_X12_SENDER_ID.to_string(),
+            x12_receiver_id: DEFAULT_X12_RECEIVER_ID.to_string(),
         }
     }
 
+    /// Sender and receiver IDs written to the ISA/GS segments of generated claims
+    pub fn with_x12_ids(mut self, sender_id: &str, receiver_id: &str) -> Self {
+        self.x12_sender_id = sender_id.to_string();
+        self.x12_receiver_id = receiver_id.to_string();
+        self
+    }
+
+    async fn generate_claim(&self, params: Value) -> AppResult<Value> {
+        let claim: ClaimRequest = serde_json::from_value(params)
+            .map_err(|e| AppError::Validation(format!("Invalid claim: {}", e)))?;
+
+        let now = chrono::Utc::now();
+        let claim_id = format!("CLM{}", now.timestamp());
+        let edi_content = ClaimBuilder::new(&self.x12_sender_id, &self.x12_receiver_id)
+            // Interchange control numbers are 9 digits
+            .control_number((now.timestamp() % 1_000_000_000) as u32)
+            .created_at(now.naive_utc())
+            .build(&claim_id, &claim)?;
+
+        Ok(json!({
+            "claim_id": claim_id,
+            "edi_content": edi_content,
+        }))
+    }
+
     async fn create_invoice(&self, params: Value) -> AppResult<Value> {
         let patient_id = params.get("patientId")
             .and_then(|v| v.as_str())
@@ -87,6 +306,7 @@ impl Connector for BillingConnector {
             "createInvoice" => self.create_invoice(params).await,
             "addInvoiceItem" => self.add_invoice_item(params).await,
             "finalizeInvoice" => self.finalize_invoice(params).await,
+            "generateClaim" => self.generate_claim(params).await,
             _ => Err(AppError::Validation(format!("Unknown billing action: {}", action))),
         }
     }
@@ -141,6 +361,54 @@ impl Connector for BillingConnector {
                     },
                 ],
             },
+            ConnectorAction {
+                name: "generateClaim".to_string(),
+                description: "Generate an X12 837P insurance claim".to_string(),
+                parameters: vec![
+                    ConnectorParameter {
+                        name: "patient_ien".to_string(),
+                        param_type: "number".to_string(),
+                        required: true,
+                        description: "Patient IEN (subscriber)".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "visit_ien".to_string(),
+                        param_type: "number".to_string(),
+                        required: true,
+                        description: "Visit IEN".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "diagnosis_codes".to_string(),
+                        param_type: "array".to_string(),
+                        required: true,
+                        description: "ICD-10-CM codes, principal diagnosis first".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "procedure_codes".to_string(),
+                        param_type: "array".to_string(),
+                        required: true,
+                        description: "CPT/HCPCS codes".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "provider_npi".to_string(),
+                        param_type: "string".to_string(),
+                        required: true,
+                        description: "Billing provider NPI".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "payer_id".to_string(),
+                        param_type: "string".to_string(),
+                        required: true,
+                        description: "Payer ID".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "charges".to_string(),
+                        param_type: "array".to_string(),
+                        required: false,
+                        description: "Charge per procedure".to_string(),
+                    },
+                ],
+            },
         ]
     }
 
@@ -148,3 +416,140 @@ impl Connector for BillingConnector {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn claim() -> ClaimRequest {
+        ClaimRequest {
+            patient_ien: 1042,
+            visit_ien: 5531,
+            diagnosis_codes: vec!["E11.9".to_string(), "I10".to_string()],
+            procedure_codes: vec!["99213".to_string(), "85025".to_string()],
+            provider_npi: "1234567893".to_string(),
+            payer_id: "87726".to_string(),
+            charges: vec![120.0, 45.5],
+        }
+    }
+
+    fn builder() -> ClaimBuilder {
+        let created_at = NaiveDate::from_ymd_opt(2024, 3, 5)
+            .and_then(|d| d.and_hms_opt(14, 30, 0))
+            .unwrap_or_default();
+        ClaimBuilder::new("HEALTHV1", "CLEARHOUSE01")
+            .control_number(42)
+            .created_at(created_at)
+    }
+
+    /// Split an interchange into segments of elements
+    fn parse(edi: &str) -> Vec<Vec<String>> {
+        edi.split('~')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.split('*').map(str::to_string).collect())
+            .collect()
+    }
+
+    fn segment<'a>(segments: &'a [Vec<String>], id: &str) -> &'a [String] {
+        segments.iter()
+            .find(|s| s[0] == id)
+            .map(Vec::as_slice)
+            .unwrap_or_else(|| panic!("missing {} segment", id))
+    }
+
+    #[test]
+    fn minimal_claim_segments_round_trip() {
+        let edi = builder().build("CLM1", &claim()).unwrap_or_default();
+        let segments = parse(&edi);
+
+        let isa = segment(&segments, "ISA");
+        assert_eq!(isa.len(), 17);
+        assert_eq!(isa[6], "HEALTHV1       ");
+        assert_eq!(isa[8], "CLEARHOUSE01   ");
+        assert_eq!(isa[9], "240305");
+        assert_eq!(isa[13], "000000042");
+
+        let gs = segment(&segments, "GS");
+        assert_eq!((gs[2].as_str(), gs[3].as_str(), gs[8].as_str()), ("HEALTHV1", "CLEARHOUSE01", "005010X222A1"));
+
+        let subscriber = segments.iter().find(|s| s[0] == "NM1" && s[1] == "IL").map(Vec::as_slice);
+        assert_eq!(subscriber.map(|s| s[9].as_str()), Some("1042"));
+
+        let clm = segment(&segments, "CLM");
+        assert_eq!((clm[1].as_str(), clm[2].as_str()), ("CLM1", "165.5"));
+        assert_eq!(segment(&segments, "REF")[2], "5531");
+        assert_eq!(segment(&segments, "HI")[1..], ["ABK:E119".to_string(), "ABF:I10".to_string()]);
+
+        let service_lines: Vec<&str> = segments.iter()
+            .filter(|s| s[0] == "SV1")
+            .map(|s| s[1].as_str())
+            .collect();
+        assert_eq!(service_lines, vec!["HC:99213", "HC:85025"]);
+    }
+
+    #[test]
+    fn envelope_counts_and_control_numbers_match() {
+        let edi = builder().build("CLM1", &claim()).unwrap_or_default();
+        let segments = parse(&edi);
+
+        let st = segments.iter().position(|s| s[0] == "ST").unwrap_or_default();
+        let se = segments.iter().position(|s| s[0] == "SE").unwrap_or_default();
+        let se_segment = segment(&segments, "SE");
+        assert_eq!(se_segment[1], (se - st + 1).to_string());
+        assert_eq!(se_segment[2], segment(&segments, "ST")[2]);
+
+        let transaction_sets = segments.iter().filter(|s| s[0] == "ST").count();
+        let groups = segments.iter().filter(|s| s[0] == "GS").count();
+        let ge = segment(&segments, "GE");
+        let iea = segment(&segments, "IEA");
+        assert_eq!(ge[1], transaction_sets.to_string());
+        assert_eq!(ge[2], segment(&segments, "GS")[6]);
+        assert_eq!(iea[1], groups.to_string());
+        assert_eq!(iea[2], segment(&segments, "ISA")[13]);
+    }
+
+    #[test]
+    fn delimiters_in_input_are_stripped() {
+        let mut claim = claim();
+        claim.payer_id = "877*26~".to_string();
+        let edi = builder().build("CLM1", &claim).unwrap_or_default();
+
+        let payer = parse(&edi).into_iter().find(|s| s[0] == "NM1" && s[1] == "PR");
+        assert_eq!(payer.map(|s| s[9].clone()).as_deref(), Some("87726"));
+    }
+
+    #[test]
+    fn invalid_claims_are_rejected() {
+        let mut no_diagnosis = claim();
+        no_diagnosis.diagnosis_codes.clear();
+        let mut bad_npi = claim();
+        bad_npi.provider_npi = "12345".to_string();
+
+        assert!(matches!(builder().build("CLM1", &no_diagnosis), Err(AppError::Validation(_))));
+        assert!(matches!(builder().build("CLM1", &bad_npi), Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn generate_claim_action_uses_configured_ids() {
+        let connector = BillingConnector::new("http://billing.local")
+            .with_x12_ids("SENDER01", "RECEIVER01");
+        let params = json!({
+            "patient_ien": 1042,
+            "visit_ien": 5531,
+            "diagnosis_codes": ["E11.9"],
+            "procedure_codes": ["99213"],
+            "provider_npi": "1234567893",
+            "payer_id": "87726",
+        });
+
+        let result = connector.execute("generateClaim", params).await
+            .unwrap_or_else(|e| panic!("claim generation failed: {}", e));
+        let claim_id = result["claim_id"].as_str().unwrap_or_default();
+        let segments = parse(result["edi_content"].as_str().unwrap_or_default());
+
+        assert_eq!(segment(&segments, "GS")[2], "SENDER01");
+        assert_eq!(segment(&segments, "GS")[3], "RECEIVER01");
+        assert_eq!(segment(&segments, "CLM")[1], claim_id);
+    }
+}
@@ -0,0 +1,499 @@
+//! Connector configuration - build a registry from a TOML or JSON file
+//!
+//! Each `[[connector]]` entry names a connector `id`, the `connector_type`
+//! used to look up its factory, and where and how to reach it:
+//!
+//! ```toml
+//! [[connector]]
+//! id = "pharmacy-main"
+//! connector_type = "pharmacy"
+//! base_url = "http://pharmacy:8080"
+//! timeout_ms = 5000
+//! auth = { type = "bearer", token = "..." }
+//! ```
+//!
+//! `http` connectors can also authenticate with OAuth2 client credentials;
+//! the client secret is read from the vault, so only its path is configured:
+//!
+//! ```toml
+//! auth = { type = "oauth2_client_credentials", token_url = "https://idp/token", client_id = "workflow", realm_id = "...", client_secret = "connectors/lab", scopes = ["lab.read"] }
+//! ```
+//!
+//! JSON files (`.json`) use the same shape: `{ "connector": [ ... ] }`.
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+use uuid::Uuid;
+
+use super::billing_connector::{DEFAULT_X12_RECEIVER_ID, DEFAULT_X12_SENDER_ID};
+use super::{
+    BillingConnector, ClientCredentials, Connector, ConnectorRegistry, DatabaseConnector, HTTPConnector,
+    OAuth2TokenProvider, OPDConnector, PharmacyConnector, RealmSecretReader,
+};
+use crate::infrastructure::encryption::RustyVaultClient;
+use crate::shared::AppError;
+
+/// Default request timeout when `timeout_ms` is omitted
+pub const DEFAULT_CONNECTOR_TIMEOUT_MS: u64 = 30_000;
+
+fn default_timeout_ms() -> u64 {
+    DEFAULT_CONNECTOR_TIMEOUT_MS
+}
+
+/// Credentials a connector sends to its backend
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ConnectorAuth {
+    #[default]
+    None,
+    Bearer { token: String },
+    Basic { username: String, password: String },
+    ApiKey { header: String, key: String },
+    /// Bearer token from the client credentials grant (`http` connectors only)
+    #[serde(rename = "oauth2_client_credentials")]
+    OAuth2ClientCredentials {
+        token_url: String,
+        client_id: String,
+        /// Vault path, within `realm_id`, of a secret with a `client_secret` field
+        client_secret: String,
+        realm_id: Uuid,
+        #[serde(default)]
+        scopes: Vec<String>,
+    },
+}
+
+/// Single connector entry of a configuration file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ConnectorConfig {
+    /// Registry key workflows use to address the connector
+    pub id: String,
+    /// Factory to build the connector with (`http`, `opd`, `database`, ...)
+    pub connector_type: String,
+    /// Backend URL; for `database` connectors, the connection URL.
+    /// Checked by each factory so a missing value names the connector.
+    #[serde(default)]
+    pub base_url: String,
+    #[serde(default)]
+    pub auth: ConnectorAuth,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+    /// Type-specific settings (e.g. `x12_sender_id` for `billing`)
+    #[serde(default)]
+    pub settings: HashMap<String, String>,
+}
+
+impl ConnectorConfig {
+    /// `base_url`, or a `MissingField` error if it is empty
+    pub fn require_base_url(&self) -> Result<&str, ConnectorLoadError> {
+        let base_url = self.base_url.trim();
+        if base_url.is_empty() {
+            return Err(ConnectorLoadError::MissingField {
+                id: self.id.clone(),
+                connector_type: self.connector_type.clone(),
+                field: "base_url",
+            });
+        }
+        Ok(base_url.trim_end_matches('/'))
+    }
+
+    /// Type-specific setting, or `default` when unset
+    pub fn setting<'a>(&'a self, name: &str, default: &'a str) -> &'a str {
+        self.settings.get(name).map_or(default, String::as_str)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+
+    fn invalid(&self, message: String) -> ConnectorLoadError {
+        ConnectorLoadError::Invalid {
+            id: self.id.clone(),
+            message,
+        }
+    }
+
+    /// HTTP client applying the configured timeout and static credentials to every request
+    pub fn http_client(&self) -> Result<Client, ConnectorLoadError> {
+        let mut headers = HeaderMap::new();
+        let invalid = |message: String| self.invalid(message);
+        match &self.auth {
+            ConnectorAuth::None => {}
+            ConnectorAuth::OAuth2ClientCredentials { .. } => {
+                return Err(invalid(format!(
+                    "OAuth2 client credentials are not supported by {} connectors",
+                    self.connector_type
+                )));
+            }
+            ConnectorAuth::Bearer { token } => {
+                let value = HeaderValue::from_str(&format!("Bearer {}", token))
+                    .map_err(|_| invalid("bearer token is not a valid header value".to_string()))?;
+                headers.insert(AUTHORIZATION, value);
+            }
+            ConnectorAuth::Basic { username, password } => {
+                use base64::Engine;
+                let encoded = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password));
+                let value = HeaderValue::from_str(&format!("Basic {}", encoded))
+                    .map_err(|_| invalid("basic credentials are not a valid header value".to_string()))?;
+                headers.insert(AUTHORIZATION, value);
+            }
+            ConnectorAuth::ApiKey { header, key } => {
+                let name = HeaderName::from_bytes(header.as_bytes())
+                    .map_err(|_| invalid(format!("'{}' is not a valid header name", header)))?;
+                let value = HeaderValue::from_str(key)
+                    .map_err(|_| invalid("API key is not a valid header value".to_string()))?;
+                headers.insert(name, value);
+            }
+        }
+
+        self.build_client(headers)
+    }
+
+    fn build_client(&self, headers: HeaderMap) -> Result<Client, ConnectorLoadError> {
+        Client::builder()
+            .timeout(self.timeout())
+            .default_headers(headers)
+            .build()
+            .map_err(|e| self.invalid(format!("failed to build HTTP client: {}", e)))
+    }
+}
+
+/// Shared services available to connector builders
+#[derive(Clone, Default)]
+pub struct ConnectorContext {
+    /// Vault used to resolve connector secrets; `RustyVaultClient::from_env`
+    /// is used when unset
+    pub secrets: Option<Arc<dyn RealmSecretReader>>,
+}
+
+impl ConnectorContext {
+    fn secrets(&self, config: &ConnectorConfig) -> Result<Arc<dyn RealmSecretReader>, ConnectorLoadError> {
+        if let Some(secrets) = &self.secrets {
+            return Ok(secrets.clone());
+        }
+        let vault = RustyVaultClient::from_env()
+            .map_err(|e| config.invalid(format!("vault unavailable for client secret: {}", e)))?;
+        Ok(Arc::new(vault))
+    }
+}
+
+fn build_http_connector(
+    config: &ConnectorConfig,
+    context: &ConnectorContext,
+) -> Result<Arc<dyn Connector>, ConnectorLoadError> {
+    let connector = HTTPConnector::with_base_url(config.require_base_url()?);
+    let connector = match &config.auth {
+        ConnectorAuth::OAuth2ClientCredentials { token_url, client_id, client_secret, realm_id, scopes } => {
+            let client = config.build_client(HeaderMap::new())?;
+            let credentials = ClientCredentials {
+                token_url: token_url.clone(),
+                client_id: client_id.clone(),
+                realm_id: *realm_id,
+                client_secret_path: client_secret.clone(),
+                scopes: scopes.clone(),
+            };
+            let provider = OAuth2TokenProvider::new(client.clone(), credentials, context.secrets(config)?);
+            connector.with_client(client).with_oauth2(provider)
+        }
+        _ => connector.with_client(config.http_client()?),
+    };
+    Ok(Arc::new(connector))
+}
+
+#[derive(Debug, Deserialize)]
+struct ConnectorFile {
+    #[serde(default)]
+    connector: Vec<ConnectorConfig>,
+}
+
+/// Why a connector configuration could not be loaded
+#[derive(Debug, Error)]
+pub enum ConnectorLoadError {
+    #[error("Failed to read connector config {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid connector config {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+
+    #[error(
+        "Unknown connector type '{connector_type}' for connector '{id}' (available types: {})",
+        available.join(", ")
+    )]
+    UnknownType {
+        id: String,
+        connector_type: String,
+        available: Vec<String>,
+    },
+
+    #[error("Connector '{id}' ({connector_type}) is missing required field '{field}'")]
+    MissingField {
+        id: String,
+        connector_type: String,
+        field: &'static str,
+    },
+
+    #[error("Duplicate connector id '{0}'")]
+    DuplicateId(String),
+
+    #[error("Connector '{id}' is invalid: {message}")]
+    Invalid { id: String, message: String },
+}
+
+impl From<ConnectorLoadError> for AppError {
+    fn from(error: ConnectorLoadError) -> Self {
+        AppError::Configuration(error.to_string())
+    }
+}
+
+/// Builds a connector from its configuration entry
+pub type ConnectorBuilder =
+    fn(&ConnectorConfig, &ConnectorContext) -> Result<Arc<dyn Connector>, ConnectorLoadError>;
+
+/// Connector factories keyed by `connector_type`
+pub struct ConnectorFactory {
+    builders: HashMap<String, ConnectorBuilder>,
+    context: ConnectorContext,
+}
+
+impl ConnectorFactory {
+    /// Factory without any connector types
+    pub fn new() -> Self {
+        Self {
+            builders: HashMap::new(),
+            context: ConnectorContext::default(),
+        }
+    }
+
+    /// Resolve connector secrets from `secrets` instead of the vault configured in the environment
+    pub fn with_secrets(mut self, secrets: Arc<dyn RealmSecretReader>) -> Self {
+        self.context.secrets = Some(secrets);
+        self
+    }
+
+    /// Factory for the built-in connector types
+    pub fn builtin() -> Self {
+        let mut factory = Self::new();
+        factory.register("http", build_http_connector);
+        factory.register("opd", |config, _| {
+            Ok(Arc::new(OPDConnector::new(config.require_base_url()?)))
+        });
+        factory.register("pharmacy", |config, _| {
+            let base_url = config.require_base_url()?;
+            Ok(Arc::new(PharmacyConnector::with_client(config.http_client()?, base_url)))
+        });
+        factory.register("billing", |config, _| {
+            let connector = BillingConnector::new(config.require_base_url()?).with_x12_ids(
+                config.setting("x12_sender_id", DEFAULT_X12_SENDER_ID),
+                config.setting("x12_receiver_id", DEFAULT_X12_RECEIVER_ID),
+            );
+            Ok(Arc::new(connector))
+        });
+        factory.register("database", |config, _| {
+            let connector = DatabaseConnector::connect_lazy(config.require_base_url()?, config.timeout())
+                .map_err(|e| ConnectorLoadError::Invalid {
+                    id: config.id.clone(),
+                    message: format!("invalid database URL: {}", e),
+                })?;
+            Ok(Arc::new(connector))
+        });
+        factory
+    }
+
+    /// Register (or replace) the builder for a connector type
+    pub fn register(&mut self, connector_type: &str, builder: ConnectorBuilder) {
+        self.builders.insert(connector_type.to_string(), builder);
+    }
+
+    /// Registered connector types, sorted
+    pub fn available_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self.builders.keys().cloned().collect();
+        types.sort();
+        types
+    }
+
+    /// Build the connector for one configuration entry
+    pub fn create(&self, config: &ConnectorConfig) -> Result<Arc<dyn Connector>, ConnectorLoadError> {
+        let builder = self.builders.get(&config.connector_type).ok_or_else(|| {
+            ConnectorLoadError::UnknownType {
+                id: config.id.clone(),
+                connector_type: config.connector_type.clone(),
+                available: self.available_types(),
+            }
+        })?;
+        builder(config, &self.context)
+    }
+
+    /// Build a registry from configuration entries, keyed by their `id`
+    pub fn build(&self, configs: &[ConnectorConfig]) -> Result<ConnectorRegistry, ConnectorLoadError> {
+        let mut registry = ConnectorRegistry::new();
+        for config in configs {
+            if registry.get(&config.id).is_some() {
+                return Err(ConnectorLoadError::DuplicateId(config.id.clone()));
+            }
+            registry.register_as(&config.id, self.create(config)?);
+        }
+        Ok(registry)
+    }
+
+    /// Build a registry from a TOML file, or JSON when the extension is `.json`
+    pub fn load(&self, path: &Path) -> Result<ConnectorRegistry, ConnectorLoadError> {
+        let source = std::fs::read_to_string(path).map_err(|source| ConnectorLoadError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let parse_error = |message: String| ConnectorLoadError::Parse {
+            path: path.to_path_buf(),
+            message,
+        };
+        let file: ConnectorFile = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => {
+                serde_json::from_str(&source).map_err(|e| parse_error(e.to_string()))?
+            }
+            _ => toml::from_str(&source).map_err(|e| parse_error(e.to_string()))?,
+        };
+        self.build(&file.connector)
+    }
+}
+
+impl Default for ConnectorFactory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_path() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/connectors/connectors.toml")
+    }
+
+    fn parse(source: &str) -> Vec<ConnectorConfig> {
+        toml::from_str::<ConnectorFile>(source)
+            .map(|file| file.connector)
+            .unwrap_or_else(|e| panic!("invalid test config: {}", e))
+    }
+
+    #[tokio::test]
+    async fn fixture_loads_every_connector_by_id() {
+        let registry = ConnectorRegistry::load_from_config(&fixture_path())
+            .unwrap_or_else(|e| panic!("fixture failed to load: {}", e));
+
+        let mut ids = registry.list_connectors();
+        ids.sort();
+        assert_eq!(ids, vec!["billing", "lab-gateway", "opd", "pharmacy-main", "reporting-db"]);
+
+        let connector_type = |id: &str| registry.get(id).map(|c| c.name().to_string());
+        assert_eq!(connector_type("pharmacy-main").as_deref(), Some("pharmacy"));
+        assert_eq!(connector_type("lab-gateway").as_deref(), Some("http"));
+        assert_eq!(connector_type("reporting-db").as_deref(), Some("database"));
+
+        let metadata = registry.get_all_metadata();
+        assert!(metadata.iter().any(|m| m.id == "reporting-db" && m.name == "Database Connector"));
+    }
+
+    #[test]
+    fn fixture_fields_and_defaults_parse() {
+        let configs = parse(&std::fs::read_to_string(fixture_path()).unwrap_or_default());
+        let pharmacy = configs.iter().find(|c| c.id == "pharmacy-main");
+        assert_eq!(pharmacy.map(|c| c.timeout_ms), Some(5000));
+        assert_eq!(
+            pharmacy.map(|c| c.auth.clone()),
+            Some(ConnectorAuth::Bearer { token: "pharmacy-token".to_string() })
+        );
+
+        let billing = configs.iter().find(|c| c.id == "billing");
+        assert_eq!(billing.map(|c| c.setting("x12_sender_id", "")), Some("HEALTHV1"));
+        assert_eq!(billing.map(|c| c.setting("x12_receiver_id", "")), Some("CLEARHOUSE01"));
+
+        let opd = configs.iter().find(|c| c.id == "opd");
+        assert_eq!(opd.map(|c| c.timeout_ms), Some(DEFAULT_CONNECTOR_TIMEOUT_MS));
+        assert_eq!(opd.map(|c| c.auth.clone()), Some(ConnectorAuth::None));
+    }
+
+    #[test]
+    fn missing_base_url_names_the_connector_and_field() {
+        let configs = parse(
+            r#"
+            [[connector]]
+            id = "external-api"
+            connector_type = "http"
+            "#,
+        );
+        let error = ConnectorFactory::builtin().build(&configs).err();
+
+        assert!(matches!(
+            &error,
+            Some(ConnectorLoadError::MissingField { id, field: "base_url", .. }) if id == "external-api"
+        ));
+        assert_eq!(
+            error.map(|e| e.to_string()).unwrap_or_default(),
+            "Connector 'external-api' (http) is missing required field 'base_url'"
+        );
+    }
+
+    #[test]
+    fn unknown_type_lists_available_types() {
+        let configs = parse(
+            r#"
+            [[connector]]
+            id = "mailer"
+            connector_type = "smtp"
+            base_url = "smtp://mail.local"
+            "#,
+        );
+        let error = ConnectorFactory::builtin().build(&configs).err();
+
+        match &error {
+            Some(ConnectorLoadError::UnknownType { connector_type, available, .. }) => {
+                assert_eq!(connector_type, "smtp");
+                assert_eq!(available, &["billing", "database", "http", "opd", "pharmacy"]);
+            }
+            other => panic!("expected UnknownType, got {:?}", other),
+        }
+        assert!(error
+            .map(|e| e.to_string())
+            .unwrap_or_default()
+            .contains("available types: billing, database, http, opd, pharmacy"));
+    }
+
+    #[test]
+    fn duplicate_ids_are_rejected() {
+        let configs = parse(
+            r#"
+            [[connector]]
+            id = "opd"
+            connector_type = "opd"
+            base_url = "http://a.local"
+
+            [[connector]]
+            id = "opd"
+            connector_type = "billing"
+            base_url = "http://b.local"
+            "#,
+        );
+
+        assert!(matches!(
+            ConnectorFactory::builtin().build(&configs),
+            Err(ConnectorLoadError::DuplicateId(id)) if id == "opd"
+        ));
+    }
+
+    #[test]
+    fn missing_file_is_a_configuration_error() {
+        let error = ConnectorRegistry::load_from_config(Path::new("/nonexistent/connectors.toml")).err();
+        assert!(matches!(error, Some(AppError::Configuration(_))));
+    }
+}
@@ -0,0 +1,155 @@
+//! Database Connector - Direct SQL against a PostgreSQL database
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use sqlx::postgres::{PgArguments, PgPool, PgPoolOptions};
+use sqlx::query::Query;
+use sqlx::{Postgres, Row};
+use std::time::Duration;
+
+use super::{Connector, ConnectorAction, ConnectorParameter};
+use crate::shared::{AppError, AppResult};
+
+pub struct DatabaseConnector {
+    pool: PgPool,
+}
+
+impl DatabaseConnector {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Connector over a pool that opens connections on first use.
+    /// `timeout` bounds how long a statement waits for a connection.
+    pub fn connect_lazy(database_url: &str, timeout: Duration) -> Result<Self, sqlx::Error> {
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(timeout)
+            .connect_lazy(database_url)?;
+        Ok(Self::new(pool))
+    }
+
+    fn sql(params: &Value) -> AppResult<&str> {
+        params.get("sql")
+            .and_then(|v| v.as_str())
+            .filter(|sql| !sql.trim().is_empty())
+            .ok_or_else(|| AppError::Validation("sql required".to_string()))
+    }
+
+    /// Bind the `params` array to `$1`, `$2`, ... by JSON type
+    fn bind_params<'q>(
+        mut query: Query<'q, Postgres, PgArguments>,
+        params: &Value,
+    ) -> AppResult<Query<'q, Postgres, PgArguments>> {
+        let values = match params.get("params") {
+            None | Some(Value::Null) => return Ok(query),
+            Some(Value::Array(values)) => values.clone(),
+            Some(_) => return Err(AppError::Validation("params must be an array".to_string())),
+        };
+        for value in values {
+            query = match value {
+                Value::Null => query.bind(None::<String>),
+                Value::Bool(b) => query.bind(b),
+                Value::Number(n) => match n.as_i64() {
+                    Some(i) => query.bind(i),
+                    None => query.bind(n.as_f64()),
+                },
+                Value::String(s) => query.bind(s),
+                other => query.bind(other),
+            };
+        }
+        Ok(query)
+    }
+
+    /// Run a SELECT and return its rows as JSON objects
+    async fn query(&self, params: Value) -> AppResult<Value> {
+        let sql = Self::sql(&params)?.trim().trim_end_matches(';');
+        let wrapped = format!("SELECT COALESCE(json_agg(t), '[]'::json) FROM ({}) t", sql);
+        let row = Self::bind_params(sqlx::query(&wrapped), &params)?
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("Database query failed: {}", e)))?;
+        let rows: Value = row.try_get(0)
+            .map_err(|e| AppError::Internal(format!("Invalid database query result: {}", e)))?;
+
+        Ok(json!({
+            "rowCount": rows.as_array().map_or(0, |r| r.len()),
+            "rows": rows,
+            "executedAt": chrono::Utc::now().to_rfc3339(),
+        }))
+    }
+
+    /// Run an INSERT/UPDATE/DELETE and return the affected row count
+    async fn execute_statement(&self, params: Value) -> AppResult<Value> {
+        let sql = Self::sql(&params)?;
+        let result = Self::bind_params(sqlx::query(sql), &params)?
+            .execute(&self.pool)
+            .await
+            .map_err(|e| AppError::Internal(format!("Database statement failed: {}", e)))?;
+
+        Ok(json!({
+            "rowsAffected": result.rows_affected(),
+            "executedAt": chrono::Utc::now().to_rfc3339(),
+        }))
+    }
+}
+
+#[async_trait]
+impl Connector for DatabaseConnector {
+    fn name(&self) -> &str {
+        "database"
+    }
+
+    fn display_name(&self) -> &str {
+        "Database Connector"
+    }
+
+    fn description(&self) -> &str {
+        "Execute SQL queries"
+    }
+
+    async fn execute(&self, action: &str, params: Value) -> AppResult<Value> {
+        self.validate_params(action, &params)?;
+        match action {
+            "query" => self.query(params).await,
+            "execute" => self.execute_statement(params).await,
+            _ => Err(AppError::Validation(format!("Unknown database action: {}", action))),
+        }
+    }
+
+    fn available_actions(&self) -> Vec<ConnectorAction> {
+        let parameters = vec![
+            ConnectorParameter {
+                name: "sql".to_string(),
+                param_type: "string".to_string(),
+                required: true,
+                description: "SQL statement with $1, $2, ... placeholders".to_string(),
+            },
+            ConnectorParameter {
+                name: "params".to_string(),
+                param_type: "array".to_string(),
+                required: false,
+                description: "Placeholder values".to_string(),
+            },
+        ];
+        vec![
+            ConnectorAction {
+                name: "query".to_string(),
+                description: "Run a query and return its rows".to_string(),
+                parameters: parameters.clone(),
+            },
+            ConnectorAction {
+                name: "execute".to_string(),
+                description: "Run a statement and return the affected row count".to_string(),
+                parameters,
+            },
+        ]
+    }
+
+    fn validate_params(&self, action: &str, params: &Value) -> AppResult<()> {
+        match action {
+            "query" | "execute" => Self::sql(params).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
@@ -1,35 +1,102 @@
 //! HTTP Connector - Generic REST API calls (like n8n HTTP Request node)
 
 use async_trait::async_trait;
+use reqwest::{Client, Method};
 use serde_json::{json, Value};
 
-use super::{Connector, ConnectorAction, ConnectorParameter};
+use super::{Connector, ConnectorAction, ConnectorParameter, OAuth2TokenProvider};
+use crate::infrastructure::tracing::InjectTraceContext;
 use crate::shared::{AppError, AppResult};
 
-pub struct HTTPConnector;
+pub struct HTTPConnector {
+    client: Client,
+    /// Prefix for relative request URLs
+    base_url: Option<String>,
+    /// Bearer token source for services requiring OAuth2
+    oauth2: Option<OAuth2TokenProvider>,
+}
 
 impl HTTPConnector {
     pub fn new() -> Self {
-        Self
+        Self {
+            client: Client::new(),
+            base_url: None,
+            oauth2: None,
+        }
+    }
+
+    /// Connector resolving relative `url` parameters against `base_url`
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: Some(base_url.trim_end_matches('/').to_string()),
+            ..Self::new()
+        }
+    }
+
+    /// Send requests through a preconfigured client (timeout, default headers)
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Authenticate every request with a client credentials bearer token
+    pub fn with_oauth2(mut self, provider: OAuth2TokenProvider) -> Self {
+        self.oauth2 = Some(provider);
+        self
+    }
+
+    fn resolve_url(&self, url: &str) -> String {
+        match &self.base_url {
+            Some(base_url) if !url.contains("://") => {
+                format!("{}/{}", base_url, url.trim_start_matches('/'))
+            }
+            _ => url.to_string(),
+        }
     }
 
     async fn http_request(&self, params: Value) -> AppResult<Value> {
         let url = params.get("url")
             .and_then(|v| v.as_str())
+            .map(|url| self.resolve_url(url))
             .ok_or_else(|| AppError::Validation("url required".to_string()))?;
 
         let method = params.get("method")
             .and_then(|v| v.as_str())
-            .unwrap_or("GET");
+            .unwrap_or("GET")
+            .to_uppercase();
+        let http_method = Method::from_bytes(method.as_bytes())
+            .map_err(|_| AppError::Validation(format!("Invalid HTTP method: {}", method)))?;
+
+        let mut request = self.client.request(http_method, &url);
+        if let Some(headers) = params.get("headers").and_then(|v| v.as_object()) {
+            for (name, value) in headers {
+                if let Some(value) = value.as_str() {
+                    request = request.header(name.as_str(), value);
+                }
+            }
+        }
+        if let Some(body) = params.get("body").filter(|b| !b.is_null()) {
+            request = request.json(body);
+        }
+        if let Some(oauth2) = &self.oauth2 {
+            request = request.bearer_auth(oauth2.access_token().await?);
+        }
 
-        let body = params.get("body");
+        let response = request
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("HTTP request to {} failed: {}", url, e)))?;
+        let status = response.status().as_u16();
+        let text = response.text().await
+            .map_err(|e| AppError::Internal(format!("Failed to read HTTP response from {}: {}", url, e)))?;
+        // Non-JSON responses are returned as text
+        let body = serde_json::from_str(&text).unwrap_or(Value::String(text));
 
-        // TODO: Make actual HTTP request using reqwest
-        // For now, return mock response
         Ok(json!({
             "url": url,
             "method": method,
-            "status": 200,
+            "status": status,
             "body": body,
             "executedAt": chrono::Utc::now().to_rfc3339(),
         }))
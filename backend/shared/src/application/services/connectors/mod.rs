@@ -2,7 +2,7 @@
 //!
 //! Connectors allow workflow Action nodes to call external systems:
 //! - OPD (create visit, update queue status)
-//! - Pharmacy (create prescription, dispense medication)
+//! - Pharmacy (create prescription, dispense medication, check inventory)
 //! - Billing (create invoice, add items, finalize)
 //! - HTTP (call any REST API)
 //! - Database (execute SQL queries)
@@ -10,6 +10,7 @@
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 
 use crate::shared::{AppError, AppResult};
@@ -18,11 +19,19 @@ pub mod opd_connector;
 pub mod pharmacy_connector;
 pub mod billing_connector;
 pub mod http_connector;
+pub mod database_connector;
+pub mod oauth2;
+pub mod config;
 
 pub use opd_connector::OPDConnector;
 pub use pharmacy_connector::PharmacyConnector;
-pub use billing_connector::BillingConnector;
+pub use billing_connector::{BillingConnector, ClaimBuilder, ClaimRequest};
 pub use http_connector::HTTPConnector;
+pub use database_connector::DatabaseConnector;
+pub use oauth2::{CachedToken, ClientCredentials, OAuth2TokenProvider, RealmSecretReader};
+pub use config::{
+    ConnectorAuth, ConnectorBuilder, ConnectorConfig, ConnectorContext, ConnectorFactory, ConnectorLoadError,
+};
 
 /// Connector trait - interface for all workflow connectors
 /// Similar to MuleSoft's connector pattern
@@ -97,11 +106,22 @@ impl ConnectorRegistry {
         }
     }
 
+    /// Load connectors from a TOML (or `.json`) configuration file using the
+    /// built-in connector types; see [`config`] for the file format
+    pub fn load_from_config(path: &Path) -> AppResult<ConnectorRegistry> {
+        Ok(ConnectorFactory::builtin().load(path)?)
+    }
+
     /// Register a connector
     pub fn register(&mut self, connector: Arc<dyn Connector>) {
         self.connectors.insert(connector.name().to_string(), connector);
     }
 
+    /// Register a connector under an explicit ID instead of its name
+    pub fn register_as(&mut self, id: &str, connector: Arc<dyn Connector>) {
+        self.connectors.insert(id.to_string(), connector);
+    }
+
     /// Get a connector by name
     pub fn get(&self, name: &str) -> Option<Arc<dyn Connector>> {
         self.connectors.get(name).cloned()
@@ -122,9 +142,9 @@ impl ConnectorRegistry {
 
     /// Get metadata for all connectors
     pub fn get_all_metadata(&self) -> Vec<ConnectorMetadata> {
-        self.connectors.values().map(|connector| {
+        self.connectors.iter().map(|(id, connector)| {
             ConnectorMetadata {
-                id: connector.name().to_string(),
+                id: id.clone(),
                 name: connector.display_name().to_string(),
                 description: connector.description().to_string(),
                 actions: connector.available_actions(),
@@ -0,0 +1,289 @@
+//! OAuth2 client credentials for outbound connector calls
+//!
+//! [`OAuth2TokenProvider`] obtains an access token from the token endpoint,
+//! caches it for its `expires_in` lifetime and fetches a new one once fewer
+//! than [`TOKEN_REFRESH_MARGIN`] remain. The client secret is read from the
+//! vault on every fetch so it never sits in connector configuration.
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::infrastructure::encryption::RustyVaultClient;
+use crate::infrastructure::tracing::InjectTraceContext;
+use crate::shared::{AppError, AppResult};
+
+/// Cached tokens with less than this left are refreshed before use
+pub const TOKEN_REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Lifetime assumed when the token response has no `expires_in`
+const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(3600);
+
+/// Field of the vault secret holding the client secret
+const CLIENT_SECRET_FIELD: &str = "client_secret";
+
+/// Read access to realm-scoped vault secrets
+#[async_trait]
+pub trait RealmSecretReader: Send + Sync {
+    /// Secret data at `path` in the realm, or `None` if it does not exist
+    async fn read_realm_secret(&self, realm_id: Uuid, path: &str) -> AppResult<Option<Value>>;
+}
+
+#[async_trait]
+impl RealmSecretReader for RustyVaultClient {
+    async fn read_realm_secret(&self, realm_id: Uuid, path: &str) -> AppResult<Option<Value>> {
+        RustyVaultClient::read_realm_secret(self, realm_id, path).await
+    }
+}
+
+/// Client credentials grant settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    /// Realm holding the client secret
+    pub realm_id: Uuid,
+    /// Vault path of the secret; its `client_secret` field is sent to the token endpoint
+    pub client_secret_path: String,
+    pub scopes: Vec<String>,
+}
+
+/// Access token with the instant it stops being valid
+#[derive(Debug, Clone)]
+pub struct CachedToken {
+    pub access_token: String,
+    pub expires_at: Instant,
+}
+
+impl CachedToken {
+    /// Whether the token is still good for at least [`TOKEN_REFRESH_MARGIN`]
+    fn is_fresh(&self) -> bool {
+        self.expires_at
+            .checked_duration_since(Instant::now())
+            .is_some_and(|remaining| remaining > TOKEN_REFRESH_MARGIN)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+/// Fetches and caches client credentials access tokens
+pub struct OAuth2TokenProvider {
+    client: Client,
+    credentials: ClientCredentials,
+    secrets: Arc<dyn RealmSecretReader>,
+    token: RwLock<Option<CachedToken>>,
+}
+
+impl OAuth2TokenProvider {
+    pub fn new(client: Client, credentials: ClientCredentials, secrets: Arc<dyn RealmSecretReader>) -> Self {
+        Self {
+            client,
+            credentials,
+            secrets,
+            token: RwLock::new(None),
+        }
+    }
+
+    /// Current access token, fetching a new one if the cached token is missing or expiring
+    pub async fn access_token(&self) -> AppResult<String> {
+        if let Some(token) = self.token.read().await.as_ref().filter(|t| t.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
+
+        let mut cached = self.token.write().await;
+        // Another request may have refreshed the token while we waited for the lock
+        if let Some(token) = cached.as_ref().filter(|t| t.is_fresh()) {
+            return Ok(token.access_token.clone());
+        }
+        let token = self.fetch_token().await?;
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    async fn client_secret(&self) -> AppResult<String> {
+        let path = &self.credentials.client_secret_path;
+        let secret = self.secrets
+            .read_realm_secret(self.credentials.realm_id, path)
+            .await?
+            .ok_or_else(|| AppError::Configuration(format!("OAuth2 client secret not found in vault at '{}'", path)))?;
+        secret.get(CLIENT_SECRET_FIELD)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| AppError::Configuration(format!(
+                "Vault secret '{}' has no '{}' field", path, CLIENT_SECRET_FIELD
+            )))
+    }
+
+    async fn fetch_token(&self) -> AppResult<CachedToken> {
+        let client_secret = self.client_secret().await?;
+        let scope = self.credentials.scopes.join(" ");
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", self.credentials.client_id.as_str()),
+            ("client_secret", client_secret.as_str()),
+        ];
+        if !scope.is_empty() {
+            form.push(("scope", scope.as_str()));
+        }
+
+        let response = self.client
+            .post(&self.credentials.token_url)
+            .form(&form)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("OAuth2 token request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "OAuth2 token request failed: {} - {}", status, error_text
+            )));
+        }
+
+        let token: TokenResponse = response.json().await
+            .map_err(|e| AppError::Internal(format!("Invalid OAuth2 token response: {}", e)))?;
+        let ttl = token.expires_in.map_or(DEFAULT_TOKEN_TTL, Duration::from_secs);
+        Ok(CachedToken {
+            access_token: token.access_token,
+            expires_at: Instant::now() + ttl,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::connectors::{Connector, HTTPConnector};
+    use axum::extract::{Form, State};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CLIENT_SECRET: &str = "vault-held-secret";
+
+    struct StaticSecrets;
+
+    #[async_trait]
+    impl RealmSecretReader for StaticSecrets {
+        async fn read_realm_secret(&self, _realm_id: Uuid, path: &str) -> AppResult<Option<Value>> {
+            Ok((path == "connectors/lab").then(|| json!({ "client_secret": CLIENT_SECRET })))
+        }
+    }
+
+    #[derive(Clone)]
+    struct MockIdp {
+        token_requests: Arc<AtomicUsize>,
+        expires_in: u64,
+    }
+
+    async fn issue_token(
+        State(idp): State<MockIdp>,
+        Form(form): Form<HashMap<String, String>>,
+    ) -> Result<Json<Value>, StatusCode> {
+        let valid = form.get("grant_type").map(String::as_str) == Some("client_credentials")
+            && form.get("client_id").map(String::as_str) == Some("workflow")
+            && form.get("client_secret").map(String::as_str) == Some(CLIENT_SECRET)
+            && form.get("scope").map(String::as_str) == Some("lab.read lab.write");
+        if !valid {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        let n = idp.token_requests.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(Json(json!({
+            "access_token": format!("token-{}", n),
+            "token_type": "Bearer",
+            "expires_in": idp.expires_in,
+        })))
+    }
+
+    async fn echo_authorization(headers: HeaderMap) -> Json<Value> {
+        let authorization = headers.get("authorization").and_then(|v| v.to_str().ok());
+        Json(json!({ "authorization": authorization }))
+    }
+
+    /// Start a token endpoint (and an echo API) on a random local port
+    async fn mock_idp(expires_in: u64) -> (String, Arc<AtomicUsize>) {
+        let token_requests = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/token", post(issue_token))
+            .route("/echo", get(echo_authorization))
+            .with_state(MockIdp { token_requests: token_requests.clone(), expires_in });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await
+            .unwrap_or_else(|e| panic!("failed to bind mock IdP: {}", e));
+        let addr = listener.local_addr()
+            .unwrap_or_else(|e| panic!("mock IdP has no address: {}", e));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        (format!("http://{}", addr), token_requests)
+    }
+
+    fn provider(base_url: &str, client_secret_path: &str) -> OAuth2TokenProvider {
+        let credentials = ClientCredentials {
+            token_url: format!("{}/token", base_url),
+            client_id: "workflow".to_string(),
+            realm_id: Uuid::nil(),
+            client_secret_path: client_secret_path.to_string(),
+            scopes: vec!["lab.read".to_string(), "lab.write".to_string()],
+        };
+        OAuth2TokenProvider::new(Client::new(), credentials, Arc::new(StaticSecrets))
+    }
+
+    #[tokio::test]
+    async fn token_is_reused_while_fresh() {
+        let (base_url, token_requests) = mock_idp(3600).await;
+        let provider = provider(&base_url, "connectors/lab");
+
+        assert_eq!(provider.access_token().await.ok().as_deref(), Some("token-1"));
+        assert_eq!(provider.access_token().await.ok().as_deref(), Some("token-1"));
+        assert_eq!(token_requests.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn token_expiring_within_margin_is_refetched() {
+        let (base_url, token_requests) = mock_idp(30).await;
+        let provider = provider(&base_url, "connectors/lab");
+
+        assert_eq!(provider.access_token().await.ok().as_deref(), Some("token-1"));
+        assert_eq!(provider.access_token().await.ok().as_deref(), Some("token-2"));
+        assert_eq!(token_requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn missing_vault_secret_fails_without_calling_token_endpoint() {
+        let (base_url, token_requests) = mock_idp(3600).await;
+        let provider = provider(&base_url, "connectors/unknown");
+
+        assert!(matches!(provider.access_token().await, Err(AppError::Configuration(_))));
+        assert_eq!(token_requests.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn http_connector_sends_bearer_token() {
+        let (base_url, token_requests) = mock_idp(3600).await;
+        let connector = HTTPConnector::with_base_url(&base_url)
+            .with_oauth2(provider(&base_url, "connectors/lab"));
+
+        for _ in 0..2 {
+            let result = connector.execute("request", json!({ "url": "/echo" })).await
+                .unwrap_or_else(|e| panic!("request failed: {}", e));
+            assert_eq!(result["status"], 200);
+            assert_eq!(result["body"]["authorization"], "Bearer token-1");
+        }
+        assert_eq!(token_requests.load(Ordering::SeqCst), 1);
+    }
+}
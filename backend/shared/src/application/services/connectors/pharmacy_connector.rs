@@ -1,18 +1,45 @@
 //! Pharmacy Connector - Prescription and medication management
 
 use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
 use serde_json::{json, Value};
 
 use super::{Connector, ConnectorAction, ConnectorParameter};
 use crate::shared::{AppError, AppResult};
+use crate::infrastructure::tracing::InjectTraceContext;
 
 pub struct PharmacyConnector {
+    client: Client,
     api_base_url: String,
 }
 
+/// Subset of the pharmacy API's `InventoryItemResponse` needed for stock checks
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InventoryItem {
+    location_code: String,
+    quantity_on_hand: i64,
+    is_low_stock: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct InventoryResponse {
+    items: Vec<InventoryItem>,
+}
+
 impl PharmacyConnector {
     pub fn new(api_base_url: &str) -> Self {
         Self {
+            client: Client::new(),
+            api_base_url: api_base_url.to_string(),
+        }
+    }
+
+    /// Connector sending requests through a preconfigured client (timeout, auth headers)
+    pub fn with_client(client: Client, api_base_url: &str) -> Self {
+        Self {
+            client,
             api_base_url: api_base_url.to_string(),
         }
     }
@@ -41,6 +68,52 @@ impl PharmacyConnector {
             "dispensedAt": chrono::Utc::now().to_rfc3339(),
         }))
     }
+
+    async fn check_inventory(&self, params: Value) -> AppResult<Value> {
+        let drug_code = params.get("drug_code")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AppError::Validation("drug_code required".to_string()))?;
+        let location_code = params.get("location_code")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AppError::Validation("location_code required".to_string()))?;
+        let required_quantity = params.get("required_quantity")
+            .and_then(|v| v.as_i64())
+            .ok_or_else(|| AppError::Validation("required_quantity required".to_string()))?;
+
+        let url = format!("{}/api/v1/pharmacy/inventory", self.api_base_url);
+        let response = self.client
+            .get(&url)
+            .query(&[("drug_code", drug_code), ("location", location_code)])
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("Pharmacy inventory request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Internal(format!(
+                "Pharmacy inventory request failed with status {}",
+                response.status()
+            )));
+        }
+
+        let inventory: InventoryResponse = response.json().await
+            .map_err(|e| AppError::Internal(format!("Invalid pharmacy inventory response: {}", e)))?;
+        let quantity_on_hand: i64 = inventory.items.iter()
+            .filter(|item| item.location_code == location_code)
+            .map(|item| item.quantity_on_hand)
+            .sum();
+        // No stock record at the location is treated as low stock
+        let is_low_stock = inventory.items.iter()
+            .filter(|item| item.location_code == location_code)
+            .all(|item| item.is_low_stock);
+
+        Ok(json!({
+            "available": quantity_on_hand >= required_quantity,
+            "quantity_on_hand": quantity_on_hand,
+            "location": location_code,
+            "is_low_stock": is_low_stock,
+        }))
+    }
 }
 
 #[async_trait]
@@ -62,6 +135,7 @@ impl Connector for PharmacyConnector {
         match action {
             "createPrescription" => self.create_prescription(params).await,
             "dispenseMedication" => self.dispense_medication(params).await,
+            "checkInventory" => self.check_inventory(params).await,
             _ => Err(AppError::Validation(format!("Unknown pharmacy action: {}", action))),
         }
     }
@@ -92,6 +166,30 @@ impl Connector for PharmacyConnector {
                     },
                 ],
             },
+            ConnectorAction {
+                name: "checkInventory".to_string(),
+                description: "Check drug availability at a pharmacy location".to_string(),
+                parameters: vec![
+                    ConnectorParameter {
+                        name: "drug_code".to_string(),
+                        param_type: "string".to_string(),
+                        required: true,
+                        description: "Drug code".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "location_code".to_string(),
+                        param_type: "string".to_string(),
+                        required: true,
+                        description: "Pharmacy location code".to_string(),
+                    },
+                    ConnectorParameter {
+                        name: "required_quantity".to_string(),
+                        param_type: "number".to_string(),
+                        required: true,
+                        description: "Quantity needed to fill the prescription".to_string(),
+                    },
+                ],
+            },
         ]
     }
 
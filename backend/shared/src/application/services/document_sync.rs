@@ -0,0 +1,94 @@
+//! Background copy of clinical documents into the full-text index
+//!
+//! Notes are written to ^TIU(8925) in YottaDB; search runs on PostgreSQL.
+//! The first pass copies every document, later passes only those listed in
+//! the ^TIU(8925,"AU",updated,IEN) cross-reference since the previous pass.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::domain::repositories::ehr::EhrDocumentSearchRepository;
+use crate::infrastructure::database::mumps::YottaDbAdapter;
+use crate::shared::AppResult;
+
+/// How often YottaDB is polled for changed documents
+pub const DOCUMENT_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Copies ^TIU(8925) documents into the PostgreSQL full-text index
+pub struct DocumentSyncTask {
+    yottadb: Arc<YottaDbAdapter>,
+    repository: Arc<dyn EhrDocumentSearchRepository>,
+    /// "AU" timestamp (YYYYMMDDHHMMSS) up to which changes are copied;
+    /// `None` until the first full pass completes
+    synced_through: Option<i64>,
+}
+
+impl DocumentSyncTask {
+    pub fn new(yottadb: Arc<YottaDbAdapter>, repository: Arc<dyn EhrDocumentSearchRepository>) -> Self {
+        Self {
+            yottadb,
+            repository,
+            synced_through: None,
+        }
+    }
+
+    /// Sync every [`DOCUMENT_SYNC_INTERVAL`], starting immediately
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(DOCUMENT_SYNC_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            interval.tick().await;
+            match self.sync_once().await {
+                Ok(0) => {}
+                Ok(synced) => tracing::info!("Indexed {} clinical documents", synced),
+                Err(e) => e.log_with_operation(concat!(file!(), ":", line!()), "document_sync"),
+            }
+        }
+    }
+
+    /// Copy changed documents, returning how many were indexed
+    ///
+    /// A failed pass is retried in full on the next tick.
+    pub async fn sync_once(&mut self) -> AppResult<usize> {
+        let Some(after) = self.synced_through else {
+            // Documents written before the "AU" cross-reference existed are
+            // only found by a full pass. Changes made during it are picked up
+            // by the next pass, since the mark is taken first.
+            let started = current_timestamp();
+            let iens = self.yottadb.list_document_iens().await?;
+            let synced = self.index(&iens).await?;
+            self.synced_through = Some(started);
+            return Ok(synced);
+        };
+
+        let changes = self.yottadb.documents_updated_after(after).await?;
+        let Some(latest) = changes.iter().map(|(_, updated)| *updated).max() else {
+            return Ok(0);
+        };
+        let mut iens: Vec<i64> = changes.into_iter().map(|(ien, _)| ien).collect();
+        iens.sort_unstable();
+        iens.dedup();
+
+        let synced = self.index(&iens).await?;
+        self.synced_through = Some(latest);
+        Ok(synced)
+    }
+
+    async fn index(&self, iens: &[i64]) -> AppResult<usize> {
+        let mut synced = 0;
+        for &ien in iens {
+            if let Some(document) = self.yottadb.get_document(ien).await? {
+                self.repository.upsert(&document).await?;
+                synced += 1;
+            }
+        }
+        Ok(synced)
+    }
+}
+
+/// Now in the "AU" cross-reference format, YYYYMMDDHHMMSS (UTC)
+fn current_timestamp() -> i64 {
+    Utc::now().format("%Y%m%d%H%M%S").to_string().parse().unwrap_or(0)
+}
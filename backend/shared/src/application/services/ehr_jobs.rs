@@ -0,0 +1,225 @@
+//! EHR Background Job Handlers
+//!
+//! Handlers for the long-running EHR tasks processed by `JobWorker`:
+//! - `ccd_generate` - render a CCD clinical summary for one patient
+//! - `fhir_export`  - export an organization's patients as a FHIR Bundle
+//! - `bulk_import`  - import patients from a FHIR Bundle
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::ehr_service::EhrService;
+use super::fhir_mapper::{self, FhirBundle, FhirBundleEntry};
+use crate::domain::ccd::{CcdBuilder, CcdPatient};
+use crate::domain::entities::ehr::{EhrPatient, Gender};
+use crate::domain::repositories::ehr::patient_repository::{EhrPatientRepository, Pagination};
+use crate::infrastructure::jobs::{JobHandler, JobWorker};
+use crate::shared::{AppError, AppResult};
+
+pub const CCD_GENERATE_JOB: &str = "ccd_generate";
+pub const FHIR_EXPORT_JOB: &str = "fhir_export";
+pub const BULK_IMPORT_JOB: &str = "bulk_import";
+
+/// Page size used when reading patients for export
+const EXPORT_PAGE_SIZE: u32 = 200;
+
+/// Register all EHR job handlers on a worker
+pub fn register_ehr_job_handlers(
+    worker: JobWorker,
+    patient_repository: Arc<dyn EhrPatientRepository>,
+) -> JobWorker {
+    worker
+        .register(CCD_GENERATE_JOB, Arc::new(CcdGenerateJobHandler::new(patient_repository.clone())))
+        .register(FHIR_EXPORT_JOB, Arc::new(FhirExportJobHandler::new(patient_repository.clone())))
+        .register(BULK_IMPORT_JOB, Arc::new(BulkImportJobHandler::new(patient_repository)))
+}
+
+fn parse_payload<T: for<'de> Deserialize<'de>>(job_type: &str, payload: Value) -> AppResult<T> {
+    serde_json::from_value(payload)
+        .map_err(|e| AppError::Validation(format!("Invalid {} payload: {}", job_type, e)))
+}
+
+/// Run CPU-bound work on the blocking thread pool
+async fn run_blocking<T, F>(f: F) -> AppResult<T>
+where
+    F: FnOnce() -> AppResult<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Internal(format!("Blocking job task failed: {}", e)))?
+}
+
+// === ccd_generate ===
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CcdGeneratePayload {
+    organization_id: Uuid,
+    patient_id: Uuid,
+}
+
+pub struct CcdGenerateJobHandler {
+    patient_repository: Arc<dyn EhrPatientRepository>,
+}
+
+impl CcdGenerateJobHandler {
+    pub fn new(patient_repository: Arc<dyn EhrPatientRepository>) -> Self {
+        Self { patient_repository }
+    }
+}
+
+#[async_trait]
+impl JobHandler for CcdGenerateJobHandler {
+    async fn handle(&self, payload: Value) -> AppResult<Value> {
+        let payload: CcdGeneratePayload = parse_payload(CCD_GENERATE_JOB, payload)?;
+
+        let patient = self
+            .patient_repository
+            .find_by_id(payload.patient_id, payload.organization_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Patient {} not found", payload.patient_id)))?;
+
+        let document = run_blocking(move || CcdBuilder::new(ccd_patient(&patient)).build()).await?;
+
+        Ok(json!({
+            "patientId": payload.patient_id,
+            "contentType": "application/xml",
+            "document": document,
+        }))
+    }
+}
+
+/// Map a patient's demographics onto the CCD header
+fn ccd_patient(patient: &EhrPatient) -> CcdPatient {
+    CcdPatient {
+        id: patient.ien.to_string(),
+        mrn: Some(patient.mrn.clone()).filter(|mrn| !mrn.is_empty()),
+        first_name: patient.first_name.clone(),
+        last_name: patient.last_name.clone(),
+        sex: match patient.gender {
+            Gender::Male => "M",
+            Gender::Female => "F",
+            Gender::Other | Gender::Unknown => "UN",
+        }
+        .to_string(),
+        birth_date: patient.date_of_birth.format("%Y-%m-%d").to_string(),
+        city: patient.city.clone(),
+        state: patient.state.clone(),
+    }
+}
+
+// === fhir_export ===
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FhirExportPayload {
+    organization_id: Uuid,
+}
+
+pub struct FhirExportJobHandler {
+    patient_repository: Arc<dyn EhrPatientRepository>,
+}
+
+impl FhirExportJobHandler {
+    pub fn new(patient_repository: Arc<dyn EhrPatientRepository>) -> Self {
+        Self { patient_repository }
+    }
+}
+
+#[async_trait]
+impl JobHandler for FhirExportJobHandler {
+    async fn handle(&self, payload: Value) -> AppResult<Value> {
+        let payload: FhirExportPayload = parse_payload(FHIR_EXPORT_JOB, payload)?;
+
+        let mut patients = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = self
+                .patient_repository
+                .list(payload.organization_id, Pagination { limit: EXPORT_PAGE_SIZE, offset })
+                .await?;
+            let has_more = page.has_more() && !page.items.is_empty();
+            offset += page.items.len() as u32;
+            patients.extend(page.items);
+            if !has_more {
+                break;
+            }
+        }
+
+        let total = patients.len();
+        let bundle = run_blocking(move || {
+            let entry = patients
+                .iter()
+                .map(|patient| {
+                    let resource = serde_json::to_value(fhir_mapper::patient_to_fhir(patient))
+                        .map_err(|e| AppError::Internal(format!("Failed to serialize Patient: {}", e)))?;
+                    Ok(FhirBundleEntry {
+                        full_url: Some(format!("urn:uuid:{}", patient.id)),
+                        resource: Some(resource),
+                    })
+                })
+                .collect::<AppResult<Vec<_>>>()?;
+
+            serde_json::to_value(FhirBundle {
+                resource_type: "Bundle".to_string(),
+                bundle_type: Some("collection".to_string()),
+                total: None,
+                entry,
+            })
+            .map_err(|e| AppError::Internal(format!("Failed to serialize Bundle: {}", e)))
+        })
+        .await?;
+
+        Ok(json!({
+            "total": total,
+            "bundle": bundle,
+        }))
+    }
+}
+
+// === bulk_import ===
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BulkImportPayload {
+    organization_id: Uuid,
+    bundle: FhirBundle,
+    batch_size: Option<usize>,
+}
+
+pub struct BulkImportJobHandler {
+    patient_repository: Arc<dyn EhrPatientRepository>,
+}
+
+impl BulkImportJobHandler {
+    pub fn new(patient_repository: Arc<dyn EhrPatientRepository>) -> Self {
+        Self { patient_repository }
+    }
+}
+
+#[async_trait]
+impl JobHandler for BulkImportJobHandler {
+    async fn handle(&self, payload: Value) -> AppResult<Value> {
+        // Large bundles make deserialization itself expensive
+        let payload: BulkImportPayload =
+            run_blocking(move || parse_payload(BULK_IMPORT_JOB, payload)).await?;
+
+        let mut ehr_service = EhrService::from_env()
+            .with_patient_repository(self.patient_repository.clone());
+        if let Some(batch_size) = payload.batch_size {
+            ehr_service = ehr_service.with_import_batch_size(batch_size);
+        }
+
+        let result = ehr_service
+            .import_fhir_bundle(payload.bundle, payload.organization_id)
+            .await?;
+
+        serde_json::to_value(result)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize import result: {}", e)))
+    }
+}
@@ -4,26 +4,57 @@
 //! for MUMPS-style hierarchical storage while maintaining PostgreSQL
 //! for relational queries and indexing.
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+
+use super::fhir_mapper::{self, FhirBundle, FhirPatient};
+use crate::domain::entities::ehr::{EhrPatient, Gender};
+use crate::domain::repositories::ehr::{
+    EhrMedicationHistoryRepository, EhrPatientMergeRepository, EhrPatientRepository,
+    EhrPatientSummaryRepository, MedicationRecord, PatientFile, PatientMerge,
+};
+use crate::domain::repositories::{AuditLogEntry, AuditLogRepository};
 use crate::infrastructure::database::mumps::{YottaDbAdapter, Global, HierarchicalAccess};
 use crate::shared::{AppError, AppResult};
 
+/// Default number of patients inserted per transaction during bulk import
+pub const DEFAULT_IMPORT_BATCH_SIZE: usize = 50;
+
 /// EHR Service for clinical data management
 pub struct EhrService {
     yottadb: Arc<YottaDbAdapter>,
+    patient_repository: Option<Arc<dyn EhrPatientRepository>>,
+    import_batch_size: usize,
 }
 
 impl EhrService {
     /// Create new EHR service
     pub fn new(yottadb: Arc<YottaDbAdapter>) -> Self {
-        Self { yottadb }
+        Self {
+            yottadb,
+            patient_repository: None,
+            import_batch_size: DEFAULT_IMPORT_BATCH_SIZE,
+        }
     }
 
     /// Create from environment
     pub fn from_env() -> Self {
-        let yottadb = Arc::new(YottaDbAdapter::from_env());
-        Self { yottadb }
+        Self::new(Arc::new(YottaDbAdapter::from_env()))
+    }
+
+    /// Attach the PostgreSQL patient repository (required for bulk import)
+    pub fn with_patient_repository(mut self, repository: Arc<dyn EhrPatientRepository>) -> Self {
+        self.patient_repository = Some(repository);
+        self
+    }
+
+    /// Set the number of patients inserted per transaction during bulk import
+    pub fn with_import_batch_size(mut self, batch_size: usize) -> Self {
+        self.import_batch_size = batch_size.max(1);
+        self
     }
 
     // === Patient Operations ===
@@ -46,6 +77,12 @@ impl EhrService {
         Ok(patient.map(EhrPatientDto::from_yottadb))
     }
 
+    /// Get patient by MRN (admissions lookup)
+    pub async fn get_patient_by_mrn(&self, mrn: &str) -> AppResult<Option<EhrPatientDto>> {
+        let patient = self.yottadb.find_patient_by_mrn(mrn).await?;
+        Ok(patient.map(EhrPatientDto::from_yottadb))
+    }
+
     /// Create new patient in YottaDB
     pub async fn create_patient(&self, request: CreatePatientDto) -> AppResult<EhrPatientDto> {
         use crate::infrastructure::database::mumps::PatientData;
@@ -126,6 +163,119 @@ impl EhrService {
         Ok(results)
     }
 
+    /// Import patients from a FHIR R4 Bundle
+    ///
+    /// Each `Patient` entry is mapped to a `CreatePatientDto` and inserted in
+    /// transactional batches of `import_batch_size`. Invalid entries and rows
+    /// the database rejects are recorded in `errors` (by entry index) without
+    /// aborting the rest of the import; non-Patient entries are counted as
+    /// skipped.
+    pub async fn import_fhir_bundle(
+        &self,
+        bundle: FhirBundle,
+        organization_id: Uuid,
+    ) -> AppResult<BulkImportResult> {
+        if bundle.resource_type != "Bundle" {
+            return Err(AppError::Validation(format!(
+                "Expected Bundle resource, got {}", bundle.resource_type
+            )));
+        }
+
+        let repository = self.patient_repository.as_ref().ok_or_else(|| {
+            AppError::Configuration("Patient repository not configured for EHR service".to_string())
+        })?;
+
+        let mut result = BulkImportResult::default();
+        let mut pending: Vec<(usize, EhrPatient)> = Vec::with_capacity(self.import_batch_size);
+
+        for (index, entry) in bundle.entry.iter().enumerate() {
+            let resource = match (entry.resource_type(), entry.resource.as_ref()) {
+                (Some("Patient"), Some(resource)) => resource,
+                _ => {
+                    result.skipped += 1;
+                    continue;
+                }
+            };
+
+            let patient = FhirPatient::from_resource(resource)
+                .and_then(|p| fhir_mapper::patient_to_create_dto(&p))
+                .and_then(|dto| Self::dto_to_patient(dto, organization_id));
+
+            match patient {
+                Ok(patient) => pending.push((index, patient)),
+                Err(e) => result.errors.push((index, e)),
+            }
+
+            if pending.len() >= self.import_batch_size {
+                Self::flush_import_batch(repository.as_ref(), &mut pending, &mut result).await;
+            }
+        }
+
+        Self::flush_import_batch(repository.as_ref(), &mut pending, &mut result).await;
+        result.errors.sort_by_key(|(index, _)| *index);
+
+        tracing::info!(
+            "FHIR bundle import: imported={}, skipped={}, errors={}",
+            result.imported, result.skipped, result.errors.len()
+        );
+
+        Ok(result)
+    }
+
+    /// Insert a batch in one transaction, reporting each rejected row; if the
+    /// transaction itself fails every entry of the batch is reported
+    async fn flush_import_batch(
+        repository: &dyn EhrPatientRepository,
+        pending: &mut Vec<(usize, EhrPatient)>,
+        result: &mut BulkImportResult,
+    ) {
+        if pending.is_empty() {
+            return;
+        }
+
+        let (indices, patients): (Vec<usize>, Vec<EhrPatient>) = pending.drain(..).unzip();
+        match repository.create_batch(patients).await {
+            Ok(created) => {
+                for (index, patient) in indices.into_iter().zip(created) {
+                    match patient {
+                        Ok(_) => result.imported += 1,
+                        Err(e) => result.errors.push((index, e.to_string())),
+                    }
+                }
+            }
+            Err(e) => {
+                let message = e.to_string();
+                result.errors.extend(indices.into_iter().map(|i| (i, message.clone())));
+            }
+        }
+    }
+
+    fn dto_to_patient(dto: CreatePatientDto, organization_id: Uuid) -> Result<EhrPatient, String> {
+        let dob = NaiveDate::parse_from_str(&dto.date_of_birth, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date of birth '{}'", dto.date_of_birth))?;
+        let gender = match dto.sex.as_str() {
+            "M" => Gender::Male,
+            "F" => Gender::Female,
+            "O" => Gender::Other,
+            _ => Gender::Unknown,
+        };
+
+        let mut patient = EhrPatient::new(
+            organization_id,
+            dto.last_name,
+            dto.first_name,
+            dob,
+            gender,
+            dto.mrn.unwrap_or_default(), // Empty MRN is generated by the database
+        );
+        patient.ssn_last_four = dto.ssn
+            .map(|ssn| ssn.chars().filter(|c| c.is_ascii_digit()).collect::<String>())
+            .filter(|digits| digits.len() >= 4)
+            .map(|digits| digits[digits.len() - 4..].to_string());
+
+        Ok(patient)
+    }
+
     // === Problem Operations ===
 
     /// Get problems for patient
@@ -271,6 +421,311 @@ impl EhrService {
     }
 }
 
+/// EHR dashboard aggregations
+pub struct EhrDashboardService {
+    summary_repository: Arc<dyn EhrPatientSummaryRepository>,
+}
+
+impl EhrDashboardService {
+    /// Create new dashboard service
+    pub fn new(summary_repository: Arc<dyn EhrPatientSummaryRepository>) -> Self {
+        Self { summary_repository }
+    }
+
+    /// Create from environment, counting directly against YottaDB
+    pub fn from_env() -> Self {
+        Self::new(Arc::new(YottaDbAdapter::from_env()))
+    }
+
+    /// Get dashboard counts for a patient
+    ///
+    /// The counts are independent, so they run concurrently and the summary
+    /// takes as long as the slowest query rather than the sum of all five.
+    /// Upcoming appointments start from today in the server's local time,
+    /// the calendar appointments are booked in.
+    pub async fn get_patient_summary(&self, patient_id: i64) -> AppResult<PatientSummary> {
+        let today = chrono::Local::now().date_naive();
+        let repo = &self.summary_repository;
+        let (problems, medications, allergies, labs, appointments) = tokio::join!(
+            repo.count_active_problems(patient_id),
+            repo.count_current_medications(patient_id),
+            repo.count_known_allergies(patient_id),
+            repo.count_pending_labs(patient_id),
+            repo.count_upcoming_appointments(patient_id, today),
+        );
+
+        Ok(PatientSummary {
+            active_problems: problems?,
+            current_medications: medications?,
+            known_allergies: allergies?,
+            pending_labs: labs?,
+            upcoming_appointments: appointments?,
+        })
+    }
+}
+
+/// Medication reconciliation between a visit and the patient's previous visit
+///
+/// Compares the medications active at the previous visit with the current
+/// active list. Medications are matched by drug code when both sides have
+/// one, otherwise by normalized drug name.
+pub struct MedicationReconciliationUseCase {
+    history_repository: Arc<dyn EhrMedicationHistoryRepository>,
+}
+
+impl MedicationReconciliationUseCase {
+    /// Create new reconciliation use case
+    pub fn new(history_repository: Arc<dyn EhrMedicationHistoryRepository>) -> Self {
+        Self { history_repository }
+    }
+
+    /// Create from environment, reading directly from YottaDB
+    pub fn from_env() -> Self {
+        Self::new(Arc::new(YottaDbAdapter::from_env()))
+    }
+
+    /// Reconcile the active medication list for `visit_ien`
+    ///
+    /// The previous visit is the latest non-cancelled visit dated before this
+    /// one; its list is every medication whose start/end dates cover that
+    /// visit's date. Without a previous visit every active medication is new.
+    pub async fn reconcile(&self, patient_ien: i64, visit_ien: i64) -> AppResult<ReconciliationResult> {
+        let repo = &self.history_repository;
+        let visit = repo
+            .find_visit(visit_ien)
+            .await?
+            .filter(|v| v.patient_ien == patient_ien)
+            .ok_or_else(|| AppError::NotFound(format!("Visit {} for patient {}", visit_ien, patient_ien)))?;
+        let visit_date = parse_ehr_date(&visit.visit_date)
+            .ok_or_else(|| AppError::InvalidState(format!("Visit {} has no valid date", visit_ien)))?;
+
+        let (visits, medications) = tokio::join!(
+            repo.find_patient_visits(patient_ien),
+            repo.find_patient_medications(patient_ien),
+        );
+        let previous_date = visits?
+            .iter()
+            .filter(|v| v.ien != visit.ien && v.status != "cancelled")
+            .filter_map(|v| parse_ehr_date(&v.visit_date))
+            .filter(|date| *date < visit_date)
+            .max();
+        let medications = medications?;
+
+        let previous = match previous_date {
+            Some(date) => medications
+                .iter()
+                .filter(|m| active_on(m, date))
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        let current = medications
+            .into_iter()
+            .filter(|m| m.status == "active")
+            .collect();
+
+        Ok(reconcile_medications(previous, current))
+    }
+}
+
+/// Date part of a stored `YYYY-MM-DD[...]` value
+fn parse_ehr_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Whether a medication's start/end dates cover `date`
+fn active_on(medication: &MedicationRecord, date: NaiveDate) -> bool {
+    let started = parse_ehr_date(&medication.start_date).is_some_and(|start| start <= date);
+    let ended = medication
+        .end_date
+        .as_deref()
+        .and_then(parse_ehr_date)
+        .is_some_and(|end| end < date);
+    started && !ended
+}
+
+/// Drug name without its strength, e.g. `"Lisinopril 10 mg tab"` -> `"lisinopril"`
+///
+/// The name is cut at the first word starting with a digit.
+pub fn normalize_drug_name(name: &str) -> String {
+    name.split_whitespace()
+        .take_while(|word| !word.starts_with(|c: char| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn drug_code(medication: &MedicationRecord) -> Option<&str> {
+    medication.drug_code.as_deref().map(str::trim).filter(|c| !c.is_empty())
+}
+
+/// Whether two orders are for the same drug
+fn same_drug(a: &MedicationRecord, b: &MedicationRecord) -> bool {
+    match (drug_code(a), drug_code(b)) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        _ => normalize_drug_name(&a.drug_name) == normalize_drug_name(&b.drug_name),
+    }
+}
+
+/// Pair each previous medication with the first unmatched current one for
+/// the same drug
+pub fn reconcile_medications(
+    previous: Vec<MedicationRecord>,
+    current: Vec<MedicationRecord>,
+) -> ReconciliationResult {
+    let mut result = ReconciliationResult::default();
+    let mut unmatched: Vec<Option<MedicationRecord>> = current.into_iter().map(Some).collect();
+
+    for previous in previous {
+        let matched = unmatched
+            .iter_mut()
+            .find(|m| m.as_ref().is_some_and(|m| same_drug(&previous, m)))
+            .and_then(Option::take);
+
+        let Some(current) = matched else {
+            result.discontinued.push(previous);
+            continue;
+        };
+
+        let changes: Vec<String> = [
+            ("dose", previous.dose.trim() != current.dose.trim()),
+            ("route", !previous.route.trim().eq_ignore_ascii_case(current.route.trim())),
+            ("frequency", !previous.frequency.trim().eq_ignore_ascii_case(current.frequency.trim())),
+        ]
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field.to_string())
+        .collect();
+
+        if changes.is_empty() {
+            result.continued.push(MedicationPair { previous, current });
+        } else {
+            result.changed.push(ChangedMedication { previous, current, changes });
+        }
+    }
+
+    result.new = unmatched.into_iter().flatten().collect();
+    result
+}
+
+/// Merge of a duplicate patient registration into the record that is kept
+///
+/// The loser's visits, problems, allergies, vitals, medications, labs,
+/// orders, documents and appointments are re-pointed at the winner and the
+/// loser is hidden behind a merged-into pointer. The moved entries are kept
+/// with the merge, so an unmerge hands back exactly those.
+pub struct PatientMergeUseCase {
+    merge_repository: Arc<dyn EhrPatientMergeRepository>,
+    audit_log: Arc<dyn AuditLogRepository>,
+}
+
+impl PatientMergeUseCase {
+    /// Create new merge use case
+    pub fn new(
+        merge_repository: Arc<dyn EhrPatientMergeRepository>,
+        audit_log: Arc<dyn AuditLogRepository>,
+    ) -> Self {
+        Self { merge_repository, audit_log }
+    }
+
+    /// Create from environment, working directly against YottaDB
+    pub fn from_env(audit_log: Arc<dyn AuditLogRepository>) -> Self {
+        Self::new(Arc::new(YottaDbAdapter::from_env()), audit_log)
+    }
+
+    /// Move every record of `loser_ien` to `winner_ien` and mark the loser merged
+    pub async fn merge(&self, winner_ien: i64, loser_ien: i64, merged_by: Uuid) -> AppResult<MergeResult> {
+        if winner_ien == loser_ien {
+            return Err(AppError::Validation("Cannot merge patient into itself".to_string()));
+        }
+        let repo = &self.merge_repository;
+        for ien in [winner_ien, loser_ien] {
+            if !repo.patient_exists(ien).await? {
+                return Err(AppError::NotFound(format!("Patient {}", ien)));
+            }
+            if repo.find_merge(ien).await?.is_some() {
+                return Err(AppError::Conflict(format!("Patient {} has already been merged", ien)));
+            }
+        }
+
+        let mut moved_records = Vec::new();
+        for file in PatientFile::ALL {
+            for ien in repo.find_record_iens(file, loser_ien).await? {
+                moved_records.push((file, ien));
+            }
+        }
+        let merge = PatientMerge {
+            merged_ien: loser_ien,
+            merged_into_ien: winner_ien,
+            merged_by,
+            merged_at: Utc::now(),
+            moved_records,
+        };
+
+        // Saved before anything moves, so an interrupted merge can still be undone
+        repo.save_merge(&merge).await?;
+        for (file, ien) in &merge.moved_records {
+            repo.reassign_record(*file, *ien, loser_ien, winner_ien).await?;
+        }
+        self.audit(merge_audit_entry("patient_merged", Some(merged_by), &merge)).await;
+
+        let mut records_moved = BTreeMap::new();
+        for (file, _) in &merge.moved_records {
+            *records_moved.entry(*file).or_insert(0) += 1;
+        }
+        Ok(MergeResult { winner_ien, loser_ien, records_moved })
+    }
+
+    /// Restore a merged patient, moving its records back from the winner
+    pub async fn unmerge(&self, merged_ien: i64) -> AppResult<()> {
+        let repo = &self.merge_repository;
+        let merge = repo
+            .find_merge(merged_ien)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Merge of patient {}", merged_ien)))?;
+        // The records now sit with whatever the winner was merged into
+        if repo.find_merge(merge.merged_into_ien).await?.is_some() {
+            return Err(AppError::Conflict(format!(
+                "Patient {} has since been merged; unmerge it first",
+                merge.merged_into_ien
+            )));
+        }
+
+        for (file, ien) in &merge.moved_records {
+            repo.reassign_record(*file, *ien, merge.merged_into_ien, merged_ien).await?;
+        }
+        // Cleared last, so an interrupted unmerge can be run again
+        repo.delete_merge(merged_ien).await?;
+        self.audit(merge_audit_entry("patient_unmerged", None, &merge)).await;
+        Ok(())
+    }
+
+    /// The merge has already happened, so a failed audit write is only logged
+    async fn audit(&self, entry: AuditLogEntry) {
+        if let Err(e) = self.audit_log.create(entry).await {
+            e.log_with_operation(concat!(file!(), ":", line!()), "patient_merge_audit");
+        }
+    }
+}
+
+/// Audit entry for a merge or unmerge; patient IENs are not UUIDs, so they
+/// go in the details rather than `resource_id`
+fn merge_audit_entry(action: &str, user_id: Option<Uuid>, merge: &PatientMerge) -> AuditLogEntry {
+    AuditLogEntry {
+        user_id,
+        action: action.to_string(),
+        resource: "patient".to_string(),
+        resource_id: None,
+        details: serde_json::json!({
+            "mergedIen": merge.merged_ien,
+            "mergedIntoIen": merge.merged_into_ien,
+            "mergedBy": merge.merged_by,
+            "mergedAt": merge.merged_at,
+            "recordsMoved": merge.moved_records.len(),
+        }),
+    }
+}
+
 // === DTOs ===
 
 use serde::{Deserialize, Serialize};
@@ -384,6 +839,67 @@ impl EhrAllergyDto {
     }
 }
 
+/// Outcome of a bulk patient import
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkImportResult {
+    pub imported: usize,
+    pub skipped: usize,
+    /// Entry index and error message for each rejected entry
+    pub errors: Vec<(usize, String)>,
+}
+
+/// Patient dashboard counts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatientSummary {
+    pub active_problems: u32,
+    pub current_medications: u32,
+    pub known_allergies: u32,
+    pub pending_labs: u32,
+    pub upcoming_appointments: u32,
+}
+
+/// Medication present at both visits with the same dose, route and frequency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MedicationPair {
+    pub previous: MedicationRecord,
+    pub current: MedicationRecord,
+}
+
+/// Medication present at both visits with a different dose, route or frequency
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedMedication {
+    pub previous: MedicationRecord,
+    pub current: MedicationRecord,
+    /// Changed fields: `dose`, `route` and/or `frequency`
+    pub changes: Vec<String>,
+}
+
+/// Medication reconciliation outcome
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconciliationResult {
+    pub continued: Vec<MedicationPair>,
+    /// On the previous list only
+    pub discontinued: Vec<MedicationRecord>,
+    /// On the current list only
+    pub new: Vec<MedicationRecord>,
+    pub changed: Vec<ChangedMedication>,
+}
+
+/// Outcome of a patient merge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    pub winner_ien: i64,
+    pub loser_ien: i64,
+    /// Entries moved to the winner, per file
+    pub records_moved: BTreeMap<PatientFile, usize>,
+}
+
 /// Create patient request
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -420,3 +936,497 @@ pub struct CreateAllergyDto {
 
 /// Shared EHR service instance
 pub type SharedEhrService = Arc<EhrService>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::repositories::ehr::patient_repository::{
+        PaginatedResult, Pagination, PatientSearchCriteria,
+    };
+    use crate::domain::repositories::ehr::VisitRecord;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::time::{Duration, Instant};
+    use tokio::sync::Mutex;
+
+    /// In-memory patient repository for import tests
+    #[derive(Default)]
+    struct MemoryPatientRepository {
+        patients: Mutex<Vec<EhrPatient>>,
+    }
+
+    #[async_trait]
+    impl EhrPatientRepository for MemoryPatientRepository {
+        async fn create(&self, patient: EhrPatient) -> AppResult<EhrPatient> {
+            self.patients.lock().await.push(patient.clone());
+            Ok(patient)
+        }
+
+        /// Rejects duplicate MRNs like the `ehr_patients` unique index
+        async fn create_batch(&self, patients: Vec<EhrPatient>) -> AppResult<Vec<AppResult<EhrPatient>>> {
+            let mut stored = self.patients.lock().await;
+            Ok(patients
+                .into_iter()
+                .map(|patient| {
+                    if stored.iter().any(|p| p.mrn == patient.mrn) {
+                        return Err(AppError::Conflict(format!("Duplicate MRN {}", patient.mrn)));
+                    }
+                    stored.push(patient.clone());
+                    Ok(patient)
+                })
+                .collect())
+        }
+
+        async fn find_by_id(&self, _id: Uuid, _organization_id: Uuid) -> AppResult<Option<EhrPatient>> {
+            Ok(None)
+        }
+
+        async fn find_by_ien(&self, _ien: i64, _organization_id: Uuid) -> AppResult<Option<EhrPatient>> {
+            Ok(None)
+        }
+
+        async fn find_by_mrn(&self, _mrn: &str, _organization_id: Uuid) -> AppResult<Option<EhrPatient>> {
+            Ok(None)
+        }
+
+        async fn find_by_ssn(&self, _ssn_last4: &str, _organization_id: Uuid) -> AppResult<Vec<EhrPatient>> {
+            Ok(Vec::new())
+        }
+
+        async fn update(&self, patient: EhrPatient) -> AppResult<EhrPatient> {
+            Ok(patient)
+        }
+
+        async fn delete(&self, _id: Uuid, _organization_id: Uuid) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn search(
+            &self,
+            _organization_id: Uuid,
+            _criteria: PatientSearchCriteria,
+            pagination: Pagination,
+        ) -> AppResult<PaginatedResult<EhrPatient>> {
+            Ok(PaginatedResult { items: Vec::new(), total: 0, limit: pagination.limit, offset: pagination.offset })
+        }
+
+        async fn list(&self, organization_id: Uuid, pagination: Pagination) -> AppResult<PaginatedResult<EhrPatient>> {
+            self.search(organization_id, PatientSearchCriteria::default(), pagination).await
+        }
+
+        async fn count(&self, _organization_id: Uuid) -> AppResult<i64> {
+            Ok(self.patients.lock().await.len() as i64)
+        }
+
+        async fn next_ien(&self, _organization_id: Uuid) -> AppResult<i64> {
+            Ok(1)
+        }
+    }
+
+    fn patient_entry(index: usize) -> serde_json::Value {
+        json!({
+            "resource": {
+                "resourceType": "Patient",
+                "identifier": [{
+                    "type": { "coding": [{ "code": "MR" }] },
+                    "value": format!("MRN-{:04}", index)
+                }],
+                "name": [{ "use": "official", "family": format!("Doe{}", index), "given": ["Jane"] }],
+                "gender": "female",
+                "birthDate": "1980-04-12"
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_import_fhir_bundle_tracks_invalid_entries() {
+        let invalid = [7usize, 21, 42, 63, 99];
+        let entries: Vec<serde_json::Value> = (0..100)
+            .map(|i| {
+                let mut entry = patient_entry(i);
+                if invalid.contains(&i) {
+                    entry["resource"]["birthDate"] = json!("not-a-date");
+                }
+                entry
+            })
+            .collect();
+
+        let bundle: FhirBundle = serde_json::from_value(json!({
+            "resourceType": "Bundle",
+            "type": "transaction",
+            "entry": entries
+        }))
+        .expect("valid bundle");
+
+        let repository = Arc::new(MemoryPatientRepository::default());
+        let service = EhrService::new(Arc::new(YottaDbAdapter::new("http://localhost:0".to_string())))
+            .with_patient_repository(repository.clone())
+            .with_import_batch_size(10);
+
+        let result = service.import_fhir_bundle(bundle, Uuid::nil()).await.expect("import succeeds");
+
+        assert_eq!(result.imported, 95);
+        assert_eq!(result.skipped, 0);
+        assert_eq!(result.errors.iter().map(|(i, _)| *i).collect::<Vec<_>>(), invalid.to_vec());
+        assert_eq!(repository.count(Uuid::nil()).await.expect("count"), 95);
+    }
+
+    #[tokio::test]
+    async fn test_import_fhir_bundle_skips_non_patient_entries() {
+        let bundle: FhirBundle = serde_json::from_value(json!({
+            "resourceType": "Bundle",
+            "entry": [
+                patient_entry(1),
+                { "resource": { "resourceType": "Observation", "status": "final" } }
+            ]
+        }))
+        .expect("valid bundle");
+
+        let service = EhrService::new(Arc::new(YottaDbAdapter::new("http://localhost:0".to_string())))
+            .with_patient_repository(Arc::new(MemoryPatientRepository::default()));
+
+        let result = service.import_fhir_bundle(bundle, Uuid::nil()).await.expect("import succeeds");
+        assert_eq!(result.imported, 1);
+        assert_eq!(result.skipped, 1);
+        assert!(result.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_fhir_bundle_reports_rejected_rows_without_dropping_batch() {
+        // Entry 3 repeats the MRN of entry 1, so the database rejects only that row
+        let mut duplicate = patient_entry(1);
+        duplicate["resource"]["name"][0]["family"] = json!("Duplicate");
+        let bundle: FhirBundle = serde_json::from_value(json!({
+            "resourceType": "Bundle",
+            "entry": [patient_entry(0), patient_entry(1), patient_entry(2), duplicate, patient_entry(4)]
+        }))
+        .expect("valid bundle");
+
+        let repository = Arc::new(MemoryPatientRepository::default());
+        let service = EhrService::new(Arc::new(YottaDbAdapter::new("http://localhost:0".to_string())))
+            .with_patient_repository(repository.clone());
+
+        let result = service.import_fhir_bundle(bundle, Uuid::nil()).await.expect("import succeeds");
+        assert_eq!(result.imported, 4);
+        assert_eq!(result.errors.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(repository.count(Uuid::nil()).await.expect("count"), 4);
+    }
+
+    /// Summary repository where every count takes the same fixed time
+    struct SlowSummaryRepository {
+        delay: Duration,
+    }
+
+    impl SlowSummaryRepository {
+        async fn count(&self, value: u32) -> AppResult<u32> {
+            tokio::time::sleep(self.delay).await;
+            Ok(value)
+        }
+    }
+
+    #[async_trait]
+    impl EhrPatientSummaryRepository for SlowSummaryRepository {
+        async fn count_active_problems(&self, _patient_ien: i64) -> AppResult<u32> {
+            self.count(1).await
+        }
+
+        async fn count_current_medications(&self, _patient_ien: i64) -> AppResult<u32> {
+            self.count(2).await
+        }
+
+        async fn count_known_allergies(&self, _patient_ien: i64) -> AppResult<u32> {
+            self.count(3).await
+        }
+
+        async fn count_pending_labs(&self, _patient_ien: i64) -> AppResult<u32> {
+            self.count(4).await
+        }
+
+        async fn count_upcoming_appointments(&self, _patient_ien: i64, _from: NaiveDate) -> AppResult<u32> {
+            self.count(5).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_patient_summary_runs_counts_concurrently() {
+        let repo = Arc::new(SlowSummaryRepository { delay: Duration::from_millis(50) });
+        let service = EhrDashboardService::new(repo.clone());
+
+        let started = Instant::now();
+        repo.count_active_problems(1).await.unwrap();
+        repo.count_current_medications(1).await.unwrap();
+        repo.count_known_allergies(1).await.unwrap();
+        repo.count_pending_labs(1).await.unwrap();
+        repo.count_upcoming_appointments(1, chrono::Local::now().date_naive()).await.unwrap();
+        let sequential = started.elapsed();
+
+        let started = Instant::now();
+        let summary = service.get_patient_summary(1).await.expect("summary succeeds");
+        let concurrent = started.elapsed();
+
+        assert_eq!(
+            summary,
+            PatientSummary {
+                active_problems: 1,
+                current_medications: 2,
+                known_allergies: 3,
+                pending_labs: 4,
+                upcoming_appointments: 5,
+            }
+        );
+        assert!(
+            concurrent * 3 <= sequential,
+            "concurrent {:?} should be at least 3x faster than sequential {:?}",
+            concurrent,
+            sequential
+        );
+    }
+
+    fn medication(ien: i64, name: &str, code: Option<&str>, dose: &str, start: &str, end: Option<&str>) -> MedicationRecord {
+        MedicationRecord {
+            ien,
+            patient_ien: 1,
+            drug_name: name.to_string(),
+            drug_code: code.map(str::to_string),
+            dose: dose.to_string(),
+            route: "PO".to_string(),
+            frequency: "daily".to_string(),
+            start_date: start.to_string(),
+            end_date: end.map(str::to_string),
+            prescriber_ien: None,
+            status: if end.is_some() { "discontinued" } else { "active" }.to_string(),
+            instructions: None,
+        }
+    }
+
+    fn visit(ien: i64, date: &str) -> VisitRecord {
+        VisitRecord { ien, patient_ien: 1, visit_date: date.to_string(), status: "completed".to_string() }
+    }
+
+    /// In-memory medication and visit history
+    struct MemoryHistoryRepository {
+        visits: Vec<VisitRecord>,
+        medications: Vec<MedicationRecord>,
+    }
+
+    #[async_trait]
+    impl EhrMedicationHistoryRepository for MemoryHistoryRepository {
+        async fn find_visit(&self, visit_ien: i64) -> AppResult<Option<VisitRecord>> {
+            Ok(self.visits.iter().find(|v| v.ien == visit_ien).cloned())
+        }
+
+        async fn find_patient_visits(&self, patient_ien: i64) -> AppResult<Vec<VisitRecord>> {
+            Ok(self.visits.iter().filter(|v| v.patient_ien == patient_ien).cloned().collect())
+        }
+
+        async fn find_patient_medications(&self, patient_ien: i64) -> AppResult<Vec<MedicationRecord>> {
+            Ok(self.medications.iter().filter(|m| m.patient_ien == patient_ien).cloned().collect())
+        }
+    }
+
+    #[test]
+    fn test_normalize_drug_name_strips_strength() {
+        assert_eq!(normalize_drug_name("Lisinopril 10 mg tablet"), "lisinopril");
+        assert_eq!(normalize_drug_name("Metformin HCl 500mg"), "metformin hcl");
+        assert_eq!(normalize_drug_name("  ASPIRIN  "), "aspirin");
+    }
+
+    #[test]
+    fn test_reconcile_medications_classifies_by_code_and_name() {
+        let previous = vec![
+            medication(1, "Lisinopril 10mg", Some("29046"), "10mg", "2024-01-01", None),
+            medication(2, "Atorvastatin 20 MG", None, "20mg", "2024-01-01", None),
+            medication(3, "Warfarin 5mg", Some("11289"), "5mg", "2024-01-01", None),
+        ];
+        let current = vec![
+            medication(4, "LISINOPRIL 20 MG TAB", Some("29046"), "20mg", "2024-03-01", None),
+            medication(2, "Atorvastatin 20 MG", None, "20mg", "2024-01-01", None),
+            medication(5, "Apixaban 5mg", Some("1364430"), "5mg", "2024-03-01", None),
+        ];
+
+        let result = reconcile_medications(previous, current);
+
+        assert_eq!(result.continued.len(), 1);
+        assert_eq!(result.continued[0].current.ien, 2);
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].previous.ien, 1);
+        assert_eq!(result.changed[0].current.ien, 4);
+        assert_eq!(result.changed[0].changes, vec!["dose".to_string()]);
+        assert_eq!(result.discontinued.iter().map(|m| m.ien).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(result.new.iter().map(|m| m.ien).collect::<Vec<_>>(), vec![5]);
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_uses_list_at_previous_visit() {
+        let repository = MemoryHistoryRepository {
+            visits: vec![visit(10, "2024-01-15"), visit(11, "2024-02-15"), visit(12, "2024-03-15")],
+            medications: vec![
+                // Stopped before the previous visit: not on either list
+                medication(1, "Amoxicillin 500mg", None, "500mg", "2024-01-10", Some("2024-01-20")),
+                // Stopped after the previous visit
+                medication(2, "Prednisone 10mg", None, "10mg", "2024-02-01", Some("2024-03-01")),
+                medication(3, "Metformin 500mg", Some("6809"), "500mg", "2024-01-01", None),
+                // Started after the previous visit
+                medication(4, "Omeprazole 20mg", None, "20mg", "2024-03-01", None),
+            ],
+        };
+        let use_case = MedicationReconciliationUseCase::new(Arc::new(repository));
+
+        let result = use_case.reconcile(1, 12).await.expect("reconciliation succeeds");
+
+        assert_eq!(result.continued.iter().map(|p| p.current.ien).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(result.discontinued.iter().map(|m| m.ien).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(result.new.iter().map(|m| m.ien).collect::<Vec<_>>(), vec![4]);
+        assert!(result.changed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_first_visit_and_unknown_visit() {
+        let repository = MemoryHistoryRepository {
+            visits: vec![visit(10, "2024-01-15")],
+            medications: vec![medication(3, "Metformin 500mg", Some("6809"), "500mg", "2024-01-01", None)],
+        };
+        let use_case = MedicationReconciliationUseCase::new(Arc::new(repository));
+
+        let result = use_case.reconcile(1, 10).await.expect("reconciliation succeeds");
+        assert_eq!(result.new.len(), 1);
+        assert!(result.continued.is_empty() && result.discontinued.is_empty());
+
+        assert!(matches!(use_case.reconcile(1, 99).await, Err(AppError::NotFound(_))));
+        assert!(matches!(use_case.reconcile(2, 10).await, Err(AppError::NotFound(_))));
+    }
+
+    /// In-memory patients and clinical entries, keyed by entry and holding the patient IEN
+    #[derive(Default)]
+    struct MemoryMergeRepository {
+        patients: Vec<i64>,
+        records: Mutex<std::collections::HashMap<(PatientFile, i64), i64>>,
+        merges: Mutex<std::collections::HashMap<i64, PatientMerge>>,
+    }
+
+    impl MemoryMergeRepository {
+        async fn count(&self, file: PatientFile, patient_ien: i64) -> usize {
+            self.find_record_iens(file, patient_ien).await.unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl EhrPatientMergeRepository for MemoryMergeRepository {
+        async fn patient_exists(&self, patient_ien: i64) -> AppResult<bool> {
+            Ok(self.patients.contains(&patient_ien))
+        }
+
+        async fn find_merge(&self, merged_ien: i64) -> AppResult<Option<PatientMerge>> {
+            Ok(self.merges.lock().await.get(&merged_ien).cloned())
+        }
+
+        async fn find_record_iens(&self, file: PatientFile, patient_ien: i64) -> AppResult<Vec<i64>> {
+            let records = self.records.lock().await;
+            let mut iens: Vec<i64> = records
+                .iter()
+                .filter(|((f, _), patient)| *f == file && **patient == patient_ien)
+                .map(|((_, ien), _)| *ien)
+                .collect();
+            iens.sort();
+            Ok(iens)
+        }
+
+        async fn reassign_record(&self, file: PatientFile, record_ien: i64, _from: i64, to: i64) -> AppResult<()> {
+            self.records.lock().await.insert((file, record_ien), to);
+            Ok(())
+        }
+
+        async fn save_merge(&self, merge: &PatientMerge) -> AppResult<()> {
+            self.merges.lock().await.insert(merge.merged_ien, merge.clone());
+            Ok(())
+        }
+
+        async fn delete_merge(&self, merged_ien: i64) -> AppResult<()> {
+            self.merges.lock().await.remove(&merged_ien);
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct MemoryAuditLog {
+        entries: Mutex<Vec<AuditLogEntry>>,
+    }
+
+    #[async_trait]
+    impl AuditLogRepository for MemoryAuditLog {
+        async fn create(&self, entry: AuditLogEntry) -> AppResult<()> {
+            self.entries.lock().await.push(entry);
+            Ok(())
+        }
+    }
+
+    /// Patients 1 and 2 registered twice for the same person, each with
+    /// visits on the same dates, plus a lab on the duplicate
+    fn duplicate_registration() -> Arc<MemoryMergeRepository> {
+        let records = [
+            ((PatientFile::Visits, 10), 1),
+            ((PatientFile::Visits, 11), 1),
+            ((PatientFile::Visits, 20), 2),
+            ((PatientFile::Visits, 21), 2),
+            ((PatientFile::Visits, 22), 2),
+            ((PatientFile::Labs, 30), 2),
+            ((PatientFile::Visits, 40), 3),
+        ];
+        Arc::new(MemoryMergeRepository {
+            patients: vec![1, 2, 3],
+            records: Mutex::new(records.into_iter().collect()),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_merge_moves_overlapping_visits_and_unmerge_restores_them() {
+        let repository = duplicate_registration();
+        let audit_log = Arc::new(MemoryAuditLog::default());
+        let use_case = PatientMergeUseCase::new(repository.clone(), audit_log.clone());
+        let merged_by = Uuid::new_v4();
+
+        let result = use_case.merge(1, 2, merged_by).await.expect("merge succeeds");
+        assert_eq!(result.records_moved.get(&PatientFile::Visits), Some(&3));
+        assert_eq!(result.records_moved.get(&PatientFile::Labs), Some(&1));
+        assert_eq!(repository.count(PatientFile::Visits, 1).await, 5);
+        assert_eq!(repository.count(PatientFile::Visits, 2).await, 0);
+        assert_eq!(repository.count(PatientFile::Labs, 1).await, 1);
+        assert_eq!(repository.find_merge(2).await.unwrap().map(|m| m.merged_into_ien), Some(1));
+
+        let entries = audit_log.entries.lock().await.clone();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, "patient_merged");
+        assert_eq!(entries[0].user_id, Some(merged_by));
+        assert_eq!(entries[0].details["recordsMoved"], 4);
+
+        use_case.unmerge(2).await.expect("unmerge succeeds");
+        assert_eq!(repository.count(PatientFile::Visits, 1).await, 2);
+        assert_eq!(repository.count(PatientFile::Visits, 2).await, 3);
+        assert_eq!(repository.count(PatientFile::Labs, 2).await, 1);
+        assert!(repository.find_merge(2).await.unwrap().is_none());
+        assert_eq!(audit_log.entries.lock().await[1].action, "patient_unmerged");
+    }
+
+    #[tokio::test]
+    async fn test_merge_rejects_invalid_pairs() {
+        let repository = duplicate_registration();
+        let use_case = PatientMergeUseCase::new(repository.clone(), Arc::new(MemoryAuditLog::default()));
+        let merged_by = Uuid::new_v4();
+
+        assert!(matches!(use_case.merge(1, 1, merged_by).await, Err(AppError::Validation(_))));
+        assert!(matches!(use_case.merge(1, 99, merged_by).await, Err(AppError::NotFound(_))));
+        assert!(matches!(use_case.unmerge(2).await, Err(AppError::NotFound(_))));
+
+        use_case.merge(1, 2, merged_by).await.expect("merge succeeds");
+        assert!(matches!(use_case.merge(3, 2, merged_by).await, Err(AppError::Conflict(_))));
+
+        // Patient 1 now holds patient 2's records, so 2 cannot be restored until 1 is
+        use_case.merge(3, 1, merged_by).await.expect("merge succeeds");
+        assert!(matches!(use_case.unmerge(2).await, Err(AppError::Conflict(_))));
+        use_case.unmerge(1).await.expect("unmerge succeeds");
+        use_case.unmerge(2).await.expect("unmerge succeeds");
+        assert_eq!(repository.count(PatientFile::Visits, 3).await, 1);
+        assert_eq!(repository.count(PatientFile::Visits, 2).await, 3);
+    }
+}
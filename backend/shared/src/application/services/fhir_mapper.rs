@@ -0,0 +1,233 @@
+//! FHIR R4 Mapper
+//!
+//! Minimal FHIR R4 resource types and mappings onto the EHR DTOs.
+//! Only the fields the EHR stores are modelled; everything else in
+//! the incoming resources is ignored.
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::ehr_service::CreatePatientDto;
+use crate::domain::entities::ehr::{EhrPatient, Gender};
+
+/// FHIR identifier system for US Social Security Numbers
+pub const FHIR_SSN_SYSTEM: &str = "http://hl7.org/fhir/sid/us-ssn";
+
+/// HL7 v2-0203 identifier type code system (`MR` = Medical Record Number)
+pub const FHIR_IDENTIFIER_TYPE_SYSTEM: &str = "http://terminology.hl7.org/CodeSystem/v2-0203";
+
+/// FHIR Bundle resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FhirBundle {
+    pub resource_type: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub bundle_type: Option<String>,
+    /// Number of matches, for `searchset` bundles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(default)]
+    pub entry: Vec<FhirBundleEntry>,
+}
+
+/// Single entry of a FHIR Bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FhirBundleEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_url: Option<String>,
+    /// Raw resource - parsed according to its `resourceType`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<JsonValue>,
+}
+
+impl FhirBundleEntry {
+    /// Resource type of the entry, if any
+    pub fn resource_type(&self) -> Option<&str> {
+        self.resource.as_ref()?.get("resourceType")?.as_str()
+    }
+}
+
+/// FHIR Coding
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FhirCoding {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display: Option<String>,
+}
+
+/// FHIR CodeableConcept
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FhirCodeableConcept {
+    #[serde(default)]
+    pub coding: Vec<FhirCoding>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// FHIR Identifier
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FhirIdentifier {
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub identifier_type: Option<FhirCodeableConcept>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl FhirIdentifier {
+    /// Whether this identifier is typed as a Medical Record Number (v2-0203 `MR`)
+    pub fn is_mrn(&self) -> bool {
+        self.identifier_type
+            .as_ref()
+            .map(|t| t.coding.iter().any(|c| c.code.as_deref() == Some("MR")))
+            .unwrap_or(false)
+    }
+}
+
+/// FHIR HumanName
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FhirHumanName {
+    #[serde(rename = "use", default, skip_serializing_if = "Option::is_none")]
+    pub name_use: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub family: Option<String>,
+    #[serde(default)]
+    pub given: Vec<String>,
+}
+
+/// FHIR R4 Patient resource (subset)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FhirPatient {
+    pub resource_type: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub identifier: Vec<FhirIdentifier>,
+    #[serde(default)]
+    pub name: Vec<FhirHumanName>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gender: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub birth_date: Option<String>,
+}
+
+impl FhirPatient {
+    /// Parse a raw bundle resource into a FHIR Patient
+    pub fn from_resource(resource: &JsonValue) -> Result<Self, String> {
+        serde_json::from_value(resource.clone())
+            .map_err(|e| format!("Invalid Patient resource: {}", e))
+    }
+
+    /// Official name if present, otherwise the first name listed
+    pub(crate) fn primary_name(&self) -> Option<&FhirHumanName> {
+        self.name
+            .iter()
+            .find(|n| n.name_use.as_deref() == Some("official"))
+            .or_else(|| self.name.first())
+    }
+}
+
+/// Map a FHIR administrative gender onto the EHR sex code (M/F/O/U)
+pub fn fhir_gender_to_sex(gender: Option<&str>) -> Result<&'static str, String> {
+    match gender {
+        Some("male") => Ok("M"),
+        Some("female") => Ok("F"),
+        Some("other") => Ok("O"),
+        Some("unknown") | None => Ok("U"),
+        Some(other) => Err(format!("Unsupported gender '{}'", other)),
+    }
+}
+
+/// Map a FHIR Patient onto a `CreatePatientDto`
+pub fn patient_to_create_dto(patient: &FhirPatient) -> Result<CreatePatientDto, String> {
+    if patient.resource_type != "Patient" {
+        return Err(format!("Expected Patient resource, got {}", patient.resource_type));
+    }
+
+    let name = patient.primary_name().ok_or("Patient has no name")?;
+    let last_name = name
+        .family
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .ok_or("Patient name has no family name")?;
+    let first_name = name
+        .given
+        .first()
+        .map(|g| g.trim())
+        .filter(|g| !g.is_empty())
+        .ok_or("Patient name has no given name")?;
+
+    let birth_date = patient.birth_date.as_deref().ok_or("Patient has no birthDate")?;
+    let dob = NaiveDate::parse_from_str(birth_date, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid birthDate '{}'", birth_date))?;
+    if dob > Utc::now().date_naive() {
+        return Err("birthDate cannot be in the future".to_string());
+    }
+
+    let sex = fhir_gender_to_sex(patient.gender.as_deref())?;
+
+    let mrn = patient
+        .identifier
+        .iter()
+        .find(|i| i.is_mrn())
+        .and_then(|i| i.value.clone());
+    let ssn = patient
+        .identifier
+        .iter()
+        .find(|i| i.system.as_deref() == Some(FHIR_SSN_SYSTEM))
+        .and_then(|i| i.value.clone());
+
+    Ok(CreatePatientDto {
+        first_name: first_name.to_string(),
+        last_name: last_name.to_string(),
+        sex: sex.to_string(),
+        date_of_birth: dob.format("%Y-%m-%d").to_string(),
+        ssn,
+        mrn,
+    })
+}
+
+/// Map an EHR patient onto a FHIR Patient resource
+pub fn patient_to_fhir(patient: &EhrPatient) -> FhirPatient {
+    let mut given = vec![patient.first_name.clone()];
+    given.extend(patient.middle_name.clone());
+
+    let gender = match patient.gender {
+        Gender::Male => "male",
+        Gender::Female => "female",
+        Gender::Other => "other",
+        Gender::Unknown => "unknown",
+    };
+
+    FhirPatient {
+        resource_type: "Patient".to_string(),
+        id: Some(patient.id.to_string()),
+        identifier: vec![FhirIdentifier {
+            identifier_type: Some(FhirCodeableConcept {
+                coding: vec![FhirCoding {
+                    system: Some(FHIR_IDENTIFIER_TYPE_SYSTEM.to_string()),
+                    code: Some("MR".to_string()),
+                    display: Some("Medical Record Number".to_string()),
+                }],
+                text: None,
+            }),
+            system: None,
+            value: Some(patient.mrn.clone()),
+        }],
+        name: vec![FhirHumanName {
+            name_use: Some("official".to_string()),
+            family: Some(patient.last_name.clone()),
+            given,
+        }],
+        gender: Some(gender.to_string()),
+        birth_date: Some(patient.date_of_birth.format("%Y-%m-%d").to_string()),
+    }
+}
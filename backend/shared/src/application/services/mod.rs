@@ -1,27 +1,55 @@
 //! Application Services
 
+pub mod appointment_events;
+pub mod document_sync;
+pub mod ehr_jobs;
 pub mod ehr_service;
+pub mod fhir_mapper;
 pub mod rules_engine;
 pub mod workflow_engine;
+pub mod workflow_schema;
 pub mod connectors;
 
+pub use appointment_events::{
+    AppointmentEventBroadcaster, AppointmentStatusEvent, SharedAppointmentEvents,
+};
+
 pub use ehr_service::{
-    EhrService, SharedEhrService,
+    EhrService, SharedEhrService, EhrDashboardService, PatientSummary,
+    MedicationReconciliationUseCase, ReconciliationResult, MedicationPair, ChangedMedication,
+    PatientMergeUseCase, MergeResult,
     EhrPatientDto, EhrProblemDto, EhrAllergyDto,
-    CreatePatientDto, CreateProblemDto, CreateAllergyDto,
+    CreatePatientDto, CreateProblemDto, CreateAllergyDto, BulkImportResult,
 };
 
+pub use document_sync::{DocumentSyncTask, DOCUMENT_SYNC_INTERVAL};
+
+pub use ehr_jobs::register_ehr_job_handlers;
+
+pub use fhir_mapper::{FhirBundle, FhirBundleEntry, FhirPatient};
+
 pub use rules_engine::{
-    RulesEngine, SharedRulesEngine, create_shared_rules_engine,
-    DecisionRule, RuleCategory, RuleContext, RuleResult,
+    RulesEngine, SharedRulesEngine, create_shared_rules_engine, create_persistent_rules_engine,
+    DecisionRule, RuleCategory, RuleContext, RuleResult, BacktestReport,
     JurisdictionContext, ServiceContext, PatientContext, UserContext,
     TaxResult, TaxComponent, DrugScheduleResult, ClinicalAlert, WorkflowDecision,
+    AlertSeverity, ClinicalAlertRule, ClinicalAlertEvaluator, create_clinical_alert_rule,
 };
 
 pub use workflow_engine::{
     WorkflowEngine, SharedWorkflowEngine,
     create_shared_workflow_engine, create_workflow_engine_with_rules,
     WorkflowDefinition, WorkflowNode, WorkflowEdge, NodeType, NodeConfig,
-    WorkflowInstance, WorkflowStatus, ExecutionStep,
-    HumanTask, TaskStatus, EscalationConfig,
+    JoinConfig, JoinMode, BranchResult, ForkOutcome,
+    TriggerType, CronScheduler, CriticalAlert, Clock, SystemClock, parse_cron_expression,
+    WorkflowInstance, WorkflowStatus, ExecutionStep, SagaCoordinator,
+    HumanTask, TaskStatus, EscalationConfig, EscalationLevel, FinalAction,
+    TaskSlaEnforcer, TaskSlaEvent, TaskSlaAlert, DEFAULT_TASK_SLA_SCHEDULE, MAX_TASK_ESCALATIONS,
+    DEFAULT_SUPERVISOR_GROUP,
+    validate_variables,
+};
+
+pub use workflow_schema::{
+    invalid_connector_references, WorkflowSchemaIssue, WorkflowValidationError,
+    WORKFLOW_SCHEMA_DIALECT, WORKFLOW_SCHEMA_ID_PREFIX,
 };
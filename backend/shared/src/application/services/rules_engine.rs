@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::sync::RwLock;
 
+use crate::domain::repositories::DecisionRuleRepository;
 use crate::shared::{AppError, AppResult};
 
 // ============================================================================
@@ -166,12 +167,56 @@ pub struct DecisionRule {
     pub jurisdiction_id: Option<String>,
     /// Tags for filtering
     pub tags: Vec<String>,
+    /// Clinical alert raised by this rule; such rules are evaluated by
+    /// [`ClinicalAlertEvaluator`] instead of through `content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clinical_alert: Option<ClinicalAlertRule>,
     /// Created timestamp
     pub created_at: chrono::DateTime<chrono::Utc>,
     /// Updated timestamp
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Severity of a clinical decision support alert
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertSeverity {
+    Critical,
+    Warning,
+    Info,
+}
+
+impl AlertSeverity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Critical => "critical",
+            Self::Warning => "warning",
+            Self::Info => "info",
+        }
+    }
+}
+
+/// Clinical decision support alert raised when `condition` holds for a patient
+///
+/// Conditions combine comparisons with `&&`, `||` and parentheses, e.g.
+/// `age > 65 && medication_count > 5` or `oxygen_saturation < 90`. Numeric
+/// facts are `age`, `weight_kg`, `medication_count`, `allergy_count`,
+/// `problem_count` and any vital or lab value by name; the lists
+/// `allergies`, `problems` and `medications` support
+/// `allergies contains "penicillin"` (case-insensitive). A comparison on a
+/// fact the patient context doesn't have is false.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClinicalAlertRule {
+    /// Alert type reported on the generated alert (e.g. "polypharmacy")
+    pub alert_type: String,
+    /// Condition expression over patient facts
+    pub condition: String,
+    /// Severity of the generated alert
+    pub severity: AlertSeverity,
+    /// Alert message; `{fact}` placeholders are replaced with the patient's values
+    pub message_template: String,
+}
+
 // ============================================================================
 // Input Context Types
 // ============================================================================
@@ -246,7 +291,7 @@ pub struct ServiceContext {
 }
 
 /// Patient context for clinical decision support
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PatientContext {
     /// Patient ID
     pub patient_id: String,
@@ -271,6 +316,10 @@ pub struct PatientContext {
     /// Lab results (name -> value)
     #[serde(default)]
     pub lab_values: HashMap<String, f64>,
+    /// Vital signs (type -> value); blood pressure as
+    /// `blood_pressure_systolic` and `blood_pressure_diastolic`
+    #[serde(default)]
+    pub vitals: HashMap<String, f64>,
 }
 
 /// User context for authorization rules
@@ -398,6 +447,42 @@ pub struct WorkflowDecision {
     pub metadata: HashMap<String, Value>,
 }
 
+/// Confusion matrix and derived metrics from replaying a rule over history
+///
+/// A case is "positive" when the rule matched. Expected outcomes come from
+/// the historical `RuleResult` recorded for each context.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BacktestReport {
+    /// Rule that was replayed
+    pub rule_id: String,
+    /// Number of historical cases evaluated
+    pub total_cases: usize,
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub true_negatives: usize,
+    pub false_negatives: usize,
+    /// TP / (TP + FP), 0.0 when the rule never matched
+    pub precision: f64,
+    /// TP / (TP + FN), 0.0 when no case was expected to match
+    pub recall: f64,
+    /// Harmonic mean of precision and recall
+    pub f1_score: f64,
+}
+
+impl BacktestReport {
+    fn finalize(mut self) -> Self {
+        let ratio = |num: usize, den: usize| if den == 0 { 0.0 } else { num as f64 / den as f64 };
+        self.precision = ratio(self.true_positives, self.true_positives + self.false_positives);
+        self.recall = ratio(self.true_positives, self.true_positives + self.false_negatives);
+        self.f1_score = if self.precision + self.recall == 0.0 {
+            0.0
+        } else {
+            2.0 * self.precision * self.recall / (self.precision + self.recall)
+        };
+        self
+    }
+}
+
 // ============================================================================
 // Rules Engine Implementation
 // ============================================================================
@@ -408,6 +493,8 @@ pub struct RulesEngine {
     rules_cache: Arc<RwLock<HashMap<String, DecisionRule>>>,
     /// Compiled regex patterns cache
     regex_cache: Arc<RwLock<HashMap<String, Regex>>>,
+    /// Persisted rules and execution history
+    repository: Option<Arc<dyn DecisionRuleRepository>>,
 }
 
 impl RulesEngine {
@@ -416,9 +503,29 @@ impl RulesEngine {
         Self {
             rules_cache: Arc::new(RwLock::new(HashMap::new())),
             regex_cache: Arc::new(RwLock::new(HashMap::new())),
+            repository: None,
         }
     }
 
+    /// Record every `evaluate` call in the repository's execution history
+    pub fn with_repository(mut self, repository: Arc<dyn DecisionRuleRepository>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Load the active rules stored in the repository
+    ///
+    /// Returns the number of rules loaded.
+    pub async fn load_persisted_rules(&self) -> AppResult<usize> {
+        let Some(repository) = &self.repository else {
+            return Ok(0);
+        };
+        let rules = repository.find_active().await?;
+        let count = rules.len();
+        self.load_rules(rules).await?;
+        Ok(count)
+    }
+
     /// Load a decision rule into the engine
     pub async fn load_rule(&self, rule: DecisionRule) -> AppResult<()> {
         // Pre-compile any regex patterns in the rule
@@ -438,6 +545,10 @@ impl RulesEngine {
             }
         }
 
+        if let Some(alert) = &rule.clinical_alert {
+            AlertCondition::parse(&alert.condition)?;
+        }
+
         let mut rules_cache = self.rules_cache.write().await;
         rules_cache.insert(rule.id.clone(), rule);
 
@@ -480,13 +591,22 @@ impl RulesEngine {
         let result = self.evaluate_decision_table(&rule.content, &context_value).await?;
         let elapsed = start.elapsed().as_secs_f64() * 1000.0;
 
-        Ok(RuleResult {
+        let result = RuleResult {
             matched: result.matched,
             output: result.output,
             match_count: result.match_count,
             trace: result.trace,
             performance_ms: Some(elapsed),
-        })
+        };
+
+        // A lost history row must not fail the evaluation itself
+        if let Some(repository) = &self.repository {
+            if let Err(e) = repository.record_execution(rule, context, &result).await {
+                tracing::warn!(rule_id = %rule_id, error = %e, "Failed to record rule execution");
+            }
+        }
+
+        Ok(result)
     }
 
     /// Evaluate a rule with raw JSON context
@@ -516,6 +636,52 @@ impl RulesEngine {
         })
     }
 
+    /// Replay a rule against historical contexts and score it against the
+    /// recorded outcomes
+    ///
+    /// Effective dates and the active flag are ignored so that rules can be
+    /// validated before they are deployed.
+    pub async fn backtest(
+        &self,
+        rule_id: &str,
+        historical_contexts: Vec<RuleContext>,
+        expected_results: Vec<RuleResult>,
+    ) -> AppResult<BacktestReport> {
+        if historical_contexts.len() != expected_results.len() {
+            return Err(AppError::Validation(format!(
+                "Backtest needs one expected result per context ({} contexts, {} results)",
+                historical_contexts.len(),
+                expected_results.len()
+            )));
+        }
+
+        let rules_cache = self.rules_cache.read().await;
+        let rule = rules_cache
+            .get(rule_id)
+            .ok_or_else(|| AppError::NotFound(format!("Rule not found: {}", rule_id)))?;
+
+        let mut report = BacktestReport {
+            rule_id: rule_id.to_string(),
+            total_cases: historical_contexts.len(),
+            ..Default::default()
+        };
+
+        for (context, expected) in historical_contexts.iter().zip(&expected_results) {
+            let context_value = serde_json::to_value(context)
+                .map_err(|e| AppError::Internal(format!("Failed to serialize context: {}", e)))?;
+            let actual = self.evaluate_decision_table(&rule.content, &context_value).await?;
+
+            match (actual.matched, expected.matched) {
+                (true, true) => report.true_positives += 1,
+                (true, false) => report.false_positives += 1,
+                (false, false) => report.true_negatives += 1,
+                (false, true) => report.false_negatives += 1,
+            }
+        }
+
+        Ok(report.finalize())
+    }
+
     /// Evaluate a decision table against input context
     async fn evaluate_decision_table(
         &self,
@@ -871,6 +1037,21 @@ impl RulesEngine {
         }
     }
 
+    /// Evaluate every effective rule carrying a [`ClinicalAlertRule`] for a patient
+    pub async fn evaluate_clinical_alerts(&self, patient: &PatientContext) -> AppResult<Vec<ClinicalAlert>> {
+        let rules_cache = self.rules_cache.read().await;
+        let mut rules: Vec<&DecisionRule> = rules_cache
+            .values()
+            .filter(|r| r.clinical_alert.is_some() && Self::is_rule_effective(r))
+            .collect();
+        rules.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let evaluator = ClinicalAlertEvaluator::new(
+            rules.into_iter().filter_map(|r| r.clinical_alert.clone()).collect(),
+        )?;
+        Ok(evaluator.evaluate(patient))
+    }
+
     /// Determine workflow routing
     pub async fn determine_workflow(
         &self,
@@ -947,6 +1128,320 @@ pub fn create_shared_rules_engine() -> SharedRulesEngine {
     Arc::new(RulesEngine::new())
 }
 
+/// Create a shared rules engine backed by `repository`
+///
+/// The active persisted rules are loaded before the engine is returned.
+pub async fn create_persistent_rules_engine(
+    repository: Arc<dyn DecisionRuleRepository>,
+) -> AppResult<SharedRulesEngine> {
+    let engine = RulesEngine::new().with_repository(repository);
+    let count = engine.load_persisted_rules().await?;
+    tracing::info!(count, "Loaded decision rules");
+    Ok(Arc::new(engine))
+}
+
+// ============================================================================
+// Clinical Alert Evaluation
+// ============================================================================
+
+/// Evaluates [`ClinicalAlertRule`]s against a patient
+#[derive(Debug, Default)]
+pub struct ClinicalAlertEvaluator {
+    rules: Vec<(ClinicalAlertRule, AlertCondition)>,
+}
+
+impl ClinicalAlertEvaluator {
+    /// Parse the rules' conditions, failing on the first invalid expression
+    pub fn new(rules: Vec<ClinicalAlertRule>) -> AppResult<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| AlertCondition::parse(&rule.condition).map(|condition| (rule, condition)))
+            .collect::<AppResult<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Alerts for every rule whose condition holds, in rule order
+    pub fn evaluate(&self, patient: &PatientContext) -> Vec<ClinicalAlert> {
+        self.rules
+            .iter()
+            .filter(|(_, condition)| condition.holds(patient))
+            .map(|(rule, _)| ClinicalAlert {
+                alert_type: rule.alert_type.clone(),
+                severity: rule.severity.as_str().to_string(),
+                message: render_alert_message(&rule.message_template, patient),
+                recommendation: None,
+                related_items: vec![],
+            })
+            .collect()
+    }
+}
+
+/// Numeric fact about a patient, by the name used in conditions
+fn patient_fact(patient: &PatientContext, name: &str) -> Option<f64> {
+    match name {
+        "age" => patient.age.map(f64::from),
+        "weight_kg" => patient.weight_kg,
+        "medication_count" => Some(patient.medications.len() as f64),
+        "allergy_count" => Some(patient.allergies.len() as f64),
+        "problem_count" => Some(patient.diagnoses.len() as f64),
+        _ => patient.vitals.get(name).or_else(|| patient.lab_values.get(name)).copied(),
+    }
+}
+
+/// List of patient entries usable with `contains`
+fn patient_list<'a>(patient: &'a PatientContext, name: &str) -> Option<&'a [String]> {
+    match name {
+        "allergies" => Some(&patient.allergies),
+        "problems" => Some(&patient.diagnoses),
+        "medications" => Some(&patient.medications),
+        _ => None,
+    }
+}
+
+/// Fill `{fact}` placeholders; unknown facts are left as written
+fn render_alert_message(template: &str, patient: &PatientContext) -> String {
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        message.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        let placeholder = &rest[open..open + close + 1];
+        match patient_fact(patient, &placeholder[1..placeholder.len() - 1]) {
+            Some(value) if value.fract() == 0.0 => message.push_str(&format!("{}", value as i64)),
+            Some(value) => message.push_str(&value.to_string()),
+            None => message.push_str(placeholder),
+        }
+        rest = &rest[open + close + 1..];
+    }
+    message.push_str(rest);
+    message
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+    Ne,
+}
+
+impl CompareOp {
+    fn apply(self, actual: f64, expected: f64) -> bool {
+        match self {
+            Self::Gt => actual > expected,
+            Self::Gte => actual >= expected,
+            Self::Lt => actual < expected,
+            Self::Lte => actual <= expected,
+            Self::Eq => actual == expected,
+            Self::Ne => actual != expected,
+        }
+    }
+}
+
+/// Parsed [`ClinicalAlertRule::condition`]
+#[derive(Debug)]
+enum AlertCondition {
+    And(Box<AlertCondition>, Box<AlertCondition>),
+    Or(Box<AlertCondition>, Box<AlertCondition>),
+    Compare { fact: String, op: CompareOp, value: f64 },
+    Contains { list: String, term: String },
+}
+
+impl AlertCondition {
+    fn parse(expression: &str) -> AppResult<Self> {
+        let mut parser = ConditionParser {
+            expression,
+            tokens: tokenize(expression)?,
+            pos: 0,
+        };
+        let condition = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(parser.error("unexpected input after the condition"));
+        }
+        Ok(condition)
+    }
+
+    fn holds(&self, patient: &PatientContext) -> bool {
+        match self {
+            Self::And(left, right) => left.holds(patient) && right.holds(patient),
+            Self::Or(left, right) => left.holds(patient) || right.holds(patient),
+            Self::Compare { fact, op, value } => {
+                patient_fact(patient, fact).is_some_and(|actual| op.apply(actual, *value))
+            }
+            Self::Contains { list, term } => {
+                let term = term.to_lowercase();
+                patient_list(patient, list)
+                    .is_some_and(|items| items.iter().any(|item| item.to_lowercase().contains(&term)))
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ConditionToken {
+    Ident(String),
+    Number(f64),
+    Text(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+fn condition_error(expression: &str, message: &str) -> AppError {
+    AppError::Validation(format!("Invalid alert condition '{}': {}", expression, message))
+}
+
+fn tokenize(expression: &str) -> AppResult<Vec<ConditionToken>> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => ConditionToken::Open,
+            ')' => ConditionToken::Close,
+            '&' | '|' => {
+                if chars.get(i) != Some(&c) {
+                    return Err(condition_error(expression, &format!("expected '{}{}'", c, c)));
+                }
+                i += 1;
+                if c == '&' { ConditionToken::And } else { ConditionToken::Or }
+            }
+            '<' | '>' | '=' | '!' => {
+                let or_equal = chars.get(i) == Some(&'=');
+                if or_equal {
+                    i += 1;
+                }
+                ConditionToken::Op(match (c, or_equal) {
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Gte,
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Lte,
+                    ('=', true) => CompareOp::Eq,
+                    ('!', true) => CompareOp::Ne,
+                    _ => return Err(condition_error(expression, &format!("unknown operator '{}'", c))),
+                })
+            }
+            '"' => {
+                let start = i;
+                while i < chars.len() && chars[i] != '"' {
+                    i += 1;
+                }
+                if i == chars.len() {
+                    return Err(condition_error(expression, "unterminated string"));
+                }
+                i += 1;
+                ConditionToken::Text(chars[start..i - 1].iter().collect())
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let start = i - 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number: String = chars[start..i].iter().collect();
+                ConditionToken::Number(number.parse().map_err(|_| {
+                    condition_error(expression, &format!("invalid number '{}'", number))
+                })?)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i - 1;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                ConditionToken::Ident(chars[start..i].iter().collect())
+            }
+            c => return Err(condition_error(expression, &format!("unexpected character '{}'", c))),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over condition tokens; `&&` binds tighter than `||`
+struct ConditionParser<'a> {
+    expression: &'a str,
+    tokens: Vec<ConditionToken>,
+    pos: usize,
+}
+
+impl ConditionParser<'_> {
+    fn error(&self, message: &str) -> AppError {
+        condition_error(self.expression, message)
+    }
+
+    fn next(&mut self) -> Option<&ConditionToken> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &ConditionToken) -> bool {
+        let matches = self.tokens.get(self.pos) == Some(expected);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    fn or(&mut self) -> AppResult<AlertCondition> {
+        let mut condition = self.and()?;
+        while self.eat(&ConditionToken::Or) {
+            condition = AlertCondition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> AppResult<AlertCondition> {
+        let mut condition = self.term()?;
+        while self.eat(&ConditionToken::And) {
+            condition = AlertCondition::And(Box::new(condition), Box::new(self.term()?));
+        }
+        Ok(condition)
+    }
+
+    fn term(&mut self) -> AppResult<AlertCondition> {
+        let name = match self.next() {
+            Some(ConditionToken::Open) => {
+                let condition = self.or()?;
+                if !self.eat(&ConditionToken::Close) {
+                    return Err(self.error("expected ')'"));
+                }
+                return Ok(condition);
+            }
+            Some(ConditionToken::Ident(name)) => name.clone(),
+            _ => return Err(self.error("expected a fact name or '('")),
+        };
+
+        match self.next() {
+            Some(ConditionToken::Op(op)) => {
+                let op = *op;
+                match self.next() {
+                    Some(ConditionToken::Number(value)) => Ok(AlertCondition::Compare { fact: name, op, value: *value }),
+                    _ => Err(self.error(&format!("expected a number to compare '{}' with", name))),
+                }
+            }
+            Some(ConditionToken::Ident(keyword)) if keyword == "contains" => {
+                if patient_list(&PatientContext::default(), &name).is_none() {
+                    return Err(self.error(&format!("'{}' is not a list", name)));
+                }
+                match self.next() {
+                    Some(ConditionToken::Text(term)) => Ok(AlertCondition::Contains { list: name, term: term.clone() }),
+                    _ => Err(self.error("expected a quoted string after 'contains'")),
+                }
+            }
+            _ => Err(self.error(&format!("expected a comparison after '{}'", name))),
+        }
+    }
+}
+
 // ============================================================================
 // Pre-built Rule Templates
 // ============================================================================
@@ -999,6 +1494,33 @@ pub fn create_tax_rule(
         organization_id: None,
         jurisdiction_id: Some(country_code.to_string()),
         tags: vec!["tax".to_string(), country_code.to_lowercase()],
+        clinical_alert: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    }
+}
+
+/// Create a clinical decision support rule raising `alert`
+pub fn create_clinical_alert_rule(id: &str, name: &str, alert: ClinicalAlertRule) -> DecisionRule {
+    DecisionRule {
+        id: id.to_string(),
+        name: name.to_string(),
+        description: Some(format!("Raises a {} alert when {}", alert.alert_type, alert.condition)),
+        category: RuleCategory::Clinical,
+        content: DecisionTable {
+            hit_policy: HitPolicy::First,
+            inputs: vec![],
+            outputs: vec![],
+            rules: vec![],
+        },
+        is_active: true,
+        version: 1,
+        effective_from: None,
+        effective_to: None,
+        organization_id: None,
+        jurisdiction_id: None,
+        tags: vec!["clinical".to_string(), alert.alert_type.clone()],
+        clinical_alert: Some(alert),
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
     }
@@ -1062,6 +1584,7 @@ mod tests {
             organization_id: None,
             jurisdiction_id: None,
             tags: vec!["test".to_string()],
+            clinical_alert: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -1138,6 +1661,7 @@ mod tests {
             organization_id: None,
             jurisdiction_id: None,
             tags: vec![],
+            clinical_alert: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -1174,6 +1698,7 @@ mod tests {
             organization_id: None,
             jurisdiction_id: None,
             tags: vec![],
+            clinical_alert: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -1227,6 +1752,7 @@ mod tests {
             organization_id: None,
             jurisdiction_id: None,
             tags: vec![],
+            clinical_alert: None,
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
         };
@@ -1252,4 +1778,288 @@ mod tests {
         let result = engine.evaluate("regex_test", &context).await.expect("Failed to evaluate");
         assert!(!result.matched);
     }
+
+    #[tokio::test]
+    async fn test_backtest_drug_interaction_rule() {
+        let engine = RulesEngine::new();
+
+        // Flags aspirin for anticoagulated patients, but misses other NSAIDs
+        let rule = DecisionRule {
+            id: "anticoagulant_interaction".to_string(),
+            name: "Anticoagulant Interaction".to_string(),
+            description: None,
+            category: RuleCategory::Clinical,
+            content: DecisionTable {
+                hit_policy: HitPolicy::First,
+                inputs: vec!["custom.proposed_medication".to_string(), "custom.on_anticoagulant".to_string()],
+                outputs: vec!["alert_type".to_string()],
+                rules: vec![DecisionTableRow {
+                    conditions: vec![
+                        RuleCondition {
+                            field: "custom.proposed_medication".to_string(),
+                            operator: ConditionOperator::Eq,
+                            value: Value::String("aspirin".to_string()),
+                        },
+                        RuleCondition {
+                            field: "custom.on_anticoagulant".to_string(),
+                            operator: ConditionOperator::Eq,
+                            value: Value::Bool(true),
+                        },
+                    ],
+                    output: serde_json::json!({"alert_type": "interaction"}),
+                    priority: 0,
+                    description: None,
+                }],
+            },
+            // Not yet deployed
+            is_active: false,
+            version: 1,
+            effective_from: None,
+            effective_to: None,
+            organization_id: None,
+            jurisdiction_id: None,
+            tags: vec![],
+            clinical_alert: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        engine.load_rule(rule).await.expect("Failed to load rule");
+
+        // 100 historical medication orders; pharmacists flagged every NSAID
+        // ordered for a patient on an anticoagulant
+        let mut contexts = Vec::new();
+        let mut expected = Vec::new();
+        for i in 0..100 {
+            let medication = match i % 4 {
+                0 => "aspirin",
+                2 => "ibuprofen",
+                _ => "acetaminophen",
+            };
+            let on_anticoagulant = i % 2 == 0;
+
+            let mut context = RuleContext::default();
+            context.custom.insert("proposed_medication".to_string(), Value::String(medication.to_string()));
+            context.custom.insert("on_anticoagulant".to_string(), Value::Bool(on_anticoagulant));
+            contexts.push(context);
+
+            expected.push(RuleResult {
+                matched: on_anticoagulant && medication != "acetaminophen",
+                output: Value::Null,
+                match_count: 0,
+                trace: None,
+                performance_ms: None,
+            });
+        }
+
+        let report = engine
+            .backtest("anticoagulant_interaction", contexts, expected)
+            .await
+            .expect("Failed to backtest");
+
+        assert_eq!(report.total_cases, 100);
+        assert_eq!(report.true_positives, 25);
+        assert_eq!(report.false_positives, 0);
+        assert_eq!(report.true_negatives, 50);
+        assert_eq!(report.false_negatives, 25);
+        assert_eq!(report.precision, 1.0);
+        assert_eq!(report.recall, 0.5);
+        assert!((report.f1_score - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    fn polypharmacy_rule() -> ClinicalAlertRule {
+        ClinicalAlertRule {
+            alert_type: "polypharmacy".to_string(),
+            condition: "age > 65 && medication_count > 5".to_string(),
+            severity: AlertSeverity::Warning,
+            message_template: "{medication_count} active medications at age {age}".to_string(),
+        }
+    }
+
+    fn patient_with_medications(age: i32, medication_count: usize) -> PatientContext {
+        PatientContext {
+            patient_id: "1017".to_string(),
+            age: Some(age),
+            medications: (0..medication_count).map(|i| format!("Medication {}", i)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_polypharmacy_alert() {
+        let engine = RulesEngine::new();
+        engine
+            .load_rule(create_clinical_alert_rule("cds_polypharmacy", "Polypharmacy", polypharmacy_rule()))
+            .await
+            .expect("Failed to load rule");
+
+        let alerts = engine
+            .evaluate_clinical_alerts(&patient_with_medications(70, 8))
+            .await
+            .expect("Failed to evaluate");
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].alert_type, "polypharmacy");
+        assert_eq!(alerts[0].severity, "warning");
+        assert_eq!(alerts[0].message, "8 active medications at age 70");
+
+        for patient in [patient_with_medications(60, 8), patient_with_medications(70, 5)] {
+            let alerts = engine.evaluate_clinical_alerts(&patient).await.expect("Failed to evaluate");
+            assert!(alerts.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_clinical_alert_conditions() {
+        let evaluator = ClinicalAlertEvaluator::new(vec![
+            ClinicalAlertRule {
+                alert_type: "hypoxemia".to_string(),
+                condition: "oxygen_saturation < 90".to_string(),
+                severity: AlertSeverity::Critical,
+                message_template: "SpO2 {oxygen_saturation}%".to_string(),
+            },
+            ClinicalAlertRule {
+                alert_type: "beta_lactam_allergy".to_string(),
+                condition: r#"allergies contains "penicillin" && (problems contains "J18" || age >= 80)"#.to_string(),
+                severity: AlertSeverity::Info,
+                message_template: "Avoid beta-lactams".to_string(),
+            },
+        ])
+        .expect("Failed to parse rules");
+
+        // Facts the patient doesn't have never match
+        let mut patient = PatientContext::default();
+        assert!(evaluator.evaluate(&patient).is_empty());
+
+        patient.vitals.insert("oxygen_saturation".to_string(), 88.5);
+        patient.allergies.push("Penicillin G".to_string());
+        patient.diagnoses.push("J18.9 Pneumonia".to_string());
+        let alerts = evaluator.evaluate(&patient);
+        assert_eq!(
+            alerts.iter().map(|a| a.alert_type.as_str()).collect::<Vec<_>>(),
+            vec!["hypoxemia", "beta_lactam_allergy"]
+        );
+        assert_eq!(alerts[0].severity, "critical");
+        assert_eq!(alerts[0].message, "SpO2 88.5%");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_alert_condition_rejected() {
+        let engine = RulesEngine::new();
+        for condition in ["age >", "age > 65 &&", "age = 65", "(age > 65", "age contains \"x\""] {
+            let rule = ClinicalAlertRule {
+                condition: condition.to_string(),
+                ..polypharmacy_rule()
+            };
+            let result = engine.load_rule(create_clinical_alert_rule("invalid", "Invalid", rule)).await;
+            assert!(matches!(result, Err(AppError::Validation(_))), "accepted: {}", condition);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backtest_rejects_mismatched_lengths() {
+        let engine = RulesEngine::new();
+        let result = engine
+            .backtest("missing", vec![RuleContext::default()], vec![])
+            .await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    /// Persisted rules, and the executions recorded against them
+    #[derive(Default)]
+    struct InMemoryDecisionRuleRepository {
+        rules: Vec<DecisionRule>,
+        executions: std::sync::Mutex<Vec<(String, RuleContext, RuleResult)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl DecisionRuleRepository for InMemoryDecisionRuleRepository {
+        async fn find_active(&self) -> AppResult<Vec<DecisionRule>> {
+            Ok(self.rules.iter().filter(|r| r.is_active).cloned().collect())
+        }
+
+        async fn record_execution(
+            &self,
+            rule: &DecisionRule,
+            context: &RuleContext,
+            result: &RuleResult,
+        ) -> AppResult<()> {
+            self.executions.lock().unwrap().push((rule.id.clone(), context.clone(), result.clone()));
+            Ok(())
+        }
+    }
+
+    fn country_rule(id: &str, country_code: &str, is_active: bool) -> DecisionRule {
+        DecisionRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            category: RuleCategory::Custom,
+            content: DecisionTable {
+                hit_policy: HitPolicy::First,
+                inputs: vec!["jurisdiction.country_code".to_string()],
+                outputs: vec!["result".to_string()],
+                rules: vec![DecisionTableRow {
+                    conditions: vec![RuleCondition {
+                        field: "jurisdiction.country_code".to_string(),
+                        operator: ConditionOperator::Eq,
+                        value: Value::String(country_code.to_string()),
+                    }],
+                    output: serde_json::json!({"country": country_code}),
+                    priority: 0,
+                    description: None,
+                }],
+            },
+            is_active,
+            version: 1,
+            effective_from: None,
+            effective_to: None,
+            organization_id: None,
+            jurisdiction_id: None,
+            tags: vec![],
+            clinical_alert: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn country_context(country_code: &str) -> RuleContext {
+        RuleContext {
+            jurisdiction: JurisdictionContext {
+                country_code: country_code.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_persistent_engine_loads_active_rules_and_records_executions() {
+        let repository = Arc::new(InMemoryDecisionRuleRepository {
+            rules: vec![country_rule("us_rule", "US", true), country_rule("retired_rule", "IN", false)],
+            ..Default::default()
+        });
+        let engine = create_persistent_rules_engine(repository.clone())
+            .await
+            .expect("Failed to create engine");
+
+        let loaded: Vec<_> = engine.list_rules().await.into_iter().map(|r| r.id).collect();
+        assert_eq!(loaded, vec!["us_rule"]);
+
+        for country_code in ["US", "IN", "US"] {
+            engine.evaluate("us_rule", &country_context(country_code)).await.expect("Failed to evaluate");
+        }
+
+        let (contexts, expected): (Vec<_>, Vec<_>) = {
+            let executions = repository.executions.lock().unwrap();
+            assert_eq!(executions.len(), 3);
+            assert!(executions.iter().all(|(rule_id, _, _)| rule_id == "us_rule"));
+            executions.iter().map(|(_, context, result)| (context.clone(), result.clone())).unzip()
+        };
+        assert_eq!(expected.iter().filter(|r| r.matched).count(), 2);
+
+        // The recorded history replays as the backtest's expected outcomes
+        let report = engine.backtest("us_rule", contexts, expected).await.expect("Failed to backtest");
+        assert_eq!(report.true_positives, 2);
+        assert_eq!(report.true_negatives, 1);
+        assert_eq!(report.f1_score, 1.0);
+    }
 }
@@ -14,8 +14,9 @@ use tokio::sync::RwLock;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+use crate::domain::state_machine::{SchemaError, WorkflowContext, WorkflowVariableSchema};
 use crate::shared::{AppError, AppResult};
-use super::rules_engine::{RulesEngine, RuleContext, SharedRulesEngine};
+use super::rules_engine::{ClinicalAlert, RulesEngine, RuleContext, SharedRulesEngine};
 use super::connectors::ConnectorRegistry;
 
 /// Node types in a workflow
@@ -34,6 +35,10 @@ pub enum NodeType {
     ParallelSplit,
     /// Parallel join - wait for parallel branches
     ParallelJoin,
+    /// Fork - run each outgoing branch concurrently up to their common Join
+    Fork,
+    /// Join - wait for the branches of a Fork (see [`JoinConfig`])
+    Join,
     /// Human task - requires user interaction
     HumanTask,
     /// Timer - wait for duration or until time
@@ -80,6 +85,13 @@ pub struct NodeConfig {
     /// Parameters for the action
     #[serde(default)]
     pub parameters: HashMap<String, Value>,
+    /// Action that undoes this one when the instance is cancelled or a later
+    /// action fails (e.g. `billing.cancelInvoice`)
+    #[serde(default)]
+    pub compensation: Option<String>,
+    /// Context variables set when the action completes
+    #[serde(default)]
+    pub outputs: HashMap<String, Value>,
 
     // Decision node
     /// Condition expression
@@ -89,6 +101,11 @@ pub struct NodeConfig {
     #[serde(default)]
     pub rule_id: Option<String>,
 
+    // Join node
+    /// When the join proceeds
+    #[serde(default)]
+    pub join: JoinConfig,
+
     // Human task
     /// Assignee (role or user ID)
     #[serde(default)]
@@ -143,17 +160,64 @@ pub struct NodeConfig {
     /// Template ID
     #[serde(default)]
     pub template_id: Option<String>,
+
+    // All nodes
+    /// Context variables that must be valid before the node executes
+    #[serde(default)]
+    pub input_schema: Vec<WorkflowVariableSchema>,
 }
 
 /// Escalation configuration for human tasks
+///
+/// A task that stays pending past its SLA moves to the next level; once the
+/// levels (or [`MAX_TASK_ESCALATIONS`]) run out, `final_action` applies.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EscalationConfig {
-    /// Time before escalation
-    pub after: String,
-    /// Escalation target (role or user)
+    /// Escalation chain, in order
+    pub levels: Vec<EscalationLevel>,
+    /// What happens when nobody along the chain accepts the task
+    #[serde(default)]
+    pub final_action: FinalAction,
+}
+
+/// One step of an escalation chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationLevel {
+    /// Minutes the current assignee has to accept the task
+    pub after_minutes: u32,
+    /// Escalation target (user ID or group name)
     pub escalate_to: String,
-    /// Escalation action
-    pub action: String,
+}
+
+/// What happens to a task nobody accepted
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalAction {
+    /// Complete the task with no result and resume the workflow
+    AutoComplete,
+    /// Cancel the task's workflow instance
+    AutoCancel,
+    /// Mark the task overdue and leave it open
+    #[default]
+    HardAlert,
+}
+
+/// When a Join node proceeds
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JoinMode {
+    /// AND-join: wait for every branch; any failure fails the join
+    #[default]
+    All,
+    /// OR-join: proceed with the first branch to complete successfully
+    Any,
+}
+
+/// Join node configuration
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct JoinConfig {
+    #[serde(default)]
+    pub mode: JoinMode,
 }
 
 /// An edge connecting nodes
@@ -201,6 +265,12 @@ pub struct WorkflowDefinition {
     /// Output variables schema
     #[serde(default)]
     pub output_schema: Option<Value>,
+    /// Declared workflow variables, checked when an instance starts
+    #[serde(default)]
+    pub variables: Vec<WorkflowVariableSchema>,
+    /// How instances are started
+    #[serde(default)]
+    pub trigger: TriggerType,
     /// Whether the workflow is active
     pub is_active: bool,
     /// Organization ID
@@ -218,6 +288,18 @@ pub struct WorkflowDefinition {
     pub created_by: Option<String>,
 }
 
+/// How instances of a workflow are started
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TriggerType {
+    /// Started explicitly through `start_workflow`
+    #[default]
+    Manual,
+    /// Started by the [`CronScheduler`] on a standard 5-field cron schedule
+    /// (`minute hour day-of-month month day-of-week`, UTC)
+    Cron { expression: String },
+}
+
 /// Workflow execution instance
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowInstance {
@@ -249,6 +331,9 @@ pub struct WorkflowInstance {
     /// Correlation ID for tracking related workflows
     #[serde(default)]
     pub correlation_id: Option<String>,
+    /// Why the instance was cancelled
+    #[serde(default)]
+    pub cancellation_reason: Option<String>,
 }
 
 /// Workflow execution status
@@ -267,6 +352,8 @@ pub enum WorkflowStatus {
     Failed,
     /// Cancelled
     Cancelled,
+    /// An action failed and the completed steps were rolled back
+    Compensated,
 }
 
 /// A single execution step in history
@@ -298,6 +385,13 @@ pub struct ExecutionStep {
     /// Decision taken (for decision nodes)
     #[serde(default)]
     pub decision: Option<String>,
+    /// Action that undoes this step if a later step fails
+    #[serde(default)]
+    pub compensation_action: Option<String>,
+    /// What the step produced, passed to its compensation action (e.g. the
+    /// ID of the invoice it created)
+    #[serde(default)]
+    pub output_context: Option<Value>,
 }
 
 /// Human task for user interaction
@@ -341,6 +435,42 @@ pub struct HumanTask {
     /// Result data
     #[serde(default)]
     pub result: Option<Value>,
+    /// When the current assignee was assigned
+    #[serde(default = "Utc::now")]
+    pub assigned_at: DateTime<Utc>,
+    /// Minutes the current assignee has to accept the task
+    #[serde(default)]
+    pub sla_minutes: Option<u32>,
+    /// Next escalation target (user ID or group name)
+    #[serde(default)]
+    pub escalate_to: Option<String>,
+    /// Times the task has been escalated
+    #[serde(default)]
+    pub escalation_count: u32,
+    /// Escalation chain copied from the node
+    #[serde(default)]
+    pub escalation: Option<EscalationConfig>,
+}
+
+impl HumanTask {
+    /// Reassign the task to its escalation target
+    ///
+    /// Only open (pending or claimed) tasks can be escalated.
+    pub fn escalate(&mut self, escalate_to: &str) -> AppResult<()> {
+        match self.status {
+            TaskStatus::Pending | TaskStatus::Claimed => {
+                self.assignee = escalate_to.to_string();
+                self.claimed_by = None;
+                self.status = TaskStatus::Pending;
+                self.assigned_at = Utc::now();
+                Ok(())
+            }
+            _ => Err(AppError::Conflict(format!(
+                "Task {} cannot be escalated from status {:?}",
+                self.id, self.status
+            ))),
+        }
+    }
 }
 
 /// Human task status
@@ -357,9 +487,62 @@ pub enum TaskStatus {
     Expired,
     /// Cancelled
     Cancelled,
+    /// Escalated to the end of its chain without being accepted
+    Overdue,
+}
+
+/// Outcome of one branch of a Fork
+#[derive(Debug, Clone)]
+pub struct BranchResult {
+    /// First node of the branch
+    pub branch: String,
+    /// Steps executed by the branch, in order
+    pub history: Vec<ExecutionStep>,
+    /// The branch's copy of the workflow variables after its last step
+    pub variables: HashMap<String, Value>,
+    /// Why the branch failed, if it did
+    pub error: Option<String>,
+}
+
+/// Branches run by a Fork, up to its Join
+#[derive(Debug, Clone)]
+pub struct ForkOutcome {
+    /// The Join node the branches converge on
+    pub join_id: String,
+    pub mode: JoinMode,
+    /// Branch results in edge order. For an OR-join this is only the winning
+    /// branch, or every branch if all of them failed.
+    pub branches: Vec<BranchResult>,
+}
+
+impl ForkOutcome {
+    /// First failed branch, if the join cannot proceed
+    pub fn failure(&self) -> Option<&BranchResult> {
+        match self.mode {
+            JoinMode::All => self.branches.iter().find(|b| b.error.is_some()),
+            JoinMode::Any if self.branches.iter().all(|b| b.error.is_some()) => self.branches.first(),
+            JoinMode::Any => None,
+        }
+    }
+
+    /// Variables changed by the branches, merged over `base` in branch order;
+    /// when two branches set the same variable the later branch wins
+    pub fn merge_into(&self, base: &mut HashMap<String, Value>) {
+        let original = base.clone();
+        for branch in self.branches.iter().filter(|b| b.error.is_none()) {
+            for (name, value) in &branch.variables {
+                if original.get(name) != Some(value) {
+                    base.insert(name.clone(), value.clone());
+                }
+            }
+        }
+    }
 }
 
 /// Workflow Engine Service
+///
+/// Clones share the same definitions, instances, tasks and connectors.
+#[derive(Clone)]
 pub struct WorkflowEngine {
     /// Workflow definitions cache
     definitions: Arc<RwLock<HashMap<String, WorkflowDefinition>>>,
@@ -396,6 +579,12 @@ impl WorkflowEngine {
         }
     }
 
+    /// Use a different connector registry for Action nodes
+    pub fn with_connectors(mut self, connectors: Arc<ConnectorRegistry>) -> Self {
+        self.connectors = connectors;
+        self
+    }
+
     /// Register a workflow definition
     pub async fn register_workflow(&self, definition: WorkflowDefinition) -> AppResult<()> {
         // Validate the workflow
@@ -422,7 +611,7 @@ impl WorkflowEngine {
     pub async fn start_workflow(
         &self,
         workflow_id: &str,
-        variables: HashMap<String, Value>,
+        mut variables: HashMap<String, Value>,
         correlation_id: Option<String>,
     ) -> AppResult<WorkflowInstance> {
         let definition = self.get_workflow(workflow_id).await
@@ -432,6 +621,9 @@ impl WorkflowEngine {
             return Err(AppError::Validation("Workflow is not active".to_string()));
         }
 
+        validate_variables(&mut variables, &definition.variables)
+            .map_err(|errors| AppError::Validation(schema_violation_message(&errors)))?;
+
         // Find start node
         let start_node = definition.nodes.iter()
             .find(|n| n.node_type == NodeType::Start)
@@ -450,10 +642,12 @@ impl WorkflowEngine {
             error: None,
             parent_instance_id: None,
             correlation_id,
+            cancellation_reason: None,
         };
 
         let mut instances = self.instances.write().await;
         instances.insert(instance.id.clone(), instance.clone());
+        drop(instances); // Release lock before execution
 
         // Start execution
         self.execute_instance(&instance.id).await?;
@@ -467,6 +661,18 @@ impl WorkflowEngine {
         instances.get(instance_id).cloned()
     }
 
+    /// Load a persisted instance so it can be resumed or cancelled
+    pub async fn restore_instance(&self, instance: WorkflowInstance) {
+        let mut instances = self.instances.write().await;
+        instances.insert(instance.id.clone(), instance);
+    }
+
+    /// Drop a restored instance and its tasks once it has been persisted again
+    pub async fn remove_instance(&self, instance_id: &str) -> Option<WorkflowInstance> {
+        self.tasks.write().await.retain(|_, task| task.instance_id != instance_id);
+        self.instances.write().await.remove(instance_id)
+    }
+
     /// Complete a human task
     pub async fn complete_task(
         &self,
@@ -515,6 +721,28 @@ impl WorkflowEngine {
             let step_id = Uuid::new_v4().to_string();
             let started_at = Utc::now();
 
+            if let Err(errors) = validate_variables(&mut instance.variables, &node.config.input_schema) {
+                let message = schema_violation_message(&errors);
+                instance.status = WorkflowStatus::Failed;
+                instance.completed_at = Some(Utc::now());
+                instance.error = Some(message.clone());
+                instance.history.push(ExecutionStep {
+                    id: step_id,
+                    node_id: node_id.clone(),
+                    node_name: node.name.clone(),
+                    started_at,
+                    ended_at: Some(Utc::now()),
+                    duration_ms: Some(0),
+                    input: Some(serde_json::to_value(&instance.variables).unwrap_or_default()),
+                    output: None,
+                    error: Some(message),
+                    decision: None,
+                    compensation_action: None,
+                    output_context: None,
+                });
+                return Ok(());
+            }
+
             match node.node_type {
                 NodeType::Start => {
                     // Start node: move to next nodes
@@ -538,6 +766,8 @@ impl WorkflowEngine {
                         output: None,
                         error: None,
                         decision: None,
+                        compensation_action: None,
+                        output_context: None,
                     });
                 }
 
@@ -557,12 +787,52 @@ impl WorkflowEngine {
                         output: None,
                         error: None,
                         decision: None,
+                        compensation_action: None,
+                        output_context: None,
                     });
                 }
 
                 NodeType::Action => {
-                    // Action node: execute action (placeholder for now)
-                    // TODO: Call module connectors based on action config
+                    let output_context = match self.execute_node(node, &instance.variables).await {
+                        Ok(output_context) => output_context,
+                        Err(e) => {
+                            let message = e.to_string();
+                            instance.history.push(ExecutionStep {
+                                id: step_id,
+                                node_id: node_id.clone(),
+                                node_name: node.name.clone(),
+                                started_at,
+                                ended_at: Some(Utc::now()),
+                                duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
+                                input: Some(serde_json::to_value(&node.config.parameters).unwrap_or_default()),
+                                output: None,
+                                error: Some(message.clone()),
+                                decision: None,
+                                compensation_action: None,
+                                output_context: None,
+                            });
+
+                            let rollback = SagaCoordinator::rollback(Self::completed_steps(instance), &self.connectors).await;
+                            instance.current_nodes.clear();
+                            instance.completed_at = Some(Utc::now());
+                            match rollback {
+                                Ok(()) => {
+                                    instance.status = WorkflowStatus::Compensated;
+                                    instance.error = Some(message);
+                                }
+                                Err(rollback_error) => {
+                                    instance.status = WorkflowStatus::Failed;
+                                    instance.error = Some(format!("{}; rollback failed: {}", message, rollback_error));
+                                }
+                            }
+                            return Ok(());
+                        }
+                    };
+                    instance.variables.extend(node.config.outputs.clone());
+                    if let Value::Object(output) = &output_context {
+                        instance.variables.extend(output.clone());
+                    }
+
                     let edges: Vec<_> = definition.edges.iter()
                         .filter(|e| &e.source == node_id)
                         .collect();
@@ -577,24 +847,54 @@ impl WorkflowEngine {
                         node_name: node.name.clone(),
                         started_at,
                         ended_at: Some(Utc::now()),
-                        duration_ms: Some(10),
+                        duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
                         input: None,
                         output: Some(serde_json::json!({"status": "executed"})),
                         error: None,
                         decision: None,
+                        compensation_action: node.config.compensation.clone(),
+                        output_context: Some(output_context),
                     });
                 }
 
                 NodeType::Decision => {
-                    // Decision node: evaluate condition and choose branch
-                    // For now, take the first edge (simplified)
-                    let edges: Vec<_> = definition.edges.iter()
-                        .filter(|e| &e.source == node_id)
-                        .collect();
+                    // Decision node: take the first edge whose condition holds
+                    let edge = Self::decision_edge(&definition, node_id, &instance.variables);
+                    let error = edge.is_none()
+                        .then(|| format!("No outgoing edge of decision node '{}' matches", node.name));
 
-                    if let Some(edge) = edges.first() {
-                        next_nodes.push(edge.target.clone());
+                    instance.history.push(ExecutionStep {
+                        id: step_id,
+                        node_id: node_id.clone(),
+                        node_name: node.name.clone(),
+                        started_at,
+                        ended_at: Some(Utc::now()),
+                        duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
+                        input: None,
+                        output: None,
+                        error: error.clone(),
+                        decision: edge.map(|e| e.label.clone().unwrap_or_else(|| e.target.clone())),
+                        compensation_action: None,
+                        output_context: None,
+                    });
+
+                    match edge {
+                        Some(edge) => next_nodes.push(edge.target.clone()),
+                        None => {
+                            instance.status = WorkflowStatus::Failed;
+                            instance.completed_at = Some(Utc::now());
+                            instance.error = error;
+                            return Ok(());
+                        }
                     }
+                }
+
+                NodeType::Fork | NodeType::ParallelSplit => {
+                    // Fork: run the branches concurrently, then continue after their join
+                    let outcome = self.execute_parallel_branches(&definition, node_id, &instance.variables).await?;
+                    let Some(join) = definition.nodes.iter().find(|n| n.id == outcome.join_id) else {
+                        return Err(AppError::Internal(format!("Node not found: {}", outcome.join_id)));
+                    };
 
                     instance.history.push(ExecutionStep {
                         id: step_id,
@@ -602,17 +902,46 @@ impl WorkflowEngine {
                         node_name: node.name.clone(),
                         started_at,
                         ended_at: Some(Utc::now()),
-                        duration_ms: Some(5),
+                        duration_ms: Some((Utc::now() - started_at).num_milliseconds()),
                         input: None,
-                        output: None,
+                        output: Some(serde_json::json!({
+                            "branches": outcome.branches.iter().map(|b| &b.branch).collect::<Vec<_>>(),
+                        })),
                         error: None,
-                        decision: Some("default".to_string()),
+                        decision: None,
+                        compensation_action: None,
+                        output_context: None,
                     });
+                    for branch in &outcome.branches {
+                        instance.history.extend(branch.history.iter().cloned());
+                    }
+
+                    if let Some(failed) = outcome.failure() {
+                        let message = format!(
+                            "Branch '{}' failed: {}",
+                            failed.branch,
+                            failed.error.as_deref().unwrap_or_default()
+                        );
+                        instance.status = WorkflowStatus::Failed;
+                        instance.completed_at = Some(Utc::now());
+                        instance.error = Some(message.clone());
+                        instance.history.push(Self::join_step(join, &outcome, Some(message)));
+                        return Ok(());
+                    }
+
+                    outcome.merge_into(&mut instance.variables);
+                    instance.history.push(Self::join_step(join, &outcome, None));
+                    next_nodes.extend(
+                        definition.edges.iter()
+                            .filter(|e| e.source == join.id)
+                            .map(|e| e.target.clone()),
+                    );
                 }
 
                 NodeType::HumanTask => {
                     // Human task: create task and pause workflow
                     let task_id = Uuid::new_v4().to_string();
+                    let first_level = node.config.escalation.as_ref().and_then(|e| e.levels.first());
 
                     let task = HumanTask {
                         id: task_id.clone(),
@@ -630,6 +959,11 @@ impl WorkflowEngine {
                         claimed_by: None,
                         completed_at: None,
                         result: None,
+                        assigned_at: Utc::now(),
+                        sla_minutes: first_level.map(|level| level.after_minutes),
+                        escalate_to: first_level.map(|level| level.escalate_to.clone()),
+                        escalation_count: 0,
+                        escalation: node.config.escalation.clone(),
                     };
 
                     let mut tasks = self.tasks.write().await;
@@ -649,6 +983,8 @@ impl WorkflowEngine {
                         output: Some(serde_json::json!({"task_created": true})),
                         error: None,
                         decision: None,
+                        compensation_action: None,
+                        output_context: None,
                     });
 
                     // Don't move to next nodes yet - wait for task completion
@@ -680,6 +1016,247 @@ impl WorkflowEngine {
         Ok(())
     }
 
+    /// Run an Action node, returning its output context
+    ///
+    /// Actions named `connector.action` call the connector with the node's
+    /// parameters, `${var}` placeholders replaced from `variables`, and return
+    /// its result. Other actions are placeholders whose context is the node's
+    /// configured outputs.
+    async fn execute_node(&self, node: &WorkflowNode, variables: &HashMap<String, Value>) -> AppResult<Value> {
+        match node.config.action.as_deref().and_then(|a| a.split_once('.')) {
+            Some((connector, action)) => {
+                let params = serde_json::to_value(&node.config.parameters).unwrap_or_default();
+                self.connectors.execute(connector, action, substitute_variables(&params, variables)).await
+            }
+            None => Ok(serde_json::to_value(&node.config.outputs).unwrap_or_default()),
+        }
+    }
+
+    /// Outgoing edge a Decision node takes: the first, by priority, whose
+    /// condition holds
+    ///
+    /// An edge without a condition always holds, acting as the default branch.
+    fn decision_edge<'a>(
+        definition: &'a WorkflowDefinition,
+        node_id: &str,
+        variables: &HashMap<String, Value>,
+    ) -> Option<&'a WorkflowEdge> {
+        let mut edges: Vec<_> = definition.edges.iter()
+            .filter(|e| e.source == node_id)
+            .collect();
+        edges.sort_by_key(|e| e.priority);
+        edges.into_iter()
+            .find(|e| e.condition.as_deref().is_none_or(|c| evaluate_condition(c, variables)))
+    }
+
+    /// Run each branch of a Fork on its own task, up to the branches' common Join
+    ///
+    /// Each branch works on its own copy of `variables`; merging them back is
+    /// left to the caller (see [`ForkOutcome::merge_into`]). An AND-join waits
+    /// for every branch, an OR-join for the first one to succeed, aborting
+    /// the others.
+    pub async fn execute_parallel_branches(
+        &self,
+        definition: &WorkflowDefinition,
+        fork_id: &str,
+        variables: &HashMap<String, Value>,
+    ) -> AppResult<ForkOutcome> {
+        let join_id = Self::fork_join(definition, fork_id)?;
+        let mode = definition.nodes.iter()
+            .find(|n| n.id == join_id)
+            .map(|n| n.config.join.mode)
+            .unwrap_or_default();
+        let firsts: Vec<String> = definition.edges.iter()
+            .filter(|e| e.source == fork_id)
+            .map(|e| e.target.clone())
+            .collect();
+
+        let definition = Arc::new(definition.clone());
+        let mut branches = tokio::task::JoinSet::new();
+        for (index, first) in firsts.into_iter().enumerate() {
+            let engine = self.clone();
+            let definition = Arc::clone(&definition);
+            let join_id = join_id.clone();
+            let variables = variables.clone();
+            branches.spawn(async move {
+                (index, engine.run_branch(&definition, first, &join_id, variables).await)
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = branches.join_next().await {
+            let (index, result) = joined
+                .map_err(|e| AppError::Internal(format!("Parallel branch task failed: {}", e)))?;
+            if mode == JoinMode::Any && result.error.is_none() {
+                branches.abort_all();
+                return Ok(ForkOutcome { join_id, mode, branches: vec![result] });
+            }
+            results.push((index, result));
+        }
+        results.sort_by_key(|(index, _)| *index);
+
+        Ok(ForkOutcome {
+            join_id,
+            mode,
+            branches: results.into_iter().map(|(_, result)| result).collect(),
+        })
+    }
+
+    /// Execute a branch's nodes in order until `join_id` is reached
+    ///
+    /// Branches may contain Action, Decision and pass-through nodes. Actions
+    /// run through [`Self::execute_node`] and a failing one fails the branch;
+    /// Decisions take [`Self::decision_edge`]. Nodes that pause or end the
+    /// workflow, and nested forks, fail the branch.
+    async fn run_branch(
+        &self,
+        definition: &WorkflowDefinition,
+        first: String,
+        join_id: &str,
+        mut variables: HashMap<String, Value>,
+    ) -> BranchResult {
+        let mut history = Vec::new();
+        let mut current = first.clone();
+        let fail = |history, variables, error: String| BranchResult {
+            branch: first.clone(),
+            history,
+            variables,
+            error: Some(error),
+        };
+
+        for _ in 0..=definition.nodes.len() {
+            if current == join_id {
+                return BranchResult { branch: first.clone(), history, variables, error: None };
+            }
+            let Some(node) = definition.nodes.iter().find(|n| n.id == current) else {
+                return fail(history, variables, format!("Node not found: {}", current));
+            };
+
+            let started_at = Utc::now();
+            let mut step = ExecutionStep {
+                id: Uuid::new_v4().to_string(),
+                node_id: node.id.clone(),
+                node_name: node.name.clone(),
+                started_at,
+                ended_at: None,
+                duration_ms: None,
+                input: None,
+                output: None,
+                error: None,
+                decision: None,
+                compensation_action: None,
+                output_context: None,
+            };
+            let mut next = definition.edges.iter().find(|e| e.source == node.id);
+
+            if let Err(errors) = validate_variables(&mut variables, &node.config.input_schema) {
+                step.error = Some(schema_violation_message(&errors));
+            } else {
+                match node.node_type {
+                    NodeType::Action => match self.execute_node(node, &variables).await {
+                        Ok(output_context) => {
+                            variables.extend(node.config.outputs.clone());
+                            if let Value::Object(output) = &output_context {
+                                variables.extend(output.clone());
+                            }
+                            step.output = Some(serde_json::json!({"status": "executed"}));
+                            step.compensation_action = node.config.compensation.clone();
+                            step.output_context = Some(output_context);
+                        }
+                        Err(e) => {
+                            step.input = Some(serde_json::to_value(&node.config.parameters).unwrap_or_default());
+                            step.error = Some(e.to_string());
+                        }
+                    },
+                    NodeType::Decision => {
+                        next = Self::decision_edge(definition, &node.id, &variables);
+                        match next {
+                            Some(edge) => step.decision = Some(edge.label.clone().unwrap_or_else(|| edge.target.clone())),
+                            None => step.error = Some(format!("No outgoing edge of decision node '{}' matches", node.name)),
+                        }
+                    }
+                    NodeType::Start | NodeType::End | NodeType::HumanTask | NodeType::Fork
+                    | NodeType::ParallelSplit | NodeType::Join | NodeType::ParallelJoin => {
+                        step.error = Some(format!(
+                            "{:?} node '{}' cannot run inside a parallel branch",
+                            node.node_type, node.name
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+
+            step.ended_at = Some(Utc::now());
+            step.duration_ms = Some((Utc::now() - started_at).num_milliseconds());
+            let error = step.error.clone();
+            history.push(step);
+            if let Some(error) = error {
+                return fail(history, variables, error);
+            }
+
+            match next {
+                Some(edge) => current = edge.target.clone(),
+                None => {
+                    let message = format!("Branch ended at '{}' before reaching join '{}'", node.name, join_id);
+                    return fail(history, variables, message);
+                }
+            }
+        }
+
+        fail(history, variables, "Branch does not reach its join (cycle?)".to_string())
+    }
+
+    /// Join node that every branch of a Fork leads to, following first edges
+    fn fork_join(definition: &WorkflowDefinition, fork_id: &str) -> AppResult<String> {
+        let is_join = |id: &str| {
+            definition.nodes.iter()
+                .any(|n| n.id == id && matches!(n.node_type, NodeType::Join | NodeType::ParallelJoin))
+        };
+        let mut joins = definition.edges.iter()
+            .filter(|e| e.source == fork_id)
+            .map(|e| {
+                let mut current = e.target.as_str();
+                for _ in 0..definition.nodes.len() {
+                    if is_join(current) {
+                        return Some(current.to_string());
+                    }
+                    current = definition.edges.iter().find(|e| e.source == current)?.target.as_str();
+                }
+                None
+            });
+
+        let first = joins.next().flatten();
+        match first {
+            Some(join) if joins.all(|j| j.as_deref() == Some(join.as_str())) => Ok(join),
+            _ => Err(AppError::Validation(format!(
+                "Branches of fork node '{}' must all lead to the same join node",
+                fork_id
+            ))),
+        }
+    }
+
+    /// History step recorded when a Join proceeds (or fails)
+    fn join_step(join: &WorkflowNode, outcome: &ForkOutcome, error: Option<String>) -> ExecutionStep {
+        let completed: Vec<&String> = outcome.branches.iter()
+            .filter(|b| b.error.is_none())
+            .map(|b| &b.branch)
+            .collect();
+        ExecutionStep {
+            id: Uuid::new_v4().to_string(),
+            node_id: join.id.clone(),
+            node_name: join.name.clone(),
+            started_at: Utc::now(),
+            ended_at: Some(Utc::now()),
+            duration_ms: Some(0),
+            input: None,
+            output: Some(serde_json::json!({"mode": outcome.mode, "completed": completed})),
+            error,
+            decision: None,
+            compensation_action: None,
+            output_context: None,
+        }
+    }
+
     /// Resume a paused workflow instance
     pub async fn resume_instance(&self, instance_id: &str) -> AppResult<()> {
         let mut instances = self.instances.write().await;
@@ -728,6 +1305,62 @@ impl WorkflowEngine {
         Ok(())
     }
 
+    /// Cancel a running or waiting instance
+    ///
+    /// Open human tasks are cancelled and the completed steps are rolled back
+    /// through [`SagaCoordinator::rollback`]. If a compensation fails the
+    /// instance ends up `Failed` rather than `Cancelled`.
+    pub async fn cancel(&self, instance_id: &str, reason: String) -> AppResult<()> {
+        let mut instances = self.instances.write().await;
+        let instance = instances.get_mut(instance_id)
+            .ok_or_else(|| AppError::NotFound(format!("Instance not found: {}", instance_id)))?;
+
+        if matches!(
+            instance.status,
+            WorkflowStatus::Completed
+                | WorkflowStatus::Failed
+                | WorkflowStatus::Cancelled
+                | WorkflowStatus::Compensated
+        ) {
+            return Err(AppError::Conflict(format!(
+                "Instance {} has already finished ({:?})",
+                instance_id, instance.status
+            )));
+        }
+
+        let mut tasks = self.tasks.write().await;
+        for task in tasks.values_mut().filter(|t| t.instance_id == instance_id) {
+            if matches!(task.status, TaskStatus::Pending | TaskStatus::Claimed) {
+                task.status = TaskStatus::Cancelled;
+                task.completed_at = Some(Utc::now());
+            }
+        }
+        drop(tasks);
+
+        let rollback = SagaCoordinator::rollback(Self::completed_steps(instance), &self.connectors).await;
+
+        instance.current_nodes.clear();
+        instance.completed_at = Some(Utc::now());
+        instance.cancellation_reason = Some(reason);
+        match rollback {
+            Ok(()) => instance.status = WorkflowStatus::Cancelled,
+            Err(rollback_error) => {
+                instance.status = WorkflowStatus::Failed;
+                instance.error = Some(format!("Cancelled; rollback failed: {}", rollback_error));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Steps that finished without error, oldest first
+    fn completed_steps(instance: &WorkflowInstance) -> Vec<ExecutionStep> {
+        instance.history.iter()
+            .filter(|step| step.ended_at.is_some() && step.error.is_none())
+            .cloned()
+            .collect()
+    }
+
     /// Validate a workflow definition
     fn validate_workflow(&self, definition: &WorkflowDefinition) -> AppResult<()> {
         // Must have at least one start node
@@ -782,6 +1415,20 @@ impl WorkflowEngine {
                     )));
                 }
             }
+
+            // Fork nodes need at least 2 branches that meet at one join
+            if matches!(node.node_type, NodeType::Fork | NodeType::ParallelSplit) {
+                let outgoing = definition.edges.iter()
+                    .filter(|e| e.source == node.id)
+                    .count();
+                if outgoing < 2 {
+                    return Err(AppError::Validation(format!(
+                        "Fork node '{}' must have at least 2 outgoing edges",
+                        node.name
+                    )));
+                }
+                Self::fork_join(definition, &node.id)?;
+            }
         }
 
         Ok(())
@@ -916,6 +1563,8 @@ impl WorkflowEngine {
                     "comments": { "type": "string" }
                 }
             })),
+            variables: vec![],
+            trigger: TriggerType::Manual,
             is_active: true,
             organization_id: None,
             tags: vec!["template".to_string(), "approval".to_string()],
@@ -924,86 +1573,1782 @@ impl WorkflowEngine {
             created_by: None,
         }
     }
-}
-
-impl Default for WorkflowEngine {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-/// Shared workflow engine instance
-pub type SharedWorkflowEngine = Arc<WorkflowEngine>;
-
-/// Create a shared workflow engine
-pub fn create_shared_workflow_engine() -> SharedWorkflowEngine {
-    Arc::new(WorkflowEngine::new())
-}
-
-/// Create a workflow engine with rules integration
-pub fn create_workflow_engine_with_rules(rules_engine: SharedRulesEngine) -> SharedWorkflowEngine {
-    Arc::new(WorkflowEngine::with_rules_engine(rules_engine))
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[tokio::test]
-    async fn test_workflow_engine_creation() {
-        let engine = WorkflowEngine::new();
-        let workflows = engine.list_workflows().await;
-        assert!(workflows.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_approval_template() {
-        let template = WorkflowEngine::create_approval_workflow_template(
-            "Test Approval",
-            "manager"
-        );
-
-        assert_eq!(template.nodes.len(), 5);
-        assert_eq!(template.edges.len(), 4);
 
-        let engine = WorkflowEngine::new();
-        engine.register_workflow(template).await.expect("Should register");
-    }
+    /// Create a prescription dispensing workflow template
+    ///
+    /// Checks stock with `pharmacy.checkInventory` before dispensing; when the
+    /// drug is unavailable at the location the prescription is routed to a
+    /// back-order task for pharmacy staff instead.
+    pub fn create_prescription_dispensing_workflow_template(name: &str) -> WorkflowDefinition {
+        let now = Utc::now();
 
-    #[tokio::test]
-    async fn test_workflow_validation() {
-        let engine = WorkflowEngine::new();
+        let inventory_parameters = HashMap::from([
+            ("drug_code".to_string(), serde_json::json!("${drug_code}")),
+            ("location_code".to_string(), serde_json::json!("${location_code}")),
+            ("required_quantity".to_string(), serde_json::json!("${quantity}")),
+        ]);
+        let dispense_parameters = HashMap::from([
+            ("prescriptionId".to_string(), serde_json::json!("${prescription_id}")),
+        ]);
 
-        // Invalid workflow - no start node
-        let invalid = WorkflowDefinition {
-            id: "test".to_string(),
-            name: "Test".to_string(),
-            description: None,
+        WorkflowDefinition {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            description: Some("Verify drug availability, then dispense or back-order".to_string()),
             version: 1,
-            category: None,
+            category: Some("pharmacy".to_string()),
             nodes: vec![
                 WorkflowNode {
-                    id: "end".to_string(),
-                    node_type: NodeType::End,
-                    name: "End".to_string(),
+                    id: "start".to_string(),
+                    node_type: NodeType::Start,
+                    name: "Start".to_string(),
                     description: None,
-                    position: (0.0, 0.0),
+                    position: (100.0, 200.0),
                     config: NodeConfig::default(),
                     metadata: HashMap::new(),
                 },
-            ],
-            edges: vec![],
-            input_schema: None,
-            output_schema: None,
-            is_active: true,
-            organization_id: None,
-            tags: vec![],
-            created_at: Utc::now(),
-            updated_at: Utc::now(),
-            created_by: None,
-        };
-
-        let result = engine.register_workflow(invalid).await;
-        assert!(result.is_err());
+                WorkflowNode {
+                    id: "check_inventory".to_string(),
+                    node_type: NodeType::Action,
+                    name: "Check Inventory".to_string(),
+                    description: Some("Look up stock at the dispensing location".to_string()),
+                    position: (300.0, 200.0),
+                    config: NodeConfig {
+                        action: Some("pharmacy.checkInventory".to_string()),
+                        parameters: inventory_parameters,
+                        ..Default::default()
+                    },
+                    metadata: HashMap::new(),
+                },
+                WorkflowNode {
+                    id: "availability".to_string(),
+                    node_type: NodeType::Decision,
+                    name: "In Stock?".to_string(),
+                    description: None,
+                    position: (500.0, 200.0),
+                    config: NodeConfig {
+                        condition: Some("${available}".to_string()),
+                        ..Default::default()
+                    },
+                    metadata: HashMap::new(),
+                },
+                WorkflowNode {
+                    id: "dispense".to_string(),
+                    node_type: NodeType::Action,
+                    name: "Dispense Medication".to_string(),
+                    description: None,
+                    position: (700.0, 100.0),
+                    config: NodeConfig {
+                        action: Some("pharmacy.dispenseMedication".to_string()),
+                        parameters: dispense_parameters,
+                        ..Default::default()
+                    },
+                    metadata: HashMap::new(),
+                },
+                WorkflowNode {
+                    id: "back_order".to_string(),
+                    node_type: NodeType::HumanTask,
+                    name: "Back-order Medication".to_string(),
+                    description: Some("Order stock or arrange a transfer from another location".to_string()),
+                    position: (700.0, 300.0),
+                    config: NodeConfig {
+                        assignee: Some("pharmacist".to_string()),
+                        form_schema: Some(serde_json::json!({
+                            "type": "object",
+                            "properties": {
+                                "expected_date": { "type": "string", "format": "date", "title": "Expected Date" },
+                                "notes": { "type": "string", "title": "Notes" }
+                            }
+                        })),
+                        due_offset: Some("+1d".to_string()),
+                        ..Default::default()
+                    },
+                    metadata: HashMap::new(),
+                },
+                WorkflowNode {
+                    id: "dispensed_end".to_string(),
+                    node_type: NodeType::End,
+                    name: "Dispensed".to_string(),
+                    description: None,
+                    position: (900.0, 100.0),
+                    config: NodeConfig::default(),
+                    metadata: HashMap::new(),
+                },
+                WorkflowNode {
+                    id: "back_ordered_end".to_string(),
+                    node_type: NodeType::End,
+                    name: "Back-ordered".to_string(),
+                    description: None,
+                    position: (900.0, 300.0),
+                    config: NodeConfig::default(),
+                    metadata: HashMap::new(),
+                },
+            ],
+            edges: vec![
+                WorkflowEdge {
+                    id: "e1".to_string(),
+                    source: "start".to_string(),
+                    target: "check_inventory".to_string(),
+                    label: None,
+                    condition: None,
+                    priority: 0,
+                },
+                WorkflowEdge {
+                    id: "e2".to_string(),
+                    source: "check_inventory".to_string(),
+                    target: "availability".to_string(),
+                    label: None,
+                    condition: None,
+                    priority: 0,
+                },
+                WorkflowEdge {
+                    id: "e3".to_string(),
+                    source: "availability".to_string(),
+                    target: "dispense".to_string(),
+                    label: Some("Available".to_string()),
+                    condition: Some("available == true".to_string()),
+                    priority: 0,
+                },
+                WorkflowEdge {
+                    id: "e4".to_string(),
+                    source: "availability".to_string(),
+                    target: "back_order".to_string(),
+                    label: Some("Unavailable".to_string()),
+                    condition: Some("available == false".to_string()),
+                    priority: 1,
+                },
+                WorkflowEdge {
+                    id: "e5".to_string(),
+                    source: "dispense".to_string(),
+                    target: "dispensed_end".to_string(),
+                    label: None,
+                    condition: None,
+                    priority: 0,
+                },
+                WorkflowEdge {
+                    id: "e6".to_string(),
+                    source: "back_order".to_string(),
+                    target: "back_ordered_end".to_string(),
+                    label: None,
+                    condition: None,
+                    priority: 0,
+                },
+            ],
+            input_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "prescription_id": { "type": "string" },
+                    "drug_code": { "type": "string" },
+                    "location_code": { "type": "string" },
+                    "quantity": { "type": "integer" }
+                },
+                "required": ["prescription_id", "drug_code", "location_code", "quantity"]
+            })),
+            output_schema: Some(serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "available": { "type": "boolean" },
+                    "quantity_on_hand": { "type": "integer" },
+                    "is_low_stock": { "type": "boolean" }
+                }
+            })),
+            variables: vec![],
+            trigger: TriggerType::Manual,
+            is_active: true,
+            organization_id: None,
+            tags: vec!["template".to_string(), "pharmacy".to_string()],
+            created_at: now,
+            updated_at: now,
+            created_by: None,
+        }
+    }
+}
+
+impl Default for WorkflowEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rolls back a failed workflow by compensating its completed steps
+pub struct SagaCoordinator;
+
+impl SagaCoordinator {
+    /// Call each step's compensation action through `registry`, most recent
+    /// step first, passing it the step's output context
+    ///
+    /// Compensations not named `connector.action` have nothing to call and
+    /// are skipped. A failed compensation does not stop the others; the
+    /// failures are reported together.
+    pub async fn rollback(completed_steps: Vec<ExecutionStep>, registry: &ConnectorRegistry) -> AppResult<()> {
+        let mut failures = Vec::new();
+        for step in completed_steps.iter().rev() {
+            let Some(compensation) = step.compensation_action.as_deref() else {
+                continue;
+            };
+            let Some((connector, action)) = compensation.split_once('.') else {
+                continue;
+            };
+            let params = step.output_context.clone().unwrap_or(Value::Null);
+            if let Err(e) = registry.execute(connector, action, params).await {
+                tracing::error!(step = %step.node_id, compensation = %compensation, error = %e, "Compensation failed");
+                failures.push(format!("{} ({}): {}", step.node_name, compensation, e));
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Internal(format!("Compensation failed for {}", failures.join(", "))))
+        }
+    }
+}
+
+/// Fill in declared defaults, then validate instance variables against the variable schemas
+///
+/// Defaults are inserted into `variables` even when validation fails.
+pub fn validate_variables(
+    variables: &mut HashMap<String, Value>,
+    schema: &[WorkflowVariableSchema],
+) -> Result<(), Vec<SchemaError>> {
+    let mut ctx = WorkflowContext::new(Value::Null);
+    ctx.extra = std::mem::take(variables);
+    ctx.apply_defaults(schema);
+    let result = ctx.validate_against_schema(schema);
+    *variables = ctx.extra;
+    result
+}
+
+/// Replace `${var}` placeholders in string values with workflow variables
+///
+/// A string that is a single placeholder becomes the variable itself, so
+/// numbers and booleans keep their type; placeholders inside longer strings
+/// are replaced with the variable's text. Missing variables become null (or
+/// nothing, inside a string).
+fn substitute_variables(value: &Value, variables: &HashMap<String, Value>) -> Value {
+    match value {
+        Value::String(text) => {
+            if let Some(name) = placeholder(text) {
+                return variables.get(name).cloned().unwrap_or(Value::Null);
+            }
+            let mut substituted = String::new();
+            let mut rest = text.as_str();
+            while let Some(start) = rest.find("${") {
+                let Some(end) = rest[start..].find('}').map(|len| start + len) else {
+                    break;
+                };
+                substituted.push_str(&rest[..start]);
+                match variables.get(&rest[start + 2..end]) {
+                    Some(Value::String(variable)) => substituted.push_str(variable),
+                    Some(variable) => substituted.push_str(&variable.to_string()),
+                    None => {}
+                }
+                rest = &rest[end + 1..];
+            }
+            substituted.push_str(rest);
+            Value::String(substituted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|v| substitute_variables(v, variables)).collect()),
+        Value::Object(fields) => Value::Object(
+            fields.iter()
+                .map(|(key, v)| (key.clone(), substitute_variables(v, variables)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Variable name of a string that is exactly one `${var}` placeholder
+fn placeholder(text: &str) -> Option<&str> {
+    text.strip_prefix("${")?
+        .strip_suffix('}')
+        .filter(|name| !name.contains(['{', '}']))
+}
+
+/// Evaluate an edge condition such as `available == false` or `${age} >= 18`
+///
+/// Operands are variables (`name` or `${name}`) or JSON literals; a condition
+/// without an operator tests the operand's truthiness. Ordering operators
+/// compare numbers or strings and are false for anything else.
+fn evaluate_condition(condition: &str, variables: &HashMap<String, Value>) -> bool {
+    let operand = |token: &str| {
+        let token = token.trim();
+        if let Some(name) = placeholder(token) {
+            return variables.get(name).cloned().unwrap_or(Value::Null);
+        }
+        serde_json::from_str(token)
+            .unwrap_or_else(|_| variables.get(token).cloned().unwrap_or(Value::Null))
+    };
+
+    let Some((op, lhs, rhs)) = ["==", "!=", ">=", "<=", ">", "<"]
+        .into_iter()
+        .find_map(|op| condition.split_once(op).map(|(lhs, rhs)| (op, operand(lhs), operand(rhs))))
+    else {
+        return match operand(condition) {
+            Value::Null => false,
+            Value::Bool(b) => b,
+            Value::Number(n) => n.as_f64() != Some(0.0),
+            Value::String(s) => !s.is_empty(),
+            Value::Array(items) => !items.is_empty(),
+            Value::Object(fields) => !fields.is_empty(),
+        };
+    };
+
+    let ordering = match (&lhs, &rhs) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().zip(b.as_f64()).and_then(|(a, b)| a.partial_cmp(&b)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    match op {
+        "==" => ordering.map_or(lhs == rhs, |o| o.is_eq()),
+        "!=" => ordering.map_or(lhs != rhs, |o| o.is_ne()),
+        ">=" => ordering.is_some_and(|o| o.is_ge()),
+        "<=" => ordering.is_some_and(|o| o.is_le()),
+        ">" => ordering.is_some_and(|o| o.is_gt()),
+        _ => ordering.is_some_and(|o| o.is_lt()),
+    }
+}
+
+fn schema_violation_message(errors: &[SchemaError]) -> String {
+    let details: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    format!("SchemaViolation: {}", details.join("; "))
+}
+
+/// Shared workflow engine instance
+pub type SharedWorkflowEngine = Arc<WorkflowEngine>;
+
+/// Create a shared workflow engine
+pub fn create_shared_workflow_engine() -> SharedWorkflowEngine {
+    Arc::new(WorkflowEngine::new())
+}
+
+/// Create a workflow engine with rules integration
+pub fn create_workflow_engine_with_rules(rules_engine: SharedRulesEngine) -> SharedWorkflowEngine {
+    Arc::new(WorkflowEngine::with_rules_engine(rules_engine))
+}
+
+/// How often the scheduler checks for due workflows
+pub const CRON_TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Consecutive failed runs of one workflow before a [`CriticalAlert`] is raised
+pub const CRON_FAILURE_ALERT_THRESHOLD: u32 = 3;
+
+/// Alerts buffered per subscriber before slow subscribers start lagging
+const CRITICAL_ALERT_CAPACITY: usize = 64;
+
+/// Wall-clock time source for the scheduler
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A scheduled workflow kept failing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CriticalAlert {
+    pub workflow_id: String,
+    pub consecutive_failure_count: u32,
+    pub last_error: String,
+    pub raised_at: DateTime<Utc>,
+}
+
+/// Scheduling state of one cron-triggered workflow
+struct CronJob {
+    expression: String,
+    schedule: cron::Schedule,
+    next_run: Option<DateTime<Utc>>,
+    consecutive_failure_count: u32,
+}
+
+/// Parse a standard 5-field cron expression
+///
+/// The `cron` crate expects a leading seconds field, so runs are pinned to
+/// second 0 of the matching minutes.
+pub fn parse_cron_expression(expression: &str) -> AppResult<cron::Schedule> {
+    let fields = expression.split_whitespace().count();
+    if fields != 5 {
+        return Err(AppError::Validation(format!(
+            "Cron expression '{}' must have 5 fields, found {}",
+            expression, fields
+        )));
+    }
+    format!("0 {}", expression.trim())
+        .parse()
+        .map_err(|e| AppError::Validation(format!("Invalid cron expression '{}': {}", expression, e)))
+}
+
+/// Background job run by [`CronScheduler::schedule`]
+type SystemJob = Arc<dyn Fn() -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Scheduling state of one system job
+struct ScheduledJob {
+    name: String,
+    schedule: cron::Schedule,
+    next_run: Option<DateTime<Utc>>,
+    job: SystemJob,
+    /// Set while a run is in progress, so a slow run is not started twice
+    running: Arc<std::sync::atomic::AtomicBool>,
+}
+
+/// Starts workflows with a [`TriggerType::Cron`] trigger when they are due
+///
+/// Definitions are re-read on every tick, so workflows registered after the
+/// scheduler starts are picked up. A run counts as failed if the instance
+/// cannot be started or ends up `Failed` or `Compensated`. Background jobs
+/// added with [`CronScheduler::schedule`] run on the same ticks.
+pub struct CronScheduler {
+    engine: SharedWorkflowEngine,
+    clock: Arc<dyn Clock>,
+    jobs: tokio::sync::Mutex<HashMap<String, CronJob>>,
+    system_jobs: tokio::sync::Mutex<Vec<ScheduledJob>>,
+    alerts: tokio::sync::broadcast::Sender<CriticalAlert>,
+}
+
+impl CronScheduler {
+    /// Create a scheduler over the engine's workflow definitions
+    pub fn new(engine: SharedWorkflowEngine) -> Self {
+        let (alerts, _) = tokio::sync::broadcast::channel(CRITICAL_ALERT_CAPACITY);
+        Self {
+            engine,
+            clock: Arc::new(SystemClock),
+            jobs: tokio::sync::Mutex::new(HashMap::new()),
+            system_jobs: tokio::sync::Mutex::new(Vec::new()),
+            alerts,
+        }
+    }
+
+    /// Use a different time source
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Create a scheduler on the system clock and start its loop
+    pub fn start(engine: SharedWorkflowEngine) -> Arc<Self> {
+        let scheduler = Arc::new(Self::new(engine));
+        Arc::clone(&scheduler).spawn();
+        scheduler
+    }
+
+    /// Run the scheduling loop, ticking every [`CRON_TICK_INTERVAL`]
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CRON_TICK_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                self.tick().await;
+            }
+        })
+    }
+
+    /// Receive every alert raised after this call
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<CriticalAlert> {
+        self.alerts.subscribe()
+    }
+
+    /// Run `job` at every time the 5-field cron `expression` matches
+    ///
+    /// Each run gets its own task, so a slow job holds up neither the
+    /// workflows nor other jobs; a run still in progress when the job comes
+    /// due again is skipped. `name` identifies the job in logs.
+    pub async fn schedule<F, Fut>(&self, name: impl Into<String>, expression: &str, job: F) -> AppResult<()>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let schedule = parse_cron_expression(expression)?;
+        let next_run = schedule.after(&self.clock.now()).next();
+        self.system_jobs.lock().await.push(ScheduledJob {
+            name: name.into(),
+            schedule,
+            next_run,
+            job: Arc::new(move || Box::pin(job())),
+            running: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        });
+        Ok(())
+    }
+
+    /// Failed runs of a workflow since its last successful run
+    pub async fn consecutive_failure_count(&self, workflow_id: &str) -> u32 {
+        self.jobs.lock().await
+            .get(workflow_id)
+            .map(|job| job.consecutive_failure_count)
+            .unwrap_or(0)
+    }
+
+    /// Start every workflow that is due, returning each run's workflow ID and
+    /// scheduled time
+    ///
+    /// A workflow whose runs were missed (e.g. while the host was suspended)
+    /// runs once, then resumes its schedule from now.
+    pub async fn tick(&self) -> Vec<(String, DateTime<Utc>)> {
+        let now = self.clock.now();
+        self.sync_jobs(now).await;
+
+        let due: Vec<(String, DateTime<Utc>)> = {
+            let mut jobs = self.jobs.lock().await;
+            jobs.iter_mut()
+                .filter_map(|(workflow_id, job)| {
+                    let scheduled = job.next_run.filter(|next| *next <= now)?;
+                    job.next_run = job.schedule.after(&now).next();
+                    Some((workflow_id.clone(), scheduled))
+                })
+                .collect()
+        };
+
+        self.start_system_jobs(now).await;
+        for (workflow_id, scheduled) in &due {
+            let result = self.run(workflow_id, *scheduled).await;
+            self.record(workflow_id, result).await;
+        }
+        due
+    }
+
+    /// Start every system job that is due on its own task
+    async fn start_system_jobs(&self, now: DateTime<Utc>) {
+        use std::sync::atomic::Ordering;
+
+        let mut jobs = self.system_jobs.lock().await;
+        for job in jobs.iter_mut() {
+            if job.next_run.is_none_or(|next| next > now) {
+                continue;
+            }
+            job.next_run = job.schedule.after(&now).next();
+            if job.running.swap(true, Ordering::SeqCst) {
+                tracing::warn!(job = %job.name, "Skipping scheduled job; its previous run is still in progress");
+                continue;
+            }
+
+            let (run, running) = ((job.job)(), Arc::clone(&job.running));
+            let name = job.name.clone();
+            tokio::spawn(async move {
+                if let Err(e) = tokio::spawn(run).await {
+                    tracing::error!(job = %name, error = %e, "Scheduled job panicked");
+                }
+                running.store(false, Ordering::SeqCst);
+            });
+        }
+    }
+
+    /// Track the engine's active cron-triggered workflows
+    async fn sync_jobs(&self, now: DateTime<Utc>) {
+        let expressions: HashMap<String, String> = self.engine.list_workflows().await
+            .into_iter()
+            .filter(|w| w.is_active)
+            .filter_map(|w| match w.trigger {
+                TriggerType::Cron { expression } => Some((w.id, expression)),
+                TriggerType::Manual => None,
+            })
+            .collect();
+
+        let mut jobs = self.jobs.lock().await;
+        jobs.retain(|workflow_id, _| expressions.contains_key(workflow_id));
+        for (workflow_id, expression) in expressions {
+            if jobs.get(&workflow_id).is_some_and(|job| job.expression == expression) {
+                continue;
+            }
+            match parse_cron_expression(&expression) {
+                Ok(schedule) => {
+                    let next_run = schedule.after(&now).next();
+                    jobs.insert(workflow_id, CronJob { expression, schedule, next_run, consecutive_failure_count: 0 });
+                }
+                Err(e) => {
+                    jobs.remove(&workflow_id);
+                    tracing::error!(workflow_id = %workflow_id, error = %e, "Skipping scheduled workflow");
+                }
+            }
+        }
+    }
+
+    /// Start one scheduled run
+    async fn run(&self, workflow_id: &str, scheduled: DateTime<Utc>) -> Result<(), String> {
+        let correlation_id = format!("cron:{}:{}", workflow_id, scheduled.to_rfc3339());
+        let instance = self.engine
+            .start_workflow(workflow_id, HashMap::new(), Some(correlation_id))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match self.engine.get_instance(&instance.id).await {
+            Some(run) if matches!(run.status, WorkflowStatus::Failed | WorkflowStatus::Compensated) => {
+                Err(run.error.unwrap_or_else(|| "Instance failed".to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Update the failure streak after a run, alerting once it reaches the threshold
+    async fn record(&self, workflow_id: &str, result: Result<(), String>) {
+        let mut jobs = self.jobs.lock().await;
+        let Some(job) = jobs.get_mut(workflow_id) else {
+            return;
+        };
+
+        let error = match result {
+            Ok(()) => {
+                job.consecutive_failure_count = 0;
+                return;
+            }
+            Err(error) => error,
+        };
+        job.consecutive_failure_count += 1;
+        tracing::error!(
+            workflow_id = %workflow_id,
+            error = %error,
+            consecutive_failures = job.consecutive_failure_count,
+            "Scheduled workflow run failed"
+        );
+
+        if job.consecutive_failure_count == CRON_FAILURE_ALERT_THRESHOLD {
+            let _ = self.alerts.send(CriticalAlert {
+                workflow_id: workflow_id.to_string(),
+                consecutive_failure_count: job.consecutive_failure_count,
+                last_error: error,
+                raised_at: self.clock.now(),
+            });
+        }
+    }
+}
+
+/// Every 5 minutes
+pub const DEFAULT_TASK_SLA_SCHEDULE: &str = "*/5 * * * *";
+
+/// Escalations without acceptance before a task's [`FinalAction`] applies
+pub const MAX_TASK_ESCALATIONS: u32 = 3;
+
+/// Group notified about tasks nobody accepted
+pub const DEFAULT_SUPERVISOR_GROUP: &str = "supervisors";
+
+/// What [`TaskSlaEnforcer`] did to one task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TaskSlaEvent {
+    /// Reassigned after its SLA lapsed
+    TaskEscalated {
+        task_id: String,
+        from: String,
+        to: String,
+        escalation_count: u32,
+    },
+    /// Nobody along the escalation chain accepted it
+    TaskOverdue {
+        task_id: String,
+        assignee: String,
+        final_action: FinalAction,
+    },
+}
+
+/// Alert for whoever has to act on a lapsed SLA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSlaAlert {
+    /// User ID or group name to notify
+    pub recipient: String,
+    pub alert: ClinicalAlert,
+}
+
+/// Escalates pending human tasks whose SLA has lapsed
+///
+/// A task is checked once its assignee has had `sla_minutes` to accept
+/// (claim) it. It is reassigned to `escalate_to` and the new assignee is
+/// alerted; after [`MAX_TASK_ESCALATIONS`], or once its escalation chain
+/// runs out, its [`FinalAction`] applies and the supervisor group is
+/// alerted instead.
+pub struct TaskSlaEnforcer {
+    engine: SharedWorkflowEngine,
+    clock: Arc<dyn Clock>,
+    supervisor_group: String,
+    /// 5-field cron expression, see [`parse_cron_expression`]
+    schedule: String,
+    alerts: tokio::sync::broadcast::Sender<TaskSlaAlert>,
+}
+
+impl TaskSlaEnforcer {
+    /// Enforce SLAs of the engine's tasks on [`DEFAULT_TASK_SLA_SCHEDULE`]
+    pub fn new(engine: SharedWorkflowEngine) -> Self {
+        let (alerts, _) = tokio::sync::broadcast::channel(CRITICAL_ALERT_CAPACITY);
+        Self {
+            engine,
+            clock: Arc::new(SystemClock),
+            supervisor_group: DEFAULT_SUPERVISOR_GROUP.to_string(),
+            schedule: DEFAULT_TASK_SLA_SCHEDULE.to_string(),
+            alerts,
+        }
+    }
+
+    /// Use a different time source
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Notify a different group about tasks nobody accepted
+    pub fn with_supervisor_group(mut self, group: impl Into<String>) -> Self {
+        self.supervisor_group = group.into();
+        self
+    }
+
+    /// Run on a different cron schedule
+    pub fn with_schedule(mut self, expression: impl Into<String>) -> AppResult<Self> {
+        let expression = expression.into();
+        parse_cron_expression(&expression)?;
+        self.schedule = expression;
+        Ok(self)
+    }
+
+    /// Receive every alert raised after this call
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<TaskSlaAlert> {
+        self.alerts.subscribe()
+    }
+
+    /// Enforce SLAs at every scheduled time of `scheduler`
+    pub async fn schedule(self: Arc<Self>, scheduler: &CronScheduler) -> AppResult<()> {
+        let expression = self.schedule.clone();
+        scheduler
+            .schedule("task_sla", &expression, move || {
+                let enforcer = Arc::clone(&self);
+                async move {
+                    let events = enforcer.run().await;
+                    if !events.is_empty() {
+                        tracing::info!("Enforced SLAs of {} human tasks", events.len());
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Escalate every pending task past its SLA, returning what was done
+    pub async fn run(&self) -> Vec<TaskSlaEvent> {
+        let now = self.clock.now();
+        let mut events = Vec::new();
+        let mut finished = Vec::new();
+
+        let mut tasks = self.engine.tasks.write().await;
+        for task in tasks.values_mut().filter(|task| task.status == TaskStatus::Pending) {
+            let Some(sla_minutes) = task.sla_minutes else {
+                continue;
+            };
+            if task.assigned_at + chrono::Duration::minutes(sla_minutes.into()) >= now {
+                continue;
+            }
+
+            let target = task.escalate_to.clone().filter(|_| task.escalation_count < MAX_TASK_ESCALATIONS);
+            let (event, alert) = match target {
+                Some(target) => Self::escalate(task, target, sla_minutes, now),
+                None => {
+                    let final_action = task.escalation.as_ref().map(|e| e.final_action).unwrap_or_default();
+                    finished.push((task.instance_id.clone(), final_action));
+                    self.give_up(task, final_action, now)
+                }
+            };
+            tracing::warn!(task_id = %task.id, event = ?event, "Human task SLA lapsed");
+            events.push(event);
+            let _ = self.alerts.send(alert);
+        }
+        drop(tasks);
+
+        for (instance_id, final_action) in finished {
+            let result = match final_action {
+                FinalAction::AutoComplete => self.engine.resume_instance(&instance_id).await,
+                FinalAction::AutoCancel => {
+                    self.engine.cancel(&instance_id, "Human task SLA exhausted".to_string()).await
+                }
+                FinalAction::HardAlert => Ok(()),
+            };
+            if let Err(e) = result {
+                e.log_with_operation(concat!(file!(), ":", line!()), "enforce_task_sla");
+            }
+        }
+        events
+    }
+
+    /// Reassign a task to its next escalation target
+    fn escalate(task: &mut HumanTask, target: String, sla_minutes: u32, now: DateTime<Utc>) -> (TaskSlaEvent, TaskSlaAlert) {
+        let from = std::mem::replace(&mut task.assignee, target.clone());
+        task.claimed_by = None;
+        task.assigned_at = now;
+        task.escalation_count += 1;
+
+        let next_level = task.escalation.as_ref()
+            .and_then(|e| e.levels.get(task.escalation_count as usize));
+        task.sla_minutes = Some(next_level.map_or(sla_minutes, |level| level.after_minutes));
+        task.escalate_to = next_level.map(|level| level.escalate_to.clone());
+
+        let alert = ClinicalAlert {
+            alert_type: "task_escalated".to_string(),
+            severity: "high".to_string(),
+            message: format!(
+                "Task '{}' was not accepted by {} within {} minutes",
+                task.name, from, sla_minutes
+            ),
+            recommendation: Some(format!("Claim task {}", task.id)),
+            related_items: vec![task.id.clone(), task.instance_id.clone()],
+        };
+        let event = TaskSlaEvent::TaskEscalated {
+            task_id: task.id.clone(),
+            from,
+            to: target.clone(),
+            escalation_count: task.escalation_count,
+        };
+        (event, TaskSlaAlert { recipient: target, alert })
+    }
+
+    /// Apply a task's final action and alert the supervisors
+    ///
+    /// Workflow instances are resumed or cancelled by the caller, once the
+    /// task lock is released.
+    fn give_up(&self, task: &mut HumanTask, final_action: FinalAction, now: DateTime<Utc>) -> (TaskSlaEvent, TaskSlaAlert) {
+        let recommendation = match final_action {
+            FinalAction::AutoComplete => {
+                task.status = TaskStatus::Completed;
+                task.completed_at = Some(now);
+                task.result = Some(serde_json::json!({ "auto_completed": true }));
+                format!("Review task {}, which was completed automatically", task.id)
+            }
+            FinalAction::AutoCancel => {
+                format!("Review workflow instance {}, which was cancelled", task.instance_id)
+            }
+            FinalAction::HardAlert => {
+                task.status = TaskStatus::Overdue;
+                format!("Assign task {} to someone who can accept it", task.id)
+            }
+        };
+
+        let alert = ClinicalAlert {
+            alert_type: "task_overdue".to_string(),
+            severity: if final_action == FinalAction::HardAlert { "critical" } else { "high" }.to_string(),
+            message: format!(
+                "Task '{}' was not accepted after {} escalations",
+                task.name, task.escalation_count
+            ),
+            recommendation: Some(recommendation),
+            related_items: vec![task.id.clone(), task.instance_id.clone()],
+        };
+        let event = TaskSlaEvent::TaskOverdue {
+            task_id: task.id.clone(),
+            assignee: task.assignee.clone(),
+            final_action,
+        };
+        (event, TaskSlaAlert { recipient: self.supervisor_group.clone(), alert })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_workflow_engine_creation() {
+        let engine = WorkflowEngine::new();
+        let workflows = engine.list_workflows().await;
+        assert!(workflows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_approval_template() {
+        let template = WorkflowEngine::create_approval_workflow_template(
+            "Test Approval",
+            "manager"
+        );
+
+        assert_eq!(template.nodes.len(), 5);
+        assert_eq!(template.edges.len(), 4);
+
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(template).await.expect("Should register");
+    }
+
+    #[tokio::test]
+    async fn test_dispensing_template_routes_unavailable_to_back_order() {
+        let template = WorkflowEngine::create_prescription_dispensing_workflow_template("Dispense");
+
+        let back_order_edge = template.edges.iter()
+            .find(|e| e.source == "availability" && e.target == "back_order")
+            .expect("Decision should route to back-order");
+        assert_eq!(back_order_edge.condition.as_deref(), Some("available == false"));
+
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(template).await.expect("Should register");
+    }
+
+    #[tokio::test]
+    async fn test_workflow_validation() {
+        let engine = WorkflowEngine::new();
+
+        // Invalid workflow - no start node
+        let invalid = WorkflowDefinition {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: None,
+            version: 1,
+            category: None,
+            nodes: vec![
+                WorkflowNode {
+                    id: "end".to_string(),
+                    node_type: NodeType::End,
+                    name: "End".to_string(),
+                    description: None,
+                    position: (0.0, 0.0),
+                    config: NodeConfig::default(),
+                    metadata: HashMap::new(),
+                },
+            ],
+            edges: vec![],
+            input_schema: None,
+            output_schema: None,
+            variables: vec![],
+            trigger: TriggerType::Manual,
+            is_active: true,
+            organization_id: None,
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by: None,
+        };
+
+        let result = engine.register_workflow(invalid).await;
+        assert!(result.is_err());
+    }
+
+    fn node(id: &str, node_type: NodeType, config: NodeConfig) -> WorkflowNode {
+        WorkflowNode {
+            id: id.to_string(),
+            node_type,
+            name: id.to_string(),
+            description: None,
+            position: (0.0, 0.0),
+            config,
+            metadata: HashMap::new(),
+        }
+    }
+
+    fn action(id: &str, compensation: &str) -> WorkflowNode {
+        node(id, NodeType::Action, NodeConfig {
+            action: Some(id.to_string()),
+            compensation: Some(compensation.to_string()),
+            ..Default::default()
+        })
+    }
+
+    fn edge(source: &str, target: &str) -> WorkflowEdge {
+        WorkflowEdge {
+            id: format!("{}-{}", source, target),
+            source: source.to_string(),
+            target: target.to_string(),
+            label: None,
+            condition: None,
+            priority: 0,
+        }
+    }
+
+    /// Admission: reserve bed -> notify pharmacy -> nurse review -> end
+    fn admission_workflow() -> WorkflowDefinition {
+        WorkflowDefinition {
+            id: "admission".to_string(),
+            name: "Admission".to_string(),
+            description: None,
+            version: 1,
+            category: None,
+            nodes: vec![
+                node("start", NodeType::Start, NodeConfig::default()),
+                action("reserve_bed", "release_bed"),
+                action("notify_pharmacy", "cancel_pharmacy_order"),
+                node("nurse_review", NodeType::HumanTask, NodeConfig {
+                    assignee: Some("nurse".to_string()),
+                    ..Default::default()
+                }),
+                node("end", NodeType::End, NodeConfig::default()),
+            ],
+            edges: vec![
+                edge("start", "reserve_bed"),
+                edge("reserve_bed", "notify_pharmacy"),
+                edge("notify_pharmacy", "nurse_review"),
+                edge("nurse_review", "end"),
+            ],
+            input_schema: None,
+            output_schema: None,
+            variables: vec![],
+            trigger: TriggerType::Manual,
+            is_active: true,
+            organization_id: None,
+            tags: vec![],
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            created_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_node_input_schema_violation_fails_instance() {
+        let mut workflow = admission_workflow();
+        let nurse_review = workflow.nodes.iter_mut().find(|n| n.id == "nurse_review").unwrap();
+        nurse_review.config.input_schema = vec![WorkflowVariableSchema {
+            name: "bed_id".to_string(),
+            variable_type: crate::domain::state_machine::VariableType::String,
+            required: true,
+            default: None,
+        }];
+
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(workflow).await.expect("Should register");
+
+        let instance = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+        let failed = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(failed.status, WorkflowStatus::Failed);
+        assert!(failed.error.as_deref().unwrap().starts_with("SchemaViolation: bed_id"));
+        assert_eq!(failed.history.last().unwrap().node_id, "nurse_review");
+
+        let tasks = engine.tasks.read().await;
+        assert!(tasks.values().all(|t| t.instance_id != instance.id));
+    }
+
+    #[tokio::test]
+    async fn test_variable_defaults_are_applied_before_validation() {
+        let mut workflow = admission_workflow();
+        workflow.variables = vec![WorkflowVariableSchema {
+            name: "ward".to_string(),
+            variable_type: crate::domain::state_machine::VariableType::String,
+            required: true,
+            default: Some(serde_json::json!("general")),
+        }];
+        let nurse_review = workflow.nodes.iter_mut().find(|n| n.id == "nurse_review").unwrap();
+        nurse_review.config.input_schema = vec![WorkflowVariableSchema {
+            name: "bed_id".to_string(),
+            variable_type: crate::domain::state_machine::VariableType::String,
+            required: true,
+            default: Some(serde_json::json!("unassigned")),
+        }];
+
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(workflow).await.expect("Should register");
+
+        let instance = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+        let waiting = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(waiting.status, WorkflowStatus::Waiting);
+        assert_eq!(waiting.variables.get("ward"), Some(&serde_json::json!("general")));
+        assert_eq!(waiting.variables.get("bed_id"), Some(&serde_json::json!("unassigned")));
+
+        // A supplied value is kept and still type-checked
+        let mut variables = HashMap::from([("ward".to_string(), serde_json::json!(7))]);
+        let result = validate_variables(&mut variables, &engine.get_workflow("admission").await.unwrap().variables);
+        assert!(result.is_err());
+        assert_eq!(variables.get("ward"), Some(&serde_json::json!(7)));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_rejects_finished_instance() {
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(admission_workflow()).await.expect("Should register");
+
+        let instance = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+        engine.cancel(&instance.id, "first".to_string()).await.expect("Should cancel");
+
+        let result = engine.cancel(&instance.id, "second".to_string()).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_remove_instance_drops_its_tasks() {
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(admission_workflow()).await.expect("Should register");
+        let kept = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+        let removed = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+        engine.cancel(&removed.id, "Duplicate admission".to_string()).await.expect("Should cancel");
+
+        let cancelled = engine.remove_instance(&removed.id).await.expect("Should remove");
+        assert_eq!(cancelled.status, WorkflowStatus::Cancelled);
+        assert!(engine.get_instance(&removed.id).await.is_none());
+        assert!(engine.tasks.read().await.values().all(|task| task.instance_id == kept.id));
+        assert!(engine.get_instance(&kept.id).await.is_some());
+    }
+
+    /// Billing connector that records every call and fails `finalizeInvoice`
+    #[derive(Default)]
+    struct RecordingBillingConnector {
+        calls: std::sync::Mutex<Vec<(String, Value)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::application::services::connectors::Connector for RecordingBillingConnector {
+        fn name(&self) -> &str {
+            "billing"
+        }
+
+        async fn execute(&self, action: &str, params: Value) -> AppResult<Value> {
+            self.calls.lock().unwrap().push((action.to_string(), params));
+            match action {
+                "createInvoice" => Ok(serde_json::json!({"invoice_id": "INV-1"})),
+                "addItems" => Ok(serde_json::json!({"invoice_id": "INV-1", "item_ids": ["ITEM-1"]})),
+                "finalizeInvoice" => Err(AppError::Internal("Billing service unavailable".to_string())),
+                _ => Ok(serde_json::json!({})),
+            }
+        }
+
+        fn available_actions(&self) -> Vec<crate::application::services::connectors::ConnectorAction> {
+            vec![]
+        }
+
+        fn validate_params(&self, _action: &str, _params: &Value) -> AppResult<()> {
+            Ok(())
+        }
+    }
+
+    /// Billing: create invoice -> add items -> finalize -> end
+    fn billing_workflow() -> WorkflowDefinition {
+        let mut workflow = admission_workflow();
+        workflow.id = "billing".to_string();
+        workflow.nodes = vec![
+            node("start", NodeType::Start, NodeConfig::default()),
+            action("billing.createInvoice", "billing.cancelInvoice"),
+            action("billing.addItems", "billing.removeItems"),
+            node("billing.finalizeInvoice", NodeType::Action, NodeConfig {
+                action: Some("billing.finalizeInvoice".to_string()),
+                ..Default::default()
+            }),
+            node("end", NodeType::End, NodeConfig::default()),
+        ];
+        workflow.edges = vec![
+            edge("start", "billing.createInvoice"),
+            edge("billing.createInvoice", "billing.addItems"),
+            edge("billing.addItems", "billing.finalizeInvoice"),
+            edge("billing.finalizeInvoice", "end"),
+        ];
+        workflow
+    }
+
+    #[tokio::test]
+    async fn test_failed_action_compensates_completed_steps_in_reverse_order() {
+        let billing = Arc::new(RecordingBillingConnector::default());
+        let mut registry = ConnectorRegistry::new();
+        registry.register(billing.clone());
+        let engine = WorkflowEngine::new().with_connectors(Arc::new(registry));
+        engine.register_workflow(billing_workflow()).await.expect("Should register");
+
+        let instance = engine.start_workflow("billing", HashMap::new(), None).await.expect("Should start");
+
+        let compensated = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(compensated.status, WorkflowStatus::Compensated);
+        assert!(compensated.error.as_deref().unwrap().contains("Billing service unavailable"));
+        assert!(compensated.current_nodes.is_empty());
+
+        let calls = billing.calls.lock().unwrap();
+        let actions: Vec<_> = calls.iter().map(|(action, _)| action.as_str()).collect();
+        assert_eq!(
+            actions,
+            vec!["createInvoice", "addItems", "finalizeInvoice", "removeItems", "cancelInvoice"]
+        );
+        // Each compensation gets the output of the step it undoes
+        assert_eq!(calls[3].1["item_ids"], serde_json::json!(["ITEM-1"]));
+        assert_eq!(calls[4].1, serde_json::json!({"invoice_id": "INV-1"}));
+    }
+
+    /// Billing workflow that waits for a review after adding items
+    fn billing_review_workflow() -> WorkflowDefinition {
+        let mut workflow = billing_workflow();
+        workflow.nodes[3] = node("billing_review", NodeType::HumanTask, NodeConfig {
+            assignee: Some("billing_clerk".to_string()),
+            ..Default::default()
+        });
+        workflow.edges[2] = edge("billing.addItems", "billing_review");
+        workflow.edges[3] = edge("billing_review", "end");
+        workflow
+    }
+
+    #[tokio::test]
+    async fn test_cancel_compensates_completed_steps_in_reverse_order() {
+        let billing = Arc::new(RecordingBillingConnector::default());
+        let mut registry = ConnectorRegistry::new();
+        registry.register(billing.clone());
+        let engine = WorkflowEngine::new().with_connectors(Arc::new(registry));
+        engine.register_workflow(billing_review_workflow()).await.expect("Should register");
+
+        let instance = engine.start_workflow("billing", HashMap::new(), None).await.expect("Should start");
+        let waiting = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(waiting.status, WorkflowStatus::Waiting);
+
+        engine.cancel(&instance.id, "Patient left before discharge".to_string()).await.expect("Should cancel");
+
+        let cancelled = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(cancelled.status, WorkflowStatus::Cancelled);
+        assert_eq!(cancelled.cancellation_reason.as_deref(), Some("Patient left before discharge"));
+
+        {
+            let calls = billing.calls.lock().unwrap();
+            let actions: Vec<_> = calls.iter().map(|(action, _)| action.as_str()).collect();
+            assert_eq!(actions, vec!["createInvoice", "addItems", "removeItems", "cancelInvoice"]);
+            assert_eq!(calls[3].1, serde_json::json!({"invoice_id": "INV-1"}));
+        }
+
+        let tasks = engine.tasks.read().await;
+        let task = tasks.values().find(|t| t.instance_id == instance.id).unwrap();
+        assert_eq!(task.status, TaskStatus::Cancelled);
+
+        let mut task = task.clone();
+        assert!(task.escalate("charge_nurse").is_err());
+    }
+
+    /// Pharmacy connector with a fixed stock level at every location
+    struct StockPharmacyConnector {
+        quantity_on_hand: i64,
+        calls: std::sync::Mutex<Vec<(String, Value)>>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::application::services::connectors::Connector for StockPharmacyConnector {
+        fn name(&self) -> &str {
+            "pharmacy"
+        }
+
+        async fn execute(&self, action: &str, params: Value) -> AppResult<Value> {
+            self.calls.lock().unwrap().push((action.to_string(), params.clone()));
+            match action {
+                "checkInventory" => Ok(serde_json::json!({
+                    "available": params["required_quantity"].as_i64().is_some_and(|q| self.quantity_on_hand >= q),
+                    "quantity_on_hand": self.quantity_on_hand,
+                    "location": params["location_code"],
+                    "is_low_stock": false,
+                })),
+                _ => Ok(serde_json::json!({"dispensed": true})),
+            }
+        }
+
+        fn available_actions(&self) -> Vec<crate::application::services::connectors::ConnectorAction> {
+            vec![]
+        }
+
+        fn validate_params(&self, _action: &str, _params: &Value) -> AppResult<()> {
+            Ok(())
+        }
+    }
+
+    async fn dispense(quantity_on_hand: i64) -> (WorkflowEngine, WorkflowInstance, Vec<(String, Value)>) {
+        let pharmacy = Arc::new(StockPharmacyConnector { quantity_on_hand, calls: Default::default() });
+        let mut registry = ConnectorRegistry::new();
+        registry.register(pharmacy.clone());
+        let engine = WorkflowEngine::new().with_connectors(Arc::new(registry));
+        let mut template = WorkflowEngine::create_prescription_dispensing_workflow_template("Dispense");
+        template.id = "dispense".to_string();
+        engine.register_workflow(template).await.expect("Should register");
+
+        let variables = HashMap::from([
+            ("prescription_id".to_string(), serde_json::json!("RX-1")),
+            ("drug_code".to_string(), serde_json::json!("0002-3227-30")),
+            ("location_code".to_string(), serde_json::json!("MAIN")),
+            ("quantity".to_string(), serde_json::json!(30)),
+        ]);
+        let instance = engine.start_workflow("dispense", variables, None).await.expect("Should start");
+        let instance = engine.get_instance(&instance.id).await.unwrap();
+        let calls = pharmacy.calls.lock().unwrap().clone();
+        (engine, instance, calls)
+    }
+
+    #[tokio::test]
+    async fn test_dispensing_substitutes_variables_and_dispenses_in_stock_drug() {
+        let (_, instance, calls) = dispense(100).await;
+
+        assert_eq!(instance.status, WorkflowStatus::Completed);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0, "checkInventory");
+        // A whole-string placeholder keeps the variable's type
+        assert_eq!(
+            calls[0].1,
+            serde_json::json!({"drug_code": "0002-3227-30", "location_code": "MAIN", "required_quantity": 30})
+        );
+        assert_eq!(calls[1], ("dispenseMedication".to_string(), serde_json::json!({"prescriptionId": "RX-1"})));
+
+        // Connector output is merged into the instance variables
+        assert_eq!(instance.variables["available"], serde_json::json!(true));
+        assert_eq!(instance.variables["quantity_on_hand"], serde_json::json!(100));
+        let decision = instance.history.iter().find(|s| s.node_id == "availability").unwrap();
+        assert_eq!(decision.decision.as_deref(), Some("Available"));
+    }
+
+    #[tokio::test]
+    async fn test_dispensing_routes_out_of_stock_drug_to_back_order() {
+        let (engine, instance, calls) = dispense(10).await;
+
+        assert_eq!(instance.status, WorkflowStatus::Waiting);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(instance.variables["available"], serde_json::json!(false));
+        let decision = instance.history.iter().find(|s| s.node_id == "availability").unwrap();
+        assert_eq!(decision.decision.as_deref(), Some("Unavailable"));
+
+        let tasks = engine.tasks.read().await;
+        let task = tasks.values().find(|t| t.instance_id == instance.id).unwrap();
+        assert_eq!(task.node_id, "back_order");
+    }
+
+    #[test]
+    fn test_evaluate_condition_and_substitution() {
+        let variables = HashMap::from([
+            ("age".to_string(), serde_json::json!(42)),
+            ("ward".to_string(), serde_json::json!("ICU")),
+            ("approved".to_string(), serde_json::json!(true)),
+        ]);
+
+        assert!(evaluate_condition("approved == true", &variables));
+        assert!(evaluate_condition("${approved}", &variables));
+        assert!(evaluate_condition("${age} >= 18", &variables));
+        assert!(evaluate_condition("age == 42.0", &variables));
+        assert!(evaluate_condition("ward != \"ER\"", &variables));
+        assert!(!evaluate_condition("age < 18", &variables));
+        assert!(!evaluate_condition("missing", &variables));
+        assert!(!evaluate_condition("ward > 5", &variables));
+
+        assert_eq!(
+            substitute_variables(&serde_json::json!({"note": "${ward} bed, age ${age}${missing}", "age": "${age}"}), &variables),
+            serde_json::json!({"note": "ICU bed, age 42", "age": 42})
+        );
+    }
+
+    fn setter(id: &str, outputs: Value) -> WorkflowNode {
+        let outputs = serde_json::from_value(outputs).unwrap_or_default();
+        node(id, NodeType::Action, NodeConfig { outputs, ..Default::default() })
+    }
+
+    /// Pre-procedure: start -> fork -> (vitals | consent) -> join -> end
+    fn pre_procedure_workflow(vitals: WorkflowNode, consent: WorkflowNode, mode: JoinMode) -> WorkflowDefinition {
+        let mut workflow = admission_workflow();
+        workflow.id = "pre_procedure".to_string();
+        workflow.nodes = vec![
+            node("start", NodeType::Start, NodeConfig::default()),
+            node("fork", NodeType::Fork, NodeConfig::default()),
+            vitals,
+            consent,
+            node("join", NodeType::Join, NodeConfig { join: JoinConfig { mode }, ..Default::default() }),
+            node("end", NodeType::End, NodeConfig::default()),
+        ];
+        workflow.edges = vec![
+            edge("start", "fork"),
+            edge("fork", "collect_vitals"),
+            edge("fork", "obtain_consent"),
+            edge("collect_vitals", "join"),
+            edge("obtain_consent", "join"),
+            edge("join", "end"),
+        ];
+        workflow
+    }
+
+    #[tokio::test]
+    async fn test_fork_completes_all_branches_before_join() {
+        let workflow = pre_procedure_workflow(
+            setter("collect_vitals", serde_json::json!({"vitals_recorded": true})),
+            setter("obtain_consent", serde_json::json!({"consent_signed": true})),
+            JoinMode::All,
+        );
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(workflow).await.expect("Should register");
+
+        let instance = engine.start_workflow("pre_procedure", HashMap::new(), None).await.expect("Should start");
+        let done = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(done.status, WorkflowStatus::Completed);
+
+        let order: Vec<&str> = done.history.iter().map(|s| s.node_id.as_str()).collect();
+        assert_eq!(order, vec!["start", "fork", "collect_vitals", "obtain_consent", "join", "end"]);
+        let join = done.history.iter().find(|s| s.node_id == "join").unwrap();
+        assert_eq!(join.output.as_ref().unwrap()["completed"], serde_json::json!(["collect_vitals", "obtain_consent"]));
+
+        assert_eq!(done.variables.get("vitals_recorded"), Some(&Value::Bool(true)));
+        assert_eq!(done.variables.get("consent_signed"), Some(&Value::Bool(true)));
+    }
+
+    #[tokio::test]
+    async fn test_branch_failure_fails_and_join() {
+        let mut consent = setter("obtain_consent", serde_json::json!({"consent_signed": true}));
+        consent.config.input_schema = vec![WorkflowVariableSchema {
+            name: "guardian_id".to_string(),
+            variable_type: crate::domain::state_machine::VariableType::String,
+            required: true,
+            default: None,
+        }];
+        let workflow = pre_procedure_workflow(
+            setter("collect_vitals", serde_json::json!({"vitals_recorded": true})),
+            consent,
+            JoinMode::All,
+        );
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(workflow).await.expect("Should register");
+
+        let instance = engine.start_workflow("pre_procedure", HashMap::new(), None).await.expect("Should start");
+        let failed = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(failed.status, WorkflowStatus::Failed);
+        assert!(failed.error.as_deref().unwrap().starts_with("Branch 'obtain_consent' failed: SchemaViolation"));
+        assert_eq!(failed.history.last().unwrap().node_id, "join");
+        assert!(failed.history.iter().all(|s| s.node_id != "end"));
+        assert!(failed.variables.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failing_connector_in_branch_fails_and_join() {
+        let billing = Arc::new(RecordingBillingConnector::default());
+        let mut registry = ConnectorRegistry::new();
+        registry.register(billing.clone());
+        let engine = WorkflowEngine::new().with_connectors(Arc::new(registry));
+
+        let invoice = node("collect_vitals", NodeType::Action, NodeConfig {
+            action: Some("billing.createInvoice".to_string()),
+            parameters: serde_json::from_value(serde_json::json!({"patient_id": "${patient_id}"})).unwrap(),
+            ..Default::default()
+        });
+        let finalize = node("obtain_consent", NodeType::Action, NodeConfig {
+            action: Some("billing.finalizeInvoice".to_string()),
+            ..Default::default()
+        });
+        engine.register_workflow(pre_procedure_workflow(invoice, finalize, JoinMode::All)).await.expect("Should register");
+
+        let variables = HashMap::from([("patient_id".to_string(), serde_json::json!("P-7"))]);
+        let instance = engine.start_workflow("pre_procedure", variables, None).await.expect("Should start");
+        let failed = engine.get_instance(&instance.id).await.unwrap();
+
+        assert_eq!(failed.status, WorkflowStatus::Failed);
+        let error = failed.error.as_deref().unwrap();
+        assert!(error.starts_with("Branch 'obtain_consent' failed: "));
+        assert!(error.contains("Billing service unavailable"));
+        assert_eq!(failed.history.last().unwrap().node_id, "join");
+        assert!(failed.history.iter().all(|s| s.node_id != "end"));
+
+        let calls = billing.calls.lock().unwrap().clone();
+        assert!(calls.contains(&("createInvoice".to_string(), serde_json::json!({"patient_id": "P-7"}))));
+        assert!(calls.iter().any(|(action, _)| action == "finalizeInvoice"));
+    }
+
+    #[tokio::test]
+    async fn test_decision_inside_branch_takes_matching_edge() {
+        let mut workflow = pre_procedure_workflow(
+            node("collect_vitals", NodeType::Decision, NodeConfig::default()),
+            setter("obtain_consent", serde_json::json!({"consent_signed": true})),
+            JoinMode::All,
+        );
+        workflow.nodes.push(setter("flag_fever", serde_json::json!({"fever": true})));
+        workflow.edges.retain(|e| e.source != "collect_vitals");
+        workflow.edges.push(WorkflowEdge {
+            condition: Some("temperature > 38".to_string()),
+            label: Some("fever".to_string()),
+            ..edge("collect_vitals", "flag_fever")
+        });
+        workflow.edges.push(WorkflowEdge { priority: 1, ..edge("collect_vitals", "join") });
+        workflow.edges.push(edge("flag_fever", "join"));
+
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(workflow).await.expect("Should register");
+
+        let variables = HashMap::from([("temperature".to_string(), serde_json::json!(39.2))]);
+        let instance = engine.start_workflow("pre_procedure", variables, None).await.expect("Should start");
+        let done = engine.get_instance(&instance.id).await.unwrap();
+
+        assert_eq!(done.status, WorkflowStatus::Completed);
+        assert_eq!(done.variables.get("fever"), Some(&Value::Bool(true)));
+        let decision = done.history.iter().find(|s| s.node_id == "collect_vitals").unwrap();
+        assert_eq!(decision.decision.as_deref(), Some("fever"));
+    }
+
+    #[tokio::test]
+    async fn test_or_join_proceeds_when_one_branch_completes() {
+        let mut consent = setter("obtain_consent", serde_json::json!({"consent_signed": true}));
+        consent.node_type = NodeType::HumanTask;
+        let workflow = pre_procedure_workflow(
+            setter("collect_vitals", serde_json::json!({"vitals_recorded": true})),
+            consent,
+            JoinMode::Any,
+        );
+        let engine = WorkflowEngine::new();
+        engine.register_workflow(workflow).await.expect("Should register");
+
+        let instance = engine.start_workflow("pre_procedure", HashMap::new(), None).await.expect("Should start");
+        let done = engine.get_instance(&instance.id).await.unwrap();
+        assert_eq!(done.status, WorkflowStatus::Completed);
+        assert_eq!(done.variables.get("vitals_recorded"), Some(&Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_branch_variables_merge_in_branch_order() {
+        let branch = |name: &str, variables: Value| BranchResult {
+            branch: name.to_string(),
+            history: vec![],
+            variables: serde_json::from_value(variables).unwrap(),
+            error: None,
+        };
+        let outcome = ForkOutcome {
+            join_id: "join".to_string(),
+            mode: JoinMode::All,
+            branches: vec![
+                branch("vitals", serde_json::json!({"patient": "p1", "bp": "120/80", "note": "vitals"})),
+                branch("consent", serde_json::json!({"patient": "p1", "consent": true, "note": "consent"})),
+            ],
+        };
+
+        let mut variables: HashMap<String, Value> =
+            serde_json::from_value(serde_json::json!({"patient": "p1"})).unwrap();
+        outcome.merge_into(&mut variables);
+
+        assert_eq!(
+            serde_json::to_value(&variables).unwrap(),
+            serde_json::json!({"patient": "p1", "bp": "120/80", "consent": true, "note": "consent"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fork_branches_must_share_a_join() {
+        let mut workflow = pre_procedure_workflow(
+            setter("collect_vitals", Value::Null),
+            setter("obtain_consent", Value::Null),
+            JoinMode::All,
+        );
+        workflow.edges.retain(|e| e.source != "obtain_consent");
+        workflow.edges.push(edge("obtain_consent", "end"));
+
+        let result = WorkflowEngine::new().register_workflow(workflow).await;
+        assert!(matches!(result, Err(AppError::Validation(_))));
+    }
+
+    /// Clock that only moves when told to
+    struct MockClock(std::sync::Mutex<DateTime<Utc>>);
+
+    impl MockClock {
+        fn at(time: &str) -> Arc<Self> {
+            Arc::new(Self(std::sync::Mutex::new(time.parse().unwrap())))
+        }
+
+        fn set(&self, time: &str) {
+            *self.0.lock().unwrap() = time.parse().unwrap();
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    /// start -> end, triggered every minute
+    fn every_minute_workflow() -> WorkflowDefinition {
+        let mut workflow = admission_workflow();
+        workflow.id = "low_stock_check".to_string();
+        workflow.nodes = vec![
+            node("start", NodeType::Start, NodeConfig::default()),
+            node("end", NodeType::End, NodeConfig::default()),
+        ];
+        workflow.edges = vec![edge("start", "end")];
+        workflow.trigger = TriggerType::Cron { expression: "*/1 * * * *".to_string() };
+        workflow
+    }
+
+    #[test]
+    fn test_cron_expression_requires_five_fields() {
+        assert!(parse_cron_expression("*/15 8-17 * * 1-5").is_ok());
+        assert!(parse_cron_expression("0 */1 * * * *").is_err());
+        assert!(parse_cron_expression("61 * * * *").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_cron_trigger_fires_on_the_minute() {
+        let engine = create_shared_workflow_engine();
+        engine.register_workflow(every_minute_workflow()).await.expect("Should register");
+        let clock = MockClock::at("2024-06-03T10:00:30Z");
+        let scheduler = CronScheduler::new(engine).with_clock(clock.clone());
+
+        assert!(scheduler.tick().await.is_empty());
+        clock.set("2024-06-03T10:00:59Z");
+        assert!(scheduler.tick().await.is_empty());
+
+        clock.set("2024-06-03T10:01:00Z");
+        let runs = scheduler.tick().await;
+        assert_eq!(runs, vec![("low_stock_check".to_string(), "2024-06-03T10:01:00Z".parse().unwrap())]);
+
+        clock.set("2024-06-03T10:01:30Z");
+        assert!(scheduler.tick().await.is_empty());
+
+        // Late tick: the run keeps its scheduled time
+        clock.set("2024-06-03T10:02:05Z");
+        let runs = scheduler.tick().await;
+        assert_eq!(runs, vec![("low_stock_check".to_string(), "2024-06-03T10:02:00Z".parse().unwrap())]);
+        assert_eq!(scheduler.consecutive_failure_count("low_stock_check").await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_cron_failures_raise_alert_after_three_in_a_row() {
+        let mut workflow = every_minute_workflow();
+        workflow.variables = vec![WorkflowVariableSchema {
+            name: "warehouse_id".to_string(),
+            variable_type: crate::domain::state_machine::VariableType::String,
+            required: true,
+            default: None,
+        }];
+        let engine = create_shared_workflow_engine();
+        engine.register_workflow(workflow).await.expect("Should register");
+        let clock = MockClock::at("2024-06-03T10:00:30Z");
+        let scheduler = CronScheduler::new(engine).with_clock(clock.clone());
+        let mut alerts = scheduler.subscribe();
+        scheduler.tick().await;
+
+        for (minute, expected) in [(1, 1), (2, 2)] {
+            clock.set(&format!("2024-06-03T10:0{}:00Z", minute));
+            assert_eq!(scheduler.tick().await.len(), 1);
+            assert_eq!(scheduler.consecutive_failure_count("low_stock_check").await, expected);
+            assert!(alerts.try_recv().is_err());
+        }
+
+        clock.set("2024-06-03T10:03:00Z");
+        scheduler.tick().await;
+        assert_eq!(scheduler.consecutive_failure_count("low_stock_check").await, 3);
+        let alert = alerts.try_recv().expect("alert after third failure");
+        assert_eq!(alert.workflow_id, "low_stock_check");
+        assert_eq!(alert.consecutive_failure_count, 3);
+        assert!(alert.last_error.contains("warehouse_id"));
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_job_runs_on_its_schedule() {
+        let clock = MockClock::at("2024-06-03T10:00:30Z");
+        let scheduler = CronScheduler::new(create_shared_workflow_engine()).with_clock(clock.clone());
+        let (runs, mut ran) = tokio::sync::mpsc::unbounded_channel();
+        scheduler
+            .schedule("hourly_report", "0 * * * *", move || {
+                let runs = runs.clone();
+                async move {
+                    let _ = runs.send(());
+                }
+            })
+            .await
+            .expect("Should schedule");
+        assert!(scheduler.schedule("broken", "hourly", || async {}).await.is_err());
+
+        scheduler.tick().await;
+        clock.set("2024-06-03T10:59:59Z");
+        scheduler.tick().await;
+        tokio::task::yield_now().await;
+        assert!(ran.try_recv().is_err());
+
+        clock.set("2024-06-03T11:00:00Z");
+        scheduler.tick().await;
+        tokio::time::timeout(std::time::Duration::from_secs(1), ran.recv())
+            .await
+            .expect("job should run")
+            .unwrap();
+    }
+
+    /// Admission whose nurse review escalates along `chain`, each level
+    /// allowing one minute more than the last
+    fn escalating_admission_workflow(chain: &[&str], final_action: FinalAction) -> WorkflowDefinition {
+        let mut workflow = admission_workflow();
+        let nurse_review = workflow.nodes.iter_mut().find(|n| n.id == "nurse_review").unwrap();
+        nurse_review.config.escalation = Some(EscalationConfig {
+            levels: chain.iter().zip(1..)
+                .map(|(target, minutes)| EscalationLevel { after_minutes: minutes, escalate_to: target.to_string() })
+                .collect(),
+            final_action,
+        });
+        workflow
+    }
+
+    async fn nurse_review_task(engine: &WorkflowEngine, instance_id: &str) -> HumanTask {
+        engine.tasks.read().await.values().find(|t| t.instance_id == instance_id).cloned().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_task_past_sla_is_escalated() {
+        let engine = create_shared_workflow_engine();
+        engine.register_workflow(escalating_admission_workflow(&["charge_nurse"], FinalAction::HardAlert))
+            .await.expect("Should register");
+        let instance = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+
+        // One-second SLA: escalated by the first run after it lapses
+        let task_id = {
+            let mut tasks = engine.tasks.write().await;
+            let task = tasks.values_mut().find(|t| t.instance_id == instance.id).unwrap();
+            task.sla_minutes = Some(0);
+            task.assigned_at = Utc::now() - chrono::Duration::seconds(1);
+            task.id.clone()
+        };
+        let clock = Arc::new(MockClock(std::sync::Mutex::new(Utc::now())));
+        let enforcer = TaskSlaEnforcer::new(engine.clone()).with_clock(clock.clone());
+        let mut alerts = enforcer.subscribe();
+
+        let events = enforcer.run().await;
+        assert_eq!(events, vec![TaskSlaEvent::TaskEscalated {
+            task_id,
+            from: "nurse".to_string(),
+            to: "charge_nurse".to_string(),
+            escalation_count: 1,
+        }]);
+        let task = nurse_review_task(&engine, &instance.id).await;
+        assert_eq!(task.assignee, "charge_nurse");
+        assert_eq!(task.status, TaskStatus::Pending);
+        assert_eq!(task.escalate_to, None);
+        let alert = alerts.try_recv().expect("alert for the new assignee");
+        assert_eq!(alert.recipient, "charge_nurse");
+        assert_eq!(alert.alert.alert_type, "task_escalated");
+
+        // The charge nurse gets a fresh SLA
+        assert!(enforcer.run().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_task_overdue_after_three_escalations() {
+        let engine = create_shared_workflow_engine();
+        let chain = ["charge_nurse", "ward_manager", "duty_doctor", "medical_director"];
+        engine.register_workflow(escalating_admission_workflow(&chain, FinalAction::HardAlert))
+            .await.expect("Should register");
+        let instance = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+        let created = nurse_review_task(&engine, &instance.id).await;
+        assert_eq!((created.sla_minutes, created.escalate_to.as_deref()), (Some(1), Some("charge_nurse")));
+
+        let clock = Arc::new(MockClock(std::sync::Mutex::new(created.assigned_at)));
+        let enforcer = TaskSlaEnforcer::new(engine.clone())
+            .with_clock(clock.clone())
+            .with_supervisor_group("nursing_supervisors");
+        let mut alerts = enforcer.subscribe();
+
+        for _ in 0..3 {
+            let task = nurse_review_task(&engine, &instance.id).await;
+            *clock.0.lock().unwrap() = task.assigned_at + chrono::Duration::minutes(i64::from(task.sla_minutes.unwrap()) + 1);
+            assert!(matches!(enforcer.run().await[..], [TaskSlaEvent::TaskEscalated { .. }]));
+        }
+        let task = nurse_review_task(&engine, &instance.id).await;
+        assert_eq!((task.assignee.as_str(), task.escalation_count), ("duty_doctor", 3));
+
+        *clock.0.lock().unwrap() = task.assigned_at + chrono::Duration::minutes(10);
+        let events = enforcer.run().await;
+        assert_eq!(events, vec![TaskSlaEvent::TaskOverdue {
+            task_id: task.id.clone(),
+            assignee: "duty_doctor".to_string(),
+            final_action: FinalAction::HardAlert,
+        }]);
+        assert_eq!(nurse_review_task(&engine, &instance.id).await.status, TaskStatus::Overdue);
+
+        let alert = std::iter::from_fn(|| alerts.try_recv().ok()).last().unwrap();
+        assert_eq!(alert.recipient, "nursing_supervisors");
+        assert_eq!(alert.alert.severity, "critical");
+
+        // Overdue tasks are not escalated again
+        assert!(enforcer.run().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_final_action_auto_cancel_cancels_instance() {
+        let engine = create_shared_workflow_engine();
+        engine.register_workflow(escalating_admission_workflow(&[], FinalAction::AutoCancel))
+            .await.expect("Should register");
+        let instance = engine.start_workflow("admission", HashMap::new(), None).await.expect("Should start");
+        {
+            let mut tasks = engine.tasks.write().await;
+            let task = tasks.values_mut().find(|t| t.instance_id == instance.id).unwrap();
+            task.sla_minutes = Some(5);
+        }
+
+        let task = nurse_review_task(&engine, &instance.id).await;
+        let clock = Arc::new(MockClock(std::sync::Mutex::new(task.assigned_at + chrono::Duration::minutes(6))));
+        let events = TaskSlaEnforcer::new(engine.clone()).with_clock(clock).run().await;
+
+        assert!(matches!(events[..], [TaskSlaEvent::TaskOverdue { final_action: FinalAction::AutoCancel, .. }]));
+        assert_eq!(engine.get_instance(&instance.id).await.unwrap().status, WorkflowStatus::Cancelled);
+        assert_eq!(nurse_review_task(&engine, &instance.id).await.status, TaskStatus::Cancelled);
     }
 }
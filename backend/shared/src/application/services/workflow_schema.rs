@@ -0,0 +1,340 @@
+//! Workflow definitions as JSON Schema documents
+//!
+//! Lets administrators write and edit workflows without Rust. A workflow is
+//! exchanged as a JSON Schema (draft 2020-12) document that carries the
+//! definition in an `x-workflow` extension keyword:
+//!
+//! ```json
+//! {
+//!   "$schema": "https://json-schema.org/draft/2020-12/schema",
+//!   "$id": "urn:health-v1:workflow:discharge-checklist",
+//!   "title": "Discharge checklist",
+//!   "description": "Optional",
+//!   "x-workflow": {
+//!     "version": 1,
+//!     "category": "optional",
+//!     "trigger": { "type": "manual" },
+//!     "is_active": false,
+//!     "tags": [],
+//!     "variables": [],
+//!     "input_schema": {},
+//!     "output_schema": {},
+//!     "nodes": [
+//!       { "id": "start", "node_type": "start", "name": "Start", "position": [0, 0] }
+//!     ],
+//!     "edges": []
+//!   }
+//! }
+//! ```
+//!
+//! `nodes` and `edges` take the [`WorkflowNode`] and [`WorkflowEdge`] fields.
+//! Everything else in `x-workflow` is optional, and unknown fields in it are
+//! rejected. Node actions and compensations named `connector.action` must
+//! exist in the [`ConnectorRegistry`] the document is imported against.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::domain::state_machine::WorkflowVariableSchema;
+use crate::shared::AppError;
+use super::connectors::ConnectorRegistry;
+use super::workflow_engine::{TriggerType, WorkflowDefinition, WorkflowEdge, WorkflowNode};
+
+/// JSON Schema dialect of workflow documents
+pub const WORKFLOW_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// `$id` prefix of workflow documents, followed by the workflow ID
+pub const WORKFLOW_SCHEMA_ID_PREFIX: &str = "urn:health-v1:workflow:";
+
+/// Extension keyword holding the workflow
+const WORKFLOW_KEYWORD: &str = "x-workflow";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct WorkflowDocument {
+    #[serde(rename = "$schema")]
+    schema: String,
+    #[serde(rename = "$id")]
+    id: String,
+    title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(rename = "x-workflow")]
+    workflow: WorkflowBody,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkflowBody {
+    #[serde(default = "first_version")]
+    version: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(default)]
+    trigger: TriggerType,
+    #[serde(default)]
+    is_active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    organization_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    variables: Vec<WorkflowVariableSchema>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    input_schema: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    output_schema: Option<Value>,
+    nodes: Vec<WorkflowNode>,
+    edges: Vec<WorkflowEdge>,
+}
+
+fn first_version() -> i32 {
+    1
+}
+
+/// One problem with a workflow document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkflowSchemaIssue {
+    /// JSON Pointer to the offending value, empty for the whole document
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for WorkflowSchemaIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Why a workflow document was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, thiserror::Error)]
+#[error(
+    "Invalid workflow definition: {}",
+    issues.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+)]
+pub struct WorkflowValidationError {
+    pub issues: Vec<WorkflowSchemaIssue>,
+}
+
+impl WorkflowValidationError {
+    fn at(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            issues: vec![WorkflowSchemaIssue {
+                path: path.into(),
+                message: message.into(),
+            }],
+        }
+    }
+}
+
+impl From<WorkflowValidationError> for AppError {
+    fn from(error: WorkflowValidationError) -> Self {
+        AppError::Validation(error.to_string())
+    }
+}
+
+impl WorkflowDefinition {
+    /// Import a workflow from its JSON Schema document
+    ///
+    /// The document must be a valid draft 2020-12 JSON Schema in the format
+    /// described in this module, and its connector actions must exist in
+    /// `connectors`; every invalid reference is reported. Timestamps are set
+    /// to now.
+    pub fn from_json_schema(json: &str, connectors: &ConnectorRegistry) -> Result<Self, WorkflowValidationError> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| WorkflowValidationError::at("", format!("Invalid JSON: {}", e)))?;
+
+        // Checked before compiling so no other meta-schema is ever fetched
+        if value.get("$schema").and_then(Value::as_str) != Some(WORKFLOW_SCHEMA_DIALECT) {
+            return Err(WorkflowValidationError::at(
+                "/$schema",
+                format!("must be {}", WORKFLOW_SCHEMA_DIALECT),
+            ));
+        }
+        if let Err(e) = jsonschema::validator_for(&value) {
+            return Err(WorkflowValidationError::at(
+                e.instance_path.to_string(),
+                format!("Not a valid JSON Schema: {}", e),
+            ));
+        }
+
+        let document: WorkflowDocument = serde_json::from_value(value)
+            .map_err(|e| WorkflowValidationError::at("", e.to_string()))?;
+        let id = match document.id.strip_prefix(WORKFLOW_SCHEMA_ID_PREFIX) {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => {
+                return Err(WorkflowValidationError::at(
+                    "/$id",
+                    format!("must be {}<workflow id>", WORKFLOW_SCHEMA_ID_PREFIX),
+                ))
+            }
+        };
+
+        let now = Utc::now();
+        let body = document.workflow;
+        let definition = WorkflowDefinition {
+            id,
+            name: document.title,
+            description: document.description,
+            version: body.version,
+            category: body.category,
+            nodes: body.nodes,
+            edges: body.edges,
+            input_schema: body.input_schema,
+            output_schema: body.output_schema,
+            variables: body.variables,
+            trigger: body.trigger,
+            is_active: body.is_active,
+            organization_id: body.organization_id,
+            tags: body.tags,
+            created_at: now,
+            updated_at: now,
+            created_by: None,
+        };
+
+        let issues = invalid_connector_references(&definition, connectors);
+        if issues.is_empty() {
+            Ok(definition)
+        } else {
+            Err(WorkflowValidationError { issues })
+        }
+    }
+
+    /// This workflow as a JSON Schema document (see the module docs)
+    ///
+    /// Timestamps and the creator are not exported.
+    pub fn to_json_schema(&self) -> String {
+        let document = WorkflowDocument {
+            schema: WORKFLOW_SCHEMA_DIALECT.to_string(),
+            id: format!("{}{}", WORKFLOW_SCHEMA_ID_PREFIX, self.id),
+            title: self.name.clone(),
+            description: self.description.clone(),
+            workflow: WorkflowBody {
+                version: self.version,
+                category: self.category.clone(),
+                trigger: self.trigger.clone(),
+                is_active: self.is_active,
+                organization_id: self.organization_id.clone(),
+                tags: self.tags.clone(),
+                variables: self.variables.clone(),
+                input_schema: self.input_schema.clone(),
+                output_schema: self.output_schema.clone(),
+                nodes: self.nodes.clone(),
+                edges: self.edges.clone(),
+            },
+        };
+        serde_json::to_string_pretty(&document).unwrap_or_default()
+    }
+}
+
+/// Node actions and compensations named `connector.action` that
+/// `connectors` cannot run
+///
+/// Names without a connector prefix are placeholders the engine does not
+/// dispatch, so they are not checked.
+pub fn invalid_connector_references(
+    definition: &WorkflowDefinition,
+    connectors: &ConnectorRegistry,
+) -> Vec<WorkflowSchemaIssue> {
+    let mut issues = Vec::new();
+
+    for (index, node) in definition.nodes.iter().enumerate() {
+        for (field, reference) in [("action", &node.config.action), ("compensation", &node.config.compensation)] {
+            let Some((connector_name, action)) = reference.as_deref().and_then(|r| r.split_once('.')) else {
+                continue;
+            };
+            let path = format!("/{}/nodes/{}/config/{}", WORKFLOW_KEYWORD, index, field);
+
+            let Some(connector) = connectors.get(connector_name) else {
+                issues.push(WorkflowSchemaIssue {
+                    path,
+                    message: format!("Node '{}' uses unknown connector '{}'", node.id, connector_name),
+                });
+                continue;
+            };
+            let actions: Vec<String> = connector.available_actions().into_iter().map(|a| a.name).collect();
+            if !actions.iter().any(|name| name == action) {
+                issues.push(WorkflowSchemaIssue {
+                    path,
+                    message: format!(
+                        "Node '{}' uses unknown action '{}' of connector '{}' (available: {})",
+                        node.id,
+                        action,
+                        connector_name,
+                        actions.join(", ")
+                    ),
+                });
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application::services::connectors::create_connector_registry;
+    use crate::application::services::WorkflowEngine;
+
+    fn registry() -> ConnectorRegistry {
+        create_connector_registry("http://localhost:8080/api")
+    }
+
+    fn workflow() -> WorkflowDefinition {
+        WorkflowEngine::create_prescription_dispensing_workflow_template("Dispense prescription")
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let original = workflow();
+        let exported = original.to_json_schema();
+        let imported = WorkflowDefinition::from_json_schema(&exported, &registry()).unwrap();
+
+        assert_eq!(imported.id, original.id);
+        assert_eq!(imported.name, original.name);
+        assert_eq!(imported.nodes.len(), original.nodes.len());
+        assert_eq!(imported.to_json_schema(), exported);
+    }
+
+    #[test]
+    fn test_export_is_a_valid_json_schema() {
+        let exported: Value = serde_json::from_str(&workflow().to_json_schema()).unwrap();
+        assert_eq!(exported["$schema"], WORKFLOW_SCHEMA_DIALECT);
+        assert!(jsonschema::validator_for(&exported).is_ok());
+        assert!(invalid_connector_references(&workflow(), &registry()).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_connector_actions_are_all_reported() {
+        let mut definition = workflow();
+        definition.nodes[1].config.action = Some("pharmacy.dispenseEverything".to_string());
+        definition.nodes[2].config.compensation = Some("lab.cancelOrder".to_string());
+
+        let error = WorkflowDefinition::from_json_schema(&definition.to_json_schema(), &registry()).unwrap_err();
+        let paths: Vec<&str> = error.issues.iter().map(|issue| issue.path.as_str()).collect();
+        assert_eq!(paths, vec!["/x-workflow/nodes/1/config/action", "/x-workflow/nodes/2/config/compensation"]);
+        assert!(error.issues[0].message.contains("dispenseEverything"));
+        assert!(error.issues[1].message.contains("unknown connector 'lab'"));
+    }
+
+    #[test]
+    fn test_documents_that_are_not_workflow_schemas_are_rejected() {
+        let registry = registry();
+        let mut document: Value = serde_json::from_str(&workflow().to_json_schema()).unwrap();
+
+        document["title"] = serde_json::json!(42);
+        let error = WorkflowDefinition::from_json_schema(&document.to_string(), &registry).unwrap_err();
+        assert!(error.issues[0].message.starts_with("Not a valid JSON Schema"));
+
+        document["$schema"] = serde_json::json!("http://json-schema.org/draft-07/schema#");
+        let error = WorkflowDefinition::from_json_schema(&document.to_string(), &registry).unwrap_err();
+        assert_eq!(error.issues[0].path, "/$schema");
+
+        assert!(WorkflowDefinition::from_json_schema("{", &registry).is_err());
+    }
+}
@@ -1,10 +1,14 @@
 use serde::{Deserialize, Serialize};
 use std::env;
 
+use super::features::FeatureFlags;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeploymentConfig {
     pub environment: DeploymentEnvironment,
     pub cloud_provider: CloudProvider,
+    #[serde(default)]
+    pub features: FeatureFlags,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +46,7 @@ impl DeploymentConfig {
         Ok(DeploymentConfig {
             environment,
             cloud_provider,
+            features: FeatureFlags::from_env(),
         })
     }
 }
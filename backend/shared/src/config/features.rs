@@ -0,0 +1,157 @@
+//! Feature flags
+//!
+//! Features that are still being rolled out can be switched off per
+//! deployment with `FEATURE_<NAME>` environment variables (for example
+//! `FEATURE_FHIR_EXPORT=false`). Every flag is on unless its variable says
+//! otherwise, so existing deployments keep their behavior. A realm can
+//! override the deployment's flags with [`RealmFeatureFlags`].
+
+use axum::{http::StatusCode, response::IntoResponse, response::Response, Json};
+use serde::{Deserialize, Serialize};
+
+use super::providers::parse_bool_env;
+
+/// Names of all feature flags, as accepted by [`FeatureFlags::is_enabled`]
+pub const FEATURE_NAMES: [&str; 4] = ["fhir_export", "totp_mfa", "drug_interaction_check", "workflow_engine"];
+
+/// Which features a deployment serves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureFlags {
+    /// FHIR R4 endpoints under `/v1/fhir`
+    pub fhir_export: bool,
+    /// TOTP second factor on vault logins
+    pub totp_mfa: bool,
+    /// Drug-drug interaction checks
+    pub drug_interaction_check: bool,
+    /// Starting workflow instances and triggering workflows from events
+    pub workflow_engine: bool,
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self {
+            fhir_export: true,
+            totp_mfa: true,
+            drug_interaction_check: true,
+            workflow_engine: true,
+        }
+    }
+}
+
+impl FeatureFlags {
+    /// Read flags from `FEATURE_*` environment variables
+    pub fn from_env() -> Self {
+        Self {
+            fhir_export: parse_bool_env("FEATURE_FHIR_EXPORT", true),
+            totp_mfa: parse_bool_env("FEATURE_TOTP_MFA", true),
+            drug_interaction_check: parse_bool_env("FEATURE_DRUG_INTERACTION_CHECK", true),
+            workflow_engine: parse_bool_env("FEATURE_WORKFLOW_ENGINE", true),
+        }
+    }
+
+    /// Whether the feature named `flag` is on; unknown names are off
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        match flag {
+            "fhir_export" => self.fhir_export,
+            "totp_mfa" => self.totp_mfa,
+            "drug_interaction_check" => self.drug_interaction_check,
+            "workflow_engine" => self.workflow_engine,
+            _ => false,
+        }
+    }
+
+    /// These flags with a realm's overrides applied
+    pub fn with_overrides(&self, overrides: &RealmFeatureFlags) -> Self {
+        Self {
+            fhir_export: overrides.fhir_export.unwrap_or(self.fhir_export),
+            totp_mfa: overrides.totp_mfa.unwrap_or(self.totp_mfa),
+            drug_interaction_check: overrides.drug_interaction_check.unwrap_or(self.drug_interaction_check),
+            workflow_engine: overrides.workflow_engine.unwrap_or(self.workflow_engine),
+        }
+    }
+}
+
+/// A realm's overrides of the deployment's feature flags
+///
+/// Unset flags fall back to the deployment's [`FeatureFlags`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RealmFeatureFlags {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fhir_export: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp_mfa: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drug_interaction_check: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow_engine: Option<bool>,
+}
+
+/// `404 Not Found` response for a request to a disabled feature
+pub fn feature_disabled_response(feature: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({
+            "error": "feature_disabled",
+            "feature": feature
+        })),
+    )
+        .into_response()
+}
+
+/// Return `404 Not Found` from a handler when a feature is off
+///
+/// For handlers returning [`Response`](axum::response::Response). `flags` are
+/// the [`FeatureFlags`] the request is served under, usually the deployment's
+/// flags with the caller's realm overrides applied.
+/// Usage: `feature_required!(flags, "fhir_export")`
+#[macro_export]
+macro_rules! feature_required {
+    ($flags:expr, $feature:expr) => {
+        if !$flags.is_enabled($feature) {
+            return $crate::config::features::feature_disabled_response($feature);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_enabled_by_name() {
+        let flags = FeatureFlags {
+            fhir_export: false,
+            ..FeatureFlags::default()
+        };
+
+        assert!(!flags.is_enabled("fhir_export"));
+        assert!(flags.is_enabled("workflow_engine"));
+        assert!(!flags.is_enabled("unknown_feature"));
+        assert!(FEATURE_NAMES.iter().all(|name| FeatureFlags::default().is_enabled(name)));
+    }
+
+    #[test]
+    fn test_realm_overrides_replace_only_set_flags() {
+        let overrides: RealmFeatureFlags = serde_json::from_str(r#"{"fhir_export": false}"#).unwrap();
+        let flags = FeatureFlags::default().with_overrides(&overrides);
+
+        assert!(!flags.fhir_export);
+        assert!(flags.totp_mfa);
+        assert_eq!(serde_json::to_string(&overrides).unwrap(), r#"{"fhir_export":false}"#);
+    }
+
+    #[test]
+    fn test_feature_required_returns_not_found() {
+        fn handler(flags: FeatureFlags) -> Response {
+            crate::feature_required!(flags, "fhir_export");
+            StatusCode::OK.into_response()
+        }
+
+        assert_eq!(handler(FeatureFlags::default()).status(), StatusCode::OK);
+        let disabled = FeatureFlags {
+            fhir_export: false,
+            ..FeatureFlags::default()
+        };
+        assert_eq!(handler(disabled).status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,9 +1,14 @@
 pub mod settings;
 pub mod providers;
 pub mod deployment;
+pub mod features;
+pub mod validation;
 
 pub use settings::Settings;
 pub use settings::DatabaseConfig;
+pub use settings::AuditRetentionPolicy;
 pub use providers::ProviderConfig;
 pub use deployment::DeploymentConfig;
+pub use features::{FeatureFlags, RealmFeatureFlags};
 
+pub use validation::{ConfigValidationError, EnvValidator};
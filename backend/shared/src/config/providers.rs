@@ -4,7 +4,7 @@ use std::env;
 /// Parse a boolean environment variable.
 /// Supports: "true", "1", "yes", "on" (case insensitive) → true
 ///           "false", "0", "no", "off", empty → false
-fn parse_bool_env(key: &str, default: bool) -> bool {
+pub(crate) fn parse_bool_env(key: &str, default: bool) -> bool {
     match env::var(key) {
         Ok(val) => {
             let lower = val.to_lowercase().trim().to_string();
@@ -0,0 +1,137 @@
+use thiserror::Error;
+
+/// Minimum JWT signing secret length in bytes (HS256 key size)
+pub const MIN_JWT_SECRET_BYTES: usize = 32;
+
+/// A single invalid or missing configuration value
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{variable}: {message}")]
+pub struct ConfigValidationError {
+    pub variable: String,
+    pub message: String,
+}
+
+impl ConfigValidationError {
+    pub fn new(variable: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            variable: variable.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks environment variables and collects every failure instead of
+/// stopping at the first one.
+///
+/// Variables are read through `lookup` so validation can run against a
+/// fixed map in tests rather than the process environment.
+pub struct EnvValidator<F> {
+    lookup: F,
+    errors: Vec<ConfigValidationError>,
+}
+
+impl<F> EnvValidator<F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    pub fn new(lookup: F) -> Self {
+        Self {
+            lookup,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Read a variable, treating empty values as unset
+    pub fn get(&self, variable: &str) -> Option<String> {
+        (self.lookup)(variable).filter(|value| !value.trim().is_empty())
+    }
+
+    /// Record an error for a variable
+    pub fn error(&mut self, variable: &str, message: impl Into<String>) {
+        self.errors.push(ConfigValidationError::new(variable, message));
+    }
+
+    /// Require a variable to be set
+    pub fn require(&mut self, variable: &str) -> Option<String> {
+        let value = self.get(variable);
+        if value.is_none() {
+            self.error(variable, "must be set");
+        }
+        value
+    }
+
+    /// Require every variable in `variables` to be set
+    pub fn require_all(&mut self, variables: &[&str], reason: &str) {
+        for variable in variables {
+            if self.get(variable).is_none() {
+                self.error(variable, format!("must be set {}", reason));
+            }
+        }
+    }
+
+    /// Require a PostgreSQL connection string
+    pub fn postgres_url(&mut self, variable: &str) {
+        let Some(value) = self.require(variable) else {
+            return;
+        };
+
+        match url::Url::parse(&value) {
+            Ok(parsed) if !matches!(parsed.scheme(), "postgres" | "postgresql") => self.error(
+                variable,
+                format!("must use the postgres:// or postgresql:// scheme, got {}://", parsed.scheme()),
+            ),
+            Ok(parsed) if !parsed.has_host() => self.error(variable, "must include a host"),
+            Ok(_) => {}
+            Err(e) => self.error(variable, format!("is not a valid connection string: {}", e)),
+        }
+    }
+
+    /// Check a JWT signing secret is long enough; `required` controls
+    /// whether an unset secret is an error
+    pub fn jwt_secret(&mut self, variable: &str, required: bool) {
+        let value = if required { self.require(variable) } else { self.get(variable) };
+        if let Some(secret) = value {
+            if secret.len() < MIN_JWT_SECRET_BYTES {
+                self.error(
+                    variable,
+                    format!("must be at least {} bytes, got {}", MIN_JWT_SECRET_BYTES, secret.len()),
+                );
+            }
+        }
+    }
+
+    /// Check an optional variable is an absolute http(s) URL
+    pub fn optional_url(&mut self, variable: &str) {
+        let Some(value) = self.get(variable) else {
+            return;
+        };
+
+        match url::Url::parse(&value) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => {}
+            Ok(_) => self.error(variable, "must be an http:// or https:// URL with a host"),
+            Err(e) => self.error(variable, format!("is not a valid URL: {}", e)),
+        }
+    }
+
+    /// Check an optional variable is a non-privileged port (1024-65535)
+    pub fn optional_port(&mut self, variable: &str) {
+        let Some(value) = self.get(variable) else {
+            return;
+        };
+
+        match value.trim().parse::<u16>() {
+            Ok(port) if port >= 1024 => {}
+            Ok(port) => self.error(variable, format!("must be between 1024 and 65535, got {}", port)),
+            Err(_) => self.error(variable, format!("must be a port number between 1024 and 65535, got {:?}", value)),
+        }
+    }
+
+    /// Return all collected errors, if any
+    pub fn finish(self) -> Result<(), Vec<ConfigValidationError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
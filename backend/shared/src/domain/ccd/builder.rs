@@ -0,0 +1,871 @@
+use std::io;
+
+use chrono::{DateTime, Utc};
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+
+use super::{CcdAllergy, CcdLabResult, CcdMedication, CcdPatient, CcdProblem, CcdVitalSign};
+use crate::shared::{AppError, AppResult};
+
+/// HL7's example organization OID, used until a real one is configured
+pub const DEFAULT_ORGANIZATION_OID: &str = "2.16.840.1.113883.19.5";
+
+const LOINC: &str = "2.16.840.1.113883.6.1";
+const SNOMED_CT: &str = "2.16.840.1.113883.6.96";
+const ICD10_CM: &str = "2.16.840.1.113883.6.90";
+const RXNORM: &str = "2.16.840.1.113883.6.88";
+const ACT_CLASS: &str = "2.16.840.1.113883.5.6";
+const ACT_CODE: &str = "2.16.840.1.113883.5.4";
+const ADMINISTRATIVE_GENDER: &str = "2.16.840.1.113883.5.1";
+const CONFIDENTIALITY: &str = "2.16.840.1.113883.5.25";
+const OBSERVATION_INTERPRETATION: &str = "2.16.840.1.113883.5.83";
+
+/// A coded C-CDA section
+struct Section {
+    template: &'static str,
+    extension: &'static str,
+    loinc: &'static str,
+    display: &'static str,
+    title: &'static str,
+}
+
+const ALLERGIES: Section = Section {
+    template: "2.16.840.1.113883.10.20.22.2.6.1",
+    extension: "2015-08-01",
+    loinc: "48765-2",
+    display: "Allergies and adverse reactions Document",
+    title: "Allergies and Intolerances",
+};
+
+const MEDICATIONS: Section = Section {
+    template: "2.16.840.1.113883.10.20.22.2.1.1",
+    extension: "2014-06-09",
+    loinc: "10160-0",
+    display: "History of Medication use Narrative",
+    title: "Medications",
+};
+
+const PROBLEMS: Section = Section {
+    template: "2.16.840.1.113883.10.20.22.2.5.1",
+    extension: "2015-08-01",
+    loinc: "11450-4",
+    display: "Problem list - Reported",
+    title: "Problems",
+};
+
+const RESULTS: Section = Section {
+    template: "2.16.840.1.113883.10.20.22.2.3.1",
+    extension: "2015-08-01",
+    loinc: "30954-2",
+    display: "Relevant diagnostic tests/laboratory data Narrative",
+    title: "Results",
+};
+
+const VITAL_SIGNS: Section = Section {
+    template: "2.16.840.1.113883.10.20.22.2.4.1",
+    extension: "2015-08-01",
+    loinc: "8716-3",
+    display: "Vital signs",
+    title: "Vital Signs",
+};
+
+/// LOINC codes for the vital types recorded in `^GMR(120.5)`
+fn vital_sign_code(kind: &str) -> Option<(&'static str, &'static str)> {
+    let code = match kind.to_ascii_lowercase().replace([' ', '-'], "_").as_str() {
+        "temperature" | "temp" => ("8310-5", "Body temperature"),
+        "pulse" | "heart_rate" => ("8867-4", "Heart rate"),
+        "respiration" | "respiratory_rate" | "resp" => ("9279-1", "Respiratory rate"),
+        "systolic" => ("8480-6", "Systolic blood pressure"),
+        "diastolic" => ("8462-4", "Diastolic blood pressure"),
+        "weight" => ("29463-7", "Body weight"),
+        "height" => ("8302-2", "Body height"),
+        "bmi" => ("39156-5", "Body mass index"),
+        "spo2" | "oxygen_saturation" | "pulse_oximetry" => ("59408-5", "Oxygen saturation by pulse oximetry"),
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn is_blood_pressure(kind: &str) -> bool {
+    matches!(kind.to_ascii_lowercase().as_str(), "bp" | "blood_pressure" | "blood pressure")
+}
+
+/// Digits of a date or timestamp, cut to a valid HL7 TS precision
+fn hl7_ts(value: &str) -> Option<String> {
+    let mut digits: String = value.chars().filter(char::is_ascii_digit).take(14).collect();
+    digits.truncate(digits.len() - digits.len() % 2);
+    (digits.len() >= 8).then_some(digits)
+}
+
+type Attrs<'a> = &'a [(&'a str, &'a str)];
+
+/// Thin wrapper over `quick_xml::Writer` for start/end/empty/text elements
+struct Xml {
+    writer: Writer<Vec<u8>>,
+}
+
+impl Xml {
+    fn start(&mut self, name: &str, attrs: Attrs) -> io::Result<()> {
+        self.writer.write_event(Event::Start(BytesStart::new(name).with_attributes(attrs.iter().copied())))
+    }
+
+    fn end(&mut self, name: &str) -> io::Result<()> {
+        self.writer.write_event(Event::End(BytesEnd::new(name)))
+    }
+
+    fn empty(&mut self, name: &str, attrs: Attrs) -> io::Result<()> {
+        self.writer.write_event(Event::Empty(BytesStart::new(name).with_attributes(attrs.iter().copied())))
+    }
+
+    fn text(&mut self, name: &str, attrs: Attrs, text: &str) -> io::Result<()> {
+        self.start(name, attrs)?;
+        self.writer.write_event(Event::Text(BytesText::new(text)))?;
+        self.end(name)
+    }
+
+    fn template(&mut self, root: &str, extension: &str) -> io::Result<()> {
+        self.empty("templateId", &[("root", root), ("extension", extension)])
+    }
+
+    /// `<name value="TS"/>`, or `nullFlavor="UNK"` when the time is unknown
+    fn time(&mut self, name: &str, value: Option<&str>) -> io::Result<()> {
+        match value.and_then(hl7_ts) {
+            Some(ts) => self.empty(name, &[("value", &ts)]),
+            None => self.empty(name, &[("nullFlavor", "UNK")]),
+        }
+    }
+
+    /// `<effectiveTime><low/>[<high/>]</effectiveTime>`
+    fn interval(&mut self, low: Option<&str>, high: Option<&str>) -> io::Result<()> {
+        self.start("effectiveTime", &[])?;
+        self.time("low", low)?;
+        if high.is_some() {
+            self.time("high", high)?;
+        }
+        self.end("effectiveTime")
+    }
+
+    /// A coded element, or `nullFlavor="OTH"` with the free text when uncoded
+    fn coded(&mut self, name: &str, extra: Attrs, code: Option<&str>, system: &str, display: &str) -> io::Result<()> {
+        let mut attrs = extra.to_vec();
+        match code.filter(|c| !c.is_empty()) {
+            Some(code) => {
+                attrs.extend([("code", code), ("codeSystem", system), ("displayName", display)]);
+                self.empty(name, &attrs)
+            }
+            None => {
+                attrs.push(("nullFlavor", "OTH"));
+                self.start(name, &attrs)?;
+                self.text("originalText", &[], display)?;
+                self.end(name)
+            }
+        }
+    }
+
+    fn reference(&mut self, id: &str) -> io::Result<()> {
+        self.start("text", &[])?;
+        self.empty("reference", &[("value", &format!("#{}", id))])?;
+        self.end("text")
+    }
+}
+
+/// Builds a C-CDA R2.1 CCD for one patient
+///
+/// ```ignore
+/// let xml = CcdBuilder::new(patient)
+///     .problems(problems)
+///     .medications(medications)
+///     .build()?;
+/// ```
+pub struct CcdBuilder {
+    patient: CcdPatient,
+    organization_oid: String,
+    organization_name: String,
+    effective_time: DateTime<Utc>,
+    problems: Vec<CcdProblem>,
+    medications: Vec<CcdMedication>,
+    allergies: Vec<CcdAllergy>,
+    vital_signs: Vec<CcdVitalSign>,
+    lab_results: Vec<CcdLabResult>,
+}
+
+impl CcdBuilder {
+    pub fn new(patient: CcdPatient) -> Self {
+        Self {
+            patient,
+            organization_oid: DEFAULT_ORGANIZATION_OID.to_string(),
+            organization_name: "Health V1".to_string(),
+            effective_time: Utc::now(),
+            problems: Vec::new(),
+            medications: Vec::new(),
+            allergies: Vec::new(),
+            vital_signs: Vec::new(),
+            lab_results: Vec::new(),
+        }
+    }
+
+    /// Organization that assigns the MRN and acts as document custodian
+    pub fn organization(mut self, oid: impl Into<String>, name: impl Into<String>) -> Self {
+        self.organization_oid = oid.into();
+        self.organization_name = name.into();
+        self
+    }
+
+    pub fn effective_time(mut self, time: DateTime<Utc>) -> Self {
+        self.effective_time = time;
+        self
+    }
+
+    pub fn problems(mut self, problems: Vec<CcdProblem>) -> Self {
+        self.problems = problems;
+        self
+    }
+
+    pub fn medications(mut self, medications: Vec<CcdMedication>) -> Self {
+        self.medications = medications;
+        self
+    }
+
+    pub fn allergies(mut self, allergies: Vec<CcdAllergy>) -> Self {
+        self.allergies = allergies;
+        self
+    }
+
+    pub fn vital_signs(mut self, vital_signs: Vec<CcdVitalSign>) -> Self {
+        self.vital_signs = vital_signs;
+        self
+    }
+
+    pub fn lab_results(mut self, lab_results: Vec<CcdLabResult>) -> Self {
+        self.lab_results = lab_results;
+        self
+    }
+
+    /// Render the document as UTF-8 XML
+    pub fn build(&self) -> AppResult<String> {
+        let mut xml = Xml { writer: Writer::new_with_indent(Vec::new(), b' ', 2) };
+        self.write_document(&mut xml)
+            .map_err(|e| AppError::Internal(format!("Failed to write CCD: {}", e)))?;
+        String::from_utf8(xml.writer.into_inner())
+            .map_err(|e| AppError::Internal(format!("CCD is not valid UTF-8: {}", e)))
+    }
+
+    /// MRN, falling back to the local patient ID
+    fn patient_identifier(&self) -> &str {
+        self.patient.mrn.as_deref().filter(|mrn| !mrn.is_empty()).unwrap_or(&self.patient.id)
+    }
+
+    fn write_document(&self, xml: &mut Xml) -> io::Result<()> {
+        xml.writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        xml.start(
+            "ClinicalDocument",
+            &[("xmlns", "urn:hl7-org:v3"), ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance")],
+        )?;
+        self.write_header(xml)?;
+
+        xml.start("component", &[])?;
+        xml.start("structuredBody", &[])?;
+        self.write_allergies(xml)?;
+        self.write_medications(xml)?;
+        self.write_problems(xml)?;
+        self.write_results(xml)?;
+        self.write_vital_signs(xml)?;
+        xml.end("structuredBody")?;
+        xml.end("component")?;
+
+        xml.end("ClinicalDocument")
+    }
+
+    fn write_header(&self, xml: &mut Xml) -> io::Result<()> {
+        let oid = self.organization_oid.as_str();
+        let now = self.effective_time.format("%Y%m%d%H%M%S+0000").to_string();
+
+        xml.empty("realmCode", &[("code", "US")])?;
+        xml.empty("typeId", &[("root", "2.16.840.1.113883.1.3"), ("extension", "POCD_HD000040")])?;
+        xml.template("2.16.840.1.113883.10.20.22.1.1", "2015-08-01")?;
+        xml.template("2.16.840.1.113883.10.20.22.1.2", "2015-08-01")?;
+        xml.empty("id", &[("root", oid), ("extension", self.patient_identifier())])?;
+        xml.empty(
+            "code",
+            &[
+                ("code", "34133-9"),
+                ("codeSystem", LOINC),
+                ("codeSystemName", "LOINC"),
+                ("displayName", "Summarization of Episode Note"),
+            ],
+        )?;
+        xml.text("title", &[], "Continuity of Care Document")?;
+        xml.empty("effectiveTime", &[("value", &now)])?;
+        xml.empty("confidentialityCode", &[("code", "N"), ("codeSystem", CONFIDENTIALITY)])?;
+        xml.empty("languageCode", &[("code", "en-US")])?;
+
+        self.write_record_target(xml)?;
+
+        xml.start("author", &[])?;
+        xml.empty("time", &[("value", &now)])?;
+        xml.start("assignedAuthor", &[])?;
+        xml.empty("id", &[("root", oid)])?;
+        xml.empty("addr", &[("nullFlavor", "UNK")])?;
+        xml.empty("telecom", &[("nullFlavor", "UNK")])?;
+        xml.start("assignedAuthoringDevice", &[])?;
+        xml.text("manufacturerModelName", &[], "health-v1")?;
+        xml.text("softwareName", &[], "health-v1 CCD export")?;
+        xml.end("assignedAuthoringDevice")?;
+        xml.end("assignedAuthor")?;
+        xml.end("author")?;
+
+        xml.start("custodian", &[])?;
+        xml.start("assignedCustodian", &[])?;
+        xml.start("representedCustodianOrganization", &[])?;
+        xml.empty("id", &[("root", oid)])?;
+        xml.text("name", &[], &self.organization_name)?;
+        xml.empty("telecom", &[("nullFlavor", "UNK")])?;
+        xml.empty("addr", &[("nullFlavor", "UNK")])?;
+        xml.end("representedCustodianOrganization")?;
+        xml.end("assignedCustodian")?;
+        xml.end("custodian")?;
+
+        xml.start("documentationOf", &[])?;
+        xml.start("serviceEvent", &[("classCode", "PCPR")])?;
+        xml.start("effectiveTime", &[])?;
+        xml.time("low", Some(&self.patient.birth_date))?;
+        xml.empty("high", &[("value", &now)])?;
+        xml.end("effectiveTime")?;
+        xml.end("serviceEvent")?;
+        xml.end("documentationOf")
+    }
+
+    fn write_record_target(&self, xml: &mut Xml) -> io::Result<()> {
+        let patient = &self.patient;
+        xml.start("recordTarget", &[])?;
+        xml.start("patientRole", &[])?;
+        xml.empty("id", &[("root", &self.organization_oid), ("extension", self.patient_identifier())])?;
+
+        if patient.city.is_some() || patient.state.is_some() {
+            xml.start("addr", &[("use", "HP")])?;
+            if let Some(city) = &patient.city {
+                xml.text("city", &[], city)?;
+            }
+            if let Some(state) = &patient.state {
+                xml.text("state", &[], state)?;
+            }
+            xml.text("country", &[], "US")?;
+            xml.end("addr")?;
+        } else {
+            xml.empty("addr", &[("nullFlavor", "UNK")])?;
+        }
+        xml.empty("telecom", &[("nullFlavor", "UNK")])?;
+
+        xml.start("patient", &[])?;
+        xml.start("name", &[("use", "L")])?;
+        if !patient.first_name.is_empty() {
+            xml.text("given", &[], &patient.first_name)?;
+        }
+        xml.text("family", &[], &patient.last_name)?;
+        xml.end("name")?;
+        let (gender, gender_name) = match patient.sex.to_ascii_uppercase().as_str() {
+            "M" | "MALE" => ("M", "Male"),
+            "F" | "FEMALE" => ("F", "Female"),
+            _ => ("UN", "Undifferentiated"),
+        };
+        xml.empty(
+            "administrativeGenderCode",
+            &[("code", gender), ("codeSystem", ADMINISTRATIVE_GENDER), ("displayName", gender_name)],
+        )?;
+        xml.time("birthTime", Some(&patient.birth_date))?;
+        xml.end("patient")?;
+
+        xml.end("patientRole")?;
+        xml.end("recordTarget")
+    }
+
+    /// Section wrapper with a narrative table; `rows` are `(narrative ID, cells)`
+    fn write_section(
+        xml: &mut Xml,
+        section: &Section,
+        headers: &[&str],
+        rows: &[(String, Vec<String>)],
+        entries: impl FnOnce(&mut Xml) -> io::Result<()>,
+    ) -> io::Result<()> {
+        xml.start("component", &[])?;
+        // An empty section is still required, flagged as "no information"
+        if rows.is_empty() {
+            xml.start("section", &[("nullFlavor", "NI")])?;
+        } else {
+            xml.start("section", &[])?;
+        }
+        xml.template(section.template, section.extension)?;
+        xml.empty(
+            "code",
+            &[
+                ("code", section.loinc),
+                ("codeSystem", LOINC),
+                ("codeSystemName", "LOINC"),
+                ("displayName", section.display),
+            ],
+        )?;
+        xml.text("title", &[], section.title)?;
+
+        if rows.is_empty() {
+            xml.text("text", &[], &format!("No {} recorded", section.title.to_lowercase()))?;
+        } else {
+            xml.start("text", &[])?;
+            xml.start("table", &[])?;
+            xml.start("thead", &[])?;
+            xml.start("tr", &[])?;
+            for header in headers {
+                xml.text("th", &[], header)?;
+            }
+            xml.end("tr")?;
+            xml.end("thead")?;
+            xml.start("tbody", &[])?;
+            for (id, cells) in rows {
+                xml.start("tr", &[("ID", id)])?;
+                for cell in cells {
+                    xml.text("td", &[], cell)?;
+                }
+                xml.end("tr")?;
+            }
+            xml.end("tbody")?;
+            xml.end("table")?;
+            xml.end("text")?;
+            entries(xml)?;
+        }
+
+        xml.end("section")?;
+        xml.end("component")
+    }
+
+    fn write_allergies(&self, xml: &mut Xml) -> io::Result<()> {
+        let oid = self.organization_oid.as_str();
+        let rows: Vec<_> = self
+            .allergies
+            .iter()
+            .map(|a| {
+                let cells = vec![
+                    a.allergen.clone(),
+                    a.category.clone(),
+                    a.reaction.clone().unwrap_or_default(),
+                    a.severity.clone().unwrap_or_default(),
+                    if a.active { "Active" } else { "Inactive" }.to_string(),
+                ];
+                (format!("allergy-{}", a.id), cells)
+            })
+            .collect();
+
+        Self::write_section(xml, &ALLERGIES, &["Substance", "Category", "Reaction", "Severity", "Status"], &rows, |xml| {
+            for (allergy, (narrative_id, _)) in self.allergies.iter().zip(&rows) {
+                let (code, display) = match allergy.category.to_ascii_lowercase().as_str() {
+                    "drug" => ("419511003", "Propensity to adverse reactions to drug"),
+                    "food" => ("414285001", "Allergy to food"),
+                    "environmental" => ("426232007", "Environmental allergy"),
+                    _ => ("420134006", "Propensity to adverse reactions"),
+                };
+                let status = if allergy.active { "active" } else { "completed" };
+
+                xml.start("entry", &[("typeCode", "DRIV")])?;
+                xml.start("act", &[("classCode", "ACT"), ("moodCode", "EVN")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.30", "2015-08-01")?;
+                xml.empty("id", &[("root", oid), ("extension", narrative_id)])?;
+                xml.empty("code", &[("code", "CONC"), ("codeSystem", ACT_CLASS)])?;
+                xml.empty("statusCode", &[("code", status)])?;
+                xml.interval(None, None)?;
+                xml.start("entryRelationship", &[("typeCode", "SUBJ")])?;
+                xml.start("observation", &[("classCode", "OBS"), ("moodCode", "EVN")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.7", "2014-06-09")?;
+                xml.empty("id", &[("root", oid), ("extension", &format!("{}-observation", narrative_id))])?;
+                xml.empty("code", &[("code", "ASSERTION"), ("codeSystem", ACT_CODE)])?;
+                xml.reference(narrative_id)?;
+                xml.empty("statusCode", &[("code", "completed")])?;
+                xml.interval(None, None)?;
+                xml.empty(
+                    "value",
+                    &[("xsi:type", "CD"), ("code", code), ("codeSystem", SNOMED_CT), ("displayName", display)],
+                )?;
+                xml.start("participant", &[("typeCode", "CSM")])?;
+                xml.start("participantRole", &[("classCode", "MANU")])?;
+                xml.start("playingEntity", &[("classCode", "MMAT")])?;
+                xml.coded("code", &[], None, RXNORM, &allergy.allergen)?;
+                xml.end("playingEntity")?;
+                xml.end("participantRole")?;
+                xml.end("participant")?;
+                xml.end("observation")?;
+                xml.end("entryRelationship")?;
+                xml.end("act")?;
+                xml.end("entry")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn write_medications(&self, xml: &mut Xml) -> io::Result<()> {
+        let oid = self.organization_oid.as_str();
+        let rows: Vec<_> = self
+            .medications
+            .iter()
+            .map(|m| {
+                let cells = vec![
+                    m.name.clone(),
+                    m.dose.clone(),
+                    m.route.clone(),
+                    m.frequency.clone(),
+                    m.start_date.clone(),
+                    if m.active { "Active" } else { "Completed" }.to_string(),
+                ];
+                (format!("medication-{}", m.id), cells)
+            })
+            .collect();
+
+        Self::write_section(xml, &MEDICATIONS, &["Medication", "Dose", "Route", "Frequency", "Start", "Status"], &rows, |xml| {
+            for (medication, (narrative_id, _)) in self.medications.iter().zip(&rows) {
+                let status = if medication.active { "active" } else { "completed" };
+
+                xml.start("entry", &[("typeCode", "DRIV")])?;
+                xml.start("substanceAdministration", &[("classCode", "SBADM"), ("moodCode", "INT")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.16", "2014-06-09")?;
+                xml.empty("id", &[("root", oid), ("extension", narrative_id)])?;
+                xml.reference(narrative_id)?;
+                xml.empty("statusCode", &[("code", status)])?;
+                xml.start("effectiveTime", &[("xsi:type", "IVL_TS")])?;
+                xml.time("low", Some(&medication.start_date))?;
+                if let Some(end) = &medication.end_date {
+                    xml.time("high", Some(end))?;
+                }
+                xml.end("effectiveTime")?;
+                if !medication.route.is_empty() {
+                    xml.coded("routeCode", &[], None, "2.16.840.1.113883.3.26.1.1", &medication.route)?;
+                }
+                xml.start("consumable", &[])?;
+                xml.start("manufacturedProduct", &[("classCode", "MANU")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.23", "2014-06-09")?;
+                xml.start("manufacturedMaterial", &[])?;
+                xml.coded("code", &[], medication.rxnorm_code.as_deref(), RXNORM, &medication.name)?;
+                xml.end("manufacturedMaterial")?;
+                xml.end("manufacturedProduct")?;
+                xml.end("consumable")?;
+                xml.end("substanceAdministration")?;
+                xml.end("entry")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn write_problems(&self, xml: &mut Xml) -> io::Result<()> {
+        let oid = self.organization_oid.as_str();
+        let rows: Vec<_> = self
+            .problems
+            .iter()
+            .map(|p| {
+                let cells = vec![
+                    p.name.clone(),
+                    p.icd10_code.clone().unwrap_or_default(),
+                    p.onset_date.clone().unwrap_or_default(),
+                    if p.active { "Active" } else { "Resolved" }.to_string(),
+                ];
+                (format!("problem-{}", p.id), cells)
+            })
+            .collect();
+
+        Self::write_section(xml, &PROBLEMS, &["Problem", "ICD-10", "Onset", "Status"], &rows, |xml| {
+            for (problem, (narrative_id, _)) in self.problems.iter().zip(&rows) {
+                let status = if problem.active { "active" } else { "completed" };
+                let onset = problem.onset_date.as_deref();
+
+                xml.start("entry", &[("typeCode", "DRIV")])?;
+                xml.start("act", &[("classCode", "ACT"), ("moodCode", "EVN")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.3", "2015-08-01")?;
+                xml.empty("id", &[("root", oid), ("extension", narrative_id)])?;
+                xml.empty("code", &[("code", "CONC"), ("codeSystem", ACT_CLASS)])?;
+                xml.empty("statusCode", &[("code", status)])?;
+                xml.interval(onset, None)?;
+                xml.start("entryRelationship", &[("typeCode", "SUBJ")])?;
+                xml.start("observation", &[("classCode", "OBS"), ("moodCode", "EVN")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.4", "2015-08-01")?;
+                xml.empty("id", &[("root", oid), ("extension", &format!("{}-observation", narrative_id))])?;
+                xml.empty(
+                    "code",
+                    &[("code", "55607006"), ("codeSystem", SNOMED_CT), ("displayName", "Problem")],
+                )?;
+                xml.reference(narrative_id)?;
+                xml.empty("statusCode", &[("code", "completed")])?;
+                xml.interval(onset, None)?;
+                xml.coded("value", &[("xsi:type", "CD")], problem.icd10_code.as_deref(), ICD10_CM, &problem.name)?;
+                xml.end("observation")?;
+                xml.end("entryRelationship")?;
+                xml.end("act")?;
+                xml.end("entry")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn write_results(&self, xml: &mut Xml) -> io::Result<()> {
+        let oid = self.organization_oid.as_str();
+        let rows: Vec<_> = self
+            .lab_results
+            .iter()
+            .map(|r| {
+                let value = match &r.unit {
+                    Some(unit) if !unit.is_empty() => format!("{} {}", r.value, unit),
+                    _ => r.value.clone(),
+                };
+                let cells = vec![
+                    r.name.clone(),
+                    value,
+                    r.reference_range.clone().unwrap_or_default(),
+                    r.interpretation.clone().unwrap_or_default(),
+                    r.collected_at.clone(),
+                ];
+                (format!("result-{}", r.id), cells)
+            })
+            .collect();
+
+        Self::write_section(xml, &RESULTS, &["Test", "Result", "Reference range", "Flag", "Collected"], &rows, |xml| {
+            for (result, (narrative_id, _)) in self.lab_results.iter().zip(&rows) {
+                let collected = Some(result.collected_at.as_str());
+
+                xml.start("entry", &[("typeCode", "DRIV")])?;
+                xml.start("organizer", &[("classCode", "BATTERY"), ("moodCode", "EVN")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.1", "2015-08-01")?;
+                xml.empty("id", &[("root", oid), ("extension", narrative_id)])?;
+                xml.coded("code", &[], result.loinc_code.as_deref(), LOINC, &result.name)?;
+                xml.empty("statusCode", &[("code", "completed")])?;
+                xml.interval(collected, collected)?;
+                xml.start("component", &[])?;
+                xml.start("observation", &[("classCode", "OBS"), ("moodCode", "EVN")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.2", "2015-08-01")?;
+                xml.empty("id", &[("root", oid), ("extension", &format!("{}-observation", narrative_id))])?;
+                xml.coded("code", &[], result.loinc_code.as_deref(), LOINC, &result.name)?;
+                xml.reference(narrative_id)?;
+                xml.empty("statusCode", &[("code", "completed")])?;
+                xml.time("effectiveTime", collected)?;
+                match result.value.trim().parse::<f64>() {
+                    Ok(_) => xml.empty(
+                        "value",
+                        &[
+                            ("xsi:type", "PQ"),
+                            ("value", result.value.trim()),
+                            ("unit", result.unit.as_deref().filter(|u| !u.is_empty()).unwrap_or("1")),
+                        ],
+                    )?,
+                    Err(_) => xml.text("value", &[("xsi:type", "ST")], &result.value)?,
+                }
+                if let Some(flag) = result.interpretation.as_deref().filter(|f| !f.is_empty()) {
+                    xml.empty("interpretationCode", &[("code", flag), ("codeSystem", OBSERVATION_INTERPRETATION)])?;
+                }
+                if let Some(range) = result.reference_range.as_deref().filter(|r| !r.is_empty()) {
+                    xml.start("referenceRange", &[])?;
+                    xml.start("observationRange", &[])?;
+                    xml.text("text", &[], range)?;
+                    xml.end("observationRange")?;
+                    xml.end("referenceRange")?;
+                }
+                xml.end("observation")?;
+                xml.end("component")?;
+                xml.end("organizer")?;
+                xml.end("entry")?;
+            }
+            Ok(())
+        })
+    }
+
+    fn write_vital_signs(&self, xml: &mut Xml) -> io::Result<()> {
+        let oid = self.organization_oid.as_str();
+        let rows: Vec<_> = self
+            .vital_signs
+            .iter()
+            .map(|v| {
+                let cells = vec![v.kind.clone(), format!("{} {}", v.value, v.unit), v.taken_at.clone()];
+                (format!("vital-{}", v.id), cells)
+            })
+            .collect();
+
+        Self::write_section(xml, &VITAL_SIGNS, &["Vital sign", "Value", "Taken"], &rows, |xml| {
+            for (vital, (narrative_id, _)) in self.vital_signs.iter().zip(&rows) {
+                // Blood pressure is recorded as "120/80" but coded as two observations
+                let observations: Vec<(&str, &str)> = if is_blood_pressure(&vital.kind) {
+                    let mut parts = vital.value.splitn(2, '/');
+                    let systolic = parts.next().unwrap_or("").trim();
+                    let diastolic = parts.next().unwrap_or("").trim();
+                    vec![("systolic", systolic), ("diastolic", diastolic)]
+                } else {
+                    vec![(vital.kind.as_str(), vital.value.trim())]
+                };
+                let taken = Some(vital.taken_at.as_str());
+
+                xml.start("entry", &[("typeCode", "DRIV")])?;
+                xml.start("organizer", &[("classCode", "CLUSTER"), ("moodCode", "EVN")])?;
+                xml.template("2.16.840.1.113883.10.20.22.4.26", "2015-08-01")?;
+                xml.empty("id", &[("root", oid), ("extension", narrative_id)])?;
+                xml.empty(
+                    "code",
+                    &[("code", "46680005"), ("codeSystem", SNOMED_CT), ("displayName", "Vital signs")],
+                )?;
+                xml.empty("statusCode", &[("code", "completed")])?;
+                xml.time("effectiveTime", taken)?;
+                for (index, (kind, value)) in observations.into_iter().enumerate() {
+                    let display = vital_sign_code(kind).map_or(kind, |(_, display)| display);
+                    xml.start("component", &[])?;
+                    xml.start("observation", &[("classCode", "OBS"), ("moodCode", "EVN")])?;
+                    xml.template("2.16.840.1.113883.10.20.22.4.27", "2014-06-09")?;
+                    xml.empty("id", &[("root", oid), ("extension", &format!("{}-{}", narrative_id, index))])?;
+                    xml.coded("code", &[], vital_sign_code(kind).map(|(code, _)| code), LOINC, display)?;
+                    xml.reference(narrative_id)?;
+                    xml.empty("statusCode", &[("code", "completed")])?;
+                    xml.time("effectiveTime", taken)?;
+                    match value.parse::<f64>() {
+                        Ok(_) => xml.empty(
+                            "value",
+                            &[("xsi:type", "PQ"), ("value", value), ("unit", if vital.unit.is_empty() { "1" } else { &vital.unit })],
+                        )?,
+                        Err(_) => xml.empty("value", &[("xsi:type", "PQ"), ("nullFlavor", "UNK")])?,
+                    }
+                    xml.end("observation")?;
+                    xml.end("component")?;
+                }
+                xml.end("organizer")?;
+                xml.end("entry")?;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ccd::schematron::Schematron;
+    use std::time::{Duration, Instant};
+
+    const SCHEMATRON: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/ccda/ccd-r2.1.sch"));
+
+    fn patient() -> CcdPatient {
+        CcdPatient {
+            id: "42".to_string(),
+            mrn: Some("MRN00042".to_string()),
+            first_name: "SEAN".to_string(),
+            last_name: "O'BRIEN & SONS".to_string(),
+            sex: "M".to_string(),
+            birth_date: "1980-04-12".to_string(),
+            city: Some("SPRINGFIELD".to_string()),
+            state: Some("IL".to_string()),
+        }
+    }
+
+    fn full_builder(problems: usize, medications: usize, labs: usize) -> CcdBuilder {
+        CcdBuilder::new(patient())
+            .problems(
+                (0..problems)
+                    .map(|i| CcdProblem {
+                        id: i.to_string(),
+                        name: format!("Problem {}", i),
+                        icd10_code: (i % 2 == 0).then(|| "E11.9".to_string()),
+                        onset_date: Some("2020-01-15".to_string()),
+                        active: true,
+                    })
+                    .collect(),
+            )
+            .medications(
+                (0..medications)
+                    .map(|i| CcdMedication {
+                        id: i.to_string(),
+                        name: format!("Metformin {}", i),
+                        rxnorm_code: Some("860975".to_string()),
+                        dose: "500 mg".to_string(),
+                        route: "oral".to_string(),
+                        frequency: "BID".to_string(),
+                        start_date: "2021-03-01".to_string(),
+                        end_date: None,
+                        active: true,
+                    })
+                    .collect(),
+            )
+            .allergies(vec![CcdAllergy {
+                id: "1".to_string(),
+                allergen: "Penicillin".to_string(),
+                category: "drug".to_string(),
+                severity: Some("severe".to_string()),
+                reaction: Some("Hives".to_string()),
+                active: true,
+            }])
+            .vital_signs(vec![
+                CcdVitalSign {
+                    id: "1".to_string(),
+                    kind: "blood_pressure".to_string(),
+                    value: "120/80".to_string(),
+                    unit: "mm[Hg]".to_string(),
+                    taken_at: "20240115.093000".to_string(),
+                },
+                CcdVitalSign {
+                    id: "2".to_string(),
+                    kind: "pulse".to_string(),
+                    value: "72".to_string(),
+                    unit: "/min".to_string(),
+                    taken_at: "20240115.093000".to_string(),
+                },
+            ])
+            .lab_results(
+                (0..labs)
+                    .map(|i| CcdLabResult {
+                        id: i.to_string(),
+                        name: "Hemoglobin A1c".to_string(),
+                        loinc_code: Some("4548-4".to_string()),
+                        value: if i % 10 == 0 { "pending".to_string() } else { "6.8".to_string() },
+                        unit: Some("%".to_string()),
+                        reference_range: Some("4.0-5.6".to_string()),
+                        interpretation: Some("H".to_string()),
+                        collected_at: "20240110.080000".to_string(),
+                    })
+                    .collect(),
+            )
+    }
+
+    #[test]
+    fn test_document_passes_schematron() {
+        let xml = full_builder(3, 2, 4).build().unwrap();
+        let failures = Schematron::parse(SCHEMATRON).validate(&xml);
+        assert!(failures.is_empty(), "schematron failures: {:#?}", failures);
+
+        assert!(xml.contains(r#"<id root="2.16.840.1.113883.19.5" extension="MRN00042"/>"#));
+        assert!(xml.contains("O&apos;BRIEN &amp; SONS"));
+        assert!(xml.contains(r#"codeSystem="2.16.840.1.113883.6.1" codeSystemName="LOINC" displayName="Problem list - Reported""#));
+        // Blood pressure splits into systolic and diastolic observations
+        assert!(xml.contains(r#"code="8480-6""#) && xml.contains(r#"code="8462-4""#));
+    }
+
+    #[test]
+    fn test_empty_document_passes_schematron() {
+        let xml = CcdBuilder::new(CcdPatient { mrn: None, ..patient() }).build().unwrap();
+        let failures = Schematron::parse(SCHEMATRON).validate(&xml);
+        assert!(failures.is_empty(), "schematron failures: {:#?}", failures);
+        // Falls back to the local ID without an MRN
+        assert!(xml.contains(r#"extension="42""#));
+        assert!(xml.contains(r#"<section nullFlavor="NI">"#));
+    }
+
+    #[test]
+    fn test_schematron_reports_missing_sections() {
+        let xml = full_builder(1, 1, 1).build().unwrap();
+        let without_problems = xml.replace("2.16.840.1.113883.10.20.22.2.5.1", "2.16.840.1.113883.10.20.22.2.5");
+        let failures = Schematron::parse(SCHEMATRON).validate(&without_problems);
+        assert!(failures.iter().any(|f| f.contains("exactly one [1..1] Problem Section")), "{:#?}", failures);
+    }
+
+    #[test]
+    fn test_large_document_builds_within_budget() {
+        let builder = full_builder(50, 20, 100);
+        let start = Instant::now();
+        let xml = builder.build().unwrap();
+        assert!(start.elapsed() < Duration::from_millis(500), "took {:?}", start.elapsed());
+        assert_eq!(xml.matches("2.16.840.1.113883.10.20.22.4.2\"").count(), 100);
+    }
+
+    #[test]
+    fn test_hl7_timestamps() {
+        assert_eq!(hl7_ts("1980-04-12").as_deref(), Some("19800412"));
+        assert_eq!(hl7_ts("20240115.093000").as_deref(), Some("20240115093000"));
+        assert_eq!(hl7_ts("20240115.0930").as_deref(), Some("202401150930"));
+        assert_eq!(hl7_ts("20240115.1").as_deref(), Some("20240115"));
+        assert_eq!(hl7_ts("unknown"), None);
+    }
+}
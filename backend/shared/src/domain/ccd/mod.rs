@@ -0,0 +1,88 @@
+//! C-CDA R2.1 Continuity of Care Document
+//!
+//! `CcdBuilder` renders a patient's demographics and clinical lists as a CCD
+//! (template `2.16.840.1.113883.10.20.22.1.2`) for referring providers. Each
+//! list becomes its coded section with a narrative table for display and
+//! structured entries for import.
+//!
+//! The input types are storage-agnostic; callers map their own records onto
+//! them. Dates may be given as `YYYY-MM-DD`, FileMan-style `YYYYMMDD.HHMMSS`
+//! or HL7 `YYYYMMDDHHMMSS`; only the digits are kept.
+
+mod builder;
+#[cfg(test)]
+mod schematron;
+
+pub use builder::CcdBuilder;
+
+/// Patient demographics for the document header
+#[derive(Debug, Clone, Default)]
+pub struct CcdPatient {
+    /// Local identifier (IEN), used when no MRN is recorded
+    pub id: String,
+    pub mrn: Option<String>,
+    pub first_name: String,
+    pub last_name: String,
+    /// `M`, `F` or anything else for undifferentiated
+    pub sex: String,
+    pub birth_date: String,
+    pub city: Option<String>,
+    pub state: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CcdProblem {
+    pub id: String,
+    pub name: String,
+    pub icd10_code: Option<String>,
+    pub onset_date: Option<String>,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CcdMedication {
+    pub id: String,
+    pub name: String,
+    pub rxnorm_code: Option<String>,
+    pub dose: String,
+    pub route: String,
+    pub frequency: String,
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CcdAllergy {
+    pub id: String,
+    pub allergen: String,
+    /// `drug`, `food` or `environmental`
+    pub category: String,
+    pub severity: Option<String>,
+    pub reaction: Option<String>,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CcdVitalSign {
+    pub id: String,
+    /// Vital type as recorded, e.g. `pulse`, `temperature`, `blood_pressure`
+    pub kind: String,
+    /// Numeric value, or `systolic/diastolic` for blood pressure
+    pub value: String,
+    pub unit: String,
+    pub taken_at: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CcdLabResult {
+    pub id: String,
+    pub name: String,
+    pub loinc_code: Option<String>,
+    pub value: String,
+    pub unit: Option<String>,
+    pub reference_range: Option<String>,
+    /// HL7 ObservationInterpretation code (`N`, `L`, `H`, `LL`, `HH`)
+    pub interpretation: Option<String>,
+    pub collected_at: String,
+}
@@ -0,0 +1,253 @@
+//! Minimal ISO Schematron runner for the CCD tests
+//!
+//! Understands the XPath subset used by the bundled C-CDA rules: `cda:` element
+//! steps joined by `/`, `@attr` steps, predicates (`[@a='v']` or a nested path),
+//! `count(path) = n` / `>= n`, `not(...)`, `and` and `or`. Rule contexts are a
+//! single step matched anywhere in the document.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    attrs: Vec<(String, String)>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn from_start(start: &BytesStart) -> Self {
+        let name = String::from_utf8_lossy(start.local_name().as_ref()).into_owned();
+        let attrs = start
+            .attributes()
+            .flatten()
+            .map(|attr| {
+                let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+                let value = attr.unescape_value().map(|v| v.into_owned()).unwrap_or_default();
+                (key, value)
+            })
+            .collect();
+        Self { name, attrs, children: Vec::new() }
+    }
+
+    fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    fn walk<'a>(&'a self, out: &mut Vec<&'a Node>) {
+        out.push(self);
+        for child in &self.children {
+            child.walk(out);
+        }
+    }
+}
+
+/// Parse a document into a tree under a synthetic root
+fn parse_tree(xml: &str) -> Node {
+    let mut reader = Reader::from_str(xml);
+    let mut stack = vec![Node::default()];
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(start)) => stack.push(Node::from_start(&start)),
+            Ok(Event::Empty(start)) => {
+                let node = Node::from_start(&start);
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(node);
+                }
+            }
+            Ok(Event::End(_)) => {
+                if let Some(node) = stack.pop() {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.children.push(node);
+                    }
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    stack.into_iter().next().unwrap_or_default()
+}
+
+/// Split on `sep` where it is not nested in brackets, parentheses or quotes
+fn split_top<'a>(expr: &'a str, sep: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut quoted, mut start) = (0i32, false, 0);
+    let bytes = expr.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' => quoted = !quoted,
+            b'[' | b'(' if !quoted => depth += 1,
+            b']' | b')' if !quoted => depth -= 1,
+            _ if !quoted && depth == 0 && expr[i..].starts_with(sep) => {
+                parts.push(&expr[start..i]);
+                i += sep.len();
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(&expr[start..]);
+    parts
+}
+
+/// Split a step into its name and predicate bodies
+fn parse_step(step: &str) -> (&str, Vec<&str>) {
+    let name_end = step.find('[').unwrap_or(step.len());
+    let mut predicates = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in step.char_indices().skip(name_end) {
+        match c {
+            '[' => {
+                if depth == 0 {
+                    start = i + 1;
+                }
+                depth += 1;
+            }
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    predicates.push(&step[start..i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    (&step[..name_end], predicates)
+}
+
+/// Nodes selected by `path` relative to `node`; an `@attr` step keeps the nodes having it
+fn select<'a>(path: &str, node: &'a Node) -> Vec<&'a Node> {
+    let mut current = vec![node];
+    for step in split_top(path.trim(), "/") {
+        let (name, predicates) = parse_step(step.trim());
+        if let Some(attr) = name.strip_prefix('@') {
+            current.retain(|n| n.attr(attr).is_some());
+            continue;
+        }
+        let local = name.rsplit(':').next().unwrap_or(name);
+        current = current
+            .into_iter()
+            .flat_map(|n| n.children.iter())
+            .filter(|child| child.name == local && predicates.iter().all(|p| test(p, child)))
+            .collect();
+    }
+    current
+}
+
+/// Evaluate a boolean XPath expression against `node`
+fn test(expr: &str, node: &Node) -> bool {
+    let alternatives = split_top(expr, " or ");
+    if alternatives.len() > 1 {
+        return alternatives.iter().any(|e| test(e, node));
+    }
+    let conjuncts = split_top(expr, " and ");
+    if conjuncts.len() > 1 {
+        return conjuncts.iter().all(|e| test(e, node));
+    }
+
+    let expr = expr.trim();
+    if let Some(inner) = expr.strip_prefix("not(").and_then(|e| e.strip_suffix(')')) {
+        return !test(inner, node);
+    }
+    if let Some(rest) = expr.strip_prefix("count(") {
+        let mut depth = 1;
+        let close = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        let count = select(&rest[..close], node).len();
+        let comparison = rest.get(close + 1..).unwrap_or("").replace(' ', "");
+        let (op, n) = match comparison.strip_prefix(">=") {
+            Some(n) => (">=", n),
+            None => ("=", comparison.trim_start_matches('=')),
+        };
+        let n: usize = n.parse().unwrap_or(usize::MAX);
+        return if op == ">=" { count >= n } else { count == n };
+    }
+    if let Some((attr, value)) = expr.strip_prefix('@').and_then(|e| e.split_once('=')) {
+        return node.attr(attr.trim()) == Some(value.trim().trim_matches('\''));
+    }
+    !select(expr, node).is_empty()
+}
+
+struct Rule {
+    context: String,
+    asserts: Vec<(String, String)>,
+}
+
+pub struct Schematron {
+    rules: Vec<Rule>,
+}
+
+impl Schematron {
+    pub fn parse(schema: &str) -> Self {
+        let mut reader = Reader::from_str(schema);
+        let mut rules: Vec<Rule> = Vec::new();
+        let mut pending_test: Option<String> = None;
+        let mut message = String::new();
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(start)) => {
+                    let node = Node::from_start(&start);
+                    match node.name.as_str() {
+                        "rule" => rules.push(Rule {
+                            context: node.attr("context").unwrap_or_default().to_string(),
+                            asserts: Vec::new(),
+                        }),
+                        "assert" => {
+                            pending_test = node.attr("test").map(str::to_string);
+                            message.clear();
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::Text(text)) if pending_test.is_some() => {
+                    message.push_str(&text.unescape().unwrap_or_default());
+                }
+                Ok(Event::End(end)) if end.local_name().as_ref() == b"assert" => {
+                    if let (Some(test), Some(rule)) = (pending_test.take(), rules.last_mut()) {
+                        rule.asserts.push((test, message.trim().to_string()));
+                    }
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                Ok(_) => {}
+            }
+        }
+        Self { rules }
+    }
+
+    /// Messages of every failed assertion
+    pub fn validate(&self, xml: &str) -> Vec<String> {
+        let tree = parse_tree(xml);
+        let mut nodes = Vec::new();
+        tree.walk(&mut nodes);
+
+        let mut failures = Vec::new();
+        for rule in &self.rules {
+            let (name, predicates) = parse_step(&rule.context);
+            let local = name.rsplit(':').next().unwrap_or(name);
+            let matches = nodes
+                .iter()
+                .filter(|n| n.name == local && predicates.iter().all(|p| test(p, n)));
+            for node in matches {
+                for (assertion, message) in &rule.asserts {
+                    if !test(assertion, node) {
+                        failures.push(format!("{}: {}", rule.context, message));
+                    }
+                }
+            }
+        }
+        failures
+    }
+}
@@ -15,6 +15,7 @@ pub mod order;
 pub mod appointment;
 pub mod drug;
 pub mod drug_interaction;
+pub mod provider_schedule;
 
 pub use patient::*;
 pub use visit::*;
@@ -28,3 +29,4 @@ pub use order::*;
 pub use appointment::*;
 pub use drug::*;
 pub use drug_interaction::*;
+pub use provider_schedule::*;
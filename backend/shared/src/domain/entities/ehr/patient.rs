@@ -8,6 +8,7 @@ use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
 use crate::shared::AuditFields;
+use crate::shared::impl_has_audit_fields;
 
 /// Patient gender options
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
@@ -136,6 +137,8 @@ pub struct EhrPatient {
     pub version: i64,
 }
 
+impl_has_audit_fields!(EhrPatient);
+
 impl EhrPatient {
     /// Create a new patient with required fields
     pub fn new(
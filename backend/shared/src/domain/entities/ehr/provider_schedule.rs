@@ -0,0 +1,158 @@
+//! EHR Provider Schedule Entity
+//!
+//! Corresponds to VistA Scheduling (^SDAM) - a provider's working hours
+//! and blocked time for one day, plus the appointments booked against it
+
+use chrono::{Duration, NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+/// Default start of a provider's day when no schedule is stored
+pub const DEFAULT_DAY_START: (u32, u32) = (8, 0);
+
+/// Default end of a provider's day when no schedule is stored
+pub const DEFAULT_DAY_END: (u32, u32) = (17, 0);
+
+/// Default slot length in minutes
+pub const DEFAULT_SLOT_MINUTES: i32 = 30;
+
+/// Slot status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SlotStatus {
+    #[serde(rename = "available")]
+    Available,
+    #[serde(rename = "booked")]
+    Booked,
+    #[serde(rename = "blocked")]
+    Blocked,
+}
+
+/// Period of a provider's day that cannot be booked (meetings, leave, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleBlock {
+    pub start: NaiveTime,
+    pub duration_minutes: i32,
+}
+
+/// Appointment occupying part of a provider's day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookedAppointment {
+    /// Appointment IEN (^SD(44))
+    pub appointment_ien: i64,
+    pub start: NaiveTime,
+    pub duration_minutes: i32,
+}
+
+/// Single bookable slot of a provider's day
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleSlot {
+    /// Slot start, `HH:MM`
+    pub time: String,
+    pub duration_minutes: i32,
+    pub status: SlotStatus,
+}
+
+/// Provider Schedule Entity
+///
+/// One provider's day, split into `slot_minutes` slots between
+/// `start_time` and `end_time`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderSchedule {
+    /// Provider IEN (VistA NEW PERSON file)
+    pub provider_ien: i64,
+
+    pub date: NaiveDate,
+
+    /// Working hours
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+
+    /// Slot length in minutes
+    pub slot_minutes: i32,
+
+    /// Blocked periods
+    pub blocked: Vec<ScheduleBlock>,
+
+    /// Appointments booked on this day
+    pub appointments: Vec<BookedAppointment>,
+}
+
+/// Whether [a, a + a_minutes) and [b, b + b_minutes) overlap
+fn overlaps(a: NaiveTime, a_minutes: i32, b: NaiveTime, b_minutes: i32) -> bool {
+    let a_end = a + Duration::minutes(i64::from(a_minutes));
+    let b_end = b + Duration::minutes(i64::from(b_minutes));
+    a < b_end && b < a_end
+}
+
+impl ProviderSchedule {
+    /// Create a schedule with the default working hours and no bookings
+    pub fn new(provider_ien: i64, date: NaiveDate) -> Self {
+        Self {
+            provider_ien,
+            date,
+            start_time: NaiveTime::from_hms_opt(DEFAULT_DAY_START.0, DEFAULT_DAY_START.1, 0)
+                .unwrap_or(NaiveTime::MIN),
+            end_time: NaiveTime::from_hms_opt(DEFAULT_DAY_END.0, DEFAULT_DAY_END.1, 0)
+                .unwrap_or(NaiveTime::MIN),
+            slot_minutes: DEFAULT_SLOT_MINUTES,
+            blocked: Vec::new(),
+            appointments: Vec::new(),
+        }
+    }
+
+    /// Start times of every slot in the working day
+    pub fn slot_starts(&self) -> Vec<NaiveTime> {
+        let step = Duration::minutes(i64::from(self.slot_minutes.max(1)));
+        let mut starts = Vec::new();
+        let mut time = self.start_time;
+        while time + step <= self.end_time && time + step > time {
+            starts.push(time);
+            time += step;
+        }
+        starts
+    }
+
+    /// Appointment overlapping the given period, if any
+    pub fn conflict(&self, start: NaiveTime, duration_minutes: i32) -> Option<&BookedAppointment> {
+        self.appointments
+            .iter()
+            .find(|a| overlaps(a.start, a.duration_minutes, start, duration_minutes))
+    }
+
+    /// Whether the given period overlaps blocked time
+    pub fn is_blocked(&self, start: NaiveTime, duration_minutes: i32) -> bool {
+        self.blocked
+            .iter()
+            .any(|b| overlaps(b.start, b.duration_minutes, start, duration_minutes))
+    }
+
+    /// Whether the given period is within working hours and neither booked nor blocked
+    pub fn is_free(&self, start: NaiveTime, duration_minutes: i32) -> bool {
+        let end = start + Duration::minutes(i64::from(duration_minutes));
+        start >= self.start_time
+            && end <= self.end_time
+            && end > start
+            && !self.is_blocked(start, duration_minutes)
+            && self.conflict(start, duration_minutes).is_none()
+    }
+
+    /// Every slot of the day with its status; blocked time wins over bookings
+    pub fn slots(&self) -> Vec<ScheduleSlot> {
+        self.slot_starts()
+            .into_iter()
+            .map(|start| {
+                let status = if self.is_blocked(start, self.slot_minutes) {
+                    SlotStatus::Blocked
+                } else if self.conflict(start, self.slot_minutes).is_some() {
+                    SlotStatus::Booked
+                } else {
+                    SlotStatus::Available
+                };
+                ScheduleSlot {
+                    time: start.format("%H:%M").to_string(),
+                    duration_minutes: self.slot_minutes,
+                    status,
+                }
+            })
+            .collect()
+    }
+}
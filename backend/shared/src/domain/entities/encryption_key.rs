@@ -1,18 +1,20 @@
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use crate::shared::impl_has_audit_fields;
 
 /// Data Encryption Key (DEK) entity
-/// Each user/entity has an individual DEK encrypted with the master key
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+/// Each user/entity has an individual DEK; `KeyRepository` implementations
+/// wrap it with the master key before it is persisted
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionKey {
     pub id: Uuid,
     pub entity_id: Uuid,           // User or entity this key belongs to
     pub entity_type: String,       // "user", "patient", "document", etc.
-    pub encrypted_key: Vec<u8>,     // DEK encrypted with master key
-    pub nonce: Vec<u8>,             // Nonce used for AES-256-GCM encryption (12 bytes)
-    pub key_algorithm: String,      // "AES-256-GCM"
+    #[serde(skip_serializing)]
+    pub key_material: Vec<u8>,     // Plaintext DEK, never stored as-is
+    pub key_version: i32,          // Master key version that wraps the DEK
+    pub key_algorithm: String,     // "AES-256-KW"
     pub created_at: DateTime<Utc>,
     pub rotated_at: Option<DateTime<Utc>>,
     pub is_active: bool,
@@ -25,22 +27,23 @@ pub struct EncryptionKey {
     pub version: i64,
 }
 
+impl_has_audit_fields!(EncryptionKey);
+
 impl EncryptionKey {
     pub fn new(
         entity_id: Uuid,
         entity_type: String,
-        encrypted_key: Vec<u8>,
-        nonce: Vec<u8>,
-        key_algorithm: String,
+        key_material: Vec<u8>,
+        key_version: i32,
     ) -> Self {
         let now = Utc::now();
         Self {
             id: Uuid::new_v4(),
             entity_id,
             entity_type,
-            encrypted_key,
-            nonce,
-            key_algorithm,
+            key_material,
+            key_version,
+            key_algorithm: "AES-256-KW".to_string(),
             created_at: now,
             rotated_at: None,
             is_active: true,
@@ -53,9 +56,8 @@ impl EncryptionKey {
         }
     }
 
-    pub fn rotate(&mut self, new_encrypted_key: Vec<u8>, new_nonce: Vec<u8>) {
-        self.encrypted_key = new_encrypted_key;
-        self.nonce = new_nonce;
+    pub fn rotate(&mut self, new_key_material: Vec<u8>) {
+        self.key_material = new_key_material;
         self.rotated_at = Some(Utc::now());
         self.updated_at = Utc::now();
         self.is_active = true;
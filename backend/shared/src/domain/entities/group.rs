@@ -3,6 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use crate::shared::impl_has_audit_fields;
 
 /// Group entity - metadata only, permissions managed via Zanzibar relationships
 /// Groups don't have DEKs, they're just organizational units
@@ -26,6 +27,8 @@ pub struct Group {
     pub version: i64,
 }
 
+impl_has_audit_fields!(Group);
+
 impl Group {
     pub fn new(name: String, description: Option<String>, organization_id: Option<Uuid>) -> Self {
         let now = Utc::now();
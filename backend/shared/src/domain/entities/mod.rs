@@ -14,6 +14,7 @@ pub mod policy_template;
 pub mod policy_assignment;
 pub mod session;
 pub mod request_log;
+pub mod user_activity_event;
 pub mod geographic_region;
 pub mod regulation;
 pub mod compliance_rule;
@@ -24,10 +25,10 @@ pub mod ehr;
 pub use user::User;
 pub use role::Role;
 pub use permission::Permission;
-pub use relationship::Relationship;
+pub use relationship::{Relationship, RelationshipKey, RelationshipWrite, WriteResponse, encode_zookie, decode_zookie};
 pub use encryption_key::EncryptionKey;
 pub use group::Group;
-pub use user_provisioning_checklist::UserProvisioningChecklist;
+pub use user_provisioning_checklist::{UserProvisioningChecklist, ProvisioningStep};
 pub use ui_page::UiPage;
 pub use ui_button::UiButton;
 pub use ui_field::UiField;
@@ -37,6 +38,7 @@ pub use policy_template::PolicyTemplate;
 pub use policy_assignment::PolicyAssignment;
 pub use session::Session;
 pub use request_log::RequestLog;
+pub use user_activity_event::{UserActivityEvent, EHR_ACCESS_EVENT};
 pub use geographic_region::{GeographicRegion, GeographicLevel};
 pub use regulation::{Regulation, RegulationVersion, RegulationSection, RegulationChange, RegulationCategory, RegulationStatus, ChangeType};
 pub use compliance_rule::{ComplianceRule, ComplianceAssessment, ComplianceGap, EntityType};
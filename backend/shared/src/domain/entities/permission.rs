@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use crate::shared::impl_has_audit_fields;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Permission {
@@ -20,6 +21,8 @@ pub struct Permission {
     pub version: i64,
 }
 
+impl_has_audit_fields!(Permission);
+
 impl Permission {
     pub fn new(name: String, resource: String, action: String, description: Option<String>) -> Self {
         let now = Utc::now();
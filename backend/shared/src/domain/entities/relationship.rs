@@ -3,6 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use crate::shared::impl_has_audit_fields;
 
 /// Zanzibar-style relationship tuple
 /// Format: user:123#member@group:456
@@ -32,6 +33,8 @@ pub struct Relationship {
     pub version: i64,
 }
 
+impl_has_audit_fields!(Relationship);
+
 impl Relationship {
     pub fn new(user: String, relation: String, object: String) -> Self {
         Self::new_with_organization(user, relation, object, None)
@@ -258,3 +261,65 @@ impl Relationship {
     }
 }
 
+
+/// Identifies a relationship tuple without its metadata
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct RelationshipKey {
+    pub user: String,
+    pub relation: String,
+    pub object: String,
+    pub organization_id: Option<Uuid>,
+}
+
+impl RelationshipKey {
+    pub fn new(user: String, relation: String, object: String) -> Self {
+        Self {
+            user,
+            relation,
+            object,
+            organization_id: None,
+        }
+    }
+}
+
+impl From<&Relationship> for RelationshipKey {
+    fn from(rel: &Relationship) -> Self {
+        Self {
+            user: rel.user.clone(),
+            relation: rel.relation.clone(),
+            object: rel.object.clone(),
+            organization_id: rel.organization_id,
+        }
+    }
+}
+
+/// A single mutation in a `write_tuples` batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "operation", content = "tuple", rename_all = "snake_case")]
+pub enum RelationshipWrite {
+    Write(Relationship),
+    Delete(RelationshipKey),
+}
+
+/// Result of an atomic `write_tuples` batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriteResponse {
+    /// Consistency token; pass to `PermissionChecker::check` to read at least this fresh
+    pub zookie: String,
+    pub written: usize,
+    pub deleted: usize,
+}
+
+/// Zookie format version prefix
+const ZOOKIE_PREFIX: &str = "z1.";
+
+/// Encode a commit timestamp as a zookie
+pub fn encode_zookie(committed_at: DateTime<Utc>) -> String {
+    format!("{}{:x}", ZOOKIE_PREFIX, committed_at.timestamp_micros())
+}
+
+/// Decode a zookie back into its commit timestamp
+pub fn decode_zookie(zookie: &str) -> Option<DateTime<Utc>> {
+    let micros = i64::from_str_radix(zookie.strip_prefix(ZOOKIE_PREFIX)?, 16).ok()?;
+    DateTime::from_timestamp_micros(micros)
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use crate::shared::impl_has_audit_fields;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Role {
@@ -19,6 +20,8 @@ pub struct Role {
     pub version: i64,
 }
 
+impl_has_audit_fields!(Role);
+
 impl Role {
     pub fn new(name: String, description: Option<String>) -> Self {
         let now = Utc::now();
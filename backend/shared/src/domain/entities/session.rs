@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 use uuid::Uuid;
+use crate::shared::impl_has_audit_fields;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
@@ -30,6 +31,8 @@ pub struct Session {
     pub app_device: String,
 }
 
+impl_has_audit_fields!(Session);
+
 impl Session {
     pub fn new(
         session_token: String,
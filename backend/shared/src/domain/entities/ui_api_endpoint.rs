@@ -3,6 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use crate::shared::impl_has_audit_fields;
 
 /// UI API Endpoint entity - represents an API endpoint that can be controlled
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -25,6 +26,8 @@ pub struct UiApiEndpoint {
     pub version: i64,
 }
 
+impl_has_audit_fields!(UiApiEndpoint);
+
 impl UiApiEndpoint {
     pub fn new(endpoint: String, method: String, description: Option<String>) -> Self {
         let now = Utc::now();
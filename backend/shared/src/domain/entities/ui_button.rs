@@ -3,6 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use crate::shared::impl_has_audit_fields;
 
 /// UI Button entity - represents a button in the admin UI
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -26,6 +27,8 @@ pub struct UiButton {
     pub version: i64,
 }
 
+impl_has_audit_fields!(UiButton);
+
 impl UiButton {
     pub fn new(page_id: Uuid, button_id: String, label: String, action: Option<String>) -> Self {
         let now = Utc::now();
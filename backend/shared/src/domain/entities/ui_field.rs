@@ -3,6 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use crate::shared::impl_has_audit_fields;
 
 /// UI Field entity - represents a form field in the admin UI
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -26,6 +27,8 @@ pub struct UiField {
     pub version: i64,
 }
 
+impl_has_audit_fields!(UiField);
+
 impl UiField {
     pub fn new(page_id: Uuid, field_id: String, label: String, field_type: String) -> Self {
         let now = Utc::now();
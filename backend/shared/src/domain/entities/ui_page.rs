@@ -3,6 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde_json::Value;
+use crate::shared::impl_has_audit_fields;
 
 /// UI Page entity - represents a page in the admin UI
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -25,6 +26,8 @@ pub struct UiPage {
     pub version: i64,
 }
 
+impl_has_audit_fields!(UiPage);
+
 impl UiPage {
     pub fn new(name: String, path: String, description: Option<String>) -> Self {
         let now = Utc::now();
@@ -2,6 +2,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use crate::shared::AuditFields;
+use crate::shared::impl_has_audit_fields;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -24,6 +25,8 @@ pub struct User {
     pub version: i64,
 }
 
+impl_has_audit_fields!(User);
+
 impl User {
     pub fn new(email: String, username: String, password_hash: String) -> Self {
         let audit = AuditFields::new();
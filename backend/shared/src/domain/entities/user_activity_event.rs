@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Event type recorded for EHR record access
+pub const EHR_ACCESS_EVENT: &str = "ehr_access";
+
+/// HIPAA access log entry for a user's activity on an EHR record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserActivityEvent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub session_id: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub event_type: String,
+    pub method: String,
+    pub path: String,
+    pub patient_id: Option<i64>,
+    pub access_reason: Option<String>,
+    pub status_code: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UserActivityEvent {
+    pub fn new(user_id: Uuid, event_type: impl Into<String>, method: String, path: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            session_id: None,
+            request_id: None,
+            event_type: event_type.into(),
+            method,
+            path,
+            patient_id: None,
+            access_reason: None,
+            status_code: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    pub fn with_session_id_opt(mut self, session_id: Option<Uuid>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: String) -> Self {
+        self.request_id = Some(request_id);
+        self
+    }
+
+    pub fn with_patient_context(mut self, patient_id: Option<i64>, access_reason: Option<String>) -> Self {
+        self.patient_id = patient_id;
+        self.access_reason = access_reason;
+        self
+    }
+
+    pub fn with_status_code(mut self, status_code: u16) -> Self {
+        self.status_code = Some(status_code as i32);
+        self
+    }
+}
@@ -1,7 +1,92 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+/// Provisioning steps, in the order they normally run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ProvisioningStep {
+    CreateUser,
+    GenerateDek,
+    StoreDek,
+    RealmAssignment,
+    OrganizationMembership,
+    CreateRelationships,
+    AssignRole,
+    GrantAppAccess,
+    VaultUserCreation,
+    VaultTokenCreation,
+    AuditLog,
+}
+
+impl ProvisioningStep {
+    pub const ALL: [ProvisioningStep; 11] = [
+        ProvisioningStep::CreateUser,
+        ProvisioningStep::GenerateDek,
+        ProvisioningStep::StoreDek,
+        ProvisioningStep::RealmAssignment,
+        ProvisioningStep::OrganizationMembership,
+        ProvisioningStep::CreateRelationships,
+        ProvisioningStep::AssignRole,
+        ProvisioningStep::GrantAppAccess,
+        ProvisioningStep::VaultUserCreation,
+        ProvisioningStep::VaultTokenCreation,
+        ProvisioningStep::AuditLog,
+    ];
+
+    /// Checklist item ID for this step
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProvisioningStep::CreateUser => "create_user",
+            ProvisioningStep::GenerateDek => "generate_dek",
+            ProvisioningStep::StoreDek => "store_dek",
+            ProvisioningStep::RealmAssignment => "realm_assignment",
+            ProvisioningStep::OrganizationMembership => "organization_membership",
+            ProvisioningStep::CreateRelationships => "create_relationships",
+            ProvisioningStep::AssignRole => "assign_role",
+            ProvisioningStep::GrantAppAccess => "grant_app_access",
+            ProvisioningStep::VaultUserCreation => "vault_user_creation",
+            ProvisioningStep::VaultTokenCreation => "vault_token_creation",
+            ProvisioningStep::AuditLog => "audit_log",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            ProvisioningStep::CreateUser => "Create user record in database",
+            ProvisioningStep::GenerateDek => "Generate user DEK",
+            ProvisioningStep::StoreDek => "Store DEK in vault (encrypted with master key)",
+            ProvisioningStep::RealmAssignment => "Assign vault realm for organization",
+            ProvisioningStep::OrganizationMembership => "Create organization membership relationship",
+            ProvisioningStep::CreateRelationships => "Create default Zanzibar relationships",
+            ProvisioningStep::AssignRole => "Assign default role (if any)",
+            ProvisioningStep::GrantAppAccess => "Grant default app access",
+            ProvisioningStep::VaultUserCreation => "Create vault user",
+            ProvisioningStep::VaultTokenCreation => "Create vault token",
+            ProvisioningStep::AuditLog => "Create audit log entry",
+        }
+    }
+}
+
+/// Steps that must be `Completed` before each step may run
+pub fn default_step_dependencies() -> HashMap<ProvisioningStep, Vec<ProvisioningStep>> {
+    use ProvisioningStep::*;
+    HashMap::from([
+        (CreateUser, vec![]),
+        (GenerateDek, vec![CreateUser]),
+        (StoreDek, vec![GenerateDek]),
+        (RealmAssignment, vec![]),
+        (OrganizationMembership, vec![CreateUser]),
+        (CreateRelationships, vec![CreateUser]),
+        (AssignRole, vec![CreateUser]),
+        (GrantAppAccess, vec![OrganizationMembership]),
+        (VaultUserCreation, vec![CreateUser, RealmAssignment]),
+        (VaultTokenCreation, vec![CreateUser, RealmAssignment]),
+        (AuditLog, vec![CreateUser]),
+    ])
+}
+
 /// Checklist item status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ChecklistItemStatus {
@@ -9,6 +94,8 @@ pub enum ChecklistItemStatus {
     InProgress,
     Completed,
     Failed,
+    /// Not run, because it was not requested or its service is not configured
+    Skipped,
 }
 
 /// Individual checklist item
@@ -30,75 +117,72 @@ pub struct UserProvisioningChecklist {
     pub overall_status: ChecklistItemStatus,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    /// Organization and realm the user was provisioned into (used when retrying steps)
+    #[serde(default)]
+    pub organization_id: Option<Uuid>,
+    #[serde(default)]
+    pub realm_id: Option<Uuid>,
+    /// Steps that must be completed before a step can run
+    #[serde(default = "default_step_dependencies")]
+    pub step_dependencies: HashMap<ProvisioningStep, Vec<ProvisioningStep>>,
 }
 
 impl UserProvisioningChecklist {
     pub fn new(user_id: Uuid) -> Self {
         Self {
             user_id,
-            items: vec![
-                ChecklistItem {
-                    id: "generate_dek".to_string(),
-                    description: "Generate user DEK".to_string(),
-                    status: ChecklistItemStatus::Pending,
-                    error: None,
-                    completed_at: None,
-                },
-                ChecklistItem {
-                    id: "store_dek".to_string(),
-                    description: "Store DEK in vault (encrypted with master key)".to_string(),
-                    status: ChecklistItemStatus::Pending,
-                    error: None,
-                    completed_at: None,
-                },
-                ChecklistItem {
-                    id: "create_user".to_string(),
-                    description: "Create user record in database".to_string(),
-                    status: ChecklistItemStatus::Pending,
-                    error: None,
-                    completed_at: None,
-                },
-                ChecklistItem {
-                    id: "create_relationships".to_string(),
-                    description: "Create default Zanzibar relationships".to_string(),
-                    status: ChecklistItemStatus::Pending,
-                    error: None,
-                    completed_at: None,
-                },
-                ChecklistItem {
-                    id: "assign_role".to_string(),
-                    description: "Assign default role (if any)".to_string(),
-                    status: ChecklistItemStatus::Pending,
-                    error: None,
-                    completed_at: None,
-                },
-                ChecklistItem {
-                    id: "organization_membership".to_string(),
-                    description: "Create organization membership relationship".to_string(),
-                    status: ChecklistItemStatus::Pending,
-                    error: None,
-                    completed_at: None,
-                },
-                ChecklistItem {
-                    id: "grant_app_access".to_string(),
-                    description: "Grant default app access".to_string(),
-                    status: ChecklistItemStatus::Pending,
-                    error: None,
-                    completed_at: None,
-                },
-                ChecklistItem {
-                    id: "audit_log".to_string(),
-                    description: "Create audit log entry".to_string(),
+            items: ProvisioningStep::ALL
+                .iter()
+                .map(|step| ChecklistItem {
+                    id: step.as_str().to_string(),
+                    description: step.description().to_string(),
                     status: ChecklistItemStatus::Pending,
                     error: None,
                     completed_at: None,
-                },
-            ],
+                })
+                .collect(),
             overall_status: ChecklistItemStatus::Pending,
             started_at: Utc::now(),
             completed_at: None,
+            organization_id: None,
+            realm_id: None,
+            step_dependencies: default_step_dependencies(),
         }
     }
+
+    /// Status of a step's checklist item
+    pub fn step_status(&self, step: ProvisioningStep) -> Option<&ChecklistItemStatus> {
+        self.items.iter().find(|i| i.id == step.as_str()).map(|i| &i.status)
+    }
+
+    /// Dependencies of `step` that are not yet completed
+    pub fn unmet_dependencies(&self, step: ProvisioningStep) -> Vec<ProvisioningStep> {
+        self.step_dependencies
+            .get(&step)
+            .map(|deps| {
+                deps.iter()
+                    .copied()
+                    .filter(|dep| self.step_status(*dep) != Some(&ChecklistItemStatus::Completed))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Steps that have not completed and whose dependencies are all completed
+    pub fn executable_steps(&self) -> Vec<ProvisioningStep> {
+        ProvisioningStep::ALL
+            .iter()
+            .copied()
+            .filter(|step| {
+                matches!(
+                    self.step_status(*step),
+                    Some(ChecklistItemStatus::Pending)
+                        | Some(ChecklistItemStatus::Failed)
+                        | Some(ChecklistItemStatus::Skipped)
+                ) && self.unmet_dependencies(*step).is_empty()
+            })
+            .collect()
+    }
     
     pub fn mark_item_in_progress(&mut self, item_id: &str) {
         if let Some(item) = self.items.iter_mut().find(|i| i.id == item_id) {
@@ -123,9 +207,19 @@ impl UserProvisioningChecklist {
         self.update_overall_status();
     }
     
+    /// Record that a step was deliberately not run; it does not satisfy dependencies
+    pub fn mark_item_skipped(&mut self, item_id: &str) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == item_id) {
+            item.status = ChecklistItemStatus::Skipped;
+        }
+        self.update_overall_status();
+    }
+    
     fn update_overall_status(&mut self) {
         let has_failed = self.items.iter().any(|i| i.status == ChecklistItemStatus::Failed);
-        let all_completed = self.items.iter().all(|i| i.status == ChecklistItemStatus::Completed);
+        let all_completed = self.items.iter().all(|i| {
+            matches!(i.status, ChecklistItemStatus::Completed | ChecklistItemStatus::Skipped)
+        });
         let has_in_progress = self.items.iter().any(|i| i.status == ChecklistItemStatus::InProgress);
         
         if has_failed {
@@ -0,0 +1,13 @@
+//! FHIR R4 resource mapping for the EHR
+//!
+//! `FhirPatientMapper` converts between the YottaDB-backed `EhrPatientDto`
+//! and the FHIR `Patient` resource, keeping the VistA IEN as an identifier so
+//! a resource read back from the EHR can be matched to the one posted.
+//! `FhirValidator` checks posted resources against the fields the EHR
+//! requires before they are mapped.
+
+mod patient_mapper;
+mod validator;
+
+pub use patient_mapper::{FhirPatientMapper, FHIR_IEN_SYSTEM};
+pub use validator::{FhirIssue, FhirValidator};
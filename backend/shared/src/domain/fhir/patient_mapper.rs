@@ -0,0 +1,161 @@
+use chrono::NaiveDate;
+
+use crate::application::services::ehr_service::EhrPatientDto;
+use crate::application::services::fhir_mapper::{
+    fhir_gender_to_sex, FhirCodeableConcept, FhirCoding, FhirHumanName, FhirIdentifier, FhirPatient,
+    FHIR_IDENTIFIER_TYPE_SYSTEM, FHIR_SSN_SYSTEM,
+};
+
+/// Identifier system carrying the VistA IEN
+pub const FHIR_IEN_SYSTEM: &str = "urn:oid:2.16.840.1.113883.4.6";
+
+/// Maps `EhrPatientDto` to and from the FHIR R4 `Patient` resource
+pub struct FhirPatientMapper;
+
+impl FhirPatientMapper {
+    /// FHIR Patient for an EHR patient; the resource id is the IEN
+    pub fn to_fhir(patient: &EhrPatientDto) -> FhirPatient {
+        let mut identifier = vec![FhirIdentifier {
+            identifier_type: None,
+            system: Some(FHIR_IEN_SYSTEM.to_string()),
+            value: Some(patient.ien.to_string()),
+        }];
+        if let Some(mrn) = patient.mrn.as_ref().filter(|m| !m.is_empty()) {
+            identifier.push(FhirIdentifier {
+                identifier_type: Some(FhirCodeableConcept {
+                    coding: vec![FhirCoding {
+                        system: Some(FHIR_IDENTIFIER_TYPE_SYSTEM.to_string()),
+                        code: Some("MR".to_string()),
+                        display: Some("Medical Record Number".to_string()),
+                    }],
+                    text: None,
+                }),
+                system: None,
+                value: Some(mrn.clone()),
+            });
+        }
+        if let Some(ssn) = patient.ssn.as_ref().filter(|s| !s.is_empty()) {
+            identifier.push(FhirIdentifier {
+                identifier_type: None,
+                system: Some(FHIR_SSN_SYSTEM.to_string()),
+                value: Some(ssn.clone()),
+            });
+        }
+
+        let gender = match patient.sex.trim().to_uppercase().as_str() {
+            "M" | "MALE" => "male",
+            "F" | "FEMALE" => "female",
+            "O" | "OTHER" => "other",
+            _ => "unknown",
+        };
+
+        FhirPatient {
+            resource_type: "Patient".to_string(),
+            id: Some(patient.ien.to_string()),
+            identifier,
+            name: vec![FhirHumanName {
+                name_use: Some("official".to_string()),
+                family: Some(patient.last_name.clone()),
+                given: Some(patient.first_name.clone()).filter(|g| !g.is_empty()).into_iter().collect(),
+            }],
+            gender: Some(gender.to_string()),
+            birth_date: iso_date(&patient.date_of_birth),
+        }
+    }
+
+    /// EHR patient for a FHIR Patient; the IEN is 0 unless the resource carries one
+    pub fn from_fhir(patient: &FhirPatient) -> Result<EhrPatientDto, String> {
+        let name = patient.primary_name().ok_or("Patient has no name")?;
+        let last_name = name
+            .family
+            .as_deref()
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .ok_or("Patient name has no family name")?;
+        let first_name = name.given.first().map(|g| g.trim()).unwrap_or_default();
+
+        let birth_date = patient.birth_date.as_deref().ok_or("Patient has no birthDate")?;
+        let date_of_birth = iso_date(birth_date).ok_or_else(|| format!("Invalid birthDate '{}'", birth_date))?;
+
+        let identifier = |matches: &dyn Fn(&FhirIdentifier) -> bool| {
+            patient.identifier.iter().find(|i| matches(i)).and_then(|i| i.value.clone())
+        };
+
+        Ok(EhrPatientDto {
+            ien: Self::ien(patient).unwrap_or(0),
+            name: format!("{},{}", last_name.to_uppercase(), first_name.to_uppercase()),
+            first_name: first_name.to_string(),
+            last_name: last_name.to_string(),
+            sex: fhir_gender_to_sex(patient.gender.as_deref())?.to_string(),
+            date_of_birth,
+            ssn: identifier(&|i| i.system.as_deref() == Some(FHIR_SSN_SYSTEM)),
+            mrn: identifier(&FhirIdentifier::is_mrn),
+        })
+    }
+
+    /// IEN from the IEN identifier
+    pub fn ien(patient: &FhirPatient) -> Option<i64> {
+        patient
+            .identifier
+            .iter()
+            .filter(|i| i.system.as_deref() == Some(FHIR_IEN_SYSTEM))
+            .find_map(|i| i.value.as_deref()?.trim().parse().ok())
+    }
+}
+
+/// ISO-8601 date from `YYYY-MM-DD`, `YYYYMMDD` or FileMan `YYYMMDD` (year - 1700)
+fn iso_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    let date = match value.len() {
+        10 => NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+        8 => NaiveDate::parse_from_str(value, "%Y%m%d").ok(),
+        7 if value.bytes().all(|b| b.is_ascii_digit()) => {
+            let year = value[..3].parse::<i32>().ok()? + 1700;
+            NaiveDate::parse_from_str(&format!("{}{}", year, &value[3..]), "%Y%m%d").ok()
+        }
+        _ => None,
+    }?;
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn patient() -> EhrPatientDto {
+        EhrPatientDto {
+            ien: 42,
+            name: "DOE,JANE".to_string(),
+            first_name: "JANE".to_string(),
+            last_name: "DOE".to_string(),
+            sex: "F".to_string(),
+            date_of_birth: "2800412".to_string(),
+            ssn: None,
+            mrn: Some("MRN-0042".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_preserves_ien() {
+        let fhir = FhirPatientMapper::to_fhir(&patient());
+        assert_eq!(fhir.id.as_deref(), Some("42"));
+        assert_eq!(fhir.gender.as_deref(), Some("female"));
+        assert_eq!(fhir.birth_date.as_deref(), Some("1980-04-12"));
+
+        let back = FhirPatientMapper::from_fhir(&fhir).unwrap();
+        assert_eq!(back.ien, 42);
+        assert_eq!(back.name, "DOE,JANE");
+        assert_eq!(back.sex, "F");
+        assert_eq!(back.date_of_birth, "1980-04-12");
+        assert_eq!(back.mrn.as_deref(), Some("MRN-0042"));
+    }
+
+    #[test]
+    fn test_iso_date_formats() {
+        assert_eq!(iso_date("1980-04-12").as_deref(), Some("1980-04-12"));
+        assert_eq!(iso_date("19800412").as_deref(), Some("1980-04-12"));
+        assert_eq!(iso_date("3100101").as_deref(), Some("2010-01-01"));
+        assert_eq!(iso_date("1980-13-01"), None);
+        assert_eq!(iso_date(""), None);
+    }
+}
@@ -0,0 +1,140 @@
+use chrono::{NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use crate::application::services::fhir_mapper::FhirPatient;
+
+/// Administrative genders allowed by FHIR R4
+const GENDERS: [&str; 4] = ["male", "female", "other", "unknown"];
+
+/// Validation failure at a FHIRPath location, reported as an OperationOutcome issue
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FhirIssue {
+    pub expression: String,
+    pub diagnostics: String,
+}
+
+impl FhirIssue {
+    fn new(expression: impl Into<String>, diagnostics: impl Into<String>) -> Self {
+        Self { expression: expression.into(), diagnostics: diagnostics.into() }
+    }
+}
+
+/// Checks posted resources for the fields the EHR requires
+pub struct FhirValidator;
+
+impl FhirValidator {
+    /// Validate a raw Patient resource and parse it
+    ///
+    /// Beyond the base R4 profile, a name with family and given parts, a
+    /// `gender` and a full `birthDate` are required since the EHR can't
+    /// register a patient without them. Every issue found is returned.
+    pub fn validate_patient(resource: &JsonValue) -> Result<FhirPatient, Vec<FhirIssue>> {
+        let mut issues = Vec::new();
+        if resource.get("resourceType").and_then(JsonValue::as_str) != Some("Patient") {
+            issues.push(FhirIssue::new("Patient.resourceType", "resourceType must be 'Patient'"));
+            return Err(issues);
+        }
+
+        match resource.get("name").and_then(JsonValue::as_array) {
+            Some(names) if !names.is_empty() => {
+                let index = names
+                    .iter()
+                    .position(|n| n.get("use").and_then(JsonValue::as_str) == Some("official"))
+                    .unwrap_or(0);
+                let name = &names[index];
+                let family = name.get("family").and_then(JsonValue::as_str).map(str::trim);
+                if family.filter(|v| !v.is_empty()).is_none() {
+                    issues.push(FhirIssue::new(format!("Patient.name[{}].family", index), "Family name is required"));
+                }
+                let given = name
+                    .get("given")
+                    .and_then(JsonValue::as_array)
+                    .and_then(|g| g.first())
+                    .and_then(JsonValue::as_str)
+                    .map(str::trim);
+                if given.filter(|v| !v.is_empty()).is_none() {
+                    issues.push(FhirIssue::new(format!("Patient.name[{}].given", index), "Given name is required"));
+                }
+            }
+            _ => issues.push(FhirIssue::new("Patient.name", "At least one name is required")),
+        }
+
+        match resource.get("gender").and_then(JsonValue::as_str) {
+            Some(gender) if GENDERS.contains(&gender) => {}
+            Some(gender) => issues.push(FhirIssue::new(
+                "Patient.gender",
+                format!("'{}' is not one of male, female, other, unknown", gender),
+            )),
+            None => issues.push(FhirIssue::new("Patient.gender", "gender is required")),
+        }
+
+        match resource.get("birthDate").and_then(JsonValue::as_str) {
+            Some(birth_date) => match NaiveDate::parse_from_str(birth_date, "%Y-%m-%d") {
+                Ok(date) if date > Utc::now().date_naive() => {
+                    issues.push(FhirIssue::new("Patient.birthDate", "birthDate cannot be in the future"));
+                }
+                Ok(_) => {}
+                Err(_) => issues.push(FhirIssue::new(
+                    "Patient.birthDate",
+                    format!("'{}' is not a full date (YYYY-MM-DD)", birth_date),
+                )),
+            },
+            None => issues.push(FhirIssue::new("Patient.birthDate", "birthDate is required")),
+        }
+
+        if let Some(identifiers) = resource.get("identifier").and_then(JsonValue::as_array) {
+            for (index, identifier) in identifiers.iter().enumerate() {
+                let value = identifier.get("value").and_then(JsonValue::as_str).map(str::trim);
+                if value.filter(|v| !v.is_empty()).is_none() {
+                    issues.push(FhirIssue::new(format!("Patient.identifier[{}].value", index), "Identifier value is required"));
+                }
+            }
+        }
+
+        if !issues.is_empty() {
+            return Err(issues);
+        }
+        FhirPatient::from_resource(resource).map_err(|e| vec![FhirIssue::new("Patient", e)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_accepts_complete_patient() {
+        let resource = json!({
+            "resourceType": "Patient",
+            "name": [{"use": "official", "family": "Doe", "given": ["Jane"]}],
+            "gender": "female",
+            "birthDate": "1980-04-12"
+        });
+        let patient = FhirValidator::validate_patient(&resource).unwrap();
+        assert_eq!(patient.birth_date.as_deref(), Some("1980-04-12"));
+    }
+
+    #[test]
+    fn test_reports_every_missing_field() {
+        let resource = json!({
+            "resourceType": "Patient",
+            "name": [{"given": ["Jane"]}],
+            "gender": "f",
+            "identifier": [{"system": "urn:oid:2.16.840.1.113883.4.6"}]
+        });
+        let issues = FhirValidator::validate_patient(&resource).unwrap_err();
+        let expressions: Vec<&str> = issues.iter().map(|i| i.expression.as_str()).collect();
+        assert_eq!(
+            expressions,
+            ["Patient.name[0].family", "Patient.gender", "Patient.birthDate", "Patient.identifier[0].value"]
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_resource_types() {
+        let issues = FhirValidator::validate_patient(&json!({"resourceType": "Practitioner"})).unwrap_err();
+        assert_eq!(issues[0].expression, "Patient.resourceType");
+    }
+}
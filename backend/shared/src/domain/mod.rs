@@ -1,4 +1,6 @@
+pub mod ccd;
 pub mod entities;
+pub mod fhir;
 pub mod repositories;
 pub mod services;
 pub mod state_machine;
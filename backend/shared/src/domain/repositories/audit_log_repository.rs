@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+use crate::shared::AppResult;
+use serde_json::Value;
+use uuid::Uuid;
+
+/// Row of the `audit_logs` security audit trail
+#[derive(Debug, Clone)]
+pub struct AuditLogEntry {
+    pub user_id: Option<Uuid>,
+    pub action: String,
+    pub resource: String,
+    pub resource_id: Option<Uuid>,
+    pub details: Value,
+}
+
+#[async_trait]
+pub trait AuditLogRepository: Send + Sync {
+    async fn create(&self, entry: AuditLogEntry) -> AppResult<()>;
+}
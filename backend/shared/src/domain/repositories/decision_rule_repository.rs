@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+use crate::application::services::{DecisionRule, RuleContext, RuleResult};
+use crate::shared::AppResult;
+
+#[async_trait]
+pub trait DecisionRuleRepository: Send + Sync {
+    /// Active rules, loaded into the rules engine at startup
+    async fn find_active(&self) -> AppResult<Vec<DecisionRule>>;
+    /// Record one evaluation in `rule_execution_history` for backtests
+    async fn record_execution(
+        &self,
+        rule: &DecisionRule,
+        context: &RuleContext,
+        result: &RuleResult,
+    ) -> AppResult<()>;
+}
@@ -0,0 +1,32 @@
+//! EHR Appointment Reminder Repository Trait
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::state_machine::{ReminderChannel, ReminderNotification};
+use crate::shared::AppResult;
+
+/// Confirmed appointment due a pre-appointment reminder
+#[derive(Debug, Clone)]
+pub struct UpcomingAppointment {
+    pub appointment_ien: i64,
+    pub patient_ien: i64,
+    pub scheduled_time: DateTime<Utc>,
+    /// The patient's preferred reminder channel
+    pub channel: ReminderChannel,
+}
+
+/// Pre-appointment reminders
+#[async_trait]
+pub trait EhrAppointmentReminderRepository: Send + Sync {
+    /// Confirmed appointments scheduled after `start` and up to `end`, across
+    /// all organizations, that have not been reminded yet
+    async fn find_confirmed_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> AppResult<Vec<UpcomingAppointment>>;
+
+    /// Record that a reminder went out
+    async fn mark_reminder_sent(&self, notification: &ReminderNotification) -> AppResult<()>;
+}
@@ -1,7 +1,8 @@
 //! EHR Document Repository Trait
 
 use async_trait::async_trait;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
 use uuid::Uuid;
 
 use crate::domain::entities::ehr::{EhrDocument, DocumentType, DocumentStatus};
@@ -37,9 +38,22 @@ pub struct DocumentSearchCriteria {
     pub unsigned_only: bool,
 }
 
+/// Document body text, read apart from document metadata
+///
+/// Split out of [`EhrDocumentRepository`] so the YottaDB API can serve it
+/// from the TIU globals without the rest of the repository.
+#[async_trait]
+pub trait EhrDocumentContentRepository: Send + Sync {
+    /// Get only a document's body text, by IEN
+    /// Read from the `^TIU(8925,{id},"TEXT",*)` word-processing nodes so list
+    /// and metadata queries never load large note bodies. `None` when there
+    /// is no such document.
+    async fn get_content(&self, document_id: i64) -> AppResult<Option<String>>;
+}
+
 /// EHR Document Repository Trait
 #[async_trait]
-pub trait EhrDocumentRepository: Send + Sync {
+pub trait EhrDocumentRepository: EhrDocumentContentRepository {
     /// Create a new document
     async fn create(&self, document: EhrDocument) -> AppResult<EhrDocument>;
 
@@ -103,3 +117,49 @@ pub trait EhrDocumentRepository: Send + Sync {
     /// Get next IEN
     async fn next_ien(&self, organization_id: Uuid) -> AppResult<i64>;
 }
+
+/// A ^TIU(8925) document as copied into the full-text index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedDocument {
+    pub ien: i64,
+    pub patient_ien: i64,
+    /// e.g. `progress_note`, as reported by the YottaDB API
+    pub document_type: String,
+    pub title: String,
+    /// Note text followed by any addenda
+    pub content: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A document matching a full-text search
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentSearchResult {
+    pub ien: i64,
+    pub title: String,
+    pub patient_ien: i64,
+    pub created_at: DateTime<Utc>,
+    /// About 150 characters around the first match, matched terms wrapped in `<mark>`
+    pub snippet: String,
+}
+
+/// Full-text index of clinical document content (PostgreSQL)
+///
+/// Kept apart from [`EhrDocumentRepository`], whose `search` filters on
+/// document metadata; documents are copied in from YottaDB by
+/// `DocumentSyncTask`.
+#[async_trait]
+pub trait EhrDocumentSearchRepository: Send + Sync {
+    /// Search document titles and text, best matches first
+    ///
+    /// `query` uses web search syntax: quoted phrases, `or`, and `-` to exclude.
+    async fn search(
+        &self,
+        query: &str,
+        patient_ien: Option<i64>,
+        doc_type: Option<&str>,
+    ) -> AppResult<Vec<DocumentSearchResult>>;
+
+    /// Insert or replace a document in the index
+    async fn upsert(&self, document: &IndexedDocument) -> AppResult<()>;
+}
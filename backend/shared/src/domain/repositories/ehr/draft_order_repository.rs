@@ -0,0 +1,17 @@
+//! EHR Draft Order Repository Trait
+
+use async_trait::async_trait;
+use std::time::Duration;
+
+use crate::shared::AppResult;
+
+/// Unsigned orders in the VistA Orders file (^OR(100)), by IEN
+#[async_trait]
+pub trait EhrDraftOrderRepository: Send + Sync {
+    /// Unsigned (pending) orders entered more than `older_than` ago, across
+    /// all patients
+    async fn find_stale_drafts(&self, older_than: Duration) -> AppResult<Vec<i64>>;
+
+    /// Cancel an unsigned order, returning `false` if it is no longer pending
+    async fn cancel_draft(&self, order_ien: i64, reason: &str) -> AppResult<bool>;
+}
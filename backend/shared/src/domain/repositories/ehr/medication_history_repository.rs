@@ -0,0 +1,51 @@
+//! EHR Medication History Repository Trait
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::shared::AppResult;
+
+/// Outpatient medication order (^PS(52))
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MedicationRecord {
+    pub ien: i64,
+    pub patient_ien: i64,
+    pub drug_name: String,
+    pub drug_code: Option<String>,
+    pub dose: String,
+    pub route: String,
+    pub frequency: String,
+    /// `YYYY-MM-DD`
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub prescriber_ien: Option<i64>,
+    /// `active`, `discontinued`, `completed` or `on_hold`
+    pub status: String,
+    pub instructions: Option<String>,
+}
+
+/// Visit (^AUPNVSIT), reduced to what is needed to order a patient's visits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisitRecord {
+    pub ien: i64,
+    pub patient_ien: i64,
+    /// `YYYY-MM-DD`
+    pub visit_date: String,
+    /// `active`, `completed` or `cancelled`
+    pub status: String,
+}
+
+/// Medication and visit history backing medication reconciliation
+#[async_trait]
+pub trait EhrMedicationHistoryRepository: Send + Sync {
+    /// Get a visit by IEN
+    async fn find_visit(&self, visit_ien: i64) -> AppResult<Option<VisitRecord>>;
+
+    /// Every visit of a patient
+    async fn find_patient_visits(&self, patient_ien: i64) -> AppResult<Vec<VisitRecord>>;
+
+    /// Every medication order of a patient, whatever its status
+    async fn find_patient_medications(&self, patient_ien: i64) -> AppResult<Vec<MedicationRecord>>;
+}
@@ -11,7 +11,12 @@ pub mod vital_repository;
 pub mod lab_result_repository;
 pub mod document_repository;
 pub mod order_repository;
+pub mod draft_order_repository;
 pub mod appointment_repository;
+pub mod appointment_reminder_repository;
+pub mod patient_summary_repository;
+pub mod medication_history_repository;
+pub mod patient_merge_repository;
 pub mod drug_repository;
 
 pub use patient_repository::EhrPatientRepository;
@@ -21,9 +26,18 @@ pub use medication_repository::EhrMedicationRepository;
 pub use allergy_repository::EhrAllergyRepository;
 pub use vital_repository::EhrVitalRepository;
 pub use lab_result_repository::EhrLabResultRepository;
-pub use document_repository::EhrDocumentRepository;
+pub use document_repository::{
+    EhrDocumentContentRepository, EhrDocumentRepository, EhrDocumentSearchRepository, DocumentSearchResult, IndexedDocument,
+};
 pub use order_repository::EhrOrderRepository;
+pub use draft_order_repository::EhrDraftOrderRepository;
 pub use appointment_repository::EhrAppointmentRepository;
+pub use appointment_reminder_repository::{EhrAppointmentReminderRepository, UpcomingAppointment};
+pub use patient_summary_repository::EhrPatientSummaryRepository;
+pub use medication_history_repository::{
+    EhrMedicationHistoryRepository, MedicationRecord, VisitRecord,
+};
+pub use patient_merge_repository::{EhrPatientMergeRepository, PatientFile, PatientMerge};
 pub use drug_repository::{
     DrugCatalogRepository, DrugScheduleRepository, DrugRepository,
     DrugInteractionRepository, DrugContraindicationRepository,
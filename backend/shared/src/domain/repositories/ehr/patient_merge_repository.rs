@@ -0,0 +1,109 @@
+//! EHR Patient Merge Repository Trait
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::shared::AppResult;
+
+/// Clinical file whose entries point at a patient and are indexed by
+/// patient in its "C" cross-reference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatientFile {
+    /// ^AUPNVSIT
+    Visits,
+    /// ^AUPNPROB
+    Problems,
+    /// ^GMRA
+    Allergies,
+    /// ^GMR(120.5)
+    Vitals,
+    /// ^PS(52)
+    Medications,
+    /// ^LR(63)
+    Labs,
+    /// ^OR(100)
+    Orders,
+    /// ^TIU(8925)
+    Documents,
+    /// ^SD(44)
+    Appointments,
+}
+
+impl PatientFile {
+    /// Every file moved by a merge
+    pub const ALL: [PatientFile; 9] = [
+        PatientFile::Visits,
+        PatientFile::Problems,
+        PatientFile::Allergies,
+        PatientFile::Vitals,
+        PatientFile::Medications,
+        PatientFile::Labs,
+        PatientFile::Orders,
+        PatientFile::Documents,
+        PatientFile::Appointments,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PatientFile::Visits => "visits",
+            PatientFile::Problems => "problems",
+            PatientFile::Allergies => "allergies",
+            PatientFile::Vitals => "vitals",
+            PatientFile::Medications => "medications",
+            PatientFile::Labs => "labs",
+            PatientFile::Orders => "orders",
+            PatientFile::Documents => "documents",
+            PatientFile::Appointments => "appointments",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|file| file.as_str() == value)
+    }
+}
+
+/// Merge of a duplicate patient into a survivor, kept in ^DPT(merged_ien,"MERGE")
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatientMerge {
+    /// Duplicate record, hidden from lists while merged
+    pub merged_ien: i64,
+    /// Survivor now holding the duplicate's records
+    pub merged_into_ien: i64,
+    pub merged_by: Uuid,
+    pub merged_at: DateTime<Utc>,
+    /// Entries moved to the survivor, so an unmerge moves back only these
+    pub moved_records: Vec<(PatientFile, i64)>,
+}
+
+/// Patient records and merge pointers backing patient merge/unmerge
+#[async_trait]
+pub trait EhrPatientMergeRepository: Send + Sync {
+    /// Whether ^DPT(patient_ien,0) exists
+    async fn patient_exists(&self, patient_ien: i64) -> AppResult<bool>;
+
+    /// Merge recorded for a patient merged into another
+    async fn find_merge(&self, merged_ien: i64) -> AppResult<Option<PatientMerge>>;
+
+    /// IENs of a patient's entries in `file`
+    async fn find_record_iens(&self, file: PatientFile, patient_ien: i64) -> AppResult<Vec<i64>>;
+
+    /// Point an entry at `to_patient_ien` and re-index it
+    ///
+    /// Must be safe to repeat, so an interrupted merge or unmerge can be run again.
+    async fn reassign_record(
+        &self,
+        file: PatientFile,
+        record_ien: i64,
+        from_patient_ien: i64,
+        to_patient_ien: i64,
+    ) -> AppResult<()>;
+
+    /// Mark `merge.merged_ien` as merged and record what was moved
+    async fn save_merge(&self, merge: &PatientMerge) -> AppResult<()>;
+
+    /// Clear the merge pointer, restoring the patient
+    async fn delete_merge(&self, merged_ien: i64) -> AppResult<()>;
+}
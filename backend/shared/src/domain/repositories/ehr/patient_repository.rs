@@ -63,6 +63,13 @@ pub trait EhrPatientRepository: Send + Sync {
     /// Create a new patient
     async fn create(&self, patient: EhrPatient) -> AppResult<EhrPatient>;
 
+    /// Create several patients in one transaction
+    ///
+    /// Returns one result per patient, in order. A patient that fails to
+    /// insert (e.g. a duplicate MRN) is reported in its slot without rolling
+    /// back the others.
+    async fn create_batch(&self, patients: Vec<EhrPatient>) -> AppResult<Vec<AppResult<EhrPatient>>>;
+
     /// Find patient by ID
     async fn find_by_id(&self, id: Uuid, organization_id: Uuid) -> AppResult<Option<EhrPatient>>;
 
@@ -72,6 +79,12 @@ pub trait EhrPatientRepository: Send + Sync {
     /// Find patient by MRN
     async fn find_by_mrn(&self, mrn: &str, organization_id: Uuid) -> AppResult<Option<EhrPatient>>;
 
+    /// Find patients by the last four digits of their SSN
+    ///
+    /// Only the last four are ever searched (HIPAA minimum necessary), and every
+    /// call is recorded in `patient_access_log`.
+    async fn find_by_ssn(&self, ssn_last4: &str, organization_id: Uuid) -> AppResult<Vec<EhrPatient>>;
+
     /// Update patient
     async fn update(&self, patient: EhrPatient) -> AppResult<EhrPatient>;
 
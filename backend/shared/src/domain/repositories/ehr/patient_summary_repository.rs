@@ -0,0 +1,27 @@
+//! EHR Patient Summary Repository Trait
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use crate::shared::AppResult;
+
+/// Per-patient counts backing the dashboard summary
+///
+/// Each method is an independent query so callers can run them concurrently.
+#[async_trait]
+pub trait EhrPatientSummaryRepository: Send + Sync {
+    /// Count active problems on the problem list
+    async fn count_active_problems(&self, patient_ien: i64) -> AppResult<u32>;
+
+    /// Count active medications
+    async fn count_current_medications(&self, patient_ien: i64) -> AppResult<u32>;
+
+    /// Count allergies that have not been inactivated
+    async fn count_known_allergies(&self, patient_ien: i64) -> AppResult<u32>;
+
+    /// Count lab results still awaiting a final result
+    async fn count_pending_labs(&self, patient_ien: i64) -> AppResult<u32>;
+
+    /// Count scheduled appointments on or after `from`
+    async fn count_upcoming_appointments(&self, patient_ien: i64, from: NaiveDate) -> AppResult<u32>;
+}
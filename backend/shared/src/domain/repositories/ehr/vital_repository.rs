@@ -1,7 +1,7 @@
 //! EHR Vital Signs Repository Trait
 
 use async_trait::async_trait;
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
 use crate::domain::entities::ehr::{EhrVital, VitalType};
@@ -79,6 +79,15 @@ pub trait EhrVitalRepository: Send + Sync {
         limit: u32,
     ) -> AppResult<Vec<EhrVital>>;
 
+    /// Get readings of one type taken since `since`, oldest first
+    async fn find_recent_by_type(
+        &self,
+        patient_id: Uuid,
+        organization_id: Uuid,
+        vital_type: VitalType,
+        since: DateTime<Utc>,
+    ) -> AppResult<Vec<EhrVital>>;
+
     /// Get next IEN
     async fn next_ien(&self, organization_id: Uuid) -> AppResult<i64>;
 }
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use crate::shared::AppResult;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Response recorded for an `Idempotency-Key`, replayed on retries
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyRecord {
+    pub key: Uuid,
+    /// User who made the original request; only they may replay it
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    /// `None` while the original request is still running
+    pub status: Option<u16>,
+    pub body: Vec<u8>,
+}
+
+impl IdempotencyRecord {
+    /// Whether the key is reserved by a request that has not finished yet
+    pub fn is_pending(&self) -> bool {
+        self.status.is_none()
+    }
+}
+
+#[async_trait]
+pub trait IdempotencyKeyRepository: Send + Sync {
+    async fn find(&self, key: Uuid) -> AppResult<Option<IdempotencyRecord>>;
+    /// Reserve `key` for `user_id` before running the request, returning
+    /// whether it was taken
+    ///
+    /// A key held by a reservation from before `stale_before` (its request
+    /// never finished) or a response from before `expired_before` is taken over.
+    async fn reserve(
+        &self,
+        key: Uuid,
+        user_id: Uuid,
+        stale_before: DateTime<Utc>,
+        expired_before: DateTime<Utc>,
+    ) -> AppResult<bool>;
+    /// Record the response of the request holding the reservation
+    async fn complete(&self, record: &IdempotencyRecord) -> AppResult<()>;
+    /// Drop an unfinished reservation so the request can be retried
+    async fn release(&self, key: Uuid) -> AppResult<()>;
+}
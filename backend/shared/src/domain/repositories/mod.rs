@@ -1,4 +1,7 @@
 pub mod user_repository;
+pub mod audit_log_repository;
+pub mod decision_rule_repository;
+pub mod idempotency_key_repository;
 pub mod key_repository;
 pub mod relationship_repository;
 pub mod role_repository;
@@ -9,10 +12,15 @@ pub mod group_repository;
 pub mod ui_entity_repository;
 pub mod session_repository;
 pub mod request_log_repository;
+pub mod user_activity_event_repository;
+pub mod user_provisioning_checklist_repository;
 pub mod visual_workflow_repository;
 pub mod ehr;
 
 pub use user_repository::UserRepository;
+pub use audit_log_repository::{AuditLogEntry, AuditLogRepository};
+pub use decision_rule_repository::DecisionRuleRepository;
+pub use idempotency_key_repository::{IdempotencyKeyRepository, IdempotencyRecord};
 pub use key_repository::KeyRepository;
 pub use relationship_repository::RelationshipRepository;
 pub use role_repository::RoleRepository;
@@ -23,5 +31,7 @@ pub use group_repository::GroupRepository;
 pub use ui_entity_repository::UiEntityRepository;
 pub use session_repository::SessionRepository;
 pub use request_log_repository::RequestLogRepository;
+pub use user_activity_event_repository::UserActivityEventRepository;
+pub use user_provisioning_checklist_repository::UserProvisioningChecklistRepository;
 pub use visual_workflow_repository::VisualWorkflowRepository;
 
@@ -7,6 +7,8 @@ use uuid::Uuid;
 pub trait PermissionRepository: Send + Sync {
     async fn create(&self, permission: Permission) -> AppResult<Permission>;
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Permission>>;
+    /// Permissions with any of the given IDs, in one query; unknown IDs are skipped
+    async fn find_by_ids(&self, ids: &[Uuid]) -> AppResult<Vec<Permission>>;
     async fn find_by_name(&self, name: &str) -> AppResult<Option<Permission>>;
     async fn find_by_resource_and_action(&self, resource: &str, action: &str) -> AppResult<Option<Permission>>;
     async fn list(&self) -> AppResult<Vec<Permission>>;
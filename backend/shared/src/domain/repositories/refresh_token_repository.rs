@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use crate::shared::AppResult;
 use uuid::Uuid;
 use chrono::DateTime;
+use chrono::Duration;
 use chrono::Utc;
 
 #[derive(Debug, Clone)]
@@ -13,6 +14,8 @@ pub struct RefreshToken {
     pub created_at: DateTime<Utc>,
     pub revoked_at: Option<DateTime<Utc>>,
     pub is_revoked: bool,
+    /// SHA-256 of the User-Agent and Accept-Language of the client that logged in
+    pub device_fingerprint: String,
 }
 
 #[async_trait]
@@ -20,8 +23,14 @@ pub trait RefreshTokenRepository: Send + Sync {
     async fn create(&self, token: RefreshToken) -> AppResult<RefreshToken>;
     async fn find_by_token_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>>;
     async fn find_by_user_id(&self, user_id: Uuid) -> AppResult<Vec<RefreshToken>>;
+    /// Record a use of a valid token, sliding its expiry to `extend_by` from now
+    /// but never past `not_after`. Returns the updated token, or `None` if the
+    /// token is unknown, revoked or expired.
+    async fn use_token(&self, token_hash: &str, extend_by: Duration, not_after: DateTime<Utc>) -> AppResult<Option<RefreshToken>>;
     async fn revoke_token(&self, token_hash: &str) -> AppResult<()>;
     async fn revoke_all_user_tokens(&self, user_id: Uuid) -> AppResult<()>;
+    /// Revoke every active token of a user, returning how many were revoked
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> AppResult<u64>;
     async fn delete_expired_tokens(&self) -> AppResult<u64>;
 }
 
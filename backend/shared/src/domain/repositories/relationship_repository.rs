@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::domain::entities::Relationship;
+use crate::domain::entities::{Relationship, RelationshipKey, RelationshipWrite, WriteResponse};
 use crate::shared::AppResult;
 use uuid::Uuid;
 
@@ -16,6 +16,21 @@ pub trait RelationshipRepository: Send + Sync {
     async fn delete_by_tuple(&self, user: &str, relation: &str, object: &str) -> AppResult<()>;
     async fn soft_delete(&self, id: Uuid, deleted_by: Option<Uuid>) -> AppResult<()>;
     async fn list_all(&self) -> AppResult<Vec<Relationship>>;
+    /// Role assignments (`has_role`) of `user` that are active and inside their validity window
+    async fn find_roles_for_user(&self, user: &str) -> AppResult<Vec<Relationship>>;
+    /// Permanently delete relationships whose `expires_at` has passed; returns how many were deleted
+    async fn delete_expired(&self) -> AppResult<u64>;
+
+    // Bulk operations (Zanzibar Write API)
+    /// Apply all writes and deletes in a single transaction; returns a zookie for the commit
+    async fn write_tuples(&self, writes: Vec<RelationshipWrite>) -> AppResult<WriteResponse>;
+    /// Delete tuples in a single transaction
+    async fn delete_tuples(&self, deletes: Vec<RelationshipKey>) -> AppResult<()>;
+    /// Insert tuples in a single transaction, leaving existing ones untouched;
+    /// returns how many were newly inserted
+    async fn write_batch(&self, tuples: Vec<Relationship>) -> AppResult<u64>;
+    /// Delete tuples in a single transaction; returns how many were deleted
+    async fn delete_batch(&self, keys: Vec<RelationshipKey>) -> AppResult<u64>;
     
     // Organization-scoped methods
     async fn find_by_user_and_org(&self, user: &str, organization_id: Uuid) -> AppResult<Vec<Relationship>>;
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::domain::entities::Role;
+use crate::domain::entities::{Permission, Role};
 use crate::shared::AppResult;
 use uuid::Uuid;
 
@@ -13,5 +13,9 @@ pub trait RoleRepository: Send + Sync {
     async fn remove_permission_from_role(&self, role_id: Uuid, permission_id: Uuid) -> AppResult<()>;
     async fn get_role_permissions(&self, role_id: Uuid) -> AppResult<Vec<Uuid>>;
     async fn get_user_roles(&self, user_id: Uuid) -> AppResult<Vec<Role>>;
+    /// Set (or clear) the parent role this role inherits permissions from
+    async fn set_parent_role(&self, role_id: Uuid, parent_role_id: Option<Uuid>) -> AppResult<()>;
+    /// Permissions granted to the role directly or through any ancestor role
+    async fn get_effective_permissions(&self, role_id: Uuid) -> AppResult<Vec<Permission>>;
 }
 
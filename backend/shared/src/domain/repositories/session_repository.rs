@@ -11,6 +11,7 @@ pub trait SessionRepository: Send + Sync {
     async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Session>>;
     async fn find_active_by_user(&self, user_id: Uuid) -> AppResult<Vec<Session>>;
     async fn find_active_by_user_and_app(&self, user_id: Uuid, app_type: &str) -> AppResult<Vec<Session>>;
+    async fn count_active_by_user(&self, user_id: Uuid) -> AppResult<u64>;
     async fn update(&self, session: Session) -> AppResult<Session>;
     async fn end_session(&self, id: Uuid, ended_at: DateTime<Utc>) -> AppResult<()>;
     async fn cleanup_expired(&self) -> AppResult<u64>;
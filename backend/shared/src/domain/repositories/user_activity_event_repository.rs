@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+use crate::domain::entities::UserActivityEvent;
+use crate::shared::AppResult;
+
+#[async_trait]
+pub trait UserActivityEventRepository: Send + Sync {
+    async fn create(&self, event: UserActivityEvent) -> AppResult<()>;
+    async fn find_by_patient(&self, patient_id: i64, limit: u32) -> AppResult<Vec<UserActivityEvent>>;
+}
@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+use crate::domain::entities::UserProvisioningChecklist;
+use crate::shared::AppResult;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait UserProvisioningChecklistRepository: Send + Sync {
+    async fn find_by_user_id(&self, user_id: Uuid) -> AppResult<Option<UserProvisioningChecklist>>;
+    /// Insert or replace the checklist for its user
+    async fn save(&self, checklist: &UserProvisioningChecklist) -> AppResult<()>;
+}
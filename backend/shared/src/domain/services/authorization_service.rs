@@ -1,6 +1,14 @@
 use async_trait::async_trait;
+use std::collections::HashSet;
+use crate::domain::entities::Permission;
 use crate::shared::AppResult;
 
+/// Separator of the permission name hierarchy (`domain:resource:action`)
+pub const PERMISSION_SEPARATOR: char = ':';
+
+/// Segment matching any segment of a permission name
+pub const PERMISSION_WILDCARD: &str = "*";
+
 #[async_trait]
 pub trait AuthorizationService: Send + Sync {
     /// Check if user has permission on object
@@ -20,3 +28,109 @@ pub trait AuthorizationService: Send + Sync {
     async fn can_access(&self, user_id: &str, resource: &str, action: &str) -> AppResult<bool>;
 }
 
+/// Resolves wildcard permissions over the colon-separated name hierarchy
+///
+/// A `*` segment matches exactly one segment, except in last position where
+/// it matches everything below: `ehr:*` covers `ehr:patients:view`, while
+/// `*:view` covers `patients:view` and `orders:view` but not `patients:view:full`.
+pub struct PermissionExpander;
+
+impl PermissionExpander {
+    /// Whether `name` contains a wildcard segment
+    pub fn is_wildcard(name: &str) -> bool {
+        name.split(PERMISSION_SEPARATOR).any(|segment| segment == PERMISSION_WILDCARD)
+    }
+
+    /// Whether the (possibly wildcard) `pattern` grants the permission `name`
+    pub fn matches(pattern: &str, name: &str) -> bool {
+        let pattern: Vec<&str> = pattern.split(PERMISSION_SEPARATOR).collect();
+        let name: Vec<&str> = name.split(PERMISSION_SEPARATOR).collect();
+        let Some((last, prefix)) = pattern.split_last() else {
+            return false;
+        };
+
+        let segments_match = |pattern: &[&str], name: &[&str]| {
+            pattern.iter().zip(name).all(|(p, n)| *p == PERMISSION_WILDCARD || p == n)
+        };
+        if *last == PERMISSION_WILDCARD {
+            name.len() > prefix.len() && segments_match(prefix, &name[..prefix.len()])
+        } else {
+            name.len() == pattern.len() && segments_match(&pattern, &name)
+        }
+    }
+
+    /// Replace each wildcard in `raw` with the permissions of `all_permissions` it matches
+    ///
+    /// Non-wildcard permissions are kept as they are. The result keeps the
+    /// order of `raw` and holds each permission (by id) once.
+    pub fn expand(raw: &[Permission], all_permissions: &[Permission]) -> Vec<Permission> {
+        let mut seen = HashSet::new();
+        let mut expanded = Vec::new();
+        for permission in raw {
+            if !Self::is_wildcard(&permission.name) {
+                if seen.insert(permission.id) {
+                    expanded.push(permission.clone());
+                }
+                continue;
+            }
+            for candidate in all_permissions {
+                if !Self::is_wildcard(&candidate.name)
+                    && Self::matches(&permission.name, &candidate.name)
+                    && seen.insert(candidate.id)
+                {
+                    expanded.push(candidate.clone());
+                }
+            }
+        }
+        expanded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permission(name: &str) -> Permission {
+        let (resource, action) = name.rsplit_once(PERMISSION_SEPARATOR).unwrap_or((name, ""));
+        Permission::new(name.to_string(), resource.to_string(), action.to_string(), None)
+    }
+
+    fn catalog() -> Vec<Permission> {
+        ["ehr:patients:read", "ehr:notes:write", "lab:read", "pharmacy:read", "pharmacy:dispense", "patients:view:full"]
+            .into_iter()
+            .map(permission)
+            .collect()
+    }
+
+    fn names(permissions: &[Permission]) -> Vec<&str> {
+        permissions.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn domain_wildcard_expands_to_everything_below_it() {
+        let all = catalog();
+        let expanded = PermissionExpander::expand(&[permission("ehr:*")], &all);
+        assert_eq!(names(&expanded), vec!["ehr:patients:read", "ehr:notes:write"]);
+    }
+
+    #[test]
+    fn action_wildcard_matches_across_domains() {
+        let all = catalog();
+        let expanded = PermissionExpander::expand(&[permission("*:read")], &all);
+        assert_eq!(names(&expanded), vec!["lab:read", "pharmacy:read"]);
+        assert!(PermissionExpander::matches("*:*:read", "ehr:patients:read"));
+        assert!(!PermissionExpander::matches("*:view", "patients:view:full"));
+    }
+
+    #[test]
+    fn non_wildcard_permissions_are_returned_unchanged() {
+        let all = catalog();
+        let raw = [permission("billing:invoice:create"), all[2].clone(), permission("lab:*")];
+        // Already held permissions are not duplicated by an overlapping wildcard
+        let expanded = PermissionExpander::expand(&raw, &all);
+
+        assert_eq!(names(&expanded), vec!["billing:invoice:create", "lab:read"]);
+        assert_eq!(expanded[0].id, raw[0].id);
+        assert_eq!(expanded[1].id, raw[1].id);
+    }
+}
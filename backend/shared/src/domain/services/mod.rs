@@ -3,10 +3,16 @@ pub mod encryption_service;
 pub mod authorization_service;
 pub mod sync_service;
 pub mod compliance_service;
+pub mod vital_trend;
+pub mod provider_availability;
+pub mod polypharmacy;
 
 pub use auth_service::AuthService;
 pub use encryption_service::EncryptionService;
-pub use authorization_service::AuthorizationService;
+pub use authorization_service::{AuthorizationService, PermissionExpander};
 pub use sync_service::SyncService;
 pub use compliance_service::{ComplianceService, ComplianceDetector, ApplicableRegulation, LocationInput};
+pub use provider_availability::ProviderAvailabilityService;
+pub use polypharmacy::{score_polypharmacy, DrugInteractionDto, PolypharmacyScore, RiskLevel};
+pub use vital_trend::{TrendDirection, TrendResult, VitalReading, VitalTrendCalculator};
 
@@ -0,0 +1,156 @@
+//! Polypharmacy Risk Scoring
+//!
+//! Combines a regimen's drug-drug interactions and its size into one risk
+//! level. Major and contraindicated interactions score 3 points, moderate 2,
+//! minor (or unknown) 1, and each medication beyond
+//! [`POLYPHARMACY_THRESHOLD`] adds 1.
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::entities::ehr::InteractionSeverity;
+
+/// Number of medications a regimen can have before each extra one adds risk
+pub const POLYPHARMACY_THRESHOLD: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Moderate,
+    High,
+    Critical,
+}
+
+impl RiskLevel {
+    /// Level of a weighted score: 0-2 low, 3-5 moderate, 6-9 high, 10+ critical
+    pub fn from_points(points: u32) -> Self {
+        match points {
+            0..=2 => RiskLevel::Low,
+            3..=5 => RiskLevel::Moderate,
+            6..=9 => RiskLevel::High,
+            _ => RiskLevel::Critical,
+        }
+    }
+}
+
+/// Interaction between two of the patient's medications
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrugInteractionDto {
+    pub drug_a: String,
+    pub drug_b: String,
+    pub severity: InteractionSeverity,
+    pub description: String,
+}
+
+/// Overall interaction risk of a medication regimen
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolypharmacyScore {
+    /// Number of medications scored, capped at 255
+    pub count: u8,
+    pub interaction_pairs: Vec<DrugInteractionDto>,
+    pub overall_risk: RiskLevel,
+    /// What added to the score, e.g. `"2 major interactions"`
+    pub contributing_factors: Vec<String>,
+}
+
+fn interaction_points(severity: &InteractionSeverity) -> u32 {
+    match severity {
+        InteractionSeverity::Contraindicated | InteractionSeverity::Major => 3,
+        InteractionSeverity::Moderate => 2,
+        InteractionSeverity::Minor | InteractionSeverity::Unknown => 1,
+    }
+}
+
+/// Score a regimen of `medication_count` drugs with the given interactions
+/// between them
+pub fn score_polypharmacy(medication_count: usize, interaction_pairs: Vec<DrugInteractionDto>) -> PolypharmacyScore {
+    let mut points = 0;
+    let mut contributing_factors = Vec::new();
+
+    for (severity, label) in [
+        (InteractionSeverity::Contraindicated, "contraindicated"),
+        (InteractionSeverity::Major, "major"),
+        (InteractionSeverity::Moderate, "moderate"),
+        (InteractionSeverity::Minor, "minor"),
+        (InteractionSeverity::Unknown, "unrated"),
+    ] {
+        let count = interaction_pairs.iter().filter(|pair| pair.severity == severity).count();
+        if count == 0 {
+            continue;
+        }
+        points += interaction_points(&severity) * u32::try_from(count).unwrap_or(u32::MAX);
+        let noun = if count == 1 { "interaction" } else { "interactions" };
+        contributing_factors.push(format!("{} {} {}", count, label, noun));
+    }
+
+    let extra_medications = medication_count.saturating_sub(POLYPHARMACY_THRESHOLD);
+    if extra_medications > 0 {
+        points += u32::try_from(extra_medications).unwrap_or(u32::MAX);
+        contributing_factors.push(format!(
+            "{} medications, {} above the polypharmacy threshold of {}",
+            medication_count, extra_medications, POLYPHARMACY_THRESHOLD
+        ));
+    }
+
+    PolypharmacyScore {
+        count: u8::try_from(medication_count).unwrap_or(u8::MAX),
+        interaction_pairs,
+        overall_risk: RiskLevel::from_points(points),
+        contributing_factors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(drug_a: &str, drug_b: &str, severity: InteractionSeverity) -> DrugInteractionDto {
+        DrugInteractionDto {
+            drug_a: drug_a.to_string(),
+            drug_b: drug_b.to_string(),
+            severity,
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_three_major_interactions_on_seven_medications_is_critical() {
+        let pairs = vec![
+            pair("Warfarin", "Aspirin", InteractionSeverity::Major),
+            pair("Warfarin", "Ibuprofen", InteractionSeverity::Major),
+            pair("Warfarin", "Amiodarone", InteractionSeverity::Major),
+        ];
+        let score = score_polypharmacy(7, pairs);
+
+        assert_eq!(score.count, 7);
+        assert_eq!(score.overall_risk, RiskLevel::Critical);
+        assert_eq!(
+            score.contributing_factors,
+            vec![
+                "3 major interactions".to_string(),
+                "7 medications, 2 above the polypharmacy threshold of 5".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_four_medications_without_interactions_is_low() {
+        let score = score_polypharmacy(4, Vec::new());
+        assert_eq!(score.overall_risk, RiskLevel::Low);
+        assert!(score.contributing_factors.is_empty());
+    }
+
+    #[test]
+    fn test_risk_level_boundaries() {
+        assert_eq!(score_polypharmacy(8, Vec::new()).overall_risk, RiskLevel::Moderate);
+        let moderate_pairs = vec![
+            pair("A", "B", InteractionSeverity::Moderate),
+            pair("C", "D", InteractionSeverity::Moderate),
+            pair("E", "F", InteractionSeverity::Minor),
+        ];
+        assert_eq!(score_polypharmacy(6, moderate_pairs).overall_risk, RiskLevel::High);
+        assert_eq!(RiskLevel::from_points(10), RiskLevel::Critical);
+    }
+}
@@ -0,0 +1,144 @@
+//! Provider Availability
+//!
+//! Answers booking questions over a set of loaded provider schedules:
+//! whether a period clashes with an existing appointment, and when the
+//! next free period of a given length starts.
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use crate::domain::entities::ehr::ProviderSchedule;
+
+/// Availability lookups over provider schedules
+#[derive(Debug, Clone, Default)]
+pub struct ProviderAvailabilityService {
+    schedules: Vec<ProviderSchedule>,
+}
+
+impl ProviderAvailabilityService {
+    /// Create the service over the schedules loaded for the dates of interest.
+    ///
+    /// Days without a schedule are treated as unavailable.
+    pub fn new(mut schedules: Vec<ProviderSchedule>) -> Self {
+        schedules.sort_by_key(|s| (s.provider_ien, s.date));
+        Self { schedules }
+    }
+
+    fn schedule(&self, provider_ien: i64, date: NaiveDate) -> Option<&ProviderSchedule> {
+        self.schedules
+            .iter()
+            .find(|s| s.provider_ien == provider_ien && s.date == date)
+    }
+
+    /// Start of the first free slot on or after `after` that fits `duration_minutes`
+    pub fn find_next_available(
+        &self,
+        provider_ien: i64,
+        after: NaiveDate,
+        duration_minutes: i32,
+    ) -> Option<NaiveDateTime> {
+        self.schedules
+            .iter()
+            .filter(|s| s.provider_ien == provider_ien && s.date >= after)
+            .find_map(|s| {
+                s.slot_starts()
+                    .into_iter()
+                    .find(|&start| s.is_free(start, duration_minutes))
+                    .map(|start| s.date.and_time(start))
+            })
+    }
+
+    /// IEN of an appointment overlapping the given period, if any
+    pub fn find_conflict(
+        &self,
+        provider_ien: i64,
+        start: NaiveDateTime,
+        duration_minutes: i32,
+    ) -> Option<i64> {
+        self.schedule(provider_ien, start.date())?
+            .conflict(start.time(), duration_minutes)
+            .map(|a| a.appointment_ien)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::ehr::{BookedAppointment, ScheduleBlock, SlotStatus};
+    use chrono::NaiveTime;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap_or_default()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap_or_default()
+    }
+
+    fn booked(ien: i64, start: NaiveTime, duration_minutes: i32) -> BookedAppointment {
+        BookedAppointment { appointment_ien: ien, start, duration_minutes }
+    }
+
+    #[test]
+    fn same_slot_is_rejected_for_second_booking() {
+        let mut schedule = ProviderSchedule::new(7, date(3));
+        schedule.appointments.push(booked(101, time(9, 0), 30));
+        let service = ProviderAvailabilityService::new(vec![schedule]);
+
+        assert_eq!(service.find_conflict(7, date(3).and_time(time(9, 0)), 30), Some(101));
+        assert_eq!(service.find_conflict(7, date(3).and_time(time(9, 15)), 30), Some(101));
+        assert_eq!(service.find_conflict(7, date(3).and_time(time(8, 45)), 30), Some(101));
+    }
+
+    #[test]
+    fn back_to_back_slots_do_not_conflict() {
+        let mut schedule = ProviderSchedule::new(7, date(3));
+        schedule.appointments.push(booked(101, time(9, 0), 30));
+        let service = ProviderAvailabilityService::new(vec![schedule]);
+
+        assert_eq!(service.find_conflict(7, date(3).and_time(time(9, 30)), 30), None);
+        assert_eq!(service.find_conflict(7, date(3).and_time(time(8, 30)), 30), None);
+        assert_eq!(service.find_conflict(8, date(3).and_time(time(9, 0)), 30), None);
+    }
+
+    #[test]
+    fn next_available_skips_booked_and_blocked_slots() {
+        let mut schedule = ProviderSchedule::new(7, date(3));
+        schedule.appointments.push(booked(101, time(8, 0), 30));
+        schedule.blocked.push(ScheduleBlock { start: time(8, 30), duration_minutes: 60 });
+        let service = ProviderAvailabilityService::new(vec![schedule]);
+
+        assert_eq!(service.find_next_available(7, date(3), 30), Some(date(3).and_time(time(9, 30))));
+    }
+
+    #[test]
+    fn next_available_rolls_over_to_the_next_scheduled_day() {
+        let mut full = ProviderSchedule::new(7, date(3));
+        full.start_time = time(9, 0);
+        full.end_time = time(10, 0);
+        full.appointments.push(booked(101, time(9, 0), 60));
+        let next = ProviderSchedule::new(7, date(4));
+        let service = ProviderAvailabilityService::new(vec![next, full]);
+
+        assert_eq!(service.find_next_available(7, date(3), 30), Some(date(4).and_time(time(8, 0))));
+        assert_eq!(service.find_next_available(7, date(5), 30), None);
+    }
+
+    #[test]
+    fn slots_report_status() {
+        let mut schedule = ProviderSchedule::new(7, date(3));
+        schedule.end_time = time(9, 30);
+        schedule.appointments.push(booked(101, time(8, 0), 30));
+        schedule.blocked.push(ScheduleBlock { start: time(9, 0), duration_minutes: 30 });
+
+        let statuses: Vec<(String, SlotStatus)> =
+            schedule.slots().into_iter().map(|s| (s.time, s.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("08:00".to_string(), SlotStatus::Booked),
+                ("08:30".to_string(), SlotStatus::Available),
+                ("09:00".to_string(), SlotStatus::Blocked),
+            ]
+        );
+    }
+}
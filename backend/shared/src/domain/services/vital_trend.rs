@@ -0,0 +1,166 @@
+//! Vital Sign Trend Analysis
+//!
+//! Fits a least-squares line through a series of readings and classifies
+//! the slope (units per day) as increasing, decreasing or stable.
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Default `|slope|` below which a trend is reported as stable (units/day)
+pub const DEFAULT_STABLE_THRESHOLD: f64 = 0.1;
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Single numeric reading of one vital type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VitalReading {
+    pub taken_at: NaiveDateTime,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendDirection {
+    Increasing,
+    Decreasing,
+    Stable,
+}
+
+/// Trend of a series of readings; `readings` are in chronological order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendResult {
+    /// Change in units per day
+    pub slope: f64,
+    pub direction: TrendDirection,
+    pub readings: Vec<VitalReading>,
+}
+
+/// Linear regression over vital sign readings
+#[derive(Debug, Clone, Copy)]
+pub struct VitalTrendCalculator {
+    threshold: f64,
+}
+
+impl Default for VitalTrendCalculator {
+    fn default() -> Self {
+        Self { threshold: DEFAULT_STABLE_THRESHOLD }
+    }
+}
+
+impl VitalTrendCalculator {
+    /// Calculator reporting slopes with `|slope| < threshold` as stable
+    pub fn new(threshold: f64) -> Self {
+        Self { threshold: threshold.abs() }
+    }
+
+    /// Fit the readings against time in days.
+    ///
+    /// With fewer than two readings, or all readings at the same instant,
+    /// the slope is undefined and the trend is reported as stable.
+    pub fn calculate(&self, readings: &[VitalReading]) -> TrendResult {
+        let mut readings = readings.to_vec();
+        readings.sort_by_key(|r| r.taken_at);
+
+        let slope = Self::slope(&readings).unwrap_or(0.0);
+        let direction = if slope.abs() < self.threshold {
+            TrendDirection::Stable
+        } else if slope > 0.0 {
+            TrendDirection::Increasing
+        } else {
+            TrendDirection::Decreasing
+        };
+
+        TrendResult { slope, direction, readings }
+    }
+
+    fn slope(readings: &[VitalReading]) -> Option<f64> {
+        let first = readings.first()?.taken_at;
+        if readings.len() < 2 {
+            return None;
+        }
+
+        let points: Vec<(f64, f64)> = readings
+            .iter()
+            .map(|r| ((r.taken_at - first).num_seconds() as f64 / SECONDS_PER_DAY, r.value))
+            .collect();
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+        let (covariance, variance) = points.iter().fold((0.0, 0.0), |(cov, var), (x, y)| {
+            let dx = x - mean_x;
+            (cov + dx * (y - mean_y), var + dx * dx)
+        });
+        (variance > 0.0).then(|| covariance / variance)
+    }
+}
+
+/// Split a blood pressure value such as `"120/80"` into (systolic, diastolic)
+pub fn parse_blood_pressure(value: &str) -> Option<(f64, f64)> {
+    let (systolic, diastolic) = value.split_once('/')?;
+    Some((systolic.trim().parse().ok()?, diastolic.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    fn series(values: &[f64]) -> Vec<VitalReading> {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .and_then(|d| d.and_hms_opt(8, 0, 0))
+            .unwrap_or_default();
+        values
+            .iter()
+            .enumerate()
+            .map(|(day, &value)| VitalReading { taken_at: start + Duration::days(day as i64), value })
+            .collect()
+    }
+
+    #[test]
+    fn increasing_readings_have_positive_slope() {
+        let trend = VitalTrendCalculator::default().calculate(&series(&[70.0, 71.0, 72.5, 74.0]));
+        assert!(trend.slope > 0.0);
+        assert_eq!(trend.direction, TrendDirection::Increasing);
+        assert_eq!(trend.readings.len(), 4);
+    }
+
+    #[test]
+    fn decreasing_readings_are_sorted_chronologically() {
+        let mut readings = series(&[90.0, 88.0, 86.0]);
+        readings.reverse();
+        let trend = VitalTrendCalculator::default().calculate(&readings);
+        assert!((trend.slope + 2.0).abs() < 1e-9);
+        assert_eq!(trend.direction, TrendDirection::Decreasing);
+        assert_eq!(trend.readings[0].value, 90.0);
+    }
+
+    #[test]
+    fn flat_readings_are_stable() {
+        let trend = VitalTrendCalculator::default().calculate(&series(&[98.6, 98.6, 98.6]));
+        assert_eq!(trend.slope, 0.0);
+        assert_eq!(trend.direction, TrendDirection::Stable);
+    }
+
+    #[test]
+    fn single_reading_is_stable() {
+        let trend = VitalTrendCalculator::default().calculate(&series(&[120.0]));
+        assert_eq!(trend.slope, 0.0);
+        assert_eq!(trend.direction, TrendDirection::Stable);
+    }
+
+    #[test]
+    fn threshold_is_configurable() {
+        let readings = series(&[80.0, 80.5, 81.0]);
+        assert_eq!(VitalTrendCalculator::default().calculate(&readings).direction, TrendDirection::Increasing);
+        assert_eq!(VitalTrendCalculator::new(1.0).calculate(&readings).direction, TrendDirection::Stable);
+    }
+
+    #[test]
+    fn parses_blood_pressure() {
+        assert_eq!(parse_blood_pressure("120/80"), Some((120.0, 80.0)));
+        assert_eq!(parse_blood_pressure("120"), None);
+        assert_eq!(parse_blood_pressure("high/80"), None);
+    }
+}
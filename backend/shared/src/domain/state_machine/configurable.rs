@@ -201,6 +201,146 @@ impl WorkflowContext {
         self.extra.insert(key.into(), value);
         self
     }
+
+    /// Insert the declared default of every variable missing from `extra`
+    pub fn apply_defaults(&mut self, schema: &[WorkflowVariableSchema]) {
+        for variable in schema {
+            if let Some(default) = &variable.default {
+                self.extra.entry(variable.name.clone()).or_insert_with(|| default.clone());
+            }
+        }
+    }
+
+    /// Validate context variables (`extra`) against declared variable schemas
+    ///
+    /// Required variables with a default are not reported as missing.
+    /// Returns every violation rather than stopping at the first.
+    pub fn validate_against_schema(&self, schema: &[WorkflowVariableSchema]) -> Result<(), Vec<SchemaError>> {
+        if schema.is_empty() {
+            return Ok(());
+        }
+
+        let json_schema = variables_json_schema(schema);
+        let validator = jsonschema::validator_for(&json_schema).map_err(|e| {
+            vec![SchemaError {
+                variable: String::new(),
+                message: format!("Invalid variable schema: {}", e),
+            }]
+        })?;
+
+        let instance = Value::Object(self.extra.clone().into_iter().collect());
+        let errors: Vec<SchemaError> = validator.iter_errors(&instance).map(SchemaError::from).collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// ============================================================================
+// Variable Schemas
+// ============================================================================
+
+/// Type of a workflow context variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Object,
+    Array,
+    /// RFC 3339 timestamp string
+    DateTime,
+}
+
+impl VariableType {
+    /// JSON Schema fragment describing this type
+    fn json_schema(&self) -> Value {
+        match self {
+            Self::String => serde_json::json!({ "type": "string" }),
+            Self::Number => serde_json::json!({ "type": "number" }),
+            Self::Integer => serde_json::json!({ "type": "integer" }),
+            Self::Boolean => serde_json::json!({ "type": "boolean" }),
+            Self::Object => serde_json::json!({ "type": "object" }),
+            Self::Array => serde_json::json!({ "type": "array" }),
+            Self::DateTime => serde_json::json!({
+                "type": "string",
+                "pattern": r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})$"
+            }),
+        }
+    }
+}
+
+/// Declared type of a workflow context variable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowVariableSchema {
+    pub name: String,
+    pub variable_type: VariableType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<Value>,
+}
+
+/// A context variable that violates its schema
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaError {
+    /// Variable name (empty when the schema itself is invalid)
+    pub variable: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.variable.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.variable, self.message)
+        }
+    }
+}
+
+impl From<jsonschema::ValidationError<'_>> for SchemaError {
+    fn from(error: jsonschema::ValidationError<'_>) -> Self {
+        let variable = match &error.kind {
+            jsonschema::error::ValidationErrorKind::Required { property } => {
+                property.as_str().unwrap_or_default().to_string()
+            }
+            _ => error.instance_path.as_str()
+                .trim_start_matches('/')
+                .split('/')
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        Self {
+            variable,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// Build a JSON Schema for an object holding the declared variables
+fn variables_json_schema(schema: &[WorkflowVariableSchema]) -> Value {
+    let properties: serde_json::Map<String, Value> = schema
+        .iter()
+        .map(|v| (v.name.clone(), v.variable_type.json_schema()))
+        .collect();
+    let required: Vec<&str> = schema
+        .iter()
+        .filter(|v| v.required && v.default.is_none())
+        .map(|v| v.name.as_str())
+        .collect();
+
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
 }
 
 // ============================================================================
@@ -666,4 +806,45 @@ mod tests {
         assert_eq!(events[0].event_code, "complete");
         assert!(events[0].requires_confirmation);
     }
+
+    fn variable(name: &str, variable_type: VariableType, required: bool) -> WorkflowVariableSchema {
+        WorkflowVariableSchema {
+            name: name.to_string(),
+            variable_type,
+            required,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_against_schema_accepts_matching_context() {
+        let schema = vec![
+            variable("patient_id", VariableType::String, true),
+            variable("quantity", VariableType::Integer, false),
+            variable("due_at", VariableType::DateTime, false),
+        ];
+        let ctx = WorkflowContext::new(serde_json::json!({}))
+            .with_extra("patient_id", serde_json::json!("P-100"))
+            .with_extra("due_at", serde_json::json!("2026-01-05T09:30:00Z"));
+
+        assert!(ctx.validate_against_schema(&schema).is_ok());
+    }
+
+    #[test]
+    fn test_validate_against_schema_reports_every_violation() {
+        let mut priority = variable("priority", VariableType::String, true);
+        priority.default = Some(serde_json::json!("routine"));
+        let schema = vec![
+            variable("patient_id", VariableType::String, true),
+            variable("quantity", VariableType::Integer, false),
+            priority,
+        ];
+        let ctx = WorkflowContext::new(serde_json::json!({}))
+            .with_extra("quantity", serde_json::json!("two"));
+
+        let errors = ctx.validate_against_schema(&schema).unwrap_err();
+        let mut variables: Vec<&str> = errors.iter().map(|e| e.variable.as_str()).collect();
+        variables.sort();
+        assert_eq!(variables, vec!["patient_id", "quantity"]);
+    }
 }
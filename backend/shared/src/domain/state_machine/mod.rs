@@ -39,6 +39,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use uuid::Uuid;
+
+use crate::application::services::SharedAppointmentEvents;
 
 // Re-export the proc macro
 pub use state_machine_macro::state_machine;
@@ -48,6 +51,24 @@ pub mod configurable;
 pub use configurable::{
     WorkflowDefinition, WorkflowState, WorkflowTransition,
     WorkflowContext, WorkflowEntityType, TransitionResult, ValidEvent,
+    WorkflowVariableSchema, VariableType, SchemaError,
+};
+
+// Timeout-driven transitions
+pub mod timeout;
+pub use timeout::{TimedStateMachine, TimeoutScheduler};
+
+// Scheduled cancellation of unsigned orders
+pub mod stale_orders;
+pub use stale_orders::{
+    StaleOrderCanceller, DEFAULT_STALE_ORDER_AGE, DEFAULT_STALE_ORDER_SCHEDULE, STALE_DRAFT_REASON,
+};
+
+// Pre-appointment reminders
+pub mod reminders;
+pub use reminders::{
+    record_reminders, NotificationQueue, ReminderChannel, ReminderNotification, ReminderScheduler,
+    DEFAULT_REMINDER_SCHEDULE, REMINDER_QUEUE_CAPACITY,
 };
 
 // ============================================================================
@@ -231,15 +252,18 @@ state_machine! {
         Scheduled => {
             Confirm => Confirmed,
             Cancel [guard: cancellation_allowed] => Cancelled,
+            CheckIn [action: record_check_in_time] => CheckedIn,
         },
         Confirmed => {
             CheckIn [action: record_check_in_time] => CheckedIn,
             Cancel [guard: cancellation_allowed] => Cancelled,
             MarkNoShow [guard: past_scheduled_time] => NoShow,
+            Remind [action: send_reminder] => Confirmed,
         },
         CheckedIn => {
             StartExam [action: record_exam_start] => InProgress,
             Cancel => Cancelled,
+            timeout: checked_in_timeout [action: auto_complete] => Completed,
         },
         InProgress => {
             Complete [action: record_completion] => Completed,
@@ -264,6 +288,22 @@ pub struct AppointmentContext {
     pub wait_time_minutes: Option<i32>,
     /// Calculated exam duration in minutes
     pub exam_duration_minutes: Option<i32>,
+    /// Whether the visit was closed by the check-in timeout
+    pub auto_completed: bool,
+    /// Appointment IEN, needed to send reminders
+    pub appointment_ien: Option<i64>,
+    /// Patient IEN, needed to send reminders
+    pub patient_ien: Option<i64>,
+    /// How the patient is reminded
+    pub reminder_channel: ReminderChannel,
+    /// Where reminders are queued; none are sent without one
+    pub notification_queue: Option<NotificationQueue>,
+    /// Appointment ID, carried on status change events
+    pub appointment_id: Option<Uuid>,
+    /// Patient name, carried on status change events
+    pub patient_name: Option<String>,
+    /// Where status changes are published; none are published without one
+    pub status_events: Option<SharedAppointmentEvents>,
 }
 
 impl AppointmentContext {
@@ -277,8 +317,44 @@ impl AppointmentContext {
             cancellation_reason: None,
             wait_time_minutes: None,
             exam_duration_minutes: None,
+            auto_completed: false,
+            appointment_ien: None,
+            patient_ien: None,
+            reminder_channel: ReminderChannel::Sms,
+            notification_queue: None,
+            appointment_id: None,
+            patient_name: None,
+            status_events: None,
         }
     }
+
+    /// Identify the appointment so `Remind` can queue a reminder for it
+    pub fn with_reminder(
+        mut self,
+        appointment_ien: i64,
+        patient_ien: i64,
+        channel: ReminderChannel,
+        queue: NotificationQueue,
+    ) -> Self {
+        self.appointment_ien = Some(appointment_ien);
+        self.patient_ien = Some(patient_ien);
+        self.reminder_channel = channel;
+        self.notification_queue = Some(queue);
+        self
+    }
+
+    /// Publish every status change of this appointment to `events`
+    pub fn with_status_events(
+        mut self,
+        appointment_id: Uuid,
+        patient_name: impl Into<String>,
+        events: SharedAppointmentEvents,
+    ) -> Self {
+        self.appointment_id = Some(appointment_id);
+        self.patient_name = Some(patient_name.into());
+        self.status_events = Some(events);
+        self
+    }
 }
 
 /// Appointment state machine implementation
@@ -320,6 +396,46 @@ impl AppointmentStateMachine<AppointmentContext> for AppointmentMachine {
             ctx.exam_duration_minutes = Some((now - start).num_minutes() as i32);
         }
     }
+
+    /// Timeout: Close visits left checked in for 8 hours
+    fn checked_in_timeout() -> std::time::Duration {
+        std::time::Duration::from_secs(8 * 60 * 60)
+    }
+
+    /// Action: Complete a visit that timed out in check-in
+    fn auto_complete(ctx: &mut AppointmentContext) {
+        ctx.completion_time = Some(Utc::now());
+        ctx.auto_completed = true;
+    }
+
+    /// Action: Queue a reminder for the patient
+    ///
+    /// Never blocks; a reminder that does not fit in the queue is dropped.
+    fn send_reminder(ctx: &mut AppointmentContext) {
+        let (Some(queue), Some(appointment_ien), Some(patient_ien)) =
+            (&ctx.notification_queue, ctx.appointment_ien, ctx.patient_ien)
+        else {
+            tracing::warn!("Reminder skipped: appointment context has no reminder target");
+            return;
+        };
+        let notification = ReminderNotification {
+            appointment_ien,
+            patient_ien,
+            scheduled_time: ctx.scheduled_time,
+            channel: ctx.reminder_channel,
+        };
+        if let Err(e) = queue.try_send(notification) {
+            tracing::warn!("Reminder for appointment {} dropped: {}", appointment_ien, e);
+        }
+    }
+
+    /// Publish the status change to the live appointment stream
+    fn on_transition(from: &AppointmentStatus, to: &AppointmentStatus, ctx: &mut AppointmentContext) {
+        if let (Some(events), Some(appointment_id)) = (&ctx.status_events, ctx.appointment_id) {
+            let patient_name = ctx.patient_name.clone().unwrap_or_default();
+            events.publish(appointment_id, patient_name, from.to_string(), to.to_string());
+        }
+    }
 }
 
 // ============================================================================
@@ -402,6 +518,8 @@ pub struct OrderContext {
     pub completed_at: Option<DateTime<Utc>>,
     /// Discontinue reason
     pub discontinue_reason: Option<String>,
+    /// Why an unsigned order was cancelled
+    pub cancellation_reason: Option<String>,
     /// Results (for completed orders)
     pub results: Option<serde_json::Value>,
 }
@@ -416,6 +534,7 @@ impl OrderContext {
             started_at: None,
             completed_at: None,
             discontinue_reason: None,
+            cancellation_reason: None,
             results: None,
         }
     }
@@ -456,9 +575,116 @@ impl OrderStateMachine<OrderContext> for OrderMachine {
     }
 }
 
+// ============================================================================
+// Example: Clinical Document Status State Machine
+// ============================================================================
+
+/// Clinical document (TIU note) status states
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentStatus {
+    /// Note written, awaiting signature
+    Unsigned,
+    /// Note signed by its author
+    Signed,
+    /// Note has one or more addenda
+    Amended,
+    /// Note withdrawn (entered in error)
+    Retracted,
+}
+
+impl State for DocumentStatus {}
+
+impl std::fmt::Display for DocumentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsigned => write!(f, "unsigned"),
+            Self::Signed => write!(f, "signed"),
+            Self::Amended => write!(f, "amended"),
+            Self::Retracted => write!(f, "retracted"),
+        }
+    }
+}
+
+impl std::str::FromStr for DocumentStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "unsigned" => Ok(Self::Unsigned),
+            "signed" => Ok(Self::Signed),
+            "amended" => Ok(Self::Amended),
+            "retracted" => Ok(Self::Retracted),
+            _ => Err(format!("Unknown document status: {}", s)),
+        }
+    }
+}
+
+// Addenda keep the original text intact, so any note that has not been
+// retracted can be amended - repeatedly
+state_machine! {
+    DocumentStateMachine for DocumentStatus {
+        initial: Unsigned,
+
+        Unsigned => {
+            Sign [guard: has_signer] => Signed,
+            Amend [action: record_amendment] => Amended,
+            Retract => Retracted,
+        },
+        Signed => {
+            Amend [action: record_amendment] => Amended,
+            Retract => Retracted,
+        },
+        Amended => {
+            Amend [action: record_amendment] => Amended,
+            Retract => Retracted,
+        },
+    }
+}
+
+/// Context for clinical document state transitions
+#[derive(Debug, Clone)]
+pub struct DocumentContext {
+    /// Document ID
+    pub document_id: String,
+    /// Signing provider ID
+    pub signed_by: Option<String>,
+    /// When the latest addendum was added
+    pub amended_at: Option<DateTime<Utc>>,
+}
+
+impl DocumentContext {
+    /// Create a new document context
+    pub fn new(document_id: impl Into<String>) -> Self {
+        Self {
+            document_id: document_id.into(),
+            signed_by: None,
+            amended_at: None,
+        }
+    }
+}
+
+/// Clinical document state machine implementation
+pub struct DocumentMachine;
+
+impl DocumentStateMachine<DocumentContext> for DocumentMachine {
+    /// Guard: A note is signed by a known provider
+    fn has_signer(ctx: &DocumentContext) -> bool {
+        ctx.signed_by.is_some()
+    }
+
+    /// Action: Record when the addendum was added
+    fn record_amendment(ctx: &mut DocumentContext) {
+        ctx.amended_at = Some(Utc::now());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Mutex;
 
     #[test]
     fn test_appointment_state_machine_valid_transitions() {
@@ -482,6 +708,76 @@ mod tests {
         assert!(ctx.check_in_time.is_some());
     }
 
+    #[test]
+    fn test_remind_stays_confirmed_and_queues_reminder() {
+        let scheduled_time = Utc::now() + chrono::Duration::days(1);
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(1);
+        let mut ctx = AppointmentContext::new(scheduled_time)
+            .with_reminder(42, 7, ReminderChannel::Email, std::sync::Arc::new(sender));
+
+        let result = AppointmentMachine::transition(
+            &AppointmentStatus::Confirmed,
+            AppointmentStateMachineEvent::Remind,
+            &mut ctx,
+        );
+
+        assert_eq!(result.unwrap(), AppointmentStatus::Confirmed);
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            ReminderNotification {
+                appointment_ien: 42,
+                patient_ien: 7,
+                scheduled_time,
+                channel: ReminderChannel::Email,
+            }
+        );
+        // Only confirmed appointments are reminded
+        assert!(AppointmentMachine::transition(
+            &AppointmentStatus::Scheduled,
+            AppointmentStateMachineEvent::Remind,
+            &mut ctx,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_status_changes_are_published() {
+        let events = Arc::new(crate::application::services::AppointmentEventBroadcaster::new());
+        let mut updates = events.subscribe();
+        let appointment_id = Uuid::new_v4();
+        let mut ctx = AppointmentContext::new(Utc::now() + chrono::Duration::days(1))
+            .with_status_events(appointment_id, "Jane Doe", events.clone());
+
+        // A self-transition leaves the status unchanged, so nothing is published
+        AppointmentMachine::transition(
+            &AppointmentStatus::Confirmed,
+            AppointmentStateMachineEvent::Remind,
+            &mut ctx,
+        )
+        .unwrap();
+        AppointmentMachine::transition(
+            &AppointmentStatus::Confirmed,
+            AppointmentStateMachineEvent::CheckIn,
+            &mut ctx,
+        )
+        .unwrap();
+
+        let event = updates.try_recv().unwrap();
+        assert_eq!(event.appointment_id, appointment_id);
+        assert_eq!(event.patient_name, "Jane Doe");
+        assert_eq!((event.old_status.as_str(), event.new_status.as_str()), ("confirmed", "checked_in"));
+        assert!(updates.try_recv().is_err());
+
+        // Failed transitions publish nothing either
+        assert!(AppointmentMachine::transition(
+            &AppointmentStatus::Completed,
+            AppointmentStateMachineEvent::Cancel,
+            &mut ctx,
+        )
+        .is_err());
+        assert!(updates.try_recv().is_err());
+    }
+
     #[test]
     fn test_appointment_invalid_transition() {
         let mut ctx = AppointmentContext::new(Utc::now() + chrono::Duration::days(1));
@@ -512,7 +808,7 @@ mod tests {
     #[test]
     fn test_valid_transitions_list() {
         let transitions = AppointmentMachine::valid_transitions(&AppointmentStatus::Scheduled);
-        assert_eq!(transitions.len(), 2); // Confirm, Cancel
+        assert_eq!(transitions.len(), 3); // Confirm, Cancel, CheckIn
 
         let transitions = AppointmentMachine::valid_transitions(&AppointmentStatus::Completed);
         assert!(transitions.is_empty()); // Terminal state
@@ -549,6 +845,77 @@ mod tests {
         assert_eq!(result.unwrap(), OrderStatus::Active);
     }
 
+    #[test]
+    fn test_document_amendment() {
+        let mut ctx = DocumentContext::new("TIU-001");
+
+        // Signed -> Amended, and a further addendum keeps it Amended
+        let result = DocumentMachine::transition(
+            &DocumentStatus::Signed,
+            DocumentStateMachineEvent::Amend,
+            &mut ctx,
+        );
+        assert_eq!(result.unwrap(), DocumentStatus::Amended);
+        assert!(ctx.amended_at.is_some());
+
+        let result = DocumentMachine::transition(
+            &DocumentStatus::Amended,
+            DocumentStateMachineEvent::Amend,
+            &mut ctx,
+        );
+        assert_eq!(result.unwrap(), DocumentStatus::Amended);
+
+        // Retracted notes cannot be amended
+        let result = DocumentMachine::transition(
+            &DocumentStatus::Retracted,
+            DocumentStateMachineEvent::Amend,
+            &mut ctx,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_appointment_diagram_dot() {
+        let expected = r#"digraph AppointmentStateMachine {
+    rankdir=LR;
+    __start [shape=point];
+    Scheduled [shape=circle];
+    Confirmed [shape=circle];
+    Cancelled [shape=doublecircle];
+    CheckedIn [shape=circle];
+    NoShow [shape=doublecircle];
+    InProgress [shape=circle];
+    Completed [shape=doublecircle];
+    __start -> Scheduled;
+    Scheduled -> Confirmed [label="Confirm"];
+    Scheduled -> Cancelled [label="Cancel [cancellation_allowed]"];
+    Scheduled -> CheckedIn [label="CheckIn / record_check_in_time"];
+    Confirmed -> CheckedIn [label="CheckIn / record_check_in_time"];
+    Confirmed -> Cancelled [label="Cancel [cancellation_allowed]"];
+    Confirmed -> NoShow [label="MarkNoShow [past_scheduled_time]"];
+    Confirmed -> Confirmed [label="Remind / send_reminder"];
+    CheckedIn -> InProgress [label="StartExam / record_exam_start"];
+    CheckedIn -> Cancelled [label="Cancel"];
+    CheckedIn -> Completed [label="Timeout(checked_in_timeout) / auto_complete"];
+    InProgress -> Completed [label="Complete / record_completion"];
+}
+"#;
+        assert_eq!(AppointmentMachine::diagram_dot(), expected);
+    }
+
+    #[test]
+    fn test_order_diagram_handles_cycles() {
+        let diagram = OrderMachine::diagram();
+
+        assert!(diagram.starts_with("stateDiagram-v2\n    [*] --> Draft\n"));
+        assert!(diagram.contains("    Active --> OnHold : Hold [hold_allowed]\n"));
+        assert!(diagram.contains("    OnHold --> Active : Release\n"));
+        // Active and OnHold lead somewhere, so neither is terminal
+        assert!(!diagram.contains("Active --> [*]"));
+        assert!(!diagram.contains("OnHold --> [*]"));
+        assert!(diagram.contains("    Cancelled --> [*]\n"));
+    }
+
     #[test]
     fn test_order_guard_blocks_unsigned() {
         let mut ctx = OrderContext::new("ORD-001");
@@ -562,4 +929,309 @@ mod tests {
         );
         assert!(matches!(result, Err(TransitionError::GuardFailed { .. })));
     }
+
+    // ------------------------------------------------------------------
+    // Timeout transitions
+    // ------------------------------------------------------------------
+
+    type AppointmentScheduler =
+        TimeoutScheduler<TimedAppointmentStateMachine<AppointmentMachine>, AppointmentContext>;
+
+    fn checked_in_appointment() -> Arc<Mutex<(AppointmentStatus, AppointmentContext)>> {
+        let ctx = AppointmentContext::new(Utc::now());
+        Arc::new(Mutex::new((AppointmentStatus::CheckedIn, ctx)))
+    }
+
+    #[test]
+    fn test_timeout_transitions_list() {
+        let timeouts = AppointmentMachine::timeout_transitions(&AppointmentStatus::CheckedIn);
+        assert_eq!(
+            timeouts,
+            vec![(
+                AppointmentMachine::checked_in_timeout(),
+                AppointmentStateMachineEvent::Timeout
+            )]
+        );
+
+        assert!(AppointmentMachine::timeout_transitions(&AppointmentStatus::Scheduled).is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_checked_in_times_out_to_completed() {
+        let appointment = checked_in_appointment();
+        let scheduler = AppointmentScheduler::new(appointment.clone());
+        scheduler.schedule().await;
+
+        tokio::time::sleep(AppointmentMachine::checked_in_timeout() + Duration::from_secs(1)).await;
+
+        let (state, ctx) = &*appointment.lock().await;
+        assert_eq!(*state, AppointmentStatus::Completed);
+        assert!(ctx.auto_completed);
+        assert_eq!(scheduler.pending(), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_explicit_transition_cancels_timeout() {
+        let appointment = checked_in_appointment();
+        let scheduler = AppointmentScheduler::new(appointment.clone());
+        scheduler.schedule().await;
+        assert_eq!(scheduler.pending(), 1);
+
+        let next = scheduler.transition(AppointmentStateMachineEvent::StartExam).await;
+        assert_eq!(next.unwrap(), AppointmentStatus::InProgress);
+        assert_eq!(scheduler.pending(), 0);
+
+        tokio::time::sleep(AppointmentMachine::checked_in_timeout() * 2).await;
+
+        let (state, ctx) = &*appointment.lock().await;
+        assert_eq!(*state, AppointmentStatus::InProgress);
+        assert!(!ctx.auto_completed);
+    }
+
+    // ------------------------------------------------------------------
+    // Async guards and actions
+    // ------------------------------------------------------------------
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ReferralStatus {
+        Pending,
+        Accepted,
+        Declined,
+    }
+
+    state_machine! {
+        ReferralStateMachine for ReferralStatus {
+            initial: Pending,
+
+            Pending => {
+                Accept [guard: async provider_licensed, action: async record_acceptance] => Accepted,
+                Decline [guard: has_reason] => Declined,
+            },
+        }
+    }
+
+    struct ReferralContext {
+        provider_licensed: bool,
+        reason: Option<String>,
+        accepted_at: Option<DateTime<Utc>>,
+        /// When set, the license check goes through the database
+        pool: Option<sqlx::PgPool>,
+    }
+
+    impl ReferralContext {
+        fn new(provider_licensed: bool) -> Self {
+            Self {
+                provider_licensed,
+                reason: None,
+                accepted_at: None,
+                pool: None,
+            }
+        }
+    }
+
+    struct ReferralMachine;
+
+    #[async_trait::async_trait]
+    impl ReferralStateMachine<ReferralContext> for ReferralMachine {
+        async fn provider_licensed(ctx: &ReferralContext) -> bool {
+            match &ctx.pool {
+                Some(pool) => sqlx::query_scalar::<_, bool>("SELECT $1::boolean")
+                    .bind(ctx.provider_licensed)
+                    .fetch_one(pool)
+                    .await
+                    .unwrap_or(false),
+                None => {
+                    tokio::task::yield_now().await;
+                    ctx.provider_licensed
+                }
+            }
+        }
+
+        async fn record_acceptance(ctx: &mut ReferralContext) {
+            tokio::task::yield_now().await;
+            ctx.accepted_at = Some(Utc::now());
+        }
+
+        fn has_reason(ctx: &ReferralContext) -> bool {
+            ctx.reason.is_some()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_guard_and_action() {
+        let mut ctx = ReferralContext::new(true);
+
+        let result = ReferralMachine::transition(
+            &ReferralStatus::Pending,
+            ReferralStateMachineEvent::Accept,
+            &mut ctx,
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), ReferralStatus::Accepted);
+        assert!(ctx.accepted_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_async_guard_blocks_transition() {
+        let mut ctx = ReferralContext::new(false);
+
+        let result = ReferralMachine::transition(
+            &ReferralStatus::Pending,
+            ReferralStateMachineEvent::Accept,
+            &mut ctx,
+        )
+        .await;
+
+        assert_eq!(
+            result,
+            Err(TransitionError::GuardFailed {
+                from: "Pending".to_string(),
+                event: "Accept".to_string(),
+                guard: "provider_licensed".to_string(),
+            })
+        );
+        assert!(ctx.accepted_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sync_guard_in_async_machine() {
+        let mut ctx = ReferralContext::new(true);
+        assert!(!ReferralMachine::can_transition(&ReferralStatus::Pending, &ReferralStateMachineEvent::Decline, &ctx).await);
+
+        ctx.reason = Some("Outside specialty".to_string());
+        assert!(ReferralMachine::can_transition(&ReferralStatus::Pending, &ReferralStateMachineEvent::Decline, &ctx).await);
+        assert_eq!(
+            ReferralMachine::valid_transitions(&ReferralStatus::Pending),
+            vec![ReferralStateMachineEvent::Accept, ReferralStateMachineEvent::Decline]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires test database to be running
+    async fn test_async_guard_queries_database() {
+        let mut ctx = ReferralContext::new(true);
+        ctx.pool = Some(crate::testing::create_test_pool().await);
+
+        let result = ReferralMachine::transition(
+            &ReferralStatus::Pending,
+            ReferralStateMachineEvent::Accept,
+            &mut ctx,
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), ReferralStatus::Accepted);
+    }
+
+    // ------------------------------------------------------------------
+    // on_enter / on_exit state hooks
+    // ------------------------------------------------------------------
+
+    state_machine! {
+        VisitStateMachine for AppointmentStatus {
+            initial: Scheduled,
+
+            Scheduled => {
+                Confirm => Confirmed,
+                Cancel [action: record_cancellation] => Cancelled,
+            },
+            Confirmed => {
+                Cancel => Cancelled,
+                Remind [action: record_reminder] => Confirmed,
+            },
+            Cancelled => {
+                on_enter: notify_cancelled,
+            },
+        }
+    }
+
+    #[derive(Default)]
+    struct VisitContext {
+        calls: Vec<&'static str>,
+    }
+
+    struct VisitMachine;
+
+    impl VisitStateMachine<VisitContext> for VisitMachine {
+        fn record_cancellation(ctx: &mut VisitContext) {
+            ctx.calls.push("record_cancellation");
+        }
+
+        fn record_reminder(ctx: &mut VisitContext) {
+            ctx.calls.push("record_reminder");
+        }
+
+        fn notify_cancelled(ctx: &mut VisitContext) {
+            ctx.calls.push("notify_cancelled");
+        }
+
+        fn on_exit_Scheduled(ctx: &mut VisitContext) {
+            ctx.calls.push("on_exit_Scheduled");
+        }
+
+        fn on_enter_Confirmed(ctx: &mut VisitContext) {
+            ctx.calls.push("on_enter_Confirmed");
+        }
+    }
+
+    #[test]
+    fn test_exit_hook_runs_before_enter_hook() {
+        let mut ctx = VisitContext::default();
+
+        // Confirm has no per-transition action; only the state hooks fire
+        let result = VisitMachine::transition(
+            &AppointmentStatus::Scheduled,
+            VisitStateMachineEvent::Confirm,
+            &mut ctx,
+        );
+
+        assert_eq!(result.unwrap(), AppointmentStatus::Confirmed);
+        assert_eq!(ctx.calls, vec!["on_exit_Scheduled", "on_enter_Confirmed"]);
+    }
+
+    #[test]
+    fn test_state_hooks_wrap_transition_action() {
+        let mut ctx = VisitContext::default();
+
+        let result = VisitMachine::transition(
+            &AppointmentStatus::Scheduled,
+            VisitStateMachineEvent::Cancel,
+            &mut ctx,
+        );
+
+        assert_eq!(result.unwrap(), AppointmentStatus::Cancelled);
+        assert_eq!(
+            ctx.calls,
+            vec!["on_exit_Scheduled", "record_cancellation", "notify_cancelled"]
+        );
+    }
+
+    #[test]
+    fn test_named_enter_hook_fires_without_action() {
+        let mut ctx = VisitContext::default();
+
+        let result = VisitMachine::transition(
+            &AppointmentStatus::Confirmed,
+            VisitStateMachineEvent::Cancel,
+            &mut ctx,
+        );
+
+        assert_eq!(result.unwrap(), AppointmentStatus::Cancelled);
+        assert_eq!(ctx.calls, vec!["notify_cancelled"]);
+    }
+
+    #[test]
+    fn test_self_transition_skips_state_hooks() {
+        let mut ctx = VisitContext::default();
+
+        let result = VisitMachine::transition(
+            &AppointmentStatus::Confirmed,
+            VisitStateMachineEvent::Remind,
+            &mut ctx,
+        );
+
+        // on_enter_Confirmed is not called again
+        assert_eq!(result.unwrap(), AppointmentStatus::Confirmed);
+        assert_eq!(ctx.calls, vec!["record_reminder"]);
+    }
 }
@@ -0,0 +1,238 @@
+//! Pre-appointment reminders
+//!
+//! `Remind` is a `Confirmed => Confirmed` self-transition of
+//! `AppointmentStateMachine` whose `send_reminder` action queues a
+//! `ReminderNotification`. `ReminderScheduler` fires it on a `CronScheduler`
+//! schedule (daily at 8 AM UTC by default) for every confirmed appointment in
+//! the next 24 hours that has not been reminded yet. `record_reminders`
+//! consumes the queue and marks each appointment as reminded.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{AppointmentContext, AppointmentMachine, AppointmentStateMachine, AppointmentStateMachineEvent, AppointmentStatus};
+use crate::application::services::{parse_cron_expression, CronScheduler};
+use crate::domain::repositories::ehr::{EhrAppointmentReminderRepository, UpcomingAppointment};
+use crate::shared::{AppError, AppResult};
+
+/// Daily at 8 AM (UTC)
+pub const DEFAULT_REMINDER_SCHEDULE: &str = "0 8 * * *";
+
+/// How far ahead of an appointment the reminder goes out
+const REMINDER_LEAD_TIME_HOURS: i64 = 24;
+
+/// Reminders buffered between `send_reminder` and the queue consumer
+pub const REMINDER_QUEUE_CAPACITY: usize = 1024;
+
+/// Channel a reminder is delivered on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderChannel {
+    Sms,
+    Email,
+}
+
+/// Reminder queued for delivery to a patient
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReminderNotification {
+    pub appointment_ien: i64,
+    pub patient_ien: i64,
+    pub scheduled_time: DateTime<Utc>,
+    pub channel: ReminderChannel,
+}
+
+/// Queue the `send_reminder` action pushes reminders onto
+pub type NotificationQueue = Arc<tokio::sync::mpsc::Sender<ReminderNotification>>;
+
+/// Fires `Remind` for confirmed appointments in the next 24 hours
+pub struct ReminderScheduler {
+    appointments: Arc<dyn EhrAppointmentReminderRepository>,
+    queue: NotificationQueue,
+    /// 5-field cron expression, see [`parse_cron_expression`]
+    schedule: String,
+}
+
+impl ReminderScheduler {
+    /// Send reminders on [`DEFAULT_REMINDER_SCHEDULE`]
+    pub fn new(appointments: Arc<dyn EhrAppointmentReminderRepository>, queue: NotificationQueue) -> Self {
+        Self {
+            appointments,
+            queue,
+            schedule: DEFAULT_REMINDER_SCHEDULE.to_string(),
+        }
+    }
+
+    /// Run on a different cron schedule
+    pub fn with_schedule(mut self, expression: impl Into<String>) -> AppResult<Self> {
+        let expression = expression.into();
+        parse_cron_expression(&expression)?;
+        self.schedule = expression;
+        Ok(self)
+    }
+
+    /// Send reminders at every scheduled time of `scheduler`
+    pub async fn schedule(self: Arc<Self>, scheduler: &CronScheduler) -> AppResult<()> {
+        let expression = self.schedule.clone();
+        scheduler
+            .schedule("appointment_reminders", &expression, move || {
+                let reminders = Arc::clone(&self);
+                async move {
+                    // Windows start on the minute, so consecutive runs stay adjacent
+                    let now = Utc::now();
+                    let now = now.duration_trunc(chrono::Duration::minutes(1)).unwrap_or(now);
+                    match reminders.send_reminders(now).await {
+                        Ok(sent) if sent > 0 => tracing::info!("Queued {} appointment reminders", sent),
+                        Ok(_) => {}
+                        Err(e) => e.log_with_operation(concat!(file!(), ":", line!()), "send_reminders"),
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Fire `Remind` for every confirmed appointment after `now` and within
+    /// the next 24 hours, returning how many were reminded
+    pub async fn send_reminders(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let until = now + chrono::Duration::hours(REMINDER_LEAD_TIME_HOURS);
+        let upcoming = self.appointments.find_confirmed_between(now, until).await?;
+        for appointment in &upcoming {
+            self.remind(appointment)?;
+        }
+        Ok(upcoming.len())
+    }
+
+    fn remind(&self, appointment: &UpcomingAppointment) -> AppResult<()> {
+        let mut ctx = AppointmentContext::new(appointment.scheduled_time).with_reminder(
+            appointment.appointment_ien,
+            appointment.patient_ien,
+            appointment.channel,
+            Arc::clone(&self.queue),
+        );
+        AppointmentMachine::transition(&AppointmentStatus::Confirmed, AppointmentStateMachineEvent::Remind, &mut ctx)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Take reminders off the queue until every sender is dropped, recording each
+/// one on its appointment
+pub async fn record_reminders(
+    mut queue: tokio::sync::mpsc::Receiver<ReminderNotification>,
+    appointments: Arc<dyn EhrAppointmentReminderRepository>,
+) {
+    while let Some(notification) = queue.recv().await {
+        tracing::info!(
+            "Reminder for appointment {} sent to patient {} by {:?}",
+            notification.appointment_ien,
+            notification.patient_ien,
+            notification.channel
+        );
+        if let Err(e) = appointments.mark_reminder_sent(&notification).await {
+            e.log_with_operation(concat!(file!(), ":", line!()), "mark_reminder_sent");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::TimeZone;
+    use std::sync::Mutex;
+
+    /// Confirmed appointments only
+    #[derive(Default)]
+    struct InMemoryAppointmentRepository {
+        confirmed: Vec<UpcomingAppointment>,
+        reminded: Mutex<Vec<ReminderNotification>>,
+    }
+
+    #[async_trait]
+    impl EhrAppointmentReminderRepository for InMemoryAppointmentRepository {
+        async fn find_confirmed_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> AppResult<Vec<UpcomingAppointment>> {
+            let reminded = self.reminded.lock().unwrap();
+            Ok(self
+                .confirmed
+                .iter()
+                .filter(|a| a.scheduled_time > start && a.scheduled_time <= end)
+                .filter(|a| !reminded.iter().any(|n| n.appointment_ien == a.appointment_ien))
+                .cloned()
+                .collect())
+        }
+
+        async fn mark_reminder_sent(&self, notification: &ReminderNotification) -> AppResult<()> {
+            self.reminded.lock().unwrap().push(notification.clone());
+            Ok(())
+        }
+    }
+
+    fn appointment(appointment_ien: i64, scheduled_time: DateTime<Utc>, channel: ReminderChannel) -> UpcomingAppointment {
+        UpcomingAppointment { appointment_ien, patient_ien: appointment_ien * 10, scheduled_time, channel }
+    }
+    #[tokio::test]
+    async fn test_reminds_each_appointment_once() {
+        let eight_am = Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap();
+        let repository = InMemoryAppointmentRepository {
+            confirmed: vec![
+                appointment(1, eight_am + chrono::Duration::hours(2), ReminderChannel::Sms),
+                appointment(2, eight_am + chrono::Duration::hours(23), ReminderChannel::Email),
+                appointment(3, eight_am + chrono::Duration::hours(30), ReminderChannel::Sms),
+            ],
+            ..Default::default()
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+        let scheduler = ReminderScheduler::new(Arc::new(repository), Arc::new(sender));
+
+        // Two consecutive daily runs
+        assert_eq!(scheduler.send_reminders(eight_am).await.unwrap(), 2);
+        assert_eq!(scheduler.send_reminders(eight_am + chrono::Duration::days(1)).await.unwrap(), 1);
+
+        let mut reminded = Vec::new();
+        while let Ok(notification) = receiver.try_recv() {
+            reminded.push(notification);
+        }
+        assert_eq!(reminded.iter().map(|n| n.appointment_ien).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(
+            reminded[1],
+            ReminderNotification {
+                appointment_ien: 2,
+                patient_ien: 20,
+                scheduled_time: eight_am + chrono::Duration::hours(23),
+                channel: ReminderChannel::Email,
+            }
+        );
+    }
+
+    #[test]
+    fn test_rejects_invalid_schedule() {
+        let (sender, _receiver) = tokio::sync::mpsc::channel(1);
+        let scheduler = ReminderScheduler::new(
+            Arc::new(InMemoryAppointmentRepository::default()),
+            Arc::new(sender),
+        );
+        assert!(matches!(scheduler.with_schedule("8am"), Err(AppError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn test_recorded_reminders_are_not_sent_again() {
+        let eight_am = Utc.with_ymd_and_hms(2026, 3, 2, 8, 0, 0).unwrap();
+        let repository = Arc::new(InMemoryAppointmentRepository {
+            confirmed: vec![appointment(1, eight_am + chrono::Duration::hours(2), ReminderChannel::Sms)],
+            ..Default::default()
+        });
+        let (sender, receiver) = tokio::sync::mpsc::channel(REMINDER_QUEUE_CAPACITY);
+        let scheduler = ReminderScheduler::new(repository.clone(), Arc::new(sender));
+
+        assert_eq!(scheduler.send_reminders(eight_am).await.unwrap(), 1);
+        // The consumer finishes once the scheduler's sender is gone
+        drop(scheduler);
+        record_reminders(receiver, repository.clone()).await;
+        assert_eq!(repository.reminded.lock().unwrap().len(), 1);
+
+        let (sender, _receiver) = tokio::sync::mpsc::channel(REMINDER_QUEUE_CAPACITY);
+        let rerun = ReminderScheduler::new(repository.clone(), Arc::new(sender));
+        assert_eq!(rerun.send_reminders(eight_am).await.unwrap(), 0);
+    }
+}
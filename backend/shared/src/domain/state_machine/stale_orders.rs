@@ -0,0 +1,241 @@
+//! Scheduled cancellation of stale draft orders
+//!
+//! Orders that are never signed would otherwise stay in draft forever.
+//! `StaleOrderCanceller` cancels drafts older than a threshold through
+//! `OrderMachine` on a `CronScheduler` schedule (daily at midnight UTC by
+//! default) and writes a `StateTransitionAudit` for each one to the audit log.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{
+    OrderContext, OrderMachine, OrderStateMachine, OrderStateMachineEvent, OrderStatus,
+    StateTransitionAudit,
+};
+use crate::application::services::{parse_cron_expression, CronScheduler};
+use crate::domain::repositories::ehr::EhrDraftOrderRepository;
+use crate::domain::repositories::{AuditLogEntry, AuditLogRepository};
+use crate::shared::{AppError, AppResult};
+
+/// Daily at midnight (UTC)
+pub const DEFAULT_STALE_ORDER_SCHEDULE: &str = "0 0 * * *";
+
+/// Drafts left unsigned for a day are cancelled
+pub const DEFAULT_STALE_ORDER_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Cancellation reason recorded on drafts cancelled for age
+pub const STALE_DRAFT_REASON: &str = "auto_cancelled_stale";
+
+/// Cancels draft orders left unsigned for longer than a threshold
+pub struct StaleOrderCanceller {
+    orders: Arc<dyn EhrDraftOrderRepository>,
+    audit_log: Arc<dyn AuditLogRepository>,
+    older_than: Duration,
+    /// 5-field cron expression, see [`parse_cron_expression`]
+    schedule: String,
+}
+
+impl StaleOrderCanceller {
+    /// Cancel drafts older than `older_than` on [`DEFAULT_STALE_ORDER_SCHEDULE`]
+    pub fn new(
+        orders: Arc<dyn EhrDraftOrderRepository>,
+        audit_log: Arc<dyn AuditLogRepository>,
+        older_than: Duration,
+    ) -> Self {
+        Self {
+            orders,
+            audit_log,
+            older_than,
+            schedule: DEFAULT_STALE_ORDER_SCHEDULE.to_string(),
+        }
+    }
+
+    /// Run on a different cron schedule
+    pub fn with_schedule(mut self, expression: impl Into<String>) -> AppResult<Self> {
+        let expression = expression.into();
+        parse_cron_expression(&expression)?;
+        self.schedule = expression;
+        Ok(self)
+    }
+
+    /// Cancel stale drafts at every scheduled time of `scheduler`
+    pub async fn schedule(self: Arc<Self>, scheduler: &CronScheduler) -> AppResult<()> {
+        let expression = self.schedule.clone();
+        scheduler
+            .schedule("stale_orders", &expression, move || {
+                let canceller = Arc::clone(&self);
+                async move {
+                    match canceller.cancel_stale_drafts().await {
+                        Ok(cancelled) if !cancelled.is_empty() => {
+                            tracing::info!("Cancelled {} stale draft orders", cancelled.len())
+                        }
+                        Ok(_) => {}
+                        Err(e) => e.log_with_operation(concat!(file!(), ":", line!()), "cancel_stale_drafts"),
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Cancel every draft older than the threshold, returning the audit of
+    /// each cancellation
+    ///
+    /// Drafts signed or cancelled since they were found are skipped, so
+    /// repeated or overlapping runs are harmless.
+    pub async fn cancel_stale_drafts(&self) -> AppResult<Vec<StateTransitionAudit>> {
+        let mut audits = Vec::new();
+        for ien in self.orders.find_stale_drafts(self.older_than).await? {
+            if let Some(audit) = self.cancel(ien).await? {
+                audits.push(audit);
+            }
+        }
+        Ok(audits)
+    }
+
+    async fn cancel(&self, ien: i64) -> AppResult<Option<StateTransitionAudit>> {
+        let from = OrderStatus::Draft;
+        let event = OrderStateMachineEvent::Cancel;
+        let mut ctx = OrderContext::new(ien.to_string());
+        ctx.cancellation_reason = Some(STALE_DRAFT_REASON.to_string());
+        let to = OrderMachine::transition(&from, event, &mut ctx)
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+
+        if !self.orders.cancel_draft(ien, STALE_DRAFT_REASON).await? {
+            return Ok(None);
+        }
+
+        let audit = StateTransitionAudit::new("order", ien.to_string(), from.to_string(), to.to_string(), event.to_string())
+            .with_context(serde_json::json!({ "reason": ctx.cancellation_reason }));
+        self.audit_log
+            .create(AuditLogEntry {
+                user_id: None,
+                action: STALE_DRAFT_REASON.to_string(),
+                resource: "order".to_string(),
+                // Orders are keyed by IEN, which is in the audit's entity_id
+                resource_id: None,
+                details: serde_json::to_value(&audit).unwrap_or_default(),
+            })
+            .await?;
+        Ok(Some(audit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// ^OR(100) 0-node status piece, entry time and cancellation reason
+    struct StoredOrder {
+        status: &'static str,
+        entered_at: DateTime<Utc>,
+        reason: Option<String>,
+    }
+
+    #[derive(Default)]
+    struct InMemoryDraftOrders {
+        orders: Mutex<HashMap<i64, StoredOrder>>,
+    }
+
+    impl InMemoryDraftOrders {
+        fn draft_entered_hours_ago(&self, hours: i64) -> i64 {
+            let mut orders = self.orders.lock().unwrap();
+            let ien = orders.len() as i64 + 1;
+            orders.insert(ien, StoredOrder { status: "P", entered_at: Utc::now() - chrono::Duration::hours(hours), reason: None });
+            ien
+        }
+
+        fn status(&self, ien: i64) -> Option<&'static str> {
+            self.orders.lock().unwrap().get(&ien).map(|order| order.status)
+        }
+    }
+
+    #[async_trait]
+    impl EhrDraftOrderRepository for InMemoryDraftOrders {
+        async fn find_stale_drafts(&self, older_than: Duration) -> AppResult<Vec<i64>> {
+            let cutoff = Utc::now() - chrono::Duration::from_std(older_than).unwrap();
+            let orders = self.orders.lock().unwrap();
+            Ok(orders
+                .iter()
+                .filter(|(_, order)| order.status == "P" && order.entered_at < cutoff)
+                .map(|(ien, _)| *ien)
+                .collect())
+        }
+
+        async fn cancel_draft(&self, order_ien: i64, reason: &str) -> AppResult<bool> {
+            let mut orders = self.orders.lock().unwrap();
+            let Some(order) = orders.get_mut(&order_ien).filter(|order| order.status == "P") else {
+                return Ok(false);
+            };
+            order.status = "X";
+            order.reason = Some(reason.to_string());
+            Ok(true)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingAuditLog {
+        entries: Mutex<Vec<AuditLogEntry>>,
+    }
+
+    #[async_trait]
+    impl AuditLogRepository for RecordingAuditLog {
+        async fn create(&self, entry: AuditLogEntry) -> AppResult<()> {
+            self.entries.lock().unwrap().push(entry);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancels_drafts_past_threshold() {
+        let orders = Arc::new(InMemoryDraftOrders::default());
+        let audit_log = Arc::new(RecordingAuditLog::default());
+        let stale = orders.draft_entered_hours_ago(25);
+        let recent = orders.draft_entered_hours_ago(23);
+        let canceller = StaleOrderCanceller::new(orders.clone(), audit_log.clone(), DEFAULT_STALE_ORDER_AGE);
+
+        let audits = canceller.cancel_stale_drafts().await.unwrap();
+
+        assert_eq!(orders.status(stale), Some("X"));
+        assert_eq!(orders.status(recent), Some("P"));
+        assert_eq!(orders.orders.lock().unwrap()[&stale].reason.as_deref(), Some(STALE_DRAFT_REASON));
+        assert_eq!(audits.len(), 1);
+        assert_eq!(audits[0].entity_id, stale.to_string());
+        assert_eq!((audits[0].from_state.as_str(), audits[0].to_state.as_str()), ("draft", "cancelled"));
+        assert_eq!(audits[0].context, Some(serde_json::json!({ "reason": "auto_cancelled_stale" })));
+
+        let entries = audit_log.entries.lock().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, STALE_DRAFT_REASON);
+        assert_eq!(entries[0].details["entity_id"], stale.to_string());
+    }
+
+    #[tokio::test]
+    async fn test_rerun_is_a_no_op() {
+        let orders = Arc::new(InMemoryDraftOrders::default());
+        let audit_log = Arc::new(RecordingAuditLog::default());
+        let stale = orders.draft_entered_hours_ago(25);
+        let canceller = StaleOrderCanceller::new(orders.clone(), audit_log.clone(), DEFAULT_STALE_ORDER_AGE);
+
+        assert_eq!(canceller.cancel_stale_drafts().await.unwrap().len(), 1);
+        assert!(canceller.cancel_stale_drafts().await.unwrap().is_empty());
+        // Already cancelled by the time it is processed
+        assert!(canceller.cancel(stale).await.unwrap().is_none());
+
+        assert_eq!(orders.status(stale), Some("X"));
+        assert_eq!(audit_log.entries.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rejects_invalid_schedule() {
+        let canceller = StaleOrderCanceller::new(
+            Arc::new(InMemoryDraftOrders::default()),
+            Arc::new(RecordingAuditLog::default()),
+            Duration::from_secs(60),
+        );
+        assert!(matches!(canceller.with_schedule("daily"), Err(AppError::Validation(_))));
+    }
+}
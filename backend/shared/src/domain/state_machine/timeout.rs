@@ -0,0 +1,159 @@
+//! Timeout-driven transitions
+//!
+//! States declared with `timeout: duration_fn [...] => Target` in
+//! `state_machine!` fire a `Timeout` event once they have lasted long enough.
+//! `TimeoutScheduler` owns the timers: it arms them whenever the machine
+//! enters a state and cancels them when an explicit transition leaves it first.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+
+use super::TransitionError;
+
+/// A machine whose states can time out
+///
+/// Implemented by the `Timed<Machine><M>` adapter that `state_machine!`
+/// generates for machines with `timeout:` transitions.
+pub trait TimedStateMachine<Ctx> {
+    type State: Debug + Copy + PartialEq + Send + 'static;
+    type Event: Debug + Copy + Send + 'static;
+
+    /// Timeouts armed while in `state`, as (duration, event) pairs
+    fn timeout_transitions(state: &Self::State) -> Vec<(Duration, Self::Event)>;
+
+    /// Execute a state transition
+    fn transition(
+        state: &Self::State,
+        event: Self::Event,
+        ctx: &mut Ctx,
+    ) -> Result<Self::State, TransitionError>;
+}
+
+/// Fires `M`'s timeout transitions on a shared `(state, context)` pair
+///
+/// Transitions made through [`TimeoutScheduler::transition`] cancel the
+/// pending timers and arm the new state's. A timer that fires after the
+/// state was changed some other way is ignored.
+pub struct TimeoutScheduler<M: TimedStateMachine<Ctx>, Ctx> {
+    inner: Arc<Inner<M, Ctx>>,
+}
+
+struct Inner<M: TimedStateMachine<Ctx>, Ctx> {
+    machine: Arc<Mutex<(M::State, Ctx)>>,
+    timers: std::sync::Mutex<Vec<AbortHandle>>,
+    /// Bumped whenever timers are re-armed; stale timers see a newer value
+    epoch: AtomicU64,
+}
+
+impl<M, Ctx> TimeoutScheduler<M, Ctx>
+where
+    M: TimedStateMachine<Ctx> + 'static,
+    Ctx: Send + 'static,
+{
+    pub fn new(machine: Arc<Mutex<(M::State, Ctx)>>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                machine,
+                timers: std::sync::Mutex::new(Vec::new()),
+                epoch: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Arm the timeouts of the current state, replacing any pending timers
+    pub async fn schedule(&self) {
+        let guard = self.inner.machine.lock().await;
+        self.inner.rearm(&guard.0);
+    }
+
+    /// Execute an explicit transition, superseding the current state's timers
+    pub async fn transition(&self, event: M::Event) -> Result<M::State, TransitionError> {
+        let mut guard = self.inner.machine.lock().await;
+        let (state, ctx) = &mut *guard;
+        let next = M::transition(state, event, ctx)?;
+        *state = next;
+        self.inner.rearm(&next);
+        Ok(next)
+    }
+
+    /// Cancel all pending timers
+    pub fn cancel(&self) {
+        self.inner.epoch.fetch_add(1, Ordering::SeqCst);
+        self.inner.abort_timers();
+    }
+
+    /// Number of timers currently armed
+    pub fn pending(&self) -> usize {
+        self.inner
+            .timers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|timer| !timer.is_finished())
+            .count()
+    }
+}
+
+impl<M: TimedStateMachine<Ctx>, Ctx> Drop for TimeoutScheduler<M, Ctx> {
+    fn drop(&mut self) {
+        self.inner.epoch.fetch_add(1, Ordering::SeqCst);
+        self.inner.abort_timers();
+    }
+}
+
+impl<M: TimedStateMachine<Ctx>, Ctx> Inner<M, Ctx> {
+    fn abort_timers(&self) {
+        let mut timers = self.timers.lock().unwrap_or_else(|e| e.into_inner());
+        for timer in timers.drain(..) {
+            timer.abort();
+        }
+    }
+}
+
+impl<M, Ctx> Inner<M, Ctx>
+where
+    M: TimedStateMachine<Ctx> + 'static,
+    Ctx: Send + 'static,
+{
+    /// Cancel pending timers and arm those of `state`
+    ///
+    /// Callers hold the machine lock, so a timer cannot fire in between.
+    fn rearm(self: &Arc<Self>, state: &M::State) {
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst) + 1;
+        self.abort_timers();
+
+        let mut timers = self.timers.lock().unwrap_or_else(|e| e.into_inner());
+        for (after, event) in M::timeout_transitions(state) {
+            let inner = Arc::clone(self);
+            let armed_in = *state;
+            let handle = tokio::spawn(async move {
+                tokio::time::sleep(after).await;
+                inner.fire(epoch, armed_in, event).await;
+            });
+            timers.push(handle.abort_handle());
+        }
+    }
+
+    async fn fire(self: Arc<Self>, epoch: u64, armed_in: M::State, event: M::Event) {
+        let mut guard = self.machine.lock().await;
+        // Superseded by an explicit transition, or the state moved on directly
+        if self.epoch.load(Ordering::SeqCst) != epoch || guard.0 != armed_in {
+            return;
+        }
+
+        let (state, ctx) = &mut *guard;
+        match M::transition(state, event, ctx) {
+            Ok(next) => {
+                tracing::info!("Timeout {:?} moved {:?} to {:?}", event, armed_in, next);
+                *state = next;
+                self.rearm(&next);
+            }
+            Err(e) => tracing::warn!("Timeout {:?} in {:?} was rejected: {}", event, armed_in, e),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Audit log retention
+//!
+//! `audit_logs` rows, including the `StateTransitionAudit`s state machines
+//! write there, would otherwise accumulate forever. `AuditArchiver` moves
+//! rows older than the policy's `hot_days` to cold [`Storage`] as one
+//! gzip-compressed NDJSON file per UTC day, `audit/{year}/{month}/{day}.ndjson.gz`,
+//! deletes them from the database, and deletes archive files older than
+//! `delete_after_days`. It runs on a `CronScheduler` schedule (daily at
+//! 02:00 UTC by default).
+//!
+//! A file archived to twice (e.g. with `hot_days = 0`) holds one gzip member
+//! per run; `zcat` and [`read_archive`] read them all. If rows cannot be
+//! deleted after their file is uploaded, the next run archives them again,
+//! so restores should drop duplicate row IDs.
+
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, NaiveTime, Utc};
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::application::services::{parse_cron_expression, CronScheduler};
+use crate::config::AuditRetentionPolicy;
+use crate::infrastructure::database::RepositoryErrorExt;
+use crate::infrastructure::storage::Storage;
+use crate::shared::{AppError, AppResult};
+
+/// Daily at 02:00 (UTC)
+pub const DEFAULT_AUDIT_ARCHIVE_SCHEDULE: &str = "0 2 * * *";
+
+/// Storage prefix of the archive files
+const ARCHIVE_PREFIX: &str = "audit";
+
+const ARCHIVE_SUFFIX: &str = ".ndjson.gz";
+
+/// One `audit_logs` row, as written to the archive
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedAuditLog {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub action: String,
+    pub resource: String,
+    pub resource_id: Option<Uuid>,
+    pub details: Option<serde_json::Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What one archiver run did
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AuditArchiveReport {
+    /// Rows moved from the database to cold storage
+    pub rows_archived: usize,
+    /// Archive files written to
+    pub files_written: Vec<String>,
+    /// Archive files deleted for being older than `delete_after_days`
+    pub files_deleted: Vec<String>,
+}
+
+/// Moves old audit log rows to cold storage on a schedule
+pub struct AuditArchiver {
+    policy: AuditRetentionPolicy,
+    pool: PgPool,
+    storage: Arc<dyn Storage>,
+    /// 5-field cron expression, see [`parse_cron_expression`]
+    schedule: String,
+}
+
+impl AuditArchiver {
+    /// Archive on [`DEFAULT_AUDIT_ARCHIVE_SCHEDULE`]
+    pub fn new(policy: AuditRetentionPolicy, pool: PgPool, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            policy,
+            pool,
+            storage,
+            schedule: DEFAULT_AUDIT_ARCHIVE_SCHEDULE.to_string(),
+        }
+    }
+
+    /// Run on a different cron schedule
+    pub fn with_schedule(mut self, expression: impl Into<String>) -> AppResult<Self> {
+        let expression = expression.into();
+        parse_cron_expression(&expression)?;
+        self.schedule = expression;
+        Ok(self)
+    }
+
+    /// Archive at every scheduled time of `scheduler`
+    pub async fn schedule(self: Arc<Self>, scheduler: &CronScheduler) -> AppResult<()> {
+        let expression = self.schedule.clone();
+        scheduler
+            .schedule("audit_archive", &expression, move || {
+                let archiver = Arc::clone(&self);
+                async move {
+                    match Self::run(archiver.policy, &archiver.pool, Arc::clone(&archiver.storage)).await {
+                        Ok(report) => tracing::info!(
+                            rows_archived = report.rows_archived,
+                            files_deleted = report.files_deleted.len(),
+                            "Archived audit logs"
+                        ),
+                        Err(e) => e.log_with_operation(concat!(file!(), ":", line!()), "archive_audit_logs"),
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Archive rows older than `policy.hot_days`, then delete archive files
+    /// older than `policy.delete_after_days`
+    ///
+    /// Rows are archived a day at a time, oldest first, covering at most
+    /// `policy.archive_days` days (0 for all of them).
+    pub async fn run(
+        policy: AuditRetentionPolicy,
+        pool: &PgPool,
+        storage: Arc<dyn Storage>,
+    ) -> AppResult<AuditArchiveReport> {
+        let now = Utc::now();
+        let cutoff = now - ChronoDuration::days(policy.hot_days.into());
+        let mut report = AuditArchiveReport::default();
+
+        let mut from = DateTime::<Utc>::UNIX_EPOCH;
+        let mut first_day = None;
+        while let Some(oldest) = oldest_row_between(pool, from, cutoff).await? {
+            let day = oldest.date_naive();
+            let first_day = *first_day.get_or_insert(day);
+            if policy.archive_days > 0 && day >= first_day + ChronoDuration::days(policy.archive_days.into()) {
+                break;
+            }
+
+            let end = start_of_day(day + ChronoDuration::days(1)).min(cutoff);
+            let rows = sqlx::query_as!(
+                ArchivedAuditLog,
+                r#"
+                SELECT id, user_id, action, resource, resource_id, details, created_at
+                FROM audit_logs
+                WHERE created_at >= $1 AND created_at < $2
+                ORDER BY created_at, id
+                "#,
+                start_of_day(day),
+                end
+            )
+            .fetch_all(pool)
+            .await
+            .map_db_error("find", "audit log")?;
+
+            report.rows_archived += rows.len();
+            report.files_written.push(archive_day(pool, storage.as_ref(), day, &rows).await?);
+            from = end;
+        }
+
+        let expired_before = now.date_naive() - ChronoDuration::days(policy.delete_after_days.into());
+        report.files_deleted = delete_archives_before(storage.as_ref(), expired_before).await?;
+        Ok(report)
+    }
+}
+
+/// Creation time of the oldest row in `[from, to)`
+async fn oldest_row_between(
+    pool: &PgPool,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> AppResult<Option<DateTime<Utc>>> {
+    sqlx::query_scalar!(
+        r#"SELECT MIN(created_at) FROM audit_logs WHERE created_at >= $1 AND created_at < $2"#,
+        from,
+        to
+    )
+    .fetch_one(pool)
+    .await
+    .map_db_error("find", "audit log")
+}
+
+/// Append `rows` to the day's archive file, then delete them from the database
+async fn archive_day(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    day: NaiveDate,
+    rows: &[ArchivedAuditLog],
+) -> AppResult<String> {
+    let key = archive_key(day);
+    let mut data = storage.get(&key).await?.unwrap_or_default();
+    data.extend(encode_archive(rows)?);
+    storage.put(&key, &data).await?;
+
+    let ids: Vec<Uuid> = rows.iter().map(|row| row.id).collect();
+    sqlx::query!("DELETE FROM audit_logs WHERE id = ANY($1)", &ids)
+        .execute(pool)
+        .await
+        .map_db_error("delete", "audit log")?;
+    Ok(key)
+}
+
+fn start_of_day(day: NaiveDate) -> DateTime<Utc> {
+    day.and_time(NaiveTime::MIN).and_utc()
+}
+
+/// `audit/{year}/{month}/{day}.ndjson.gz`
+pub fn archive_key(day: NaiveDate) -> String {
+    format!("{}/{}{}", ARCHIVE_PREFIX, day.format("%Y/%m/%d"), ARCHIVE_SUFFIX)
+}
+
+/// Day an archive file covers, None for other keys
+fn archive_date(key: &str) -> Option<NaiveDate> {
+    let path = key.strip_prefix(ARCHIVE_PREFIX)?.strip_prefix('/')?.strip_suffix(ARCHIVE_SUFFIX)?;
+    NaiveDate::parse_from_str(path, "%Y/%m/%d").ok()
+}
+
+/// Gzip member holding one JSON row per line
+fn encode_archive(rows: &[ArchivedAuditLog]) -> AppResult<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for row in rows {
+        let line = serde_json::to_vec(row)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize audit log {}: {}", row.id, e)))?;
+        encoder
+            .write_all(&line)
+            .and_then(|_| encoder.write_all(b"\n"))
+            .map_err(|e| AppError::Internal(format!("Failed to compress audit archive: {}", e)))?;
+    }
+    encoder
+        .finish()
+        .map_err(|e| AppError::Internal(format!("Failed to compress audit archive: {}", e)))
+}
+
+/// Rows in an archive file, across every gzip member
+pub fn read_archive(data: &[u8]) -> AppResult<Vec<ArchivedAuditLog>> {
+    let mut text = String::new();
+    MultiGzDecoder::new(data)
+        .read_to_string(&mut text)
+        .map_err(|e| AppError::Storage(format!("Invalid audit archive: {}", e)))?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str(line).map_err(|e| AppError::Storage(format!("Invalid audit archive row: {}", e)))
+        })
+        .collect()
+}
+
+/// Keys of every archive file
+///
+/// Object stores list every key under a prefix while the local filesystem
+/// lists one directory level, so directories are descended into.
+async fn list_archives(storage: &dyn Storage) -> AppResult<Vec<String>> {
+    let mut archives = Vec::new();
+    let mut prefixes = vec![ARCHIVE_PREFIX.to_string()];
+    while let Some(prefix) = prefixes.pop() {
+        for key in storage.list(&prefix).await? {
+            if archive_date(&key).is_some() {
+                archives.push(key);
+            } else if key.len() > prefix.len() && key.matches('/').count() < 3 {
+                prefixes.push(key);
+            }
+        }
+    }
+    archives.sort();
+    Ok(archives)
+}
+
+/// Delete archive files covering days before `before`
+async fn delete_archives_before(storage: &dyn Storage, before: NaiveDate) -> AppResult<Vec<String>> {
+    let mut deleted = Vec::new();
+    for key in list_archives(storage).await? {
+        if archive_date(&key).is_some_and(|day| day < before) {
+            storage.delete(&key).await?;
+            deleted.push(key);
+        }
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::storage::LocalFsStorage;
+
+    fn storage_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("audit-archive-{}", Uuid::new_v4()))
+    }
+
+    fn row(action: &str) -> ArchivedAuditLog {
+        ArchivedAuditLog {
+            id: Uuid::new_v4(),
+            user_id: None,
+            action: action.to_string(),
+            resource: "order".to_string(),
+            resource_id: Some(Uuid::new_v4()),
+            details: Some(serde_json::json!({ "from_state": "draft", "to_state": "cancelled" })),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_archive_key() {
+        let day = NaiveDate::from_ymd_opt(2026, 3, 7).unwrap();
+        assert_eq!(archive_key(day), "audit/2026/03/07.ndjson.gz");
+        assert_eq!(archive_date(&archive_key(day)), Some(day));
+        assert_eq!(archive_date("audit/2026/03"), None);
+    }
+
+    #[test]
+    fn test_appended_archives_read_back() {
+        let first = vec![row("login"), row("logout")];
+        let second = vec![row("auto_cancelled_stale")];
+        let mut data = encode_archive(&first).unwrap();
+        data.extend(encode_archive(&second).unwrap());
+
+        let rows = read_archive(&data).unwrap();
+        assert_eq!(rows, [first, second].concat());
+    }
+
+    #[tokio::test]
+    async fn test_expired_archives_are_deleted() {
+        let dir = storage_dir();
+        let storage = LocalFsStorage::new(&dir.to_string_lossy());
+        let old = NaiveDate::from_ymd_opt(2019, 12, 31).unwrap();
+        let recent = NaiveDate::from_ymd_opt(2026, 1, 2).unwrap();
+        for day in [old, recent] {
+            storage.put(&archive_key(day), &encode_archive(&[row("login")]).unwrap()).await.unwrap();
+        }
+
+        let deleted = delete_archives_before(&storage, NaiveDate::from_ymd_opt(2020, 1, 1).unwrap()).await.unwrap();
+        assert_eq!(deleted, vec![archive_key(old)]);
+        assert_eq!(list_archives(&storage).await.unwrap(), vec![archive_key(recent)]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires test database to be running
+    async fn test_run_moves_rows_to_archive() {
+        let pool = crate::testing::create_test_pool().await;
+        let created_at = Utc::now() - ChronoDuration::minutes(1);
+        let mut ids: Vec<Uuid> = Vec::new();
+        for i in 0..100 {
+            let id = sqlx::query_scalar!(
+                r#"
+                INSERT INTO audit_logs (action, resource, details, created_at)
+                VALUES ('state_transition', 'order', $1, $2)
+                RETURNING id
+                "#,
+                serde_json::json!({ "sequence": i }),
+                created_at
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            ids.push(id);
+        }
+
+        let dir = storage_dir();
+        let storage: Arc<dyn Storage> = Arc::new(LocalFsStorage::new(&dir.to_string_lossy()));
+        let policy = AuditRetentionPolicy { hot_days: 0, archive_days: 0, delete_after_days: 2190 };
+        let report = AuditArchiver::run(policy, &pool, storage.clone()).await.unwrap();
+        assert!(report.rows_archived >= 100);
+
+        let key = archive_key(created_at.date_naive());
+        let archived: Vec<Uuid> = read_archive(&storage.get(&key).await.unwrap().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|row| row.id)
+            .collect();
+        assert!(ids.iter().all(|id| archived.contains(id)));
+
+        let remaining: i64 = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM audit_logs WHERE id = ANY($1)"#, &ids)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -1,6 +1,10 @@
-use sqlx::SqlitePool;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use std::collections::HashMap;
 use crate::shared::AppResult;
 
+/// Maximum bound parameters per statement (SQLite's default limit is 999)
+const MAX_BATCH_PARAMS: usize = 900;
+
 pub struct LocalDb {
     pool: SqlitePool,
 }
@@ -8,11 +12,135 @@ pub struct LocalDb {
 impl LocalDb {
     pub async fn new(db_path: &str) -> AppResult<Self> {
         let pool = SqlitePool::connect(db_path).await?;
-        Ok(Self { pool })
+        let db = Self { pool };
+        db.ensure_kv_table().await?;
+        Ok(db)
     }
 
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
+
+    async fn ensure_kv_table(&self) -> AppResult<()> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS kv_store (key TEXT PRIMARY KEY NOT NULL, value BLOB NOT NULL)",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Read a single key
+    pub async fn read(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        let row = sqlx::query("SELECT value FROM kv_store WHERE key = ?")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| row.get("value")))
+    }
+
+    /// Write a single key
+    pub async fn write(&self, key: &str, value: &[u8]) -> AppResult<()> {
+        sqlx::query(
+            "INSERT INTO kv_store (key, value) VALUES (?, ?) \
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete a single key
+    pub async fn delete(&self, key: &str) -> AppResult<()> {
+        sqlx::query("DELETE FROM kv_store WHERE key = ?")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// List keys starting with `prefix`, sorted
+    pub async fn list_keys(&self, prefix: &str) -> AppResult<Vec<String>> {
+        let rows = sqlx::query("SELECT key FROM kv_store WHERE substr(key, 1, length(?1)) = ?1 ORDER BY key")
+            .bind(prefix)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|row| row.get("key")).collect())
+    }
+
+    /// Read many keys in one query
+    /// Results are returned in the same order as `keys`; missing keys are `None`.
+    pub async fn batch_read(&self, keys: &[String]) -> AppResult<Vec<Option<Vec<u8>>>> {
+        let mut found: HashMap<String, Vec<u8>> = HashMap::with_capacity(keys.len());
+
+        // A single statement unless the key count exceeds SQLite's parameter limit
+        for chunk in keys.chunks(MAX_BATCH_PARAMS) {
+            let mut query: QueryBuilder<Sqlite> =
+                QueryBuilder::new("SELECT key, value FROM kv_store WHERE key IN (");
+            let mut separated = query.separated(", ");
+            for key in chunk {
+                separated.push_bind(key);
+            }
+            separated.push_unseparated(")");
+
+            for row in query.build().fetch_all(&self.pool).await? {
+                found.insert(row.get("key"), row.get("value"));
+            }
+        }
+
+        Ok(keys.iter().map(|key| found.get(key).cloned()).collect())
+    }
+
+    /// Write many keys atomically
+    pub async fn batch_write(&self, entries: &[(String, Vec<u8>)]) -> AppResult<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        // Two bound parameters per entry
+        for chunk in entries.chunks(MAX_BATCH_PARAMS / 2) {
+            let mut query: QueryBuilder<Sqlite> =
+                QueryBuilder::new("INSERT INTO kv_store (key, value) ");
+            query.push_values(chunk, |mut row, (key, value)| {
+                row.push_bind(key).push_bind(value);
+            });
+            query.push(" ON CONFLICT(key) DO UPDATE SET value = excluded.value");
+            query.build().execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_read_preserves_key_order() {
+        let db = LocalDb::new("sqlite::memory:").await.unwrap();
+        db.batch_write(&[
+            ("a".to_string(), b"1".to_vec()),
+            ("b".to_string(), b"2".to_vec()),
+        ])
+        .await
+        .unwrap();
+
+        let values = db
+            .batch_read(&["b".to_string(), "missing".to_string(), "a".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(values, vec![Some(b"2".to_vec()), None, Some(b"1".to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn test_batch_write_overwrites_existing_keys() {
+        let db = LocalDb::new("sqlite::memory:").await.unwrap();
+        db.write("a", b"old").await.unwrap();
+        db.batch_write(&[("a".to_string(), b"new".to_vec())]).await.unwrap();
+        assert_eq!(db.read("a").await.unwrap(), Some(b"new".to_vec()));
+    }
+}
@@ -1,9 +1,16 @@
+use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use crate::domain::repositories::ehr::{
+    EhrDraftOrderRepository, EhrMedicationHistoryRepository, EhrPatientMergeRepository,
+    EhrPatientSummaryRepository,
+    IndexedDocument, MedicationRecord, PatientFile, PatientMerge, VisitRecord,
+};
 use crate::infrastructure::database::mumps::{Global, HierarchicalAccess};
 use crate::shared::{AppError, AppResult};
+use crate::infrastructure::tracing::InjectTraceContext;
 
 /// YottaDB adapter - connects to YottaDB via M Web Server REST API
 ///
@@ -72,6 +79,22 @@ impl YottaDbAdapter {
         Self::new(base_url)
     }
 
+    /// Check that the M Web Server is reachable
+    pub async fn ping(&self) -> AppResult<()> {
+        let response = self.client
+            .get(format!("{}/health", self.base_url))
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(format!("YottaDB request failed: {}", e)))?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(AppError::Internal(format!("YottaDB health check failed: {}", response.status())))
+        }
+    }
+
     /// Build URL path for global access
     fn build_path(&self, global: &Global) -> String {
         let mut path = format!("{}/api/v1/global/{}", self.base_url, global.name);
@@ -88,6 +111,7 @@ impl YottaDbAdapter {
 
         let response = self.client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Internal(format!("YottaDB request failed: {}", e)))?;
@@ -109,6 +133,7 @@ impl YottaDbAdapter {
 
         let response = self.client
             .get(&url)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Internal(format!("YottaDB request failed: {}", e)))?;
@@ -174,6 +199,19 @@ impl YottaDbAdapter {
         }
     }
 
+    /// Get patient by MRN, through the `^DPT("MRN",mrn,ien)` index
+    pub async fn find_patient_by_mrn(&self, mrn: &str) -> AppResult<Option<PatientData>> {
+        let index = Global::new("DPT".to_string())
+            .with_subscript("MRN".to_string())
+            .with_subscript(mrn.to_string())
+            .with_subscript(String::new());
+
+        match self.order_next(&index).await?.and_then(|ien| ien.parse::<i64>().ok()) {
+            Some(ien) => self.get_patient(ien).await,
+            None => Ok(None),
+        }
+    }
+
     /// Create patient in ^DPT
     pub async fn create_patient(&self, data: &PatientData) -> AppResult<i64> {
         // Get next IEN
@@ -339,6 +377,481 @@ impl YottaDbAdapter {
             None => Ok(None),
         }
     }
+
+    // === TIU Documents (File #8925) ===
+
+    /// IENs of every document in ^TIU(8925), in order
+    pub async fn list_document_iens(&self) -> AppResult<Vec<i64>> {
+        let mut iens = Vec::new();
+        let mut ien = "0".to_string();
+
+        loop {
+            let global = tiu_file().with_subscript(ien.clone());
+            // Numeric IENs sort before the "AU" and "C" cross-references
+            match self.order_next(&global).await?.and_then(|n| n.parse::<i64>().ok()) {
+                Some(next) => {
+                    iens.push(next);
+                    ien = next.to_string();
+                }
+                None => break,
+            }
+        }
+
+        Ok(iens)
+    }
+
+    /// Documents changed after `after` (YYYYMMDDHHMMSS), oldest first
+    ///
+    /// Walks the ^TIU(8925,"AU",updated,IEN) cross-reference and returns each
+    /// IEN with the time it changed.
+    pub async fn documents_updated_after(&self, after: i64) -> AppResult<Vec<(i64, i64)>> {
+        let index = tiu_file().with_subscript("AU".to_string());
+        let mut changes = Vec::new();
+        let mut updated = after.to_string();
+
+        while let Some(next) = self.order_next(&index.clone().with_subscript(updated.clone())).await? {
+            updated = next;
+            let Ok(updated_at) = updated.parse::<i64>() else {
+                break;
+            };
+            for ien in self.order(&index.clone().with_subscript(updated.clone())).await? {
+                if let Ok(ien) = ien.parse() {
+                    changes.push((ien, updated_at));
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// A document with its text and addenda, as copied into the full-text index
+    pub async fn get_document(&self, ien: i64) -> AppResult<Option<IndexedDocument>> {
+        let entry = tiu_file().with_subscript(ien.to_string());
+        let Some(node) = self.get(&entry.clone().with_subscript("0".to_string())).await? else {
+            return Ok(None);
+        };
+
+        let mut content = self.word_processing_text(&entry.clone().with_subscript("TEXT".to_string())).await?;
+        let addenda = entry.with_subscript("ADD".to_string());
+        for addendum in self.order(&addenda).await? {
+            let text_root = addenda
+                .clone()
+                .with_subscript(addendum)
+                .with_subscript("TEXT".to_string());
+            let text = self.word_processing_text(&text_root).await?;
+            if !text.is_empty() {
+                if !content.is_empty() {
+                    content.push_str("\n\n");
+                }
+                content.push_str(&text);
+            }
+        }
+
+        Ok(Some(indexed_document(ien, &node, content)))
+    }
+
+    /// Link an uploaded file to a document by recording its storage key in
+    /// `^TIU(8925,ien,"FILE")`
+    ///
+    /// Returns false when there is no document `ien`.
+    pub async fn set_document_file(&self, ien: i64, key: &str) -> AppResult<bool> {
+        let entry = tiu_file().with_subscript(ien.to_string());
+        if self.get(&entry.clone().with_subscript("0".to_string())).await?.is_none() {
+            return Ok(false);
+        }
+        self.set(&entry.with_subscript("FILE".to_string()), key).await?;
+        Ok(true)
+    }
+
+    /// Text of a word-processing field: lines in `root,n,0`, header in `root,0`
+    async fn word_processing_text(&self, root: &Global) -> AppResult<String> {
+        let mut lines = Vec::new();
+        for line in self.order(root).await? {
+            if line == "0" {
+                continue;
+            }
+            let node = root.clone().with_subscript(line).with_subscript("0".to_string());
+            if let Some(text) = self.get(&node).await? {
+                lines.push(text);
+            }
+        }
+        Ok(lines.join("\n"))
+    }
+
+    /// IEN and 0-node of each of a patient's entries in a file, found by
+    /// walking its "C" cross-reference
+    ///
+    /// `file` is the file root (e.g. `^PS(52)`). Index entries whose 0-node
+    /// is missing are skipped.
+    async fn patient_entries(&self, file: &Global, patient_ien: i64) -> AppResult<Vec<(i64, String)>> {
+        let mut entries = Vec::new();
+        let mut ien = String::new();
+
+        loop {
+            let index = file
+                .clone()
+                .with_subscript("C".to_string())
+                .with_subscript(patient_ien.to_string())
+                .with_subscript(ien.clone());
+
+            match self.order_next(&index).await? {
+                Some(n) => {
+                    ien = n;
+                    let node = file
+                        .clone()
+                        .with_subscript(ien.clone())
+                        .with_subscript("0".to_string());
+                    if let Some(v) = self.get(&node).await? {
+                        entries.push((ien.parse().unwrap_or(0), v));
+                    }
+                }
+                None => break,
+            }
+        }
+
+        Ok(entries)
+    }
+
+    /// Count a patient's entries in a file by walking its "C" cross-reference
+    ///
+    /// `matches` receives the `^`-split pieces of each entry's 0-node.
+    async fn count_patient_entries<F>(&self, file: &Global, patient_ien: i64, matches: F) -> AppResult<u32>
+    where
+        F: Fn(&[&str]) -> bool,
+    {
+        let entries = self.patient_entries(file, patient_ien).await?;
+        let count = entries
+            .iter()
+            .filter(|(_, node)| matches(&node.split('^').collect::<Vec<_>>()))
+            .count();
+        Ok(u32::try_from(count).unwrap_or(u32::MAX))
+    }
+}
+
+/// Non-empty piece of a 0-node
+fn optional_piece(parts: &[&str], index: usize) -> Option<String> {
+    parts.get(index).filter(|p| !p.is_empty()).map(|p| p.to_string())
+}
+
+/// ^TIU(8925) - TIU Document File
+fn tiu_file() -> Global {
+    Global::new("TIU".to_string()).with_subscript("8925".to_string())
+}
+
+/// Map a ^TIU(8925) 0-node onto an index entry, with the codes the YottaDB API reports
+fn indexed_document(ien: i64, node: &str, content: String) -> IndexedDocument {
+    let parts: Vec<&str> = node.split('^').collect();
+    let created = parts.get(5).copied().unwrap_or("");
+    IndexedDocument {
+        ien,
+        patient_ien: parts.get(0).and_then(|s| s.parse().ok()).unwrap_or(0),
+        document_type: match parts.get(2).copied().unwrap_or("PN") {
+            "PN" => "progress_note".to_string(),
+            "HP" => "hp_note".to_string(),
+            "DS" => "discharge_summary".to_string(),
+            "CN" => "consultation".to_string(),
+            "OP" => "operative_note".to_string(),
+            other => other.to_string(),
+        },
+        title: parts.get(3).unwrap_or(&"").to_string(),
+        content,
+        status: match parts.get(8).copied().unwrap_or("U") {
+            "U" => "unsigned".to_string(),
+            "S" => "signed".to_string(),
+            "A" => "amended".to_string(),
+            "R" => "retracted".to_string(),
+            other => other.to_string(),
+        },
+        // FileMan-style YYYYMMDD.HHMMSS
+        created_at: chrono::NaiveDateTime::parse_from_str(created, "%Y%m%d.%H%M%S")
+            .map(|dt| dt.and_utc())
+            .unwrap_or_default(),
+    }
+}
+
+fn visit_record(ien: i64, node: &str) -> VisitRecord {
+    let parts: Vec<&str> = node.split('^').collect();
+    VisitRecord {
+        ien,
+        patient_ien: parts.get(0).and_then(|s| s.parse().ok()).unwrap_or(0),
+        visit_date: parts.get(2).unwrap_or(&"").to_string(),
+        status: match parts.get(7).copied().unwrap_or("A") {
+            "A" => "active".to_string(),
+            "C" => "completed".to_string(),
+            "X" => "cancelled".to_string(),
+            other => other.to_string(),
+        },
+    }
+}
+
+fn medication_record(ien: i64, node: &str) -> MedicationRecord {
+    let parts: Vec<&str> = node.split('^').collect();
+    MedicationRecord {
+        ien,
+        patient_ien: parts.get(0).and_then(|s| s.parse().ok()).unwrap_or(0),
+        drug_name: parts.get(1).unwrap_or(&"").to_string(),
+        drug_code: optional_piece(&parts, 2),
+        dose: parts.get(3).unwrap_or(&"").to_string(),
+        route: parts.get(4).unwrap_or(&"").to_string(),
+        frequency: parts.get(5).unwrap_or(&"").to_string(),
+        start_date: parts.get(6).unwrap_or(&"").to_string(),
+        end_date: optional_piece(&parts, 7),
+        prescriber_ien: parts.get(8).and_then(|s| s.parse().ok()).filter(|&p: &i64| p > 0),
+        status: match parts.get(9).copied().unwrap_or("A") {
+            "A" => "active".to_string(),
+            "D" => "discontinued".to_string(),
+            "C" => "completed".to_string(),
+            "H" => "on_hold".to_string(),
+            other => other.to_string(),
+        },
+        instructions: optional_piece(&parts, 10),
+    }
+}
+
+#[async_trait]
+impl EhrPatientSummaryRepository for YottaDbAdapter {
+    async fn count_active_problems(&self, patient_ien: i64) -> AppResult<u32> {
+        let file = Global::new("AUPNPROB".to_string());
+        self.count_patient_entries(&file, patient_ien, |p| p.get(5) == Some(&"A")).await
+    }
+
+    async fn count_current_medications(&self, patient_ien: i64) -> AppResult<u32> {
+        let file = Global::new("PS".to_string()).with_subscript("52".to_string());
+        self.count_patient_entries(&file, patient_ien, |p| p.get(9) == Some(&"A")).await
+    }
+
+    async fn count_known_allergies(&self, patient_ien: i64) -> AppResult<u32> {
+        // Allergies without a status are treated as active, matching get_allergy
+        let file = Global::new("GMRA".to_string());
+        self.count_patient_entries(&file, patient_ien, |p| p.get(5) != Some(&"I")).await
+    }
+
+    async fn count_pending_labs(&self, patient_ien: i64) -> AppResult<u32> {
+        let file = Global::new("LR".to_string()).with_subscript("63".to_string());
+        self.count_patient_entries(&file, patient_ien, |p| p.get(10) == Some(&"P")).await
+    }
+
+    async fn count_upcoming_appointments(&self, patient_ien: i64, from: chrono::NaiveDate) -> AppResult<u32> {
+        let from = from.format("%Y-%m-%d").to_string();
+        let file = Global::new("SD".to_string()).with_subscript("44".to_string());
+        self.count_patient_entries(&file, patient_ien, |p| {
+            p.get(7) == Some(&"S") && p.get(1).is_some_and(|date| *date >= from.as_str())
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl EhrMedicationHistoryRepository for YottaDbAdapter {
+    async fn find_visit(&self, visit_ien: i64) -> AppResult<Option<VisitRecord>> {
+        let node = Global::new("AUPNVSIT".to_string())
+            .with_subscript(visit_ien.to_string())
+            .with_subscript("0".to_string());
+        Ok(self.get(&node).await?.map(|v| visit_record(visit_ien, &v)))
+    }
+
+    async fn find_patient_visits(&self, patient_ien: i64) -> AppResult<Vec<VisitRecord>> {
+        let file = Global::new("AUPNVSIT".to_string());
+        let entries = self.patient_entries(&file, patient_ien).await?;
+        Ok(entries.iter().map(|(ien, node)| visit_record(*ien, node)).collect())
+    }
+
+    async fn find_patient_medications(&self, patient_ien: i64) -> AppResult<Vec<MedicationRecord>> {
+        let file = Global::new("PS".to_string()).with_subscript("52".to_string());
+        let entries = self.patient_entries(&file, patient_ien).await?;
+        Ok(entries.iter().map(|(ien, node)| medication_record(*ien, node)).collect())
+    }
+}
+
+/// File root and the 0-node piece (0-based) holding the patient IEN
+fn patient_file_global(file: PatientFile) -> (Global, usize) {
+    let global = |name: &str| Global::new(name.to_string());
+    match file {
+        PatientFile::Visits => (global("AUPNVSIT"), 0),
+        PatientFile::Problems => (global("AUPNPROB"), 1),
+        PatientFile::Allergies => (global("GMRA"), 1),
+        PatientFile::Vitals => (global("GMR").with_subscript("120.5".to_string()), 0),
+        PatientFile::Medications => (global("PS").with_subscript("52".to_string()), 0),
+        PatientFile::Labs => (global("LR").with_subscript("63".to_string()), 0),
+        PatientFile::Orders => (global("OR").with_subscript("100".to_string()), 0),
+        PatientFile::Documents => (global("TIU").with_subscript("8925".to_string()), 0),
+        PatientFile::Appointments => (global("SD").with_subscript("44".to_string()), 0),
+    }
+}
+
+/// ^DPT(ien,"MERGE"): `merged_into^merged_by^merged_at`, with the moved
+/// entries under it as ^DPT(ien,"MERGE",file,record_ien)
+fn merge_node(merged_ien: i64) -> Global {
+    Global::new("DPT".to_string())
+        .with_subscript(merged_ien.to_string())
+        .with_subscript("MERGE".to_string())
+}
+
+/// ^DPT(ien,-9): VistA's merged-into pointer, which drops the record from
+/// patient lists and MRN lookups
+fn merged_into_node(merged_ien: i64) -> Global {
+    Global::new("DPT".to_string())
+        .with_subscript(merged_ien.to_string())
+        .with_subscript("-9".to_string())
+}
+
+#[async_trait]
+impl EhrPatientMergeRepository for YottaDbAdapter {
+    async fn patient_exists(&self, patient_ien: i64) -> AppResult<bool> {
+        let node = Global::new("DPT".to_string())
+            .with_subscript(patient_ien.to_string())
+            .with_subscript("0".to_string());
+        Ok(self.get(&node).await?.is_some())
+    }
+
+    async fn find_merge(&self, merged_ien: i64) -> AppResult<Option<PatientMerge>> {
+        let root = merge_node(merged_ien);
+        let Some(value) = self.get(&root).await? else {
+            return Ok(None);
+        };
+        let parts: Vec<&str> = value.split('^').collect();
+        let invalid = || AppError::Internal(format!("Invalid merge record for patient {}: {}", merged_ien, value));
+        let merged_into_ien = parts.first().and_then(|s| s.parse().ok()).ok_or_else(invalid)?;
+        let merged_by = parts.get(1).and_then(|s| uuid::Uuid::parse_str(s).ok()).ok_or_else(invalid)?;
+        let merged_at = parts
+            .get(2)
+            .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+            .ok_or_else(invalid)?
+            .with_timezone(&chrono::Utc);
+
+        let mut moved_records = Vec::new();
+        for key in self.order(&root).await? {
+            let Some(file) = PatientFile::parse(&key) else {
+                continue;
+            };
+            let file_root = root.clone().with_subscript(key);
+            for ien in self.order(&file_root).await? {
+                if let Ok(ien) = ien.parse() {
+                    moved_records.push((file, ien));
+                }
+            }
+        }
+
+        Ok(Some(PatientMerge { merged_ien, merged_into_ien, merged_by, merged_at, moved_records }))
+    }
+
+    async fn find_record_iens(&self, file: PatientFile, patient_ien: i64) -> AppResult<Vec<i64>> {
+        let (root, _) = patient_file_global(file);
+        let entries = self.patient_entries(&root, patient_ien).await?;
+        Ok(entries.into_iter().map(|(ien, _)| ien).collect())
+    }
+
+    async fn reassign_record(
+        &self,
+        file: PatientFile,
+        record_ien: i64,
+        from_patient_ien: i64,
+        to_patient_ien: i64,
+    ) -> AppResult<()> {
+        let (root, piece) = patient_file_global(file);
+        let node = root
+            .clone()
+            .with_subscript(record_ien.to_string())
+            .with_subscript("0".to_string());
+        if let Some(value) = self.get(&node).await? {
+            let to_patient = to_patient_ien.to_string();
+            let mut parts: Vec<&str> = value.split('^').collect();
+            if parts.len() <= piece {
+                parts.resize(piece + 1, "");
+            }
+            parts[piece] = &to_patient;
+            self.set(&node, &parts.join("^")).await?;
+        }
+
+        // Index under the new patient before dropping the old entry, so the
+        // record is never missing from both
+        let index = |patient_ien: i64| {
+            root.clone()
+                .with_subscript("C".to_string())
+                .with_subscript(patient_ien.to_string())
+                .with_subscript(record_ien.to_string())
+        };
+        self.set(&index(to_patient_ien), "").await?;
+        self.kill(&index(from_patient_ien)).await
+    }
+
+    async fn save_merge(&self, merge: &PatientMerge) -> AppResult<()> {
+        let root = merge_node(merge.merged_ien);
+        let value = format!("{}^{}^{}", merge.merged_into_ien, merge.merged_by, merge.merged_at.to_rfc3339());
+        self.set(&root, &value).await?;
+        for (file, ien) in &merge.moved_records {
+            let entry = root
+                .clone()
+                .with_subscript(file.as_str().to_string())
+                .with_subscript(ien.to_string());
+            self.set(&entry, "").await?;
+        }
+        self.set(&merged_into_node(merge.merged_ien), &merge.merged_into_ien.to_string()).await
+    }
+
+    async fn delete_merge(&self, merged_ien: i64) -> AppResult<()> {
+        self.kill(&merged_into_node(merged_ien)).await?;
+        self.kill(&merge_node(merged_ien)).await
+    }
+}
+
+/// ^OR(100) - VistA Orders File (File #100)
+fn order_file() -> Global {
+    Global::new("OR".to_string()).with_subscript("100".to_string())
+}
+
+fn order_node(order_ien: i64) -> Global {
+    order_file()
+        .with_subscript(order_ien.to_string())
+        .with_subscript("0".to_string())
+}
+
+#[async_trait]
+impl EhrDraftOrderRepository for YottaDbAdapter {
+    async fn find_stale_drafts(&self, older_than: std::time::Duration) -> AppResult<Vec<i64>> {
+        let older_than = chrono::Duration::from_std(older_than)
+            .map_err(|e| AppError::Validation(format!("Invalid draft order age: {}", e)))?;
+        let cutoff = chrono::Utc::now().naive_utc() - older_than;
+
+        let mut stale = Vec::new();
+        for ien in self.order(&order_file()).await? {
+            // Skips the file header (0) and the "C" patient index
+            let Some(ien) = ien.parse::<i64>().ok().filter(|&ien| ien > 0) else {
+                continue;
+            };
+            let Some(node) = self.get(&order_node(ien)).await? else {
+                continue;
+            };
+            let parts: Vec<&str> = node.split('^').collect();
+            // FileMan-style YYYYMMDD.HHMMSS, written in UTC by create_order
+            let ordered_at = parts
+                .get(5)
+                .and_then(|dt| chrono::NaiveDateTime::parse_from_str(dt, "%Y%m%d.%H%M%S").ok());
+            if parts.get(7) == Some(&"P") && ordered_at.is_some_and(|at| at < cutoff) {
+                stale.push(ien);
+            }
+        }
+        Ok(stale)
+    }
+
+    async fn cancel_draft(&self, order_ien: i64, reason: &str) -> AppResult<bool> {
+        let node = order_node(order_ien);
+        let Some(value) = self.get(&node).await? else {
+            return Ok(false);
+        };
+        let mut parts: Vec<&str> = value.split('^').collect();
+        if parts.get(7) != Some(&"P") {
+            return Ok(false);
+        }
+        if parts.len() < 9 {
+            parts.resize(9, "");
+        }
+        // Status piece 8 to cancelled, with the reason in piece 9
+        parts[7] = "X";
+        parts[8] = reason;
+        self.set(&node, &parts.join("^")).await?;
+        Ok(true)
+    }
 }
 
 impl HierarchicalAccess for YottaDbAdapter {
@@ -354,6 +867,7 @@ impl HierarchicalAccess for YottaDbAdapter {
         let response = self.client
             .post(&url)
             .json(&body)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Internal(format!("YottaDB request failed: {}", e)))?;
@@ -372,6 +886,7 @@ impl HierarchicalAccess for YottaDbAdapter {
 
         let response = self.client
             .delete(&url)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Internal(format!("YottaDB request failed: {}", e)))?;
@@ -476,4 +991,19 @@ mod tests {
         let path = adapter.build_path(&global);
         assert!(path.contains("DOE%2CJOHN"));
     }
+
+    #[test]
+    fn test_indexed_document_maps_tiu_codes() {
+        let document = indexed_document(
+            42,
+            "17^3^DS^Discharge Summary^9^20260314.093000^^^S",
+            "Discharged home.".to_string(),
+        );
+
+        assert_eq!(document.patient_ien, 17);
+        assert_eq!(document.document_type, "discharge_summary");
+        assert_eq!(document.title, "Discharge Summary");
+        assert_eq!(document.status, "signed");
+        assert_eq!(document.created_at.to_rfc3339(), "2026-03-14T09:30:00+00:00");
+    }
 }
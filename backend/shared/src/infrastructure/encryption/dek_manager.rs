@@ -1,19 +1,112 @@
+use crate::domain::entities::EncryptionKey;
+use crate::domain::repositories::KeyRepository;
 use crate::infrastructure::encryption::{MasterKey, Vault};
-use crate::shared::AppResult;
+use crate::infrastructure::repositories::KeyRepositoryImpl;
+use crate::shared::{AppError, AppResult};
 use aes_gcm::{
     aead::{Aead, AeadCore, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
+use sqlx::PgPool;
+use std::sync::{Arc, PoisonError, RwLock};
 use uuid::Uuid;
 
+/// Prefix of DEKs wrapped by the vault's transit engine rather than the master key
+const TRANSIT_PREFIX: &[u8] = b"vault:v";
+
+/// Prefix of field values encrypted with [`Dek::encrypt_field`]
+pub const ENCRYPTED_FIELD_PREFIX: &str = "vault:v1:";
+
 pub struct DekManager {
-    master_key: MasterKey,
+    /// Replaced by `rotate_master_key`
+    master_key: RwLock<Arc<MasterKey>>,
     vault: Box<dyn Vault>,
+    /// Wrapped DEK storage in PostgreSQL; when unset DEKs live in the vault
+    pool: Option<PgPool>,
+    /// Transit key used to wrap DEKs stored in the vault
+    transit_key: Option<String>,
+    /// Held for writing while the master key is rotated, so no stored DEK is
+    /// wrapped or unwrapped with a master key that is being replaced
+    rotation: tokio::sync::RwLock<()>,
+}
+
+/// A DEK stored in `encryption_keys`, with the id of its row
+pub struct Dek {
+    pub key_id: Uuid,
+    key_material: Vec<u8>,
+}
+
+impl Dek {
+    pub fn new(key_id: Uuid, key_material: Vec<u8>) -> Self {
+        Self { key_id, key_material }
+    }
+
+    /// Encrypt a field value as `vault:v1:{base64(nonce || ciphertext)}`
+    pub fn encrypt_field(&self, value: &str) -> AppResult<String> {
+        use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+        let sealed = DekManager::seal(&self.key_material, value.as_bytes())?;
+        Ok(format!("{}{}", ENCRYPTED_FIELD_PREFIX, STANDARD.encode(sealed)))
+    }
+
+    /// Decrypt a value from [`Dek::encrypt_field`]
+    pub fn decrypt_field(&self, encrypted_value: &str) -> AppResult<String> {
+        use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+        let encoded = encrypted_value
+            .strip_prefix(ENCRYPTED_FIELD_PREFIX)
+            .ok_or_else(|| AppError::Encryption("Invalid encrypted field format".to_string()))?;
+        let sealed = STANDARD.decode(encoded)
+            .map_err(|e| AppError::Encryption(format!("Base64 decode error: {}", e)))?;
+        String::from_utf8(DekManager::open(&self.key_material, &sealed)?)
+            .map_err(|e| AppError::Encryption(format!("UTF-8 decode error: {}", e)))
+    }
 }
 
 impl DekManager {
     pub fn new(master_key: MasterKey, vault: Box<dyn Vault>) -> Self {
-        Self { master_key, vault }
+        Self {
+            master_key: RwLock::new(Arc::new(master_key)),
+            vault,
+            pool: None,
+            transit_key: None,
+            rotation: tokio::sync::RwLock::new(()),
+        }
+    }
+
+    /// Store new DEKs in `encryption_keys`, wrapped with the master key
+    ///
+    /// DEKs already in the vault are still read from there.
+    pub fn with_database_storage(mut self, pool: PgPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Wrap DEKs stored in the vault with its transit engine
+    ///
+    /// While the vault is sealed new DEKs fall back to the master key; DEKs
+    /// wrapped either way stay readable.
+    pub fn with_transit_key(mut self, key_name: impl Into<String>) -> Self {
+        self.transit_key = Some(key_name.into());
+        self
+    }
+
+    /// Master key protecting stored DEKs
+    pub fn master_key(&self) -> Arc<MasterKey> {
+        self.master_key.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Repository wrapping DEKs with the current master key
+    ///
+    /// Build it while holding `rotation` so the master key cannot change under it.
+    fn key_repository(&self) -> Option<KeyRepositoryImpl> {
+        self.pool.clone().map(|pool| KeyRepositoryImpl::new(pool, self.master_key()))
+    }
+
+    fn require_key_repository(&self) -> AppResult<KeyRepositoryImpl> {
+        self.key_repository().ok_or_else(|| {
+            AppError::Configuration("Database key storage is not configured".to_string())
+        })
     }
 
     /// Generate a new DEK for an entity
@@ -22,8 +115,16 @@ impl DekManager {
         let dek = Aes256Gcm::generate_key(&mut OsRng);
         let dek_bytes = dek.as_slice().to_vec();
 
-        // Encrypt DEK with master key
-        let encrypted_dek = self.encrypt_dek(&dek_bytes)?;
+        let _rotation = self.rotation.read().await;
+        if let Some(repository) = self.key_repository() {
+            // Repository wraps the DEK before it is written
+            let key = EncryptionKey::new(entity_id, entity_type.to_string(), dek_bytes.clone(), self.master_key().version());
+            repository.create(key).await?;
+            return Ok(dek_bytes);
+        }
+
+        // Encrypt DEK with the transit key, else the master key
+        let encrypted_dek = self.wrap_dek_for_vault(&dek_bytes).await?;
 
         // Store encrypted DEK in vault
         self.vault
@@ -33,16 +134,22 @@ impl DekManager {
         Ok(dek_bytes)
     }
 
-    /// Get DEK for an entity (decrypts from vault)
+    /// Get DEK for an entity (unwraps from the database, else decrypts from vault)
     pub async fn get_dek(&self, entity_id: Uuid, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+        let _rotation = self.rotation.read().await;
+        if let Some(repository) = self.key_repository() {
+            if let Some(key) = repository.find_active_by_entity(entity_id, entity_type).await? {
+                return Ok(Some(key.key_material));
+            }
+        }
+
         // Retrieve encrypted DEK from vault
         let encrypted_dek = self.vault
             .get_dek(&entity_id.to_string(), entity_type)
             .await?;
 
         if let Some(encrypted) = encrypted_dek {
-            // Decrypt DEK with master key
-            let dek = self.decrypt_dek(&encrypted)?;
+            let dek = self.unwrap_dek_from_vault(&encrypted).await?;
             Ok(Some(dek))
         } else {
             Ok(None)
@@ -97,8 +204,149 @@ impl DekManager {
         self.generate_dek(entity_id, entity_type).await
     }
 
-    // Note: DEK rotation should use the DekRotation service which handles
-    // the full workflow including re-encrypting data. See dek_rotation.rs
+    /// Encrypt `plaintext` with the entity's DEK and store it in `encrypted_values`
+    ///
+    /// Stored values are re-encrypted when their DEK is rotated with
+    /// [`DekManager::rotate_dek`]. Requires database key storage.
+    pub async fn encrypt_value(&self, entity_id: Uuid, entity_type: &str, plaintext: &[u8]) -> AppResult<Uuid> {
+        let _rotation = self.rotation.read().await;
+        let repository = self.require_key_repository()?;
+        let key = match repository.find_active_by_entity(entity_id, entity_type).await? {
+            Some(key) => key,
+            None => {
+                let key = EncryptionKey::new(entity_id, entity_type.to_string(), Self::new_dek(), self.master_key().version());
+                repository.create(key).await?
+            }
+        };
+
+        let encrypted_value = Self::seal(&key.key_material, plaintext)?;
+        repository.create_encrypted_value(key.id, &encrypted_value).await
+    }
+
+    /// Decrypt a value stored with [`DekManager::encrypt_value`]
+    pub async fn decrypt_value(&self, value_id: Uuid) -> AppResult<Vec<u8>> {
+        let _rotation = self.rotation.read().await;
+        let repository = self.require_key_repository()?;
+        let (key_id, encrypted_value) = repository.find_encrypted_value(value_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("Encrypted value {} not found", value_id)))?;
+        let key = repository.find_by_id(key_id).await?
+            .ok_or_else(|| AppError::Encryption(format!("DEK {} not found", key_id)))?;
+
+        Self::open(&key.key_material, &encrypted_value)
+    }
+
+    /// The entity's active stored DEK, generated when it has none
+    ///
+    /// Requires database key storage, which gives DEKs their ids.
+    pub async fn active_dek(&self, entity_id: Uuid, entity_type: &str) -> AppResult<Dek> {
+        let _rotation = self.rotation.read().await;
+        let repository = self.require_key_repository()?;
+        let key = match repository.find_active_by_entity(entity_id, entity_type).await? {
+            Some(key) => key,
+            None => {
+                let key = EncryptionKey::new(entity_id, entity_type.to_string(), Self::new_dek(), self.master_key().version());
+                repository.create(key).await?
+            }
+        };
+        Ok(Dek::new(key.id, key.key_material))
+    }
+
+    /// Replace the stored DEK `key_id` with a new one, returning the new DEK's id
+    ///
+    /// The new DEK is wrapped with the current master key and becomes the
+    /// entity's active DEK. Every value encrypted with the old DEK is
+    /// re-encrypted with the new one and repointed to it in the same
+    /// transaction, so a failure leaves the old DEK in place.
+    pub async fn rotate_dek(&self, key_id: Uuid) -> AppResult<Uuid> {
+        let _rotation = self.rotation.read().await;
+        let repository = self.require_key_repository()?;
+        let old_key = repository.find_by_id(key_id).await?
+            .ok_or_else(|| AppError::NotFound(format!("DEK {} not found", key_id)))?;
+        if !old_key.is_active {
+            return Err(AppError::InvalidState(format!("DEK {} has already been rotated", key_id)));
+        }
+
+        let new_key = EncryptionKey::new(
+            old_key.entity_id,
+            old_key.entity_type.clone(),
+            Self::new_dek(),
+            self.master_key().version(),
+        );
+        let new_key_id = new_key.id;
+        let new_dek = new_key.key_material.clone();
+        repository
+            .rotate(&old_key, new_key, |encrypted_value| {
+                let plaintext = Self::open(&old_key.key_material, encrypted_value)?;
+                Self::seal(&new_dek, &plaintext)
+            })
+            .await?;
+
+        Ok(new_key_id)
+    }
+
+    /// Rewrap every stored DEK with `new_master_key` and make it the active master key
+    ///
+    /// Data is not re-encrypted: it stays encrypted with the same DEKs. The new
+    /// key gets the next version unless it already has a later one, and is
+    /// saved to the vault before the DEKs are rewrapped; if rewrapping fails
+    /// the old key is saved back. Returns how many DEKs were rewrapped.
+    ///
+    /// DEKs kept in the vault are not rewrapped, so only rotate once they are
+    /// transit-wrapped or moved to database storage.
+    pub async fn rotate_master_key(&self, new_master_key: MasterKey) -> AppResult<u64> {
+        let _rotation = self.rotation.write().await;
+        let repository = self.require_key_repository()?;
+        let old_master_key = self.master_key();
+        let new_master_key = if new_master_key.version() > old_master_key.version() {
+            new_master_key
+        } else {
+            new_master_key.with_version(old_master_key.version() + 1)
+        };
+
+        new_master_key.save_to_vault(self.vault.as_ref()).await?;
+        let rewrapped = match repository.rewrap_all(&new_master_key).await {
+            Ok(rewrapped) => rewrapped,
+            Err(e) => {
+                if let Err(restore) = old_master_key.save_to_vault(self.vault.as_ref()).await {
+                    restore.log_with_operation(concat!(file!(), ":", line!()), "restore_master_key");
+                }
+                return Err(e);
+            }
+        };
+
+        *self.master_key.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(new_master_key);
+        Ok(rewrapped)
+    }
+
+    fn new_dek() -> Vec<u8> {
+        Aes256Gcm::generate_key(&mut OsRng).to_vec()
+    }
+
+    /// Encrypt with `dek`, returning the nonce followed by the ciphertext
+    fn seal(dek: &[u8], plaintext: &[u8]) -> AppResult<Vec<u8>> {
+        let cipher = Aes256Gcm::new_from_slice(dek)
+            .map_err(|e| AppError::Encryption(format!("Invalid DEK: {}", e)))?;
+
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext)
+            .map_err(|e| AppError::Encryption(format!("Encryption failed: {}", e)))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt the output of [`DekManager::seal`]
+    fn open(dek: &[u8], sealed: &[u8]) -> AppResult<Vec<u8>> {
+        if sealed.len() < 12 {
+            return Err(AppError::Encryption("Invalid encrypted value format".to_string()));
+        }
+
+        let cipher = Aes256Gcm::new_from_slice(dek)
+            .map_err(|e| AppError::Encryption(format!("Invalid DEK: {}", e)))?;
+        cipher.decrypt(Nonce::from_slice(&sealed[..12]), &sealed[12..])
+            .map_err(|e| AppError::Encryption(format!("Decryption failed: {}", e)))
+    }
 
     /// Encrypt data using entity's DEK
     pub async fn encrypt(&self, entity_id: Uuid, entity_type: &str, data: &[u8]) -> AppResult<(Vec<u8>, Vec<u8>)> {
@@ -137,7 +385,7 @@ impl DekManager {
         _entity_type: &str,
         dek: &[u8],
     ) -> AppResult<(Vec<u8>, Vec<u8>)> {
-        let cipher = Aes256Gcm::new_from_slice(self.master_key.key())
+        let cipher = Aes256Gcm::new_from_slice(self.master_key().key())
             .map_err(|e| crate::shared::AppError::Encryption(format!("Invalid master key: {}", e)))?;
 
         let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
@@ -147,9 +395,33 @@ impl DekManager {
         Ok((ciphertext, nonce.to_vec()))
     }
 
+    /// Wrap a DEK for vault storage, via transit when configured and unsealed
+    async fn wrap_dek_for_vault(&self, dek: &[u8]) -> AppResult<Vec<u8>> {
+        if let Some(key_name) = &self.transit_key {
+            if let Some(ciphertext) = self.vault.transit_encrypt(key_name, dek).await? {
+                return Ok(ciphertext.into_bytes());
+            }
+        }
+        self.encrypt_dek(dek)
+    }
+
+    /// Unwrap a DEK read from the vault, whichever way it was wrapped
+    async fn unwrap_dek_from_vault(&self, encrypted_dek: &[u8]) -> AppResult<Vec<u8>> {
+        if !encrypted_dek.starts_with(TRANSIT_PREFIX) {
+            return self.decrypt_dek(encrypted_dek);
+        }
+
+        let key_name = self.transit_key.as_deref().ok_or_else(|| {
+            crate::shared::AppError::Encryption("DEK is transit-wrapped but no transit key is configured".to_string())
+        })?;
+        let ciphertext = std::str::from_utf8(encrypted_dek)
+            .map_err(|_| crate::shared::AppError::Encryption("Invalid transit ciphertext".to_string()))?;
+        self.vault.transit_decrypt(key_name, ciphertext).await
+    }
+
     /// Encrypt DEK with master key (for vault storage - combined format)
     fn encrypt_dek(&self, dek: &[u8]) -> AppResult<Vec<u8>> {
-        let cipher = Aes256Gcm::new_from_slice(self.master_key.key())
+        let cipher = Aes256Gcm::new_from_slice(self.master_key().key())
             .map_err(|e| crate::shared::AppError::Encryption(format!("Invalid master key: {}", e)))?;
 
         let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
@@ -171,7 +443,7 @@ impl DekManager {
         let nonce = Nonce::from_slice(&encrypted_dek[..12]);
         let ciphertext = &encrypted_dek[12..];
 
-        let cipher = Aes256Gcm::new_from_slice(self.master_key.key())
+        let cipher = Aes256Gcm::new_from_slice(self.master_key().key())
             .map_err(|e| crate::shared::AppError::Encryption(format!("Invalid master key: {}", e)))?;
 
         let dek = cipher.decrypt(nonce, ciphertext)
@@ -215,3 +487,124 @@ impl DekManager {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// In-memory vault whose transit engine can be switched off (sealed)
+    #[derive(Default)]
+    struct MemoryVault {
+        deks: Mutex<HashMap<String, Vec<u8>>>,
+        sealed: bool,
+    }
+
+    #[async_trait]
+    impl Vault for MemoryVault {
+        async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+            self.deks.lock().unwrap().insert(format!("{}/{}", entity_type, entity_id), encrypted_dek.to_vec());
+            Ok(())
+        }
+
+        async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+            Ok(self.deks.lock().unwrap().get(&format!("{}/{}", entity_type, entity_id)).cloned())
+        }
+
+        async fn delete_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<()> {
+            self.deks.lock().unwrap().remove(&format!("{}/{}", entity_type, entity_id));
+            Ok(())
+        }
+
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> {
+            Ok(())
+        }
+
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+            Ok(None)
+        }
+
+        async fn transit_encrypt(&self, _key_name: &str, plaintext: &[u8]) -> AppResult<Option<String>> {
+            if self.sealed {
+                return Ok(None);
+            }
+            Ok(Some(format!("vault:v1:{}", STANDARD.encode(plaintext))))
+        }
+
+        async fn transit_decrypt(&self, _key_name: &str, ciphertext: &str) -> AppResult<Vec<u8>> {
+            let encoded = ciphertext.strip_prefix("vault:v1:").unwrap();
+            Ok(STANDARD.decode(encoded).unwrap())
+        }
+    }
+
+    fn dek_manager(sealed: bool) -> DekManager {
+        let vault = MemoryVault { sealed, ..Default::default() };
+        DekManager::new(MasterKey::generate().unwrap(), Box::new(vault)).with_transit_key("deks")
+    }
+
+    #[tokio::test]
+    async fn test_dek_is_wrapped_by_transit_when_unsealed() {
+        let manager = dek_manager(false);
+        let entity_id = Uuid::new_v4();
+
+        let dek = manager.generate_dek(entity_id, "patient").await.unwrap();
+
+        let stored = manager.vault.get_dek(&entity_id.to_string(), "patient").await.unwrap().unwrap();
+        assert!(stored.starts_with(TRANSIT_PREFIX));
+        assert_eq!(manager.get_dek(entity_id, "patient").await.unwrap(), Some(dek));
+    }
+
+    #[tokio::test]
+    async fn test_dek_falls_back_to_master_key_when_sealed() {
+        let manager = dek_manager(true);
+        let entity_id = Uuid::new_v4();
+
+        let dek = manager.generate_dek(entity_id, "patient").await.unwrap();
+
+        let stored = manager.vault.get_dek(&entity_id.to_string(), "patient").await.unwrap().unwrap();
+        assert!(!stored.starts_with(TRANSIT_PREFIX));
+        assert_eq!(manager.get_dek(entity_id, "patient").await.unwrap(), Some(dek));
+    }
+
+    #[test]
+    fn test_value_sealed_before_rotation_opens_with_new_dek() {
+        let old_dek = DekManager::new_dek();
+        let new_dek = DekManager::new_dek();
+        let sealed = DekManager::seal(&old_dek, b"MRN-0042").unwrap();
+
+        // What rotate_dek applies to every stored value
+        let resealed = DekManager::seal(&new_dek, &DekManager::open(&old_dek, &sealed).unwrap()).unwrap();
+
+        assert_eq!(DekManager::open(&new_dek, &resealed).unwrap(), b"MRN-0042");
+        assert!(DekManager::open(&old_dek, &resealed).is_err());
+    }
+
+    #[test]
+    fn test_dek_field_round_trip() {
+        let dek = Dek::new(Uuid::new_v4(), DekManager::new_dek());
+        let encrypted = dek.encrypt_field("123-45-6789").unwrap();
+
+        assert!(encrypted.starts_with(ENCRYPTED_FIELD_PREFIX));
+        assert_eq!(dek.decrypt_field(&encrypted).unwrap(), "123-45-6789");
+        assert!(dek.decrypt_field("123-45-6789").is_err());
+        assert!(Dek::new(dek.key_id, DekManager::new_dek()).decrypt_field(&encrypted).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_master_key_requires_database_storage() {
+        let manager = dek_manager(false);
+        let fingerprint = manager.master_key().fingerprint();
+
+        let err = manager.rotate_master_key(MasterKey::generate().unwrap()).await.unwrap_err();
+
+        assert!(matches!(err, AppError::Configuration(_)));
+        assert_eq!(manager.master_key().fingerprint(), fingerprint);
+    }
+}
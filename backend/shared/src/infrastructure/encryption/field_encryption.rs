@@ -1,15 +1,53 @@
 use crate::infrastructure::encryption::DekManager;
-use crate::shared::AppResult;
+use crate::shared::{AppError, AppResult};
+use aes_siv::{siv::Aes256Siv, KeyInit};
 use base64::{Engine as _, engine::general_purpose::STANDARD};
+use sha2::{Digest, Sha512};
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Domain separation label for deriving the deterministic key from its DEK
+const SIV_KEY_LABEL: &[u8] = b"health-v1/field-encryption/aes-siv";
+
+/// Global DEK scope the deterministic key is derived from
+const SIV_DEK_SCOPE: &str = "field-encryption-siv";
+
+/// Fixed entity id of the deterministic key's DEK
+const SIV_DEK_ID: Uuid = Uuid::nil();
+
 pub struct FieldEncryption {
-    dek_manager: DekManager,
+    dek_manager: Arc<DekManager>,
+    /// AES-SIV key (two 256-bit keys) for searchable, deterministic encryption
+    siv_key: [u8; 64],
 }
 
 impl FieldEncryption {
-    pub fn new(dek_manager: DekManager) -> Self {
-        Self { dek_manager }
+    /// Load the deterministic key, creating it on first start
+    pub async fn new(dek_manager: Arc<DekManager>) -> AppResult<Self> {
+        let dek = match dek_manager.get_global_dek(SIV_DEK_SCOPE, SIV_DEK_ID).await {
+            Ok(dek) => dek,
+            // Another instance created it first; only one active DEK is stored per entity
+            Err(e) => {
+                let entity_type = format!("global/{}", SIV_DEK_SCOPE);
+                dek_manager.get_dek(SIV_DEK_ID, &entity_type).await?.ok_or(e)?
+            }
+        };
+        let siv_key = Self::derive_siv_key(&dek);
+        Ok(Self { dek_manager, siv_key })
+    }
+
+    /// Deterministic encryption must not depend on per-entity DEKs, otherwise a
+    /// lookup would need the entity before it could search for it. The key
+    /// comes from a dedicated global DEK instead of the master key: rotating
+    /// the master key only rewraps that DEK, so stored ciphertexts stay valid.
+    fn derive_siv_key(dek: &[u8]) -> [u8; 64] {
+        let digest = Sha512::new()
+            .chain_update(SIV_KEY_LABEL)
+            .chain_update(dek)
+            .finalize();
+        let mut key = [0u8; 64];
+        key.copy_from_slice(&digest);
+        key
     }
 
     /// Encrypt a field value
@@ -39,5 +77,128 @@ impl FieldEncryption {
         String::from_utf8(plaintext)
             .map_err(|e| crate::shared::AppError::Encryption(format!("UTF-8 decode error: {}", e)))
     }
+
+    fn siv(&self) -> AppResult<Aes256Siv> {
+        Aes256Siv::new_from_slice(&self.siv_key)
+            .map_err(|e| AppError::Encryption(format!("Invalid AES-SIV key: {}", e)))
+    }
+
+    /// Encrypt deterministically with AES-SIV (RFC 5297) so encrypted columns can be searched
+    ///
+    /// The same plaintext and `context` always give the same ciphertext (16 byte
+    /// synthetic IV followed by the encrypted data). `context` is bound as
+    /// associated data; use the table and column (e.g. `users.email`) so equal
+    /// values in different columns cannot be correlated.
+    pub fn encrypt_deterministic(&self, plaintext: &[u8], context: &[u8]) -> AppResult<Vec<u8>> {
+        self.siv()?
+            .encrypt([context], plaintext)
+            .map_err(|e| AppError::Encryption(format!("Deterministic encryption failed: {}", e)))
+    }
+
+    /// Decrypt a value from `encrypt_deterministic` with the same `context`
+    pub fn decrypt_deterministic(&self, ciphertext: &[u8], context: &[u8]) -> AppResult<Vec<u8>> {
+        self.siv()?
+            .decrypt([context], ciphertext)
+            .map_err(|e| AppError::Encryption(format!("Deterministic decryption failed: {}", e)))
+    }
+
+    /// Deterministic ciphertexts of every prefix of `value`, shortest first
+    ///
+    /// Prefixes end on character boundaries. A stored ciphertext can then be
+    /// matched against any prefix of a search term with
+    /// `WHERE encrypted_ssn IN (...)`.
+    pub fn encrypt_prefix(&self, value: &str, context: &[u8]) -> AppResult<Vec<Vec<u8>>> {
+        let mut siv = self.siv()?;
+        value
+            .char_indices()
+            .map(|(i, c)| &value[..i + c.len_utf8()])
+            .map(|prefix| {
+                siv.encrypt([context], prefix.as_bytes())
+                    .map_err(|e| AppError::Encryption(format!("Deterministic encryption failed: {}", e)))
+            })
+            .collect()
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::encryption::{MasterKey, Vault};
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Vault keeping DEKs in memory
+    #[derive(Default)]
+    struct MemoryVault {
+        deks: Mutex<HashMap<(String, String), Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl Vault for MemoryVault {
+        async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+            self.deks.lock().unwrap().insert((entity_id.to_string(), entity_type.to_string()), encrypted_dek.to_vec());
+            Ok(())
+        }
+        async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+            Ok(self.deks.lock().unwrap().get(&(entity_id.to_string(), entity_type.to_string())).cloned())
+        }
+        async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> { Ok(()) }
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+    }
+
+    async fn field_encryption() -> FieldEncryption {
+        let vault = Box::new(MemoryVault::default());
+        FieldEncryption::new(Arc::new(DekManager::new(MasterKey::generate().unwrap(), vault))).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_encryption_is_repeatable() {
+        let encryption = field_encryption().await;
+        let first = encryption.encrypt_deterministic(b"jane@example.com", b"users.email").unwrap();
+        let second = encryption.encrypt_deterministic(b"jane@example.com", b"users.email").unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(&first[16..], b"jane@example.com");
+        assert_eq!(encryption.decrypt_deterministic(&first, b"users.email").unwrap(), b"jane@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_encryption_differs_per_context() {
+        let encryption = field_encryption().await;
+        let email = encryption.encrypt_deterministic(b"123-45-6789", b"users.email").unwrap();
+        let ssn = encryption.encrypt_deterministic(b"123-45-6789", b"patients.ssn").unwrap();
+
+        assert_ne!(email, ssn);
+        assert!(encryption.decrypt_deterministic(&email, b"patients.ssn").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_prefix_matches_full_value_ciphertext() {
+        let encryption = field_encryption().await;
+        let prefixes = encryption.encrypt_prefix("123-4", b"patients.ssn").unwrap();
+
+        assert_eq!(prefixes.len(), 5);
+        assert_eq!(prefixes[2], encryption.encrypt_deterministic(b"123", b"patients.ssn").unwrap());
+        assert_eq!(prefixes[4], encryption.encrypt_deterministic(b"123-4", b"patients.ssn").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_key_is_reused_from_its_dek() {
+        let vault = Arc::new(MemoryVault::default());
+        let master_key = MasterKey::generate().unwrap();
+        let first = FieldEncryption::new(Arc::new(DekManager::new(master_key.clone(), Box::new(vault.clone()))))
+            .await
+            .unwrap();
+        let second = FieldEncryption::new(Arc::new(DekManager::new(master_key, Box::new(vault))))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            first.encrypt_deterministic(b"jane@example.com", b"users.email").unwrap(),
+            second.encrypt_deterministic(b"jane@example.com", b"users.email").unwrap()
+        );
+    }
+}
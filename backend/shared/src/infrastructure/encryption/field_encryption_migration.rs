@@ -0,0 +1,476 @@
+//! Encrypting the existing values of a column
+//!
+//! When a column joins field-level encryption its existing rows still hold
+//! plaintext. `FieldEncryptionMigrator` encrypts them in place, a batch at a
+//! time, and records each run's progress in `encryption_migrations`.
+//!
+//! Only the columns listed in [`ENCRYPTED_FIELDS`] can be migrated. Each value
+//! is encrypted the way [`FieldEncryption::encrypt_field`] does, with the DEK of
+//! the row's entity, so the column's repository can decrypt it with
+//! [`FieldEncryption::decrypt_field`]. Values that are already ciphertext,
+//! either `vault:v` prefixed or decrypting with the entity's DEK, are left
+//! alone, so running a migration again only encrypts rows written in
+//! plaintext since.
+//!
+//! [`FieldEncryption::encrypt_field`]: crate::infrastructure::encryption::FieldEncryption::encrypt_field
+//! [`FieldEncryption::decrypt_field`]: crate::infrastructure::encryption::FieldEncryption::decrypt_field
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::infrastructure::database::RepositoryErrorExt;
+use crate::infrastructure::encryption::{DekManager, ENCRYPTED_FIELD_PREFIX};
+use crate::shared::{AppError, AppResult};
+
+/// Rows encrypted per transaction
+pub const MIGRATION_BATCH_SIZE: i64 = 500;
+
+/// `LIKE` pattern of values that are already encrypted with a [`Dek`](crate::infrastructure::encryption::Dek)
+const ENCRYPTED_VALUE_PATTERN: &str = "vault:v%";
+
+/// Longest identifier PostgreSQL keeps without truncating
+const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// A text column its repository reads with `FieldEncryption::decrypt_field`
+///
+/// The table must have a UUID `id` primary key; each value is encrypted with
+/// the DEK of `(id, entity_type)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncryptedField {
+    pub table: &'static str,
+    pub column: &'static str,
+    pub entity_type: &'static str,
+}
+
+/// Columns a migration can run over
+///
+/// Register a column here together with the repository change that decrypts
+/// it; encrypting a column nothing decrypts makes its values unreadable.
+pub const ENCRYPTED_FIELDS: &[EncryptedField] = &[];
+
+/// Where a migration run is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationStatus {
+    Running,
+    Completed,
+    /// Stopped on an error; rows encrypted before it stay encrypted
+    Failed,
+}
+
+/// One run of [`FieldEncryptionMigrator`] over a column
+#[derive(Debug, Clone, Serialize)]
+pub struct EncryptionMigration {
+    pub id: Uuid,
+    pub table_name: String,
+    pub column_name: String,
+    /// DEK of runs that used a single key; `None` when each row uses its entity's DEK
+    pub key_id: Option<Uuid>,
+    pub migrated_rows: u64,
+    /// Values in the column without the `vault:v` prefix when the run started,
+    /// including values already encrypted with their entity's DEK
+    pub total_rows: u64,
+    pub status: MigrationStatus,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+struct EncryptionMigrationRow {
+    id: Uuid,
+    table_name: String,
+    column_name: String,
+    key_id: Option<Uuid>,
+    migrated_rows: i64,
+    total_rows: i64,
+    status: MigrationStatus,
+    error: Option<String>,
+    started_at: DateTime<Utc>,
+    completed_at: Option<DateTime<Utc>>,
+}
+
+impl From<EncryptionMigrationRow> for EncryptionMigration {
+    fn from(row: EncryptionMigrationRow) -> Self {
+        Self {
+            id: row.id,
+            table_name: row.table_name,
+            column_name: row.column_name,
+            key_id: row.key_id,
+            migrated_rows: u64::try_from(row.migrated_rows).unwrap_or_default(),
+            total_rows: u64::try_from(row.total_rows).unwrap_or_default(),
+            status: row.status,
+            error: row.error,
+            started_at: row.started_at,
+            completed_at: row.completed_at,
+        }
+    }
+}
+
+/// Encrypts the plaintext values of a column in place
+pub struct FieldEncryptionMigrator;
+
+impl FieldEncryptionMigrator {
+    /// Encrypt every plaintext value of `field`
+    pub async fn run(field: &EncryptedField, dek_manager: &DekManager, pool: &PgPool) -> AppResult<EncryptionMigration> {
+        let migration = Self::start(field, pool).await?;
+        Self::resume(migration, field, dek_manager, pool).await
+    }
+
+    /// The registered field for `table.column`
+    ///
+    /// Fails with a validation error for columns not in [`ENCRYPTED_FIELDS`].
+    pub fn registered(table: &str, column: &str) -> AppResult<&'static EncryptedField> {
+        ENCRYPTED_FIELDS
+            .iter()
+            .find(|field| field.table == table && field.column == column)
+            .ok_or_else(|| AppError::Validation(format!("{}.{} is not a registered encrypted field", table, column)))
+    }
+
+    /// Record a run over `field` and count the values it may have to encrypt
+    ///
+    /// Hand the result to [`FieldEncryptionMigrator::resume`] to encrypt them,
+    /// e.g. in a background task.
+    pub async fn start(field: &EncryptedField, pool: &PgPool) -> AppResult<EncryptionMigration> {
+        let (table, column) = (field.table, field.column);
+        Self::validate_column(table, column)?;
+
+        let count_sql = format!(
+            r#"SELECT COUNT(*) FROM "{table}" WHERE "{column}" IS NOT NULL AND "{column}" NOT LIKE $1"#
+        );
+        let total_rows: i64 = sqlx::query_scalar(&count_sql)
+            .bind(ENCRYPTED_VALUE_PATTERN)
+            .fetch_one(pool)
+            .await
+            .map_db_error("count", "plaintext value")?;
+
+        let row = sqlx::query_as!(
+            EncryptionMigrationRow,
+            r#"
+            INSERT INTO encryption_migrations (table_name, column_name, total_rows)
+            VALUES ($1, $2, $3)
+            RETURNING id, table_name, column_name, key_id, migrated_rows, total_rows,
+                      status as "status: MigrationStatus", error, started_at, completed_at
+            "#,
+            table,
+            column,
+            total_rows
+        )
+        .fetch_one(pool)
+        .await
+        .map_db_error("create", "encryption migration")?;
+        Ok(row.into())
+    }
+
+    /// Check that `table` and `column` are names a migration can run over
+    ///
+    /// They are interpolated into SQL, so only plain lowercase identifiers
+    /// are accepted.
+    pub fn validate_column(table: &str, column: &str) -> AppResult<()> {
+        validate_identifier(table)?;
+        validate_identifier(column)
+    }
+
+    /// Encrypt the values of a started run, recording progress after every batch
+    ///
+    /// A failure is recorded on the run before it is returned.
+    pub async fn resume(
+        mut migration: EncryptionMigration,
+        field: &EncryptedField,
+        dek_manager: &DekManager,
+        pool: &PgPool,
+    ) -> AppResult<EncryptionMigration> {
+        let mut after = None;
+        loop {
+            let (encrypted, last_id) = match encrypt_batch(field, after, dek_manager, pool).await {
+                Ok(batch) => batch,
+                Err(e) => {
+                    finish(pool, migration.id, MigrationStatus::Failed, Some(&e.to_string())).await?;
+                    return Err(e);
+                }
+            };
+            let Some(last_id) = last_id else {
+                break;
+            };
+            after = Some(last_id);
+            if encrypted == 0 {
+                continue;
+            }
+
+            migration.migrated_rows += encrypted;
+            sqlx::query!(
+                "UPDATE encryption_migrations SET migrated_rows = $2 WHERE id = $1",
+                migration.id,
+                i64::try_from(migration.migrated_rows).unwrap_or(i64::MAX)
+            )
+            .execute(pool)
+            .await
+            .map_db_error("update", "encryption migration")?;
+        }
+
+        Ok(finish(pool, migration.id, MigrationStatus::Completed, None).await?.into())
+    }
+
+    /// The most recent run over `table.column`
+    pub async fn latest(table: &str, column: &str, pool: &PgPool) -> AppResult<Option<EncryptionMigration>> {
+        let row = sqlx::query_as!(
+            EncryptionMigrationRow,
+            r#"
+            SELECT id, table_name, column_name, key_id, migrated_rows, total_rows,
+                   status as "status: MigrationStatus", error, started_at, completed_at
+            FROM encryption_migrations
+            WHERE table_name = $1 AND column_name = $2
+            ORDER BY started_at DESC
+            LIMIT 1
+            "#,
+            table,
+            column
+        )
+        .fetch_optional(pool)
+        .await
+        .map_db_error("find", "encryption migration")?;
+        Ok(row.map(Into::into))
+    }
+
+    /// The `limit` most recent runs over any column
+    pub async fn recent(limit: i64, pool: &PgPool) -> AppResult<Vec<EncryptionMigration>> {
+        let rows = sqlx::query_as!(
+            EncryptionMigrationRow,
+            r#"
+            SELECT id, table_name, column_name, key_id, migrated_rows, total_rows,
+                   status as "status: MigrationStatus", error, started_at, completed_at
+            FROM encryption_migrations
+            ORDER BY started_at DESC
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+        .map_db_error("list", "encryption migration")?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Encrypt the plaintext among the next [`MIGRATION_BATCH_SIZE`] values after
+/// `after` in one transaction
+///
+/// Returns how many were encrypted and the id of the last row looked at, which
+/// is `None` once no rows are left. Rows are walked by id because values
+/// already encrypted with their entity's DEK stay in the candidate set.
+async fn encrypt_batch(
+    field: &EncryptedField,
+    after: Option<Uuid>,
+    dek_manager: &DekManager,
+    pool: &PgPool,
+) -> AppResult<(u64, Option<Uuid>)> {
+    let (table, column) = (field.table, field.column);
+    let mut tx = pool.begin().await.map_db_error("begin", "encryption migration")?;
+
+    // Locked rows are being encrypted by a concurrent run
+    let select_sql = format!(
+        r#"SELECT id, "{column}" FROM "{table}"
+           WHERE "{column}" IS NOT NULL AND "{column}" NOT LIKE $1
+             AND ($3::uuid IS NULL OR id > $3)
+           ORDER BY id
+           LIMIT $2
+           FOR UPDATE SKIP LOCKED"#
+    );
+    let rows: Vec<(Uuid, String)> = sqlx::query_as(&select_sql)
+        .bind(ENCRYPTED_VALUE_PATTERN)
+        .bind(MIGRATION_BATCH_SIZE)
+        .bind(after)
+        .fetch_all(&mut *tx)
+        .await
+        .map_db_error("find", "plaintext value")?;
+
+    let update_sql = format!(r#"UPDATE "{table}" SET "{column}" = $2 WHERE id = $1"#);
+    let mut encrypted = 0;
+    for (id, value) in &rows {
+        if is_encrypted(field, dek_manager, *id, value).await {
+            continue;
+        }
+        dek_manager.get_or_create_dek(*id, field.entity_type).await?;
+        sqlx::query(&update_sql)
+            .bind(id)
+            .bind(dek_manager.encrypt_field(*id, field.entity_type, value).await?)
+            .execute(&mut *tx)
+            .await
+            .map_db_error("update", "encrypted value")?;
+        encrypted += 1;
+    }
+
+    tx.commit().await.map_db_error("commit", "encryption migration")?;
+    Ok((encrypted, rows.last().map(|(id, _)| *id)))
+}
+
+/// Whether `value` is already ciphertext, in either stored format
+async fn is_encrypted(field: &EncryptedField, dek_manager: &DekManager, id: Uuid, value: &str) -> bool {
+    value.starts_with(ENCRYPTED_FIELD_PREFIX)
+        || dek_manager.decrypt_field(id, field.entity_type, value).await.is_ok()
+}
+
+async fn finish(
+    pool: &PgPool,
+    id: Uuid,
+    status: MigrationStatus,
+    error: Option<&str>,
+) -> AppResult<EncryptionMigrationRow> {
+    sqlx::query_as!(
+        EncryptionMigrationRow,
+        r#"
+        UPDATE encryption_migrations
+        SET status = $2, error = $3, completed_at = NOW()
+        WHERE id = $1
+        RETURNING id, table_name, column_name, key_id, migrated_rows, total_rows,
+                  status as "status: MigrationStatus", error, started_at, completed_at
+        "#,
+        id,
+        status as MigrationStatus,
+        error
+    )
+    .fetch_one(pool)
+    .await
+    .map_db_error("update", "encryption migration")
+}
+
+fn validate_identifier(name: &str) -> AppResult<()> {
+    let mut chars = name.chars();
+    let valid = name.len() <= MAX_IDENTIFIER_LENGTH
+        && chars.next().is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!("Invalid table or column name: {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::encryption::{MasterKey, Vault};
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Vault keeping DEKs in memory
+    #[derive(Default)]
+    struct MemoryVault {
+        deks: Mutex<HashMap<(String, String), Vec<u8>>>,
+    }
+
+    #[async_trait]
+    impl Vault for MemoryVault {
+        async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+            self.deks.lock().unwrap().insert((entity_id.to_string(), entity_type.to_string()), encrypted_dek.to_vec());
+            Ok(())
+        }
+        async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+            Ok(self.deks.lock().unwrap().get(&(entity_id.to_string(), entity_type.to_string())).cloned())
+        }
+        async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> { Ok(()) }
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+    }
+
+    #[test]
+    fn test_identifiers_are_validated() {
+        assert!(validate_identifier("patients").is_ok());
+        assert!(validate_identifier("ssn_last_4").is_ok());
+        assert!(validate_identifier("").is_err());
+        assert!(validate_identifier("4patients").is_err());
+        assert!(validate_identifier("Patients").is_err());
+        assert!(validate_identifier("patients\"; DROP TABLE users; --").is_err());
+        assert!(validate_identifier(&"a".repeat(64)).is_err());
+    }
+
+    #[test]
+    fn test_only_registered_fields_can_be_migrated() {
+        for field in ENCRYPTED_FIELDS {
+            assert!(FieldEncryptionMigrator::validate_column(field.table, field.column).is_ok());
+            assert_eq!(FieldEncryptionMigrator::registered(field.table, field.column).unwrap(), field);
+        }
+        assert!(matches!(
+            FieldEncryptionMigrator::registered("users", "password_hash"),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires test database to be running
+    async fn test_run_encrypts_plaintext_rows_once() {
+        let pool = crate::testing::create_test_pool().await;
+        sqlx::query("CREATE TABLE IF NOT EXISTS field_encryption_migration_test (id UUID PRIMARY KEY, secret TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("TRUNCATE field_encryption_migration_test")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let field = EncryptedField {
+            table: "field_encryption_migration_test",
+            column: "secret",
+            entity_type: "field_encryption_migration_test",
+        };
+        let dek_manager = DekManager::new(MasterKey::generate().unwrap(), Box::new(MemoryVault::default()));
+
+        let mut ids = Vec::new();
+        for i in 0..100 {
+            let id = Uuid::new_v4();
+            sqlx::query("INSERT INTO field_encryption_migration_test (id, secret) VALUES ($1, $2)")
+                .bind(id)
+                .bind(format!("secret-{}", i))
+                .execute(&pool)
+                .await
+                .unwrap();
+            ids.push(id);
+        }
+        // Written by the repository before the migration ran
+        dek_manager.generate_dek(ids[0], field.entity_type).await.unwrap();
+        let written = dek_manager.encrypt_field(ids[0], field.entity_type, "secret-0").await.unwrap();
+        sqlx::query("UPDATE field_encryption_migration_test SET secret = $2 WHERE id = $1")
+            .bind(ids[0])
+            .bind(&written)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let migration = FieldEncryptionMigrator::run(&field, &dek_manager, &pool).await.unwrap();
+        assert_eq!(migration.status, MigrationStatus::Completed);
+        assert_eq!((migration.migrated_rows, migration.total_rows), (99, 100));
+        assert_eq!(migration.key_id, None);
+
+        let rows: Vec<(Uuid, String)> = sqlx::query_as("SELECT id, secret FROM field_encryption_migration_test ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 100);
+        for (id, secret) in &rows {
+            let plaintext = dek_manager.decrypt_field(*id, field.entity_type, secret).await.unwrap();
+            assert!(plaintext.starts_with("secret-"));
+        }
+        assert!(rows.iter().any(|(id, secret)| *id == ids[0] && *secret == written));
+
+        // Nothing left to encrypt
+        let again = FieldEncryptionMigrator::run(&field, &dek_manager, &pool).await.unwrap();
+        assert_eq!((again.migrated_rows, again.total_rows), (0, 100));
+        let unchanged: Vec<(Uuid, String)> = sqlx::query_as("SELECT id, secret FROM field_encryption_migration_test ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(unchanged, rows);
+
+        let latest = FieldEncryptionMigrator::latest(field.table, field.column, &pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.id, again.id);
+
+        sqlx::query("DROP TABLE field_encryption_migration_test").execute(&pool).await.unwrap();
+    }
+}
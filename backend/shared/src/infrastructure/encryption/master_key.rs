@@ -1,9 +1,24 @@
+use crate::infrastructure::encryption::shamir::ShamirSecret;
 use crate::shared::AppResult;
+use aes_kw::KekAes256;
 use std::fs;
 use std::path::Path;
 
+/// Version assigned to a master key that has never been rotated
+const INITIAL_KEY_VERSION: i32 = 1;
+
 pub struct MasterKey {
     key: Vec<u8>,
+    version: i32,
+}
+
+/// A DEK wrapped with the master key (AES-256 key wrap, RFC 3394)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedDek {
+    /// Wrapped key material; 8 bytes longer than the DEK
+    pub bytes: Vec<u8>,
+    /// Version of the master key that wrapped it
+    pub key_version: i32,
 }
 
 impl MasterKey {
@@ -11,7 +26,7 @@ impl MasterKey {
     pub fn from_file(path: &Path) -> AppResult<Self> {
         let key = fs::read(path)
             .map_err(|e| crate::shared::AppError::Encryption(format!("Failed to read master key: {}", e)))?;
-        Ok(Self { key })
+        Ok(Self { key, version: INITIAL_KEY_VERSION })
     }
 
     /// Load master key from environment variable
@@ -20,7 +35,7 @@ impl MasterKey {
             .map_err(|_| crate::shared::AppError::Encryption("Master key not found in environment".to_string()))?;
         let key = hex::decode(&key_str)
             .map_err(|e| crate::shared::AppError::Encryption(format!("Invalid master key format: {}", e)))?;
-        Ok(Self { key })
+        Ok(Self { key, version: INITIAL_KEY_VERSION })
     }
 
     /// Generate a new master key (for initial setup)
@@ -33,13 +48,97 @@ impl MasterKey {
         let mut key = vec![0u8; 32]; // 256-bit key
         rng.fill(&mut key)
             .map_err(|e| crate::shared::AppError::Encryption(format!("Failed to generate random key: {}", e)))?;
-        Ok(Self { key })
+        Ok(Self { key, version: INITIAL_KEY_VERSION })
+    }
+
+    /// Recover a master key from Shamir shares (key ceremony recovery)
+    ///
+    /// # Errors
+    /// Returns an error if fewer than `threshold` shares are given or the
+    /// shares are malformed (duplicate IDs, mismatched lengths)
+    pub fn from_shamir_shares(shares: Vec<Vec<u8>>, threshold: usize) -> AppResult<Self> {
+        if threshold < 2 {
+            return Err(crate::shared::AppError::Encryption("Shamir threshold must be at least 2".to_string()));
+        }
+        if shares.len() < threshold {
+            return Err(crate::shared::AppError::Encryption(format!(
+                "At least {} shares are required, got {}",
+                threshold,
+                shares.len()
+            )));
+        }
+        let key = ShamirSecret::combine(shares)
+            .ok_or_else(|| crate::shared::AppError::Encryption("Failed to combine master key shares".to_string()))?;
+        Ok(Self { key, version: INITIAL_KEY_VERSION })
+    }
+
+    /// Split the master key into Shamir shares (key ceremony)
+    ///
+    /// Any `threshold` of the `total_shares` returned can recover the key.
+    pub fn split_into_shares(&self, threshold: usize, total_shares: usize) -> AppResult<Vec<Vec<u8>>> {
+        let threshold = u8::try_from(threshold)
+            .map_err(|_| crate::shared::AppError::Encryption("Shamir threshold must be below 256".to_string()))?;
+        let total_shares = u8::try_from(total_shares)
+            .map_err(|_| crate::shared::AppError::Encryption("Shamir share count must be below 255".to_string()))?;
+        let shares = ShamirSecret::split(&self.key, total_shares, threshold)?;
+        Ok(shares.to_vec())
+    }
+
+    /// SHA-256 fingerprint of the key, safe to log and compare
+    pub fn fingerprint(&self) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(&self.key))
     }
 
     pub fn key(&self) -> &[u8] {
         &self.key
     }
 
+    /// Version recorded alongside every DEK this key wraps
+    pub fn version(&self) -> i32 {
+        self.version
+    }
+
+    /// Set the version after a master key rotation
+    pub fn with_version(mut self, version: i32) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Wrap a DEK for storage (AES-256 key wrap, RFC 3394)
+    ///
+    /// # Errors
+    /// Returns an error if the master key is not 256 bits or the DEK is not
+    /// a multiple of 8 bytes
+    pub fn wrap_dek(&self, dek: &[u8]) -> AppResult<WrappedDek> {
+        let bytes = self.kek()?
+            .wrap_vec(dek)
+            .map_err(|e| crate::shared::AppError::Encryption(format!("DEK wrap failed: {}", e)))?;
+        Ok(WrappedDek { bytes, key_version: self.version })
+    }
+
+    /// Unwrap a DEK previously wrapped with [`MasterKey::wrap_dek`]
+    ///
+    /// # Errors
+    /// Returns an error if the DEK was wrapped by a different master key
+    /// version or fails the RFC 3394 integrity check
+    pub fn unwrap_dek(&self, wrapped: &WrappedDek) -> AppResult<Vec<u8>> {
+        if wrapped.key_version != self.version {
+            return Err(crate::shared::AppError::Encryption(format!(
+                "DEK was wrapped with master key version {}, active version is {}",
+                wrapped.key_version, self.version
+            )));
+        }
+        self.kek()?
+            .unwrap_vec(&wrapped.bytes)
+            .map_err(|e| crate::shared::AppError::Encryption(format!("DEK unwrap failed: {}", e)))
+    }
+
+    fn kek(&self) -> AppResult<KekAes256> {
+        KekAes256::try_from(self.key.as_slice())
+            .map_err(|e| crate::shared::AppError::Encryption(format!("Invalid master key: {}", e)))
+    }
+
     /// Save master key to file (use with caution!)
     pub fn save_to_file(&self, path: &Path) -> AppResult<()> {
         fs::write(path, &self.key)
@@ -50,7 +149,7 @@ impl MasterKey {
     /// Load master key from vault (async)
     pub async fn from_vault(vault: &dyn crate::infrastructure::encryption::vault::Vault) -> AppResult<Option<Self>> {
         match vault.get_master_key().await? {
-            Some(key_bytes) => Ok(Some(Self { key: key_bytes })),
+            Some(key_bytes) => Ok(Some(Self { key: key_bytes, version: INITIAL_KEY_VERSION })),
             None => Ok(None),
         }
     }
@@ -61,3 +160,46 @@ impl MasterKey {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shamir_shares_round_trip() {
+        let master_key = MasterKey::generate().unwrap();
+        let shares = master_key.split_into_shares(3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        let recovered = MasterKey::from_shamir_shares(vec![shares[4].clone(), shares[0].clone(), shares[2].clone()], 3).unwrap();
+        assert_eq!(recovered.key(), master_key.key());
+    }
+
+    #[test]
+    fn test_from_shamir_shares_rejects_too_few_shares() {
+        let master_key = MasterKey::generate().unwrap();
+        let shares = master_key.split_into_shares(3, 5).unwrap();
+        assert!(MasterKey::from_shamir_shares(shares[..2].to_vec(), 3).is_err());
+    }
+
+    #[test]
+    fn test_wrap_dek_round_trip() {
+        let master_key = MasterKey::generate().unwrap();
+        let dek = [7u8; 32];
+
+        let wrapped = master_key.wrap_dek(&dek).unwrap();
+        assert_eq!(wrapped.bytes.len(), dek.len() + 8);
+        assert_eq!(wrapped.key_version, 1);
+        assert_ne!(&wrapped.bytes[8..], &dek[..]);
+
+        assert_eq!(master_key.unwrap_dek(&wrapped).unwrap(), dek.to_vec());
+    }
+
+    #[test]
+    fn test_unwrap_dek_rejects_other_master_key() {
+        let dek = [7u8; 32];
+        let wrapped = MasterKey::generate().unwrap().wrap_dek(&dek).unwrap();
+
+        assert!(MasterKey::generate().unwrap().unwrap_dek(&wrapped).is_err());
+        assert!(MasterKey::generate().unwrap().with_version(2).unwrap_dek(&wrapped).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::infrastructure::encryption::{MasterKey, Vault};
+use crate::shared::{AppError, AppResult};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where a master key can be loaded from or persisted to
+#[derive(Clone)]
+pub enum MasterKeySource {
+    /// OpenBao/Vault (preferred)
+    Vault(Arc<dyn Vault>),
+    /// Raw key bytes in a file
+    File(PathBuf),
+    /// Hex-encoded key in the named environment variable
+    Environment(String),
+    /// A freshly generated key (first-time setup)
+    Generate,
+}
+
+impl MasterKeySource {
+    /// Description safe to log
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Vault(_) => "vault".to_string(),
+            Self::File(path) => format!("file {}", path.display()),
+            Self::Environment(name) => format!("environment variable {}", name),
+            Self::Generate => "newly generated key".to_string(),
+        }
+    }
+
+    /// `Ok(None)` when the source holds no key, so the next source is tried
+    async fn load(&self) -> AppResult<Option<MasterKey>> {
+        match self {
+            Self::Vault(vault) => MasterKey::from_vault(vault.as_ref()).await,
+            Self::File(path) if !path.exists() => Ok(None),
+            Self::File(path) => MasterKey::from_file(path).map(Some),
+            Self::Environment(name) if std::env::var_os(name).is_none() => Ok(None),
+            Self::Environment(name) => MasterKey::from_env(name).map(Some),
+            Self::Generate => MasterKey::generate().map(Some),
+        }
+    }
+
+    async fn save(&self, key: &MasterKey) -> AppResult<()> {
+        match self {
+            Self::Vault(vault) => key.save_to_vault(vault.as_ref()).await,
+            Self::File(path) => {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)
+                        .map_err(|e| AppError::Encryption(format!("Failed to create master key directory: {}", e)))?;
+                }
+                key.save_to_file(path)
+            }
+            Self::Environment(name) => Err(AppError::Configuration(format!(
+                "Cannot persist the master key to environment variable {}",
+                name
+            ))),
+            Self::Generate => Err(AppError::Configuration("Cannot persist the master key to a generator".to_string())),
+        }
+    }
+
+    fn is_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Vault(a), Self::Vault(b)) => Arc::ptr_eq(a, b),
+            (Self::File(a), Self::File(b)) => a == b,
+            (Self::Environment(a), Self::Environment(b)) => a == b,
+            (Self::Generate, Self::Generate) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Loads the master key from the first source that has one
+pub struct MasterKeyLoader {
+    sources: Vec<MasterKeySource>,
+    persist_to: Option<MasterKeySource>,
+}
+
+impl MasterKeyLoader {
+    /// Sources are tried in order
+    pub fn new(sources: Vec<MasterKeySource>) -> Self {
+        Self { sources, persist_to: None }
+    }
+
+    /// Store the loaded key in `target` unless it was loaded from there
+    ///
+    /// A failed save is logged rather than failing the load.
+    pub fn persist_to(mut self, target: MasterKeySource) -> Self {
+        self.persist_to = Some(target);
+        self
+    }
+
+    /// Load the key, returning it with the source it came from
+    ///
+    /// A source that holds no key is skipped. A source that fails (e.g. an
+    /// unreachable vault) is skipped too, but then `Generate` is never used:
+    /// a new key would leave the data under the existing one unreadable.
+    ///
+    /// # Errors
+    /// Returns the first failure, or `NotFound` if no source has a key
+    pub async fn load(&self) -> AppResult<(MasterKey, MasterKeySource)> {
+        let mut first_error = None;
+
+        for source in &self.sources {
+            if matches!(source, MasterKeySource::Generate) && first_error.is_some() {
+                break;
+            }
+            match source.load().await {
+                Ok(Some(key)) => {
+                    self.persist(&key, source).await;
+                    return Ok((key, source.clone()));
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Failed to load master key from {}: {}", source.describe(), e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+
+        Err(first_error.unwrap_or_else(|| AppError::NotFound("No master key source has a key".to_string())))
+    }
+
+    async fn persist(&self, key: &MasterKey, source: &MasterKeySource) {
+        let Some(target) = &self.persist_to else {
+            return;
+        };
+        if target.is_same(source) {
+            return;
+        }
+        if let Err(e) = target.save(key).await {
+            tracing::warn!(
+                "Master key loaded from {} but not persisted to {}: {}",
+                source.describe(),
+                target.describe(),
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    /// Vault holding only a master key
+    #[derive(Default)]
+    struct MasterKeyVault {
+        master_key: Mutex<Option<Vec<u8>>>,
+        unreachable: bool,
+    }
+
+    #[async_trait]
+    impl Vault for MasterKeyVault {
+        async fn store_dek(&self, _entity_id: &str, _entity_type: &str, _encrypted_dek: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn get_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+        async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> { Ok(()) }
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn store_master_key(&self, master_key: &[u8]) -> AppResult<()> {
+            *self.master_key.lock().unwrap() = Some(master_key.to_vec());
+            Ok(())
+        }
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+            if self.unreachable {
+                return Err(AppError::Encryption("vault unreachable".to_string()));
+            }
+            Ok(self.master_key.lock().unwrap().clone())
+        }
+    }
+
+    fn missing_file() -> MasterKeySource {
+        MasterKeySource::File(std::env::temp_dir().join(format!("missing-{}", uuid::Uuid::new_v4())).join("master.key"))
+    }
+
+    #[tokio::test]
+    async fn missing_file_falls_back_to_environment() {
+        let key = [0x42u8; 32];
+        std::env::set_var("MASTER_KEY_LOADER_TEST", hex::encode(key));
+        let loader = MasterKeyLoader::new(vec![
+            missing_file(),
+            MasterKeySource::Environment("MASTER_KEY_LOADER_TEST".to_string()),
+        ]);
+
+        let (loaded, source) = loader.load().await.unwrap();
+
+        assert_eq!(loaded.key(), key);
+        assert!(matches!(source, MasterKeySource::Environment(name) if name == "MASTER_KEY_LOADER_TEST"));
+    }
+
+    #[tokio::test]
+    async fn loaded_key_is_persisted_to_vault() {
+        let vault = Arc::new(MasterKeyVault::default());
+        let loader = MasterKeyLoader::new(vec![MasterKeySource::Vault(vault.clone()), MasterKeySource::Generate])
+            .persist_to(MasterKeySource::Vault(vault.clone()));
+
+        let (generated, source) = loader.load().await.unwrap();
+        assert!(matches!(source, MasterKeySource::Generate));
+        assert_eq!(vault.master_key.lock().unwrap().as_deref(), Some(generated.key()));
+
+        // The next start finds it in the vault
+        let (loaded, source) = loader.load().await.unwrap();
+        assert!(matches!(source, MasterKeySource::Vault(_)));
+        assert_eq!(loaded.key(), generated.key());
+    }
+
+    #[tokio::test]
+    async fn failed_source_prevents_generating_a_new_key() {
+        let vault = Arc::new(MasterKeyVault { unreachable: true, ..Default::default() });
+        let loader = MasterKeyLoader::new(vec![MasterKeySource::Vault(vault), missing_file(), MasterKeySource::Generate]);
+
+        assert!(matches!(loader.load().await, Err(AppError::Encryption(_))));
+    }
+}
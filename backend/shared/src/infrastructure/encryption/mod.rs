@@ -2,19 +2,25 @@ pub mod vault;
 pub mod vault_impl;
 pub mod dek_manager;
 pub mod master_key;
+pub mod master_key_loader;
 pub mod field_encryption;
+pub mod field_encryption_migration;
 pub mod master_key_rotation;
 pub mod dek_rotation;
 pub mod relationship_encryption;
 pub mod service_encryption;
+pub mod shamir;
 
 pub use vault::Vault;
 pub use vault_impl::{RustyVaultClient, CreateTokenRequest, TokenAuth, TokenEntry};
-pub use dek_manager::DekManager;
-pub use master_key::MasterKey;
+pub use dek_manager::{Dek, DekManager, ENCRYPTED_FIELD_PREFIX};
+pub use master_key::{MasterKey, WrappedDek};
+pub use master_key_loader::{MasterKeyLoader, MasterKeySource};
 pub use field_encryption::FieldEncryption;
+pub use field_encryption_migration::{EncryptedField, EncryptionMigration, FieldEncryptionMigrator, MigrationStatus, ENCRYPTED_FIELDS};
 pub use master_key_rotation::MasterKeyRotation;
 pub use dek_rotation::DekRotation;
 pub use relationship_encryption::RelationshipEncryption;
 pub use service_encryption::{ServiceEncryption, ServiceEncryptionBuilder};
+pub use shamir::ShamirSecret;
 
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use crate::shared::AppResult;
+use crate::shared::{AppError, AppResult};
 
 /// Key vault trait for storing encrypted DEKs and master key
 #[async_trait]
@@ -23,5 +23,63 @@ pub trait Vault: Send + Sync {
     /// Retrieve master key from vault
     /// Returns None if master key doesn't exist (first-time setup)
     async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>>;
+
+    /// Encrypt with a named transit key, returning `vault:v{version}:...`
+    /// Returns None when the vault cannot serve transit requests right now
+    /// (no transit engine, or sealed)
+    async fn transit_encrypt(&self, _key_name: &str, _plaintext: &[u8]) -> AppResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Decrypt a ciphertext produced by `transit_encrypt`
+    async fn transit_decrypt(&self, _key_name: &str, _ciphertext: &str) -> AppResult<Vec<u8>> {
+        Err(AppError::Encryption("Transit decryption is not supported by this vault".to_string()))
+    }
+
+    /// Whether the vault is sealed and cannot serve keys
+    /// Vaults without a seal (cloud KMS) are never sealed
+    async fn is_sealed(&self) -> AppResult<bool> {
+        Ok(false)
+    }
 }
 
+
+/// Lets one vault be shared, e.g. by the master key loader and the DEK manager
+#[async_trait]
+impl<V: Vault + ?Sized> Vault for std::sync::Arc<V> {
+    async fn store_dek(&self, entity_id: &str, entity_type: &str, encrypted_dek: &[u8]) -> AppResult<()> {
+        (**self).store_dek(entity_id, entity_type, encrypted_dek).await
+    }
+
+    async fn get_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<Option<Vec<u8>>> {
+        (**self).get_dek(entity_id, entity_type).await
+    }
+
+    async fn delete_dek(&self, entity_id: &str, entity_type: &str) -> AppResult<()> {
+        (**self).delete_dek(entity_id, entity_type).await
+    }
+
+    async fn rotate_master_key(&self, new_master_key: &[u8]) -> AppResult<()> {
+        (**self).rotate_master_key(new_master_key).await
+    }
+
+    async fn store_master_key(&self, master_key: &[u8]) -> AppResult<()> {
+        (**self).store_master_key(master_key).await
+    }
+
+    async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> {
+        (**self).get_master_key().await
+    }
+
+    async fn transit_encrypt(&self, key_name: &str, plaintext: &[u8]) -> AppResult<Option<String>> {
+        (**self).transit_encrypt(key_name, plaintext).await
+    }
+
+    async fn transit_decrypt(&self, key_name: &str, ciphertext: &str) -> AppResult<Vec<u8>> {
+        (**self).transit_decrypt(key_name, ciphertext).await
+    }
+
+    async fn is_sealed(&self) -> AppResult<bool> {
+        (**self).is_sealed().await
+    }
+}
@@ -207,5 +207,27 @@ impl Vault for HashiCorpVault {
             ))
         }
     }
-}
 
+    async fn is_sealed(&self) -> AppResult<bool> {
+        // Unauthenticated in OpenBao/Vault
+        let path = format!("{}/v1/sys/seal-status", self.addr);
+
+        let response = self.client
+            .get(&path)
+            .send()
+            .await
+            .map_err(|e| crate::shared::AppError::Encryption(format!("Vault request error: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(crate::shared::AppError::Encryption(
+                format!("Seal status check failed: {}", response.status())
+            ));
+        }
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| crate::shared::AppError::Encryption(format!("Vault response parse error: {}", e)))?;
+        json.get("sealed")
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| crate::shared::AppError::Encryption("Seal status response has no sealed flag".to_string()))
+    }
+}
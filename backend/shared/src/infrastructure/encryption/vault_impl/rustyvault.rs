@@ -3,7 +3,9 @@
 //! Extends the base Vault trait with policy and token management
 //! for integration with RustyVault service.
 
+use crate::config::RealmFeatureFlags;
 use crate::infrastructure::encryption::vault::Vault;
+use crate::infrastructure::tracing::InjectTraceContext;
 use crate::shared::{AppError, AppResult};
 use async_trait::async_trait;
 use base64::{Engine as _, engine::general_purpose::STANDARD};
@@ -129,6 +131,7 @@ impl RustyVaultClient {
                     "role_id": role_id,
                     "secret_id": secret_id
                 }))
+                .with_trace_context()
                 .send()
                 .await
                 .map_err(|e| AppError::Encryption(format!("AppRole login error: {}", e)))?;
@@ -184,6 +187,7 @@ impl RustyVaultClient {
                 "role_id": role_id,
                 "secret_id": secret_id
             }))
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("AppRole login error: {}", e)))?;
@@ -230,6 +234,7 @@ impl RustyVaultClient {
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault policy write error: {}", e)))?;
@@ -252,6 +257,7 @@ impl RustyVaultClient {
         let response = self.client
             .delete(&path)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault policy delete error: {}", e)))?;
@@ -274,6 +280,7 @@ impl RustyVaultClient {
         let response = self.client
             .get(&path)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault policy list error: {}", e)))?;
@@ -307,6 +314,7 @@ impl RustyVaultClient {
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(request)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault token create error: {}", e)))?;
@@ -336,6 +344,7 @@ impl RustyVaultClient {
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault token lookup error: {}", e)))?;
@@ -371,6 +380,7 @@ impl RustyVaultClient {
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault token revoke error: {}", e)))?;
@@ -466,6 +476,7 @@ path "sys/policies/*" {{
         let response = self.client
             .get(&path)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault realm lookup error: {}", e)))?;
@@ -499,6 +510,7 @@ path "sys/policies/*" {{
             .post(&create_path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault realm create error: {}", e)))?;
@@ -522,6 +534,38 @@ path "sys/policies/*" {{
         }
     }
 
+    /// Feature flag overrides set on a realm; none when the realm has no overrides
+    pub async fn realm_feature_flags(&self, realm_id: Uuid) -> AppResult<RealmFeatureFlags> {
+        let url = format!("{}/v1/realms/{}/features", self.addr, realm_id);
+
+        let response = self.client
+            .get(&url)
+            .header("X-Vault-Token", &self.token)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Encryption(format!("Vault realm features error: {}", e)))?;
+
+        if response.status().is_success() {
+            let json: serde_json::Value = response.json().await
+                .map_err(|e| AppError::Encryption(format!("Parse error: {}", e)))?;
+
+            match json.get("data").and_then(|d| d.get("overrides")) {
+                Some(overrides) => serde_json::from_value(overrides.clone())
+                    .map_err(|e| AppError::Encryption(format!("Invalid realm features: {}", e))),
+                None => Ok(RealmFeatureFlags::default()),
+            }
+        } else if response.status() == 404 {
+            Ok(RealmFeatureFlags::default())
+        } else {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            Err(AppError::Encryption(format!(
+                "Failed to read realm features: {} - {}", status, error_text
+            )))
+        }
+    }
+
     /// Create a user in a realm
     pub async fn create_realm_user(
         &self,
@@ -540,6 +584,7 @@ path "sys/policies/*" {{
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault user create error: {}", e)))?;
@@ -581,6 +626,7 @@ path "sys/policies/*" {{
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&request)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault token create error: {}", e)))?;
@@ -612,6 +658,7 @@ path "sys/policies/*" {{
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault realm policy write error: {}", e)))?;
@@ -648,6 +695,7 @@ path "sys/policies/*" {{
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault app register error: {}", e)))?;
@@ -670,6 +718,7 @@ path "sys/policies/*" {{
         let response = self.client
             .post(&path)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault app register error: {}", e)))?;
@@ -703,6 +752,7 @@ path "sys/policies/*" {{
         let response = self.client
             .get(&url)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault read error: {}", e)))?;
@@ -744,6 +794,7 @@ path "sys/policies/*" {{
             .post(&url)
             .header("X-Vault-Token", &self.token)
             .json(&body)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault write error: {}", e)))?;
@@ -773,6 +824,7 @@ path "sys/policies/*" {{
         let response = self.client
             .delete(&url)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault delete error: {}", e)))?;
@@ -802,6 +854,7 @@ path "sys/policies/*" {{
         let response = self.client
             .request(reqwest::Method::from_bytes(b"LIST").unwrap_or(reqwest::Method::GET), &url)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault list error: {}", e)))?;
@@ -828,6 +881,65 @@ path "sys/policies/*" {{
             )))
         }
     }
+
+    // ==========================================
+    // TOTP (second factor)
+    // ==========================================
+
+    /// Whether a TOTP key is registered under `name`
+    pub async fn totp_key_exists(&self, name: &str) -> AppResult<bool> {
+        let path = format!("{}/v1/auth/totp/keys/{}", self.addr, name);
+
+        let response = self.client
+            .get(&path)
+            .header("X-Vault-Token", &self.token)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Encryption(format!("Vault TOTP lookup error: {}", e)))?;
+
+        if response.status() == 404 {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Encryption(format!(
+                "TOTP key lookup failed: {} - {}", status, error_text
+            )));
+        }
+        Ok(true)
+    }
+
+    /// Validate a TOTP code; each code is accepted at most once
+    pub async fn validate_totp(&self, name: &str, code: &str) -> AppResult<bool> {
+        let path = format!("{}/v1/auth/totp/validate", self.addr);
+        let data = serde_json::json!({ "name": name, "code": code });
+
+        let response = self.client
+            .post(&path)
+            .header("X-Vault-Token", &self.token)
+            .json(&data)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Encryption(format!("Vault TOTP validate error: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Encryption(format!(
+                "TOTP validation failed: {} - {}", status, error_text
+            )));
+        }
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| AppError::Encryption(format!("Vault response parse error: {}", e)))?;
+        Ok(json.get("data")
+            .and_then(|d| d.get("valid"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false))
+    }
 }
 
 // Implement base Vault trait for RustyVaultClient
@@ -845,6 +957,7 @@ impl Vault for RustyVaultClient {
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
@@ -866,6 +979,7 @@ impl Vault for RustyVaultClient {
         let response = self.client
             .get(&path)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
@@ -897,6 +1011,7 @@ impl Vault for RustyVaultClient {
         let response = self.client
             .delete(&path)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault delete error: {}", e)))?;
@@ -930,6 +1045,7 @@ impl Vault for RustyVaultClient {
             .post(&path)
             .header("X-Vault-Token", &self.token)
             .json(&data)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
@@ -951,6 +1067,7 @@ impl Vault for RustyVaultClient {
         let response = self.client
             .get(&path)
             .header("X-Vault-Token", &self.token)
+            .with_trace_context()
             .send()
             .await
             .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
@@ -981,5 +1098,90 @@ impl Vault for RustyVaultClient {
             ))
         }
     }
-}
 
+    async fn transit_encrypt(&self, key_name: &str, plaintext: &[u8]) -> AppResult<Option<String>> {
+        let path = format!("{}/v1/transit/encrypt/{}", self.addr, key_name);
+        let data = serde_json::json!({ "plaintext": STANDARD.encode(plaintext) });
+
+        let response = self.client
+            .post(&path)
+            .header("X-Vault-Token", &self.token)
+            .json(&data)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
+
+        // Sealed (503) or no transit engine mounted (404)
+        if response.status() == 503 || response.status() == 404 {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Encryption(format!(
+                "Transit encrypt failed: {} - {}", status, error_text
+            )));
+        }
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| AppError::Encryption(format!("Vault response parse error: {}", e)))?;
+        json.get("data")
+            .and_then(|d| d.get("ciphertext"))
+            .and_then(|v| v.as_str())
+            .map(|c| Some(c.to_string()))
+            .ok_or_else(|| AppError::Encryption("Transit response has no ciphertext".to_string()))
+    }
+
+    async fn transit_decrypt(&self, key_name: &str, ciphertext: &str) -> AppResult<Vec<u8>> {
+        let path = format!("{}/v1/transit/decrypt/{}", self.addr, key_name);
+        let data = serde_json::json!({ "ciphertext": ciphertext });
+
+        let response = self.client
+            .post(&path)
+            .header("X-Vault-Token", &self.token)
+            .json(&data)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(AppError::Encryption(format!(
+                "Transit decrypt failed: {} - {}", status, error_text
+            )));
+        }
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| AppError::Encryption(format!("Vault response parse error: {}", e)))?;
+        let plaintext = json.get("data")
+            .and_then(|d| d.get("plaintext"))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| AppError::Encryption("Transit response has no plaintext".to_string()))?;
+        STANDARD.decode(plaintext)
+            .map_err(|e| AppError::Encryption(format!("Base64 decode error: {}", e)))
+    }
+
+    async fn is_sealed(&self) -> AppResult<bool> {
+        let path = format!("{}/v1/sys/seal-status", self.addr);
+
+        let response = self.client
+            .get(&path)
+            .with_trace_context()
+            .send()
+            .await
+            .map_err(|e| AppError::Encryption(format!("Vault request error: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Encryption(format!("Seal status check failed: {}", response.status())));
+        }
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| AppError::Encryption(format!("Vault response parse error: {}", e)))?;
+        json.get("sealed")
+            .and_then(|v| v.as_bool())
+            .ok_or_else(|| AppError::Encryption("Seal status response has no sealed flag".to_string()))
+    }
+}
@@ -0,0 +1,363 @@
+//! Dependency health checks
+//!
+//! `GET /health` reports every dependency and answers 503 when any is down,
+//! `GET /ready` answers 503 only when a required dependency (database, vault)
+//! is down, and `GET /live` always answers 200 while the process is serving.
+//! Each probe is given [`PROBE_TIMEOUT`] to answer.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::infrastructure::database::mumps::YottaDbAdapter;
+use crate::infrastructure::database::DatabaseService;
+use crate::infrastructure::encryption::Vault;
+use crate::infrastructure::session::RedisStore;
+use crate::shared::{AppError, AppResult};
+
+/// How long one probe may take before its dependency is reported down
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Check of one dependency
+#[async_trait]
+pub trait DependencyProbe: Send + Sync {
+    /// Key under `dependencies` in the health response
+    fn name(&self) -> &'static str;
+
+    /// Whether the service cannot take traffic while this dependency is down
+    fn required(&self) -> bool {
+        true
+    }
+
+    /// Ok when the dependency is up
+    async fn check(&self) -> AppResult<()>;
+}
+
+/// PostgreSQL, via `SELECT 1`
+pub struct DatabaseProbe(pub Arc<DatabaseService>);
+
+#[async_trait]
+impl DependencyProbe for DatabaseProbe {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    async fn check(&self) -> AppResult<()> {
+        self.0.health_check().await.map(|_| ())
+    }
+}
+
+/// Vault/KMS holding the master key; down while sealed
+pub struct VaultProbe(pub Arc<dyn Vault>);
+
+#[async_trait]
+impl DependencyProbe for VaultProbe {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn check(&self) -> AppResult<()> {
+        if self.0.is_sealed().await? {
+            return Err(AppError::Encryption("Vault is sealed".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Redis session cache, via `PING`; sessions fall back to the database without it
+pub struct SessionCacheProbe(pub Arc<dyn RedisStore>);
+
+#[async_trait]
+impl DependencyProbe for SessionCacheProbe {
+    fn name(&self) -> &'static str {
+        "session_cache"
+    }
+
+    fn required(&self) -> bool {
+        false
+    }
+
+    async fn check(&self) -> AppResult<()> {
+        self.0.ping().await
+    }
+}
+
+/// YottaDB M Web Server backing the EHR globals
+pub struct MumpsProbe(pub Arc<YottaDbAdapter>);
+
+#[async_trait]
+impl DependencyProbe for MumpsProbe {
+    fn name(&self) -> &'static str {
+        "mumps"
+    }
+
+    fn required(&self) -> bool {
+        false
+    }
+
+    async fn check(&self) -> AppResult<()> {
+        self.0.ping().await
+    }
+}
+
+/// Outcome of one probe
+#[derive(Debug, Clone)]
+pub struct DependencyStatus {
+    pub name: &'static str,
+    pub required: bool,
+    /// None when the dependency is up
+    pub error: Option<String>,
+}
+
+impl DependencyStatus {
+    pub fn is_up(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Outcome of every probe, in registration order
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub dependencies: Vec<DependencyStatus>,
+}
+
+impl HealthReport {
+    /// Every dependency is up
+    pub fn is_healthy(&self) -> bool {
+        self.dependencies.iter().all(DependencyStatus::is_up)
+    }
+
+    /// Every required dependency is up
+    pub fn is_ready(&self) -> bool {
+        self.dependencies.iter().filter(|d| d.required).all(DependencyStatus::is_up)
+    }
+
+    /// `{"status": "healthy"|"degraded", "dependencies": {"database": "up", ..., "error": "..."}}`
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut dependencies = BTreeMap::new();
+        let mut errors = Vec::new();
+        for dependency in &self.dependencies {
+            let state = if dependency.is_up() { "up" } else { "down" };
+            dependencies.insert(dependency.name.to_string(), state.to_string());
+            if let Some(error) = &dependency.error {
+                errors.push(format!("{}: {}", dependency.name, error));
+            }
+        }
+        if !errors.is_empty() {
+            dependencies.insert("error".to_string(), errors.join("; "));
+        }
+
+        serde_json::json!({
+            "status": if self.is_healthy() { "healthy" } else { "degraded" },
+            "dependencies": dependencies,
+        })
+    }
+}
+
+/// Runs the registered probes
+pub struct HealthChecker {
+    probes: Vec<Arc<dyn DependencyProbe>>,
+    timeout: Duration,
+}
+
+impl Default for HealthChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthChecker {
+    pub fn new() -> Self {
+        Self { probes: Vec::new(), timeout: PROBE_TIMEOUT }
+    }
+
+    pub fn with_probe(mut self, probe: Arc<dyn DependencyProbe>) -> Self {
+        self.probes.push(probe);
+        self
+    }
+
+    /// Give each probe `timeout` instead of [`PROBE_TIMEOUT`]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Probe every dependency concurrently
+    pub async fn check(&self) -> HealthReport {
+        let timeout = self.timeout;
+        let handles: Vec<_> = self
+            .probes
+            .iter()
+            .map(|probe| {
+                let probe = Arc::clone(probe);
+                tokio::spawn(async move {
+                    match tokio::time::timeout(timeout, probe.check()).await {
+                        Ok(Ok(())) => None,
+                        Ok(Err(e)) => Some(e.to_string()),
+                        Err(_) => Some(format!("no response within {}s", timeout.as_secs_f64())),
+                    }
+                })
+            })
+            .collect();
+
+        let mut dependencies = Vec::with_capacity(handles.len());
+        for (probe, handle) in self.probes.iter().zip(handles) {
+            let error = handle.await.unwrap_or_else(|e| Some(format!("probe failed: {}", e)));
+            dependencies.push(DependencyStatus { name: probe.name(), required: probe.required(), error });
+        }
+        HealthReport { dependencies }
+    }
+}
+
+fn report_response(healthy: bool, report: &HealthReport) -> Response {
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report.to_json())).into_response()
+}
+
+/// `GET /health`: 503 when any dependency is down
+pub async fn health(State(checker): State<Arc<HealthChecker>>) -> Response {
+    let report = checker.check().await;
+    report_response(report.is_healthy(), &report)
+}
+
+/// `GET /ready`: 503 when a required dependency is down
+pub async fn ready(State(checker): State<Arc<HealthChecker>>) -> Response {
+    let report = checker.check().await;
+    report_response(report.is_ready(), &report)
+}
+
+/// `GET /live`: the process is up; dependencies are not probed
+pub async fn live() -> Response {
+    (StatusCode::OK, Json(serde_json::json!({ "status": "alive" }))).into_response()
+}
+
+/// `/health`, `/ready` and `/live`
+pub fn health_routes(checker: Arc<HealthChecker>) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/live", get(live))
+        .with_state(checker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::extract::Request;
+    use tower::ServiceExt;
+
+    struct FakeProbe {
+        name: &'static str,
+        required: bool,
+        result: Result<(), &'static str>,
+        delay: Duration,
+    }
+
+    impl FakeProbe {
+        fn up(name: &'static str, required: bool) -> Arc<dyn DependencyProbe> {
+            Arc::new(Self { name, required, result: Ok(()), delay: Duration::ZERO })
+        }
+
+        fn down(name: &'static str, required: bool, error: &'static str) -> Arc<dyn DependencyProbe> {
+            Arc::new(Self { name, required, result: Err(error), delay: Duration::ZERO })
+        }
+    }
+
+    #[async_trait]
+    impl DependencyProbe for FakeProbe {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn required(&self) -> bool {
+            self.required
+        }
+
+        async fn check(&self) -> AppResult<()> {
+            tokio::time::sleep(self.delay).await;
+            self.result.map_err(|e| AppError::Internal(e.to_string()))
+        }
+    }
+
+    async fn get_json(checker: HealthChecker, path: &str) -> (StatusCode, serde_json::Value) {
+        let app = health_routes(Arc::new(checker));
+        let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    fn failing_database() -> HealthChecker {
+        HealthChecker::new()
+            .with_probe(FakeProbe::down("database", true, "connection refused"))
+            .with_probe(FakeProbe::up("vault", true))
+    }
+
+    #[tokio::test]
+    async fn test_failing_database_is_not_ready_but_live() {
+        let (status, body) = get_json(failing_database(), "/ready").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["status"], "degraded");
+        assert_eq!(body["dependencies"]["database"], "down");
+        assert_eq!(body["dependencies"]["error"], "database: Internal error: connection refused");
+
+        let (status, _) = get_json(failing_database(), "/live").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_healthy_dependencies() {
+        let checker = HealthChecker::new()
+            .with_probe(FakeProbe::up("database", true))
+            .with_probe(FakeProbe::up("vault", true))
+            .with_probe(FakeProbe::up("session_cache", false));
+        let (status, body) = get_json(checker, "/health").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "status": "healthy",
+                "dependencies": { "database": "up", "vault": "up", "session_cache": "up" },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_optional_dependency_down_is_degraded_but_ready() {
+        let checker = || {
+            HealthChecker::new()
+                .with_probe(FakeProbe::up("database", true))
+                .with_probe(FakeProbe::down("session_cache", false, "PING failed"))
+        };
+        let (status, body) = get_json(checker(), "/health").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["dependencies"]["session_cache"], "down");
+
+        let (status, _) = get_json(checker(), "/ready").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_slow_probe_times_out() {
+        let checker = HealthChecker::new()
+            .with_timeout(Duration::from_millis(10))
+            .with_probe(Arc::new(FakeProbe {
+                name: "database",
+                required: true,
+                result: Ok(()),
+                delay: Duration::from_secs(60),
+            }));
+        let report = checker.check().await;
+        assert!(!report.is_ready());
+        assert_eq!(report.dependencies[0].error.as_deref(), Some("no response within 0.01s"));
+    }
+}
@@ -0,0 +1,339 @@
+//! Background job queue
+//!
+//! Long-running work (CCD generation, FHIR export, bulk import) is enqueued in
+//! the `jobs` table and processed by a `JobWorker`. Workers claim jobs with
+//! `SELECT ... FOR UPDATE SKIP LOCKED`, so several api-service instances can
+//! poll the same queue without handing out a job twice.
+//!
+//! A worker refreshes the job's heartbeat while its handler runs. A running job
+//! whose heartbeat is older than the lease timeout belongs to a worker that
+//! crashed, and is claimed again.
+
+use crate::infrastructure::database::RepositoryErrorExt;
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Default delay between polls when the queue is empty
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default time a running job may go without a heartbeat before it is reclaimed
+const DEFAULT_LEASE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Job lifecycle status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A queued background job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: Value,
+    pub status: JobStatus,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    pub result: Option<Value>,
+}
+
+/// Handler for a single job type
+///
+/// CPU-bound work (serialization, document rendering) should be moved off the
+/// async runtime with `tokio::task::spawn_blocking`.
+#[async_trait]
+pub trait JobHandler: Send + Sync {
+    async fn handle(&self, payload: Value) -> AppResult<Value>;
+}
+
+/// PostgreSQL-backed job queue
+pub struct JobQueue {
+    pool: Arc<PgPool>,
+    lease_timeout: Duration,
+}
+
+impl JobQueue {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self {
+            pool,
+            lease_timeout: DEFAULT_LEASE_TIMEOUT,
+        }
+    }
+
+    /// Set how long a running job may go without a heartbeat before it is reclaimed
+    pub fn with_lease_timeout(mut self, lease_timeout: Duration) -> Self {
+        self.lease_timeout = lease_timeout;
+        self
+    }
+
+    /// Enqueue a job and return its ID
+    pub async fn enqueue(&self, job_type: &str, payload: Value) -> AppResult<Uuid> {
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO jobs (job_type, payload, status)
+            VALUES ($1, $2, 'pending')
+            RETURNING id
+            "#,
+            job_type,
+            payload
+        )
+        .fetch_one(self.pool.as_ref())
+        .await
+        .map_db_error("create", "job")?;
+
+        tracing::debug!("Enqueued {} job {}", job_type, id);
+        Ok(id)
+    }
+
+    /// Get a job by ID
+    pub async fn get(&self, id: Uuid) -> AppResult<Option<Job>> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            SELECT id, job_type, payload, status as "status: JobStatus",
+                   created_at, started_at, completed_at, error, result
+            FROM jobs
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await
+        .map_db_error("find", "job")
+    }
+
+    /// Claim the oldest pending job of one of the given types
+    ///
+    /// A running job whose lease has expired is claimed as if it were pending.
+    /// Rows locked by another worker are skipped rather than waited on.
+    pub async fn claim_next(&self, job_types: &[String]) -> AppResult<Option<Job>> {
+        sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE jobs
+            SET status = 'running', started_at = NOW(), heartbeat_at = NOW()
+            WHERE id = (
+                SELECT id FROM jobs
+                WHERE job_type = ANY($1)
+                  AND (status = 'pending'
+                       OR (status = 'running'
+                           AND COALESCE(heartbeat_at, started_at) < NOW() - make_interval(secs => $2)))
+                ORDER BY created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, job_type, payload, status as "status: JobStatus",
+                      created_at, started_at, completed_at, error, result
+            "#,
+            job_types,
+            self.lease_timeout.as_secs_f64()
+        )
+        .fetch_optional(self.pool.as_ref())
+        .await
+        .map_db_error("claim", "job")
+    }
+
+    /// Extend the lease of a running job
+    pub async fn heartbeat(&self, id: Uuid) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET heartbeat_at = NOW()
+            WHERE id = $1 AND status = 'running'
+            "#,
+            id
+        )
+        .execute(self.pool.as_ref())
+        .await
+        .map_db_error("update", "job")?;
+        Ok(())
+    }
+
+    /// Mark a job completed with its result
+    pub async fn complete(&self, id: Uuid, result: Value) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET status = 'completed', completed_at = NOW(), result = $2, error = NULL
+            WHERE id = $1
+            "#,
+            id,
+            result
+        )
+        .execute(self.pool.as_ref())
+        .await
+        .map_db_error("update", "job")?;
+        Ok(())
+    }
+
+    /// Mark a job failed with an error message
+    pub async fn fail(&self, id: Uuid, error: &str) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET status = 'failed', completed_at = NOW(), error = $2
+            WHERE id = $1
+            "#,
+            id,
+            error
+        )
+        .execute(self.pool.as_ref())
+        .await
+        .map_db_error("update", "job")?;
+        Ok(())
+    }
+}
+
+/// Polls the job queue and dispatches jobs to registered handlers
+pub struct JobWorker {
+    queue: Arc<JobQueue>,
+    handlers: HashMap<String, Arc<dyn JobHandler>>,
+    poll_interval: Duration,
+}
+
+impl JobWorker {
+    pub fn new(queue: Arc<JobQueue>) -> Self {
+        Self {
+            queue,
+            handlers: HashMap::new(),
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Set the delay between polls when the queue is empty
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Register the handler for a job type
+    pub fn register(mut self, job_type: impl Into<String>, handler: Arc<dyn JobHandler>) -> Self {
+        self.handlers.insert(job_type.into(), handler);
+        self
+    }
+
+    /// Job types this worker can process
+    pub fn job_types(&self) -> Vec<String> {
+        self.handlers.keys().cloned().collect()
+    }
+
+    /// Claim and process a single job
+    /// Returns false when no job was available.
+    pub async fn run_once(&self) -> AppResult<bool> {
+        let Some(job) = self.queue.claim_next(&self.job_types()).await? else {
+            return Ok(false);
+        };
+
+        let handler = self.handlers.get(&job.job_type).cloned().ok_or_else(|| {
+            AppError::Internal(format!("No handler registered for job type {}", job.job_type))
+        })?;
+
+        tracing::info!("Processing {} job {}", job.job_type, job.id);
+        let heartbeat = self.spawn_heartbeat(job.id);
+        let outcome = handler.handle(job.payload).await;
+        heartbeat.abort();
+
+        match outcome {
+            Ok(result) => {
+                self.queue.complete(job.id, result).await?;
+                tracing::info!("Completed {} job {}", job.job_type, job.id);
+            }
+            Err(e) => {
+                tracing::warn!("{} job {} failed: {}", job.job_type, job.id, e);
+                self.queue.fail(job.id, &e.to_string()).await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Refresh the job's heartbeat well within the lease until aborted
+    fn spawn_heartbeat(&self, id: Uuid) -> tokio::task::JoinHandle<()> {
+        let queue = self.queue.clone();
+        let period = queue.lease_timeout / 3;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            // The first tick completes immediately; claim_next already set the heartbeat
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Err(e) = queue.heartbeat(id).await {
+                    tracing::warn!("Failed to refresh heartbeat of job {}: {}", id, e);
+                }
+            }
+        })
+    }
+
+    /// Process jobs until the task is aborted
+    pub async fn run(self) {
+        loop {
+            match self.run_once().await {
+                // Drain the queue before sleeping again
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => tracing::error!("Job worker error: {}", e),
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Spawn the worker loop on the Tokio runtime
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ignore] // Requires test database to be running
+    async fn test_claim_next_reclaims_job_with_expired_lease() {
+        let pool = Arc::new(crate::testing::create_test_pool().await);
+        let queue = JobQueue::new(pool.clone()).with_lease_timeout(Duration::from_secs(60));
+        let job_type = format!("lease_test_{}", Uuid::new_v4());
+        let job_types = vec![job_type.clone()];
+
+        let id = queue.enqueue(&job_type, serde_json::json!({})).await.unwrap();
+        let claimed = queue.claim_next(&job_types).await.unwrap().unwrap();
+        assert_eq!(claimed.id, id);
+
+        // A live lease is not handed out again
+        queue.heartbeat(id).await.unwrap();
+        assert!(queue.claim_next(&job_types).await.unwrap().is_none());
+
+        // The worker crashed: no heartbeat for longer than the lease
+        sqlx::query("UPDATE jobs SET heartbeat_at = NOW() - INTERVAL '5 minutes' WHERE id = $1")
+            .bind(id)
+            .execute(pool.as_ref())
+            .await
+            .unwrap();
+        let reclaimed = queue.claim_next(&job_types).await.unwrap().unwrap();
+        assert_eq!(reclaimed.id, id);
+        assert_eq!(reclaimed.status, JobStatus::Running);
+
+        queue.complete(id, serde_json::json!({})).await.unwrap();
+        sqlx::query("UPDATE jobs SET heartbeat_at = NOW() - INTERVAL '5 minutes' WHERE id = $1")
+            .bind(id)
+            .execute(pool.as_ref())
+            .await
+            .unwrap();
+        assert!(queue.claim_next(&job_types).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,204 @@
+//! Request/response body sampling for debugging
+//!
+//! When `DEBUG_BODY_SAMPLING_RATE` is set, that fraction of requests has its
+//! JSON request body and the first 2 KB of its JSON response body logged, with
+//! [`SENSITIVE_FIELDS`](crate::shared::masking::SENSITIVE_FIELDS) redacted.
+//! Other bodies are streamed through untouched.
+
+use axum::body::{to_bytes, Body, Bytes};
+use axum::extract::Request;
+use axum::http::{header, HeaderMap};
+use axum::response::Response;
+use crate::shared::masking::redact_json;
+
+/// Logged prefix of a sampled response body
+pub const MAX_LOGGED_RESPONSE_BYTES: usize = 2048;
+
+/// Decides which requests have their bodies logged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodySampler {
+    rate: f64,
+}
+
+impl BodySampler {
+    /// Sample `rate` of requests, clamped to 0.0–1.0
+    pub fn new(rate: f64) -> Self {
+        let rate = if rate.is_nan() { 0.0 } else { rate.clamp(0.0, 1.0) };
+        Self { rate }
+    }
+
+    /// Sample no requests
+    pub fn disabled() -> Self {
+        Self::new(0.0)
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Roll for one request
+    pub fn should_sample(&self) -> bool {
+        self.rate > 0.0 && rand::random::<f64>() < self.rate
+    }
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.contains("json"))
+}
+
+/// Body as logged: redacted JSON, or a note when it could not be parsed
+fn redacted(bytes: &Bytes) -> String {
+    match serde_json::from_slice::<serde_json::Value>(bytes) {
+        Ok(mut value) => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("[unparseable JSON body, {} bytes]", bytes.len()),
+    }
+}
+
+/// Cut `text` to at most `max_bytes`, on a character boundary
+fn truncate(mut text: String, max_bytes: usize) -> String {
+    if text.len() > max_bytes {
+        let mut end = max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+/// Buffer a JSON request body for logging, rebuilding the request for the handler
+pub async fn sample_request_body(request: Request) -> (Request, Option<String>) {
+    if !is_json(request.headers()) {
+        return (request, None);
+    }
+    let (parts, body) = request.into_parts();
+    match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => {
+            let logged = redacted(&bytes);
+            (Request::from_parts(parts, Body::from(bytes)), Some(logged))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to buffer request body for sampling: {}", e);
+            (Request::from_parts(parts, Body::empty()), None)
+        }
+    }
+}
+
+/// Buffer a JSON response body, returning its first 2 KB (redacted) for logging
+pub async fn sample_response_body(response: Response) -> (Response, Option<String>) {
+    if !is_json(response.headers()) {
+        return (response, None);
+    }
+    let (parts, body) = response.into_parts();
+    match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => {
+            let logged = truncate(redacted(&bytes), MAX_LOGGED_RESPONSE_BYTES);
+            (Response::from_parts(parts, Body::from(bytes)), Some(logged))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to buffer response body for sampling: {}", e);
+            (Response::from_parts(parts, Body::empty()), None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use axum::{middleware::Next, routing::post, Json, Router};
+    use tower::ServiceExt;
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogBuffer {
+        type Writer = LogBuffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Logs like `request_logging_middleware`
+    async fn sampling_middleware(request: Request, next: Next) -> Response {
+        let sampler = BodySampler::new(1.0);
+        let body_sampled = sampler.should_sample();
+        let (request, request_body) = sample_request_body(request).await;
+        let (response, response_body) = sample_response_body(next.run(request).await).await;
+        tracing::info!(
+            body_sampled,
+            request_body = request_body.as_deref(),
+            response_body = response_body.as_deref(),
+            "Request completed"
+        );
+        response
+    }
+
+    /// Echoes the password so the test can see the handler got the real body
+    async fn login(Json(body): Json<serde_json::Value>) -> Json<serde_json::Value> {
+        Json(serde_json::json!({ "password": body["password"], "padding": "x".repeat(4096) }))
+    }
+
+    #[tokio::test]
+    async fn test_sampled_request_body_is_logged_redacted() {
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::fmt().json().with_writer(logs.clone()).finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/v1/auth/login", post(login))
+            .layer(axum::middleware::from_fn(sampling_middleware));
+        let request = Request::builder()
+            .method("POST")
+            .uri("/v1/auth/login")
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"username":"jdoe","password":"hunter2"}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        let echoed: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(echoed["password"], "hunter2");
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let line = output.lines().find(|line| line.contains("Request completed")).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(entry["fields"]["body_sampled"], true);
+        let request_body: serde_json::Value =
+            serde_json::from_str(entry["fields"]["request_body"].as_str().unwrap()).unwrap();
+        assert_eq!(request_body, serde_json::json!({ "username": "jdoe", "password": "[REDACTED]" }));
+        assert!(!line.contains("hunter2"), "{}", line);
+        assert_eq!(
+            entry["fields"]["response_body"].as_str().unwrap().len(),
+            MAX_LOGGED_RESPONSE_BYTES
+        );
+    }
+
+    #[test]
+    fn test_rate_is_clamped() {
+        assert_eq!(BodySampler::new(2.5).rate(), 1.0);
+        assert_eq!(BodySampler::new(-1.0).rate(), 0.0);
+        assert_eq!(BodySampler::new(f64::NAN).rate(), 0.0);
+        assert!(!BodySampler::disabled().should_sample());
+        assert!(BodySampler::new(1.0).should_sample());
+    }
+}
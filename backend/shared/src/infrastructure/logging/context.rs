@@ -1,5 +1,7 @@
 use crate::shared::RequestContext;
+use opentelemetry::trace::TraceContextExt;
 use tracing::span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 
 /// Structured logging context
@@ -97,15 +99,25 @@ pub fn log_error_with_context(
 }
 
 /// Create a tracing span with request context
+///
+/// The span joins the active OpenTelemetry trace, including from tasks that
+/// only carry a `RequestContext`, and records its `trace_id`.
 pub fn span_with_context(_name: &'static str, context: &LogContext) -> tracing::Span {
-    // Create span with fields from context
-    // Note: We use a simple approach - the span will be created and fields can be added via enter()
-    let span = tracing::span!(tracing::Level::INFO, "operation");
+    let span = tracing::span!(
+        tracing::Level::INFO,
+        "operation",
+        request_id = tracing::field::Empty,
+        user_id = tracing::field::Empty,
+        operation = tracing::field::Empty,
+        resource = tracing::field::Empty,
+        resource_id = tracing::field::Empty,
+        trace_id = tracing::field::Empty,
+    );
     if let Some(ref request_id) = context.request_id {
         span.record("request_id", request_id.as_str());
     }
     if let Some(user_id) = context.user_id {
-        span.record("user_id", &tracing::field::display(user_id));
+        span.record("user_id", tracing::field::display(user_id));
     }
     if let Some(ref operation) = context.operation {
         span.record("operation", operation.as_str());
@@ -116,6 +128,13 @@ pub fn span_with_context(_name: &'static str, context: &LogContext) -> tracing::
     if let Some(ref resource_id) = context.resource_id {
         span.record("resource_id", resource_id.as_str());
     }
+
+    let parent = crate::infrastructure::tracing::current_context();
+    let span_context = parent.span().span_context().clone();
+    if span_context.is_valid() {
+        span.record("trace_id", tracing::field::display(span_context.trace_id()));
+        span.set_parent(parent);
+    }
     span
 }
 
@@ -1,5 +1,8 @@
 use super::config::{LogFormat, LoggerConfig};
+use super::masking_layer::PiiMaskingLayer;
 use std::env;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 /// Initialize the logger with the given configuration
 pub fn init_logger(config: &LoggerConfig) {
@@ -10,25 +13,33 @@ pub fn init_logger(config: &LoggerConfig) {
         env::set_var("RUST_LOG", &filter_string);
     }
 
+    // Propagation works even when spans aren't exported
+    crate::infrastructure::tracing::init_propagator();
+    let otel_layer = crate::infrastructure::tracing::otlp_tracer()
+        .map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+    let registry = tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(otel_layer);
+
     match config.format {
         LogFormat::Json => {
             // JSON format for production
-            tracing_subscriber::fmt()
-                .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-                .json()
-                .with_target(true)
-                .with_file(config.include_location)
-                .with_line_number(config.include_location)
+            registry
+                .with(PiiMaskingLayer::new(tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_target(true)
+                    .with_file(config.include_location)
+                    .with_line_number(config.include_location)))
                 .init();
         }
         LogFormat::Pretty => {
             // Pretty format for development
-            tracing_subscriber::fmt()
-                .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
-                .pretty()
-                .with_target(true)
-                .with_file(config.include_location)
-                .with_line_number(config.include_location)
+            registry
+                .with(PiiMaskingLayer::new(tracing_subscriber::fmt::layer()
+                    .pretty()
+                    .with_target(true)
+                    .with_file(config.include_location)
+                    .with_line_number(config.include_location)))
                 .init();
         }
     }
@@ -0,0 +1,280 @@
+//! PII masking for log output
+//!
+//! `PiiMaskingLayer` wraps the layer that writes log lines and hands it a
+//! copy of each event with PII masked by a [`PiiMasker`]: fields with a
+//! configured name are masked whole, and JSON embedded in other fields (a
+//! serialized request body, or a message that interpolates one) has its
+//! configured fields masked. Events with nothing to mask are passed through
+//! as they are. Span fields are not masked.
+
+use std::fmt;
+
+use serde_json::Value as JsonValue;
+use tracing::field::{display, DisplayValue, Field, Value, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+use crate::shared::masking::PiiMasker;
+
+/// Masks PII in events before `inner` sees them
+pub struct PiiMaskingLayer<L> {
+    inner: L,
+    masker: PiiMasker,
+}
+
+impl<L> PiiMaskingLayer<L> {
+    /// Redact [`SENSITIVE_FIELDS`](crate::shared::masking::SENSITIVE_FIELDS)
+    pub fn new(inner: L) -> Self {
+        Self::with_masker(inner, PiiMasker::default())
+    }
+
+    pub fn with_masker(inner: L, masker: PiiMasker) -> Self {
+        Self { inner, masker }
+    }
+}
+
+/// Recorded value of one event field
+enum FieldValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Text(DisplayValue<String>),
+}
+
+impl FieldValue {
+    fn as_value(&self) -> &dyn Value {
+        match self {
+            FieldValue::Bool(value) => value,
+            FieldValue::I64(value) => value,
+            FieldValue::U64(value) => value,
+            FieldValue::F64(value) => value,
+            FieldValue::Text(value) => value,
+        }
+    }
+}
+
+/// An event's fields, masked
+struct MaskedFields<'a> {
+    masker: &'a PiiMasker,
+    /// Indexed like the event's field set
+    values: Vec<Option<FieldValue>>,
+    changed: bool,
+}
+
+impl<'a> MaskedFields<'a> {
+    fn new(masker: &'a PiiMasker, field_count: usize) -> Self {
+        Self { masker, values: (0..field_count).map(|_| None).collect(), changed: false }
+    }
+
+    fn set(&mut self, field: &Field, value: FieldValue) {
+        if let Some(slot) = self.values.get_mut(field.index()) {
+            *slot = Some(value);
+        }
+    }
+
+    fn record_masked(&mut self, field: &Field, value: JsonValue) {
+        let masked = match self.masker.mask_value(&value) {
+            JsonValue::String(text) => text,
+            other => other.to_string(),
+        };
+        self.changed = true;
+        self.set(field, FieldValue::Text(display(masked)));
+    }
+
+    fn record_text(&mut self, field: &Field, text: String) {
+        if self.masker.is_masked_field(field.name()) {
+            return self.record_masked(field, JsonValue::String(text));
+        }
+        let text = match self.masker.mask_embedded_json(&text) {
+            Some(masked) => {
+                self.changed = true;
+                masked
+            }
+            None => text,
+        };
+        self.set(field, FieldValue::Text(display(text)));
+    }
+
+    fn record_scalar(&mut self, field: &Field, value: FieldValue, json: JsonValue) {
+        if self.masker.is_masked_field(field.name()) {
+            self.record_masked(field, json);
+        } else {
+            self.set(field, value);
+        }
+    }
+}
+
+impl Visit for MaskedFields<'_> {
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record_scalar(field, FieldValue::Bool(value), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record_scalar(field, FieldValue::I64(value), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.record_scalar(field, FieldValue::U64(value), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record_scalar(field, FieldValue::F64(value), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record_text(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record_text(field, format!("{:?}", value));
+    }
+}
+
+impl<S, L> Layer<S> for PiiMaskingLayer<L>
+where
+    S: Subscriber,
+    L: Layer<S>,
+{
+    fn on_register_dispatch(&self, subscriber: &tracing::Dispatch) {
+        self.inner.on_register_dispatch(subscriber);
+    }
+
+    fn on_layer(&mut self, subscriber: &mut S) {
+        self.inner.on_layer(subscriber);
+    }
+
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> tracing::subscriber::Interest {
+        self.inner.register_callsite(metadata)
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.enabled(metadata, ctx)
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_new_span(attrs, id, ctx);
+    }
+
+    fn max_level_hint(&self) -> Option<tracing::level_filters::LevelFilter> {
+        self.inner.max_level_hint()
+    }
+
+    fn on_record(&self, span: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        self.inner.on_record(span, values, ctx);
+    }
+
+    fn on_follows_from(&self, span: &Id, follows: &Id, ctx: Context<'_, S>) {
+        self.inner.on_follows_from(span, follows, ctx);
+    }
+
+    fn event_enabled(&self, event: &Event<'_>, ctx: Context<'_, S>) -> bool {
+        self.inner.event_enabled(event, ctx)
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut fields = MaskedFields::new(&self.masker, metadata.fields().len());
+        event.record(&mut fields);
+        if !fields.changed {
+            return self.inner.on_event(event, ctx);
+        }
+
+        // Same layout the `event!` macro builds: one optional value per field
+        let values: Vec<Option<&dyn Value>> =
+            fields.values.iter().map(|value| value.as_ref().map(FieldValue::as_value)).collect();
+        let value_set = metadata.fields().value_set_all(&values);
+        let masked = if event.is_contextual() {
+            Event::new(metadata, &value_set)
+        } else {
+            Event::new_child_of(event.parent().cloned(), metadata, &value_set)
+        };
+        self.inner.on_event(&masked, ctx);
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_enter(id, ctx);
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        self.inner.on_exit(id, ctx);
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        self.inner.on_close(id, ctx);
+    }
+
+    fn on_id_change(&self, old: &Id, new: &Id, ctx: Context<'_, S>) {
+        self.inner.on_id_change(old, new, ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogBuffer {
+        type Writer = LogBuffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// JSON lines written while `log` runs
+    fn logged(log: impl FnOnce()) -> Vec<serde_json::Value> {
+        let logs = LogBuffer::default();
+        let output = tracing_subscriber::fmt::layer().json().with_writer(logs.clone());
+        let subscriber = tracing_subscriber::registry().with(PiiMaskingLayer::new(output));
+        tracing::subscriber::with_default(subscriber, log);
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        output.lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_embedded_json_is_redacted() {
+        let body = serde_json::json!({ "ssn": "123-45-6789" });
+        let lines = logged(|| tracing::info!(body = %body, "Patient registered"));
+
+        let fields = &lines[0]["fields"];
+        let logged_body: serde_json::Value = serde_json::from_str(fields["body"].as_str().unwrap()).unwrap();
+        assert_eq!(logged_body, serde_json::json!({ "ssn": "[REDACTED]" }));
+        assert_eq!(fields["message"], "Patient registered");
+    }
+
+    #[test]
+    fn test_sensitive_fields_and_messages_are_masked() {
+        let lines = logged(|| {
+            let span = tracing::info_span!("login");
+            let _entered = span.enter();
+            tracing::warn!(ssn = "123-45-6789", attempts = 3, "Lookup for {}", r#"{"password":"hunter2"}"#);
+            tracing::info!(user = "jdoe", "Nothing to mask");
+        });
+
+        let fields = &lines[0]["fields"];
+        assert_eq!(fields["ssn"], "[REDACTED]");
+        assert_eq!(fields["attempts"], 3);
+        assert_eq!(fields["message"], r#"Lookup for {"password":"[REDACTED]"}"#);
+        assert_eq!(lines[0]["span"]["name"], "login");
+        assert_eq!(lines[1]["fields"]["user"], "jdoe");
+    }
+}
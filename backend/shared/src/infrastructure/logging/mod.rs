@@ -1,10 +1,14 @@
+pub mod body_sampling;
 pub mod config;
 pub mod context;
 pub mod formatter;
+pub mod masking_layer;
 
+pub use body_sampling::{sample_request_body, sample_response_body, BodySampler};
 pub use config::{LogFormat, LoggerConfig};
 pub use context::{LogContext, span_with_context, span_from_request_context};
 pub use formatter::{init_logger, init_default};
+pub use masking_layer::PiiMaskingLayer;
 
 use crate::config::settings::LoggingConfig;
 use crate::config::deployment::DeploymentConfig;
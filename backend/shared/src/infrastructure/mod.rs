@@ -6,8 +6,12 @@ pub mod oidc;
 pub mod zanzibar;
 pub mod repositories;
 pub mod logging;
+pub mod tracing;
 pub mod session;
 pub mod runtime;
 pub mod api;
 pub mod validation;
+pub mod jobs;
 
+pub mod health;
+pub mod audit_archive;
@@ -3,6 +3,6 @@ pub mod token;
 pub mod jwks;
 
 pub use provider::OidcProvider;
-pub use token::{TokenManager, Claims};
+pub use token::{TokenManager, Claims, API_SERVICE_AUDIENCE, RUSTYVAULT_AUDIENCE};
 pub use jwks::Jwks;
 
@@ -1,5 +1,6 @@
-use crate::shared::AppResult;
+use crate::shared::{AppError, AppResult};
 use crate::domain::entities::User;
+use super::token::{Claims, TokenManager};
 
 pub struct OidcProvider {
     issuer: String,
@@ -7,6 +8,7 @@ pub struct OidcProvider {
     client_id: String,
     #[allow(dead_code)]
     client_secret: String,
+    token_manager: Option<TokenManager>,
 }
 
 impl OidcProvider {
@@ -15,9 +17,26 @@ impl OidcProvider {
             issuer,
             client_id,
             client_secret,
+            token_manager: None,
         }
     }
 
+    /// Set the token manager used to verify issued tokens
+    pub fn with_token_manager(mut self, token_manager: TokenManager) -> Self {
+        self.token_manager = Some(token_manager);
+        self
+    }
+
+    /// Verify a token for a specific service
+    /// Service middleware should call this with its own audience so tokens
+    /// issued for one service are not accepted by another.
+    pub fn verify_token_for_audience(&self, token: &str, expected_audience: &str) -> AppResult<Claims> {
+        self.token_manager
+            .as_ref()
+            .ok_or_else(|| AppError::Configuration("OIDC token verification is not configured".to_string()))?
+            .verify_token_for_audience(token, expected_audience)
+    }
+
     pub fn authorization_endpoint(&self) -> String {
         format!("{}/auth/authorize", self.issuer)
     }
@@ -4,7 +4,12 @@ use jsonwebtoken::{encode, decode, Header, EncodingKey, DecodingKey, Validation}
 use serde::{Deserialize, Serialize};
 use chrono::{Utc, Duration};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// `aud` claim for tokens accepted by api-service
+pub const API_SERVICE_AUDIENCE: &str = "api-service";
+/// `aud` claim for tokens accepted by rustyvault-service
+pub const RUSTYVAULT_AUDIENCE: &str = "rustyvault";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // User ID
     pub email: String,
@@ -30,6 +35,7 @@ pub struct TokenManager {
     decoding_key: DecodingKey,
     issuer: String,
     expiration: u64,
+    refresh_token_days: i64,
 }
 
 impl TokenManager {
@@ -41,9 +47,16 @@ impl TokenManager {
             decoding_key,
             issuer,
             expiration,
+            refresh_token_days: 7,
         }
     }
 
+    /// Lifetime of issued refresh tokens (7 days unless set)
+    pub fn with_refresh_token_days(mut self, days: i64) -> Self {
+        self.refresh_token_days = days;
+        self
+    }
+
     pub fn generate_access_token(&self, user: &User) -> AppResult<String> {
         self.generate_access_token_with_context(user, "", &[], None, None)
     }
@@ -71,23 +84,62 @@ impl TokenManager {
         organization_id: Option<String>,
         realm_id: Option<String>,
     ) -> AppResult<String> {
+        let claims = self.build_access_claims(user, role, permissions, organization_id, realm_id);
+        self.sign_claims(&claims)
+    }
+
+    /// Build access token claims without signing them.
+    ///
+    /// Used by callers that enrich the claims (e.g. with realm-specific custom claims)
+    /// before handing them to [`TokenManager::sign_claims`].
+    pub fn build_access_claims(
+        &self,
+        user: &User,
+        role: &str,
+        permissions: &[String],
+        organization_id: Option<String>,
+        realm_id: Option<String>,
+    ) -> Claims {
+        self.build_access_claims_for_audience(
+            user,
+            role,
+            permissions,
+            organization_id,
+            realm_id,
+            API_SERVICE_AUDIENCE,
+        )
+    }
+
+    /// Build access token claims for a specific service audience
+    pub fn build_access_claims_for_audience(
+        &self,
+        user: &User,
+        role: &str,
+        permissions: &[String],
+        organization_id: Option<String>,
+        realm_id: Option<String>,
+        audience: &str,
+    ) -> Claims {
         let now = Utc::now();
         let exp = now + Duration::seconds(self.expiration as i64);
 
-        let claims = Claims {
+        Claims {
             sub: user.id.to_string(),
             email: user.email.clone(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
             iss: self.issuer.clone(),
-            aud: "api-service".to_string(),
+            aud: audience.to_string(),
             role: if role.is_empty() { None } else { Some(role.to_string()) },
             permissions: if permissions.is_empty() { None } else { Some(permissions.to_vec()) },
             organization_id,
             realm_id,
-        };
+        }
+    }
 
-        encode(&Header::default(), &claims, &self.encoding_key)
+    /// Sign an arbitrary claims payload with this manager's key
+    pub fn sign_claims<T: Serialize>(&self, claims: &T) -> AppResult<String> {
+        encode(&Header::default(), claims, &self.encoding_key)
             .map_err(|e| crate::shared::AppError::Authentication(format!("Token generation failed: {}", e)))
     }
 
@@ -101,9 +153,9 @@ impl TokenManager {
         organization_id: Option<String>,
         realm_id: Option<String>,
     ) -> AppResult<String> {
-        // Refresh tokens have longer expiration (7 days)
+        // Refresh tokens have longer expiration
         let now = Utc::now();
-        let exp = now + Duration::days(7);
+        let exp = now + Duration::days(self.refresh_token_days);
 
         let claims = Claims {
             sub: user.id.to_string(),
@@ -111,7 +163,7 @@ impl TokenManager {
             exp: exp.timestamp(),
             iat: now.timestamp(),
             iss: self.issuer.clone(),
-            aud: "api-service".to_string(),
+            aud: API_SERVICE_AUDIENCE.to_string(),
             role: None,
             permissions: None,
             // Include org/realm in refresh token to maintain context across refresh
@@ -124,9 +176,15 @@ impl TokenManager {
     }
 
     pub fn validate_token(&self, token: &str) -> AppResult<Claims> {
+        self.verify_token_for_audience(token, API_SERVICE_AUDIENCE)
+    }
+
+    /// Validate a token and require its `aud` claim to match `expected_audience`
+    /// Rejects tokens issued for other services.
+    pub fn verify_token_for_audience(&self, token: &str, expected_audience: &str) -> AppResult<Claims> {
         let mut validation = Validation::default();
         validation.set_issuer(&[&self.issuer]);
-        validation.set_audience(&["api-service"]);
+        validation.set_audience(&[expected_audience]);
 
         let token_data = decode::<Claims>(token, &self.decoding_key, &validation)
             .map_err(|e| crate::shared::AppError::Authentication(format!("Token validation failed: {}", e)))?;
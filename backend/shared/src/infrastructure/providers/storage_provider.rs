@@ -35,12 +35,19 @@ pub fn create_storage_provider(config: &StorageProviderConfig) -> AppResult<Box<
         }
         StorageProvider::S3 => {
             if let Some(ref s3_config) = config.s3 {
-                Ok(Box::new(S3Storage::new(
-                    &s3_config.region,
-                    &s3_config.bucket,
-                    &s3_config.access_key_id,
-                    &s3_config.secret_access_key,
-                )))
+                // Self-hosted S3 APIs such as MinIO only serve path-style bucket URLs
+                let endpoint_url = std::env::var("STORAGE_ENDPOINT_URL")
+                    .ok()
+                    .filter(|url| !url.is_empty())
+                    .or_else(|| s3_config.endpoint.clone());
+                Ok(Box::new(S3Storage::new(S3StorageConfig {
+                    force_path_style: endpoint_url.is_some(),
+                    endpoint_url,
+                    region: s3_config.region.clone(),
+                    bucket: s3_config.bucket.clone(),
+                    access_key_id: s3_config.access_key_id.clone(),
+                    secret_access_key: s3_config.secret_access_key.clone(),
+                })))
             } else {
                 Err(crate::shared::AppError::Configuration(
                     "S3 config not provided".to_string(),
@@ -78,6 +85,32 @@ pub fn create_storage_provider(config: &StorageProviderConfig) -> AppResult<Box<
     }
 }
 
+/// Create a storage provider that can presign URLs for direct uploads and
+/// downloads
+///
+/// Object stores presign their own URLs; local storage presigns URLs to the
+/// API's storage endpoint with `url_signer`. Files uploaded that way are not
+/// encrypted, even with `EncryptedLocal` storage.
+pub fn create_presigning_storage_provider(
+    config: &StorageProviderConfig,
+    url_signer: StorageUrlSigner,
+) -> AppResult<Box<dyn Storage>> {
+    match local_storage_path(config) {
+        Some(path) => Ok(Box::new(LocalFsStorage::new(path).with_url_signer(url_signer))),
+        None => create_storage_provider(config),
+    }
+}
+
+/// Directory files are stored in, None for object stores
+pub fn local_storage_path(config: &StorageProviderConfig) -> Option<&str> {
+    match (&config.provider, &config.encrypted_local, &config.local) {
+        (StorageProvider::EncryptedLocal, Some(encrypted_config), _) => Some(encrypted_config.path.as_str()),
+        (StorageProvider::Local | StorageProvider::EncryptedLocal, _, Some(local_config)) => Some(local_config.path.as_str()),
+        (StorageProvider::Local | StorageProvider::EncryptedLocal, _, None) => Some("./storage"),
+        _ => None,
+    }
+}
+
 /// Create encrypted storage provider for a specific realm
 pub fn create_realm_storage(
     config: &EncryptedLocalStorageConfig,
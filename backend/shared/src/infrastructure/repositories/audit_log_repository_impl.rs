@@ -0,0 +1,36 @@
+use crate::domain::repositories::audit_log_repository::{AuditLogEntry, AuditLogRepository};
+use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
+use crate::shared::AppResult;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub struct AuditLogRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl AuditLogRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+#[async_trait]
+impl AuditLogRepository for AuditLogRepositoryImpl {
+    async fn create(&self, entry: AuditLogEntry) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO audit_logs (user_id, action, resource, resource_id, details)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            entry.user_id,
+            entry.action,
+            entry.resource,
+            entry.resource_id,
+            entry.details
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("create", "audit log")?;
+        Ok(())
+    }
+}
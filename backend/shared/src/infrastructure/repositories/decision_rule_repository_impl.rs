@@ -0,0 +1,73 @@
+use crate::application::services::{DecisionRule, RuleContext, RuleResult};
+use crate::domain::repositories::DecisionRuleRepository;
+use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub struct DecisionRuleRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl DecisionRuleRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+#[async_trait]
+impl DecisionRuleRepository for DecisionRuleRepositoryImpl {
+    async fn find_active(&self) -> AppResult<Vec<DecisionRule>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, rule
+            FROM decision_rules
+            WHERE is_active = true
+            ORDER BY id
+            "#
+        )
+        .fetch_all(self.database_service.pool())
+        .await
+        .map_db_error("find", "decision rule")?;
+
+        rows.into_iter()
+            .map(|r| {
+                serde_json::from_value(r.rule)
+                    .map_err(|e| AppError::Internal(format!("Invalid decision rule {}: {}", r.id, e)))
+            })
+            .collect()
+    }
+
+    async fn record_execution(
+        &self,
+        rule: &DecisionRule,
+        context: &RuleContext,
+        result: &RuleResult,
+    ) -> AppResult<()> {
+        let context_snapshot = serde_json::to_value(context)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize rule context: {}", e)))?;
+        let recorded = serde_json::to_value(result)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize rule result: {}", e)))?;
+        let organization_id = rule.organization_id.as_deref().and_then(|id| Uuid::parse_str(id).ok());
+
+        sqlx::query!(
+            r#"
+            INSERT INTO rule_execution_history (
+                rule_id, rule_version, context_snapshot, result, matched, organization_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            rule.id,
+            rule.version,
+            context_snapshot,
+            recorded,
+            result.matched,
+            organization_id
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("record", "rule execution")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+//! EHR Appointment Reminder Repository Implementation
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::domain::repositories::ehr::{EhrAppointmentReminderRepository, UpcomingAppointment};
+use crate::domain::state_machine::{ReminderChannel, ReminderNotification};
+use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
+use crate::shared::AppResult;
+
+/// PostgreSQL implementation over the `appointments` table, with the reminder
+/// channel taken from the patient's communication preferences
+pub struct EhrAppointmentReminderRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl EhrAppointmentReminderRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+fn channel_name(channel: ReminderChannel) -> &'static str {
+    match channel {
+        ReminderChannel::Sms => "sms",
+        ReminderChannel::Email => "email",
+    }
+}
+
+#[async_trait]
+impl EhrAppointmentReminderRepository for EhrAppointmentReminderRepositoryImpl {
+    async fn find_confirmed_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> AppResult<Vec<UpcomingAppointment>> {
+        // Email when the patient prefers it, otherwise SMS; patients who opted
+        // out of both are skipped
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                a.ien::bigint as "appointment_ien!",
+                a.patient_ien::bigint as "patient_ien!",
+                a.scheduled_datetime,
+                (COALESCE(p.appointment_reminder_email, TRUE)
+                    AND (COALESCE(p.preferred_contact_method = 'email', FALSE)
+                         OR NOT COALESCE(p.appointment_reminder_sms, TRUE))) as "by_email!"
+            FROM appointments a
+            JOIN ehr_patients p ON p.id = a.patient_id
+            WHERE a.status = 'confirmed'
+              AND a.deleted_at IS NULL
+              AND a.ien IS NOT NULL
+              AND NOT COALESCE(a.reminder_sent, FALSE)
+              AND a.scheduled_datetime > $1
+              AND a.scheduled_datetime <= $2
+              AND (COALESCE(p.appointment_reminder_sms, TRUE) OR COALESCE(p.appointment_reminder_email, TRUE))
+            ORDER BY a.scheduled_datetime
+            "#,
+            start,
+            end
+        )
+        .fetch_all(self.database_service.pool())
+        .await
+        .map_db_error("find_confirmed_between", "appointments")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| UpcomingAppointment {
+                appointment_ien: row.appointment_ien,
+                patient_ien: row.patient_ien,
+                scheduled_time: row.scheduled_datetime,
+                channel: if row.by_email { ReminderChannel::Email } else { ReminderChannel::Sms },
+            })
+            .collect())
+    }
+
+    async fn mark_reminder_sent(&self, notification: &ReminderNotification) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE appointments
+            SET reminder_sent = TRUE,
+                reminder_sent_datetime = NOW(),
+                reminder_method = $3
+            WHERE ien = $1::bigint AND patient_ien = $2::bigint AND deleted_at IS NULL
+            "#,
+            notification.appointment_ien,
+            notification.patient_ien,
+            channel_name(notification.channel)
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("mark_reminder_sent", "appointments")?;
+        Ok(())
+    }
+}
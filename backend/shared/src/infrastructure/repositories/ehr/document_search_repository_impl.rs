@@ -0,0 +1,168 @@
+//! EHR Document Full-Text Search Repository Implementation
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use std::sync::Arc;
+
+use crate::domain::repositories::ehr::document_repository::{
+    DocumentSearchResult, EhrDocumentSearchRepository, IndexedDocument,
+};
+use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
+use crate::shared::AppResult;
+
+/// Most results returned by one search
+const SEARCH_LIMIT: i64 = 50;
+
+/// Visible characters in a result snippet
+const SNIPPET_LENGTH: usize = 150;
+
+/// Visible characters kept before the first match
+const SNIPPET_LEAD: usize = 40;
+
+const MARK_START: &str = "<mark>";
+const MARK_END: &str = "</mark>";
+
+#[derive(Debug, FromRow)]
+struct DocumentSearchRow {
+    ien: i64,
+    title: String,
+    patient_ien: i64,
+    created_at: DateTime<Utc>,
+    headline: String,
+}
+
+/// PostgreSQL implementation of the document full-text index
+pub struct EhrDocumentSearchRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl EhrDocumentSearchRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+#[async_trait]
+impl EhrDocumentSearchRepository for EhrDocumentSearchRepositoryImpl {
+    async fn search(
+        &self,
+        query: &str,
+        patient_ien: Option<i64>,
+        doc_type: Option<&str>,
+    ) -> AppResult<Vec<DocumentSearchResult>> {
+        // ts_headline returns whole words around the best match; the excerpt
+        // is then cut to SNIPPET_LENGTH around the first highlighted term
+        let rows = sqlx::query_as!(
+            DocumentSearchRow,
+            r#"
+            SELECT
+                d.ien, d.title, d.patient_ien, d.created_at,
+                ts_headline(
+                    'english', d.content, q,
+                    'StartSel=<mark>, StopSel=</mark>, MaxWords=35, MinWords=15, MaxFragments=1'
+                ) as "headline!"
+            FROM ehr_document_index d, websearch_to_tsquery('english', $1) q
+            WHERE d.content_tsv @@ q
+              AND d.status <> 'retracted'
+              AND ($2::bigint IS NULL OR d.patient_ien = $2)
+              AND ($3::text IS NULL OR d.document_type = $3)
+            ORDER BY ts_rank(d.content_tsv, q) DESC, d.created_at DESC
+            LIMIT $4
+            "#,
+            query,
+            patient_ien,
+            doc_type,
+            SEARCH_LIMIT
+        )
+        .fetch_all(self.database_service.pool())
+        .await
+        .map_db_error("search", "ehr_document_index")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| DocumentSearchResult {
+                ien: row.ien,
+                title: row.title,
+                patient_ien: row.patient_ien,
+                created_at: row.created_at,
+                snippet: excerpt(&row.headline, SNIPPET_LENGTH),
+            })
+            .collect())
+    }
+
+    async fn upsert(&self, document: &IndexedDocument) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO ehr_document_index (
+                ien, patient_ien, document_type, title, content, status, created_at, synced_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (ien) DO UPDATE SET
+                patient_ien = EXCLUDED.patient_ien,
+                document_type = EXCLUDED.document_type,
+                title = EXCLUDED.title,
+                content = EXCLUDED.content,
+                status = EXCLUDED.status,
+                created_at = EXCLUDED.created_at,
+                synced_at = NOW()
+            "#,
+            document.ien,
+            document.patient_ien,
+            document.document_type,
+            document.title,
+            document.content,
+            document.status,
+            document.created_at
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("upsert", "ehr_document_index")?;
+        Ok(())
+    }
+}
+
+/// Cut a `ts_headline` result to about `length` visible characters, starting
+/// shortly before the first `<mark>` and keeping the highlight tags balanced
+fn excerpt(headline: &str, length: usize) -> String {
+    // Plain text, with whether each character was highlighted
+    let mut text: Vec<(char, bool)> = Vec::new();
+    let mut rest = headline;
+    while let Some(start) = rest.find(MARK_START) {
+        text.extend(rest[..start].chars().map(|c| (c, false)));
+        rest = &rest[start + MARK_START.len()..];
+        let end = rest.find(MARK_END).unwrap_or(rest.len());
+        text.extend(rest[..end].chars().map(|c| (c, true)));
+        rest = rest.get(end + MARK_END.len()..).unwrap_or("");
+    }
+    text.extend(rest.chars().map(|c| (c, false)));
+
+    let first_match = text.iter().position(|(_, marked)| *marked).unwrap_or(0);
+    let mut start = first_match.saturating_sub(SNIPPET_LEAD).min(text.len().saturating_sub(length));
+    // Begin on a word boundary
+    if start > 0 {
+        if let Some(space) = text[start..first_match.max(start)].iter().position(|(c, _)| c.is_whitespace()) {
+            start += space + 1;
+        }
+    }
+    let end = (start + length).min(text.len());
+
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    for i in start..end {
+        let (c, marked) = text[i];
+        if marked && (i == start || !text[i - 1].1) {
+            snippet.push_str(MARK_START);
+        }
+        snippet.push(c);
+        if marked && (i + 1 == end || !text[i + 1].1) {
+            snippet.push_str(MARK_END);
+        }
+    }
+    if end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
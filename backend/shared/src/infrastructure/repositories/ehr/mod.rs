@@ -2,6 +2,10 @@
 //!
 //! PostgreSQL implementations of EHR repository traits.
 
+pub mod appointment_reminder_repository_impl;
+pub mod document_search_repository_impl;
 pub mod patient_repository_impl;
 
+pub use appointment_reminder_repository_impl::EhrAppointmentReminderRepositoryImpl;
+pub use document_search_repository_impl::EhrDocumentSearchRepositoryImpl;
 pub use patient_repository_impl::EhrPatientRepositoryImpl;
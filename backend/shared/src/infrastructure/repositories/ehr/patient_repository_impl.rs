@@ -2,7 +2,7 @@
 
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDate, Utc};
-use sqlx::FromRow;
+use sqlx::{Acquire, FromRow};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -11,7 +11,8 @@ use crate::domain::repositories::ehr::patient_repository::{
     EhrPatientRepository, PaginatedResult, Pagination, PatientSearchCriteria,
 };
 use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
-use crate::shared::AppResult;
+use crate::infrastructure::encryption::FieldEncryption;
+use crate::shared::{AppError, AppResult, HasAuditFields, RequestContext};
 
 /// Database row for EHR patient
 /// Maps actual DB column names (via SQL aliases) to entity field names
@@ -120,14 +121,41 @@ impl From<EhrPatientRow> for EhrPatient {
     }
 }
 
+/// Context binding `ehr_patients.ssn_last4_ciphertext` to its column
+const SSN_LAST4_CONTEXT: &[u8] = b"ehr_patients.ssn_last4";
+
+/// `patient_access_log.access_type` of `find_by_ssn`
+const SSN_SEARCH_ACCESS: &str = "ssn_search";
+
 /// PostgreSQL implementation of EHR Patient Repository
 pub struct EhrPatientRepositoryImpl {
     database_service: Arc<DatabaseService>,
+    field_encryption: Option<Arc<FieldEncryption>>,
 }
 
 impl EhrPatientRepositoryImpl {
     pub fn new(database_service: Arc<DatabaseService>) -> Self {
-        Self { database_service }
+        Self {
+            database_service,
+            field_encryption: None,
+        }
+    }
+
+    /// Store a deterministic ciphertext of the SSN last four and allow `find_by_ssn`
+    pub fn with_field_encryption(mut self, field_encryption: Arc<FieldEncryption>) -> Self {
+        self.field_encryption = Some(field_encryption);
+        self
+    }
+
+    /// `ehr_patients.ssn_last4_ciphertext` for `ssn_last4`; `None` without field
+    /// encryption, as the digits are never stored in plaintext
+    fn ssn_last4_ciphertext(&self, ssn_last4: Option<&str>) -> AppResult<Option<Vec<u8>>> {
+        match (&self.field_encryption, ssn_last4) {
+            (Some(encryption), Some(ssn_last4)) => {
+                encryption.encrypt_deterministic(ssn_last4.as_bytes(), SSN_LAST4_CONTEXT).map(Some)
+            }
+            _ => Ok(None),
+        }
     }
 
     /// Convert Gender enum to DB value (sex column uses M/F/O/U)
@@ -147,11 +175,16 @@ impl EhrPatientRepositoryImpl {
             PatientStatus::Deceased => "deceased",
         }
     }
-}
 
-#[async_trait]
-impl EhrPatientRepository for EhrPatientRepositoryImpl {
-    async fn create(&self, patient: EhrPatient) -> AppResult<EhrPatient> {
+    /// Insert a patient using any executor (pool or open transaction)
+    async fn insert_patient<'e, E>(
+        executor: E,
+        patient: &EhrPatient,
+        ssn_last4_ciphertext: Option<&[u8]>,
+    ) -> AppResult<EhrPatient>
+    where
+        E: sqlx::PgExecutor<'e>,
+    {
         let sex = Self::gender_to_db(&patient.gender);
         let status = Self::status_to_string(&patient.status);
 
@@ -169,12 +202,12 @@ impl EhrPatientRepository for EhrPatientRepositoryImpl {
                 emergency_contact_name, emergency_contact_phone, emergency_contact_relationship,
                 insurance_primary_carrier, insurance_primary_policy_number, insurance_primary_group_number,
                 status, deceased_date, primary_care_provider_id, primary_facility_id,
-                mumps_data, created_by, updated_by
+                mumps_data, created_by, updated_by, ssn_last4_ciphertext
             )
             VALUES (
                 $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14,
                 $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27,
-                $28, $29, $30, $31, $32, $33
+                $28, $29, $30, $31, $32, $33, $34
             )
             RETURNING
                 id, ien::bigint as "ien!", organization_id,
@@ -234,14 +267,57 @@ impl EhrPatientRepository for EhrPatientRepositoryImpl {
             patient.primary_location_id,
             patient.mumps_data.as_ref(),
             patient.created_by,
-            patient.updated_by
+            patient.updated_by,
+            ssn_last4_ciphertext
         )
-        .fetch_one(self.database_service.pool())
+        .fetch_one(executor)
         .await
         .map_db_error("insert", "ehr_patient")?;
 
         Ok(row.into())
     }
+}
+
+#[async_trait]
+impl EhrPatientRepository for EhrPatientRepositoryImpl {
+    async fn create(&self, mut patient: EhrPatient) -> AppResult<EhrPatient> {
+        if let Some(ctx) = RequestContext::current() {
+            patient.apply_create_audit(&ctx);
+        }
+        let ssn_last4_ciphertext = self.ssn_last4_ciphertext(patient.ssn_last_four.as_deref())?;
+        Self::insert_patient(self.database_service.pool(), &patient, ssn_last4_ciphertext.as_deref()).await
+    }
+
+    async fn create_batch(&self, mut patients: Vec<EhrPatient>) -> AppResult<Vec<AppResult<EhrPatient>>> {
+        if let Some(ctx) = RequestContext::current() {
+            for patient in &mut patients {
+                patient.apply_create_audit(&ctx);
+            }
+        }
+        let mut tx = self.database_service.pool()
+            .begin()
+            .await
+            .map_db_error("begin", "ehr_patient")?;
+
+        // Each patient gets a savepoint so a failed insert does not abort the transaction
+        let mut created = Vec::with_capacity(patients.len());
+        for patient in &patients {
+            let mut savepoint = tx.begin().await.map_db_error("begin", "ehr_patient")?;
+            let inserted = match self.ssn_last4_ciphertext(patient.ssn_last_four.as_deref()) {
+                Ok(ciphertext) => Self::insert_patient(&mut *savepoint, patient, ciphertext.as_deref()).await,
+                Err(e) => Err(e),
+            };
+            if inserted.is_ok() {
+                savepoint.commit().await.map_db_error("release", "ehr_patient")?;
+            } else {
+                savepoint.rollback().await.map_db_error("rollback", "ehr_patient")?;
+            }
+            created.push(inserted);
+        }
+
+        tx.commit().await.map_db_error("commit", "ehr_patient")?;
+        Ok(created)
+    }
 
     async fn find_by_id(&self, id: Uuid, organization_id: Uuid) -> AppResult<Option<EhrPatient>> {
         let row = sqlx::query_as!(
@@ -369,9 +445,81 @@ impl EhrPatientRepository for EhrPatientRepositoryImpl {
         Ok(row.map(Into::into))
     }
 
-    async fn update(&self, patient: EhrPatient) -> AppResult<EhrPatient> {
+    async fn find_by_ssn(&self, ssn_last4: &str, organization_id: Uuid) -> AppResult<Vec<EhrPatient>> {
+        if ssn_last4.len() != 4 || !ssn_last4.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(AppError::Validation("SSN search takes the last four digits only".to_string()));
+        }
+        let ssn_last4_ciphertext = self
+            .ssn_last4_ciphertext(Some(ssn_last4))?
+            .ok_or_else(|| AppError::Encryption("SSN search requires field encryption".to_string()))?;
+
+        let rows = sqlx::query_as!(
+            EhrPatientRow,
+            r#"
+            SELECT
+                id, ien::bigint as "ien!", organization_id,
+                COALESCE(last_name, '') as "last_name!",
+                COALESCE(first_name, '') as "first_name!",
+                middle_name, suffix,
+                maiden_name as preferred_name,
+                date_of_birth as "date_of_birth!",
+                COALESCE(sex, 'U') as "gender!",
+                NULL::text as ssn_last_four,
+                mrn,
+                email, phone_home, phone_mobile, phone_work,
+                address_line1, address_line2, city, state, zip_code, country,
+                emergency_contact_name, emergency_contact_phone, emergency_contact_relationship,
+                insurance_primary_carrier as insurance_carrier,
+                insurance_primary_policy_number as insurance_policy_number,
+                insurance_primary_group_number as insurance_group_number,
+                COALESCE(status, 'active') as "status!",
+                deceased_date,
+                primary_care_provider_id as primary_provider_id,
+                primary_facility_id as primary_location_id,
+                mumps_data,
+                NULL::text as request_id,
+                created_at, updated_at, created_by, updated_by,
+                NULL::text as system_id,
+                version
+            FROM ehr_patients
+            WHERE ssn_last4_ciphertext = $1 AND organization_id = $2 AND deleted_at IS NULL
+            ORDER BY last_name, first_name
+            "#,
+            ssn_last4_ciphertext,
+            organization_id
+        )
+        .fetch_all(self.database_service.pool())
+        .await
+        .map_db_error("search", "ehr_patient")?;
+
+        // One entry per patient found, or one without a patient for an empty result
+        let patient_iens: Vec<i64> = rows.iter().map(|row| row.ien).collect();
+        let user_id = RequestContext::current().map(|ctx| ctx.user_id);
+        sqlx::query!(
+            r#"
+            INSERT INTO patient_access_log (user_id, patient_ien, access_type)
+            SELECT $1::uuid, found.ien, $3::text
+            FROM (SELECT 1) AS search
+            LEFT JOIN UNNEST($2::bigint[]) AS found(ien) ON true
+            "#,
+            user_id,
+            &patient_iens,
+            SSN_SEARCH_ACCESS
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("insert", "patient_access_log")?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn update(&self, mut patient: EhrPatient) -> AppResult<EhrPatient> {
+        if let Some(ctx) = RequestContext::current() {
+            patient.apply_update_audit(&ctx);
+        }
         let sex = Self::gender_to_db(&patient.gender);
         let status = Self::status_to_string(&patient.status);
+        let ssn_last4_ciphertext = self.ssn_last4_ciphertext(patient.ssn_last_four.as_deref())?;
 
         let row = sqlx::query_as!(
             EhrPatientRow,
@@ -389,6 +537,7 @@ impl EhrPatientRepository for EhrPatientRepositoryImpl {
                 status = $26, deceased_date = $27,
                 primary_care_provider_id = $28, primary_facility_id = $29,
                 mumps_data = $30, updated_by = $31, updated_at = NOW(),
+                ssn_last4_ciphertext = COALESCE($32, ssn_last4_ciphertext),
                 version = version + 1
             WHERE id = $1 AND organization_id = $2 AND deleted_at IS NULL
             RETURNING
@@ -447,7 +596,8 @@ impl EhrPatientRepository for EhrPatientRepositoryImpl {
             patient.primary_provider_id,
             patient.primary_location_id,
             patient.mumps_data.as_ref(),
-            patient.updated_by
+            patient.updated_by,
+            ssn_last4_ciphertext
         )
         .fetch_one(self.database_service.pool())
         .await
@@ -599,3 +749,41 @@ impl EhrPatientRepository for EhrPatientRepositoryImpl {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::encryption::{DekManager, MasterKey, Vault};
+
+    /// Vault without storage; each `FieldEncryption` gets a fresh key
+    struct NullVault;
+
+    #[async_trait]
+    impl Vault for NullVault {
+        async fn store_dek(&self, _entity_id: &str, _entity_type: &str, _encrypted_dek: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn get_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+        async fn delete_dek(&self, _entity_id: &str, _entity_type: &str) -> AppResult<()> { Ok(()) }
+        async fn rotate_master_key(&self, _new_master_key: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn store_master_key(&self, _master_key: &[u8]) -> AppResult<()> { Ok(()) }
+        async fn get_master_key(&self) -> AppResult<Option<Vec<u8>>> { Ok(None) }
+    }
+
+    fn database_service() -> Arc<DatabaseService> {
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        Arc::new(DatabaseService::new(pool))
+    }
+
+    #[tokio::test]
+    async fn test_ssn_last4_is_encrypted_only_with_field_encryption() {
+        let plain = EhrPatientRepositoryImpl::new(database_service());
+        assert_eq!(plain.ssn_last4_ciphertext(Some("6789")).unwrap(), None);
+
+        let dek_manager = Arc::new(DekManager::new(MasterKey::generate().unwrap(), Box::new(NullVault)));
+        let encryption = Arc::new(FieldEncryption::new(dek_manager).await.unwrap());
+        let encrypted = EhrPatientRepositoryImpl::new(database_service()).with_field_encryption(encryption.clone());
+
+        let ciphertext = encrypted.ssn_last4_ciphertext(Some("6789")).unwrap().unwrap();
+        assert_eq!(ciphertext, encryption.encrypt_deterministic(b"6789", SSN_LAST4_CONTEXT).unwrap());
+        assert_eq!(encrypted.ssn_last4_ciphertext(None).unwrap(), None);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::domain::entities::Group;
 use crate::domain::repositories::GroupRepository;
 use crate::infrastructure::database::RepositoryErrorExt;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, HasAuditFields, RequestContext};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -18,7 +18,10 @@ impl GroupRepositoryImpl {
 
 #[async_trait]
 impl GroupRepository for GroupRepositoryImpl {
-    async fn create(&self, group: Group) -> AppResult<Group> {
+    async fn create(&self, mut group: Group) -> AppResult<Group> {
+        if let Some(ctx) = RequestContext::current() {
+            group.apply_create_audit(&ctx);
+        }
         // ✨ DRY: Using map_db_error trait extension
         sqlx::query_as!(
             Group,
@@ -51,7 +54,10 @@ impl GroupRepository for GroupRepositoryImpl {
         .map_db_error("create", "group")
     }
 
-    async fn update(&self, group: Group) -> AppResult<Group> {
+    async fn update(&self, mut group: Group) -> AppResult<Group> {
+        if let Some(ctx) = RequestContext::current() {
+            group.apply_update_audit(&ctx);
+        }
         // ✨ DRY: Using map_db_error trait extension
         sqlx::query_as!(
             Group,
@@ -0,0 +1,109 @@
+use crate::domain::repositories::{IdempotencyKeyRepository, IdempotencyRecord};
+use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub struct IdempotencyKeyRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl IdempotencyKeyRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+#[async_trait]
+impl IdempotencyKeyRepository for IdempotencyKeyRepositoryImpl {
+    async fn find(&self, key: Uuid) -> AppResult<Option<IdempotencyRecord>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT key, user_id, created_at, status, body
+            FROM idempotency_keys
+            WHERE key = $1
+            "#,
+            key
+        )
+        .fetch_optional(self.database_service.pool())
+        .await
+        .map_db_error("find", "idempotency key")?;
+
+        row.map(|row| {
+            let status = row
+                .status
+                .map(|status| {
+                    u16::try_from(status).map_err(|_| AppError::Internal(format!("Invalid stored status {}", status)))
+                })
+                .transpose()?;
+            Ok(IdempotencyRecord {
+                key: row.key,
+                user_id: row.user_id,
+                created_at: row.created_at,
+                status,
+                body: row.body.unwrap_or_default(),
+            })
+        })
+        .transpose()
+    }
+
+    async fn reserve(
+        &self,
+        key: Uuid,
+        user_id: Uuid,
+        stale_before: DateTime<Utc>,
+        expired_before: DateTime<Utc>,
+    ) -> AppResult<bool> {
+        let reserved = sqlx::query_scalar!(
+            r#"
+            INSERT INTO idempotency_keys (key, user_id)
+            VALUES ($1, $2)
+            ON CONFLICT (key) DO UPDATE
+            SET user_id = EXCLUDED.user_id, created_at = NOW(), status = NULL, body = NULL
+            WHERE (idempotency_keys.status IS NULL AND idempotency_keys.created_at < $3)
+               OR idempotency_keys.created_at < $4
+            RETURNING key
+            "#,
+            key,
+            user_id,
+            stale_before,
+            expired_before
+        )
+        .fetch_optional(self.database_service.pool())
+        .await
+        .map_db_error("reserve", "idempotency key")?;
+        Ok(reserved.is_some())
+    }
+
+    async fn complete(&self, record: &IdempotencyRecord) -> AppResult<()> {
+        let status = record
+            .status
+            .ok_or_else(|| AppError::Internal("Idempotent response has no status".to_string()))?;
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys
+            SET created_at = $3, status = $4, body = $5
+            WHERE key = $1 AND user_id = $2 AND status IS NULL
+            "#,
+            record.key,
+            record.user_id,
+            record.created_at,
+            i32::from(status),
+            record.body
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("update", "idempotency key")?;
+        Ok(())
+    }
+
+    async fn release(&self, key: Uuid) -> AppResult<()> {
+        sqlx::query!("DELETE FROM idempotency_keys WHERE key = $1 AND status IS NULL", key)
+            .execute(self.database_service.pool())
+            .await
+            .map_db_error("delete", "idempotency key")?;
+        Ok(())
+    }
+}
@@ -1,58 +1,383 @@
 use crate::domain::entities::EncryptionKey;
 use crate::domain::repositories::KeyRepository;
-use crate::shared::AppResult;
+use crate::infrastructure::database::RepositoryErrorExt;
+use crate::infrastructure::encryption::{MasterKey, WrappedDek};
+use crate::shared::{AppResult, HasAuditFields, RequestContext};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool};
+use std::sync::Arc;
 use uuid::Uuid;
 
+/// Row as stored in `encryption_keys` (key material is wrapped)
+#[derive(Debug)]
+struct EncryptionKeyRow {
+    id: Uuid,
+    entity_id: Uuid,
+    entity_type: String,
+    key_material_wrapped: Vec<u8>,
+    key_version: i32,
+    key_algorithm: String,
+    created_at: DateTime<Utc>,
+    rotated_at: Option<DateTime<Utc>>,
+    is_active: bool,
+    request_id: Option<String>,
+    updated_at: DateTime<Utc>,
+    created_by: Option<Uuid>,
+    updated_by: Option<Uuid>,
+    system_id: Option<String>,
+    version: i64,
+}
+
+/// Stores DEKs in PostgreSQL, wrapped with the master key (RFC 3394)
+///
+/// Plaintext key material only exists in memory: it is wrapped on every
+/// write and unwrapped on every read.
 pub struct KeyRepositoryImpl {
     pool: PgPool,
+    master_key: Arc<MasterKey>,
 }
 
 impl KeyRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, master_key: Arc<MasterKey>) -> Self {
+        Self { pool, master_key }
+    }
+
+    fn unwrap_row(&self, row: EncryptionKeyRow) -> AppResult<EncryptionKey> {
+        let key_material = self.master_key.unwrap_dek(&WrappedDek {
+            bytes: row.key_material_wrapped,
+            key_version: row.key_version,
+        })?;
+
+        Ok(EncryptionKey {
+            id: row.id,
+            entity_id: row.entity_id,
+            entity_type: row.entity_type,
+            key_material,
+            key_version: row.key_version,
+            key_algorithm: row.key_algorithm,
+            created_at: row.created_at,
+            rotated_at: row.rotated_at,
+            is_active: row.is_active,
+            request_id: row.request_id,
+            updated_at: row.updated_at,
+            created_by: row.created_by,
+            updated_by: row.updated_by,
+            system_id: row.system_id,
+            version: row.version,
+        })
+    }
+
+    async fn insert(executor: impl PgExecutor<'_>, key: &EncryptionKey, wrapped: &WrappedDek) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO encryption_keys (
+                id, entity_id, entity_type, key_material_wrapped, key_version, key_algorithm,
+                created_at, rotated_at, is_active, request_id, updated_at,
+                created_by, updated_by, system_id, version
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#,
+            key.id,
+            key.entity_id,
+            key.entity_type,
+            wrapped.bytes,
+            wrapped.key_version,
+            key.key_algorithm,
+            key.created_at,
+            key.rotated_at,
+            key.is_active,
+            key.request_id,
+            key.updated_at,
+            key.created_by,
+            key.updated_by,
+            key.system_id,
+            key.version
+        )
+        .execute(executor)
+        .await
+        .map_db_error("create", "encryption_key")?;
+
+        Ok(())
+    }
+
+    /// Master key version wrapping the stored DEKs, `None` while no DEK is stored
+    ///
+    /// `rewrap_all` moves every DEK to the new version at once, so this is the
+    /// version of the master key in use. Versions are not kept with the key
+    /// itself; call this at startup to restore it.
+    pub async fn latest_key_version(pool: &PgPool) -> AppResult<Option<i32>> {
+        sqlx::query_scalar!("SELECT MAX(key_version) FROM encryption_keys")
+            .fetch_one(pool)
+            .await
+            .map_db_error("find", "encryption_key")
+    }
+
+    /// Store a value encrypted with the DEK `key_id`, returning its id
+    pub async fn create_encrypted_value(&self, key_id: Uuid, encrypted_value: &[u8]) -> AppResult<Uuid> {
+        let id = Uuid::new_v4();
+        sqlx::query!(
+            "INSERT INTO encrypted_values (id, key_id, encrypted_value) VALUES ($1, $2, $3)",
+            id,
+            key_id,
+            encrypted_value
+        )
+        .execute(&self.pool)
+        .await
+        .map_db_error("create", "encrypted_value")?;
+
+        Ok(id)
+    }
+
+    /// Id of the DEK a stored value is encrypted with, and the value
+    pub async fn find_encrypted_value(&self, id: Uuid) -> AppResult<Option<(Uuid, Vec<u8>)>> {
+        let row = sqlx::query!(
+            "SELECT key_id, encrypted_value FROM encrypted_values WHERE id = $1",
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_db_error("find", "encrypted_value")?;
+
+        Ok(row.map(|r| (r.key_id, r.encrypted_value)))
+    }
+
+    /// Replace `old_key` with `new_key` in one transaction
+    ///
+    /// `old_key` is deactivated, `new_key` becomes the entity's active DEK and
+    /// every value encrypted with `old_key` is passed through `reencrypt` and
+    /// moved to `new_key`. Returns how many values were re-encrypted.
+    pub async fn rotate<F>(&self, old_key: &EncryptionKey, mut new_key: EncryptionKey, reencrypt: F) -> AppResult<u64>
+    where
+        F: Fn(&[u8]) -> AppResult<Vec<u8>> + Send + Sync,
+    {
+        if let Some(ctx) = RequestContext::current() {
+            new_key.apply_create_audit(&ctx);
+        }
+        let wrapped = self.master_key.wrap_dek(&new_key.key_material)?;
+
+        let mut tx = self.pool.begin().await.map_db_error("begin", "encryption_key")?;
+
+        // Deactivate first: an entity has at most one active DEK
+        sqlx::query!(
+            r#"
+            UPDATE encryption_keys
+            SET is_active = false,
+                rotated_at = NOW(),
+                updated_at = NOW(),
+                version = version + 1
+            WHERE id = $1
+            "#,
+            old_key.id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_db_error("deactivate", "encryption_key")?;
+
+        Self::insert(&mut *tx, &new_key, &wrapped).await?;
+
+        let values = sqlx::query!(
+            "SELECT id, encrypted_value FROM encrypted_values WHERE key_id = $1 FOR UPDATE",
+            old_key.id
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_db_error("find", "encrypted_value")?;
+
+        for value in &values {
+            let encrypted_value = reencrypt(&value.encrypted_value)?;
+            sqlx::query!(
+                r#"
+                UPDATE encrypted_values
+                SET key_id = $2,
+                    encrypted_value = $3,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#,
+                value.id,
+                new_key.id,
+                encrypted_value
+            )
+            .execute(&mut *tx)
+            .await
+            .map_db_error("update", "encrypted_value")?;
+        }
+
+        tx.commit().await.map_db_error("commit", "encryption_key")?;
+        Ok(values.len() as u64)
+    }
+
+    /// Rewrap every stored DEK with `new_master_key` in one transaction
+    ///
+    /// Values encrypted with the DEKs are untouched. Returns how many DEKs
+    /// were rewrapped.
+    pub async fn rewrap_all(&self, new_master_key: &MasterKey) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await.map_db_error("begin", "encryption_key")?;
+
+        let rows = sqlx::query!(
+            "SELECT id, key_material_wrapped, key_version FROM encryption_keys FOR UPDATE"
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_db_error("find", "encryption_key")?;
+
+        for row in &rows {
+            let dek = self.master_key.unwrap_dek(&WrappedDek {
+                bytes: row.key_material_wrapped.clone(),
+                key_version: row.key_version,
+            })?;
+            let wrapped = new_master_key.wrap_dek(&dek)?;
+            sqlx::query!(
+                r#"
+                UPDATE encryption_keys
+                SET key_material_wrapped = $2,
+                    key_version = $3,
+                    updated_at = NOW(),
+                    version = version + 1
+                WHERE id = $1
+                "#,
+                row.id,
+                wrapped.bytes,
+                wrapped.key_version
+            )
+            .execute(&mut *tx)
+            .await
+            .map_db_error("update", "encryption_key")?;
+        }
+
+        tx.commit().await.map_db_error("commit", "encryption_key")?;
+        Ok(rows.len() as u64)
     }
 }
 
 #[async_trait]
 impl KeyRepository for KeyRepositoryImpl {
-    async fn create(&self, _key: EncryptionKey) -> AppResult<EncryptionKey> {
-        // NOTE: encryption_keys table was removed in migration 17
-        // DEKs are now stored in Vault (OpenBao), not in database
-        // DekManager uses Vault instead of this repository
-        Err(crate::shared::AppError::Internal(
-            "KeyRepository is deprecated - use DekManager with Vault instead".to_string()
-        ))
+    async fn create(&self, mut key: EncryptionKey) -> AppResult<EncryptionKey> {
+        if let Some(ctx) = RequestContext::current() {
+            key.apply_create_audit(&ctx);
+        }
+        let wrapped = self.master_key.wrap_dek(&key.key_material)?;
+        key.key_version = wrapped.key_version;
+
+        Self::insert(&self.pool, &key, &wrapped).await?;
+
+        Ok(key)
     }
 
-    async fn find_by_id(&self, _id: Uuid) -> AppResult<Option<EncryptionKey>> {
-        Err(crate::shared::AppError::Internal(
-            "KeyRepository is deprecated - use DekManager with Vault instead".to_string()
-        ))
+    async fn find_by_id(&self, id: Uuid) -> AppResult<Option<EncryptionKey>> {
+        let row = sqlx::query_as!(
+            EncryptionKeyRow,
+            r#"
+            SELECT id, entity_id, entity_type, key_material_wrapped, key_version, key_algorithm,
+                   created_at, rotated_at, is_active, request_id, updated_at,
+                   created_by, updated_by, system_id, version
+            FROM encryption_keys
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_db_error("find", "encryption_key")?;
+
+        row.map(|r| self.unwrap_row(r)).transpose()
     }
 
-    async fn find_by_entity(&self, _entity_id: Uuid, _entity_type: &str) -> AppResult<Option<EncryptionKey>> {
-        Err(crate::shared::AppError::Internal(
-            "KeyRepository is deprecated - use DekManager with Vault instead".to_string()
-        ))
+    async fn find_by_entity(&self, entity_id: Uuid, entity_type: &str) -> AppResult<Option<EncryptionKey>> {
+        let row = sqlx::query_as!(
+            EncryptionKeyRow,
+            r#"
+            SELECT id, entity_id, entity_type, key_material_wrapped, key_version, key_algorithm,
+                   created_at, rotated_at, is_active, request_id, updated_at,
+                   created_by, updated_by, system_id, version
+            FROM encryption_keys
+            WHERE entity_id = $1 AND entity_type = $2
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            entity_id,
+            entity_type
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_db_error("find", "encryption_key")?;
+
+        row.map(|r| self.unwrap_row(r)).transpose()
     }
 
-    async fn find_active_by_entity(&self, _entity_id: Uuid, _entity_type: &str) -> AppResult<Option<EncryptionKey>> {
-        Err(crate::shared::AppError::Internal(
-            "KeyRepository is deprecated - use DekManager with Vault instead".to_string()
-        ))
+    async fn find_active_by_entity(&self, entity_id: Uuid, entity_type: &str) -> AppResult<Option<EncryptionKey>> {
+        let row = sqlx::query_as!(
+            EncryptionKeyRow,
+            r#"
+            SELECT id, entity_id, entity_type, key_material_wrapped, key_version, key_algorithm,
+                   created_at, rotated_at, is_active, request_id, updated_at,
+                   created_by, updated_by, system_id, version
+            FROM encryption_keys
+            WHERE entity_id = $1 AND entity_type = $2 AND is_active = true
+            "#,
+            entity_id,
+            entity_type
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_db_error("find", "encryption_key")?;
+
+        row.map(|r| self.unwrap_row(r)).transpose()
     }
 
-    async fn update(&self, _key: EncryptionKey) -> AppResult<EncryptionKey> {
-        Err(crate::shared::AppError::Internal(
-            "KeyRepository is deprecated - use DekManager with Vault instead".to_string()
-        ))
+    async fn update(&self, mut key: EncryptionKey) -> AppResult<EncryptionKey> {
+        if let Some(ctx) = RequestContext::current() {
+            key.apply_update_audit(&ctx);
+        }
+        let wrapped = self.master_key.wrap_dek(&key.key_material)?;
+        key.key_version = wrapped.key_version;
+
+        sqlx::query!(
+            r#"
+            UPDATE encryption_keys
+            SET key_material_wrapped = $2,
+                key_version = $3,
+                rotated_at = $4,
+                is_active = $5,
+                request_id = $6,
+                updated_at = $7,
+                updated_by = $8,
+                version = $9
+            WHERE id = $1
+            "#,
+            key.id,
+            wrapped.bytes,
+            wrapped.key_version,
+            key.rotated_at,
+            key.is_active,
+            key.request_id,
+            key.updated_at,
+            key.updated_by,
+            key.version
+        )
+        .execute(&self.pool)
+        .await
+        .map_db_error("update", "encryption_key")?;
+
+        Ok(key)
     }
 
-    async fn deactivate_all_for_entity(&self, _entity_id: Uuid, _entity_type: &str) -> AppResult<()> {
-        Err(crate::shared::AppError::Internal(
-            "KeyRepository is deprecated - use DekManager with Vault instead".to_string()
-        ))
+    async fn deactivate_all_for_entity(&self, entity_id: Uuid, entity_type: &str) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE encryption_keys
+            SET is_active = false,
+                updated_at = NOW(),
+                version = version + 1
+            WHERE entity_id = $1 AND entity_type = $2 AND is_active = true
+            "#,
+            entity_id,
+            entity_type
+        )
+        .execute(&self.pool)
+        .await
+        .map_db_error("deactivate", "encryption_key")?;
+
+        Ok(())
     }
 }
@@ -1,4 +1,7 @@
 pub mod user_repository_impl;
+pub mod audit_log_repository_impl;
+pub mod decision_rule_repository_impl;
+pub mod idempotency_key_repository_impl;
 pub mod key_repository_impl;
 pub mod relationship_repository_impl;
 pub mod role_repository_impl;
@@ -9,10 +12,15 @@ pub mod group_repository_impl;
 pub mod ui_entity_repository_impl;
 pub mod session_repository_impl;
 pub mod request_log_repository_impl;
+pub mod user_activity_event_repository_impl;
+pub mod user_provisioning_checklist_repository_impl;
 pub mod visual_workflow_repository_impl;
 pub mod ehr;
 
 pub use user_repository_impl::UserRepositoryImpl;
+pub use audit_log_repository_impl::AuditLogRepositoryImpl;
+pub use decision_rule_repository_impl::DecisionRuleRepositoryImpl;
+pub use idempotency_key_repository_impl::IdempotencyKeyRepositoryImpl;
 pub use key_repository_impl::KeyRepositoryImpl;
 pub use relationship_repository_impl::RelationshipRepositoryImpl;
 pub use role_repository_impl::RoleRepositoryImpl;
@@ -23,5 +31,7 @@ pub use group_repository_impl::GroupRepositoryImpl;
 pub use ui_entity_repository_impl::UiEntityRepositoryImpl;
 pub use session_repository_impl::SessionRepositoryImpl;
 pub use request_log_repository_impl::RequestLogRepositoryImpl;
+pub use user_activity_event_repository_impl::UserActivityEventRepositoryImpl;
+pub use user_provisioning_checklist_repository_impl::UserProvisioningChecklistRepositoryImpl;
 pub use visual_workflow_repository_impl::VisualWorkflowRepositoryImpl;
 
@@ -1,6 +1,6 @@
 use crate::domain::entities::Permission;
 use crate::domain::repositories::PermissionRepository;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, HasAuditFields, RequestContext};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -18,12 +18,15 @@ impl PermissionRepositoryImpl {
 
 #[async_trait]
 impl PermissionRepository for PermissionRepositoryImpl {
-    async fn create(&self, permission: Permission) -> AppResult<Permission> {
+    async fn create(&self, mut permission: Permission) -> AppResult<Permission> {
+        if let Some(ctx) = RequestContext::current() {
+            permission.apply_create_audit(&ctx);
+        }
         sqlx::query_as!(
             Permission,
             r#"
-            INSERT INTO permissions (id, name, resource, action, description, created_at)
-            VALUES ($1, $2, $3, $4, $5, NOW())
+            INSERT INTO permissions (id, name, resource, action, description, created_at, request_id, created_by, updated_by)
+            VALUES ($1, $2, $3, $4, $5, NOW(), $6, $7, $8)
             ON CONFLICT (name) DO UPDATE SET resource = EXCLUDED.resource, action = EXCLUDED.action
             RETURNING id, name, resource, action, description, request_id, created_at, updated_at, created_by, updated_by, system_id, version
             "#,
@@ -31,7 +34,10 @@ impl PermissionRepository for PermissionRepositoryImpl {
             permission.name,
             permission.resource,
             permission.action,
-            permission.description
+            permission.description,
+            permission.request_id,
+            permission.created_by,
+            permission.updated_by
         )
         .fetch_one(&self.pool)
         .await
@@ -53,6 +59,21 @@ impl PermissionRepository for PermissionRepositoryImpl {
         .map_db_error("query", "record")
     }
 
+    async fn find_by_ids(&self, ids: &[Uuid]) -> AppResult<Vec<Permission>> {
+        sqlx::query_as!(
+            Permission,
+            r#"
+            SELECT id, name, resource, action, description, request_id, created_at, updated_at, created_by, updated_by, system_id, version
+            FROM permissions
+            WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_db_error("query", "record")
+    }
+
     async fn find_by_name(&self, name: &str) -> AppResult<Option<Permission>> {
         sqlx::query_as!(
             Permission,
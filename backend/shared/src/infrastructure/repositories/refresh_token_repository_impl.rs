@@ -1,7 +1,7 @@
 use crate::domain::repositories::refresh_token_repository::{RefreshToken, RefreshTokenRepository};
 use crate::shared::AppResult;
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sqlx::PgPool;
 use uuid::Uuid;
 use crate::infrastructure::database::RepositoryErrorExt;
@@ -16,6 +16,7 @@ struct RefreshTokenRow {
     created_at: DateTime<Utc>,
     revoked_at: Option<DateTime<Utc>>,
     is_revoked: bool,
+    device_fingerprint: String,
 }
 
 impl From<RefreshTokenRow> for RefreshToken {
@@ -28,6 +29,7 @@ impl From<RefreshTokenRow> for RefreshToken {
             created_at: row.created_at,
             revoked_at: row.revoked_at,
             is_revoked: row.is_revoked,
+            device_fingerprint: row.device_fingerprint,
         }
     }
 }
@@ -47,8 +49,8 @@ impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
     async fn create(&self, token: RefreshToken) -> AppResult<RefreshToken> {
         sqlx::query!(
             r#"
-            INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, created_at, revoked_at, is_revoked)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, created_at, revoked_at, is_revoked, device_fingerprint)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             "#,
             token.id,
             token.user_id,
@@ -56,7 +58,8 @@ impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
             token.expires_at,
             token.created_at,
             token.revoked_at,
-            token.is_revoked
+            token.is_revoked,
+            token.device_fingerprint
         )
         .execute(&self.pool)
         .await
@@ -69,7 +72,7 @@ impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
         let row = sqlx::query_as!(
             RefreshTokenRow,
             r#"
-            SELECT id, user_id, token_hash, expires_at, created_at, revoked_at, is_revoked
+            SELECT id, user_id, token_hash, expires_at, created_at, revoked_at, is_revoked, device_fingerprint
             FROM refresh_tokens
             WHERE token_hash = $1 AND is_revoked = false AND expires_at > NOW()
             "#,
@@ -86,7 +89,7 @@ impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
         let rows = sqlx::query_as!(
             RefreshTokenRow,
             r#"
-            SELECT id, user_id, token_hash, expires_at, created_at, revoked_at, is_revoked
+            SELECT id, user_id, token_hash, expires_at, created_at, revoked_at, is_revoked, device_fingerprint
             FROM refresh_tokens
             WHERE user_id = $1
             ORDER BY created_at DESC
@@ -100,6 +103,26 @@ impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
 
+    async fn use_token(&self, token_hash: &str, extend_by: Duration, not_after: DateTime<Utc>) -> AppResult<Option<RefreshToken>> {
+        let row = sqlx::query_as!(
+            RefreshTokenRow,
+            r#"
+            UPDATE refresh_tokens
+            SET expires_at = LEAST(NOW() + make_interval(secs => $2), $3)
+            WHERE token_hash = $1 AND is_revoked = false AND expires_at > NOW()
+            RETURNING id, user_id, token_hash, expires_at, created_at, revoked_at, is_revoked, device_fingerprint
+            "#,
+            token_hash,
+            extend_by.num_seconds() as f64,
+            not_after
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_db_error("query", "record")?;
+
+        Ok(row.map(|r| r.into()))
+    }
+
     async fn revoke_token(&self, token_hash: &str) -> AppResult<()> {
         sqlx::query!(
             r#"
@@ -117,7 +140,11 @@ impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
     }
 
     async fn revoke_all_user_tokens(&self, user_id: Uuid) -> AppResult<()> {
-        sqlx::query!(
+        self.revoke_all_for_user(user_id).await.map(|_| ())
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> AppResult<u64> {
+        let result = sqlx::query!(
             r#"
             UPDATE refresh_tokens
             SET is_revoked = true, revoked_at = NOW()
@@ -128,8 +155,8 @@ impl RefreshTokenRepository for RefreshTokenRepositoryImpl {
         .execute(&self.pool)
         .await
         .map_db_error("query", "record")?;
-        
-        Ok(())
+
+        Ok(result.rows_affected())
     }
 
     async fn delete_expired_tokens(&self) -> AppResult<u64> {
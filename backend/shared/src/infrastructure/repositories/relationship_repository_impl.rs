@@ -1,8 +1,9 @@
-use crate::domain::entities::Relationship;
+use crate::domain::entities::{encode_zookie, Relationship, RelationshipKey, RelationshipWrite, WriteResponse};
 use crate::domain::repositories::RelationshipRepository;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, HasAuditFields, RequestContext};
 use async_trait::async_trait;
-use sqlx::PgPool;
+use chrono::Utc;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 use crate::infrastructure::database::RepositoryErrorExt;
 
@@ -14,11 +15,124 @@ impl RelationshipRepositoryImpl {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    async fn upsert_in_tx(tx: &mut Transaction<'_, Postgres>, relationship: &Relationship) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO relationships (
+                id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                created_by, updated_by, system_id, version
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            ON CONFLICT ("user", relation, object, organization_id) 
+            WHERE deleted_at IS NULL
+            DO UPDATE SET 
+                valid_from = EXCLUDED.valid_from,
+                expires_at = EXCLUDED.expires_at,
+                is_active = EXCLUDED.is_active,
+                metadata = EXCLUDED.metadata,
+                updated_at = EXCLUDED.updated_at,
+                updated_by = EXCLUDED.updated_by,
+                version = relationships.version + 1
+            "#,
+            relationship.id,
+            relationship.user,
+            relationship.relation,
+            relationship.object,
+            relationship.organization_id,
+            relationship.created_at,
+            relationship.valid_from,
+            relationship.expires_at,
+            relationship.is_active,
+            relationship.metadata,
+            relationship.deleted_at,
+            relationship.deleted_by,
+            relationship.request_id,
+            relationship.updated_at,
+            relationship.created_by,
+            relationship.updated_by,
+            relationship.system_id,
+            relationship.version
+        )
+        .execute(&mut **tx)
+        .await
+        .map_db_error("create", "relationship")?;
+
+        Ok(())
+    }
+
+    /// Insert unless a live tuple with the same key exists; returns the rows inserted
+    async fn insert_new_in_tx(tx: &mut Transaction<'_, Postgres>, relationship: &Relationship) -> AppResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO relationships (
+                id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                created_by, updated_by, system_id, version
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            ON CONFLICT ("user", relation, object, organization_id) 
+            WHERE deleted_at IS NULL
+            DO NOTHING
+            "#,
+            relationship.id,
+            relationship.user,
+            relationship.relation,
+            relationship.object,
+            relationship.organization_id,
+            relationship.created_at,
+            relationship.valid_from,
+            relationship.expires_at,
+            relationship.is_active,
+            relationship.metadata,
+            relationship.deleted_at,
+            relationship.deleted_by,
+            relationship.request_id,
+            relationship.updated_at,
+            relationship.created_by,
+            relationship.updated_by,
+            relationship.system_id,
+            relationship.version
+        )
+        .execute(&mut **tx)
+        .await
+        .map_db_error("create", "relationship")?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn delete_in_tx(tx: &mut Transaction<'_, Postgres>, key: &RelationshipKey) -> AppResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE relationships
+            SET deleted_at = NOW(),
+                is_active = false,
+                updated_at = NOW(),
+                version = version + 1
+            WHERE "user" = $1 AND relation = $2 AND object = $3
+            AND organization_id IS NOT DISTINCT FROM $4
+            AND deleted_at IS NULL
+            "#,
+            key.user,
+            key.relation,
+            key.object,
+            key.organization_id
+        )
+        .execute(&mut **tx)
+        .await
+        .map_db_error("delete", "relationship")?;
+
+        Ok(result.rows_affected())
+    }
 }
 
 #[async_trait]
 impl RelationshipRepository for RelationshipRepositoryImpl {
-    async fn create(&self, relationship: Relationship) -> AppResult<Relationship> {
+    async fn create(&self, mut relationship: Relationship) -> AppResult<Relationship> {
+        if let Some(ctx) = RequestContext::current() {
+            relationship.apply_create_audit(&ctx);
+        }
         sqlx::query_as!(
             Relationship,
             r#"
@@ -169,7 +283,10 @@ impl RelationshipRepository for RelationshipRepositoryImpl {
         Ok(())
     }
 
-    async fn update(&self, relationship: Relationship) -> AppResult<Relationship> {
+    async fn update(&self, mut relationship: Relationship) -> AppResult<Relationship> {
+        if let Some(ctx) = RequestContext::current() {
+            relationship.apply_update_audit(&ctx);
+        }
         sqlx::query_as!(
             Relationship,
             r#"
@@ -267,7 +384,110 @@ impl RelationshipRepository for RelationshipRepositoryImpl {
         .await
         .map_db_error("query", "record")
     }
+
+    async fn find_roles_for_user(&self, user: &str) -> AppResult<Vec<Relationship>> {
+        sqlx::query_as!(
+            Relationship,
+            r#"
+            SELECT id, "user", relation, object, organization_id, created_at, valid_from, expires_at, 
+                   is_active, metadata, deleted_at, deleted_by, request_id, updated_at, 
+                   created_by, updated_by, system_id, version
+            FROM relationships
+            WHERE "user" = $1
+              AND relation = 'has_role'
+              AND deleted_at IS NULL
+              AND is_active
+              AND (valid_from IS NULL OR valid_from <= NOW())
+              AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY created_at DESC
+            "#,
+            user
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_db_error("query", "record")
+    }
+
+    async fn delete_expired(&self) -> AppResult<u64> {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM relationships
+            WHERE expires_at IS NOT NULL AND expires_at <= NOW()
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_db_error("delete", "relationship")?;
+
+        Ok(result.rows_affected())
+    }
     
+    async fn write_tuples(&self, writes: Vec<RelationshipWrite>) -> AppResult<WriteResponse> {
+        let ctx = RequestContext::current();
+        let mut tx = self.pool.begin().await.map_db_error("begin", "relationship")?;
+        let mut written = 0;
+        let mut deleted = 0;
+
+        for write in writes {
+            match write {
+                RelationshipWrite::Write(mut relationship) => {
+                    // An existing tuple keeps its creator; the upsert only takes updated_by
+                    if let Some(ctx) = &ctx {
+                        relationship.apply_create_audit(ctx);
+                    }
+                    Self::upsert_in_tx(&mut tx, &relationship).await?;
+                    written += 1;
+                }
+                RelationshipWrite::Delete(key) => {
+                    Self::delete_in_tx(&mut tx, &key).await?;
+                    deleted += 1;
+                }
+            }
+        }
+
+        tx.commit().await.map_db_error("commit", "relationship")?;
+
+        // Taken after commit, so any snapshot at or after this time includes the batch
+        Ok(WriteResponse {
+            zookie: encode_zookie(Utc::now()),
+            written,
+            deleted,
+        })
+    }
+
+    async fn delete_tuples(&self, deletes: Vec<RelationshipKey>) -> AppResult<()> {
+        let mut tx = self.pool.begin().await.map_db_error("begin", "relationship")?;
+        for key in &deletes {
+            Self::delete_in_tx(&mut tx, key).await?;
+        }
+        tx.commit().await.map_db_error("commit", "relationship")?;
+        Ok(())
+    }
+
+    async fn write_batch(&self, tuples: Vec<Relationship>) -> AppResult<u64> {
+        let ctx = RequestContext::current();
+        let mut tx = self.pool.begin().await.map_db_error("begin", "relationship")?;
+        let mut inserted = 0;
+        for mut relationship in tuples {
+            if let Some(ctx) = &ctx {
+                relationship.apply_create_audit(ctx);
+            }
+            inserted += Self::insert_new_in_tx(&mut tx, &relationship).await?;
+        }
+        tx.commit().await.map_db_error("commit", "relationship")?;
+        Ok(inserted)
+    }
+
+    async fn delete_batch(&self, keys: Vec<RelationshipKey>) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await.map_db_error("begin", "relationship")?;
+        let mut deleted = 0;
+        for key in &keys {
+            deleted += Self::delete_in_tx(&mut tx, key).await?;
+        }
+        tx.commit().await.map_db_error("commit", "relationship")?;
+        Ok(deleted)
+    }
+
     async fn find_by_user_and_org(&self, user: &str, organization_id: Uuid) -> AppResult<Vec<Relationship>> {
         sqlx::query_as!(
             Relationship,
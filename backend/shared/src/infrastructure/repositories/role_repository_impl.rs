@@ -1,8 +1,8 @@
-use crate::domain::entities::Role;
+use crate::domain::entities::{Permission, Role};
 use crate::domain::repositories::{RoleRepository, PermissionRepository};
 use crate::infrastructure::database::DatabaseService;
 use crate::infrastructure::zanzibar::RelationshipStore;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, HasAuditFields, RequestContext};
 use async_trait::async_trait;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -30,7 +30,10 @@ impl RoleRepositoryImpl {
 
 #[async_trait]
 impl RoleRepository for RoleRepositoryImpl {
-    async fn create(&self, role: Role) -> AppResult<Role> {
+    async fn create(&self, mut role: Role) -> AppResult<Role> {
+        if let Some(ctx) = RequestContext::current() {
+            role.apply_create_audit(&ctx);
+        }
         let role_id = role.id;
         
         // Insert role with audit fields
@@ -295,5 +298,94 @@ impl RoleRepository for RoleRepositoryImpl {
         
         Ok(roles)
     }
-}
 
+    async fn set_parent_role(&self, role_id: Uuid, parent_role_id: Option<Uuid>) -> AppResult<()> {
+        if let Some(parent_id) = parent_role_id {
+            // Reject cycles: the new parent must not already inherit from this role
+            let creates_cycle = sqlx::query_scalar!(
+                r#"
+                WITH RECURSIVE ancestors AS (
+                    SELECT id, role_parent_id FROM roles WHERE id = $1
+                    UNION
+                    SELECT r.id, r.role_parent_id
+                    FROM roles r
+                    JOIN ancestors a ON r.id = a.role_parent_id
+                )
+                SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = $2) as "exists!"
+                "#,
+                parent_id,
+                role_id
+            )
+            .fetch_one(self.database_service.pool())
+            .await
+            .map_db_error("query", "role_hierarchy")?;
+
+            if creates_cycle {
+                return Err(crate::shared::AppError::Validation(
+                    format!("Role {} cannot inherit from its own descendant {}", role_id, parent_id)
+                ));
+            }
+        }
+
+        let updated_by = RequestContext::current().map(|ctx| ctx.user_id);
+        let result = sqlx::query!(
+            r#"
+            UPDATE roles
+            SET role_parent_id = $2, updated_at = NOW(), updated_by = COALESCE($3, updated_by)
+            WHERE id = $1
+            "#,
+            role_id,
+            parent_role_id,
+            updated_by
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("update", "role")?;
+
+        if result.rows_affected() == 0 {
+            return Err(crate::shared::AppError::NotFound(format!("Role {} not found", role_id)));
+        }
+
+        Ok(())
+    }
+
+    async fn get_effective_permissions(&self, role_id: Uuid) -> AppResult<Vec<Permission>> {
+        // Walk the role and all of its ancestors, then resolve each role's
+        // Zanzibar grants (role:{name}#{action}@resource:{resource}) to permissions.
+        // UNION (not UNION ALL) guarantees termination even if a cycle slipped in.
+        sqlx::query_as!(
+            Permission,
+            r#"
+            WITH RECURSIVE role_hierarchy AS (
+                SELECT id, name, role_parent_id
+                FROM roles
+                WHERE id = $1
+                UNION
+                SELECT parent.id, parent.name, parent.role_parent_id
+                FROM roles parent
+                JOIN role_hierarchy child ON parent.id = child.role_parent_id
+            )
+            SELECT DISTINCT
+                p.id as "id!", p.name as "name!", p.resource as "resource!", p.action as "action!",
+                p.description, p.request_id,
+                p.created_at as "created_at!", p.updated_at as "updated_at!",
+                p.created_by, p.updated_by, p.system_id, p.version as "version!"
+            FROM role_hierarchy rh
+            JOIN relationships rel
+                ON rel."user" = 'role:' || rh.name
+            JOIN permissions p
+                ON rel.object = 'resource:' || p.resource
+               AND rel.relation = p.action
+            WHERE rel.is_active = true
+              AND rel.deleted_at IS NULL
+              AND (rel.valid_from IS NULL OR rel.valid_from <= NOW())
+              AND (rel.expires_at IS NULL OR rel.expires_at > NOW())
+            ORDER BY p.name
+            "#,
+            role_id
+        )
+        .fetch_all(self.database_service.pool())
+        .await
+        .map_db_error("query", "role_hierarchy")
+    }
+}
@@ -235,6 +235,22 @@ impl SessionRepository for SessionRepositoryImpl {
         Ok(rows.into_iter().map(|r| r.into()).collect())
     }
 
+    async fn count_active_by_user(&self, user_id: Uuid) -> AppResult<u64> {
+        let location = concat!(file!(), ":", line!());
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM sessions WHERE user_id = $1 AND is_active = true",
+            user_id
+        )
+        .fetch_one(self.database_service.pool())
+        .await
+        .map_err(|e| {
+            let err = crate::shared::AppError::Database(e);
+            err.log_with_operation(location, "session_repository.count_active_by_user");
+            err
+        })?;
+        Ok(count.unwrap_or(0) as u64)
+    }
+
     async fn update(&self, mut session: Session) -> AppResult<Session> {
         let location = concat!(file!(), ":", line!());
         let current_version = session.version;
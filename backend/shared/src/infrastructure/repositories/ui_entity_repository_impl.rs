@@ -1,6 +1,6 @@
 use crate::domain::entities::{UiPage, UiButton, UiField, UiApiEndpoint};
 use crate::domain::repositories::UiEntityRepository;
-use crate::shared::AppResult;
+use crate::shared::{AppResult, HasAuditFields, RequestContext};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -19,7 +19,10 @@ impl UiEntityRepositoryImpl {
 #[async_trait]
 impl UiEntityRepository for UiEntityRepositoryImpl {
     // Page methods
-    async fn register_page(&self, page: UiPage) -> AppResult<UiPage> {
+    async fn register_page(&self, mut page: UiPage) -> AppResult<UiPage> {
+        if let Some(ctx) = RequestContext::current() {
+            page.apply_create_audit(&ctx);
+        }
         sqlx::query_as!(
             UiPage,
             r#"
@@ -113,7 +116,10 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
         .map_db_error("fetch_all", "ui_page")
     }
 
-    async fn update_page(&self, page: UiPage) -> AppResult<UiPage> {
+    async fn update_page(&self, mut page: UiPage) -> AppResult<UiPage> {
+        if let Some(ctx) = RequestContext::current() {
+            page.apply_update_audit(&ctx);
+        }
         sqlx::query_as!(
             UiPage,
             r#"
@@ -160,7 +166,10 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
     }
 
     // Button methods
-    async fn register_button(&self, button: UiButton) -> AppResult<UiButton> {
+    async fn register_button(&self, mut button: UiButton) -> AppResult<UiButton> {
+        if let Some(ctx) = RequestContext::current() {
+            button.apply_create_audit(&ctx);
+        }
         sqlx::query_as!(
             UiButton,
             r#"
@@ -241,7 +250,10 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
         .map_db_error("fetch_all", "ui_button")
     }
 
-    async fn update_button(&self, button: UiButton) -> AppResult<UiButton> {
+    async fn update_button(&self, mut button: UiButton) -> AppResult<UiButton> {
+        if let Some(ctx) = RequestContext::current() {
+            button.apply_update_audit(&ctx);
+        }
         sqlx::query_as!(
             UiButton,
             r#"
@@ -289,7 +301,10 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
     }
 
     // Field methods
-    async fn register_field(&self, field: UiField) -> AppResult<UiField> {
+    async fn register_field(&self, mut field: UiField) -> AppResult<UiField> {
+        if let Some(ctx) = RequestContext::current() {
+            field.apply_create_audit(&ctx);
+        }
         sqlx::query_as!(
             UiField,
             r#"
@@ -370,7 +385,10 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
         .map_db_error("fetch_all", "ui_field")
     }
 
-    async fn update_field(&self, field: UiField) -> AppResult<UiField> {
+    async fn update_field(&self, mut field: UiField) -> AppResult<UiField> {
+        if let Some(ctx) = RequestContext::current() {
+            field.apply_update_audit(&ctx);
+        }
         sqlx::query_as!(
             UiField,
             r#"
@@ -418,7 +436,10 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
     }
 
     // API endpoint methods
-    async fn register_api(&self, api: UiApiEndpoint) -> AppResult<UiApiEndpoint> {
+    async fn register_api(&self, mut api: UiApiEndpoint) -> AppResult<UiApiEndpoint> {
+        if let Some(ctx) = RequestContext::current() {
+            api.apply_create_audit(&ctx);
+        }
         sqlx::query_as!(
             UiApiEndpoint,
             r#"
@@ -497,7 +518,10 @@ impl UiEntityRepository for UiEntityRepositoryImpl {
         .map_db_error("fetch_all", "ui_api_endpoint")
     }
 
-    async fn update_api(&self, api: UiApiEndpoint) -> AppResult<UiApiEndpoint> {
+    async fn update_api(&self, mut api: UiApiEndpoint) -> AppResult<UiApiEndpoint> {
+        if let Some(ctx) = RequestContext::current() {
+            api.apply_update_audit(&ctx);
+        }
         sqlx::query_as!(
             UiApiEndpoint,
             r#"
@@ -0,0 +1,65 @@
+use crate::domain::entities::UserActivityEvent;
+use crate::domain::repositories::UserActivityEventRepository;
+use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
+use crate::shared::AppResult;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+pub struct UserActivityEventRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl UserActivityEventRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+#[async_trait]
+impl UserActivityEventRepository for UserActivityEventRepositoryImpl {
+    async fn create(&self, event: UserActivityEvent) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_activity_events (
+                id, user_id, session_id, request_id, event_type, method, path,
+                patient_id, access_reason, status_code, created_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            event.id,
+            event.user_id,
+            event.session_id,
+            event.request_id,
+            event.event_type,
+            event.method,
+            event.path,
+            event.patient_id,
+            event.access_reason,
+            event.status_code,
+            event.created_at
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("create", "user activity event")?;
+        Ok(())
+    }
+
+    async fn find_by_patient(&self, patient_id: i64, limit: u32) -> AppResult<Vec<UserActivityEvent>> {
+        sqlx::query_as!(
+            UserActivityEvent,
+            r#"
+            SELECT id, user_id, session_id, request_id, event_type, method, path,
+                   patient_id, access_reason, status_code, created_at
+            FROM user_activity_events
+            WHERE patient_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            patient_id,
+            limit as i64
+        )
+        .fetch_all(self.database_service.pool())
+        .await
+        .map_db_error("find", "user activity event")
+    }
+}
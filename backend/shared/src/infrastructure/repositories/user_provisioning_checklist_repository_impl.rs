@@ -0,0 +1,70 @@
+use crate::domain::entities::UserProvisioningChecklist;
+use crate::domain::repositories::UserProvisioningChecklistRepository;
+use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub struct UserProvisioningChecklistRepositoryImpl {
+    database_service: Arc<DatabaseService>,
+}
+
+impl UserProvisioningChecklistRepositoryImpl {
+    pub fn new(database_service: Arc<DatabaseService>) -> Self {
+        Self { database_service }
+    }
+}
+
+#[async_trait]
+impl UserProvisioningChecklistRepository for UserProvisioningChecklistRepositoryImpl {
+    async fn find_by_user_id(&self, user_id: Uuid) -> AppResult<Option<UserProvisioningChecklist>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT checklist
+            FROM user_provisioning_checklists
+            WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(self.database_service.pool())
+        .await
+        .map_db_error("find", "user provisioning checklist")?;
+
+        row.map(|r| {
+            serde_json::from_value(r.checklist).map_err(|e| {
+                AppError::Internal(format!("Invalid provisioning checklist for user {}: {}", user_id, e))
+            })
+        })
+        .transpose()
+    }
+
+    async fn save(&self, checklist: &UserProvisioningChecklist) -> AppResult<()> {
+        let json = serde_json::to_value(checklist)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize provisioning checklist: {}", e)))?;
+        let overall_status = format!("{:?}", checklist.overall_status);
+
+        sqlx::query!(
+            r#"
+            INSERT INTO user_provisioning_checklists (
+                user_id, checklist, overall_status, started_at, completed_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            ON CONFLICT (user_id) DO UPDATE SET
+                checklist = EXCLUDED.checklist,
+                overall_status = EXCLUDED.overall_status,
+                completed_at = EXCLUDED.completed_at,
+                updated_at = NOW()
+            "#,
+            checklist.user_id,
+            json,
+            overall_status,
+            checklist.started_at,
+            checklist.completed_at
+        )
+        .execute(self.database_service.pool())
+        .await
+        .map_db_error("save", "user provisioning checklist")?;
+        Ok(())
+    }
+}
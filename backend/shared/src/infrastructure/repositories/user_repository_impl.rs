@@ -1,7 +1,8 @@
 use crate::domain::entities::User;
 use crate::domain::repositories::UserRepository;
 use crate::infrastructure::database::{DatabaseService, RepositoryErrorExt};
-use crate::shared::AppResult;
+use crate::infrastructure::encryption::FieldEncryption;
+use crate::shared::{AppResult, HasAuditFields, RequestContext};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use sqlx::FromRow;
@@ -52,19 +53,44 @@ impl From<UserRow> for User {
     }
 }
 
+/// Context binding `users.email_ciphertext` to its column
+const EMAIL_CONTEXT: &[u8] = b"users.email";
+
 pub struct UserRepositoryImpl {
     database_service: Arc<DatabaseService>,
+    field_encryption: Option<Arc<FieldEncryption>>,
 }
 
 impl UserRepositoryImpl {
     pub fn new(database_service: Arc<DatabaseService>) -> Self {
-        Self { database_service }
+        Self {
+            database_service,
+            field_encryption: None,
+        }
+    }
+
+    /// Store a deterministic ciphertext of the email and look users up by it
+    pub fn with_field_encryption(mut self, field_encryption: Arc<FieldEncryption>) -> Self {
+        self.field_encryption = Some(field_encryption);
+        self
+    }
+
+    /// `users.email_ciphertext` for `email`; `None` without field encryption
+    fn email_ciphertext(&self, email: &str) -> AppResult<Option<Vec<u8>>> {
+        self.field_encryption
+            .as_ref()
+            .map(|encryption| encryption.encrypt_deterministic(email.as_bytes(), EMAIL_CONTEXT))
+            .transpose()
     }
 }
 
 #[async_trait]
 impl UserRepository for UserRepositoryImpl {
-    async fn create(&self, user: User) -> AppResult<User> {
+    async fn create(&self, mut user: User) -> AppResult<User> {
+        if let Some(ctx) = RequestContext::current() {
+            user.apply_create_audit(&ctx);
+        }
+        let email_ciphertext = self.email_ciphertext(&user.email)?;
         let location = concat!(file!(), ":", line!());
         let row: UserRow = sqlx::query_as!(
             UserRow,
@@ -72,9 +98,9 @@ impl UserRepository for UserRepositoryImpl {
             INSERT INTO users (
                 id, email, username, password_hash, is_active, is_verified, is_super_user, 
                 organization_id, created_at, updated_at, last_login,
-                request_id, created_by, updated_by, system_id, version
+                request_id, created_by, updated_by, system_id, version, email_ciphertext
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
             RETURNING 
                 id, email, username, password_hash, is_active, is_verified, is_super_user, 
                 created_at, updated_at, last_login, organization_id, request_id,
@@ -95,7 +121,8 @@ impl UserRepository for UserRepositoryImpl {
             user.created_by,
             user.updated_by,
             user.system_id,
-            user.version
+            user.version,
+            email_ciphertext
         )
         .fetch_one(self.database_service.pool())
         .await
@@ -132,21 +159,40 @@ impl UserRepository for UserRepositoryImpl {
 
     async fn find_by_email(&self, email: &str) -> AppResult<Option<User>> {
         let location = concat!(file!(), ":", line!());
-        let row = sqlx::query_as!(
-            UserRow,
-            r#"
-            SELECT 
-                id, email, username, password_hash, is_active, is_verified, is_super_user, 
-                created_at, updated_at, last_login, organization_id, request_id,
-                created_by, updated_by, system_id, version
-            FROM users
-            WHERE email = $1
-            "#,
-            email
-        )
-        .fetch_optional(self.database_service.pool())
-        .await
-        .map_err(|e| {
+        let query = match self.email_ciphertext(email)? {
+            // Indexed lookup by ciphertext; rows written without field encryption
+            // have no ciphertext yet and are matched on the plaintext email
+            Some(email_ciphertext) => sqlx::query_as!(
+                UserRow,
+                r#"
+                SELECT 
+                    id, email, username, password_hash, is_active, is_verified, is_super_user, 
+                    created_at, updated_at, last_login, organization_id, request_id,
+                    created_by, updated_by, system_id, version
+                FROM users
+                WHERE email_ciphertext = $1 OR (email_ciphertext IS NULL AND email = $2)
+                "#,
+                email_ciphertext,
+                email
+            )
+            .fetch_optional(self.database_service.pool())
+            .await,
+            None => sqlx::query_as!(
+                UserRow,
+                r#"
+                SELECT 
+                    id, email, username, password_hash, is_active, is_verified, is_super_user, 
+                    created_at, updated_at, last_login, organization_id, request_id,
+                    created_by, updated_by, system_id, version
+                FROM users
+                WHERE email = $1
+                "#,
+                email
+            )
+            .fetch_optional(self.database_service.pool())
+            .await,
+        };
+        let row = query.map_err(|e| {
             let err = crate::shared::AppError::Database(e);
             err.log_with_operation(location, "user_repository.find_by_email");
             err
@@ -174,10 +220,16 @@ impl UserRepository for UserRepositoryImpl {
     }
 
     async fn update(&self, mut user: User) -> AppResult<User> {
+        if let Some(ctx) = RequestContext::current() {
+            user.apply_update_audit(&ctx);
+        }
         // Store current version for optimistic locking
         let current_version = user.version;
         // Increment version for update
         user.version += 1;
+        // Without field encryption the ciphertext is cleared so a changed email
+        // is found through the plaintext fallback
+        let email_ciphertext = self.email_ciphertext(&user.email)?;
         
         let row: UserRow = sqlx::query_as!(
             UserRow,
@@ -185,7 +237,7 @@ impl UserRepository for UserRepositoryImpl {
             UPDATE users
             SET email = $2, username = $3, password_hash = $4, is_active = $5, is_verified = $6, 
                 is_super_user = $7, organization_id = $8, updated_at = $9, last_login = $10,
-                request_id = $11, updated_by = $12, version = $13
+                request_id = $11, updated_by = $12, version = $13, email_ciphertext = $15
             WHERE id = $1 AND version = $14
             RETURNING 
                 id, email, username, password_hash, is_active, is_verified, is_super_user, 
@@ -205,7 +257,8 @@ impl UserRepository for UserRepositoryImpl {
             user.request_id,
             user.updated_by,
             user.version, // New incremented version
-            current_version // Current version for WHERE clause (optimistic locking)
+            current_version, // Current version for WHERE clause (optimistic locking)
+            email_ciphertext
         )
         .fetch_one(self.database_service.pool())
         .await
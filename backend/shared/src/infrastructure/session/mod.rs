@@ -1,6 +1,7 @@
 pub mod session_cache;
+pub mod redis_session_cache;
 pub mod session_service;
 
-pub use session_cache::SessionCache;
-pub use session_service::SessionService;
-
+pub use session_cache::{SessionCache, SessionCacheBackend};
+pub use redis_session_cache::{RedisConnection, RedisSessionCache, RedisStore, SESSION_INVALIDATED_CHANNEL};
+pub use session_service::{SessionEvictedEvent, SessionService};
@@ -0,0 +1,668 @@
+use crate::domain::entities::Session;
+use crate::infrastructure::session::{SessionCache, SessionCacheBackend};
+use crate::shared::{AppError, AppResult};
+use async_trait::async_trait;
+use chrono::Utc;
+use redis::aio::ConnectionManager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+use uuid::Uuid;
+
+/// Pub/sub channel announcing invalidated sessions.
+///
+/// Messages are either a user ID (every session of that user) or
+/// `session:{id}` (one session).
+pub const SESSION_INVALIDATED_CHANNEL: &str = "session_invalidated";
+
+const SESSION_KEY_PREFIX: &str = "session:";
+const TOKEN_KEY_PREFIX: &str = "session_token:";
+
+/// Delay before resubscribing after the invalidation channel drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+fn session_key(id: Uuid) -> String {
+    format!("{}{}", SESSION_KEY_PREFIX, id)
+}
+
+fn token_key(token: &str) -> String {
+    format!("{}{}", TOKEN_KEY_PREFIX, token)
+}
+
+fn redis_error(error: redis::RedisError) -> AppError {
+    AppError::Internal(format!("Redis error: {}", error))
+}
+
+/// Redis commands used by [`RedisSessionCache`]
+#[async_trait]
+pub trait RedisStore: Send + Sync {
+    /// `GET key`
+    async fn get(&self, key: &str) -> AppResult<Option<String>>;
+
+    /// `SET key value EX ttl_seconds`
+    async fn set_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> AppResult<()>;
+
+    /// `INCR key` then `EXPIRE key ttl_seconds` in one `MULTI` transaction,
+    /// returning the incremented value
+    async fn incr_ex(&self, key: &str, ttl_seconds: u64) -> AppResult<u64>;
+
+    /// `TTL key` in seconds; `None` if the key is missing or never expires
+    async fn ttl(&self, key: &str) -> AppResult<Option<u64>>;
+
+    /// `DEL key...`
+    async fn del(&self, keys: &[String]) -> AppResult<()>;
+
+    /// Every key matching `pattern`, collected with `SCAN`
+    async fn scan_match(&self, pattern: &str) -> AppResult<Vec<String>>;
+
+    /// `PUBLISH channel message`
+    async fn publish(&self, channel: &str, message: &str) -> AppResult<()>;
+
+    /// `SUBSCRIBE channel`, delivering message payloads until the receiver is dropped
+    async fn subscribe(&self, channel: &str) -> AppResult<mpsc::UnboundedReceiver<String>>;
+
+    /// `PING`
+    async fn ping(&self) -> AppResult<()>;
+}
+
+/// [`RedisStore`] over a Redis server
+pub struct RedisConnection {
+    client: redis::Client,
+    manager: ConnectionManager,
+}
+
+impl RedisConnection {
+    /// Connect to `redis_url` (`redis://host:port/db`)
+    pub async fn connect(redis_url: &str) -> AppResult<Self> {
+        let client = redis::Client::open(redis_url).map_err(redis_error)?;
+        let manager = ConnectionManager::new(client.clone()).await.map_err(redis_error)?;
+        Ok(Self { client, manager })
+    }
+}
+
+#[async_trait]
+impl RedisStore for RedisConnection {
+    async fn get(&self, key: &str) -> AppResult<Option<String>> {
+        let mut conn = self.manager.clone();
+        let value: Option<String> = redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(value)
+    }
+
+    async fn set_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> AppResult<()> {
+        let mut conn = self.manager.clone();
+        let _: () = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("EX")
+            .arg(ttl_seconds)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(())
+    }
+
+    async fn incr_ex(&self, key: &str, ttl_seconds: u64) -> AppResult<u64> {
+        let mut conn = self.manager.clone();
+        let (value,): (u64,) = redis::pipe()
+            .atomic()
+            .cmd("INCR")
+            .arg(key)
+            .cmd("EXPIRE")
+            .arg(key)
+            .arg(ttl_seconds)
+            .ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(value)
+    }
+
+    async fn ttl(&self, key: &str) -> AppResult<Option<u64>> {
+        let mut conn = self.manager.clone();
+        let ttl: i64 = redis::cmd("TTL")
+            .arg(key)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        // -2: no such key, -1: no expiry
+        Ok(u64::try_from(ttl).ok())
+    }
+
+    async fn del(&self, keys: &[String]) -> AppResult<()> {
+        if keys.is_empty() {
+            return Ok(());
+        }
+        let mut conn = self.manager.clone();
+        let _: i64 = redis::cmd("DEL")
+            .arg(keys)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(())
+    }
+
+    async fn scan_match(&self, pattern: &str) -> AppResult<Vec<String>> {
+        let mut conn = self.manager.clone();
+        let mut keys = Vec::new();
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(100)
+                .query_async(&mut conn)
+                .await
+                .map_err(redis_error)?;
+            keys.extend(batch);
+            if next == 0 {
+                return Ok(keys);
+            }
+            cursor = next;
+        }
+    }
+
+    async fn publish(&self, channel: &str, message: &str) -> AppResult<()> {
+        let mut conn = self.manager.clone();
+        let _: i64 = redis::cmd("PUBLISH")
+            .arg(channel)
+            .arg(message)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(())
+    }
+
+    async fn subscribe(&self, channel: &str) -> AppResult<mpsc::UnboundedReceiver<String>> {
+        let mut pubsub = self.client.get_async_pubsub().await.map_err(redis_error)?;
+        pubsub.subscribe(channel).await.map_err(redis_error)?;
+
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let channel = channel.to_string();
+        tokio::spawn(async move {
+            let mut messages = Box::pin(pubsub.into_on_message());
+            while let Some(message) = messages.next().await {
+                match message.get_payload::<String>() {
+                    Ok(payload) => {
+                        if sender.send(payload).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => tracing::warn!("Ignoring invalid message on {}: {}", channel, e),
+                }
+            }
+            tracing::warn!("Redis subscription to {} closed", channel);
+        });
+        Ok(receiver)
+    }
+
+    async fn ping(&self) -> AppResult<()> {
+        let mut conn = self.manager.clone();
+        let _: String = redis::cmd("PING")
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(())
+    }
+}
+
+/// Session cache shared by every service instance through Redis
+///
+/// Sessions are stored as JSON under `session:{id}` with a TTL matching
+/// their expiry, plus a `session_token:{token}` index. Each instance keeps
+/// recently used sessions in a local [`SessionCache`] and drops them when
+/// an invalidation arrives on [`SESSION_INVALIDATED_CHANNEL`]. While the
+/// subscription is down the local copies are cleared and bypassed, since
+/// invalidations published meanwhile would be missed.
+pub struct RedisSessionCache {
+    store: Arc<dyn RedisStore>,
+    local: Arc<SessionCache>,
+    subscribed: Arc<AtomicBool>,
+}
+
+impl RedisSessionCache {
+    /// Connect to Redis, keeping up to `max_local_entries` sessions in memory
+    pub async fn connect(redis_url: &str, max_local_entries: usize) -> AppResult<Self> {
+        let store = RedisConnection::connect(redis_url).await?;
+        Self::with_store(Arc::new(store), max_local_entries).await
+    }
+
+    /// Cache over any [`RedisStore`], subscribing to invalidations
+    pub async fn with_store(store: Arc<dyn RedisStore>, max_local_entries: usize) -> AppResult<Self> {
+        let local = Arc::new(SessionCache::with_max_entries(max_local_entries));
+        let invalidations = store.subscribe(SESSION_INVALIDATED_CHANNEL).await?;
+        let subscribed = Arc::new(AtomicBool::new(true));
+
+        tokio::spawn(Self::listen(
+            store.clone(),
+            Arc::downgrade(&local),
+            subscribed.clone(),
+            invalidations,
+        ));
+
+        Ok(Self { store, local, subscribed })
+    }
+
+    /// Apply invalidations, resubscribing whenever the channel closes
+    ///
+    /// Runs until the cache is dropped.
+    async fn listen(
+        store: Arc<dyn RedisStore>,
+        local: Weak<SessionCache>,
+        subscribed: Arc<AtomicBool>,
+        mut invalidations: mpsc::UnboundedReceiver<String>,
+    ) {
+        loop {
+            while let Some(message) = invalidations.recv().await {
+                let Some(local) = local.upgrade() else {
+                    return;
+                };
+                Self::apply_invalidation(&local, &message);
+            }
+
+            subscribed.store(false, Ordering::SeqCst);
+            tracing::warn!("Session invalidation channel closed; bypassing local session cache");
+            invalidations = loop {
+                let Some(local) = local.upgrade() else {
+                    return;
+                };
+                local.clear();
+                match store.subscribe(SESSION_INVALIDATED_CHANNEL).await {
+                    Ok(invalidations) => break invalidations,
+                    Err(e) => tracing::warn!("Failed to resubscribe to session invalidations: {}", e),
+                }
+                drop(local);
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            };
+
+            // Drop anything cached between the last clear and the new subscription
+            if let Some(local) = local.upgrade() {
+                local.clear();
+            }
+            subscribed.store(true, Ordering::SeqCst);
+            tracing::info!("Resubscribed to session invalidations");
+        }
+    }
+
+    /// Whether local copies can be trusted to receive invalidations
+    fn local_enabled(&self) -> bool {
+        self.subscribed.load(Ordering::SeqCst)
+    }
+
+    fn apply_invalidation(local: &SessionCache, message: &str) {
+        if let Ok(user_id) = Uuid::parse_str(message) {
+            local.remove_by_user(user_id);
+        } else if let Some(id) = message
+            .strip_prefix(SESSION_KEY_PREFIX)
+            .and_then(|id| Uuid::parse_str(id).ok())
+        {
+            local.remove_by_id(id);
+        } else {
+            tracing::warn!("Ignoring unknown session invalidation: {}", message);
+        }
+    }
+
+    /// End every cached session of a user on all instances
+    ///
+    /// Publishes the user ID so other instances drop their local copies,
+    /// then deletes the user's `session:{id}` keys found with `SCAN`.
+    /// Returns how many sessions were deleted from Redis.
+    pub async fn invalidate_all_for_user(&self, user_id: Uuid) -> AppResult<usize> {
+        self.store.publish(SESSION_INVALIDATED_CHANNEL, &user_id.to_string()).await?;
+        self.local.remove_by_user(user_id);
+
+        let mut keys = Vec::new();
+        for key in self.store.scan_match(&format!("{}*", SESSION_KEY_PREFIX)).await? {
+            let Some(data) = self.store.get(&key).await? else {
+                continue;
+            };
+            match serde_json::from_str::<Session>(&data) {
+                Ok(session) if session.user_id == Some(user_id) => {
+                    keys.push(token_key(&session.session_token));
+                    keys.push(key);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Skipping unreadable cached session {}: {}", key, e),
+            }
+        }
+
+        let deleted = keys.len() / 2;
+        self.store.del(&keys).await?;
+        Ok(deleted)
+    }
+
+    async fn load(&self, id: Uuid) -> AppResult<Option<Session>> {
+        let Some(data) = self.store.get(&session_key(id)).await? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(|e| AppError::Internal(format!("Invalid cached session {}: {}", id, e)))
+    }
+
+    /// Look up a session in Redis by token
+    async fn fetch(&self, token: &str) -> AppResult<Option<Session>> {
+        let Some(id) = self.store.get(&token_key(token)).await? else {
+            return Ok(None);
+        };
+        let Ok(id) = Uuid::parse_str(&id) else {
+            return Ok(None);
+        };
+        self.load(id).await
+    }
+
+    async fn delete(&self, session: &Session) -> AppResult<()> {
+        self.store
+            .del(&[session_key(session.id), token_key(&session.session_token)])
+            .await?;
+        self.store
+            .publish(SESSION_INVALIDATED_CHANNEL, &session_key(session.id))
+            .await
+    }
+}
+
+#[async_trait]
+impl SessionCacheBackend for RedisSessionCache {
+    async fn get(&self, token: &str) -> AppResult<Option<Session>> {
+        if !self.local_enabled() {
+            return self.fetch(token).await;
+        }
+        if let Some(session) = self.local.get(token) {
+            return Ok(Some(session));
+        }
+
+        let session = self.fetch(token).await?;
+        if let Some(session) = &session {
+            if self.local_enabled() {
+                self.local.set(token, session.clone());
+            }
+        }
+        Ok(session)
+    }
+
+    async fn set(&self, token: &str, session: Session) -> AppResult<()> {
+        let ttl_seconds = (session.expires_at - Utc::now()).num_seconds();
+        if ttl_seconds <= 0 {
+            // Already expired: make sure no stale copy is served
+            self.local.remove(token);
+            return self.delete(&session).await;
+        }
+
+        let data = serde_json::to_string(&session)
+            .map_err(|e| AppError::Internal(format!("Failed to serialize session: {}", e)))?;
+        let ttl_seconds = ttl_seconds as u64;
+        self.store.set_ex(&session_key(session.id), &data, ttl_seconds).await?;
+        self.store.set_ex(&token_key(token), &session.id.to_string(), ttl_seconds).await?;
+        // Other instances may hold the previous version
+        self.store
+            .publish(SESSION_INVALIDATED_CHANNEL, &session_key(session.id))
+            .await?;
+        if self.local_enabled() {
+            self.local.set(token, session);
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, token: &str) -> AppResult<()> {
+        let session = match self.local.get(token) {
+            Some(session) => Some(session),
+            None => self.fetch(token).await?,
+        };
+        self.local.remove(token);
+        match session {
+            Some(session) => self.delete(&session).await,
+            None => self.store.del(&[token_key(token)]).await,
+        }
+    }
+
+    async fn remove_by_id(&self, id: Uuid) -> AppResult<()> {
+        self.local.remove_by_id(id);
+        match self.load(id).await? {
+            Some(session) => self.delete(&session).await,
+            None => self.store.del(&[session_key(id)]).await,
+        }
+    }
+
+    async fn remove_by_user(&self, user_id: Uuid) -> AppResult<usize> {
+        self.invalidate_all_for_user(user_id).await
+    }
+
+    async fn cleanup_expired(&self) -> AppResult<usize> {
+        // Redis expires entries itself; only the local copies need sweeping
+        Ok(self.local.cleanup_expired())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// In-memory stand-in for Redis: string keys with TTLs and pub/sub fan-out
+    #[derive(Default)]
+    struct FakeRedis {
+        entries: Mutex<HashMap<String, (String, u64)>>,
+        subscribers: Mutex<Vec<(String, mpsc::UnboundedSender<String>)>>,
+        refuse_subscriptions: AtomicBool,
+    }
+
+    impl FakeRedis {
+        fn entry(&self, key: &str) -> Option<(String, u64)> {
+            self.entries.lock().unwrap().get(key).cloned()
+        }
+
+        /// Close every subscription, refusing new ones while `refuse` is set
+        fn drop_subscriptions(&self, refuse: bool) {
+            self.refuse_subscriptions.store(refuse, Ordering::SeqCst);
+            self.subscribers.lock().unwrap().clear();
+        }
+    }
+
+    #[async_trait]
+    impl RedisStore for FakeRedis {
+        async fn get(&self, key: &str) -> AppResult<Option<String>> {
+            Ok(self.entry(key).map(|(value, _)| value))
+        }
+
+        async fn set_ex(&self, key: &str, value: &str, ttl_seconds: u64) -> AppResult<()> {
+            self.entries.lock().unwrap().insert(key.to_string(), (value.to_string(), ttl_seconds));
+            Ok(())
+        }
+
+        async fn incr_ex(&self, key: &str, ttl_seconds: u64) -> AppResult<u64> {
+            let mut entries = self.entries.lock().unwrap();
+            let value = entries.get(key).map_or(Ok(0), |(v, _)| v.parse::<u64>())
+                .map_err(|_| AppError::Internal("Redis error: value is not an integer".to_string()))? + 1;
+            entries.insert(key.to_string(), (value.to_string(), ttl_seconds));
+            Ok(value)
+        }
+
+        async fn ttl(&self, key: &str) -> AppResult<Option<u64>> {
+            Ok(self.entry(key).map(|(_, ttl)| ttl))
+        }
+
+        async fn del(&self, keys: &[String]) -> AppResult<()> {
+            let mut entries = self.entries.lock().unwrap();
+            for key in keys {
+                entries.remove(key);
+            }
+            Ok(())
+        }
+
+        async fn scan_match(&self, pattern: &str) -> AppResult<Vec<String>> {
+            let prefix = pattern.trim_end_matches('*');
+            Ok(self.entries.lock().unwrap().keys().filter(|k| k.starts_with(prefix)).cloned().collect())
+        }
+
+        async fn publish(&self, channel: &str, message: &str) -> AppResult<()> {
+            for (subscribed, sender) in self.subscribers.lock().unwrap().iter() {
+                if subscribed == channel {
+                    let _ = sender.send(message.to_string());
+                }
+            }
+            Ok(())
+        }
+
+        async fn subscribe(&self, channel: &str) -> AppResult<mpsc::UnboundedReceiver<String>> {
+            if self.refuse_subscriptions.load(Ordering::SeqCst) {
+                return Err(AppError::Internal("Redis error: connection refused".to_string()));
+            }
+            let (sender, receiver) = mpsc::unbounded_channel();
+            self.subscribers.lock().unwrap().push((channel.to_string(), sender));
+            Ok(receiver)
+        }
+
+        async fn ping(&self) -> AppResult<()> {
+            Ok(())
+        }
+    }
+
+    fn session(token: &str, user_id: Option<Uuid>) -> Session {
+        let mut session = Session::new(
+            token.to_string(),
+            "127.0.0.1".parse().unwrap(),
+            None,
+            Utc::now() + Duration::hours(1),
+            "api".to_string(),
+            "web".to_string(),
+        );
+        if let Some(user_id) = user_id {
+            session.authenticate(user_id, None);
+        }
+        session
+    }
+
+    /// Wait for the invalidation listener to catch up
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    async fn instance(redis: &Arc<FakeRedis>) -> RedisSessionCache {
+        RedisSessionCache::with_store(redis.clone(), 100).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn sessions_are_stored_as_json_with_ttl_and_shared() {
+        let redis = Arc::new(FakeRedis::default());
+        let first = instance(&redis).await;
+        let second = instance(&redis).await;
+        let stored = session("token-a", None);
+
+        first.set("token-a", stored.clone()).await.unwrap();
+
+        let (data, ttl) = redis.entry(&session_key(stored.id)).unwrap();
+        let decoded: Session = serde_json::from_str(&data).unwrap();
+        assert_eq!(decoded.session_token, "token-a");
+        assert!((3590..=3600).contains(&ttl));
+
+        let loaded = second.get("token-a").await.unwrap();
+        assert_eq!(loaded.map(|s| s.id), Some(stored.id));
+    }
+
+    #[tokio::test]
+    async fn invalidating_a_user_propagates_to_other_instances() {
+        let redis = Arc::new(FakeRedis::default());
+        let first = instance(&redis).await;
+        let second = instance(&redis).await;
+        let user = Uuid::new_v4();
+        let other_user = Uuid::new_v4();
+
+        first.set("token-a", session("token-a", Some(user))).await.unwrap();
+        first.set("token-b", session("token-b", Some(user))).await.unwrap();
+        first.set("token-c", session("token-c", Some(other_user))).await.unwrap();
+        // Warm the second instance's local cache
+        for token in ["token-a", "token-b", "token-c"] {
+            assert!(second.get(token).await.unwrap().is_some());
+        }
+
+        assert_eq!(first.invalidate_all_for_user(user).await.unwrap(), 2);
+        settle().await;
+
+        assert!(second.local.get("token-a").is_none());
+        assert!(second.local.get("token-b").is_none());
+        assert!(second.get("token-a").await.unwrap().is_none());
+        assert!(second.get("token-c").await.unwrap().is_some());
+        assert!(redis.entry(&token_key("token-a")).is_none());
+    }
+
+    #[tokio::test]
+    async fn removing_a_session_propagates_to_other_instances() {
+        let redis = Arc::new(FakeRedis::default());
+        let first = instance(&redis).await;
+        let second = instance(&redis).await;
+
+        first.set("token-a", session("token-a", None)).await.unwrap();
+        assert!(second.get("token-a").await.unwrap().is_some());
+
+        first.remove("token-a").await.unwrap();
+        settle().await;
+
+        assert!(second.local.get("token-a").is_none());
+        assert!(second.get("token-a").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn updating_a_session_propagates_to_other_instances() {
+        let redis = Arc::new(FakeRedis::default());
+        let first = instance(&redis).await;
+        let second = instance(&redis).await;
+        let user = Uuid::new_v4();
+        let mut stored = session("token-a", None);
+
+        first.set("token-a", stored.clone()).await.unwrap();
+        assert_eq!(second.get("token-a").await.unwrap().unwrap().user_id, None);
+
+        stored.authenticate(user, None);
+        first.set("token-a", stored).await.unwrap();
+        settle().await;
+
+        assert!(second.local.get("token-a").is_none());
+        assert_eq!(second.get("token-a").await.unwrap().unwrap().user_id, Some(user));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn local_cache_is_bypassed_until_resubscribed() {
+        let redis = Arc::new(FakeRedis::default());
+        let first = instance(&redis).await;
+        let second = instance(&redis).await;
+        let user = Uuid::new_v4();
+
+        first.set("token-a", session("token-a", None)).await.unwrap();
+        assert!(second.get("token-a").await.unwrap().is_some());
+
+        redis.drop_subscriptions(true);
+        settle().await;
+        assert!(!second.local_enabled());
+        assert!(second.local.get("token-a").is_none());
+
+        // Invalidations published now never reach the second instance
+        let mut updated = session("token-a", None);
+        updated.id = second.get("token-a").await.unwrap().unwrap().id;
+        updated.authenticate(user, None);
+        first.set("token-a", updated).await.unwrap();
+        assert_eq!(second.get("token-a").await.unwrap().unwrap().user_id, Some(user));
+        assert_eq!(second.local.len(), 0);
+
+        redis.drop_subscriptions(false);
+        tokio::time::sleep(RESUBSCRIBE_DELAY * 2).await;
+        settle().await;
+        assert!(second.local_enabled());
+
+        assert!(second.get("token-a").await.unwrap().is_some());
+        assert!(second.local.get("token-a").is_some());
+        first.remove("token-a").await.unwrap();
+        settle().await;
+        assert!(second.local.get("token-a").is_none());
+    }
+}
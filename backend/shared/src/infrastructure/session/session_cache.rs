@@ -1,10 +1,37 @@
 use crate::domain::entities::Session;
+use crate::shared::AppResult;
+use async_trait::async_trait;
 use chrono::Utc;
 use lru::LruCache;
 use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+/// Session cache used by `SessionService`
+///
+/// Implemented by the in-process [`SessionCache`] and by
+/// `RedisSessionCache`, which shares sessions between service instances.
+#[async_trait]
+pub trait SessionCacheBackend: Send + Sync {
+    /// Get session by token
+    async fn get(&self, token: &str) -> AppResult<Option<Session>>;
+
+    /// Store session under its token
+    async fn set(&self, token: &str, session: Session) -> AppResult<()>;
+
+    /// Remove session by token
+    async fn remove(&self, token: &str) -> AppResult<()>;
+
+    /// Remove session by ID
+    async fn remove_by_id(&self, id: Uuid) -> AppResult<()>;
+
+    /// Remove every session of a user, returning how many were removed
+    async fn remove_by_user(&self, user_id: Uuid) -> AppResult<usize>;
+
+    /// Drop expired or inactive sessions, returning how many were removed
+    async fn cleanup_expired(&self) -> AppResult<usize>;
+}
+
 /// In-memory cache for active sessions
 /// Provides fast access to session data without hitting the database
 /// Uses LRU eviction to limit memory usage
@@ -60,6 +87,20 @@ impl SessionCache {
         }
     }
 
+    /// Remove every session of a user, returning how many were removed
+    pub fn remove_by_user(&self, user_id: Uuid) -> usize {
+        let mut sessions = self.sessions.lock().unwrap();
+        let keys_to_remove: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| session.user_id == Some(user_id))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys_to_remove {
+            sessions.pop(key);
+        }
+        keys_to_remove.len()
+    }
+
     /// Clean up expired sessions from cache
     pub fn cleanup_expired(&self) -> usize {
         let mut sessions = self.sessions.lock().unwrap();
@@ -109,3 +150,33 @@ impl Default for SessionCache {
     }
 }
 
+
+#[async_trait]
+impl SessionCacheBackend for SessionCache {
+    async fn get(&self, token: &str) -> AppResult<Option<Session>> {
+        Ok(SessionCache::get(self, token))
+    }
+
+    async fn set(&self, token: &str, session: Session) -> AppResult<()> {
+        SessionCache::set(self, token, session);
+        Ok(())
+    }
+
+    async fn remove(&self, token: &str) -> AppResult<()> {
+        SessionCache::remove(self, token);
+        Ok(())
+    }
+
+    async fn remove_by_id(&self, id: Uuid) -> AppResult<()> {
+        SessionCache::remove_by_id(self, id);
+        Ok(())
+    }
+
+    async fn remove_by_user(&self, user_id: Uuid) -> AppResult<usize> {
+        Ok(SessionCache::remove_by_user(self, user_id))
+    }
+
+    async fn cleanup_expired(&self) -> AppResult<usize> {
+        Ok(SessionCache::cleanup_expired(self))
+    }
+}
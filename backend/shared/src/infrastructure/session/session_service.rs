@@ -1,30 +1,86 @@
 use crate::domain::entities::Session;
-use crate::domain::repositories::SessionRepository;
-use crate::infrastructure::session::SessionCache;
+use crate::domain::repositories::{AuditLogEntry, AuditLogRepository, SessionRepository};
+use crate::infrastructure::session::SessionCacheBackend;
 use crate::shared::AppResult;
 use crate::config::settings::SessionConfig;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
 use std::net::IpAddr;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Audit record of a session ended to keep a user within
+/// `max_concurrent_sessions`
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionEvictedEvent {
+    pub user_id: Uuid,
+    /// Oldest session, now ended
+    pub session_id: Uuid,
+    /// Session whose authentication went over the limit
+    pub replaced_by: Uuid,
+    pub max_concurrent_sessions: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl SessionEvictedEvent {
+    fn to_audit_entry(&self) -> AuditLogEntry {
+        AuditLogEntry {
+            user_id: Some(self.user_id),
+            action: "session_evicted".to_string(),
+            resource: "session".to_string(),
+            resource_id: Some(self.session_id),
+            details: serde_json::to_value(self).unwrap_or_default(),
+        }
+    }
+}
+
 /// Service for managing session lifecycle
 pub struct SessionService {
     repository: Arc<dyn SessionRepository>,
-    cache: Arc<SessionCache>,
+    cache: Box<dyn SessionCacheBackend>,
     session_config: SessionConfig,
+    audit_log: Option<Arc<dyn AuditLogRepository>>,
 }
 
 impl SessionService {
     pub fn new(
         repository: Arc<dyn SessionRepository>,
-        cache: Arc<SessionCache>,
+        cache: Box<dyn SessionCacheBackend>,
         session_config: SessionConfig,
     ) -> Self {
         Self {
             repository,
             cache,
             session_config,
+            audit_log: None,
+        }
+    }
+
+    /// Record evicted sessions as `SessionEvictedEvent`s in `audit_log`
+    pub fn with_audit_log(mut self, audit_log: Arc<dyn AuditLogRepository>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+
+    // The cache is an accelerator: failures are logged and the database
+    // stays the source of truth.
+
+    async fn cache_get(&self, token: &str) -> Option<Session> {
+        self.cache.get(token).await.unwrap_or_else(|e| {
+            tracing::warn!("Session cache read failed: {}", e);
+            None
+        })
+    }
+
+    async fn cache_set(&self, token: &str, session: Session) {
+        if let Err(e) = self.cache.set(token, session).await {
+            tracing::warn!("Session cache write failed: {}", e);
+        }
+    }
+
+    async fn cache_remove(&self, token: &str) {
+        if let Err(e) = self.cache.remove(token).await {
+            tracing::warn!("Session cache removal failed: {}", e);
         }
     }
 
@@ -49,7 +105,7 @@ impl SessionService {
         app_device: &str,
     ) -> AppResult<Session> {
         // Try cache first
-        if let Some(session) = self.cache.get(session_token) {
+        if let Some(session) = self.cache_get(session_token).await {
             if session.is_active && !session.is_expired() {
                 // If app_type or app_device changed, update the session
                 if session.app_type != app_type || session.app_device != app_device {
@@ -58,7 +114,7 @@ impl SessionService {
                     updated_session.app_device = app_device.to_string();
                     let saved = self.repository.update(updated_session.clone()).await?;
                     let token = saved.session_token.clone();
-                    self.cache.set(&token, saved.clone());
+                    self.cache_set(&token, saved.clone()).await;
                     return Ok(saved);
                 }
                 return Ok(session);
@@ -77,7 +133,7 @@ impl SessionService {
                 session.update_activity();
                 let updated = self.repository.update(session.clone()).await?;
                 let token = updated.session_token.clone();
-                self.cache.set(&token, updated.clone());
+                self.cache_set(&token, updated.clone()).await;
                 return Ok(updated);
             }
         }
@@ -95,7 +151,7 @@ impl SessionService {
         );
 
         let created = self.repository.create(session.clone()).await?;
-        self.cache.set(session_token, created.clone());
+        self.cache_set(session_token, created.clone()).await;
         Ok(created)
     }
 
@@ -137,13 +193,18 @@ impl SessionService {
             session.app_device = app_device_val.to_string();
         }
 
+        // Sessions count against a user once they are linked to them
+        if session.user_id != Some(user_id) {
+            self.enforce_session_limit(user_id, session_id).await?;
+        }
+
         session.authenticate(user_id, organization_id);
         
         // Try to update - handle optimistic locking race conditions gracefully
         match self.repository.update(session.clone()).await {
             Ok(updated) => {
                 let session_token = updated.session_token.clone();
-                self.cache.set(&session_token, updated.clone());
+                self.cache_set(&session_token, updated.clone()).await;
                 Ok(updated)
             }
             Err(e) => {
@@ -156,7 +217,7 @@ impl SessionService {
                         // Try to fetch the updated session and return it
                         if let Ok(Some(updated_session)) = self.repository.find_by_id(session_id).await {
                             let session_token = updated_session.session_token.clone();
-                            self.cache.set(&session_token, updated_session.clone());
+                            self.cache_set(&session_token, updated_session.clone()).await;
                             return Ok(updated_session);
                         }
                     }
@@ -167,6 +228,44 @@ impl SessionService {
         }
     }
 
+    /// End the user's oldest sessions (by `created_at`) so that `new_session_id`
+    /// fits within `max_concurrent_sessions`
+    async fn enforce_session_limit(&self, user_id: Uuid, new_session_id: Uuid) -> AppResult<()> {
+        let limit = self.session_config.max_concurrent_sessions;
+        if limit == 0 {
+            return Ok(());
+        }
+        let active = self.repository.count_active_by_user(user_id).await? as usize;
+        if active < limit {
+            return Ok(());
+        }
+
+        let mut sessions = self.repository.find_active_by_user(user_id).await?;
+        sessions.retain(|s| s.id != new_session_id);
+        sessions.sort_by_key(|s| s.created_at);
+        let excess = (sessions.len() + 1).saturating_sub(limit);
+        for oldest in sessions.into_iter().take(excess) {
+            self.repository.end_session(oldest.id, Utc::now()).await?;
+            self.cache_remove(&oldest.session_token).await;
+
+            let event = SessionEvictedEvent {
+                user_id,
+                session_id: oldest.id,
+                replaced_by: new_session_id,
+                max_concurrent_sessions: limit,
+                timestamp: Utc::now(),
+            };
+            tracing::info!("Ended session {} of user {}: concurrent session limit of {} reached", oldest.id, user_id, limit);
+            if let Some(audit_log) = &self.audit_log {
+                // The session is already ended; a failed audit write must not undo it
+                if let Err(e) = audit_log.create(event.to_audit_entry()).await {
+                    e.log_with_operation(concat!(file!(), ":", line!()), "session_evicted_audit");
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Update session activity timestamp
     /// This is a best-effort operation that handles race conditions gracefully
     pub async fn update_activity(&self, session_id: Uuid) -> AppResult<()> {
@@ -190,7 +289,7 @@ impl SessionService {
         match self.repository.update(session.clone()).await {
             Ok(updated) => {
                 let session_token = updated.session_token.clone();
-                self.cache.set(&session_token, updated.clone());
+                self.cache_set(&session_token, updated.clone()).await;
                 Ok(())
             }
             Err(e) => {
@@ -221,14 +320,50 @@ impl SessionService {
             })?;
 
         self.repository.end_session(session_id, Utc::now()).await?;
-        self.cache.remove(&session.session_token);
+        self.cache_remove(&session.session_token).await;
+        Ok(())
+    }
+
+    /// End every active session of a user, on all devices
+    ///
+    /// Returns how many sessions were ended.
+    pub async fn invalidate_all_for_user(&self, user_id: Uuid) -> AppResult<u64> {
+        let sessions = self.repository.find_active_by_user(user_id).await?;
+        let ended_at = Utc::now();
+        for session in &sessions {
+            self.repository.end_session(session.id, ended_at).await?;
+        }
+        if let Err(e) = self.cache.remove_by_user(user_id).await {
+            tracing::warn!("Session cache removal failed: {}", e);
+        }
+        Ok(sessions.len() as u64)
+    }
+
+    /// Active sessions of a user, most recently active first
+    pub async fn list_active_for_user(&self, user_id: Uuid) -> AppResult<Vec<Session>> {
+        self.repository.find_active_by_user(user_id).await
+    }
+
+    /// End one session of a user (admin force-logout of a single device)
+    pub async fn end_user_session(&self, user_id: Uuid, session_id: Uuid) -> AppResult<()> {
+        let session = self
+            .repository
+            .find_by_id(session_id)
+            .await?
+            .filter(|s| s.user_id == Some(user_id) && s.is_active)
+            .ok_or_else(|| {
+                crate::shared::AppError::NotFound(format!("Active session {} not found for user {}", session_id, user_id))
+            })?;
+
+        self.repository.end_session(session.id, Utc::now()).await?;
+        self.cache_remove(&session.session_token).await;
         Ok(())
     }
 
     /// Get active session by token
     pub async fn get_active_session(&self, token: &str) -> AppResult<Option<Session>> {
         // Try cache first
-        if let Some(session) = self.cache.get(token) {
+        if let Some(session) = self.cache_get(token).await {
             if session.is_active && !session.is_expired() {
                 return Ok(Some(session));
             }
@@ -237,7 +372,7 @@ impl SessionService {
         // Try database
         if let Some(session) = self.repository.find_by_token(token).await? {
             if session.is_active && !session.is_expired() {
-                self.cache.set(token, session.clone());
+                self.cache_set(token, session.clone()).await;
                 return Ok(Some(session));
             }
         }
@@ -248,8 +383,144 @@ impl SessionService {
     /// Cleanup expired sessions (should be called periodically)
     pub async fn cleanup_expired(&self) -> AppResult<u64> {
         let count = self.repository.cleanup_expired().await?;
-        self.cache.cleanup_expired();
+        if let Err(e) = self.cache.cleanup_expired().await {
+            tracing::warn!("Session cache cleanup failed: {}", e);
+        }
         Ok(count)
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::session::SessionCache;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct InMemorySessionRepository {
+        sessions: Mutex<Vec<Session>>,
+    }
+
+    #[async_trait]
+    impl SessionRepository for InMemorySessionRepository {
+        async fn create(&self, session: Session) -> AppResult<Session> {
+            self.sessions.lock().unwrap().push(session.clone());
+            Ok(session)
+        }
+        async fn find_by_token(&self, token: &str) -> AppResult<Option<Session>> {
+            Ok(self.sessions.lock().unwrap().iter().find(|s| s.session_token == token).cloned())
+        }
+        async fn find_by_id(&self, id: Uuid) -> AppResult<Option<Session>> {
+            Ok(self.sessions.lock().unwrap().iter().find(|s| s.id == id).cloned())
+        }
+        async fn find_active_by_user(&self, user_id: Uuid) -> AppResult<Vec<Session>> {
+            let sessions = self.sessions.lock().unwrap();
+            Ok(sessions.iter().filter(|s| s.user_id == Some(user_id) && s.is_active).cloned().collect())
+        }
+        async fn find_active_by_user_and_app(&self, user_id: Uuid, app_type: &str) -> AppResult<Vec<Session>> {
+            let sessions = self.find_active_by_user(user_id).await?;
+            Ok(sessions.into_iter().filter(|s| s.app_type == app_type).collect())
+        }
+        async fn count_active_by_user(&self, user_id: Uuid) -> AppResult<u64> {
+            Ok(self.find_active_by_user(user_id).await?.len() as u64)
+        }
+        async fn update(&self, session: Session) -> AppResult<Session> {
+            let mut sessions = self.sessions.lock().unwrap();
+            if let Some(existing) = sessions.iter_mut().find(|s| s.id == session.id) {
+                *existing = session.clone();
+            }
+            Ok(session)
+        }
+        async fn end_session(&self, id: Uuid, ended_at: DateTime<Utc>) -> AppResult<()> {
+            let mut sessions = self.sessions.lock().unwrap();
+            if let Some(session) = sessions.iter_mut().find(|s| s.id == id) {
+                session.is_active = false;
+                session.ended_at = Some(ended_at);
+            }
+            Ok(())
+        }
+        async fn cleanup_expired(&self) -> AppResult<u64> { Ok(0) }
+    }
+
+    #[derive(Default)]
+    struct InMemoryAuditLog {
+        entries: Mutex<Vec<AuditLogEntry>>,
+    }
+
+    #[async_trait]
+    impl AuditLogRepository for InMemoryAuditLog {
+        async fn create(&self, entry: AuditLogEntry) -> AppResult<()> {
+            self.entries.lock().unwrap().push(entry);
+            Ok(())
+        }
+    }
+
+    fn session_config(max_concurrent_sessions: usize) -> SessionConfig {
+        SessionConfig {
+            admin_ui_ttl_hours: 8,
+            client_ui_ttl_hours: 24,
+            api_ttl_hours: 1,
+            admin_ui_cors_origins: vec![],
+            client_ui_cors_origins: vec![],
+            cache_max_entries: 100,
+            redis_url: None,
+            login_max_attempts: 5,
+            login_lockout_duration_minutes: 15,
+            max_concurrent_sessions,
+        }
+    }
+
+    /// Ghost session for `token`, then logged in as `user_id`
+    async fn log_in(service: &SessionService, token: &str, user_id: Uuid) -> Session {
+        let ghost = service
+            .create_or_get_session(token, "127.0.0.1".parse().unwrap(), None, "admin-ui", "web")
+            .await
+            .unwrap();
+        service.authenticate_session(ghost.id, user_id, None, None, None).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sixth_session_evicts_oldest() {
+        let repository = Arc::new(InMemorySessionRepository::default());
+        let audit_log = Arc::new(InMemoryAuditLog::default());
+        let service = SessionService::new(repository.clone(), Box::new(SessionCache::new()), session_config(5))
+            .with_audit_log(audit_log.clone());
+        let user_id = Uuid::new_v4();
+
+        let mut sessions = Vec::new();
+        for i in 1..=6 {
+            sessions.push(log_in(&service, &format!("token-{}", i), user_id).await);
+        }
+
+        let active = service.list_active_for_user(user_id).await.unwrap();
+        assert_eq!(active.len(), 5);
+        assert!(active.iter().all(|s| s.id != sessions[0].id));
+        assert!(service.get_active_session("token-1").await.unwrap().is_none());
+        let newest = service.get_active_session("token-6").await.unwrap().unwrap();
+        assert_eq!(newest.user_id, Some(user_id));
+
+        let entries = audit_log.entries.lock().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].action, "session_evicted");
+        assert_eq!(entries[0].resource_id, Some(sessions[0].id));
+        assert_eq!(entries[0].details["replaced_by"], serde_json::json!(sessions[5].id));
+    }
+
+    #[tokio::test]
+    async fn test_end_user_session_requires_owner() {
+        let repository = Arc::new(InMemorySessionRepository::default());
+        let service = SessionService::new(repository, Box::new(SessionCache::new()), session_config(5));
+        let user_id = Uuid::new_v4();
+        let session = log_in(&service, "token", user_id).await;
+
+        let other_user = Uuid::new_v4();
+        assert!(matches!(
+            service.end_user_session(other_user, session.id).await,
+            Err(crate::shared::AppError::NotFound(_))
+        ));
+        service.end_user_session(user_id, session.id).await.unwrap();
+        assert!(service.get_active_session("token").await.unwrap().is_none());
+    }
+}
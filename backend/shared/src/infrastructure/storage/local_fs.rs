@@ -1,28 +1,138 @@
-use crate::infrastructure::storage::storage_trait::Storage;
+//! Files under a base directory, each with a `<file>.sha256` sidecar holding
+//! the hex SHA-256 digest of its contents as written. Reads are checked
+//! against the sidecar, so corrupted or tampered files are reported rather
+//! than returned; files written before checksums were recorded (no sidecar)
+//! are returned unchecked.
+
+use crate::infrastructure::storage::storage_trait::{PresignedUrl, Storage, StorageError};
+use crate::infrastructure::storage::url_signer::{PresignedMethod, StorageUrlSigner};
 use crate::shared::AppResult;
 use async_trait::async_trait;
-use std::path::PathBuf;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 
+/// Suffix of checksum sidecar files
+const CHECKSUM_SUFFIX: &str = ".sha256";
+
+/// Result of checking one stored file against its checksum
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityStatus {
+    Ok,
+    /// The file changed since it was written
+    Mismatch,
+    /// The file has no checksum to check against
+    MissingChecksum,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IntegrityReport {
+    pub key: String,
+    pub status: IntegrityStatus,
+    /// Digest recorded when the file was written
+    pub expected: Option<String>,
+    /// Digest of the file as stored now
+    pub actual: String,
+}
+
 pub struct LocalFsStorage {
     base_path: PathBuf,
+    url_signer: Option<StorageUrlSigner>,
 }
 
 impl LocalFsStorage {
     pub fn new(path: &str) -> Self {
         Self {
             base_path: PathBuf::from(path),
+            url_signer: None,
+        }
+    }
+
+    /// Presign URLs to the API's storage endpoint
+    pub fn with_url_signer(mut self, url_signer: StorageUrlSigner) -> Self {
+        self.url_signer = Some(url_signer);
+        self
+    }
+
+    fn presign(&self, key: &str, method: PresignedMethod, expires_in: Duration) -> AppResult<PresignedUrl> {
+        match &self.url_signer {
+            Some(signer) => signer.sign(key, method, expires_in),
+            None => Err(crate::shared::AppError::Storage(
+                "Local storage has no URL signer configured for presigned URLs".to_string(),
+            )),
         }
     }
 
     fn get_path(&self, key: &str) -> PathBuf {
         self.base_path.join(key)
     }
+
+    fn checksum_path(&self, key: &str) -> PathBuf {
+        self.base_path.join(format!("{}{}", key, CHECKSUM_SUFFIX))
+    }
+
+    /// Check every stored file against its checksum
+    pub async fn verify_all(&self) -> AppResult<Vec<IntegrityReport>> {
+        let mut reports = Vec::new();
+        let mut dirs = vec![self.base_path.clone()];
+
+        while let Some(dir) = dirs.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(crate::shared::AppError::Storage(format!("Failed to read directory: {}", e))),
+            };
+
+            while let Some(entry) = entries.next_entry().await
+                .map_err(|e| crate::shared::AppError::Storage(format!("Failed to read entry: {}", e)))? {
+                let path = entry.path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if !is_checksum_file(&path) {
+                    if let Some(key) = self.key_of(&path) {
+                        reports.push(self.verify(key).await?);
+                    }
+                }
+            }
+        }
+
+        reports.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(reports)
+    }
+
+    async fn verify(&self, key: String) -> AppResult<IntegrityReport> {
+        let data = fs::read(self.get_path(&key)).await
+            .map_err(|e| crate::shared::AppError::Storage(format!("Failed to read file: {}", e)))?;
+        let actual = sha256_hex(&data);
+        let expected = self.checksum(&key).await?;
+        let status = match &expected {
+            None => IntegrityStatus::MissingChecksum,
+            Some(expected) if *expected == actual => IntegrityStatus::Ok,
+            Some(_) => IntegrityStatus::Mismatch,
+        };
+        Ok(IntegrityReport { key, status, expected, actual })
+    }
+
+    /// Key of a file under the base directory, `/`-separated
+    fn key_of(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.base_path).ok()?;
+        let parts: Option<Vec<&str>> = relative.components().map(|c| c.as_os_str().to_str()).collect();
+        Some(parts?.join("/"))
+    }
 }
 
 #[async_trait]
 impl Storage for LocalFsStorage {
     async fn put(&self, key: &str, data: &[u8]) -> AppResult<()> {
+        if key.ends_with(CHECKSUM_SUFFIX) {
+            return Err(crate::shared::AppError::Validation(format!(
+                "Keys ending in {} are reserved for checksums",
+                CHECKSUM_SUFFIX
+            )));
+        }
         let path = self.get_path(key);
         
         // Create parent directories if needed
@@ -33,6 +143,8 @@ impl Storage for LocalFsStorage {
 
         fs::write(&path, data).await
             .map_err(|e| crate::shared::AppError::Storage(format!("Failed to write file: {}", e)))?;
+        fs::write(self.checksum_path(key), sha256_hex(data)).await
+            .map_err(|e| crate::shared::AppError::Storage(format!("Failed to write checksum: {}", e)))?;
         
         Ok(())
     }
@@ -40,18 +152,31 @@ impl Storage for LocalFsStorage {
     async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
         let path = self.get_path(key);
         
-        match fs::read(&path).await {
-            Ok(data) => Ok(Some(data)),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
-            Err(e) => Err(crate::shared::AppError::Storage(format!("Failed to read file: {}", e))),
+        let data = match fs::read(&path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(crate::shared::AppError::Storage(format!("Failed to read file: {}", e))),
+        };
+
+        if let Some(expected) = self.checksum(key).await? {
+            let actual = sha256_hex(&data);
+            if actual != expected {
+                return Err(StorageError::ChecksumMismatch { key: key.to_string(), expected, actual }.into());
+            }
         }
+        Ok(Some(data))
     }
 
     async fn delete(&self, key: &str) -> AppResult<()> {
         let path = self.get_path(key);
         fs::remove_file(&path).await
             .map_err(|e| crate::shared::AppError::Storage(format!("Failed to delete file: {}", e)))?;
-        Ok(())
+        match fs::remove_file(self.checksum_path(key)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(crate::shared::AppError::Storage(
+                format!("Failed to delete checksum: {}", e),
+            )),
+            _ => Ok(()),
+        }
     }
 
     async fn list(&self, prefix: &str) -> AppResult<Vec<String>> {
@@ -65,12 +190,95 @@ impl Storage for LocalFsStorage {
             while let Some(entry) = entries.next_entry().await
                 .map_err(|e| crate::shared::AppError::Storage(format!("Failed to read entry: {}", e)))? {
                 if let Some(file_name) = entry.file_name().to_str() {
-                    keys.push(format!("{}/{}", prefix, file_name));
+                    if !file_name.ends_with(CHECKSUM_SUFFIX) {
+                        keys.push(format!("{}/{}", prefix, file_name));
+                    }
                 }
             }
         }
 
         Ok(keys)
     }
+    async fn checksum(&self, key: &str) -> AppResult<Option<String>> {
+        match fs::read_to_string(self.checksum_path(key)).await {
+            Ok(checksum) => Ok(Some(checksum.trim().to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(crate::shared::AppError::Storage(format!("Failed to read checksum: {}", e))),
+        }
+    }
+
+    async fn presign_put(&self, key: &str, expires_in: Duration) -> AppResult<PresignedUrl> {
+        self.presign(key, PresignedMethod::Put, expires_in)
+    }
+
+    async fn presign_get(&self, key: &str, expires_in: Duration) -> AppResult<PresignedUrl> {
+        self.presign(key, PresignedMethod::Get, expires_in)
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
 }
 
+fn is_checksum_file(path: &Path) -> bool {
+    path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.ends_with(CHECKSUM_SUFFIX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::AppError;
+
+    fn storage_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("local-fs-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_file_is_not_returned() {
+        let dir = storage_dir();
+        let storage = LocalFsStorage::new(&dir.to_string_lossy());
+        storage.put("documents/scan.pdf", b"%PDF-1.7 scan").await.unwrap();
+        assert_eq!(storage.get("documents/scan.pdf").await.unwrap().unwrap(), b"%PDF-1.7 scan");
+
+        let path = dir.join("documents/scan.pdf");
+        let mut data = std::fs::read(&path).unwrap();
+        data[0] = b'X';
+        std::fs::write(&path, data).unwrap();
+
+        let err = storage.get("documents/scan.pdf").await.unwrap_err();
+        assert!(matches!(
+            err,
+            AppError::StorageIntegrity(StorageError::ChecksumMismatch { ref key, .. }) if key == "documents/scan.pdf"
+        ));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_verify_all_reports_each_file() {
+        let dir = storage_dir();
+        let storage = LocalFsStorage::new(&dir.to_string_lossy());
+        storage.put("a/intact.txt", b"intact").await.unwrap();
+        storage.put("a/b/tampered.txt", b"original").await.unwrap();
+        std::fs::write(dir.join("a/b/tampered.txt"), b"tampered").unwrap();
+        std::fs::write(dir.join("legacy.txt"), b"written before checksums").unwrap();
+
+        let reports = storage.verify_all().await.unwrap();
+        let statuses: Vec<(&str, &IntegrityStatus)> =
+            reports.iter().map(|report| (report.key.as_str(), &report.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("a/b/tampered.txt", &IntegrityStatus::Mismatch),
+                ("a/intact.txt", &IntegrityStatus::Ok),
+                ("legacy.txt", &IntegrityStatus::MissingChecksum),
+            ]
+        );
+        assert_eq!(reports[1].expected.as_deref(), Some(reports[1].actual.as_str()));
+
+        // Sidecars are neither listed nor left behind
+        assert_eq!(storage.list("a").await.unwrap().len(), 2);
+        storage.delete("a/intact.txt").await.unwrap();
+        assert!(!dir.join("a/intact.txt.sha256").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -4,11 +4,13 @@ pub mod gcs;
 pub mod azure_blob;
 pub mod local_fs;
 pub mod encrypted_local_fs;
+pub mod url_signer;
 
-pub use storage_trait::Storage;
-pub use s3::S3Storage;
+pub use storage_trait::{PresignedUrl, Storage, StorageError};
+pub use s3::{MinioStorage, S3Storage, S3StorageConfig};
 pub use gcs::GcsStorage;
 pub use azure_blob::AzureBlobStorage;
-pub use local_fs::LocalFsStorage;
+pub use local_fs::{IntegrityReport, IntegrityStatus, LocalFsStorage};
 pub use encrypted_local_fs::{EncryptedLocalFsStorage, EncryptionScope};
+pub use url_signer::{PresignedMethod, StorageUrlSigner};
 
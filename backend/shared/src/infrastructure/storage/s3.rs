@@ -1,41 +1,293 @@
-use crate::infrastructure::storage::storage_trait::Storage;
-use crate::shared::AppResult;
+use crate::infrastructure::storage::storage_trait::{PresignedUrl, Storage};
+use crate::shared::{AppError, AppResult};
 use async_trait::async_trait;
+use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+use aws_sdk_s3::presigning::PresigningConfig;
+use aws_sdk_s3::primitives::ByteStream;
+use aws_sdk_s3::types::{BucketLocationConstraint, CreateBucketConfiguration};
+use aws_sdk_s3::Client;
+use chrono::Utc;
+use std::time::Duration;
+
+/// Region S3 creates buckets in when no location constraint is given
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// Connection settings for AWS S3 or an S3-compatible object store
+#[derive(Debug, Clone)]
+pub struct S3StorageConfig {
+    /// S3 API of a self-hosted or third-party store (MinIO, DigitalOcean
+    /// Spaces); None for AWS
+    pub endpoint_url: Option<String>,
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Address buckets as `{endpoint}/{bucket}` rather than as a
+    /// `{bucket}.{endpoint}` subdomain, as MinIO requires
+    pub force_path_style: bool,
+}
 
 pub struct S3Storage {
-    // AWS S3 implementation placeholder
+    client: Client,
+    bucket: String,
+    region: String,
 }
 
+/// [`S3Storage`] pointed at a MinIO server with `endpoint_url` and
+/// `force_path_style`
+pub type MinioStorage = S3Storage;
+
 impl S3Storage {
-    pub fn new(_region: &str, _bucket: &str, _access_key: &str, _secret_key: &str) -> Self {
-        Self {}
+    pub fn new(config: S3StorageConfig) -> Self {
+        let mut builder = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new(config.region.clone()))
+            .credentials_provider(Credentials::new(
+                config.access_key_id,
+                config.secret_access_key,
+                None,
+                None,
+                "static",
+            ))
+            .force_path_style(config.force_path_style);
+        if let Some(endpoint_url) = config.endpoint_url {
+            builder = builder.endpoint_url(endpoint_url);
+        }
+        Self {
+            client: Client::from_conf(builder.build()),
+            bucket: config.bucket,
+            region: config.region,
+        }
+    }
+
+    fn presigning_config(expires_in: Duration) -> AppResult<PresigningConfig> {
+        PresigningConfig::expires_in(expires_in)
+            .map_err(|e| AppError::Validation(format!("Invalid presigned URL lifetime: {}", e)))
+    }
+
+    fn presigned_url(uri: &str, expires_in: Duration) -> AppResult<PresignedUrl> {
+        let expires_in = chrono::Duration::from_std(expires_in)
+            .map_err(|e| AppError::Validation(format!("Invalid presigned URL lifetime: {}", e)))?;
+        Ok(PresignedUrl {
+            url: uri.to_string(),
+            expires_at: Utc::now() + expires_in,
+        })
     }
 }
 
 #[async_trait]
 impl Storage for S3Storage {
-    async fn put(&self, _key: &str, _data: &[u8]) -> AppResult<()> {
-        Err(crate::shared::AppError::Storage(
-            "S3 storage not yet implemented".to_string(),
-        ))
+    async fn put(&self, key: &str, data: &[u8]) -> AppResult<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .body(ByteStream::from(data.to_vec()))
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(format!("Failed to upload {} to S3: {}", key, e)))?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        let output = match self.client.get_object().bucket(&self.bucket).key(key).send().await {
+            Ok(output) => output,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+            Err(e) => return Err(AppError::Storage(format!("Failed to download {} from S3: {}", key, e))),
+        };
+        let data = output
+            .body
+            .collect()
+            .await
+            .map_err(|e| AppError::Storage(format!("Failed to download {} from S3: {}", key, e)))?;
+        Ok(Some(data.into_bytes().to_vec()))
     }
 
-    async fn get(&self, _key: &str) -> AppResult<Option<Vec<u8>>> {
-        Err(crate::shared::AppError::Storage(
-            "S3 storage not yet implemented".to_string(),
-        ))
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(format!("Failed to delete {} from S3: {}", key, e)))?;
+        Ok(())
     }
 
-    async fn delete(&self, _key: &str) -> AppResult<()> {
-        Err(crate::shared::AppError::Storage(
-            "S3 storage not yet implemented".to_string(),
-        ))
+    async fn list(&self, prefix: &str) -> AppResult<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(prefix)
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .map_err(|e| AppError::Storage(format!("Failed to list {} in S3: {}", prefix, e)))?;
+            keys.extend(output.contents().iter().filter_map(|object| object.key().map(str::to_string)));
+
+            continuation_token = output.next_continuation_token().map(str::to_string);
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(keys)
+    }
+
+    async fn presign_put(&self, key: &str, expires_in: Duration) -> AppResult<PresignedUrl> {
+        let request = self
+            .client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(Self::presigning_config(expires_in)?)
+            .await
+            .map_err(|e| AppError::Storage(format!("Failed to presign upload of {}: {}", key, e)))?;
+        Self::presigned_url(request.uri(), expires_in)
     }
 
-    async fn list(&self, _prefix: &str) -> AppResult<Vec<String>> {
-        Err(crate::shared::AppError::Storage(
-            "S3 storage not yet implemented".to_string(),
-        ))
+    async fn presign_get(&self, key: &str, expires_in: Duration) -> AppResult<PresignedUrl> {
+        let request = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .presigned(Self::presigning_config(expires_in)?)
+            .await
+            .map_err(|e| AppError::Storage(format!("Failed to presign download of {}: {}", key, e)))?;
+        Self::presigned_url(request.uri(), expires_in)
+    }
+
+    async fn create_bucket_if_not_exists(&self) -> AppResult<()> {
+        match self.client.head_bucket().bucket(&self.bucket).send().await {
+            Ok(_) => return Ok(()),
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_not_found()) => {}
+            Err(e) => return Err(AppError::Storage(format!("Failed to check S3 bucket {}: {}", self.bucket, e))),
+        }
+
+        let mut request = self.client.create_bucket().bucket(&self.bucket);
+        // Naming the default region as a location constraint is rejected
+        if self.region != DEFAULT_REGION {
+            request = request.create_bucket_configuration(
+                CreateBucketConfiguration::builder()
+                    .location_constraint(BucketLocationConstraint::from(self.region.as_str()))
+                    .build(),
+            );
+        }
+        request
+            .send()
+            .await
+            .map_err(|e| AppError::Storage(format!("Failed to create S3 bucket {}: {}", self.bucket, e)))?;
+        tracing::info!(bucket = %self.bucket, "Created S3 bucket");
+        Ok(())
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage() -> S3Storage {
+        S3Storage::new(S3StorageConfig {
+            endpoint_url: None,
+            region: "us-east-1".to_string(),
+            bucket: "patient-documents".to_string(),
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            force_path_style: false,
+        })
+    }
+
+    fn minio_storage(endpoint_url: &str, bucket: &str) -> MinioStorage {
+        S3Storage::new(S3StorageConfig {
+            endpoint_url: Some(endpoint_url.to_string()),
+            region: "us-east-1".to_string(),
+            bucket: bucket.to_string(),
+            access_key_id: "minioadmin".to_string(),
+            secret_access_key: "minioadmin".to_string(),
+            force_path_style: true,
+        })
+    }
+
+    // Presigning happens offline, so no S3 endpoint is needed
+    #[tokio::test]
+    async fn test_presigned_put_url_contains_key() {
+        let url = storage()
+            .presign_put("documents/1234/scan.pdf", Duration::from_secs(900))
+            .await
+            .unwrap();
+
+        assert!(url.url.starts_with("https://patient-documents.s3.us-east-1.amazonaws.com/documents/1234/scan.pdf?"));
+        assert!(url.url.contains("X-Amz-Expires=900"));
+        assert!(url.url.contains("X-Amz-Signature="));
+        assert!(url.expires_at > Utc::now());
+    }
+
+    #[tokio::test]
+    async fn test_presigned_get_url_contains_key() {
+        let url = storage()
+            .presign_get("documents/1234/scan.pdf", Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(url.url.contains("/documents/1234/scan.pdf?"));
+        assert!(url.url.contains("X-Amz-Expires=60"));
+    }
+
+    #[tokio::test]
+    async fn test_presigned_url_lifetime_is_limited() {
+        // SigV4 presigned URLs last at most a week
+        assert!(storage()
+            .presign_put("documents/1234/scan.pdf", Duration::from_secs(8 * 24 * 3600))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_custom_endpoint_uses_path_style_urls() {
+        let url = minio_storage("http://localhost:9000", "patient-documents")
+            .presign_get("documents/1234/scan.pdf", Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(url.url.starts_with("http://localhost:9000/patient-documents/documents/1234/scan.pdf?"));
+    }
+
+    #[tokio::test]
+    #[ignore] // Requires Docker to start a MinIO container
+    async fn test_minio_round_trip() {
+        use testcontainers_modules::minio::MinIO;
+        use testcontainers_modules::testcontainers::runners::AsyncRunner;
+
+        let container = MinIO::default().start().await.unwrap();
+        let endpoint_url = format!(
+            "http://{}:{}",
+            container.get_host().await.unwrap(),
+            container.get_host_port_ipv4(9000).await.unwrap()
+        );
+        let storage = minio_storage(&endpoint_url, "patient-documents");
+
+        storage.create_bucket_if_not_exists().await.unwrap();
+        // Already exists, so nothing to do
+        storage.create_bucket_if_not_exists().await.unwrap();
+
+        storage.put("documents/1234/scan.pdf", b"%PDF-1.7").await.unwrap();
+        storage.put("documents/1234/notes.txt", b"notes").await.unwrap();
+        storage.put("documents/5678/scan.pdf", b"other").await.unwrap();
+        assert_eq!(
+            storage.get("documents/1234/scan.pdf").await.unwrap().as_deref(),
+            Some(&b"%PDF-1.7"[..])
+        );
+
+        let mut keys = storage.list("documents/1234/").await.unwrap();
+        keys.sort();
+        assert_eq!(keys, vec!["documents/1234/notes.txt", "documents/1234/scan.pdf"]);
+
+        storage.delete("documents/1234/scan.pdf").await.unwrap();
+        assert!(storage.get("documents/1234/scan.pdf").await.unwrap().is_none());
+        assert_eq!(storage.list("documents/1234/").await.unwrap(), vec!["documents/1234/notes.txt"]);
+    }
+}
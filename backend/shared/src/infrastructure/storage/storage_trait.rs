@@ -1,5 +1,25 @@
 use async_trait::async_trait;
-use crate::shared::AppResult;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+use crate::shared::{AppError, AppResult};
+
+/// Time-limited URL a client can upload or download one object with,
+/// without credentials of its own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PresignedUrl {
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Storage failures callers may need to tell apart
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum StorageError {
+    /// The stored bytes no longer match the checksum recorded when they were
+    /// written: the file was corrupted or tampered with
+    #[error("checksum mismatch for {key}: expected {expected}, got {actual}")]
+    ChecksumMismatch { key: String, expected: String, actual: String },
+}
 
 #[async_trait]
 pub trait Storage: Send + Sync {
@@ -7,5 +27,26 @@ pub trait Storage: Send + Sync {
     async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>>;
     async fn delete(&self, key: &str) -> AppResult<()>;
     async fn list(&self, prefix: &str) -> AppResult<Vec<String>>;
-}
 
+    /// URL that uploads `key` with an HTTP PUT of the object's bytes
+    async fn presign_put(&self, key: &str, _expires_in: Duration) -> AppResult<PresignedUrl> {
+        Err(AppError::Storage(format!("Presigned uploads are not supported by this storage backend ({})", key)))
+    }
+
+    /// URL that downloads `key` with an HTTP GET
+    async fn presign_get(&self, key: &str, _expires_in: Duration) -> AppResult<PresignedUrl> {
+        Err(AppError::Storage(format!("Presigned downloads are not supported by this storage backend ({})", key)))
+    }
+
+    /// Hex SHA-256 digest recorded when `key` was written, None when the
+    /// backend does not record one
+    async fn checksum(&self, _key: &str) -> AppResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Create the bucket objects are stored in when it does not exist yet;
+    /// a no-op for backends without buckets
+    async fn create_bucket_if_not_exists(&self) -> AppResult<()> {
+        Ok(())
+    }
+}
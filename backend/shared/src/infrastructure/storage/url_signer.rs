@@ -0,0 +1,140 @@
+//! Presigned URLs served by this API
+//!
+//! Backends without presigning of their own (the local filesystem) hand out
+//! URLs to the API's `/v1/storage/presigned` endpoint instead. Each carries a
+//! short-lived HS256 JWT naming the object key and the HTTP method it
+//! allows; the endpoint checks it with [`StorageUrlSigner::verify`] before
+//! reading or writing the object.
+//!
+//! The signing key is derived from the secret rather than being the secret
+//! itself, so a presigned URL token is never accepted as an access token.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::infrastructure::storage::storage_trait::PresignedUrl;
+use crate::shared::{AppError, AppResult};
+
+/// Path of the endpoint presigned URLs point at, relative to the API base URL
+pub const PRESIGNED_PATH: &str = "/v1/storage/presigned";
+
+const AUDIENCE: &str = "storage";
+
+/// HTTP method a presigned URL allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum PresignedMethod {
+    Get,
+    Put,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PresignedClaims {
+    key: String,
+    method: PresignedMethod,
+    aud: String,
+    exp: i64,
+}
+
+/// Issues and checks presigned URLs to the API's storage endpoint
+#[derive(Clone)]
+pub struct StorageUrlSigner {
+    /// API base URL, without a trailing slash
+    base_url: String,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl StorageUrlSigner {
+    /// Sign URLs under `base_url` (e.g. `https://ehr.example.org/api`) with a
+    /// key derived from `secret`
+    pub fn new(base_url: &str, secret: &str) -> Self {
+        let key = Sha256::digest(format!("presigned-storage-url:{}", secret).as_bytes());
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            encoding_key: EncodingKey::from_secret(&key),
+            decoding_key: DecodingKey::from_secret(&key),
+        }
+    }
+
+    /// URL allowing `method` on `key` until `expires_in` from now
+    pub fn sign(&self, key: &str, method: PresignedMethod, expires_in: Duration) -> AppResult<PresignedUrl> {
+        let expires_at = Utc::now()
+            + chrono::Duration::from_std(expires_in)
+                .map_err(|e| AppError::Validation(format!("Invalid presigned URL lifetime: {}", e)))?;
+        let claims = PresignedClaims {
+            key: key.to_string(),
+            method,
+            aud: AUDIENCE.to_string(),
+            exp: expires_at.timestamp(),
+        };
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| AppError::Internal(format!("Failed to sign presigned URL: {}", e)))?;
+
+        Ok(PresignedUrl {
+            url: format!("{}{}?token={}", self.base_url, PRESIGNED_PATH, token),
+            expires_at,
+        })
+    }
+
+    /// Object key a presigned URL's token allows `method` on
+    pub fn verify(&self, token: &str, method: PresignedMethod) -> AppResult<String> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[AUDIENCE]);
+        validation.leeway = 0;
+        let claims = decode::<PresignedClaims>(token, &self.decoding_key, &validation)
+            .map_err(|e| AppError::Authentication(format!("Invalid presigned URL: {}", e)))?
+            .claims;
+
+        if claims.method != method {
+            return Err(AppError::Authorization(format!(
+                "Presigned URL does not allow {:?} requests",
+                method
+            )));
+        }
+        Ok(claims.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> StorageUrlSigner {
+        StorageUrlSigner::new("http://localhost:4117/api/", "test-secret")
+    }
+
+    fn token(url: &PresignedUrl) -> &str {
+        url.url.split_once("?token=").unwrap().1
+    }
+
+    #[test]
+    fn test_signed_url_round_trips() {
+        let url = signer().sign("documents/a/scan.pdf", PresignedMethod::Put, Duration::from_secs(900)).unwrap();
+        assert!(url.url.starts_with("http://localhost:4117/api/v1/storage/presigned?token="));
+        assert!(url.expires_at > Utc::now());
+
+        assert_eq!(signer().verify(token(&url), PresignedMethod::Put).unwrap(), "documents/a/scan.pdf");
+        assert!(signer().verify(token(&url), PresignedMethod::Get).is_err());
+    }
+
+    #[test]
+    fn test_other_secret_and_expired_tokens_are_rejected() {
+        let url = signer().sign("documents/a/scan.pdf", PresignedMethod::Get, Duration::from_secs(900)).unwrap();
+        let other = StorageUrlSigner::new("http://localhost:4117/api", "other-secret");
+        assert!(other.verify(token(&url), PresignedMethod::Get).is_err());
+
+        let expired = PresignedClaims {
+            key: "documents/a/scan.pdf".to_string(),
+            method: PresignedMethod::Get,
+            aud: AUDIENCE.to_string(),
+            exp: Utc::now().timestamp() - 10,
+        };
+        let expired = encode(&Header::new(Algorithm::HS256), &expired, &signer().encoding_key).unwrap();
+        assert!(signer().verify(&expired, PresignedMethod::Get).is_err());
+    }
+}
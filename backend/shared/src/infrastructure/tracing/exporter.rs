@@ -0,0 +1,56 @@
+//! OTLP span export and propagator setup
+
+use opentelemetry::propagation::TextMapCompositePropagator;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+
+/// Collector endpoint, e.g. `http://otel-collector:4317`; export is off when unset
+pub const OTLP_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+const DEFAULT_SERVICE_NAME: &str = "health-v1";
+
+/// Install W3C trace context and Jaeger propagation as the global propagator
+///
+/// Extraction accepts either header format; injection writes both.
+pub fn init_propagator() {
+    global::set_text_map_propagator(TextMapCompositePropagator::new(vec![
+        Box::new(TraceContextPropagator::new()),
+        Box::new(opentelemetry_jaeger_propagator::Propagator::new()),
+    ]));
+}
+
+/// Build a tracer that batches spans to the OTLP collector
+///
+/// Returns `None` when `OTEL_EXPORTER_OTLP_ENDPOINT` is not set or the
+/// exporter can't be built. Must be called from within a Tokio runtime.
+pub fn otlp_tracer() -> Option<Tracer> {
+    let endpoint = std::env::var(OTLP_ENDPOINT_ENV).ok().filter(|e| !e.is_empty())?;
+
+    let exporter = match SpanExporter::builder().with_tonic().with_endpoint(endpoint).build() {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            // The logger isn't installed yet
+            eprintln!("Failed to build OTLP span exporter, tracing export disabled: {}", e);
+            return None;
+        }
+    };
+
+    let service_name = std::env::var("OTEL_SERVICE_NAME")
+        .unwrap_or_else(|_| DEFAULT_SERVICE_NAME.to_string());
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new(vec![KeyValue::new("service.name", service_name.clone())]))
+        .build();
+    let tracer = provider.tracer(service_name);
+    global::set_tracer_provider(provider);
+    Some(tracer)
+}
+
+/// Flush buffered spans; call before the process exits
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
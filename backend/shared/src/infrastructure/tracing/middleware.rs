@@ -0,0 +1,121 @@
+//! Axum middleware that continues the caller's trace
+
+use axum::extract::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use opentelemetry::trace::TraceContextExt;
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use super::propagation::extract_context;
+
+/// Open the request span as a child of the incoming trace context
+///
+/// The span records `trace_id` so it appears on every log line, and the span
+/// context is placed in the request extensions for the auth middleware to put
+/// on `RequestContext`. Install it outermost so the whole request is covered.
+pub async fn tracing_middleware(mut request: Request, next: Next) -> Response {
+    let parent = extract_context(request.headers());
+    let span = tracing::info_span!(
+        "http_request",
+        otel.name = %format!("{} {}", request.method(), request.uri().path()),
+        http.method = %request.method(),
+        http.target = %request.uri().path(),
+        trace_id = tracing::field::Empty,
+    );
+    span.set_parent(parent.clone());
+
+    // Without an OpenTelemetry layer the span has no context of its own; the
+    // caller's is still the right trace to log
+    let mut span_context = span.context().span().span_context().clone();
+    if !span_context.is_valid() {
+        span_context = parent.span().span_context().clone();
+    }
+    if span_context.is_valid() {
+        span.record("trace_id", tracing::field::display(span_context.trace_id()));
+        request.extensions_mut().insert(span_context);
+    }
+
+    next.run(request).instrument(span).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use axum::{body::Body, routing::get, Router};
+    use opentelemetry::trace::TracerProvider as _;
+    use tower::ServiceExt;
+    use tracing_subscriber::layer::SubscriberExt;
+    use crate::infrastructure::tracing::{init_propagator, InjectTraceContext};
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const PARENT_SPAN_ID: &str = "00f067aa0ba902b7";
+
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogBuffer {
+        type Writer = LogBuffer;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// Stands in for a handler calling rustyvault-service / yottadb-api
+    async fn downstream_call() -> String {
+        tracing::info!("calling downstream");
+        let outbound = reqwest::Client::new()
+            .get("http://yottadb-api:8080/health")
+            .with_trace_context()
+            .build()
+            .unwrap();
+        outbound.headers().get("traceparent")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_incoming_trace_reaches_logs_and_outbound_calls() {
+        init_propagator();
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder().build();
+        let logs = LogBuffer::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")))
+            .with(tracing_subscriber::fmt::layer().json().with_writer(logs.clone()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = Router::new()
+            .route("/downstream", get(downstream_call))
+            .layer(axum::middleware::from_fn(tracing_middleware));
+        let request = Request::builder()
+            .uri("/downstream")
+            .header("traceparent", format!("00-{}-{}-01", TRACE_ID, PARENT_SPAN_ID))
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        // Outbound call continues the trace under the request span
+        let traceparent = String::from_utf8(body.to_vec()).unwrap();
+        assert!(traceparent.starts_with(&format!("00-{}-", TRACE_ID)), "{}", traceparent);
+        assert!(!traceparent.contains(PARENT_SPAN_ID));
+
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let line = output.lines().find(|line| line.contains("calling downstream")).unwrap();
+        assert!(line.contains(&format!("\"trace_id\":\"{}\"", TRACE_ID)), "{}", line);
+    }
+}
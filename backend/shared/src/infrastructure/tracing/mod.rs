@@ -0,0 +1,16 @@
+//! Distributed tracing across api-service, rustyvault-service and yottadb-api
+//!
+//! Incoming W3C `traceparent`/`tracestate` (or Jaeger `uber-trace-id`) headers
+//! parent the request span, the span context is carried on `RequestContext`,
+//! and outbound `reqwest` calls forward it. Spans are exported over OTLP when
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+
+pub mod exporter;
+pub mod middleware;
+pub mod propagation;
+
+pub use opentelemetry::trace::SpanContext;
+
+pub use exporter::{init_propagator, otlp_tracer, shutdown, OTLP_ENDPOINT_ENV};
+pub use middleware::tracing_middleware;
+pub use propagation::{current_context, current_trace_id, extract_context, inject_context, InjectTraceContext};
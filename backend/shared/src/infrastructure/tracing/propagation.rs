@@ -0,0 +1,94 @@
+//! Trace context extraction and injection over HTTP headers
+
+use axum::http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::trace::{TraceContextExt, TraceId};
+use opentelemetry::{global, Context};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use crate::shared::RequestContext;
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(&value)) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+/// Parent context carried by incoming request headers
+pub fn extract_context(headers: &HeaderMap) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+}
+
+/// OpenTelemetry context of the work in progress
+///
+/// Prefers the current tracing span. Falls back to the span context stored on
+/// the task's `RequestContext` when no OpenTelemetry layer is installed or the
+/// caller runs outside the request span.
+pub fn current_context() -> Context {
+    let cx = tracing::Span::current().context();
+    if cx.span().span_context().is_valid() {
+        return cx;
+    }
+    match RequestContext::current().and_then(|ctx| ctx.span_context) {
+        Some(span_context) => Context::new().with_remote_span_context(span_context),
+        None => cx,
+    }
+}
+
+/// Trace ID of the work in progress, if it is part of a trace
+pub fn current_trace_id() -> Option<TraceId> {
+    let span_context = current_context().span().span_context().clone();
+    span_context.is_valid().then(|| span_context.trace_id())
+}
+
+/// Write the current trace context into outbound request headers
+pub fn inject_context(headers: &mut HeaderMap) {
+    let cx = current_context();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&cx, &mut HeaderInjector(headers)));
+}
+
+/// Forward the current trace context on an outbound `reqwest` call
+pub trait InjectTraceContext {
+    fn with_trace_context(self) -> Self;
+}
+
+impl InjectTraceContext for reqwest::RequestBuilder {
+    fn with_trace_context(self) -> Self {
+        let mut headers = HeaderMap::new();
+        inject_context(&mut headers);
+        self.headers(headers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infrastructure::tracing::init_propagator;
+
+    #[test]
+    fn test_extracts_jaeger_header() {
+        init_propagator();
+        let mut headers = HeaderMap::new();
+        headers.insert("uber-trace-id", HeaderValue::from_static("4bf92f3577b34da6a3ce929d0e0e4736:00f067aa0ba902b7:0:1"));
+
+        let cx = extract_context(&headers);
+        let span_context = cx.span().span_context().clone();
+        assert!(span_context.is_remote());
+        assert_eq!(span_context.trace_id().to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    }
+}
@@ -0,0 +1,108 @@
+//! ICD-10-CM code lookup
+//!
+//! Codes are checked against `icd10cm_codes.txt.gz`, a gzipped flat file in
+//! the layout of the CMS `icd10cm-codes-<year>.txt` release: one billable code
+//! per line, without its dot and padded to 8 columns, followed by the
+//! description. To refresh it from a CMS release:
+//!
+//! ```text
+//! gzip -9c icd10cm-codes-2025.txt > icd10cm_codes.txt.gz
+//! ```
+//!
+//! The file is decompressed and indexed on first use.
+
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::OnceLock;
+
+static ICD10CM_CODES_GZ: &[u8] = include_bytes!("icd10cm_codes.txt.gz");
+
+/// A known ICD-10-CM code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icd10Entry {
+    /// Canonical form, with the dot after the category (e.g. `E11.9`)
+    pub code: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Icd10Error {
+    #[error("{0} is not a valid ICD-10-CM code")]
+    InvalidCode(String),
+}
+
+/// Looks codes up in the embedded ICD-10-CM index
+pub struct Icd10Validator;
+
+impl Icd10Validator {
+    /// Entry for `code`, which may be lowercase and with or without its dot
+    pub fn validate(code: &str) -> Result<Icd10Entry, Icd10Error> {
+        let normalized = normalize(code);
+        index()
+            .get(&normalized)
+            .map(|description| Icd10Entry {
+                code: with_dot(&normalized),
+                description: description.clone(),
+            })
+            .ok_or_else(|| Icd10Error::InvalidCode(code.trim().to_string()))
+    }
+}
+
+/// Uppercase, without dots or surrounding whitespace
+fn normalize(code: &str) -> String {
+    code.trim().chars().filter(|c| *c != '.').flat_map(char::to_uppercase).collect()
+}
+
+/// `E119` -> `E11.9`; three-character categories have no dot
+fn with_dot(code: &str) -> String {
+    match code.get(3..) {
+        Some(rest) if !rest.is_empty() => format!("{}.{}", &code[..3], rest),
+        _ => code.to_string(),
+    }
+}
+
+/// Dotless code -> description
+fn index() -> &'static HashMap<String, String> {
+    static INDEX: OnceLock<HashMap<String, String>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let mut text = String::new();
+        if let Err(e) = GzDecoder::new(ICD10CM_CODES_GZ).read_to_string(&mut text) {
+            tracing::error!("Invalid embedded ICD-10-CM index: {}", e);
+            return HashMap::new();
+        }
+        text.lines()
+            .filter_map(|line| {
+                let (code, description) = line.split_once(' ')?;
+                Some((code.to_string(), description.trim().to_string()))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_code() {
+        let entry = Icd10Validator::validate("E11.9").unwrap();
+        assert_eq!(entry.code, "E11.9");
+        assert_eq!(entry.description, "Type 2 diabetes mellitus without complications");
+    }
+
+    #[test]
+    fn test_code_is_normalized() {
+        assert_eq!(Icd10Validator::validate("e119").unwrap().code, "E11.9");
+        assert_eq!(Icd10Validator::validate(" i10 ").unwrap().code, "I10");
+    }
+
+    #[test]
+    fn test_unknown_code_is_invalid() {
+        assert_eq!(
+            Icd10Validator::validate("ZZZZZ"),
+            Err(Icd10Error::InvalidCode("ZZZZZ".to_string()))
+        );
+        assert!(Icd10Validator::validate("").is_err());
+    }
+}
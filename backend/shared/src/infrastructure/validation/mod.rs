@@ -1,5 +1,11 @@
 use crate::shared::{AppError, AppResult};
 
+mod icd10;
+mod password;
+
+pub use icd10::{Icd10Entry, Icd10Error, Icd10Validator};
+pub use password::{HibpResult, PasswordPolicy, PasswordValidator, HIBP_API_URL};
+
 /// Validates that a string field is not empty after trimming whitespace.
 ///
 /// This utility eliminates 27+ duplicate `trim().is_empty()` validations
@@ -73,10 +79,8 @@ pub fn validate_username(username: &str) -> AppResult<()> {
     Ok(())
 }
 
+/// Checks the default [`PasswordPolicy`]; use [`PasswordValidator`] for a
+/// configured policy or the breached-password check
 pub fn validate_password(password: &str) -> AppResult<()> {
-    if password.len() < 8 {
-        return Err(AppError::Validation("Password must be at least 8 characters long".to_string()));
-    }
-
-    Ok(())
+    PasswordPolicy::default().check(password)
 }
\ No newline at end of file
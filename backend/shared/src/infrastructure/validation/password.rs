@@ -0,0 +1,235 @@
+//! Password policy and breached-password check
+//!
+//! Breached passwords are looked up with the Have I Been Pwned range API
+//! (k-anonymity): only the first five hex characters of the password's SHA-1
+//! hash leave the process, and the matching suffix is searched locally.
+
+use crate::shared::{AppError, AppResult};
+use sha1::{Digest, Sha1};
+use std::time::Duration;
+
+/// Default Have I Been Pwned Passwords API (v3)
+pub const HIBP_API_URL: &str = "https://api.pwnedpasswords.com";
+
+/// How long to wait for HIBP before allowing the password anyway
+const HIBP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Hex characters of the SHA-1 hash sent to HIBP
+const HIBP_PREFIX_LEN: usize = 5;
+
+/// Rules a new password must satisfy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: u8,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_special: bool,
+    /// Reject passwords found in Have I Been Pwned
+    pub check_hibp: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_uppercase: false,
+            require_digit: false,
+            require_special: false,
+            check_hibp: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Local checks only; see [`PasswordValidator::validate`] for the HIBP check
+    pub fn check(&self, password: &str) -> AppResult<()> {
+        if password.chars().count() < usize::from(self.min_length) {
+            return Err(AppError::Validation(format!(
+                "Password must be at least {} characters long",
+                self.min_length
+            )));
+        }
+        if self.require_uppercase && !password.chars().any(char::is_uppercase) {
+            return Err(AppError::Validation("Password must contain an uppercase letter".to_string()));
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            return Err(AppError::Validation("Password must contain a digit".to_string()));
+        }
+        if self.require_special && password.chars().all(char::is_alphanumeric) {
+            return Err(AppError::Validation("Password must contain a special character".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Result of a Have I Been Pwned lookup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HibpResult {
+    pub pwned: bool,
+    /// Times the password appears in known breaches
+    pub breach_count: u64,
+}
+
+/// Applies a [`PasswordPolicy`], including the optional HIBP check
+pub struct PasswordValidator {
+    policy: PasswordPolicy,
+    client: reqwest::Client,
+    hibp_url: String,
+}
+
+impl PasswordValidator {
+    pub fn new(policy: PasswordPolicy) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(HIBP_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        Self {
+            policy,
+            client,
+            hibp_url: HIBP_API_URL.to_string(),
+        }
+    }
+
+    /// Query another HIBP-compatible range API (e.g. a self-hosted mirror)
+    pub fn with_hibp_url(mut self, url: impl Into<String>) -> Self {
+        self.hibp_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn policy(&self) -> &PasswordPolicy {
+        &self.policy
+    }
+
+    /// Check `password` against the policy
+    ///
+    /// When HIBP cannot be reached the password is allowed: an outage must
+    /// not block account creation.
+    pub async fn validate(&self, password: &str) -> AppResult<()> {
+        self.policy.check(password)?;
+        if !self.policy.check_hibp {
+            return Ok(());
+        }
+
+        match self.check_hibp(password).await {
+            Ok(result) if result.pwned => Err(AppError::Validation(format!(
+                "Password has appeared in {} data breaches; choose a different password",
+                result.breach_count
+            ))),
+            Ok(_) => Ok(()),
+            Err(e) => {
+                tracing::warn!("Skipping breached password check: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    /// Look the password up in Have I Been Pwned
+    pub async fn check_hibp(&self, password: &str) -> AppResult<HibpResult> {
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(HIBP_PREFIX_LEN);
+
+        let response = self.client
+            .get(format!("{}/range/{}", self.hibp_url, prefix))
+            // Pads the response with fake suffixes (count 0) so its size says nothing about the prefix
+            .header("Add-Padding", "true")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::Internal(format!("HIBP request failed: {}", e)))?;
+        let body = response.text().await
+            .map_err(|e| AppError::Internal(format!("HIBP response unreadable: {}", e)))?;
+
+        let breach_count = Self::breach_count(&body, suffix);
+        Ok(HibpResult {
+            pwned: breach_count > 0,
+            breach_count,
+        })
+    }
+
+    /// Count for `suffix` in a range response of `SUFFIX:COUNT` lines
+    fn breach_count(body: &str, suffix: &str) -> u64 {
+        body.lines()
+            .filter_map(|line| line.trim().split_once(':'))
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(suffix))
+            .and_then(|(_, count)| count.trim().parse().ok())
+            .unwrap_or(0)
+    }
+}
+
+impl Default for PasswordValidator {
+    fn default() -> Self {
+        Self::new(PasswordPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, routing::get, Router};
+
+    /// SHA-1 of "password": 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+    const PASSWORD_SUFFIX: &str = "1E4C9B93F3F0682250B6CF8331B7EE68FD8";
+
+    async fn range(Path(prefix): Path<String>) -> String {
+        if prefix != "5BAA6" {
+            return "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n".to_string();
+        }
+        format!("003D68EB55068C33ACE09247EE4C639306B:3\r\n{}:9659365\r\n011053FD0102E94D6AE2F8B83D76FAF94F6:0\r\n", PASSWORD_SUFFIX)
+    }
+
+    /// Start a HIBP range endpoint on a random local port
+    async fn mock_hibp() -> String {
+        let app = Router::new().route("/range/{prefix}", get(range));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await
+            .unwrap_or_else(|e| panic!("failed to bind mock HIBP: {}", e));
+        let addr = listener.local_addr()
+            .unwrap_or_else(|e| panic!("mock HIBP has no address: {}", e));
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        format!("http://{}", addr)
+    }
+
+    fn validator(hibp_url: &str) -> PasswordValidator {
+        let policy = PasswordPolicy { check_hibp: true, ..PasswordPolicy::default() };
+        PasswordValidator::new(policy).with_hibp_url(hibp_url)
+    }
+
+    #[tokio::test]
+    async fn breached_password_is_rejected() {
+        let validator = validator(&mock_hibp().await);
+
+        let result = validator.check_hibp("password").await.unwrap();
+        assert_eq!(result, HibpResult { pwned: true, breach_count: 9659365 });
+
+        let err = validator.validate("password").await.unwrap_err();
+        assert!(matches!(err, AppError::Validation(message) if message.contains("9659365")));
+        assert!(validator.validate("correct horse battery staple").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn unreachable_hibp_allows_password() {
+        // Nothing listens on port 9 (discard) locally
+        let validator = validator("http://127.0.0.1:9");
+
+        assert!(validator.check_hibp("password").await.is_err());
+        assert!(validator.validate("password").await.is_ok());
+    }
+
+    #[test]
+    fn policy_requires_configured_character_classes() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            require_uppercase: true,
+            require_digit: true,
+            require_special: true,
+            check_hibp: false,
+        };
+
+        assert!(policy.check("Sh0rt!").is_err());
+        assert!(policy.check("nouppercase1!").is_err());
+        assert!(policy.check("NoDigitsHere!").is_err());
+        assert!(policy.check("NoSpecial123").is_err());
+        assert!(policy.check("Enough-Chars1").is_ok());
+    }
+}
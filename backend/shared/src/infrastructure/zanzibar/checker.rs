@@ -1,10 +1,93 @@
 use crate::infrastructure::zanzibar::{RelationshipStore, GraphPermissionChecker, GraphCache};
 use crate::domain::repositories::RelationshipRepository;
-use crate::shared::AppResult;
+use crate::domain::entities::{decode_zookie, Relationship};
+use crate::domain::services::authorization_service::{PermissionExpander, PERMISSION_SEPARATOR};
+use chrono::{DateTime, Utc};
+use crate::shared::{AppError, AppResult};
+use serde::Serialize;
 use std::collections::HashSet;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Why a permission check was denied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DenialReason {
+    /// No relationship grants the relation on any traversed path
+    NoRelationship,
+    /// A matching relationship exists but is expired, revoked or not yet valid
+    RelationshipExpired,
+    /// A valid matching relationship exists but the check still denied it
+    /// (e.g. organization scoping or a stale graph cache)
+    PolicyDenied,
+}
+
+/// Result of `PermissionChecker::explain_denial`
+#[derive(Debug, Clone, Serialize)]
+pub struct DenialExplanation {
+    pub reason: DenialReason,
+    /// Relationship paths evaluated, in traversal order
+    pub checked_paths: Vec<String>,
+    /// Most similar relationship found along the traversed paths
+    pub closest_match: Option<Relationship>,
+}
+
+/// Relation on [`AUTHORIZATION_OBJECT`] allowing [`PermissionChecker::explain`]
+///
+/// Explanations reveal how relationships are structured, so they are not
+/// available to regular users.
+pub const EXPLAIN_PERMISSIONS_CAPABILITY: &str = "explain_permissions";
+
+/// Object that authorization administration capabilities are granted on
+pub const AUTHORIZATION_OBJECT: &str = "system:authorization";
+
+/// Kind of rule evaluated by one [`ExplainStep`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplainStepType {
+    /// The subject's own tuple for the requested relation (or `*@*`)
+    This,
+    /// A membership edge followed to another subject (`member`, `has_role`)
+    ComputedUserset,
+    /// A tuple for the requested relation held by a subject reached through
+    /// computed usersets
+    DirectTuple,
+}
+
+/// One rule evaluated by [`PermissionChecker::explain`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExplainStep {
+    pub step_type: ExplainStepType,
+    pub subject: String,
+    pub relation: String,
+    pub object: String,
+    /// Whether the tuple exists and is valid
+    pub matched: bool,
+}
+
+impl ExplainStep {
+    fn new(step_type: ExplainStepType, subject: &str, relation: &str, object: &str, matched: bool) -> Self {
+        Self {
+            step_type,
+            subject: subject.to_string(),
+            relation: relation.to_string(),
+            object: object.to_string(),
+            matched,
+        }
+    }
+}
+
+/// Result of `PermissionChecker::explain`
+///
+/// Paths are combined by union: the check is allowed if any path matches.
+/// When allowed, `path` is the first matching path; when denied, it is
+/// every step evaluated, in order.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExplainResult {
+    pub allowed: bool,
+    pub path: Vec<ExplainStep>,
+}
+
 pub struct PermissionChecker {
     store: RelationshipStore,
     graph_cache: Option<Arc<GraphCache>>,
@@ -46,8 +129,18 @@ impl PermissionChecker {
     /// 4. Group membership: user#member@group → group#relation@resource
     /// 5. Group role inheritance: user#member@group → group#has_role@role → role#relation@resource
     /// Returns true if ANY path grants permission (union, not override)
-    pub async fn check(&self, user: &str, relation: &str, object: &str) -> AppResult<bool> {
-        self.check_with_organization(user, relation, object, None).await
+    ///
+    /// Hierarchical relations (`ehr:patients:view`) are also granted by wildcard
+    /// relations on the same object (`ehr:*`), see `PermissionExpander`.
+    ///
+    /// `zookie` is a consistency token from `RelationshipRepository::write_tuples`.
+    /// When given, cached results older than that write are ignored so the check
+    /// sees at least that snapshot.
+    pub async fn check(&self, user: &str, relation: &str, object: &str, zookie: Option<&str>) -> AppResult<bool> {
+        let min_snapshot = zookie
+            .map(|z| decode_zookie(z).ok_or_else(|| AppError::Validation(format!("Invalid zookie: {}", z))))
+            .transpose()?;
+        self.check_at_snapshot(user, relation, object, None, min_snapshot).await
     }
     
     /// Check if user has relation on object with organization scoping
@@ -59,6 +152,17 @@ impl PermissionChecker {
         relation: &str,
         object: &str,
         organization_id: Option<Uuid>,
+    ) -> AppResult<bool> {
+        self.check_at_snapshot(user, relation, object, organization_id, None).await
+    }
+
+    async fn check_at_snapshot(
+        &self,
+        user: &str,
+        relation: &str,
+        object: &str,
+        organization_id: Option<Uuid>,
+        min_snapshot: Option<DateTime<Utc>>,
     ) -> AppResult<bool> {
         // First, check for wildcard permission (super admin bypass)
         // This must be checked first to ensure super admins have access to everything
@@ -67,14 +171,25 @@ impl PermissionChecker {
             return Ok(true);
         }
 
-        // Use graph-based checker if available and enabled
-        if self.should_use_graph() {
+        // Use graph-based checker if available and enabled; the graph only
+        // knows exact relations, so wildcard-expandable ones skip it
+        if self.should_use_graph() && !relation.contains(PERMISSION_SEPARATOR) {
             if let Some(cache) = &self.graph_cache {
+                // Cached check results are not organization-scoped
+                if organization_id.is_none() {
+                    if let Some(allowed) = cache.get_check_as_of(user, relation, object, min_snapshot) {
+                        return Ok(allowed);
+                    }
+                }
+
                 // Try to get cached graph
-                if let Some(graph) = cache.get_cached() {
+                if let Some(graph) = cache.get_cached_as_of(min_snapshot) {
                     let graph_checker = GraphPermissionChecker::new(graph);
                     if let Ok(result) = graph_checker.check(user, relation, object) {
                         // TODO: Add organization filtering to graph checker
+                        if organization_id.is_none() {
+                            cache.put_check(user, relation, object, result);
+                        }
                         return Ok(result);
                     }
                     // Fall through to database-based check if graph check fails
@@ -84,7 +199,7 @@ impl PermissionChecker {
         
         // Fallback to database-based check (original implementation)
         // 1. Direct user permission check (with organization filtering)
-        if self.store.check_with_organization(user, relation, object, organization_id).await? {
+        if self.grants(user, relation, object, organization_id).await? {
             return Ok(true);
         }
 
@@ -101,7 +216,7 @@ impl PermissionChecker {
                 // User has a role, check if role has the relation
                 let role_str = &rel.object; // e.g., "role:admin"
                 // Check role permission with same organization context
-                if self.store.check_with_organization(role_str, relation, object, organization_id).await? {
+                if self.grants(role_str, relation, object, organization_id).await? {
                     return Ok(true);
                 }
             }
@@ -113,7 +228,7 @@ impl PermissionChecker {
                 let group_str = &rel.object; // e.g., "group:doctors"
                 
                 // 4a. Direct group permission (with organization context)
-                if self.store.check_with_organization(group_str, relation, object, organization_id).await? {
+                if self.grants(group_str, relation, object, organization_id).await? {
                     return Ok(true);
                 }
                 
@@ -133,7 +248,7 @@ impl PermissionChecker {
                 for group_rel in &group_relationships {
                     if group_rel.relation == "has_role" {
                         let role_str = &group_rel.object;
-                        if self.store.check_with_organization(role_str, relation, object, organization_id).await? {
+                        if self.grants(role_str, relation, object, organization_id).await? {
                             return Ok(true);
                         }
                     }
@@ -143,6 +258,34 @@ impl PermissionChecker {
 
         Ok(false)
     }
+
+    /// Whether `subject` itself holds `relation` on `object`, either exactly or
+    /// through a wildcard relation such as `ehr:*`
+    async fn grants(
+        &self,
+        subject: &str,
+        relation: &str,
+        object: &str,
+        organization_id: Option<Uuid>,
+    ) -> AppResult<bool> {
+        if self.store.check_with_organization(subject, relation, object, organization_id).await? {
+            return Ok(true);
+        }
+        // Only hierarchical relations can be matched by a wildcard
+        if !relation.contains(PERMISSION_SEPARATOR) {
+            return Ok(false);
+        }
+
+        let relationships = match organization_id {
+            Some(org_id) => self.store.get_valid_relationships_by_org(subject, org_id).await?,
+            None => self.store.get_valid_relationships(subject).await?,
+        };
+        Ok(relationships.iter().any(|r| {
+            r.object == object
+                && PermissionExpander::is_wildcard(&r.relation)
+                && PermissionExpander::matches(&r.relation, relation)
+        }))
+    }
     
     /// Check permission using graph (for complex queries)
     pub async fn check_with_graph(
@@ -158,7 +301,7 @@ impl PermissionChecker {
             graph_checker.check(user, relation, object)
         } else {
             // Fallback to regular check
-            self.check(user, relation, object).await
+            self.check(user, relation, object, None).await
         }
     }
     
@@ -168,7 +311,7 @@ impl PermissionChecker {
     pub async fn can_access_app(&self, user: &str, app_name: &str) -> AppResult<bool> {
         // Legacy format for backward compatibility
         let app_str = format!("app:{}", app_name);
-        self.check(user, "can_access", &app_str).await
+        self.check(user, "can_access", &app_str, None).await
     }
     
     /// Check if user can access a specific app within an organization
@@ -241,14 +384,306 @@ impl PermissionChecker {
         Ok(permissions)
     }
 
+    /// Explain why `subject` does not have `relation` on `object`
+    /// Walks the same paths as `check` (direct, role, group, group role), but
+    /// also looks at invalid relationships so expired grants can be reported.
+    pub async fn explain_denial(
+        &self,
+        subject_type: &str,
+        subject_id: &str,
+        object_type: &str,
+        object_id: &str,
+        relation: &str,
+    ) -> AppResult<DenialExplanation> {
+        let subject = format!("{}:{}", subject_type, subject_id);
+        let object = format!("{}:{}", object_type, object_id);
+
+        if self.check(&subject, relation, &object, None).await? {
+            return Err(AppError::Validation(format!(
+                "{} has {} on {}; permission is not denied",
+                subject, relation, object
+            )));
+        }
+
+        let mut checked_paths = vec![format!("{}#*@*", subject)];
+        let mut seen = Vec::new();
+
+        // Each entry is (entity, path prefix leading to it)
+        let mut frontier = vec![(subject.clone(), String::new())];
+        let mut visited = HashSet::new();
+
+        while let Some((entity, prefix)) = frontier.pop() {
+            if !visited.insert(entity.clone()) {
+                continue;
+            }
+
+            checked_paths.push(format!("{}{}#{}@{}", prefix, entity, relation, object));
+
+            let relationships = self.store.get_relationships(&entity).await?;
+            for rel in &relationships {
+                // Follow role and group edges the same way `check` does:
+                // user → role, user → group, group → role
+                let follow = rel.is_valid()
+                    && ((rel.relation == "has_role" && !entity.starts_with("role:"))
+                        || (rel.relation == "member" && entity == subject));
+                if follow {
+                    frontier.push((
+                        rel.object.clone(),
+                        format!("{}{}#{}@{} → ", prefix, entity, rel.relation, rel.object),
+                    ));
+                }
+            }
+            seen.extend(relationships);
+        }
+
+        let exact: Vec<&Relationship> = seen
+            .iter()
+            .filter(|r| r.relation == relation && r.object == object)
+            .collect();
+
+        let reason = if exact.iter().any(|r| r.is_valid()) {
+            DenialReason::PolicyDenied
+        } else if !exact.is_empty() {
+            DenialReason::RelationshipExpired
+        } else {
+            DenialReason::NoRelationship
+        };
+
+        let closest_match = seen
+            .iter()
+            .filter(|r| r.relation != "has_role" && r.relation != "member")
+            .map(|r| (match_score(r, relation, &object, object_type), r))
+            .filter(|(score, _)| *score > 0)
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, r)| r.clone());
+
+        Ok(DenialExplanation {
+            reason,
+            checked_paths,
+            closest_match,
+        })
+    }
+
+    /// Whether `user` may call [`Self::explain`]
+    pub async fn can_explain(&self, user: &str) -> AppResult<bool> {
+        self.check(user, EXPLAIN_PERMISSIONS_CAPABILITY, AUTHORIZATION_OBJECT, None).await
+    }
+
+    /// Trace how `check` evaluates `subject#relation@object`
+    ///
+    /// Follows the same paths as the database-backed check (wildcard, direct,
+    /// role, group, group role). Expired or revoked membership edges are
+    /// reported as unmatched and not followed. Callers must check
+    /// [`Self::can_explain`] first.
+    pub async fn explain(&self, subject: &str, object: &str, relation: &str) -> AppResult<ExplainResult> {
+        use ExplainStepType::{ComputedUserset, DirectTuple, This};
+
+        let wildcard = ExplainStep::new(This, subject, "*", "*", self.store.check(subject, "*", "*").await?);
+        if wildcard.matched {
+            return Ok(ExplainResult { allowed: true, path: vec![wildcard] });
+        }
+        let direct = ExplainStep::new(This, subject, relation, object, self.grants(subject, relation, object, None).await?);
+        if direct.matched {
+            return Ok(ExplainResult { allowed: true, path: vec![direct] });
+        }
+        let mut trace = vec![wildcard, direct];
+
+        let relationships = self.store.get_relationships(subject).await?;
+        for edge in ["has_role", "member"] {
+            for rel in relationships.iter().filter(|r| r.relation == edge) {
+                let hop = ExplainStep::new(ComputedUserset, subject, edge, &rel.object, rel.is_valid());
+                if !hop.matched {
+                    trace.push(hop);
+                    continue;
+                }
+                let granted = self.grants(&rel.object, relation, object, None).await?;
+                let tuple = ExplainStep::new(DirectTuple, &rel.object, relation, object, granted);
+                if granted {
+                    return Ok(ExplainResult { allowed: true, path: vec![hop, tuple] });
+                }
+                trace.extend([hop.clone(), tuple]);
+                if edge == "has_role" {
+                    continue;
+                }
+
+                // Group role inheritance: group#has_role@role → role#relation@object
+                let group_relationships = self.store.get_relationships(&rel.object).await?;
+                for group_rel in group_relationships.iter().filter(|r| r.relation == "has_role") {
+                    let role_hop =
+                        ExplainStep::new(ComputedUserset, &rel.object, "has_role", &group_rel.object, group_rel.is_valid());
+                    if !role_hop.matched {
+                        trace.push(role_hop);
+                        continue;
+                    }
+                    let granted = self.grants(&group_rel.object, relation, object, None).await?;
+                    let tuple = ExplainStep::new(DirectTuple, &group_rel.object, relation, object, granted);
+                    if granted {
+                        return Ok(ExplainResult { allowed: true, path: vec![hop, role_hop, tuple] });
+                    }
+                    trace.extend([role_hop, tuple]);
+                }
+            }
+        }
+
+        Ok(ExplainResult { allowed: false, path: trace })
+    }
+
     /// Batch check multiple permissions
     pub async fn check_batch(&self, checks: Vec<(String, String, String)>) -> AppResult<Vec<bool>> {
         let mut results = Vec::new();
         for (user, relation, object) in checks {
-            let result = self.check(&user, &relation, &object).await?;
+            let result = self.check(&user, &relation, &object, None).await?;
             results.push(result);
         }
         Ok(results)
     }
 }
 
+
+/// Similarity between a relationship and the requested relation/object
+fn match_score(rel: &Relationship, relation: &str, object: &str, object_type: &str) -> u8 {
+    let mut score = 0;
+    if rel.object == object {
+        score += 4;
+    } else if rel.object.split(':').next() == Some(object_type) {
+        score += 1;
+    }
+    if rel.relation == relation {
+        score += 2;
+    }
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::entities::{RelationshipKey, RelationshipWrite, WriteResponse};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct InMemoryRelationshipRepository {
+        relationships: Mutex<Vec<Relationship>>,
+    }
+
+    #[async_trait]
+    impl RelationshipRepository for InMemoryRelationshipRepository {
+        async fn create(&self, relationship: Relationship) -> AppResult<Relationship> {
+            self.relationships.lock().unwrap().push(relationship.clone());
+            Ok(relationship)
+        }
+        async fn update(&self, relationship: Relationship) -> AppResult<Relationship> { Ok(relationship) }
+        async fn find_by_id(&self, _id: Uuid) -> AppResult<Option<Relationship>> { Ok(None) }
+        async fn find_by_user(&self, user: &str) -> AppResult<Vec<Relationship>> {
+            Ok(self.relationships.lock().unwrap().iter().filter(|r| r.user == user).cloned().collect())
+        }
+        async fn find_by_object(&self, _object: &str) -> AppResult<Vec<Relationship>> { Ok(vec![]) }
+        async fn find_by_user_and_relation(&self, _user: &str, _relation: &str) -> AppResult<Vec<Relationship>> { Ok(vec![]) }
+        async fn find_by_user_object_relation(&self, user: &str, object: &str, relation: &str) -> AppResult<Option<Relationship>> {
+            self.find_by_user_object_relation_org(user, object, relation, None).await
+        }
+        async fn delete(&self, _id: Uuid) -> AppResult<()> { Ok(()) }
+        async fn delete_by_tuple(&self, _user: &str, _relation: &str, _object: &str) -> AppResult<()> { Ok(()) }
+        async fn soft_delete(&self, _id: Uuid, _deleted_by: Option<Uuid>) -> AppResult<()> { Ok(()) }
+        async fn list_all(&self) -> AppResult<Vec<Relationship>> { Ok(self.relationships.lock().unwrap().clone()) }
+        async fn find_roles_for_user(&self, _user: &str) -> AppResult<Vec<Relationship>> { Ok(vec![]) }
+        async fn delete_expired(&self) -> AppResult<u64> { Ok(0) }
+        async fn write_tuples(&self, _writes: Vec<RelationshipWrite>) -> AppResult<WriteResponse> {
+            Err(AppError::Internal("Not supported".to_string()))
+        }
+        async fn delete_tuples(&self, _deletes: Vec<RelationshipKey>) -> AppResult<()> { Ok(()) }
+        async fn write_batch(&self, _tuples: Vec<Relationship>) -> AppResult<u64> { Ok(0) }
+        async fn delete_batch(&self, _keys: Vec<RelationshipKey>) -> AppResult<u64> { Ok(0) }
+        async fn find_by_user_and_org(&self, _user: &str, _organization_id: Uuid) -> AppResult<Vec<Relationship>> { Ok(vec![]) }
+        async fn find_by_organization(&self, _organization_id: Uuid) -> AppResult<Vec<Relationship>> { Ok(vec![]) }
+        async fn find_by_user_object_relation_org(
+            &self,
+            user: &str,
+            object: &str,
+            relation: &str,
+            organization_id: Option<Uuid>,
+        ) -> AppResult<Option<Relationship>> {
+            Ok(self.relationships.lock().unwrap().iter()
+                .find(|r| {
+                    r.user == user
+                        && r.object == object
+                        && r.relation == relation
+                        && (organization_id.is_none() || r.organization_id == organization_id)
+                })
+                .cloned())
+        }
+    }
+
+    fn tuple(user: &str, relation: &str, object: &str) -> Relationship {
+        Relationship::new(user.to_string(), relation.to_string(), object.to_string())
+    }
+
+    /// alice → group:ward_7 → role:charge_nurse → can_view@patient:1; bob
+    /// can_view@patient:1 directly
+    fn checker(extra: Vec<Relationship>) -> PermissionChecker {
+        let repository = InMemoryRelationshipRepository::default();
+        repository.relationships.lock().unwrap().extend(
+            [
+                tuple("user:alice", "member", "group:ward_7"),
+                tuple("group:ward_7", "has_role", "role:charge_nurse"),
+                tuple("role:charge_nurse", "can_view", "patient:1"),
+                tuple("user:bob", "can_view", "patient:1"),
+            ]
+            .into_iter()
+            .chain(extra),
+        );
+        PermissionChecker::new(RelationshipStore::new(Box::new(repository)))
+    }
+
+    fn hops(result: &ExplainResult) -> Vec<(ExplainStepType, &str, &str, &str, bool)> {
+        result.path.iter()
+            .map(|s| (s.step_type, s.subject.as_str(), s.relation.as_str(), s.object.as_str(), s.matched))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_explain_shows_every_hop_of_inherited_permission() {
+        let result = checker(vec![]).explain("user:alice", "patient:1", "can_view").await.unwrap();
+
+        assert!(result.allowed);
+        assert_eq!(hops(&result), vec![
+            (ExplainStepType::ComputedUserset, "user:alice", "member", "group:ward_7", true),
+            (ExplainStepType::ComputedUserset, "group:ward_7", "has_role", "role:charge_nurse", true),
+            (ExplainStepType::DirectTuple, "role:charge_nurse", "can_view", "patient:1", true),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_explain_direct_permission_is_a_single_step() {
+        let result = checker(vec![]).explain("user:bob", "patient:1", "can_view").await.unwrap();
+
+        assert!(result.allowed);
+        assert_eq!(hops(&result), vec![(ExplainStepType::This, "user:bob", "can_view", "patient:1", true)]);
+    }
+
+    #[tokio::test]
+    async fn test_explain_denial_lists_every_step_evaluated() {
+        let mut expired = tuple("user:carol", "member", "group:ward_7");
+        expired.expires_at = Some(Utc::now() - chrono::Duration::hours(1));
+        let checker = checker(vec![expired, tuple("user:carol", "has_role", "role:porter")]);
+
+        let result = checker.explain("user:carol", "patient:1", "can_view").await.unwrap();
+
+        assert!(!result.allowed);
+        assert_eq!(hops(&result), vec![
+            (ExplainStepType::This, "user:carol", "*", "*", false),
+            (ExplainStepType::This, "user:carol", "can_view", "patient:1", false),
+            (ExplainStepType::ComputedUserset, "user:carol", "has_role", "role:porter", true),
+            (ExplainStepType::DirectTuple, "role:porter", "can_view", "patient:1", false),
+            (ExplainStepType::ComputedUserset, "user:carol", "member", "group:ward_7", false),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_explain_requires_capability() {
+        let checker = checker(vec![tuple("user:bob", EXPLAIN_PERMISSIONS_CAPABILITY, AUTHORIZATION_OBJECT)]);
+
+        assert!(checker.can_explain("user:bob").await.unwrap());
+        assert!(!checker.can_explain("user:alice").await.unwrap());
+    }
+}
@@ -0,0 +1,26 @@
+//! Background purge of expired relationships
+//!
+//! Expired role assignments (and other time-bounded relationships) are
+//! already ignored by permission checks; this task deletes the rows so the
+//! relationships table and the authorization graph do not keep growing.
+
+use crate::domain::repositories::RelationshipRepository;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often expired relationships are purged
+pub const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Purge expired relationships every [`CLEANUP_INTERVAL`], starting immediately
+pub async fn run(repository: Arc<dyn RelationshipRepository>) {
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    loop {
+        interval.tick().await;
+        match repository.delete_expired().await {
+            Ok(0) => {}
+            Ok(deleted) => tracing::info!("Deleted {} expired relationships", deleted),
+            Err(e) => e.log_with_operation(concat!(file!(), ":", line!()), "expired_role_cleanup"),
+        }
+    }
+}
@@ -1,39 +1,270 @@
 use crate::infrastructure::zanzibar::graph_builder::AuthorizationGraph;
 use crate::domain::repositories::RelationshipRepository;
 use crate::shared::AppResult;
-use std::sync::{Arc, RwLock};
+use lru::LruCache;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use chrono::{DateTime, Utc, Duration};
 
+/// Cached check results kept when no limit is configured
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// TTL configuration for cached permission queries
+#[derive(Debug, Clone)]
+pub struct GraphCacheConfig {
+    /// TTL for the graph and any relation without an override
+    pub default_ttl_secs: u64,
+    /// Relation-specific TTLs (e.g. short TTL for `can_view_patient`)
+    pub ttl_by_relation: HashMap<String, u64>,
+    /// Cached check results kept before the least recently used is evicted
+    pub max_entries: usize,
+}
+
+impl Default for GraphCacheConfig {
+    fn default() -> Self {
+        Self {
+            default_ttl_secs: 60,
+            ttl_by_relation: HashMap::new(),
+            max_entries: DEFAULT_MAX_ENTRIES,
+        }
+    }
+}
+
+/// Snapshot of cache counters
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Check results evicted to make room for new ones
+    pub evictions: u64,
+    /// Cached check results
+    pub current_size: usize,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from cache (0.0 when nothing was looked up)
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+
+    /// Render in Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        format!(
+            "# HELP zanzibar_cache_hit_ratio Fraction of permission cache lookups served from cache\n\
+             # TYPE zanzibar_cache_hit_ratio gauge\n\
+             zanzibar_cache_hit_ratio {}\n\
+             # HELP zanzibar_cache_hits Permission cache hits since startup\n\
+             # TYPE zanzibar_cache_hits gauge\n\
+             zanzibar_cache_hits {}\n\
+             # HELP zanzibar_cache_misses Permission cache misses since startup\n\
+             # TYPE zanzibar_cache_misses gauge\n\
+             zanzibar_cache_misses {}\n\
+             # HELP zanzibar_cache_evictions Permission cache entries evicted since startup\n\
+             # TYPE zanzibar_cache_evictions gauge\n\
+             zanzibar_cache_evictions {}\n\
+             # HELP zanzibar_cache_entries Cached permission check results\n\
+             # TYPE zanzibar_cache_entries gauge\n\
+             zanzibar_cache_entries {}\n",
+            self.hit_ratio(),
+            self.hits,
+            self.misses,
+            self.evictions,
+            self.current_size,
+        )
+    }
+}
+
+/// Cached result of a single (subject, relation, object) check
+struct CheckEntry {
+    allowed: bool,
+    /// Point in time the result reflects (used for zookie freshness)
+    snapshot_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+type CheckKey = (String, String, String);
+
 /// Cache entry for authorization graph
-#[allow(dead_code)]
 struct CacheEntry {
     graph: Arc<AuthorizationGraph>, // Use Arc to avoid cloning the entire graph
-    created_at: DateTime<Utc>, // Kept for future use (e.g., cache statistics)
+    created_at: DateTime<Utc>, // When the graph's relationships were read
     expires_at: DateTime<Utc>,
 }
 
 /// Graph cache manager
+///
+/// Check results are kept in an LRU of at most `max_entries`; once full, the
+/// least recently used result is evicted to make room for a new one.
 pub struct GraphCache {
     cache: Arc<RwLock<Option<CacheEntry>>>,
-    ttl: Duration,
+    checks: Arc<Mutex<LruCache<CheckKey, CheckEntry>>>,
+    config: GraphCacheConfig,
     enabled: bool,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    evictions: AtomicU64,
 }
 
 impl GraphCache {
-    pub fn new(ttl_seconds: i64, enabled: bool) -> Self {
+    /// Create an enabled cache holding at most `max_entries` check results
+    pub fn new(max_entries: usize, ttl: std::time::Duration) -> Self {
+        Self::with_config(
+            GraphCacheConfig {
+                default_ttl_secs: ttl.as_secs(),
+                max_entries,
+                ..GraphCacheConfig::default()
+            },
+            true,
+        )
+    }
+
+    /// Create with per-relation TTL configuration
+    pub fn with_config(config: GraphCacheConfig, enabled: bool) -> Self {
+        let capacity = NonZeroUsize::new(config.max_entries.max(1)).unwrap_or(NonZeroUsize::MIN);
         Self {
             cache: Arc::new(RwLock::new(None)),
-            ttl: Duration::seconds(ttl_seconds),
+            checks: Arc::new(Mutex::new(LruCache::new(capacity))),
+            config,
             enabled,
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
         }
     }
     
     pub fn with_default_ttl() -> Self {
-        Self::new(60, true) // 60 seconds default TTL, enabled by default
+        // 60 seconds default TTL, enabled by default
+        Self::new(DEFAULT_MAX_ENTRIES, std::time::Duration::from_secs(60))
     }
 
     pub fn disabled() -> Self {
-        Self::new(0, false)
+        Self::with_config(GraphCacheConfig { default_ttl_secs: 0, ..GraphCacheConfig::default() }, false)
+    }
+
+    /// TTL for a relation, falling back to the default TTL
+    pub fn get_ttl(&self, relation: &str) -> std::time::Duration {
+        let secs = self
+            .config
+            .ttl_by_relation
+            .get(relation)
+            .copied()
+            .unwrap_or(self.config.default_ttl_secs);
+        std::time::Duration::from_secs(secs)
+    }
+
+    /// Graph TTL (the graph serves every relation, so it uses the default)
+    fn graph_ttl(&self) -> Duration {
+        Duration::seconds(self.config.default_ttl_secs as i64)
+    }
+
+    fn record_hit(&self) {
+        self.cache_hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_miss(&self) {
+        self.cache_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current hit/miss/eviction counters and cache size
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.cache_hits.load(Ordering::Relaxed),
+            misses: self.cache_misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            current_size: self.checks.lock().unwrap().len(),
+        }
+    }
+
+    /// Get a cached check result (if not expired)
+    pub fn get_check(&self, subject: &str, relation: &str, object: &str) -> Option<bool> {
+        self.get_check_as_of(subject, relation, object, None)
+    }
+
+    /// Get a cached check result that is at least as fresh as `min_snapshot`
+    /// Results older than the snapshot (e.g. from before a zookie's write) count as misses.
+    pub fn get_check_as_of(
+        &self,
+        subject: &str,
+        relation: &str,
+        object: &str,
+        min_snapshot: Option<DateTime<Utc>>,
+    ) -> Option<bool> {
+        if !self.enabled {
+            return None;
+        }
+
+        let key = (subject.to_string(), relation.to_string(), object.to_string());
+        let mut checks = self.checks.lock().unwrap();
+        let cached = match checks.get(&key) {
+            Some(entry) if Utc::now() >= entry.expires_at => {
+                checks.pop(&key);
+                None
+            }
+            Some(entry) if min_snapshot.map_or(true, |min| entry.snapshot_at >= min) => Some(entry.allowed),
+            _ => None,
+        };
+        drop(checks);
+
+        match cached {
+            Some(_) => self.record_hit(),
+            None => self.record_miss(),
+        }
+        cached
+    }
+
+    /// Cache a check result using the relation's TTL
+    pub fn put_check(&self, subject: &str, relation: &str, object: &str, allowed: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        let ttl = Duration::from_std(self.get_ttl(relation)).unwrap_or_else(|_| self.graph_ttl());
+        // Results are computed from the cached graph, so they are only as fresh as it is
+        let snapshot_at = self
+            .cache
+            .read()
+            .unwrap()
+            .as_ref()
+            .map_or_else(Utc::now, |entry| entry.created_at);
+        let key = (subject.to_string(), relation.to_string(), object.to_string());
+        let entry = CheckEntry {
+            allowed,
+            snapshot_at,
+            expires_at: Utc::now() + ttl,
+        };
+        // `push` hands back the replaced entry for an existing key, or the
+        // least recently used one when it had to evict
+        let displaced = self.checks.lock().unwrap().push(key.clone(), entry);
+        if displaced.is_some_and(|(displaced_key, _)| displaced_key != key) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Purge all cached entries for a subject (call when their roles change)
+    /// Accepts either a bare ID or a typed subject (`user:{id}`).
+    pub fn invalidate_for_subject(&self, subject_id: &str) {
+        let typed = format!("user:{}", subject_id);
+        let mut checks = self.checks.lock().unwrap();
+        // LruCache doesn't have retain, so we need to collect keys to remove
+        let stale: Vec<CheckKey> = checks
+            .iter()
+            .filter(|((subject, _, _), _)| subject == subject_id || *subject == typed)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in stale {
+            checks.pop(&key);
+        }
+        drop(checks);
+
+        // The graph holds the subject's role edges, so it is stale as well
+        *self.cache.write().unwrap() = None;
     }
     
     /// Get graph from cache or build new one
@@ -51,12 +282,15 @@ impl GraphCache {
             let cache = self.cache.read().unwrap();
             if let Some(entry) = cache.as_ref() {
                 if Utc::now() < entry.expires_at {
+                    self.record_hit();
                     return Ok(Arc::clone(&entry.graph));
                 }
             }
         }
         
         // Cache miss or expired, build new graph
+        self.record_miss();
+        let read_at = Utc::now();
         let graph = Arc::new(self.build_graph(repository).await?);
         
         // Update cache
@@ -64,8 +298,8 @@ impl GraphCache {
             let mut cache = self.cache.write().unwrap();
             *cache = Some(CacheEntry {
                 graph: Arc::clone(&graph),
-                created_at: Utc::now(),
-                expires_at: Utc::now() + self.ttl,
+                created_at: read_at,
+                expires_at: Utc::now() + self.graph_ttl(),
             });
         }
         
@@ -90,6 +324,7 @@ impl GraphCache {
     pub fn invalidate(&self) {
         let mut cache = self.cache.write().unwrap();
         *cache = None;
+        self.checks.lock().unwrap().clear();
     }
     
     /// Force refresh cache
@@ -121,9 +356,15 @@ impl GraphCache {
     
     /// Get cached graph (if valid) without building
     pub fn get_cached(&self) -> Option<Arc<AuthorizationGraph>> {
+        self.get_cached_as_of(None)
+    }
+
+    /// Get cached graph only if it was read at or after `min_snapshot`
+    pub fn get_cached_as_of(&self, min_snapshot: Option<DateTime<Utc>>) -> Option<Arc<AuthorizationGraph>> {
         let cache = self.cache.read().unwrap();
         if let Some(entry) = cache.as_ref() {
-            if Utc::now() < entry.expires_at {
+            let fresh_enough = min_snapshot.map_or(true, |min| entry.created_at >= min);
+            if Utc::now() < entry.expires_at && fresh_enough {
                 return Some(Arc::clone(&entry.graph));
             }
         }
@@ -131,3 +372,107 @@ impl GraphCache {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_with_overrides() -> GraphCache {
+        let mut ttl_by_relation = HashMap::new();
+        ttl_by_relation.insert("can_view_patient".to_string(), 5);
+        GraphCache::with_config(
+            GraphCacheConfig {
+                default_ttl_secs: 120,
+                ttl_by_relation,
+                ..GraphCacheConfig::default()
+            },
+            true,
+        )
+    }
+
+    #[test]
+    fn test_get_ttl_prefers_relation_override() {
+        let cache = cache_with_overrides();
+        assert_eq!(cache.get_ttl("can_view_patient"), std::time::Duration::from_secs(5));
+        assert_eq!(cache.get_ttl("read"), std::time::Duration::from_secs(120));
+    }
+
+    #[test]
+    fn test_stats_track_hits_and_misses() {
+        let cache = cache_with_overrides();
+        assert_eq!(cache.get_check("user:1", "read", "patient:1"), None);
+        cache.put_check("user:1", "read", "patient:1", true);
+        assert_eq!(cache.get_check("user:1", "read", "patient:1"), Some(true));
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hit_ratio(), 0.5);
+        assert!(stats.to_prometheus().contains("zanzibar_cache_hit_ratio 0.5"));
+    }
+
+    #[test]
+    fn test_invalidate_for_subject_only_purges_that_subject() {
+        let cache = cache_with_overrides();
+        cache.put_check("user:1", "read", "patient:1", true);
+        cache.put_check("user:2", "read", "patient:1", true);
+
+        cache.invalidate_for_subject("1");
+
+        assert_eq!(cache.get_check("user:1", "read", "patient:1"), None);
+        assert_eq!(cache.get_check("user:2", "read", "patient:1"), Some(true));
+    }
+
+    #[test]
+    fn test_get_check_as_of_skips_results_older_than_snapshot() {
+        let cache = cache_with_overrides();
+        cache.put_check("user:1", "read", "patient:1", false);
+
+        let before = Utc::now() - Duration::seconds(10);
+        let after = Utc::now() + Duration::seconds(10);
+        assert_eq!(cache.get_check_as_of("user:1", "read", "patient:1", Some(before)), Some(false));
+        assert_eq!(cache.get_check_as_of("user:1", "read", "patient:1", Some(after)), None);
+    }
+
+    #[test]
+    fn test_full_cache_evicts_least_recently_used() {
+        let cache = GraphCache::new(3, std::time::Duration::from_secs(60));
+        for patient in 1..=3 {
+            cache.put_check("user:1", "read", &format!("patient:{}", patient), true);
+        }
+        // Touch patient:1 so patient:2 becomes least recently used
+        assert_eq!(cache.get_check("user:1", "read", "patient:1"), Some(true));
+
+        cache.put_check("user:1", "read", "patient:4", true);
+
+        assert_eq!(cache.get_check("user:1", "read", "patient:2"), None);
+        for patient in [1, 3, 4] {
+            assert_eq!(cache.get_check("user:1", "read", &format!("patient:{}", patient)), Some(true));
+        }
+        let stats = cache.stats();
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.current_size, 3);
+
+        // Overwriting a cached result is not an eviction
+        cache.put_check("user:1", "read", "patient:4", false);
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_lookups_in_full_cache_within_budget() {
+        let cache = GraphCache::new(DEFAULT_MAX_ENTRIES, std::time::Duration::from_secs(60));
+        for patient in 0..DEFAULT_MAX_ENTRIES {
+            cache.put_check("user:1", "read", &format!("patient:{}", patient), true);
+        }
+
+        let start = std::time::Instant::now();
+        for patient in 0..DEFAULT_MAX_ENTRIES {
+            assert!(cache.get_check("user:1", "read", &format!("patient:{}", patient)).is_some());
+            assert!(cache.get_check("user:2", "read", &format!("patient:{}", patient)).is_none());
+        }
+        // 20,000 lookups; a scan per lookup would take orders of magnitude longer
+        let elapsed = start.elapsed();
+        assert!(elapsed < std::time::Duration::from_millis(500), "took {:?}", elapsed);
+        assert_eq!(cache.stats().current_size, DEFAULT_MAX_ENTRIES);
+    }
+}
@@ -5,12 +5,16 @@ pub mod graph_types;
 pub mod graph_builder;
 pub mod graph_checker;
 pub mod graph_cache;
+pub mod expired_role_cleanup;
 
-pub use checker::PermissionChecker;
+pub use checker::{
+    DenialExplanation, DenialReason, ExplainResult, ExplainStep, ExplainStepType, PermissionChecker,
+    AUTHORIZATION_OBJECT, EXPLAIN_PERMISSIONS_CAPABILITY,
+};
 pub use relationship_store::RelationshipStore;
 pub use tuple::RelationshipTuple;
 pub use graph_types::{EntityType, GraphNode, RelationshipEdge};
 pub use graph_builder::AuthorizationGraph;
 pub use graph_checker::GraphPermissionChecker;
-pub use graph_cache::GraphCache;
+pub use graph_cache::{CacheStats, GraphCache, GraphCacheConfig, DEFAULT_MAX_ENTRIES};
 
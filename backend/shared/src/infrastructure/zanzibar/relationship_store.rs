@@ -1,18 +1,41 @@
 use crate::domain::entities::Relationship;
 use crate::domain::repositories::RelationshipRepository;
+use crate::infrastructure::zanzibar::GraphCache;
 use crate::shared::AppResult;
 use chrono::{DateTime, Utc};
+use std::collections::BTreeSet;
+use std::sync::Arc;
 use uuid::Uuid;
 use serde_json::Value;
 use tracing;
 
 pub struct RelationshipStore {
     repository: Box<dyn RelationshipRepository>,
+    graph_cache: Option<Arc<GraphCache>>,
 }
 
 impl RelationshipStore {
     pub fn new(repository: Box<dyn RelationshipRepository>) -> Self {
-        Self { repository }
+        Self { repository, graph_cache: None }
+    }
+
+    /// Invalidate cached permission checks on batch writes
+    pub fn with_graph_cache(mut self, graph_cache: Arc<GraphCache>) -> Self {
+        self.graph_cache = Some(graph_cache);
+        self
+    }
+
+    /// Insert tuples in one transaction (see `RelationshipRepository::write_batch`),
+    /// then purge cached checks for every subject they touch
+    pub async fn write_batch_cached(&self, tuples: Vec<Relationship>) -> AppResult<u64> {
+        let subjects: BTreeSet<String> = tuples.iter().map(|t| t.user.clone()).collect();
+        let inserted = self.repository.write_batch(tuples).await?;
+        if let Some(cache) = &self.graph_cache {
+            for subject in &subjects {
+                cache.invalidate_for_subject(subject);
+            }
+        }
+        Ok(inserted)
     }
 
     pub async fn add(&self, user: &str, relation: &str, object: &str) -> AppResult<()> {
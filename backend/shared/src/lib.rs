@@ -5,7 +5,7 @@ pub mod domain;
 pub mod i18n;
 pub mod infrastructure;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 pub mod testing;
 
 pub use application::*;
@@ -2,7 +2,7 @@
 // Standard response structures for HTTP APIs
 
 use axum::{
-    http::StatusCode,
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -60,10 +60,15 @@ impl IntoResponse for ApiError {
         let (status, error_code) = match &self.0.kind() {
             ErrorKind::NotFound => (StatusCode::NOT_FOUND, "NOT_FOUND"),
             ErrorKind::Unauthorized => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED"),
+            ErrorKind::MfaRequired => (StatusCode::UNAUTHORIZED, "MFA_REQUIRED"),
+            ErrorKind::UnauthorizedDevice => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED_DEVICE"),
             ErrorKind::Forbidden => (StatusCode::FORBIDDEN, "FORBIDDEN"),
             ErrorKind::Validation => (StatusCode::BAD_REQUEST, "VALIDATION_ERROR"),
             ErrorKind::Conflict => (StatusCode::CONFLICT, "CONFLICT"),
             ErrorKind::InvalidState => (StatusCode::UNPROCESSABLE_ENTITY, "INVALID_STATE"),
+            ErrorKind::PreconditionFailed => (StatusCode::PRECONDITION_FAILED, "PRECONDITION_FAILED"),
+            ErrorKind::AccountLocked => (StatusCode::TOO_MANY_REQUESTS, "ACCOUNT_LOCKED"),
+            ErrorKind::TooManyAttempts => (StatusCode::TOO_MANY_REQUESTS, "TOO_MANY_ATTEMPTS"),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
         };
 
@@ -72,7 +77,11 @@ impl IntoResponse for ApiError {
             self.0.to_string(),
         );
 
-        (status, Json(response)).into_response()
+        let mut response = (status, Json(response)).into_response();
+        if let Some(seconds) = self.0.retry_after_seconds() {
+            response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        response
     }
 }
 
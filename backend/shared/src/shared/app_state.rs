@@ -1,11 +1,17 @@
 use std::sync::Arc;
 use sqlx::PgPool;
 use crate::domain::repositories::{SetupRepository, RoleRepository};
+use crate::domain::repositories::ehr::EhrPatientRepository;
 use crate::infrastructure::database::DatabaseService;
 use crate::infrastructure::oidc::TokenManager;
 use crate::infrastructure::zanzibar::{PermissionChecker, RelationshipStore, GraphCache};
 use crate::infrastructure::encryption::{DekManager, RustyVaultClient};
+use crate::infrastructure::logging::BodySampler;
 use crate::infrastructure::session::SessionService;
+use crate::infrastructure::storage::{Storage, StorageUrlSigner};
+use crate::application::services::{SharedAppointmentEvents, SharedRulesEngine, SharedWorkflowEngine};
+use crate::config::FeatureFlags;
+use crate::shared::RequestContext;
 
 /// Application state that holds shared services and use cases.
 /// Note: Use case types are provided by the consuming crate (e.g., api-service)
@@ -25,10 +31,55 @@ pub struct AppState<LoginUseCase, RefreshTokenUseCase, LogoutUseCase, UserInfoUs
     pub create_super_admin_use_case: Arc<CreateSuperAdminUseCase>,
     pub dek_manager: Arc<DekManager>,
     pub role_repository: Arc<dyn RoleRepository>,
+    /// EHR patients, storing and searching SSNs through field encryption
+    pub ehr_patient_repository: Arc<dyn EhrPatientRepository>,
     pub graph_cache: Option<Arc<GraphCache>>,
     pub session_service: Arc<SessionService>,
+    /// Decision rules loaded for evaluation and backtesting
+    pub rules_engine: SharedRulesEngine,
+    /// Workflow engine shared by the handlers and the cron scheduler
+    pub workflow_engine: SharedWorkflowEngine,
+    /// Appointment status changes streamed to live dashboards
+    pub appointment_events: SharedAppointmentEvents,
     /// Vault client for realm lookups and on-demand token minting
     /// Optional because vault may not be configured in all environments
     pub vault_client: Option<Arc<RustyVaultClient>>,
+    /// Reject EHR requests without a `HIPAA-Access-Reason` header
+    pub require_access_reason: bool,
+    /// Requests whose bodies are logged by the request logging middleware
+    pub body_sampler: BodySampler,
+    /// Where uploaded patient documents are stored
+    pub document_storage: Arc<dyn Storage>,
+    /// Checks presigned URLs the API serves itself (local document storage)
+    pub storage_url_signer: StorageUrlSigner,
+    /// The deployment's feature flags, before any realm overrides
+    pub features: FeatureFlags,
+}
+
+impl<LoginUseCase, RefreshTokenUseCase, LogoutUseCase, UserInfoUseCase, SetupOrganizationUseCase, CreateSuperAdminUseCase>
+    AppState<LoginUseCase, RefreshTokenUseCase, LogoutUseCase, UserInfoUseCase, SetupOrganizationUseCase, CreateSuperAdminUseCase>
+{
+    /// Feature flags for a request: the deployment's flags with the caller's realm overrides applied
+    ///
+    /// Falls back to the deployment's flags when the caller has no organization,
+    /// vault is not configured, or the realm's overrides can't be read.
+    pub async fn features_for(&self, context: &RequestContext) -> FeatureFlags {
+        let (Some(vault_client), Some(organization_id)) = (&self.vault_client, context.organization_id) else {
+            return self.features;
+        };
+
+        let overrides = match vault_client.get_or_create_realm_for_org(organization_id).await {
+            Ok(realm_id) => vault_client.realm_feature_flags(realm_id).await,
+            Err(e) => Err(e),
+        };
+
+        match overrides {
+            Ok(overrides) => self.features.with_overrides(&overrides),
+            Err(e) => {
+                tracing::warn!("Using deployment feature flags for organization {}: {}", organization_id, e);
+                self.features
+            }
+        }
+    }
 }
 
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use crate::shared::RequestContext;
 
 /// Audit fields that should be present on all database entities
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,14 +78,30 @@ impl AuditFields {
     }
 }
 
+/// Stamp audit fields for a record being created by the request's user
+pub fn populate_audit_fields(fields: &mut AuditFields, ctx: &RequestContext) {
+    let now = Utc::now();
+    fields.request_id = Some(ctx.request_id.clone());
+    fields.created_at = now;
+    fields.updated_at = now;
+    fields.created_by = Some(ctx.user_id);
+    fields.updated_by = Some(ctx.user_id);
+}
+
 /// Trait for entities that have audit fields
+///
+/// Entities store their audit columns inline, so implementations copy them
+/// in and out of an `AuditFields` value; see `impl_has_audit_fields!`.
 pub trait HasAuditFields {
-    fn audit_fields(&self) -> &AuditFields;
-    fn audit_fields_mut(&mut self) -> &mut AuditFields;
+    /// Snapshot of the entity's audit fields
+    fn audit_fields(&self) -> AuditFields;
+
+    /// Overwrite the entity's audit fields
+    fn set_audit_fields(&mut self, fields: AuditFields);
     
     /// Get request ID
-    fn request_id(&self) -> Option<&String> {
-        self.audit_fields().request_id.as_ref()
+    fn request_id(&self) -> Option<String> {
+        self.audit_fields().request_id
     }
     
     /// Get created timestamp
@@ -108,8 +125,8 @@ pub trait HasAuditFields {
     }
     
     /// Get system ID
-    fn system_id(&self) -> Option<&String> {
-        self.audit_fields().system_id.as_ref()
+    fn system_id(&self) -> Option<String> {
+        self.audit_fields().system_id
     }
     
     /// Get version
@@ -119,10 +136,65 @@ pub trait HasAuditFields {
     
     /// Touch the record (update timestamp and version)
     fn touch(&mut self, request_id: Option<String>, updated_by: Option<Uuid>) {
-        self.audit_fields_mut().touch(request_id, updated_by);
+        let mut fields = self.audit_fields();
+        fields.touch(request_id, updated_by);
+        self.set_audit_fields(fields);
+    }
+
+    /// Record the request's user as creator and last updater
+    fn apply_create_audit(&mut self, ctx: &RequestContext) {
+        let mut fields = self.audit_fields();
+        populate_audit_fields(&mut fields, ctx);
+        self.set_audit_fields(fields);
+    }
+
+    /// Record the request's user as last updater
+    ///
+    /// The version is left alone; repositories bump it for optimistic locking.
+    fn apply_update_audit(&mut self, ctx: &RequestContext) {
+        let mut fields = self.audit_fields();
+        fields.request_id = Some(ctx.request_id.clone());
+        fields.updated_at = Utc::now();
+        fields.updated_by = Some(ctx.user_id);
+        self.set_audit_fields(fields);
     }
 }
 
+/// Implement `HasAuditFields` for entities with inline audit columns
+/// (`request_id`, `created_at`, `updated_at`, `created_by`, `updated_by`,
+/// `system_id`, `version`)
+macro_rules! impl_has_audit_fields {
+    ($($entity:ty),+ $(,)?) => {
+        $(
+            impl $crate::shared::HasAuditFields for $entity {
+                fn audit_fields(&self) -> $crate::shared::AuditFields {
+                    $crate::shared::AuditFields {
+                        request_id: self.request_id.clone(),
+                        created_at: self.created_at,
+                        updated_at: self.updated_at,
+                        created_by: self.created_by,
+                        updated_by: self.updated_by,
+                        system_id: self.system_id.clone(),
+                        version: self.version,
+                    }
+                }
+
+                fn set_audit_fields(&mut self, fields: $crate::shared::AuditFields) {
+                    self.request_id = fields.request_id;
+                    self.created_at = fields.created_at;
+                    self.updated_at = fields.updated_at;
+                    self.created_by = fields.created_by;
+                    self.updated_by = fields.updated_by;
+                    self.system_id = fields.system_id;
+                    self.version = fields.version;
+                }
+            }
+        )+
+    };
+}
+
+pub(crate) use impl_has_audit_fields;
+
 /// Context for database operations with audit information
 #[derive(Debug, Clone)]
 pub struct AuditContext {
@@ -149,3 +221,62 @@ impl AuditContext {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Note {
+        request_id: Option<String>,
+        created_at: DateTime<Utc>,
+        updated_at: DateTime<Utc>,
+        created_by: Option<Uuid>,
+        updated_by: Option<Uuid>,
+        system_id: Option<String>,
+        version: i64,
+    }
+
+    impl_has_audit_fields!(Note);
+
+    fn new_note() -> Note {
+        let audit = AuditFields::new();
+        Note {
+            request_id: audit.request_id,
+            created_at: audit.created_at,
+            updated_at: audit.updated_at,
+            created_by: audit.created_by,
+            updated_by: audit.updated_by,
+            system_id: audit.system_id,
+            version: audit.version,
+        }
+    }
+
+    fn context(user_id: Uuid) -> RequestContext {
+        RequestContext::new("req-1".to_string(), user_id, "user@example.com".to_string(), None, vec![])
+    }
+
+    #[test]
+    fn test_apply_create_audit_sets_creator() {
+        let user_id = Uuid::new_v4();
+        let mut note = new_note();
+        note.apply_create_audit(&context(user_id));
+
+        assert_eq!(note.created_by, Some(user_id));
+        assert_eq!(note.updated_by, Some(user_id));
+        assert_eq!(note.request_id.as_deref(), Some("req-1"));
+        assert_eq!(note.version, 1);
+    }
+
+    #[test]
+    fn test_apply_update_audit_keeps_creator_and_version() {
+        let creator = Uuid::new_v4();
+        let editor = Uuid::new_v4();
+        let mut note = new_note();
+        note.apply_create_audit(&context(creator));
+        note.apply_update_audit(&context(editor));
+
+        assert_eq!(note.created_by, Some(creator));
+        assert_eq!(note.updated_by, Some(editor));
+        assert_eq!(note.version, 1);
+    }
+}
@@ -1,5 +1,6 @@
 use thiserror::Error;
 use crate::infrastructure::logging::context::LogContext;
+use crate::infrastructure::storage::StorageError;
 
 #[derive(Error, Debug)]
 pub enum AppError {
@@ -12,6 +13,12 @@ pub enum AppError {
     #[error("Authentication error: {0}")]
     Authentication(String),
 
+    #[error("MFA required: {0}")]
+    MfaRequired(String),
+
+    #[error("Unauthorized device: {0}")]
+    UnauthorizedDevice(String),
+
     #[error("Authorization error: {0}")]
     Authorization(String),
 
@@ -27,12 +34,18 @@ pub enum AppError {
     #[error("Invalid state: {0}")]
     InvalidState(String),
 
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
     #[error("Configuration error: {0}")]
     Configuration(String),
 
     #[error("Storage error: {0}")]
     Storage(String),
 
+    #[error("Storage error: {0}")]
+    StorageIntegrity(#[from] StorageError),
+
     #[error("Validation error: {0}")]
     Validation(String),
 
@@ -44,6 +57,12 @@ pub enum AppError {
 
     #[error("Request timeout: {0}")]
     Timeout(String),
+
+    #[error("Account locked: try again in {remaining_minutes} minute(s)")]
+    AccountLocked { remaining_minutes: u64 },
+
+    #[error("Too many failed attempts: retry after {retry_after_seconds} second(s)")]
+    TooManyAttempts { retry_after_seconds: u64 },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -51,17 +70,22 @@ pub enum ErrorKind {
     Database,
     Encryption,
     Authentication,
+    MfaRequired,
+    UnauthorizedDevice,
     Authorization,
     Unauthorized,
     Forbidden,
     Conflict,
     InvalidState,
+    PreconditionFailed,
     Configuration,
     Storage,
     Validation,
     NotFound,
     Internal,
     Timeout,
+    AccountLocked,
+    TooManyAttempts,
 }
 
 impl From<AppError> for ErrorKind {
@@ -70,17 +94,22 @@ impl From<AppError> for ErrorKind {
             AppError::Database(_) => ErrorKind::Database,
             AppError::Encryption(_) => ErrorKind::Encryption,
             AppError::Authentication(_) => ErrorKind::Authentication,
+            AppError::MfaRequired(_) => ErrorKind::MfaRequired,
+            AppError::UnauthorizedDevice(_) => ErrorKind::UnauthorizedDevice,
             AppError::Authorization(_) => ErrorKind::Authorization,
             AppError::Unauthorized(_) => ErrorKind::Unauthorized,
             AppError::Forbidden(_) => ErrorKind::Forbidden,
             AppError::Conflict(_) => ErrorKind::Conflict,
             AppError::InvalidState(_) => ErrorKind::InvalidState,
+            AppError::PreconditionFailed(_) => ErrorKind::PreconditionFailed,
             AppError::Configuration(_) => ErrorKind::Configuration,
-            AppError::Storage(_) => ErrorKind::Storage,
+            AppError::Storage(_) | AppError::StorageIntegrity(_) => ErrorKind::Storage,
             AppError::Validation(_) => ErrorKind::Validation,
             AppError::NotFound(_) => ErrorKind::NotFound,
             AppError::Internal(_) => ErrorKind::Internal,
             AppError::Timeout(_) => ErrorKind::Timeout,
+            AppError::AccountLocked { .. } => ErrorKind::AccountLocked,
+            AppError::TooManyAttempts { .. } => ErrorKind::TooManyAttempts,
         }
     }
 }
@@ -91,17 +120,22 @@ impl From<&AppError> for ErrorKind {
             AppError::Database(_) => ErrorKind::Database,
             AppError::Encryption(_) => ErrorKind::Encryption,
             AppError::Authentication(_) => ErrorKind::Authentication,
+            AppError::MfaRequired(_) => ErrorKind::MfaRequired,
+            AppError::UnauthorizedDevice(_) => ErrorKind::UnauthorizedDevice,
             AppError::Authorization(_) => ErrorKind::Authorization,
             AppError::Unauthorized(_) => ErrorKind::Unauthorized,
             AppError::Forbidden(_) => ErrorKind::Forbidden,
             AppError::Conflict(_) => ErrorKind::Conflict,
             AppError::InvalidState(_) => ErrorKind::InvalidState,
+            AppError::PreconditionFailed(_) => ErrorKind::PreconditionFailed,
             AppError::Configuration(_) => ErrorKind::Configuration,
-            AppError::Storage(_) => ErrorKind::Storage,
+            AppError::Storage(_) | AppError::StorageIntegrity(_) => ErrorKind::Storage,
             AppError::Validation(_) => ErrorKind::Validation,
             AppError::NotFound(_) => ErrorKind::NotFound,
             AppError::Internal(_) => ErrorKind::Internal,
             AppError::Timeout(_) => ErrorKind::Timeout,
+            AppError::AccountLocked { .. } => ErrorKind::AccountLocked,
+            AppError::TooManyAttempts { .. } => ErrorKind::TooManyAttempts,
         }
     }
 }
@@ -161,5 +195,14 @@ impl AppError {
     pub fn kind(&self) -> ErrorKind {
         ErrorKind::from(self)
     }
+
+    /// Seconds a client should wait before retrying, for rate-limited errors
+    pub fn retry_after_seconds(&self) -> Option<u64> {
+        match self {
+            AppError::AccountLocked { remaining_minutes } => Some(remaining_minutes * 60),
+            AppError::TooManyAttempts { retry_after_seconds } => Some(*retry_after_seconds),
+            _ => None,
+        }
+    }
 }
 
@@ -1,3 +1,6 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha512};
+
 /// Mask sensitive field values for display
 pub fn mask_field(value: &str, mask_char: char, visible_chars: usize) -> String {
     if value.len() <= visible_chars {
@@ -43,3 +46,199 @@ pub fn mask_ssn(ssn: &str) -> String {
     }
 }
 
+
+/// Fields whose values never appear in logs, compared ignoring case, `_` and `-`
+pub const SENSITIVE_FIELDS: &[&str] = &[
+    "password",
+    "current_password",
+    "new_password",
+    "ssn",
+    "social_security_number",
+    "dek",
+    "master_key",
+    "access_token",
+    "refresh_token",
+    "client_secret",
+    "api_key",
+    "totp_secret",
+    "token",
+    "secret",
+    "credit_card",
+];
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+fn normalize_field_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Whether `name` is one of [`SENSITIVE_FIELDS`] (`newPassword` matches `new_password`)
+pub fn is_sensitive_field(name: &str) -> bool {
+    let name = normalize_field_name(name);
+    SENSITIVE_FIELDS.iter().any(|field| normalize_field_name(field) == name)
+}
+
+/// Replace the value of every sensitive field, at any depth, with [`REDACTED`]
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (name, field) in fields.iter_mut() {
+                if is_sensitive_field(name) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Digest used by [`MaskingStrategy::Hash`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+/// How [`PiiMasker`] replaces a sensitive value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskingStrategy {
+    /// Replace with [`REDACTED`]
+    Redact,
+    /// Keep the first N characters and replace the rest with `*`
+    Partial(u8),
+    /// Replace with the hex digest, so log lines about the same value can
+    /// still be correlated
+    ///
+    /// Unsalted digests of short values such as SSNs can be reversed by brute
+    /// force; use `Redact` for those.
+    Hash(HashAlgorithm),
+}
+
+/// Fields [`PiiMasker`] masks, and how
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaskingConfig {
+    /// Field names, compared like [`SENSITIVE_FIELDS`]
+    pub fields: Vec<String>,
+    pub strategy: MaskingStrategy,
+}
+
+impl Default for MaskingConfig {
+    /// Redact [`SENSITIVE_FIELDS`]
+    fn default() -> Self {
+        Self {
+            fields: SENSITIVE_FIELDS.iter().map(|field| field.to_string()).collect(),
+            strategy: MaskingStrategy::Redact,
+        }
+    }
+}
+
+/// Masks configured fields in JSON values, including JSON embedded in strings
+#[derive(Debug, Clone)]
+pub struct PiiMasker {
+    /// Normalized field names
+    fields: Vec<String>,
+    strategy: MaskingStrategy,
+}
+
+impl Default for PiiMasker {
+    fn default() -> Self {
+        Self::new(MaskingConfig::default())
+    }
+}
+
+impl PiiMasker {
+    pub fn new(config: MaskingConfig) -> Self {
+        Self {
+            fields: config.fields.iter().map(|field| normalize_field_name(field)).collect(),
+            strategy: config.strategy,
+        }
+    }
+
+    /// Whether values of the field `name` are masked
+    pub fn is_masked_field(&self, name: &str) -> bool {
+        let name = normalize_field_name(name);
+        self.fields.contains(&name)
+    }
+
+    /// `value` with every masked field, at any depth, replaced
+    ///
+    /// Strings holding JSON (e.g. a serialized request body) are masked too.
+    pub fn mask(&self, value: Value) -> Value {
+        match value {
+            Value::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(name, field)| {
+                        let field = if self.is_masked_field(&name) { self.mask_value(&field) } else { self.mask(field) };
+                        (name, field)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.into_iter().map(|item| self.mask(item)).collect()),
+            Value::String(text) => Value::String(self.mask_embedded_json(&text).unwrap_or(text)),
+            other => other,
+        }
+    }
+
+    /// Replacement for the value of a masked field
+    pub fn mask_value(&self, value: &Value) -> Value {
+        let text = match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        let masked = match self.strategy {
+            MaskingStrategy::Redact => REDACTED.to_string(),
+            MaskingStrategy::Partial(visible) => {
+                let visible = usize::from(visible);
+                let hidden = text.chars().count().saturating_sub(visible);
+                text.chars().take(visible).chain(std::iter::repeat_n('*', hidden)).collect()
+            }
+            MaskingStrategy::Hash(HashAlgorithm::Sha256) => hex::encode(Sha256::digest(text.as_bytes())),
+            MaskingStrategy::Hash(HashAlgorithm::Sha512) => hex::encode(Sha512::digest(text.as_bytes())),
+        };
+        Value::String(masked)
+    }
+
+    /// `text` with the JSON objects and arrays embedded in it masked, or None
+    /// when none of them had anything to mask
+    ///
+    /// `Request body: {"ssn":"123-45-6789"}` becomes
+    /// `Request body: {"ssn":"[REDACTED]"}`.
+    pub fn mask_embedded_json(&self, text: &str) -> Option<String> {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        let mut changed = false;
+        while let Some(start) = rest.find(['{', '[']) {
+            let (before, candidate) = rest.split_at(start);
+            output.push_str(before);
+
+            let mut values = serde_json::Deserializer::from_str(candidate).into_iter::<Value>();
+            match values.next() {
+                Some(Ok(value)) => {
+                    let end = values.byte_offset();
+                    let masked = self.mask(value.clone());
+                    if masked == value {
+                        output.push_str(&candidate[..end]);
+                    } else {
+                        output.push_str(&masked.to_string());
+                        changed = true;
+                    }
+                    rest = &candidate[end..];
+                }
+                _ => {
+                    output.push_str(&candidate[..1]);
+                    rest = &candidate[1..];
+                }
+            }
+        }
+        output.push_str(rest);
+        changed.then_some(output)
+    }
+}
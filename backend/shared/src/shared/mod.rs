@@ -11,8 +11,9 @@ pub mod db_utils;
 pub use error::{AppError, ErrorKind};
 pub use result::AppResult;
 pub use app_state::AppState;
-pub use request_context::RequestContext;
-pub use audit::{AuditFields, HasAuditFields, AuditContext};
+pub use request_context::{device_fingerprint, RequestContext, HIPAA_ACCESS_REASON_HEADER};
+pub use audit::{AuditFields, HasAuditFields, AuditContext, populate_audit_fields};
+pub(crate) use audit::impl_has_audit_fields;
 pub use api_response::{ApiResponse, ApiError, ErrorResponse};
 pub use auth::User;
 pub use db_utils::{db_error, opt_bool, opt_string, opt_i32, validate_pagination, parse_datetime, parse_optional_datetime};
@@ -1,8 +1,33 @@
+use std::future::Future;
 use std::net::IpAddr;
 use uuid::Uuid;
 use axum::extract::FromRequestParts;
+use axum::http::header::{ACCEPT_LANGUAGE, USER_AGENT};
 use axum::http::request::Parts;
-use axum::http::StatusCode;
+use axum::http::{HeaderMap, StatusCode};
+use opentelemetry::trace::SpanContext;
+use sha2::{Digest, Sha256};
+
+/// Header carrying the HIPAA reason for accessing a patient's record
+pub const HIPAA_ACCESS_REASON_HEADER: &str = "HIPAA-Access-Reason";
+
+/// SHA-256 (hex) of the client's `User-Agent` and `Accept-Language` headers
+///
+/// Refresh tokens are bound to the fingerprint of the client that logged in.
+pub fn device_fingerprint(headers: &HeaderMap) -> String {
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let mut hasher = Sha256::new();
+    hasher.update(header(USER_AGENT).as_bytes());
+    // Headers cannot contain newlines, so the split between the two is unambiguous
+    hasher.update(b"\n");
+    hasher.update(header(ACCEPT_LANGUAGE).as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+tokio::task_local! {
+    /// Context of the request being handled, set by the auth middleware
+    static CURRENT_REQUEST_CONTEXT: RequestContext;
+}
 
 /// Request context containing authenticated user information
 #[derive(Debug, Clone)]
@@ -17,6 +42,14 @@ pub struct RequestContext {
     pub organization_id: Option<Uuid>,
     pub app_type: Option<String>,
     pub app_device: Option<String>,
+    /// Patient IEN of the EHR record being accessed (HIPAA audit)
+    pub patient_id: Option<i64>,
+    /// Reason given for the access via the `HIPAA-Access-Reason` header
+    pub access_reason: Option<String>,
+    /// Span context of the request, set by `tracing_middleware`
+    pub span_context: Option<SpanContext>,
+    /// [`device_fingerprint`] of the request headers, set by the auth middleware
+    pub device_fingerprint: Option<String>,
 }
 
 impl RequestContext {
@@ -38,6 +71,10 @@ impl RequestContext {
             organization_id: None,
             app_type: None,
             app_device: None,
+            patient_id: None,
+            access_reason: None,
+            span_context: None,
+            device_fingerprint: None,
         }
     }
 
@@ -65,6 +102,45 @@ impl RequestContext {
         self.app_device = Some(app_device);
         self
     }
+
+    pub fn with_span_context(mut self, span_context: SpanContext) -> Self {
+        self.span_context = Some(span_context);
+        self
+    }
+
+    pub fn with_device_fingerprint(mut self, device_fingerprint: String) -> Self {
+        self.device_fingerprint = Some(device_fingerprint);
+        self
+    }
+
+    /// Trace ID of the request, if it is part of a distributed trace
+    pub fn trace_id(&self) -> Option<String> {
+        self.span_context.as_ref().map(|span_context| span_context.trace_id().to_string())
+    }
+
+    pub fn with_patient_context(mut self, patient_id: Option<i64>, access_reason: Option<String>) -> Self {
+        self.set_patient_context(patient_id, access_reason);
+        self
+    }
+
+    /// Run `future` with this context available through `RequestContext::current`
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_REQUEST_CONTEXT.scope(self, future).await
+    }
+
+    /// Context of the request the current task is handling, if any
+    ///
+    /// Lets repositories stamp audit fields without threading the context
+    /// through every call. Tasks spawned off the request don't inherit it.
+    pub fn current() -> Option<RequestContext> {
+        CURRENT_REQUEST_CONTEXT.try_with(|ctx| ctx.clone()).ok()
+    }
+
+    /// Record which patient an EHR request touches and why
+    pub fn set_patient_context(&mut self, patient_id: Option<i64>, access_reason: Option<String>) {
+        self.patient_id = patient_id;
+        self.access_reason = access_reason;
+    }
     
     /// Create audit context from request context
     pub fn to_audit_context(&self, system_id: Option<String>) -> crate::shared::AuditContext {
@@ -86,6 +86,8 @@ pub async fn cleanup_database(pool: &PgPool) {
         "request_logs",
         "policy_assignments",
         "user_provisioning_checklists",
+        "encrypted_values",
+        "encryption_keys",
 
         // Base tables
         "users",
@@ -21,3 +21,64 @@ fn test_mask_ssn() {
     assert_eq!(mask_ssn("123-45-6789"), "***-**-6789");
 }
 
+
+#[test]
+fn test_redact_json() {
+    let mut body = serde_json::json!({
+        "username": "jdoe",
+        "password": "hunter2",
+        "patient": { "ssn": "123-45-6789", "name": "Doe" },
+        "keys": [{ "DEK": "abc" }],
+        "newPassword": "hunter3",
+    });
+    redact_json(&mut body);
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "username": "jdoe",
+            "password": REDACTED,
+            "patient": { "ssn": REDACTED, "name": "Doe" },
+            "keys": [{ "DEK": REDACTED }],
+            "newPassword": REDACTED,
+        })
+    );
+}
+
+#[test]
+fn test_pii_masker_strategies() {
+    let body = serde_json::json!({ "patient": { "ssn": "123-45-6789", "name": "Doe" } });
+
+    let redacted = PiiMasker::default().mask(body.clone());
+    assert_eq!(redacted, serde_json::json!({ "patient": { "ssn": REDACTED, "name": "Doe" } }));
+
+    let partial = PiiMasker::new(MaskingConfig {
+        fields: vec!["ssn".to_string()],
+        strategy: MaskingStrategy::Partial(3),
+    });
+    assert_eq!(partial.mask(body.clone())["patient"]["ssn"], "123********");
+
+    let hashed = PiiMasker::new(MaskingConfig {
+        fields: vec!["name".to_string()],
+        strategy: MaskingStrategy::Hash(HashAlgorithm::Sha256),
+    });
+    assert_eq!(
+        hashed.mask(body)["patient"]["name"],
+        "fd53ef835b15485572a6e82cf470dcb41fd218ae5751ab7531c956a2a6bcd3c7"
+    );
+}
+
+#[test]
+fn test_pii_masker_masks_embedded_json() {
+    let masker = PiiMasker::default();
+    assert_eq!(
+        masker.mask_embedded_json(r#"Request body: {"ssn":"123-45-6789"} (200)"#).as_deref(),
+        Some(r#"Request body: {"ssn":"[REDACTED]"} (200)"#)
+    );
+    assert_eq!(masker.mask_embedded_json(r#"Loaded {"name":"Doe"} from {cache"#), None);
+
+    let nested = serde_json::json!({ "request_body": r#"{"credit_card":"4111111111111111"}"# });
+    assert_eq!(
+        masker.mask(nested),
+        serde_json::json!({ "request_body": r#"{"credit_card":"[REDACTED]"}"# })
+    );
+}
@@ -27,6 +27,33 @@
 //!     }
 //! }
 //! ```
+//!
+//! Guards and actions marked `async` (`Cancel [guard: async cancellation_allowed]`)
+//! are generated as `async fn`. A machine with any async hook gets an
+//! `#[async_trait]` trait whose `can_transition` and `transition` are async
+//! too, so implementations must be annotated with `#[async_trait]` as well.
+//! Machines without async hooks are generated exactly as before.
+//!
+//! Each state block may name `on_enter: fn` and `on_exit: fn` hooks. Every
+//! state gets overridable `on_enter_<State>` / `on_exit_<State>` methods
+//! (no-ops unless a hook is named); `transition` calls `on_exit_<from>`
+//! before the transition's action and `on_enter_<to>` after it, then
+//! `on_transition(from, to, ctx)`, a single overridable hook that sees every
+//! state change.
+//!
+//! A transition whose target is its own state (`Remind [action: send_reminder]
+//! => Confirmed` inside `Confirmed`) is a self-transition: the guard and
+//! action run, but the state is unchanged and its hooks do not fire.
+//!
+//! `timeout: duration_fn [action: fn] => Target` inside a state block adds a
+//! `Timeout` event that fires once the state has lasted `duration_fn()`.
+//! `timeout_transitions(state)` lists the armed timeouts, and a
+//! `Timed<Machine><M>` adapter lets `TimeoutScheduler` fire them.
+//!
+//! Every machine also gets `diagram()` (Mermaid `stateDiagram-v2`) and
+//! `diagram_dot()` (Graphviz DOT), rendered from the definition at compile
+//! time. Edges are labelled with the event, guards appear as `[guard]` and
+//! states without outgoing transitions are drawn as terminal states.
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
@@ -39,22 +66,62 @@ use syn::{
     Ident, Result, Token,
 };
 
-/// A single transition: `Event [guard: fn, action: fn] => TargetState`
+/// A guard or action function, optionally marked `async`
+#[derive(Clone)]
+struct Hook {
+    name: Ident,
+    is_async: bool,
+}
+
+impl Parse for Hook {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let is_async = input.peek(Token![async]);
+        if is_async {
+            input.parse::<Token![async]>()?;
+        }
+        Ok(Hook {
+            name: input.parse()?,
+            is_async,
+        })
+    }
+}
+
+impl Hook {
+    /// Call expression, awaited for async hooks
+    fn call(&self) -> TokenStream2 {
+        let name = &self.name;
+        if self.is_async {
+            quote! { Self::#name(ctx).await }
+        } else {
+            quote! { Self::#name(ctx) }
+        }
+    }
+}
+
+/// A single transition: `Event [guard: [async] fn, action: [async] fn] => TargetState`
 struct Transition {
     event: Ident,
-    guard: Option<Ident>,
-    action: Option<Ident>,
+    guard: Option<Hook>,
+    action: Option<Hook>,
     target: Ident,
+    /// Duration function for `timeout: fn [...] => TargetState` transitions
+    timeout: Option<Ident>,
 }
 
 impl Parse for Transition {
     fn parse(input: ParseStream) -> Result<Self> {
         let event: Ident = input.parse()?;
+        Transition::parse_after_event(event, input)
+    }
+}
 
+impl Transition {
+    /// Parse `[guard: ..., action: ...] => TargetState` following the event
+    fn parse_after_event(event: Ident, input: ParseStream) -> Result<Self> {
         let mut guard = None;
         let mut action = None;
 
-        // Parse optional [guard: fn, action: fn]
+        // Parse optional [guard: [async] fn, action: [async] fn]
         if input.peek(syn::token::Bracket) {
             let content;
             bracketed!(content in input);
@@ -62,7 +129,7 @@ impl Parse for Transition {
             while !content.is_empty() {
                 let attr_name: Ident = content.parse()?;
                 content.parse::<Token![:]>()?;
-                let attr_value: Ident = content.parse()?;
+                let attr_value: Hook = content.parse()?;
 
                 match attr_name.to_string().as_str() {
                     "guard" => guard = Some(attr_value),
@@ -90,13 +157,16 @@ impl Parse for Transition {
             guard,
             action,
             target,
+            timeout: None,
         })
     }
 }
 
-/// A state with its transitions: `StateName => { transitions... }`
+/// A state with its transitions: `StateName => { on_enter: fn, on_exit: fn, transitions... }`
 struct StateDefinition {
     name: Ident,
+    on_enter: Option<Ident>,
+    on_exit: Option<Ident>,
     transitions: Vec<Transition>,
 }
 
@@ -108,16 +178,49 @@ impl Parse for StateDefinition {
         let content;
         braced!(content in input);
 
+        let mut on_enter = None;
+        let mut on_exit = None;
         let mut transitions = Vec::new();
         while !content.is_empty() {
-            transitions.push(content.parse()?);
+            // `hook: fn` entries; transitions never have a colon after the event
+            if content.peek(Ident) && content.peek2(Token![:]) {
+                let hook_name: Ident = content.parse()?;
+                content.parse::<Token![:]>()?;
+                let hook_fn: Ident = content.parse()?;
+                match hook_name.to_string().as_str() {
+                    "on_enter" => on_enter = Some(hook_fn),
+                    "on_exit" => on_exit = Some(hook_fn),
+                    "timeout" => {
+                        let event = Ident::new("Timeout", hook_name.span());
+                        let mut transition = Transition::parse_after_event(event, &content)?;
+                        transition.timeout = Some(hook_fn);
+                        transitions.push(transition);
+                    }
+                    _ => {
+                        return Err(syn::Error::new(
+                            hook_name.span(),
+                            format!(
+                                "Unknown state hook: {}. Expected 'on_enter', 'on_exit' or 'timeout'",
+                                hook_name
+                            ),
+                        ))
+                    }
+                }
+            } else {
+                transitions.push(content.parse()?);
+            }
             // Optional comma/semicolon between transitions
             if content.peek(Token![,]) {
                 content.parse::<Token![,]>()?;
             }
         }
 
-        Ok(StateDefinition { name, transitions })
+        Ok(StateDefinition {
+            name,
+            on_enter,
+            on_exit,
+            transitions,
+        })
     }
 }
 
@@ -170,11 +273,98 @@ impl Parse for StateMachineDefinition {
     }
 }
 
+impl StateMachineDefinition {
+    /// States in order of first appearance, starting with the initial state
+    fn all_states(&self) -> Vec<String> {
+        let mut states = vec![self.initial_state.to_string()];
+        let candidates = self.states.iter().flat_map(|state| {
+            std::iter::once(state.name.to_string())
+                .chain(state.transitions.iter().map(|t| t.target.to_string()))
+        });
+        for name in candidates {
+            if !states.contains(&name) {
+                states.push(name);
+            }
+        }
+        states
+    }
+
+    /// States with no outgoing transitions
+    fn terminal_states(&self) -> Vec<String> {
+        self.all_states()
+            .into_iter()
+            .filter(|name| {
+                !self
+                    .states
+                    .iter()
+                    .any(|state| state.name == name && !state.transitions.is_empty())
+            })
+            .collect()
+    }
+
+    /// Every `(from, transition)` pair, in definition order
+    fn edges(&self) -> impl Iterator<Item = (&Ident, &Transition)> {
+        self.states
+            .iter()
+            .flat_map(|state| state.transitions.iter().map(move |t| (&state.name, t)))
+    }
+}
+
+impl Transition {
+    /// Edge label: `Event [guard] / action`, or `Timeout(duration_fn) ...`
+    fn label(&self) -> String {
+        let mut label = self.event.to_string();
+        if let Some(timeout) = &self.timeout {
+            label.push_str(&format!("({})", timeout));
+        }
+        if let Some(guard) = &self.guard {
+            label.push_str(&format!(" [{}]", guard.name));
+        }
+        if let Some(action) = &self.action {
+            label.push_str(&format!(" / {}", action.name));
+        }
+        label
+    }
+}
+
+/// Render the machine as a Mermaid `stateDiagram-v2`
+fn render_mermaid(def: &StateMachineDefinition) -> String {
+    let mut out = String::from("stateDiagram-v2\n");
+    out.push_str(&format!("    [*] --> {}\n", def.initial_state));
+    for (from, t) in def.edges() {
+        out.push_str(&format!("    {} --> {} : {}\n", from, t.target, t.label()));
+    }
+    for state in def.terminal_states() {
+        out.push_str(&format!("    {} --> [*]\n", state));
+    }
+    out
+}
+
+/// Render the machine as a Graphviz DOT digraph
+fn render_dot(def: &StateMachineDefinition) -> String {
+    let terminal = def.terminal_states();
+    let mut out = format!("digraph {} {{\n", def.machine_name);
+    out.push_str("    rankdir=LR;\n");
+    out.push_str("    __start [shape=point];\n");
+    for state in def.all_states() {
+        let shape = if terminal.contains(&state) { "doublecircle" } else { "circle" };
+        out.push_str(&format!("    {} [shape={}];\n", state, shape));
+    }
+    out.push_str(&format!("    __start -> {};\n", def.initial_state));
+    for (from, t) in def.edges() {
+        out.push_str(&format!("    {} -> {} [label=\"{}\"];\n", from, t.target, t.label()));
+    }
+    out.push_str("}\n");
+    out
+}
+
 /// Generate the state machine implementation
 fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
     let machine_name = def.machine_name.clone();
     let state_enum = def.state_enum.clone();
     let initial_state = def.initial_state.clone();
+    let mermaid = render_mermaid(&def);
+    let dot = render_dot(&def);
 
     // Collect all unique events
     let mut all_events: Vec<Ident> = Vec::new();
@@ -204,7 +394,7 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
                 let event_enum_name = event_enum_name.clone();
                 let state_name = state_name.clone();
                 let guard_check = if let Some(guard) = &t.guard {
-                    quote! { Self::#guard(ctx) }
+                    guard.call()
                 } else {
                     quote! { true }
                 };
@@ -231,12 +421,14 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
                 let state_name = state_name.clone();
 
                 let guard_check = if let Some(guard) = &t.guard {
+                    let guard_call = guard.call();
+                    let guard_name = &guard.name;
                     quote! {
-                        if !Self::#guard(ctx) {
+                        if !#guard_call {
                             return std::result::Result::Err(TransitionError::GuardFailed {
                                 from: stringify!(#state_name).to_string(),
                                 event: stringify!(#event).to_string(),
-                                guard: stringify!(#guard).to_string(),
+                                guard: stringify!(#guard_name).to_string(),
                             });
                         }
                     }
@@ -245,15 +437,32 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
                 };
 
                 let action_call = if let Some(action) = &t.action {
-                    quote! { Self::#action(ctx); }
+                    let call = action.call();
+                    quote! { #call; }
                 } else {
                     quote! {}
                 };
+                // A self-transition stays in its state, so only the action runs
+                let (on_exit_call, on_enter_call) = if state_name == target {
+                    (quote! {}, quote! {})
+                } else {
+                    let on_exit = format_ident!("on_exit_{}", state_name);
+                    let on_enter = format_ident!("on_enter_{}", target);
+                    (
+                        quote! { Self::#on_exit(ctx); },
+                        quote! {
+                            Self::#on_enter(ctx);
+                            Self::on_transition(&#state_enum::#state_name, &#state_enum::#target, ctx);
+                        },
+                    )
+                };
 
                 quote! {
                     (#state_enum::#state_name, #event_enum_name::#event) => {
                         #guard_check
+                        #on_exit_call
                         #action_call
+                        #on_enter_call
                         std::result::Result::Ok(#state_enum::#target)
                     }
                 }
@@ -309,31 +518,129 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
     let defined_states: Vec<_> = def.states.iter().map(|s| &s.name).collect();
 
     // Collect unique guards and actions (deduplicate)
-    let mut unique_guards: Vec<Ident> = Vec::new();
-    let mut unique_actions: Vec<Ident> = Vec::new();
+    let mut unique_guards: Vec<Hook> = Vec::new();
+    let mut unique_actions: Vec<Hook> = Vec::new();
 
     for state in &def.states {
         for t in &state.transitions {
             if let Some(g) = &t.guard {
-                if !unique_guards.iter().any(|x| x == g) {
+                if !unique_guards.iter().any(|x| x.name == g.name) {
                     unique_guards.push(g.clone());
                 }
             }
             if let Some(a) = &t.action {
-                if !unique_actions.iter().any(|x| x == a) {
+                if !unique_actions.iter().any(|x| x.name == a.name) {
                     unique_actions.push(a.clone());
                 }
             }
         }
     }
 
+    // One async hook makes the whole trait async
+    let is_async = unique_guards.iter().chain(&unique_actions).any(|h| h.is_async);
+    let (trait_attr, ctx_bounds, transition_async) = if is_async {
+        (
+            quote! { #[::async_trait::async_trait] },
+            quote! { : Send + Sync },
+            quote! { async },
+        )
+    } else {
+        (quote! {}, quote! {}, quote! {})
+    };
+
+    // on_enter_<State>/on_exit_<State> for every state, no-ops unless the
+    // state block names a hook
+    let mut state_hook_fns: Vec<Ident> = Vec::new();
+    let state_hooks: Vec<_> = def
+        .all_states()
+        .iter()
+        .map(|name| {
+            let state = def.states.iter().find(|s| s.name == name);
+            let on_enter = format_ident!("on_enter_{}", name);
+            let on_exit = format_ident!("on_exit_{}", name);
+            let enter_body = match state.and_then(|s| s.on_enter.as_ref()) {
+                Some(hook) => quote! { Self::#hook(ctx) },
+                None => quote! {},
+            };
+            let exit_body = match state.and_then(|s| s.on_exit.as_ref()) {
+                Some(hook) => quote! { Self::#hook(ctx) },
+                None => quote! {},
+            };
+            for hook in state.into_iter().flat_map(|s| s.on_enter.iter().chain(&s.on_exit)) {
+                let is_action = unique_actions.iter().any(|a| &a.name == hook);
+                if !is_action && !state_hook_fns.contains(hook) {
+                    state_hook_fns.push(hook.clone());
+                }
+            }
+            quote! {
+                /// Called whenever the machine enters this state
+                #[allow(non_snake_case, unused_variables)]
+                fn #on_enter(ctx: &mut Ctx) {
+                    #enter_body
+                }
+
+                /// Called whenever the machine leaves this state
+                #[allow(non_snake_case, unused_variables)]
+                fn #on_exit(ctx: &mut Ctx) {
+                    #exit_body
+                }
+            }
+        })
+        .collect();
+
+    let state_hook_stubs: Vec<_> = state_hook_fns
+        .iter()
+        .map(|name| {
+            quote! {
+                /// State hook: executed on entering or leaving a state
+                fn #name(ctx: &mut Ctx);
+            }
+        })
+        .collect();
+
+    // Timeout transitions: duration functions and timeout_transitions arms
+    let mut timeout_fns: Vec<Ident> = Vec::new();
+    let mut timeout_arms = Vec::new();
+    for state in &def.states {
+        let state_name = &state.name;
+        let timeouts: Vec<_> = state
+            .transitions
+            .iter()
+            .filter_map(|t| {
+                let timeout = t.timeout.as_ref()?;
+                if !timeout_fns.contains(timeout) {
+                    timeout_fns.push(timeout.clone());
+                }
+                let event = &t.event;
+                Some(quote! { (Self::#timeout(), #event_enum_name::#event) })
+            })
+            .collect();
+        if !timeouts.is_empty() {
+            timeout_arms.push(quote! {
+                #state_enum::#state_name => vec![#(#timeouts),*]
+            });
+        }
+    }
+
+    let timeout_stubs: Vec<_> = timeout_fns
+        .iter()
+        .map(|name| {
+            quote! {
+                /// Timeout: how long a state may last before its timeout transition fires
+                fn #name() -> std::time::Duration;
+            }
+        })
+        .collect();
+
     // Generate guard/action stub traits
     let guard_stubs: Vec<_> = unique_guards
         .iter()
         .map(|g| {
+            let name = &g.name;
+            let asyncness = if g.is_async { quote! { async } } else { quote! {} };
             quote! {
                 /// Guard function: returns true if transition is allowed
-                fn #g(ctx: &Ctx) -> bool;
+                #asyncness fn #name(ctx: &Ctx) -> bool;
             }
         })
         .collect();
@@ -341,14 +648,52 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
     let action_stubs: Vec<_> = unique_actions
         .iter()
         .map(|a| {
+            let name = &a.name;
+            let asyncness = if a.is_async { quote! { async } } else { quote! {} };
             quote! {
                 /// Action function: executed when transition occurs
-                fn #a(ctx: &mut Ctx);
+                #asyncness fn #name(ctx: &mut Ctx);
             }
         })
         .collect();
 
+    // Adapter so TimeoutScheduler can drive implementations of this trait
+    let timed_adapter = if timeout_fns.is_empty() {
+        quote! {}
+    } else if is_async {
+        quote! {
+            compile_error!("timeout transitions are not supported on machines with async guards or actions");
+        }
+    } else {
+        let timed_name = format_ident!("Timed{}", machine_name);
+        quote! {
+            /// Drives an implementation `M` from a `TimeoutScheduler`
+            ///
+            /// Note: TimedStateMachine must be in scope (use `shared::domain::state_machine::TimedStateMachine`)
+            pub struct #timed_name<M>(std::marker::PhantomData<M>);
+
+            impl<Ctx, M: #machine_name<Ctx>> TimedStateMachine<Ctx> for #timed_name<M> {
+                type State = #state_enum;
+                type Event = #event_enum_name;
+
+                fn timeout_transitions(state: &#state_enum) -> Vec<(std::time::Duration, #event_enum_name)> {
+                    M::timeout_transitions(state)
+                }
+
+                fn transition(
+                    state: &#state_enum,
+                    event: #event_enum_name,
+                    ctx: &mut Ctx,
+                ) -> std::result::Result<#state_enum, TransitionError> {
+                    M::transition(state, event, ctx)
+                }
+            }
+        }
+    };
+
     quote! {
+        #timed_adapter
+
         /// Events that can trigger state transitions
         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         pub enum #event_enum_name {
@@ -364,15 +709,24 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
         }
 
         /// State machine trait - implement guards and actions
-        pub trait #machine_name<Ctx> {
+        #trait_attr
+        pub trait #machine_name<Ctx #ctx_bounds> {
             /// Initial state
             const INITIAL: #state_enum = #state_enum::#initial_state;
 
             #(#guard_stubs)*
             #(#action_stubs)*
+            #(#state_hook_stubs)*
+            #(#timeout_stubs)*
+            #(#state_hooks)*
+
+            /// Called after every transition that changes state, once its
+            /// action and state hooks have run
+            #[allow(unused_variables)]
+            fn on_transition(from: &#state_enum, to: &#state_enum, ctx: &mut Ctx) {}
 
             /// Check if a transition is valid without executing it
-            fn can_transition(state: &#state_enum, event: &#event_enum_name, ctx: &Ctx) -> bool {
+            #transition_async fn can_transition(state: &#state_enum, event: &#event_enum_name, ctx: &Ctx) -> bool {
                 match (state, event) {
                     #(#can_transition_arms,)*
                     _ => false,
@@ -382,7 +736,7 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
             /// Execute a state transition
             ///
             /// Note: TransitionError must be in scope (use `shared::domain::state_machine::TransitionError`)
-            fn transition(
+            #transition_async fn transition(
                 state: &#state_enum,
                 event: #event_enum_name,
                 ctx: &mut Ctx,
@@ -412,6 +766,23 @@ fn generate_state_machine(def: StateMachineDefinition) -> TokenStream2 {
                 }
             }
 
+            /// Timeout transitions armed while in `state`, as (duration, event) pairs
+            fn timeout_transitions(state: &#state_enum) -> Vec<(std::time::Duration, #event_enum_name)> {
+                match state {
+                    #(#timeout_arms,)*
+                    _ => vec![],
+                }
+            }
+
+            /// Mermaid `stateDiagram-v2` of this machine
+            fn diagram() -> &'static str {
+                #mermaid
+            }
+
+            /// Graphviz DOT digraph of this machine
+            fn diagram_dot() -> &'static str {
+                #dot
+            }
         }
     }
 }
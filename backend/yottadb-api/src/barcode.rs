@@ -0,0 +1,453 @@
+//! Prescription label barcodes (GS1 DataMatrix / QR)
+//!
+//! A label encodes its prescription as a GS1 element string:
+//!
+//! | AI | Field | Length |
+//! |----|-------|--------|
+//! | 01 | GTIN-14 built from the 10-digit NDC | 14 |
+//! | 17 | Expiration date (YYMMDD) | 6 |
+//! | 13 | Fill (packaging) date (YYMMDD) | 6 |
+//! | 10 | Lot number | up to 20 |
+//! | 30 | Quantity | up to 8 |
+//! | 91 | RX number (company internal) | up to 90 |
+//! | 92 | Patient IEN (company internal) | up to 90 |
+//!
+//! Variable-length elements are terminated by the GS character (FNC1 in the
+//! symbol) unless they come last. Scanners report the same string, possibly
+//! prefixed with a symbology identifier such as `]d2`; the human readable
+//! `(01)...(17)...` form is accepted as well.
+
+use std::io::Cursor;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use rxing::{EncodeHintValue, EncodeHints, MultiFormatWriter, Writer};
+use serde::Serialize;
+use thiserror::Error;
+
+/// GS1 group separator, encoded as FNC1
+pub const GS: char = '\u{1d}';
+
+/// Pixels per barcode module in rendered images
+const MODULE_SIZE: u32 = 8;
+/// Blank modules around the symbol
+const QUIET_ZONE: u32 = 2;
+
+/// GTIN-14 prefix for a 10-digit NDC: indicator digit 0 and the `03` US drug prefix
+const NDC_GTIN_PREFIX: &str = "003";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BarcodeError {
+    #[error("Unsupported barcode format: {0} (expected datamatrix or qr)")]
+    UnsupportedFormat(String),
+    #[error("Drug code {0} is not a 10-digit NDC")]
+    InvalidNdc(String),
+    #[error("GTIN {0} is not an NDC-based GTIN-14")]
+    InvalidGtin(String),
+    #[error("Invalid date for AI {0}: {1}")]
+    InvalidDate(&'static str, String),
+    #[error("Unknown application identifier at: {0}")]
+    UnknownIdentifier(String),
+    #[error("AI {0} is truncated or too long")]
+    InvalidLength(&'static str),
+    #[error("Barcode is missing AI {0}")]
+    MissingIdentifier(&'static str),
+    #[error("Invalid value for AI {0}: {1}")]
+    InvalidValue(&'static str, String),
+    #[error("Barcode rendering failed: {0}")]
+    Render(String),
+}
+
+/// Symbology of a rendered label
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarcodeFormat {
+    DataMatrix,
+    Qr,
+}
+
+impl BarcodeFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::DataMatrix => "datamatrix",
+            Self::Qr => "qr",
+        }
+    }
+
+    fn symbology(self) -> rxing::BarcodeFormat {
+        match self {
+            Self::DataMatrix => rxing::BarcodeFormat::DATA_MATRIX,
+            Self::Qr => rxing::BarcodeFormat::QR_CODE,
+        }
+    }
+}
+
+impl FromStr for BarcodeFormat {
+    type Err = BarcodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "datamatrix" | "data_matrix" => Ok(Self::DataMatrix),
+            "qr" | "qrcode" => Ok(Self::Qr),
+            other => Err(BarcodeError::UnsupportedFormat(other.to_string())),
+        }
+    }
+}
+
+/// Prescription fields carried on a dispense label
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrescriptionLabel {
+    #[serde(rename = "rxNumber")]
+    pub rx_number: String,
+    #[serde(rename = "patientIen")]
+    pub patient_ien: i64,
+    /// NDC, digits only
+    #[serde(rename = "drugCode")]
+    pub drug_code: String,
+    pub quantity: i32,
+    #[serde(rename = "fillDate")]
+    pub fill_date: Option<NaiveDate>,
+    #[serde(rename = "expirationDate")]
+    pub expiration_date: Option<NaiveDate>,
+    #[serde(rename = "lotNumber")]
+    pub lot_number: Option<String>,
+}
+
+impl PrescriptionLabel {
+    /// GS1 element string, with GS after each variable-length element but the last
+    pub fn to_gs1(&self) -> Result<String, BarcodeError> {
+        let mut fixed = format!("01{}", ndc_to_gtin(&self.drug_code)?);
+        if let Some(expiration) = self.expiration_date {
+            fixed.push_str(&format!("17{}", expiration.format("%y%m%d")));
+        }
+        if let Some(fill) = self.fill_date {
+            fixed.push_str(&format!("13{}", fill.format("%y%m%d")));
+        }
+
+        let mut variable = Vec::new();
+        if let Some(lot) = self.lot_number.as_deref().filter(|lot| !lot.is_empty()) {
+            variable.push(("10", lot.to_string()));
+        }
+        variable.push(("30", self.quantity.to_string()));
+        variable.push(("91", self.rx_number.clone()));
+        variable.push(("92", self.patient_ien.to_string()));
+
+        let mut data = fixed;
+        for (i, (ai, value)) in variable.iter().enumerate() {
+            let spec = ai_spec(ai).ok_or_else(|| BarcodeError::UnknownIdentifier(ai.to_string()))?;
+            if value.is_empty() || value.len() > spec.max_len || value.contains(GS) {
+                return Err(BarcodeError::InvalidValue(spec.ai, value.clone()));
+            }
+            if i > 0 {
+                data.push(GS);
+            }
+            data.push_str(ai);
+            data.push_str(value);
+        }
+        Ok(data)
+    }
+
+    /// Parse a scanned GS1 element string
+    pub fn from_gs1(payload: &str) -> Result<Self, BarcodeError> {
+        let mut rx_number = None;
+        let mut patient_ien = None;
+        let mut drug_code = None;
+        let mut quantity = None;
+        let mut fill_date = None;
+        let mut expiration_date = None;
+        let mut lot_number = None;
+
+        for (spec, value) in parse_elements(payload)? {
+            match spec.ai {
+                "01" => drug_code = Some(gtin_to_ndc(&value)?),
+                "17" => expiration_date = Some(parse_yymmdd(spec.ai, &value)?),
+                "13" => fill_date = Some(parse_yymmdd(spec.ai, &value)?),
+                "10" => lot_number = Some(value),
+                "30" => quantity = Some(parse_number(spec.ai, &value)?),
+                "91" => rx_number = Some(value),
+                "92" => patient_ien = Some(parse_number(spec.ai, &value)?),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            rx_number: rx_number.ok_or(BarcodeError::MissingIdentifier("91"))?,
+            patient_ien: patient_ien.ok_or(BarcodeError::MissingIdentifier("92"))?,
+            drug_code: drug_code.ok_or(BarcodeError::MissingIdentifier("01"))?,
+            quantity: quantity.ok_or(BarcodeError::MissingIdentifier("30"))?,
+            fill_date,
+            expiration_date,
+            lot_number,
+        })
+    }
+
+    /// Fields of this (scanned) label that differ from the prescription on record
+    ///
+    /// Optional fields absent from the label are not compared.
+    pub fn mismatches(&self, expected: &PrescriptionLabel) -> Vec<&'static str> {
+        let mut fields = Vec::new();
+        if self.rx_number != expected.rx_number {
+            fields.push("rxNumber");
+        }
+        if self.patient_ien != expected.patient_ien {
+            fields.push("patientIen");
+        }
+        if self.drug_code != ndc_digits(&expected.drug_code) {
+            fields.push("drugCode");
+        }
+        if self.quantity != expected.quantity {
+            fields.push("quantity");
+        }
+        if self.fill_date.is_some() && self.fill_date != expected.fill_date {
+            fields.push("fillDate");
+        }
+        if self.expiration_date.is_some() && self.expiration_date != expected.expiration_date {
+            fields.push("expirationDate");
+        }
+        if self.lot_number.is_some() && self.lot_number != expected.lot_number {
+            fields.push("lotNumber");
+        }
+        fields
+    }
+}
+
+/// Render `data` as a PNG image
+pub fn render_png(data: &str, format: BarcodeFormat) -> Result<Vec<u8>, BarcodeError> {
+    let hints = EncodeHints::default().with(EncodeHintValue::Gs1Format(true));
+    let matrix = MultiFormatWriter
+        .encode_with_hints(data, &format.symbology(), 0, 0, &hints)
+        .map_err(|e| BarcodeError::Render(e.to_string()))?;
+
+    let (width, height) = (matrix.getWidth(), matrix.getHeight());
+    let image = image::GrayImage::from_fn(
+        (width + 2 * QUIET_ZONE) * MODULE_SIZE,
+        (height + 2 * QUIET_ZONE) * MODULE_SIZE,
+        |x, y| {
+            let (x, y) = (x / MODULE_SIZE, y / MODULE_SIZE);
+            let dark = x >= QUIET_ZONE
+                && y >= QUIET_ZONE
+                && x - QUIET_ZONE < width
+                && y - QUIET_ZONE < height
+                && matrix.get(x - QUIET_ZONE, y - QUIET_ZONE);
+            image::Luma([if dark { 0 } else { 255 }])
+        },
+    );
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| BarcodeError::Render(e.to_string()))?;
+    Ok(png.into_inner())
+}
+
+/// Parse a date as stored on a prescription (YYYYMMDD or YYYY-MM-DD)
+pub fn parse_stored_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .ok()
+}
+
+struct AiSpec {
+    ai: &'static str,
+    /// Fixed-length elements need no separator
+    fixed: bool,
+    max_len: usize,
+}
+
+const AI_SPECS: &[AiSpec] = &[
+    AiSpec { ai: "01", fixed: true, max_len: 14 },
+    AiSpec { ai: "10", fixed: false, max_len: 20 },
+    AiSpec { ai: "13", fixed: true, max_len: 6 },
+    AiSpec { ai: "17", fixed: true, max_len: 6 },
+    AiSpec { ai: "30", fixed: false, max_len: 8 },
+    AiSpec { ai: "91", fixed: false, max_len: 90 },
+    AiSpec { ai: "92", fixed: false, max_len: 90 },
+];
+
+fn ai_spec(ai: &str) -> Option<&'static AiSpec> {
+    AI_SPECS.iter().find(|spec| spec.ai == ai)
+}
+
+/// Split an element string into (AI, value) pairs
+fn parse_elements(payload: &str) -> Result<Vec<(&'static AiSpec, String)>, BarcodeError> {
+    let payload = payload.trim();
+    // Symbology identifiers: GS1 DataMatrix, GS1 QR, GS1-128
+    let payload = ["]d2", "]Q3", "]C1"]
+        .iter()
+        .find_map(|prefix| payload.strip_prefix(prefix))
+        .unwrap_or(payload);
+
+    if payload.starts_with('(') {
+        return parse_human_readable(payload);
+    }
+
+    let mut elements = Vec::new();
+    let mut rest = payload.trim_start_matches(GS);
+    while !rest.is_empty() {
+        let spec = rest.get(..2).and_then(ai_spec)
+            .ok_or_else(|| BarcodeError::UnknownIdentifier(rest.chars().take(10).collect()))?;
+        rest = &rest[2..];
+
+        let end = if spec.fixed {
+            if rest.len() < spec.max_len || !rest.is_char_boundary(spec.max_len) {
+                return Err(BarcodeError::InvalidLength(spec.ai));
+            }
+            spec.max_len
+        } else {
+            rest.find(GS).unwrap_or(rest.len())
+        };
+        let value = &rest[..end];
+        if value.is_empty() || value.len() > spec.max_len {
+            return Err(BarcodeError::InvalidLength(spec.ai));
+        }
+        elements.push((spec, value.to_string()));
+        rest = rest[end..].trim_start_matches(GS);
+    }
+    Ok(elements)
+}
+
+/// Parse the `(01)00312345678906(17)261231...` form printed under a symbol
+fn parse_human_readable(payload: &str) -> Result<Vec<(&'static AiSpec, String)>, BarcodeError> {
+    let mut elements = Vec::new();
+    for part in payload.split('(').skip(1) {
+        let (ai, value) = part.split_once(')')
+            .ok_or_else(|| BarcodeError::UnknownIdentifier(part.to_string()))?;
+        let spec = ai_spec(ai).ok_or_else(|| BarcodeError::UnknownIdentifier(ai.to_string()))?;
+        let value = value.trim();
+        let valid_length = if spec.fixed { value.len() == spec.max_len } else { value.len() <= spec.max_len };
+        if value.is_empty() || !valid_length {
+            return Err(BarcodeError::InvalidLength(spec.ai));
+        }
+        elements.push((spec, value.to_string()));
+    }
+    Ok(elements)
+}
+
+fn ndc_digits(ndc: &str) -> String {
+    ndc.chars().filter(char::is_ascii_digit).collect()
+}
+
+/// GTIN-14 for a 10-digit NDC (hyphens ignored)
+pub fn ndc_to_gtin(ndc: &str) -> Result<String, BarcodeError> {
+    let digits = ndc_digits(ndc);
+    if digits.len() != 10 || ndc.chars().any(|c| !c.is_ascii_digit() && c != '-') {
+        return Err(BarcodeError::InvalidNdc(ndc.to_string()));
+    }
+    let body = format!("{}{}", NDC_GTIN_PREFIX, digits);
+    let check = gs1_check_digit(&body);
+    Ok(format!("{}{}", body, check))
+}
+
+/// 10-digit NDC from an NDC-based GTIN-14, verifying the check digit
+pub fn gtin_to_ndc(gtin: &str) -> Result<String, BarcodeError> {
+    let invalid = || BarcodeError::InvalidGtin(gtin.to_string());
+    if gtin.len() != 14 || !gtin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let (body, check) = gtin.split_at(13);
+    if gs1_check_digit(body).to_string() != check {
+        return Err(invalid());
+    }
+    body.strip_prefix(NDC_GTIN_PREFIX).map(str::to_string).ok_or_else(invalid)
+}
+
+/// GS1 mod-10 check digit: weights 3,1,3,... from the rightmost digit
+fn gs1_check_digit(digits: &str) -> u32 {
+    let sum: u32 = digits
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { d })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+fn parse_yymmdd(ai: &'static str, value: &str) -> Result<NaiveDate, BarcodeError> {
+    let invalid = || BarcodeError::InvalidDate(ai, value.to_string());
+    if value.len() != 6 || !value.is_ascii() {
+        return Err(invalid());
+    }
+    let (yy, mmdd) = value.split_at(2);
+    let yy: i32 = yy.parse().map_err(|_| invalid())?;
+    // GS1 day 00 means the last day of the month; labels always carry a real day
+    NaiveDate::parse_from_str(&format!("{}{}", 2000 + yy, mmdd), "%Y%m%d").map_err(|_| invalid())
+}
+
+fn parse_number<T: FromStr>(ai: &'static str, value: &str) -> Result<T, BarcodeError> {
+    value.parse().map_err(|_| BarcodeError::InvalidValue(ai, value.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label() -> PrescriptionLabel {
+        PrescriptionLabel {
+            rx_number: "RX2642".to_string(),
+            patient_ien: 1017,
+            drug_code: "0002322730".to_string(),
+            quantity: 30,
+            fill_date: NaiveDate::from_ymd_opt(2026, 10, 16),
+            expiration_date: NaiveDate::from_ymd_opt(2027, 4, 30),
+            lot_number: Some("LOT-A12".to_string()),
+        }
+    }
+
+    #[test]
+    fn gs1_round_trips_prescription_fields() {
+        let data = label().to_gs1().unwrap();
+        assert_eq!(
+            data,
+            "0100300023227300172704301326101610LOT-A12\u{1d}3030\u{1d}91RX2642\u{1d}921017"
+        );
+
+        assert_eq!(PrescriptionLabel::from_gs1(&data).unwrap(), label());
+        // As reported by a scanner, and as printed under the symbol
+        assert_eq!(PrescriptionLabel::from_gs1(&format!("]d2{}", data)).unwrap(), label());
+        let printed = "(01)00300023227300(17)270430(13)261016(10)LOT-A12(30)30(91)RX2642(92)1017";
+        assert_eq!(PrescriptionLabel::from_gs1(printed).unwrap(), label());
+    }
+
+    #[test]
+    fn ndc_converts_to_gtin_with_check_digit() {
+        assert_eq!(ndc_to_gtin("0002-3227-30").unwrap(), "00300023227300");
+        assert_eq!(gtin_to_ndc("00300023227300").unwrap(), "0002322730");
+        assert!(matches!(gtin_to_ndc("00300023227301"), Err(BarcodeError::InvalidGtin(_))));
+        assert!(matches!(ndc_to_gtin("00002-3227-30"), Err(BarcodeError::InvalidNdc(_))));
+    }
+
+    #[test]
+    fn mismatches_report_differing_fields() {
+        let on_record = label();
+        let mut scanned = label();
+        assert!(scanned.mismatches(&on_record).is_empty());
+
+        scanned.quantity = 60;
+        scanned.lot_number = Some("LOT-B7".to_string());
+        assert_eq!(scanned.mismatches(&on_record), vec!["quantity", "lotNumber"]);
+
+        // Labels printed before dispensing carry no lot
+        scanned = PrescriptionLabel { lot_number: None, ..label() };
+        assert!(scanned.mismatches(&on_record).is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_identifiers_and_truncated_values() {
+        assert!(matches!(PrescriptionLabel::from_gs1("2112345"), Err(BarcodeError::UnknownIdentifier(_))));
+        assert_eq!(PrescriptionLabel::from_gs1("01003000"), Err(BarcodeError::InvalidLength("01")));
+        assert_eq!(
+            PrescriptionLabel::from_gs1("0100300023227300\u{1d}3030"),
+            Err(BarcodeError::MissingIdentifier("91"))
+        );
+    }
+
+    #[test]
+    fn renders_png() {
+        let data = label().to_gs1().unwrap();
+        for format in [BarcodeFormat::DataMatrix, BarcodeFormat::Qr] {
+            let png = render_png(&data, format).unwrap();
+            assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        }
+    }
+}
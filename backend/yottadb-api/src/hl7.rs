@@ -0,0 +1,306 @@
+//! HL7 v2 ADT message parsing
+//!
+//! Hospital feeds send demographics as `ADT^A01` (admit), `ADT^A08` (update)
+//! and `ADT^A40` (merge). Only the segments carrying demographics are read:
+//! MSH for the event type, PID for the patient and MRG for the record being
+//! merged away. Delimiters are taken from MSH, so feeds using non-default
+//! encoding characters or `\n` segment terminators parse the same way.
+
+use chrono::NaiveDate;
+use thiserror::Error;
+
+use crate::{CreatePatientRequest, PatientUpdateRequest};
+
+/// MLLP block framing bytes, left in place by some interface engines
+const MLLP_START: char = '\u{0b}';
+const MLLP_END: char = '\u{1c}';
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Hl7Error {
+    #[error("Message does not start with an MSH segment")]
+    MissingHeader,
+    #[error("Message has no {0} segment")]
+    MissingSegment(&'static str),
+    #[error("{0} is required")]
+    MissingField(&'static str),
+    #[error("Unsupported message type: {0}")]
+    UnsupportedEvent(String),
+    #[error("Invalid date of birth: {0}")]
+    InvalidDate(String),
+}
+
+/// Separators declared in MSH-1 and MSH-2
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delimiters {
+    pub segment: &'static str,
+    pub field: char,
+    pub component: char,
+    pub repetition: char,
+    pub escape: char,
+    pub subcomponent: char,
+}
+
+impl Delimiters {
+    /// Read the delimiters from the MSH segment at the start of `message`
+    pub fn detect(message: &str) -> Result<Self, Hl7Error> {
+        let mut chars = message.strip_prefix("MSH").ok_or(Hl7Error::MissingHeader)?.chars();
+        let field = chars.next().ok_or(Hl7Error::MissingHeader)?;
+        let encoding: Vec<char> = chars
+            .take_while(|c| *c != field && *c != '\r' && *c != '\n')
+            .collect();
+        let encoding_char = |i: usize, default: char| encoding.get(i).copied().unwrap_or(default);
+
+        let segment = match message.find(['\r', '\n']) {
+            Some(end) if message[end..].starts_with("\r\n") => "\r\n",
+            Some(end) if message[end..].starts_with('\n') => "\n",
+            _ => "\r",
+        };
+
+        Ok(Self {
+            segment,
+            field,
+            component: encoding_char(0, '^'),
+            repetition: encoding_char(1, '~'),
+            escape: encoding_char(2, '\\'),
+            subcomponent: encoding_char(3, '&'),
+        })
+    }
+
+    /// Replace HL7 escape sequences (`\F\`, `\S\`, ...) with the characters they stand for
+    fn unescape(&self, value: &str) -> String {
+        let mut out = String::with_capacity(value.len());
+        let mut parts = value.split(self.escape);
+        out.push_str(parts.next().unwrap_or(""));
+        // Escape sequences sit between pairs of escape characters
+        while let Some(sequence) = parts.next() {
+            match sequence {
+                "F" => out.push(self.field),
+                "S" => out.push(self.component),
+                "T" => out.push(self.subcomponent),
+                "R" => out.push(self.repetition),
+                "E" => out.push(self.escape),
+                // Formatting and hex sequences carry nothing for demographics
+                _ => {}
+            }
+            out.push_str(parts.next().unwrap_or(""));
+        }
+        out
+    }
+}
+
+/// A parsed segment; fields are numbered as in the HL7 spec
+struct Segment<'a> {
+    id: &'a str,
+    fields: Vec<&'a str>,
+}
+
+impl<'a> Segment<'a> {
+    fn field(&self, n: usize) -> &'a str {
+        // MSH-1 is the field separator itself, so MSH-2 is the first split field
+        let index = if self.id == "MSH" { n.saturating_sub(1) } else { n };
+        self.fields.get(index).copied().unwrap_or("")
+    }
+}
+
+/// ADT event, mapped to the patient operation it triggers
+#[derive(Debug)]
+pub enum AdtMessage {
+    /// A01: admit, registering the patient if the MRN is new
+    Admit(CreatePatientRequest),
+    /// A08: demographics changed for the patient with this MRN
+    Update { mrn: String, update: PatientUpdateRequest },
+    /// A40: `prior_mrn` is a duplicate of `mrn` and is merged into it
+    Merge { mrn: String, prior_mrn: String },
+}
+
+pub struct AdtParser {
+    delimiters: Delimiters,
+}
+
+impl AdtParser {
+    /// Parse an ADT A01, A08 or A40 message
+    pub fn parse(message: &str) -> Result<AdtMessage, Hl7Error> {
+        let message = message.trim_start_matches(|c: char| c == MLLP_START || c.is_whitespace());
+        let message = message.trim_end_matches(|c: char| c == MLLP_END || c.is_whitespace());
+        let parser = Self { delimiters: Delimiters::detect(message)? };
+
+        let segments: Vec<Segment> = message
+            .split(parser.delimiters.segment)
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| {
+                let fields: Vec<&str> = s.split(parser.delimiters.field).collect();
+                Segment { id: fields[0], fields }
+            })
+            .collect();
+        let segment = |id: &'static str| {
+            segments.iter().find(|s| s.id == id).ok_or(Hl7Error::MissingSegment(id))
+        };
+
+        let msh = segment("MSH")?;
+        let message_type = parser.components(msh.field(9));
+        let mut event = message_type.get(1).copied().unwrap_or("").to_string();
+        if event.is_empty() {
+            // HL7 2.2 and earlier carried the trigger event in EVN-1 only
+            event = segments.iter().find(|s| s.id == "EVN").map_or("", |evn| evn.field(1)).to_string();
+        }
+        let message_code = message_type.first().copied().unwrap_or("");
+        if message_code != "ADT" {
+            return Err(Hl7Error::UnsupportedEvent(format!("{}^{}", message_code, event)));
+        }
+
+        let pid = segment("PID")?;
+        match event.as_str() {
+            "A01" => parser.admit(pid).map(AdtMessage::Admit),
+            "A08" => {
+                let mrn = parser.mrn(pid.field(3)).ok_or(Hl7Error::MissingField("PID-3 patient identifier"))?;
+                Ok(AdtMessage::Update { mrn, update: parser.update(pid)? })
+            }
+            "A40" => {
+                let mrn = parser.mrn(pid.field(3)).ok_or(Hl7Error::MissingField("PID-3 patient identifier"))?;
+                let prior_mrn = parser
+                    .mrn(segment("MRG")?.field(1))
+                    .ok_or(Hl7Error::MissingField("MRG-1 prior patient identifier"))?;
+                Ok(AdtMessage::Merge { mrn, prior_mrn })
+            }
+            _ => Err(Hl7Error::UnsupportedEvent(format!("ADT^{}", event))),
+        }
+    }
+
+    fn admit(&self, pid: &Segment) -> Result<CreatePatientRequest, Hl7Error> {
+        let update = self.update(pid)?;
+        Ok(CreatePatientRequest {
+            last_name: update.last_name.ok_or(Hl7Error::MissingField("PID-5 patient name"))?,
+            first_name: update.first_name.unwrap_or_default(),
+            sex: update.sex.unwrap_or_else(|| "U".to_string()),
+            date_of_birth: update.date_of_birth.ok_or(Hl7Error::MissingField("PID-7 date of birth"))?,
+            ssn: update.ssn,
+            mrn: Some(self.mrn(pid.field(3)).ok_or(Hl7Error::MissingField("PID-3 patient identifier"))?),
+            city: update.city,
+            state: update.state,
+        })
+    }
+
+    /// Demographics present in PID; empty fields mean "unchanged"
+    fn update(&self, pid: &Segment) -> Result<PatientUpdateRequest, Hl7Error> {
+        let name = self.components(self.first_repetition(pid.field(5)));
+        let address = self.components(self.first_repetition(pid.field(11)));
+        let date_of_birth = match pid.field(7) {
+            "" => None,
+            ts => Some(parse_date(ts)?),
+        };
+
+        Ok(PatientUpdateRequest {
+            last_name: self.value(name.first().copied()),
+            first_name: self.value(name.get(1).copied()),
+            sex: self.value(Some(pid.field(8))).map(|s| s.to_uppercase()),
+            date_of_birth,
+            ssn: self.value(Some(pid.field(19))),
+            city: self.value(address.get(2).copied()),
+            state: self.value(address.get(3).copied()),
+        })
+    }
+
+    /// MRN from a PID-3/MRG-1 identifier list: the `MR` typed entry, else the first
+    fn mrn(&self, identifiers: &str) -> Option<String> {
+        let ids: Vec<Vec<&str>> = identifiers
+            .split(self.delimiters.repetition)
+            .map(|id| self.components(id))
+            .collect();
+        let mrn = ids
+            .iter()
+            .find(|id| id.get(4) == Some(&"MR"))
+            .or_else(|| ids.first())?;
+        self.value(mrn.first().copied())
+    }
+
+    fn first_repetition<'a>(&self, field: &'a str) -> &'a str {
+        field.split(self.delimiters.repetition).next().unwrap_or("")
+    }
+
+    fn components<'a>(&self, field: &'a str) -> Vec<&'a str> {
+        field.split(self.delimiters.component).collect()
+    }
+
+    /// Unescaped, trimmed value; HL7's `""` (explicit null) counts as absent
+    fn value(&self, raw: Option<&str>) -> Option<String> {
+        let raw = raw?.split(self.delimiters.subcomponent).next()?.trim();
+        if raw.is_empty() || raw == "\"\"" {
+            return None;
+        }
+        Some(self.delimiters.unescape(raw))
+    }
+}
+
+/// HL7 TS (`YYYYMMDD[HHMM[SS]]...`) to the API's `YYYY-MM-DD`
+fn parse_date(ts: &str) -> Result<String, Hl7Error> {
+    ts.get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .ok_or_else(|| Hl7Error::InvalidDate(ts.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A01: &str = "MSH|^~\\&|ADT1|GOOD HEALTH HOSPITAL|EHR|EHR|202401151200||ADT^A01^ADT_A01|MSG00001|P|2.5\r\
+EVN|A01|202401151200\r\
+PID|1||99-1234^^^GHH^SS~MRN00042^^^GHH^MR||O\\E\\BRIEN^SEAN^P||19800412|M|||12 MAIN ST^^SPRINGFIELD^IL^62701||||||||123-45-6789\r\
+PV1|1|I|2000^2012^01||||004777^ATTEND^AARON^A\r";
+
+    #[test]
+    fn parses_admit_into_create_request() {
+        let AdtMessage::Admit(req) = AdtParser::parse(A01).unwrap() else {
+            panic!("expected admit");
+        };
+        assert_eq!(req.last_name, "O\\BRIEN");
+        assert_eq!(req.first_name, "SEAN");
+        assert_eq!(req.sex, "M");
+        assert_eq!(req.date_of_birth, "1980-04-12");
+        assert_eq!(req.mrn.as_deref(), Some("MRN00042"));
+        assert_eq!(req.ssn.as_deref(), Some("123-45-6789"));
+        assert_eq!(req.city.as_deref(), Some("SPRINGFIELD"));
+        assert_eq!(req.state.as_deref(), Some("IL"));
+    }
+
+    #[test]
+    fn parses_update_with_custom_delimiters() {
+        // `#` field separator, `$` components and newline segment terminators
+        let a08 = "MSH#$~\\&#ADT1#GHH#EHR#EHR#202401151300##ADT$A08#MSG00002#P#2.5\n\
+EVN#A08#202401151300\n\
+PID#1##MRN00042##SMITH$JANE###F###$$BOSTON$MA\n";
+        let AdtMessage::Update { mrn, update } = AdtParser::parse(a08).unwrap() else {
+            panic!("expected update");
+        };
+        assert_eq!(mrn, "MRN00042");
+        assert_eq!(update.last_name.as_deref(), Some("SMITH"));
+        assert_eq!(update.first_name.as_deref(), Some("JANE"));
+        assert_eq!(update.sex.as_deref(), Some("F"));
+        assert_eq!(update.date_of_birth, None);
+        assert_eq!(update.city.as_deref(), Some("BOSTON"));
+        assert_eq!(update.state.as_deref(), Some("MA"));
+    }
+
+    #[test]
+    fn parses_merge_of_prior_mrn() {
+        let a40 = "\u{0b}MSH|^~\\&|ADT1|GHH|EHR|EHR|202401151400||ADT^A40^ADT_A39|MSG00003|P|2.5\r\n\
+EVN|A40|202401151400\r\n\
+PID|1||MRN00042^^^GHH^MR||SMITH^JANE\r\n\
+MRG|MRN00099^^^GHH^MR\r\n\u{1c}\r";
+        let AdtMessage::Merge { mrn, prior_mrn } = AdtParser::parse(a40).unwrap() else {
+            panic!("expected merge");
+        };
+        assert_eq!(mrn, "MRN00042");
+        assert_eq!(prior_mrn, "MRN00099");
+
+        let without_mrg = a40.replace("MRG|MRN00099^^^GHH^MR\r\n", "");
+        assert_eq!(AdtParser::parse(&without_mrg).unwrap_err(), Hl7Error::MissingSegment("MRG"));
+    }
+
+    #[test]
+    fn rejects_non_adt_messages() {
+        let oru = A01.replace("ADT^A01^ADT_A01", "ORU^R01^ORU_R01");
+        assert!(matches!(AdtParser::parse(&oru), Err(Hl7Error::UnsupportedEvent(_))));
+        assert_eq!(AdtParser::parse("PID|1").unwrap_err(), Hl7Error::MissingHeader);
+    }
+}
@@ -0,0 +1,165 @@
+//! Lab panel grouping
+//!
+//! Lab results are stored one test per entry in ^LR(63). Panels (CBC, BMP,
+//! ...) are defined in the embedded `panels.toml`, which maps each LOINC
+//! panel code to its member tests; results are grouped by matching their test
+//! code against those members. Results outside every panel are returned as
+//! `ungrouped`.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::LabResultResponse;
+
+#[derive(Debug, Deserialize)]
+struct PanelFile {
+    panel: Vec<PanelDefinition>,
+}
+
+/// Panel from `panels.toml`
+#[derive(Debug, Deserialize)]
+pub struct PanelDefinition {
+    /// LOINC panel code
+    pub code: String,
+    pub name: String,
+    pub tests: Vec<PanelTest>,
+}
+
+/// Member test of a panel, matched by LOINC or local lab code
+#[derive(Debug, Deserialize)]
+pub struct PanelTest {
+    pub loinc: String,
+    pub code: String,
+}
+
+impl PanelDefinition {
+    /// Whether `test_code` is one of this panel's tests
+    pub fn contains(&self, test_code: &str) -> bool {
+        let test_code = test_code.trim();
+        self.tests
+            .iter()
+            .any(|t| t.loinc == test_code || t.code.eq_ignore_ascii_case(test_code))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LabPanel {
+    #[serde(rename = "panelName")]
+    pub panel_name: String,
+    #[serde(rename = "panelCode")]
+    pub panel_code: String,
+    pub results: Vec<LabResultResponse>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LabPanelsResponse {
+    pub panels: Vec<LabPanel>,
+    pub ungrouped: Vec<LabResultResponse>,
+}
+
+fn parse_panels(source: &str) -> Result<Vec<PanelDefinition>, toml::de::Error> {
+    toml::from_str::<PanelFile>(source).map(|file| file.panel)
+}
+
+/// Panel definitions, parsed from the embedded `panels.toml` on first use
+pub fn panel_definitions() -> &'static [PanelDefinition] {
+    static PANELS: OnceLock<Vec<PanelDefinition>> = OnceLock::new();
+    PANELS.get_or_init(|| {
+        parse_panels(include_str!("panels.toml")).unwrap_or_else(|e| {
+            tracing::error!("Invalid panels.toml: {}", e);
+            Vec::new()
+        })
+    })
+}
+
+/// Group results by panel, in `panels` order; panels without results are omitted.
+/// A test belonging to several panels is placed in the first one.
+pub fn group_by_panel(results: Vec<LabResultResponse>, panels: &[PanelDefinition]) -> LabPanelsResponse {
+    let mut grouped: Vec<Vec<LabResultResponse>> = panels.iter().map(|_| Vec::new()).collect();
+    let mut ungrouped = Vec::new();
+
+    for result in results {
+        let panel = result
+            .test_code
+            .as_deref()
+            .and_then(|code| panels.iter().position(|p| p.contains(code)));
+        match panel {
+            Some(index) => grouped[index].push(result),
+            None => ungrouped.push(result),
+        }
+    }
+
+    let panels = panels
+        .iter()
+        .zip(grouped)
+        .filter(|(_, results)| !results.is_empty())
+        .map(|(panel, results)| LabPanel {
+            panel_name: panel.name.clone(),
+            panel_code: panel.code.clone(),
+            results,
+        })
+        .collect();
+
+    LabPanelsResponse { panels, ungrouped }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(ien: i64, test_code: &str) -> LabResultResponse {
+        LabResultResponse {
+            ien,
+            patient_ien: 1,
+            visit_ien: None,
+            test_name: test_code.to_string(),
+            test_code: Some(test_code.to_string()),
+            value: "1".to_string(),
+            unit: None,
+            reference_range: None,
+            abnormal_flag: None,
+            collected_at: "20240301.080000".to_string(),
+            resulted_at: None,
+            status: "final".to_string(),
+        }
+    }
+
+    #[test]
+    fn embedded_panels_parse() {
+        let codes: Vec<&str> = panel_definitions().iter().map(|p| p.code.as_str()).collect();
+        assert!(codes.contains(&"58410-2"));
+        assert!(codes.contains(&"51990-0"));
+    }
+
+    #[test]
+    fn cbc_results_group_into_one_panel() {
+        let results = vec![
+            result(1, "WBC"),
+            result(2, "789-8"),
+            result(3, "hgb"),
+            result(4, "HCT"),
+            result(5, "777-3"),
+        ];
+        let grouped = group_by_panel(results, panel_definitions());
+
+        assert_eq!(grouped.panels.len(), 1);
+        assert_eq!(grouped.panels[0].panel_code, "58410-2");
+        assert_eq!(
+            grouped.panels[0].results.iter().map(|r| r.ien).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        assert!(grouped.ungrouped.is_empty());
+    }
+
+    #[test]
+    fn unknown_test_code_is_ungrouped() {
+        let mut missing_code = result(3, "");
+        missing_code.test_code = None;
+        let grouped = group_by_panel(vec![result(1, "NA"), result(2, "XYZ-1"), missing_code], panel_definitions());
+
+        assert_eq!(grouped.panels.len(), 1);
+        assert_eq!(grouped.panels[0].panel_code, "51990-0");
+        assert_eq!(grouped.ungrouped.iter().map(|r| r.ien).collect::<Vec<_>>(), vec![2, 3]);
+    }
+}
@@ -3,23 +3,75 @@
 //! Provides REST API access to VistA-style MUMPS globals in YottaDB.
 //! Uses shell commands to execute MUMPS code.
 
+use async_trait::async_trait;
+use chrono::Timelike;
 use axum::{
-    extract::Path,
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     routing::{get, post},
     Json, Router,
 };
-use serde::{Deserialize, Serialize};
-use std::process::Command;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use shared::application::services::{
+    ClinicalAlert, ClinicalAlertEvaluator, EhrDashboardService, PatientContext,
+};
+use shared::domain::ccd::{
+    CcdAllergy, CcdBuilder, CcdLabResult, CcdMedication, CcdPatient, CcdProblem, CcdVitalSign,
+};
+use shared::domain::entities::ehr::{
+    BookedAppointment, InteractionSeverity, ProviderSchedule, ScheduleBlock, ScheduleSlot,
+};
+use shared::domain::repositories::ehr::{EhrDocumentContentRepository, EhrPatientSummaryRepository};
+use shared::domain::services::polypharmacy::{
+    score_polypharmacy, DrugInteractionDto, PolypharmacyScore, RiskLevel,
+};
+use shared::domain::services::provider_availability::ProviderAvailabilityService;
+use shared::domain::services::vital_trend::{
+    parse_blood_pressure, TrendResult, VitalReading, VitalTrendCalculator,
+};
+use shared::domain::state_machine::{
+    DocumentContext, DocumentMachine, DocumentStateMachine, DocumentStateMachineEvent, DocumentStatus,
+};
+use shared::{AppError, AppResult};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
 use tower_http::cors::{Any, CorsLayer};
 
+mod barcode;
+mod hl7;
+mod lab_panels;
+mod metrics;
+mod mumps_pool;
+mod pagination;
+mod trace_context;
+
+use barcode::{BarcodeFormat, PrescriptionLabel};
+use hl7::{AdtMessage, AdtParser};
+use lab_panels::LabPanelsResponse;
+use mumps_pool::MumpsPool;
+use pagination::{Page, PageQuery};
+
 // === Data Structures ===
 
 #[derive(Debug, Serialize)]
 struct HealthResponse {
     status: String,
     database: String,
+    /// YottaDB workers free to take a call
+    #[serde(rename = "mumpsWorkersAvailable")]
+    mumps_workers_available: i64,
+    #[serde(rename = "mumpsCallsInFlight")]
+    mumps_calls_in_flight: usize,
 }
 
 #[derive(Debug, Serialize)]
@@ -44,6 +96,18 @@ struct PatientResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     state: Option<String>,
     status: String, // Default to "active"
+    /// Fuzzy search relevance in 0..=1, only set for `?q=` searches
+    #[serde(rename = "relevanceScore", skip_serializing_if = "Option::is_none")]
+    relevance_score: Option<f64>,
+    /// Which field produced the search match (`firstName`, `lastName`, `mrn`)
+    #[serde(rename = "matchedField", skip_serializing_if = "Option::is_none")]
+    matched_field: Option<&'static str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PatientListQuery {
+    /// Fuzzy search term; empty returns every patient
+    q: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -51,10 +115,12 @@ struct PatientsResponse {
     items: Vec<PatientResponse>,
     total: usize,
     limit: usize,
-    offset: usize,
+    /// Pass as `?cursor=` to fetch the next page
+    next_cursor: Option<String>,
+    has_more: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct ProblemResponse {
     ien: i64,
     diagnosis: String,
@@ -99,6 +165,64 @@ struct CreatePatientRequest {
     date_of_birth: String,
     ssn: Option<String>,
     mrn: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+}
+
+/// Partial demographics update; absent fields are left unchanged
+#[derive(Debug, Default, Deserialize)]
+struct PatientUpdateRequest {
+    #[serde(rename = "firstName")]
+    first_name: Option<String>,
+    #[serde(rename = "lastName")]
+    last_name: Option<String>,
+    sex: Option<String>,
+    #[serde(rename = "dateOfBirth")]
+    date_of_birth: Option<String>,
+    ssn: Option<String>,
+    city: Option<String>,
+    state: Option<String>,
+}
+
+impl From<CreatePatientRequest> for PatientUpdateRequest {
+    fn from(req: CreatePatientRequest) -> Self {
+        Self {
+            first_name: Some(req.first_name),
+            last_name: Some(req.last_name),
+            sex: Some(req.sex),
+            date_of_birth: Some(req.date_of_birth),
+            ssn: req.ssn,
+            city: req.city,
+            state: req.state,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MergePatientsRequest {
+    /// Record that remains after the merge
+    #[serde(rename = "survivorIen")]
+    survivor_ien: i64,
+    /// Record merged away; it keeps its data but points at the survivor
+    #[serde(rename = "duplicateIen")]
+    duplicate_ien: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatePatientQuery {
+    /// Skip the duplicate check and always create the patient
+    #[serde(default)]
+    allow_similar: bool,
+}
+
+/// Returned with 409 when a likely duplicate already exists
+#[derive(Debug, PartialEq, Serialize)]
+struct DuplicatePatientResponse {
+    #[serde(rename = "existingIen")]
+    existing_ien: i64,
+    name: String,
+    #[serde(rename = "dateOfBirth")]
+    date_of_birth: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -114,7 +238,7 @@ struct ErrorResponse {
 
 // === Visit Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct VisitResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -157,7 +281,7 @@ struct CreateVisitRequest {
 
 // === Vital Signs Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct VitalResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -179,6 +303,55 @@ struct VitalsResponse {
     vitals: Vec<VitalResponse>,
 }
 
+#[derive(Debug, Deserialize)]
+struct VitalTrendQuery {
+    /// Vital type, e.g. `heart_rate` or `blood_pressure`
+    #[serde(rename = "type")]
+    vital_type: String,
+    /// Look-back window in days
+    days: Option<u32>,
+}
+
+/// Blood pressure is trended as separate systolic and diastolic series
+#[derive(Debug, Serialize)]
+struct VitalTrendResponse {
+    #[serde(rename = "patientIen")]
+    patient_ien: i64,
+    #[serde(rename = "vitalType")]
+    vital_type: String,
+    days: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trend: Option<TrendResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    systolic: Option<TrendResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diastolic: Option<TrendResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateVitalResponse {
+    success: bool,
+    ien: i64,
+    /// CDS alerts raised by the recorded value
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    alerts: Vec<ClinicalAlert>,
+}
+
+#[derive(Debug, Serialize)]
+struct PolypharmacyScoreResponse {
+    #[serde(rename = "patientIen")]
+    patient_ien: i64,
+    #[serde(flatten)]
+    score: PolypharmacyScore,
+}
+
+#[derive(Debug, Serialize)]
+struct CdsAlertsResponse {
+    #[serde(rename = "patientIen")]
+    patient_ien: i64,
+    alerts: Vec<ClinicalAlert>,
+}
+
 #[derive(Debug, Deserialize)]
 struct CreateVitalRequest {
     #[serde(rename = "patientIen")]
@@ -195,7 +368,7 @@ struct CreateVitalRequest {
 
 // === Medication Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct MedicationResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -244,7 +417,7 @@ struct CreateMedicationRequest {
 
 // === Lab Results Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct LabResultResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -293,7 +466,7 @@ struct CreateLabResultRequest {
 
 // === Document Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct DocumentResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -313,6 +486,8 @@ struct DocumentResponse {
     signed_by: Option<i64>,
     status: String,
     content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    addenda: Vec<DocumentAddendum>,
 }
 
 #[derive(Debug, Serialize)]
@@ -320,6 +495,51 @@ struct DocumentsResponse {
     documents: Vec<DocumentResponse>,
 }
 
+#[derive(Debug, Deserialize)]
+struct DocumentQuery {
+    /// Also load the note text and addenda; metadata only by default
+    #[serde(default)]
+    include_content: bool,
+}
+
+/// Addendum appended to a document by an amendment
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct DocumentAddendum {
+    number: i64,
+    #[serde(rename = "amendedAt")]
+    amended_at: String,
+    #[serde(rename = "authorIen")]
+    author_ien: Option<i64>,
+    content: String,
+}
+
+/// Note text and addenda read from the word-processing nodes
+#[derive(Debug, Default, PartialEq)]
+struct DocumentText {
+    content: Option<String>,
+    addenda: Vec<DocumentAddendum>,
+}
+
+#[derive(Debug, Serialize)]
+struct DocumentContentResponse {
+    ien: i64,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AmendDocumentRequest {
+    content: String,
+    #[serde(rename = "authorIen")]
+    author_ien: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct AmendDocumentResponse {
+    ien: i64,
+    addendum: i64,
+    status: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct CreateDocumentRequest {
     #[serde(rename = "patientIen")]
@@ -331,13 +551,12 @@ struct CreateDocumentRequest {
     title: String,
     #[serde(rename = "authorIen")]
     author_ien: Option<i64>,
-    #[allow(dead_code)] // Will be used when document content storage is implemented
     content: Option<String>,
 }
 
 // === Order Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct OrderResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -378,7 +597,7 @@ struct CreateOrderRequest {
 
 // === Prescription/Dispensing Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct PrescriptionResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -422,6 +641,8 @@ struct PrescriptionResponse {
 #[derive(Debug, Serialize)]
 struct PrescriptionsResponse {
     prescriptions: Vec<PrescriptionResponse>,
+    next_cursor: Option<String>,
+    has_more: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -461,6 +682,114 @@ struct DispensePrescriptionRequest {
     lot_number: Option<String>,
     #[serde(rename = "expirationDate")]
     expiration_date: Option<String>,
+    /// Return the label barcode URL with the response
+    #[serde(rename = "includeBarcode")]
+    include_barcode: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct DispensePrescriptionResponse {
+    success: bool,
+    ien: i64,
+    #[serde(rename = "barcodeUrl", skip_serializing_if = "Option::is_none")]
+    barcode_url: Option<String>,
+}
+
+/// Prescription fields printed on the label, as stored in ^PSO(52) and ^DISP
+#[derive(Debug, Deserialize)]
+struct PrescriptionLabelRecord {
+    ien: i64,
+    #[serde(rename = "rxNumber")]
+    rx_number: String,
+    #[serde(rename = "patientIen")]
+    patient_ien: i64,
+    #[serde(rename = "drugCode")]
+    drug_code: String,
+    quantity: i32,
+    #[serde(rename = "fillDate")]
+    fill_date: Option<String>,
+    #[serde(rename = "expirationDate")]
+    expiration_date: Option<String>,
+    #[serde(rename = "lotNumber")]
+    lot_number: Option<String>,
+}
+
+impl PrescriptionLabelRecord {
+    fn into_label(self) -> PrescriptionLabel {
+        PrescriptionLabel {
+            rx_number: self.rx_number,
+            patient_ien: self.patient_ien,
+            drug_code: self.drug_code,
+            quantity: self.quantity,
+            fill_date: self.fill_date.as_deref().and_then(barcode::parse_stored_date),
+            expiration_date: self.expiration_date.as_deref().and_then(barcode::parse_stored_date),
+            lot_number: self.lot_number.filter(|lot| !lot.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BarcodeQuery {
+    format: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BarcodeResponse {
+    format: String,
+    /// GS1 element string encoded in the symbol
+    data: String,
+    image_base64: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanVerifyRequest {
+    /// Decoded barcode contents as reported by the scanner
+    payload: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ScanVerifyResponse {
+    /// match, mismatch or not_found
+    status: &'static str,
+    #[serde(rename = "prescriptionIen", skip_serializing_if = "Option::is_none")]
+    prescription_ien: Option<i64>,
+    mismatches: Vec<&'static str>,
+    decoded: PrescriptionLabel,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DispensingHistoryResponse {
+    ien: i64,
+    #[serde(rename = "prescriptionIen")]
+    prescription_ien: i64,
+    #[serde(rename = "patientIen")]
+    patient_ien: i64,
+    #[serde(rename = "lotNumber")]
+    lot_number: Option<String>,
+    #[serde(rename = "quantityDispensed")]
+    quantity_dispensed: i32,
+    #[serde(rename = "dispensedBy")]
+    dispensed_by: i64,
+    #[serde(rename = "dispensedAt")]
+    dispensed_at: String,
+    #[serde(rename = "inventoryIen")]
+    inventory_ien: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct DispensingHistoryListResponse {
+    #[serde(rename = "dispensingHistory")]
+    dispensing_history: Vec<DispensingHistoryResponse>,
+}
+
+#[derive(Debug, Serialize)]
+struct LotDispensingResponse {
+    #[serde(rename = "lotNumber")]
+    lot_number: String,
+    #[serde(rename = "dispensingHistory")]
+    dispensing_history: Vec<DispensingHistoryResponse>,
+    #[serde(rename = "patientIens")]
+    patient_iens: Vec<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -476,7 +805,92 @@ struct AllergyCheckResponse {
     has_allergy_conflict: bool,
     allergies: Vec<AllergyResponse>,
     #[serde(rename = "matchedAllergens")]
-    matched_allergens: Vec<String>,
+    matched_allergens: Vec<MatchedAllergenResponse>,
+}
+
+/// An allergy that conflicts with the checked drug
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MatchedAllergenResponse {
+    #[serde(rename = "allergyIen")]
+    allergy_ien: i64,
+    allergen: String,
+    /// "direct" (name match) or "class" (drug class cross-reactivity)
+    #[serde(rename = "matchType")]
+    match_type: String,
+    #[serde(rename = "drugClass")]
+    drug_class: Option<String>,
+    severity: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AllergyCheckQuery {
+    /// Only report matches at or above this severity (mild, moderate, severe, life_threatening)
+    #[serde(rename = "minSeverity")]
+    min_severity: Option<String>,
+}
+
+/// Rank allergy severities so matches can be filtered by a minimum level
+fn severity_rank(severity: &str) -> Option<u8> {
+    match severity {
+        "mild" => Some(1),
+        "moderate" => Some(2),
+        "severe" => Some(3),
+        "life_threatening" => Some(4),
+        _ => None,
+    }
+}
+
+/// A known interaction between two drugs, from `interactions.json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InteractionRule {
+    drug_a: String,
+    drug_b: String,
+    severity: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+struct InteractionDetail {
+    /// The drug being checked
+    #[serde(rename = "drugA")]
+    drug_a: String,
+    /// The patient's active medication it interacts with
+    #[serde(rename = "drugB")]
+    drug_b: String,
+    /// "major", "moderate" or "minor"
+    severity: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DrugInteractionResponse {
+    #[serde(rename = "hasInteraction")]
+    has_interaction: bool,
+    interactions: Vec<InteractionDetail>,
+    /// Worst severity across drug pairs and allergy conflicts, or "none"
+    severity: String,
+    #[serde(rename = "allergyCheck")]
+    allergy_check: AllergyCheckResponse,
+}
+
+/// Rank interaction severities so the worst case can be reported
+fn interaction_severity_rank(severity: &str) -> u8 {
+    match severity {
+        "minor" => 1,
+        "moderate" => 2,
+        "major" => 3,
+        _ => 0,
+    }
+}
+
+/// Express an allergy conflict severity on the interaction scale
+fn allergy_interaction_severity(severity: &str) -> &'static str {
+    match severity {
+        "mild" => "minor",
+        "moderate" => "moderate",
+        _ => "major",
+    }
 }
 
 // === Pharmacy Inventory Structures ===
@@ -508,12 +922,22 @@ struct InventoryItemResponse {
     schedule: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct InventoryQuery {
+    /// Only return stock of this drug
+    drug_code: Option<String>,
+    /// Only return stock held at this location code
+    location: Option<String>,
+}
+
 #[derive(Debug, Serialize)]
 struct InventoryResponse {
     items: Vec<InventoryItemResponse>,
+    next_cursor: Option<String>,
+    has_more: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct LotResponse {
     ien: i64,
     #[serde(rename = "inventoryIen")]
@@ -609,6 +1033,19 @@ struct LowStockAlertResponse {
     count: i32,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct InventoryAlert {
+    #[serde(rename = "inventoryIen")]
+    inventory_ien: i64,
+    #[serde(rename = "drugName")]
+    drug_name: String,
+    #[serde(rename = "quantityOnHand")]
+    quantity_on_hand: i32,
+    #[serde(rename = "reorderPoint")]
+    reorder_point: i32,
+    timestamp: String,
+}
+
 #[derive(Debug, Serialize)]
 struct ExpiringLotsResponse {
     lots: Vec<LotResponse>,
@@ -617,7 +1054,7 @@ struct ExpiringLotsResponse {
 
 // === Appointment Structures ===
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct AppointmentResponse {
     ien: i64,
     #[serde(rename = "patientIen")]
@@ -660,140 +1097,245 @@ struct CreateAppointmentRequest {
     reason: Option<String>,
 }
 
-// === MUMPS Execution ===
+#[derive(Debug, Deserialize)]
+struct CreateAppointmentQuery {
+    /// Book even if the patient already has an overlapping appointment
+    /// (urgent add-ons); provider conflicts are always rejected
+    #[serde(default)]
+    force: bool,
+}
 
-fn run_mumps(code: &str) -> Result<String, String> {
-    // Execute MUMPS code in the yottadb container via docker exec
-    // This allows the API to run in a separate container while accessing YottaDB
-    let script = format!(
-        r#". /opt/yottadb/current/ydb_env_set && export ydb_routines="/data/r $ydb_routines" && echo '{}' | yottadb -direct 2>/dev/null | grep -v '^YDB>'"#,
-        code.replace("'", "'\"'\"'")
-    );
+/// Why a booking was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ConflictReason {
+    PatientConflict,
+    ProviderConflict,
+}
 
-    let output = Command::new("docker")
-        .arg("exec")
-        .arg("health-yottadb")  // YottaDB container name
-        .arg("bash")
-        .arg("-c")
-        .arg(&script)
-        .output()
-        .map_err(|e| format!("Failed to execute in YottaDB container: {}", e))?;
-
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
-    } else {
-        Err(String::from_utf8_lossy(&output.stderr).to_string())
-    }
+/// Returned with 409 when the patient or provider already has an appointment
+/// overlapping the requested period
+#[derive(Debug, Serialize)]
+struct ConflictResponse {
+    error: String,
+    reason: ConflictReason,
+    #[serde(rename = "conflictingIen")]
+    conflicting_ien: i64,
+    /// Start of the conflicting appointment, `YYYY-MM-DDTHH:MM`
+    #[serde(rename = "conflictingTime")]
+    conflicting_time: String,
+    /// Next free start with the same provider for the same duration, `YYYY-MM-DDTHH:MM`
+    #[serde(rename = "nextAvailable", skip_serializing_if = "Option::is_none")]
+    next_available: Option<String>,
 }
 
-fn extract_json_from_http(response: &str) -> String {
-    // EHRAPI returns HTTP response format:
-    // HTTP/1.1 200 OK
-    // Content-Type: application/json
-    // ...
-    //
-    // {json body}
+// === Provider Schedule Structures ===
 
-    // Find the empty line that separates headers from body
-    if let Some(pos) = response.find("\n\n") {
-        response[pos + 2..].trim().to_string()
-    } else if let Some(pos) = response.find("\r\n\r\n") {
-        response[pos + 4..].trim().to_string()
-    } else {
-        // If no HTTP headers found, assume it's already just JSON
-        response.trim().to_string()
-    }
+#[derive(Debug, Deserialize)]
+struct ProviderScheduleQuery {
+    /// Day to show, `YYYY-MM-DD`; defaults to today
+    date: Option<String>,
 }
 
-// === Handlers ===
+#[derive(Debug, Serialize)]
+struct ProviderScheduleResponse {
+    #[serde(rename = "providerIen")]
+    provider_ien: i64,
+    date: String,
+    #[serde(rename = "startTime")]
+    start_time: String,
+    #[serde(rename = "endTime")]
+    end_time: String,
+    slots: Vec<ScheduleSlot>,
+}
 
-async fn health() -> impl IntoResponse {
-    Json(HealthResponse {
-        status: "ok".to_string(),
-        database: "yottadb".to_string(),
-    })
+#[derive(Debug, Deserialize)]
+struct ScheduleBlockEntry {
+    time: String,
+    #[serde(rename = "durationMinutes")]
+    duration_minutes: i32,
 }
 
-async fn list_patients() -> impl IntoResponse {
-    // Call EHRAPI routine to get patient list
-    let code = r#"W $$LISTPAT^EHRAPI()"#;
+#[derive(Debug, Deserialize)]
+struct SetProviderScheduleRequest {
+    date: String,
+    #[serde(rename = "startTime")]
+    start_time: String,
+    #[serde(rename = "endTime")]
+    end_time: String,
+    #[serde(rename = "slotMinutes")]
+    slot_minutes: Option<i32>,
+    #[serde(default)]
+    blocked: Vec<ScheduleBlockEntry>,
+}
 
-    match run_mumps(code) {
-        Ok(output) => {
-            // EHRAPI returns HTTP response, extract JSON body
-            let json_body = extract_json_from_http(&output);
+/// Working hours stored in ^SDAM for one day
+#[derive(Debug, Deserialize)]
+struct ScheduleHoursRow {
+    /// `YYYYMMDD`
+    date: String,
+    start: String,
+    end: String,
+    #[serde(rename = "slotMinutes")]
+    slot_minutes: i32,
+    blocked: Vec<ScheduleBlockEntry>,
+}
 
-            // Parse using serde_json for proper JSON handling
-            match serde_json::from_str::<serde_json::Value>(&json_body) {
-                Ok(json) => {
-                    if let Some(patients_array) = json.get("patients").and_then(|p| p.as_array()) {
-                        let patients: Vec<PatientResponse> = patients_array
-                            .iter()
-                            .filter_map(|p| {
-                                let ien = p.get("ien")?.as_i64()?;
-                                // Filter out invalid IENs (0, negative, or string indices like "B")
-                                if ien <= 0 {
-                                    return None;
-                                }
-
-                                let name = p.get("name")?.as_str().unwrap_or("").to_string();
-                                let sex = p.get("sex").and_then(|s| s.as_str()).unwrap_or("").to_string();
-                                let dob = p.get("dateOfBirth").and_then(|d| d.as_str()).unwrap_or("").to_string();
-                                let ssn = p.get("ssn").and_then(|s| s.as_str()).map(|s| s.to_string());
-                                let mrn = p.get("mrn").and_then(|m| m.as_str()).map(|s| s.to_string());
-
-                                let (last, first) = if let Some(pos) = name.find(',') {
-                                    (name[..pos].trim().to_string(), name[pos + 1..].trim().to_string())
-                                } else {
-                                    (name.clone(), String::new())
-                                };
-
-                                let gender = match sex.to_uppercase().as_str() {
-                                    "M" => "male",
-                                    "F" => "female",
-                                    _ => "unknown",
-                                }.to_string();
-
-                                Some(PatientResponse {
-                                    id: ien.to_string(),
-                                    ien,
-                                    name,
-                                    first_name: first,
-                                    last_name: last,
-                                    middle_name: None,
-                                    sex,
-                                    gender,
-                                    date_of_birth: dob,
-                                    ssn,
-                                    mrn,
-                                    city: None,
-                                    state: None,
-                                    status: "active".to_string(),
-                                })
-                            })
-                            .collect();
-
-                        let total = patients.len();
-                        (StatusCode::OK, Json(PatientsResponse {
-                            items: patients,
-                            total,
-                            limit: 100, // Default limit
-                            offset: 0,  // Default offset
-                        })).into_response()
-                    } else {
-                        (StatusCode::OK, Json(PatientsResponse {
-                            items: vec![],
-                            total: 0,
-                            limit: 100,
-                            offset: 0,
-                        })).into_response()
-                    }
-                }
-                Err(e) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse { error: format!("JSON parse error: {}", e) }),
-                ).into_response(),
-            }
+#[derive(Debug, Deserialize)]
+struct BookedAppointmentRow {
+    ien: i64,
+    date: String,
+    time: String,
+    #[serde(rename = "durationMinutes")]
+    duration_minutes: i32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleRows {
+    hours: Vec<ScheduleHoursRow>,
+    appointments: Vec<BookedAppointmentRow>,
+}
+
+// === MUMPS Execution ===
+
+/// Worker pool shared by every handler, started in `main`
+static MUMPS_POOL: OnceLock<MumpsPool> = OnceLock::new();
+
+async fn run_mumps(code: &str) -> Result<String, String> {
+    let timer = metrics::mumps_timer();
+    let result = match MUMPS_POOL.get() {
+        Some(pool) => pool.execute(code).await,
+        None => Err("MUMPS pool is not initialized".to_string()),
+    };
+    timer.observe_duration();
+    if let Err(e) = &result {
+        tracing::error!(
+            trace_id = trace_context::current_trace_id().as_deref().unwrap_or("-"),
+            error = %e,
+            "MUMPS execution failed"
+        );
+    }
+    result
+}
+
+/// Escape a value for embedding inside a MUMPS string literal
+///
+/// Control characters are concatenated in as `$C(n)`, so a value can't end
+/// the line of code it is embedded in.
+fn mumps_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\"\""),
+            c if c.is_control() => escaped.push_str(&format!("\"_$C({})_\"", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `^` delimits the pieces of a global node, so a stored piece cannot contain it;
+/// control characters would end the line of code the piece is written by
+fn validate_piece(field: &str, value: &str) -> Result<(), String> {
+    if value.contains('^') {
+        Err(format!("{} must not contain '^'", field))
+    } else if value.chars().any(char::is_control) {
+        Err(format!("{} must not contain control characters", field))
+    } else {
+        Ok(())
+    }
+}
+
+/// [`validate_piece`] over `(field, value)` pairs, failing on the first bad one
+fn validate_pieces(pieces: &[(&str, &str)]) -> Result<(), String> {
+    pieces.iter().try_for_each(|(field, value)| validate_piece(field, value))
+}
+
+/// Deserialize the JSON written by a MUMPS routine
+///
+/// Malformed output is an error rather than a silently truncated result.
+fn parse_mumps_json<T: DeserializeOwned>(output: &str) -> Result<T, String> {
+    serde_json::from_str(output.trim())
+        .map_err(|e| format!("Invalid JSON from YottaDB: {}", e))
+}
+
+fn extract_json_from_http(response: &str) -> String {
+    // EHRAPI returns HTTP response format:
+    // HTTP/1.1 200 OK
+    // Content-Type: application/json
+    // ...
+    //
+    // {json body}
+
+    // Find the empty line that separates headers from body
+    if let Some(pos) = response.find("\n\n") {
+        response[pos + 2..].trim().to_string()
+    } else if let Some(pos) = response.find("\r\n\r\n") {
+        response[pos + 4..].trim().to_string()
+    } else {
+        // If no HTTP headers found, assume it's already just JSON
+        response.trim().to_string()
+    }
+}
+
+// === Handlers ===
+
+async fn health() -> impl IntoResponse {
+    let _timer = metrics::handler_timer("health");
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        database: "yottadb".to_string(),
+        mumps_workers_available: MUMPS_POOL.get().map_or(0, |pool| pool.available().get()),
+        mumps_calls_in_flight: MUMPS_POOL.get().map_or(0, MumpsPool::in_flight),
+    })
+}
+
+async fn list_patients(
+    Query(query): Query<PatientListQuery>,
+    Query(page): Query<PageQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("list_patients");
+    let term = query.q.as_deref().map(normalize_search_term).unwrap_or_default();
+    if !term.is_empty() {
+        return search_patients(&term).await;
+    }
+
+    let page = match Page::from_query(&page, "patients") {
+        Ok(page) => page,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response(),
+    };
+
+    // Walk numeric IENs only; $O reaches the "B" index after them and stops there.
+    // Records merged into another patient (^DPT(IEN,-9)) are left out.
+    let code = format!(
+        r#"
+N IEN,D0,CNT,SKIP
+S IEN={after},CNT=0,SKIP={skip}
+F  S IEN=$O(^DPT(IEN)) Q:'IEN  Q:CNT'<{fetch}  D
+. S D0=$G(^DPT(IEN,0)) Q:D0=""
+. Q:$D(^DPT(IEN,-9))
+. I SKIP S SKIP=SKIP-1 Q
+. S CNT=CNT+1
+. W IEN,"^",$P(D0,"^",1,4),"^",$G(^DPT(IEN,991)),!
+"#,
+        after = page.after,
+        skip = page.skip,
+        fetch = page.fetch(),
+    );
+
+    match run_mumps(&code).await {
+        Ok(output) => {
+            let patients: Vec<PatientResponse> = output.lines().filter_map(parse_patient_line).collect();
+            let result = page.finish(patients, |p| p.ien);
+            page.respond(
+                (StatusCode::OK, Json(PatientsResponse {
+                    total: result.items.len(),
+                    items: result.items,
+                    limit: page.limit,
+                    next_cursor: result.next_cursor,
+                    has_more: result.has_more,
+                })).into_response(),
+            )
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -803,11 +1345,212 @@ async fn list_patients() -> impl IntoResponse {
     }
 }
 
+/// Parse an `IEN^name^sex^dob^ssn^mrn` line written by the patient list walks
+fn parse_patient_line(line: &str) -> Option<PatientResponse> {
+    let fields: Vec<&str> = line.split('^').collect();
+    let ien = fields.first()?.trim().parse::<i64>().ok().filter(|ien| *ien > 0)?;
+    let field = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or("");
+    let optional = |i: usize| Some(field(i).to_string()).filter(|f| !f.is_empty());
+
+    let name = field(1).to_string();
+    let (last, first) = match name.find(',') {
+        Some(pos) => (name[..pos].trim().to_string(), name[pos + 1..].trim().to_string()),
+        None => (name.clone(), String::new()),
+    };
+
+    let sex = field(2).to_string();
+    let gender = match sex.to_uppercase().as_str() {
+        "M" => "male",
+        "F" => "female",
+        _ => "unknown",
+    }.to_string();
+
+    Some(PatientResponse {
+        id: ien.to_string(),
+        ien,
+        name,
+        first_name: first,
+        last_name: last,
+        middle_name: None,
+        sex,
+        gender,
+        date_of_birth: field(3).to_string(),
+        ssn: optional(4),
+        mrn: optional(5),
+        city: None,
+        state: None,
+        status: "active".to_string(),
+        relevance_score: None,
+        matched_field: None,
+    })
+}
+
+/// Fuzzy patient search over the `^DPT("B")` name index
+///
+/// MUMPS seeks the index with `$ORDER` to the term's first letter and walks
+/// only the names that start with it, which covers every exact, prefix and
+/// Soundex match on the last name (a Soundex code keeps the first letter).
+/// First names and MRNs are matched within that range. Candidates are then
+/// scored and ranked here.
+async fn search_patients(term: &str) -> axum::response::Response {
+    let code = format!(
+        r#"
+N NM,IEN,D0,Q,QI
+S Q="{term}",QI=$E(Q,1)
+S NM=$O(^DPT("B",QI),-1)
+F  S NM=$O(^DPT("B",NM)) Q:NM=""  Q:$E(NM,1)'=QI  D
+. S IEN=0
+. F  S IEN=$O(^DPT("B",NM,IEN)) Q:IEN=""  D
+. . S D0=$G(^DPT(IEN,0)) Q:D0=""
+. . W IEN,"^",$P(D0,"^",1,4),"^",$G(^DPT(IEN,991)),!
+"#,
+        term = mumps_escape(term),
+    );
+
+    match run_mumps(&code).await {
+        Ok(output) => {
+            let mut patients: Vec<PatientResponse> = output
+                .lines()
+                .filter_map(|line| {
+                    let mut patient = parse_patient_line(line)?;
+                    let (matched_field, score) = score_patient_match(
+                        term,
+                        &patient.last_name,
+                        &patient.first_name,
+                        patient.mrn.as_deref(),
+                    )?;
+                    patient.relevance_score = Some(score);
+                    patient.matched_field = Some(matched_field);
+                    Some(patient)
+                })
+                .collect();
+
+            patients.sort_by(|a, b| {
+                b.relevance_score
+                    .partial_cmp(&a.relevance_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+
+            // Ranked results have no stable IEN order to resume from, so
+            // searches return every match on one page
+            let total = patients.len();
+            (StatusCode::OK, Json(PatientsResponse {
+                items: patients,
+                total,
+                limit: total,
+                next_cursor: None,
+                has_more: false,
+            })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
+/// Uppercase a search term and drop anything that isn't part of a name or MRN
+///
+/// Commas, carets and quotes are MUMPS piece/string delimiters, so they never
+/// reach the generated code.
+fn normalize_search_term(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '\''))
+        .collect::<String>()
+        .trim()
+        .to_uppercase()
+}
+
+/// Best match of `term` against a patient's last name, first name and MRN
+///
+/// Exact > prefix > substring > Soundex; names are preferred over the MRN
+/// when scores tie. Returns `None` when nothing matches.
+fn score_patient_match(term: &str, last: &str, first: &str, mrn: Option<&str>) -> Option<(&'static str, f64)> {
+    let term_soundex = soundex(term);
+    let name_score = |value: &str| -> Option<f64> {
+        let value = value.to_uppercase();
+        if value.is_empty() {
+            None
+        } else if value == term {
+            Some(1.0)
+        } else if value.starts_with(term) {
+            Some(0.9)
+        } else if value.contains(term) {
+            Some(0.75)
+        } else if term_soundex.is_some() && soundex(&value) == term_soundex {
+            Some(0.6)
+        } else {
+            None
+        }
+    };
+    let mrn_score = mrn.map(str::to_uppercase).and_then(|mrn| {
+        if mrn == term {
+            Some(1.0)
+        } else if mrn.contains(term) {
+            Some(0.75)
+        } else {
+            None
+        }
+    });
+
+    [
+        ("lastName", name_score(last)),
+        ("firstName", name_score(first)),
+        ("mrn", mrn_score),
+    ]
+    .into_iter()
+    .filter_map(|(field, score)| score.map(|s| (field, s)))
+    .fold(None, |best: Option<(&'static str, f64)>, candidate| match best {
+        Some(b) if b.1 >= candidate.1 => Some(b),
+        _ => Some(candidate),
+    })
+}
+
+/// American Soundex code (letter + three digits), `None` without letters
+fn soundex(value: &str) -> Option<String> {
+    fn digit(c: char) -> Option<char> {
+        match c {
+            'B' | 'F' | 'P' | 'V' => Some('1'),
+            'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => Some('2'),
+            'D' | 'T' => Some('3'),
+            'L' => Some('4'),
+            'M' | 'N' => Some('5'),
+            'R' => Some('6'),
+            _ => None,
+        }
+    }
+
+    let mut letters = value.chars().filter(char::is_ascii_alphabetic).map(|c| c.to_ascii_uppercase());
+    let first = letters.next()?;
+    let mut code = String::from(first);
+    let mut previous = digit(first);
+    for c in letters {
+        let current = digit(c);
+        if current.is_some() && current != previous {
+            code.extend(current);
+            if code.len() == 4 {
+                break;
+            }
+        }
+        // H and W don't separate letters with the same code; vowels do
+        if !matches!(c, 'H' | 'W') {
+            previous = current;
+        }
+    }
+    while code.len() < 4 {
+        code.push('0');
+    }
+    Some(code)
+}
+
 async fn get_patient(Path(ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient");
     // Call EHRAPI routine to get single patient
     let code = format!(r#"W $$GETPAT^EHRAPI({})"#, ien);
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             // Extract JSON from HTTP response
             let json_body = extract_json_from_http(&output);
@@ -860,6 +1603,8 @@ async fn get_patient(Path(ien): Path<i64>) -> impl IntoResponse {
                         city: None,
                         state: None,
                         status: "active".to_string(),
+                        relevance_score: None,
+                        matched_field: None,
                     };
 
                     (StatusCode::OK, Json(patient)).into_response()
@@ -878,7 +1623,7 @@ async fn get_patient(Path(ien): Path<i64>) -> impl IntoResponse {
     }
 }
 
-async fn get_patient_problems(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+async fn query_patient_problems(patient_ien: i64) -> Result<Vec<ProblemResponse>, String> {
     let code = format!(
         r#"
 N IEN,D0,FIRST
@@ -889,17 +1634,21 @@ F  S IEN=$O(^AUPNPROB("C",{},IEN)) Q:IEN=""  D
 . I 'FIRST W ","
 . S FIRST=0
 . S DX=$P(D0,"^",1),PAT=$P(D0,"^",2),ICD=$P(D0,"^",3),ST=$P(D0,"^",6)
-. W "{{""ien"":"_IEN_",""diagnosis"":"""_DX_""",""patientIen"":"_PAT
-. I ICD'="" W ",""icdCode"":"""_ICD_""""
-. W ",""status"":"""_$S(ST="A":"active",ST="I":"inactive",1:ST)_"""}}"
+. W "{{""ien"":"_IEN_",""diagnosis"":"""_$$ESC^EHRAPI(DX)_""",""patientIen"":"_PAT
+. I ICD'="" W ",""icdCode"":"""_$$ESC^EHRAPI(ICD)_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="A":"active",ST="I":"inactive",1:ST))_"""}}"
 W "]"
 "#,
         patient_ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let problems = parse_problems(&output);
+    run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))
+}
+
+async fn get_patient_problems(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_problems");
+    match query_patient_problems(patient_ien).await {
+        Ok(problems) => {
             (StatusCode::OK, Json(ProblemsResponse { problems })).into_response()
         }
         Err(e) => (
@@ -910,50 +1659,7 @@ W "]"
     }
 }
 
-fn parse_problems(json: &str) -> Vec<ProblemResponse> {
-    let mut problems = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return problems;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut diagnosis = String::new();
-        let mut patient_ien = 0i64;
-        let mut icd_code = None;
-        let mut status = String::new();
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "diagnosis" => diagnosis = val.to_string(),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "icdCode" => icd_code = Some(val.to_string()),
-                    "status" => status = val.to_string(),
-                    _ => {}
-                }
-            }
-        }
-
-        problems.push(ProblemResponse {
-            ien,
-            diagnosis,
-            patient_ien,
-            icd_code,
-            status,
-        });
-    }
-
-    problems
-}
-
-async fn get_patient_allergies(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+async fn query_patient_allergies(patient_ien: i64) -> Result<Vec<AllergyResponse>, String> {
     let code = format!(
         r#"
 N IEN,D0,FIRST
@@ -964,19 +1670,23 @@ F  S IEN=$O(^GMRA("C",{},IEN)) Q:IEN=""  D
 . I 'FIRST W ","
 . S FIRST=0
 . S ALG=$P(D0,"^",1),PAT=$P(D0,"^",2),TYP=$P(D0,"^",3),SEV=$P(D0,"^",4),REACT=$P(D0,"^",5),ST=$P(D0,"^",6)
-. W "{{""ien"":"_IEN_",""allergen"":"""_ALG_""",""patientIen"":"_PAT
-. W ",""allergyType"":"""_$S(TYP="D":"drug",TYP="F":"food",TYP="E":"environmental",1:TYP)_""""
-. W ",""severity"":"""_$S(SEV="MI":"mild",SEV="MO":"moderate",SEV="SE":"severe",SEV="LT":"life_threatening",1:SEV)_""""
-. I REACT'="" W ",""reactions"":"""_REACT_""""
-. W ",""status"":"""_$S(ST="A":"active",ST="I":"inactive",1:ST)_"""}}"
+. W "{{""ien"":"_IEN_",""allergen"":"""_$$ESC^EHRAPI(ALG)_""",""patientIen"":"_PAT
+. W ",""allergyType"":"""_$$ESC^EHRAPI($S(TYP="D":"drug",TYP="F":"food",TYP="E":"environmental",1:TYP))_""""
+. W ",""severity"":"""_$$ESC^EHRAPI($S(SEV="MI":"mild",SEV="MO":"moderate",SEV="SE":"severe",SEV="LT":"life_threatening",1:SEV))_""""
+. I REACT'="" W ",""reactions"":"""_$$ESC^EHRAPI(REACT)_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="A":"active",ST="I":"inactive",1:ST))_"""}}"
 W "]"
 "#,
         patient_ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let allergies = parse_allergies(&output);
+    run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))
+}
+
+async fn get_patient_allergies(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_allergies");
+    match query_patient_allergies(patient_ien).await {
+        Ok(allergies) => {
             (StatusCode::OK, Json(AllergiesResponse { allergies })).into_response()
         }
         Err(e) => (
@@ -987,82 +1697,604 @@ W "]"
     }
 }
 
-fn parse_allergies(json: &str) -> Vec<AllergyResponse> {
-    let mut allergies = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return allergies;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut allergen = String::new();
-        let mut patient_ien = 0i64;
-        let mut allergy_type = String::new();
-        let mut severity = String::new();
-        let mut reactions = None;
-        let mut status = String::new();
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "allergen" => allergen = val.to_string(),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "allergyType" => allergy_type = val.to_string(),
-                    "severity" => severity = val.to_string(),
-                    "reactions" => reactions = Some(val.to_string()),
-                    "status" => status = val.to_string(),
-                    _ => {}
+/// Count a patient's entries in a file via its "C" cross-reference
+///
+/// `root` is the global reference up to the first subscript (e.g. `^PS(52,`)
+/// and `condition` is a MUMPS truth-value over the entry's 0-node `D0`.
+async fn count_patient_entries(root: &str, patient_ien: i64, condition: &str) -> Result<u32, String> {
+    let code = format!(
+        r#"
+N IEN,D0,CNT
+S CNT=0,IEN=0
+F  S IEN=$O({root}"C",{patient_ien},IEN)) Q:IEN=""  S D0=$G({root}IEN,0)) I D0'="",{condition} S CNT=CNT+1
+W CNT
+"#
+    );
+
+    let output = run_mumps(&code).await?;
+    output
+        .trim()
+        .parse()
+        .map_err(|_| format!("Unexpected count output: {}", output))
+}
+
+/// Dashboard counts read from the globals by `count_patient_entries`
+///
+/// Each count is a separate MUMPS call; `EhrDashboardService` runs them
+/// concurrently across pool workers.
+struct MumpsPatientSummaryRepository;
+
+#[async_trait]
+impl EhrPatientSummaryRepository for MumpsPatientSummaryRepository {
+    async fn count_active_problems(&self, patient_ien: i64) -> AppResult<u32> {
+        count_patient_entries("^AUPNPROB(", patient_ien, r#"$P(D0,"^",6)="A""#)
+            .await
+            .map_err(AppError::Internal)
+    }
+
+    async fn count_current_medications(&self, patient_ien: i64) -> AppResult<u32> {
+        count_patient_entries("^PS(52,", patient_ien, r#"$P(D0,"^",10)="A""#)
+            .await
+            .map_err(AppError::Internal)
+    }
+
+    async fn count_known_allergies(&self, patient_ien: i64) -> AppResult<u32> {
+        count_patient_entries("^GMRA(", patient_ien, r#"$P(D0,"^",6)'="I""#)
+            .await
+            .map_err(AppError::Internal)
+    }
+
+    async fn count_pending_labs(&self, patient_ien: i64) -> AppResult<u32> {
+        count_patient_entries("^LR(63,", patient_ien, r#"$P(D0,"^",11)="P""#)
+            .await
+            .map_err(AppError::Internal)
+    }
+
+    async fn count_upcoming_appointments(&self, patient_ien: i64, from: chrono::NaiveDate) -> AppResult<u32> {
+        let condition = format!(r#"$P(D0,"^",8)="S",'("{}"]]$P(D0,"^",2))"#, from.format("%Y-%m-%d"));
+        count_patient_entries("^SD(44,", patient_ien, &condition)
+            .await
+            .map_err(AppError::Internal)
+    }
+}
+
+async fn get_patient_summary(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_summary");
+    let service = EhrDashboardService::new(Arc::new(MumpsPatientSummaryRepository));
+
+    match service.get_patient_summary(patient_ien).await {
+        Ok(summary) => (StatusCode::OK, Json(summary)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e.to_string() }),
+        )
+            .into_response(),
+    }
+}
+
+/// Vital sign readings included in a CCD, most recent first
+const CCD_RECENT_VITALS: usize = 20;
+/// Lab results included in a CCD, most recent first
+const CCD_RECENT_LABS: usize = 100;
+
+/// Representation requested by a CCD export's `Accept` header
+#[derive(Debug, PartialEq)]
+enum CcdFormat {
+    Xml,
+    FhirBundle,
+}
+
+/// Pick the CCD representation from `Accept`; `None` if nothing offered is supported
+fn negotiate_ccd_format(headers: &HeaderMap) -> Option<CcdFormat> {
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok()) else {
+        return Some(CcdFormat::Xml);
+    };
+    accept.split(',').find_map(|media| {
+        let media = media.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match media.as_str() {
+            "application/xml" | "text/xml" | "application/*" | "*/*" | "" => Some(CcdFormat::Xml),
+            "application/json+fhir" | "application/fhir+json" => Some(CcdFormat::FhirBundle),
+            _ => None,
+        }
+    })
+}
+
+/// Demographics for the CCD header from `^DPT`; `None` if the patient doesn't exist
+async fn query_patient_demographics(patient_ien: i64) -> Result<Option<CcdPatient>, String> {
+    // name^sex^dob^mrn^city^state
+    let code = format!(
+        r#"
+N D0
+S D0=$G(^DPT({ien},0))
+I D0'="" W $P(D0,"^",1,3),"^",$G(^DPT({ien},991)),"^",$P($G(^DPT({ien},.11)),"^",4,5)
+"#,
+        ien = patient_ien
+    );
+
+    let output = run_mumps(&code).await?;
+    let Some(line) = output.lines().map(str::trim).find(|l| !l.is_empty()) else {
+        return Ok(None);
+    };
+    let fields: Vec<&str> = line.split('^').collect();
+    let field = |i: usize| fields.get(i).map(|f| f.trim()).unwrap_or("").to_string();
+    let optional = |i: usize| Some(field(i)).filter(|f| !f.is_empty());
+
+    let name = field(0);
+    let (last, first) = match name.split_once(',') {
+        Some((last, first)) => (last.trim().to_string(), first.trim().to_string()),
+        None => (name.clone(), String::new()),
+    };
+
+    Ok(Some(CcdPatient {
+        id: patient_ien.to_string(),
+        mrn: optional(3),
+        first_name: first,
+        last_name: last,
+        sex: field(1),
+        birth_date: field(2),
+        city: optional(4),
+        state: optional(5),
+    }))
+}
+
+/// Export a patient's clinical summary as a C-CDA R2.1 Continuity of Care Document
+///
+/// Active problems, medications and allergies are included along with the
+/// most recent vitals and resulted labs. `Accept: application/json+fhir` is
+/// recognised but answered with 501 until the FHIR Bundle export exists.
+async fn get_patient_ccd(Path(patient_ien): Path<i64>, headers: HeaderMap) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_ccd");
+    match negotiate_ccd_format(&headers) {
+        Some(CcdFormat::Xml) => {}
+        Some(CcdFormat::FhirBundle) => {
+            return (
+                StatusCode::NOT_IMPLEMENTED,
+                Json(ErrorResponse { error: "FHIR Bundle export is not available yet".to_string() }),
+            )
+                .into_response();
+        }
+        None => {
+            return (
+                StatusCode::NOT_ACCEPTABLE,
+                Json(ErrorResponse {
+                    error: "CCD is available as application/xml or application/json+fhir".to_string(),
+                }),
+            )
+                .into_response();
+        }
+    }
+
+    // Each list is a separate MUMPS call, so run them concurrently across pool workers
+    let (patient, problems, medications, allergies, vitals, labs) = tokio::join!(
+        query_patient_demographics(patient_ien),
+        query_patient_problems(patient_ien),
+        query_patient_medications(patient_ien),
+        query_patient_allergies(patient_ien),
+        query_patient_vitals(patient_ien),
+        query_patient_labs(patient_ien),
+    );
+
+    let patient = match patient {
+        Ok(Some(patient)) => patient,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse { error: "Patient not found".to_string() }),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e }),
+            )
+                .into_response();
+        }
+    };
+
+    let document = problems
+        .and_then(|problems| Ok(ccd_builder(patient, problems, medications?, allergies?, vitals?, labs?)))
+        .and_then(|builder| builder.build().map_err(|e| e.to_string()));
+
+    match document {
+        Ok(xml) => (StatusCode::OK, [(header::CONTENT_TYPE, "application/xml")], xml).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
+/// Map a patient's records onto the CCD sections
+fn ccd_builder(
+    patient: CcdPatient,
+    problems: Vec<ProblemResponse>,
+    medications: Vec<MedicationResponse>,
+    allergies: Vec<AllergyResponse>,
+    mut vitals: Vec<VitalResponse>,
+    labs: Vec<LabResultResponse>,
+) -> CcdBuilder {
+    vitals.sort_by(|a, b| b.taken_at.cmp(&a.taken_at));
+    let mut labs: Vec<LabResultResponse> = labs.into_iter().filter(|l| l.status != "pending").collect();
+    labs.sort_by(|a, b| b.collected_at.cmp(&a.collected_at));
+
+    let builder = CcdBuilder::new(patient)
+        .problems(
+            problems
+                .into_iter()
+                .filter(|p| p.status == "active")
+                .map(|p| CcdProblem {
+                    id: p.ien.to_string(),
+                    name: p.diagnosis,
+                    icd10_code: p.icd_code,
+                    onset_date: None,
+                    active: true,
+                })
+                .collect(),
+        )
+        .medications(
+            medications
+                .into_iter()
+                .filter(|m| m.status == "active")
+                .map(|m| CcdMedication {
+                    id: m.ien.to_string(),
+                    name: m.drug_name,
+                    rxnorm_code: m.drug_code,
+                    dose: m.dose,
+                    route: m.route,
+                    frequency: m.frequency,
+                    start_date: m.start_date,
+                    end_date: m.end_date,
+                    active: true,
+                })
+                .collect(),
+        )
+        .allergies(
+            allergies
+                .into_iter()
+                .filter(|a| a.status != "inactive")
+                .map(|a| CcdAllergy {
+                    id: a.ien.to_string(),
+                    allergen: a.allergen,
+                    category: a.allergy_type,
+                    severity: Some(a.severity).filter(|s| !s.is_empty()),
+                    reaction: a.reactions,
+                    active: true,
+                })
+                .collect(),
+        )
+        .vital_signs(
+            vitals
+                .into_iter()
+                .take(CCD_RECENT_VITALS)
+                .map(|v| CcdVitalSign {
+                    id: v.ien.to_string(),
+                    kind: v.vital_type,
+                    value: v.value,
+                    unit: v.unit,
+                    taken_at: v.taken_at,
+                })
+                .collect(),
+        )
+        .lab_results(
+            labs.into_iter()
+                .take(CCD_RECENT_LABS)
+                .map(|l| CcdLabResult {
+                    id: l.ien.to_string(),
+                    name: l.test_name,
+                    loinc_code: l.test_code,
+                    value: l.value,
+                    unit: l.unit,
+                    reference_range: l.reference_range,
+                    interpretation: l.abnormal_flag.as_deref().and_then(lab_interpretation_code).map(str::to_string),
+                    collected_at: l.collected_at,
+                })
+                .collect(),
+        );
+
+    match std::env::var("CCD_ORGANIZATION_OID") {
+        Ok(oid) => {
+            let name = std::env::var("CCD_ORGANIZATION_NAME").unwrap_or_else(|_| "Health V1".to_string());
+            builder.organization(oid, name)
+        }
+        Err(_) => builder,
+    }
+}
+
+/// HL7 ObservationInterpretation code for a lab abnormal flag
+fn lab_interpretation_code(flag: &str) -> Option<&'static str> {
+    match flag {
+        "normal" => Some("N"),
+        "low" => Some("L"),
+        "high" => Some("H"),
+        "critical_low" => Some("LL"),
+        "critical_high" => Some("HH"),
+        _ => None,
+    }
+}
+
+/// First candidate that is likely the same person as `last_name, first_name`
+///
+/// Candidates must share the date of birth. Names are compared after search
+/// normalization, and names whose last and first parts both have the same
+/// Soundex code count as matches (SMITH,JON vs SMYTH,JOHN). A name that
+/// merely contains the other one does not.
+fn find_duplicate_patient(
+    candidates: &[(i64, String, String)],
+    last_name: &str,
+    first_name: &str,
+    date_of_birth: &str,
+) -> Option<DuplicatePatientResponse> {
+    let last = normalize_search_term(last_name);
+    let first = normalize_search_term(first_name);
+    let last_soundex = soundex(&last);
+    let first_soundex = soundex(&first);
+
+    candidates
+        .iter()
+        .filter(|(_, _, dob)| dob.trim() == date_of_birth.trim())
+        .find(|(_, name, _)| {
+            let (candidate_last, candidate_first) = name.split_once(',').unwrap_or((name.as_str(), ""));
+            let candidate_last = normalize_search_term(candidate_last);
+            let candidate_first = normalize_search_term(candidate_first);
+
+            (candidate_last == last && candidate_first == first)
+                || (soundex(&candidate_last) == last_soundex && soundex(&candidate_first) == first_soundex)
+        })
+        .map(|(ien, name, dob)| DuplicatePatientResponse {
+            existing_ien: *ien,
+            name: name.clone(),
+            date_of_birth: dob.clone(),
+        })
+}
+
+/// Create a patient, refusing likely duplicates unless `?allow_similar=true`
+///
+/// The duplicate lookup and the insert run as one routine under the
+/// `^DPT(0)` lock. The routine refuses to insert while any patient with the
+/// same last-name initial and DOB has not been compared yet, returning those
+/// candidates instead; once they are all cleared it inserts in the same
+/// locked pass that re-checked them.
+async fn create_patient(
+    Query(query): Query<CreatePatientQuery>,
+    Json(req): Json<CreatePatientRequest>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_patient");
+    let pieces = [
+        ("lastName", req.last_name.as_str()),
+        ("firstName", req.first_name.as_str()),
+        ("sex", req.sex.as_str()),
+        ("dateOfBirth", req.date_of_birth.as_str()),
+        ("ssn", req.ssn.as_deref().unwrap_or("")),
+        ("mrn", req.mrn.as_deref().unwrap_or("")),
+        ("city", req.city.as_deref().unwrap_or("")),
+        ("state", req.state.as_deref().unwrap_or("")),
+    ];
+    if let Err(error) = validate_pieces(&pieces) {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
+    }
+
+    // Every round clears at least one more patient, so this ends
+    let mut cleared = Vec::new();
+    let ien = loop {
+        let code = create_patient_code(&req, query.allow_similar, &cleared);
+        match run_mumps(&code).await.and_then(|output| parse_patient_creation(&output)) {
+            Ok(PatientCreation::Created(ien)) => break ien,
+            Ok(PatientCreation::Candidates(candidates)) => {
+                if let Some(duplicate) =
+                    find_duplicate_patient(&candidates, &req.last_name, &req.first_name, &req.date_of_birth)
+                {
+                    return (StatusCode::CONFLICT, Json(duplicate)).into_response();
                 }
+                cleared.extend(candidates.iter().map(|(ien, _, _)| *ien));
+            }
+            Ok(PatientCreation::Busy) => {
+                return (
+                    StatusCode::CONFLICT,
+                    Json(ErrorResponse { error: "Patient file is busy; retry".to_string() }),
+                )
+                    .into_response()
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse { error: e }),
+                )
+                    .into_response()
             }
         }
+    };
 
-        allergies.push(AllergyResponse {
-            ien,
-            allergen,
-            patient_ien,
-            allergy_type,
-            severity,
-            reactions,
-            status,
-        });
+    metrics::patient_created();
+    (
+        StatusCode::CREATED,
+        Json(CreateResponse { success: true, ien }),
+    )
+        .into_response()
+}
+
+/// Routine creating `req` under the `^DPT(0)` lock, unless a patient with the
+/// same last-name initial and DOB is missing from `cleared`
+fn create_patient_code(req: &CreatePatientRequest, allow_similar: bool, cleared: &[i64]) -> String {
+    let initial = normalize_search_term(&req.last_name).chars().next().map(String::from).unwrap_or_default();
+    let cleared: String = cleared.iter().map(|ien| format!("{}^", ien)).collect();
+    let name = mumps_escape(&format!("{},{}", req.last_name.to_uppercase(), req.first_name.to_uppercase()));
+    let sex = mumps_escape(&req.sex.chars().next().unwrap_or('U').to_string());
+    let mrn = mumps_escape(req.mrn.as_deref().unwrap_or(""));
+
+    // Seek to just before the initial in ^DPT("B") and stop once past it
+    format!(
+        r#"
+N IEN,NM,X,D0,P,DOB,CL,R S R="",P="{initial}",DOB="{search_dob}",CL="^{cleared}"
+L +^DPT(0):5 S:'$T R="BUSY"
+I R="",'{allow},P'="" S NM=$O(^DPT("B",P),-1) F  S NM=$O(^DPT("B",NM)) Q:NM=""  Q:$E(NM,1)'=P  S X=0 F  S X=$O(^DPT("B",NM,X)) Q:X=""  S D0=$G(^DPT(X,0)) I D0'="",$P(D0,"^",3)=DOB,CL'[("^"_X_"^") S R="CANDIDATES" W X,"^",$P(D0,"^",1),"^",$P(D0,"^",3),!
+I R="" S IEN=$P($G(^DPT(0)),"^",3)+1
+I R="" S ^DPT(IEN,0)="{name}^{sex}^{dob}^{ssn}"
+I R="","{mrn}"'="" S ^DPT(IEN,991)="{mrn}"
+I R="","{city}{state}"'="" S ^DPT(IEN,.11)="^^^{city}^{state}"
+I R="" S ^DPT("B","{name}",IEN)="",$P(^DPT(0),"^",3)=IEN,$P(^DPT(0),"^",4)=IEN,R="OK^"_IEN
+L
+W R
+"#,
+        initial = mumps_escape(&initial),
+        search_dob = mumps_escape(req.date_of_birth.trim()),
+        allow = u8::from(allow_similar),
+        dob = mumps_escape(&req.date_of_birth),
+        ssn = mumps_escape(req.ssn.as_deref().unwrap_or("")),
+        city = mumps_escape(req.city.as_deref().unwrap_or("")),
+        state = mumps_escape(req.state.as_deref().unwrap_or("")),
+    )
+}
+
+/// Result of the locked routine in `create_patient`
+#[derive(Debug, PartialEq)]
+enum PatientCreation {
+    Created(i64),
+    /// Patients with the same initial and DOB not compared yet, as `(ien, name, dob)`
+    Candidates(Vec<(i64, String, String)>),
+    /// The `^DPT(0)` lock could not be taken in time
+    Busy,
+}
+
+/// Parse `OK^IEN`, `BUSY`, or `IEN^NAME^DOB` lines followed by `CANDIDATES`
+fn parse_patient_creation(output: &str) -> Result<PatientCreation, String> {
+    let mut lines: Vec<&str> = output.trim().lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    let unexpected = || format!("Unexpected patient creation result: {}", output.trim());
+    let result = lines.pop().ok_or_else(unexpected)?;
+    match result.split('^').collect::<Vec<_>>().as_slice() {
+        ["OK", ien] if lines.is_empty() => ien.parse().map(PatientCreation::Created).map_err(|_| unexpected()),
+        ["BUSY"] if lines.is_empty() => Ok(PatientCreation::Busy),
+        ["CANDIDATES"] => lines
+            .iter()
+            .map(|line| {
+                let mut fields = line.split('^');
+                let ien = fields.next().and_then(|ien| ien.parse().ok()).ok_or_else(unexpected)?;
+                let name = fields.next().ok_or_else(unexpected)?.to_string();
+                let dob = fields.next().unwrap_or("").to_string();
+                Ok((ien, name, dob))
+            })
+            .collect::<Result<_, _>>()
+            .map(PatientCreation::Candidates),
+        _ => Err(unexpected()),
     }
+}
 
-    allergies
+/// Apply a partial demographics update, keeping the "B" name index in step
+async fn update_patient(
+    Path(ien): Path<i64>,
+    Json(req): Json<PatientUpdateRequest>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("update_patient");
+    let pieces = [
+        ("lastName", req.last_name.as_deref().unwrap_or("")),
+        ("firstName", req.first_name.as_deref().unwrap_or("")),
+        ("sex", req.sex.as_deref().unwrap_or("")),
+        ("dateOfBirth", req.date_of_birth.as_deref().unwrap_or("")),
+        ("ssn", req.ssn.as_deref().unwrap_or("")),
+        ("city", req.city.as_deref().unwrap_or("")),
+        ("state", req.state.as_deref().unwrap_or("")),
+    ];
+    if let Err(error) = validate_pieces(&pieces) {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
+    }
+    let upper = |value: &Option<String>| mumps_escape(&value.as_deref().unwrap_or("").to_uppercase());
+    let plain = |value: &Option<String>| mumps_escape(value.as_deref().unwrap_or(""));
+    let sex = req.sex.as_deref().and_then(|s| s.chars().next()).map(|c| c.to_string());
+
+    let code = format!(
+        r#"
+N D0,OLD,NM,LN,FN,R
+S D0=$G(^DPT({ien},0)),R=$S(D0="":"NOT_FOUND",1:"")
+I R="" S OLD=$P(D0,"^",1),LN="{last}",FN="{first}"
+I R="" S NM=$S(LN'="":LN,1:$P(OLD,",",1))_","_$S(FN'="":FN,1:$P(OLD,",",2))
+I R="",LN'=""!(FN'="") S $P(D0,"^",1)=NM
+I R="","{sex}"'="" S $P(D0,"^",2)="{sex}"
+I R="","{dob}"'="" S $P(D0,"^",3)="{dob}"
+I R="","{ssn}"'="" S $P(D0,"^",4)="{ssn}"
+I R="" S ^DPT({ien},0)=D0
+I R="",$P(D0,"^",1)'=OLD K ^DPT("B",OLD,{ien}) S ^DPT("B",$P(D0,"^",1),{ien})=""
+I R="","{city}"'="" S $P(^DPT({ien},.11),"^",4)="{city}"
+I R="","{state}"'="" S $P(^DPT({ien},.11),"^",5)="{state}"
+I R="" S R="OK"
+W R
+"#,
+        ien = ien,
+        last = upper(&req.last_name),
+        first = upper(&req.first_name),
+        sex = upper(&sex),
+        dob = plain(&req.date_of_birth),
+        ssn = plain(&req.ssn),
+        city = plain(&req.city),
+        state = plain(&req.state),
+    );
+
+    match run_mumps(&code).await {
+        Ok(output) => match output.trim() {
+            "OK" => (StatusCode::OK, Json(CreateResponse { success: true, ien })).into_response(),
+            "NOT_FOUND" => (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse { error: "Patient not found".to_string() }),
+            ).into_response(),
+            _ => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: format!("Unexpected response: {}", output) }),
+            ).into_response(),
+        },
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
 }
 
-async fn create_patient(Json(req): Json<CreatePatientRequest>) -> impl IntoResponse {
-    let name = format!("{},{}", req.last_name.to_uppercase(), req.first_name.to_uppercase());
-    let sex = req.sex.chars().next().unwrap_or('U');
-    let ssn = req.ssn.unwrap_or_default();
-    let mrn = req.mrn.unwrap_or_default();
+/// Merge a duplicate patient into a survivor
+///
+/// Follows the VistA convention of setting `^DPT(duplicate,-9)` to the
+/// survivor's IEN: the duplicate drops out of patient lists and MRN lookups
+/// while its clinical records stay where they are.
+async fn merge_patients(Json(req): Json<MergePatientsRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("merge_patients");
+    if req.survivor_ien == req.duplicate_ien {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse { error: "Cannot merge patient into itself".to_string() }),
+        )
+            .into_response();
+    }
 
     let code = format!(
         r#"
-N IEN S IEN=$P($G(^DPT(0)),"^",3)+1
-S ^DPT(IEN,0)="{}^{}^{}^{}"
-I "{}"'="" S ^DPT(IEN,991)="{}"
-S ^DPT("B","{}",IEN)=""
-S $P(^DPT(0),"^",3)=IEN,$P(^DPT(0),"^",4)=IEN
-W IEN
+N R S R=""
+I $G(^DPT({survivor},0))=""!($G(^DPT({duplicate},0))="") S R="NOT_FOUND"
+I R="",$D(^DPT({survivor},-9))!$D(^DPT({duplicate},-9)) S R="ALREADY_MERGED"
+I R="" S ^DPT({duplicate},-9)={survivor},R="OK"
+W R
 "#,
-        name, sex, req.date_of_birth, ssn, mrn, mrn, name
+        survivor = req.survivor_ien,
+        duplicate = req.duplicate_ien,
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let ien: i64 = output.trim().parse().unwrap_or(0);
-            (
-                StatusCode::CREATED,
-                Json(CreateResponse { success: true, ien }),
-            )
-                .into_response()
-        }
+    match run_mumps(&code).await {
+        Ok(output) => match output.trim() {
+            "OK" => (
+                StatusCode::OK,
+                Json(CreateResponse { success: true, ien: req.survivor_ien }),
+            ).into_response(),
+            "NOT_FOUND" => (
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse { error: "Patient not found".to_string() }),
+            ).into_response(),
+            "ALREADY_MERGED" => (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse { error: "Patient has already been merged".to_string() }),
+            ).into_response(),
+            _ => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: format!("Unexpected response: {}", output) }),
+            ).into_response(),
+        },
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse { error: e }),
@@ -1071,9 +2303,83 @@ W IEN
     }
 }
 
+/// IEN of the unmerged patient whose MRN (`^DPT(IEN,991)`) is `mrn`
+async fn find_patient_by_mrn(mrn: &str) -> Result<Option<i64>, String> {
+    let code = format!(
+        r#"
+N IEN S IEN=0
+F  S IEN=$O(^DPT(IEN)) Q:'IEN  I $G(^DPT(IEN,991))="{mrn}",'$D(^DPT(IEN,-9)) W IEN Q
+"#,
+        mrn = mumps_escape(mrn),
+    );
+    Ok(run_mumps(&code).await?.trim().parse().ok())
+}
+
+/// `POST /api/v1/ehr/hl7/adt`: apply an HL7 v2 ADT message
+///
+/// A01 registers the patient, or updates them when the MRN is already known;
+/// A08 updates demographics and A40 merges the MRG-1 record into the PID one.
+async fn receive_adt(headers: HeaderMap, body: String) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("receive_adt");
+    let content_type = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("");
+    if !content_type.starts_with("application/hl7-v2") {
+        return (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(ErrorResponse { error: "Expected Content-Type: application/hl7-v2".to_string() }),
+        )
+            .into_response();
+    }
+
+    let message = match AdtParser::parse(&body) {
+        Ok(message) => message,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })).into_response(),
+    };
+
+    let resolve = |mrn: String| async move {
+        match find_patient_by_mrn(&mrn).await {
+            Ok(Some(ien)) => Ok(ien),
+            Ok(None) => Err((
+                StatusCode::NOT_FOUND,
+                Json(ErrorResponse { error: format!("No patient with MRN {}", mrn) }),
+            )
+                .into_response()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error: e })).into_response()),
+        }
+    };
+
+    match message {
+        AdtMessage::Admit(req) => {
+            let mrn = req.mrn.clone().unwrap_or_default();
+            match find_patient_by_mrn(&mrn).await {
+                Ok(Some(ien)) => update_patient(Path(ien), Json(req.into())).await.into_response(),
+                Ok(None) => create_patient(Query(CreatePatientQuery { allow_similar: false }), Json(req))
+                    .await
+                    .into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse { error: e })).into_response(),
+            }
+        }
+        AdtMessage::Update { mrn, update } => match resolve(mrn).await {
+            Ok(ien) => update_patient(Path(ien), Json(update)).await.into_response(),
+            Err(response) => response,
+        },
+        AdtMessage::Merge { mrn, prior_mrn } => {
+            let survivor_ien = match resolve(mrn).await {
+                Ok(ien) => ien,
+                Err(response) => return response,
+            };
+            let duplicate_ien = match resolve(prior_mrn).await {
+                Ok(ien) => ien,
+                Err(response) => return response,
+            };
+            merge_patients(Json(MergePatientsRequest { survivor_ien, duplicate_ien })).await.into_response()
+        }
+    }
+}
+
 // === Visit Handlers ===
 
 async fn get_patient_visits(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_visits");
     // ^AUPNVSIT - VistA Visit File (File #9000010)
     let code = format!(
         r#"
@@ -1086,21 +2392,20 @@ F  S IEN=$O(^AUPNVSIT("C",{},IEN)) Q:IEN=""  D
 . S FIRST=0
 . S PAT=$P(D0,"^",1),TYP=$P(D0,"^",2),DT=$P(D0,"^",3),TM=$P(D0,"^",4),LOC=$P(D0,"^",5),PROV=$P(D0,"^",6),CC=$P(D0,"^",7),ST=$P(D0,"^",8)
 . W "{{""ien"":"_IEN_",""patientIen"":"_PAT
-. W ",""visitType"":"""_$S(TYP="O":"outpatient",TYP="I":"inpatient",TYP="E":"emergency",TYP="T":"telehealth",1:TYP)_""""
-. W ",""visitDate"":"""_DT_""""
-. I TM'="" W ",""visitTime"":"""_TM_""""
-. I LOC'="" W ",""location"":"""_LOC_""""
+. W ",""visitType"":"""_$$ESC^EHRAPI($S(TYP="O":"outpatient",TYP="I":"inpatient",TYP="E":"emergency",TYP="T":"telehealth",1:TYP))_""""
+. W ",""visitDate"":"""_$$ESC^EHRAPI(DT)_""""
+. I TM'="" W ",""visitTime"":"""_$$ESC^EHRAPI(TM)_""""
+. I LOC'="" W ",""location"":"""_$$ESC^EHRAPI(LOC)_""""
 . I PROV W ",""providerIen"":"_PROV
-. I CC'="" W ",""chiefComplaint"":"""_CC_""""
-. W ",""status"":"""_$S(ST="A":"active",ST="C":"completed",ST="X":"cancelled",1:ST)_"""}}"
+. I CC'="" W ",""chiefComplaint"":"""_$$ESC^EHRAPI(CC)_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="A":"active",ST="C":"completed",ST="X":"cancelled",1:ST))_"""}}"
 W "]"
 "#,
         patient_ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let visits = parse_visits(&output);
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(visits) => {
             (StatusCode::OK, Json(VisitsResponse { visits })).into_response()
         }
         Err(e) => (
@@ -1111,62 +2416,8 @@ W "]"
     }
 }
 
-fn parse_visits(json: &str) -> Vec<VisitResponse> {
-    let mut visits = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return visits;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut visit_type = String::new();
-        let mut visit_date = String::new();
-        let mut visit_time = None;
-        let mut location = None;
-        let mut provider_ien = None;
-        let mut chief_complaint = None;
-        let mut status = String::new();
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "visitType" => visit_type = val.to_string(),
-                    "visitDate" => visit_date = val.to_string(),
-                    "visitTime" => visit_time = Some(val.to_string()),
-                    "location" => location = Some(val.to_string()),
-                    "providerIen" => provider_ien = val.parse().ok(),
-                    "chiefComplaint" => chief_complaint = Some(val.to_string()),
-                    "status" => status = val.to_string(),
-                    _ => {}
-                }
-            }
-        }
-
-        visits.push(VisitResponse {
-            ien,
-            patient_ien,
-            visit_type,
-            visit_date,
-            visit_time,
-            location,
-            provider_ien,
-            chief_complaint,
-            status,
-        });
-    }
-
-    visits
-}
-
 async fn create_visit(Json(req): Json<CreateVisitRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_visit");
     let visit_type = match req.visit_type.as_str() {
         "outpatient" => "O",
         "inpatient" => "I",
@@ -1190,7 +2441,7 @@ W IEN
         req.patient_ien, visit_type, req.visit_date, visit_time, location, provider_ien, chief_complaint, req.patient_ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
             (
@@ -1209,7 +2460,7 @@ W IEN
 
 // === Vital Signs Handlers ===
 
-async fn get_patient_vitals(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+async fn query_patient_vitals(patient_ien: i64) -> Result<Vec<VitalResponse>, String> {
     // ^GMR(120.5) - VistA Vital Signs File (File #120.5)
     let code = format!(
         r#"
@@ -1223,17 +2474,21 @@ F  S IEN=$O(^GMR(120.5,"C",{},IEN)) Q:IEN=""  D
 . S PAT=$P(D0,"^",1),VIS=$P(D0,"^",2),TYP=$P(D0,"^",3),VAL=$P(D0,"^",4),UNT=$P(D0,"^",5),DT=$P(D0,"^",6),BY=$P(D0,"^",7)
 . W "{{""ien"":"_IEN_",""patientIen"":"_PAT
 . I VIS W ",""visitIen"":"_VIS
-. W ",""vitalType"":"""_TYP_""",""value"":"""_VAL_""",""unit"":"""_UNT_""",""takenAt"":"""_DT_""""
-. I BY'="" W ",""takenBy"":"""_BY_""""
+. W ",""vitalType"":"""_$$ESC^EHRAPI(TYP)_""",""value"":"""_$$ESC^EHRAPI(VAL)_""",""unit"":"""_$$ESC^EHRAPI(UNT)_""",""takenAt"":"""_$$ESC^EHRAPI(DT)_""""
+. I BY'="" W ",""takenBy"":"""_$$ESC^EHRAPI(BY)_""""
 . W "}}"
 W "]"
 "#,
         patient_ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let vitals = parse_vitals(&output);
+    run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))
+}
+
+async fn get_patient_vitals(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_vitals");
+    match query_patient_vitals(patient_ien).await {
+        Ok(vitals) => {
             (StatusCode::OK, Json(VitalsResponse { vitals })).into_response()
         }
         Err(e) => (
@@ -1244,59 +2499,111 @@ W "]"
     }
 }
 
-fn parse_vitals(json: &str) -> Vec<VitalResponse> {
-    let mut vitals = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return vitals;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut visit_ien = None;
-        let mut vital_type = String::new();
-        let mut value = String::new();
-        let mut unit = String::new();
-        let mut taken_at = String::new();
-        let mut taken_by = None;
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "visitIen" => visit_ien = val.parse().ok(),
-                    "vitalType" => vital_type = val.to_string(),
-                    "value" => value = val.to_string(),
-                    "unit" => unit = val.to_string(),
-                    "takenAt" => taken_at = val.to_string(),
-                    "takenBy" => taken_by = Some(val.to_string()),
-                    _ => {}
-                }
-            }
+/// Default look-back window for vital trends
+const VITAL_TREND_DEFAULT_DAYS: u32 = 30;
+/// Longest look-back window accepted for vital trends
+const VITAL_TREND_MAX_DAYS: u32 = 3650;
+
+/// Readings of one vital type taken since `since` (`YYYYMMDD.HHMMSS`)
+async fn query_recent_vitals_by_type(
+    patient_ien: i64,
+    vital_type: &str,
+    since: &str,
+) -> Result<Vec<VitalResponse>, String> {
+    let code = format!(
+        r#"
+N IEN,D0,FIRST
+W "["
+S FIRST=1,IEN=0
+F  S IEN=$O(^GMR(120.5,"C",{},IEN)) Q:IEN=""  D
+. S D0=$G(^GMR(120.5,IEN,0)) Q:D0=""
+. Q:$P(D0,"^",3)'="{}"
+. Q:+$P(D0,"^",6)<{}
+. I 'FIRST W ","
+. S FIRST=0
+. S PAT=$P(D0,"^",1),VIS=$P(D0,"^",2),TYP=$P(D0,"^",3),VAL=$P(D0,"^",4),UNT=$P(D0,"^",5),DT=$P(D0,"^",6),BY=$P(D0,"^",7)
+. W "{{""ien"":"_IEN_",""patientIen"":"_PAT
+. I VIS W ",""visitIen"":"_VIS
+. W ",""vitalType"":"""_$$ESC^EHRAPI(TYP)_""",""value"":"""_$$ESC^EHRAPI(VAL)_""",""unit"":"""_$$ESC^EHRAPI(UNT)_""",""takenAt"":"""_$$ESC^EHRAPI(DT)_""""
+. I BY'="" W ",""takenBy"":"""_$$ESC^EHRAPI(BY)_""""
+. W "}}"
+W "]"
+"#,
+        patient_ien,
+        mumps_escape(vital_type),
+        since
+    );
+
+    run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))
+}
+
+/// Numeric readings of a vital series; blood pressure yields (systolic, diastolic) pairs.
+/// Values that do not parse are skipped.
+fn vital_trend_readings(vitals: &[VitalResponse]) -> (Vec<VitalReading>, Vec<VitalReading>) {
+    let mut primary = Vec::new();
+    let mut diastolic = Vec::new();
+    for vital in vitals {
+        let Ok(taken_at) = chrono::NaiveDateTime::parse_from_str(&vital.taken_at, "%Y%m%d.%H%M%S") else {
+            continue;
+        };
+        if let Some((sys, dia)) = parse_blood_pressure(&vital.value) {
+            primary.push(VitalReading { taken_at, value: sys });
+            diastolic.push(VitalReading { taken_at, value: dia });
+        } else if let Ok(value) = vital.value.trim().parse() {
+            primary.push(VitalReading { taken_at, value });
         }
+    }
+    (primary, diastolic)
+}
 
-        vitals.push(VitalResponse {
-            ien,
-            patient_ien,
-            visit_ien,
-            vital_type,
-            value,
-            unit,
-            taken_at,
-            taken_by,
-        });
+/// Readings of one vital type over the last `days` days with their linear trend
+async fn get_patient_vital_trend(
+    Path(patient_ien): Path<i64>,
+    Query(query): Query<VitalTrendQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_vital_trend");
+    let days = query.days.unwrap_or(VITAL_TREND_DEFAULT_DAYS).clamp(1, VITAL_TREND_MAX_DAYS);
+    let since = (chrono::Utc::now() - chrono::Duration::days(i64::from(days)))
+        .format("%Y%m%d.%H%M%S")
+        .to_string();
+
+    let vitals = match query_recent_vitals_by_type(patient_ien, &query.vital_type, &since).await {
+        Ok(vitals) => vitals,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e }),
+            )
+                .into_response()
+        }
+    };
+
+    let calculator = VitalTrendCalculator::default();
+    let (primary, diastolic) = vital_trend_readings(&vitals);
+    let mut response = VitalTrendResponse {
+        patient_ien,
+        vital_type: query.vital_type,
+        days,
+        trend: None,
+        systolic: None,
+        diastolic: None,
+    };
+    if response.vital_type == "blood_pressure" {
+        response.systolic = Some(calculator.calculate(&primary));
+        response.diastolic = Some(calculator.calculate(&diastolic));
+    } else {
+        response.trend = Some(calculator.calculate(&primary));
     }
 
-    vitals
+    (StatusCode::OK, Json(response)).into_response()
 }
 
+/// Record a vital sign, returning any CDS alerts the value alone raises
+///
+/// Only the new reading is evaluated, so rules on age or medications are
+/// left to `POST /api/v1/ehr/patients/{ien}/cds-alerts`.
 async fn create_vital(Json(req): Json<CreateVitalRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_vital");
     let visit_ien = req.visit_ien.unwrap_or(0);
     let taken_by = req.taken_by.unwrap_or_default();
     let now = chrono::Utc::now().format("%Y%m%d.%H%M%S").to_string();
@@ -1312,12 +2619,18 @@ W IEN
         req.patient_ien, visit_ien, req.vital_type, req.value, req.unit, now, taken_by, req.patient_ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
+            let mut patient = PatientContext {
+                patient_id: req.patient_ien.to_string(),
+                ..Default::default()
+            };
+            add_vital_facts(&mut patient.vitals, &req.vital_type, &req.value);
+            let alerts = cds_evaluator().evaluate(&patient);
             (
                 StatusCode::CREATED,
-                Json(CreateResponse { success: true, ien }),
+                Json(CreateVitalResponse { success: true, ien, alerts }),
             )
                 .into_response()
         }
@@ -1331,7 +2644,7 @@ W IEN
 
 // === Medication Handlers ===
 
-async fn get_patient_medications(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+async fn query_patient_medications(patient_ien: i64) -> Result<Vec<MedicationResponse>, String> {
     // ^PS(52) - VistA Pharmacy Patient File (File #52)
     let code = format!(
         r#"
@@ -1344,22 +2657,26 @@ F  S IEN=$O(^PS(52,"C",{},IEN)) Q:IEN=""  D
 . S FIRST=0
 . S PAT=$P(D0,"^",1),DRG=$P(D0,"^",2),CODE=$P(D0,"^",3),DOS=$P(D0,"^",4),RTE=$P(D0,"^",5)
 . S FRQ=$P(D0,"^",6),SDT=$P(D0,"^",7),EDT=$P(D0,"^",8),PRV=$P(D0,"^",9),ST=$P(D0,"^",10),INST=$P(D0,"^",11)
-. W "{{""ien"":"_IEN_",""patientIen"":"_PAT_",""drugName"":"""_DRG_""""
-. I CODE'="" W ",""drugCode"":"""_CODE_""""
-. W ",""dose"":"""_DOS_""",""route"":"""_RTE_""",""frequency"":"""_FRQ_""",""startDate"":"""_SDT_""""
-. I EDT'="" W ",""endDate"":"""_EDT_""""
+. W "{{""ien"":"_IEN_",""patientIen"":"_PAT_",""drugName"":"""_$$ESC^EHRAPI(DRG)_""""
+. I CODE'="" W ",""drugCode"":"""_$$ESC^EHRAPI(CODE)_""""
+. W ",""dose"":"""_$$ESC^EHRAPI(DOS)_""",""route"":"""_$$ESC^EHRAPI(RTE)_""",""frequency"":"""_$$ESC^EHRAPI(FRQ)_""",""startDate"":"""_$$ESC^EHRAPI(SDT)_""""
+. I EDT'="" W ",""endDate"":"""_$$ESC^EHRAPI(EDT)_""""
 . I PRV W ",""prescriberIen"":"_PRV
-. W ",""status"":"""_$S(ST="A":"active",ST="D":"discontinued",ST="C":"completed",ST="H":"on_hold",1:ST)_""""
-. I INST'="" W ",""instructions"":"""_INST_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="A":"active",ST="D":"discontinued",ST="C":"completed",ST="H":"on_hold",1:ST))_""""
+. I INST'="" W ",""instructions"":"""_$$ESC^EHRAPI(INST)_""""
 . W "}}"
 W "]"
 "#,
         patient_ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let medications = parse_medications(&output);
+    run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))
+}
+
+async fn get_patient_medications(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_medications");
+    match query_patient_medications(patient_ien).await {
+        Ok(medications) => {
             (StatusCode::OK, Json(MedicationsResponse { medications })).into_response()
         }
         Err(e) => (
@@ -1370,71 +2687,8 @@ W "]"
     }
 }
 
-fn parse_medications(json: &str) -> Vec<MedicationResponse> {
-    let mut medications = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return medications;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut drug_name = String::new();
-        let mut drug_code = None;
-        let mut dose = String::new();
-        let mut route = String::new();
-        let mut frequency = String::new();
-        let mut start_date = String::new();
-        let mut end_date = None;
-        let mut prescriber_ien = None;
-        let mut status = String::new();
-        let mut instructions = None;
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "drugName" => drug_name = val.to_string(),
-                    "drugCode" => drug_code = Some(val.to_string()),
-                    "dose" => dose = val.to_string(),
-                    "route" => route = val.to_string(),
-                    "frequency" => frequency = val.to_string(),
-                    "startDate" => start_date = val.to_string(),
-                    "endDate" => end_date = Some(val.to_string()),
-                    "prescriberIen" => prescriber_ien = val.parse().ok(),
-                    "status" => status = val.to_string(),
-                    "instructions" => instructions = Some(val.to_string()),
-                    _ => {}
-                }
-            }
-        }
-
-        medications.push(MedicationResponse {
-            ien,
-            patient_ien,
-            drug_name,
-            drug_code,
-            dose,
-            route,
-            frequency,
-            start_date,
-            end_date,
-            prescriber_ien,
-            status,
-            instructions,
-        });
-    }
-
-    medications
-}
-
 async fn create_medication(Json(req): Json<CreateMedicationRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_medication");
     let drug_code = req.drug_code.unwrap_or_default();
     let end_date = req.end_date.unwrap_or_default();
     let prescriber_ien = req.prescriber_ien.unwrap_or(0);
@@ -1452,7 +2706,7 @@ W IEN
         req.start_date, end_date, prescriber_ien, instructions, req.patient_ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
             (
@@ -1471,7 +2725,7 @@ W IEN
 
 // === Lab Results Handlers ===
 
-async fn get_patient_labs(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+async fn query_patient_labs(patient_ien: i64) -> Result<Vec<LabResultResponse>, String> {
     // ^LR(63) - VistA Lab Data File (File #63)
     let code = format!(
         r#"
@@ -1486,23 +2740,27 @@ F  S IEN=$O(^LR(63,"C",{},IEN)) Q:IEN=""  D
 . S UNT=$P(D0,"^",6),REF=$P(D0,"^",7),ABN=$P(D0,"^",8),CDT=$P(D0,"^",9),RDT=$P(D0,"^",10),ST=$P(D0,"^",11)
 . W "{{""ien"":"_IEN_",""patientIen"":"_PAT
 . I VIS W ",""visitIen"":"_VIS
-. W ",""testName"":"""_TST_""""
-. I CODE'="" W ",""testCode"":"""_CODE_""""
-. W ",""value"":"""_VAL_""""
-. I UNT'="" W ",""unit"":"""_UNT_""""
-. I REF'="" W ",""referenceRange"":"""_REF_""""
-. I ABN'="" W ",""abnormalFlag"":"""_$S(ABN="N":"normal",ABN="L":"low",ABN="H":"high",ABN="LL":"critical_low",ABN="HH":"critical_high",1:ABN)_""""
-. W ",""collectedAt"":"""_CDT_""""
-. I RDT'="" W ",""resultedAt"":"""_RDT_""""
-. W ",""status"":"""_$S(ST="P":"pending",ST="F":"final",ST="C":"corrected",1:ST)_"""}}"
+. W ",""testName"":"""_$$ESC^EHRAPI(TST)_""""
+. I CODE'="" W ",""testCode"":"""_$$ESC^EHRAPI(CODE)_""""
+. W ",""value"":"""_$$ESC^EHRAPI(VAL)_""""
+. I UNT'="" W ",""unit"":"""_$$ESC^EHRAPI(UNT)_""""
+. I REF'="" W ",""referenceRange"":"""_$$ESC^EHRAPI(REF)_""""
+. I ABN'="" W ",""abnormalFlag"":"""_$$ESC^EHRAPI($S(ABN="N":"normal",ABN="L":"low",ABN="H":"high",ABN="LL":"critical_low",ABN="HH":"critical_high",1:ABN))_""""
+. W ",""collectedAt"":"""_$$ESC^EHRAPI(CDT)_""""
+. I RDT'="" W ",""resultedAt"":"""_$$ESC^EHRAPI(RDT)_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="P":"pending",ST="F":"final",ST="C":"corrected",1:ST))_"""}}"
 W "]"
 "#,
         patient_ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let results = parse_lab_results(&output);
+    run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))
+}
+
+async fn get_patient_labs(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_labs");
+    match query_patient_labs(patient_ien).await {
+        Ok(results) => {
             (StatusCode::OK, Json(LabResultsResponse { results })).into_response()
         }
         Err(e) => (
@@ -1513,71 +2771,27 @@ W "]"
     }
 }
 
-fn parse_lab_results(json: &str) -> Vec<LabResultResponse> {
-    let mut results = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return results;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut visit_ien = None;
-        let mut test_name = String::new();
-        let mut test_code = None;
-        let mut value = String::new();
-        let mut unit = None;
-        let mut reference_range = None;
-        let mut abnormal_flag = None;
-        let mut collected_at = String::new();
-        let mut resulted_at = None;
-        let mut status = String::new();
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "visitIen" => visit_ien = val.parse().ok(),
-                    "testName" => test_name = val.to_string(),
-                    "testCode" => test_code = Some(val.to_string()),
-                    "value" => value = val.to_string(),
-                    "unit" => unit = Some(val.to_string()),
-                    "referenceRange" => reference_range = Some(val.to_string()),
-                    "abnormalFlag" => abnormal_flag = Some(val.to_string()),
-                    "collectedAt" => collected_at = val.to_string(),
-                    "resultedAt" => resulted_at = Some(val.to_string()),
-                    "status" => status = val.to_string(),
-                    _ => {}
-                }
-            }
-        }
+/// A patient's lab results grouped by panel, most recent first within each panel
+async fn query_patient_lab_panels(patient_ien: i64) -> Result<LabPanelsResponse, String> {
+    let mut results = query_patient_labs(patient_ien).await?;
+    results.sort_by(|a, b| b.collected_at.cmp(&a.collected_at));
+    Ok(lab_panels::group_by_panel(results, lab_panels::panel_definitions()))
+}
 
-        results.push(LabResultResponse {
-            ien,
-            patient_ien,
-            visit_ien,
-            test_name,
-            test_code,
-            value,
-            unit,
-            reference_range,
-            abnormal_flag,
-            collected_at,
-            resulted_at,
-            status,
-        });
+async fn get_patient_lab_panels(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_lab_panels");
+    match query_patient_lab_panels(patient_ien).await {
+        Ok(panels) => (StatusCode::OK, Json(panels)).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
     }
-
-    results
 }
 
 async fn create_lab_result(Json(req): Json<CreateLabResultRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_lab_result");
     let visit_ien = req.visit_ien.unwrap_or(0);
     let test_code = req.test_code.unwrap_or_default();
     let unit = req.unit.unwrap_or_default();
@@ -1597,7 +2811,7 @@ W IEN
         reference_range, abnormal_flag, now, req.patient_ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
             (
@@ -1616,7 +2830,25 @@ W IEN
 
 // === Document Handlers ===
 
+/// Writes one document as a JSON object; expects IEN set and FIRST initialised
+const DOCUMENT_JSON_M: &str = r#". S D0=$G(^TIU(8925,IEN,0)) Q:D0=""
+. I 'FIRST W ","
+. S FIRST=0
+. S PAT=$P(D0,"^",1),VIS=$P(D0,"^",2),TYP=$P(D0,"^",3),TIT=$P(D0,"^",4),AUTH=$P(D0,"^",5)
+. S CDT=$P(D0,"^",6),SDT=$P(D0,"^",7),SBY=$P(D0,"^",8),ST=$P(D0,"^",9)
+. W "{""ien"":"_IEN_",""patientIen"":"_PAT
+. I VIS W ",""visitIen"":"_VIS
+. W ",""documentType"":"""_$$ESC^EHRAPI($S(TYP="PN":"progress_note",TYP="HP":"hp_note",TYP="DS":"discharge_summary",TYP="CN":"consultation",TYP="OP":"operative_note",1:TYP))_""""
+. W ",""title"":"""_$$ESC^EHRAPI(TIT)_""""
+. I AUTH W ",""authorIen"":"_AUTH
+. W ",""createdAt"":"""_$$ESC^EHRAPI(CDT)_""""
+. I SDT'="" W ",""signedAt"":"""_$$ESC^EHRAPI(SDT)_""""
+. I SBY W ",""signedBy"":"_SBY
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="U":"unsigned",ST="S":"signed",ST="A":"amended",ST="R":"retracted",1:ST))_"""}""#;
+
+/// List a patient's documents (metadata only; `content` is always null)
 async fn get_patient_documents(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_documents");
     // ^TIU(8925) - VistA TIU Document File (File #8925)
     let code = format!(
         r#"
@@ -1624,28 +2856,14 @@ N IEN,D0,FIRST
 W "["
 S FIRST=1,IEN=0
 F  S IEN=$O(^TIU(8925,"C",{},IEN)) Q:IEN=""  D
-. S D0=$G(^TIU(8925,IEN,0)) Q:D0=""
-. I 'FIRST W ","
-. S FIRST=0
-. S PAT=$P(D0,"^",1),VIS=$P(D0,"^",2),TYP=$P(D0,"^",3),TIT=$P(D0,"^",4),AUTH=$P(D0,"^",5)
-. S CDT=$P(D0,"^",6),SDT=$P(D0,"^",7),SBY=$P(D0,"^",8),ST=$P(D0,"^",9)
-. W "{{""ien"":"_IEN_",""patientIen"":"_PAT
-. I VIS W ",""visitIen"":"_VIS
-. W ",""documentType"":"""_$S(TYP="PN":"progress_note",TYP="HP":"hp_note",TYP="DS":"discharge_summary",TYP="CN":"consultation",TYP="OP":"operative_note",1:TYP)_""""
-. W ",""title"":"""_TIT_""""
-. I AUTH W ",""authorIen"":"_AUTH
-. W ",""createdAt"":"""_CDT_""""
-. I SDT'="" W ",""signedAt"":"""_SDT_""""
-. I SBY W ",""signedBy"":"_SBY
-. W ",""status"":"""_$S(ST="U":"unsigned",ST="S":"signed",ST="A":"amended",ST="R":"retracted",1:ST)_"""}}"
+{}
 W "]"
 "#,
-        patient_ien
+        patient_ien, DOCUMENT_JSON_M
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let documents = parse_documents(&output);
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(documents) => {
             (StatusCode::OK, Json(DocumentsResponse { documents })).into_response()
         }
         Err(e) => (
@@ -1656,66 +2874,180 @@ W "]"
     }
 }
 
-fn parse_documents(json: &str) -> Vec<DocumentResponse> {
-    let mut documents = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return documents;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut visit_ien = None;
-        let mut document_type = String::new();
-        let mut title = String::new();
-        let mut author_ien = None;
-        let mut created_at = String::new();
-        let mut signed_at = None;
-        let mut signed_by = None;
-        let mut status = String::new();
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "visitIen" => visit_ien = val.parse().ok(),
-                    "documentType" => document_type = val.to_string(),
-                    "title" => title = val.to_string(),
-                    "authorIen" => author_ien = val.parse().ok(),
-                    "createdAt" => created_at = val.to_string(),
-                    "signedAt" => signed_at = Some(val.to_string()),
-                    "signedBy" => signed_by = val.parse().ok(),
-                    "status" => status = val.to_string(),
-                    _ => {}
-                }
+/// Get a single document's metadata; `?include_content=true` also loads its text and addenda
+async fn get_document(
+    Path(ien): Path<i64>,
+    Query(query): Query<DocumentQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_document");
+    let code = format!(
+        r#"
+N IEN,D0,FIRST
+W "["
+S FIRST=1,IEN={}
+I $D(^TIU(8925,IEN,0)) D
+{}
+W "]"
+"#,
+        ien, DOCUMENT_JSON_M
+    );
+
+    let mut document = match run_mumps(&code).await.and_then(|output| parse_mumps_json::<Vec<DocumentResponse>>(&output)) {
+        Ok(documents) => match documents.into_iter().next() {
+            Some(document) => document,
+            None => {
+                return (
+                    StatusCode::NOT_FOUND,
+                    Json(ErrorResponse { error: format!("Document {} not found", ien) }),
+                )
+                    .into_response()
             }
+        },
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e }),
+            )
+                .into_response()
         }
+    };
 
-        documents.push(DocumentResponse {
-            ien,
-            patient_ien,
-            visit_ien,
-            document_type,
-            title,
-            author_ien,
-            created_at,
-            signed_at,
-            signed_by,
-            status,
-            content: None, // Content fetched separately
-        });
+    if query.include_content {
+        match read_document_content(ien).await {
+            Ok(text) => {
+                let text = text.unwrap_or_default();
+                document.content = text.content;
+                document.addenda = text.addenda;
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ErrorResponse { error: e }),
+                )
+                    .into_response()
+            }
+        }
+    }
+
+    (StatusCode::OK, Json(document)).into_response()
+}
+
+/// Get only a document's body text
+async fn get_document_content(Path(ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_document_content");
+    match MumpsDocumentRepository.get_content(ien).await {
+        Ok(Some(content)) => (StatusCode::OK, Json(DocumentContentResponse { ien, content })).into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse { error: format!("Document {} not found", ien) }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e.to_string() }),
+        )
+            .into_response(),
+    }
+}
+
+/// Writes FOUND then each ^TIU(8925,IEN,"TEXT",n,0) line prefixed with "T ",
+/// or NOTFOUND; expects IEN set
+const DOCUMENT_TEXT_M: &str = r#"I '$D(^TIU(8925,IEN,0)) W "NOTFOUND",!
+I $D(^TIU(8925,IEN,0)) W "FOUND",! S L=0 F  S L=$O(^TIU(8925,IEN,"TEXT",L)) Q:L=""  W "T ",$G(^TIU(8925,IEN,"TEXT",L,0)),!"#;
+
+/// Document text read from the TIU word-processing nodes
+struct MumpsDocumentRepository;
+
+#[async_trait]
+impl EhrDocumentContentRepository for MumpsDocumentRepository {
+    async fn get_content(&self, document_id: i64) -> AppResult<Option<String>> {
+        let code = format!(
+            r#"
+N IEN,L S IEN={}
+{}
+"#,
+            document_id, DOCUMENT_TEXT_M
+        );
+
+        let output = run_mumps(&code).await.map_err(AppError::Internal)?;
+        let text = parse_document_text(&output)
+            .map_err(|e| AppError::Internal(format!("{} reading document {}", e, document_id)))?;
+        Ok(text.map(|text| text.content.unwrap_or_default()))
+    }
+}
+
+/// Read document text from the ^TIU(8925,IEN,"TEXT",n,0) word-processing nodes,
+/// followed by each addendum in ^TIU(8925,IEN,"ADD",a). Returns `None` if the
+/// document does not exist.
+async fn read_document_content(ien: i64) -> Result<Option<DocumentText>, String> {
+    // Text lines are prefixed with "T " and addendum headers with "A " so note
+    // text can never be mistaken for a marker
+    let code = format!(
+        r#"
+N IEN,L,A S IEN={}
+{}
+S A=0 F  S A=$O(^TIU(8925,IEN,"ADD",A)) Q:A=""  D
+. W "A ",A,"^",$G(^TIU(8925,IEN,"ADD",A,0)),!
+. S L=0 F  S L=$O(^TIU(8925,IEN,"ADD",A,"TEXT",L)) Q:L=""  W "T ",$G(^TIU(8925,IEN,"ADD",A,"TEXT",L,0)),!
+"#,
+        ien, DOCUMENT_TEXT_M
+    );
+
+    let output = run_mumps(&code).await?;
+    parse_document_text(&output).map_err(|e| format!("{} reading document {}", e, ien))
+}
+
+/// Parse the output of `read_document_content`
+fn parse_document_text(output: &str) -> Result<Option<DocumentText>, String> {
+    let mut lines = output.lines();
+    match lines.next().map(str::trim) {
+        Some("FOUND") => {}
+        Some("NOTFOUND") => return Ok(None),
+        _ => return Err(format!("Unexpected response: {}", output)),
+    }
+
+    let mut text: Vec<&str> = Vec::new();
+    let mut addenda: Vec<(DocumentAddendum, Vec<&str>)> = Vec::new();
+    for line in lines {
+        if let Some(header) = line.strip_prefix("A ") {
+            let mut pieces = header.split('^');
+            let number = pieces.next().and_then(|n| n.trim().parse().ok()).unwrap_or(0);
+            let amended_at = pieces.next().unwrap_or_default().to_string();
+            let author_ien = pieces.next().and_then(|a| a.trim().parse().ok()).filter(|&a| a > 0);
+            addenda.push((DocumentAddendum { number, amended_at, author_ien, content: String::new() }, Vec::new()));
+        } else if let Some(line) = line.strip_prefix('T') {
+            // Output is trimmed, so an empty last line may have lost its separator
+            let line = line.strip_prefix(' ').unwrap_or(line);
+            match addenda.last_mut() {
+                Some((_, addendum_text)) => addendum_text.push(line),
+                None => text.push(line),
+            }
+        }
     }
 
-    documents
+    Ok(Some(DocumentText {
+        content: if text.is_empty() { None } else { Some(text.join("\n")) },
+        addenda: addenda
+            .into_iter()
+            .map(|(addendum, lines)| DocumentAddendum { content: lines.join("\n"), ..addendum })
+            .collect(),
+    }))
+}
+
+/// MUMPS setting `content` as word-processing lines under `root`, e.g.
+/// `^TIU(8925,IEN,"TEXT"`, with the `^^lines^lines^date^` header in node 0
+fn word_processing_nodes(root: &str, content: &str, now: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut nodes = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        nodes.push_str(&format!("S {},{},0)=\"{}\"\n", root, i + 1, mumps_escape(line)));
+    }
+    nodes.push_str(&format!("S {},0)=\"^^{}^{}^{}^\"\n", root, lines.len(), lines.len(), now));
+    nodes
 }
 
 async fn create_document(Json(req): Json<CreateDocumentRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_document");
     let visit_ien = req.visit_ien.unwrap_or(0);
     let doc_type = match req.document_type.as_str() {
         "progress_note" => "PN",
@@ -1727,19 +3059,29 @@ async fn create_document(Json(req): Json<CreateDocumentRequest>) -> impl IntoRes
     };
     let author_ien = req.author_ien.unwrap_or(0);
     let now = chrono::Utc::now().format("%Y%m%d.%H%M%S").to_string();
+    let updated = document_updated_timestamp();
+
+    // Body text goes in word-processing nodes so it can be loaded separately from metadata
+    let text_nodes = req
+        .content
+        .as_deref()
+        .filter(|c| !c.is_empty())
+        .map(|content| word_processing_nodes("^TIU(8925,IEN,\"TEXT\"", content, &now))
+        .unwrap_or_default();
 
     let code = format!(
         r#"
 N IEN S IEN=$P($G(^TIU(8925,0)),"^",3)+1
-S ^TIU(8925,IEN,0)="{}^{}^{}^{}^{}^{}^^^U"
-S ^TIU(8925,"C",{},IEN)=""
-S $P(^TIU(8925,0),"^",3)=IEN,$P(^TIU(8925,0),"^",4)=IEN
+S ^TIU(8925,IEN,0)="{}^{}^{}^{}^{}^{}^^^U^{}"
+S ^TIU(8925,"C",{},IEN)="",^TIU(8925,"AU",{},IEN)=""
+{}S $P(^TIU(8925,0),"^",3)=IEN,$P(^TIU(8925,0),"^",4)=IEN
 W IEN
 "#,
-        req.patient_ien, visit_ien, doc_type, req.title, author_ien, now, req.patient_ien
+        req.patient_ien, visit_ien, doc_type, req.title, author_ien, now, updated,
+        req.patient_ien, updated, text_nodes
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
             (
@@ -1756,9 +3098,152 @@ W IEN
     }
 }
 
+/// Change time for the ^TIU(8925,"AU",updated,IEN) cross-reference, also kept
+/// in piece 10 of the 0-node; YYYYMMDDHHMMSS so subscripts sort numerically
+fn document_updated_timestamp() -> String {
+    chrono::Utc::now().format("%Y%m%d%H%M%S").to_string()
+}
+
+/// Map a ^TIU(8925) status code (piece 9) onto the document state machine
+fn document_status_from_code(code: &str) -> Option<DocumentStatus> {
+    match code {
+        "U" => Some(DocumentStatus::Unsigned),
+        "S" => Some(DocumentStatus::Signed),
+        "A" => Some(DocumentStatus::Amended),
+        "R" => Some(DocumentStatus::Retracted),
+        _ => None,
+    }
+}
+
+fn document_status_code(status: DocumentStatus) -> &'static str {
+    match status {
+        DocumentStatus::Unsigned => "U",
+        DocumentStatus::Signed => "S",
+        DocumentStatus::Amended => "A",
+        DocumentStatus::Retracted => "R",
+    }
+}
+
+/// Current status of a document, `None` if it does not exist
+async fn read_document_status(ien: i64) -> Result<Option<DocumentStatus>, String> {
+    let code = format!(
+        r#"
+I '$D(^TIU(8925,{ien},0)) W "NOTFOUND"
+I $D(^TIU(8925,{ien},0)) W "STATUS:",$P(^TIU(8925,{ien},0),"^",9)
+"#,
+        ien = ien
+    );
+
+    let output = run_mumps(&code).await?;
+    match output.trim() {
+        "NOTFOUND" => Ok(None),
+        other => other
+            .strip_prefix("STATUS:")
+            .and_then(document_status_from_code)
+            .map(Some)
+            .ok_or_else(|| format!("Unexpected status for document {}: {}", ien, other)),
+    }
+}
+
+/// Amend a document: the text is stored as a new addendum, leaving the
+/// original note untouched, and the status moves to amended
+async fn amend_document_content(
+    Path(ien): Path<i64>,
+    Json(req): Json<AmendDocumentRequest>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("amend_document_content");
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse { error: format!("Document {} not found", ien) }),
+        )
+            .into_response()
+    };
+    if req.content.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse { error: "Addendum content is required".to_string() }),
+        )
+            .into_response();
+    }
+
+    let status = match read_document_status(ien).await {
+        Ok(Some(status)) => status,
+        Ok(None) => return not_found(),
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e }),
+            )
+                .into_response()
+        }
+    };
+    let mut ctx = DocumentContext::new(ien.to_string());
+    let amended = match DocumentMachine::transition(&status, DocumentStateMachineEvent::Amend, &mut ctx) {
+        Ok(amended) => amended,
+        Err(e) => {
+            return (StatusCode::CONFLICT, Json(ErrorResponse { error: e.to_string() })).into_response()
+        }
+    };
+
+    let now = chrono::Utc::now().format("%Y%m%d.%H%M%S").to_string();
+    let text_nodes: String = word_processing_nodes("^TIU(8925,IEN,\"ADD\",A,\"TEXT\"", &req.content, &now)
+        .lines()
+        .map(|line| format!("I OK {}\n", line))
+        .collect();
+    // The status is re-checked under the lock so concurrent edits are not overwritten
+    let code = format!(
+        r#"
+N IEN,A,AU,OK,LK S IEN={ien},OK=0
+L +^TIU(8925,IEN):5 S LK=$T
+I 'LK W "CONFLICT"
+I LK,'$D(^TIU(8925,IEN,0)) W "NOTFOUND"
+I LK,$D(^TIU(8925,IEN,0)) S OK=($P(^TIU(8925,IEN,0),"^",9)="{expected}") I 'OK W "CONFLICT"
+I OK S A=$O(^TIU(8925,IEN,"ADD",""),-1)+1,^TIU(8925,IEN,"ADD",A,0)="{now}^{author}"
+{text_nodes}I OK S AU=$P(^TIU(8925,IEN,0),"^",10) K:AU ^TIU(8925,"AU",AU,IEN)
+I OK S $P(^TIU(8925,IEN,0),"^",10)={updated},^TIU(8925,"AU",{updated},IEN)=""
+I OK S $P(^TIU(8925,IEN,0),"^",9)="{amended}" W A
+I LK L -^TIU(8925,IEN)
+"#,
+        ien = ien,
+        expected = document_status_code(status),
+        now = now,
+        updated = document_updated_timestamp(),
+        author = req.author_ien.unwrap_or(0),
+        text_nodes = text_nodes,
+        amended = document_status_code(amended),
+    );
+
+    match run_mumps(&code).await {
+        Ok(output) => match output.trim() {
+            "NOTFOUND" => not_found(),
+            "CONFLICT" => (
+                StatusCode::CONFLICT,
+                Json(ErrorResponse { error: format!("Document {} was changed while amending; retry", ien) }),
+            )
+                .into_response(),
+            number => (
+                StatusCode::OK,
+                Json(AmendDocumentResponse {
+                    ien,
+                    addendum: number.parse().unwrap_or(0),
+                    status: amended.to_string(),
+                }),
+            )
+                .into_response(),
+        },
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
 // === Order Handlers ===
 
 async fn get_patient_orders(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_orders");
     // ^OR(100) - VistA Orders File (File #100)
     let code = format!(
         r#"
@@ -1773,20 +3258,19 @@ F  S IEN=$O(^OR(100,"C",{},IEN)) Q:IEN=""  D
 . S BY=$P(D0,"^",5),DT=$P(D0,"^",6),PRI=$P(D0,"^",7),ST=$P(D0,"^",8)
 . W "{{""ien"":"_IEN_",""patientIen"":"_PAT
 . I VIS W ",""visitIen"":"_VIS
-. W ",""orderType"":"""_$S(TYP="L":"lab",TYP="R":"radiology",TYP="M":"medication",TYP="C":"consult",TYP="P":"procedure",TYP="D":"diet",TYP="N":"nursing",TYP="A":"activity",1:TYP)_""""
-. W ",""orderText"":"""_TXT_""""
+. W ",""orderType"":"""_$$ESC^EHRAPI($S(TYP="L":"lab",TYP="R":"radiology",TYP="M":"medication",TYP="C":"consult",TYP="P":"procedure",TYP="D":"diet",TYP="N":"nursing",TYP="A":"activity",1:TYP))_""""
+. W ",""orderText"":"""_$$ESC^EHRAPI(TXT)_""""
 . I BY W ",""orderedBy"":"_BY
-. W ",""orderedAt"":"""_DT_""""
-. W ",""priority"":"""_$S(PRI="S":"stat",PRI="A":"asap",PRI="R":"routine",1:PRI)_""""
-. W ",""status"":"""_$S(ST="P":"pending",ST="A":"active",ST="C":"completed",ST="D":"discontinued",ST="X":"cancelled",1:ST)_"""}}"
+. W ",""orderedAt"":"""_$$ESC^EHRAPI(DT)_""""
+. W ",""priority"":"""_$$ESC^EHRAPI($S(PRI="S":"stat",PRI="A":"asap",PRI="R":"routine",1:PRI))_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="P":"pending",ST="A":"active",ST="C":"completed",ST="D":"discontinued",ST="X":"cancelled",1:ST))_"""}}"
 W "]"
 "#,
         patient_ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let orders = parse_orders(&output);
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(orders) => {
             (StatusCode::OK, Json(OrdersResponse { orders })).into_response()
         }
         Err(e) => (
@@ -1797,62 +3281,8 @@ W "]"
     }
 }
 
-fn parse_orders(json: &str) -> Vec<OrderResponse> {
-    let mut orders = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return orders;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut visit_ien = None;
-        let mut order_type = String::new();
-        let mut order_text = String::new();
-        let mut ordered_by = None;
-        let mut ordered_at = String::new();
-        let mut priority = String::new();
-        let mut status = String::new();
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "visitIen" => visit_ien = val.parse().ok(),
-                    "orderType" => order_type = val.to_string(),
-                    "orderText" => order_text = val.to_string(),
-                    "orderedBy" => ordered_by = val.parse().ok(),
-                    "orderedAt" => ordered_at = val.to_string(),
-                    "priority" => priority = val.to_string(),
-                    "status" => status = val.to_string(),
-                    _ => {}
-                }
-            }
-        }
-
-        orders.push(OrderResponse {
-            ien,
-            patient_ien,
-            visit_ien,
-            order_type,
-            order_text,
-            ordered_by,
-            ordered_at,
-            priority,
-            status,
-        });
-    }
-
-    orders
-}
-
 async fn create_order(Json(req): Json<CreateOrderRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_order");
     let visit_ien = req.visit_ien.unwrap_or(0);
     let order_type = match req.order_type.as_str() {
         "lab" => "L",
@@ -1884,7 +3314,7 @@ W IEN
         req.patient_ien, visit_ien, order_type, req.order_text, ordered_by, now, priority, req.patient_ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
             (
@@ -1901,39 +3331,382 @@ W IEN
     }
 }
 
-// === Appointment Handlers ===
+// === Appointment Handlers ===
+
+async fn get_patient_appointments(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_appointments");
+    // ^SD(44) - VistA Hospital Location File / Scheduling (File #44)
+    let code = format!(
+        r#"
+N IEN,D0,FIRST
+W "["
+S FIRST=1,IEN=0
+F  S IEN=$O(^SD(44,"C",{},IEN)) Q:IEN=""  D
+. S D0=$G(^SD(44,IEN,0)) Q:D0=""
+. I 'FIRST W ","
+. S FIRST=0
+. S PAT=$P(D0,"^",1),DT=$P(D0,"^",2),TM=$P(D0,"^",3),TYP=$P(D0,"^",4)
+. S PRV=$P(D0,"^",5),LOC=$P(D0,"^",6),DUR=$P(D0,"^",7),ST=$P(D0,"^",8),RSN=$P(D0,"^",9)
+. W "{{""ien"":"_IEN_",""patientIen"":"_PAT
+. W ",""appointmentDate"":"""_$$ESC^EHRAPI(DT)_""",""appointmentTime"":"""_$$ESC^EHRAPI(TM)_""""
+. W ",""appointmentType"":"""_$$ESC^EHRAPI($S(TYP="N":"new_patient",TYP="F":"follow_up",TYP="A":"annual_exam",TYP="U":"urgent",TYP="T":"telehealth",TYP="P":"procedure",TYP="L":"lab",1:TYP))_""""
+. I PRV W ",""providerIen"":"_PRV
+. I LOC'="" W ",""location"":"""_$$ESC^EHRAPI(LOC)_""""
+. W ",""durationMinutes"":"_+DUR
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="S":"scheduled",ST="I":"checked_in",ST="P":"in_progress",ST="C":"completed",ST="N":"no_show",ST="X":"cancelled",1:ST))_""""
+. I RSN'="" W ",""reason"":"""_$$ESC^EHRAPI(RSN)_""""
+. W "}}"
+W "]"
+"#,
+        patient_ien
+    );
+
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(appointments) => {
+            (StatusCode::OK, Json(AppointmentsResponse { appointments })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
+/// Book an appointment, refusing overlaps with the patient's or the provider's
+/// existing appointments; `?force=true` skips only the patient check
+async fn create_appointment(
+    Query(query): Query<CreateAppointmentQuery>,
+    Json(req): Json<CreateAppointmentRequest>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_appointment");
+    let appt_type = match req.appointment_type.as_str() {
+        "new_patient" => "N",
+        "follow_up" => "F",
+        "annual_exam" => "A",
+        "urgent" => "U",
+        "telehealth" => "T",
+        "procedure" => "P",
+        "lab" => "L",
+        _ => "F",
+    };
+    let provider_ien = req.provider_ien.unwrap_or(0);
+    let location = req.location.unwrap_or_default();
+    let duration = req.duration_minutes.unwrap_or(30);
+    let reason = req.reason.unwrap_or_default();
+
+    let Some(start) = parse_appointment_start(&req.appointment_date, &req.appointment_time) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: "appointmentDate must be YYYY-MM-DD and appointmentTime HH:MM".to_string(),
+            }),
+        )
+            .into_response();
+    };
+    if let Err(error) = validate_piece("location", &location).and(validate_piece("reason", &reason)) {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
+    }
+
+    // The overlap checks and the write run as one routine under the patient's
+    // ^SD(44,"C") lock and a provider lock, so concurrent bookings can't both pass
+    let code = format!(
+        r#"
+N IEN,D0,ST,S,E,X,XS,R S R="",S={start_minutes},E=S+{dur}
+L +^SD(44,"C",{pat}):5 S:'$T R="BUSY"
+I R="",{prv} L +^SD(44,"PRV",{prv}):5 S:'$T R="BUSY"
+I R="",{prv} S X=0 F  S X=$O(^SD(44,X)) Q:'X  D  Q:R'=""
+. S D0=$G(^SD(44,X,0)) Q:D0=""
+. Q:$P(D0,"^",5)'={prv}
+. S ST=$P(D0,"^",8) Q:ST="X"!(ST="N")
+. Q:$TR($P(D0,"^",2),"-")'={date}
+. S XS=$P($P(D0,"^",3),":")*60+$P($P(D0,"^",3),":",2)
+. I XS<E,S<(XS+$P(D0,"^",7)) S R="CONFLICT^P^"_X_"^"_$P(D0,"^",2)_"^"_$P(D0,"^",3)
+I R="",'{force} S X=0 F  S X=$O(^SD(44,"C",{pat},X)) Q:X=""  D  Q:R'=""
+. S D0=$G(^SD(44,X,0)) Q:D0=""
+. S ST=$P(D0,"^",8) Q:ST="X"!(ST="N")
+. Q:$TR($P(D0,"^",2),"-")'={date}
+. S XS=$P($P(D0,"^",3),":")*60+$P($P(D0,"^",3),":",2)
+. I XS<E,S<(XS+$P(D0,"^",7)) S R="CONFLICT^C^"_X_"^"_$P(D0,"^",2)_"^"_$P(D0,"^",3)
+I R="" L +^SD(44,0):5 S:'$T R="BUSY"
+I R="" S IEN=$P($G(^SD(44,0)),"^",3)+1
+I R="" S ^SD(44,IEN,0)="{pat}^{appt_date}^{appt_time}^{appt_type}^{prv}^{location}^{dur}^S^{reason}"
+I R="" S ^SD(44,"C",{pat},IEN)="",$P(^SD(44,0),"^",3)=IEN,$P(^SD(44,0),"^",4)=IEN,R="OK^"_IEN
+L
+W R
+"#,
+        start_minutes = start.hour() * 60 + start.minute(),
+        dur = duration,
+        pat = req.patient_ien,
+        prv = provider_ien,
+        force = u8::from(query.force),
+        date = start.format("%Y%m%d"),
+        appt_date = mumps_escape(&req.appointment_date),
+        appt_time = mumps_escape(&req.appointment_time),
+        appt_type = appt_type,
+        location = mumps_escape(&location),
+        reason = mumps_escape(&reason),
+    );
+
+    let outcome = match run_mumps(&code).await.and_then(|output| parse_booking_outcome(&output)) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e }),
+            )
+                .into_response()
+        }
+    };
+
+    booking_response(outcome, req.patient_ien, provider_ien, start, duration).await
+}
+
+/// Response for the outcome of the booking routine; provider conflicts suggest the next free slot
+async fn booking_response(
+    outcome: BookingOutcome,
+    patient_ien: i64,
+    provider_ien: i64,
+    start: chrono::NaiveDateTime,
+    duration: i32,
+) -> axum::response::Response {
+    match outcome {
+        BookingOutcome::Booked(ien) => (
+            StatusCode::CREATED,
+            Json(CreateResponse { success: true, ien }),
+        )
+            .into_response(),
+        BookingOutcome::Busy => (
+            StatusCode::CONFLICT,
+            Json(ErrorResponse { error: "Appointment book is busy; retry".to_string() }),
+        )
+            .into_response(),
+        BookingOutcome::Conflict { reason, ien, start: conflicting_start } => {
+            let error = match reason {
+                ConflictReason::ProviderConflict => {
+                    format!("Provider {} is already booked at {}", provider_ien, start.format("%Y-%m-%d %H:%M"))
+                }
+                ConflictReason::PatientConflict => format!(
+                    "Patient {} already has an appointment at {}",
+                    patient_ien,
+                    conflicting_start.format("%Y-%m-%d %H:%M")
+                ),
+            };
+            let next_available = match reason {
+                ConflictReason::ProviderConflict => provider_next_available(provider_ien, start, duration).await,
+                ConflictReason::PatientConflict => None,
+            };
+            (
+                StatusCode::CONFLICT,
+                Json(ConflictResponse {
+                    error,
+                    reason,
+                    conflicting_ien: ien,
+                    conflicting_time: format_appointment_start(conflicting_start),
+                    next_available,
+                }),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Result of the locked booking routine in `create_appointment`
+#[derive(Debug, PartialEq)]
+enum BookingOutcome {
+    Booked(i64),
+    Conflict {
+        reason: ConflictReason,
+        ien: i64,
+        start: chrono::NaiveDateTime,
+    },
+    /// A lock could not be taken in time
+    Busy,
+}
+
+/// Parse `OK^IEN`, `CONFLICT^P|C^IEN^DATE^TIME` or `BUSY`
+fn parse_booking_outcome(output: &str) -> Result<BookingOutcome, String> {
+    let fields: Vec<&str> = output.trim().split('^').collect();
+    let unexpected = || format!("Unexpected booking result: {}", output.trim());
+    match fields.as_slice() {
+        ["OK", ien] => ien.parse().map(BookingOutcome::Booked).map_err(|_| unexpected()),
+        ["CONFLICT", kind, ien, date, time] => Ok(BookingOutcome::Conflict {
+            reason: match *kind {
+                "P" => ConflictReason::ProviderConflict,
+                "C" => ConflictReason::PatientConflict,
+                _ => return Err(unexpected()),
+            },
+            ien: ien.parse().map_err(|_| unexpected())?,
+            start: parse_appointment_start(date, time).ok_or_else(unexpected)?,
+        }),
+        ["BUSY"] => Ok(BookingOutcome::Busy),
+        _ => Err(unexpected()),
+    }
+}
+
+// === Provider Schedule Handlers ===
+
+/// Days searched for the next free slot when a booking conflicts
+const APPOINTMENT_SEARCH_DAYS: i64 = 14;
+
+/// Parse an appointment's `YYYY-MM-DD` date and `HH:MM` (or `HH:MM:SS`) time
+fn parse_appointment_start(date: &str, time: &str) -> Option<chrono::NaiveDateTime> {
+    let date = chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()?;
+    let time = chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .or_else(|_| chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M:%S"))
+        .ok()?;
+    Some(date.and_time(time))
+}
+
+fn parse_schedule_time(time: &str) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
 
-async fn get_patient_appointments(Path(patient_ien): Path<i64>) -> impl IntoResponse {
-    // ^SD(44) - VistA Hospital Location File / Scheduling (File #44)
+/// Build one schedule per day from `from`, using the default hours for days without ^SDAM hours
+fn provider_schedules(
+    provider_ien: i64,
+    from: chrono::NaiveDate,
+    days: i64,
+    rows: ScheduleRows,
+) -> Vec<ProviderSchedule> {
+    (0..days.max(1))
+        .map(|offset| {
+            let date = from + chrono::Duration::days(offset);
+            let key = date.format("%Y%m%d").to_string();
+            let mut schedule = ProviderSchedule::new(provider_ien, date);
+
+            if let Some(hours) = rows.hours.iter().find(|h| h.date == key) {
+                if let (Some(start), Some(end)) = (parse_schedule_time(&hours.start), parse_schedule_time(&hours.end)) {
+                    schedule.start_time = start;
+                    schedule.end_time = end;
+                }
+                if hours.slot_minutes > 0 {
+                    schedule.slot_minutes = hours.slot_minutes;
+                }
+                schedule.blocked = hours
+                    .blocked
+                    .iter()
+                    .filter_map(|b| {
+                        Some(ScheduleBlock { start: parse_schedule_time(&b.time)?, duration_minutes: b.duration_minutes })
+                    })
+                    .collect();
+            }
+
+            schedule.appointments = rows
+                .appointments
+                .iter()
+                .filter_map(|a| {
+                    let start = parse_appointment_start(&a.date, &a.time)?;
+                    (start.date() == date).then(|| BookedAppointment {
+                        appointment_ien: a.ien,
+                        start: start.time(),
+                        duration_minutes: a.duration_minutes,
+                    })
+                })
+                .collect();
+            schedule
+        })
+        .collect()
+}
+
+/// Load a provider's schedules for `days` days from `from`: hours and blocked time
+/// from ^SDAM, plus appointments in ^SD(44) that are not cancelled or no-shows
+async fn load_provider_schedules(
+    provider_ien: i64,
+    from: chrono::NaiveDate,
+    days: i64,
+) -> Result<Vec<ProviderSchedule>, String> {
+    let to = from + chrono::Duration::days(days.max(1) - 1);
+    // ^SDAM(PROVIDER,YYYYMMDD) = start^end^slot minutes
+    // ^SDAM(PROVIDER,YYYYMMDD,"B",HH:MM) = blocked minutes
     let code = format!(
         r#"
-N IEN,D0,FIRST
-W "["
+N D,D0,T,BF,IEN,DT,ST,FIRST
+W "{{""hours"":["
+S FIRST=1,D={from}-1
+F  S D=$O(^SDAM({prv},D)) Q:D=""!(D>{to})  D
+. S D0=$G(^SDAM({prv},D))
+. I 'FIRST W ","
+. S FIRST=0
+. W "{{""date"":"""_$$ESC^EHRAPI(D)_""",""start"":"""_$$ESC^EHRAPI($P(D0,"^",1))_""",""end"":"""_$$ESC^EHRAPI($P(D0,"^",2))_""",""slotMinutes"":"_+$P(D0,"^",3)_",""blocked"":["
+. S T="",BF=1 F  S T=$O(^SDAM({prv},D,"B",T)) Q:T=""  W:'BF "," S BF=0 W "{{""time"":"""_$$ESC^EHRAPI(T)_""",""durationMinutes"":"_+^SDAM({prv},D,"B",T)_"}}"
+. W "]}}"
+W "],""appointments"":["
 S FIRST=1,IEN=0
-F  S IEN=$O(^SD(44,"C",{},IEN)) Q:IEN=""  D
+F  S IEN=$O(^SD(44,IEN)) Q:'IEN  D
 . S D0=$G(^SD(44,IEN,0)) Q:D0=""
+. Q:$P(D0,"^",5)'={prv}
+. S ST=$P(D0,"^",8) Q:ST="X"!(ST="N")
+. S DT=$TR($P(D0,"^",2),"-") Q:DT<{from}!(DT>{to})
 . I 'FIRST W ","
 . S FIRST=0
-. S PAT=$P(D0,"^",1),DT=$P(D0,"^",2),TM=$P(D0,"^",3),TYP=$P(D0,"^",4)
-. S PRV=$P(D0,"^",5),LOC=$P(D0,"^",6),DUR=$P(D0,"^",7),ST=$P(D0,"^",8),RSN=$P(D0,"^",9)
-. W "{{""ien"":"_IEN_",""patientIen"":"_PAT
-. W ",""appointmentDate"":"""_DT_""",""appointmentTime"":"""_TM_""""
-. W ",""appointmentType"":"""_$S(TYP="N":"new_patient",TYP="F":"follow_up",TYP="A":"annual_exam",TYP="U":"urgent",TYP="T":"telehealth",TYP="P":"procedure",TYP="L":"lab",1:TYP)_""""
-. I PRV W ",""providerIen"":"_PRV
-. I LOC'="" W ",""location"":"""_LOC_""""
-. W ",""durationMinutes"":"_+DUR
-. W ",""status"":"""_$S(ST="S":"scheduled",ST="I":"checked_in",ST="P":"in_progress",ST="C":"completed",ST="N":"no_show",ST="X":"cancelled",1:ST)_""""
-. I RSN'="" W ",""reason"":"""_RSN_""""
-. W "}}"
-W "]"
+. W "{{""ien"":"_IEN_",""date"":"""_$$ESC^EHRAPI($P(D0,"^",2))_""",""time"":"""_$$ESC^EHRAPI($P(D0,"^",3))_""",""durationMinutes"":"_+$P(D0,"^",7)_"}}"
+W "]}}"
 "#,
-        patient_ien
+        prv = provider_ien,
+        from = from.format("%Y%m%d"),
+        to = to.format("%Y%m%d"),
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let appointments = parse_appointments(&output);
-            (StatusCode::OK, Json(AppointmentsResponse { appointments })).into_response()
+    let rows: ScheduleRows = run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))?;
+    Ok(provider_schedules(provider_ien, from, days, rows))
+}
+
+fn format_appointment_start(start: chrono::NaiveDateTime) -> String {
+    start.format("%Y-%m-%dT%H:%M").to_string()
+}
+
+/// Next free start with the provider for the same duration, `YYYY-MM-DDTHH:MM`
+///
+/// `None` when the schedule can't be read or has no free slot in the search window.
+async fn provider_next_available(
+    provider_ien: i64,
+    start: chrono::NaiveDateTime,
+    duration_minutes: i32,
+) -> Option<String> {
+    let schedules = load_provider_schedules(provider_ien, start.date(), APPOINTMENT_SEARCH_DAYS)
+        .await
+        .map_err(|e| tracing::warn!("Failed to load schedule for provider {}: {}", provider_ien, e))
+        .ok()?;
+    ProviderAvailabilityService::new(schedules)
+        .find_next_available(provider_ien, start.date(), duration_minutes)
+        .map(format_appointment_start)
+}
+
+/// A provider's slots for one day with their booking status
+async fn get_provider_schedule(
+    Path(provider_ien): Path<i64>,
+    Query(query): Query<ProviderScheduleQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_provider_schedule");
+    let date = match query.date.as_deref() {
+        Some(date) => match chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse { error: format!("Invalid date '{}', expected YYYY-MM-DD", date) }),
+                )
+                    .into_response()
+            }
+        },
+        None => chrono::Local::now().date_naive(),
+    };
+
+    match load_provider_schedules(provider_ien, date, 1).await {
+        Ok(schedules) => {
+            let schedule = schedules.into_iter().next().unwrap_or_else(|| ProviderSchedule::new(provider_ien, date));
+            (
+                StatusCode::OK,
+                Json(ProviderScheduleResponse {
+                    provider_ien,
+                    date: date.format("%Y-%m-%d").to_string(),
+                    start_time: schedule.start_time.format("%H:%M").to_string(),
+                    end_time: schedule.end_time.format("%H:%M").to_string(),
+                    slots: schedule.slots(),
+                }),
+            )
+                .into_response()
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -1943,101 +3716,63 @@ W "]"
     }
 }
 
-fn parse_appointments(json: &str) -> Vec<AppointmentResponse> {
-    let mut appointments = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return appointments;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut appointment_date = String::new();
-        let mut appointment_time = String::new();
-        let mut appointment_type = String::new();
-        let mut provider_ien = None;
-        let mut location = None;
-        let mut duration_minutes = 30;
-        let mut status = String::new();
-        let mut reason = None;
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "appointmentDate" => appointment_date = val.to_string(),
-                    "appointmentTime" => appointment_time = val.to_string(),
-                    "appointmentType" => appointment_type = val.to_string(),
-                    "providerIen" => provider_ien = val.parse().ok(),
-                    "location" => location = Some(val.to_string()),
-                    "durationMinutes" => duration_minutes = val.parse().unwrap_or(30),
-                    "status" => status = val.to_string(),
-                    "reason" => reason = Some(val.to_string()),
-                    _ => {}
-                }
-            }
-        }
+/// Store a provider's working hours and blocked time for one day, replacing any existing entry
+async fn set_provider_schedule(
+    Path(provider_ien): Path<i64>,
+    Json(req): Json<SetProviderScheduleRequest>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("set_provider_schedule");
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
 
-        appointments.push(AppointmentResponse {
-            ien,
-            patient_ien,
-            appointment_date,
-            appointment_time,
-            appointment_type,
-            provider_ien,
-            location,
-            duration_minutes,
-            status,
-            reason,
-        });
+    let Ok(date) = chrono::NaiveDate::parse_from_str(req.date.trim(), "%Y-%m-%d") else {
+        return bad_request(format!("Invalid date '{}', expected YYYY-MM-DD", req.date));
+    };
+    let (Some(start), Some(end)) = (parse_schedule_time(&req.start_time), parse_schedule_time(&req.end_time)) else {
+        return bad_request("startTime and endTime must be HH:MM".to_string());
+    };
+    if end <= start {
+        return bad_request("endTime must be after startTime".to_string());
+    }
+    let slot_minutes = req.slot_minutes.unwrap_or(shared::domain::entities::ehr::DEFAULT_SLOT_MINUTES);
+    if slot_minutes <= 0 {
+        return bad_request("slotMinutes must be positive".to_string());
     }
 
-    appointments
-}
-
-async fn create_appointment(Json(req): Json<CreateAppointmentRequest>) -> impl IntoResponse {
-    let appt_type = match req.appointment_type.as_str() {
-        "new_patient" => "N",
-        "follow_up" => "F",
-        "annual_exam" => "A",
-        "urgent" => "U",
-        "telehealth" => "T",
-        "procedure" => "P",
-        "lab" => "L",
-        _ => "F",
-    };
-    let provider_ien = req.provider_ien.unwrap_or(0);
-    let location = req.location.unwrap_or_default();
-    let duration = req.duration_minutes.unwrap_or(30);
-    let reason = req.reason.unwrap_or_default();
+    let mut blocks = String::new();
+    for block in &req.blocked {
+        let Some(time) = parse_schedule_time(&block.time) else {
+            return bad_request(format!("Invalid blocked time '{}', expected HH:MM", block.time));
+        };
+        if block.duration_minutes <= 0 {
+            return bad_request("Blocked durationMinutes must be positive".to_string());
+        }
+        blocks.push_str(&format!(
+            "S ^SDAM({},{},\"B\",\"{}\")={}\n",
+            provider_ien,
+            date.format("%Y%m%d"),
+            time.format("%H:%M"),
+            block.duration_minutes
+        ));
+    }
 
     let code = format!(
         r#"
-N IEN S IEN=$P($G(^SD(44,0)),"^",3)+1
-S ^SD(44,IEN,0)="{}^{}^{}^{}^{}^{}^{}^S^{}"
-S ^SD(44,"C",{},IEN)=""
-S $P(^SD(44,0),"^",3)=IEN,$P(^SD(44,0),"^",4)=IEN
-W IEN
+K ^SDAM({prv},{date})
+S ^SDAM({prv},{date})="{start}^{end}^{slot}"
+{blocks}W "OK"
 "#,
-        req.patient_ien, req.appointment_date, req.appointment_time, appt_type,
-        provider_ien, location, duration, reason, req.patient_ien
+        prv = provider_ien,
+        date = date.format("%Y%m%d"),
+        start = start.format("%H:%M"),
+        end = end.format("%H:%M"),
+        slot = slot_minutes,
+        blocks = blocks,
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let ien: i64 = output.trim().parse().unwrap_or(0);
-            (
-                StatusCode::CREATED,
-                Json(CreateResponse { success: true, ien }),
-            )
-                .into_response()
-        }
+    match run_mumps(&code).await {
+        Ok(_) => get_provider_schedule(Path(provider_ien), Query(ProviderScheduleQuery { date: Some(req.date) }))
+            .await
+            .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse { error: e }),
@@ -2048,17 +3783,27 @@ W IEN
 
 // === Prescription/Dispensing Handlers ===
 
-async fn get_patient_prescriptions(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+async fn get_patient_prescriptions(
+    Path(patient_ien): Path<i64>,
+    Query(page): Query<PageQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_prescriptions");
+    let page = match Page::from_query(&page, format!("prescriptions:{}", patient_ien)) {
+        Ok(page) => page,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response(),
+    };
     // ^PSO(52) - VistA Outpatient Pharmacy File (File #52)
     // Extended to include dispensing workflow
     let code = format!(
         r#"
-N IEN,D0,FIRST
+N IEN,D0,FIRST,CNT,SKIP
 W "["
-S FIRST=1,IEN=0
-F  S IEN=$O(^PSO(52,"C",{},IEN)) Q:IEN=""  D
+S FIRST=1,IEN={after},CNT=0,SKIP={skip}
+F  S IEN=$O(^PSO(52,"C",{patient_ien},IEN)) Q:IEN=""  Q:CNT'<{fetch}  D
 . S D0=$G(^PSO(52,IEN,0)) Q:D0=""
 . S D1=$G(^PSO(52,IEN,1))
+. I SKIP S SKIP=SKIP-1 Q
+. S CNT=CNT+1
 . I 'FIRST W ","
 . S FIRST=0
 . S PAT=$P(D0,"^",1),RX=$P(D0,"^",2),DRG=$P(D0,"^",3),CODE=$P(D0,"^",4)
@@ -2067,30 +3812,39 @@ F  S IEN=$O(^PSO(52,"C",{},IEN)) Q:IEN=""  D
 . S PRV=$P(D0,"^",13),LOC=$P(D0,"^",14)
 . S ODT=$P(D1,"^",1),FDT=$P(D1,"^",2),EXP=$P(D1,"^",3),ST=$P(D1,"^",4)
 . S DST=$P(D1,"^",5),VBY=$P(D1,"^",6),DBY=$P(D1,"^",7)
-. W "{{""ien"":"_IEN_",""patientIen"":"_PAT_",""rxNumber"":"""_RX_""""
-. W ",""drugName"":"""_DRG_""""
-. I CODE'="" W ",""drugCode"":"""_CODE_""""
-. W ",""dose"":"""_DOS_""",""route"":"""_RTE_""",""frequency"":"""_FRQ_""",""sig"":"""_SG_""""
+. W "{{""ien"":"_IEN_",""patientIen"":"_PAT_",""rxNumber"":"""_$$ESC^EHRAPI(RX)_""""
+. W ",""drugName"":"""_$$ESC^EHRAPI(DRG)_""""
+. I CODE'="" W ",""drugCode"":"""_$$ESC^EHRAPI(CODE)_""""
+. W ",""dose"":"""_$$ESC^EHRAPI(DOS)_""",""route"":"""_$$ESC^EHRAPI(RTE)_""",""frequency"":"""_$$ESC^EHRAPI(FRQ)_""",""sig"":"""_$$ESC^EHRAPI(SG)_""""
 . W ",""quantity"":"_+QTY_",""daysSupply"":"_+DAYS_",""refillsAllowed"":"_+RFLA_",""refillsRemaining"":"_+RFLR
 . I PRV W ",""prescriberIen"":"_PRV
-. I LOC'="" W ",""pharmacyLocation"":"""_LOC_""""
-. W ",""orderDate"":"""_ODT_""""
-. I FDT'="" W ",""fillDate"":"""_FDT_""""
-. I EXP'="" W ",""expirationDate"":"""_EXP_""""
-. W ",""status"":"""_$S(ST="A":"active",ST="D":"discontinued",ST="E":"expired",ST="H":"on_hold",1:ST)_""""
-. W ",""dispensingStatus"":"""_$S(DST="P":"pending",DST="V":"verified",DST="D":"dispensed",DST="C":"completed",DST="R":"ready_for_pickup",1:DST)_""""
+. I LOC'="" W ",""pharmacyLocation"":"""_$$ESC^EHRAPI(LOC)_""""
+. W ",""orderDate"":"""_$$ESC^EHRAPI(ODT)_""""
+. I FDT'="" W ",""fillDate"":"""_$$ESC^EHRAPI(FDT)_""""
+. I EXP'="" W ",""expirationDate"":"""_$$ESC^EHRAPI(EXP)_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="A":"active",ST="D":"discontinued",ST="E":"expired",ST="H":"on_hold",1:ST))_""""
+. W ",""dispensingStatus"":"""_$$ESC^EHRAPI($S(DST="P":"pending",DST="V":"verified",DST="D":"dispensed",DST="C":"completed",DST="R":"ready_for_pickup",1:DST))_""""
 . I VBY W ",""verifiedBy"":"_VBY
 . I DBY W ",""dispensedBy"":"_DBY
 . W "}}"
 W "]"
 "#,
-        patient_ien
+        patient_ien = patient_ien,
+        after = page.after,
+        skip = page.skip,
+        fetch = page.fetch(),
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let prescriptions = parse_prescriptions(&output);
-            (StatusCode::OK, Json(PrescriptionsResponse { prescriptions })).into_response()
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(prescriptions) => {
+            let result = page.finish(prescriptions, |rx: &PrescriptionResponse| rx.ien);
+            page.respond(
+                (StatusCode::OK, Json(PrescriptionsResponse {
+                    prescriptions: result.items,
+                    next_cursor: result.next_cursor,
+                    has_more: result.has_more,
+                })).into_response(),
+            )
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -2100,111 +3854,25 @@ W "]"
     }
 }
 
-fn parse_prescriptions(json: &str) -> Vec<PrescriptionResponse> {
-    let mut prescriptions = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return prescriptions;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut patient_ien = 0i64;
-        let mut rx_number = String::new();
-        let mut drug_name = String::new();
-        let mut drug_code = None;
-        let mut dose = String::new();
-        let mut route = String::new();
-        let mut frequency = String::new();
-        let mut sig = String::new();
-        let mut quantity = 0i32;
-        let mut days_supply = 0i32;
-        let mut refills_allowed = 0i32;
-        let mut refills_remaining = 0i32;
-        let mut prescriber_ien = None;
-        let mut pharmacy_location = None;
-        let mut order_date = String::new();
-        let mut fill_date = None;
-        let mut expiration_date = None;
-        let mut status = String::new();
-        let mut dispensing_status = String::new();
-        let mut verified_by = None;
-        let mut dispensed_by = None;
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "patientIen" => patient_ien = val.parse().unwrap_or(0),
-                    "rxNumber" => rx_number = val.to_string(),
-                    "drugName" => drug_name = val.to_string(),
-                    "drugCode" => drug_code = Some(val.to_string()),
-                    "dose" => dose = val.to_string(),
-                    "route" => route = val.to_string(),
-                    "frequency" => frequency = val.to_string(),
-                    "sig" => sig = val.to_string(),
-                    "quantity" => quantity = val.parse().unwrap_or(0),
-                    "daysSupply" => days_supply = val.parse().unwrap_or(0),
-                    "refillsAllowed" => refills_allowed = val.parse().unwrap_or(0),
-                    "refillsRemaining" => refills_remaining = val.parse().unwrap_or(0),
-                    "prescriberIen" => prescriber_ien = val.parse().ok(),
-                    "pharmacyLocation" => pharmacy_location = Some(val.to_string()),
-                    "orderDate" => order_date = val.to_string(),
-                    "fillDate" => fill_date = Some(val.to_string()),
-                    "expirationDate" => expiration_date = Some(val.to_string()),
-                    "status" => status = val.to_string(),
-                    "dispensingStatus" => dispensing_status = val.to_string(),
-                    "verifiedBy" => verified_by = val.parse().ok(),
-                    "dispensedBy" => dispensed_by = val.parse().ok(),
-                    _ => {}
-                }
-            }
-        }
-
-        prescriptions.push(PrescriptionResponse {
-            ien,
-            patient_ien,
-            rx_number,
-            drug_name,
-            drug_code,
-            dose,
-            route,
-            frequency,
-            sig,
-            quantity,
-            days_supply,
-            refills_allowed,
-            refills_remaining,
-            prescriber_ien,
-            pharmacy_location,
-            order_date,
-            fill_date,
-            expiration_date,
-            status,
-            dispensing_status,
-            verified_by,
-            dispensed_by,
-        });
-    }
-
-    prescriptions
-}
-
-async fn get_pending_prescriptions() -> impl IntoResponse {
+async fn get_pending_prescriptions(Query(page): Query<PageQuery>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_pending_prescriptions");
+    let page = match Page::from_query(&page, "prescriptions:pending") {
+        Ok(page) => page,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response(),
+    };
     // Get all prescriptions pending verification or dispensing
-    let code = r#"
-N IEN,D0,D1,FIRST,DST
+    let code = format!(
+        r#"
+N IEN,D0,D1,FIRST,DST,CNT,SKIP
 W "["
-S FIRST=1,IEN=0
-F  S IEN=$O(^PSO(52,IEN)) Q:IEN=""  Q:'IEN  D
+S FIRST=1,IEN={after},CNT=0,SKIP={skip}
+F  S IEN=$O(^PSO(52,IEN)) Q:IEN=""  Q:'IEN  Q:CNT'<{fetch}  D
 . S D0=$G(^PSO(52,IEN,0)) Q:D0=""
 . S D1=$G(^PSO(52,IEN,1))
 . S DST=$P(D1,"^",5)
 . Q:DST'="P"&(DST'="V")
+. I SKIP S SKIP=SKIP-1 Q
+. S CNT=CNT+1
 . I 'FIRST W ","
 . S FIRST=0
 . S PAT=$P(D0,"^",1),RX=$P(D0,"^",2),DRG=$P(D0,"^",3),CODE=$P(D0,"^",4)
@@ -2213,28 +3881,38 @@ F  S IEN=$O(^PSO(52,IEN)) Q:IEN=""  Q:'IEN  D
 . S PRV=$P(D0,"^",13),LOC=$P(D0,"^",14)
 . S ODT=$P(D1,"^",1),FDT=$P(D1,"^",2),EXP=$P(D1,"^",3),ST=$P(D1,"^",4)
 . S VBY=$P(D1,"^",6),DBY=$P(D1,"^",7)
-. W "{""ien"":"_IEN_",""patientIen"":"_PAT_",""rxNumber"":"""_RX_""""
-. W ",""drugName"":"""_DRG_""""
-. I CODE'="" W ",""drugCode"":"""_CODE_""""
-. W ",""dose"":"""_DOS_""",""route"":"""_RTE_""",""frequency"":"""_FRQ_""",""sig"":"""_SG_""""
+. W "{{""ien"":"_IEN_",""patientIen"":"_PAT_",""rxNumber"":"""_$$ESC^EHRAPI(RX)_""""
+. W ",""drugName"":"""_$$ESC^EHRAPI(DRG)_""""
+. I CODE'="" W ",""drugCode"":"""_$$ESC^EHRAPI(CODE)_""""
+. W ",""dose"":"""_$$ESC^EHRAPI(DOS)_""",""route"":"""_$$ESC^EHRAPI(RTE)_""",""frequency"":"""_$$ESC^EHRAPI(FRQ)_""",""sig"":"""_$$ESC^EHRAPI(SG)_""""
 . W ",""quantity"":"_+QTY_",""daysSupply"":"_+DAYS_",""refillsAllowed"":"_+RFLA_",""refillsRemaining"":"_+RFLR
 . I PRV W ",""prescriberIen"":"_PRV
-. I LOC'="" W ",""pharmacyLocation"":"""_LOC_""""
-. W ",""orderDate"":"""_ODT_""""
-. I FDT'="" W ",""fillDate"":"""_FDT_""""
-. I EXP'="" W ",""expirationDate"":"""_EXP_""""
-. W ",""status"":"""_$S(ST="A":"active",ST="D":"discontinued",ST="E":"expired",ST="H":"on_hold",1:ST)_""""
-. W ",""dispensingStatus"":"""_$S(DST="P":"pending",DST="V":"verified",1:DST)_""""
+. I LOC'="" W ",""pharmacyLocation"":"""_$$ESC^EHRAPI(LOC)_""""
+. W ",""orderDate"":"""_$$ESC^EHRAPI(ODT)_""""
+. I FDT'="" W ",""fillDate"":"""_$$ESC^EHRAPI(FDT)_""""
+. I EXP'="" W ",""expirationDate"":"""_$$ESC^EHRAPI(EXP)_""""
+. W ",""status"":"""_$$ESC^EHRAPI($S(ST="A":"active",ST="D":"discontinued",ST="E":"expired",ST="H":"on_hold",1:ST))_""""
+. W ",""dispensingStatus"":"""_$$ESC^EHRAPI($S(DST="P":"pending",DST="V":"verified",1:DST))_""""
 . I VBY W ",""verifiedBy"":"_VBY
 . I DBY W ",""dispensedBy"":"_DBY
-. W "}"
+. W "}}"
 W "]"
-"#;
+"#,
+        after = page.after,
+        skip = page.skip,
+        fetch = page.fetch(),
+    );
 
-    match run_mumps(code) {
-        Ok(output) => {
-            let prescriptions = parse_prescriptions(&output);
-            (StatusCode::OK, Json(PrescriptionsResponse { prescriptions })).into_response()
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(prescriptions) => {
+            let result = page.finish(prescriptions, |rx: &PrescriptionResponse| rx.ien);
+            page.respond(
+                (StatusCode::OK, Json(PrescriptionsResponse {
+                    prescriptions: result.items,
+                    next_cursor: result.next_cursor,
+                    has_more: result.has_more,
+                })).into_response(),
+            )
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -2245,6 +3923,7 @@ W "]"
 }
 
 async fn create_prescription(Json(req): Json<CreatePrescriptionRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_prescription");
     let drug_code = req.drug_code.unwrap_or_default();
     let refills_allowed = req.refills_allowed.unwrap_or(0);
     let prescriber_ien = req.prescriber_ien.unwrap_or(0);
@@ -2271,7 +3950,7 @@ W IEN
         prescriber_ien, pharmacy_location, now, req.patient_ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
             (
@@ -2292,6 +3971,7 @@ async fn verify_prescription(
     Path(ien): Path<i64>,
     Json(req): Json<VerifyPrescriptionRequest>,
 ) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("verify_prescription");
     // Update prescription status from Pending to Verified
     let code = format!(
         r#"
@@ -2306,7 +3986,7 @@ W "OK"
         ien, req.verified_by, ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             match output.trim() {
                 "OK" => (StatusCode::OK, Json(CreateResponse { success: true, ien })).into_response(),
@@ -2336,13 +4016,25 @@ async fn dispense_prescription(
     Path(ien): Path<i64>,
     Json(req): Json<DispensePrescriptionRequest>,
 ) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("dispense_prescription");
     // Update prescription status from Verified to Dispensed
+    // and record the dispensed lot in ^DISP for recall tracking
     let now = chrono::Utc::now().format("%Y%m%d").to_string();
+    let dispensed_at = chrono::Utc::now().format("%Y%m%d.%H%M%S").to_string();
     let exp_date = req.expiration_date.unwrap_or_default();
+    let lot_number = req.lot_number.unwrap_or_default();
+    if let Err(error) = validate_piece("lotNumber", &lot_number).and(validate_piece("expirationDate", &exp_date)) {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error })).into_response();
+    }
+    let exp_date = mumps_escape(&exp_date);
+    let lot_number = mumps_escape(&lot_number);
+    let barcode_url = req.include_barcode.unwrap_or(false)
+        .then(|| format!("/api/v1/pharmacy/prescriptions/{}/barcode", ien));
 
     let code = format!(
         r#"
-N D1 S D1=$G(^PSO(52,{},1))
+N D0,D1,DIEN,INV,LOT
+S D0=$G(^PSO(52,{},0)),D1=$G(^PSO(52,{},1))
 I D1="" W "NOT_FOUND" Q
 S DST=$P(D1,"^",5)
 I DST'="V" W "INVALID_STATUS" Q
@@ -2350,15 +4042,26 @@ S $P(D1,"^",2)="{}"
 I "{}"'="" S $P(D1,"^",3)="{}"
 S $P(D1,"^",5)="R",$P(D1,"^",7)={}
 S ^PSO(52,{},1)=D1
+; Resolve the inventory item holding this lot (^PSD(INV,2,"L",LOT))
+S LOT="{}",INV=0
+I LOT'="" F  S INV=$O(^PSD(INV)) Q:'INV  Q:$D(^PSD(INV,2,"L",LOT))
+S INV=+INV
+; Dispensing history: RXIEN^LOT^QTY^DISPENSED_BY^DISPENSED_AT^INVIEN^PATIEN
+S DIEN=$P($G(^DISP(0)),"^",3)+1
+S ^DISP(DIEN,0)="{}^"_LOT_"^"_$P(D0,"^",9)_"^{}^{}^"_INV_"^"_$P(D0,"^",1)
+S ^DISP("RX",{},DIEN)=""
+I LOT'="" S ^DISP("LOT",LOT,DIEN)=""
+S $P(^DISP(0),"^",3)=DIEN,$P(^DISP(0),"^",4)=DIEN
 W "OK"
 "#,
-        ien, now, exp_date, exp_date, req.dispensed_by, ien
+        ien, ien, now, exp_date, exp_date, req.dispensed_by, ien,
+        lot_number, ien, req.dispensed_by, dispensed_at, ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             match output.trim() {
-                "OK" => (StatusCode::OK, Json(CreateResponse { success: true, ien })).into_response(),
+                "OK" => (StatusCode::OK, Json(DispensePrescriptionResponse { success: true, ien, barcode_url })).into_response(),
                 "NOT_FOUND" => (
                     StatusCode::NOT_FOUND,
                     Json(ErrorResponse { error: "Prescription not found".to_string() }),
@@ -2381,7 +4084,190 @@ W "OK"
     }
 }
 
+/// Build MUMPS code writing the label fields of the prescription whose IEN
+/// `lookup` sets, or NOT_FOUND
+///
+/// The lot comes from the most recent dispensing of the prescription.
+fn prescription_label_code(lookup: &str) -> String {
+    format!(
+        r#"
+N IEN,D0,D1,DIEN,LOT
+{}
+I IEN="" W "NOT_FOUND" Q
+S D0=$G(^PSO(52,IEN,0)),D1=$G(^PSO(52,IEN,1))
+I D0="" W "NOT_FOUND" Q
+S LOT="",DIEN=$O(^DISP("RX",IEN,""),-1)
+I DIEN S LOT=$P($G(^DISP(DIEN,0)),"^",2)
+W "{{""ien"":"_IEN_",""rxNumber"":"""_$$ESC^EHRAPI($P(D0,"^",2))_""",""patientIen"":"_+$P(D0,"^",1)
+W ",""drugCode"":"""_$$ESC^EHRAPI($P(D0,"^",4))_""",""quantity"":"_+$P(D0,"^",9)
+I $P(D1,"^",2)'="" W ",""fillDate"":"""_$$ESC^EHRAPI($P(D1,"^",2))_""""
+I $P(D1,"^",3)'="" W ",""expirationDate"":"""_$$ESC^EHRAPI($P(D1,"^",3))_""""
+I LOT'="" W ",""lotNumber"":"""_$$ESC^EHRAPI(LOT)_""""
+W "}}"
+"#,
+        lookup
+    )
+}
+
+/// Load label fields with `prescription_label_code`; `Ok(None)` if not found
+async fn load_prescription_label(lookup: &str) -> Result<Option<PrescriptionLabelRecord>, String> {
+    let output = run_mumps(&prescription_label_code(lookup)).await?;
+    if output.trim() == "NOT_FOUND" {
+        return Ok(None);
+    }
+    parse_mumps_json(&output).map(Some)
+}
+
+async fn get_prescription_barcode(
+    Path(ien): Path<i64>,
+    Query(query): Query<BarcodeQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_prescription_barcode");
+    let format = match query.format.as_deref().unwrap_or("datamatrix").parse::<BarcodeFormat>() {
+        Ok(format) => format,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })).into_response(),
+    };
+
+    let record = match load_prescription_label(&format!("S IEN={}", ien)).await {
+        Ok(Some(record)) => record,
+        Ok(None) => return (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse { error: "Prescription not found".to_string() }),
+        ).into_response(),
+        Err(e) => return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        ).into_response(),
+    };
+
+    // A prescription without an NDC cannot be labelled
+    let data = match record.into_label().to_gs1() {
+        Ok(data) => data,
+        Err(e) => return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(ErrorResponse { error: e.to_string() }),
+        ).into_response(),
+    };
+
+    match barcode::render_png(&data, format) {
+        Ok(png) => {
+            use base64::{Engine as _, engine::general_purpose::STANDARD};
+            (StatusCode::OK, Json(BarcodeResponse {
+                format: format.as_str().to_string(),
+                data,
+                image_base64: STANDARD.encode(png),
+            })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e.to_string() }),
+        )
+            .into_response(),
+    }
+}
+
+async fn scan_verify_prescription(Json(req): Json<ScanVerifyRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("scan_verify_prescription");
+    let decoded = match PrescriptionLabel::from_gs1(&req.payload) {
+        Ok(decoded) => decoded,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e.to_string() })).into_response(),
+    };
+
+    // ^PSO(52,"RX",RX,IEN) - prescription by RX number
+    let lookup = format!(r#"S IEN=$O(^PSO(52,"RX","{}",""))"#, mumps_escape(&decoded.rx_number));
+    match load_prescription_label(&lookup).await {
+        Ok(Some(record)) => {
+            let prescription_ien = Some(record.ien);
+            let mismatches = decoded.mismatches(&record.into_label());
+            let status = if mismatches.is_empty() { "match" } else { "mismatch" };
+            (StatusCode::OK, Json(ScanVerifyResponse { status, prescription_ien, mismatches, decoded })).into_response()
+        }
+        Ok(None) => (StatusCode::OK, Json(ScanVerifyResponse {
+            status: "not_found",
+            prescription_ien: None,
+            mismatches: Vec::new(),
+            decoded,
+        })).into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
+/// Build MUMPS code listing ^DISP entries referenced by the given index node
+fn dispensing_history_code(index: &str) -> String {
+    format!(
+        r#"
+N DIEN,D0,FIRST
+W "["
+S FIRST=1,DIEN=0
+F  S DIEN=$O({}DIEN)) Q:DIEN=""  D
+. S D0=$G(^DISP(DIEN,0)) Q:D0=""
+. I 'FIRST W ","
+. S FIRST=0
+. S RX=$P(D0,"^",1),LOT=$P(D0,"^",2),QTY=$P(D0,"^",3),DBY=$P(D0,"^",4)
+. S DAT=$P(D0,"^",5),INV=$P(D0,"^",6),PAT=$P(D0,"^",7)
+. W "{{""ien"":"_DIEN_",""prescriptionIen"":"_+RX_",""patientIen"":"_+PAT
+. I LOT'="" W ",""lotNumber"":"""_$$ESC^EHRAPI(LOT)_""""
+. W ",""quantityDispensed"":"_+QTY_",""dispensedBy"":"_+DBY_",""dispensedAt"":"""_$$ESC^EHRAPI(DAT)_""""
+. I INV W ",""inventoryIen"":"_INV
+. W "}}"
+W "]"
+"#,
+        index
+    )
+}
+
+async fn get_dispensing_history(Path(ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_dispensing_history");
+    // ^DISP("RX",RXIEN,DIEN) - dispensing events for a prescription
+    let code = dispensing_history_code(&format!(r#"^DISP("RX",{},"#, ien));
+
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(dispensing_history) => {
+            (StatusCode::OK, Json(DispensingHistoryListResponse { dispensing_history })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
+async fn get_lot_dispensing(Path(lot_number): Path<String>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_lot_dispensing");
+    // ^DISP("LOT",LOT,DIEN) - every dispensing of a lot, used for recall investigation
+    let code = dispensing_history_code(&format!(r#"^DISP("LOT","{}","#, mumps_escape(&lot_number)));
+
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json::<Vec<DispensingHistoryResponse>>(&output)) {
+        Ok(dispensing_history) => {
+            let mut patient_iens: Vec<i64> = dispensing_history
+                .iter()
+                .map(|d| d.patient_ien)
+                .filter(|ien| *ien > 0)
+                .collect();
+            patient_iens.sort_unstable();
+            patient_iens.dedup();
+
+            (StatusCode::OK, Json(LotDispensingResponse {
+                lot_number,
+                dispensing_history,
+                patient_iens,
+            })).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
 async fn complete_prescription(Path(ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("complete_prescription");
     // Mark prescription as picked up/completed
     let code = format!(
         r#"
@@ -2396,7 +4282,7 @@ W "OK"
         ien, ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             match output.trim() {
                 "OK" => (StatusCode::OK, Json(CreateResponse { success: true, ien })).into_response(),
@@ -2426,6 +4312,7 @@ async fn refill_prescription(
     Path(ien): Path<i64>,
     Json(req): Json<RefillPrescriptionRequest>,
 ) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("refill_prescription");
     // Create a refill - decrements refills remaining, resets to pending
     let code = format!(
         r#"
@@ -2448,7 +4335,7 @@ W "OK"
         ien, ien, ien, req.dispensed_by, ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             match output.trim() {
                 "OK" => (StatusCode::OK, Json(CreateResponse { success: true, ien })).into_response(),
@@ -2478,56 +4365,372 @@ W "OK"
     }
 }
 
-async fn check_drug_allergies(
+/// Active allergies of a patient and those that conflict with `drug_name`
+async fn query_drug_allergies(patient_ien: i64, drug_name: &str) -> Result<AllergyCheckResponse, String> {
+    // Check if patient has allergy to specified drug or drug class
+    // Lookup tables:
+    //   drug_class_mappings                   ^PSDCLS(DRUG_CODE,DRUG_CLASS)=""
+    //   allergy_drug_class_contraindications  ^GMRACI(ALLERGY_PATTERN,CONTRAINDICATED_CLASS)=SEVERITY
+    let code = format!(
+        r#"
+N IEN,D0,FIRST,MFIRST,MATCH,ST,ALG,PAT,TYP,SEV,REACT,ALGUP,DRGUP,APAT,CLS,MATCHES
+S MATCH=0,MATCHES=""
+S DRGUP=$$UP^XLFSTR("{drug}")
+W "{{"
+W """allergies"":["
+S FIRST=1,MFIRST=1,IEN=0
+F  S IEN=$O(^GMRA("C",{patient},IEN)) Q:IEN=""  D
+. S D0=$G(^GMRA(IEN,0)) Q:D0=""
+. S ST=$P(D0,"^",6) Q:ST'="A"
+. I 'FIRST W ","
+. S FIRST=0
+. S ALG=$P(D0,"^",1),PAT=$P(D0,"^",2),TYP=$P(D0,"^",3),SEV=$P(D0,"^",4),REACT=$P(D0,"^",5)
+. S SEV=$S(SEV="MI":"mild",SEV="MO":"moderate",SEV="SE":"severe",SEV="LT":"life_threatening",1:SEV)
+. ; Direct match: allergen matches drug name (case-insensitive)
+. S ALGUP=$$UP^XLFSTR(ALG)
+. I ALGUP[DRGUP!(DRGUP[ALGUP) D
+. . S MATCH=1
+. . S:'MFIRST MATCHES=MATCHES_"," S MFIRST=0
+. . S MATCHES=MATCHES_"{{""allergyIen"":"_IEN_",""allergen"":"""_$$ESC^EHRAPI(ALG)_""",""matchType"":""direct"",""drugClass"":null,""severity"":"""_$$ESC^EHRAPI(SEV)_"""}}"
+. ; Class match: allergen pattern contraindicates a class the drug belongs to
+. S APAT="" F  S APAT=$O(^GMRACI(APAT)) Q:APAT=""  D:ALGUP[APAT
+. . S CLS="" F  S CLS=$O(^GMRACI(APAT,CLS)) Q:CLS=""  D:$D(^PSDCLS(DRGUP,CLS))
+. . . N CSEV S CSEV=$G(^GMRACI(APAT,CLS))
+. . . S CSEV=$S(CSEV="MI":"mild",CSEV="MO":"moderate",CSEV="SE":"severe",CSEV="LT":"life_threatening",1:CSEV)
+. . . S MATCH=1
+. . . S:'MFIRST MATCHES=MATCHES_"," S MFIRST=0
+. . . S MATCHES=MATCHES_"{{""allergyIen"":"_IEN_",""allergen"":"""_$$ESC^EHRAPI(ALG)_""",""matchType"":""class"",""drugClass"":"""_$$ESC^EHRAPI(CLS)_""",""severity"":"""_$$ESC^EHRAPI(CSEV)_"""}}"
+. W "{{""ien"":"_IEN_",""allergen"":"""_$$ESC^EHRAPI(ALG)_""",""patientIen"":"_PAT
+. W ",""allergyType"":"""_$$ESC^EHRAPI($S(TYP="D":"drug",TYP="F":"food",TYP="E":"environmental",1:TYP))_""""
+. W ",""severity"":"""_$$ESC^EHRAPI(SEV)_""""
+. I REACT'="" W ",""reactions"":"""_$$ESC^EHRAPI(REACT)_""""
+. W ",""status"":""active""}}"
+W "],"
+W """matchedAllergens"":["_MATCHES_"],"
+I MATCH W """hasAllergyConflict"":true"
+E  W """hasAllergyConflict"":false"
+W "}}"
+"#,
+        patient = patient_ien,
+        drug = mumps_escape(drug_name)
+    );
+
+    // An unreadable response must not be reported as "no conflict"
+    run_mumps(&code).await.and_then(|output| parse_mumps_json(&output))
+}
+
+async fn check_drug_allergies(
+    Path((patient_ien, drug_name)): Path<(i64, String)>,
+    Query(query): Query<AllergyCheckQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("check_drug_allergies");
+    let min_rank = match query.min_severity.as_deref() {
+        Some(severity) => match severity_rank(severity) {
+            Some(rank) => rank,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse { error: format!("Invalid minSeverity: {}", severity) }),
+                )
+                    .into_response()
+            }
+        },
+        None => 0,
+    };
+
+    match query_drug_allergies(patient_ien, &drug_name).await {
+        Ok(mut response) => {
+            if min_rank > 0 {
+                response.matched_allergens.retain(|m| {
+                    severity_rank(&m.severity).is_some_and(|rank| rank >= min_rank)
+                });
+                response.has_allergy_conflict = !response.matched_allergens.is_empty();
+            }
+            (StatusCode::OK, Json(response)).into_response()
+        }
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
+/// Interaction table, parsed from the embedded `interactions.json` on first use
+///
+/// `main` loads it before serving, so a malformed table stops start-up
+/// instead of silently checking against no rules.
+fn interaction_rules() -> Result<&'static [InteractionRule], String> {
+    static RULES: OnceLock<Result<Vec<InteractionRule>, String>> = OnceLock::new();
+    RULES
+        .get_or_init(|| {
+            serde_json::from_str(include_str!("interactions.json"))
+                .map_err(|e| format!("Invalid interactions.json: {}", e))
+        })
+        .as_deref()
+        .map_err(Clone::clone)
+}
+
+/// Names of a patient's active medications from ^PS(52)
+async fn query_active_medication_names(patient_ien: i64) -> Result<Vec<String>, String> {
+    let code = format!(
+        r#"
+N IEN,D0
+S IEN=0
+F  S IEN=$O(^PS(52,"C",{},IEN)) Q:IEN=""  D
+. S D0=$G(^PS(52,IEN,0)) Q:D0=""
+. I $P(D0,"^",10)="A" W $P(D0,"^",2),!
+"#,
+        patient_ien
+    );
+
+    let output = run_mumps(&code).await?;
+    Ok(output
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Interactions between `drug_name` and each of `medications`
+///
+/// Names match case-insensitively when they contain a rule's drug name, so
+/// "Warfarin 5 mg" matches a WARFARIN rule.
+fn find_interactions(drug_name: &str, medications: &[String], rules: &[InteractionRule]) -> Vec<InteractionDetail> {
+    let drug = drug_name.to_uppercase();
+    let mut interactions = Vec::new();
+
+    for medication in medications {
+        let other = medication.to_uppercase();
+        for rule in rules {
+            let (rule_a, rule_b) = (rule.drug_a.to_uppercase(), rule.drug_b.to_uppercase());
+            let matches = (drug.contains(&rule_a) && other.contains(&rule_b))
+                || (drug.contains(&rule_b) && other.contains(&rule_a));
+            if matches {
+                interactions.push(InteractionDetail {
+                    drug_a: drug_name.to_string(),
+                    drug_b: medication.clone(),
+                    severity: rule.severity.clone(),
+                    description: rule.description.clone(),
+                });
+            }
+        }
+    }
+
+    interactions
+}
+
+/// Every interaction between two medications of a regimen
+fn regimen_interactions(medications: &[String], rules: &[InteractionRule]) -> Vec<DrugInteractionDto> {
+    medications
+        .iter()
+        .enumerate()
+        .flat_map(|(i, medication)| find_interactions(medication, &medications[i + 1..], rules))
+        .map(|interaction| DrugInteractionDto {
+            severity: match interaction.severity.as_str() {
+                "contraindicated" => InteractionSeverity::Contraindicated,
+                "major" => InteractionSeverity::Major,
+                "moderate" => InteractionSeverity::Moderate,
+                "minor" => InteractionSeverity::Minor,
+                _ => InteractionSeverity::Unknown,
+            },
+            drug_a: interaction.drug_a,
+            drug_b: interaction.drug_b,
+            description: interaction.description,
+        })
+        .collect()
+}
+
+/// Polypharmacy risk of a list of active medication names
+fn polypharmacy_score(medications: &[String]) -> Result<PolypharmacyScore, String> {
+    let rules = interaction_rules()?;
+    Ok(score_polypharmacy(medications.len(), regimen_interactions(medications, rules)))
+}
+
+/// Interaction risk of the patient's active medications from ^PS(52)
+async fn get_patient_polypharmacy_score(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_polypharmacy_score");
+    let score = query_active_medication_names(patient_ien)
+        .await
+        .and_then(|medications| polypharmacy_score(&medications));
+    match score {
+        Ok(score) => (
+            StatusCode::OK,
+            Json(PolypharmacyScoreResponse { patient_ien, score }),
+        )
+            .into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse { error: e }),
+        )
+            .into_response(),
+    }
+}
+
+/// Check a drug against the patient's active medications and allergies
+async fn check_drug_interactions(
     Path((patient_ien, drug_name)): Path<(i64, String)>,
 ) -> impl IntoResponse {
-    // Check if patient has allergy to specified drug or drug class
-    let code = format!(
-        r#"
-N IEN,D0,FIRST,MATCH
-S MATCH=0
-W "{{"
-W """hasAllergyConflict"":false,"
-W """allergies"":["
-S FIRST=1,IEN=0
-F  S IEN=$O(^GMRA("C",{},IEN)) Q:IEN=""  D
-. S D0=$G(^GMRA(IEN,0)) Q:D0=""
-. S ST=$P(D0,"^",6) Q:ST'="A"
-. I 'FIRST W ","
-. S FIRST=0
-. S ALG=$P(D0,"^",1),PAT=$P(D0,"^",2),TYP=$P(D0,"^",3),SEV=$P(D0,"^",4),REACT=$P(D0,"^",5)
-. ; Check if allergen matches drug name (case-insensitive)
-. S ALGUP=$$UP^XLFSTR(ALG),DRGUP=$$UP^XLFSTR("{}")
-. I ALGUP[DRGUP!(DRGUP[ALGUP) S MATCH=1
-. W "{{""ien"":"_IEN_",""allergen"":"""_ALG_""",""patientIen"":"_PAT
-. W ",""allergyType"":"""_$S(TYP="D":"drug",TYP="F":"food",TYP="E":"environmental",1:TYP)_""""
-. W ",""severity"":"""_$S(SEV="MI":"mild",SEV="MO":"moderate",SEV="SE":"severe",SEV="LT":"life_threatening",1:SEV)_""""
-. I REACT'="" W ",""reactions"":"""_REACT_""""
-. W ",""status"":""active""}}"
-W "],"
-W """matchedAllergens"":[],"
-I MATCH W """hasAllergyConflict"":true"
-E  W """hasAllergyConflict"":false"
-W "}}"
-"#,
-        patient_ien, drug_name
+    let _timer = metrics::handler_timer("check_drug_interactions");
+    let (medications, allergy_check) = tokio::join!(
+        query_active_medication_names(patient_ien),
+        query_drug_allergies(patient_ien, &drug_name),
     );
-
-    match run_mumps(&code) {
-        Ok(output) => {
-            // Parse the JSON response
-            match serde_json::from_str::<AllergyCheckResponse>(&output) {
-                Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-                Err(_) => {
-                    // Fallback - return simple response
-                    (StatusCode::OK, Json(AllergyCheckResponse {
-                        has_allergy_conflict: false,
-                        allergies: vec![],
-                        matched_allergens: vec![],
-                    })).into_response()
-                }
-            }
+    let (medications, allergy_check, rules) = match (medications, allergy_check, interaction_rules()) {
+        (Ok(medications), Ok(allergy_check), Ok(rules)) => (medications, allergy_check, rules),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e }),
+            )
+                .into_response()
         }
+    };
+
+    let interactions = find_interactions(&drug_name, &medications, rules);
+    let severity = interactions
+        .iter()
+        .map(|i| i.severity.as_str())
+        .chain(
+            allergy_check
+                .matched_allergens
+                .iter()
+                .map(|m| allergy_interaction_severity(&m.severity)),
+        )
+        .max_by_key(|severity| interaction_severity_rank(severity))
+        .unwrap_or("none")
+        .to_string();
+
+    (StatusCode::OK, Json(DrugInteractionResponse {
+        has_interaction: !interactions.is_empty() || allergy_check.has_allergy_conflict,
+        interactions,
+        severity,
+        allergy_check,
+    }))
+        .into_response()
+}
+
+// === Clinical Decision Support ===
+
+/// CDS rules, parsed from the embedded `cds_rules.json` on first use
+fn cds_evaluator() -> &'static ClinicalAlertEvaluator {
+    static EVALUATOR: OnceLock<ClinicalAlertEvaluator> = OnceLock::new();
+    EVALUATOR.get_or_init(|| {
+        serde_json::from_str(include_str!("cds_rules.json"))
+            .map_err(|e| e.to_string())
+            .and_then(|rules| ClinicalAlertEvaluator::new(rules).map_err(|e| e.to_string()))
+            .unwrap_or_else(|e| {
+                tracing::error!("Invalid cds_rules.json: {}", e);
+                ClinicalAlertEvaluator::default()
+            })
+    })
+}
+
+/// Add a vital reading under the names CDS conditions use; blood pressure
+/// becomes `<type>_systolic` and `<type>_diastolic`
+fn add_vital_facts(vitals: &mut std::collections::HashMap<String, f64>, vital_type: &str, value: &str) {
+    if let Some((systolic, diastolic)) = parse_blood_pressure(value) {
+        vitals.insert(format!("{}_systolic", vital_type), systolic);
+        vitals.insert(format!("{}_diastolic", vital_type), diastolic);
+    } else if let Ok(value) = value.trim().parse() {
+        vitals.insert(vital_type.to_string(), value);
+    }
+}
+
+/// Age, active medications, allergies and problems, and the latest reading
+/// of each vital type; `None` if the patient doesn't exist
+async fn query_patient_cds_context(patient_ien: i64) -> Result<Option<PatientContext>, String> {
+    let (demographics, medications, allergies, problems, vitals) = tokio::join!(
+        query_patient_demographics(patient_ien),
+        query_active_medication_names(patient_ien),
+        query_patient_allergies(patient_ien),
+        query_patient_problems(patient_ien),
+        query_patient_vitals(patient_ien),
+    );
+    let Some(demographics) = demographics? else {
+        return Ok(None);
+    };
+
+    let today = chrono::Utc::now().date_naive();
+    let mut patient = PatientContext {
+        patient_id: patient_ien.to_string(),
+        age: chrono::NaiveDate::parse_from_str(demographics.birth_date.trim(), "%Y-%m-%d")
+            .ok()
+            .and_then(|birth_date| today.years_since(birth_date))
+            .and_then(|years| i32::try_from(years).ok()),
+        gender: Some(demographics.sex).filter(|sex| !sex.is_empty()),
+        medications: medications?,
+        allergies: allergies?
+            .into_iter()
+            .filter(|allergy| allergy.status != "inactive")
+            .map(|allergy| allergy.allergen)
+            .collect(),
+        diagnoses: problems?
+            .into_iter()
+            .filter(|problem| problem.status == "active")
+            .map(|problem| match problem.icd_code.filter(|icd| !icd.is_empty()) {
+                Some(icd) => format!("{} {}", icd, problem.diagnosis),
+                None => problem.diagnosis,
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    // Later readings overwrite earlier ones of the same type
+    let mut vitals = vitals?;
+    vitals.sort_by(|a, b| a.taken_at.cmp(&b.taken_at));
+    for vital in &vitals {
+        add_vital_facts(&mut patient.vitals, &vital.vital_type, &vital.value);
+    }
+    Ok(Some(patient))
+}
+
+/// Alert for a regimen whose polypharmacy risk is critical
+fn polypharmacy_alert(score: &PolypharmacyScore) -> Option<ClinicalAlert> {
+    if score.overall_risk != RiskLevel::Critical {
+        return None;
+    }
+    Some(ClinicalAlert {
+        alert_type: "polypharmacy_risk".to_string(),
+        severity: "critical".to_string(),
+        message: format!(
+            "Critical polypharmacy risk: {}.",
+            score.contributing_factors.join("; ")
+        ),
+        recommendation: Some("Review the interacting medications and deprescribe where possible.".to_string()),
+        related_items: score
+            .interaction_pairs
+            .iter()
+            .map(|pair| format!("{} + {}", pair.drug_a, pair.drug_b))
+            .collect(),
+    })
+}
+
+/// CDS rule alerts for a patient, plus the polypharmacy risk alert
+fn patient_cds_alerts(patient: &PatientContext) -> Result<Vec<ClinicalAlert>, String> {
+    let mut alerts = cds_evaluator().evaluate(patient);
+    alerts.extend(polypharmacy_alert(&polypharmacy_score(&patient.medications)?));
+    Ok(alerts)
+}
+
+/// Evaluate every CDS rule for a patient on demand
+async fn evaluate_patient_cds_alerts(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("evaluate_patient_cds_alerts");
+    let alerts = query_patient_cds_context(patient_ien)
+        .await
+        .and_then(|patient| patient.map(|patient| patient_cds_alerts(&patient)).transpose());
+    match alerts {
+        Ok(Some(alerts)) => (
+            StatusCode::OK,
+            Json(CdsAlertsResponse { patient_ien, alerts }),
+        )
+            .into_response(),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse { error: "Patient not found".to_string() }),
+        )
+            .into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse { error: e }),
@@ -2538,37 +4741,66 @@ W "}}"
 
 // === Pharmacy Inventory Handlers ===
 
-async fn list_inventory() -> impl IntoResponse {
+async fn list_inventory(
+    Query(query): Query<InventoryQuery>,
+    Query(page): Query<PageQuery>,
+) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("list_inventory");
+    let drug_code = query.drug_code.as_deref().unwrap_or("");
+    let location = query.location.as_deref().unwrap_or("");
+    let page = match Page::from_query(&page, format!("inventory:{}:{}", drug_code, location)) {
+        Ok(page) => page,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ErrorResponse { error: e })).into_response(),
+    };
     // ^PSD - VistA Pharmacy Drug Inventory
-    let code = r#"
-N IEN,D0,FIRST,NOW
+    // Empty DCODE/DLOC match every drug/location
+    let code = format!(
+        r#"
+N IEN,D0,FIRST,NOW,DCODE,DLOC,CNT,SKIP
 S NOW=$H
+S DCODE="{drug_code}",DLOC="{location}"
 W "["
-S FIRST=1,IEN=0
-F  S IEN=$O(^PSD(IEN)) Q:IEN=""  Q:'IEN  D
+S FIRST=1,IEN={after},CNT=0,SKIP={skip}
+F  S IEN=$O(^PSD(IEN)) Q:IEN=""  Q:'IEN  Q:CNT'<{fetch}  D
 . S D0=$G(^PSD(IEN,0)) Q:D0=""
+. S CODE=$P(D0,"^",1),NAME=$P(D0,"^",2),LOC=$P(D0,"^",3),LOCN=$P(D0,"^",4)
+. I DCODE'="",CODE'=DCODE Q
+. I DLOC'="",LOC'=DLOC Q
+. I SKIP S SKIP=SKIP-1 Q
+. S CNT=CNT+1
 . I 'FIRST W ","
 . S FIRST=0
-. S CODE=$P(D0,"^",1),NAME=$P(D0,"^",2),LOC=$P(D0,"^",3),LOCN=$P(D0,"^",4)
 . S QTY=$P(D0,"^",5),ROP=$P(D0,"^",6),ROQ=$P(D0,"^",7),UNIT=$P(D0,"^",8)
 . S UPD=$P(D0,"^",9),CTRL=$P(D0,"^",10),SCH=$P(D0,"^",11)
 . S LOW=$S(+QTY<+ROP:1,1:0)
-. W "{""ien"":"_IEN_",""drugCode"":"""_CODE_""",""drugName"":"""_NAME_""""
-. W ",""locationCode"":"""_LOC_""""
-. I LOCN'="" W ",""locationName"":"""_LOCN_""""
+. W "{{""ien"":"_IEN_",""drugCode"":"""_$$ESC^EHRAPI(CODE)_""",""drugName"":"""_$$ESC^EHRAPI(NAME)_""""
+. W ",""locationCode"":"""_$$ESC^EHRAPI(LOC)_""""
+. I LOCN'="" W ",""locationName"":"""_$$ESC^EHRAPI(LOCN)_""""
 . W ",""quantityOnHand"":"_+QTY_",""reorderPoint"":"_+ROP_",""reorderQuantity"":"_+ROQ
-. W ",""unit"":"""_UNIT_""",""lastUpdated"":"""_UPD_""""
+. W ",""unit"":"""_$$ESC^EHRAPI(UNIT)_""",""lastUpdated"":"""_$$ESC^EHRAPI(UPD)_""""
 . W ",""isLowStock"":"_$S(LOW:"true",1:"false")
 . W ",""isControlled"":"_$S(CTRL:"true",1:"false")
-. I SCH'="" W ",""schedule"":"""_SCH_""""
-. W "}"
+. I SCH'="" W ",""schedule"":"""_$$ESC^EHRAPI(SCH)_""""
+. W "}}"
 W "]"
-"#;
+"#,
+        drug_code = mumps_escape(drug_code),
+        location = mumps_escape(location),
+        after = page.after,
+        skip = page.skip,
+        fetch = page.fetch(),
+    );
 
-    match run_mumps(code) {
-        Ok(output) => {
-            let items = parse_inventory_items(&output);
-            (StatusCode::OK, Json(InventoryResponse { items })).into_response()
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(items) => {
+            let result = page.finish(items, |item: &InventoryItemResponse| item.ien);
+            page.respond(
+                (StatusCode::OK, Json(InventoryResponse {
+                    items: result.items,
+                    next_cursor: result.next_cursor,
+                    has_more: result.has_more,
+                })).into_response(),
+            )
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -2578,74 +4810,8 @@ W "]"
     }
 }
 
-fn parse_inventory_items(json: &str) -> Vec<InventoryItemResponse> {
-    let mut items = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return items;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut drug_code = String::new();
-        let mut drug_name = String::new();
-        let mut location_code = String::new();
-        let mut location_name = None;
-        let mut quantity_on_hand = 0i32;
-        let mut reorder_point = 0i32;
-        let mut reorder_quantity = 0i32;
-        let mut unit = String::from("EA");
-        let mut last_updated = String::new();
-        let mut is_low_stock = false;
-        let mut is_controlled = false;
-        let mut schedule = None;
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "drugCode" => drug_code = val.to_string(),
-                    "drugName" => drug_name = val.to_string(),
-                    "locationCode" => location_code = val.to_string(),
-                    "locationName" => location_name = Some(val.to_string()),
-                    "quantityOnHand" => quantity_on_hand = val.parse().unwrap_or(0),
-                    "reorderPoint" => reorder_point = val.parse().unwrap_or(0),
-                    "reorderQuantity" => reorder_quantity = val.parse().unwrap_or(0),
-                    "unit" => unit = val.to_string(),
-                    "lastUpdated" => last_updated = val.to_string(),
-                    "isLowStock" => is_low_stock = val == "true",
-                    "isControlled" => is_controlled = val == "true",
-                    "schedule" => schedule = Some(val.to_string()),
-                    _ => {}
-                }
-            }
-        }
-
-        items.push(InventoryItemResponse {
-            ien,
-            drug_code,
-            drug_name,
-            location_code,
-            location_name,
-            quantity_on_hand,
-            reorder_point,
-            reorder_quantity,
-            unit,
-            last_updated,
-            is_low_stock,
-            is_controlled,
-            schedule,
-        });
-    }
-
-    items
-}
-
 async fn get_inventory_item(Path(ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_inventory_item");
     let code = format!(
         r#"
 S D0=$G(^PSD({},0))
@@ -2654,38 +4820,33 @@ S CODE=$P(D0,"^",1),NAME=$P(D0,"^",2),LOC=$P(D0,"^",3),LOCN=$P(D0,"^",4)
 S QTY=$P(D0,"^",5),ROP=$P(D0,"^",6),ROQ=$P(D0,"^",7),UNIT=$P(D0,"^",8)
 S UPD=$P(D0,"^",9),CTRL=$P(D0,"^",10),SCH=$P(D0,"^",11)
 S LOW=$S(+QTY<+ROP:1,1:0)
-W "{{""ien"":{},""drugCode"":"""_CODE_""",""drugName"":"""_NAME_""""
-W ",""locationCode"":"""_LOC_""""
-I LOCN'="" W ",""locationName"":"""_LOCN_""""
+W "{{""ien"":{},""drugCode"":"""_$$ESC^EHRAPI(CODE)_""",""drugName"":"""_$$ESC^EHRAPI(NAME)_""""
+W ",""locationCode"":"""_$$ESC^EHRAPI(LOC)_""""
+I LOCN'="" W ",""locationName"":"""_$$ESC^EHRAPI(LOCN)_""""
 W ",""quantityOnHand"":"_+QTY_",""reorderPoint"":"_+ROP_",""reorderQuantity"":"_+ROQ
-W ",""unit"":"""_UNIT_""",""lastUpdated"":"""_UPD_""""
+W ",""unit"":"""_$$ESC^EHRAPI(UNIT)_""",""lastUpdated"":"""_$$ESC^EHRAPI(UPD)_""""
 W ",""isLowStock"":"_$S(LOW:"true",1:"false")
 W ",""isControlled"":"_$S(CTRL:"true",1:"false")
-I SCH'="" W ",""schedule"":"""_SCH_""""
+I SCH'="" W ",""schedule"":"""_$$ESC^EHRAPI(SCH)_""""
 W "}}"
 "#,
         ien, ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            if output.trim() == "{}" || output.is_empty() {
-                (
-                    StatusCode::NOT_FOUND,
-                    Json(ErrorResponse { error: "Inventory item not found".to_string() }),
-                ).into_response()
-            } else {
-                let items = parse_inventory_items(&format!("[{}]", output));
-                if let Some(item) = items.into_iter().next() {
-                    (StatusCode::OK, Json(item)).into_response()
-                } else {
-                    (
-                        StatusCode::NOT_FOUND,
-                        Json(ErrorResponse { error: "Inventory item not found".to_string() }),
-                    ).into_response()
-                }
-            }
-        }
+    match run_mumps(&code).await {
+        Ok(output) if output.trim() == "{}" || output.is_empty() => (
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse { error: "Inventory item not found".to_string() }),
+        )
+            .into_response(),
+        Ok(output) => match parse_mumps_json::<InventoryItemResponse>(&output) {
+            Ok(item) => (StatusCode::OK, Json(item)).into_response(),
+            Err(e) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse { error: e }),
+            )
+                .into_response(),
+        },
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse { error: e }),
@@ -2695,6 +4856,7 @@ W "}}"
 }
 
 async fn create_inventory_item(Json(req): Json<CreateInventoryItemRequest>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("create_inventory_item");
     let location_name = req.location_name.unwrap_or_default();
     let reorder_point = req.reorder_point.unwrap_or(10);
     let reorder_quantity = req.reorder_quantity.unwrap_or(50);
@@ -2718,7 +4880,7 @@ W IEN
         req.drug_code, req.location_code
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             let ien: i64 = output.trim().parse().unwrap_or(0);
             (
@@ -2736,9 +4898,11 @@ W IEN
 }
 
 async fn adjust_inventory(
+    State(alerts): State<AlertHub>,
     Path(ien): Path<i64>,
     Json(req): Json<AdjustInventoryRequest>,
 ) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("adjust_inventory");
     let adjusted_by = req.adjusted_by.unwrap_or(0);
     let lot_number = req.lot_number.unwrap_or_default();
     let now = chrono::Utc::now().format("%Y%m%d.%H%M%S").to_string();
@@ -2757,15 +4921,29 @@ S ^PSD({},0)=D0
 S TXIEN=$P($G(^PSD({},1,0)),"^",3)+1
 S ^PSD({},1,TXIEN)="ADJ^{}^"_PREV_"^"_NEW_"^{}^{}^{}^{}"
 S $P(^PSD({},1,0),"^",3)=TXIEN
-W "OK"
+W "OK^"_NEW_"^"_+$P(D0,"^",6)_"^"_$P(D0,"^",2)
 "#,
         ien, req.quantity, now, ien, ien, ien, req.quantity, req.reason, adjusted_by, lot_number, now, ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
-            match output.trim() {
-                "OK" => (StatusCode::OK, Json(CreateResponse { success: true, ien })).into_response(),
+            let mut pieces = output.trim().splitn(4, '^');
+            match pieces.next().unwrap_or_default() {
+                "OK" => {
+                    let new_quantity: i32 = pieces.next().and_then(|q| q.parse().ok()).unwrap_or(0);
+                    let reorder_point: i32 = pieces.next().and_then(|r| r.parse().ok()).unwrap_or(0);
+                    if new_quantity < reorder_point {
+                        alerts.broadcast(InventoryAlert {
+                            inventory_ien: ien,
+                            drug_name: pieces.next().unwrap_or_default().to_string(),
+                            quantity_on_hand: new_quantity,
+                            reorder_point,
+                            timestamp: chrono::Utc::now().to_rfc3339(),
+                        });
+                    }
+                    (StatusCode::OK, Json(CreateResponse { success: true, ien })).into_response()
+                }
                 "NOT_FOUND" => (
                     StatusCode::NOT_FOUND,
                     Json(ErrorResponse { error: "Inventory item not found".to_string() }),
@@ -2788,8 +4966,7 @@ W "OK"
     }
 }
 
-async fn get_low_stock_items() -> impl IntoResponse {
-    let code = r#"
+const LOW_STOCK_ITEMS_M: &str = r#"
 N IEN,D0,FIRST,CNT
 S CNT=0
 W "{""items"":["
@@ -2803,28 +4980,29 @@ F  S IEN=$O(^PSD(IEN)) Q:IEN=""  Q:'IEN  D
 . S FIRST=0
 . S CODE=$P(D0,"^",1),NAME=$P(D0,"^",2),LOC=$P(D0,"^",3),LOCN=$P(D0,"^",4)
 . S ROQ=$P(D0,"^",7),UNIT=$P(D0,"^",8),UPD=$P(D0,"^",9),CTRL=$P(D0,"^",10),SCH=$P(D0,"^",11)
-. W "{""ien"":"_IEN_",""drugCode"":"""_CODE_""",""drugName"":"""_NAME_""""
-. W ",""locationCode"":"""_LOC_""""
-. I LOCN'="" W ",""locationName"":"""_LOCN_""""
+. W "{""ien"":"_IEN_",""drugCode"":"""_$$ESC^EHRAPI(CODE)_""",""drugName"":"""_$$ESC^EHRAPI(NAME)_""""
+. W ",""locationCode"":"""_$$ESC^EHRAPI(LOC)_""""
+. I LOCN'="" W ",""locationName"":"""_$$ESC^EHRAPI(LOCN)_""""
 . W ",""quantityOnHand"":"_+QTY_",""reorderPoint"":"_+ROP_",""reorderQuantity"":"_+ROQ
-. W ",""unit"":"""_UNIT_""",""lastUpdated"":"""_UPD_""""
+. W ",""unit"":"""_$$ESC^EHRAPI(UNIT)_""",""lastUpdated"":"""_$$ESC^EHRAPI(UPD)_""""
 . W ",""isLowStock"":true"
 . W ",""isControlled"":"_$S(CTRL:"true",1:"false")
-. I SCH'="" W ",""schedule"":"""_SCH_""""
+. I SCH'="" W ",""schedule"":"""_$$ESC^EHRAPI(SCH)_""""
 . W "}"
 W "],""count"":"_CNT_"}"
 "#;
 
-    match run_mumps(code) {
-        Ok(output) => {
-            match serde_json::from_str::<LowStockAlertResponse>(&output) {
-                Ok(response) => (StatusCode::OK, Json(response)).into_response(),
-                Err(_) => (StatusCode::OK, Json(LowStockAlertResponse {
-                    items: vec![],
-                    count: 0,
-                })).into_response(),
-            }
-        }
+async fn query_low_stock_items() -> Result<LowStockAlertResponse, String> {
+    let response: LowStockAlertResponse =
+        run_mumps(LOW_STOCK_ITEMS_M).await.and_then(|output| parse_mumps_json(&output))?;
+    metrics::set_low_stock_items(response.items.len());
+    Ok(response)
+}
+
+async fn get_low_stock_items() -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_low_stock_items");
+    match query_low_stock_items().await {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ErrorResponse { error: e }),
@@ -2834,6 +5012,7 @@ W "],""count"":"_CNT_"}"
 }
 
 async fn get_inventory_lots(Path(ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_inventory_lots");
     // Get lots for an inventory item
     let now = chrono::Utc::now().format("%Y%m%d").to_string();
     let soon = chrono::Utc::now()
@@ -2854,17 +5033,16 @@ F  S LIEN=$O(^PSD({},2,LIEN)) Q:LIEN=""  D
 . S FIRST=0
 . S LOT=$P(D0,"^",1),EXP=$P(D0,"^",2),QTY=$P(D0,"^",3),RCV=$P(D0,"^",4)
 . S XPRD=$S(EXP<NOW:1,1:0),XPRS=$S(EXP<SOON:1,1:0)
-. W "{{""ien"":"_LIEN_",""inventoryIen"":{},""lotNumber"":"""_LOT_""""
-. W ",""expirationDate"":"""_EXP_""",""quantity"":"_+QTY_",""receivedDate"":"""_RCV_""""
+. W "{{""ien"":"_LIEN_",""inventoryIen"":{},""lotNumber"":"""_$$ESC^EHRAPI(LOT)_""""
+. W ",""expirationDate"":"""_$$ESC^EHRAPI(EXP)_""",""quantity"":"_+QTY_",""receivedDate"":"""_$$ESC^EHRAPI(RCV)_""""
 . W ",""isExpired"":"_$S(XPRD:"true",1:"false")_",""isExpiringSoon"":"_$S(XPRS:"true",1:"false")_"}}"
 W "]"
 "#,
         now, soon, ien, ien, ien
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let lots = parse_lots(&output);
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(lots) => {
             (StatusCode::OK, Json(LotsResponse { lots })).into_response()
         }
         Err(e) => (
@@ -2875,62 +5053,11 @@ W "]"
     }
 }
 
-fn parse_lots(json: &str) -> Vec<LotResponse> {
-    let mut lots = Vec::new();
-    let content = json.trim().trim_start_matches('[').trim_end_matches(']');
-    if content.is_empty() {
-        return lots;
-    }
-
-    for obj in content.split("},{") {
-        let obj = obj.trim_start_matches('{').trim_end_matches('}');
-        let mut ien = 0i64;
-        let mut inventory_ien = 0i64;
-        let mut lot_number = String::new();
-        let mut expiration_date = String::new();
-        let mut quantity = 0i32;
-        let mut received_date = String::new();
-        let mut is_expired = false;
-        let mut is_expiring_soon = false;
-
-        for pair in obj.split(',') {
-            let parts: Vec<&str> = pair.splitn(2, ':').collect();
-            if parts.len() == 2 {
-                let key = parts[0].trim().trim_matches('"');
-                let val = parts[1].trim().trim_matches('"');
-                match key {
-                    "ien" => ien = val.parse().unwrap_or(0),
-                    "inventoryIen" => inventory_ien = val.parse().unwrap_or(0),
-                    "lotNumber" => lot_number = val.to_string(),
-                    "expirationDate" => expiration_date = val.to_string(),
-                    "quantity" => quantity = val.parse().unwrap_or(0),
-                    "receivedDate" => received_date = val.to_string(),
-                    "isExpired" => is_expired = val == "true",
-                    "isExpiringSoon" => is_expiring_soon = val == "true",
-                    _ => {}
-                }
-            }
-        }
-
-        lots.push(LotResponse {
-            ien,
-            inventory_ien,
-            lot_number,
-            expiration_date,
-            quantity,
-            received_date,
-            is_expired,
-            is_expiring_soon,
-        });
-    }
-
-    lots
-}
-
 async fn add_lot(
     Path(ien): Path<i64>,
     Json(req): Json<AddLotRequest>,
 ) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("add_lot");
     let now = chrono::Utc::now().format("%Y%m%d").to_string();
 
     let code = format!(
@@ -2952,7 +5079,7 @@ W LIEN
         ien, req.lot_number, ien, req.quantity, now, ien
     );
 
-    match run_mumps(&code) {
+    match run_mumps(&code).await {
         Ok(output) => {
             match output.trim() {
                 "NOT_FOUND" => (
@@ -2977,6 +5104,7 @@ W LIEN
 }
 
 async fn get_inventory_by_location(Path(location_code): Path<String>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_inventory_by_location");
     let code = format!(
         r#"
 N IEN,D0,FIRST
@@ -2990,24 +5118,23 @@ F  S IEN=$O(^PSD("L","{}",IEN)) Q:IEN=""  D
 . S QTY=$P(D0,"^",5),ROP=$P(D0,"^",6),ROQ=$P(D0,"^",7),UNIT=$P(D0,"^",8)
 . S UPD=$P(D0,"^",9),CTRL=$P(D0,"^",10),SCH=$P(D0,"^",11)
 . S LOW=$S(+QTY<+ROP:1,1:0)
-. W "{{""ien"":"_IEN_",""drugCode"":"""_CODE_""",""drugName"":"""_NAME_""""
-. W ",""locationCode"":"""_LOC_""""
-. I LOCN'="" W ",""locationName"":"""_LOCN_""""
+. W "{{""ien"":"_IEN_",""drugCode"":"""_$$ESC^EHRAPI(CODE)_""",""drugName"":"""_$$ESC^EHRAPI(NAME)_""""
+. W ",""locationCode"":"""_$$ESC^EHRAPI(LOC)_""""
+. I LOCN'="" W ",""locationName"":"""_$$ESC^EHRAPI(LOCN)_""""
 . W ",""quantityOnHand"":"_+QTY_",""reorderPoint"":"_+ROP_",""reorderQuantity"":"_+ROQ
-. W ",""unit"":"""_UNIT_""",""lastUpdated"":"""_UPD_""""
+. W ",""unit"":"""_$$ESC^EHRAPI(UNIT)_""",""lastUpdated"":"""_$$ESC^EHRAPI(UPD)_""""
 . W ",""isLowStock"":"_$S(LOW:"true",1:"false")
 . W ",""isControlled"":"_$S(CTRL:"true",1:"false")
-. I SCH'="" W ",""schedule"":"""_SCH_""""
+. I SCH'="" W ",""schedule"":"""_$$ESC^EHRAPI(SCH)_""""
 . W "}}"
 W "]"
 "#,
         location_code
     );
 
-    match run_mumps(&code) {
-        Ok(output) => {
-            let items = parse_inventory_items(&output);
-            (StatusCode::OK, Json(InventoryResponse { items })).into_response()
+    match run_mumps(&code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(items) => {
+            (StatusCode::OK, Json(InventoryResponse { items, next_cursor: None, has_more: false })).into_response()
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -3018,6 +5145,7 @@ W "]"
 }
 
 async fn get_controlled_substances() -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_controlled_substances");
     let code = r#"
 N IEN,D0,FIRST
 W "["
@@ -3031,22 +5159,21 @@ F  S IEN=$O(^PSD(IEN)) Q:IEN=""  Q:'IEN  D
 . S QTY=$P(D0,"^",5),ROP=$P(D0,"^",6),ROQ=$P(D0,"^",7),UNIT=$P(D0,"^",8)
 . S UPD=$P(D0,"^",9),SCH=$P(D0,"^",11)
 . S LOW=$S(+QTY<+ROP:1,1:0)
-. W "{""ien"":"_IEN_",""drugCode"":"""_CODE_""",""drugName"":"""_NAME_""""
-. W ",""locationCode"":"""_LOC_""""
-. I LOCN'="" W ",""locationName"":"""_LOCN_""""
+. W "{""ien"":"_IEN_",""drugCode"":"""_$$ESC^EHRAPI(CODE)_""",""drugName"":"""_$$ESC^EHRAPI(NAME)_""""
+. W ",""locationCode"":"""_$$ESC^EHRAPI(LOC)_""""
+. I LOCN'="" W ",""locationName"":"""_$$ESC^EHRAPI(LOCN)_""""
 . W ",""quantityOnHand"":"_+QTY_",""reorderPoint"":"_+ROP_",""reorderQuantity"":"_+ROQ
-. W ",""unit"":"""_UNIT_""",""lastUpdated"":"""_UPD_""""
+. W ",""unit"":"""_$$ESC^EHRAPI(UNIT)_""",""lastUpdated"":"""_$$ESC^EHRAPI(UPD)_""""
 . W ",""isLowStock"":"_$S(LOW:"true",1:"false")
 . W ",""isControlled"":true"
-. I SCH'="" W ",""schedule"":"""_SCH_""""
+. I SCH'="" W ",""schedule"":"""_$$ESC^EHRAPI(SCH)_""""
 . W "}"
 W "]"
 "#;
 
-    match run_mumps(code) {
-        Ok(output) => {
-            let items = parse_inventory_items(&output);
-            (StatusCode::OK, Json(InventoryResponse { items })).into_response()
+    match run_mumps(code).await.and_then(|output| parse_mumps_json(&output)) {
+        Ok(items) => {
+            (StatusCode::OK, Json(InventoryResponse { items, next_cursor: None, has_more: false })).into_response()
         }
         Err(e) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -3056,10 +5183,125 @@ W "]"
     }
 }
 
+// === Inventory Alerts ===
+
+/// Alerts buffered per subscriber; a client that falls this far behind is disconnected
+const ALERT_CHANNEL_CAPACITY: usize = 64;
+
+/// Fan-out of low-stock alerts to connected WebSocket and SSE clients
+#[derive(Clone, Default)]
+struct AlertHub {
+    subscribers: Arc<Mutex<Vec<mpsc::Sender<InventoryAlert>>>>,
+}
+
+impl AlertHub {
+    fn subscribe(&self) -> mpsc::Receiver<InventoryAlert> {
+        let (tx, rx) = mpsc::channel(ALERT_CHANNEL_CAPACITY);
+        self.subscribers.lock().unwrap_or_else(|e| e.into_inner()).push(tx);
+        rx
+    }
+
+    /// Send to every subscriber, dropping closed ones and any whose buffer is full
+    fn broadcast(&self, alert: InventoryAlert) {
+        let mut subscribers = self.subscribers.lock().unwrap_or_else(|e| e.into_inner());
+        subscribers.retain(|tx| tx.try_send(alert.clone()).is_ok());
+    }
+}
+
+/// Items currently below their reorder point, as alerts
+async fn active_low_stock_alerts() -> Vec<InventoryAlert> {
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    match query_low_stock_items().await {
+        Ok(response) => response
+            .items
+            .into_iter()
+            .map(|item| InventoryAlert {
+                inventory_ien: item.ien,
+                drug_name: item.drug_name,
+                quantity_on_hand: item.quantity_on_hand,
+                reorder_point: item.reorder_point,
+                timestamp: timestamp.clone(),
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to load active low-stock items: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+async fn inventory_alerts_ws(ws: WebSocketUpgrade, State(alerts): State<AlertHub>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("inventory_alerts_ws");
+    ws.on_upgrade(move |socket| stream_inventory_alerts(socket, alerts))
+}
+
+async fn stream_inventory_alerts(mut socket: WebSocket, alerts: AlertHub) {
+    // Subscribe before the snapshot so alerts raised in between are not missed
+    let mut rx = alerts.subscribe();
+
+    for alert in active_low_stock_alerts().await {
+        if send_alert(&mut socket, &alert).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            alert = rx.recv() => match alert {
+                Some(alert) => {
+                    if send_alert(&mut socket, &alert).await.is_err() {
+                        return;
+                    }
+                }
+                None => {
+                    // The hub dropped this client because its buffer filled up
+                    let _ = socket
+                        .send(Message::Close(Some(CloseFrame {
+                            code: close_code::AGAIN,
+                            reason: "alert buffer full".into(),
+                        })))
+                        .await;
+                    return;
+                }
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+async fn send_alert(socket: &mut WebSocket, alert: &InventoryAlert) -> Result<(), axum::Error> {
+    let payload = serde_json::to_string(alert).unwrap_or_default();
+    socket.send(Message::Text(payload.into())).await
+}
+
+/// SSE fallback for clients without WebSocket support
+async fn subscribe_inventory_alerts(
+    State(alerts): State<AlertHub>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let _timer = metrics::handler_timer("subscribe_inventory_alerts");
+    let rx = alerts.subscribe();
+    let active = active_low_stock_alerts().await;
+
+    let stream = tokio_stream::iter(active)
+        .chain(ReceiverStream::new(rx))
+        .map(|alert| {
+            Ok(Event::default()
+                .event("low_stock")
+                .json_data(&alert)
+                .unwrap_or_default())
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
 // === Stub Handlers ===
 
 // Stub handler for latest vitals
 async fn get_patient_latest_vitals(Path(patient_ien): Path<i64>) -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_patient_latest_vitals");
     // TODO: Implement latest vitals query based on patient_ien
     // For now, return empty vitals object
     let _ = patient_ien; // Suppress unused variable warning
@@ -3078,6 +5320,7 @@ async fn get_patient_latest_vitals(Path(patient_ien): Path<i64>) -> impl IntoRes
 
 // Stub handler for actionable labs
 async fn get_actionable_labs() -> impl IntoResponse {
+    let _timer = metrics::handler_timer("get_actionable_labs");
     // TODO: Implement actionable labs query
     // For now, return empty items array
     Json(serde_json::json!({
@@ -3100,59 +5343,109 @@ async fn main() -> anyhow::Result<()> {
         .init();
 
     eprintln!("Tracing initialized");
+
+    let pool_size = std::env::var("MUMPS_POOL_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(mumps_pool::DEFAULT_POOL_SIZE);
+    let timeout = std::env::var("MUMPS_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map_or(mumps_pool::DEFAULT_TIMEOUT, Duration::from_secs);
+    let pool = MumpsPool::new(pool_size).map_err(anyhow::Error::msg)?.with_timeout(timeout);
+    if MUMPS_POOL.set(pool).is_err() {
+        anyhow::bail!("MUMPS pool initialized twice");
+    }
+    tracing::info!("Started {} YottaDB workers", pool_size);
+
+    interaction_rules().map_err(anyhow::Error::msg)?;
+    metrics::init();
+    // Seed the patient gauge from the ^DPT header count; later creates increment it
+    match run_mumps(r#"W +$P($G(^DPT(0)),"^",4)"#).await {
+        Ok(output) => metrics::set_patients_total(output.trim().parse().unwrap_or(0)),
+        Err(e) => tracing::warn!("Failed to read patient count for metrics: {}", e),
+    }
     tracing::info!("Starting YottaDB REST API server...");
 
     let app = Router::new()
         // Health
         .route("/health", get(health))
         .route("/api/health", get(health))
+        .route("/metrics", get(metrics::metrics_handler))
         // Patients
         .route("/api/v1/ehr/patients", get(list_patients).post(create_patient))
-        .route("/api/v1/ehr/patients/{ien}", get(get_patient))
+        .route("/api/v1/ehr/patients/merge", post(merge_patients))
+        .route("/api/v1/ehr/patients/{ien}", get(get_patient).put(update_patient))
         .route("/api/v1/ehr/patients/{ien}/problems", get(get_patient_problems))
         .route("/api/v1/ehr/patients/{ien}/allergies", get(get_patient_allergies))
+        .route("/api/v1/ehr/patients/{ien}/summary", get(get_patient_summary))
+        .route("/api/v1/ehr/patients/{ien}/ccd", get(get_patient_ccd))
+        .route("/api/v1/ehr/patients/{ien}/cds-alerts", post(evaluate_patient_cds_alerts))
+        .route("/api/v1/ehr/hl7/adt", post(receive_adt))
         // Visits
         .route("/api/v1/ehr/patients/{ien}/visits", get(get_patient_visits))
         .route("/api/v1/ehr/visits", post(create_visit))
         // Vitals
         .route("/api/v1/ehr/patients/{ien}/vitals", get(get_patient_vitals))
         .route("/api/v1/ehr/patients/{ien}/vitals/latest", get(get_patient_latest_vitals))
+        .route("/api/v1/ehr/patients/{ien}/vitals/trend", get(get_patient_vital_trend))
+        .route("/api/v1/ehr/patients/{ien}/polypharmacy-score", get(get_patient_polypharmacy_score))
         .route("/api/v1/ehr/vitals", post(create_vital))
         // Medications
         .route("/api/v1/ehr/patients/{ien}/medications", get(get_patient_medications))
         .route("/api/v1/ehr/medications", post(create_medication))
         // Lab Results
         .route("/api/v1/ehr/patients/{ien}/labs", get(get_patient_labs))
+        .route("/api/v1/ehr/patients/{ien}/labs/panels", get(get_patient_lab_panels))
         .route("/api/v1/ehr/labs", post(create_lab_result))
         .route("/api/v1/ehr/labs/actionable", get(get_actionable_labs))
         // Documents
         .route("/api/v1/ehr/patients/{ien}/documents", get(get_patient_documents))
         .route("/api/v1/ehr/documents", post(create_document))
+        .route("/api/v1/ehr/documents/{id}", get(get_document))
+        .route(
+            "/api/v1/ehr/documents/{id}/content",
+            get(get_document_content).patch(amend_document_content),
+        )
         // Orders
         .route("/api/v1/ehr/patients/{ien}/orders", get(get_patient_orders))
         .route("/api/v1/ehr/orders", post(create_order))
         // Appointments
         .route("/api/v1/ehr/patients/{ien}/appointments", get(get_patient_appointments))
         .route("/api/v1/ehr/appointments", post(create_appointment))
+        .route(
+            "/api/v1/ehr/providers/{provider_ien}/schedule",
+            get(get_provider_schedule).put(set_provider_schedule),
+        )
         // Prescriptions / Pharmacy Dispensing
         .route("/api/v1/pharmacy/patients/{ien}/prescriptions", get(get_patient_prescriptions))
         .route("/api/v1/pharmacy/prescriptions", post(create_prescription))
         .route("/api/v1/pharmacy/prescriptions/pending", get(get_pending_prescriptions))
+        .route("/api/v1/pharmacy/prescriptions/scan-verify", post(scan_verify_prescription))
         .route("/api/v1/pharmacy/prescriptions/{ien}/verify", post(verify_prescription))
         .route("/api/v1/pharmacy/prescriptions/{ien}/dispense", post(dispense_prescription))
         .route("/api/v1/pharmacy/prescriptions/{ien}/complete", post(complete_prescription))
         .route("/api/v1/pharmacy/prescriptions/{ien}/refill", post(refill_prescription))
+        .route("/api/v1/pharmacy/prescriptions/{ien}/dispensing-history", get(get_dispensing_history))
+        .route("/api/v1/pharmacy/prescriptions/{ien}/barcode", get(get_prescription_barcode))
         // Allergy Checking
         .route("/api/v1/pharmacy/patients/{patient_ien}/allergies/check/{drug_name}", get(check_drug_allergies))
+        .route("/api/v1/pharmacy/patients/{patient_ien}/interactions/check/{drug_name}", get(check_drug_interactions))
         // Pharmacy Inventory
         .route("/api/v1/pharmacy/inventory", get(list_inventory).post(create_inventory_item))
         .route("/api/v1/pharmacy/inventory/low-stock", get(get_low_stock_items))
+        .route("/api/v1/pharmacy/inventory/alerts/subscribe", get(subscribe_inventory_alerts))
+        .route("/ws/api/v1/pharmacy/inventory/alerts", get(inventory_alerts_ws))
         .route("/api/v1/pharmacy/inventory/controlled", get(get_controlled_substances))
         .route("/api/v1/pharmacy/inventory/location/{location_code}", get(get_inventory_by_location))
+        .route("/api/v1/pharmacy/inventory/lots/{lot_number}/dispensing", get(get_lot_dispensing))
         .route("/api/v1/pharmacy/inventory/{ien}", get(get_inventory_item))
         .route("/api/v1/pharmacy/inventory/{ien}/adjust", post(adjust_inventory))
         .route("/api/v1/pharmacy/inventory/{ien}/lots", get(get_inventory_lots).post(add_lot))
-        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any));
+        .layer(axum::middleware::from_fn(metrics::track_requests))
+        .layer(axum::middleware::from_fn(trace_context::trace_middleware))
+        .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any))
+        .with_state(AlertHub::default());
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     let addr = format!("0.0.0.0:{}", port);
@@ -3167,3 +5460,583 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::domain::entities::ehr::SlotStatus;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    #[ignore = "requires the health-yottadb container"]
+    async fn create_patient_increments_request_counter() {
+        if MUMPS_POOL.get().is_none() {
+            let _ = MUMPS_POOL.set(MumpsPool::new(1).unwrap());
+        }
+        let app = Router::new()
+            .route("/api/v1/ehr/patients", post(create_patient))
+            .layer(axum::middleware::from_fn(metrics::track_requests));
+        let created_before = metrics::requests_total("create_patient", "201");
+        let patients_before = metrics::patients_total();
+
+        let body = serde_json::json!({
+            "firstName": "Metrics",
+            "lastName": "Test",
+            "sex": "F",
+            "dateOfBirth": "19800101",
+        });
+        let request = axum::http::Request::builder()
+            .method("POST")
+            .uri("/api/v1/ehr/patients?allow_similar=true")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(metrics::requests_total("create_patient", "201"), created_before + 1);
+        assert_eq!(metrics::patients_total(), patients_before + 1);
+    }
+
+    #[test]
+    fn mumps_escape_keeps_values_inside_the_literal() {
+        assert_eq!(mumps_escape(r#"Smith, "Jr""#), r#"Smith, ""Jr"""#);
+        assert_eq!(mumps_escape("a\r\nH"), r#"a"_$C(13)_""_$C(10)_"H"#);
+        assert_eq!(mumps_escape("C:\tnotes"), r#"C:"_$C(9)_"notes"#);
+    }
+
+    #[test]
+    fn validate_piece_rejects_piece_delimiter() {
+        assert!(validate_piece("lotNumber", "LOT-42A").is_ok());
+        assert_eq!(validate_piece("lotNumber", "LOT^42").unwrap_err(), "lotNumber must not contain '^'");
+        assert_eq!(
+            validate_piece("reason", "Follow-up\nH").unwrap_err(),
+            "reason must not contain control characters"
+        );
+    }
+
+    #[test]
+    fn validate_pieces_reports_the_first_bad_field() {
+        assert!(validate_pieces(&[("lastName", "DOE"), ("ssn", "")]).is_ok());
+        assert_eq!(
+            validate_pieces(&[("lastName", "DOE"), ("firstName", "JOHN^X"), ("ssn", "1\r2")]).unwrap_err(),
+            "firstName must not contain '^'"
+        );
+    }
+
+    #[test]
+    fn parse_mumps_json_keeps_embedded_commas() {
+        let output = r#"[{"ien":7,"allergen":"Penicillin","patientIen":42,"allergyType":"drug","severity":"severe","reactions":"hives, facial swelling, shortness of breath","status":"active"}]"#;
+
+        let allergies: Vec<AllergyResponse> = parse_mumps_json(output).unwrap();
+
+        assert_eq!(allergies.len(), 1);
+        assert_eq!(allergies[0].reactions.as_deref(), Some("hives, facial swelling, shortness of breath"));
+        assert_eq!(allergies[0].status, "active");
+    }
+
+    #[test]
+    fn parse_mumps_json_reads_optional_fields_and_empty_lists() {
+        let output = r#"
+[{"ien":3,"patientIen":42,"drugName":"Smith, Jr. Compound","dose":"5 mg","route":"PO","frequency":"BID","startDate":"20240101","status":"active"},{"ien":4,"patientIen":42,"drugName":"Aspirin","drugCode":"ASA81","dose":"81 mg","route":"PO","frequency":"QD","startDate":"20240102","prescriberIen":9,"status":"active"}]
+"#;
+
+        let medications: Vec<MedicationResponse> = parse_mumps_json(output).unwrap();
+
+        assert_eq!(medications.len(), 2);
+        assert_eq!(medications[0].drug_name, "Smith, Jr. Compound");
+        assert_eq!(medications[0].drug_code, None);
+        assert_eq!(medications[1].prescriber_ien, Some(9));
+        assert!(parse_mumps_json::<Vec<MedicationResponse>>("[]").unwrap().is_empty());
+    }
+
+    #[test]
+    fn soundex_follows_american_rules() {
+        assert_eq!(soundex("Robert").as_deref(), Some("R163"));
+        assert_eq!(soundex("Rupert").as_deref(), Some("R163"));
+        assert_eq!(soundex("Ashcraft").as_deref(), Some("A261"));
+        assert_eq!(soundex("Tymczak").as_deref(), Some("T522"));
+        assert_eq!(soundex("Pfister").as_deref(), Some("P236"));
+        assert_eq!(soundex("Lee").as_deref(), Some("L000"));
+        assert_eq!(soundex("123"), None);
+    }
+
+    #[test]
+    fn search_scores_exact_over_prefix_over_substring_over_soundex() {
+        let score = |last: &str| score_patient_match("SMITH", last, "JOHN", None);
+
+        assert_eq!(score("Smith"), Some(("lastName", 1.0)));
+        assert_eq!(score("Smithson"), Some(("lastName", 0.9)));
+        assert_eq!(score("Goldsmith"), Some(("lastName", 0.75)));
+        assert_eq!(score("Smyth"), Some(("lastName", 0.6)));
+        assert_eq!(score("Jones"), None);
+    }
+
+    #[test]
+    fn search_prefers_names_over_mrn_on_ties() {
+        assert_eq!(score_patient_match("MRN001", "DOE", "JANE", Some("mrn001")), Some(("mrn", 1.0)));
+        assert_eq!(score_patient_match("JANE", "DOE", "JANE", Some("JANE")), Some(("firstName", 1.0)));
+        assert_eq!(score_patient_match("DOE", "DOE", "DOE", Some("DOE")), Some(("lastName", 1.0)));
+    }
+
+    #[test]
+    fn search_term_drops_mumps_delimiters() {
+        assert_eq!(normalize_search_term("  smith, john "), "SMITH JOHN");
+        assert_eq!(normalize_search_term("o'brien^\"X\""), "O'BRIENX");
+        assert_eq!(normalize_search_term(" , ^ "), "");
+    }
+
+    fn candidates() -> Vec<(i64, String, String)> {
+        vec![
+            (12, "SMITH,JOHN".to_string(), "1980-04-12".to_string()),
+            (15, "SMITHSON,JOHN".to_string(), "1975-01-30".to_string()),
+        ]
+    }
+
+    #[test]
+    fn duplicate_check_allows_same_name_with_different_dob() {
+        assert_eq!(find_duplicate_patient(&candidates(), "Smith", "John", "1991-09-01"), None);
+    }
+
+    #[test]
+    fn duplicate_check_flags_exact_duplicate() {
+        let duplicate = find_duplicate_patient(&candidates(), "smith", "john", "1980-04-12");
+
+        assert_eq!(
+            duplicate,
+            Some(DuplicatePatientResponse {
+                existing_ien: 12,
+                name: "SMITH,JOHN".to_string(),
+                date_of_birth: "1980-04-12".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn duplicate_check_matches_similar_but_not_partial_names() {
+        // Same Soundex for both parts: likely a misspelling of the same person
+        let similar = find_duplicate_patient(&candidates(), "Smyth", "Jon", "1980-04-12");
+        assert_eq!(similar.map(|d| d.existing_ien), Some(12));
+
+        // Only a prefix of the existing last name, with a different Soundex code
+        assert_eq!(find_duplicate_patient(&candidates(), "Smith", "John", "1975-01-30"), None);
+    }
+
+    #[test]
+    fn parses_patient_creation_results() {
+        assert_eq!(parse_patient_creation("OK^42\n"), Ok(PatientCreation::Created(42)));
+        assert_eq!(parse_patient_creation("BUSY"), Ok(PatientCreation::Busy));
+        assert_eq!(
+            parse_patient_creation("12^SMITH,JOHN^1980-04-12\n15^SMYTH,JON^1980-04-12\nCANDIDATES"),
+            Ok(PatientCreation::Candidates(vec![
+                (12, "SMITH,JOHN".to_string(), "1980-04-12".to_string()),
+                (15, "SMYTH,JON".to_string(), "1980-04-12".to_string()),
+            ]))
+        );
+        assert!(parse_patient_creation("12^SMITH,JOHN^1980-04-12\nOK^42").is_err());
+        assert!(parse_patient_creation("").is_err());
+    }
+
+    #[test]
+    fn create_patient_routine_clears_known_candidates() {
+        let req: CreatePatientRequest = serde_json::from_value(serde_json::json!({
+            "firstName": "John",
+            "lastName": "Smith",
+            "sex": "M",
+            "dateOfBirth": "1980-04-12",
+        }))
+        .unwrap();
+
+        let code = create_patient_code(&req, false, &[12, 15]);
+        assert!(code.contains(r#"P="S",DOB="1980-04-12",CL="^12^15^""#));
+        assert!(code.contains("L +^DPT(0):5"));
+        assert!(create_patient_code(&req, true, &[]).contains(r#"I R="",'1,P'="""#));
+    }
+
+    #[test]
+    fn interaction_table_parses() {
+        assert!(!interaction_rules().unwrap().is_empty());
+    }
+
+    #[test]
+    fn cds_rules_raise_vital_and_polypharmacy_alerts() {
+        let mut patient = PatientContext {
+            age: Some(70),
+            medications: (1..=8).map(|i| format!("Medication {}", i)).collect(),
+            ..Default::default()
+        };
+        add_vital_facts(&mut patient.vitals, "oxygen_saturation", "88");
+        add_vital_facts(&mut patient.vitals, "blood_pressure", "128/84");
+
+        let alerts = cds_evaluator().evaluate(&patient);
+        let types: Vec<&str> = alerts.iter().map(|a| a.alert_type.as_str()).collect();
+        assert_eq!(types, vec!["polypharmacy", "hypoxemia"]);
+        assert_eq!(alerts[1].severity, "critical");
+        assert_eq!(alerts[1].message, "SpO2 88% is below 90%.");
+    }
+
+    #[test]
+    fn critical_polypharmacy_score_raises_cds_alert() {
+        let patient = PatientContext {
+            medications: vec![
+                "Warfarin 5 mg".to_string(),
+                "Aspirin 81 mg".to_string(),
+                "Ibuprofen 400 mg".to_string(),
+                "Amiodarone 200 mg".to_string(),
+                "Metformin 500 mg".to_string(),
+                "Lisinopril 10 mg".to_string(),
+                "Atorvastatin 20 mg".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let score = polypharmacy_score(&patient.medications).unwrap();
+        assert_eq!(score.count, 7);
+        assert_eq!(score.overall_risk, RiskLevel::Critical);
+
+        let alerts = patient_cds_alerts(&patient).unwrap();
+        let alert = alerts.iter().find(|a| a.alert_type == "polypharmacy_risk").unwrap();
+        assert_eq!(alert.severity, "critical");
+        assert!(alert.related_items.contains(&"Warfarin 5 mg + Aspirin 81 mg".to_string()));
+    }
+
+    #[test]
+    fn low_polypharmacy_score_raises_no_alert() {
+        let medications: Vec<String> = ["Metformin", "Lisinopril", "Atorvastatin", "Omeprazole"]
+            .iter()
+            .map(|m| m.to_string())
+            .collect();
+        let score = polypharmacy_score(&medications).unwrap();
+        assert_eq!(score.overall_risk, RiskLevel::Low);
+        assert!(polypharmacy_alert(&score).is_none());
+    }
+
+    #[test]
+    fn find_interactions_matches_either_direction() {
+        let medications = vec!["Warfarin 5 mg".to_string(), "Metformin".to_string()];
+
+        let interactions = find_interactions("aspirin", &medications, interaction_rules().unwrap());
+
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions[0].drug_b, "Warfarin 5 mg");
+        assert_eq!(interactions[0].severity, "major");
+        assert!(find_interactions("acetaminophen", &medications, interaction_rules().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn parse_mumps_json_rejects_malformed_output() {
+        let output = r#"[{"ien":1,"diagnosis":"Hypertension","patientIen":}]"#;
+
+        assert!(parse_mumps_json::<Vec<ProblemResponse>>(output).is_err());
+    }
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn ccd_format_follows_accept_header() {
+        assert_eq!(negotiate_ccd_format(&HeaderMap::new()), Some(CcdFormat::Xml));
+        assert_eq!(negotiate_ccd_format(&accept("application/xml")), Some(CcdFormat::Xml));
+        assert_eq!(negotiate_ccd_format(&accept("text/html, */*;q=0.8")), Some(CcdFormat::Xml));
+        assert_eq!(negotiate_ccd_format(&accept("application/json+fhir")), Some(CcdFormat::FhirBundle));
+        assert_eq!(negotiate_ccd_format(&accept("application/fhir+json; fhirVersion=4.0")), Some(CcdFormat::FhirBundle));
+        assert_eq!(negotiate_ccd_format(&accept("application/json")), None);
+    }
+
+    fn vital(value: &str, taken_at: &str) -> VitalResponse {
+        VitalResponse {
+            ien: 1,
+            patient_ien: 1,
+            visit_ien: None,
+            vital_type: "blood_pressure".to_string(),
+            value: value.to_string(),
+            unit: "mmHg".to_string(),
+            taken_at: taken_at.to_string(),
+            taken_by: None,
+        }
+    }
+
+    #[test]
+    fn blood_pressure_trend_splits_systolic_and_diastolic() {
+        let vitals = vec![
+            vital("120/80", "20240301.080000"),
+            vital("130/85", "20240302.080000"),
+            vital("garbled", "20240303.080000"),
+        ];
+        let (systolic, diastolic) = vital_trend_readings(&vitals);
+        assert_eq!(systolic.iter().map(|r| r.value).collect::<Vec<_>>(), vec![120.0, 130.0]);
+        assert_eq!(diastolic.iter().map(|r| r.value).collect::<Vec<_>>(), vec![80.0, 85.0]);
+
+        let trend = VitalTrendCalculator::default().calculate(&systolic);
+        assert!((trend.slope - 10.0).abs() < 1e-9);
+    }
+
+    /// Store word-processing nodes as MUMPS would, keyed numerically, and
+    /// print them back the way `read_document_content` does
+    fn store_and_read(nodes: &str) -> String {
+        let mut stored = std::collections::BTreeMap::new();
+        for line in nodes.lines() {
+            let Some((subscripts, value)) = line.split_once(")=\"") else { continue };
+            let Some(line_number) = subscripts
+                .strip_suffix(",0")
+                .and_then(|s| s.rsplit(',').next())
+                .and_then(|n| n.parse::<u32>().ok())
+                .filter(|&n| n > 0)
+            else {
+                continue;
+            };
+            let value = value.strip_suffix('"').unwrap_or(value).replace("\"\"", "\"");
+            stored.insert(line_number, value);
+        }
+        let mut output = String::from("FOUND\n");
+        for text in stored.values() {
+            output.push_str(&format!("T {}\n", text));
+        }
+        output.trim().to_string()
+    }
+
+    #[test]
+    fn long_document_keeps_line_order() {
+        let content: String = (1..=500)
+            .map(|n| format!("Line {} of the \"progress\" note", n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let nodes = word_processing_nodes("^TIU(8925,IEN,\"TEXT\"", &content, "20240301.080000");
+        assert!(nodes.contains("S ^TIU(8925,IEN,\"TEXT\",0)=\"^^500^500^20240301.080000^\""));
+
+        let text = parse_document_text(&store_and_read(&nodes)).ok().flatten().unwrap_or_default();
+        assert_eq!(text.content.as_deref(), Some(content.as_str()));
+        assert!(text.addenda.is_empty());
+    }
+
+    #[test]
+    fn document_text_splits_addenda() {
+        let output = "FOUND\nT Original line 1\nT \nT Original line 3\nA 1^20240302.090000^42\nT Correction\nA 2^20240303.100000^0\nT";
+        let text = parse_document_text(output).ok().flatten().unwrap_or_default();
+
+        assert_eq!(text.content.as_deref(), Some("Original line 1\n\nOriginal line 3"));
+        assert_eq!(
+            text.addenda,
+            vec![
+                DocumentAddendum {
+                    number: 1,
+                    amended_at: "20240302.090000".to_string(),
+                    author_ien: Some(42),
+                    content: "Correction".to_string(),
+                },
+                DocumentAddendum {
+                    number: 2,
+                    amended_at: "20240303.100000".to_string(),
+                    author_ien: None,
+                    content: String::new(),
+                },
+            ]
+        );
+        assert_eq!(parse_document_text("NOTFOUND"), Ok(None));
+        assert!(parse_document_text("%YDB-E-UNDEF").is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the health-yottadb container"]
+    async fn problem_text_with_json_delimiters_round_trips_through_mumps() {
+        if MUMPS_POOL.get().is_none() {
+            let _ = MUMPS_POOL.set(MumpsPool::new(1).unwrap());
+        }
+        let diagnosis = "Fracture, \"left\" arm\tC:\\notes";
+        let patient_ien = 999_999;
+        let stored = run_mumps(&format!(
+            r#"N IEN S IEN=$O(^AUPNPROB(""),-1)+1 S ^AUPNPROB(IEN,0)="{}^{}^S52.5^^^A",^AUPNPROB("C",{},IEN)="" W IEN"#,
+            mumps_escape(diagnosis),
+            patient_ien,
+            patient_ien
+        ))
+        .await
+        .unwrap();
+
+        let problems = query_patient_problems(patient_ien).await;
+        run_mumps(&format!(r#"K ^AUPNPROB({0}),^AUPNPROB("C",{1},{0})"#, stored.trim(), patient_ien)).await.unwrap();
+
+        let problems = problems.unwrap();
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].diagnosis, diagnosis);
+        assert_eq!(problems[0].icd_code.as_deref(), Some("S52.5"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the health-yottadb container"]
+    async fn document_content_round_trips_through_mumps() {
+        if MUMPS_POOL.get().is_none() {
+            let _ = MUMPS_POOL.set(MumpsPool::new(1).unwrap());
+        }
+        let app = Router::new()
+            .route("/api/v1/ehr/documents", post(create_document))
+            .route("/api/v1/ehr/documents/{id}", get(get_document))
+            .route("/api/v1/ehr/documents/{id}/content", get(get_document_content).patch(amend_document_content));
+        let send = |method: &str, uri: String, body: serde_json::Value| {
+            axum::http::Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+        let read_json = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let content: String = (1..=500).map(|n| format!("Line {}", n)).collect::<Vec<_>>().join("\n");
+        let body = serde_json::json!({
+            "patientIen": 1,
+            "documentType": "progress_note",
+            "title": "Long note",
+            "content": content,
+        });
+        let response = app.clone().oneshot(send("POST", "/api/v1/ehr/documents".to_string(), body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let ien = read_json(response).await["ien"].as_i64().unwrap();
+
+        let response = app.clone().oneshot(send("GET", format!("/api/v1/ehr/documents/{}", ien), serde_json::Value::Null)).await.unwrap();
+        assert!(read_json(response).await["content"].is_null());
+        let response = app.clone().oneshot(send("GET", format!("/api/v1/ehr/documents/{}/content", ien), serde_json::Value::Null)).await.unwrap();
+        assert_eq!(read_json(response).await["content"].as_str(), Some(content.as_str()));
+
+        let amendment = serde_json::json!({ "content": "Addendum text" });
+        let response = app.clone().oneshot(send("PATCH", format!("/api/v1/ehr/documents/{}/content", ien), amendment)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.oneshot(send("GET", format!("/api/v1/ehr/documents/{}?include_content=true", ien), serde_json::Value::Null)).await.unwrap();
+        let document = read_json(response).await;
+        assert_eq!(document["status"], "amended");
+        assert_eq!(document["addenda"][0]["content"], "Addendum text");
+    }
+
+    fn schedule_rows() -> ScheduleRows {
+        parse_mumps_json(
+            r#"{"hours":[{"date":"20240604","start":"09:00","end":"12:00","slotMinutes":30,"blocked":[{"time":"10:00","durationMinutes":30}]}],
+               "appointments":[{"ien":41,"date":"2024-06-03","time":"09:00","durationMinutes":30},
+                               {"ien":42,"date":"2024-06-04","time":"09:00","durationMinutes":30}]}"#,
+        )
+        .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    #[test]
+    fn provider_schedules_use_stored_hours_or_defaults() {
+        let from = chrono::NaiveDate::from_ymd_opt(2024, 6, 3).unwrap_or_default();
+        let schedules = provider_schedules(7, from, 2, schedule_rows());
+        assert_eq!(schedules.len(), 2);
+
+        assert_eq!(schedules[0].start_time.format("%H:%M").to_string(), "08:00");
+        assert_eq!(schedules[0].appointments.len(), 1);
+
+        let slots = schedules[1].slots();
+        assert_eq!(slots.len(), 6);
+        assert_eq!(slots[0].status, SlotStatus::Booked);
+        assert_eq!(slots[1].status, SlotStatus::Available);
+        assert_eq!(slots[2].status, SlotStatus::Blocked);
+    }
+
+    #[test]
+    fn back_to_back_booking_of_same_provider_slot_is_rejected() {
+        let from = chrono::NaiveDate::from_ymd_opt(2024, 6, 4).unwrap_or_default();
+        let availability = ProviderAvailabilityService::new(provider_schedules(7, from, 2, schedule_rows()));
+
+        let same_slot = parse_appointment_start("2024-06-04", "09:00").unwrap_or_default();
+        assert_eq!(availability.find_conflict(7, same_slot, 30), Some(42));
+        let next_slot = parse_appointment_start("2024-06-04", "09:30").unwrap_or_default();
+        assert_eq!(availability.find_conflict(7, next_slot, 30), None);
+        assert_eq!(
+            availability.find_next_available(7, from, 30),
+            parse_appointment_start("2024-06-04", "09:30")
+        );
+    }
+
+    #[test]
+    fn booking_outcome_parses_routine_result() {
+        assert_eq!(parse_booking_outcome("OK^42\n"), Ok(BookingOutcome::Booked(42)));
+        assert_eq!(parse_booking_outcome("BUSY"), Ok(BookingOutcome::Busy));
+        assert_eq!(
+            parse_booking_outcome("CONFLICT^C^51^2024-06-04^09:00"),
+            Ok(BookingOutcome::Conflict {
+                reason: ConflictReason::PatientConflict,
+                ien: 51,
+                start: parse_appointment_start("2024-06-04", "09:00").unwrap_or_default(),
+            })
+        );
+        assert!(matches!(
+            parse_booking_outcome("CONFLICT^P^7^2024-06-04^13:15"),
+            Ok(BookingOutcome::Conflict { reason: ConflictReason::ProviderConflict, ien: 7, .. })
+        ));
+        assert!(parse_booking_outcome("CONFLICT^X^7^2024-06-04^13:15").is_err());
+        assert!(parse_booking_outcome("").is_err());
+    }
+
+    #[test]
+    fn conflict_response_reports_reason() {
+        let conflict = ConflictResponse {
+            error: "Provider 7 is already booked at 2024-06-04 09:00".to_string(),
+            reason: ConflictReason::ProviderConflict,
+            conflicting_ien: 42,
+            conflicting_time: "2024-06-04T09:00".to_string(),
+            next_available: None,
+        };
+        let json = serde_json::to_value(conflict).unwrap_or_default();
+        assert_eq!(json["reason"], "provider_conflict");
+        assert_eq!(json["conflictingIen"], 42);
+        assert_eq!(json["conflictingTime"], "2024-06-04T09:00");
+        assert!(json.get("nextAvailable").is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "requires the health-yottadb container"]
+    async fn force_overrides_patient_but_not_provider_conflicts() {
+        if MUMPS_POOL.get().is_none() {
+            let _ = MUMPS_POOL.set(MumpsPool::new(1).unwrap());
+        }
+        let app = Router::new().route("/api/v1/ehr/appointments", post(create_appointment));
+        let book = |uri: &str, patient_ien: i64, provider_ien: i64, time: &str| {
+            let body = serde_json::json!({
+                "patientIen": patient_ien,
+                "appointmentDate": "2031-03-03",
+                "appointmentTime": time,
+                "appointmentType": "follow_up",
+                "providerIen": provider_ien,
+                "durationMinutes": 60,
+            });
+            axum::http::Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap()
+        };
+        let read_json = |response: axum::response::Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+        let patient_ien = 900_000 + i64::from(chrono::Utc::now().timestamp_subsec_micros() % 90_000);
+        let uri = "/api/v1/ehr/appointments";
+
+        let response = app.clone().oneshot(book(uri, patient_ien, 9901, "09:00")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Same patient, different provider, overlapping: rejected unless forced
+        let response = app.clone().oneshot(book(uri, patient_ien, 9902, "09:30")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(read_json(response).await["reason"], "patient_conflict");
+        let response = app.clone().oneshot(book("/api/v1/ehr/appointments?force=true", patient_ien, 9902, "09:30")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // Same provider, different patient: rejected even when forced
+        let response = app.clone().oneshot(book("/api/v1/ehr/appointments?force=true", patient_ien + 1, 9901, "09:15")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(read_json(response).await["reason"], "provider_conflict");
+
+        // Back to back with the patient's last appointment
+        let response = app.oneshot(book(uri, patient_ien, 9903, "10:30")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+}
@@ -0,0 +1,166 @@
+//! Prometheus metrics for the EHR API
+//!
+//! Handlers time themselves with `handler_timer` at entry; `track_requests`
+//! counts responses under the same handler name once they complete. MUMPS
+//! execution is timed separately in `run_mumps` so slow routines can be told
+//! apart from handler overhead.
+
+use std::cell::Cell;
+use std::sync::LazyLock;
+
+use axum::{
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    core::Collector, Encoder, Histogram, HistogramOpts, HistogramTimer, HistogramVec, IntCounterVec,
+    IntGauge, Opts, Registry, TextEncoder,
+};
+
+/// Label for requests that never reached a timed handler (404s, rejections)
+const UNMATCHED_HANDLER: &str = "unmatched";
+
+pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+static REQUESTS_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("yottadb_requests_total", "HTTP requests handled, by handler and status"),
+        &["handler", "status"],
+    ))
+});
+
+static REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(HistogramVec::new(
+        HistogramOpts::new("yottadb_request_duration_seconds", "Handler duration including MUMPS calls"),
+        &["handler"],
+    ))
+});
+
+static MUMPS_EXEC_DURATION: LazyLock<Histogram> = LazyLock::new(|| {
+    register(Histogram::with_opts(HistogramOpts::new(
+        "yottadb_mumps_exec_duration_seconds",
+        "Time spent executing MUMPS code in the worker pool",
+    )))
+});
+
+static PATIENTS_TOTAL: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(IntGauge::new("yottadb_patients_total", "Patients registered in ^DPT"))
+});
+
+static LOW_STOCK_ITEMS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(IntGauge::new("yottadb_low_stock_items", "Inventory items at or below their reorder level"))
+});
+
+tokio::task_local! {
+    /// Name of the handler serving the current request, set by `handler_timer`
+    static HANDLER: Cell<&'static str>;
+}
+
+fn register<C: Collector + Clone + 'static>(collector: prometheus::Result<C>) -> C {
+    let collector = collector.expect("valid metric definition");
+    REGISTRY.register(Box::new(collector.clone())).expect("metric registered once");
+    collector
+}
+
+/// Register every metric so `/metrics` lists them before first use
+pub fn init() {
+    LazyLock::force(&REQUESTS_TOTAL);
+    LazyLock::force(&REQUEST_DURATION);
+    LazyLock::force(&MUMPS_EXEC_DURATION);
+    LazyLock::force(&PATIENTS_TOTAL);
+    LazyLock::force(&LOW_STOCK_ITEMS);
+}
+
+/// Start timing a handler; the duration is observed when the guard drops
+pub fn handler_timer(handler: &'static str) -> HistogramTimer {
+    let _ = HANDLER.try_with(|name| name.set(handler));
+    REQUEST_DURATION.with_label_values(&[handler]).start_timer()
+}
+
+/// Start timing a MUMPS call
+pub fn mumps_timer() -> HistogramTimer {
+    MUMPS_EXEC_DURATION.start_timer()
+}
+
+pub fn set_patients_total(count: i64) {
+    PATIENTS_TOTAL.set(count);
+}
+
+pub fn patient_created() {
+    PATIENTS_TOTAL.inc();
+}
+
+pub fn set_low_stock_items(count: usize) {
+    LOW_STOCK_ITEMS.set(count as i64);
+}
+
+/// Count each response under the handler that produced it
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let (handler, response) = HANDLER
+        .scope(Cell::new(UNMATCHED_HANDLER), async move {
+            let response = next.run(request).await;
+            (HANDLER.with(Cell::get), response)
+        })
+        .await;
+    REQUESTS_TOTAL
+        .with_label_values(&[handler, response.status().as_str()])
+        .inc();
+    response
+}
+
+/// `GET /metrics` in the Prometheus text format
+pub async fn metrics_handler() -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    match encoder.encode(&REGISTRY.gather(), &mut body) {
+        Ok(()) => (StatusCode::OK, [(header::CONTENT_TYPE, encoder.format_type().to_string())], body).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// Current value of `yottadb_requests_total` for a handler and status
+#[cfg(test)]
+pub fn requests_total(handler: &str, status: &str) -> u64 {
+    REQUESTS_TOTAL.with_label_values(&[handler, status]).get()
+}
+
+/// Current value of `yottadb_patients_total`
+#[cfg(test)]
+pub fn patients_total() -> i64 {
+    PATIENTS_TOTAL.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn timed() -> StatusCode {
+        let _timer = handler_timer("timed");
+        StatusCode::ACCEPTED
+    }
+
+    #[tokio::test]
+    async fn counts_requests_under_handler_name() {
+        let app = Router::new()
+            .route("/timed", get(timed))
+            .route("/metrics", get(metrics_handler))
+            .layer(axum::middleware::from_fn(track_requests));
+        let before = requests_total("timed", "202");
+
+        for _ in 0..2 {
+            let request = Request::builder().uri("/timed").body(Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+        assert_eq!(requests_total("timed", "202"), before + 2);
+
+        let request = Request::builder().uri("/metrics").body(Body::empty()).unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("yottadb_request_duration_seconds_count{handler=\"timed\"}"));
+    }
+}
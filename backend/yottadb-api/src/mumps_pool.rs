@@ -0,0 +1,289 @@
+//! Pool of persistent YottaDB direct-mode processes
+//!
+//! Spawning `docker exec` per call costs 80–200 ms of process start-up, so
+//! MUMPS code is instead written to long-lived `yottadb -direct` workers over
+//! stdin. Each call starts by releasing locks, rolling back any open
+//! transaction and killing locals left by the previous call, and is followed
+//! by a sentinel write carrying `$ZSTATUS`; output is read up to the sentinel
+//! line. A worker that crashes, times out or raises a MUMPS error is killed
+//! and replaced.
+
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{Mutex, Semaphore};
+
+/// Workers started when `MUMPS_POOL_SIZE` is not set
+pub const DEFAULT_POOL_SIZE: usize = 4;
+
+/// Per-call timeout when `MUMPS_TIMEOUT_SECS` is not set
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Line written after every call to mark the end of its output
+const END_SENTINEL: &str = "##END##";
+
+/// Written before every call so a reused worker holds no locks, open
+/// transaction, locals or error status from the previous call
+const RESET: &str = r#"L  TROLLBACK:$TLEVEL  K  S $ZSTATUS="""#;
+
+/// Direct-mode prompt, printed before every line read from stdin
+const PROMPT: &str = "YDB>";
+
+/// YottaDB container name
+const CONTAINER: &str = "health-yottadb";
+
+/// A value that can go up and down, read by the health endpoint
+#[derive(Debug, Default)]
+pub struct Gauge(AtomicI64);
+
+impl Gauge {
+    pub fn set(&self, value: i64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    pub fn inc(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn dec(&self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// One `yottadb -direct` process
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    fn spawn() -> Result<Self, String> {
+        let mut child = Command::new("docker")
+            .arg("exec")
+            .arg("-i")
+            .arg(CONTAINER)
+            .arg("bash")
+            .arg("-c")
+            .arg(r#". /opt/yottadb/current/ydb_env_set && export ydb_routines="/data/r $ydb_routines" && exec yottadb -direct 2>/dev/null"#)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start YottaDB worker: {}", e))?;
+
+        let stdin = child.stdin.take().ok_or("YottaDB worker has no stdin")?;
+        let stdout = child.stdout.take().ok_or("YottaDB worker has no stdout")?;
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    /// Send `code` and collect its output up to the sentinel line
+    ///
+    /// A MUMPS error raised by `code` is returned as `Err`, so the pool
+    /// replaces the worker.
+    async fn execute(&mut self, code: &str) -> Result<String, String> {
+        let input = call_input(code);
+        self.stdin
+            .write_all(input.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to YottaDB worker: {}", e))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write to YottaDB worker: {}", e))?;
+
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = self
+                .stdout
+                .read_line(&mut line)
+                .await
+                .map_err(|e| format!("Failed to read from YottaDB worker: {}", e))?;
+            if read == 0 {
+                return Err("YottaDB worker exited unexpectedly".to_string());
+            }
+
+            let content = strip_prompts(line.trim_end_matches(['\r', '\n']));
+            if let Some(status) = content.trim().strip_prefix(END_SENTINEL) {
+                if !status.is_empty() {
+                    return Err(format!("MUMPS error: {}", status));
+                }
+                break;
+            }
+            // Lines that held nothing but a prompt are not output
+            if content.is_empty() && line.starts_with(PROMPT) {
+                continue;
+            }
+            lines.push(content.to_string());
+        }
+
+        Ok(lines.join("\n").trim().to_string())
+    }
+}
+
+/// Lines written to a worker for one call: reset, the code, then the sentinel
+fn call_input(code: &str) -> String {
+    let mut input = String::with_capacity(code.len() + RESET.len() + 32);
+    input.push_str(RESET);
+    input.push('\n');
+    for line in code.lines().filter(|line| !line.trim().is_empty()) {
+        input.push_str(line);
+        input.push('\n');
+    }
+    input.push_str(&format!("W !,\"{}\",$ZSTATUS,!\n", END_SENTINEL));
+    input
+}
+
+fn strip_prompts(mut line: &str) -> &str {
+    while let Some(rest) = line.strip_prefix(PROMPT) {
+        line = rest;
+    }
+    line
+}
+
+/// Fixed-size pool of YottaDB workers
+pub struct MumpsPool {
+    idle: Mutex<Vec<Worker>>,
+    slots: Semaphore,
+    timeout: Duration,
+    in_flight: AtomicUsize,
+    available: Gauge,
+}
+
+impl MumpsPool {
+    /// Spawn `size` worker processes
+    pub fn new(size: usize) -> Result<Self, String> {
+        let size = size.max(1);
+        let workers = (0..size).map(|_| Worker::spawn()).collect::<Result<Vec<_>, _>>()?;
+
+        let available = Gauge::default();
+        available.set(size as i64);
+
+        Ok(Self {
+            idle: Mutex::new(workers),
+            slots: Semaphore::new(size),
+            timeout: DEFAULT_TIMEOUT,
+            in_flight: AtomicUsize::new(0),
+            available,
+        })
+    }
+
+    /// Fail calls that take longer than `timeout`; the worker is replaced
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run MUMPS code on the next free worker
+    pub async fn execute(&self, code: &str) -> Result<String, String> {
+        let _permit = self
+            .slots
+            .acquire()
+            .await
+            .map_err(|_| "MUMPS pool is closed".to_string())?;
+        let worker = self.idle.lock().await.pop();
+        let mut worker = match worker {
+            Some(worker) => worker,
+            // A previous restart failed; try again now
+            None => Worker::spawn()?,
+        };
+
+        let in_flight = InFlight::start(self);
+        let result = tokio::time::timeout(self.timeout, worker.execute(code)).await;
+        drop(in_flight);
+
+        let result = match result {
+            Ok(Ok(output)) => {
+                self.idle.lock().await.push(worker);
+                return Ok(output);
+            }
+            Ok(Err(e)) => e,
+            Err(_) => format!("MUMPS call timed out after {:?}", self.timeout),
+        };
+
+        // The worker's stream position or process state is unknown; replace it
+        tracing::warn!("Restarting YottaDB worker: {}", result);
+        let _ = worker.child.kill().await;
+        match Worker::spawn() {
+            Ok(replacement) => self.idle.lock().await.push(replacement),
+            Err(e) => tracing::error!("Failed to restart YottaDB worker: {}", e),
+        }
+        Err(result)
+    }
+
+    /// Calls currently executing
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Relaxed)
+    }
+
+    /// Workers free to take a call
+    pub fn available(&self) -> &Gauge {
+        &self.available
+    }
+}
+
+/// Counts a call as in flight until dropped, including when the caller's
+/// future is cancelled mid-call
+struct InFlight<'a>(&'a MumpsPool);
+
+impl<'a> InFlight<'a> {
+    fn start(pool: &'a MumpsPool) -> Self {
+        pool.in_flight.fetch_add(1, Ordering::Relaxed);
+        pool.available.dec();
+        Self(pool)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.0.available.inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_prompts_removes_leading_prompts_only() {
+        assert_eq!(strip_prompts("YDB>YDB>[{\"ien\":1}"), "[{\"ien\":1}");
+        assert_eq!(strip_prompts("value YDB>"), "value YDB>");
+        assert_eq!(strip_prompts("YDB>"), "");
+    }
+
+    #[test]
+    fn call_input_resets_worker_state_before_code() {
+        let input = call_input("S X=1\n\nW X\n");
+        let lines: Vec<&str> = input.lines().collect();
+
+        assert_eq!(lines, [RESET, "S X=1", "W X", "W !,\"##END##\",$ZSTATUS,!"]);
+        assert!(RESET.starts_with("L  TROLLBACK:$TLEVEL  K"));
+    }
+
+    #[test]
+    fn gauge_tracks_changes() {
+        let gauge = Gauge::default();
+        gauge.set(4);
+        gauge.dec();
+        gauge.dec();
+        gauge.inc();
+
+        assert_eq!(gauge.get(), 3);
+    }
+}
@@ -0,0 +1,195 @@
+//! Cursor pagination for list endpoints
+//!
+//! A page cursor is a short-lived HS256 JWT carrying the last IEN returned and
+//! the list it belongs to. MUMPS resumes `$ORDER` from that IEN, so fetching a
+//! page costs `limit` iterations no matter how deep into the list it is.
+//!
+//! `?offset=` is still accepted for older clients: it is turned into a skip
+//! count on the first page and the response carries `Deprecation: true`.
+
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{
+    http::{HeaderName, HeaderValue},
+    response::Response,
+};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_LIMIT: usize = 50;
+pub const MAX_LIMIT: usize = 500;
+
+/// How long a cursor stays valid after the page that issued it
+const CURSOR_TTL_SECS: u64 = 15 * 60;
+
+/// Secret used to sign cursors; a random per-process key is used when unset,
+/// which invalidates outstanding cursors on restart
+const CURSOR_SECRET_ENV: &str = "PAGINATION_CURSOR_SECRET";
+
+static CURSOR_KEY: LazyLock<Vec<u8>> = LazyLock::new(|| match std::env::var(CURSOR_SECRET_ENV) {
+    Ok(secret) if !secret.is_empty() => secret.into_bytes(),
+    _ => rand::random::<[u8; 32]>().to_vec(),
+});
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    /// Deprecated: use `cursor`
+    pub offset: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CursorClaims {
+    /// Last IEN on the page that issued the cursor
+    after: i64,
+    /// List the cursor was issued for, including its filters
+    scope: String,
+    exp: u64,
+}
+
+/// Where a page starts and how much of it to return
+#[derive(Debug)]
+pub struct Page {
+    /// Resume `$ORDER` after this IEN
+    pub after: i64,
+    /// Matching records to pass over before emitting (legacy `offset`)
+    pub skip: usize,
+    pub limit: usize,
+    scope: String,
+    deprecated_offset: bool,
+}
+
+/// One page of results
+pub struct PageOf<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+impl Page {
+    /// Resolve the page requested for the list identified by `scope`
+    ///
+    /// A cursor takes precedence over `offset`; with neither the page starts
+    /// at the beginning of the list.
+    pub fn from_query(query: &PageQuery, scope: impl Into<String>) -> Result<Self, String> {
+        let scope = scope.into();
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let (after, skip, deprecated_offset) = match (&query.cursor, query.offset) {
+            (Some(cursor), _) => (decode_cursor(cursor, &scope)?, 0, false),
+            (None, Some(offset)) => (0, offset, true),
+            (None, None) => (0, 0, false),
+        };
+        Ok(Self { after, skip, limit, scope, deprecated_offset })
+    }
+
+    /// Records to ask MUMPS for: one past the limit to learn whether more follow
+    pub fn fetch(&self) -> usize {
+        self.limit + 1
+    }
+
+    /// Trim the fetched records to the page and issue a cursor for the next one
+    pub fn finish<T>(&self, mut items: Vec<T>, ien: impl Fn(&T) -> i64) -> PageOf<T> {
+        let has_more = items.len() > self.limit;
+        items.truncate(self.limit);
+        let next_cursor = if has_more {
+            items.last().map(|item| encode_cursor(ien(item), &self.scope, CURSOR_TTL_SECS))
+        } else {
+            None
+        };
+        PageOf { items, next_cursor, has_more }
+    }
+
+    /// Mark the response deprecated when the client paged with `offset`
+    pub fn respond(&self, mut response: Response) -> Response {
+        if self.deprecated_offset {
+            response
+                .headers_mut()
+                .insert(HeaderName::from_static("deprecation"), HeaderValue::from_static("true"));
+        }
+        response
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn encode_cursor(after: i64, scope: &str, ttl_secs: u64) -> String {
+    let claims = CursorClaims { after, scope: scope.to_string(), exp: now_secs() + ttl_secs };
+    jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(&CURSOR_KEY))
+        .expect("HS256 signing does not fail")
+}
+
+fn decode_cursor(token: &str, scope: &str) -> Result<i64, String> {
+    let claims = jsonwebtoken::decode::<CursorClaims>(
+        token,
+        &DecodingKey::from_secret(&CURSOR_KEY),
+        &Validation::new(Algorithm::HS256),
+    )
+    .map_err(|e| match e.kind() {
+        jsonwebtoken::errors::ErrorKind::ExpiredSignature => "Cursor has expired".to_string(),
+        _ => "Invalid cursor".to_string(),
+    })?
+    .claims;
+
+    if claims.scope != scope {
+        return Err("Cursor does not belong to this list".to_string());
+    }
+    Ok(claims.after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(cursor: Option<String>, offset: Option<usize>) -> PageQuery {
+        PageQuery { cursor, limit: Some(2), offset }
+    }
+
+    #[test]
+    fn cursor_resumes_after_last_ien() {
+        let page = Page::from_query(&query(None, None), "patients").unwrap();
+        assert_eq!((page.after, page.skip), (0, 0));
+
+        let first = page.finish(vec![3, 7, 9], |ien| *ien);
+        assert_eq!(first.items, vec![3, 7]);
+        assert!(first.has_more);
+
+        let next = Page::from_query(&query(first.next_cursor, None), "patients").unwrap();
+        assert_eq!(next.after, 7);
+        let last = next.finish(vec![9], |ien| *ien);
+        assert!(!last.has_more);
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[test]
+    fn rejects_foreign_expired_and_tampered_cursors() {
+        let cursor = encode_cursor(7, "prescriptions:1", CURSOR_TTL_SECS);
+        assert!(Page::from_query(&query(Some(cursor.clone()), None), "prescriptions:2").is_err());
+
+        let mut tampered = cursor;
+        tampered.pop();
+        assert!(Page::from_query(&query(Some(tampered), None), "prescriptions:1").is_err());
+
+        let claims = CursorClaims { after: 7, scope: "patients".to_string(), exp: now_secs() - 120 };
+        let expired = jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(&CURSOR_KEY)).unwrap();
+        assert_eq!(
+            Page::from_query(&query(Some(expired), None), "patients").unwrap_err(),
+            "Cursor has expired"
+        );
+    }
+
+    #[test]
+    fn offset_becomes_skip_and_is_flagged_deprecated() {
+        let page = Page::from_query(&query(None, Some(40)), "inventory").unwrap();
+        assert_eq!((page.after, page.skip), (0, 40));
+
+        let response = page.respond(Response::default());
+        assert_eq!(response.headers().get("deprecation").unwrap(), "true");
+        let response = Page::from_query(&query(None, None), "inventory").unwrap().respond(Response::default());
+        assert!(response.headers().get("deprecation").is_none());
+    }
+}
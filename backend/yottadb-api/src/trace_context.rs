@@ -0,0 +1,106 @@
+//! Trace correlation for calls from api-service
+//!
+//! The caller forwards its trace in a W3C `traceparent` (or Jaeger
+//! `uber-trace-id`) header. The trace ID is recorded on the request span and
+//! kept task-local so MUMPS failures can be matched to the originating request.
+
+use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Response};
+use tracing::Instrument;
+
+tokio::task_local! {
+    static TRACE_ID: String;
+}
+
+/// Trace ID from a `traceparent` header: `{version}-{trace-id}-{parent-id}-{flags}`
+pub fn parse_traceparent(value: &str) -> Option<String> {
+    let mut parts = value.trim().split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let parent_id = parts.next()?;
+    let flags = parts.next()?;
+    let valid = version.len() == 2
+        && version != "ff"
+        && is_hex_id(trace_id, 32)
+        && is_hex_id(parent_id, 16)
+        && flags.len() == 2;
+    valid.then(|| trace_id.to_ascii_lowercase())
+}
+
+/// Trace ID from an `uber-trace-id` header: `{trace-id}:{span-id}:{parent-span-id}:{flags}`
+///
+/// Jaeger allows 64-bit trace IDs; they are left-padded to 128 bits like W3C ones.
+pub fn parse_uber_trace_id(value: &str) -> Option<String> {
+    let trace_id = value.trim().split(':').next()?;
+    let valid = (1..=32).contains(&trace_id.len())
+        && trace_id.chars().all(|c| c.is_ascii_hexdigit())
+        && trace_id.chars().any(|c| c != '0');
+    valid.then(|| format!("{:0>32}", trace_id.to_ascii_lowercase()))
+}
+
+fn is_hex_id(id: &str, len: usize) -> bool {
+    id.len() == len && id.chars().all(|c| c.is_ascii_hexdigit()) && id.chars().any(|c| c != '0')
+}
+
+/// Trace ID of the request being handled, if the caller sent one
+pub fn current_trace_id() -> Option<String> {
+    TRACE_ID.try_with(Clone::clone).ok()
+}
+
+fn trace_id_from_headers(headers: &HeaderMap) -> Option<String> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    header("traceparent")
+        .and_then(parse_traceparent)
+        .or_else(|| header("uber-trace-id").and_then(parse_uber_trace_id))
+}
+
+/// Open a request span carrying the caller's trace ID
+pub async fn trace_middleware(request: Request, next: Next) -> Response {
+    let trace_id = trace_id_from_headers(request.headers());
+
+    let span = tracing::info_span!(
+        "request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        trace_id = tracing::field::Empty,
+    );
+    match trace_id {
+        Some(trace_id) => {
+            span.record("trace_id", trace_id.as_str());
+            TRACE_ID.scope(trace_id, next.run(request).instrument(span)).await
+        }
+        None => next.run(request).instrument(span).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_w3c_traceparent() {
+        assert_eq!(
+            parse_traceparent("00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01").as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+        assert_eq!(parse_traceparent("00-00000000000000000000000000000000-00f067aa0ba902b7-01"), None);
+        assert_eq!(parse_traceparent("00-4bf92f3577b34da6-00f067aa0ba902b7-01"), None);
+    }
+
+    #[test]
+    fn parses_jaeger_uber_trace_id() {
+        assert_eq!(
+            parse_uber_trace_id("a3ce929d0e0e4736:00f067aa0ba902b7:0:1").as_deref(),
+            Some("0000000000000000a3ce929d0e0e4736")
+        );
+        assert_eq!(parse_uber_trace_id("not-hex:1:0:1"), None);
+    }
+
+    #[tokio::test]
+    async fn trace_id_is_visible_while_handling_the_request() {
+        assert_eq!(current_trace_id(), None);
+        let seen = TRACE_ID
+            .scope("4bf92f3577b34da6a3ce929d0e0e4736".to_string(), async { current_trace_id() })
+            .await;
+        assert_eq!(seen.as_deref(), Some("4bf92f3577b34da6a3ce929d0e0e4736"));
+    }
+}
@@ -248,11 +248,15 @@ export async function resumeInstance(id: string): Promise<ApiResponse<WorkflowIn
 }
 
 /**
- * Cancel a workflow instance
+ * Cancel a workflow instance, cancelling its open tasks
  */
-export async function cancelInstance(id: string): Promise<ApiResponse<WorkflowInstance>> {
+export async function cancelInstance(
+  id: string,
+  reason: string
+): Promise<ApiResponse<WorkflowInstance>> {
   return apiRequest(API_ROUTES.ADMIN.WORKFLOWS.INSTANCES.CANCEL(id), {
     method: "POST",
+    body: JSON.stringify({ reason }),
   });
 }
 
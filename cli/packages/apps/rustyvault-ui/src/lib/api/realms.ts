@@ -4,6 +4,8 @@ import { API_ROUTES } from "@lazarus-life/shared/api";
 // Alias for convenience
 const VAULT_ROUTES = API_ROUTES.VAULT_DIRECT;
 
+export type RealmStatus = "active" | "suspended" | "deleted";
+
 export interface Realm {
   id: string;
   name: string;
@@ -14,6 +16,8 @@ export interface Realm {
   default_lease_ttl?: number;
   max_lease_ttl?: number;
   is_active?: boolean;
+  status?: RealmStatus;
+  status_reason?: string;
   config?: Record<string, unknown>;
   created_at?: string;
   updated_at?: string;
@@ -96,6 +100,20 @@ export const realmsApi = {
     await apiClient.delete(VAULT_ROUTES.REALMS.DELETE(realmId));
   },
 
+  /**
+   * Suspend a realm, revoking its tokens until it is activated
+   */
+  suspend: async (realmId: string, reason: string): Promise<void> => {
+    await apiClient.post(VAULT_ROUTES.REALMS.SUSPEND(realmId), { reason });
+  },
+
+  /**
+   * Activate a suspended realm
+   */
+  activate: async (realmId: string): Promise<void> => {
+    await apiClient.post(VAULT_ROUTES.REALMS.ACTIVATE(realmId), {});
+  },
+
   /**
    * Get realms by organization ID
    */
@@ -76,6 +76,8 @@ export const API_ROUTES = {
     PERMISSIONS: {
       CHECK: "/v1/admin/permissions/check",
       CHECK_BATCH: "/v1/admin/permissions/check-batch",
+      EXPLAIN_DENIAL: "/v1/admin/permissions/explain-denial",
+      EXPLAIN: "/v1/admin/permissions/explain",
       USER: (id: string) => `/v1/admin/permissions/user/${id}`,
       USER_PAGES: (id: string) => `/v1/admin/permissions/user/${id}/pages`,
       USER_BUTTONS: (id: string, page: string) =>
@@ -105,6 +107,9 @@ export const API_ROUTES = {
       ASSIGN_ROLE: (groupId: string, roleId: string) =>
         `/v1/admin/groups/${groupId}/roles/${roleId}`,
     },
+    USERS: {
+      PROVISIONING_STATUS: (id: string) => `/v1/admin/users/${id}/provisioning-status`,
+    },
     DASHBOARD: {
       STATS: "/v1/admin/dashboard/stats",
     },
@@ -131,6 +136,12 @@ export const API_ROUTES = {
       MASTER_KEY_STATUS: "/v1/admin/encryption/master-key/status",
       MASTER_KEY_ROTATE: "/v1/admin/encryption/master-key/rotate",
       STATS: "/v1/admin/encryption/stats",
+      KEY_CEREMONY_SPLIT: "/v1/admin/encryption/key-ceremony/split",
+      KEY_CEREMONY_RECOVER: "/v1/admin/encryption/key-ceremony/recover",
+    },
+    /** Decision rules */
+    RULES: {
+      BACKTEST: (id: string) => `/v1/admin/rules/${id}/backtest`,
     },
     /** Visual Workflow Designer (n8n-style) */
     WORKFLOWS: {
@@ -144,6 +155,8 @@ export const API_ROUTES = {
         CLONE: (id: string) => `/v1/admin/workflows/${id}/clone`,
         ACTIVATE: (id: string) => `/v1/admin/workflows/${id}/activate`,
         DEACTIVATE: (id: string) => `/v1/admin/workflows/${id}/deactivate`,
+        VALIDATE_CONTEXT: (id: string) => `/v1/admin/workflows/${id}/validate-context`,
+        DIAGRAM: (machineName: string) => `/v1/admin/workflows/${machineName}/diagram`,
       },
       /** Workflow Instances */
       INSTANCES: {
@@ -188,6 +201,8 @@ export const API_ROUTES = {
       HEALTH: "/sys/health",
       SEAL_STATUS: "/sys/seal-status",
       SEAL: "/sys/seal",
+      ROTATE: "/sys/rotate",
+      ROTATE_STATUS: "/sys/rotate/status",
       UNSEAL: "/sys/unseal",
       INIT: "/sys/init",
       KEYS_DOWNLOAD: "/sys/init/keys.txt",
@@ -239,6 +254,8 @@ export const API_ROUTES = {
       UPDATE: (id: string) => `/sys/realm/${id}`,
       DELETE: (id: string) => `/sys/realm/${id}`,
       BY_ORG: (orgId: string) => `/sys/realm/organization/${orgId}`,
+      SUSPEND: (id: string) => `/realms/${id}/suspend`,
+      ACTIVATE: (id: string) => `/realms/${id}/activate`,
     },
 
     // Realm-scoped Applications
@@ -249,6 +266,9 @@ export const API_ROUTES = {
       UPDATE: (realmId: string, appName: string) => `/realm/${realmId}/sys/apps/${appName}`,
       DELETE: (realmId: string, appName: string) => `/realm/${realmId}/sys/apps/${appName}`,
       REGISTER_DEFAULTS: (realmId: string) => `/realm/${realmId}/sys/apps/register-defaults`,
+      ROTATE_SECRET: (realmId: string, appId: string) =>
+        `/realm/${realmId}/applications/${appId}/rotate-secret`,
+      CLIENT_LOGIN: (realmId: string) => `/realm/${realmId}/auth/client/login`,
     },
 
     // Realm-scoped AppRole
@@ -314,6 +334,7 @@ export const API_ROUTES = {
       DELETE: (id: string) => `/v1/ehr/patients/${id}`,
       SEARCH: "/v1/ehr/patients/search",
       BANNER: (id: string) => `/v1/ehr/patients/${id}/banner`,
+      SUMMARY: (ien: number) => `/v1/ehr/patients/${ien}/summary`,
       FIND_DUPLICATES: "/v1/ehr/patients/find-duplicates",
       MERGE: "/v1/ehr/patients/merge",
     },
@@ -399,6 +420,7 @@ export const API_ROUTES = {
     DOCUMENTS: {
       LIST: "/v1/ehr/documents",
       GET: (id: string) => `/v1/ehr/documents/${id}`,
+      CONTENT: (id: string) => `/v1/ehr/documents/${id}/content`,
       CREATE: "/v1/ehr/documents",
       UPDATE: (id: string) => `/v1/ehr/documents/${id}`,
       DELETE: (id: string) => `/v1/ehr/documents/${id}`,
@@ -439,6 +461,7 @@ export const API_ROUTES = {
       BY_LOCATION: (locationId: string) => `/v1/ehr/locations/${locationId}/appointments`,
       CHECK_IN: (id: string) => `/v1/ehr/appointments/${id}/check-in`,
       CANCEL: (id: string) => `/v1/ehr/appointments/${id}/cancel`,
+      STREAM: "/v1/ehr/appointments/stream",
       RESCHEDULE: (id: string) => `/v1/ehr/appointments/${id}/reschedule`,
       NO_SHOW: (id: string) => `/v1/ehr/appointments/${id}/no-show`,
       TODAY: "/v1/ehr/appointments/today",
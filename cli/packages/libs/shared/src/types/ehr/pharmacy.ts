@@ -266,7 +266,14 @@ export interface DrugAllergyCheckResponse {
     reactions?: string;
     status: string;
   }[];
-  matchedAllergens: string[];
+  matchedAllergens: {
+    allergyIen: number;
+    allergen: string;
+    /** "direct" name match or "class" cross-reactivity match */
+    matchType: "direct" | "class";
+    drugClass: string | null;
+    severity: string;
+  }[];
 }
 
 /** Catalog list response */
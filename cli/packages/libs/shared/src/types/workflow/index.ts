@@ -99,14 +99,23 @@ export interface NodeConfig {
 
 /** Escalation configuration */
 export interface EscalationConfig {
-  /** Time before escalation (e.g., "+1d") */
-  after: string;
-  /** Escalation target */
+  /** Escalation chain, in order */
+  levels: EscalationLevel[];
+  /** What happens when nobody along the chain accepts the task */
+  finalAction?: FinalAction;
+}
+
+/** One step of an escalation chain */
+export interface EscalationLevel {
+  /** Minutes the current assignee has to accept the task */
+  afterMinutes: number;
+  /** Escalation target (user ID or group name) */
   escalateTo: string;
-  /** Escalation action */
-  action: string;
 }
 
+/** What happens to a task nobody accepted */
+export type FinalAction = "auto_complete" | "auto_cancel" | "hard_alert";
+
 // ============================================================================
 // Edge Types
 // ============================================================================
@@ -235,7 +244,7 @@ export interface ExecutionStep {
 // ============================================================================
 
 /** Human task status */
-export type TaskStatus = "pending" | "claimed" | "completed" | "expired" | "cancelled";
+export type TaskStatus = "pending" | "claimed" | "completed" | "expired" | "cancelled" | "overdue";
 
 /** A human task for user interaction */
 export interface HumanTask {
@@ -269,6 +278,14 @@ export interface HumanTask {
   completedAt?: string;
   /** Result data */
   result?: unknown;
+  /** When the current assignee was assigned */
+  assignedAt: string;
+  /** Minutes the current assignee has to accept the task */
+  slaMinutes?: number;
+  /** Next escalation target */
+  escalateTo?: string;
+  /** Times the task has been escalated */
+  escalationCount: number;
 }
 
 // ============================================================================
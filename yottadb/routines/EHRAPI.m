@@ -276,8 +276,8 @@ JSON(STATUS,BODY) ; Build HTTP response
 ERROR(CODE,MSG) Q $$JSON(CODE,"{""error"":"""_$$ESC(MSG)_"""}")
  ;
 ESC(S) ; Escape for JSON
- N I,C,O S O=""
- F I=1:1:$L(S) S C=$E(S,I) S:C="""" C="\""" S:C="\" C="\\" S O=O_C
+ N I,C,A,H,O S O="",H="0123456789abcdef"
+ F I=1:1:$L(S) S C=$E(S,I),A=$A(C) S:C="""" C="\""" S:C="\" C="\\" S:A<32 C="\u00"_$E(H,A\16+1)_$E(H,A#16+1) S O=O_C
  Q O
  ;
 JSONVAL(J,K) ; Extract JSON value
@@ -8,6 +8,8 @@ INITVISTA ; Initialize VistA-style globals for EHR ;2024-01-01
  ;   ^PS - Pharmacy/Medications (File #52)
  ;   ^GMR - Vitals (File #120.5)
  ;   ^GMRA - Allergies (File #120.8)
+ ;   ^GMRACI - Allergy/drug class contraindications
+ ;   ^PSDCLS - Drug class mappings
  ;   ^LR - Lab Results (File #63)
  ;   ^TIU - Clinical Documents (File #8925)
  ;   ^OR - Orders (File #100)
@@ -195,6 +197,8 @@ RESET ; Reset all globals (DANGEROUS - for testing only)
  K ^PS
  K ^GMR
  K ^GMRA
+ K ^GMRACI
+ K ^PSDCLS
  K ^LR
  K ^TIU
  K ^OR
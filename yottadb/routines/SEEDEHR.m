@@ -22,6 +22,7 @@ EN ; Entry point
  D VISITS     ; Create visits/encounters
  D PROBLEMS   ; Create problem lists
  D ALLERGIES  ; Create allergies
+ D DRUGCLASS  ; Drug class cross-reactivity lookups
  D VITALS     ; Create vital signs with trends
  D MEDS       ; Create medications
  D LABS       ; Create lab results
@@ -283,6 +284,35 @@ ALLERGIES ; Create allergy records
  W "    Created "_IEN_" allergies",!
  Q
  ;
+DRUGCLASS ; Drug class mappings and allergy cross-reactivity
+ W "  Creating drug class lookups...",!
+ ;
+ ; drug_class_mappings: ^PSDCLS(DRUG_CODE,DRUG_CLASS)=""
+ S ^PSDCLS("AMOXICILLIN","PENICILLINS")=""
+ S ^PSDCLS("AMPICILLIN","PENICILLINS")=""
+ S ^PSDCLS("PIPERACILLIN","PENICILLINS")=""
+ S ^PSDCLS("CEPHALEXIN","CEPHALOSPORINS")=""
+ S ^PSDCLS("CEFAZOLIN","CEPHALOSPORINS")=""
+ S ^PSDCLS("CEFTRIAXONE","CEPHALOSPORINS")=""
+ S ^PSDCLS("SULFAMETHOXAZOLE","SULFONAMIDES")=""
+ S ^PSDCLS("SULFAMETHOXAZOLE/TRIMETHOPRIM","SULFONAMIDES")=""
+ S ^PSDCLS("SULFASALAZINE","SULFONAMIDES")=""
+ S ^PSDCLS("IBUPROFEN","NSAIDS")=""
+ S ^PSDCLS("NAPROXEN","NSAIDS")=""
+ S ^PSDCLS("KETOROLAC","NSAIDS")=""
+ S ^PSDCLS("ASPIRIN","NSAIDS")=""
+ ;
+ ; allergy_drug_class_contraindications: ^GMRACI(ALLERGY_PATTERN,CONTRAINDICATED_CLASS)=SEVERITY
+ ; Patterns are matched as substrings of the uppercased allergen
+ S ^GMRACI("PENICILLIN","PENICILLINS")="SE"
+ S ^GMRACI("PENICILLIN","CEPHALOSPORINS")="MI"
+ S ^GMRACI("SULFA","SULFONAMIDES")="SE"
+ S ^GMRACI("NSAID","NSAIDS")="SE"
+ S ^GMRACI("ASPIRIN","NSAIDS")="MO"
+ ;
+ W "    Created drug class lookups",!
+ Q
+ ;
 VITALS ; Create vital signs with realistic trends
  W "  Creating vital signs...",!
  N IEN S IEN=0
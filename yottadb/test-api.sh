@@ -62,4 +62,10 @@ echo "----------------------"
 run_mumps 'S IEN=$O(^DPT(""),-1) W "Latest patient: ",$P($G(^DPT(IEN,0)),"^",1)," (IEN="_IEN_")",!'
 echo ""
 
+echo "9. Penicillin Allergy vs Amoxicillin (class cross-reactivity):"
+echo "---------------------------------------------------------------"
+run_mumps 'S APAT="" F  S APAT=$O(^GMRACI(APAT)) Q:APAT=""  I "PENICILLIN"[APAT S CLS="" F  S CLS=$O(^GMRACI(APAT,CLS)) Q:CLS=""  I $D(^PSDCLS("AMOXICILLIN",CLS)) W "Conflict: "_APAT_" -> "_CLS_" ("_^GMRACI(APAT,CLS)_")",!'
+echo "Expected: Conflict: PENICILLIN -> PENICILLINS (SE)"
+echo ""
+
 echo "=== Test Complete ==="